@@ -3,10 +3,12 @@
 //! Git hooks and development tools for Foodshare Android.
 
 use anyhow::Result;
-use clap::{Parser, Subcommand};
-use foodshare_cli::output::Status;
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use foodshare_cli::output::{self, Format, Status};
+use foodshare_cli::{prompt, textln};
 use foodshare_core::config::Config;
 use foodshare_core::error::exit_codes;
+use foodshare_hooks::plugins::Hook;
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -30,6 +32,14 @@ struct Cli {
     #[arg(long, global = true)]
     no_color: bool,
 
+    /// Output format: text, json, ndjson (lint-report and secrets also take sarif)
+    #[arg(long, global = true, default_value = "text")]
+    format: String,
+
+    /// Answer yes to confirmations and take the default elsewhere
+    #[arg(short, long, global = true)]
+    yes: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -66,6 +76,9 @@ enum Commands {
         /// Language: kotlin, swift, both
         #[arg(long, default_value = "both")]
         lang: String,
+        /// Accept current detekt findings into the baseline instead of linting
+        #[arg(long)]
+        update_baseline: bool,
     },
 
     /// Validate commit message
@@ -76,10 +89,28 @@ enum Commands {
     },
 
     /// Scan for secrets
+    #[command(args_conflicts_with_subcommands = true)]
     Secrets {
+        #[command(subcommand)]
+        action: Option<SecretsAction>,
+        /// Files to scan instead of the staged files
+        #[arg(trailing_var_arg = true)]
+        files: Vec<PathBuf>,
         /// Check all files
         #[arg(long)]
         all: bool,
+        /// Scan every file again instead of reusing findings for unchanged ones
+        #[arg(long)]
+        no_cache: bool,
+        /// Check found AWS, GitHub, Stripe and Supabase keys with their providers
+        #[arg(long)]
+        verify: bool,
+    },
+
+    /// Custom checks from the plugins directory and config
+    Plugins {
+        #[command(subcommand)]
+        action: PluginsAction,
     },
 
     /// Check migrations status
@@ -102,6 +133,92 @@ enum Commands {
         bundle: bool,
     },
 
+    /// Analyze APK/AAB size against the baseline and budget
+    Size {
+        /// APK or AAB to analyze (default: the release bundle, else the release APK)
+        package: Option<PathBuf>,
+        /// Baseline report to compare against
+        #[arg(long, default_value = foodshare_android::app_size::BASELINE_FILE)]
+        baseline: PathBuf,
+        /// Save this report as the new baseline instead of checking the budget
+        #[arg(long)]
+        update_baseline: bool,
+        /// Number of components to show
+        #[arg(long, default_value = "15")]
+        top: usize,
+    },
+
+    /// Audit Gradle dependencies against the version catalog and repository allowlist
+    Deps {
+        /// Project root (default: current directory)
+        #[arg(long, default_value = ".")]
+        path: PathBuf,
+        /// Skip querying Maven repositories for newer releases
+        #[arg(long)]
+        offline: bool,
+    },
+
+    /// Check every ABI ships FoodshareCore and the Swift runtime, consistently
+    #[command(name = "native-libs")]
+    NativeLibs {
+        /// APK or AAB to check (default: the release bundle, else the release APK)
+        package: Option<PathBuf>,
+        /// Additional library every ABI must contain (repeatable)
+        #[arg(long = "require", value_name = "LIB")]
+        require: Vec<String>,
+    },
+
+    /// Run Android Lint through Gradle and report new findings
+    LintReport {
+        /// Existing XML report to read instead of running lint
+        #[arg(long)]
+        report: Option<PathBuf>,
+        /// Build variant to lint
+        #[arg(long, default_value = "debug")]
+        variant: String,
+        /// Write the report to a file instead of stdout
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// Baseline of accepted findings
+        #[arg(long, default_value = foodshare_android::lint_report::BASELINE_FILE)]
+        baseline: PathBuf,
+        /// Accept all current findings into the baseline
+        #[arg(long)]
+        update_baseline: bool,
+        /// Fail on warnings too
+        #[arg(long)]
+        strict: bool,
+    },
+
+    /// Check string resources for missing, stale, mistranslated and hardcoded strings
+    #[command(alias = "l10n")]
+    Localization {
+        /// Directory to search for res/values*/ and Kotlin sources
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Locale of the default values/ directory
+        #[arg(long, default_value = foodshare_android::localization::SOURCE_LOCALE)]
+        source: String,
+        /// Fail on warnings (missing, stale, untranslated, plurals, hardcoded), not just placeholder mismatches
+        #[arg(long)]
+        strict: bool,
+        /// Issues to list per locale
+        #[arg(long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Check the merged AndroidManifest.xml against the permissions and security policy
+    Manifest {
+        /// Manifest to check (default: the merged manifest of the variant)
+        path: Option<PathBuf>,
+        /// Build variant whose merged manifest to check
+        #[arg(long, default_value = "release")]
+        variant: String,
+        /// Restricted permission the app has a declared use case for (repeatable)
+        #[arg(long = "allow-permission")]
+        allow_permissions: Vec<String>,
+    },
+
     /// Run tests
     Test {
         /// Enable coverage
@@ -111,11 +228,88 @@ enum Commands {
 
     /// Manage emulators
     Emulator {
-        /// Action: list, boot, shutdown
+        /// Action: list, boot, shutdown, snapshot-save, snapshot-load, snapshot-list, snapshot-delete
         action: String,
         /// AVD name
         #[arg(long)]
         name: Option<String>,
+        /// Running emulator to act on (default: the only one)
+        #[arg(long)]
+        device: Option<String>,
+        /// Snapshot to boot from, or to save, load or delete
+        #[arg(long)]
+        snapshot: Option<String>,
+        /// Boot without a window, audio or boot animation (for CI)
+        #[arg(long)]
+        headless: bool,
+        /// Console port (even, 5554-5682; default: the first free one)
+        #[arg(long)]
+        port: Option<u16>,
+        /// Don't write to the AVD, so several emulators can run it at once
+        #[arg(long)]
+        read_only: bool,
+        /// Seconds to wait for boot to complete
+        #[arg(long, default_value = "300")]
+        timeout: u64,
+        /// Return once the emulator process starts instead of waiting for boot
+        #[arg(long)]
+        no_wait: bool,
+    },
+
+    /// List connected devices and running emulators
+    Devices,
+
+    /// Install an APK on a device or emulator
+    Install {
+        /// APK to install (default: the built APK for --configuration)
+        apk: Option<PathBuf>,
+        /// Device serial or model (default: the only connected device)
+        #[arg(long)]
+        device: Option<String>,
+        /// Build configuration of the APK to find
+        #[arg(long, default_value = "debug")]
+        configuration: String,
+    },
+
+    /// Build, install, and launch the app on a device or emulator
+    Run {
+        /// Device serial or model (default: the only connected device)
+        #[arg(long)]
+        device: Option<String>,
+        /// Build configuration
+        #[arg(long, default_value = "debug")]
+        configuration: String,
+        /// Clean before building
+        #[arg(long)]
+        clean: bool,
+        /// Stream app logs after launch
+        #[arg(long)]
+        logs: bool,
+        /// Application ID (default: applicationId from app/build.gradle.kts)
+        #[arg(long)]
+        package: Option<String>,
+    },
+
+    /// Stream app logs, highlighting and capturing crashes and ANRs
+    Logs {
+        /// Device serial or model (default: the only connected device)
+        #[arg(long)]
+        device: Option<String>,
+        /// Application ID (default: applicationId from app/build.gradle.kts)
+        #[arg(long)]
+        package: Option<String>,
+        /// Minimum level: verbose, debug, info, warn, error, fatal
+        #[arg(long, default_value = "verbose")]
+        level: String,
+        /// Show every process's logs, not just the app's
+        #[arg(long)]
+        all: bool,
+        /// Print the current log buffer and exit instead of following
+        #[arg(long)]
+        dump: bool,
+        /// Write each captured crash to this file (JSON if it ends in .json)
+        #[arg(long)]
+        crash_report: Option<PathBuf>,
     },
 
     /// Build Swift for Android
@@ -129,11 +323,23 @@ enum Commands {
         configuration: String,
     },
 
-    /// Generate Swift-Java bindings
+    /// Generate Swift-Java bindings and check them against the Swift core
     #[command(name = "swift-java")]
     SwiftJava {
-        /// Action: generate, verify
+        /// Action: generate, check, verify
         action: String,
+        /// Swift sources exporting JNI symbols
+        #[arg(long, default_value = "swift-core/Sources")]
+        sources: PathBuf,
+        /// Generated bindings directory
+        #[arg(long, default_value = "app/src/main/java")]
+        output: PathBuf,
+        /// App sources declaring the Kotlin/Java side
+        #[arg(long, default_value = "app/src/main")]
+        app_sources: PathBuf,
+        /// Package prefix to check
+        #[arg(long, default_value = foodshare_android::jni_bindings::DEFAULT_PACKAGE)]
+        package: String,
     },
 
     /// Build FoodshareCore Swift library for Android
@@ -145,19 +351,68 @@ enum Commands {
 
     /// Diagnose environment
     Doctor {
-        /// Output as JSON
+        /// Output as JSON (same as --format json)
         #[arg(long)]
         json: bool,
+        /// Offer to install missing tools with Homebrew
+        #[arg(long)]
+        fix: bool,
     },
 
     /// Verify setup
     Verify,
+
+    /// Update this binary to the latest signed release
+    #[command(name = "self-update")]
+    SelfUpdate(foodshare_self_update::SelfUpdateArgs),
+}
+
+#[derive(Subcommand)]
+enum SecretsAction {
+    /// Manage the baseline of accepted findings
+    Baseline {
+        #[command(subcommand)]
+        action: BaselineAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum BaselineAction {
+    /// Write the findings in all tracked files to the baseline
+    Create {
+        /// Replace an existing baseline
+        #[arg(long)]
+        force: bool,
+    },
+    /// Rescan tracked files: add new findings, drop fixed ones, keep audit verdicts
+    Update,
+    /// Label baselined findings as real secrets or false positives
+    Audit,
+}
+
+#[derive(Subcommand)]
+enum PluginsAction {
+    /// List the registered plugins
+    List,
+    /// Run the plugins of a hook
+    Run {
+        /// Hook whose plugins run: pre-commit, pre-push
+        #[arg(long, default_value = "pre-commit")]
+        hook: Hook,
+        /// Files to check instead of the staged files
+        #[arg(trailing_var_arg = true)]
+        files: Vec<PathBuf>,
+    },
 }
 
 #[derive(Subcommand)]
 enum SwiftCoreAction {
     /// Check prerequisites for building Swift for Android
-    Check,
+    Check {
+        /// Also cross-compile a hello-world package
+        #[arg(long)]
+        verify: bool,
+    },
     /// Build FoodshareCore for Android
     Build {
         /// Target: arm64, x86_64, all
@@ -188,16 +443,52 @@ enum SwiftCoreAction {
         #[arg(long, default_value = ".")]
         android_dir: PathBuf,
     },
-    /// Print setup instructions
-    Setup,
+    /// Install the NDK and Swift SDK for Android, then verify a cross-compile
+    Setup {
+        /// Swift SDK bundle URL or path (default: the bundle matching the toolchain)
+        #[arg(long)]
+        sdk: Option<String>,
+        /// SHA-256 checksum of the SDK bundle (required for URLs)
+        #[arg(long)]
+        checksum: Option<String>,
+        /// Android API level for the verification build
+        #[arg(long, default_value = "24")]
+        api_level: u8,
+        /// Skip the hello-world cross-compile
+        #[arg(long)]
+        skip_verify: bool,
+        /// Only print `export` lines for the selected NDK, for `eval`
+        #[arg(long)]
+        print_env: bool,
+    },
 }
 
 fn main() -> Result<()> {
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches)?;
+    let command = output::command_path(&matches);
+
+    // lint-report renders its own json and sarif, secrets its own sarif
+    let sarif = matches!(cli.command, Commands::Secrets { .. }) && cli.format == "sarif";
+    let format = if sarif || matches!(cli.command, Commands::LintReport { .. }) {
+        Ok(Format::Text)
+    } else if matches!(cli.command, Commands::Doctor { json: true, .. }) {
+        Ok(Format::Json)
+    } else {
+        cli.format.parse::<Format>()
+    };
+    match format {
+        Ok(format) => output::init(format),
+        Err(e) => {
+            Status::error(&e);
+            std::process::exit(exit_codes::FAILURE);
+        }
+    }
 
     if cli.no_color {
         owo_colors::set_override(false);
     }
+    prompt::set_assume_yes(cli.yes);
 
     let config = Config::load(cli.config.as_deref().map(|p| p.to_str().unwrap()))?;
 
@@ -205,14 +496,20 @@ fn main() -> Result<()> {
         Commands::Format { files, check, staged, lang } => {
             run_format(&files, check, staged, &lang)
         }
-        Commands::Lint { files, strict, fix, lang } => {
-            run_lint(&files, strict, fix, &lang)
+        Commands::Lint { files, strict, fix, lang, update_baseline } => {
+            run_lint(&files, strict, fix, &lang, update_baseline)
         }
         Commands::CommitMsg { file } => {
             run_commit_msg(&file, &config)
         }
-        Commands::Secrets { all } => {
-            run_secrets(all, &config)
+        Commands::Secrets { action: Some(SecretsAction::Baseline { action }), .. } => {
+            run_secrets_baseline(action, &config)
+        }
+        Commands::Secrets { action: None, files, all, no_cache, verify } => {
+            run_secrets(&files, all, sarif, no_cache, verify, &config)
+        }
+        Commands::Plugins { action } => {
+            run_plugins(action, &config)
         }
         Commands::Migrations { dir } => {
             run_migrations(&dir)
@@ -220,30 +517,70 @@ fn main() -> Result<()> {
         Commands::Build { configuration, clean, bundle } => {
             run_build(&configuration, clean, bundle)
         }
+        Commands::Size { package, baseline, update_baseline, top } => {
+            run_size(package.as_deref(), &baseline, update_baseline, top, &config)
+        }
+        Commands::Deps { path, offline } => {
+            run_deps(&path, offline, &config)
+        }
+        Commands::NativeLibs { package, require } => {
+            run_native_libs(package.as_deref(), &require)
+        }
+        Commands::LintReport { report, variant, output, baseline, update_baseline, strict } => {
+            run_lint_report(report.as_deref(), &variant, &cli.format, output.as_deref(), &baseline, update_baseline, strict)
+        }
+        Commands::Localization { path, source, strict, limit } => {
+            run_localization(&path, &source, strict, limit)
+        }
+        Commands::Manifest { path, variant, allow_permissions } => {
+            run_manifest(path.as_deref(), &variant, &allow_permissions)
+        }
         Commands::Test { coverage } => {
             run_test(coverage)
         }
-        Commands::Emulator { action, name } => {
-            run_emulator(&action, name.as_deref())
+        Commands::Emulator { action, name, device, snapshot, headless, port, read_only, timeout, no_wait } => {
+            let options = foodshare_android::emulator::BootOptions {
+                headless,
+                port,
+                snapshot,
+                read_only,
+            };
+            let timeout = (!no_wait).then(|| std::time::Duration::from_secs(timeout));
+            run_emulator(&action, name.as_deref(), device.as_deref(), &options, timeout)
+        }
+        Commands::Devices => {
+            run_devices()
+        }
+        Commands::Install { apk, device, configuration } => {
+            run_install(apk.as_deref(), device.as_deref(), &configuration)
+        }
+        Commands::Run { device, configuration, clean, logs, package } => {
+            run_app(device.as_deref(), &configuration, clean, logs, package.as_deref())
+        }
+        Commands::Logs { device, package, level, all, dump, crash_report } => {
+            run_logs(device.as_deref(), package.as_deref(), &level, all, dump, crash_report.as_deref())
         }
         Commands::SwiftBuild { target, configuration } => {
             run_swift_build(&target, &configuration)
         }
-        Commands::SwiftJava { action } => {
-            run_swift_java(&action)
+        Commands::SwiftJava { action, sources, output, app_sources, package } => {
+            run_swift_java(&action, &sources, &output, &app_sources, &package)
         }
         Commands::SwiftCore { action } => {
             run_swift_core(action)
         }
-        Commands::Doctor { json } => {
-            run_doctor(json)
+        Commands::Doctor { fix, .. } => {
+            run_doctor(fix)
         }
         Commands::Verify => {
             run_verify()
         }
+        Commands::SelfUpdate(args) => {
+            foodshare_self_update::run(env!("CARGO_BIN_NAME"), env!("CARGO_PKG_VERSION"), &args, &config.schema.update)
+        }
     };
 
-    std::process::exit(exit_code);
+    std::process::exit(output::finish(&command, exit_code));
 }
 
 fn run_format(_files: &[PathBuf], _check: bool, _staged: bool, lang: &str) -> i32 {
@@ -279,9 +616,13 @@ fn run_format(_files: &[PathBuf], _check: bool, _staged: bool, lang: &str) -> i3
     exit_codes::SUCCESS
 }
 
-fn run_lint(_files: &[PathBuf], strict: bool, _fix: bool, lang: &str) -> i32 {
+fn run_lint(files: &[PathBuf], strict: bool, fix: bool, lang: &str, update_baseline: bool) -> i32 {
     use foodshare_android::kotlin_tools;
 
+    if update_baseline {
+        return run_detekt_baseline(files);
+    }
+
     if lang == "kotlin" || lang == "both" {
         if !kotlin_tools::has_ktlint() {
             Status::error("ktlint not found");
@@ -295,7 +636,8 @@ fn run_lint(_files: &[PathBuf], strict: bool, _fix: bool, lang: &str) -> i32 {
                     Status::success("Kotlin lint passed");
                 } else {
                     Status::error("Kotlin lint found issues");
-                    println!("{}", result.stdout);
+                    textln!("{}", result.stdout);
+                    output::data("ktlint_output", &result.stdout);
                     if strict {
                         return exit_codes::FAILURE;
                     }
@@ -306,11 +648,83 @@ fn run_lint(_files: &[PathBuf], strict: bool, _fix: bool, lang: &str) -> i32 {
                 return exit_codes::FAILURE;
             }
         }
+
+        let code = run_detekt(files, strict, fix);
+        if code != exit_codes::SUCCESS {
+            return code;
+        }
     }
 
     exit_codes::SUCCESS
 }
 
+/// detekt options for the files given on the command line, or the app module
+fn detekt_options(files: &[PathBuf], auto_correct: bool) -> foodshare_android::detekt::DetektOptions {
+    let input = if files.is_empty() { vec![PathBuf::from("app/src")] } else { files.to_vec() };
+    foodshare_android::detekt::DetektOptions {
+        input,
+        auto_correct,
+        ..Default::default()
+    }
+}
+
+fn run_detekt(files: &[PathBuf], strict: bool, fix: bool) -> i32 {
+    use foodshare_android::{detekt, kotlin_tools};
+
+    if !kotlin_tools::has_detekt() {
+        Status::warning("detekt not found, skipping static analysis. Install with: brew install detekt");
+        return exit_codes::SUCCESS;
+    }
+
+    Status::info("Running detekt...");
+    let findings = match detekt::analyze(std::path::Path::new("."), &detekt_options(files, fix)) {
+        Ok(findings) => findings,
+        Err(e) => {
+            Status::error(&format!("detekt error: {}", e));
+            return exit_codes::FAILURE;
+        }
+    };
+    if findings.is_empty() {
+        Status::success("detekt passed");
+        return exit_codes::SUCCESS;
+    }
+
+    detekt::print_findings(&findings);
+    let errors = findings.iter().filter(|f| f.severity == detekt::Severity::Error).count();
+    let message = format!(
+        "detekt found {} issue(s), {} error(s); accept existing ones with `lint --update-baseline`",
+        findings.len(),
+        errors
+    );
+    if errors > 0 || strict {
+        Status::error(&message);
+        exit_codes::FAILURE
+    } else {
+        Status::warning(&message);
+        exit_codes::SUCCESS
+    }
+}
+
+fn run_detekt_baseline(files: &[PathBuf]) -> i32 {
+    use foodshare_android::{detekt, kotlin_tools};
+
+    if !kotlin_tools::has_detekt() {
+        Status::error("detekt not found. Install with: brew install detekt");
+        return exit_codes::FAILURE;
+    }
+
+    match detekt::create_baseline(std::path::Path::new("."), &detekt_options(files, false)) {
+        Ok(path) => {
+            Status::success(&format!("detekt baseline written to {}", path.display()));
+            exit_codes::SUCCESS
+        }
+        Err(e) => {
+            Status::error(&format!("Failed to create detekt baseline: {}", e));
+            exit_codes::FAILURE
+        }
+    }
+}
+
 fn run_commit_msg(file: &PathBuf, config: &Config) -> i32 {
     use foodshare_hooks::commit_msg;
 
@@ -333,10 +747,12 @@ fn run_commit_msg(file: &PathBuf, config: &Config) -> i32 {
     }
 }
 
-fn run_secrets(all: bool, config: &Config) -> i32 {
+fn run_secrets(files: &[PathBuf], all: bool, sarif: bool, no_cache: bool, verify: bool, config: &Config) -> i32 {
     use foodshare_hooks::secrets;
 
-    let files = if all {
+    let files = if !files.is_empty() {
+        files.to_vec()
+    } else if all {
         foodshare_core::file_scanner::scan_kotlin_files(std::path::Path::new("."))
             .unwrap_or_default()
     } else {
@@ -345,12 +761,58 @@ fn run_secrets(all: bool, config: &Config) -> i32 {
             .unwrap_or_default()
     };
 
-    match secrets::scan_files(&files, &config.schema.secrets) {
-        Ok(matches) => secrets::print_results(&matches),
+    let baseline = match secrets::load_baseline(&config.schema.secrets) {
+        Ok(baseline) => baseline,
         Err(e) => {
-            Status::error(&format!("Scan error: {}", e));
-            exit_codes::FAILURE
+            Status::error(&e.to_string());
+            return exit_codes::FAILURE;
+        }
+    };
+    let secrets_config = foodshare_core::config::SecretsConfig {
+        cache: config.schema.secrets.cache && !no_cache,
+        ..config.schema.secrets.clone()
+    };
+    let mut output = secrets::scan_files_with_config(&files, &secrets_config, baseline.as_ref());
+    if verify {
+        if let Err(e) = secrets::verify_output(&mut output) {
+            Status::error(&e.to_string());
+            return exit_codes::FAILURE;
+        }
+    }
+    if sarif {
+        println!("{}", output.to_sarif());
+        return if output.has_secrets() { exit_codes::FAILURE } else { exit_codes::SUCCESS };
+    }
+    secrets::print_output(&output)
+}
+
+fn run_secrets_baseline(action: BaselineAction, config: &Config) -> i32 {
+    use foodshare_hooks::secrets;
+
+    if let BaselineAction::Audit = action {
+        return secrets::audit_baseline(&config.schema.secrets);
+    }
+    let tracked = match foodshare_core::git::GitRepo::open_current()
+        .and_then(|r| r.files_at("HEAD", std::path::Path::new(".")))
+    {
+        Ok(files) => files,
+        Err(e) => {
+            Status::error(&format!("Failed to list tracked files: {}", e));
+            return exit_codes::FAILURE;
         }
+    };
+    match action {
+        BaselineAction::Create { force } => secrets::create_baseline(&tracked, &config.schema.secrets, force),
+        _ => secrets::update_baseline(&tracked, &config.schema.secrets),
+    }
+}
+
+fn run_plugins(action: PluginsAction, config: &Config) -> i32 {
+    use foodshare_hooks::plugins;
+
+    match action {
+        PluginsAction::List => plugins::list(&config.schema.plugins),
+        PluginsAction::Run { hook, files } => plugins::run_for_hook(hook, &files, &config.schema.plugins),
     }
 }
 
@@ -358,7 +820,23 @@ fn run_migrations(dir: &std::path::Path) -> i32 {
     use foodshare_hooks::migrations;
 
     match migrations::check_migrations(dir, true, true) {
-        Ok(check) => migrations::print_results(&check),
+        Ok(check) => {
+            let code = migrations::print_results(&check);
+            if check.destructive.is_empty() {
+                return code;
+            }
+            match migrations::approve_destructive(&check) {
+                Ok(true) => code,
+                Ok(false) => {
+                    Status::error("Destructive migration not approved");
+                    exit_codes::FAILURE
+                }
+                Err(e) => {
+                    Status::error(&e.to_string());
+                    exit_codes::FAILURE
+                }
+            }
+        }
         Err(e) => {
             Status::error(&format!("Migration check error: {}", e));
             exit_codes::FAILURE
@@ -414,82 +892,770 @@ fn run_build(configuration: &str, clean: bool, bundle: bool) -> i32 {
     }
 }
 
-fn run_test(_coverage: bool) -> i32 {
-    use foodshare_android::gradle;
-
-    Status::info("Running tests...");
-
-    match gradle::test(std::path::Path::new(".")) {
-        Ok(result) => {
-            if result.success {
-                Status::success("Tests passed");
-                exit_codes::SUCCESS
-            } else {
-                Status::error("Tests failed");
-                eprintln!("{}", result.stderr);
-                exit_codes::FAILURE
+fn run_lint_report(
+    report: Option<&std::path::Path>,
+    variant: &str,
+    format: &str,
+    output: Option<&std::path::Path>,
+    baseline_path: &std::path::Path,
+    update_baseline: bool,
+    strict: bool,
+) -> i32 {
+    use foodshare_android::lint_report::{self, ReportFormat, Severity};
+
+    let format: ReportFormat = match format.parse() {
+        Ok(format) => format,
+        Err(e) => {
+            Status::error(&e.to_string());
+            return exit_codes::VALIDATION_ERROR;
+        }
+    };
+    let root = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    // Keep stdout parseable when JSON or SARIF goes there
+    let machine_output = format != ReportFormat::Text && output.is_none();
+
+    let report = match report {
+        Some(report) => report.to_path_buf(),
+        None => {
+            if !machine_output {
+                Status::info(&format!("Running Android Lint ({})...", variant));
+            }
+            match lint_report::run(&root, variant) {
+                Ok(report) => report,
+                Err(e) => {
+                    Status::error(&format!("Lint failed: {}", e));
+                    return exit_codes::FAILURE;
+                }
             }
         }
+    };
+    let findings = match lint_report::load(&report, &root) {
+        Ok(findings) => findings,
         Err(e) => {
-            Status::error(&format!("Test error: {}", e));
-            exit_codes::FAILURE
+            Status::error(&format!("Failed to read lint report: {}", e));
+            return exit_codes::FAILURE;
         }
-    }
-}
+    };
 
-fn run_emulator(action: &str, name: Option<&str>) -> i32 {
-    use foodshare_android::emulator;
+    if update_baseline {
+        return match lint_report::write_baseline(&findings, baseline_path) {
+            Ok(()) => {
+                Status::success(&format!(
+                    "Baseline with {} finding(s) saved to {}",
+                    findings.len(),
+                    baseline_path.display()
+                ));
+                exit_codes::SUCCESS
+            }
+            Err(e) => {
+                Status::error(&format!("Failed to save baseline: {}", e));
+                exit_codes::FAILURE
+            }
+        };
+    }
 
-    match action {
-        "list" => {
-            match emulator::list_avds() {
-                Ok(avds) => {
-                    println!("Available AVDs:");
-                    for avd in avds {
-                        println!("  - {}", avd);
-                    }
-                    exit_codes::SUCCESS
-                }
-                Err(e) => {
-                    Status::error(&format!("Failed to list AVDs: {}", e));
-                    exit_codes::FAILURE
-                }
+    let (findings, accepted) = if baseline_path.exists() {
+        match lint_report::load(baseline_path, &root) {
+            Ok(baseline) => lint_report::filter_baseline(findings, &baseline),
+            Err(e) => {
+                Status::warning(&format!("Ignoring unreadable baseline: {}", e));
+                (findings, 0)
             }
         }
-        "boot" => {
-            let avd_name = name.unwrap_or("Pixel_7_API_34");
-            Status::info(&format!("Booting {}...", avd_name));
-            match emulator::boot(avd_name) {
-                Ok(_) => {
-                    Status::success(&format!("Started {}", avd_name));
-                    exit_codes::SUCCESS
-                }
-                Err(e) => {
-                    Status::error(&format!("Failed to boot: {}", e));
-                    exit_codes::FAILURE
-                }
+    } else {
+        (findings, 0)
+    };
+
+    let rendered = match format {
+        ReportFormat::Text => None,
+        ReportFormat::Json => Some(lint_report::to_json(&findings)),
+        ReportFormat::Sarif => Some(lint_report::to_sarif(&findings)),
+    };
+    match (rendered, output) {
+        (None, _) => lint_report::print_findings(&findings),
+        (Some(Ok(rendered)), Some(output)) => {
+            if let Err(e) = std::fs::write(output, rendered) {
+                Status::error(&format!("Failed to write {}: {}", output.display(), e));
+                return exit_codes::FAILURE;
             }
+            Status::info(&format!("Report written to {}", output.display()));
         }
-        "shutdown" => {
-            match emulator::shutdown_all() {
-                Ok(_) => {
-                    Status::success("Shutdown all emulators");
-                    exit_codes::SUCCESS
-                }
-                Err(e) => {
-                    Status::error(&format!("Failed to shutdown: {}", e));
-                    exit_codes::FAILURE
-                }
-            }
+        (Some(Ok(rendered)), None) => println!("{}", rendered),
+        (Some(Err(e)), _) => {
+            Status::error(&format!("Failed to render report: {}", e));
+            return exit_codes::FAILURE;
         }
-        _ => {
-            Status::error(&format!("Unknown action: {}", action));
-            exit_codes::FAILURE
+    }
+
+    let errors = findings.iter().filter(|f| f.severity == Severity::Error).count();
+    let summary = format!(
+        "{} new lint finding(s), {} error(s), {} in baseline",
+        findings.len(),
+        errors,
+        accepted
+    );
+    if errors > 0 || (strict && !findings.is_empty()) {
+        Status::error(&summary);
+        exit_codes::FAILURE
+    } else if findings.is_empty() {
+        if !machine_output {
+            Status::success(&summary);
         }
+        exit_codes::SUCCESS
+    } else {
+        Status::warning(&summary);
+        exit_codes::SUCCESS
     }
 }
 
-fn run_swift_build(target: &str, configuration: &str) -> i32 {
+fn run_localization(path: &std::path::Path, source: &str, strict: bool, limit: usize) -> i32 {
+    use foodshare_android::localization;
+
+    let report = match localization::lint(path, source) {
+        Ok(report) => report,
+        Err(e) => {
+            Status::error(&format!("Localization lint failed: {}", e));
+            return exit_codes::FAILURE;
+        }
+    };
+    if report.modules == 0 && report.issues.is_empty() {
+        Status::info("No res/values directories found");
+        return exit_codes::SUCCESS;
+    }
+
+    report.print(limit);
+    textln!();
+
+    let errors = report.error_count();
+    let warnings = report.issues.len() - errors;
+    if errors > 0 {
+        Status::error(&format!("{} placeholder mismatches, {} warnings", errors, warnings));
+        exit_codes::FAILURE
+    } else if warnings > 0 && strict {
+        Status::error(&format!("{} warnings (--strict)", warnings));
+        exit_codes::FAILURE
+    } else if warnings > 0 {
+        Status::warning(&format!("{} warnings", warnings));
+        exit_codes::SUCCESS
+    } else {
+        Status::success("All locales complete");
+        exit_codes::SUCCESS
+    }
+}
+
+fn run_manifest(path: Option<&std::path::Path>, variant: &str, allowed: &[String]) -> i32 {
+    use foodshare_android::manifest::{self, Manifest, Severity};
+    use owo_colors::OwoColorize;
+
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => match manifest::find_merged_manifest(std::path::Path::new("."), variant) {
+            Ok(path) => path,
+            Err(_) => {
+                Status::warning(&format!(
+                    "No merged {} manifest; checking {} (build first to include library manifests)",
+                    variant,
+                    manifest::SOURCE_MANIFEST
+                ));
+                PathBuf::from(manifest::SOURCE_MANIFEST)
+            }
+        },
+    };
+
+    let parsed = match Manifest::load(&path) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            Status::error(&format!("Failed to read manifest: {}", e));
+            return exit_codes::FAILURE;
+        }
+    };
+
+    Status::info(&format!("Checking {}", path.display()));
+    let issues = manifest::check(&parsed, variant == "release", allowed);
+    for issue in &issues {
+        let severity = match issue.severity {
+            Severity::Error => "error".red().to_string(),
+            Severity::Warning => "warning".yellow().to_string(),
+        };
+        textln!(
+            "  {}:{} {} {} {}",
+            path.display(),
+            issue.line,
+            severity,
+            issue.message,
+            format!("({})", issue.check).dimmed()
+        );
+    }
+
+    let errors = issues.iter().filter(|i| i.severity == Severity::Error).count();
+    if errors > 0 {
+        Status::error(&format!("{} manifest policy violation(s)", errors));
+        exit_codes::FAILURE
+    } else if issues.is_empty() {
+        Status::success("Manifest passes policy checks");
+        exit_codes::SUCCESS
+    } else {
+        Status::warning(&format!("{} manifest warning(s)", issues.len()));
+        exit_codes::SUCCESS
+    }
+}
+
+fn run_size(
+    package: Option<&std::path::Path>,
+    baseline_path: &std::path::Path,
+    update_baseline: bool,
+    top: usize,
+    config: &Config,
+) -> i32 {
+    use foodshare_android::app_size::{self, PackageSizeReport};
+
+    let package = match package {
+        Some(package) => package.to_path_buf(),
+        None => match app_size::find_release_package(std::path::Path::new(".")) {
+            Ok(package) => package,
+            Err(e) => {
+                Status::error(&format!(
+                    "{} (build it with `foodshare-android build --configuration release --bundle`)",
+                    e
+                ));
+                return exit_codes::FAILURE;
+            }
+        },
+    };
+
+    let report = match PackageSizeReport::from_file(&package) {
+        Ok(report) => report,
+        Err(e) => {
+            Status::error(&format!("Failed to measure {}: {}", package.display(), e));
+            return exit_codes::FAILURE;
+        }
+    };
+
+    let baseline = if baseline_path.exists() {
+        match PackageSizeReport::load(baseline_path) {
+            Ok(baseline) => Some(baseline),
+            Err(e) => {
+                Status::warning(&format!("Ignoring unreadable baseline: {}", e));
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    textln!();
+    report.print(baseline.as_ref(), top);
+    textln!();
+
+    if update_baseline {
+        return match report.save(baseline_path) {
+            Ok(()) => {
+                Status::success(&format!("Baseline saved to {}", baseline_path.display()));
+                exit_codes::SUCCESS
+            }
+            Err(e) => {
+                Status::error(&format!("Failed to save baseline: {}", e));
+                exit_codes::FAILURE
+            }
+        };
+    }
+    if baseline.is_none() {
+        Status::info(&format!(
+            "No baseline at {}; run with --update-baseline to create one",
+            baseline_path.display()
+        ));
+    }
+
+    let violations = report.budget_violations(baseline.as_ref(), &config.schema.size);
+    if violations.is_empty() {
+        Status::success("App size within budget");
+        exit_codes::SUCCESS
+    } else {
+        for violation in &violations {
+            Status::error(violation);
+        }
+        exit_codes::FAILURE
+    }
+}
+
+fn run_deps(path: &std::path::Path, offline: bool, config: &Config) -> i32 {
+    use foodshare_android::gradle_deps;
+
+    if !offline {
+        Status::info("Checking Maven repositories for newer releases...");
+    }
+    let audit = match gradle_deps::audit(path, &config.schema.deps.allowed_repositories, !offline) {
+        Ok(audit) => audit,
+        Err(e) => {
+            Status::error(&format!("Dependency audit failed: {}", e));
+            return exit_codes::FAILURE;
+        }
+    };
+    if audit.build_files.is_empty() {
+        Status::info("No Gradle build files found");
+        return exit_codes::SUCCESS;
+    }
+    if audit.catalog_file.is_none() {
+        Status::warning(&format!("No version catalog at {}", gradle_deps::VERSION_CATALOG));
+    }
+
+    audit.print(path);
+    textln!();
+
+    if audit.has_failures() {
+        Status::error(&format!(
+            "{} hardcoded versions, {} conflicting modules, {} non-allowlisted repositories",
+            audit.hardcoded.len(),
+            audit.duplicates.len(),
+            audit.disallowed.len()
+        ));
+        exit_codes::FAILURE
+    } else if !audit.unused.is_empty() || !audit.outdated.is_empty() {
+        Status::warning(&format!(
+            "{} unused catalog entries, {} outdated",
+            audit.unused.len(),
+            audit.outdated.len()
+        ));
+        exit_codes::SUCCESS
+    } else {
+        Status::success("Dependencies are consistent and up to date");
+        exit_codes::SUCCESS
+    }
+}
+
+fn run_native_libs(package: Option<&std::path::Path>, require: &[String]) -> i32 {
+    use foodshare_android::app_size;
+    use foodshare_android::native_libs::{self, NativeLibReport};
+
+    let package = match package {
+        Some(package) => package.to_path_buf(),
+        None => match app_size::find_release_package(std::path::Path::new(".")) {
+            Ok(package) => package,
+            Err(e) => {
+                Status::error(&format!(
+                    "{} (build it with `foodshare-android build --configuration release --bundle`)",
+                    e
+                ));
+                return exit_codes::FAILURE;
+            }
+        },
+    };
+
+    let report = match NativeLibReport::from_file(&package) {
+        Ok(report) => report,
+        Err(e) => {
+            Status::error(&format!("Failed to read {}: {}", package.display(), e));
+            return exit_codes::FAILURE;
+        }
+    };
+
+    textln!();
+    report.print();
+    textln!();
+
+    let mut required: Vec<String> = native_libs::REQUIRED_LIBS.iter().map(|lib| lib.to_string()).collect();
+    for lib in require {
+        if !required.contains(lib) {
+            required.push(lib.clone());
+        }
+    }
+
+    let problems = report.problems(&required);
+    if problems.is_empty() {
+        Status::success(&format!("{} ABI(s) ship consistent native libraries", report.abis().len()));
+        exit_codes::SUCCESS
+    } else {
+        for problem in &problems {
+            Status::error(problem);
+        }
+        exit_codes::FAILURE
+    }
+}
+
+fn run_test(_coverage: bool) -> i32 {
+    use foodshare_android::gradle;
+
+    Status::info("Running tests...");
+
+    match gradle::test(std::path::Path::new(".")) {
+        Ok(result) => {
+            if result.success {
+                Status::success("Tests passed");
+                exit_codes::SUCCESS
+            } else {
+                Status::error("Tests failed");
+                eprintln!("{}", result.stderr);
+                exit_codes::FAILURE
+            }
+        }
+        Err(e) => {
+            Status::error(&format!("Test error: {}", e));
+            exit_codes::FAILURE
+        }
+    }
+}
+
+fn run_emulator(
+    action: &str,
+    name: Option<&str>,
+    device: Option<&str>,
+    options: &foodshare_android::emulator::BootOptions,
+    timeout: Option<std::time::Duration>,
+) -> i32 {
+    use foodshare_android::emulator;
+
+    match action {
+        "list" => {
+            match emulator::list_avds() {
+                Ok(avds) => {
+                    textln!("Available AVDs:");
+                    for avd in avds {
+                        textln!("  - {}", avd);
+                    }
+                    exit_codes::SUCCESS
+                }
+                Err(e) => {
+                    Status::error(&format!("Failed to list AVDs: {}", e));
+                    exit_codes::FAILURE
+                }
+            }
+        }
+        "boot" => {
+            let avd_name = name.unwrap_or("Pixel_7_API_34");
+            Status::info(&format!("Booting {}...", avd_name));
+            let mut started = match emulator::boot(avd_name, options) {
+                Ok(started) => started,
+                Err(e) => {
+                    Status::error(&format!("Failed to boot: {}", e));
+                    return exit_codes::FAILURE;
+                }
+            };
+            let Some(timeout) = timeout else {
+                Status::success(&format!("Started {} as {}", avd_name, started.serial));
+                return exit_codes::SUCCESS;
+            };
+            match started.wait_for_boot(timeout) {
+                Ok(()) => {
+                    Status::success(&format!("Booted {} as {}", avd_name, started.serial));
+                    exit_codes::SUCCESS
+                }
+                Err(e) => {
+                    Status::error(&format!("Failed to boot: {}", e));
+                    let _ = emulator::shutdown(&started.serial);
+                    exit_codes::FAILURE
+                }
+            }
+        }
+        "shutdown" => {
+            let result = match device {
+                Some(serial) => emulator::shutdown(serial).map(drop),
+                None => emulator::shutdown_all(),
+            };
+            match result {
+                Ok(_) => {
+                    Status::success(&format!("Shutdown {}", device.unwrap_or("all emulators")));
+                    exit_codes::SUCCESS
+                }
+                Err(e) => {
+                    Status::error(&format!("Failed to shutdown: {}", e));
+                    exit_codes::FAILURE
+                }
+            }
+        }
+        "snapshot-save" | "snapshot-load" | "snapshot-list" | "snapshot-delete" => {
+            let serial = match running_emulator(device) {
+                Ok(serial) => serial,
+                Err(e) => {
+                    Status::error(&e.to_string());
+                    return exit_codes::FAILURE;
+                }
+            };
+            if action == "snapshot-list" {
+                return match emulator::list_snapshots(&serial) {
+                    Ok(snapshots) => {
+                        textln!("Snapshots of {}:", serial);
+                        for snapshot in snapshots {
+                            textln!("  - {}", snapshot);
+                        }
+                        exit_codes::SUCCESS
+                    }
+                    Err(e) => {
+                        Status::error(&format!("Failed to list snapshots: {}", e));
+                        exit_codes::FAILURE
+                    }
+                };
+            }
+
+            let Some(snapshot) = options.snapshot.as_deref() else {
+                Status::error(&format!("{} needs --snapshot <name>", action));
+                return exit_codes::VALIDATION_ERROR;
+            };
+            let (result, done) = match action {
+                "snapshot-save" => (emulator::save_snapshot(&serial, snapshot), "Saved"),
+                "snapshot-load" => (emulator::load_snapshot(&serial, snapshot), "Loaded"),
+                _ => (emulator::delete_snapshot(&serial, snapshot), "Deleted"),
+            };
+            match result {
+                Ok(()) => {
+                    Status::success(&format!("{} snapshot {} on {}", done, snapshot, serial));
+                    exit_codes::SUCCESS
+                }
+                Err(e) => {
+                    Status::error(&e.to_string());
+                    exit_codes::FAILURE
+                }
+            }
+        }
+        _ => {
+            Status::error(&format!("Unknown action: {}", action));
+            exit_codes::FAILURE
+        }
+    }
+}
+
+/// Serial of the running emulator matching `query`, or the only one running
+fn running_emulator(query: Option<&str>) -> foodshare_core::error::Result<String> {
+    use foodshare_android::device;
+
+    let emulators: Vec<_> = device::list_devices()?
+        .into_iter()
+        .filter(|d| d.is_emulator())
+        .collect();
+    device::select_device(&emulators, query).map(|d| d.serial)
+}
+
+fn run_devices() -> i32 {
+    use foodshare_android::device;
+    use owo_colors::OwoColorize;
+
+    match device::list_devices() {
+        Ok(devices) if devices.is_empty() => {
+            Status::warning("No devices found. Connect a phone with USB debugging enabled or boot an emulator");
+            exit_codes::SUCCESS
+        }
+        Ok(devices) => {
+            textln!("Connected Devices:");
+            for d in &devices {
+                let status = if d.is_ready() { "🟢" } else { "⚪" };
+                let kind = if d.is_emulator() { "emulator" } else { "device" };
+                let mut details = vec![kind.to_string()];
+                details.extend(d.api_level.map(|level| format!("API {}", level)));
+                if !d.is_ready() {
+                    details.push(d.state.clone());
+                }
+                let model = d.model.as_deref().unwrap_or("Unknown");
+                textln!("  {} {} ({}) {}", status, model, details.join(", "), d.serial.dimmed());
+            }
+            exit_codes::SUCCESS
+        }
+        Err(e) => {
+            Status::error(&format!("Failed to list devices: {}", e));
+            exit_codes::FAILURE
+        }
+    }
+}
+
+/// Pick the target device, reporting errors
+fn select_android_device(query: Option<&str>) -> Option<foodshare_android::device::AndroidDevice> {
+    use foodshare_android::device;
+
+    match device::list_devices().and_then(|devices| device::select_device(&devices, query)) {
+        Ok(device) => Some(device),
+        Err(e) => {
+            Status::error(&e.to_string());
+            None
+        }
+    }
+}
+
+/// Install an APK on a device, reporting progress
+fn install_on_device(serial: &str, apk: &std::path::Path) -> bool {
+    use foodshare_android::device;
+
+    Status::info(&format!("Installing {} on {}...", apk.display(), serial));
+    match device::install_apk(serial, apk) {
+        Ok(result) if result.success => {
+            Status::success("App installed");
+            true
+        }
+        Ok(result) => {
+            Status::error("Install failed");
+            eprintln!("{}", result.combined_output());
+            false
+        }
+        Err(e) => {
+            Status::error(&format!("Install error: {}", e));
+            false
+        }
+    }
+}
+
+fn run_install(apk: Option<&std::path::Path>, device: Option<&str>, configuration: &str) -> i32 {
+    use foodshare_android::device as android_device;
+
+    let apk = match apk {
+        Some(apk) => apk.to_path_buf(),
+        None => match android_device::find_apk(std::path::Path::new("."), configuration) {
+            Ok(apk) => apk,
+            Err(e) => {
+                Status::error(&format!("{} (build it with `foodshare-android build`)", e));
+                return exit_codes::FAILURE;
+            }
+        },
+    };
+
+    let Some(target) = select_android_device(device) else {
+        return exit_codes::FAILURE;
+    };
+    if install_on_device(&target.serial, &apk) {
+        exit_codes::SUCCESS
+    } else {
+        exit_codes::FAILURE
+    }
+}
+
+fn run_app(device: Option<&str>, configuration: &str, clean: bool, logs: bool, package: Option<&str>) -> i32 {
+    use foodshare_android::device as android_device;
+
+    let project_dir = std::path::Path::new(".");
+    let Some(package) = package.map(String::from).or_else(|| android_device::application_id(project_dir)) else {
+        Status::error("No applicationId found in app/build.gradle.kts; pass --package");
+        return exit_codes::FAILURE;
+    };
+
+    // Step 1: Pick the device before spending time on the build
+    let Some(target) = select_android_device(device) else {
+        return exit_codes::FAILURE;
+    };
+
+    // Step 2: Build
+    let code = run_build(configuration, clean, false);
+    if code != exit_codes::SUCCESS {
+        return code;
+    }
+
+    // Step 3: Install
+    let apk = match android_device::find_apk(project_dir, configuration) {
+        Ok(apk) => apk,
+        Err(e) => {
+            Status::error(&format!("Built APK not found: {}", e));
+            return exit_codes::FAILURE;
+        }
+    };
+    if !install_on_device(&target.serial, &apk) {
+        return exit_codes::FAILURE;
+    }
+
+    // Step 4: Launch
+    Status::info("Launching app...");
+    match android_device::launch_app(&target.serial, &package) {
+        Ok(result) if result.success => Status::success("App launched"),
+        Ok(result) => {
+            Status::error("Launch failed");
+            eprintln!("{}", result.combined_output());
+            return exit_codes::FAILURE;
+        }
+        Err(e) => {
+            Status::error(&format!("Launch error: {}", e));
+            return exit_codes::FAILURE;
+        }
+    }
+
+    // Step 5: Stream logs if requested
+    if logs {
+        Status::info("Streaming logs (Ctrl+C to stop)...");
+        // Give the process a moment to start so its pid can be found
+        std::thread::sleep(std::time::Duration::from_secs(1));
+        if let Err(e) = android_device::stream_logcat(&target.serial, &package) {
+            Status::error(&format!("Failed to stream logs: {}", e));
+            return exit_codes::FAILURE;
+        }
+    }
+
+    exit_codes::SUCCESS
+}
+
+fn run_logs(
+    device: Option<&str>,
+    package: Option<&str>,
+    level: &str,
+    all: bool,
+    dump: bool,
+    crash_report: Option<&std::path::Path>,
+) -> i32 {
+    use foodshare_android::device as android_device;
+    use foodshare_android::logcat::{self, AppFilter, CrashDetector, CrashReport, LogLevel, LogLine};
+
+    let Some(min_level) = LogLevel::parse(level) else {
+        Status::error(&format!("Unknown level: {} (expected verbose, debug, info, warn, error or fatal)", level));
+        return exit_codes::VALIDATION_ERROR;
+    };
+    let Some(package) = package.map(String::from).or_else(|| android_device::application_id(std::path::Path::new("."))) else {
+        Status::error("No applicationId found in app/build.gradle.kts; pass --package");
+        return exit_codes::FAILURE;
+    };
+    let Some(target) = select_android_device(device) else {
+        return exit_codes::FAILURE;
+    };
+
+    let pids = android_device::app_pids(&target.serial, &package).unwrap_or_default();
+    if pids.is_empty() && !dump {
+        Status::info(&format!("{} is not running; waiting for it to start...", package));
+    }
+    let device_name = format!(
+        "{}{}",
+        target.model.as_deref().unwrap_or(&target.serial),
+        target.api_level.map(|level| format!(", API {}", level)).unwrap_or_default()
+    );
+
+    let mut filter = AppFilter::new(&package, pids);
+    let mut detector = CrashDetector::default();
+    let mut crashes = 0;
+    let mut on_crash = |mut report: CrashReport| {
+        crashes += 1;
+        report.device = Some(device_name.clone());
+        logcat::print_crash(&report);
+        if let Some(path) = crash_report {
+            match report.write(path) {
+                Ok(()) => Status::info(&format!("Crash report written to {}", path.display())),
+                Err(e) => Status::warning(&format!("Failed to write crash report: {}", e)),
+            }
+        }
+    };
+
+    let result = logcat::stream(&target.serial, dump, |raw| {
+        let Some(line) = LogLine::parse(raw) else {
+            return;
+        };
+        let is_app = filter.matches(&line);
+        if is_app {
+            if let Some(report) = detector.feed(&line, raw) {
+                on_crash(report);
+            }
+        }
+        if (is_app || all) && line.level >= min_level {
+            logcat::print_line(&line, raw, is_app && detector.in_crash());
+        }
+    });
+    if let Some(report) = detector.finish() {
+        on_crash(report);
+    }
+
+    match result {
+        Ok(_) => {
+            if dump && crashes > 0 {
+                Status::warning(&format!("{} crash(es) in the log buffer", crashes));
+            }
+            exit_codes::SUCCESS
+        }
+        Err(e) => {
+            Status::error(&format!("Failed to stream logs: {}", e));
+            exit_codes::FAILURE
+        }
+    }
+}
+
+fn run_swift_build(target: &str, configuration: &str) -> i32 {
     use foodshare_android::swift_android::{self, AndroidTarget};
 
     if !swift_android::has_swift() {
@@ -531,7 +1697,13 @@ fn run_swift_build(target: &str, configuration: &str) -> i32 {
     }
 }
 
-fn run_swift_java(action: &str) -> i32 {
+fn run_swift_java(
+    action: &str,
+    sources: &std::path::Path,
+    output: &std::path::Path,
+    app_sources: &std::path::Path,
+    package: &str,
+) -> i32 {
     use foodshare_android::swift_android;
 
     match action {
@@ -546,15 +1718,11 @@ fn run_swift_java(action: &str) -> i32 {
         }
         "generate" => {
             Status::info("Generating bindings...");
-            match swift_android::generate_bindings(
-                std::path::Path::new("swift-core/Sources"),
-                std::path::Path::new("app/src/main/java"),
-                "com.foodshare.swift",
-            ) {
+            match swift_android::generate_bindings(sources, output, "com.foodshare.swift") {
                 Ok(result) => {
                     if result.success {
                         Status::success("Bindings generated");
-                        exit_codes::SUCCESS
+                        check_bindings(sources, output, app_sources, package)
                     } else {
                         Status::error("Binding generation failed");
                         eprintln!("{}", result.stderr);
@@ -567,6 +1735,7 @@ fn run_swift_java(action: &str) -> i32 {
                 }
             }
         }
+        "check" => check_bindings(sources, output, app_sources, package),
         _ => {
             Status::error(&format!("Unknown action: {}", action));
             exit_codes::FAILURE
@@ -574,23 +1743,56 @@ fn run_swift_java(action: &str) -> i32 {
     }
 }
 
-fn run_doctor(_json: bool) -> i32 {
+fn check_bindings(
+    sources: &std::path::Path,
+    output: &std::path::Path,
+    app_sources: &std::path::Path,
+    package: &str,
+) -> i32 {
+    use foodshare_android::jni_bindings;
+
+    Status::info("Checking JNI bindings against the Swift core...");
+    let jvm_dirs = [output.to_path_buf(), app_sources.to_path_buf()];
+    match jni_bindings::verify(sources, &jvm_dirs, package) {
+        Ok(issues) => {
+            jni_bindings::print_issues(&issues);
+            let errors = issues.iter().filter(|i| i.is_error()).count();
+            if errors > 0 {
+                Status::error(&format!("{} binding mismatch(es) would fail at runtime", errors));
+                exit_codes::FAILURE
+            } else {
+                Status::success("JNI bindings match the Swift core");
+                exit_codes::SUCCESS
+            }
+        }
+        Err(e) => {
+            Status::error(&format!("Binding check error: {}", e));
+            exit_codes::FAILURE
+        }
+    }
+}
+
+fn run_doctor(fix: bool) -> i32 {
     use foodshare_android::{emulator, kotlin_tools, swift_android};
 
-    println!("Environment Check");
-    println!();
+    let mut missing = Vec::new();
+
+    textln!("Environment Check");
+    textln!();
 
     // Kotlin tools
     if kotlin_tools::has_ktlint() {
         Status::success("ktlint: installed");
     } else {
         Status::warning("ktlint: not found");
+        missing.push("ktlint");
     }
 
     if kotlin_tools::has_detekt() {
         Status::success("detekt: installed");
     } else {
         Status::warning("detekt: not found");
+        missing.push("detekt");
     }
 
     // Android tools
@@ -621,9 +1823,51 @@ fn run_doctor(_json: bool) -> i32 {
         Status::warning("swift-java: not found");
     }
 
+    if fix {
+        return install_missing_tools(&missing);
+    }
     exit_codes::SUCCESS
 }
 
+/// Offer to install missing tools with Homebrew (`doctor --fix`)
+fn install_missing_tools(missing: &[&str]) -> i32 {
+    use foodshare_core::process;
+
+    if missing.is_empty() {
+        return exit_codes::SUCCESS;
+    }
+    textln!();
+    if !process::command_exists("brew") {
+        Status::warning(&format!("Homebrew not found; install {} manually", missing.join(", ")));
+        return exit_codes::FAILURE;
+    }
+
+    let selected = match prompt::multi_select("Install with Homebrew:", missing, &vec![true; missing.len()]) {
+        Ok(selected) => selected,
+        Err(e) => {
+            Status::error(&e.to_string());
+            return exit_codes::FAILURE;
+        }
+    };
+
+    let mut exit_code = exit_codes::SUCCESS;
+    for (step, tool) in selected.iter().map(|&i| missing[i]).enumerate() {
+        Status::step(step + 1, selected.len(), &format!("brew install {}", tool));
+        match process::run_command("brew", &["install", tool]) {
+            Ok(result) if result.success => Status::success(&format!("{}: installed", tool)),
+            Ok(result) => {
+                Status::error(&format!("{}: brew install failed: {}", tool, result.stderr.trim()));
+                exit_code = exit_codes::FAILURE;
+            }
+            Err(e) => {
+                Status::error(&format!("{}: {}", tool, e));
+                exit_code = exit_codes::FAILURE;
+            }
+        }
+    }
+    exit_code
+}
+
 fn run_verify() -> i32 {
     Status::info("Verifying setup...");
 
@@ -643,16 +1887,28 @@ fn run_swift_core(action: SwiftCoreAction) -> i32 {
     use owo_colors::OwoColorize;
 
     match action {
-        SwiftCoreAction::Check => {
+        SwiftCoreAction::Check { verify } => {
             match swift_core::check_prerequisites() {
                 Ok(status) => {
                     status.print_status();
                     if status.is_ready() {
-                        println!();
+                        textln!();
+                        if verify {
+                            Status::info("Cross-compiling hello world...");
+                            let ndk = swift_core::detect_ndk();
+                            if let Err(e) = swift_core::verify_cross_compile(
+                                SwiftAndroidTarget::Arm64,
+                                swift_core::DEFAULT_API_LEVEL,
+                                ndk.as_ref(),
+                            ) {
+                                Status::error(&format!("Verification failed: {}", e));
+                                return exit_codes::FAILURE;
+                            }
+                        }
                         Status::success("Ready to build Swift for Android");
                         exit_codes::SUCCESS
                     } else {
-                        println!();
+                        textln!();
                         Status::error("Prerequisites not met");
                         swift_core::print_setup_instructions();
                         exit_codes::FAILURE
@@ -672,17 +1928,17 @@ fn run_swift_core(action: SwiftCoreAction) -> i32 {
             api_level,
             copy,
         } => {
-            println!("{}", "Building FoodshareCore for Android".bold());
-            println!("Architecture: {}", target);
-            println!("Configuration: {}", configuration);
-            println!();
+            textln!("{}", "Building FoodshareCore for Android".bold());
+            textln!("Architecture: {}", target);
+            textln!("Configuration: {}", configuration);
+            textln!();
 
             // Check prerequisites first
             match swift_core::check_prerequisites() {
                 Ok(status) => {
                     if !status.is_ready() {
                         status.print_status();
-                        Status::error("Prerequisites not met. Run 'swift-core setup' for instructions.");
+                        Status::error("Prerequisites not met. Run 'swift-core setup' to install them.");
                         return exit_codes::FAILURE;
                     }
                 }
@@ -729,14 +1985,14 @@ fn run_swift_core(action: SwiftCoreAction) -> i32 {
 
             match results {
                 Ok(build_results) => {
-                    println!();
+                    textln!();
                     let success_count = build_results.iter().filter(|r| r.success).count();
                     let total = build_results.len();
 
                     if success_count == total {
                         Status::success(&format!("Built {} target(s) successfully", total));
-                        println!();
-                        println!("{}", "Build complete!".green().bold());
+                        textln!();
+                        textln!("{}", "Build complete!".green().bold());
                         exit_codes::SUCCESS
                     } else {
                         for result in &build_results {
@@ -771,9 +2027,106 @@ fn run_swift_core(action: SwiftCoreAction) -> i32 {
                 }
             }
         }
-        SwiftCoreAction::Setup => {
-            swift_core::print_setup_instructions();
-            exit_codes::SUCCESS
+        SwiftCoreAction::Setup {
+            sdk,
+            checksum,
+            api_level,
+            skip_verify,
+            print_env,
+        } => run_swift_core_setup(sdk, checksum, api_level, skip_verify, print_env),
+    }
+}
+
+fn run_swift_core_setup(
+    sdk: Option<String>,
+    checksum: Option<String>,
+    api_level: u8,
+    skip_verify: bool,
+    print_env: bool,
+) -> i32 {
+    use foodshare_android::swift_core::{self, SwiftAndroidTarget};
+
+    if print_env {
+        return match swift_core::detect_ndk() {
+            Some(ndk) => {
+                print!("{}", ndk.exports());
+                exit_codes::SUCCESS
+            }
+            None => {
+                Status::error(&format!("No Android NDK r{}+ found", swift_core::MIN_NDK_MAJOR));
+                exit_codes::FAILURE
+            }
+        };
+    }
+
+    let status = match swift_core::check_prerequisites() {
+        Ok(status) => status,
+        Err(e) => {
+            Status::error(&format!("Prerequisite check failed: {}", e));
+            return exit_codes::FAILURE;
         }
+    };
+    if !status.swift_installed {
+        Status::error("Swift not found");
+        swift_core::print_setup_instructions();
+        return exit_codes::FAILURE;
     }
+
+    let ndk = match swift_core::detect_ndk() {
+        Some(ndk) => {
+            Status::success(&format!("Android NDK {} ({})", ndk.version, ndk.path.display()));
+            ndk
+        }
+        None => {
+            Status::info(&format!("Installing Android NDK {}...", swift_core::DEFAULT_NDK_VERSION));
+            match swift_core::install_ndk() {
+                Ok(ndk) => {
+                    Status::success(&format!("Installed Android NDK {}", ndk.version));
+                    ndk
+                }
+                Err(e) => {
+                    Status::error(&format!("Setup failed: {}", e));
+                    return exit_codes::FAILURE;
+                }
+            }
+        }
+    };
+
+    if status.android_sdk_installed {
+        Status::success(&format!("Swift SDK for Android: {}", status.android_sdks.join(", ")));
+    } else {
+        let Some(source) = sdk.or_else(|| status.swift_tag.as_deref().map(swift_core::android_sdk_url)) else {
+            Status::error("Cannot tell which Swift SDK matches this toolchain; pass --sdk <URL>");
+            return exit_codes::FAILURE;
+        };
+        Status::info(&format!("Installing Swift SDK for Android from {}...", source));
+        if let Err(e) = swift_core::install_android_sdk(&source, checksum.as_deref()) {
+            Status::error(&format!("Setup failed: {}", e));
+            return exit_codes::FAILURE;
+        }
+        if let Some(script) = swift_core::find_sdk_setup_script() {
+            Status::info("Linking the NDK into the Swift SDK...");
+            if let Err(e) = swift_core::run_sdk_setup_script(&script, &ndk) {
+                Status::error(&format!("Setup failed: {}", e));
+                return exit_codes::FAILURE;
+            }
+        }
+        Status::success("Installed Swift SDK for Android");
+    }
+
+    if !skip_verify {
+        Status::info("Cross-compiling hello world...");
+        if let Err(e) = swift_core::verify_cross_compile(SwiftAndroidTarget::Arm64, api_level, Some(&ndk)) {
+            Status::error(&format!("Setup failed: {}", e));
+            return exit_codes::FAILURE;
+        }
+        Status::success("Cross-compile works");
+    }
+
+    if !status.ndk_env_set {
+        textln!();
+        textln!("Add the NDK to your shell:");
+        print!("{}", ndk.exports());
+    }
+    exit_codes::SUCCESS
 }
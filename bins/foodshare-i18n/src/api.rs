@@ -4,9 +4,43 @@
 
 use crate::types::*;
 use anyhow::{Context, Result};
+use foodshare_api_client::middleware::{
+    CorrelationId, HttpCache, RequestLogger, RequestMetrics, TraceContextPropagation,
+};
+use foodshare_api_client::transport::ReplayTransport;
 use foodshare_api_client::{ClientConfig, FoodshareClient};
+use foodshare_cli::progress;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
 use std::time::Duration;
 
+/// Cassette that every client answers from (set by `--offline-fixtures`)
+static OFFLINE_FIXTURES: OnceLock<PathBuf> = OnceLock::new();
+
+/// Answer all API requests from a recorded cassette instead of the network
+///
+/// Must be called before the first [`ApiClient`] is created; later calls are ignored.
+pub fn use_offline_fixtures(path: PathBuf) {
+    let _ = OFFLINE_FIXTURES.set(path);
+}
+
+/// Whether clients answer from the HTTP cache alone (set by `--offline`)
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Answer GET requests from the on-disk HTTP cache, never the network
+///
+/// Bundles are cached whenever a command fetches them online. Must be called
+/// before the first [`ApiClient`] is created.
+pub fn use_offline_cache() {
+    OFFLINE.store(true, Ordering::Relaxed);
+}
+
+/// Whether `--offline` was passed
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
 /// HTTP client wrapper for translation API
 ///
 /// This is a compatibility layer that wraps `FoodshareClient` and provides
@@ -17,12 +51,43 @@ pub struct ApiClient {
 
 impl ApiClient {
     /// Create a new API client
+    ///
+    /// GET responses are cached on disk and revalidated with ETags, so
+    /// unchanged translation bundles are not downloaded again. With
+    /// `--offline-fixtures`, responses come from the cassette instead, and
+    /// with `--offline` from the cache alone.
     pub fn new() -> Result<Self> {
-        let config = ClientConfig::from_env()
+        let config = crate::config::client_config()
             .map_err(|e| anyhow::anyhow!("Failed to load config: {}", e))?;
 
-        let client = FoodshareClient::with_config(config)
-            .map_err(|e| anyhow::anyhow!("Failed to create client: {}", e))?;
+        let metrics = RequestMetrics::for_config(&config);
+        let mut client = FoodshareClient::with_config(config)
+            .map_err(|e| anyhow::anyhow!("Failed to create client: {}", e))?
+            .with_middleware(CorrelationId::new())
+            .with_middleware(TraceContextPropagation)
+            .with_middleware(RequestLogger)
+            .with_middleware(metrics);
+
+        if let Some(path) = OFFLINE_FIXTURES.get() {
+            let replay = ReplayTransport::open(path)
+                .with_context(|| format!("Failed to load offline fixtures {}", path.display()))?;
+            return Ok(Self {
+                client: client.with_transport(Arc::new(replay)),
+            });
+        }
+
+        if is_offline() {
+            let cache = HttpCache::open_default()
+                .map_err(|e| anyhow::anyhow!("Failed to open the HTTP cache: {}", e))?;
+            return Ok(Self {
+                client: client.with_http_cache(cache).with_cache_only(),
+            });
+        }
+
+        // The cache is an optimization; run without it if the directory is unusable
+        if let Ok(cache) = HttpCache::open_default() {
+            client = client.with_http_cache(cache);
+        }
 
         Ok(Self { client })
     }
@@ -140,6 +205,20 @@ impl ApiClient {
         Ok((convert_translation_response(translations), elapsed))
     }
 
+    /// Download translations for a locale, drawing a transfer progress bar
+    pub async fn download_translations(&self, locale: &str) -> Result<TranslationResponse> {
+        let bar = progress::transfer_bar(None, &format!("Downloading {locale} bundle"));
+        let result = self
+            .client
+            .translations()
+            .download(locale, |p| progress::set_transfer(&bar, p.transferred, p.total))
+            .await;
+        bar.finish_and_clear();
+
+        let translations = result.context("Failed to download translations")?;
+        Ok(convert_translation_response(translations))
+    }
+
     /// Test ETag caching
     pub async fn test_etag_caching(&self, locale: &str, etag: &str) -> Result<u16> {
         self.client
@@ -239,6 +318,7 @@ impl ApiClient {
             new_version: result.new_version,
             message: result.message,
             error: result.error,
+            usage: result.usage,
         })
     }
 
@@ -0,0 +1,146 @@
+//! Resumable progress for long translation runs
+//!
+//! A checkpoint records the units of work a run finished (posts for
+//! `backfill`, locales for `translate all`) and the tokens used so far, and
+//! is saved as the run goes. With `--resume` the next run skips the finished
+//! units and keeps counting usage where the interrupted one stopped. A run
+//! that finishes without failures deletes its checkpoint.
+
+use crate::cost::Usage;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// Progress of one command's run
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Checkpoint {
+    #[serde(skip)]
+    path: PathBuf,
+    command: String,
+    done: BTreeSet<String>,
+    pub usage: Usage,
+}
+
+impl Checkpoint {
+    /// Default location (`<data dir>/foodshare-tools/checkpoints/<command>.json`)
+    pub fn default_path(command: &str) -> PathBuf {
+        dirs::data_local_dir()
+            .unwrap_or_else(|| PathBuf::from(".local/share"))
+            .join("foodshare-tools")
+            .join("checkpoints")
+            .join(format!("{}.json", command))
+    }
+
+    /// Open the checkpoint for `command` at `path` (or the default)
+    ///
+    /// Without `resume` the run starts over and an old checkpoint is replaced
+    /// on the first save.
+    pub fn open(path: Option<&Path>, command: &str, resume: bool) -> Result<Self> {
+        let path = path.map_or_else(|| Self::default_path(command), Path::to_path_buf);
+        if !resume || !path.exists() {
+            return Ok(Self {
+                path,
+                command: command.to_string(),
+                ..Self::default()
+            });
+        }
+
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let mut checkpoint: Self = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse checkpoint {}", path.display()))?;
+        if checkpoint.command != command {
+            anyhow::bail!(
+                "{} is a checkpoint of `{}`, not `{}`",
+                path.display(),
+                checkpoint.command,
+                command
+            );
+        }
+        checkpoint.path = path;
+        Ok(checkpoint)
+    }
+
+    /// Checkpoint file path
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Units finished by earlier runs or this one
+    pub fn done(&self) -> usize {
+        self.done.len()
+    }
+
+    pub fn is_done(&self, id: &str) -> bool {
+        self.done.contains(id)
+    }
+
+    /// Mark a unit finished
+    pub fn complete(&mut self, id: impl Into<String>) {
+        self.done.insert(id.into());
+    }
+
+    /// Write the checkpoint, replacing the file atomically
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let temp = self.path.with_extension("json.tmp");
+        std::fs::write(&temp, serde_json::to_string(self)?)
+            .with_context(|| format!("Failed to write {}", temp.display()))?;
+        std::fs::rename(&temp, &self.path)
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+
+    /// Delete the checkpoint of a finished run
+    pub fn remove(&self) -> Result<()> {
+        match std::fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(e).with_context(|| format!("Failed to remove {}", self.path.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resume() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("checkpoints/backfill.json");
+
+        let mut checkpoint = Checkpoint::open(Some(&path), "backfill", true).unwrap();
+        assert_eq!(checkpoint.done(), 0);
+        checkpoint.complete("post-1");
+        checkpoint.complete("post-2");
+        checkpoint.save().unwrap();
+
+        let resumed = Checkpoint::open(Some(&path), "backfill", true).unwrap();
+        assert_eq!(resumed.done(), 2);
+        assert!(resumed.is_done("post-1"));
+        assert!(!resumed.is_done("post-3"));
+
+        // Without --resume the run starts over
+        let fresh = Checkpoint::open(Some(&path), "backfill", false).unwrap();
+        assert_eq!(fresh.done(), 0);
+
+        resumed.remove().unwrap();
+        assert!(!path.exists());
+        resumed.remove().unwrap();
+    }
+
+    #[test]
+    fn test_other_command() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("checkpoint.json");
+        Checkpoint::open(Some(&path), "backfill", false).unwrap().save().unwrap();
+
+        let err = Checkpoint::open(Some(&path), "translate-all", true).unwrap_err();
+        assert!(err.to_string().contains("checkpoint of `backfill`"));
+    }
+}
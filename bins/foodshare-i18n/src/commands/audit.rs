@@ -1,17 +1,41 @@
 //! Audit command - check translation coverage
+//!
+//! Coverage is measured against the English bundle, or with an inventory
+//! from `extract` against the keys the apps actually use; English keys no
+//! app uses are then reported as unused. With `--offline` the bundles come
+//! from the local cache and missing keys are found by comparing them with
+//! English instead of asking the server.
 
-use crate::api::ApiClient;
+use crate::api::{self, ApiClient};
 use crate::config::SUPPORTED_LOCALES;
-use crate::types::{JsonAuditOutput, LocaleAudit};
+use crate::inventory::Inventory;
+use crate::messageformat;
+use crate::types::{JsonAuditOutput, LocaleAudit, TranslationResponse};
 use anyhow::Result;
 use owo_colors::OwoColorize;
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::time::Duration;
+
+/// Locales fetched at once
+const AUDIT_CONCURRENCY: usize = 4;
 
 /// Run translation audit
-pub async fn run(locale: Option<&str>, show_missing: bool, limit: usize, format: &str) -> Result<()> {
+///
+/// `inventory` is an inventory file written by `extract --out`, or a
+/// directory to extract one from.
+pub async fn run(
+    locale: Option<&str>,
+    show_missing: bool,
+    limit: usize,
+    inventory: Option<&Path>,
+    format: &str,
+) -> Result<()> {
     let client = ApiClient::new()?;
+    let inventory = inventory.map(Inventory::from_path).transpose()?;
 
     if format == "json" {
-        return run_json(&client, locale, show_missing, limit).await;
+        return run_json(&client, locale, show_missing, limit, inventory.as_ref()).await;
     }
 
     println!();
@@ -27,27 +51,76 @@ pub async fn run(locale: Option<&str>, show_missing: bool, limit: usize, format:
             .blue()
     );
     println!();
+    if api::is_offline() {
+        println!(
+            "{} Offline: reading bundles from the local cache",
+            "ℹ".cyan()
+        );
+        println!();
+    }
 
     // Get English key count as reference
-    let (en_trans, _) = client.fetch_direct_translations("en").await?;
-    let en_keys = en_trans
-        .data
-        .as_ref()
-        .map(|d| count_keys(&d.messages))
-        .unwrap_or(0);
-    println!(
-        "Reference: English has {} translation keys",
-        en_keys.to_string().green()
-    );
+    let en_trans = client.download_translations("en").await?;
+    let en_keys = match &inventory {
+        Some(inventory) => {
+            println!(
+                "Reference: the apps use {} translation keys",
+                inventory.keys.len().to_string().green()
+            );
+            inventory.keys.len()
+        }
+        None => {
+            let en_keys = en_trans
+                .data
+                .as_ref()
+                .map(|d| count_keys(&d.messages))
+                .unwrap_or(0);
+            println!(
+                "Reference: English has {} translation keys",
+                en_keys.to_string().green()
+            );
+            en_keys
+        }
+    };
     println!();
 
-    if let Some(loc) = locale {
-        // Audit single locale
-        audit_single_locale(&client, loc, en_keys, show_missing, limit).await?;
-    } else {
-        // Audit all locales
-        for loc in SUPPORTED_LOCALES {
-            audit_single_locale(&client, loc, en_keys, show_missing, limit).await?;
+    let locales: Vec<&str> = match locale {
+        Some(loc) => vec![loc],
+        None => SUPPORTED_LOCALES.to_vec(),
+    };
+
+    let reference = offline_reference(&en_trans, inventory.as_ref());
+    let fetched = fetch_locales(&client, &locales).await;
+    for (loc, result) in locales.iter().zip(fetched) {
+        audit_single_locale(
+            &client,
+            loc,
+            result,
+            en_keys,
+            show_missing,
+            limit,
+            inventory.as_ref(),
+            reference.as_ref(),
+        )
+        .await?;
+    }
+
+    if let (Some(inventory), Some(data)) = (&inventory, &en_trans.data) {
+        let en_bundle = bundle_keys(&data.messages);
+        let unused = inventory.unused_in(&en_bundle);
+        if !unused.is_empty() {
+            println!();
+            println!(
+                "  {} {} English key(s) unused by the apps:",
+                "⚠".yellow(),
+                unused.len()
+            );
+            for key in unused.iter().take(limit) {
+                println!("    {}", key.dimmed());
+            }
+            if unused.len() > limit {
+                println!("    ... and {} more", unused.len() - limit);
+            }
         }
     }
 
@@ -55,18 +128,39 @@ pub async fn run(locale: Option<&str>, show_missing: bool, limit: usize, format:
     Ok(())
 }
 
+/// Fetch translations for several locales concurrently, in locale order
+async fn fetch_locales(
+    client: &ApiClient,
+    locales: &[&str],
+) -> Vec<Result<(TranslationResponse, Duration)>> {
+    client
+        .inner()
+        .batch(locales.iter().copied(), AUDIT_CONCURRENCY, |loc| {
+            client.fetch_direct_translations(loc)
+        })
+        .await
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn audit_single_locale(
     client: &ApiClient,
     locale: &str,
+    fetched: Result<(TranslationResponse, Duration)>,
     en_keys: usize,
     show_missing: bool,
     limit: usize,
+    inventory: Option<&Inventory>,
+    reference: Option<&BTreeSet<String>>,
 ) -> Result<()> {
-    // Fetch translations for this locale
-    match client.fetch_direct_translations(locale).await {
+    match fetched {
         Ok((resp, _)) => {
             if let Some(data) = resp.data {
-                let key_count = count_keys(&data.messages);
+                let local_missing =
+                    missing_locally(&bundle_keys(&data.messages), inventory, reference);
+                let key_count = match &local_missing {
+                    Some(missing) => en_keys.saturating_sub(missing.len()),
+                    None => count_keys(&data.messages),
+                };
                 let coverage = if en_keys > 0 {
                     (key_count as f64 / en_keys as f64) * 100.0
                 } else {
@@ -94,7 +188,12 @@ async fn audit_single_locale(
                 );
 
                 // Show missing keys if requested
-                if show_missing && key_count < en_keys {
+                if let (true, Some(missing)) = (show_missing, &local_missing) {
+                    if !missing.is_empty() {
+                        let shown: Vec<_> = missing.iter().take(limit).copied().collect();
+                        println!("         Missing: {}", shown.join(", ").dimmed());
+                    }
+                } else if show_missing && key_count < en_keys {
                     // Try to get audit info
                     if let Ok(audit) = client.audit_locale(locale, limit).await {
                         if let Some(untranslated) = audit.untranslated {
@@ -114,6 +213,9 @@ async fn audit_single_locale(
                 }
             }
         }
+        Err(e) if api::is_offline() => {
+            println!("  {:<5}: {} {:#}", locale, "Not cached:".red(), e);
+        }
         Err(_) => {
             println!("  {:<5}: {}", locale, "Error fetching".red());
         }
@@ -127,14 +229,18 @@ async fn run_json(
     locale: Option<&str>,
     show_missing: bool,
     limit: usize,
+    inventory: Option<&Inventory>,
 ) -> Result<()> {
     // Get English key count
     let (en_trans, _) = client.fetch_direct_translations("en").await?;
-    let en_keys = en_trans
-        .data
-        .as_ref()
-        .map(|d| count_keys(&d.messages))
-        .unwrap_or(0);
+    let en_keys = match inventory {
+        Some(inventory) => inventory.keys.len(),
+        None => en_trans
+            .data
+            .as_ref()
+            .map(|d| count_keys(&d.messages))
+            .unwrap_or(0),
+    };
 
     let locales_to_audit: Vec<&str> = if let Some(loc) = locale {
         vec![loc]
@@ -145,10 +251,17 @@ async fn run_json(
     let mut audits = Vec::new();
     let mut total_coverage = 0.0;
 
-    for loc in &locales_to_audit {
-        if let Ok((resp, _)) = client.fetch_direct_translations(loc).await {
+    let reference = offline_reference(&en_trans, inventory);
+    let fetched = fetch_locales(client, &locales_to_audit).await;
+    for (loc, result) in locales_to_audit.iter().zip(fetched) {
+        if let Ok((resp, _)) = result {
             if let Some(data) = resp.data {
-                let key_count = count_keys(&data.messages);
+                let local_missing =
+                    missing_locally(&bundle_keys(&data.messages), inventory, reference.as_ref());
+                let key_count = match &local_missing {
+                    Some(missing) => en_keys.saturating_sub(missing.len()),
+                    None => count_keys(&data.messages),
+                };
                 let coverage = if en_keys > 0 {
                     (key_count as f64 / en_keys as f64) * 100.0
                 } else {
@@ -156,45 +269,97 @@ async fn run_json(
                 };
                 total_coverage += coverage;
 
-                let missing_keys = if show_missing && key_count < en_keys {
-                    client
-                        .audit_locale(loc, limit)
-                        .await
-                        .ok()
-                        .and_then(|a| a.untranslated)
-                        .map(|u| u.iter().take(limit).map(|k| k.key.clone()).collect())
-                } else {
-                    None
-                };
-
                 audits.push(LocaleAudit {
                     locale: loc.to_string(),
                     total_keys: en_keys,
                     translated: key_count,
                     untranslated: en_keys.saturating_sub(key_count),
                     coverage,
-                    missing_keys,
+                    missing_keys: local_missing
+                        .filter(|_| show_missing)
+                        .map(|missing| missing.iter().take(limit).map(|k| k.to_string()).collect()),
                 });
             }
         }
     }
 
+    if show_missing && inventory.is_none() && reference.is_none() {
+        let incomplete: Vec<usize> = (0..audits.len())
+            .filter(|&i| audits[i].translated < en_keys)
+            .collect();
+        let missing = client
+            .inner()
+            .batch(incomplete.iter().copied(), AUDIT_CONCURRENCY, |i| {
+                client.audit_locale(&audits[i].locale, limit)
+            })
+            .await;
+        for (i, audit) in incomplete.into_iter().zip(missing) {
+            audits[i].missing_keys = audit
+                .ok()
+                .and_then(|a| a.untranslated)
+                .map(|u| u.iter().take(limit).map(|k| k.key.clone()).collect());
+        }
+    }
+
     let avg_coverage = if !audits.is_empty() {
         total_coverage / audits.len() as f64
     } else {
         0.0
     };
 
+    let unused_keys = inventory
+        .zip(en_trans.data.as_ref())
+        .map(|(inventory, data)| {
+            let en_bundle = bundle_keys(&data.messages);
+            inventory
+                .unused_in(&en_bundle)
+                .into_iter()
+                .map(String::from)
+                .collect()
+        });
+
     let output = JsonAuditOutput {
         locales: audits,
         total_locales: locales_to_audit.len(),
         average_coverage: avg_coverage,
+        unused_keys,
     };
 
     println!("{}", serde_json::to_string_pretty(&output)?);
     Ok(())
 }
 
+/// English keys to find missing keys against when offline, where the
+/// server's audit can't be asked
+fn offline_reference(
+    en_trans: &TranslationResponse,
+    inventory: Option<&Inventory>,
+) -> Option<BTreeSet<String>> {
+    if !api::is_offline() || inventory.is_some() {
+        return None;
+    }
+    en_trans.data.as_ref().map(|d| bundle_keys(&d.messages))
+}
+
+/// Keys missing from `bundle` by the inventory, or by the offline reference
+fn missing_locally<'a>(
+    bundle: &BTreeSet<String>,
+    inventory: Option<&'a Inventory>,
+    reference: Option<&'a BTreeSet<String>>,
+) -> Option<Vec<&'a str>> {
+    match (inventory, reference) {
+        (Some(inventory), _) => Some(inventory.missing_from(bundle)),
+        (None, Some(reference)) => Some(
+            reference
+                .iter()
+                .filter(|key| !bundle.contains(*key))
+                .map(String::as_str)
+                .collect(),
+        ),
+        (None, None) => None,
+    }
+}
+
 /// Count keys in a nested JSON object
 fn count_keys(value: &serde_json::Value) -> usize {
     match value {
@@ -212,3 +377,10 @@ fn count_keys(value: &serde_json::Value) -> usize {
         _ => 0,
     }
 }
+
+/// Dotted keys of a messages object
+fn bundle_keys(messages: &serde_json::Value) -> BTreeSet<String> {
+    messageformat::flatten_messages(messages)
+        .into_keys()
+        .collect()
+}
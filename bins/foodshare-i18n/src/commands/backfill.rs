@@ -4,12 +4,24 @@
 //!
 //! Fetches all active posts from the database and triggers batch translation
 //! to populate Redis cache and PostgreSQL with translations for all locales.
+//! Posts are sent by a pool of `--concurrency` workers paced by the client's
+//! rate limiter, and each finished post is recorded in a checkpoint so an
+//! interrupted run continues with `--resume` (see [`crate::checkpoint`]).
+//! The run ends with a token and cost report (see [`crate::cost`]).
+//!
+//! With `--offline-queue`, translation requests that fail because the backend
+//! is unreachable are saved and replayed at the start of the next run.
 
+use crate::checkpoint::Checkpoint;
+use crate::cost::{CostReport, Pricing};
 use anyhow::{Context, Result};
+use foodshare_api_client::endpoints::translations::TokenUsage;
+use foodshare_api_client::{ApiError, FoodshareClient, OfflineQueue};
+use futures_util::StreamExt;
 use owo_colors::OwoColorize;
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
-use tokio::time::sleep;
+use std::path::Path;
+use std::time::Instant;
 
 /// Post data from the database
 #[derive(Debug, Deserialize)]
@@ -41,6 +53,8 @@ struct BatchTranslateResponse {
     total_translations: Option<i32>,
     queued: Option<i32>,
     error: Option<String>,
+    #[serde(default)]
+    usage: Option<TokenUsage>,
 }
 
 /// Supabase query response
@@ -56,52 +70,134 @@ struct SupabaseError {
     message: String,
 }
 
+/// Result of sending one post for translation
+enum PostOutcome {
+    /// Translations were queued server-side
+    Translated {
+        translations: usize,
+        usage: Option<TokenUsage>,
+        /// Characters of the fields sent
+        chars: usize,
+        fields: usize,
+    },
+    /// Backend unreachable; request saved to the offline queue
+    Queued,
+}
+
+/// Counts over a run
+#[derive(Debug, Default, Serialize)]
+struct Tally {
+    succeeded: usize,
+    failed: usize,
+    queued: usize,
+    translations: usize,
+}
+
+/// Posts finished between checkpoint saves
+const CHECKPOINT_EVERY: usize = 10;
+
+/// Checkpoint name of this command
+const CHECKPOINT_COMMAND: &str = "backfill";
+
 /// Run the backfill command
+#[allow(clippy::too_many_arguments)]
 pub async fn run(
-    batch_size: usize,
-    delay_ms: u64,
+    concurrency: usize,
     limit: Option<usize>,
     dry_run: bool,
+    offline_queue: bool,
+    resume: bool,
+    checkpoint: Option<&Path>,
     format: &str,
 ) -> Result<()> {
-    if format == "json" {
-        return run_json(batch_size, delay_ms, limit, dry_run).await;
-    }
+    let json = format == "json";
+    let started = Instant::now();
 
-    println!("{}", "Post Translation Backfill".bold().cyan());
-    println!("{}", "=".repeat(40).dimmed());
-    println!();
-
-    // Validate environment
-    let base_url =
-        std::env::var("SUPABASE_URL").context("SUPABASE_URL environment variable required")?;
-    let service_key = std::env::var("SUPABASE_SERVICE_ROLE_KEY")
-        .context("SUPABASE_SERVICE_ROLE_KEY environment variable required")?;
+    let client = backfill_client(offline_queue && !dry_run)?;
+    let mut checkpoint = Checkpoint::open(checkpoint, CHECKPOINT_COMMAND, resume)?;
+    let pricing = Pricing::from_env();
 
-    println!("Configuration:");
-    println!("  Batch size: {}", batch_size);
-    println!("  Delay between batches: {}ms", delay_ms);
-    if let Some(l) = limit {
-        println!("  Limit: {} posts", l);
-    }
-    if dry_run {
-        println!("  {} Dry run mode", "!".yellow());
+    if !json {
+        println!("{}", "Post Translation Backfill".bold().cyan());
+        println!("{}", "=".repeat(40).dimmed());
+        println!();
+        println!("Configuration:");
+        println!("  Concurrency: {}", concurrency);
+        if let Some(l) = limit {
+            println!("  Limit: {} posts", l);
+        }
+        if dry_run {
+            println!("  {} Dry run mode", "!".yellow());
+        }
+        if offline_queue {
+            println!("  Offline queue: {}", OfflineQueue::default_path(client.config().project_url()).display());
+        }
+        println!("  Checkpoint: {}", checkpoint.path().display());
+        if checkpoint.done() > 0 {
+            println!(
+                "  Resuming: {} posts already done",
+                checkpoint.done().to_string().green()
+            );
+        }
+        println!();
     }
-    println!();
 
-    // Fetch posts
-    println!("{}", "Fetching active posts...".bold());
-    let posts = fetch_active_posts(&base_url, &service_key, limit).await?;
+    let replay = match client.offline_queue() {
+        Some(queue) if !queue.is_empty() => {
+            if !json {
+                println!("{}", format!("Replaying {} queued requests...", queue.len()).bold());
+            }
+            let report = client
+                .replay_offline_queue()
+                .await
+                .context("Failed to replay offline queue")?;
+            if !json {
+                println!(
+                    "  {} replayed, {} dropped, {} still queued",
+                    report.replayed.to_string().green(),
+                    report.dropped.to_string().red(),
+                    report.remaining
+                );
+                println!();
+            }
+            Some(report)
+        }
+        _ => None,
+    };
 
-    if posts.is_empty() {
-        println!("{}", "No posts to translate".yellow());
-        return Ok(());
+    // Fetch posts
+    if !json {
+        println!("{}", "Fetching active posts...".bold());
     }
-
-    println!("  Found {} active posts", posts.len().to_string().green());
-    println!();
+    let posts = fetch_active_posts(&client, limit).await?;
+    let found = posts.len();
+    let posts: Vec<Post> = posts
+        .into_iter()
+        .filter(|p| !checkpoint.is_done(&p.id.to_string()))
+        .collect();
+    let skipped = found - posts.len();
 
     if dry_run {
+        if json {
+            let output = serde_json::json!({
+                "success": true,
+                "dry_run": true,
+                "posts_found": found,
+                "already_done": skipped,
+                "posts": posts.iter().take(10).map(|p| serde_json::json!({
+                    "id": p.id,
+                    "title": p.post_name,
+                    "has_description": p.post_description.is_some()
+                })).collect::<Vec<_>>()
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+            return Ok(());
+        }
+        println!("  Found {} active posts", found.to_string().green());
+        if skipped > 0 {
+            println!("  {} already done in the checkpoint", skipped);
+        }
+        println!();
         println!("{}", "Dry run - would process:".yellow());
         for post in posts.iter().take(5) {
             println!(
@@ -116,101 +212,116 @@ pub async fn run(
         return Ok(());
     }
 
-    // Process posts in batches
-    process_posts(&base_url, &service_key, &posts, batch_size, delay_ms).await?;
-
-    println!();
-    println!(
-        "{}",
-        "Backfill complete! Translations are processing in the background.".green().bold()
-    );
-    println!("  Check Redis and PostgreSQL for cached translations.");
-
-    Ok(())
-}
-
-/// Run in JSON output mode
-async fn run_json(
-    batch_size: usize,
-    delay_ms: u64,
-    limit: Option<usize>,
-    dry_run: bool,
-) -> Result<()> {
-    let base_url = std::env::var("SUPABASE_URL")?;
-    let service_key = std::env::var("SUPABASE_SERVICE_ROLE_KEY")?;
+    if !json {
+        println!("  Found {} active posts", found.to_string().green());
+        if skipped > 0 {
+            println!("  {} already done in the checkpoint", skipped);
+        }
+        println!();
+        if posts.is_empty() {
+            println!("{}", "No posts to translate".yellow());
+            checkpoint.remove()?;
+            return Ok(());
+        }
+    }
 
-    let posts = fetch_active_posts(&base_url, &service_key, limit).await?;
+    let tally = process_posts(&client, &posts, concurrency, &mut checkpoint, json).await?;
+    let report = CostReport::new(checkpoint.usage, &pricing, started.elapsed());
+    if tally.failed == 0 {
+        checkpoint.remove()?;
+    }
 
-    if dry_run {
+    if json {
         let output = serde_json::json!({
             "success": true,
-            "dry_run": true,
-            "posts_found": posts.len(),
-            "posts": posts.iter().take(10).map(|p| serde_json::json!({
-                "id": p.id,
-                "title": p.post_name,
-                "has_description": p.post_description.is_some()
-            })).collect::<Vec<_>>()
+            "total": found,
+            "already_done": skipped,
+            "succeeded": tally.succeeded,
+            "failed": tally.failed,
+            "queued": tally.queued,
+            "translations": tally.translations,
+            "replayed": replay,
+            "cost": report,
+            "checkpoint": (tally.failed > 0).then(|| checkpoint.path().display().to_string()),
         });
         println!("{}", serde_json::to_string_pretty(&output)?);
         return Ok(());
     }
 
-    let (succeeded, failed) =
-        process_posts_counted(&base_url, &service_key, &posts, batch_size, delay_ms).await?;
-
-    let output = serde_json::json!({
-        "success": true,
-        "total": posts.len(),
-        "succeeded": succeeded,
-        "failed": failed,
-        "estimated_translations": succeeded * 20 * 2
-    });
+    println!();
+    println!("{}", "Summary:".bold());
+    println!("  Total: {}", found);
+    if skipped > 0 {
+        println!("  Done in earlier runs: {}", skipped);
+    }
+    println!("  Succeeded: {}", tally.succeeded.to_string().green());
+    println!("  Failed: {}", tally.failed.to_string().red());
+    if tally.queued > 0 {
+        println!(
+            "  Queued for replay: {} (sent on the next run)",
+            tally.queued.to_string().yellow()
+        );
+    }
+    println!("  Translations queued: {}", tally.translations);
+    println!();
+    report.print();
+    println!();
+    if tally.failed > 0 {
+        println!(
+            "{} {} posts failed; run again with --resume to retry only those",
+            "!".yellow(),
+            tally.failed
+        );
+    } else {
+        println!(
+            "{}",
+            "Backfill complete! Translations are processing in the background.".green().bold()
+        );
+        println!("  Check Redis and PostgreSQL for cached translations.");
+    }
 
-    println!("{}", serde_json::to_string_pretty(&output)?);
     Ok(())
 }
 
-/// Fetch active posts from the database
-async fn fetch_active_posts(
-    base_url: &str,
-    service_key: &str,
-    limit: Option<usize>,
-) -> Result<Vec<Post>> {
-    let client = reqwest::Client::new();
+/// Build the API client for the selected environment
+///
+/// The service role key doubles as the `apikey` the REST API requires when
+/// the profile has no anon key.
+fn backfill_client(offline_queue: bool) -> Result<FoodshareClient> {
+    let mut config = crate::config::service_client_config()
+        .map_err(|e| anyhow::anyhow!("Failed to load config: {}", e))?;
+    if config.anon_key.is_none() {
+        config.anon_key = config.service_role_key.clone();
+    }
+
+    let mut client = FoodshareClient::with_config(config)
+        .map_err(|e| anyhow::anyhow!("Failed to create client: {}", e))?;
+
+    if offline_queue {
+        let queue = OfflineQueue::open_default(client.config().project_url())
+            .map_err(|e| anyhow::anyhow!("Failed to open offline queue: {}", e))?;
+        client = client.with_offline_queue(queue);
+    }
 
+    Ok(client)
+}
+
+/// Fetch active posts from the database
+async fn fetch_active_posts(client: &FoodshareClient, limit: Option<usize>) -> Result<Vec<Post>> {
     let mut url = format!(
         "{}/rest/v1/posts?select=id,post_name,post_description&is_active=eq.true&post_name=not.is.null&order=created_at.desc",
-        base_url
+        client.config().project_url()
     );
 
     if let Some(l) = limit {
         url.push_str(&format!("&limit={}", l));
     }
 
-    let response = client
-        .get(&url)
-        .header("apikey", service_key)
-        .header("Authorization", format!("Bearer {}", service_key))
-        .send()
-        .await
-        .context("Failed to connect to Supabase")?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        anyhow::bail!("Failed to fetch posts: HTTP {} - {}", status, body);
-    }
-
-    let posts: Vec<Post> = response.json().await.context("Failed to parse posts")?;
-
-    Ok(posts)
+    client.get_url(&url).await.context("Failed to fetch posts")
 }
 
 /// Translate a single post
-async fn translate_post(base_url: &str, service_key: &str, post: &Post) -> Result<i32> {
-    let client = reqwest::Client::new();
-
+async fn translate_post(client: &FoodshareClient, post: &Post) -> Result<PostOutcome> {
     let mut fields = vec![TranslationField {
         name: "title".to_string(),
         text: post.post_name.clone(),
@@ -224,6 +335,8 @@ async fn translate_post(base_url: &str, service_key: &str, post: &Post) -> Resul
             });
         }
     }
+    let chars = fields.iter().map(|f| f.text.chars().count()).sum();
+    let field_count = fields.len();
 
     let request = BatchTranslateRequest {
         content_type: "post".to_string(),
@@ -231,136 +344,106 @@ async fn translate_post(base_url: &str, service_key: &str, post: &Post) -> Resul
         fields,
     };
 
-    let response = client
-        .post(format!(
-            "{}/functions/v1/localization/translate-batch",
-            base_url
-        ))
-        .header("Authorization", format!("Bearer {}", service_key))
-        .header("Content-Type", "application/json")
-        .json(&request)
-        .send()
-        .await
-        .context("Failed to send translation request")?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        anyhow::bail!("HTTP {}: {}", status, body);
-    }
-
-    let result: BatchTranslateResponse = response.json().await.context("Failed to parse response")?;
+    let result: BatchTranslateResponse = match client.post("localization/translate-batch", &request).await {
+        Ok(result) => result,
+        Err(ApiError::Queued { .. }) => return Ok(PostOutcome::Queued),
+        Err(e) => return Err(e).context("Failed to send translation request"),
+    };
 
     if let Some(err) = result.error {
         anyhow::bail!("{}", err);
     }
 
-    Ok(result.total_translations.unwrap_or(0))
+    Ok(PostOutcome::Translated {
+        translations: result.total_translations.unwrap_or(0).max(0) as usize,
+        usage: result.usage,
+        chars,
+        fields: field_count,
+    })
 }
 
-/// Process posts in batches
+/// Send posts through a pool of `concurrency` workers, recording each
+/// finished post in the checkpoint
 async fn process_posts(
-    base_url: &str,
-    service_key: &str,
+    client: &FoodshareClient,
     posts: &[Post],
-    batch_size: usize,
-    delay_ms: u64,
-) -> Result<()> {
-    println!(
-        "Processing {} posts in batches of {}...",
-        posts.len(),
-        batch_size
-    );
-    println!();
-
-    let mut processed = 0;
-    let mut succeeded = 0;
-    let mut failed = 0;
-
-    let total_batches = (posts.len() + batch_size - 1) / batch_size;
-
-    for (batch_idx, batch) in posts.chunks(batch_size).enumerate() {
+    concurrency: usize,
+    checkpoint: &mut Checkpoint,
+    quiet: bool,
+) -> Result<Tally> {
+    if !quiet {
         println!(
-            "{}",
-            format!("Batch {}/{}:", batch_idx + 1, total_batches).bold()
+            "Processing {} posts, {} at a time...",
+            posts.len(),
+            concurrency
         );
+        println!();
+    }
+
+    let mut tally = Tally::default();
+    let mut finished = 0;
+    let mut outcomes = client.batch_unordered(posts, concurrency, |post| async move {
+        (post, translate_post(client, post).await)
+    });
 
-        for post in batch {
-            match translate_post(base_url, service_key, post).await {
-                Ok(count) => {
+    while let Some((post, outcome)) = outcomes.next().await {
+        finished += 1;
+        let progress = format!("[{}/{}]", finished, posts.len()).dimmed().to_string();
+        match outcome {
+            Ok(PostOutcome::Translated {
+                translations,
+                usage,
+                chars,
+                fields,
+            }) => {
+                // One LLM request per locale, each translating every field
+                let locales = translations.div_ceil(fields.max(1)).max(1);
+                checkpoint.usage.record(
+                    usage,
+                    locales as u64,
+                    chars * locales,
+                    chars * locales,
+                );
+                checkpoint.complete(post.id.to_string());
+                tally.succeeded += 1;
+                tally.translations += translations;
+                if !quiet {
                     println!(
-                        "  {} Post {}: {} translations queued",
+                        "  {} {} Post {}: {} translations queued",
+                        progress,
                         "✓".green(),
                         post.id,
-                        count
+                        translations
                     );
-                    succeeded += 1;
                 }
-                Err(e) => {
-                    println!("  {} Post {}: {}", "✗".red(), post.id, e);
-                    failed += 1;
+            }
+            Ok(PostOutcome::Queued) => {
+                checkpoint.complete(post.id.to_string());
+                tally.queued += 1;
+                if !quiet {
+                    println!(
+                        "  {} {} Post {}: offline, saved for replay",
+                        progress,
+                        "…".yellow(),
+                        post.id
+                    );
                 }
             }
-        }
-
-        processed += batch.len();
-        println!(
-            "Progress: {}/{} ({} succeeded, {} failed)",
-            processed,
-            posts.len(),
-            succeeded.to_string().green(),
-            failed.to_string().red()
-        );
-
-        // Delay between batches
-        if batch_idx + 1 < total_batches {
-            println!("  Waiting {}ms before next batch...", delay_ms);
-            sleep(Duration::from_millis(delay_ms)).await;
-        }
-
-        println!();
-    }
-
-    println!("{}", "Summary:".bold());
-    println!("  Total: {}", posts.len());
-    println!("  Succeeded: {}", succeeded.to_string().green());
-    println!("  Failed: {}", failed.to_string().red());
-    println!(
-        "  Estimated translations: {} (20 locales x 2 fields avg)",
-        succeeded * 20 * 2
-    );
-
-    Ok(())
-}
-
-/// Process posts and return counts for JSON output
-async fn process_posts_counted(
-    base_url: &str,
-    service_key: &str,
-    posts: &[Post],
-    batch_size: usize,
-    delay_ms: u64,
-) -> Result<(usize, usize)> {
-    let mut succeeded = 0;
-    let mut failed = 0;
-
-    let total_batches = (posts.len() + batch_size - 1) / batch_size;
-
-    for (batch_idx, batch) in posts.chunks(batch_size).enumerate() {
-        for post in batch {
-            match translate_post(base_url, service_key, post).await {
-                Ok(_) => succeeded += 1,
-                Err(_) => failed += 1,
+            Err(e) => {
+                tally.failed += 1;
+                if !quiet {
+                    println!("  {} {} Post {}: {:#}", progress, "✗".red(), post.id, e);
+                }
             }
         }
 
-        // Delay between batches
-        if batch_idx + 1 < total_batches {
-            sleep(Duration::from_millis(delay_ms)).await;
+        if finished % CHECKPOINT_EVERY == 0 {
+            checkpoint.save()?;
         }
     }
+    checkpoint.save()?;
 
-    Ok((succeeded, failed))
+    Ok(tally)
 }
 
 /// Truncate a string to a maximum length
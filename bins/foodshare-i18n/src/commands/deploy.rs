@@ -5,7 +5,9 @@
 //! - deploy-translation-system.sh
 
 use anyhow::{Context, Result};
+use foodshare_api_client::FoodshareClient;
 use owo_colors::OwoColorize;
+use reqwest::Method;
 use std::process::Command;
 
 /// Run the deploy command
@@ -162,12 +164,25 @@ async fn run_json(
     Ok(())
 }
 
-/// Check required environment variables
+/// Check the selected environment and optional environment variables
 fn check_environment() -> Result<()> {
-    let required_vars = [
-        ("SUPABASE_URL", "Supabase project URL"),
-        ("SUPABASE_SERVICE_ROLE_KEY", "Supabase service role key"),
-    ];
+    let config = match crate::config::service_client_config() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!();
+            eprintln!("{} {}", "Environment not configured:".red().bold(), e);
+            eprintln!();
+            eprintln!("Select a profile with --env, or set the service role key:");
+            eprintln!("  export SUPABASE_SERVICE_ROLE_KEY='your-service-role-key'");
+            anyhow::bail!("Environment not configured");
+        }
+    };
+    println!(
+        "  {} Target {} ({})",
+        "✓".green(),
+        config.project_url(),
+        config.environment.as_str()
+    );
 
     let optional_vars = [
         ("LLM_TRANSLATION_ENDPOINT", "LLM translation API endpoint"),
@@ -176,16 +191,6 @@ fn check_environment() -> Result<()> {
         ("UPSTASH_REDIS_TOKEN", "Redis auth token"),
     ];
 
-    let mut missing = Vec::new();
-
-    for (var, desc) in &required_vars {
-        if std::env::var(var).is_err() {
-            missing.push((*var, *desc));
-        } else {
-            println!("  {} {} is set", "✓".green(), var);
-        }
-    }
-
     for (var, desc) in &optional_vars {
         if std::env::var(var).is_err() {
             println!("  {} {} not set (optional: {})", "⚠".yellow(), var, desc);
@@ -194,19 +199,6 @@ fn check_environment() -> Result<()> {
         }
     }
 
-    if !missing.is_empty() {
-        eprintln!();
-        eprintln!("{}", "Missing required environment variables:".red().bold());
-        for (var, desc) in &missing {
-            eprintln!("  {} - {}", var.red(), desc);
-        }
-        eprintln!();
-        eprintln!("Set them in your shell or .env file:");
-        eprintln!("  export SUPABASE_URL='https://your-project.supabase.co'");
-        eprintln!("  export SUPABASE_SERVICE_ROLE_KEY='your-service-role-key'");
-        anyhow::bail!("Missing required environment variables");
-    }
-
     Ok(())
 }
 
@@ -255,47 +247,29 @@ fn deploy_edge_functions() -> Result<()> {
     Ok(())
 }
 
-/// Test deployment endpoints
+/// Test deployment endpoints of the selected environment
 async fn test_deployment() -> Result<()> {
-    let base_url =
-        std::env::var("SUPABASE_URL").context("SUPABASE_URL not set")?;
-    let service_key = std::env::var("SUPABASE_SERVICE_ROLE_KEY")
-        .context("SUPABASE_SERVICE_ROLE_KEY not set")?;
-
-    let client = reqwest::Client::new();
-
-    // Test localization endpoint
-    println!("  Testing /localization...");
-    let resp = client
-        .get(format!("{}/functions/v1/localization", base_url))
-        .header("Authorization", format!("Bearer {}", service_key))
-        .send()
-        .await
-        .context("Failed to connect to localization endpoint")?;
-
-    if resp.status().is_success() {
-        println!("    {} Localization endpoint responding", "✓".green());
-    } else {
-        println!(
-            "    {} Localization returned HTTP {}",
-            "✗".red(),
-            resp.status()
-        );
-    }
-
-    // Test BFF endpoint
-    println!("  Testing /bff...");
-    let resp = client
-        .get(format!("{}/functions/v1/bff", base_url))
-        .header("Authorization", format!("Bearer {}", service_key))
-        .send()
-        .await
-        .context("Failed to connect to BFF endpoint")?;
-
-    if resp.status().is_success() {
-        println!("    {} BFF endpoint responding", "✓".green());
-    } else {
-        println!("    {} BFF returned HTTP {}", "✗".red(), resp.status());
+    let config = crate::config::service_client_config()
+        .map_err(|e| anyhow::anyhow!("Failed to load config: {}", e))?;
+    let service_key = config.service_role_key.clone().unwrap_or_default();
+    let client = FoodshareClient::with_config(config)
+        .map_err(|e| anyhow::anyhow!("Failed to create client: {}", e))?;
+
+    for (path, name) in [("localization", "Localization"), ("bff", "BFF")] {
+        println!("  Testing /{}...", path);
+        let request = client
+            .request_builder(Method::GET, path)
+            .bearer_auth(&service_key);
+        let resp = client
+            .execute_raw(request)
+            .await
+            .with_context(|| format!("Failed to connect to {} endpoint", path))?;
+
+        if resp.status().is_success() {
+            println!("    {} {} endpoint responding", "✓".green(), name);
+        } else {
+            println!("    {} {} returned HTTP {}", "✗".red(), name, resp.status());
+        }
     }
 
     Ok(())
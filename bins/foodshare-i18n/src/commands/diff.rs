@@ -0,0 +1,443 @@
+//! Diff command - what changed in the bundles between two releases
+//!
+//! Each side is a snapshot file written by `snapshot`, a directory of
+//! `<locale>.json` bundles, `api` for the live bundles, or a git revision,
+//! read from the bundle directory (`--path`) at that revision. Keys are
+//! reported added, removed or changed per locale with word counts, as text,
+//! JSON, or Markdown for release notes.
+
+use crate::commands::validate;
+use crate::config::{SUPPORTED_LOCALES, get_locale_info};
+use crate::messageformat::{self, Part};
+use anyhow::{Context, Result};
+use foodshare_cli::output::diff::render_replacement;
+use foodshare_core::git::GitRepo;
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Component, Path, PathBuf};
+
+/// Locale → dotted key → message
+type Bundles = BTreeMap<String, BTreeMap<String, String>>;
+
+/// A key added, removed or changed
+#[derive(Debug, Serialize)]
+struct KeyChange {
+    key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    old: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    new: Option<String>,
+    /// Words in the new message, or the removed one
+    words: usize,
+}
+
+/// Changes to one locale
+#[derive(Debug, Serialize)]
+struct LocaleDiff {
+    locale: String,
+    added: Vec<KeyChange>,
+    changed: Vec<KeyChange>,
+    removed: Vec<KeyChange>,
+    added_words: usize,
+    changed_words: usize,
+    removed_words: usize,
+}
+
+/// JSON output for diff
+#[derive(Debug, Serialize)]
+struct JsonDiffOutput {
+    from: String,
+    to: String,
+    locales: Vec<LocaleDiff>,
+    added: usize,
+    changed: usize,
+    removed: usize,
+    words: usize,
+}
+
+/// Run diff command
+pub async fn run(
+    from: &str,
+    to: &str,
+    path: &Path,
+    locales: &[String],
+    markdown: bool,
+    limit: usize,
+    format: &str,
+) -> Result<()> {
+    let old = load(from, path, locales).await?;
+    let new = load(to, path, locales).await?;
+
+    let all: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    let empty = BTreeMap::new();
+    let diffs: Vec<LocaleDiff> = all
+        .into_iter()
+        .map(|locale| {
+            diff_locale(
+                locale,
+                old.get(locale).unwrap_or(&empty),
+                new.get(locale).unwrap_or(&empty),
+            )
+        })
+        .filter(|d| !d.added.is_empty() || !d.changed.is_empty() || !d.removed.is_empty())
+        .collect();
+
+    if format == "json" {
+        let output = JsonDiffOutput {
+            from: from.to_string(),
+            to: to.to_string(),
+            added: diffs.iter().map(|d| d.added.len()).sum(),
+            changed: diffs.iter().map(|d| d.changed.len()).sum(),
+            removed: diffs.iter().map(|d| d.removed.len()).sum(),
+            words: diffs.iter().map(|d| d.added_words + d.changed_words).sum(),
+            locales: diffs,
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else if markdown {
+        print!("{}", release_notes(from, to, &diffs, limit));
+    } else {
+        print_report(from, to, &diffs, limit);
+    }
+    Ok(())
+}
+
+/// Run snapshot command: write the live bundles to one file for later diffs
+pub async fn snapshot(out: &Path, locales: &[String], format: &str) -> Result<()> {
+    let bundles = load("api", Path::new(""), locales).await?;
+    let mut json = serde_json::to_string_pretty(&bundles)?;
+    json.push('\n');
+    std::fs::write(out, json).with_context(|| format!("Failed to write {}", out.display()))?;
+
+    let keys: usize = bundles.values().map(BTreeMap::len).sum();
+    if format == "json" {
+        let output = serde_json::json!({
+            "path": out.display().to_string(),
+            "locales": bundles.len(),
+            "keys": keys,
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else {
+        println!(
+            "{} Saved {} locale(s), {} messages to {}",
+            "✓".green(),
+            bundles.len(),
+            keys,
+            out.display()
+        );
+    }
+    Ok(())
+}
+
+/// Read the bundles one side of the diff names
+async fn load(spec: &str, path: &Path, locales: &[String]) -> Result<Bundles> {
+    let wanted = |locale: &str| locales.is_empty() || locales.iter().any(|l| l == locale);
+    let mut bundles = Bundles::new();
+
+    let file = Path::new(spec);
+    if spec == "api" {
+        let requested: Vec<&str> = SUPPORTED_LOCALES
+            .iter()
+            .copied()
+            .filter(|l| wanted(l))
+            .collect();
+        for (locale, result) in requested
+            .iter()
+            .zip(validate::fetch_bundles(&requested).await?)
+        {
+            bundles.insert(locale.to_string(), result?);
+        }
+    } else if file.is_file() {
+        let content = std::fs::read_to_string(file)
+            .with_context(|| format!("Failed to read {}", file.display()))?;
+        let snapshot: BTreeMap<String, serde_json::Value> = serde_json::from_str(&content)
+            .with_context(|| format!("{} is not a snapshot (locale → messages)", file.display()))?;
+        for (locale, messages) in snapshot.into_iter().filter(|(l, _)| wanted(l)) {
+            bundles.insert(locale, messageformat::flatten_messages(&messages));
+        }
+    } else if file.is_dir() {
+        for locale in json_stems(std::fs::read_dir(file)?.filter_map(|e| e.ok().map(|e| e.path())))
+        {
+            if wanted(&locale) {
+                let messages = validate::load_bundle(file, &locale)?;
+                bundles.insert(locale, messages);
+            }
+        }
+    } else {
+        let repo = GitRepo::open_current()
+            .context("Not a snapshot, directory or `api`, and not in a git repository")?;
+        if !repo.rev_exists(spec) {
+            anyhow::bail!(
+                "'{}' is neither a snapshot file, a directory, `api` nor a git revision",
+                spec
+            );
+        }
+        let dir = repo_relative(repo.workdir(), path)?;
+        let files = repo.files_at(spec, &dir)?;
+        for locale in json_stems(files.into_iter().filter(|f| f.parent() == Some(&dir))) {
+            if wanted(&locale) {
+                let file = dir.join(format!("{}.json", locale));
+                let content = repo.file_at(spec, &file)?;
+                let messages: serde_json::Value =
+                    serde_json::from_str(&content).with_context(|| {
+                        format!("Failed to parse JSON from {}:{}", spec, file.display())
+                    })?;
+                bundles.insert(locale, messageformat::flatten_messages(&messages));
+            }
+        }
+        if bundles.is_empty() {
+            anyhow::bail!("No bundles in {} at {}", dir.display(), spec);
+        }
+    }
+    Ok(bundles)
+}
+
+/// Locales of the `<locale>.json` files among `paths`
+fn json_stems(paths: impl Iterator<Item = PathBuf>) -> BTreeSet<String> {
+    paths
+        .filter(|p| p.extension().is_some_and(|e| e == "json"))
+        .filter_map(|p| p.file_stem().map(|s| s.to_string_lossy().into_owned()))
+        .collect()
+}
+
+/// `path` (relative to the current directory) relative to the repository root
+fn repo_relative(root: &Path, path: &Path) -> Result<PathBuf> {
+    let cwd = std::env::current_dir()?.canonicalize()?;
+    let root = root.canonicalize()?;
+    let relative = cwd
+        .strip_prefix(&root)
+        .with_context(|| format!("{} is outside the repository", cwd.display()))?;
+
+    let mut normalized = PathBuf::new();
+    for component in relative.join(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if !normalized.pop() => {
+                anyhow::bail!("{} is outside the repository", path.display());
+            }
+            Component::ParentDir => {}
+            component => normalized.push(component),
+        }
+    }
+    Ok(normalized)
+}
+
+fn diff_locale(
+    locale: &str,
+    old: &BTreeMap<String, String>,
+    new: &BTreeMap<String, String>,
+) -> LocaleDiff {
+    let mut diff = LocaleDiff {
+        locale: locale.to_string(),
+        added: Vec::new(),
+        changed: Vec::new(),
+        removed: Vec::new(),
+        added_words: 0,
+        changed_words: 0,
+        removed_words: 0,
+    };
+    for (key, message) in new {
+        match old.get(key) {
+            None => {
+                let words = word_count(message);
+                diff.added_words += words;
+                diff.added.push(KeyChange {
+                    key: key.clone(),
+                    old: None,
+                    new: Some(message.clone()),
+                    words,
+                });
+            }
+            Some(previous) if previous != message => {
+                let words = word_count(message);
+                diff.changed_words += words;
+                diff.changed.push(KeyChange {
+                    key: key.clone(),
+                    old: Some(previous.clone()),
+                    new: Some(message.clone()),
+                    words,
+                });
+            }
+            Some(_) => {}
+        }
+    }
+    for (key, message) in old.iter().filter(|(key, _)| !new.contains_key(*key)) {
+        let words = word_count(message);
+        diff.removed_words += words;
+        diff.removed.push(KeyChange {
+            key: key.clone(),
+            old: Some(message.clone()),
+            new: None,
+            words,
+        });
+    }
+    diff
+}
+
+/// Words a translator handles in a message: placeholders and ICU syntax
+/// don't count, every plural and select case does, and each Han or kana
+/// character counts as a word since those scripts don't space words
+fn word_count(message: &str) -> usize {
+    fn text(parts: &[Part], out: &mut String) {
+        for part in parts {
+            match part {
+                Part::Text(t) => out.push_str(t),
+                Part::Argument { .. } | Part::Pound => out.push(' '),
+                Part::Plural { cases, .. } | Part::Select { cases, .. } => {
+                    for (_, case) in cases {
+                        out.push(' ');
+                        text(case, out);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut plain = String::new();
+    match messageformat::parse(message) {
+        Ok(parts) => text(&parts, &mut plain),
+        Err(_) => plain.push_str(message),
+    }
+
+    let mut count = 0;
+    let mut in_word = false;
+    for c in plain.chars() {
+        if matches!(c, '\u{3040}'..='\u{30FF}' | '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}')
+        {
+            count += 1;
+            in_word = false;
+        } else if c.is_whitespace() {
+            in_word = false;
+        } else if c.is_alphanumeric() && !in_word {
+            count += 1;
+            in_word = true;
+        }
+    }
+    count
+}
+
+/// Messages on one line for display
+fn one_line(message: &str) -> String {
+    message.replace('\n', "\\n")
+}
+
+fn print_report(from: &str, to: &str, diffs: &[LocaleDiff], limit: usize) {
+    println!();
+    println!(
+        "{}",
+        "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━".blue()
+    );
+    println!(
+        "  {}",
+        format!("🧾 Translation Changes: {} → {}", from, to)
+            .blue()
+            .bold()
+    );
+    println!(
+        "{}",
+        "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━".blue()
+    );
+    println!();
+
+    if diffs.is_empty() {
+        println!("  {} No translation changes", "✓".green());
+        println!();
+        return;
+    }
+
+    for diff in diffs {
+        println!(
+            "  {:<5}: {} {} {}  ({} words)",
+            diff.locale,
+            format!("+{}", diff.added.len()).green(),
+            format!("~{}", diff.changed.len()).yellow(),
+            format!("-{}", diff.removed.len()).red(),
+            diff.added_words + diff.changed_words
+        );
+
+        let changes: Vec<(char, &KeyChange)> = diff
+            .added
+            .iter()
+            .map(|c| ('+', c))
+            .chain(diff.changed.iter().map(|c| ('~', c)))
+            .chain(diff.removed.iter().map(|c| ('-', c)))
+            .collect();
+        for (kind, change) in changes.iter().take(limit) {
+            let old = change.old.as_deref().map(one_line).unwrap_or_default();
+            let new = change.new.as_deref().map(one_line).unwrap_or_default();
+            match kind {
+                '+' => println!("         {} {} {}", "+".green(), change.key, new.dimmed()),
+                '-' => println!("         {} {} {}", "-".red(), change.key, old.dimmed()),
+                _ => {
+                    let (before, after) = render_replacement(&old, &new);
+                    println!("         {} {}", "~".yellow(), change.key);
+                    println!("             {}", before);
+                    println!("             {}", after);
+                }
+            }
+        }
+        if changes.len() > limit {
+            println!("         ... and {} more", changes.len() - limit);
+        }
+    }
+
+    let words: usize = diffs.iter().map(|d| d.added_words + d.changed_words).sum();
+    println!();
+    println!(
+        "  {} locale(s) changed, {} words added or changed",
+        diffs.len().to_string().cyan(),
+        words.to_string().cyan()
+    );
+    println!();
+}
+
+/// Markdown for release notes: a summary table, then the keys per locale
+fn release_notes(from: &str, to: &str, diffs: &[LocaleDiff], limit: usize) -> String {
+    let names: BTreeMap<&str, &str> = get_locale_info().iter().map(|l| (l.code, l.name)).collect();
+    let mut md = format!("## Translations ({} → {})\n\n", from, to);
+    if diffs.is_empty() {
+        md.push_str("No translation changes.\n");
+        return md;
+    }
+
+    md.push_str("| Locale | Added | Changed | Removed | Words |\n");
+    md.push_str("|--------|------:|--------:|--------:|------:|\n");
+    for diff in diffs {
+        let locale = match names.get(diff.locale.as_str()) {
+            Some(name) => format!("{} (`{}`)", name, diff.locale),
+            None => format!("`{}`", diff.locale),
+        };
+        md.push_str(&format!(
+            "| {} | {} | {} | {} | {} |\n",
+            locale,
+            diff.added.len(),
+            diff.changed.len(),
+            diff.removed.len(),
+            diff.added_words + diff.changed_words
+        ));
+    }
+
+    for diff in diffs {
+        md.push_str(&format!(
+            "\n<details><summary>{}</summary>\n\n",
+            diff.locale
+        ));
+        let sections = [
+            ("Added", &diff.added),
+            ("Changed", &diff.changed),
+            ("Removed", &diff.removed),
+        ];
+        for (title, changes) in sections.into_iter().filter(|(_, c)| !c.is_empty()) {
+            md.push_str(&format!("**{}**\n\n", title));
+            for change in changes.iter().take(limit) {
+                md.push_str(&format!("- `{}`\n", change.key));
+            }
+            if changes.len() > limit {
+                md.push_str(&format!("- … and {} more\n", changes.len() - limit));
+            }
+            md.push('\n');
+        }
+        md.push_str("</details>\n");
+    }
+    md
+}
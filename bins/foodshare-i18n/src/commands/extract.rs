@@ -0,0 +1,100 @@
+//! Extract command - build the key inventory from the app sources
+//!
+//! Scans the iOS, Android and web sources for the keys they look up (see
+//! [`crate::inventory`]) and writes the result, which `audit --inventory`
+//! reads to report keys missing from the bundles and keys no app uses.
+
+use crate::inventory::{self, Inventory, Platform};
+use anyhow::{Context, Result};
+use owo_colors::OwoColorize;
+use std::path::{Path, PathBuf};
+
+/// Run extract command
+pub fn run(paths: &[PathBuf], out: Option<&Path>, limit: usize, format: &str) -> Result<()> {
+    let mut inventory = Inventory::default();
+    for path in paths {
+        if !path.is_dir() {
+            anyhow::bail!("Not a directory: {}", path.display());
+        }
+        inventory.merge(inventory::scan(path, Path::new(""), Platform::ALL)?);
+    }
+
+    if let Some(out) = out {
+        let mut json = serde_json::to_string_pretty(&inventory)?;
+        json.push('\n');
+        std::fs::write(out, json).with_context(|| format!("Failed to write {}", out.display()))?;
+    }
+
+    if format == "json" {
+        if out.is_none() {
+            println!("{}", serde_json::to_string_pretty(&inventory)?);
+        }
+        return Ok(());
+    }
+
+    print_report(&inventory, paths, out, limit);
+    Ok(())
+}
+
+fn print_report(inventory: &Inventory, paths: &[PathBuf], out: Option<&Path>, limit: usize) {
+    println!();
+    println!(
+        "{}",
+        "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━".blue()
+    );
+    println!("  {}", "🗂️  Translation Key Inventory".blue().bold());
+    println!(
+        "{}",
+        "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━".blue()
+    );
+    println!();
+
+    let scanned: Vec<String> = paths.iter().map(|p| p.display().to_string()).collect();
+    println!(
+        "  {} keys used in {} files under {}",
+        inventory.keys.len().to_string().cyan(),
+        inventory.files,
+        scanned.join(", ")
+    );
+    for (platform, count) in inventory.platform_counts() {
+        println!("    {:<8} {} keys", platform.as_str(), count);
+    }
+    if !inventory.dynamic_prefixes.is_empty() {
+        println!(
+            "  {} dynamic key prefix(es): {}",
+            inventory.dynamic_prefixes.len(),
+            inventory
+                .dynamic_prefixes
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(", ")
+                .dimmed()
+        );
+    }
+    println!();
+
+    for (key, usage) in inventory.keys.iter().take(limit) {
+        let platforms: Vec<&str> = usage.platforms.iter().map(|p| p.as_str()).collect();
+        let location = usage
+            .locations
+            .first()
+            .map(|l| format!("{}:{}", l.file, l.line))
+            .unwrap_or_default();
+        println!(
+            "    {} {} {}",
+            key,
+            format!("[{}]", platforms.join(", ")).cyan(),
+            location.dimmed()
+        );
+    }
+    if inventory.keys.len() > limit {
+        println!("    ... and {} more", inventory.keys.len() - limit);
+    }
+
+    if let Some(out) = out {
+        println!();
+        println!("  {} Written to {}", "✓".green(), out.display());
+    }
+    println!();
+}
@@ -0,0 +1,409 @@
+//! Gate command - fail CI when a locale's coverage is below its threshold
+//!
+//! Thresholds come from a gate file, by default `i18n-gate.json`:
+//!
+//! ```json
+//! {
+//!   "default": 80,
+//!   "tiers": [
+//!     { "name": "launch", "coverage": 95, "locales": ["de", "es", "fr"] }
+//!   ]
+//! }
+//! ```
+//!
+//! A locale takes the highest threshold of the tiers it is in, else the
+//! default; without a default, locales in no tier are reported but not
+//! gated. Coverage counts the source keys (or, with `--inventory`, the keys
+//! the apps use) that have a non-empty translation.
+
+use crate::commands::validate;
+use crate::config::SUPPORTED_LOCALES;
+use crate::inventory::Inventory;
+use anyhow::{Context, Result};
+use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// Gate file read when `--config` isn't given
+pub const DEFAULT_CONFIG: &str = "i18n-gate.json";
+
+/// Coverage thresholds
+#[derive(Debug, Deserialize)]
+struct GateConfig {
+    /// Percent required of locales in no tier
+    #[serde(default)]
+    default: Option<f64>,
+    #[serde(default)]
+    tiers: Vec<Tier>,
+}
+
+/// Locales held to the same threshold
+#[derive(Debug, Deserialize)]
+struct Tier {
+    name: String,
+    /// Percent required
+    coverage: f64,
+    locales: Vec<String>,
+}
+
+impl GateConfig {
+    fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let config: Self = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse gate config {}", path.display()))?;
+        let thresholds = config
+            .default
+            .iter()
+            .chain(config.tiers.iter().map(|t| &t.coverage));
+        if let Some(bad) = thresholds.copied().find(|t| !(0.0..=100.0).contains(t)) {
+            anyhow::bail!("{}: threshold {} is not a percentage", path.display(), bad);
+        }
+        Ok(config)
+    }
+
+    /// Tier name and threshold for `locale`
+    fn threshold(&self, locale: &str) -> Option<(Option<&str>, f64)> {
+        self.tiers
+            .iter()
+            .filter(|t| t.locales.iter().any(|l| l == locale))
+            .max_by(|a, b| a.coverage.total_cmp(&b.coverage))
+            .map(|t| (Some(t.name.as_str()), t.coverage))
+            .or_else(|| self.default.map(|d| (None, d)))
+    }
+}
+
+/// Outcome for one locale
+#[derive(Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum Status {
+    Pass,
+    Fail,
+    /// The bundle couldn't be loaded
+    Error,
+    /// No threshold applies
+    Ungated,
+}
+
+/// Gate result for one locale
+#[derive(Debug, Serialize)]
+struct LocaleGate {
+    locale: String,
+    tier: Option<String>,
+    threshold: Option<f64>,
+    coverage: f64,
+    translated: usize,
+    total_keys: usize,
+    status: Status,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// JSON report of a gate run
+#[derive(Debug, Serialize)]
+struct JsonGateOutput {
+    source: String,
+    config: String,
+    total_keys: usize,
+    passed: bool,
+    /// Gated locales below their threshold or not loaded
+    failed: Vec<String>,
+    locales: Vec<LocaleGate>,
+}
+
+/// Run gate command
+///
+/// `report` also writes the JSON report to a file, whatever the output format.
+pub async fn run(
+    locales: &[String],
+    source: &str,
+    config: Option<&Path>,
+    dir: Option<&Path>,
+    inventory: Option<&Path>,
+    report: Option<&Path>,
+    format: &str,
+) -> Result<()> {
+    let config_path = config.unwrap_or(Path::new(DEFAULT_CONFIG));
+    if config.is_none() && !config_path.exists() {
+        anyhow::bail!(
+            "No {} in the current directory; pass --config <FILE> with the thresholds",
+            DEFAULT_CONFIG
+        );
+    }
+    let config = GateConfig::load(config_path)?;
+    let inventory = inventory.map(Inventory::from_path).transpose()?;
+
+    let locales: Vec<&str> = if locales.is_empty() {
+        let mut all: BTreeSet<&str> = SUPPORTED_LOCALES.iter().copied().collect();
+        all.extend(
+            config
+                .tiers
+                .iter()
+                .flat_map(|t| t.locales.iter().map(String::as_str)),
+        );
+        all.remove(source);
+        all.into_iter().collect()
+    } else {
+        locales.iter().map(String::as_str).collect()
+    };
+
+    let source_messages = match dir {
+        Some(dir) => validate::load_bundle(dir, source)?,
+        None => validate::fetch_bundles(&[source]).await?.remove(0)?,
+    };
+    let loaded: Vec<Result<BTreeMap<String, String>>> = match dir {
+        Some(dir) => locales
+            .iter()
+            .map(|loc| validate::load_bundle(dir, loc))
+            .collect(),
+        None => validate::fetch_bundles(&locales).await?,
+    };
+
+    let keys: BTreeSet<&str> = match &inventory {
+        Some(inventory) => inventory.keys.keys().map(String::as_str).collect(),
+        None => source_messages.keys().map(String::as_str).collect(),
+    };
+
+    let results: Vec<LocaleGate> = locales
+        .iter()
+        .zip(loaded)
+        .map(|(loc, messages)| gate_locale(loc, messages, &keys, &config))
+        .collect();
+    let failed: Vec<String> = results
+        .iter()
+        .filter(|r| matches!(r.status, Status::Fail | Status::Error))
+        .map(|r| r.locale.clone())
+        .collect();
+
+    let output = JsonGateOutput {
+        source: source.to_string(),
+        config: config_path.display().to_string(),
+        total_keys: keys.len(),
+        passed: failed.is_empty(),
+        failed,
+        locales: results,
+    };
+    if let Some(report) = report {
+        let json = serde_json::to_string_pretty(&output)?;
+        std::fs::write(report, json + "\n")
+            .with_context(|| format!("Failed to write {}", report.display()))?;
+    }
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else {
+        print_report(&output, inventory.is_some(), report);
+    }
+
+    if !output.passed {
+        anyhow::bail!(
+            "{} locale(s) failed the coverage gate: {}",
+            output.failed.len(),
+            output.failed.join(", ")
+        );
+    }
+    Ok(())
+}
+
+fn gate_locale(
+    locale: &str,
+    messages: Result<BTreeMap<String, String>>,
+    keys: &BTreeSet<&str>,
+    config: &GateConfig,
+) -> LocaleGate {
+    let (tier, threshold) = config.threshold(locale).unzip();
+    let mut gate = LocaleGate {
+        locale: locale.to_string(),
+        tier: tier.flatten().map(String::from),
+        threshold,
+        coverage: 0.0,
+        translated: 0,
+        total_keys: keys.len(),
+        status: Status::Ungated,
+        error: None,
+    };
+
+    let messages = match messages {
+        Ok(messages) => messages,
+        Err(e) => {
+            gate.error = Some(format!("{:#}", e));
+            if threshold.is_some() {
+                gate.status = Status::Error;
+            }
+            return gate;
+        }
+    };
+
+    gate.translated = keys
+        .iter()
+        .filter(|key| messages.get(**key).is_some_and(|m| !m.trim().is_empty()))
+        .count();
+    gate.coverage = if keys.is_empty() {
+        100.0
+    } else {
+        gate.translated as f64 / keys.len() as f64 * 100.0
+    };
+    if let Some(threshold) = threshold {
+        gate.status = if gate.coverage >= threshold {
+            Status::Pass
+        } else {
+            Status::Fail
+        };
+    }
+    gate
+}
+
+fn print_report(output: &JsonGateOutput, inventory: bool, report: Option<&Path>) {
+    println!();
+    println!(
+        "{}",
+        "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━".blue()
+    );
+    println!("  {}", "🚦 Translation Coverage Gate".blue().bold());
+    println!(
+        "{}",
+        "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━".blue()
+    );
+    println!();
+    println!(
+        "Reference: {} {} keys, thresholds from {}",
+        output.total_keys.to_string().green(),
+        if inventory {
+            "app"
+        } else {
+            output.source.as_str()
+        },
+        output.config
+    );
+    println!();
+    println!(
+        "  {:<7} {:<10} {:>9} {:>9}  {}",
+        "Locale".bold(),
+        "Tier".bold(),
+        "Coverage".bold(),
+        "Required".bold(),
+        "Status".bold()
+    );
+
+    for gate in &output.locales {
+        let tier = gate.tier.as_deref().unwrap_or("-");
+        let required = gate
+            .threshold
+            .map_or_else(|| "-".to_string(), |t| format!("{:.1}%", t));
+        let coverage = if gate.error.is_some() {
+            "-".to_string()
+        } else {
+            format!("{:.1}%", gate.coverage)
+        };
+        let status = match gate.status {
+            Status::Pass => "✓ pass".green().to_string(),
+            Status::Fail => format!("✗ fail ({} missing)", gate.total_keys - gate.translated)
+                .red()
+                .to_string(),
+            Status::Error => "✗ error".red().to_string(),
+            Status::Ungated => "not gated".dimmed().to_string(),
+        };
+        println!(
+            "  {:<7} {:<10} {:>9} {:>9}  {}",
+            gate.locale, tier, coverage, required, status
+        );
+        if let Some(error) = &gate.error {
+            println!("          {}", error.dimmed());
+        }
+    }
+
+    let gated = output
+        .locales
+        .iter()
+        .filter(|g| g.status != Status::Ungated)
+        .count();
+    println!();
+    if output.passed {
+        println!(
+            "  {} All {} gated locale(s) meet their threshold",
+            "✓".green(),
+            gated
+        );
+    } else {
+        println!(
+            "  {} {} of {} gated locale(s) failed: {}",
+            "✗".red(),
+            output.failed.len(),
+            gated,
+            output.failed.join(", ")
+        );
+    }
+    if let Some(report) = report {
+        println!("  {} Report written to {}", "ℹ".cyan(), report.display());
+    }
+    println!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> GateConfig {
+        serde_json::from_value(serde_json::json!({
+            "default": 50.0,
+            "tiers": [
+                {"name": "launch", "coverage": 95.0, "locales": ["de", "fr"]},
+                {"name": "beta", "coverage": 80.0, "locales": ["fr", "pl"]},
+            ],
+        }))
+        .unwrap()
+    }
+
+    fn messages(keys: &[&str]) -> Result<BTreeMap<String, String>> {
+        Ok(keys.iter().map(|k| (k.to_string(), "x".to_string())).collect())
+    }
+
+    #[test]
+    fn test_threshold() {
+        let config = config();
+        assert_eq!(config.threshold("fr"), Some((Some("launch"), 95.0)));
+        assert_eq!(config.threshold("pl"), Some((Some("beta"), 80.0)));
+        assert_eq!(config.threshold("es"), Some((None, 50.0)));
+
+        let no_default: GateConfig = serde_json::from_str(r#"{"tiers": []}"#).unwrap();
+        assert_eq!(no_default.threshold("es"), None);
+    }
+
+    #[test]
+    fn test_gate_locale() {
+        let config = config();
+        let keys = BTreeSet::from(["a", "b", "c", "d", "e"]);
+
+        let gate = gate_locale("pl", messages(&["a", "b", "c", "d"]), &keys, &config);
+        assert_eq!((gate.coverage, gate.status), (80.0, Status::Pass));
+        let gate = gate_locale("de", messages(&["a", "b", "c", "d"]), &keys, &config);
+        assert_eq!(gate.status, Status::Fail);
+
+        // Blank messages aren't translations
+        let mut blank = messages(&["a", "b", "c"]).unwrap();
+        blank.insert("d".to_string(), "  ".to_string());
+        let gate = gate_locale("pl", Ok(blank), &keys, &config);
+        assert_eq!((gate.translated, gate.status), (3, Status::Fail));
+
+        let gate = gate_locale("de", Err(anyhow::anyhow!("not found")), &keys, &config);
+        assert_eq!((gate.status, gate.error.as_deref()), (Status::Error, Some("not found")));
+
+        let ungated: GateConfig = serde_json::from_str("{}").unwrap();
+        let gate = gate_locale("de", Err(anyhow::anyhow!("not found")), &keys, &ungated);
+        assert_eq!(gate.status, Status::Ungated);
+    }
+
+    #[test]
+    fn test_load_rejects_bad_thresholds() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(DEFAULT_CONFIG);
+        std::fs::write(&path, r#"{"tiers": [{"name": "all", "coverage": 120, "locales": []}]}"#).unwrap();
+        assert!(
+            GateConfig::load(&path)
+                .unwrap_err()
+                .to_string()
+                .contains("threshold 120 is not a percentage")
+        );
+    }
+}
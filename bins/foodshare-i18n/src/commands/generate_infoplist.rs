@@ -9,7 +9,7 @@
 
 use crate::api::ApiClient;
 use crate::config::get_locale_info;
-use crate::types::{GenerateInfoPlistStringsResponse, InfoPlistStats, JsonGenerateInfoPlistOutput};
+use crate::types::{GenerateInfoPlistStringsResponse, JsonGenerateInfoPlistOutput};
 use anyhow::{Context, Result};
 use owo_colors::OwoColorize;
 use std::collections::HashMap;
@@ -0,0 +1,375 @@
+//! Generate resources - Android strings.xml and an iOS string catalog
+//!
+//! Converts the central translations into per-locale `values*/strings.xml`
+//! files under an Android `res` directory and a `.xcstrings` catalog for
+//! iOS (see [`crate::resources`] for the conversion and escaping rules).
+//! The generated files are owned by this command and overwritten whole;
+//! `--dry-run` shows what would change as a diff instead.
+
+use crate::commands::validate;
+use crate::config::SUPPORTED_LOCALES;
+use crate::resources::{self, Resource, Target};
+use anyhow::{Context, Result};
+use foodshare_cli::output::diff::Diff;
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Diff lines shown per file in a dry run
+const DIFF_MAX_LINES: usize = 60;
+
+/// A message left out of the generated resources
+#[derive(Debug, Serialize)]
+struct Skipped {
+    platform: &'static str,
+    locale: String,
+    key: String,
+    reason: String,
+}
+
+/// A generated file and what generating it changes
+#[derive(Debug)]
+struct Generated {
+    path: PathBuf,
+    old: Option<String>,
+    new: String,
+}
+
+impl Generated {
+    fn status(&self) -> &'static str {
+        match &self.old {
+            None => "created",
+            Some(old) if *old == self.new => "unchanged",
+            Some(_) => "updated",
+        }
+    }
+}
+
+/// Result for one file
+#[derive(Debug, Serialize)]
+struct FileResult {
+    path: String,
+    status: &'static str,
+}
+
+/// JSON output for generate-resources
+#[derive(Debug, Serialize)]
+struct JsonGenerateResourcesOutput {
+    dry_run: bool,
+    locales: Vec<String>,
+    files: Vec<FileResult>,
+    skipped: Vec<Skipped>,
+    errors: Vec<String>,
+}
+
+/// Run the generate-resources command
+#[allow(clippy::too_many_arguments)]
+pub async fn run(
+    android: Option<&Path>,
+    xcstrings: Option<&Path>,
+    locales: &[String],
+    source: &str,
+    dir: Option<&Path>,
+    dry_run: bool,
+    limit: usize,
+    format: &str,
+) -> Result<()> {
+    if android.is_none() && xcstrings.is_none() {
+        anyhow::bail!("Nothing to generate: pass --android <RES_DIR> and/or --xcstrings <FILE>");
+    }
+
+    // The source locale comes first: it decides which keys are generated
+    let mut wanted = vec![source];
+    if locales.is_empty() {
+        wanted.extend(
+            SUPPORTED_LOCALES
+                .iter()
+                .copied()
+                .filter(|loc| *loc != source),
+        );
+    } else {
+        wanted.extend(
+            locales
+                .iter()
+                .map(String::as_str)
+                .filter(|loc| *loc != source),
+        );
+    }
+
+    let loaded: Vec<Result<BTreeMap<String, String>>> = match dir {
+        Some(dir) => wanted
+            .iter()
+            .map(|loc| validate::load_bundle(dir, loc))
+            .collect(),
+        None => validate::fetch_bundles(&wanted).await?,
+    };
+    let mut bundles = Vec::new();
+    let mut errors = Vec::new();
+    for (locale, result) in wanted.iter().zip(loaded) {
+        match result {
+            Ok(messages) => bundles.push((locale.to_string(), messages)),
+            Err(e) if *locale == source => {
+                return Err(e.context(format!("Failed to load the {} bundle", source)));
+            }
+            Err(e) => errors.push(format!("{}: {:#}", locale, e)),
+        }
+    }
+
+    let mut skipped = Vec::new();
+    let mut generated = Vec::new();
+    if let Some(res) = android {
+        generated.extend(android_files(res, &bundles, &mut skipped)?);
+    }
+    if let Some(path) = xcstrings {
+        generated.push(catalog_file(path, &bundles, &mut skipped)?);
+    }
+
+    if !dry_run {
+        for file in generated.iter().filter(|f| f.status() != "unchanged") {
+            if let Some(parent) = file.path.parent() {
+                std::fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create {}", parent.display()))?;
+            }
+            std::fs::write(&file.path, &file.new)
+                .with_context(|| format!("Failed to write {}", file.path.display()))?;
+        }
+    }
+
+    if format == "json" {
+        let output = JsonGenerateResourcesOutput {
+            dry_run,
+            locales: bundles.iter().map(|(loc, _)| loc.clone()).collect(),
+            files: generated
+                .iter()
+                .map(|f| FileResult {
+                    path: f.path.display().to_string(),
+                    status: f.status(),
+                })
+                .collect(),
+            skipped,
+            errors: errors.clone(),
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else {
+        print_report(&generated, &skipped, &errors, bundles.len(), dry_run, limit);
+    }
+
+    if !errors.is_empty() {
+        anyhow::bail!("{} locale(s) could not be loaded", errors.len());
+    }
+    Ok(())
+}
+
+/// One `strings.xml` per locale, with the source locale's as the default `values`
+fn android_files(
+    res: &Path,
+    bundles: &[(String, BTreeMap<String, String>)],
+    skipped: &mut Vec<Skipped>,
+) -> Result<Vec<Generated>> {
+    let Some((source, _)) = bundles.first() else {
+        return Ok(Vec::new());
+    };
+
+    // key → (resource name, plural) as generated for the source locale
+    let mut names: BTreeMap<&str, (String, bool)> = BTreeMap::new();
+    let mut taken: BTreeMap<String, &str> = BTreeMap::new();
+    let mut files = Vec::new();
+    for (locale, messages) in bundles {
+        let mut skip = |key: &str, reason: String| {
+            skipped.push(Skipped {
+                platform: "android",
+                locale: locale.clone(),
+                key: key.to_string(),
+                reason,
+            })
+        };
+
+        let mut strings = BTreeMap::new();
+        for (key, message) in messages {
+            let name = if locale == source {
+                let Some(name) = resources::android_name(key) else {
+                    skip(key, "not a valid Android resource name".to_string());
+                    continue;
+                };
+                if let Some(other) = taken.get(&name) {
+                    skip(
+                        key,
+                        format!("resource name '{}' is taken by {}", name, other),
+                    );
+                    continue;
+                }
+                name
+            } else {
+                match names.get(key.as_str()) {
+                    Some((name, _)) => name.clone(),
+                    None => continue,
+                }
+            };
+
+            let resource = match resources::convert(message, Target::Android) {
+                Ok(resource) => resource,
+                Err(reason) => {
+                    skip(key, reason);
+                    continue;
+                }
+            };
+            let plural = matches!(resource, Resource::Plural(_));
+            if locale == source {
+                taken.insert(name.clone(), key);
+                names.insert(key, (name.clone(), plural));
+            } else if names[key.as_str()].1 != plural {
+                let reason = if plural {
+                    "plural here but not in the source"
+                } else {
+                    "plural in the source but not here"
+                };
+                skip(key, reason.to_string());
+                continue;
+            }
+            strings.insert(name, resource);
+        }
+
+        let path = res
+            .join(resources::android_values_dir(locale, source))
+            .join("strings.xml");
+        files.push(generated(
+            path,
+            resources::android_strings_xml(locale, &strings),
+        )?);
+    }
+    Ok(files)
+}
+
+/// A string catalog with every locale's translations of the source keys
+fn catalog_file(
+    path: &Path,
+    bundles: &[(String, BTreeMap<String, String>)],
+    skipped: &mut Vec<Skipped>,
+) -> Result<Generated> {
+    let source = bundles.first().map(|(loc, _)| loc.as_str()).unwrap_or("en");
+    let mut strings: BTreeMap<String, BTreeMap<String, Resource>> = BTreeMap::new();
+    for (locale, messages) in bundles {
+        for (key, message) in messages {
+            if locale != source && !strings.contains_key(key) {
+                continue;
+            }
+            match resources::convert(message, Target::Apple) {
+                Ok(resource) => {
+                    strings
+                        .entry(key.clone())
+                        .or_default()
+                        .insert(resources::apple_language(locale).to_string(), resource);
+                }
+                Err(reason) => skipped.push(Skipped {
+                    platform: "ios",
+                    locale: locale.clone(),
+                    key: key.clone(),
+                    reason,
+                }),
+            }
+        }
+    }
+
+    let catalog = resources::xcstrings(resources::apple_language(source), &strings)
+        .context("Failed to serialize the string catalog")?;
+    generated(path.to_path_buf(), catalog)
+}
+
+fn generated(path: PathBuf, new: String) -> Result<Generated> {
+    let old = if path.exists() {
+        Some(
+            std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?,
+        )
+    } else {
+        None
+    };
+    Ok(Generated { path, old, new })
+}
+
+fn print_report(
+    generated: &[Generated],
+    skipped: &[Skipped],
+    errors: &[String],
+    locales: usize,
+    dry_run: bool,
+    limit: usize,
+) {
+    println!();
+    println!(
+        "{}",
+        "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━".blue()
+    );
+    println!("  {}", "📦 Platform String Resources".blue().bold());
+    println!(
+        "{}",
+        "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━".blue()
+    );
+    println!();
+
+    if dry_run {
+        println!("  {} Dry-run mode - no files will be written", "ℹ".cyan());
+        println!();
+    }
+    println!(
+        "  {} locale(s), {} file(s)",
+        locales.to_string().cyan(),
+        generated.len()
+    );
+    println!();
+
+    for file in generated {
+        let path = file.path.display().to_string();
+        let old = file.old.as_deref().unwrap_or_default();
+        let diff = Diff::new(old, &file.new);
+        match file.status() {
+            "unchanged" => println!("  {} {} {}", "=".dimmed(), path, "unchanged".dimmed()),
+            status => {
+                let stats = diff.stats();
+                println!(
+                    "  {} {} {} ({}, {})",
+                    "✓".green(),
+                    path,
+                    status,
+                    format!("+{}", stats.insertions).green(),
+                    format!("-{}", stats.deletions).red()
+                );
+            }
+        }
+        if dry_run && !diff.is_empty() {
+            let old_label = if file.old.is_some() {
+                path.as_str()
+            } else {
+                "/dev/null"
+            };
+            diff.header(old_label, &path)
+                .max_lines(DIFF_MAX_LINES)
+                .indent(4)
+                .print();
+            println!();
+        }
+    }
+
+    if !skipped.is_empty() {
+        println!();
+        println!("  {} {} message(s) left out:", "⚠".yellow(), skipped.len());
+        for skip in skipped.iter().take(limit) {
+            println!(
+                "    {} {} {} {}",
+                format!("[{}]", skip.platform).cyan(),
+                skip.locale,
+                skip.key,
+                skip.reason.dimmed()
+            );
+        }
+        if skipped.len() > limit {
+            println!("    ... and {} more", skipped.len() - limit);
+        }
+    }
+
+    for error in errors {
+        println!("  {} {}", "✗".red(), error);
+    }
+    println!();
+}
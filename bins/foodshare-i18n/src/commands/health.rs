@@ -96,8 +96,8 @@ async fn run_detailed(client: &ApiClient) -> Result<()> {
             if let Some(features) = &health.features {
                 let delta = features.delta_sync.unwrap_or(false);
                 let prefetch = features.prefetch.unwrap_or(false);
-                println!("  delta_sync: {}", if delta { "on".green() } else { "off".red() });
-                println!("  prefetch:   {}", if prefetch { "on".green() } else { "off".red() });
+                println!("  delta_sync: {}", if delta { "on".green().to_string() } else { "off".red().to_string() });
+                println!("  prefetch:   {}", if prefetch { "on".green().to_string() } else { "off".red().to_string() });
             }
         }
         _ => { println!("{}", "FAIL".red()); all_healthy = false; }
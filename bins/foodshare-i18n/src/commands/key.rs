@@ -0,0 +1,846 @@
+//! Key command - rename and deprecate translation keys
+//!
+//! Removing or renaming a key breaks every app version already released, so
+//! both commands work with a transition window instead:
+//!
+//! - `rename` copies the old key's message to the new key in every locale
+//!   and leaves the old key in place, so released apps keep finding it.
+//! - `deprecate` marks a key for removal, optionally naming its replacement.
+//!
+//! Both record the change in an alias file (`i18n-aliases.json`) that apps
+//! bundle to redirect old keys while the window lasts, and rewrite the
+//! lookups `extract` finds in the given source trees where the new key can be
+//! written in place. `key list` shows the aliases and which windows are over,
+//! whose old keys can then be dropped from the bundles.
+//!
+//! ```json
+//! {
+//!   "renamed": {
+//!     "listing.save": { "to": "common.save", "since": "2026-10-16", "until": "2027-01-14" }
+//!   },
+//!   "deprecated": {
+//!     "listing.legacyBanner": { "since": "2026-10-16", "until": "2027-01-14" }
+//!   }
+//! }
+//! ```
+
+use crate::commands::{update, validate, xliff};
+use crate::config::SUPPORTED_LOCALES;
+use crate::inventory::{self, Location, Platform};
+use anyhow::{Context, Result};
+use chrono::{Days, Local, NaiveDate};
+use owo_colors::OwoColorize;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+
+/// Alias file used when `--aliases` isn't given
+pub const DEFAULT_ALIASES: &str = "i18n-aliases.json";
+
+/// Locale whose bundle must have the key
+const SOURCE_LOCALE: &str = "en";
+
+/// Redirects and deprecations apps honour during the transition window
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Aliases {
+    #[serde(default)]
+    renamed: BTreeMap<String, Rename>,
+    #[serde(default)]
+    deprecated: BTreeMap<String, Deprecation>,
+}
+
+/// An old key and the key that replaced it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Rename {
+    to: String,
+    since: NaiveDate,
+    /// Last day the old key is kept in the bundles
+    until: NaiveDate,
+}
+
+/// A key on its way out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Deprecation {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    replacement: Option<String>,
+    since: NaiveDate,
+    until: NaiveDate,
+}
+
+impl Aliases {
+    fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse alias file {}", path.display()))
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        let mut json = serde_json::to_string_pretty(self)?;
+        json.push('\n');
+        std::fs::write(path, json).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Record `old` → `new`; earlier renames to `old` now point at `new`
+    fn rename(&mut self, old: &str, new: &str, since: NaiveDate, until: NaiveDate) {
+        for rename in self.renamed.values_mut() {
+            if rename.to == old {
+                rename.to = new.to_string();
+                rename.until = rename.until.max(until);
+            }
+        }
+        self.renamed.remove(new);
+        self.renamed.insert(
+            old.to_string(),
+            Rename {
+                to: new.to_string(),
+                since,
+                until,
+            },
+        );
+    }
+}
+
+/// What happens to one locale's bundle in a rename
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum LocaleAction {
+    /// The message is copied to the new key
+    Copy,
+    /// The new key has a different message, replaced with `--force`
+    Overwrite,
+    /// The new key already has the message
+    Done,
+    /// The new key has a different message
+    Conflict,
+    /// The locale has neither key
+    Missing,
+    /// The bundle couldn't be loaded or uploaded
+    Error,
+}
+
+/// Rename result for one locale
+#[derive(Debug, Serialize)]
+struct LocaleRename {
+    locale: String,
+    action: LocaleAction,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// A source lookup of the old key
+#[derive(Debug, Serialize)]
+struct SourceEdit {
+    file: String,
+    line: usize,
+    platform: Platform,
+    /// Whether the lookup can be rewritten in place
+    rewritable: bool,
+}
+
+/// JSON output for key rename
+#[derive(Debug, Serialize)]
+struct JsonRenameOutput {
+    from: String,
+    to: String,
+    applied: bool,
+    until: NaiveDate,
+    aliases: String,
+    locales: Vec<LocaleRename>,
+    usages: Vec<SourceEdit>,
+}
+
+/// JSON output for key deprecate
+#[derive(Debug, Serialize)]
+struct JsonDeprecateOutput {
+    key: String,
+    replacement: Option<String>,
+    applied: bool,
+    until: NaiveDate,
+    aliases: String,
+    usages: Vec<SourceEdit>,
+}
+
+/// JSON output for key list
+#[derive(Debug, Serialize)]
+struct JsonListOutput {
+    aliases: String,
+    renamed: BTreeMap<String, Rename>,
+    deprecated: BTreeMap<String, Deprecation>,
+    /// Old keys whose window is over
+    expired: Vec<String>,
+}
+
+/// Rename `old` to `new` in every locale, the alias file and the sources
+#[allow(clippy::too_many_arguments)]
+pub async fn rename(
+    old: &str,
+    new: &str,
+    sources: &[PathBuf],
+    aliases_path: Option<&Path>,
+    window: u64,
+    force: bool,
+    apply: bool,
+    limit: usize,
+    format: &str,
+) -> Result<()> {
+    check_key_name(new)?;
+    if old == new {
+        anyhow::bail!("The old and new keys are the same");
+    }
+    let aliases_path = aliases_path.unwrap_or(Path::new(DEFAULT_ALIASES));
+    let mut aliases = Aliases::load(aliases_path)?;
+    let (since, until) = window_dates(window)?;
+
+    let locales: Vec<&str> = std::iter::once(SOURCE_LOCALE)
+        .chain(SUPPORTED_LOCALES.iter().copied())
+        .collect();
+    let bundles = validate::fetch_bundles(&locales).await?;
+
+    let mut results = Vec::new();
+    let mut copies = Vec::new();
+    for (locale, bundle) in locales.iter().zip(bundles) {
+        let (action, error) = match bundle {
+            Ok(messages) => match (messages.get(old), messages.get(new)) {
+                (Some(message), None) => {
+                    copies.push((*locale, message.clone()));
+                    (LocaleAction::Copy, None)
+                }
+                (Some(message), Some(existing)) if message == existing => {
+                    (LocaleAction::Done, None)
+                }
+                (Some(message), Some(_)) if force => {
+                    copies.push((*locale, message.clone()));
+                    (LocaleAction::Overwrite, None)
+                }
+                (Some(_), Some(_)) => (LocaleAction::Conflict, None),
+                (None, Some(_)) => (LocaleAction::Done, None),
+                (None, None) => (LocaleAction::Missing, None),
+            },
+            Err(e) => (LocaleAction::Error, Some(format!("{:#}", e))),
+        };
+        results.push(LocaleRename {
+            locale: locale.to_string(),
+            action,
+            error,
+        });
+    }
+
+    match results[0].action {
+        LocaleAction::Missing => {
+            anyhow::bail!("`{}` is not in the {} bundle", old, SOURCE_LOCALE)
+        }
+        LocaleAction::Error => anyhow::bail!(
+            "Failed to load the {} bundle: {}",
+            SOURCE_LOCALE,
+            results[0].error.as_deref().unwrap_or_default()
+        ),
+        _ => {}
+    }
+    let conflicts: Vec<&str> = results
+        .iter()
+        .filter(|r| r.action == LocaleAction::Conflict)
+        .map(|r| r.locale.as_str())
+        .collect();
+    if !conflicts.is_empty() {
+        anyhow::bail!(
+            "`{}` already has a different message in {}; pass --force to overwrite it",
+            new,
+            conflicts.join(", ")
+        );
+    }
+
+    let mut usages = Vec::new();
+    let mut edits = Vec::new();
+    for root in sources {
+        let (found, rewrites) = find_usages(root, old, Some(new))?;
+        usages.extend(found);
+        edits.extend(rewrites);
+    }
+
+    // The sources and the alias file only change once every copy is in the
+    // backend, so a failed upload never leaves lookups of a missing key
+    let uploaded = if apply {
+        upload_copies(&copies, new, &mut results, upload).await
+    } else {
+        Ok(())
+    };
+    let written = apply && uploaded.is_ok();
+    if written {
+        aliases.rename(old, new, since, until);
+        aliases.save(aliases_path)?;
+        write_edits(&edits)?;
+    }
+
+    if format == "json" {
+        let output = JsonRenameOutput {
+            from: old.to_string(),
+            to: new.to_string(),
+            applied: written,
+            until,
+            aliases: aliases_path.display().to_string(),
+            locales: results,
+            usages,
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else {
+        print_banner("🏷️  Key Rename");
+        println!("  {} → {}", old.yellow(), new.green());
+        println!();
+        print_locales(&results, apply);
+        print_usages(&usages, sources, written, limit);
+        print_aliases(aliases_path, &format!("{} → {}", old, new), until, written);
+    }
+
+    uploaded
+}
+
+/// Mark `key` deprecated, rewriting its lookups to `replacement` if given
+#[allow(clippy::too_many_arguments)]
+pub async fn deprecate(
+    key: &str,
+    replacement: Option<&str>,
+    sources: &[PathBuf],
+    aliases_path: Option<&Path>,
+    window: u64,
+    apply: bool,
+    limit: usize,
+    format: &str,
+) -> Result<()> {
+    let aliases_path = aliases_path.unwrap_or(Path::new(DEFAULT_ALIASES));
+    let mut aliases = Aliases::load(aliases_path)?;
+    let (since, until) = window_dates(window)?;
+
+    let messages = validate::fetch_bundles(&[SOURCE_LOCALE])
+        .await?
+        .remove(0)
+        .with_context(|| format!("Failed to load the {} bundle", SOURCE_LOCALE))?;
+    if !messages.contains_key(key) {
+        anyhow::bail!("`{}` is not in the {} bundle", key, SOURCE_LOCALE);
+    }
+    if let Some(replacement) = replacement {
+        if replacement == key {
+            anyhow::bail!("A key can't replace itself");
+        }
+        if !messages.contains_key(replacement) {
+            anyhow::bail!(
+                "The replacement `{}` is not in the {} bundle",
+                replacement,
+                SOURCE_LOCALE
+            );
+        }
+    }
+
+    let mut usages = Vec::new();
+    let mut edits = Vec::new();
+    for root in sources {
+        let (found, rewrites) = find_usages(root, key, replacement)?;
+        usages.extend(found);
+        edits.extend(rewrites);
+    }
+
+    if apply {
+        aliases.deprecated.insert(
+            key.to_string(),
+            Deprecation {
+                replacement: replacement.map(String::from),
+                since,
+                until,
+            },
+        );
+        aliases.save(aliases_path)?;
+        write_edits(&edits)?;
+    }
+
+    if format == "json" {
+        let output = JsonDeprecateOutput {
+            key: key.to_string(),
+            replacement: replacement.map(String::from),
+            applied: apply,
+            until,
+            aliases: aliases_path.display().to_string(),
+            usages,
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    print_banner("🏷️  Key Deprecation");
+    match replacement {
+        Some(replacement) => println!("  {} → {}", key.yellow(), replacement.green()),
+        None => println!("  {}", key.yellow()),
+    }
+    println!();
+    print_usages(&usages, sources, apply, limit);
+    print_aliases(aliases_path, &format!("{} deprecated", key), until, apply);
+    Ok(())
+}
+
+/// Show the alias file and the windows that are over
+pub fn list(aliases_path: Option<&Path>, format: &str) -> Result<()> {
+    let aliases_path = aliases_path.unwrap_or(Path::new(DEFAULT_ALIASES));
+    let aliases = Aliases::load(aliases_path)?;
+    let today = Local::now().date_naive();
+
+    let mut expired: Vec<String> = aliases
+        .renamed
+        .iter()
+        .filter(|(_, r)| r.until < today)
+        .map(|(key, _)| key.clone())
+        .chain(
+            aliases
+                .deprecated
+                .iter()
+                .filter(|(_, d)| d.until < today)
+                .map(|(key, _)| key.clone()),
+        )
+        .collect();
+    expired.sort();
+
+    if format == "json" {
+        let output = JsonListOutput {
+            aliases: aliases_path.display().to_string(),
+            renamed: aliases.renamed,
+            deprecated: aliases.deprecated,
+            expired,
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    print_banner("🏷️  Key Aliases");
+    if aliases.renamed.is_empty() && aliases.deprecated.is_empty() {
+        println!(
+            "  No renamed or deprecated keys in {}",
+            aliases_path.display()
+        );
+        println!();
+        return Ok(());
+    }
+
+    let window = |until: NaiveDate| {
+        if until < today {
+            format!("expired {}", until).red().to_string()
+        } else {
+            format!("until {}", until).dimmed().to_string()
+        }
+    };
+    if !aliases.renamed.is_empty() {
+        println!("{}", "Renamed:".bold());
+        for (old, rename) in &aliases.renamed {
+            println!(
+                "  {} → {}  {}",
+                old.yellow(),
+                rename.to.green(),
+                window(rename.until)
+            );
+        }
+        println!();
+    }
+    if !aliases.deprecated.is_empty() {
+        println!("{}", "Deprecated:".bold());
+        for (key, deprecation) in &aliases.deprecated {
+            match &deprecation.replacement {
+                Some(replacement) => print!("  {} → {}", key.yellow(), replacement.green()),
+                None => print!("  {}", key.yellow()),
+            }
+            println!("  {}", window(deprecation.until));
+        }
+        println!();
+    }
+    if !expired.is_empty() {
+        println!(
+            "  {} {} key(s) past their window can be removed from the bundles and {}",
+            "ℹ".cyan(),
+            expired.len(),
+            aliases_path.display()
+        );
+        println!();
+    }
+    Ok(())
+}
+
+/// Keys are dotted names without whitespace or quotes
+fn check_key_name(key: &str) -> Result<()> {
+    let valid = !key.is_empty()
+        && !key.starts_with('.')
+        && !key.ends_with('.')
+        && !key.contains("..")
+        && !key
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '"' | '\'' | '`' | '{' | '}'));
+    if !valid {
+        anyhow::bail!("`{}` is not a valid translation key", key);
+    }
+    Ok(())
+}
+
+/// Today and the last day of a `window`-day transition
+fn window_dates(window: u64) -> Result<(NaiveDate, NaiveDate)> {
+    let today = Local::now().date_naive();
+    let until = today
+        .checked_add_days(Days::new(window))
+        .context("Transition window is too long")?;
+    Ok((today, until))
+}
+
+/// Upload every copy of the new key, marking the locales that failed
+async fn upload_copies<F, Fut>(
+    copies: &[(&str, String)],
+    new: &str,
+    results: &mut [LocaleRename],
+    upload: F,
+) -> Result<()>
+where
+    F: Fn(String, serde_json::Value) -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    let mut failed = Vec::new();
+    for (locale, message) in copies {
+        let mut bundle = serde_json::json!({});
+        xliff::set_message(&mut bundle, new, message);
+        if let Err(e) = upload(locale.to_string(), bundle).await {
+            let result = results
+                .iter_mut()
+                .find(|r| r.locale == *locale)
+                .expect("copied locales have a result");
+            result.action = LocaleAction::Error;
+            result.error = Some(format!("{:#}", e));
+            failed.push(*locale);
+        }
+    }
+    if !failed.is_empty() {
+        anyhow::bail!(
+            "Failed to upload `{}` for {}; the sources and alias file were left unchanged",
+            new,
+            failed.join(", ")
+        );
+    }
+    Ok(())
+}
+
+async fn upload(locale: String, bundle: serde_json::Value) -> Result<()> {
+    let response = update::update_locale(&locale, &bundle).await?;
+    if !response.success {
+        anyhow::bail!(
+            "{}",
+            response
+                .error
+                .unwrap_or_else(|| "Unknown error".to_string())
+        );
+    }
+    Ok(())
+}
+
+/// A rewrite of one line of a source file
+struct LineEdit {
+    path: PathBuf,
+    line: usize,
+    text: String,
+}
+
+/// Lookups of `key` under `root`, and the line rewrites to `replacement`
+fn find_usages(
+    root: &Path,
+    key: &str,
+    replacement: Option<&str>,
+) -> Result<(Vec<SourceEdit>, Vec<LineEdit>)> {
+    let inventory = inventory::scan(root, root, Platform::ALL)?;
+    let mut locations: Vec<(Platform, &Location)> = inventory.locations_of(key);
+    locations.sort_by(|a, b| (&a.1.file, a.1.line).cmp(&(&b.1.file, b.1.line)));
+    locations.dedup_by(|a, b| a.1 == b.1);
+
+    let mut usages = Vec::new();
+    let mut edits = Vec::new();
+    let mut contents: BTreeMap<&str, String> = BTreeMap::new();
+    for (platform, location) in locations {
+        let path = root.join(&location.file);
+        let rewritten = match replacement {
+            Some(replacement) => {
+                if !contents.contains_key(location.file.as_str()) {
+                    let content = std::fs::read_to_string(&path)
+                        .with_context(|| format!("Failed to read {}", path.display()))?;
+                    contents.insert(&location.file, content);
+                }
+                contents[location.file.as_str()]
+                    .split_inclusive('\n')
+                    .nth(location.line - 1)
+                    .and_then(|line| rewrite_line(line, platform, key, replacement))
+            }
+            None => None,
+        };
+        usages.push(SourceEdit {
+            file: path.display().to_string(),
+            line: location.line,
+            platform,
+            rewritable: rewritten.is_some(),
+        });
+        if let Some(text) = rewritten {
+            edits.push(LineEdit {
+                path,
+                line: location.line,
+                text,
+            });
+        }
+    }
+    Ok((usages, edits))
+}
+
+/// `line` with its lookup of `old` changed to `new`, if it can be done in place
+///
+/// Web lookups through a namespaced translator name the key relative to the
+/// namespace, so they are rewritten only when both keys share it.
+fn rewrite_line(line: &str, platform: Platform, old: &str, new: &str) -> Option<String> {
+    match platform {
+        Platform::Android => {
+            let reference = Regex::new(&format!(
+                r"(R\.(?:string|plurals)\.|@(?:string|plurals)/){}\b",
+                regex::escape(&inventory::resource_name(old))
+            ))
+            .expect("escaped pattern");
+            let replacement = format!("${{1}}{}", inventory::resource_name(new));
+            let rewritten = reference.replace_all(line, replacement.as_str());
+            (rewritten != line).then(|| rewritten.into_owned())
+        }
+        Platform::Ios => replace_quoted(line, old, new, &['"']),
+        Platform::Web => {
+            let relative = old.match_indices('.').filter_map(|(i, _)| {
+                let prefix = &old[..=i];
+                new.strip_prefix(prefix)
+                    .map(|new_suffix| (&old[i + 1..], new_suffix))
+            });
+            std::iter::once((old, new))
+                .chain(relative)
+                .find_map(|(old, new)| replace_quoted(line, old, new, &['\'', '"', '`']))
+        }
+    }
+}
+
+fn replace_quoted(line: &str, old: &str, new: &str, quotes: &[char]) -> Option<String> {
+    let mut rewritten = line.to_string();
+    for quote in quotes {
+        rewritten = rewritten.replace(
+            &format!("{quote}{old}{quote}"),
+            &format!("{quote}{new}{quote}"),
+        );
+    }
+    (rewritten != line).then_some(rewritten)
+}
+
+/// Write the rewritten lines back to their files
+fn write_edits(edits: &[LineEdit]) -> Result<()> {
+    let mut by_file: BTreeMap<&Path, Vec<&LineEdit>> = BTreeMap::new();
+    for edit in edits {
+        by_file.entry(&edit.path).or_default().push(edit);
+    }
+    for (path, edits) in by_file {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let mut lines: Vec<&str> = content.split_inclusive('\n').collect();
+        for edit in &edits {
+            lines[edit.line - 1] = &edit.text;
+        }
+        std::fs::write(path, lines.concat())
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    Ok(())
+}
+
+fn print_banner(title: &str) {
+    println!();
+    println!(
+        "{}",
+        "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━".blue()
+    );
+    println!("  {}", title.blue().bold());
+    println!(
+        "{}",
+        "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━".blue()
+    );
+    println!();
+}
+
+fn print_locales(results: &[LocaleRename], apply: bool) {
+    println!("{}", "Bundles:".bold());
+    for result in results {
+        let action = match (result.action, apply) {
+            (LocaleAction::Copy, true) => "copied".green().to_string(),
+            (LocaleAction::Copy, false) => "would copy".cyan().to_string(),
+            (LocaleAction::Overwrite, true) => "overwritten".yellow().to_string(),
+            (LocaleAction::Overwrite, false) => "would overwrite".yellow().to_string(),
+            (LocaleAction::Done, _) => "already renamed".dimmed().to_string(),
+            (LocaleAction::Conflict, _) => "conflict".red().to_string(),
+            (LocaleAction::Missing, _) => "no message".dimmed().to_string(),
+            (LocaleAction::Error, _) => "error".red().to_string(),
+        };
+        println!("  {:<5}: {}", result.locale, action);
+        if let Some(error) = &result.error {
+            println!("         {}", error.dimmed());
+        }
+    }
+    println!();
+}
+
+fn print_usages(usages: &[SourceEdit], sources: &[PathBuf], apply: bool, limit: usize) {
+    if sources.is_empty() {
+        return;
+    }
+    println!("{}", "Source lookups:".bold());
+    if usages.is_empty() {
+        println!("  None found");
+    }
+    for usage in usages.iter().take(limit) {
+        let status = match (usage.rewritable, apply) {
+            (true, true) => "rewritten".green().to_string(),
+            (true, false) => "would rewrite".cyan().to_string(),
+            (false, _) => "update by hand".yellow().to_string(),
+        };
+        println!(
+            "  {}:{} [{}] {}",
+            usage.file,
+            usage.line,
+            usage.platform.as_str(),
+            status
+        );
+    }
+    if usages.len() > limit {
+        println!("  ... and {} more", usages.len() - limit);
+    }
+    println!();
+}
+
+fn print_aliases(path: &Path, entry: &str, until: NaiveDate, apply: bool) {
+    if apply {
+        println!(
+            "  {} Recorded {} in {} until {}",
+            "✓".green(),
+            entry,
+            path.display(),
+            until
+        );
+    } else {
+        println!(
+            "  {} Dry run - pass --apply to record {} in {} until {}",
+            "ℹ".cyan(),
+            entry,
+            path.display(),
+            until
+        );
+    }
+    println!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 1, day).unwrap()
+    }
+
+    #[test]
+    fn test_rename_chains_aliases() {
+        let mut aliases = Aliases::default();
+        aliases.rename("home.title", "feed.title", date(1), date(10));
+        aliases.rename("feed.title", "feed.heading", date(5), date(20));
+
+        assert_eq!(aliases.renamed["home.title"].to, "feed.heading");
+        assert_eq!(aliases.renamed["home.title"].until, date(20));
+        assert_eq!(aliases.renamed["feed.title"].to, "feed.heading");
+
+        // Renaming back drops the alias of the key that is in use again
+        aliases.rename("feed.heading", "feed.title", date(6), date(8));
+        assert!(!aliases.renamed.contains_key("feed.title"));
+        assert_eq!(aliases.renamed["home.title"].to, "feed.title");
+        assert_eq!(aliases.renamed["home.title"].until, date(20));
+    }
+
+    #[test]
+    fn test_aliases_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(DEFAULT_ALIASES);
+        assert!(Aliases::load(&path).unwrap().renamed.is_empty());
+
+        let mut aliases = Aliases::default();
+        aliases.rename("a.old", "a.new", date(1), date(31));
+        aliases.save(&path).unwrap();
+        assert_eq!(Aliases::load(&path).unwrap().renamed["a.old"].to, "a.new");
+
+        std::fs::write(&path, "{\"renamed\": []}").unwrap();
+        assert!(Aliases::load(&path).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_failed_upload() {
+        let copies = [("en", "Save".to_string()), ("de", "Speichern".to_string()), ("fr", "Enregistrer".to_string())];
+        let mut results: Vec<LocaleRename> = copies
+            .iter()
+            .map(|(locale, _)| LocaleRename {
+                locale: locale.to_string(),
+                action: LocaleAction::Copy,
+                error: None,
+            })
+            .collect();
+        let attempted = std::sync::Mutex::new(Vec::new());
+
+        let err = upload_copies(&copies, "common.save", &mut results, |locale, bundle| {
+            assert_eq!(bundle, serde_json::json!({"common": {"save": copies.iter().find(|c| c.0 == locale).unwrap().1}}));
+            attempted.lock().unwrap().push(locale.clone());
+            async move {
+                if locale == "de" {
+                    anyhow::bail!("HTTP 500");
+                }
+                Ok(())
+            }
+        })
+        .await
+        .unwrap_err();
+
+        // Every copy is still tried, and the failure is reported, not swallowed
+        assert_eq!(*attempted.lock().unwrap(), ["en", "de", "fr"]);
+        assert!(err.to_string().contains("for de;"));
+        let actions: Vec<LocaleAction> = results.iter().map(|r| r.action).collect();
+        assert_eq!(actions, [LocaleAction::Copy, LocaleAction::Error, LocaleAction::Copy]);
+        assert_eq!(results[1].error.as_deref(), Some("HTTP 500"));
+
+        let mut results = Vec::new();
+        upload_copies(&[], "common.save", &mut results, |_, _| async { Ok(()) })
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn test_key_names() {
+        assert!(check_key_name("common.save").is_ok());
+        for key in ["", ".save", "save.", "a..b", "a b", "a'b", "{a}"] {
+            assert!(check_key_name(key).is_err(), "{}", key);
+        }
+    }
+
+    #[test]
+    fn test_rewrite_line() {
+        assert_eq!(
+            rewrite_line("t('home.title') + t(\"home.title\")", Platform::Web, "home.title", "feed.title")
+                .as_deref(),
+            Some("t('feed.title') + t(\"feed.title\")")
+        );
+        // Namespaced lookups stay relative to the namespace
+        assert_eq!(
+            rewrite_line("t('title')", Platform::Web, "home.title", "home.heading").as_deref(),
+            Some("t('heading')")
+        );
+        assert_eq!(
+            rewrite_line("t('title')", Platform::Web, "home.title", "feed.title"),
+            None
+        );
+        assert_eq!(
+            rewrite_line("Text(\"home.title\")", Platform::Ios, "home.title", "feed.title").as_deref(),
+            Some("Text(\"feed.title\")")
+        );
+    }
+}
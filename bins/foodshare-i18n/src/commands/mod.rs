@@ -4,12 +4,22 @@ pub mod audit;
 pub mod backfill;
 pub mod bench;
 pub mod deploy;
+pub mod diff;
+pub mod extract;
+pub mod gate;
 pub mod generate_infoplist;
+pub mod generate_resources;
 pub mod health;
+pub mod key;
 pub mod locales;
+pub mod pseudo;
+pub mod review;
 pub mod status;
 pub mod test;
 pub mod test_llm;
 pub mod test_translation;
 pub mod translate;
 pub mod update;
+pub mod usage;
+pub mod validate;
+pub mod xliff;
@@ -0,0 +1,410 @@
+//! Pseudo command - generate a pseudo-locale for QA
+//!
+//! Turns the source bundle into a pseudo-locale (`en-XA` by default) that
+//! still reads as English but can't pass for it: letters become accented
+//! lookalikes, every message grows by `--expand` percent and is wrapped in
+//! `[` `]`. Unaccented text in the app is hardcoded, and a message missing a
+//! bracket was truncated or concatenated. Placeholders, ICU syntax and markup
+//! tags are left as they are, so every message still formats.
+
+use crate::api::ApiClient;
+use crate::commands::update;
+use crate::messageformat::{self, Part};
+use anyhow::{Context, Result};
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use std::path::Path;
+
+/// Words the padding is made of
+const FILLER: &[&str] = &[
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+];
+
+/// A message before and after
+#[derive(Debug, Serialize)]
+struct Sample {
+    key: String,
+    source: String,
+    pseudo: String,
+}
+
+/// JSON output for pseudo
+#[derive(Debug, Serialize)]
+struct JsonPseudoOutput {
+    locale: String,
+    source: String,
+    messages: usize,
+    /// Keys left unchanged because they don't parse
+    invalid: Vec<String>,
+    written: Option<String>,
+    uploaded: bool,
+}
+
+/// Run pseudo command
+#[allow(clippy::too_many_arguments)]
+pub async fn run(
+    locale: &str,
+    source: &str,
+    dir: Option<&Path>,
+    out: Option<&Path>,
+    expand: usize,
+    apply: bool,
+    limit: usize,
+    format: &str,
+) -> Result<()> {
+    let messages = match dir {
+        Some(dir) => {
+            let path = dir.join(format!("{}.json", source));
+            let content = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse JSON from {}", path.display()))?
+        }
+        None => {
+            let client = ApiClient::new()?;
+            client
+                .download_translations(source)
+                .await?
+                .data
+                .map(|d| d.messages)
+                .with_context(|| format!("No translations returned for {}", source))?
+        }
+    };
+
+    let mut samples = Vec::new();
+    let mut invalid = Vec::new();
+    let pseudo = pseudo_value(&messages, "", expand, &mut samples, &mut invalid);
+
+    if let Some(out) = out {
+        let mut json = serde_json::to_string_pretty(&pseudo)?;
+        json.push('\n');
+        std::fs::write(out, json).with_context(|| format!("Failed to write {}", out.display()))?;
+    }
+    if apply {
+        let response = update::update_locale(locale, &pseudo).await?;
+        if !response.success {
+            anyhow::bail!(
+                "Upload failed: {}",
+                response
+                    .error
+                    .unwrap_or_else(|| "Unknown error".to_string())
+            );
+        }
+    }
+
+    if format == "json" {
+        let output = JsonPseudoOutput {
+            locale: locale.to_string(),
+            source: source.to_string(),
+            messages: samples.len(),
+            invalid,
+            written: out.map(|p| p.display().to_string()),
+            uploaded: apply,
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    println!();
+    println!(
+        "{}",
+        "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━".blue()
+    );
+    println!("  {}", "🔤 Pseudo-Localization".blue().bold());
+    println!(
+        "{}",
+        "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━".blue()
+    );
+    println!();
+    println!(
+        "  {} messages from {}, expanded {}%",
+        samples.len().to_string().cyan(),
+        source,
+        expand
+    );
+    println!();
+    for sample in samples.iter().take(limit) {
+        println!("    {}", sample.key.dimmed());
+        println!("      {}", sample.source);
+        println!("      {}", sample.pseudo.green());
+    }
+    if samples.len() > limit {
+        println!("    ... and {} more", samples.len() - limit);
+    }
+
+    if !invalid.is_empty() {
+        println!();
+        println!(
+            "  {} {} message(s) don't parse and were copied unchanged (see `validate`):",
+            "⚠".yellow(),
+            invalid.len()
+        );
+        for key in invalid.iter().take(limit) {
+            println!("    {}", key.dimmed());
+        }
+    }
+
+    println!();
+    if let Some(out) = out {
+        println!("  {} Written to {}", "✓".green(), out.display());
+    }
+    if apply {
+        println!("  {} Uploaded as {}", "✓".green(), locale.cyan());
+    }
+    if out.is_none() && !apply {
+        println!(
+            "  {} Dry run - pass --out <FILE> to write the bundle or --apply to upload it",
+            "ℹ".cyan()
+        );
+    }
+    println!();
+    Ok(())
+}
+
+/// The messages object with every message pseudo-localized
+fn pseudo_value(
+    value: &serde_json::Value,
+    prefix: &str,
+    expand: usize,
+    samples: &mut Vec<Sample>,
+    invalid: &mut Vec<String>,
+) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => serde_json::Value::Object(
+            map.iter()
+                .map(|(name, child)| {
+                    let key = if prefix.is_empty() {
+                        name.clone()
+                    } else {
+                        format!("{}.{}", prefix, name)
+                    };
+                    let child = pseudo_value(child, &key, expand, samples, invalid);
+                    (name.clone(), child)
+                })
+                .collect(),
+        ),
+        serde_json::Value::String(message) => match messageformat::parse(message) {
+            Ok(parts) => {
+                let mut wrapped = vec![Part::Text("[".to_string())];
+                wrapped.extend(pseudo_parts(&parts, expand));
+                wrapped.push(Part::Text("]".to_string()));
+                let pseudo = messageformat::format(&wrapped);
+                samples.push(Sample {
+                    key: prefix.to_string(),
+                    source: message.clone(),
+                    pseudo: pseudo.clone(),
+                });
+                serde_json::Value::String(pseudo)
+            }
+            Err(_) => {
+                invalid.push(prefix.to_string());
+                value.clone()
+            }
+        },
+        other => other.clone(),
+    }
+}
+
+/// Accent the text of `parts` and pad it; plural and select cases are padded
+/// by their own length
+fn pseudo_parts(parts: &[Part], expand: usize) -> Vec<Part> {
+    let mut length = 0;
+    let mut out: Vec<Part> = parts
+        .iter()
+        .map(|part| match part {
+            Part::Text(text) => {
+                length += text.chars().count();
+                Part::Text(accent(text))
+            }
+            Part::Plural {
+                name,
+                ordinal,
+                offset,
+                cases,
+            } => Part::Plural {
+                name: name.clone(),
+                ordinal: *ordinal,
+                offset: *offset,
+                cases: pseudo_cases(cases, expand),
+            },
+            Part::Select { name, cases } => Part::Select {
+                name: name.clone(),
+                cases: pseudo_cases(cases, expand),
+            },
+            part => part.clone(),
+        })
+        .collect();
+
+    let padding = (length * expand).div_ceil(100);
+    if padding > 0 {
+        out.push(Part::Text(format!(" {}", filler(padding))));
+    }
+    out
+}
+
+fn pseudo_cases(cases: &messageformat::Cases, expand: usize) -> messageformat::Cases {
+    cases
+        .iter()
+        .map(|(selector, case)| (selector.clone(), pseudo_parts(case, expand)))
+        .collect()
+}
+
+/// Accented lookalikes for ASCII letters, outside `<tag>` markup
+fn accent(text: &str) -> String {
+    let mut in_tag = false;
+    text.chars()
+        .map(|c| {
+            match c {
+                '<' => in_tag = true,
+                '>' => in_tag = false,
+                _ => {}
+            }
+            if in_tag { c } else { accented(c) }
+        })
+        .collect()
+}
+
+fn accented(c: char) -> char {
+    match c {
+        'a' => 'á',
+        'b' => 'ƀ',
+        'c' => 'ç',
+        'd' => 'ð',
+        'e' => 'é',
+        'f' => 'ƒ',
+        'g' => 'ĝ',
+        'h' => 'ĥ',
+        'i' => 'î',
+        'j' => 'ĵ',
+        'k' => 'ķ',
+        'l' => 'ļ',
+        'm' => 'ɱ',
+        'n' => 'ñ',
+        'o' => 'ö',
+        'p' => 'þ',
+        'q' => 'ǫ',
+        'r' => 'ŕ',
+        's' => 'š',
+        't' => 'ţ',
+        'u' => 'û',
+        'v' => 'ṽ',
+        'w' => 'ŵ',
+        'x' => 'ẋ',
+        'y' => 'ý',
+        'z' => 'ž',
+        'A' => 'Å',
+        'B' => 'Ɓ',
+        'C' => 'Ç',
+        'D' => 'Ð',
+        'E' => 'É',
+        'F' => 'Ƒ',
+        'G' => 'Ĝ',
+        'H' => 'Ĥ',
+        'I' => 'Î',
+        'J' => 'Ĵ',
+        'K' => 'Ķ',
+        'L' => 'Ļ',
+        'M' => 'Ṁ',
+        'N' => 'Ñ',
+        'O' => 'Ö',
+        'P' => 'Þ',
+        'Q' => 'Ǫ',
+        'R' => 'Ŕ',
+        'S' => 'Š',
+        'T' => 'Ţ',
+        'U' => 'Û',
+        'V' => 'Ṽ',
+        'W' => 'Ŵ',
+        'X' => 'Ẋ',
+        'Y' => 'Ý',
+        'Z' => 'Ž',
+        c => c,
+    }
+}
+
+/// `length` characters of accented filler words
+fn filler(length: usize) -> String {
+    FILLER
+        .iter()
+        .cycle()
+        .flat_map(|word| word.chars().chain([' ']))
+        .take(length)
+        .map(accented)
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pseudo(message: &str, expand: usize) -> String {
+        let (mut samples, mut invalid) = (Vec::new(), Vec::new());
+        let value = pseudo_value(
+            &serde_json::json!(message),
+            "key",
+            expand,
+            &mut samples,
+            &mut invalid,
+        );
+        assert!(invalid.is_empty(), "{} doesn't parse", message);
+        value.as_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_expansion() {
+        assert_eq!(pseudo("Save changes", 0), "[Šáṽé çĥáñĝéš]");
+        // 40% of 12 characters, rounded up, in filler words
+        let expanded = pseudo("Save changes", 40);
+        assert_eq!(expanded, "[Šáṽé çĥáñĝéš öñé ţ]");
+        assert_eq!(expanded.chars().count(), "[Save changes]".chars().count() + 1 + 5);
+        assert_eq!(pseudo("Save changes", 100).chars().count(), 2 + 12 + 1 + 12);
+        assert_eq!(filler(14), "öñé ţŵö ţĥŕéé");
+    }
+
+    #[test]
+    fn test_placeholders_survive() {
+        for message in [
+            "Hi {name}, {count, number} new",
+            "{count, plural, =0 {No items} one {# item} other {# items}} from {name}",
+            "{gender, select, female {She} other {They}} shared it",
+            "{n, selectordinal, one {#st} other {#th}}",
+            "It''s '{literal}' <b>bold</b>",
+        ] {
+            let pseudo = pseudo(message, 30);
+            assert_eq!(
+                messageformat::arguments(&pseudo).unwrap(),
+                messageformat::arguments(message).unwrap(),
+                "{}",
+                pseudo
+            );
+            assert!(pseudo.starts_with('[') && pseudo.ends_with(']'), "{}", pseudo);
+        }
+
+        let plural = pseudo("{count, plural, one {# item} other {# items}}", 50);
+        // Each case is padded by its own length
+        assert!(plural.contains("{# îţéɱ öñé}") && plural.contains("{# îţéɱš öñé}"), "{}", plural);
+        assert!(pseudo("Tap <b>Share</b>", 0).contains("<b>Šĥáŕé</b>"));
+    }
+
+    #[test]
+    fn test_bundle() {
+        let bundle = serde_json::json!({
+            "common": {"save": "Save", "broken": "Hi {name", "count": 3},
+            "title": "Foodshare",
+        });
+        let (mut samples, mut invalid) = (Vec::new(), Vec::new());
+        let pseudo = pseudo_value(&bundle, "", 0, &mut samples, &mut invalid);
+        assert_eq!(
+            pseudo,
+            serde_json::json!({
+                "common": {"save": "[Šáṽé]", "broken": "Hi {name", "count": 3},
+                "title": "[Ƒööðšĥáŕé]",
+            })
+        );
+        assert_eq!(invalid, ["common.broken"]);
+        let keys: Vec<&str> = samples.iter().map(|s| s.key.as_str()).collect();
+        assert_eq!(keys, ["common.save", "title"]);
+    }
+}
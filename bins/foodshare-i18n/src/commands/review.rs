@@ -0,0 +1,761 @@
+//! Review command - a queue of low-confidence machine translations
+//!
+//! `review scan` scores a locale's translations (see [`crate::quality`]) and
+//! queues the ones below the threshold; `translate --apply` queues its own
+//! low scorers the same way. The queue is a JSON file,
+//! `<data dir>/foodshare-tools/review-queue.json` unless `--queue` points
+//! elsewhere (a file in the repository lets a team share it).
+//! `review approve` uploads the queued text, or a reviewer's correction, and
+//! `review reject` asks for a fresh translation, uploading it if it scores
+//! well and queueing it again if not.
+
+use crate::api::ApiClient;
+use crate::commands::{update, validate, xliff};
+use crate::messageformat;
+use crate::quality::{self, Glossary, Score};
+use anyhow::{Context, Result};
+use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Confidence below which a translation is queued
+pub const DEFAULT_THRESHOLD: f64 = 0.7;
+
+/// Glossary used when `--glossary` isn't given, if it exists
+pub const DEFAULT_GLOSSARY: &str = "i18n-glossary.json";
+
+/// Keys per back-translation request
+const BACK_TRANSLATE_BATCH: usize = 50;
+
+/// A queued translation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub locale: String,
+    pub key: String,
+    pub source_locale: String,
+    pub source: String,
+    /// The translation awaiting review
+    pub translation: String,
+    #[serde(flatten)]
+    pub score: Score,
+    /// Fresh translations asked for by `review reject`
+    #[serde(default)]
+    pub attempts: u32,
+}
+
+/// The review queue file
+pub struct ReviewQueue {
+    path: PathBuf,
+    entries: Vec<Entry>,
+}
+
+impl ReviewQueue {
+    /// Default queue location (`<data dir>/foodshare-tools/review-queue.json`)
+    pub fn default_path() -> PathBuf {
+        dirs::data_local_dir()
+            .unwrap_or_else(|| PathBuf::from(".local/share"))
+            .join("foodshare-tools")
+            .join("review-queue.json")
+    }
+
+    /// Open the queue at `path` (or the default), empty if the file doesn't exist
+    pub fn open(path: Option<&Path>) -> Result<Self> {
+        let path = path.map_or_else(Self::default_path, Path::to_path_buf);
+        let entries = if path.exists() {
+            let content = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse review queue {}", path.display()))?
+        } else {
+            Vec::new()
+        };
+        Ok(Self { path, entries })
+    }
+
+    /// Queue file path
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Write the queue back, sorted by locale and key
+    pub fn save(&mut self) -> Result<()> {
+        self.entries
+            .sort_by(|a, b| (&a.locale, &a.key).cmp(&(&b.locale, &b.key)));
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let mut json = serde_json::to_string_pretty(&self.entries)?;
+        json.push('\n');
+        std::fs::write(&self.path, json)
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+
+    /// Queue an entry, replacing the one for the same locale and key
+    pub fn push(&mut self, mut entry: Entry) {
+        if let Some(old) = self.remove(&entry.locale, &entry.key) {
+            entry.attempts = entry.attempts.max(old.attempts);
+        }
+        self.entries.push(entry);
+    }
+
+    fn remove(&mut self, locale: &str, key: &str) -> Option<Entry> {
+        let index = self
+            .entries
+            .iter()
+            .position(|e| e.locale == locale && e.key == key)?;
+        Some(self.entries.remove(index))
+    }
+
+    /// Queued entries of `locale`, or of every locale, lowest confidence first
+    fn pending(&self, locale: Option<&str>) -> Vec<Entry> {
+        let mut entries: Vec<Entry> = self
+            .entries
+            .iter()
+            .filter(|e| locale.is_none_or(|loc| e.locale == loc))
+            .cloned()
+            .collect();
+        entries.sort_by(|a, b| a.score.confidence.total_cmp(&b.score.confidence));
+        entries
+    }
+
+    /// The queued entries of `locale` named by `keys`, or all of them
+    fn select(&self, locale: &str, keys: &[String], all: bool) -> Result<Vec<Entry>> {
+        if all {
+            return Ok(self
+                .entries
+                .iter()
+                .filter(|e| e.locale == locale)
+                .cloned()
+                .collect());
+        }
+        if keys.is_empty() {
+            anyhow::bail!("Name the keys to review, or pass --all");
+        }
+        keys.iter()
+            .map(|key| {
+                self.entries
+                    .iter()
+                    .find(|e| e.locale == locale && e.key == *key)
+                    .cloned()
+                    .with_context(|| format!("{} {} is not in the review queue", locale, key))
+            })
+            .collect()
+    }
+}
+
+/// How translations are scored
+pub struct Scoring {
+    pub threshold: f64,
+    pub glossary: Glossary,
+    pub back_translate: bool,
+}
+
+impl Scoring {
+    /// Scoring with the glossary at `glossary`, or the default one if present
+    pub fn new(threshold: f64, glossary: Option<&Path>, back_translate: bool) -> Result<Self> {
+        let glossary = match glossary {
+            Some(path) => Glossary::load(path)?,
+            None if Path::new(DEFAULT_GLOSSARY).exists() => {
+                Glossary::load(Path::new(DEFAULT_GLOSSARY))?
+            }
+            None => Glossary::default(),
+        };
+        Ok(Self {
+            threshold,
+            glossary,
+            back_translate,
+        })
+    }
+
+    /// Score `(key, source, translation)` triples, back-translating them
+    /// into `source_locale` first if asked to
+    pub async fn score(
+        &self,
+        client: &ApiClient,
+        source_locale: &str,
+        locale: &str,
+        messages: &[(String, String, String)],
+    ) -> Result<Vec<Score>> {
+        let mut back: HashMap<String, String> = HashMap::new();
+        if self.back_translate {
+            for chunk in messages.chunks(BACK_TRANSLATE_BATCH) {
+                let keys: serde_json::Value = chunk
+                    .iter()
+                    .map(|(key, _, translation)| {
+                        (key.clone(), serde_json::Value::String(translation.clone()))
+                    })
+                    .collect::<serde_json::Map<String, serde_json::Value>>()
+                    .into();
+                let response = client
+                    .translate_batch(source_locale, &keys, false)
+                    .await
+                    .context("Back-translation failed")?;
+                if !response.success {
+                    anyhow::bail!(
+                        "Back-translation failed: {}",
+                        response.error.unwrap_or_else(|| "Unknown error".to_string())
+                    );
+                }
+                back.extend(response.translations.unwrap_or_default());
+            }
+        }
+
+        Ok(messages
+            .iter()
+            .map(|(key, source, translation)| {
+                quality::score(
+                    source,
+                    translation,
+                    locale,
+                    &self.glossary,
+                    back.get(key).map(String::as_str),
+                )
+            })
+            .collect())
+    }
+}
+
+/// Score fresh translations and queue the low-confidence ones in the
+/// default queue; returns how many were queued
+pub async fn queue_low_confidence(
+    client: &ApiClient,
+    locale: &str,
+    sources: &HashMap<String, String>,
+    translations: &HashMap<String, String>,
+) -> Result<usize> {
+    let scoring = Scoring::new(DEFAULT_THRESHOLD, None, false)?;
+    let messages: Vec<(String, String, String)> = translations
+        .iter()
+        .filter_map(|(key, translation)| {
+            sources
+                .get(key)
+                .map(|source| (key.clone(), source.clone(), translation.clone()))
+        })
+        .collect();
+    let scores = scoring.score(client, "en", locale, &messages).await?;
+
+    let mut queue = ReviewQueue::open(None)?;
+    let mut queued = 0;
+    for ((key, source, translation), score) in messages.into_iter().zip(scores) {
+        if score.confidence < scoring.threshold {
+            queue.push(Entry {
+                locale: locale.to_string(),
+                key,
+                source_locale: "en".to_string(),
+                source,
+                translation,
+                score,
+                attempts: 0,
+            });
+            queued += 1;
+        }
+    }
+    if queued > 0 {
+        queue.save()?;
+    }
+    Ok(queued)
+}
+
+/// JSON output for review scan
+#[derive(Debug, Serialize)]
+struct JsonScanOutput {
+    locale: String,
+    queue: String,
+    threshold: f64,
+    scanned: usize,
+    average_confidence: Option<f64>,
+    /// Keys that left the queue because they now score well
+    cleared: Vec<String>,
+    queued: Vec<Entry>,
+}
+
+/// JSON output for review list
+#[derive(Debug, Serialize)]
+struct JsonListOutput {
+    queue: String,
+    entries: Vec<Entry>,
+}
+
+/// JSON output for review approve
+#[derive(Debug, Serialize)]
+struct JsonApproveOutput {
+    locale: String,
+    approved: Vec<String>,
+}
+
+/// JSON output for review reject
+#[derive(Debug, Serialize)]
+struct JsonRejectOutput {
+    locale: String,
+    /// Retranslated well enough to upload
+    uploaded: Vec<String>,
+    /// Retranslated and still low-confidence
+    requeued: Vec<Entry>,
+    /// No new translation came back
+    unchanged: Vec<String>,
+}
+
+/// Run review scan
+pub async fn scan(
+    locale: &str,
+    source: &str,
+    dir: Option<&Path>,
+    scoring: &Scoring,
+    queue: Option<&Path>,
+    limit: usize,
+    format: &str,
+) -> Result<()> {
+    if locale == source {
+        anyhow::bail!("{} is the source locale; there is nothing to review", locale);
+    }
+    let client = ApiClient::new()?;
+    let (sources, translations) = match dir {
+        Some(dir) => (
+            validate::load_bundle(dir, source)?,
+            validate::load_bundle(dir, locale)?,
+        ),
+        None => {
+            let mut bundles = validate::fetch_bundles(&[source, locale]).await?.into_iter();
+            let mut next = || bundles.next().context("Missing bundle")?;
+            (next()?, next()?)
+        }
+    };
+
+    let messages: Vec<(String, String, String)> = translations
+        .into_iter()
+        .filter_map(|(key, translation)| {
+            let source = sources.get(&key)?.clone();
+            Some((key, source, translation))
+        })
+        .collect();
+    let scores = scoring.score(&client, source, locale, &messages).await?;
+
+    let mut queue = ReviewQueue::open(queue)?;
+    let scanned = messages.len();
+    let average = (scanned > 0)
+        .then(|| scores.iter().map(|s| s.confidence).sum::<f64>() / scanned as f64);
+    let mut queued = Vec::new();
+    let mut cleared = Vec::new();
+    for ((key, source_text, translation), score) in messages.into_iter().zip(scores) {
+        if score.confidence < scoring.threshold {
+            let entry = Entry {
+                locale: locale.to_string(),
+                key,
+                source_locale: source.to_string(),
+                source: source_text,
+                translation,
+                score,
+                attempts: 0,
+            };
+            queue.push(entry.clone());
+            queued.push(entry);
+        } else if queue.remove(locale, &key).is_some() {
+            cleared.push(key);
+        }
+    }
+    queue.save()?;
+    queued.sort_by(|a, b| a.score.confidence.total_cmp(&b.score.confidence));
+
+    if format == "json" {
+        let output = JsonScanOutput {
+            locale: locale.to_string(),
+            queue: queue.path().display().to_string(),
+            threshold: scoring.threshold,
+            scanned,
+            average_confidence: average,
+            cleared,
+            queued,
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    print_banner(&format!("🔎 Translation Review: {}", locale));
+    println!(
+        "  {} translations scored, average confidence {}",
+        scanned.to_string().cyan(),
+        average.map_or_else(|| "-".to_string(), |a| format!("{:.2}", a))
+    );
+    if !scoring.back_translate {
+        println!(
+            "  {} Back-translation skipped (pass --back-translate to include it)",
+            "ℹ".cyan()
+        );
+    }
+    println!();
+    if queued.is_empty() {
+        println!(
+            "  {} Nothing below {:.2}",
+            "✓".green(),
+            scoring.threshold
+        );
+    } else {
+        println!(
+            "  {} {} translation(s) below {:.2} queued for review:",
+            "⚠".yellow(),
+            queued.len(),
+            scoring.threshold
+        );
+        println!();
+        print_entries(&queued, limit);
+    }
+    if !cleared.is_empty() {
+        println!(
+            "  {} {} queued translation(s) now score well and left the queue",
+            "✓".green(),
+            cleared.len()
+        );
+    }
+    println!();
+    println!("  Queue: {}", queue.path().display().to_string().dimmed());
+    println!();
+    Ok(())
+}
+
+/// Run review list
+pub fn list(locale: Option<&str>, queue: Option<&Path>, limit: usize, format: &str) -> Result<()> {
+    let queue = ReviewQueue::open(queue)?;
+    let entries = queue.pending(locale);
+
+    if format == "json" {
+        let output = JsonListOutput {
+            queue: queue.path().display().to_string(),
+            entries,
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    print_banner("📋 Review Queue");
+    if entries.is_empty() {
+        println!("  {} Nothing awaiting review", "✓".green());
+    } else {
+        let mut per_locale: BTreeMap<&str, usize> = BTreeMap::new();
+        for entry in &entries {
+            *per_locale.entry(&entry.locale).or_default() += 1;
+        }
+        let counts: Vec<String> = per_locale
+            .iter()
+            .map(|(loc, count)| format!("{} {}", loc, count))
+            .collect();
+        println!(
+            "  {} translation(s) awaiting review ({})",
+            entries.len().to_string().yellow(),
+            counts.join(", ")
+        );
+        println!();
+        print_entries(&entries, limit);
+    }
+    println!();
+    println!("  Queue: {}", queue.path().display().to_string().dimmed());
+    println!();
+    Ok(())
+}
+
+/// Run review approve
+pub async fn approve(
+    locale: &str,
+    keys: &[String],
+    all: bool,
+    text: Option<&str>,
+    queue: Option<&Path>,
+    format: &str,
+) -> Result<()> {
+    let mut queue = ReviewQueue::open(queue)?;
+    let mut entries = queue.select(locale, keys, all)?;
+    if entries.is_empty() {
+        anyhow::bail!("No {} translations are awaiting review", locale);
+    }
+    if let Some(text) = text {
+        let [entry] = entries.as_mut_slice() else {
+            anyhow::bail!("--text replaces a single translation; name one key");
+        };
+        let expected = messageformat::arguments(&entry.source).ok();
+        let found = messageformat::arguments(text)
+            .map_err(|e| anyhow::anyhow!("--text doesn't parse: {}", e))?;
+        if expected.is_some_and(|expected| expected != found) {
+            anyhow::bail!("--text must use the source's placeholders: {}", entry.source);
+        }
+        entry.translation = text.to_string();
+    }
+
+    upload(locale, &entries).await?;
+    for entry in &entries {
+        queue.remove(locale, &entry.key);
+    }
+    queue.save()?;
+
+    let approved: Vec<String> = entries.into_iter().map(|e| e.key).collect();
+    if format == "json" {
+        let output = JsonApproveOutput {
+            locale: locale.to_string(),
+            approved,
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    println!();
+    println!(
+        "  {} Approved and uploaded {} {} translation(s)",
+        "✓".green(),
+        approved.len().to_string().green(),
+        locale
+    );
+    println!();
+    Ok(())
+}
+
+/// Run review reject
+pub async fn reject(
+    locale: &str,
+    keys: &[String],
+    all: bool,
+    scoring: &Scoring,
+    queue: Option<&Path>,
+    format: &str,
+) -> Result<()> {
+    let mut queue = ReviewQueue::open(queue)?;
+    let entries = queue.select(locale, keys, all)?;
+    if entries.is_empty() {
+        anyhow::bail!("No {} translations are awaiting review", locale);
+    }
+
+    let client = ApiClient::new()?;
+    let request: serde_json::Value = entries
+        .iter()
+        .map(|e| (e.key.clone(), serde_json::Value::String(e.source.clone())))
+        .collect::<serde_json::Map<String, serde_json::Value>>()
+        .into();
+    let response = client.translate_batch(locale, &request, false).await?;
+    if !response.success {
+        anyhow::bail!(
+            "Translation failed: {}",
+            response.error.unwrap_or_else(|| "Unknown error".to_string())
+        );
+    }
+    let fresh = response.translations.unwrap_or_default();
+
+    let mut unchanged = Vec::new();
+    let mut retranslated = Vec::new();
+    for entry in entries {
+        match fresh.get(&entry.key) {
+            Some(translation) => retranslated.push((entry, translation.clone())),
+            None => unchanged.push(entry.key),
+        }
+    }
+    let source_locale = retranslated
+        .first()
+        .map_or("en", |(e, _)| e.source_locale.as_str())
+        .to_string();
+    let messages: Vec<(String, String, String)> = retranslated
+        .iter()
+        .map(|(e, translation)| (e.key.clone(), e.source.clone(), translation.clone()))
+        .collect();
+    let scores = scoring
+        .score(&client, &source_locale, locale, &messages)
+        .await?;
+
+    let mut passed = Vec::new();
+    let mut requeued = Vec::new();
+    for ((mut entry, translation), score) in retranslated.into_iter().zip(scores) {
+        entry.translation = translation;
+        entry.attempts += 1;
+        let low = score.confidence < scoring.threshold;
+        entry.score = score;
+        if low {
+            requeued.push(entry);
+        } else {
+            passed.push(entry);
+        }
+    }
+
+    if !passed.is_empty() {
+        upload(locale, &passed).await?;
+    }
+    for entry in &passed {
+        queue.remove(locale, &entry.key);
+    }
+    for entry in &requeued {
+        queue.push(entry.clone());
+    }
+    queue.save()?;
+
+    let uploaded: Vec<String> = passed.into_iter().map(|e| e.key).collect();
+    if format == "json" {
+        let output = JsonRejectOutput {
+            locale: locale.to_string(),
+            uploaded,
+            requeued,
+            unchanged,
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    println!();
+    if !uploaded.is_empty() {
+        println!(
+            "  {} {} retranslated and uploaded",
+            "✓".green(),
+            uploaded.len().to_string().green()
+        );
+    }
+    if !requeued.is_empty() {
+        println!(
+            "  {} {} retranslated but still below {:.2}, queued again:",
+            "⚠".yellow(),
+            requeued.len().to_string().yellow(),
+            scoring.threshold
+        );
+        println!();
+        print_entries(&requeued, requeued.len());
+        println!();
+    }
+    if !unchanged.is_empty() {
+        println!(
+            "  {} No new translation for {}; left in the queue",
+            "✗".red(),
+            unchanged.join(", ")
+        );
+    }
+    println!();
+    Ok(())
+}
+
+/// Upload entries' translations to the backend
+async fn upload(locale: &str, entries: &[Entry]) -> Result<()> {
+    let mut bundle = serde_json::json!({});
+    for entry in entries {
+        xliff::set_message(&mut bundle, &entry.key, &entry.translation);
+    }
+    let response = update::update_locale(locale, &bundle).await?;
+    if !response.success {
+        anyhow::bail!(
+            "Upload failed: {}",
+            response.error.unwrap_or_else(|| "Unknown error".to_string())
+        );
+    }
+    Ok(())
+}
+
+fn print_entries(entries: &[Entry], limit: usize) {
+    for entry in entries.iter().take(limit) {
+        let confidence = format!("{:.2}", entry.score.confidence);
+        let confidence = if entry.score.confidence < 0.4 {
+            confidence.red().to_string()
+        } else {
+            confidence.yellow().to_string()
+        };
+        println!("  {}  {} {}", confidence, entry.locale.cyan(), entry.key);
+        println!("        {} {}", format!("{}:", entry.source_locale).dimmed(), entry.source);
+        println!("        {} {}", format!("{}:", entry.locale).dimmed(), entry.translation);
+        for problem in &entry.score.problems {
+            println!("        {} {}", "⚠".yellow(), problem.dimmed());
+        }
+    }
+    if entries.len() > limit {
+        println!("  ... and {} more", entries.len() - limit);
+    }
+}
+
+fn print_banner(title: &str) {
+    println!();
+    println!(
+        "{}",
+        "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━".blue()
+    );
+    println!("  {}", title.blue().bold());
+    println!(
+        "{}",
+        "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━".blue()
+    );
+    println!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(locale: &str, key: &str, confidence: f64, attempts: u32) -> Entry {
+        Entry {
+            locale: locale.to_string(),
+            key: key.to_string(),
+            source_locale: "en".to_string(),
+            source: "Save".to_string(),
+            translation: "Speichern".to_string(),
+            score: Score {
+                confidence,
+                length: None,
+                placeholders: 1.0,
+                glossary: None,
+                back_translation: None,
+                problems: Vec::new(),
+            },
+            attempts,
+        }
+    }
+
+    fn keys(entries: &[Entry]) -> Vec<(&str, &str)> {
+        entries
+            .iter()
+            .map(|e| (e.locale.as_str(), e.key.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_queue_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("review/queue.json");
+
+        let mut queue = ReviewQueue::open(Some(&path)).unwrap();
+        assert!(queue.pending(None).is_empty());
+        queue.push(entry("fr", "common.save", 0.4, 0));
+        queue.push(entry("de", "listing.title", 0.6, 2));
+        queue.push(entry("de", "common.save", 0.5, 0));
+        // Queueing a key again replaces its entry but keeps the attempt count
+        queue.push(entry("de", "listing.title", 0.3, 0));
+        queue.save().unwrap();
+
+        let queue = ReviewQueue::open(Some(&path)).unwrap();
+        assert_eq!(
+            keys(&queue.entries),
+            [("de", "common.save"), ("de", "listing.title"), ("fr", "common.save")]
+        );
+        assert_eq!(queue.entries[1].attempts, 2);
+        assert_eq!(queue.entries[1].score.confidence, 0.3);
+
+        std::fs::write(&path, "{}").unwrap();
+        assert!(ReviewQueue::open(Some(&path)).is_err());
+    }
+
+    #[test]
+    fn test_pending_order() {
+        let mut queue = ReviewQueue::open(Some(Path::new("unused.json"))).unwrap();
+        queue.push(entry("de", "a", 0.6, 0));
+        queue.push(entry("fr", "b", 0.1, 0));
+        queue.push(entry("de", "c", 0.2, 0));
+
+        assert_eq!(keys(&queue.pending(None)), [("fr", "b"), ("de", "c"), ("de", "a")]);
+        assert_eq!(keys(&queue.pending(Some("de"))), [("de", "c"), ("de", "a")]);
+        assert!(queue.pending(Some("pl")).is_empty());
+    }
+
+    #[test]
+    fn test_select() {
+        let mut queue = ReviewQueue::open(Some(Path::new("unused.json"))).unwrap();
+        queue.push(entry("de", "a", 0.6, 0));
+        queue.push(entry("de", "b", 0.1, 0));
+        queue.push(entry("fr", "a", 0.2, 0));
+
+        assert_eq!(keys(&queue.select("de", &[], true).unwrap()), [("de", "a"), ("de", "b")]);
+        let selected = queue.select("fr", &["a".to_string()], false).unwrap();
+        assert_eq!(keys(&selected), [("fr", "a")]);
+
+        let err = queue.select("fr", &["b".to_string()], false).unwrap_err();
+        assert_eq!(err.to_string(), "fr b is not in the review queue");
+        assert!(queue.select("de", &[], false).is_err());
+    }
+}
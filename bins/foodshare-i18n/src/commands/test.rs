@@ -3,6 +3,7 @@
 use crate::api::ApiClient;
 use crate::types::{DeltaTestResult, JsonTestOutput, TestResult};
 use anyhow::{Context, Result};
+use foodshare_api_client::FoodshareClient;
 use owo_colors::OwoColorize;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -301,12 +302,13 @@ struct TranslationField {
 }
 
 pub async fn run_posts(locale: &str, limit: usize, skip_trigger: bool, verbose: bool, format: &str) -> Result<()> {
-    let supabase_url = std::env::var("SUPABASE_URL").context("SUPABASE_URL required")?;
-    let anon_key = std::env::var("SUPABASE_ANON_KEY").context("SUPABASE_ANON_KEY required")?;
-    let service_key = std::env::var("SUPABASE_SERVICE_ROLE_KEY").ok();
+    let config = crate::config::client_config().map_err(|e| anyhow::anyhow!("Failed to load config: {}", e))?;
+    let supabase_url = config.project_url().to_string();
+    let anon_key = config.anon_key.clone().context("No anon key for the selected environment (SUPABASE_ANON_KEY)")?;
+    let writer = trigger_client()?;
 
     if format == "json" {
-        return run_posts_json(&supabase_url, &anon_key, service_key.as_deref(), locale, limit, skip_trigger).await;
+        return run_posts_json(&supabase_url, &anon_key, writer.as_ref(), locale, limit, skip_trigger).await;
     }
 
     println!("{}", "Testing Post Translation System".bold().cyan());
@@ -333,11 +335,10 @@ pub async fn run_posts(locale: &str, limit: usize, skip_trigger: bool, verbose:
     }
 
     // Trigger translations
-    if !skip_trigger && service_key.is_some() {
+    if let (false, Some(writer)) = (skip_trigger, &writer) {
         print!("Triggering translations... ");
-        let key = service_key.as_ref().unwrap();
         for post in &posts {
-            trigger_translation(&client, &supabase_url, key, post).await?;
+            trigger_translation(writer, post).await?;
         }
         println!("{}", "OK".green());
         println!("Waiting 30s for processing...");
@@ -362,16 +363,15 @@ pub async fn run_posts(locale: &str, limit: usize, skip_trigger: bool, verbose:
     Ok(())
 }
 
-async fn run_posts_json(supabase_url: &str, anon_key: &str, service_key: Option<&str>, locale: &str, limit: usize, skip_trigger: bool) -> Result<()> {
+async fn run_posts_json(supabase_url: &str, anon_key: &str, writer: Option<&FoodshareClient>, locale: &str, limit: usize, skip_trigger: bool) -> Result<()> {
     let client = Client::builder().timeout(Duration::from_secs(30)).build()?;
 
     let schema_ok = check_schema(&client, supabase_url, anon_key).await.is_ok();
     let posts = fetch_posts(&client, supabase_url, anon_key, limit).await.unwrap_or_default();
 
-    if !skip_trigger && service_key.is_some() {
-        let key = service_key.unwrap();
+    if let (false, Some(writer)) = (skip_trigger, writer) {
         for post in &posts {
-            let _ = trigger_translation(&client, supabase_url, key, post).await;
+            let _ = trigger_translation(writer, post).await;
         }
         tokio::time::sleep(Duration::from_secs(30)).await;
     }
@@ -410,15 +410,20 @@ async fn fetch_posts(client: &Client, url: &str, key: &str, limit: usize) -> Res
     Ok(posts)
 }
 
-async fn trigger_translation(client: &Client, url: &str, key: &str, post: &Post) -> Result<()> {
+/// Client sending translation requests with the service role key, if one is available
+fn trigger_client() -> Result<Option<FoodshareClient>> {
+    let Ok(config) = crate::config::service_client_config() else { return Ok(None) };
+    let client = FoodshareClient::with_config(config).map_err(|e| anyhow::anyhow!("Failed to create client: {}", e))?;
+    Ok(Some(client))
+}
+
+async fn trigger_translation(client: &FoodshareClient, post: &Post) -> Result<()> {
     let mut fields = vec![TranslationField { name: "title".to_string(), text: post.post_name.clone() }];
     if let Some(desc) = &post.post_description {
         if !desc.is_empty() { fields.push(TranslationField { name: "description".to_string(), text: desc.clone() }); }
     }
     let req = TranslateBatchRequest { content_type: "post".to_string(), content_id: post.id.to_string(), fields };
-    client.post(format!("{}/functions/v1/localization/translate-batch", url))
-        .header("Authorization", format!("Bearer {}", key))
-        .json(&req).send().await?;
+    client.post::<serde_json::Value, _>("localization/translate-batch", &req).await.context("Failed to trigger translation")?;
     Ok(())
 }
 
@@ -1,10 +1,24 @@
 //! Translate command - auto-translate missing keys
 
 use crate::api::ApiClient;
+use crate::checkpoint::Checkpoint;
+use crate::commands::review;
 use crate::config::SUPPORTED_LOCALES;
+use crate::cost::{CostReport, Pricing, Usage};
+use crate::types::TranslateBatchResponse;
 use anyhow::Result;
+use futures_util::StreamExt;
 use owo_colors::OwoColorize;
 use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Instant;
+
+/// Keys per translate request
+const TRANSLATE_BATCH: usize = 50;
+
+/// Checkpoint name of `translate all`
+const CHECKPOINT_COMMAND: &str = "translate-all";
 
 /// JSON output for translate command
 #[derive(Debug, Serialize)]
@@ -12,8 +26,11 @@ struct JsonTranslateOutput {
     locale: String,
     dry_run: bool,
     translated: usize,
-    translations: Option<std::collections::HashMap<String, String>>,
+    translations: Option<HashMap<String, String>>,
     new_version: Option<String>,
+    /// Low-confidence translations added to the review queue
+    queued_for_review: usize,
+    cost: Option<CostReport>,
     error: Option<String>,
 }
 
@@ -22,8 +39,13 @@ struct JsonTranslateOutput {
 struct JsonSyncOutput {
     dry_run: bool,
     locales_processed: usize,
+    /// Locales skipped because the checkpoint has them done
+    already_done: usize,
     total_translated: usize,
     results: Vec<LocaleSyncResult>,
+    cost: CostReport,
+    /// Checkpoint left for --resume when locales failed
+    checkpoint: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -37,9 +59,10 @@ struct LocaleSyncResult {
 /// Run translate command for a single locale
 pub async fn run(locale: &str, apply: bool, limit: usize, format: &str) -> Result<()> {
     let client = ApiClient::new()?;
+    let started = Instant::now();
 
     if format == "json" {
-        return run_json(&client, locale, apply, limit).await;
+        return run_json(&client, locale, apply, limit, started).await;
     }
 
     println!();
@@ -114,6 +137,9 @@ pub async fn run(locale: &str, apply: bool, limit: usize, format: &str) -> Resul
     match client.translate_batch(locale, &keys, apply).await {
         Ok(resp) => {
             if resp.success {
+                let mut usage = Usage::default();
+                record_usage(&mut usage, &keys, &resp);
+                let report = CostReport::new(usage, &Pricing::from_env(), started.elapsed());
                 let translated = resp.translated.unwrap_or(0);
                 if apply {
                     println!(
@@ -124,6 +150,24 @@ pub async fn run(locale: &str, apply: bool, limit: usize, format: &str) -> Resul
                     if let Some(version) = resp.new_version {
                         println!("    └─ New version: {}", version);
                     }
+                    if let Some(translations) = &resp.translations {
+                        let sources = sources(&keys);
+                        match review::queue_low_confidence(&client, locale, &sources, translations)
+                            .await
+                        {
+                            Ok(0) => {}
+                            Ok(queued) => println!(
+                                "  {} {} low-confidence translation(s) queued for review (see `review list`)",
+                                "⚠".yellow(),
+                                queued.to_string().yellow()
+                            ),
+                            Err(e) => println!(
+                                "  {} Could not queue translations for review: {}",
+                                "⚠".yellow(),
+                                e
+                            ),
+                        }
+                    }
                 } else {
                     println!(
                         "  {} Would translate {} keys (dry-run)",
@@ -143,6 +187,8 @@ pub async fn run(locale: &str, apply: bool, limit: usize, format: &str) -> Resul
                         }
                     }
                 }
+                println!();
+                report.print();
             } else {
                 let error = resp.error.unwrap_or_else(|| "Unknown error".to_string());
                 println!("  {} Translation failed: {}", "✗".red(), error);
@@ -157,7 +203,13 @@ pub async fn run(locale: &str, apply: bool, limit: usize, format: &str) -> Resul
     Ok(())
 }
 
-async fn run_json(client: &ApiClient, locale: &str, apply: bool, limit: usize) -> Result<()> {
+async fn run_json(
+    client: &ApiClient,
+    locale: &str,
+    apply: bool,
+    limit: usize,
+    started: Instant,
+) -> Result<()> {
     // Audit to find missing keys
     let audit = client.audit_locale(locale, limit).await?;
     let missing_keys = audit.untranslated.unwrap_or_default();
@@ -169,6 +221,8 @@ async fn run_json(client: &ApiClient, locale: &str, apply: bool, limit: usize) -
             translated: 0,
             translations: None,
             new_version: None,
+            queued_for_review: 0,
+            cost: None,
             error: None,
         };
         println!("{}", serde_json::to_string_pretty(&output)?);
@@ -190,12 +244,24 @@ async fn run_json(client: &ApiClient, locale: &str, apply: bool, limit: usize) -
     // Translate
     match client.translate_batch(locale, &keys, apply).await {
         Ok(resp) => {
+            let queued_for_review = match &resp.translations {
+                Some(translations) if apply && resp.success => {
+                    review::queue_low_confidence(client, locale, &sources(&keys), translations)
+                        .await?
+                }
+                _ => 0,
+            };
+            let mut usage = Usage::default();
+            record_usage(&mut usage, &keys, &resp);
+            let cost = CostReport::new(usage, &Pricing::from_env(), started.elapsed());
             let output = JsonTranslateOutput {
                 locale: locale.to_string(),
                 dry_run: !apply,
                 translated: resp.translated.unwrap_or(0),
                 translations: resp.translations,
                 new_version: resp.new_version,
+                queued_for_review,
+                cost: Some(cost),
                 error: resp.error,
             };
             println!("{}", serde_json::to_string_pretty(&output)?);
@@ -207,6 +273,8 @@ async fn run_json(client: &ApiClient, locale: &str, apply: bool, limit: usize) -
                 translated: 0,
                 translations: None,
                 new_version: None,
+                queued_for_review: 0,
+                cost: None,
                 error: Some(e.to_string()),
             };
             println!("{}", serde_json::to_string_pretty(&output)?);
@@ -216,102 +284,127 @@ async fn run_json(client: &ApiClient, locale: &str, apply: bool, limit: usize) -
     Ok(())
 }
 
-/// Sync all locales
-pub async fn sync_all(apply: bool, format: &str) -> Result<()> {
-    let client = ApiClient::new()?;
-
-    if format == "json" {
-        return sync_all_json(&client, apply).await;
-    }
+/// Key → source text of a translate request's keys object
+fn sources(keys: &serde_json::Value) -> HashMap<String, String> {
+    keys.as_object()
+        .map(|map| {
+            map.iter()
+                .filter_map(|(key, value)| value.as_str().map(|v| (key.clone(), v.to_string())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
 
-    println!();
-    println!(
-        "{}",
-        "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━"
-            .blue()
-    );
-    println!("  {}", "🔄 Sync All Locales".blue().bold());
-    println!(
-        "{}",
-        "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━"
-            .blue()
-    );
-    println!();
+/// Sync all locales, `concurrency` at a time
+pub async fn sync_all(
+    apply: bool,
+    limit: usize,
+    concurrency: usize,
+    resume: bool,
+    checkpoint: Option<&Path>,
+    format: &str,
+) -> Result<()> {
+    let client = ApiClient::new()?;
+    let json = format == "json";
+    let started = Instant::now();
+    let pricing = Pricing::from_env();
+    let mut checkpoint = Checkpoint::open(checkpoint, CHECKPOINT_COMMAND, resume)?;
+    let locales: Vec<&str> = SUPPORTED_LOCALES
+        .iter()
+        .copied()
+        .filter(|loc| !checkpoint.is_done(loc))
+        .collect();
+    let skipped = SUPPORTED_LOCALES.len() - locales.len();
 
-    if !apply {
+    if !json {
+        println!();
         println!(
-            "  {} Running in dry-run mode (use --apply to save)",
-            "ℹ".cyan()
+            "{}",
+            "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━"
+                .blue()
+        );
+        println!("  {}", "🔄 Sync All Locales".blue().bold());
+        println!(
+            "{}",
+            "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━"
+                .blue()
         );
         println!();
-    }
 
-    let mut total_translated = 0;
-    let mut success_count = 0;
-    let mut error_count = 0;
-
-    for locale in SUPPORTED_LOCALES {
-        print!("  {:<5}: ", locale);
-
-        // Audit locale
-        match client.audit_locale(locale, 50).await {
-            Ok(audit) => {
-                let missing_keys = audit.untranslated.unwrap_or_default();
-                if missing_keys.is_empty() {
-                    println!("{}", "✓ Complete".green());
-                    success_count += 1;
-                    continue;
-                }
+        if !apply {
+            println!(
+                "  {} Running in dry-run mode (use --apply to save)",
+                "ℹ".cyan()
+            );
+        }
+        if skipped > 0 {
+            println!(
+                "  {} Resuming: {} locales already done",
+                "ℹ".cyan(),
+                skipped
+            );
+        }
+        println!();
+    }
 
-                // Build keys object
-                let keys: serde_json::Value = missing_keys
-                    .iter()
-                    .take(50)
-                    .filter_map(|k| {
-                        k.english_value
-                            .as_ref()
-                            .map(|v| (k.key.clone(), serde_json::Value::String(v.clone())))
-                    })
-                    .collect::<serde_json::Map<String, serde_json::Value>>()
-                    .into();
-
-                // Translate
-                match client.translate_batch(locale, &keys, apply).await {
-                    Ok(resp) => {
-                        if resp.success {
-                            let count = resp.translated.unwrap_or(0);
-                            total_translated += count;
-                            if apply {
-                                println!(
-                                    "{} ({} keys translated)",
-                                    "✓".green(),
-                                    count.to_string().green()
-                                );
-                            } else {
-                                println!(
-                                    "{} ({} keys would be translated)",
-                                    "○".cyan(),
-                                    count.to_string().cyan()
-                                );
-                            }
-                            success_count += 1;
-                        } else {
-                            let error = resp.error.unwrap_or_else(|| "Unknown".to_string());
-                            println!("{} ({})", "✗".red(), error.dimmed());
-                            error_count += 1;
-                        }
-                    }
-                    Err(e) => {
-                        println!("{} ({})", "✗".red(), e.to_string().dimmed());
-                        error_count += 1;
-                    }
+    let mut results = Vec::new();
+    let mut stream = client
+        .inner()
+        .batch_unordered(locales, concurrency, |locale| {
+            let client = &client;
+            async move { sync_locale(client, locale, apply, limit).await }
+        });
+    while let Some(sync) = stream.next().await {
+        checkpoint.usage.add(&sync.usage);
+        if sync.result.error.is_none() {
+            checkpoint.complete(sync.result.locale.clone());
+        }
+        checkpoint.save()?;
+
+        if !json {
+            let result = &sync.result;
+            print!("  {:<5}: ", result.locale);
+            match &result.error {
+                Some(error) => println!("{} ({})", "✗".red(), error.dimmed()),
+                None if result.translated == 0 && sync.missing == 0 => {
+                    println!("{}", "✓ Complete".green())
                 }
-            }
-            Err(e) => {
-                println!("{} ({})", "✗".red(), e.to_string().dimmed());
-                error_count += 1;
+                None if apply => println!(
+                    "{} ({} keys translated)",
+                    "✓".green(),
+                    result.translated.to_string().green()
+                ),
+                None => println!(
+                    "{} ({} keys would be translated)",
+                    "○".cyan(),
+                    result.translated.to_string().cyan()
+                ),
             }
         }
+        results.push(sync.result);
+    }
+    drop(stream);
+    results.sort_by(|a, b| a.locale.cmp(&b.locale));
+
+    let errors = results.iter().filter(|r| r.error.is_some()).count();
+    let total_translated: usize = results.iter().map(|r| r.translated).sum();
+    let report = CostReport::new(checkpoint.usage, &pricing, started.elapsed());
+    if errors == 0 {
+        checkpoint.remove()?;
+    }
+
+    if json {
+        let output = JsonSyncOutput {
+            dry_run: !apply,
+            locales_processed: results.len(),
+            already_done: skipped,
+            total_translated,
+            results,
+            cost: report,
+            checkpoint: (errors > 0).then(|| checkpoint.path().display().to_string()),
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
     }
 
     println!();
@@ -322,87 +415,96 @@ pub async fn sync_all(apply: bool, format: &str) -> Result<()> {
     );
     println!(
         "  Summary: {} locales processed, {} total translations{}",
-        success_count.to_string().green(),
+        (results.len() - errors).to_string().green(),
         total_translated.to_string().cyan(),
-        if error_count > 0 {
-            format!(", {} errors", error_count.to_string().red())
+        if errors > 0 {
+            format!(", {} errors", errors.to_string().red())
         } else {
             String::new()
         }
     );
     println!();
+    report.print();
+    if errors > 0 {
+        println!();
+        println!(
+            "  {} Run again with --resume to retry only the failed locales",
+            "ℹ".cyan()
+        );
+    }
+    println!();
 
     Ok(())
 }
 
-async fn sync_all_json(client: &ApiClient, apply: bool) -> Result<()> {
-    let mut results = Vec::new();
-    let mut total_translated = 0;
-
-    for locale in SUPPORTED_LOCALES {
-        match client.audit_locale(locale, 50).await {
-            Ok(audit) => {
-                let missing_keys = audit.untranslated.unwrap_or_default();
-                if missing_keys.is_empty() {
-                    results.push(LocaleSyncResult {
-                        locale: locale.to_string(),
-                        translated: 0,
-                        success: true,
-                        error: None,
-                    });
-                    continue;
-                }
+/// One locale's sync and the tokens it used
+struct LocaleSync {
+    result: LocaleSyncResult,
+    /// Keys the audit found missing
+    missing: usize,
+    usage: Usage,
+}
 
-                let keys: serde_json::Value = missing_keys
-                    .iter()
-                    .take(50)
-                    .filter_map(|k| {
-                        k.english_value
-                            .as_ref()
-                            .map(|v| (k.key.clone(), serde_json::Value::String(v.clone())))
-                    })
-                    .collect::<serde_json::Map<String, serde_json::Value>>()
-                    .into();
-
-                match client.translate_batch(locale, &keys, apply).await {
-                    Ok(resp) => {
-                        let count = resp.translated.unwrap_or(0);
-                        total_translated += count;
-                        results.push(LocaleSyncResult {
-                            locale: locale.to_string(),
-                            translated: count,
-                            success: resp.success,
-                            error: resp.error,
-                        });
-                    }
-                    Err(e) => {
-                        results.push(LocaleSyncResult {
-                            locale: locale.to_string(),
-                            translated: 0,
-                            success: false,
-                            error: Some(e.to_string()),
-                        });
-                    }
-                }
+/// Translate up to `limit` missing keys of a locale, `TRANSLATE_BATCH` per request
+async fn sync_locale(client: &ApiClient, locale: &str, apply: bool, limit: usize) -> LocaleSync {
+    let mut sync = LocaleSync {
+        result: LocaleSyncResult {
+            locale: locale.to_string(),
+            translated: 0,
+            success: true,
+            error: None,
+        },
+        missing: 0,
+        usage: Usage::default(),
+    };
+
+    let missing_keys = match client.audit_locale(locale, limit).await {
+        Ok(audit) => audit.untranslated.unwrap_or_default(),
+        Err(e) => {
+            sync.result.success = false;
+            sync.result.error = Some(format!("{:#}", e));
+            return sync;
+        }
+    };
+    sync.missing = missing_keys.len();
+
+    let keys: Vec<(String, String)> = missing_keys
+        .into_iter()
+        .take(limit)
+        .filter_map(|k| k.english_value.map(|v| (k.key, v)))
+        .collect();
+    for chunk in keys.chunks(TRANSLATE_BATCH) {
+        let request: serde_json::Value = chunk
+            .iter()
+            .map(|(key, value)| (key.clone(), serde_json::Value::String(value.clone())))
+            .collect::<serde_json::Map<String, serde_json::Value>>()
+            .into();
+        match client.translate_batch(locale, &request, apply).await {
+            Ok(resp) if resp.success => {
+                record_usage(&mut sync.usage, &request, &resp);
+                sync.result.translated += resp.translated.unwrap_or(0);
+            }
+            Ok(resp) => {
+                sync.result.success = false;
+                sync.result.error = Some(resp.error.unwrap_or_else(|| "Unknown".to_string()));
+                break;
             }
             Err(e) => {
-                results.push(LocaleSyncResult {
-                    locale: locale.to_string(),
-                    translated: 0,
-                    success: false,
-                    error: Some(e.to_string()),
-                });
+                sync.result.success = false;
+                sync.result.error = Some(format!("{:#}", e));
+                break;
             }
         }
     }
+    sync
+}
 
-    let output = JsonSyncOutput {
-        dry_run: !apply,
-        locales_processed: results.len(),
-        total_translated,
-        results,
-    };
-
-    println!("{}", serde_json::to_string_pretty(&output)?);
-    Ok(())
+/// Count a translate request's tokens: the service's figures, or an
+/// estimate from the source texts and the translations that came back
+fn record_usage(usage: &mut Usage, request: &serde_json::Value, resp: &TranslateBatchResponse) {
+    let input_chars: usize = sources(request).values().map(|v| v.chars().count()).sum();
+    let output_chars = resp.translations.as_ref().map_or(input_chars, |t| {
+        t.values().map(|v| v.chars().count()).sum()
+    });
+    usage.record(resp.usage, 1, input_chars, output_chars);
 }
@@ -5,7 +5,9 @@
 //! - update_translations.sh
 //! - update_ios_translations.sh
 
+use crate::commands::validate;
 use anyhow::{Context, Result};
+use foodshare_api_client::FoodshareClient;
 use owo_colors::OwoColorize;
 use serde::Deserialize;
 use std::collections::HashMap;
@@ -13,17 +15,31 @@ use std::path::Path;
 
 /// Response from the update-translations endpoint
 #[derive(Debug, Deserialize)]
-struct UpdateResponse {
-    success: bool,
-    added: Option<usize>,
-    total: Option<usize>,
-    error: Option<String>,
+pub struct UpdateResponse {
+    pub success: bool,
+    pub added: Option<usize>,
+    pub total: Option<usize>,
+    pub error: Option<String>,
 }
 
+/// Source locale uploaded bundles are validated against
+const VALIDATION_SOURCE: &str = "en";
+
+/// Issues shown when a bundle fails validation
+const VALIDATION_LIMIT: usize = 20;
+
 /// Run the update command for a specific locale from a JSON file
-pub async fn run_from_file(locale: &str, file_path: &str, format: &str) -> Result<()> {
+///
+/// With `validate`, the file's messages are checked (ICU syntax, placeholders
+/// against the source locale) and nothing is uploaded if any fail.
+pub async fn run_from_file(
+    locale: &str,
+    file_path: &str,
+    validate: bool,
+    format: &str,
+) -> Result<()> {
     if format == "json" {
-        return run_from_file_json(locale, file_path).await;
+        return run_from_file_json(locale, file_path, validate).await;
     }
 
     println!("{}", "Updating Translations from File".bold().cyan());
@@ -51,6 +67,25 @@ pub async fn run_from_file(locale: &str, file_path: &str, format: &str) -> Resul
     println!("Keys to update: {}", key_count);
     println!();
 
+    if validate {
+        let issues = validate::check_bundle(locale, VALIDATION_SOURCE, &translations).await?;
+        if !issues.is_empty() {
+            println!(
+                "{} {} message(s) failed validation:",
+                "✗".red(),
+                issues.len()
+            );
+            validate::print_issues(&issues, VALIDATION_LIMIT);
+            println!();
+            anyhow::bail!(
+                "Not uploading {}: fix the messages or pass --no-validate",
+                file_path
+            );
+        }
+        println!("{} Messages validated", "✓".green());
+        println!();
+    }
+
     // Send to API
     let result = update_locale(locale, &translations).await?;
 
@@ -77,7 +112,7 @@ pub async fn run_from_file(locale: &str, file_path: &str, format: &str) -> Resul
 }
 
 /// Run from file in JSON mode
-async fn run_from_file_json(locale: &str, file_path: &str) -> Result<()> {
+async fn run_from_file_json(locale: &str, file_path: &str, validate: bool) -> Result<()> {
     let path = Path::new(file_path);
     if !path.exists() {
         let output = serde_json::json!({
@@ -91,6 +126,21 @@ async fn run_from_file_json(locale: &str, file_path: &str) -> Result<()> {
     let content = std::fs::read_to_string(path)?;
     let translations: serde_json::Value = serde_json::from_str(&content)?;
 
+    if validate {
+        let issues = validate::check_bundle(locale, VALIDATION_SOURCE, &translations).await?;
+        if !issues.is_empty() {
+            let output = serde_json::json!({
+                "success": false,
+                "locale": locale,
+                "file": file_path,
+                "error": "validation failed",
+                "issues": issues
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+            anyhow::bail!("{} message(s) failed validation", issues.len());
+        }
+    }
+
     let result = update_locale(locale, &translations).await?;
 
     let output = serde_json::json!({
@@ -106,6 +156,11 @@ async fn run_from_file_json(locale: &str, file_path: &str) -> Result<()> {
     Ok(())
 }
 
+/// Run the update command for one locale from its preset translations
+pub async fn run_preset(locale: &str, format: &str) -> Result<()> {
+    run_batch(&[locale.to_string()], format).await
+}
+
 /// Run the update command for multiple locales from inline data
 pub async fn run_batch(locales: &[String], format: &str) -> Result<()> {
     if format == "json" {
@@ -209,41 +264,34 @@ async fn run_batch_json(locales: &[String]) -> Result<()> {
     Ok(())
 }
 
-/// Send translations to the API
-async fn update_locale(locale: &str, translations: &serde_json::Value) -> Result<UpdateResponse> {
-    let base_url = std::env::var("SUPABASE_URL")
-        .unwrap_or_else(|_| "https://api.foodshare.club".to_string());
-
-    let service_key = std::env::var("SUPABASE_SERVICE_ROLE_KEY")
-        .or_else(|_| std::env::var("SUPABASE_ANON_KEY"))
-        .context("SUPABASE_SERVICE_ROLE_KEY or SUPABASE_ANON_KEY must be set")?;
-
-    let client = reqwest::Client::new();
+/// Send translations to the API of the selected environment
+pub async fn update_locale(
+    locale: &str,
+    translations: &serde_json::Value,
+) -> Result<UpdateResponse> {
+    let config = crate::config::service_client_config()
+        .map_err(|e| anyhow::anyhow!("Failed to load config: {}", e))?;
+    let client = FoodshareClient::with_config(config)
+        .map_err(|e| anyhow::anyhow!("Failed to create client: {}", e))?;
+
+    send_update(&client, locale, translations).await
+}
 
+/// POST translations to `update-translations`
+async fn send_update(
+    client: &FoodshareClient,
+    locale: &str,
+    translations: &serde_json::Value,
+) -> Result<UpdateResponse> {
     let payload = serde_json::json!({
         "locale": locale,
         "translations": translations
     });
 
-    let response = client
-        .post(format!("{}/functions/v1/update-translations", base_url))
-        .header("Authorization", format!("Bearer {}", service_key))
-        .header("Content-Type", "application/json")
-        .json(&payload)
-        .send()
+    client
+        .post("update-translations", &payload)
         .await
-        .context("Failed to connect to update-translations endpoint")?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        anyhow::bail!("HTTP {}: {}", status, body);
-    }
-
-    response
-        .json::<UpdateResponse>()
-        .await
-        .context("Failed to parse response")
+        .context("Failed to send translations to update-translations")
 }
 
 /// Count keys in a nested JSON object
@@ -449,3 +497,55 @@ fn get_preset_translations(locale: &str) -> Option<serde_json::Value> {
         serde_json::Value::Object(map)
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::confirm_production;
+    use foodshare_api_client::transport::{MockResponse, MockRoute, MockTransport};
+    use foodshare_api_client::{ApiError, Profiles};
+    use reqwest::Method;
+    use std::sync::Arc;
+
+    const PROFILES: &str = r#"
+[environments.prod]
+environment = "production"
+base_url = "https://api.example.com/functions/v1"
+service_role_key = { value = "prod-service-role" }
+"#;
+
+    fn prod_client(yes_prod: bool, mock: &Arc<MockTransport>) -> FoodshareClient {
+        let profiles = Profiles::parse(PROFILES).unwrap();
+        let config = profiles.select(Some("prod")).unwrap().to_client_config().unwrap();
+        FoodshareClient::with_config(confirm_production(config, yes_prod))
+            .unwrap()
+            .with_transport(mock.clone())
+    }
+
+    #[tokio::test]
+    async fn test_update_refused_on_prod_without_yes_prod() {
+        let mock = Arc::new(MockTransport::new().with_route(MockRoute::new(
+            Method::POST,
+            "update-translations",
+            MockResponse::json(200, &serde_json::json!({"success": true, "added": 1})),
+        )));
+        let translations = serde_json::json!({"yes": "Ja"});
+
+        let err = send_update(&prod_client(false, &mock), "de", &translations)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ApiError>(),
+            Some(ApiError::ProductionWriteBlocked { .. })
+        ));
+        assert!(mock.requests().is_empty());
+
+        let resp = send_update(&prod_client(true, &mock), "de", &translations)
+            .await
+            .unwrap();
+        assert!(resp.success);
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].path, "/functions/v1/update-translations");
+    }
+}
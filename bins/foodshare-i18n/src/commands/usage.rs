@@ -0,0 +1,270 @@
+//! Usage command - compare translation keys used by the web app with the bundle
+//!
+//! Extracts `t('key')` calls from the web sources the way `extract` does
+//! (see [`crate::inventory`]). Keys built from templates (`t(`status.${s}`)`)
+//! count as using every key under their static prefix.
+
+use crate::api::ApiClient;
+use crate::inventory::{self, Inventory, Platform};
+use anyhow::{Context, Result};
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// A key used in the sources
+#[derive(Debug, Clone, Serialize)]
+struct Usage {
+    key: String,
+    file: String,
+    line: usize,
+}
+
+/// JSON output for usage
+#[derive(Debug, Serialize)]
+struct JsonUsageOutput {
+    locale: String,
+    files: usize,
+    used_keys: usize,
+    defined_keys: usize,
+    missing: Vec<Usage>,
+    unused: Vec<String>,
+}
+
+/// Run usage command
+pub async fn run(
+    root: &Path,
+    locale: &str,
+    bundle: Option<&Path>,
+    fail_on_unused: bool,
+    limit: usize,
+    format: &str,
+) -> Result<()> {
+    // Sources are read from root/src, or root without one
+    let src = root.join("src");
+    let base = if src.is_dir() { src.as_path() } else { root };
+    let sources = inventory::scan(base, root, &[Platform::Web])?;
+    let defined = match bundle {
+        Some(path) => {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let messages: serde_json::Value = serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse JSON from {}", path.display()))?;
+            flatten_keys(&messages)
+        }
+        None => {
+            let client = ApiClient::new()?;
+            let response = client.download_translations(locale).await?;
+            let messages = response
+                .data
+                .map(|d| d.messages)
+                .with_context(|| format!("No translations returned for {}", locale))?;
+            flatten_keys(&messages)
+        }
+    };
+
+    let mut missing: Vec<Usage> = sources
+        .missing_from(&defined)
+        .into_iter()
+        .flat_map(|key| {
+            sources.keys[key]
+                .locations
+                .iter()
+                .map(move |location| Usage {
+                    key: key.to_string(),
+                    file: location.file.clone(),
+                    line: location.line,
+                })
+        })
+        .collect();
+    missing.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+    let used = sources.keys.len();
+    let unused: Vec<String> = sources
+        .unused_in(&defined)
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+    if format == "json" {
+        let output = JsonUsageOutput {
+            locale: locale.to_string(),
+            files: sources.files,
+            used_keys: used,
+            defined_keys: defined.len(),
+            missing: missing.clone(),
+            unused: unused.clone(),
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else {
+        print_report(&sources, &defined, used, &missing, &unused, locale, limit);
+    }
+
+    if !missing.is_empty() {
+        anyhow::bail!(
+            "{} translation key(s) used but missing from the {} bundle",
+            missing.len(),
+            locale
+        );
+    }
+    if fail_on_unused && !unused.is_empty() {
+        anyhow::bail!("{} translation key(s) defined but unused", unused.len());
+    }
+    Ok(())
+}
+
+fn print_report(
+    sources: &Inventory,
+    defined: &BTreeSet<String>,
+    used: usize,
+    missing: &[Usage],
+    unused: &[String],
+    locale: &str,
+    limit: usize,
+) {
+    println!();
+    println!(
+        "{}",
+        "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━".blue()
+    );
+    println!("  {}", "🔑 Translation Key Usage".blue().bold());
+    println!(
+        "{}",
+        "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━".blue()
+    );
+    println!();
+    println!(
+        "  {} keys used in {} files, {} defined in the {} bundle",
+        used.to_string().cyan(),
+        sources.files,
+        defined.len().to_string().cyan(),
+        locale
+    );
+    if !sources.dynamic_prefixes.is_empty() {
+        println!(
+            "  {} dynamic key prefix(es): {}",
+            sources.dynamic_prefixes.len(),
+            sources
+                .dynamic_prefixes
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(", ")
+                .dimmed()
+        );
+    }
+    println!();
+
+    if missing.is_empty() {
+        println!("  {} Every used key is defined", "✓".green());
+    } else {
+        println!("  {} {} key(s) used but missing:", "✗".red(), missing.len());
+        for usage in missing {
+            println!("    {}:{} {}", usage.file, usage.line, usage.key.red());
+        }
+    }
+
+    if !unused.is_empty() {
+        println!();
+        println!(
+            "  {} {} key(s) defined but unused by the web app:",
+            "⚠".yellow(),
+            unused.len()
+        );
+        for key in unused.iter().take(limit) {
+            println!("    {}", key.dimmed());
+        }
+        if unused.len() > limit {
+            println!("    ... and {} more", unused.len() - limit);
+        }
+    }
+    println!();
+}
+
+/// Dotted paths of the string leaves of a messages object
+fn flatten_keys(messages: &serde_json::Value) -> BTreeSet<String> {
+    fn walk(value: &serde_json::Value, prefix: &str, keys: &mut BTreeSet<String>) {
+        let serde_json::Value::Object(map) = value else {
+            return;
+        };
+        for (name, child) in map {
+            let key = if prefix.is_empty() {
+                name.clone()
+            } else {
+                format!("{}.{}", prefix, name)
+            };
+            if child.is_string() {
+                keys.insert(key);
+            } else {
+                walk(child, &key, keys);
+            }
+        }
+    }
+
+    let mut keys = BTreeSet::new();
+    walk(messages, "", &mut keys);
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A web app whose sources use `common.save`, `feed.title` (twice) and
+    /// keys under `status.`
+    fn web_app() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let app = dir.path().join("src/app");
+        std::fs::create_dir_all(&app).unwrap();
+        std::fs::create_dir_all(dir.path().join("src/node_modules/lib")).unwrap();
+        std::fs::write(
+            app.join("page.tsx"),
+            "const t = useTranslations('feed');\nt('title');\n{t('title')}\n// t('commented.out')\n",
+        )
+        .unwrap();
+        std::fs::write(
+            app.join("row.tsx"),
+            "export const Row = ({ t }) => t('common.save') + t(`status.${status}`);\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("src/node_modules/lib/index.js"), "t('vendor.key')").unwrap();
+        dir
+    }
+
+    async fn usage(dir: &Path, bundle: serde_json::Value, fail_on_unused: bool) -> Result<()> {
+        let path = dir.join("en.json");
+        std::fs::write(&path, bundle.to_string()).unwrap();
+        run(dir, "en", Some(&path), fail_on_unused, 10, "json").await
+    }
+
+    #[tokio::test]
+    async fn test_missing_keys() {
+        let dir = web_app();
+        let bundle = serde_json::json!({"common": {"save": "Save"}, "status": {"open": "Open"}});
+        // Each lookup of a missing key counts
+        let err = usage(dir.path(), bundle, false).await.unwrap_err();
+        assert_eq!(err.to_string(), "2 translation key(s) used but missing from the en bundle");
+    }
+
+    #[tokio::test]
+    async fn test_unused_keys() {
+        let dir = web_app();
+        let bundle = serde_json::json!({
+            "common": {"save": "Save", "cancel": "Cancel"},
+            "feed": {"title": "Feed"},
+            "status": {"open": "Open", "closed": "Closed"},
+        });
+        usage(dir.path(), bundle.clone(), false).await.unwrap();
+        // Keys under a dynamic prefix count as used
+        let err = usage(dir.path(), bundle, true).await.unwrap_err();
+        assert_eq!(err.to_string(), "1 translation key(s) defined but unused");
+    }
+
+    #[test]
+    fn test_flatten_keys() {
+        let messages = serde_json::json!({"common": {"save": "Save", "count": 2}, "title": "Foodshare"});
+        assert_eq!(
+            flatten_keys(&messages),
+            BTreeSet::from(["common.save".to_string(), "title".to_string()])
+        );
+    }
+}
@@ -0,0 +1,216 @@
+//! Validate command - check ICU MessageFormat syntax and placeholders
+//!
+//! Every message is parsed for plural/select syntax, and each translation's
+//! placeholders are compared with the source locale's, so a malformed plural
+//! or a dropped `{count}` is caught before it reaches the apps.
+
+use crate::api::ApiClient;
+use crate::config::SUPPORTED_LOCALES;
+use crate::messageformat::{self, Issue};
+use anyhow::{Context, Result};
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Locales fetched at once
+const VALIDATE_CONCURRENCY: usize = 4;
+
+/// Validation result for one locale
+#[derive(Debug, Serialize)]
+struct LocaleValidation {
+    locale: String,
+    messages: usize,
+    issues: Vec<Issue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// JSON output for validate
+#[derive(Debug, Serialize)]
+struct JsonValidateOutput {
+    source: String,
+    locales: Vec<LocaleValidation>,
+    total_issues: usize,
+}
+
+/// Run validate command
+pub async fn run(
+    locale: Option<&str>,
+    source: &str,
+    dir: Option<&Path>,
+    limit: usize,
+    format: &str,
+) -> Result<()> {
+    let locales: Vec<&str> = match locale {
+        Some(loc) => vec![loc],
+        None => SUPPORTED_LOCALES
+            .iter()
+            .copied()
+            .filter(|loc| *loc != source)
+            .collect(),
+    };
+
+    let source_messages = match dir {
+        Some(dir) => load_bundle(dir, source)?,
+        None => fetch_bundles(&[source]).await?.remove(0)?,
+    };
+    let loaded: Vec<Result<BTreeMap<String, String>>> = match dir {
+        Some(dir) => locales.iter().map(|loc| load_bundle(dir, loc)).collect(),
+        None => fetch_bundles(&locales).await?,
+    };
+
+    // The source is only checked for syntax
+    let mut results = vec![LocaleValidation {
+        locale: source.to_string(),
+        messages: source_messages.len(),
+        issues: messageformat::validate_locale(&BTreeMap::new(), &source_messages),
+        error: None,
+    }];
+    for (loc, messages) in locales.iter().zip(loaded) {
+        results.push(match messages {
+            Ok(messages) => LocaleValidation {
+                locale: loc.to_string(),
+                messages: messages.len(),
+                issues: messageformat::validate_locale(&source_messages, &messages),
+                error: None,
+            },
+            Err(e) => LocaleValidation {
+                locale: loc.to_string(),
+                messages: 0,
+                issues: Vec::new(),
+                error: Some(e.to_string()),
+            },
+        });
+    }
+
+    let total_issues: usize = results.iter().map(|r| r.issues.len()).sum();
+    let errors = results.iter().filter(|r| r.error.is_some()).count();
+
+    if format == "json" {
+        let output = JsonValidateOutput {
+            source: source.to_string(),
+            locales: results,
+            total_issues,
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else {
+        print_report(&results, source, limit);
+    }
+
+    if total_issues > 0 {
+        anyhow::bail!("{} message(s) failed validation", total_issues);
+    }
+    if errors > 0 {
+        anyhow::bail!("{} locale(s) could not be loaded", errors);
+    }
+    Ok(())
+}
+
+/// Problems with the messages of a bundle about to be uploaded for `locale`
+///
+/// Placeholders are compared with the `source` bundle from the API unless
+/// `locale` is the source.
+pub async fn check_bundle(
+    locale: &str,
+    source: &str,
+    messages: &serde_json::Value,
+) -> Result<Vec<Issue>> {
+    let messages = messageformat::flatten_messages(messages);
+    let source_messages = if locale == source {
+        BTreeMap::new()
+    } else {
+        fetch_bundles(&[source])
+            .await?
+            .remove(0)
+            .with_context(|| format!("Failed to fetch the {} bundle to validate against", source))?
+    };
+    Ok(messageformat::validate_locale(&source_messages, &messages))
+}
+
+/// Print issues, at most `limit` of them
+pub fn print_issues(issues: &[Issue], limit: usize) {
+    for issue in issues.iter().take(limit) {
+        println!("    {} {}", issue.key.yellow(), issue.message);
+    }
+    if issues.len() > limit {
+        println!("    ... and {} more", issues.len() - limit);
+    }
+}
+
+fn print_report(results: &[LocaleValidation], source: &str, limit: usize) {
+    println!();
+    println!(
+        "{}",
+        "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━".blue()
+    );
+    println!("  {}", "🧩 Message Format Validation".blue().bold());
+    println!(
+        "{}",
+        "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━".blue()
+    );
+    println!();
+    println!("Placeholders checked against {}", source.cyan());
+    println!();
+
+    for result in results {
+        if let Some(error) = &result.error {
+            println!(
+                "  {:<5}: {} {}",
+                result.locale,
+                "Error loading:".red(),
+                error
+            );
+        } else if result.issues.is_empty() {
+            println!(
+                "  {:<5}: {} {} messages",
+                result.locale,
+                "✓".green(),
+                result.messages
+            );
+        } else {
+            println!(
+                "  {:<5}: {} {} issue(s) in {} messages",
+                result.locale,
+                "✗".red(),
+                result.issues.len(),
+                result.messages
+            );
+            print_issues(&result.issues, limit);
+        }
+    }
+    println!();
+}
+
+/// Fetch the flattened messages of several locales concurrently, in locale order
+pub async fn fetch_bundles(locales: &[&str]) -> Result<Vec<Result<BTreeMap<String, String>>>> {
+    let client = ApiClient::new()?;
+    let fetched = client
+        .inner()
+        .batch(locales.iter().copied(), VALIDATE_CONCURRENCY, |loc| {
+            client.fetch_direct_translations(loc)
+        })
+        .await
+        .into_iter()
+        .zip(locales)
+        .map(|(result, loc)| {
+            let (response, _) = result?;
+            let messages = response
+                .data
+                .map(|d| d.messages)
+                .with_context(|| format!("No translations returned for {}", loc))?;
+            Ok(messageformat::flatten_messages(&messages))
+        })
+        .collect();
+    Ok(fetched)
+}
+
+/// Read the flattened messages of `<dir>/<locale>.json`
+pub fn load_bundle(dir: &Path, locale: &str) -> Result<BTreeMap<String, String>> {
+    let path = dir.join(format!("{}.json", locale));
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let messages: serde_json::Value = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse JSON from {}", path.display()))?;
+    Ok(messageformat::flatten_messages(&messages))
+}
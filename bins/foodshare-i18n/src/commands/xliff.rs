@@ -0,0 +1,695 @@
+//! XLIFF commands - round-trip bundles through the translation vendor
+//!
+//! `export-xliff` writes one XLIFF 2.0 file per locale with the source and
+//! current translation of every key. `import-xliff` merges the returned
+//! files back: a key is only taken when the vendor's text differs from the
+//! bundle, the source text hasn't changed since export, and the bundle
+//! itself hasn't been edited since export (a conflict, unless `--force`).
+//! Imported messages are validated like `validate` before anything is
+//! uploaded.
+
+use crate::api::ApiClient;
+use crate::commands::{update, validate};
+use crate::config::SUPPORTED_LOCALES;
+use crate::messageformat::{self, Issue};
+use crate::xliff::{Document, State, Unit};
+use anyhow::{Context, Result};
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Keys sent to the translate endpoint at once, as `translate` does
+const MACHINE_TRANSLATE_BATCH: usize = 50;
+
+/// Export result for one locale
+#[derive(Debug, Serialize)]
+struct LocaleExport {
+    locale: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<PathBuf>,
+    units: usize,
+    untranslated: usize,
+    machine_translated: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// JSON output for export-xliff
+#[derive(Debug, Serialize)]
+struct JsonExportOutput {
+    source: String,
+    locales: Vec<LocaleExport>,
+}
+
+/// A key the import left alone
+#[derive(Debug, Clone, Serialize)]
+struct Conflict {
+    key: String,
+    reason: String,
+}
+
+/// Import result for one file
+#[derive(Debug, Serialize)]
+struct FileImport {
+    file: PathBuf,
+    locale: String,
+    updated: usize,
+    unchanged: usize,
+    untranslated: usize,
+    unreviewed: usize,
+    conflicts: Vec<Conflict>,
+    issues: Vec<Issue>,
+    applied: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// JSON output for import-xliff
+#[derive(Debug, Serialize)]
+struct JsonImportOutput {
+    dry_run: bool,
+    files: Vec<FileImport>,
+}
+
+/// How a document merges into a bundle
+#[derive(Debug, Default)]
+struct Merge {
+    updates: BTreeMap<String, String>,
+    unchanged: usize,
+    untranslated: usize,
+    unreviewed: usize,
+    conflicts: Vec<Conflict>,
+    issues: Vec<Issue>,
+}
+
+/// Run the export-xliff command
+pub async fn export(
+    locales: &[String],
+    source: &str,
+    dir: Option<&Path>,
+    out: &Path,
+    missing_only: bool,
+    machine_translate: bool,
+    format: &str,
+) -> Result<()> {
+    let locales: Vec<&str> = if locales.is_empty() {
+        SUPPORTED_LOCALES
+            .iter()
+            .copied()
+            .filter(|loc| *loc != source)
+            .collect()
+    } else {
+        locales.iter().map(String::as_str).collect()
+    };
+
+    let source_messages = match dir {
+        Some(dir) => validate::load_bundle(dir, source)?,
+        None => validate::fetch_bundles(&[source]).await?.remove(0)?,
+    };
+    let loaded: Vec<Result<BTreeMap<String, String>>> = match dir {
+        Some(dir) => locales
+            .iter()
+            .map(|loc| validate::load_bundle(dir, loc))
+            .collect(),
+        None => validate::fetch_bundles(&locales).await?,
+    };
+
+    std::fs::create_dir_all(out).with_context(|| format!("Failed to create {}", out.display()))?;
+
+    let mut results = Vec::new();
+    for (locale, messages) in locales.iter().zip(loaded) {
+        let result = match messages {
+            Ok(messages) => {
+                export_locale(
+                    locale,
+                    source,
+                    &source_messages,
+                    &messages,
+                    out,
+                    missing_only,
+                    machine_translate,
+                )
+                .await
+            }
+            Err(e) => Err(e),
+        };
+        results.push(result.unwrap_or_else(|e| LocaleExport {
+            locale: locale.to_string(),
+            file: None,
+            units: 0,
+            untranslated: 0,
+            machine_translated: 0,
+            error: Some(e.to_string()),
+        }));
+    }
+
+    let errors = results.iter().filter(|r| r.error.is_some()).count();
+    if format == "json" {
+        let output = JsonExportOutput {
+            source: source.to_string(),
+            locales: results,
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else {
+        print_export(&results, source, out);
+    }
+
+    if errors > 0 {
+        anyhow::bail!("{} locale(s) could not be exported", errors);
+    }
+    Ok(())
+}
+
+async fn export_locale(
+    locale: &str,
+    source: &str,
+    source_messages: &BTreeMap<String, String>,
+    messages: &BTreeMap<String, String>,
+    out: &Path,
+    missing_only: bool,
+    machine_translate: bool,
+) -> Result<LocaleExport> {
+    let missing: Vec<&String> = source_messages
+        .keys()
+        .filter(|key| !messages.contains_key(*key))
+        .collect();
+    let machine = if machine_translate && !missing.is_empty() {
+        machine_translations(locale, &missing, source_messages).await?
+    } else {
+        HashMap::new()
+    };
+
+    let mut units = Vec::new();
+    for (key, message) in source_messages {
+        let current = messages.get(key);
+        if missing_only && current.is_some() {
+            continue;
+        }
+        let (target, state, machine_translated) = match (current, machine.get(key)) {
+            (Some(current), _) => (Some(current.clone()), State::Translated, false),
+            (None, Some(suggestion)) => (Some(suggestion.clone()), State::Initial, true),
+            (None, None) => (None, State::Initial, false),
+        };
+        units.push(Unit {
+            key: key.clone(),
+            source: message.clone(),
+            target,
+            state,
+            machine_translated,
+            base: current.cloned(),
+            placeholders: messageformat::arguments(message)
+                .map(|args| args.into_iter().collect())
+                .unwrap_or_default(),
+        });
+    }
+
+    let document = Document {
+        source_locale: source.to_string(),
+        target_locale: locale.to_string(),
+        units,
+    };
+    let path = out.join(format!("{}.xlf", locale));
+    std::fs::write(&path, document.to_xml())
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+
+    Ok(LocaleExport {
+        locale: locale.to_string(),
+        units: document.units.len(),
+        untranslated: missing.len(),
+        machine_translated: document
+            .units
+            .iter()
+            .filter(|u| u.machine_translated)
+            .count(),
+        file: Some(path),
+        error: None,
+    })
+}
+
+/// Machine translations of `keys`, without saving them
+async fn machine_translations(
+    locale: &str,
+    keys: &[&String],
+    source_messages: &BTreeMap<String, String>,
+) -> Result<HashMap<String, String>> {
+    let client = ApiClient::new()?;
+    let mut translations = HashMap::new();
+    for chunk in keys.chunks(MACHINE_TRANSLATE_BATCH) {
+        let batch: serde_json::Value = chunk
+            .iter()
+            .map(|key| {
+                (
+                    key.to_string(),
+                    serde_json::Value::String(source_messages[*key].clone()),
+                )
+            })
+            .collect::<serde_json::Map<String, serde_json::Value>>()
+            .into();
+        let response = client.translate_batch(locale, &batch, false).await?;
+        if !response.success {
+            anyhow::bail!(
+                "Machine translation failed: {}",
+                response
+                    .error
+                    .unwrap_or_else(|| "Unknown error".to_string())
+            );
+        }
+        translations.extend(response.translations.unwrap_or_default());
+    }
+    Ok(translations)
+}
+
+fn print_export(results: &[LocaleExport], source: &str, out: &Path) {
+    print_banner("📤 XLIFF Export");
+    println!(
+        "Source {}, writing to {}",
+        source.cyan(),
+        out.display().to_string().cyan()
+    );
+    println!();
+
+    for result in results {
+        if let Some(error) = &result.error {
+            println!("  {:<5}: {} {}", result.locale, "Error:".red(), error);
+            continue;
+        }
+        let mut details = format!("{} untranslated", result.untranslated);
+        if result.machine_translated > 0 {
+            details.push_str(&format!(
+                ", {} machine-translated",
+                result.machine_translated
+            ));
+        }
+        println!(
+            "  {:<5}: {} {} units ({})",
+            result.locale,
+            "✓".green(),
+            result.units,
+            details.dimmed()
+        );
+    }
+    println!();
+}
+
+/// Run the import-xliff command
+pub async fn import(
+    files: &[PathBuf],
+    dir: Option<&Path>,
+    apply: bool,
+    force: bool,
+    accept_machine: bool,
+    limit: usize,
+    format: &str,
+) -> Result<()> {
+    let mut results = Vec::new();
+    for file in files {
+        let result = import_file(file, dir, apply, force, accept_machine).await;
+        results.push(result.unwrap_or_else(|e| FileImport {
+            file: file.clone(),
+            locale: String::new(),
+            updated: 0,
+            unchanged: 0,
+            untranslated: 0,
+            unreviewed: 0,
+            conflicts: Vec::new(),
+            issues: Vec::new(),
+            applied: false,
+            error: Some(format!("{:#}", e)),
+        }));
+    }
+
+    let conflicts: usize = results.iter().map(|r| r.conflicts.len()).sum();
+    let invalid: usize = results.iter().map(|r| r.issues.len()).sum();
+    let errors = results.iter().filter(|r| r.error.is_some()).count();
+
+    if format == "json" {
+        let output = JsonImportOutput {
+            dry_run: !apply,
+            files: results,
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else {
+        print_import(&results, apply, limit);
+    }
+
+    if errors > 0 {
+        anyhow::bail!("{} file(s) could not be imported", errors);
+    }
+    if conflicts > 0 {
+        anyhow::bail!(
+            "{} conflicting and {} invalid message(s) were not imported; pass --force to take the vendor's text over bundle edits",
+            conflicts,
+            invalid
+        );
+    }
+    if invalid > 0 {
+        anyhow::bail!("{} invalid message(s) were not imported", invalid);
+    }
+    Ok(())
+}
+
+async fn import_file(
+    file: &Path,
+    dir: Option<&Path>,
+    apply: bool,
+    force: bool,
+    accept_machine: bool,
+) -> Result<FileImport> {
+    let content = std::fs::read_to_string(file)
+        .with_context(|| format!("Failed to read {}", file.display()))?;
+    let document = Document::parse(&content)
+        .map_err(|e| anyhow::anyhow!("Invalid XLIFF in {}: {}", file.display(), e))?;
+    let source = document.source_locale.as_str();
+    let locale = document.target_locale.as_str();
+
+    let (source_messages, messages) = match dir {
+        Some(dir) => (
+            validate::load_bundle(dir, source)?,
+            validate::load_bundle(dir, locale)?,
+        ),
+        None => {
+            let mut fetched = validate::fetch_bundles(&[source, locale]).await?;
+            let messages = fetched.remove(1)?;
+            (fetched.remove(0)?, messages)
+        }
+    };
+
+    let merge = merge(
+        &document,
+        &source_messages,
+        &messages,
+        force,
+        accept_machine,
+    );
+    let applied = apply && !merge.updates.is_empty();
+    if applied {
+        match dir {
+            Some(dir) => write_bundle(&dir.join(format!("{}.json", locale)), &merge.updates)?,
+            None => {
+                let mut translations = serde_json::json!({});
+                for (key, message) in &merge.updates {
+                    set_message(&mut translations, key, message);
+                }
+                let response = update::update_locale(locale, &translations).await?;
+                if !response.success {
+                    anyhow::bail!(
+                        "Upload failed: {}",
+                        response
+                            .error
+                            .unwrap_or_else(|| "Unknown error".to_string())
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(FileImport {
+        file: file.to_path_buf(),
+        locale: locale.to_string(),
+        updated: merge.updates.len(),
+        unchanged: merge.unchanged,
+        untranslated: merge.untranslated,
+        unreviewed: merge.unreviewed,
+        conflicts: merge.conflicts,
+        issues: merge.issues,
+        applied,
+        error: None,
+    })
+}
+
+/// Decide which of a document's translations to take
+fn merge(
+    document: &Document,
+    source_messages: &BTreeMap<String, String>,
+    messages: &BTreeMap<String, String>,
+    force: bool,
+    accept_machine: bool,
+) -> Merge {
+    let mut merge = Merge::default();
+    for unit in &document.units {
+        let Some(target) = &unit.target else {
+            merge.untranslated += 1;
+            continue;
+        };
+        if unit.machine_translated && unit.state == State::Initial && !accept_machine {
+            merge.unreviewed += 1;
+            continue;
+        }
+
+        let conflict = |reason: String| Conflict {
+            key: unit.key.clone(),
+            reason,
+        };
+        let current = messages.get(&unit.key);
+        match source_messages.get(&unit.key) {
+            None => {
+                merge.conflicts.push(conflict(format!(
+                    "no longer in the {} bundle",
+                    document.source_locale
+                )));
+                continue;
+            }
+            Some(source) if *source != unit.source => {
+                merge.conflicts.push(conflict(format!(
+                    "{} text changed since export",
+                    document.source_locale
+                )));
+                continue;
+            }
+            Some(_) => {}
+        }
+
+        if current == Some(target) {
+            merge.unchanged += 1;
+        } else if current != unit.base.as_ref() && !force {
+            merge.conflicts.push(conflict(
+                "translation changed in the bundle since export".to_string(),
+            ));
+        } else {
+            merge.updates.insert(unit.key.clone(), target.clone());
+        }
+    }
+
+    merge.issues = messageformat::validate_locale(source_messages, &merge.updates);
+    for issue in &merge.issues {
+        merge.updates.remove(&issue.key);
+    }
+    merge
+}
+
+/// Merge messages into the bundle file at `path`, keeping its other keys
+fn write_bundle(path: &Path, updates: &BTreeMap<String, String>) -> Result<()> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut bundle: serde_json::Value = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse JSON from {}", path.display()))?;
+    for (key, message) in updates {
+        set_message(&mut bundle, key, message);
+    }
+    let mut json = serde_json::to_string_pretty(&bundle)?;
+    json.push('\n');
+    std::fs::write(path, json).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Set the message at a dotted key, creating objects along the way
+pub fn set_message(bundle: &mut serde_json::Value, key: &str, message: &str) {
+    let mut node = bundle;
+    let mut parts = key.split('.').peekable();
+    while let Some(part) = parts.next() {
+        if !node.is_object() {
+            *node = serde_json::json!({});
+        }
+        let map = node.as_object_mut().expect("just made an object");
+        if parts.peek().is_none() {
+            map.insert(
+                part.to_string(),
+                serde_json::Value::String(message.to_string()),
+            );
+            return;
+        }
+        node = map.entry(part).or_insert_with(|| serde_json::json!({}));
+    }
+}
+
+fn print_import(results: &[FileImport], apply: bool, limit: usize) {
+    print_banner("📥 XLIFF Import");
+    if !apply {
+        println!(
+            "  {} Running in dry-run mode (use --apply to save)",
+            "ℹ".cyan()
+        );
+        println!();
+    }
+
+    for result in results {
+        let name = result.file.display().to_string();
+        if let Some(error) = &result.error {
+            println!("  {}: {} {}", name, "Error:".red(), error);
+            continue;
+        }
+
+        let verb = if result.applied {
+            "imported"
+        } else {
+            "to import"
+        };
+        let mut details = format!(
+            "{} unchanged, {} untranslated",
+            result.unchanged, result.untranslated
+        );
+        if result.unreviewed > 0 {
+            details.push_str(&format!(
+                ", {} unreviewed machine translations",
+                result.unreviewed
+            ));
+        }
+        let mark = if result.conflicts.is_empty() && result.issues.is_empty() {
+            "✓".green().to_string()
+        } else {
+            "✗".red().to_string()
+        };
+        println!(
+            "  {:<5}: {} {} {} ({}) from {}",
+            result.locale,
+            mark,
+            result.updated,
+            verb,
+            details.dimmed(),
+            name.dimmed()
+        );
+
+        if !result.conflicts.is_empty() {
+            println!("    {} conflict(s):", result.conflicts.len());
+            for conflict in result.conflicts.iter().take(limit) {
+                println!("    {} {}", conflict.key.yellow(), conflict.reason);
+            }
+            if result.conflicts.len() > limit {
+                println!("    ... and {} more", result.conflicts.len() - limit);
+            }
+        }
+        if !result.issues.is_empty() {
+            println!("    {} invalid message(s):", result.issues.len());
+            validate::print_issues(&result.issues, limit);
+        }
+    }
+    println!();
+}
+
+fn print_banner(title: &str) {
+    println!();
+    println!(
+        "{}",
+        "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━".blue()
+    );
+    println!("  {}", title.blue().bold());
+    println!(
+        "{}",
+        "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━".blue()
+    );
+    println!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    fn unit(key: &str, source: &str, target: &str, base: Option<&str>) -> Unit {
+        Unit {
+            key: key.to_string(),
+            source: source.to_string(),
+            target: Some(target.to_string()),
+            state: State::Translated,
+            machine_translated: false,
+            base: base.map(str::to_string),
+            placeholders: Vec::new(),
+        }
+    }
+
+    fn document(units: Vec<Unit>) -> Document {
+        Document {
+            source_locale: "en".to_string(),
+            target_locale: "de".to_string(),
+            units,
+        }
+    }
+
+    #[test]
+    fn test_merge_conflicts() {
+        let source = messages(&[
+            ("save", "Save"),
+            ("edited", "Edited"),
+            ("changed", "Changed text"),
+            ("new", "New"),
+            ("same", "Same"),
+        ]);
+        let current = messages(&[
+            ("save", "Sichern"),
+            ("edited", "Im Bundle bearbeitet"),
+            ("changed", "Geändert"),
+            ("same", "Gleich"),
+        ]);
+        let document = document(vec![
+            unit("save", "Save", "Speichern", Some("Sichern")),
+            unit("edited", "Edited", "Bearbeitet", Some("Bearbeitet alt")),
+            unit("changed", "Changed", "Geändert neu", Some("Geändert")),
+            unit("new", "New", "Neu", None),
+            unit("same", "Same", "Gleich", Some("Gleich")),
+            unit("removed", "Removed", "Entfernt", None),
+        ]);
+
+        let merge = merge(&document, &source, &current, false, false);
+        assert_eq!(merge.updates, messages(&[("new", "Neu"), ("save", "Speichern")]));
+        assert_eq!(merge.unchanged, 1);
+        let conflicts: Vec<(&str, &str)> = merge
+            .conflicts
+            .iter()
+            .map(|c| (c.key.as_str(), c.reason.as_str()))
+            .collect();
+        assert_eq!(
+            conflicts,
+            [
+                ("edited", "translation changed in the bundle since export"),
+                ("changed", "en text changed since export"),
+                ("removed", "no longer in the en bundle"),
+            ]
+        );
+
+        // --force takes the vendor's text over bundle edits, not over source changes
+        let forced = super::merge(&document, &source, &current, true, false);
+        assert_eq!(forced.updates["edited"], "Bearbeitet");
+        assert_eq!(forced.conflicts.len(), 2);
+    }
+
+    #[test]
+    fn test_merge_skips_unreviewed_and_invalid() {
+        let source = messages(&[("greeting", "Hi {name}"), ("bye", "Bye")]);
+        let mut machine = unit("bye", "Bye", "Tschüss", None);
+        machine.state = State::Initial;
+        machine.machine_translated = true;
+        let document = document(vec![unit("greeting", "Hi {name}", "Hallo {nom}", None), machine]);
+
+        let merge = super::merge(&document, &source, &BTreeMap::new(), false, false);
+        assert!(merge.updates.is_empty());
+        assert_eq!(merge.unreviewed, 1);
+        assert_eq!(merge.issues.len(), 2);
+
+        let accepted = super::merge(&document, &source, &BTreeMap::new(), false, true);
+        assert_eq!(accepted.updates, messages(&[("bye", "Tschüss")]));
+    }
+
+    #[test]
+    fn test_set_message() {
+        let mut bundle = serde_json::json!({"common": {"save": "Save"}, "title": "x"});
+        set_message(&mut bundle, "common.cancel", "Cancel");
+        set_message(&mut bundle, "title.short", "T");
+        assert_eq!(
+            bundle,
+            serde_json::json!({"common": {"save": "Save", "cancel": "Cancel"}, "title": {"short": "T"}})
+        );
+    }
+}
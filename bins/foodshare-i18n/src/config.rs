@@ -3,14 +3,61 @@
 //! URLs and environment configuration are now managed by `foodshare-api-client`.
 //! This module retains locale metadata for the CLI.
 
-use foodshare_api_client::ClientConfig;
+use foodshare_api_client::{profiles, ApiError, ApiResult, ClientConfig};
+use std::sync::OnceLock;
+
+/// Environment selected on the command line
+#[derive(Debug, Default)]
+struct EnvironmentSelection {
+    name: Option<String>,
+    yes_prod: bool,
+}
+
+/// Selection set by `--env` and `--yes-prod`
+static SELECTION: OnceLock<EnvironmentSelection> = OnceLock::new();
+
+/// Select the environment profile and whether production writes are confirmed
+///
+/// Must be called before the first client is created; later calls are ignored.
+pub fn select_environment(name: Option<String>, yes_prod: bool) {
+    let _ = SELECTION.set(EnvironmentSelection { name, yes_prod });
+}
+
+/// Client configuration for the selected environment
+///
+/// Mutating requests against production are refused unless `--yes-prod`
+/// was passed.
+pub fn client_config() -> ApiResult<ClientConfig> {
+    let selection = SELECTION.get_or_init(EnvironmentSelection::default);
+    profiles::select_config(selection.name.as_deref())
+        .map(|config| confirm_production(config, selection.yes_prod))
+}
+
+/// Client configuration for commands that write with the service role key
+///
+/// The base URL always comes from the selected profile; only a profile
+/// without a service role key falls back to `SUPABASE_SERVICE_ROLE_KEY`.
+pub fn service_client_config() -> ApiResult<ClientConfig> {
+    let config = client_config()?;
+    if config.service_role_key.is_some() {
+        return Ok(config);
+    }
+    std::env::var("SUPABASE_SERVICE_ROLE_KEY")
+        .map(|key| config.with_service_role_key(key))
+        .map_err(|_| ApiError::missing_env("SUPABASE_SERVICE_ROLE_KEY"))
+}
+
+/// Refuse mutating requests against production unless `yes_prod` confirms them
+pub fn confirm_production(config: ClientConfig, yes_prod: bool) -> ClientConfig {
+    config.with_production_guard(!yes_prod)
+}
 
 /// Get the base URL from configuration
 ///
 /// Delegates to `foodshare-api-client` for environment-based URL resolution.
 #[must_use]
 pub fn base_url() -> String {
-    ClientConfig::from_env()
+    client_config()
         .map(|c| c.base_url)
         .unwrap_or_else(|_| "https://api.foodshare.club/functions/v1".to_string())
 }
@@ -20,7 +67,7 @@ pub fn base_url() -> String {
 /// Delegates to `foodshare-api-client` for environment-based URL resolution.
 #[must_use]
 pub fn bff_url() -> String {
-    ClientConfig::from_env()
+    client_config()
         .map(|c| c.bff_url)
         .unwrap_or_else(|_| "https://api.foodshare.club/functions/v1/bff".to_string())
 }
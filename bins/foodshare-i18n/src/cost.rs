@@ -0,0 +1,149 @@
+//! Token and cost accounting for LLM translation runs
+//!
+//! The translation service reports the tokens a request used when it can.
+//! When it doesn't, tokens are estimated from the text sent and received
+//! (about four characters a token, plus the prompt around it) and the report
+//! says how many requests were estimated. Prices are USD per million tokens,
+//! from `FOODSHARE_LLM_INPUT_PRICE` and `FOODSHARE_LLM_OUTPUT_PRICE`.
+
+use foodshare_api_client::endpoints::translations::TokenUsage;
+use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Default USD per million input tokens
+const DEFAULT_INPUT_PRICE: f64 = 0.15;
+
+/// Default USD per million output tokens
+const DEFAULT_OUTPUT_PRICE: f64 = 0.60;
+
+/// Characters per token when estimating
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Tokens of instructions the service wraps each LLM request in
+const PROMPT_OVERHEAD_TOKENS: u64 = 200;
+
+/// Token prices
+#[derive(Debug, Clone, Copy)]
+pub struct Pricing {
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+}
+
+impl Pricing {
+    /// Prices from the environment, falling back to the defaults
+    pub fn from_env() -> Self {
+        let price = |name: &str, default: f64| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        };
+        Self {
+            input_per_million: price("FOODSHARE_LLM_INPUT_PRICE", DEFAULT_INPUT_PRICE),
+            output_per_million: price("FOODSHARE_LLM_OUTPUT_PRICE", DEFAULT_OUTPUT_PRICE),
+        }
+    }
+}
+
+/// Tokens used over a run
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Usage {
+    pub requests: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Requests whose tokens were estimated
+    pub estimated_requests: u64,
+}
+
+impl Usage {
+    /// Count `llm_requests` LLM calls: the tokens the service reported, or
+    /// an estimate from the characters sent and received
+    pub fn record(
+        &mut self,
+        reported: Option<TokenUsage>,
+        llm_requests: u64,
+        input_chars: usize,
+        output_chars: usize,
+    ) {
+        self.requests += llm_requests;
+        match reported {
+            Some(usage) => {
+                self.input_tokens += usage.input_tokens;
+                self.output_tokens += usage.output_tokens;
+            }
+            None => {
+                self.input_tokens += llm_requests * PROMPT_OVERHEAD_TOKENS + tokens(input_chars);
+                self.output_tokens += tokens(output_chars);
+                self.estimated_requests += llm_requests;
+            }
+        }
+    }
+
+    /// Add another run's usage
+    pub fn add(&mut self, other: &Usage) {
+        self.requests += other.requests;
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.estimated_requests += other.estimated_requests;
+    }
+
+    /// Cost in USD
+    pub fn cost(&self, pricing: &Pricing) -> f64 {
+        (self.input_tokens as f64 * pricing.input_per_million
+            + self.output_tokens as f64 * pricing.output_per_million)
+            / 1_000_000.0
+    }
+}
+
+fn tokens(chars: usize) -> u64 {
+    chars.div_ceil(CHARS_PER_TOKEN) as u64
+}
+
+/// Final usage and cost of a run
+#[derive(Debug, Serialize)]
+pub struct CostReport {
+    #[serde(flatten)]
+    pub usage: Usage,
+    pub cost_usd: f64,
+    pub input_price_per_million: f64,
+    pub output_price_per_million: f64,
+    pub elapsed_secs: f64,
+}
+
+impl CostReport {
+    pub fn new(usage: Usage, pricing: &Pricing, elapsed: Duration) -> Self {
+        Self {
+            usage,
+            cost_usd: usage.cost(pricing),
+            input_price_per_million: pricing.input_per_million,
+            output_price_per_million: pricing.output_per_million,
+            elapsed_secs: elapsed.as_secs_f64(),
+        }
+    }
+
+    /// Print the report as a summary block
+    pub fn print(&self) {
+        println!("{}", "Cost:".bold());
+        println!("  LLM requests: {}", self.usage.requests);
+        println!(
+            "  Tokens: {} in, {} out",
+            self.usage.input_tokens, self.usage.output_tokens
+        );
+        println!(
+            "  Cost: {} (at ${}/${} per 1M tokens)",
+            format!("${:.4}", self.cost_usd).cyan(),
+            self.input_price_per_million,
+            self.output_price_per_million
+        );
+        if self.usage.estimated_requests > 0 {
+            println!(
+                "  {} {} of {} requests estimated from text length",
+                "ℹ".cyan(),
+                self.usage.estimated_requests,
+                self.usage.requests
+            );
+        }
+        println!("  Elapsed: {:.1}s", self.elapsed_secs);
+    }
+}
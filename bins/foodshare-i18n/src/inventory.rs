@@ -0,0 +1,579 @@
+//! Translation key inventory
+//!
+//! The keys the apps look up, extracted from their sources:
+//!
+//! - **iOS**: `String(localized: "key")`, `LocalizedStringKey("key")`,
+//!   `LocalizedStringResource("key")` and `NSLocalizedString("key", ...)`
+//! - **Android**: `R.string.name` and `R.plurals.name` in Kotlin and Java,
+//!   `@string/name` in resource XML, with default values from the
+//!   `values/strings.xml` files
+//! - **Web**: `t('key')` calls. Translators bound with a namespace
+//!   (`const t = useTranslations('listing')`, `getTranslations`,
+//!   `const { t } = useTranslation('listing')`) prefix their keys with it.
+//!
+//! Keys built at runtime (`t(`status.${s}`)`, `"status.\(s)"`) count as using
+//! every key under their static prefix. Android resource names can't contain
+//! dots, so `common_save` stands for the bundle key `common.save`.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::Path;
+use std::sync::LazyLock;
+use walkdir::WalkDir;
+
+/// Directories never searched
+const SKIPPED_DIRS: &[&str] = &[
+    ".git",
+    "node_modules",
+    ".next",
+    "dist",
+    "build",
+    "out",
+    ".gradle",
+    ".build",
+    "DerivedData",
+    "Pods",
+];
+
+/// Web source extensions
+const WEB_EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx"];
+
+/// `const t = useTranslations('ns')`, `const t = await getTranslations({ namespace: 'ns' })`
+static TRANSLATOR: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?:const|let)\s+([\w$]+)\s*=\s*(?:await\s+)?(?:useTranslations|getTranslations)\s*\(\s*(?:['"]([\w.\-]+)['"]|\{[^}]*?namespace:\s*['"]([\w.\-]+)['"][^}]*\})?"#,
+    )
+    .unwrap()
+});
+
+/// `const { t } = useTranslation('ns')`, `const { t: tCommon } = useTranslation(['common'])`
+static DESTRUCTURED_TRANSLATOR: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?:const|let)\s*\{\s*t\s*(?::\s*([\w$]+))?\s*[,}][^=]*=\s*(?:await\s+)?useTranslation\s*\(\s*\[?\s*(?:['"]([\w.\-]+)['"])?"#,
+    )
+    .unwrap()
+});
+
+/// A localized string lookup in Swift, with its `defaultValue:` if on the same line
+static SWIFT_LOOKUP: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"\b(?:String\(\s*localized:\s*|LocalizedStringKey\(\s*|LocalizedStringResource\(\s*|NSLocalizedString\(\s*)"((?:[^"\\]|\\.)*)"(?:[^)\n]*?defaultValue:\s*"((?:[^"\\]|\\.)*)")?"#,
+    )
+    .unwrap()
+});
+
+/// `R.string.name` in Kotlin or Java (not the framework's `android.R.string`)
+static ANDROID_REFERENCE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:^|[^\w.])R\.(?:string|plurals)\.(\w+)").unwrap());
+
+/// `@string/name` in resource XML
+static ANDROID_XML_REFERENCE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"@(?:string|plurals)/(\w+)").unwrap());
+
+/// `<string name="name">value</string>` in `values/strings.xml`
+static ANDROID_STRING: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?s)<string\s+name\s*=\s*"(\w+)"[^>]*>(.*?)</string>"#).unwrap()
+});
+
+/// App a key is used by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Platform {
+    Ios,
+    Android,
+    Web,
+}
+
+impl Platform {
+    /// Every platform
+    pub const ALL: &[Platform] = &[Platform::Ios, Platform::Android, Platform::Web];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Ios => "ios",
+            Self::Android => "android",
+            Self::Web => "web",
+        }
+    }
+
+    /// Platform whose sources have this extension
+    fn for_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "swift" => Some(Self::Ios),
+            "kt" | "java" | "xml" => Some(Self::Android),
+            e if WEB_EXTENSIONS.contains(&e) => Some(Self::Web),
+            _ => None,
+        }
+    }
+}
+
+/// Where a key is looked up
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Location {
+    pub file: String,
+    pub line: usize,
+}
+
+/// One key and its lookups
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KeyUsage {
+    pub platforms: BTreeSet<Platform>,
+    /// Source text given next to the key (`defaultValue:`, `strings.xml`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_value: Option<String>,
+    pub locations: Vec<Location>,
+}
+
+/// The keys a set of sources uses
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Inventory {
+    /// Key → lookups
+    pub keys: BTreeMap<String, KeyUsage>,
+    /// Static prefixes of keys built at runtime
+    #[serde(default)]
+    pub dynamic_prefixes: BTreeSet<String>,
+    /// Files with at least one lookup
+    #[serde(default)]
+    pub files: usize,
+}
+
+impl Inventory {
+    /// Read an inventory written by `extract --out`, or extract one from a directory
+    pub fn from_path(path: &Path) -> Result<Self> {
+        if path.is_dir() {
+            return scan(path, Path::new(""), Platform::ALL);
+        }
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse inventory from {}", path.display()))
+    }
+
+    /// Add another inventory's keys
+    pub fn merge(&mut self, other: Inventory) {
+        for (key, usage) in other.keys {
+            let entry = self.keys.entry(key).or_default();
+            entry.platforms.extend(usage.platforms);
+            entry.locations.extend(usage.locations);
+            if entry.default_value.is_none() {
+                entry.default_value = usage.default_value;
+            }
+        }
+        self.dynamic_prefixes.extend(other.dynamic_prefixes);
+        self.files += other.files;
+    }
+
+    /// Number of keys used on each platform
+    pub fn platform_counts(&self) -> BTreeMap<Platform, usize> {
+        let mut counts = BTreeMap::new();
+        for usage in self.keys.values() {
+            for platform in &usage.platforms {
+                *counts.entry(*platform).or_default() += 1;
+            }
+        }
+        counts
+    }
+
+    /// Keys used but not in `bundle`
+    pub fn missing_from(&self, bundle: &BTreeSet<String>) -> Vec<&str> {
+        let resource_names: HashSet<String> = bundle.iter().map(|k| resource_name(k)).collect();
+        let is_defined = |key: &str, usage: &KeyUsage| {
+            bundle.contains(key)
+                || (usage.platforms.contains(&Platform::Android) && resource_names.contains(key))
+        };
+        self.keys
+            .iter()
+            .filter(|(key, usage)| !is_defined(key, usage))
+            .map(|(key, _)| key.as_str())
+            .collect()
+    }
+
+    /// Keys of `bundle` no source uses
+    pub fn unused_in<'a>(&self, bundle: &'a BTreeSet<String>) -> Vec<&'a str> {
+        bundle
+            .iter()
+            .filter(|key| {
+                !self.keys.contains_key(*key)
+                    && !self
+                        .keys
+                        .get(&resource_name(key))
+                        .is_some_and(|u| u.platforms.contains(&Platform::Android))
+                    && !self
+                        .dynamic_prefixes
+                        .iter()
+                        .any(|p| key.starts_with(p.as_str()))
+            })
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Where `key` is looked up, Android references included by resource name
+    pub fn locations_of(&self, key: &str) -> Vec<(Platform, &Location)> {
+        let resource = resource_name(key);
+        let mut names = vec![key];
+        if resource != key {
+            names.push(&resource);
+        }
+
+        let mut found = Vec::new();
+        for name in names {
+            let Some(usage) = self.keys.get(name) else {
+                continue;
+            };
+            for location in &usage.locations {
+                let platform = Path::new(&location.file)
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .and_then(Platform::for_extension);
+                match platform {
+                    Some(Platform::Android) => found.push((Platform::Android, location)),
+                    Some(platform) if name == key => found.push((platform, location)),
+                    _ => {}
+                }
+            }
+        }
+        found
+    }
+
+    fn add(&mut self, key: String, platform: Platform, file: &str, line: usize) {
+        let usage = self.keys.entry(key).or_default();
+        usage.platforms.insert(platform);
+        usage.locations.push(Location {
+            file: file.to_string(),
+            line,
+        });
+    }
+}
+
+/// Android resource name of a bundle key
+pub fn resource_name(key: &str) -> String {
+    key.replace('.', "_")
+}
+
+/// Extract the keys used by the `platforms` sources under `dir`
+///
+/// Files are reported relative to `relative_to`.
+pub fn scan(dir: &Path, relative_to: &Path, platforms: &[Platform]) -> Result<Inventory> {
+    let mut inventory = Inventory::default();
+    let mut android_defaults = BTreeMap::new();
+
+    let entries = WalkDir::new(dir).into_iter().filter_entry(|entry| {
+        !entry.file_type().is_dir()
+            || entry.depth() == 0
+            || !SKIPPED_DIRS.contains(&entry.file_name().to_string_lossy().as_ref())
+    });
+    for entry in entries {
+        let entry = entry?;
+        let path = entry.path();
+        let Some(platform) = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(Platform::for_extension)
+        else {
+            continue;
+        };
+        if !entry.file_type().is_file() || !platforms.contains(&platform) {
+            continue;
+        }
+        let is_xml = path.extension().is_some_and(|e| e == "xml");
+        if is_xml && !is_resource(path) {
+            continue;
+        }
+
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let relative = path.strip_prefix(relative_to).unwrap_or(path);
+        let file = relative
+            .strip_prefix(".")
+            .unwrap_or(relative)
+            .display()
+            .to_string();
+
+        if is_xml && is_default_strings(path) {
+            for captures in ANDROID_STRING.captures_iter(&content) {
+                android_defaults.insert(captures[1].to_string(), unescape_android(&captures[2]));
+            }
+        }
+        let found = match platform {
+            Platform::Ios => extract_swift(&content, &file, &mut inventory),
+            Platform::Android if is_xml => extract_lines(
+                &content,
+                &file,
+                &ANDROID_XML_REFERENCE,
+                Platform::Android,
+                &mut inventory,
+            ),
+            Platform::Android => extract_lines(
+                &content,
+                &file,
+                &ANDROID_REFERENCE,
+                Platform::Android,
+                &mut inventory,
+            ),
+            Platform::Web => extract_web(&content, &file, &mut inventory),
+        };
+        if found {
+            inventory.files += 1;
+        }
+    }
+
+    for (name, usage) in &mut inventory.keys {
+        if usage.platforms.contains(&Platform::Android) && usage.default_value.is_none() {
+            usage.default_value = android_defaults.get(name).cloned();
+        }
+    }
+    Ok(inventory)
+}
+
+/// Whether an XML file is an Android resource (under a `res` directory)
+fn is_resource(path: &Path) -> bool {
+    path.components().any(|c| c.as_os_str() == "res")
+}
+
+/// Whether an XML file holds the default-locale strings
+fn is_default_strings(path: &Path) -> bool {
+    let parent = path.parent().and_then(|p| p.file_name());
+    parent.is_some_and(|p| p == "values") && path.file_name().is_some_and(|n| n == "strings.xml")
+}
+
+fn unescape_android(value: &str) -> String {
+    value
+        .trim()
+        .trim_matches('"')
+        .replace("\\'", "'")
+        .replace("\\\"", "\"")
+        .replace("\\n", "\n")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+/// Whether a line is a comment in C-like sources
+fn is_comment(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with("//") || trimmed.starts_with('*')
+}
+
+/// Add the keys a pattern's first group captures, line by line
+fn extract_lines(
+    content: &str,
+    file: &str,
+    pattern: &Regex,
+    platform: Platform,
+    inventory: &mut Inventory,
+) -> bool {
+    let mut found = false;
+    for (index, line) in content.lines().enumerate() {
+        if is_comment(line) {
+            continue;
+        }
+        for captures in pattern.captures_iter(line) {
+            inventory.add(captures[1].to_string(), platform, file, index + 1);
+            found = true;
+        }
+    }
+    found
+}
+
+/// Add the keys one Swift file uses; whether it uses any
+fn extract_swift(content: &str, file: &str, inventory: &mut Inventory) -> bool {
+    let mut found = false;
+    for (index, line) in content.lines().enumerate() {
+        if is_comment(line) {
+            continue;
+        }
+        for captures in SWIFT_LOOKUP.captures_iter(line) {
+            let key = &captures[1];
+            found = true;
+            if let Some(interpolation) = key.find("\\(") {
+                if interpolation > 0 {
+                    inventory
+                        .dynamic_prefixes
+                        .insert(key[..interpolation].to_string());
+                }
+                continue;
+            }
+            if key.is_empty() {
+                continue;
+            }
+            inventory.add(key.to_string(), Platform::Ios, file, index + 1);
+            if let Some(default) = captures.get(2) {
+                let usage = inventory.keys.get_mut(key).expect("just added");
+                usage
+                    .default_value
+                    .get_or_insert_with(|| default.as_str().replace("\\\"", "\""));
+            }
+        }
+    }
+    found
+}
+
+/// Add the keys one web source file uses; whether it uses any
+fn extract_web(content: &str, file: &str, inventory: &mut Inventory) -> bool {
+    // Translator name -> namespace; a bare `t` without a binding (passed as a prop) has none
+    let mut translators: BTreeMap<String, String> = BTreeMap::new();
+    for captures in TRANSLATOR.captures_iter(content) {
+        let namespace = captures
+            .get(2)
+            .or_else(|| captures.get(3))
+            .map_or("", |m| m.as_str());
+        translators.insert(captures[1].to_string(), namespace.to_string());
+    }
+    for captures in DESTRUCTURED_TRANSLATOR.captures_iter(content) {
+        let name = captures.get(1).map_or("t", |m| m.as_str());
+        let namespace = captures.get(2).map_or("", |m| m.as_str());
+        translators.insert(name.to_string(), namespace.to_string());
+    }
+    translators.entry("t".to_string()).or_default();
+
+    let names: Vec<String> = translators.keys().map(|name| regex::escape(name)).collect();
+    let call = Regex::new(&format!(
+        r#"(?:^|[^\w$.])({})(?:\.(?:rich|markup|raw|has))?\(\s*(['"`])((?:[^'"`\\]|\\.)*)['"`]"#,
+        names.join("|")
+    ))
+    .unwrap();
+
+    let mut found = false;
+    for (index, line) in content.lines().enumerate() {
+        if is_comment(line) {
+            continue;
+        }
+        for captures in call.captures_iter(line) {
+            let namespace = &translators[&captures[1]];
+            let qualify = |key: &str| {
+                if namespace.is_empty() {
+                    key.to_string()
+                } else {
+                    format!("{}.{}", namespace, key)
+                }
+            };
+            let key = &captures[3];
+            found = true;
+            if &captures[2] == "`" && key.contains("${") {
+                // `t(`${key}`)` with no namespace could be any key
+                let prefix = qualify(&key[..key.find("${").unwrap_or(0)]);
+                if !prefix.is_empty() {
+                    inventory.dynamic_prefixes.insert(prefix);
+                }
+            } else if !key.is_empty() {
+                inventory.add(qualify(key), Platform::Web, file, index + 1);
+            }
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(inventory: &Inventory) -> Vec<&str> {
+        inventory.keys.keys().map(String::as_str).collect()
+    }
+
+    fn bundle(keys: &[&str]) -> BTreeSet<String> {
+        keys.iter().map(|k| k.to_string()).collect()
+    }
+
+    #[test]
+    fn test_extract_swift() {
+        let content = r#"
+Text(String(localized: "feed.title", defaultValue: "Feed"))
+// Text(String(localized: "old.title"))
+let a = LocalizedStringKey("common.save"); let b = LocalizedStringResource("common.cancel")
+NSLocalizedString("listing.share", comment: "")
+Text(String(localized: "status.\(status)"))
+Text(String(localized: "quote", defaultValue: "Say \"hi\""))
+"#;
+        let mut inventory = Inventory::default();
+        assert!(extract_swift(content, "Feed.swift", &mut inventory));
+        assert_eq!(
+            keys(&inventory),
+            ["common.cancel", "common.save", "feed.title", "listing.share", "quote"]
+        );
+        assert_eq!(inventory.keys["feed.title"].default_value.as_deref(), Some("Feed"));
+        assert_eq!(inventory.keys["feed.title"].locations, [Location { file: "Feed.swift".to_string(), line: 2 }]);
+        assert_eq!(inventory.keys["quote"].default_value.as_deref(), Some("Say \"hi\""));
+        assert_eq!(inventory.dynamic_prefixes, bundle(&["status."]));
+
+        assert!(!extract_swift("let x = \"feed.title\"", "A.swift", &mut Inventory::default()));
+    }
+
+    #[test]
+    fn test_extract_web() {
+        let content = r#"
+const t = useTranslations('listing');
+const tCommon = await getTranslations({ locale, namespace: 'common' });
+const { t: tNav } = useTranslation(['nav']);
+t('title'); tCommon("save"); tNav.rich(`home`, {});
+t(`status.${status}`);
+format('not.a.key'); x.t('also.not');
+// t('commented')
+"#;
+        let mut inventory = Inventory::default();
+        assert!(extract_web(content, "page.tsx", &mut inventory));
+        assert_eq!(keys(&inventory), ["common.save", "listing.title", "nav.home"]);
+        assert_eq!(inventory.dynamic_prefixes, bundle(&["listing.status."]));
+
+        // A `t` passed in as a prop has no namespace
+        let mut inventory = Inventory::default();
+        extract_web("export function Row({ t }) { return t('common.save') }", "Row.tsx", &mut inventory);
+        assert_eq!(keys(&inventory), ["common.save"]);
+    }
+
+    #[test]
+    fn test_scan_android() {
+        let dir = tempfile::tempdir().unwrap();
+        let res = dir.path().join("app/src/main/res");
+        std::fs::create_dir_all(res.join("values")).unwrap();
+        std::fs::create_dir_all(res.join("layout")).unwrap();
+        std::fs::create_dir_all(dir.path().join("build/res/values")).unwrap();
+        std::fs::write(
+            res.join("values/strings.xml"),
+            r#"<resources><string name="common_save">"Don\'t save"</string></resources>"#,
+        )
+        .unwrap();
+        std::fs::write(res.join("layout/main.xml"), r#"<Button android:text="@string/common_save"/>"#).unwrap();
+        std::fs::write(
+            dir.path().join("Main.kt"),
+            "getString(R.string.feed_title)\nandroid.R.string.ok\nresources.getQuantityString(R.plurals.items, n)\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("build/res/values/Gen.kt"), "R.string.generated").unwrap();
+        std::fs::write(dir.path().join("notes.xml"), "@string/not_a_resource").unwrap();
+
+        let inventory = scan(dir.path(), dir.path(), &[Platform::Android]).unwrap();
+        assert_eq!(keys(&inventory), ["common_save", "feed_title", "items"]);
+        assert_eq!(inventory.files, 2);
+        assert_eq!(inventory.keys["common_save"].default_value.as_deref(), Some("Don't save"));
+        assert_eq!(
+            inventory.keys["common_save"].locations[0].file,
+            "app/src/main/res/layout/main.xml"
+        );
+
+        assert!(scan(dir.path(), dir.path(), &[Platform::Ios]).unwrap().keys.is_empty());
+    }
+
+    #[test]
+    fn test_inventory_diff() {
+        let mut inventory = Inventory::default();
+        extract_web("t('common.save'); t('feed.title'); t(`status.${s}`)", "a.ts", &mut inventory);
+        let mut android = Inventory::default();
+        extract_lines("R.string.common_cancel", "A.kt", &ANDROID_REFERENCE, Platform::Android, &mut android);
+        extract_lines("R.string.legacy_only", "A.kt", &ANDROID_REFERENCE, Platform::Android, &mut android);
+        inventory.merge(android);
+
+        let bundle = bundle(&["common.save", "common.cancel", "status.open", "profile.name"]);
+        assert_eq!(inventory.missing_from(&bundle), ["feed.title", "legacy_only"]);
+        assert_eq!(inventory.unused_in(&bundle), ["profile.name"]);
+
+        let locations = inventory.locations_of("common.cancel");
+        assert_eq!(locations, [(Platform::Android, &Location { file: "A.kt".to_string(), line: 1 })]);
+        assert_eq!(
+            inventory.platform_counts(),
+            BTreeMap::from([(Platform::Android, 2), (Platform::Web, 2)])
+        );
+    }
+}
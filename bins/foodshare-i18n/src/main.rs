@@ -4,14 +4,25 @@
 
 use clap::{Parser, Subcommand};
 use owo_colors::OwoColorize;
+use std::path::PathBuf;
 use std::process::ExitCode;
 
 mod api;
+mod checkpoint;
 mod commands;
 mod config;
+mod cost;
+mod inventory;
+mod messageformat;
+mod quality;
+mod resources;
 mod types;
+mod xliff;
 
-use commands::{audit, backfill, deploy, generate_infoplist, health, test, translate, update};
+use commands::{
+    audit, backfill, deploy, generate_infoplist, health, key, review, test, translate, update,
+    usage, validate,
+};
 
 /// Enterprise Translation Management CLI for Foodshare
 #[derive(Parser)]
@@ -27,6 +38,22 @@ struct Cli {
     #[arg(short, long, global = true, default_value = "text")]
     format: String,
 
+    /// Answer API requests from a recorded cassette file instead of the network
+    #[arg(long, global = true, value_name = "FILE")]
+    offline_fixtures: Option<PathBuf>,
+
+    /// Answer API reads from the local cache of earlier fetches, without the network
+    #[arg(long, global = true)]
+    offline: bool,
+
+    /// Environment profile to use (local, staging, prod, ...); defaults to FOODSHARE_ENV
+    #[arg(long = "env", global = true, value_name = "NAME")]
+    environment: Option<String>,
+
+    /// Allow mutating requests against production
+    #[arg(long, global = true)]
+    yes_prod: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -58,6 +85,145 @@ enum Commands {
         /// Limit number of missing keys to show
         #[arg(short, long, default_value = "10")]
         limit: usize,
+
+        /// Audit against the keys the apps use: an `extract --out` file, or a directory to scan
+        #[arg(long)]
+        inventory: Option<PathBuf>,
+    },
+
+    /// Extract the translation keys the iOS, Android and web sources use
+    Extract {
+        /// Source roots to scan
+        #[arg(default_value = ".")]
+        paths: Vec<PathBuf>,
+
+        /// Write the inventory to a JSON file (for audit --inventory)
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+
+        /// Limit number of keys to show
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Compare translation keys used by the web app with the bundle
+    Usage {
+        /// Web app root (sources are read from its src/ directory)
+        #[arg(long, default_value = ".")]
+        path: PathBuf,
+
+        /// Bundle locale to compare against
+        #[arg(long, default_value = "en")]
+        locale: String,
+
+        /// Read the bundle from a JSON file instead of the API
+        #[arg(long)]
+        bundle: Option<PathBuf>,
+
+        /// Fail when bundle keys are unused (not only when used keys are missing)
+        #[arg(long)]
+        fail_on_unused: bool,
+
+        /// Limit number of unused keys to show
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Check ICU message syntax and placeholders against the source locale
+    Validate {
+        /// Locale to validate (validates all if not specified)
+        locale: Option<String>,
+
+        /// Locale whose placeholders translations must match
+        #[arg(long, default_value = "en")]
+        source: String,
+
+        /// Read bundles from <DIR>/<locale>.json instead of the API
+        #[arg(long)]
+        dir: Option<PathBuf>,
+
+        /// Limit number of issues to show per locale
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Fail when a locale's coverage is below its threshold in the gate file
+    Gate {
+        /// Locales to gate (gates all if not specified)
+        locales: Vec<String>,
+
+        /// Locale coverage is measured against
+        #[arg(long, default_value = "en")]
+        source: String,
+
+        /// Gate file with the thresholds (default: i18n-gate.json)
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+
+        /// Read bundles from <DIR>/<locale>.json instead of the API
+        #[arg(long)]
+        dir: Option<PathBuf>,
+
+        /// Measure against the keys the apps use: an `extract --out` file, or a directory to scan
+        #[arg(long)]
+        inventory: Option<PathBuf>,
+
+        /// Also write the JSON report to a file
+        #[arg(long)]
+        report: Option<PathBuf>,
+    },
+
+    /// Export locale bundles to XLIFF 2.0 for the translation vendor
+    ExportXliff {
+        /// Locales to export (exports all if not specified)
+        locales: Vec<String>,
+
+        /// Locale the translations are made from
+        #[arg(long, default_value = "en")]
+        source: String,
+
+        /// Read bundles from <DIR>/<locale>.json instead of the API
+        #[arg(long)]
+        dir: Option<PathBuf>,
+
+        /// Directory to write <locale>.xlf files to
+        #[arg(short, long, default_value = "xliff")]
+        out: PathBuf,
+
+        /// Only export keys without a translation
+        #[arg(long)]
+        missing: bool,
+
+        /// Prefill untranslated keys with machine translations, marked for review
+        #[arg(long)]
+        machine_translate: bool,
+    },
+
+    /// Merge translated XLIFF files back into locale bundles
+    ImportXliff {
+        /// XLIFF files returned by the vendor
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+
+        /// Merge into <DIR>/<locale>.json instead of the API
+        #[arg(long)]
+        dir: Option<PathBuf>,
+
+        /// Save the merged translations (dry-run if not specified)
+        #[arg(short, long)]
+        apply: bool,
+
+        /// Take the vendor's text for keys edited in the bundle since export
+        #[arg(long)]
+        force: bool,
+
+        /// Import machine translations the vendor left unreviewed
+        #[arg(long)]
+        accept_machine: bool,
+
+        /// Limit number of conflicts and issues to show per file
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
     },
 
     /// Auto-translate missing keys
@@ -72,6 +238,18 @@ enum Commands {
         /// Maximum keys to translate per locale
         #[arg(short, long, default_value = "50")]
         limit: usize,
+
+        /// Locales translated at the same time (with "all")
+        #[arg(short, long, default_value = "4")]
+        concurrency: usize,
+
+        /// Skip locales an interrupted "all" run already finished
+        #[arg(long)]
+        resume: bool,
+
+        /// Checkpoint file (defaults to the user data directory)
+        #[arg(long)]
+        checkpoint: Option<PathBuf>,
     },
 
     /// Benchmark translation endpoints
@@ -111,17 +289,17 @@ enum Commands {
         /// Path to JSON file with translations (if not specified, uses preset translations)
         #[arg(short, long)]
         file: Option<String>,
+
+        /// Upload the file without checking message syntax and placeholders
+        #[arg(long)]
+        no_validate: bool,
     },
 
     /// Backfill translations for existing posts
     Backfill {
-        /// Number of posts to process per batch
-        #[arg(short, long, default_value = "10")]
-        batch_size: usize,
-
-        /// Delay between batches in milliseconds
-        #[arg(short, long, default_value = "2000")]
-        delay: u64,
+        /// Number of posts translated at the same time
+        #[arg(short, long, default_value = "4")]
+        concurrency: usize,
 
         /// Maximum number of posts to process
         #[arg(short, long)]
@@ -130,6 +308,18 @@ enum Commands {
         /// Dry run (don't actually send translation requests)
         #[arg(long)]
         dry_run: bool,
+
+        /// Queue requests that fail while offline and replay them on the next run
+        #[arg(long)]
+        offline_queue: bool,
+
+        /// Skip posts an interrupted run already finished
+        #[arg(long)]
+        resume: bool,
+
+        /// Checkpoint file (defaults to the user data directory)
+        #[arg(long)]
+        checkpoint: Option<PathBuf>,
     },
 
     /// Generate localized InfoPlist.strings files for iOS
@@ -137,7 +327,138 @@ enum Commands {
         /// Dry run (preview translations without writing files)
         #[arg(long)]
         dry_run: bool,
+
+        /// Translate every string again instead of using cached translations
+        #[arg(long)]
+        skip_cache: bool,
+
+        /// JSON file of permission key → English string to translate
+        #[arg(long)]
+        strings_file: Option<String>,
+    },
+
+    /// Report translation keys added, changed and removed between two releases
+    Diff {
+        /// Old side: git revision, snapshot file, bundle directory, or `api`
+        from: String,
+
+        /// New side: git revision, snapshot file, bundle directory, or `api`
+        #[arg(default_value = "api")]
+        to: String,
+
+        /// Bundle directory (<locale>.json files) read at git revisions
+        #[arg(long, default_value = "messages")]
+        path: PathBuf,
+
+        /// Locales to compare (compares all if not specified)
+        #[arg(short, long, value_delimiter = ',')]
+        locales: Vec<String>,
+
+        /// Print Markdown release notes
+        #[arg(long)]
+        markdown: bool,
+
+        /// Limit number of keys to show per locale
+        #[arg(long, default_value = "10")]
+        limit: usize,
+    },
+
+    /// Generate a pseudo-locale from the source strings for UI testing
+    Pseudo {
+        /// Pseudo-locale to generate
+        #[arg(default_value = "en-XA")]
+        locale: String,
+
+        /// Locale the pseudo-locale is made from
+        #[arg(long, default_value = "en")]
+        source: String,
+
+        /// Read the source bundle from <DIR>/<source>.json instead of the API
+        #[arg(long)]
+        dir: Option<PathBuf>,
+
+        /// Write the pseudo-locale bundle to a file
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+
+        /// Percent to lengthen each message by
+        #[arg(long, default_value = "40")]
+        expand: usize,
+
+        /// Upload the pseudo-locale to the API
+        #[arg(short, long)]
+        apply: bool,
+
+        /// Limit number of messages to preview
+        #[arg(short, long, default_value = "10")]
+        limit: usize,
+    },
+
+    /// Save the live bundles to a snapshot file for later diffs
+    Snapshot {
+        /// File to write
+        #[arg(short, long)]
+        out: PathBuf,
+
+        /// Locales to save (saves all if not specified)
+        #[arg(short, long, value_delimiter = ',')]
+        locales: Vec<String>,
+    },
+
+    /// Generate Android strings.xml files and an iOS string catalog
+    GenerateResources {
+        /// Android res directory to write values*/strings.xml under
+        #[arg(long)]
+        android: Option<PathBuf>,
+
+        /// iOS string catalog (.xcstrings) to write
+        #[arg(long)]
+        xcstrings: Option<PathBuf>,
+
+        /// Locales to include (includes all if not specified)
+        #[arg(short, long, value_delimiter = ',')]
+        locales: Vec<String>,
+
+        /// Locale whose keys are generated, written as Android's default values
+        #[arg(long, default_value = "en")]
+        source: String,
+
+        /// Read bundles from <DIR>/<locale>.json instead of the API
+        #[arg(long)]
+        dir: Option<PathBuf>,
+
+        /// Show a diff of the changes without writing files
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Maximum messages left out to list
+        #[arg(long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Score machine translations and review the low-confidence ones
+    Review {
+        #[command(subcommand)]
+        action: ReviewAction,
+
+        /// Review queue file (defaults to the user data directory)
+        #[arg(long, global = true)]
+        queue: Option<PathBuf>,
+    },
+
+    /// Rename and deprecate keys without breaking released apps
+    Key {
+        #[command(subcommand)]
+        action: KeyAction,
+
+        /// Alias file apps read redirects from (default: i18n-aliases.json)
+        #[arg(long, global = true)]
+        aliases: Option<PathBuf>,
     },
+
+    /// Update this binary to the latest signed release
+    #[command(name = "self-update")]
+    SelfUpdate(foodshare_self_update::SelfUpdateArgs),
 }
 
 #[derive(Subcommand)]
@@ -192,6 +513,152 @@ enum TestTarget {
     },
 }
 
+#[derive(Subcommand)]
+enum KeyAction {
+    /// Copy a key's messages to a new key, alias the old one and rewrite its lookups
+    Rename {
+        /// Current key
+        old: String,
+
+        /// New key
+        new: String,
+
+        /// Source trees whose lookups of the old key are rewritten
+        #[arg(short, long)]
+        sources: Vec<PathBuf>,
+
+        /// Days the old key is kept for released apps
+        #[arg(short, long, default_value = "90")]
+        window: u64,
+
+        /// Overwrite the new key where it already has a different message
+        #[arg(long)]
+        force: bool,
+
+        /// Upload the messages and write the alias file and sources
+        #[arg(short, long)]
+        apply: bool,
+
+        /// Limit number of source lookups to show
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Mark a key for removal, rewriting its lookups to a replacement if given
+    Deprecate {
+        /// Key to deprecate
+        key: String,
+
+        /// Key to use instead
+        #[arg(short, long)]
+        replacement: Option<String>,
+
+        /// Source trees whose lookups are listed (and rewritten with --replacement)
+        #[arg(short, long)]
+        sources: Vec<PathBuf>,
+
+        /// Days the key is kept for released apps
+        #[arg(short, long, default_value = "90")]
+        window: u64,
+
+        /// Write the alias file and sources
+        #[arg(short, long)]
+        apply: bool,
+
+        /// Limit number of source lookups to show
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Show renamed and deprecated keys and which are past their window
+    List,
+}
+
+#[derive(Subcommand)]
+enum ReviewAction {
+    /// Score a locale's translations and queue the low-confidence ones
+    Scan {
+        /// Locale to score
+        locale: String,
+
+        /// Locale the translations are made from
+        #[arg(long, default_value = "en")]
+        source: String,
+
+        /// Read bundles from <DIR>/<locale>.json instead of the API
+        #[arg(long)]
+        dir: Option<PathBuf>,
+
+        /// Confidence below which a translation is queued
+        #[arg(short, long, default_value = "0.7")]
+        threshold: f64,
+
+        /// Glossary of required term translations (default: i18n-glossary.json if present)
+        #[arg(short, long)]
+        glossary: Option<PathBuf>,
+
+        /// Translate back into the source locale and compare (one API call per 50 keys)
+        #[arg(short, long)]
+        back_translate: bool,
+
+        /// Limit number of queued translations to show
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// List translations awaiting review, lowest confidence first
+    List {
+        /// Only this locale
+        locale: Option<String>,
+
+        /// Limit number of translations to show
+        #[arg(short, long, default_value = "50")]
+        limit: usize,
+    },
+
+    /// Upload queued translations as reviewed
+    Approve {
+        /// Locale of the translations
+        locale: String,
+
+        /// Keys to approve
+        keys: Vec<String>,
+
+        /// Approve every queued translation of the locale
+        #[arg(long)]
+        all: bool,
+
+        /// Upload this corrected text instead of the queued one (one key)
+        #[arg(long)]
+        text: Option<String>,
+    },
+
+    /// Request fresh translations for queued keys, uploading those that score well
+    Reject {
+        /// Locale of the translations
+        locale: String,
+
+        /// Keys to reject
+        keys: Vec<String>,
+
+        /// Reject every queued translation of the locale
+        #[arg(long)]
+        all: bool,
+
+        /// Confidence a fresh translation needs to be uploaded
+        #[arg(short, long, default_value = "0.7")]
+        threshold: f64,
+
+        /// Glossary of required term translations (default: i18n-glossary.json if present)
+        #[arg(short, long)]
+        glossary: Option<PathBuf>,
+
+        /// Translate back into the source locale and compare
+        #[arg(short, long)]
+        back_translate: bool,
+    },
+}
+
 #[tokio::main]
 async fn main() -> ExitCode {
     let cli = Cli::parse();
@@ -202,6 +669,15 @@ async fn main() -> ExitCode {
             .init();
     }
 
+    config::select_environment(cli.environment, cli.yes_prod);
+
+    if let Some(path) = cli.offline_fixtures {
+        api::use_offline_fixtures(path);
+    }
+    if cli.offline {
+        api::use_offline_cache();
+    }
+
     let result = match cli.command {
         Commands::Health { detailed } => health::run(detailed, &cli.format).await,
 
@@ -217,13 +693,65 @@ async fn main() -> ExitCode {
             }
         },
 
-        Commands::Audit { locale, missing, limit } => {
-            audit::run(locale.as_deref(), missing, limit, &cli.format).await
+        Commands::Audit { locale, missing, limit, inventory } => {
+            audit::run(locale.as_deref(), missing, limit, inventory.as_deref(), &cli.format).await
+        }
+
+        Commands::Extract { paths, out, limit } => {
+            commands::extract::run(&paths, out.as_deref(), limit, &cli.format)
+        }
+
+        Commands::Usage { path, locale, bundle, fail_on_unused, limit } => {
+            usage::run(&path, &locale, bundle.as_deref(), fail_on_unused, limit, &cli.format).await
+        }
+
+        Commands::Validate { locale, source, dir, limit } => {
+            validate::run(locale.as_deref(), &source, dir.as_deref(), limit, &cli.format).await
         }
 
-        Commands::Translate { locale, apply, limit } => {
+        Commands::Gate { locales, source, config, dir, inventory, report } => {
+            commands::gate::run(
+                &locales,
+                &source,
+                config.as_deref(),
+                dir.as_deref(),
+                inventory.as_deref(),
+                report.as_deref(),
+                &cli.format,
+            )
+            .await
+        }
+
+        Commands::ExportXliff { locales, source, dir, out, missing, machine_translate } => {
+            commands::xliff::export(
+                &locales,
+                &source,
+                dir.as_deref(),
+                &out,
+                missing,
+                machine_translate,
+                &cli.format,
+            )
+            .await
+        }
+
+        Commands::ImportXliff { files, dir, apply, force, accept_machine, limit } => {
+            commands::xliff::import(
+                &files,
+                dir.as_deref(),
+                apply,
+                force,
+                accept_machine,
+                limit,
+                &cli.format,
+            )
+            .await
+        }
+
+        Commands::Translate { locale, apply, limit, concurrency, resume, checkpoint } => {
             if locale == "all" {
-                translate::sync_all(apply, &cli.format).await
+                translate::sync_all(apply, limit, concurrency, resume, checkpoint.as_deref(), &cli.format)
+                    .await
             } else {
                 translate::run(&locale, apply, limit, &cli.format).await
             }
@@ -239,21 +767,114 @@ async fn main() -> ExitCode {
             deploy::run(!no_migrations, !no_functions, !no_test, &cli.format).await
         }
 
-        Commands::Update { locale, file } => {
+        Commands::Update { locale, file, no_validate } => {
             if let Some(file_path) = file {
-                update::run_from_file(&locale, &file_path, &cli.format).await
+                update::run_from_file(&locale, &file_path, !no_validate, &cli.format).await
             } else {
                 update::run_preset(&locale, &cli.format).await
             }
         }
 
-        Commands::Backfill { batch_size, delay, limit, dry_run } => {
-            backfill::run(batch_size, delay, limit, dry_run, &cli.format).await
+        Commands::Backfill { concurrency, limit, dry_run, offline_queue, resume, checkpoint } => {
+            backfill::run(
+                concurrency,
+                limit,
+                dry_run,
+                offline_queue,
+                resume,
+                checkpoint.as_deref(),
+                &cli.format,
+            )
+            .await
+        }
+
+        Commands::GenerateInfoplist { dry_run, skip_cache, strings_file } => {
+            generate_infoplist::run(dry_run, skip_cache, strings_file.as_deref(), &cli.format)
+                .await
         }
 
-        Commands::GenerateInfoplist { dry_run } => {
-            generate_infoplist::run(dry_run, &cli.format).await
+        Commands::Diff { from, to, path, locales, markdown, limit } => {
+            commands::diff::run(&from, &to, &path, &locales, markdown, limit, &cli.format).await
         }
+
+        Commands::Pseudo { locale, source, dir, out, expand, apply, limit } => {
+            commands::pseudo::run(
+                &locale,
+                &source,
+                dir.as_deref(),
+                out.as_deref(),
+                expand,
+                apply,
+                limit,
+                &cli.format,
+            )
+            .await
+        }
+
+        Commands::Snapshot { out, locales } => {
+            commands::diff::snapshot(&out, &locales, &cli.format).await
+        }
+
+        Commands::GenerateResources { android, xcstrings, locales, source, dir, dry_run, limit } => {
+            commands::generate_resources::run(
+                android.as_deref(),
+                xcstrings.as_deref(),
+                &locales,
+                &source,
+                dir.as_deref(),
+                dry_run,
+                limit,
+                &cli.format,
+            )
+            .await
+        }
+
+        Commands::Review { action, queue } => match action {
+            ReviewAction::Scan { locale, source, dir, threshold, glossary, back_translate, limit } => {
+                async {
+                    let scoring = review::Scoring::new(threshold, glossary.as_deref(), back_translate)?;
+                    review::scan(&locale, &source, dir.as_deref(), &scoring, queue.as_deref(), limit, &cli.format)
+                        .await
+                }
+                .await
+            }
+            ReviewAction::List { locale, limit } => {
+                review::list(locale.as_deref(), queue.as_deref(), limit, &cli.format)
+            }
+            ReviewAction::Approve { locale, keys, all, text } => {
+                review::approve(&locale, &keys, all, text.as_deref(), queue.as_deref(), &cli.format).await
+            }
+            ReviewAction::Reject { locale, keys, all, threshold, glossary, back_translate } => {
+                async {
+                    let scoring = review::Scoring::new(threshold, glossary.as_deref(), back_translate)?;
+                    review::reject(&locale, &keys, all, &scoring, queue.as_deref(), &cli.format).await
+                }
+                .await
+            }
+        },
+
+        Commands::Key { action, aliases } => match action {
+            KeyAction::Rename { old, new, sources, window, force, apply, limit } => {
+                key::rename(&old, &new, &sources, aliases.as_deref(), window, force, apply, limit, &cli.format)
+                    .await
+            }
+            KeyAction::Deprecate { key: name, replacement, sources, window, apply, limit } => {
+                key::deprecate(
+                    &name,
+                    replacement.as_deref(),
+                    &sources,
+                    aliases.as_deref(),
+                    window,
+                    apply,
+                    limit,
+                    &cli.format,
+                )
+                .await
+            }
+            KeyAction::List => key::list(aliases.as_deref(), &cli.format),
+        },
+
+        Commands::SelfUpdate(args) => return self_update(args).await,
     };
 
     match result {
@@ -264,3 +885,20 @@ async fn main() -> ExitCode {
         }
     }
 }
+
+/// Run `self-update` off the async runtime (it downloads with a blocking client)
+async fn self_update(args: foodshare_self_update::SelfUpdateArgs) -> ExitCode {
+    let update = match foodshare_core::config::Config::load(None) {
+        Ok(config) => config.schema.update,
+        Err(e) => {
+            eprintln!("{} {}", "Error:".red().bold(), e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let code = tokio::task::spawn_blocking(move || {
+        foodshare_self_update::run(env!("CARGO_BIN_NAME"), env!("CARGO_PKG_VERSION"), &args, &update)
+    })
+    .await
+    .unwrap_or(1);
+    ExitCode::from(u8::try_from(code).unwrap_or(1))
+}
@@ -0,0 +1,641 @@
+//! ICU MessageFormat checks
+//!
+//! Parses translation strings far enough to catch what breaks the app's
+//! formatters at runtime: unbalanced braces, plural/select arguments without
+//! an `other` case or with unknown plural categories, and apostrophes that
+//! quote away a placeholder (`l'{name}`). Parsing also lists the arguments a
+//! message takes, so translations can be checked against the source locale.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// CLDR plural categories
+pub const PLURAL_CATEGORIES: &[&str] = &["zero", "one", "two", "few", "many", "other"];
+
+/// Argument types that format a value (`{n, number}`, `{d, date, short}`)
+const FORMAT_TYPES: &[&str] = &["number", "date", "time", "spellout", "ordinal", "duration"];
+
+/// A syntax error in one message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    /// Character offset of the error
+    pub offset: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (at character {})", self.message, self.offset + 1)
+    }
+}
+
+/// What is wrong with a translation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    /// The message doesn't parse
+    Syntax,
+    /// A source placeholder is missing from the translation
+    MissingPlaceholder,
+    /// The translation has a placeholder the source doesn't
+    UnknownPlaceholder,
+}
+
+/// A problem with one key of a locale
+#[derive(Debug, Clone, Serialize)]
+pub struct Issue {
+    pub key: String,
+    pub kind: IssueKind,
+    pub message: String,
+}
+
+/// Selector → message of a plural or select argument, in source order
+pub type Cases = Vec<(String, Vec<Part>)>;
+
+/// A piece of a parsed message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Part {
+    /// Literal text, with ICU quoting resolved
+    Text(String),
+    /// `{name}`, or `{name, number}` and the like with `kind` the format
+    /// type and `style` what follows it (`short`, `::currency/EUR`)
+    Argument {
+        name: String,
+        kind: Option<String>,
+        style: Option<String>,
+    },
+    /// `#` in a plural case: the plural argument's value
+    Pound,
+    /// `{name, plural, ...}` or, if `ordinal`, `{name, selectordinal, ...}`
+    Plural {
+        name: String,
+        ordinal: bool,
+        offset: u32,
+        cases: Cases,
+    },
+    /// `{name, select, ...}`
+    Select { name: String, cases: Cases },
+}
+
+/// Parse `message`, or return its first syntax error
+pub fn parse(message: &str) -> Result<Vec<Part>, SyntaxError> {
+    let mut parser = Parser {
+        chars: message.chars().collect(),
+        pos: 0,
+    };
+    parser.message(false, 0)
+}
+
+/// ICU source for `parts`, quoting text where needed; parsing it gives `parts` back
+pub fn format(parts: &[Part]) -> String {
+    fn write(parts: &[Part], in_plural: bool, out: &mut String) {
+        for part in parts {
+            match part {
+                Part::Text(text) => {
+                    let special = |c: char| c == '{' || c == '}' || (in_plural && c == '#');
+                    let mut chars = text.chars().peekable();
+                    while let Some(c) = chars.next() {
+                        match c {
+                            '\'' => out.push_str("''"),
+                            // Quote a run of syntax characters as one literal
+                            c if special(c) => {
+                                out.push('\'');
+                                out.push(c);
+                                while let Some(c) = chars.next_if(|&c| special(c)) {
+                                    out.push(c);
+                                }
+                                out.push('\'');
+                            }
+                            c => out.push(c),
+                        }
+                    }
+                }
+                Part::Argument { name, kind, style } => {
+                    out.push('{');
+                    out.push_str(name);
+                    if let Some(kind) = kind {
+                        out.push_str(", ");
+                        out.push_str(kind);
+                    }
+                    if let Some(style) = style {
+                        out.push_str(", ");
+                        out.push_str(style);
+                    }
+                    out.push('}');
+                }
+                Part::Pound => out.push('#'),
+                Part::Plural {
+                    name,
+                    ordinal,
+                    offset,
+                    cases,
+                } => {
+                    let kind = if *ordinal { "selectordinal" } else { "plural" };
+                    out.push_str(&format!("{{{}, {},", name, kind));
+                    if *offset > 0 {
+                        out.push_str(&format!(" offset:{}", offset));
+                    }
+                    write_cases(cases, true, out);
+                }
+                Part::Select { name, cases } => {
+                    out.push_str(&format!("{{{}, select,", name));
+                    write_cases(cases, false, out);
+                }
+            }
+        }
+    }
+
+    fn write_cases(cases: &Cases, plural: bool, out: &mut String) {
+        for (selector, case) in cases {
+            out.push_str(&format!(" {} {{", selector));
+            write(case, plural, out);
+            out.push('}');
+        }
+        out.push('}');
+    }
+
+    let mut out = String::new();
+    write(parts, false, &mut out);
+    out
+}
+
+/// Names of the arguments `message` takes, or its first syntax error
+pub fn arguments(message: &str) -> Result<BTreeSet<String>, SyntaxError> {
+    fn walk(parts: &[Part], names: &mut BTreeSet<String>) {
+        for part in parts {
+            match part {
+                Part::Text(_) | Part::Pound => {}
+                Part::Argument { name, .. } => {
+                    names.insert(name.clone());
+                }
+                Part::Plural { name, cases, .. } | Part::Select { name, cases } => {
+                    names.insert(name.clone());
+                    for (_, case) in cases {
+                        walk(case, names);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut names = BTreeSet::new();
+    walk(&parse(message)?, &mut names);
+    Ok(names)
+}
+
+/// Check every message of a locale, and its placeholders against the source's
+///
+/// Keys missing from either side are left to `audit`; keys whose source
+/// message doesn't parse are only reported for the source locale.
+pub fn validate_locale(
+    source: &BTreeMap<String, String>,
+    target: &BTreeMap<String, String>,
+) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    for (key, message) in target {
+        let target_args = match arguments(message) {
+            Ok(args) => args,
+            Err(e) => {
+                issues.push(Issue {
+                    key: key.clone(),
+                    kind: IssueKind::Syntax,
+                    message: e.to_string(),
+                });
+                continue;
+            }
+        };
+        let Some(Ok(source_args)) = source.get(key).map(|m| arguments(m)) else {
+            continue;
+        };
+
+        for name in source_args.difference(&target_args) {
+            issues.push(Issue {
+                key: key.clone(),
+                kind: IssueKind::MissingPlaceholder,
+                message: format!("missing placeholder {{{}}}", name),
+            });
+        }
+        for name in target_args.difference(&source_args) {
+            issues.push(Issue {
+                key: key.clone(),
+                kind: IssueKind::UnknownPlaceholder,
+                message: format!("placeholder {{{}}} is not in the source message", name),
+            });
+        }
+    }
+    issues
+}
+
+/// Dotted key → message for the string leaves of a messages object
+pub fn flatten_messages(messages: &serde_json::Value) -> BTreeMap<String, String> {
+    fn walk(value: &serde_json::Value, prefix: &str, out: &mut BTreeMap<String, String>) {
+        let serde_json::Value::Object(map) = value else {
+            return;
+        };
+        for (name, child) in map {
+            let key = if prefix.is_empty() {
+                name.clone()
+            } else {
+                format!("{}.{}", prefix, name)
+            };
+            match child {
+                serde_json::Value::String(message) => {
+                    out.insert(key, message.clone());
+                }
+                _ => walk(child, &key, out),
+            }
+        }
+    }
+
+    let mut out = BTreeMap::new();
+    walk(messages, "", &mut out);
+    out
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, SyntaxError> {
+        Err(SyntaxError {
+            offset: self.pos,
+            message: message.into(),
+        })
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    /// Characters up to whitespace or one of `stops`
+    fn word(&mut self, stops: &[char]) -> String {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| !c.is_whitespace() && !stops.contains(&c))
+        {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    fn expect(&mut self, expected: char, what: &str) -> Result<(), SyntaxError> {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            self.error(format!("expected '{}' {}", expected, what))
+        }
+    }
+
+    /// Message text up to the `}` closing it (nested) or the end (top level)
+    fn message(&mut self, in_plural: bool, depth: usize) -> Result<Vec<Part>, SyntaxError> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        loop {
+            match self.peek() {
+                None if depth > 0 => return self.error("unclosed '{'"),
+                None => break,
+                Some('}') if depth > 0 => break,
+                Some('}') => return self.error("unmatched '}'"),
+                Some('{') => {
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(self.argument(depth)?);
+                }
+                Some('#') if in_plural => {
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(Part::Pound);
+                    self.pos += 1;
+                }
+                Some('\'') => self.apostrophe(in_plural, &mut text)?,
+                Some(c) => {
+                    text.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        Ok(parts)
+    }
+
+    /// `''` is a literal apostrophe; before a special character one starts a quoted literal
+    fn apostrophe(&mut self, in_plural: bool, text: &mut String) -> Result<(), SyntaxError> {
+        let start = self.pos;
+        self.pos += 1;
+        match self.peek() {
+            Some('\'') => {
+                text.push('\'');
+                self.pos += 1;
+            }
+            Some('{' | '}' | '|') => self.quoted(start, text)?,
+            Some('#') if in_plural => self.quoted(start, text)?,
+            _ => text.push('\''),
+        }
+        Ok(())
+    }
+
+    fn quoted(&mut self, start: usize, text: &mut String) -> Result<(), SyntaxError> {
+        loop {
+            match self.peek() {
+                None => {
+                    self.pos = start;
+                    return self.error(
+                        "apostrophe quotes the rest of the message; write '' for a literal apostrophe",
+                    );
+                }
+                Some('\'') if self.chars.get(self.pos + 1) == Some(&'\'') => {
+                    text.push('\'');
+                    self.pos += 2;
+                }
+                Some('\'') => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                Some(c) => {
+                    text.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    /// `{name}`, `{name, type[, style]}`, `{name, plural|selectordinal|select, options}`
+    fn argument(&mut self, depth: usize) -> Result<Part, SyntaxError> {
+        self.pos += 1;
+        self.skip_whitespace();
+        let name = self.word(&['{', '}', ',']);
+        if name.is_empty() {
+            return self.error("empty placeholder");
+        }
+        if !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return self.error(format!("invalid placeholder name '{}'", name));
+        }
+
+        self.skip_whitespace();
+        match self.peek() {
+            Some('}') => {
+                self.pos += 1;
+                return Ok(Part::Argument {
+                    name,
+                    kind: None,
+                    style: None,
+                });
+            }
+            Some(',') => self.pos += 1,
+            _ => return self.error(format!("expected ',' or '}}' after '{}'", name)),
+        }
+
+        self.skip_whitespace();
+        let kind = self.word(&['{', '}', ',']);
+        match kind.as_str() {
+            "plural" | "selectordinal" => {
+                self.expect(',', &format!("after '{}'", kind))?;
+                let (offset, cases) = self.options(&name, true, depth)?;
+                Ok(Part::Plural {
+                    name,
+                    ordinal: kind == "selectordinal",
+                    offset,
+                    cases,
+                })
+            }
+            "select" => {
+                self.expect(',', "after 'select'")?;
+                let (_, cases) = self.options(&name, false, depth)?;
+                Ok(Part::Select { name, cases })
+            }
+            kind if FORMAT_TYPES.contains(&kind) => {
+                self.skip_whitespace();
+                let mut style = None;
+                if self.peek() == Some(',') {
+                    // Styles and skeletons (`::currency/EUR`) have no braces
+                    self.pos += 1;
+                    let start = self.pos;
+                    while self.peek().is_some_and(|c| c != '}' && c != '{') {
+                        self.pos += 1;
+                    }
+                    let text: String = self.chars[start..self.pos].iter().collect();
+                    style = Some(text.trim().to_string());
+                }
+                self.expect('}', &format!("to close '{}'", name))?;
+                Ok(Part::Argument {
+                    name,
+                    kind: Some(kind.to_string()),
+                    style,
+                })
+            }
+            "" => self.error(format!("missing type after '{},'", name)),
+            kind => self.error(format!("unknown placeholder type '{}'", kind)),
+        }
+    }
+
+    /// Plural or select options up to and including the closing `}`
+    fn options(
+        &mut self,
+        name: &str,
+        plural: bool,
+        depth: usize,
+    ) -> Result<(u32, Cases), SyntaxError> {
+        self.skip_whitespace();
+        let mut offset = 0;
+        if plural && self.chars[self.pos..].starts_with(&['o', 'f', 'f', 's', 'e', 't', ':']) {
+            self.pos += "offset:".len();
+            self.skip_whitespace();
+            match self.word(&['{', '}']).parse::<u32>() {
+                Ok(n) => offset = n,
+                Err(_) => return self.error("offset must be a number"),
+            }
+        }
+
+        let mut cases = Cases::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None => return self.error(format!("unclosed '{{{}, ...}}'", name)),
+                Some('}') => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(_) => {}
+            }
+
+            let selector = self.word(&['{', '}']);
+            let valid = if plural {
+                PLURAL_CATEGORIES.contains(&selector.as_str())
+                    || selector
+                        .strip_prefix('=')
+                        .is_some_and(|n| n.parse::<u32>().is_ok())
+            } else {
+                !selector.is_empty()
+                    && selector
+                        .chars()
+                        .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+            };
+            if !valid {
+                let what = if plural {
+                    "plural category"
+                } else {
+                    "select case"
+                };
+                return self.error(format!("invalid {} '{}' in '{}'", what, selector, name));
+            }
+            if cases.iter().any(|(s, _)| *s == selector) {
+                return self.error(format!("duplicate case '{}' in '{}'", selector, name));
+            }
+
+            self.expect('{', &format!("after case '{}'", selector))?;
+            let case = self.message(plural, depth + 1)?;
+            self.pos += 1;
+            cases.push((selector, case));
+        }
+
+        if !cases.iter().any(|(s, _)| s == "other") {
+            return self.error(format!("'{}' has no 'other' case", name));
+        }
+        Ok((offset, cases))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> Part {
+        Part::Text(s.to_string())
+    }
+
+    fn error(message: &str) -> SyntaxError {
+        parse(message).unwrap_err()
+    }
+
+    #[test]
+    fn test_parse_plural() {
+        let parts = parse("{count, plural, offset:1 =0 {None} one {# item} other {# items}}").unwrap();
+        assert_eq!(
+            parts,
+            [Part::Plural {
+                name: "count".to_string(),
+                ordinal: false,
+                offset: 1,
+                cases: vec![
+                    ("=0".to_string(), vec![text("None")]),
+                    ("one".to_string(), vec![Part::Pound, text(" item")]),
+                    ("other".to_string(), vec![Part::Pound, text(" items")]),
+                ],
+            }]
+        );
+        assert_eq!(parse(&format(&parts)).unwrap(), parts);
+    }
+
+    #[test]
+    fn test_parse_select() {
+        let message = "{gender, select, female {She} male {He} other {They}} shared {count, number}";
+        assert_eq!(
+            arguments(message).unwrap(),
+            BTreeSet::from(["count".to_string(), "gender".to_string()])
+        );
+        let parts = parse(message).unwrap();
+        assert!(matches!(&parts[0], Part::Select { cases, .. } if cases.len() == 3));
+        assert_eq!(parse(&format(&parts)).unwrap(), parts);
+
+        // `#` is only special inside plurals
+        assert_eq!(parse("{g, select, other {#1}}").unwrap()[0], Part::Select {
+            name: "g".to_string(),
+            cases: vec![("other".to_string(), vec![text("#1")])],
+        });
+    }
+
+    #[test]
+    fn test_apostrophes() {
+        assert_eq!(
+            parse("It''s {name}'s").unwrap(),
+            [
+                text("It's "),
+                Part::Argument {
+                    name: "name".to_string(),
+                    kind: None,
+                    style: None
+                },
+                text("'s"),
+            ]
+        );
+        assert_eq!(parse("'{literal}'").unwrap(), [text("{literal}")]);
+
+        let parts = vec![text("100% {sure} it's #1")];
+        assert_eq!(parse(&format(&parts)).unwrap(), parts);
+    }
+
+    #[test]
+    fn test_error_positions() {
+        let e = error("Hello {name");
+        assert_eq!((e.offset, e.message.as_str()), (11, "expected ',' or '}' after 'name'"));
+        assert_eq!(error("Oops }").offset, 5);
+        assert_eq!(error("Hi {}").message, "empty placeholder");
+
+        // The apostrophe that swallows the placeholder
+        let e = error("l'{name}");
+        assert_eq!(e.offset, 1);
+        assert_eq!(e.to_string().split(" (at").nth(1), Some(" character 2)"));
+
+        let e = error("{n, plural, one {x}}");
+        assert_eq!((e.offset, e.message.as_str()), (20, "'n' has no 'other' case"));
+        let e = error("{n, plural, lots {x} other {y}}");
+        assert_eq!((e.offset, e.message.as_str()), (16, "invalid plural category 'lots' in 'n'"));
+        let e = error("{n, plural, one {x} one {y} other {z}}");
+        assert_eq!((e.offset, e.message.as_str()), (23, "duplicate case 'one' in 'n'"));
+        assert_eq!(error("{n, plural, other {x}").message, "unclosed '{n, ...}'");
+        assert_eq!(error("{n, currency}").message, "unknown placeholder type 'currency'");
+    }
+
+    #[test]
+    fn test_validate_locale() {
+        let source = BTreeMap::from([
+            ("greeting".to_string(), "Hi {name}".to_string()),
+            ("items".to_string(), "{count, plural, one {# item} other {# items}}".to_string()),
+        ]);
+        let target = BTreeMap::from([
+            ("greeting".to_string(), "Hallo {nom}".to_string()),
+            ("items".to_string(), "{count, plural, one {# Artikel}".to_string()),
+        ]);
+
+        let issues = validate_locale(&source, &target);
+        let issues: Vec<(&str, IssueKind)> = issues
+            .iter()
+            .map(|issue| (issue.key.as_str(), issue.kind))
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                ("greeting", IssueKind::MissingPlaceholder),
+                ("greeting", IssueKind::UnknownPlaceholder),
+                ("items", IssueKind::Syntax),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flatten_messages() {
+        let messages = serde_json::json!({"common": {"save": "Save", "count": 3}, "title": "Foodshare"});
+        assert_eq!(
+            flatten_messages(&messages),
+            BTreeMap::from([
+                ("common.save".to_string(), "Save".to_string()),
+                ("title".to_string(), "Foodshare".to_string()),
+            ])
+        );
+    }
+}
@@ -0,0 +1,331 @@
+//! Translation quality scoring
+//!
+//! Scores a machine translation against its source message with checks that
+//! need no reviewer: the length ratio against a per-script band, placeholder
+//! integrity, glossary compliance and, when one was requested, how close a
+//! translation back into the source language comes to the original. Each
+//! check scores 0.0–1.0 and the confidence is their weighted mean; checks
+//! that don't apply (a short source, no glossary terms, no back-translation)
+//! are left out rather than counted as passed.
+
+use crate::messageformat;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// Sources shorter than this vary too much in length to judge
+const MIN_LENGTH_SOURCE: usize = 12;
+
+/// Weights of the checks in the confidence
+const WEIGHT_PLACEHOLDERS: f64 = 3.0;
+const WEIGHT_GLOSSARY: f64 = 2.0;
+const WEIGHT_BACK_TRANSLATION: f64 = 2.0;
+const WEIGHT_LENGTH: f64 = 1.0;
+
+/// Glossary file of terms with fixed translations
+///
+/// ```json
+/// { "terms": [
+///     { "term": "Foodshare", "keep": true },
+///     { "term": "listing", "translations": { "de": "Angebot", "fr": "annonce" } }
+/// ] }
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Glossary {
+    #[serde(default)]
+    pub terms: Vec<Term>,
+}
+
+/// A glossary term
+#[derive(Debug, Clone, Deserialize)]
+pub struct Term {
+    pub term: String,
+    /// Left untranslated in every locale (brand and product names)
+    #[serde(default)]
+    pub keep: bool,
+    /// Locale → required translation
+    #[serde(default)]
+    pub translations: BTreeMap<String, String>,
+}
+
+impl Glossary {
+    /// Read a glossary file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse glossary {}", path.display()))
+    }
+
+    /// Term → the text it must appear as in `locale`, for the terms `source` uses
+    fn expected(&self, source: &str, locale: &str) -> Vec<(&str, &str)> {
+        self.terms
+            .iter()
+            .filter(|t| contains_term(source, &t.term, true))
+            .filter_map(|t| {
+                if t.keep {
+                    Some((t.term.as_str(), t.term.as_str()))
+                } else {
+                    t.translations
+                        .get(locale)
+                        .map(|expected| (t.term.as_str(), expected.as_str()))
+                }
+            })
+            .collect()
+    }
+}
+
+/// Check results for one translation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Score {
+    /// Weighted mean of the checks that applied
+    pub confidence: f64,
+    pub length: Option<f64>,
+    pub placeholders: f64,
+    pub glossary: Option<f64>,
+    pub back_translation: Option<f64>,
+    /// What lowered the score, for the reviewer
+    #[serde(default)]
+    pub problems: Vec<String>,
+}
+
+/// Score `translation` of `source` into `locale`
+///
+/// `back` is the translation translated back into the source language.
+pub fn score(
+    source: &str,
+    translation: &str,
+    locale: &str,
+    glossary: &Glossary,
+    back: Option<&str>,
+) -> Score {
+    let mut problems = Vec::new();
+
+    let length = length_score(source, translation, locale);
+    if let Some(s) = length.filter(|s| *s < 1.0) {
+        let ratio = translation.chars().count() as f64 / source.chars().count() as f64;
+        problems.push(format!(
+            "length is {:.1}× the source (score {:.2})",
+            ratio, s
+        ));
+    }
+
+    let placeholders = match (
+        messageformat::arguments(source),
+        messageformat::arguments(translation),
+    ) {
+        (_, Err(e)) => {
+            problems.push(format!("doesn't parse: {}", e));
+            0.0
+        }
+        (Ok(expected), Ok(found)) if expected != found => {
+            for name in expected.difference(&found) {
+                problems.push(format!("missing placeholder {{{}}}", name));
+            }
+            for name in found.difference(&expected) {
+                problems.push(format!("placeholder {{{}}} is not in the source", name));
+            }
+            0.0
+        }
+        _ => 1.0,
+    };
+
+    let terms = glossary.expected(source, locale);
+    let glossary = if terms.is_empty() {
+        None
+    } else {
+        let mut kept = 0;
+        for (term, expected) in &terms {
+            if contains_term(translation, expected, false) {
+                kept += 1;
+            } else {
+                problems.push(format!("glossary: '{}' should be '{}'", term, expected));
+            }
+        }
+        Some(kept as f64 / terms.len() as f64)
+    };
+
+    let back_translation = back.map(|back| similarity(source, back));
+    if let (Some(s), Some(back)) = (back_translation, back)
+        && s < 0.5
+    {
+        problems.push(format!("back-translation reads \"{}\"", back));
+    }
+
+    let checks = [
+        (Some(placeholders), WEIGHT_PLACEHOLDERS),
+        (glossary, WEIGHT_GLOSSARY),
+        (back_translation, WEIGHT_BACK_TRANSLATION),
+        (length, WEIGHT_LENGTH),
+    ];
+    let (total, weights) = checks
+        .iter()
+        .filter_map(|(s, w)| s.map(|s| (s * w, w)))
+        .fold((0.0, 0.0), |(total, weights), (s, w)| (total + s, weights + w));
+
+    Score {
+        confidence: total / weights,
+        length,
+        placeholders,
+        glossary,
+        back_translation,
+        problems,
+    }
+}
+
+/// 1.0 inside the locale's expected length band, falling off outside it
+fn length_score(source: &str, translation: &str, locale: &str) -> Option<f64> {
+    let source_len = source.chars().count();
+    if source_len < MIN_LENGTH_SOURCE {
+        return None;
+    }
+    // Han, kana and hangul carry a word or more per character
+    let (low, high) = match locale.split('-').next().unwrap_or(locale) {
+        "zh" | "ja" | "ko" => (0.2, 1.2),
+        _ => (0.5, 2.5),
+    };
+    let ratio = translation.chars().count() as f64 / source_len as f64;
+    Some(if ratio < low {
+        ratio / low
+    } else if ratio > high {
+        high / ratio
+    } else {
+        1.0
+    })
+}
+
+/// Dice coefficient of the two texts' word sets
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (words(a), words(b));
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    let shared = a.intersection(&b).count();
+    2.0 * shared as f64 / (a.len() + b.len()) as f64
+}
+
+fn words(text: &str) -> BTreeSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Whether `text` contains `term` at a word start, ignoring case; `whole`
+/// also rules out a longer word, otherwise inflected forms (`Angebote` for
+/// `Angebot`) count
+fn contains_term(text: &str, term: &str, whole: bool) -> bool {
+    let text = text.to_lowercase();
+    let term = term.to_lowercase();
+    let (Some(first), Some(last)) = (term.chars().next(), term.chars().next_back()) else {
+        return false;
+    };
+    text.match_indices(&term).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + term.len()..].chars().next();
+        let starts = !word_char(first) || !before.is_some_and(word_char);
+        let ends = !whole || !word_char(last) || !after.is_some_and(word_char);
+        starts && ends
+    })
+}
+
+/// Letters and digits of scripts that separate words with spaces; CJK
+/// terms match anywhere
+fn word_char(c: char) -> bool {
+    c.is_alphanumeric() && c < '\u{2E80}'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glossary() -> Glossary {
+        serde_json::from_value(serde_json::json!({
+            "terms": [
+                {"term": "Foodshare", "keep": true},
+                {"term": "listing", "translations": {"de": "Angebot"}},
+            ],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_score_clean_translation() {
+        let result = score(
+            "Share your food with neighbours",
+            "Teile dein Essen mit Nachbarn",
+            "de",
+            &Glossary::default(),
+            None,
+        );
+        assert_eq!(result.confidence, 1.0);
+        assert_eq!(result.length, Some(1.0));
+        assert_eq!((result.glossary, result.back_translation), (None, None));
+        assert!(result.problems.is_empty());
+    }
+
+    #[test]
+    fn test_score_weights() {
+        // Placeholders (weight 3) fail, length (weight 1) passes
+        let result = score("Welcome back, {name}!", "Willkommen zurück!", "de", &Glossary::default(), None);
+        assert_eq!(result.placeholders, 0.0);
+        assert_eq!(result.confidence, 0.25);
+        assert_eq!(result.problems, ["missing placeholder {name}"]);
+
+        // One of two glossary terms (weight 2) kept
+        let result = score(
+            "Share this listing on Foodshare",
+            "Teile diese Anzeige auf Foodshare",
+            "de",
+            &glossary(),
+            None,
+        );
+        assert_eq!(result.glossary, Some(0.5));
+        assert!((result.confidence - 5.0 / 6.0).abs() < 1e-9);
+        assert_eq!(result.problems, ["glossary: 'listing' should be 'Angebot'"]);
+
+        let result = score("Hi", "Hallo {name", "de", &Glossary::default(), None);
+        assert_eq!(result.confidence, 0.0);
+        assert!(result.problems[0].starts_with("doesn't parse"));
+    }
+
+    #[test]
+    fn test_back_translation() {
+        let good = score("Save", "Speichern", "de", &Glossary::default(), Some("Save"));
+        assert_eq!((good.back_translation, good.confidence), (Some(1.0), 1.0));
+
+        let bad = score("Save", "Sparen", "de", &Glossary::default(), Some("Economize"));
+        assert_eq!(bad.back_translation, Some(0.0));
+        assert_eq!(bad.confidence, 0.6);
+        assert_eq!(bad.problems, ["back-translation reads \"Economize\""]);
+    }
+
+    #[test]
+    fn test_length_score() {
+        assert_eq!(length_score("Save", "Speichern und schließen", "de"), None);
+        let source = "Pick up before 6pm";
+        assert_eq!(length_score(source, &"x".repeat(90), "de"), Some(0.5));
+        assert_eq!(length_score(source, "x", "fr").map(|s| s < 0.2), Some(true));
+        // CJK translations run much shorter
+        assert_eq!(length_score(source, "6時受取", "ja"), Some(1.0));
+        assert_eq!(length_score(source, "6時受取", "pt-BR").map(|s| s < 1.0), Some(true));
+    }
+
+    #[test]
+    fn test_terms() {
+        assert!(contains_term("Open the Listing", "listing", true));
+        assert!(!contains_term("Open the listings", "listing", true));
+        assert!(contains_term("Neue Angebote", "Angebot", false));
+        assert!(!contains_term("Sonderangebot", "Angebot", false));
+        assert!(contains_term("フードシェアの出品", "出品", true));
+
+        let glossary = glossary();
+        assert_eq!(
+            glossary.expected("Your Foodshare listing", "de"),
+            [("Foodshare", "Foodshare"), ("listing", "Angebot")]
+        );
+        assert_eq!(glossary.expected("Your Foodshare listing", "fr"), [("Foodshare", "Foodshare")]);
+        assert_eq!(similarity("Save the listing", "save listing"), 0.8);
+    }
+}
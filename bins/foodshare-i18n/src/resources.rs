@@ -0,0 +1,456 @@
+//! Android and iOS string resources
+//!
+//! Converts ICU messages from the central bundles into Android `strings.xml`
+//! resources and iOS string catalog (`.xcstrings`) entries. Arguments become
+//! positional format specifiers numbered by first appearance (`{name}` is
+//! `%1$s` on Android and `%1$@` on iOS; numbers and `#` are integers), and a
+//! message that is a single plural argument, with or without text around it,
+//! becomes a plural resource. Exact cases (`=0`) stand in for the matching
+//! category when the message has none, since neither platform selects on
+//! values. Select arguments, ordinals, offsets and several plurals in one
+//! message have no platform equivalent; converting those fails.
+
+use crate::messageformat::{self, PLURAL_CATEGORIES, Part};
+use serde::Serialize;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::io;
+
+/// Platform a message is converted for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Android,
+    Apple,
+}
+
+/// A converted message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resource {
+    String(String),
+    /// Plural category → string, in CLDR order
+    Plural(Vec<(String, String)>),
+}
+
+/// Convert an ICU message to `target`'s format and escaping
+pub fn convert(message: &str, target: Target) -> Result<Resource, String> {
+    let parts = messageformat::parse(message).map_err(|e| e.to_string())?;
+
+    let mut arguments = Vec::new();
+    collect_arguments(&parts, &mut arguments);
+    let plurals: Vec<usize> = (0..parts.len())
+        .filter(|&i| matches!(parts[i], Part::Plural { .. } | Part::Select { .. }))
+        .collect();
+    let &[index] = plurals.as_slice() else {
+        return match plurals.len() {
+            0 => render(&parts, target, &arguments, None)
+                .map(|text| Resource::String(finish(text, target))),
+            _ => Err("more than one plural or select argument".to_string()),
+        };
+    };
+
+    let (name, cases) = match &parts[index] {
+        Part::Plural { ordinal: true, .. } => {
+            return Err("ordinal plurals are not supported".to_string());
+        }
+        Part::Plural { offset, .. } if *offset > 0 => {
+            return Err("plural offsets are not supported".to_string());
+        }
+        Part::Plural { name, cases, .. } => (name, cases),
+        _ => return Err("select arguments are not supported".to_string()),
+    };
+
+    let mut variants = BTreeMap::new();
+    for (selector, case) in cases {
+        let category = match selector.as_str() {
+            "=0" => "zero",
+            "=1" => "one",
+            "=2" => "two",
+            selector if selector.starts_with('=') => continue,
+            selector => selector,
+        };
+        if selector.starts_with('=') && cases.iter().any(|(s, _)| s == category) {
+            continue;
+        }
+        let mut full = parts[..index].to_vec();
+        full.extend(case.iter().cloned());
+        full.extend(parts[index + 1..].iter().cloned());
+        let text = render(&full, target, &arguments, Some(name))?;
+        variants.insert(category, finish(text, target));
+    }
+
+    Ok(Resource::Plural(
+        PLURAL_CATEGORIES
+            .iter()
+            .filter_map(|&category| {
+                variants
+                    .remove(category)
+                    .map(|text| (category.to_string(), text))
+            })
+            .collect(),
+    ))
+}
+
+/// Argument names in order of first appearance
+fn collect_arguments(parts: &[Part], names: &mut Vec<String>) {
+    for part in parts {
+        let name = match part {
+            Part::Text(_) | Part::Pound => continue,
+            Part::Argument { name, .. } => name,
+            Part::Plural { name, .. } | Part::Select { name, .. } => name,
+        };
+        if !names.contains(name) {
+            names.push(name.clone());
+        }
+        if let Part::Plural { cases, .. } | Part::Select { cases, .. } = part {
+            for (_, case) in cases {
+                collect_arguments(case, names);
+            }
+        }
+    }
+}
+
+fn render(
+    parts: &[Part],
+    target: Target,
+    arguments: &[String],
+    plural: Option<&str>,
+) -> Result<String, String> {
+    let formatted = !arguments.is_empty();
+    let specifier = |name: &str, number: bool| {
+        let position = arguments.iter().position(|a| a == name).unwrap_or(0) + 1;
+        match (target, number) {
+            (Target::Android, true) => format!("%{}$d", position),
+            (Target::Android, false) => format!("%{}$s", position),
+            (Target::Apple, true) => format!("%{}$lld", position),
+            (Target::Apple, false) => format!("%{}$@", position),
+        }
+    };
+
+    let mut out = String::new();
+    for part in parts {
+        match part {
+            Part::Text(text) => match target {
+                Target::Android => out.push_str(&escape_android(text, formatted)),
+                Target::Apple if formatted => out.push_str(&text.replace('%', "%%")),
+                Target::Apple => out.push_str(text),
+            },
+            Part::Argument { name, kind, .. } => {
+                out.push_str(&specifier(name, kind.as_deref() == Some("number")));
+            }
+            Part::Pound => out.push_str(&specifier(plural.unwrap_or_default(), true)),
+            Part::Plural { .. } | Part::Select { .. } => {
+                return Err("nested plural or select arguments are not supported".to_string());
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Escape text for a `strings.xml` value: XML, aapt's quoting and, in
+/// messages with arguments, `%`
+fn escape_android(text: &str, formatted: bool) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut previous = None;
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '\\' => out.push_str("\\\\"),
+            '\'' => out.push_str("\\'"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '%' if formatted => out.push_str("%%"),
+            // aapt collapses runs of whitespace
+            ' ' if previous == Some(' ') => out.push_str("\\u0020"),
+            c => out.push(c),
+        }
+        previous = Some(c);
+    }
+    out
+}
+
+/// Whole-value escaping: aapt trims leading and trailing spaces and reads a
+/// leading `@` or `?` as a reference
+fn finish(mut text: String, target: Target) -> String {
+    if target == Target::Apple {
+        return text;
+    }
+    if text.starts_with(['@', '?']) {
+        text.insert(0, '\\');
+    }
+    if let Some(rest) = text.strip_prefix(' ') {
+        text = format!("\\u0020{}", rest);
+    }
+    if text.ends_with(' ') {
+        text.pop();
+        text.push_str("\\u0020");
+    }
+    text
+}
+
+/// Android resource name for a bundle key (`common.save` → `common_save`)
+pub fn android_name(key: &str) -> Option<String> {
+    let name: String = key
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        .then_some(name)
+}
+
+/// `values` directory for a locale (`pt-BR` → `values-pt-rBR`)
+pub fn android_values_dir(locale: &str, source: &str) -> String {
+    if locale == source {
+        return "values".to_string();
+    }
+    match locale.split_once('-') {
+        Some((language, region)) => format!("values-{}-r{}", android_language(language), region),
+        None => format!("values-{}", android_language(locale)),
+    }
+}
+
+/// Android resolves Indonesian from the legacy `in` code only
+fn android_language(language: &str) -> &str {
+    match language {
+        "id" => "in",
+        language => language,
+    }
+}
+
+/// String catalog language for a locale
+pub fn apple_language(locale: &str) -> &str {
+    match locale {
+        "zh" => "zh-Hans",
+        locale => locale,
+    }
+}
+
+/// A `strings.xml` file for one locale's converted resources
+pub fn android_strings_xml(locale: &str, resources: &BTreeMap<String, Resource>) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    xml.push_str(&format!(
+        "<!-- Generated by foodshare-i18n from the {} translations. Do not edit. -->\n",
+        locale
+    ));
+    xml.push_str("<resources>\n");
+    for (name, resource) in resources {
+        match resource {
+            Resource::String(value) => {
+                xml.push_str(&format!(
+                    "    <string name=\"{}\">{}</string>\n",
+                    name, value
+                ));
+            }
+            Resource::Plural(variants) => {
+                xml.push_str(&format!("    <plurals name=\"{}\">\n", name));
+                for (category, value) in variants {
+                    xml.push_str(&format!(
+                        "        <item quantity=\"{}\">{}</item>\n",
+                        category, value
+                    ));
+                }
+                xml.push_str("    </plurals>\n");
+            }
+        }
+    }
+    xml.push_str("</resources>\n");
+    xml
+}
+
+/// A string catalog from key → language → converted resource
+pub fn xcstrings(
+    source_language: &str,
+    strings: &BTreeMap<String, BTreeMap<String, Resource>>,
+) -> io::Result<String> {
+    fn unit(value: &str) -> serde_json::Value {
+        json!({ "stringUnit": { "state": "translated", "value": value } })
+    }
+
+    let strings: serde_json::Map<String, serde_json::Value> = strings
+        .iter()
+        .map(|(key, localizations)| {
+            let localizations: serde_json::Map<String, serde_json::Value> = localizations
+                .iter()
+                .map(|(language, resource)| {
+                    let localization = match resource {
+                        Resource::String(value) => unit(value),
+                        Resource::Plural(variants) => {
+                            let plural: serde_json::Map<String, serde_json::Value> = variants
+                                .iter()
+                                .map(|(category, value)| (category.clone(), unit(value)))
+                                .collect();
+                            json!({ "variations": { "plural": plural } })
+                        }
+                    };
+                    (language.clone(), localization)
+                })
+                .collect();
+            (
+                key.clone(),
+                json!({ "extractionState": "manual", "localizations": localizations }),
+            )
+        })
+        .collect();
+    let catalog = json!({
+        "sourceLanguage": source_language,
+        "strings": strings,
+        "version": "1.0",
+    });
+
+    let mut out = Vec::new();
+    let mut serializer =
+        serde_json::Serializer::with_formatter(&mut out, XcodeFormatter::default());
+    catalog.serialize(&mut serializer)?;
+    out.push(b'\n');
+    String::from_utf8(out).map_err(io::Error::other)
+}
+
+/// Pretty-printing the way Xcode saves catalogs (`"key" : value`), so
+/// regenerating a catalog Xcode has touched doesn't rewrite every line
+#[derive(Default)]
+struct XcodeFormatter(serde_json::ser::PrettyFormatter<'static>);
+
+impl serde_json::ser::Formatter for XcodeFormatter {
+    fn begin_array<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.begin_array(writer)
+    }
+
+    fn end_array<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.end_array(writer)
+    }
+
+    fn begin_array_value<W: ?Sized + io::Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        self.0.begin_array_value(writer, first)
+    }
+
+    fn end_array_value<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.end_array_value(writer)
+    }
+
+    fn begin_object<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.begin_object(writer)
+    }
+
+    fn end_object<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.end_object(writer)
+    }
+
+    fn begin_object_key<W: ?Sized + io::Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        self.0.begin_object_key(writer, first)
+    }
+
+    fn begin_object_value<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(b" : ")
+    }
+
+    fn end_object_value<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.end_object_value(writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn android(message: &str) -> Resource {
+        convert(message, Target::Android).unwrap()
+    }
+
+    fn string(value: &str) -> Resource {
+        Resource::String(value.to_string())
+    }
+
+    #[test]
+    fn test_android_escaping() {
+        assert_eq!(
+            android(r#"Don't say "hi" & <b>\ "#),
+            string(r#"Don\'t say \"hi\" &amp; &lt;b&gt;\\\u0020"#)
+        );
+        assert_eq!(android("@string/app_name"), string(r"\@string/app_name"));
+        assert_eq!(android("?attr"), string(r"\?attr"));
+        // aapt trims and collapses spaces
+        assert_eq!(android(" a  b"), string(r"\u0020a \u0020b"));
+        assert_eq!(android("Line\nTab\t"), string(r"Line\nTab\t"));
+        // `%` only needs escaping where aapt formats the string
+        assert_eq!(android("100%"), string("100%"));
+        assert_eq!(android("{n, number}% of {total}"), string("%1$d%% of %2$s"));
+    }
+
+    #[test]
+    fn test_apple() {
+        assert_eq!(convert("100%", Target::Apple).unwrap(), string("100%"));
+        assert_eq!(
+            convert("Hi {name}, {n, number}%", Target::Apple).unwrap(),
+            string("Hi %1$@, %2$lld%%")
+        );
+    }
+
+    #[test]
+    fn test_plural() {
+        let message = "{name} has {count, plural, =0 {no items} one {# item} other {# items}}";
+        let variants = |values: &[(&str, &str)]| {
+            Resource::Plural(
+                values
+                    .iter()
+                    .map(|(c, v)| (c.to_string(), v.to_string()))
+                    .collect(),
+            )
+        };
+        assert_eq!(
+            android(message),
+            variants(&[
+                ("zero", "%1$s has no items"),
+                ("one", "%1$s has %2$d item"),
+                ("other", "%1$s has %2$d items"),
+            ])
+        );
+        // An explicit category wins over the exact case
+        assert_eq!(
+            android("{n, plural, =1 {single} one {# one} other {#}}"),
+            variants(&[("one", "%1$d one"), ("other", "%1$d")])
+        );
+    }
+
+    #[test]
+    fn test_unsupported() {
+        for message in [
+            "{g, select, other {x}}",
+            "{n, selectordinal, one {#st} other {#th}}",
+            "{n, plural, offset:1 other {#}}",
+            "{a, plural, other {#}} {b, plural, other {#}}",
+            "{broken",
+        ] {
+            assert!(convert(message, Target::Android).is_err(), "{}", message);
+        }
+    }
+
+    #[test]
+    fn test_android_names() {
+        assert_eq!(android_name("common.save-button").as_deref(), Some("common_save_button"));
+        assert_eq!(android_name("404.title"), None);
+        assert_eq!(android_values_dir("en", "en"), "values");
+        assert_eq!(android_values_dir("pt-BR", "en"), "values-pt-rBR");
+        assert_eq!(android_values_dir("id", "en"), "values-in");
+    }
+
+    #[test]
+    fn test_xcstrings_format() {
+        let strings = BTreeMap::from([(
+            "save".to_string(),
+            BTreeMap::from([("en".to_string(), string("Save"))]),
+        )]);
+        let catalog = xcstrings("en", &strings).unwrap();
+        assert!(catalog.contains("\"sourceLanguage\" : \"en\""));
+        assert!(catalog.ends_with("}\n"));
+    }
+}
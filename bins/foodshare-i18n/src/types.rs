@@ -2,6 +2,7 @@
 
 #![allow(dead_code)]
 
+use foodshare_api_client::endpoints::translations::TokenUsage;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -150,6 +151,7 @@ pub struct TranslateBatchResponse {
     pub new_version: Option<String>,
     pub message: Option<String>,
     pub error: Option<String>,
+    pub usage: Option<TokenUsage>,
 }
 
 /// JSON output types
@@ -209,6 +211,9 @@ pub struct JsonAuditOutput {
     pub locales: Vec<LocaleAudit>,
     pub total_locales: usize,
     pub average_coverage: f64,
+    /// English keys no app uses, when audited against an inventory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unused_keys: Option<Vec<String>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -231,7 +236,8 @@ pub struct GenerateInfoPlistStringsResponse {
     #[serde(rename = "lprojFolders")]
     pub lproj_folders: HashMap<String, String>,
     pub stats: Option<InfoPlistStats>,
-    pub errors: Option<Vec<String>>,
+    #[serde(default)]
+    pub errors: Vec<String>,
 }
 
 /// InfoPlist.strings generation statistics
@@ -241,6 +247,8 @@ pub struct InfoPlistStats {
     pub total_locales: usize,
     #[serde(rename = "totalStrings")]
     pub total_strings: usize,
+    #[serde(rename = "fromCache", default)]
+    pub from_cache: usize,
     #[serde(rename = "translatedCount")]
     pub translated_count: usize,
     #[serde(rename = "failedCount")]
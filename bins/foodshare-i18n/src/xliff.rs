@@ -0,0 +1,486 @@
+//! XLIFF 2.0 documents for the translation vendor
+//!
+//! One file per target locale, one `<unit>` per message. The message key is
+//! the unit's `name`, placeholders go in a note for the translator, and the
+//! translation at export time is kept as `base` metadata so an import can
+//! tell vendor edits from changes made to the bundle in the meantime.
+//! Machine translations are exported as `initial` segments with the
+//! `foodshare:mt` sub-state until someone reviews them.
+//!
+//! Reading handles what vendor tools send back for such a file: units with
+//! plain-text sources and targets. Inline markup in a target is refused.
+
+use std::fmt::Write as _;
+
+/// XLIFF 2.0 core namespace
+const XLIFF_NAMESPACE: &str = "urn:oasis:names:tc:xliff:document:2.0";
+
+/// XLIFF 2.0 metadata module namespace
+const MDA_NAMESPACE: &str = "urn:oasis:names:tc:xliff:metadata:2.0";
+
+/// Sub-state marking a machine translation
+const MACHINE_SUB_STATE: &str = "foodshare:mt";
+
+/// Segment states, in workflow order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum State {
+    Initial,
+    Translated,
+    Reviewed,
+    Final,
+}
+
+impl State {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Initial => "initial",
+            Self::Translated => "translated",
+            Self::Reviewed => "reviewed",
+            Self::Final => "final",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "initial" => Some(Self::Initial),
+            "translated" => Some(Self::Translated),
+            "reviewed" => Some(Self::Reviewed),
+            "final" => Some(Self::Final),
+            _ => None,
+        }
+    }
+}
+
+/// One message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unit {
+    /// Dotted message key
+    pub key: String,
+    pub source: String,
+    pub target: Option<String>,
+    pub state: State,
+    /// The target is an unreviewed machine translation
+    pub machine_translated: bool,
+    /// Target when exported, `None` if the key was untranslated
+    pub base: Option<String>,
+    /// Placeholder names, for the translator
+    pub placeholders: Vec<String>,
+}
+
+/// A bundle pair as XLIFF
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Document {
+    pub source_locale: String,
+    pub target_locale: String,
+    pub units: Vec<Unit>,
+}
+
+impl Document {
+    /// Serialize as an XLIFF 2.0 file
+    pub fn to_xml(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            xml,
+            "<xliff xmlns=\"{}\" xmlns:mda=\"{}\" version=\"2.0\" srcLang=\"{}\" trgLang=\"{}\">",
+            XLIFF_NAMESPACE,
+            MDA_NAMESPACE,
+            escape(&self.source_locale),
+            escape(&self.target_locale)
+        );
+        let _ = writeln!(
+            xml,
+            "  <file id=\"{0}\" original=\"{0}.json\">",
+            escape(&self.target_locale)
+        );
+
+        for (index, unit) in self.units.iter().enumerate() {
+            let _ = writeln!(
+                xml,
+                "    <unit id=\"u{}\" name=\"{}\">",
+                index + 1,
+                escape(&unit.key)
+            );
+            if let Some(base) = &unit.base {
+                xml.push_str("      <mda:metadata>\n");
+                xml.push_str("        <mda:metaGroup category=\"foodshare\">\n");
+                let _ = writeln!(
+                    xml,
+                    "          <mda:meta type=\"base\">{}</mda:meta>",
+                    escape(base)
+                );
+                xml.push_str("        </mda:metaGroup>\n");
+                xml.push_str("      </mda:metadata>\n");
+            }
+            if !unit.placeholders.is_empty() {
+                let names: Vec<String> = unit
+                    .placeholders
+                    .iter()
+                    .map(|name| format!("{{{}}}", name))
+                    .collect();
+                xml.push_str("      <notes>\n");
+                let _ = writeln!(
+                    xml,
+                    "        <note category=\"placeholders\">Keep unchanged: {}</note>",
+                    escape(&names.join(", "))
+                );
+                xml.push_str("      </notes>\n");
+            }
+
+            let sub_state = if unit.machine_translated {
+                format!(" subState=\"{}\"", MACHINE_SUB_STATE)
+            } else {
+                String::new()
+            };
+            let _ = writeln!(
+                xml,
+                "      <segment state=\"{}\"{}>",
+                unit.state.as_str(),
+                sub_state
+            );
+            let _ = writeln!(xml, "        <source>{}</source>", escape(&unit.source));
+            if let Some(target) = &unit.target {
+                let _ = writeln!(xml, "        <target>{}</target>", escape(target));
+            }
+            xml.push_str("      </segment>\n");
+            xml.push_str("    </unit>\n");
+        }
+
+        xml.push_str("  </file>\n");
+        xml.push_str("</xliff>\n");
+        xml
+    }
+
+    /// Parse an XLIFF 2.0 file
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let root = element(content, "xliff", 0)?.ok_or("not an XLIFF file (no <xliff> element)")?;
+        match attr(&root.attributes, "version") {
+            Some(version) if version.starts_with("2.") => {}
+            Some(version) => return Err(format!("XLIFF {} is not supported, only 2.0", version)),
+            None => return Err("<xliff> has no version".to_string()),
+        }
+        let source_locale = attr(&root.attributes, "srcLang")
+            .ok_or("<xliff> has no srcLang")?
+            .to_string();
+        let target_locale = attr(&root.attributes, "trgLang")
+            .ok_or("<xliff> has no trgLang")?
+            .to_string();
+
+        let mut units = Vec::new();
+        let mut pos = 0;
+        while let Some(unit) = element(content, "unit", pos)? {
+            pos = unit.end;
+            let line = line_at(content, unit.start);
+            units.push(parse_unit(&unit).map_err(|e| format!("unit on line {}: {}", line, e))?);
+        }
+
+        Ok(Self {
+            source_locale,
+            target_locale,
+            units,
+        })
+    }
+}
+
+fn parse_unit(unit: &Element<'_>) -> Result<Unit, String> {
+    let key = attr(&unit.attributes, "name")
+        .or_else(|| attr(&unit.attributes, "id"))
+        .ok_or("no name")?
+        .to_string();
+    let segment = element(unit.inner, "segment", 0)?.ok_or("no <segment>")?;
+    let source = text(
+        element(segment.inner, "source", 0)?
+            .ok_or("no <source>")?
+            .inner,
+    )?;
+    let target = element(segment.inner, "target", 0)?
+        .map(|e| text(e.inner))
+        .transpose()
+        .map_err(|e| format!("{} in <target>", e))?
+        .filter(|target| !target.is_empty());
+
+    let state = match attr(&segment.attributes, "state") {
+        Some(state) => State::parse(state).ok_or_else(|| format!("unknown state '{}'", state))?,
+        None if target.is_some() => State::Translated,
+        None => State::Initial,
+    };
+    let machine_translated = attr(&segment.attributes, "subState") == Some(MACHINE_SUB_STATE);
+
+    let mut base = None;
+    let mut pos = 0;
+    while let Some(meta) = element(unit.inner, "mda:meta", pos)? {
+        pos = meta.end;
+        if attr(&meta.attributes, "type") == Some("base") {
+            base = Some(text(meta.inner)?);
+        }
+    }
+
+    Ok(Unit {
+        key,
+        source,
+        target,
+        state,
+        machine_translated,
+        base,
+        placeholders: Vec::new(),
+    })
+}
+
+/// An element, its attributes and content
+struct Element<'a> {
+    attributes: Vec<(String, String)>,
+    inner: &'a str,
+    /// Byte offset of the start tag
+    start: usize,
+    /// Byte offset after the end tag
+    end: usize,
+}
+
+/// The first `<name>` element at or after `from`
+///
+/// Elements of the same name must not nest, which holds for the ones read here.
+fn element<'a>(content: &'a str, name: &str, from: usize) -> Result<Option<Element<'a>>, String> {
+    let open = format!("<{}", name);
+    let mut pos = from;
+    let start = loop {
+        let Some(offset) = content[pos..].find(&open) else {
+            return Ok(None);
+        };
+        let start = pos + offset;
+        let after = content[start + open.len()..].chars().next();
+        if after.is_some_and(|c| c.is_whitespace() || c == '>' || c == '/') {
+            break start;
+        }
+        pos = start + open.len();
+    };
+
+    // The start tag ends at the first '>' outside attribute values
+    let mut quote = None;
+    let tag_end = content[start..]
+        .char_indices()
+        .find(|&(_, c)| {
+            match quote {
+                Some(q) if c == q => quote = None,
+                None if c == '"' || c == '\'' => quote = Some(c),
+                _ => {}
+            }
+            c == '>' && quote.is_none()
+        })
+        .map(|(i, _)| start + i + 1)
+        .ok_or_else(|| {
+            format!(
+                "unterminated <{}> on line {}",
+                name,
+                line_at(content, start)
+            )
+        })?;
+    let tag = &content[start..tag_end];
+    let attributes = attributes(tag[open.len()..].trim_end_matches(['>', '/']));
+
+    if tag.ends_with("/>") {
+        return Ok(Some(Element {
+            attributes,
+            inner: "",
+            start,
+            end: tag_end,
+        }));
+    }
+    let close = format!("</{}>", name);
+    let inner_len = content[tag_end..]
+        .find(&close)
+        .ok_or_else(|| format!("unclosed <{}> on line {}", name, line_at(content, start)))?;
+    Ok(Some(Element {
+        attributes,
+        inner: &content[tag_end..tag_end + inner_len],
+        start,
+        end: tag_end + inner_len + close.len(),
+    }))
+}
+
+fn attributes(mut rest: &str) -> Vec<(String, String)> {
+    let mut attrs = Vec::new();
+    while let Some((name, after)) = rest.split_once('=') {
+        let after = after.trim_start();
+        let Some(quote) = after.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+            break;
+        };
+        let value = &after[1..];
+        let Some(close) = value.find(quote) else {
+            break;
+        };
+        attrs.push((name.trim().to_string(), unescape(&value[..close])));
+        rest = &value[close + 1..];
+    }
+    attrs
+}
+
+fn attr<'a>(attrs: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    attrs
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.as_str())
+}
+
+/// Text content, with CDATA sections and entities decoded
+fn text(inner: &str) -> Result<String, String> {
+    let mut out = String::new();
+    let mut rest = inner;
+    while let Some(offset) = rest.find('<') {
+        out.push_str(&unescape(&rest[..offset]));
+        let tag = &rest[offset..];
+        let Some(cdata) = tag.strip_prefix("<![CDATA[") else {
+            return Err("inline markup is not supported".to_string());
+        };
+        let end = cdata.find("]]>").ok_or("unterminated CDATA section")?;
+        out.push_str(&cdata[..end]);
+        rest = &cdata[end + 3..];
+    }
+    out.push_str(&unescape(rest));
+    Ok(out)
+}
+
+fn unescape(value: &str) -> String {
+    if !value.contains('&') {
+        return value.to_string();
+    }
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(offset) = rest.find('&') {
+        out.push_str(&rest[..offset]);
+        let entity = &rest[offset..];
+        let decoded = entity.find(';').and_then(|end| {
+            let name = &entity[1..end];
+            let c = match name {
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "amp" => Some('&'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ => name
+                    .strip_prefix("#x")
+                    .map(|hex| u32::from_str_radix(hex, 16))
+                    .or_else(|| name.strip_prefix('#').map(str::parse::<u32>))
+                    .and_then(Result::ok)
+                    .and_then(char::from_u32),
+            };
+            c.map(|c| (c, end + 1))
+        });
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &entity[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &entity[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// 1-based line of a byte offset
+fn line_at(content: &str, offset: usize) -> usize {
+    content[..offset].matches('\n').count() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit(key: &str, source: &str, target: Option<&str>) -> Unit {
+        Unit {
+            key: key.to_string(),
+            source: source.to_string(),
+            target: target.map(str::to_string),
+            state: if target.is_some() {
+                State::Translated
+            } else {
+                State::Initial
+            },
+            machine_translated: false,
+            base: target.map(str::to_string),
+            placeholders: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut machine = unit("share.title", "Share \"{item}\" & <more>", Some("Teilen: {item}"));
+        machine.state = State::Initial;
+        machine.machine_translated = true;
+        let document = Document {
+            source_locale: "en".to_string(),
+            target_locale: "de".to_string(),
+            units: vec![
+                unit("common.save", "Save", Some("Speichern")),
+                machine,
+                unit("common.cancel", "Cancel", None),
+            ],
+        };
+
+        assert_eq!(Document::parse(&document.to_xml()).unwrap(), document);
+    }
+
+    #[test]
+    fn test_placeholders_are_notes_only() {
+        let mut with_placeholders = unit("greeting", "Hi {name}", Some("Hallo {name}"));
+        with_placeholders.placeholders = vec!["name".to_string()];
+        let document = Document {
+            source_locale: "en".to_string(),
+            target_locale: "de".to_string(),
+            units: vec![with_placeholders],
+        };
+
+        let xml = document.to_xml();
+        assert!(xml.contains("Keep unchanged: {name}"));
+        assert!(Document::parse(&xml).unwrap().units[0].placeholders.is_empty());
+    }
+
+    #[test]
+    fn test_parse_vendor_file() {
+        let content = r#"<?xml version="1.0"?>
+<xliff xmlns="urn:oasis:names:tc:xliff:document:2.0" version="2.1" srcLang="en" trgLang="fr">
+  <file id="f1">
+    <unit id="greeting">
+      <segment>
+        <source>Hi &amp; welcome</source>
+        <target><![CDATA[Salut & bienvenue]]>&#x21;</target>
+      </segment>
+    </unit>
+    <unit id="u2" name="empty"><segment><source>Empty</source><target/></segment></unit>
+  </file>
+</xliff>"#;
+
+        let document = Document::parse(content).unwrap();
+        assert_eq!(document.target_locale, "fr");
+        assert_eq!(document.units[0].key, "greeting");
+        assert_eq!(document.units[0].source, "Hi & welcome");
+        assert_eq!(document.units[0].target.as_deref(), Some("Salut & bienvenue!"));
+        assert_eq!(document.units[0].state, State::Translated);
+        assert_eq!(document.units[1].target, None);
+        assert_eq!(document.units[1].state, State::Initial);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Document::parse(r#"<xliff version="1.2" srcLang="en" trgLang="de"></xliff>"#).unwrap_err(),
+            "XLIFF 1.2 is not supported, only 2.0"
+        );
+        let content = "<xliff version=\"2.0\" srcLang=\"en\" trgLang=\"de\">\n<unit name=\"k\"><segment>\
+            <source>A</source><target>B <pc id=\"1\">bold</pc></target></segment></unit></xliff>";
+        assert_eq!(
+            Document::parse(content).unwrap_err(),
+            "unit on line 2: inline markup is not supported in <target>"
+        );
+    }
+}
@@ -3,10 +3,12 @@
 //! Git hooks and development tools for Foodshare iOS.
 
 use anyhow::Result;
-use clap::{Parser, Subcommand};
-use foodshare_cli::output::Status;
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use foodshare_cli::output::{self, Format, Status};
+use foodshare_cli::{prompt, textln};
 use foodshare_core::config::Config;
 use foodshare_core::error::exit_codes;
+use foodshare_hooks::plugins::Hook;
 use owo_colors::OwoColorize;
 use std::path::PathBuf;
 
@@ -31,6 +33,14 @@ struct Cli {
     #[arg(long, global = true)]
     no_color: bool,
 
+    /// Output format: text, json, ndjson (secrets also takes sarif)
+    #[arg(long, global = true, default_value = "text")]
+    format: String,
+
+    /// Answer yes to confirmations and take the default elsewhere
+    #[arg(short, long, global = true)]
+    yes: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -45,9 +55,12 @@ enum Commands {
         /// Check only, don't modify (legacy flag, use --preview)
         #[arg(long)]
         check: bool,
-        /// Format only staged files
+        /// Format only the staged lines of staged files
         #[arg(long)]
         staged: bool,
+        /// With --staged, format whole files instead of just the staged lines
+        #[arg(long)]
+        whole_file: bool,
         /// Preview mode: show what would change without modifying files
         #[arg(long)]
         preview: bool,
@@ -84,6 +97,20 @@ enum Commands {
         fix: bool,
     },
 
+    /// Check .swiftlint.yml and .swiftformat against the canonical versions
+    #[command(name = "style-config")]
+    StyleConfig {
+        /// Repository root
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Overwrite drifted or missing files with the canonical versions
+        #[arg(long)]
+        sync: bool,
+        /// Directory with canonical config files (overrides [style] template_dir)
+        #[arg(long)]
+        template_dir: Option<PathBuf>,
+    },
+
     /// Validate commit message
     #[command(name = "commit-msg")]
     CommitMsg {
@@ -92,10 +119,45 @@ enum Commands {
     },
 
     /// Scan for secrets
+    #[command(args_conflicts_with_subcommands = true)]
     Secrets {
+        #[command(subcommand)]
+        action: Option<SecretsAction>,
+        /// Files to scan instead of the staged files
+        #[arg(trailing_var_arg = true)]
+        files: Vec<PathBuf>,
         /// Check all files
         #[arg(long)]
         all: bool,
+        /// Scan every file again instead of reusing findings for unchanged ones
+        #[arg(long)]
+        no_cache: bool,
+        /// Check found AWS, GitHub, Stripe and Supabase keys with their providers
+        #[arg(long)]
+        verify: bool,
+    },
+
+    /// Custom checks from the plugins directory and config
+    Plugins {
+        #[command(subcommand)]
+        action: PluginsAction,
+    },
+
+    /// Check string tables and catalogs for missing, stale and mistranslated keys
+    #[command(alias = "l10n")]
+    Localization {
+        /// Directory to search for .strings and .xcstrings files
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Locale the others are compared against
+        #[arg(long, default_value = foodshare_ios::localization::SOURCE_LOCALE)]
+        source: String,
+        /// Fail on warnings (missing, stale, untranslated), not just placeholder mismatches
+        #[arg(long)]
+        strict: bool,
+        /// Issues to list per locale
+        #[arg(long, default_value = "20")]
+        limit: usize,
     },
 
     /// Check migrations status
@@ -113,6 +175,12 @@ enum Commands {
         /// Clean before building
         #[arg(long)]
         clean: bool,
+        /// Skip type-check timing and the slow-file report
+        #[arg(long)]
+        no_timing: bool,
+        /// Build even if the sources are unchanged since the last successful build
+        #[arg(long)]
+        no_cache: bool,
     },
 
     /// Run tests
@@ -120,6 +188,56 @@ enum Commands {
         /// Enable coverage
         #[arg(long)]
         coverage: bool,
+        /// Where to write the .xcresult bundle (replaced on each run)
+        #[arg(long, default_value = "build/FoodShare.xcresult")]
+        result_bundle: PathBuf,
+        /// Where to write the JSON test report
+        #[arg(long, default_value = "build/test-results.json")]
+        report: PathBuf,
+        /// Run failing tests again up to N times
+        #[arg(long, default_value = "0")]
+        retries: usize,
+        /// Don't fail the run for tests whose flakiness is over [test] flaky_threshold
+        #[arg(long)]
+        quarantine_flaky: bool,
+    },
+
+    /// Report the flakiest tests recorded by `test`
+    Flaky {
+        /// Tests to show
+        #[arg(long, default_value = "10")]
+        top: usize,
+    },
+
+    /// Archive the app and report its size against the committed baseline
+    Size {
+        /// Analyze an existing .xcarchive instead of archiving
+        #[arg(long)]
+        archive: Option<PathBuf>,
+        /// Baseline report to compare against
+        #[arg(long, default_value = foodshare_ios::app_size::BASELINE_FILE)]
+        baseline: PathBuf,
+        /// Save this report as the new baseline instead of checking the budget
+        #[arg(long)]
+        update_baseline: bool,
+        /// Export method used for app thinning
+        #[arg(long, default_value = "development")]
+        export_method: String,
+        /// Number of components to show
+        #[arg(long, default_value = "15")]
+        top: usize,
+    },
+
+    /// Symbolicate a crash report (.ips or .crash) with the archive's dSYMs
+    Crash {
+        /// Crash report file
+        report: PathBuf,
+        /// Directory searched for dSYMs
+        #[arg(long, default_value = "build/FoodShare.xcarchive/dSYMs")]
+        dsyms: PathBuf,
+        /// Show every thread, not just the crashed one
+        #[arg(long)]
+        all_threads: bool,
     },
 
     /// Build, install, and run the app on simulator
@@ -136,22 +254,41 @@ enum Commands {
         /// Device name or UDID
         #[arg(long)]
         device: Option<String>,
+        /// Where to run: simulator or physical
+        #[arg(long, default_value = "simulator")]
+        device_type: String,
     },
 
-    /// List simulators
-    Simulator {
-        /// Action: list, boot, shutdown
-        action: String,
-        /// Device name or UDID
+    /// List connected physical devices
+    Devices,
+
+    /// Diagnose code signing for device builds
+    Signing {
+        /// Build configuration to check
+        #[arg(long, default_value = "Debug")]
+        configuration: String,
+        /// Device name or UDID the build will run on
         #[arg(long)]
         device: Option<String>,
+        /// Also list installed certificates and provisioning profiles
+        #[arg(long)]
+        list: bool,
+    },
+
+    /// List simulators
+    Simulator {
+        #[command(subcommand)]
+        action: SimulatorAction,
     },
 
     /// Diagnose environment
     Doctor {
-        /// Output as JSON
+        /// Output as JSON (same as --format json)
         #[arg(long)]
         json: bool,
+        /// Offer to install missing tools with Homebrew
+        #[arg(long)]
+        fix: bool,
     },
 
     /// Xcode project management
@@ -163,6 +300,10 @@ enum Commands {
     /// Verify setup
     Verify,
 
+    /// Update this binary to the latest signed release
+    #[command(name = "self-update")]
+    SelfUpdate(foodshare_self_update::SelfUpdateArgs),
+
     /// Pre-push checks - validates build, lint, and tests before push
     #[command(name = "pre-push")]
     PrePush {
@@ -179,12 +320,15 @@ enum Commands {
         /// Quick mode: skip optional checks (tests)
         #[arg(long)]
         quick: bool,
-        /// Skip specific checks (comma-separated: lint,build,test)
+        /// Skip specific checks (comma-separated: lint,build,l10n,test)
         #[arg(long, value_delimiter = ',')]
         skip: Vec<String>,
         /// Show detailed output for pre-push checks
         #[arg(long)]
         detailed: bool,
+        /// Build even if the sources are unchanged since the last successful build
+        #[arg(long)]
+        no_build_cache: bool,
     },
 
     /// Manage Swift package dependencies
@@ -206,6 +350,71 @@ enum Commands {
     },
 }
 
+#[derive(Subcommand)]
+enum SimulatorAction {
+    /// List available simulators
+    List,
+    /// Boot a simulator
+    Boot {
+        /// Device name or UDID
+        #[arg(long, default_value = "iPhone 17 Pro Max")]
+        device: String,
+    },
+    /// Shut down one simulator, or all of them
+    Shutdown {
+        /// Device name or UDID (all simulators if omitted)
+        #[arg(long)]
+        device: Option<String>,
+    },
+    /// Erase a simulator's content and settings (shuts it down first)
+    Erase {
+        /// Device name or UDID
+        #[arg(long, default_value = "iPhone 17 Pro Max")]
+        device: String,
+    },
+    /// Clone a simulator (shuts it down first)
+    Clone {
+        /// Name for the copy
+        name: String,
+        /// Device name or UDID to copy
+        #[arg(long, default_value = "iPhone 17 Pro Max")]
+        device: String,
+    },
+    /// Override the status bar for screenshots (9:41, full battery and signal)
+    StatusBar {
+        /// Device name or UDID
+        #[arg(long, default_value = "booted")]
+        device: String,
+        /// Time to show
+        #[arg(long, default_value = "9:41")]
+        time: String,
+        /// Battery level (0-100)
+        #[arg(long, default_value = "100")]
+        battery: u8,
+        /// Restore the real status bar instead
+        #[arg(long)]
+        clear: bool,
+    },
+    /// Save a screenshot
+    Screenshot {
+        /// Output file (.png)
+        #[arg(default_value = "screenshot.png")]
+        output: PathBuf,
+        /// Device name or UDID
+        #[arg(long, default_value = "booted")]
+        device: String,
+    },
+    /// Record the screen until Ctrl+C
+    RecordVideo {
+        /// Output file (.mp4)
+        #[arg(default_value = "recording.mp4")]
+        output: PathBuf,
+        /// Device name or UDID
+        #[arg(long, default_value = "booted")]
+        device: String,
+    },
+}
+
 #[derive(Subcommand)]
 enum SupabaseAction {
     /// Fix auth hook configuration (resolves "Hook requires authorization token" error)
@@ -214,9 +423,6 @@ enum SupabaseAction {
         /// Webhook secret from Supabase Dashboard (starts with v1,whsec_)
         #[arg(long)]
         secret: Option<String>,
-        /// Skip confirmation prompts
-        #[arg(long)]
-        yes: bool,
         /// Only verify current configuration without making changes
         #[arg(long)]
         check: bool,
@@ -298,6 +504,48 @@ enum ProtectAction {
         dry_run: bool,
     },
 
+    /// Show what restoring a snapshot would change
+    Diff {
+        /// Snapshot ID to diff
+        #[arg(long)]
+        snapshot: String,
+        /// Snapshot ID to compare against, or "worktree" for current files
+        #[arg(long, default_value = "worktree")]
+        against: String,
+        /// Only diff this file
+        #[arg(long)]
+        file: Option<PathBuf>,
+    },
+
+    /// Drop snapshots beyond the retention limit and unreferenced content
+    Gc,
+
+    /// Back up recent snapshots, encrypted, to Supabase Storage
+    Push {
+        /// Storage bucket
+        #[arg(long, default_value = "code-snapshots")]
+        bucket: String,
+        /// Object prefix (default: <git user.email>/<repository name>)
+        #[arg(long)]
+        prefix: Option<String>,
+        /// Number of most recent snapshots to upload
+        #[arg(long, default_value = "10")]
+        limit: usize,
+    },
+
+    /// Download backed-up snapshots from Supabase Storage
+    Pull {
+        /// Storage bucket
+        #[arg(long, default_value = "code-snapshots")]
+        bucket: String,
+        /// Object prefix (default: <git user.email>/<repository name>)
+        #[arg(long)]
+        prefix: Option<String>,
+        /// Number of most recent snapshots to download
+        #[arg(long, default_value = "10")]
+        limit: usize,
+    },
+
     /// Show what would be committed (Commit Guard)
     #[command(name = "commit-guard")]
     CommitGuard,
@@ -319,6 +567,9 @@ enum ProtectAction {
         /// Quick syntax check only
         #[arg(long)]
         quick: bool,
+        /// Build even if the sources are unchanged since the last successful build
+        #[arg(long)]
+        no_cache: bool,
     },
 
     /// Show operation history
@@ -330,6 +581,60 @@ enum ProtectAction {
 
     /// Show protection status and configuration
     Status,
+
+    /// Require confirmation for staged changes to protected paths (pre-commit)
+    Approve,
+
+    /// Snapshot protected files automatically as they change
+    Watch {
+        /// Glob to watch instead of the protected paths (repeatable)
+        #[arg(long)]
+        pattern: Vec<String>,
+        /// Seconds without changes before a burst is snapshotted
+        #[arg(long, default_value = "2")]
+        settle: u64,
+        /// Minimum seconds between automatic snapshots
+        #[arg(long, default_value = "60")]
+        interval: u64,
+    },
+}
+
+#[derive(Subcommand)]
+enum SecretsAction {
+    /// Manage the baseline of accepted findings
+    Baseline {
+        #[command(subcommand)]
+        action: BaselineAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum BaselineAction {
+    /// Write the findings in all tracked files to the baseline
+    Create {
+        /// Replace an existing baseline
+        #[arg(long)]
+        force: bool,
+    },
+    /// Rescan tracked files: add new findings, drop fixed ones, keep audit verdicts
+    Update,
+    /// Label baselined findings as real secrets or false positives
+    Audit,
+}
+
+#[derive(Subcommand)]
+enum PluginsAction {
+    /// List the registered plugins
+    List,
+    /// Run the plugins of a hook
+    Run {
+        /// Hook whose plugins run: pre-commit, pre-push
+        #[arg(long, default_value = "pre-commit")]
+        hook: Hook,
+        /// Files to check instead of the staged files
+        #[arg(trailing_var_arg = true)]
+        files: Vec<PathBuf>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -346,6 +651,15 @@ enum DepsAction {
         #[arg(long, default_value = ".")]
         path: PathBuf,
     },
+    /// Audit every Package.resolved for conflicts, hosts and outdated pins
+    Audit {
+        /// Repository root to search (default: current directory)
+        #[arg(long, default_value = ".")]
+        path: PathBuf,
+        /// Skip querying remotes for newer releases
+        #[arg(long)]
+        offline: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -400,54 +714,274 @@ enum ProjectAction {
         /// Target name to add source files to
         #[arg(long, default_value = "FoodShare")]
         target: String,
-        /// Group path (e.g., "FoodShare/Core/Design")
+        /// Group path (e.g., "FoodShare/Core/Design"); missing groups are created
         #[arg(long)]
         group: Option<String>,
         /// Preview changes without modifying the project
         #[arg(long)]
         dry_run: bool,
     },
+    /// Remove files from the Xcode project (files on disk are kept)
+    Remove {
+        /// Files to remove from the project
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Path to .xcodeproj
+        #[arg(long, default_value = "FoodShare.xcodeproj")]
+        project: PathBuf,
+        /// Preview changes without modifying the project
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Rename a file on disk and in the Xcode project
+    Rename {
+        /// Current path
+        from: PathBuf,
+        /// New path
+        to: PathBuf,
+        /// Path to .xcodeproj
+        #[arg(long, default_value = "FoodShare.xcodeproj")]
+        project: PathBuf,
+        /// Only update the project, leaving the file on disk alone
+        #[arg(long)]
+        project_only: bool,
+        /// Preview changes without modifying the project
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Move files to another group in the Xcode project
+    Move {
+        /// Files to move
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Destination group path (e.g., "FoodShare/Core/Design")
+        #[arg(long)]
+        group: String,
+        /// Path to .xcodeproj
+        #[arg(long, default_value = "FoodShare.xcodeproj")]
+        project: PathBuf,
+        /// Preview changes without modifying the project
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Read and edit build settings
+    Settings {
+        #[command(subcommand)]
+        action: SettingsAction,
+    },
+    /// List and create schemes, and toggle parallel testing
+    Scheme {
+        #[command(subcommand)]
+        action: SchemeAction,
+    },
+    /// Add missing files, remove broken references and dedupe build files
+    Fix {
+        /// Path to .xcodeproj
+        #[arg(long, default_value = "FoodShare.xcodeproj")]
+        project: PathBuf,
+        /// Target name
+        #[arg(long, default_value = "FoodShare")]
+        target: String,
+        /// Source directory
+        #[arg(long, default_value = "FoodShare")]
+        source_dir: String,
+        /// Preview changes without modifying the project
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Merge a project.pbxproj left with git conflict markers
+    ResolveConflicts {
+        /// Path to .xcodeproj
+        #[arg(long, default_value = "FoodShare.xcodeproj")]
+        project: PathBuf,
+        /// Preview the merge without modifying the project
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum SettingsAction {
+    /// Show build settings
+    Get {
+        /// Setting to show (all settings if omitted)
+        key: Option<String>,
+        /// Path to .xcodeproj
+        #[arg(long, default_value = "FoodShare.xcodeproj")]
+        project: PathBuf,
+        /// Target name (project-level settings if omitted)
+        #[arg(long)]
+        target: Option<String>,
+        /// Configuration name (all configurations if omitted)
+        #[arg(long)]
+        configuration: Option<String>,
+    },
+    /// Change a build setting
+    Set {
+        /// Setting name (e.g., SWIFT_VERSION)
+        key: String,
+        /// New value
+        value: String,
+        /// Path to .xcodeproj
+        #[arg(long, default_value = "FoodShare.xcodeproj")]
+        project: PathBuf,
+        /// Target name (project-level settings if omitted)
+        #[arg(long)]
+        target: Option<String>,
+        /// Configuration name (all configurations if omitted)
+        #[arg(long)]
+        configuration: Option<String>,
+        /// Preview changes without modifying the project
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Compare two configurations ("Release" or "FoodShare:Release")
+    Diff {
+        /// First configuration
+        left: String,
+        /// Second configuration
+        right: String,
+        /// Path to .xcodeproj
+        #[arg(long, default_value = "FoodShare.xcodeproj")]
+        project: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum SchemeAction {
+    /// List shared and per-user schemes
+    List {
+        /// Path to .xcodeproj
+        #[arg(long, default_value = "FoodShare.xcodeproj")]
+        project: PathBuf,
+    },
+    /// Create a shared CI scheme with code coverage and parallel testing
+    CreateCi {
+        /// Scheme name
+        #[arg(long, default_value = "FoodShare-CI")]
+        name: String,
+        /// App target to build and launch
+        #[arg(long, default_value = "FoodShare")]
+        target: String,
+        /// Path to .xcodeproj
+        #[arg(long, default_value = "FoodShare.xcodeproj")]
+        project: PathBuf,
+        /// Replace an existing scheme with the same name
+        #[arg(long)]
+        force: bool,
+    },
+    /// Enable or disable parallel testing (in the scheme's test plans, if any)
+    Parallel {
+        /// Scheme name
+        scheme: String,
+        /// Disable instead of enable
+        #[arg(long)]
+        disable: bool,
+        /// Path to .xcodeproj
+        #[arg(long, default_value = "FoodShare.xcodeproj")]
+        project: PathBuf,
+        /// Preview changes without modifying files
+        #[arg(long)]
+        dry_run: bool,
+    },
 }
 
 fn main() -> Result<()> {
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches)?;
+    let command = output::command_path(&matches);
+
+    // secrets renders its own sarif
+    let sarif = matches!(cli.command, Commands::Secrets { .. }) && cli.format == "sarif";
+    let format = if sarif {
+        Ok(Format::Text)
+    } else if matches!(cli.command, Commands::Doctor { json: true, .. }) {
+        Ok(Format::Json)
+    } else {
+        cli.format.parse::<Format>()
+    };
+    match format {
+        Ok(format) => output::init(format),
+        Err(e) => {
+            Status::error(&e);
+            std::process::exit(exit_codes::FAILURE);
+        }
+    }
 
     if cli.no_color {
         owo_colors::set_override(false);
     }
+    prompt::set_assume_yes(cli.yes);
 
     let config = Config::load(cli.config.as_deref().map(|p| p.to_str().unwrap()))?;
 
     let exit_code = match cli.command {
-        Commands::Format { files, check, staged, preview, backup, no_backup, show_diff, audit, snapshot, no_snapshot } => {
-            run_format(&files, check || preview, staged, preview, backup && !no_backup, show_diff, audit, snapshot && !no_snapshot)
+        Commands::Format { files, check, staged, whole_file, preview, backup, no_backup, show_diff, audit, snapshot, no_snapshot } => {
+            let options = FormatOptions {
+                check: check || preview,
+                staged,
+                staged_lines_only: !whole_file,
+                preview,
+                backup: backup && !no_backup,
+                show_diff,
+                audit,
+                create_snapshot: snapshot && !no_snapshot,
+            };
+            run_format(&files, &options)
         }
         Commands::Lint { files, strict, fix } => {
             run_lint(&files, strict, fix)
         }
+        Commands::StyleConfig { path, sync, template_dir } => {
+            run_style_config(&path, sync, template_dir, &config)
+        }
         Commands::CommitMsg { file } => {
             run_commit_msg(&file, &config)
         }
-        Commands::Secrets { all } => {
-            run_secrets(all, &config)
+        Commands::Secrets { action: Some(SecretsAction::Baseline { action }), .. } => {
+            run_secrets_baseline(action, &config)
+        }
+        Commands::Secrets { action: None, files, all, no_cache, verify } => {
+            run_secrets(&files, all, sarif, no_cache, verify, &config)
+        }
+        Commands::Plugins { action } => {
+            run_plugins(action, &config)
+        }
+        Commands::Localization { path, source, strict, limit } => {
+            run_localization(&path, &source, strict, limit)
         }
         Commands::Migrations { dir } => {
             run_migrations(&dir)
         }
-        Commands::Build { configuration, clean } => {
-            run_build(&configuration, clean)
+        Commands::Build { configuration, clean, no_timing, no_cache } => {
+            run_build(&configuration, clean, !no_timing, !no_cache)
+        }
+        Commands::Test { coverage, result_bundle, report, retries, quarantine_flaky } => {
+            run_test(coverage, &result_bundle, &report, retries, quarantine_flaky, &config)
         }
-        Commands::Test { coverage } => {
-            run_test(coverage)
+        Commands::Flaky { top } => {
+            run_flaky(top, &config)
         }
-        Commands::Run { clean, logs, release, device } => {
-            run_app(clean, logs, release, device.as_deref())
+        Commands::Size { archive, baseline, update_baseline, export_method, top } => {
+            run_size(archive.as_deref(), &baseline, update_baseline, &export_method, top, &config)
         }
-        Commands::Simulator { action, device } => {
-            run_simulator(&action, device.as_deref())
+        Commands::Crash { report, dsyms, all_threads } => {
+            run_crash(&report, &dsyms, all_threads)
         }
-        Commands::Doctor { json } => {
-            run_doctor(json)
+        Commands::Run { clean, logs, release, device, device_type } => {
+            run_app(clean, logs, release, device.as_deref(), &device_type)
+        }
+        Commands::Devices => {
+            run_devices()
+        }
+        Commands::Signing { configuration, device, list } => {
+            run_signing(&configuration, device.as_deref(), list)
+        }
+        Commands::Simulator { action } => {
+            run_simulator(action)
+        }
+        Commands::Doctor { fix, .. } => {
+            run_doctor(fix)
         }
         Commands::Project { action } => {
             run_project(action)
@@ -455,24 +989,42 @@ fn main() -> Result<()> {
         Commands::Verify => {
             run_verify()
         }
-        Commands::PrePush { remote, url, fail_fast, release, quick, skip, detailed } => {
-            run_pre_push(remote.as_deref(), url.as_deref(), fail_fast, release, quick, skip, detailed)
+        Commands::SelfUpdate(args) => {
+            foodshare_self_update::run(env!("CARGO_BIN_NAME"), env!("CARGO_PKG_VERSION"), &args, &config.schema.update)
+        }
+        Commands::PrePush { fail_fast, release, quick, skip, detailed, no_build_cache, .. } => {
+            run_pre_push(fail_fast, release, quick, skip, detailed, !no_build_cache, &config)
         }
         Commands::Deps { action } => {
-            run_deps(action)
+            run_deps(action, &config)
         }
         Commands::Protect { action } => {
-            run_protect(action)
+            run_protect(action, &config)
         }
         Commands::Supabase { action } => {
             run_supabase(action)
         }
     };
 
-    std::process::exit(exit_code);
+    std::process::exit(output::finish(&command, exit_code));
+}
+
+/// Flags of the `format` command
+struct FormatOptions {
+    /// Only report what would change
+    check: bool,
+    /// Format the staged Swift files
+    staged: bool,
+    /// With `staged`, format only the staged lines
+    staged_lines_only: bool,
+    preview: bool,
+    backup: bool,
+    show_diff: bool,
+    audit: bool,
+    create_snapshot: bool,
 }
 
-fn run_format(files: &[PathBuf], check: bool, staged: bool, preview: bool, backup: bool, show_diff: bool, audit: bool, create_snapshot: bool) -> i32 {
+fn run_format(files: &[PathBuf], options: &FormatOptions) -> i32 {
     use foodshare_ios::hooks::{SafeFormat, SafeFormatConfig, print_format_summary};
     use foodshare_ios::swift_tools;
 
@@ -482,7 +1034,7 @@ fn run_format(files: &[PathBuf], check: bool, staged: bool, preview: bool, backu
     }
 
     // Determine target files
-    let target_files = if staged {
+    let target_files = if options.staged {
         match foodshare_core::git::GitRepo::open_current()
             .and_then(|r| r.staged_swift_files())
         {
@@ -506,17 +1058,18 @@ fn run_format(files: &[PathBuf], check: bool, staged: bool, preview: bool, backu
     }
 
     // Legacy check mode - just run swiftformat --lint
-    if check && !preview {
+    if options.check && !options.preview {
         return run_legacy_format_check(&target_files);
     }
 
     // Enterprise-grade safe format
     let config = SafeFormatConfig {
-        preview,
-        backup,
-        show_diff,
-        audit,
-        create_snapshot,
+        preview: options.preview,
+        backup: options.backup,
+        show_diff: options.show_diff,
+        audit: options.audit,
+        create_snapshot: options.create_snapshot,
+        staged_only: options.staged && options.staged_lines_only,
         ..Default::default()
     };
 
@@ -528,21 +1081,24 @@ fn run_format(files: &[PathBuf], check: bool, staged: bool, preview: bool, backu
         }
     };
 
-    println!();
-    println!("{}", "Safe Format".bold());
-    println!("{}", "═".repeat(50));
-    println!();
+    textln!();
+    textln!("{}", "Safe Format".bold());
+    textln!("{}", "═".repeat(50));
+    textln!();
 
-    if preview {
-        println!("  {} Preview mode enabled - no files will be modified", "👁".yellow());
+    if options.preview {
+        textln!("  {} Preview mode enabled - no files will be modified", "👁".yellow());
+    }
+    if options.create_snapshot {
+        textln!("  {} Snapshot protection enabled", "📸".green());
     }
-    if create_snapshot {
-        println!("  {} Snapshot protection enabled", "📸".green());
+    if options.backup {
+        textln!("  {} Stash backup enabled", "💾".green());
     }
-    if backup {
-        println!("  {} Stash backup enabled", "💾".green());
+    if options.staged && options.staged_lines_only {
+        textln!("  {} Formatting staged lines only", "✂".green());
     }
-    println!();
+    textln!();
 
     match safe_format.format(&target_files) {
         Ok(result) => {
@@ -609,7 +1165,8 @@ fn run_lint(files: &[PathBuf], strict: bool, fix: bool) -> i32 {
                 exit_codes::SUCCESS
             } else {
                 Status::error("Lint found issues");
-                println!("{}", result.stdout);
+                textln!("{}", result.stdout);
+                output::data("swiftlint_output", &result.stdout);
                 exit_codes::FAILURE
             }
         }
@@ -620,6 +1177,47 @@ fn run_lint(files: &[PathBuf], strict: bool, fix: bool) -> i32 {
     }
 }
 
+fn run_style_config(root: &std::path::Path, sync: bool, template_dir: Option<PathBuf>, config: &Config) -> i32 {
+    use foodshare_ios::style_config;
+
+    let template_dir = template_dir.or_else(|| {
+        config.schema.style.template_dir.as_ref().map(|dir| root.join(dir))
+    });
+
+    let checks = match style_config::check(root, template_dir.as_deref()) {
+        Ok(checks) => checks,
+        Err(e) => {
+            Status::error(&format!("Style config check failed: {}", e));
+            return exit_codes::FAILURE;
+        }
+    };
+
+    style_config::print_checks(&checks, root);
+    if !checks.iter().any(|c| c.needs_sync()) {
+        Status::success("Lint and format configs match the canonical versions");
+        return exit_codes::SUCCESS;
+    }
+
+    if !sync {
+        Status::error("Lint and format configs differ from the canonical versions");
+        Status::info("Run: foodshare-ios style-config --sync");
+        return exit_codes::FAILURE;
+    }
+
+    match style_config::sync(&checks) {
+        Ok(written) => {
+            for path in &written {
+                Status::success(&format!("Updated {}", path.strip_prefix(root).unwrap_or(path).display()));
+            }
+            exit_codes::SUCCESS
+        }
+        Err(e) => {
+            Status::error(&format!("Sync failed: {}", e));
+            exit_codes::FAILURE
+        }
+    }
+}
+
 fn run_commit_msg(file: &PathBuf, config: &Config) -> i32 {
     use foodshare_hooks::commit_msg;
 
@@ -642,10 +1240,12 @@ fn run_commit_msg(file: &PathBuf, config: &Config) -> i32 {
     }
 }
 
-fn run_secrets(all: bool, config: &Config) -> i32 {
+fn run_secrets(files: &[PathBuf], all: bool, sarif: bool, no_cache: bool, verify: bool, config: &Config) -> i32 {
     use foodshare_hooks::secrets;
 
-    let files = if all {
+    let files = if !files.is_empty() {
+        files.to_vec()
+    } else if all {
         foodshare_core::file_scanner::scan_swift_files(std::path::Path::new("."))
             .unwrap_or_default()
     } else {
@@ -654,40 +1254,229 @@ fn run_secrets(all: bool, config: &Config) -> i32 {
             .unwrap_or_default()
     };
 
-    let matches = secrets::scan_files(&files, &config.schema.secrets);
-    secrets::print_results(&matches)
-}
-
-fn run_migrations(dir: &PathBuf) -> i32 {
-    use foodshare_hooks::migrations;
-
-    match migrations::check_migrations(dir, true, true) {
-        Ok(check) => migrations::print_results(&check),
+    let baseline = match secrets::load_baseline(&config.schema.secrets) {
+        Ok(baseline) => baseline,
         Err(e) => {
-            Status::error(&format!("Migration check error: {}", e));
-            exit_codes::FAILURE
+            Status::error(&e.to_string());
+            return exit_codes::FAILURE;
+        }
+    };
+    let secrets_config = foodshare_core::config::SecretsConfig {
+        cache: config.schema.secrets.cache && !no_cache,
+        ..config.schema.secrets.clone()
+    };
+    let mut output = secrets::scan_files_with_config(&files, &secrets_config, baseline.as_ref());
+    if verify {
+        if let Err(e) = secrets::verify_output(&mut output) {
+            Status::error(&e.to_string());
+            return exit_codes::FAILURE;
         }
     }
+    if sarif {
+        println!("{}", output.to_sarif());
+        return if output.has_secrets() { exit_codes::FAILURE } else { exit_codes::SUCCESS };
+    }
+    secrets::print_output(&output)
 }
 
-fn run_build(configuration: &str, clean: bool) -> i32 {
-    use foodshare_ios::xcode;
+fn run_secrets_baseline(action: BaselineAction, config: &Config) -> i32 {
+    use foodshare_hooks::secrets;
+
+    if let BaselineAction::Audit = action {
+        return secrets::audit_baseline(&config.schema.secrets);
+    }
+    let tracked = match foodshare_core::git::GitRepo::open_current()
+        .and_then(|r| r.files_at("HEAD", std::path::Path::new(".")))
+    {
+        Ok(files) => files,
+        Err(e) => {
+            Status::error(&format!("Failed to list tracked files: {}", e));
+            return exit_codes::FAILURE;
+        }
+    };
+    match action {
+        BaselineAction::Create { force } => secrets::create_baseline(&tracked, &config.schema.secrets, force),
+        _ => secrets::update_baseline(&tracked, &config.schema.secrets),
+    }
+}
+
+fn run_plugins(action: PluginsAction, config: &Config) -> i32 {
+    use foodshare_hooks::plugins;
+
+    match action {
+        PluginsAction::List => plugins::list(&config.schema.plugins),
+        PluginsAction::Run { hook, files } => plugins::run_for_hook(hook, &files, &config.schema.plugins),
+    }
+}
+
+fn run_localization(path: &std::path::Path, source: &str, strict: bool, limit: usize) -> i32 {
+    use foodshare_ios::localization;
+
+    let report = match localization::lint(path, source) {
+        Ok(report) => report,
+        Err(e) => {
+            Status::error(&format!("Localization lint failed: {}", e));
+            return exit_codes::FAILURE;
+        }
+    };
+    if report.tables == 0 {
+        Status::info("No .strings or .xcstrings files found");
+        return exit_codes::SUCCESS;
+    }
+
+    report.print(limit);
+    textln!();
+
+    let errors = report.error_count();
+    let warnings = report.issues.len() - errors;
+    if errors > 0 {
+        Status::error(&format!("{} placeholder mismatches, {} warnings", errors, warnings));
+        exit_codes::FAILURE
+    } else if warnings > 0 && strict {
+        Status::error(&format!("{} warnings (--strict)", warnings));
+        exit_codes::FAILURE
+    } else if warnings > 0 {
+        Status::warning(&format!("{} warnings", warnings));
+        exit_codes::SUCCESS
+    } else {
+        Status::success("All locales complete");
+        exit_codes::SUCCESS
+    }
+}
+
+fn run_deps_audit(path: &std::path::Path, offline: bool, config: &Config) -> i32 {
+    use foodshare_ios::spm;
+
+    if !offline {
+        Status::info("Checking remotes for newer releases...");
+    }
+    let audit = match spm::audit(path, &config.schema.deps.allowed_hosts, !offline) {
+        Ok(audit) => audit,
+        Err(e) => {
+            Status::error(&format!("Dependency audit failed: {}", e));
+            return exit_codes::FAILURE;
+        }
+    };
+    if audit.files.is_empty() {
+        Status::info("No Package.resolved files found");
+        return exit_codes::SUCCESS;
+    }
+
+    audit.print(path);
+    textln!();
+
+    if audit.has_failures() {
+        Status::error(&format!(
+            "{} conflicting packages, {} from non-allowlisted hosts",
+            audit.duplicates.len(),
+            audit.disallowed.len()
+        ));
+        exit_codes::FAILURE
+    } else if !audit.unpinned.is_empty() || !audit.outdated.is_empty() {
+        Status::warning(&format!(
+            "{} unpinned, {} outdated",
+            audit.unpinned.len(),
+            audit.outdated.len()
+        ));
+        exit_codes::SUCCESS
+    } else {
+        Status::success("Dependencies are consistent and up to date");
+        exit_codes::SUCCESS
+    }
+}
+
+fn run_migrations(dir: &PathBuf) -> i32 {
+    use foodshare_hooks::migrations;
+
+    match migrations::check_migrations(dir, true, true) {
+        Ok(check) => {
+            let code = migrations::print_results(&check);
+            if check.destructive.is_empty() {
+                return code;
+            }
+            match migrations::approve_destructive(&check) {
+                Ok(true) => code,
+                Ok(false) => {
+                    Status::error("Destructive migration not approved");
+                    exit_codes::FAILURE
+                }
+                Err(e) => {
+                    Status::error(&e.to_string());
+                    exit_codes::FAILURE
+                }
+            }
+        }
+        Err(e) => {
+            Status::error(&format!("Migration check error: {}", e));
+            exit_codes::FAILURE
+        }
+    }
+}
+
+fn run_build(configuration: &str, clean: bool, timing: bool, use_cache: bool) -> i32 {
+    use foodshare_ios::build_cache::{source_hash, BuildCache};
+    use foodshare_ios::{build_log, xcode};
 
     if !xcode::is_xcode_available() {
         Status::error("Xcode not found");
         return exit_codes::FAILURE;
     }
 
+    let destination = "platform=iOS Simulator,name=iPhone 17 Pro Max";
+    let root = std::env::current_dir().unwrap_or_default();
+    let cache = BuildCache::open(&root);
+    if clean {
+        if let Err(e) = cache.clear() {
+            Status::warning(&format!("Failed to clear build cache: {}", e));
+        }
+    }
+
+    let key = if use_cache {
+        match source_hash(&root, &["build", "FoodShare", configuration, destination]) {
+            Ok(key) => Some(key),
+            Err(e) => {
+                Status::warning(&format!("Build cache disabled: {}", e));
+                None
+            }
+        }
+    } else {
+        None
+    };
+    // Where xcodebuild puts the products, so they can be cached and restored
+    let products = key
+        .as_ref()
+        .and_then(|_| xcode::build_settings("FoodShare", configuration, "iphonesimulator").ok())
+        .and_then(|settings| settings.get("BUILT_PRODUCTS_DIR").map(PathBuf::from));
+
+    if let (Some(key), Some(products)) = (key.as_deref(), products.as_deref()) {
+        if cache.is_fresh(key) {
+            match cache.restore(key, products) {
+                Ok(true) => {
+                    Status::success("Sources unchanged since the last successful build; restored its products");
+                    return exit_codes::SUCCESS;
+                }
+                Ok(false) => {}
+                Err(e) => Status::warning(&format!("Failed to restore cached build products: {}", e)),
+            }
+        }
+    }
+
     Status::info(&format!("Building {} configuration...", configuration));
 
-    match xcode::build(
-        "FoodShare",
-        configuration,
-        "platform=iOS Simulator,name=iPhone 17 Pro Max",
-        clean,
-    ) {
+    let timing_settings = build_log::timing_settings();
+    let settings: Vec<&str> = if timing { vec![timing_settings.as_str()] } else { Vec::new() };
+
+    match xcode::build_with_args("FoodShare", configuration, destination, clean, &settings) {
         Ok(result) => {
+            if timing {
+                report_build_timing(&result.combined_output());
+            }
             if result.success {
+                if let Some(key) = &key {
+                    if let Err(e) = cache.record(key, products.as_deref()) {
+                        Status::warning(&format!("Failed to cache build products: {}", e));
+                    }
+                }
                 Status::success("Build succeeded");
                 exit_codes::SUCCESS
             } else {
@@ -703,36 +1492,306 @@ fn run_build(configuration: &str, clean: bool) -> i32 {
     }
 }
 
-fn run_test(coverage: bool) -> i32 {
+/// Print the slowest files of a build and record them in the trend history
+fn report_build_timing(output: &str) {
+    use foodshare_ios::build_log::{BuildLog, TRENDS_FILE};
+    use foodshare_telemetry::TrendLog;
+
+    let log = BuildLog::parse(output);
+    if log.is_empty() {
+        return;
+    }
+
+    let root = std::env::current_dir().unwrap_or_default();
+    textln!();
+    log.print_report(20, &root);
+
+    match log.record_trends(&TrendLog::new(TRENDS_FILE), 20, &root) {
+        Ok(Some(previous)) => {
+            let change = log.total_ms() - previous;
+            textln!("Previous build: {:.1}ms ({:+.1}ms)", previous, change);
+        }
+        Ok(None) => {}
+        Err(e) => Status::warning(&format!("Failed to record build trends: {}", e)),
+    }
+    textln!();
+}
+
+fn run_test(
+    coverage: bool,
+    result_bundle: &std::path::Path,
+    report: &std::path::Path,
+    retries: usize,
+    quarantine_flaky: bool,
+    config: &Config,
+) -> i32 {
+    use foodshare_ios::test_history::{self, TestHistory};
     use foodshare_ios::xcode;
+    use foodshare_ios::xcresult::XcResult;
 
     Status::info("Running tests...");
 
-    match xcode::test(
+    let result = match xcode::test_with_result_bundle(
         "FoodShare",
         "platform=iOS Simulator,name=iPhone 17 Pro Max",
         coverage,
+        retries,
+        result_bundle,
     ) {
-        Ok(result) => {
-            if result.success {
-                Status::success("Tests passed");
-                exit_codes::SUCCESS
-            } else {
-                Status::error("Tests failed");
+        Ok(result) => result,
+        Err(e) => {
+            Status::error(&format!("Test error: {}", e));
+            return exit_codes::FAILURE;
+        }
+    };
+
+    let history_path = std::path::Path::new(test_history::HISTORY_FILE);
+    let mut history = TestHistory::load(history_path).unwrap_or_else(|e| {
+        Status::warning(&format!("Ignoring unreadable test history: {}", e));
+        TestHistory::default()
+    });
+
+    let mut only_quarantined_failed = false;
+    match XcResult::load(result_bundle) {
+        Ok(results) => {
+            textln!();
+            results.print();
+            textln!();
+            match results.write_json(report) {
+                Ok(()) => Status::info(&format!("Report written to {}", report.display())),
+                Err(e) => Status::warning(&format!("Failed to write report: {}", e)),
+            }
+
+            // Quarantine on the history before this run, so a first failure still fails
+            let threshold = config.schema.test.flaky_threshold;
+            let failed: Vec<&str> = results.failed_tests().map(|t| t.identifier.as_str()).collect();
+            if quarantine_flaky
+                && !failed.is_empty()
+                && results.errors.is_empty()
+                && failed.iter().all(|id| history.is_quarantined(id, threshold))
+            {
+                only_quarantined_failed = true;
+                for id in &failed {
+                    Status::warning(&format!("Quarantined flaky test failed: {}", id));
+                }
+            }
+
+            history.record(&results.tests);
+            if let Err(e) = history.save(history_path) {
+                Status::warning(&format!("Failed to save test history: {}", e));
+            }
+        }
+        Err(e) => {
+            Status::warning(&format!("Could not read test results: {}", e));
+            if !result.success {
                 eprintln!("{}", result.stderr);
+            }
+        }
+    }
+
+    if result.success {
+        Status::success("Tests passed");
+        exit_codes::SUCCESS
+    } else if only_quarantined_failed {
+        Status::success("Tests passed (only quarantined flaky tests failed)");
+        exit_codes::SUCCESS
+    } else {
+        Status::error("Tests failed");
+        exit_codes::FAILURE
+    }
+}
+
+fn run_flaky(top: usize, config: &Config) -> i32 {
+    use foodshare_ios::test_history::{self, TestHistory};
+
+    let history = match TestHistory::load(std::path::Path::new(test_history::HISTORY_FILE)) {
+        Ok(history) => history,
+        Err(e) => {
+            Status::error(&format!("Failed to read test history: {}", e));
+            return exit_codes::FAILURE;
+        }
+    };
+    if history.tests.is_empty() {
+        Status::info("No test runs recorded yet. Run: foodshare-ios test");
+        return exit_codes::SUCCESS;
+    }
+
+    let flaky = history.flaky_tests();
+    if flaky.is_empty() {
+        Status::success(&format!("No flaky tests across {} recorded tests", history.tests.len()));
+        return exit_codes::SUCCESS;
+    }
+
+    textln!("{}", "Flakiest tests".bold());
+    textln!();
+    test_history::print_flaky_tests(&flaky[..flaky.len().min(top)]);
+    textln!();
+
+    let threshold = config.schema.test.flaky_threshold;
+    let quarantined = flaky.iter().filter(|f| history.is_quarantined(&f.identifier, threshold)).count();
+    Status::info(&format!(
+        "{} flaky tests, {} over the {:.0}% quarantine threshold",
+        flaky.len(),
+        quarantined,
+        threshold * 100.0
+    ));
+    exit_codes::SUCCESS
+}
+
+fn run_size(
+    archive: Option<&std::path::Path>,
+    baseline_path: &std::path::Path,
+    update_baseline: bool,
+    export_method: &str,
+    top: usize,
+    config: &Config,
+) -> i32 {
+    use foodshare_ios::app_size::{self, AppSizeReport};
+    use foodshare_ios::xcode;
+
+    let archive = match archive {
+        Some(archive) => archive.to_path_buf(),
+        None => {
+            let archive = PathBuf::from("build/FoodShare.xcarchive");
+            Status::info("Archiving...");
+            match xcode::archive("FoodShare", &archive) {
+                Ok(result) if result.success => archive,
+                Ok(result) => {
+                    Status::error("Archive failed");
+                    eprintln!("{}", result.stderr);
+                    return exit_codes::FAILURE;
+                }
+                Err(e) => {
+                    Status::error(&format!("Archive error: {}", e));
+                    return exit_codes::FAILURE;
+                }
+            }
+        }
+    };
+
+    let app = match app_size::archived_app(&archive) {
+        Ok(app) => app,
+        Err(e) => {
+            Status::error(&format!("Invalid archive: {}", e));
+            return exit_codes::FAILURE;
+        }
+    };
+    let mut report = match AppSizeReport::from_bundle(&app) {
+        Ok(report) => report,
+        Err(e) => {
+            Status::error(&format!("Failed to measure {}: {}", app.display(), e));
+            return exit_codes::FAILURE;
+        }
+    };
+
+    let car = app.join("Assets.car");
+    if car.exists() {
+        match app_size::asset_catalog_entries(&car) {
+            Ok(assets) => report = report.with_assets(assets),
+            Err(e) => Status::warning(&format!("Skipping asset breakdown: {}", e)),
+        }
+    }
+
+    Status::info("Exporting thinned variants...");
+    let thinning = app_size::export_thinned(&archive, std::path::Path::new("build/size-export"), export_method)
+        .and_then(|path| Ok(std::fs::read_to_string(path)?));
+    match thinning {
+        Ok(thinning) => report = report.with_thinning(&thinning),
+        Err(e) => Status::warning(&format!("Skipping thinning, download size is the bundle size: {}", e)),
+    }
+
+    let baseline = if baseline_path.exists() {
+        match AppSizeReport::load(baseline_path) {
+            Ok(baseline) => Some(baseline),
+            Err(e) => {
+                Status::warning(&format!("Ignoring unreadable baseline: {}", e));
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    textln!();
+    report.print(baseline.as_ref(), top);
+    textln!();
+
+    if update_baseline {
+        return match report.save(baseline_path) {
+            Ok(()) => {
+                Status::success(&format!("Baseline saved to {}", baseline_path.display()));
+                exit_codes::SUCCESS
+            }
+            Err(e) => {
+                Status::error(&format!("Failed to save baseline: {}", e));
                 exit_codes::FAILURE
             }
+        };
+    }
+    if baseline.is_none() {
+        Status::info(&format!(
+            "No baseline at {}; run with --update-baseline to create one",
+            baseline_path.display()
+        ));
+    }
+
+    let violations = report.budget_violations(baseline.as_ref(), &config.schema.size);
+    if violations.is_empty() {
+        Status::success("App size within budget");
+        exit_codes::SUCCESS
+    } else {
+        for violation in &violations {
+            Status::error(violation);
         }
+        exit_codes::FAILURE
+    }
+}
+
+fn run_crash(report_path: &std::path::Path, dsyms: &std::path::Path, all_threads: bool) -> i32 {
+    use foodshare_ios::crash::CrashReport;
+
+    let mut report = match CrashReport::load(report_path) {
+        Ok(report) => report,
         Err(e) => {
-            Status::error(&format!("Test error: {}", e));
-            exit_codes::FAILURE
+            Status::error(&format!("Failed to read crash report: {}", e));
+            return exit_codes::FAILURE;
+        }
+    };
+
+    if dsyms.exists() {
+        match report.symbolicate(dsyms) {
+            Ok(outcome) => {
+                for image in &outcome.mismatched {
+                    Status::warning(&format!("dSYM for {} is from a different build; skipped", image));
+                }
+                Status::info(&format!("Symbolicated {} frames", outcome.frames));
+            }
+            Err(e) => Status::warning(&format!("Symbolication failed: {}", e)),
         }
+    } else {
+        Status::warning(&format!(
+            "No dSYMs at {}; pass --dsyms with the build's archive or dSYM download",
+            dsyms.display()
+        ));
     }
+
+    textln!();
+    report.print(all_threads);
+    exit_codes::SUCCESS
 }
 
-fn run_app(clean: bool, logs: bool, release: bool, device: Option<&str>) -> i32 {
+fn run_app(clean: bool, logs: bool, release: bool, device: Option<&str>, device_type: &str) -> i32 {
     use foodshare_ios::{simulator, xcode};
 
+    match device_type {
+        "simulator" => {}
+        "physical" => return run_app_on_device(clean, logs, release, device),
+        other => {
+            Status::error(&format!("Unknown device type: {} (expected simulator or physical)", other));
+            return exit_codes::VALIDATION_ERROR;
+        }
+    }
+
     let device_name = device.unwrap_or("iPhone 17 Pro Max");
     let configuration = if release { "Release" } else { "Debug" };
     let destination = format!("platform=iOS Simulator,name={}", device_name);
@@ -853,69 +1912,374 @@ fn run_app(clean: bool, logs: bool, release: bool, device: Option<&str>) -> i32
     exit_codes::SUCCESS
 }
 
-fn run_simulator(action: &str, device: Option<&str>) -> i32 {
-    use foodshare_ios::simulator;
+fn run_app_on_device(clean: bool, logs: bool, release: bool, device: Option<&str>) -> i32 {
+    use foodshare_ios::{device as physical, xcode};
 
-    match action {
-        "list" => {
-            match simulator::list_devices() {
-                Ok(devices) => {
-                    println!("Available Simulators:");
-                    for d in devices.iter().filter(|d| d.is_available) {
-                        let status = if d.state == "Booted" { "🟢" } else { "⚪" };
-                        println!("  {} {} ({})", status, d.name, d.runtime);
-                    }
-                    exit_codes::SUCCESS
-                }
-                Err(e) => {
-                    Status::error(&format!("Failed to list simulators: {}", e));
-                    exit_codes::FAILURE
-                }
-            }
-        }
-        "boot" => {
-            let device_name = device.unwrap_or("iPhone 17 Pro Max");
-            match simulator::boot(device_name) {
-                Ok(_) => {
-                    Status::success(&format!("Booted {}", device_name));
-                    exit_codes::SUCCESS
-                }
-                Err(e) => {
-                    Status::error(&format!("Failed to boot: {}", e));
-                    exit_codes::FAILURE
-                }
-            }
+    let configuration = if release { "Release" } else { "Debug" };
+
+    // Step 1: Find the device
+    let target = match physical::list_devices().and_then(|devices| physical::select_device(&devices, device)) {
+        Ok(target) => target,
+        Err(e) => {
+            Status::error(&e.to_string());
+            return exit_codes::FAILURE;
         }
-        "shutdown" => {
-            match simulator::shutdown_all() {
-                Ok(_) => {
-                    Status::success("Shutdown all simulators");
-                    exit_codes::SUCCESS
-                }
-                Err(e) => {
-                    Status::error(&format!("Failed to shutdown: {}", e));
-                    exit_codes::FAILURE
-                }
-            }
+    };
+    if !target.available {
+        Status::error(&format!("{} is not paired or not reachable", target.name));
+        return exit_codes::FAILURE;
+    }
+    Status::info(&format!("Using {} ({})", target.name, target.udid));
+
+    // Step 2: Check signing before spending time on a build
+    Status::info("Checking code signing...");
+    let settings = match xcode::build_settings("FoodShare", configuration, "iphoneos") {
+        Ok(settings) => settings,
+        Err(e) => {
+            Status::error(&e.to_string());
+            return exit_codes::FAILURE;
         }
-        _ => {
-            Status::error(&format!("Unknown action: {}", action));
-            exit_codes::FAILURE
+    };
+    let identities = physical::codesigning_identities().unwrap_or_default();
+    let issues = physical::signing_issues(&settings, &identities);
+    if !issues.is_empty() {
+        Status::error("Code signing is not set up for device builds:");
+        for issue in &issues {
+            eprintln!("  - {}", issue);
         }
+        return exit_codes::FAILURE;
     }
-}
 
-fn run_doctor(json: bool) -> i32 {
-    use foodshare_ios::{swift_tools, xcode};
-
-    if json {
-        // TODO: JSON output
-        Status::info("JSON output not yet implemented");
-        return exit_codes::SUCCESS;
-    }
+    // Step 3: Build
+    Status::info(&format!("Building {} configuration...", configuration));
+    let destination = format!("id={}", target.udid);
+    match xcode::build_with_args("FoodShare", configuration, &destination, clean, &["-allowProvisioningUpdates"]) {
+        Ok(result) => {
+            if !result.success {
+                Status::error("Build failed");
+                eprintln!("{}", result.stderr);
+                return exit_codes::FAILURE;
+            }
+            Status::success("Build succeeded");
+        }
+        Err(e) => {
+            Status::error(&format!("Build error: {}", e));
+            return exit_codes::FAILURE;
+        }
+    }
+
+    // Step 4: Find the built app
+    let app_path = std::env::current_dir()
+        .unwrap_or_default()
+        .join("build")
+        .join("Build")
+        .join("Products")
+        .join(format!("{}-iphoneos", configuration))
+        .join("FoodShare.app");
+
+    if !app_path.exists() {
+        Status::error(&format!("App not found at: {}", app_path.display()));
+        return exit_codes::FAILURE;
+    }
+
+    // Step 5: Install
+    Status::info("Installing app...");
+    match physical::install_app(&target.udid, &app_path.to_string_lossy()) {
+        Ok(result) if result.success => Status::success("App installed"),
+        Ok(result) => {
+            Status::error("Install failed");
+            eprintln!("{}", result.stderr);
+            return exit_codes::FAILURE;
+        }
+        Err(e) => {
+            Status::error(&format!("Install error: {}", e));
+            return exit_codes::FAILURE;
+        }
+    }
+
+    // Step 6: Launch, attaching to the console if logs were requested
+    let bundle_id = settings
+        .get("PRODUCT_BUNDLE_IDENTIFIER")
+        .map_or("com.flutterflow.foodshare", String::as_str);
+    Status::info("Launching app...");
+    if logs {
+        Status::info("Streaming console output (Ctrl+C to stop)...");
+        return match physical::launch_app_with_console(&target.udid, bundle_id) {
+            Ok(code) => code,
+            Err(e) => {
+                Status::error(&format!("Launch error: {}", e));
+                exit_codes::FAILURE
+            }
+        };
+    }
+    match physical::launch_app(&target.udid, bundle_id) {
+        Ok(result) if result.success => {
+            Status::success("App launched");
+            exit_codes::SUCCESS
+        }
+        Ok(result) => {
+            Status::error("Launch failed (is the device unlocked?)");
+            eprintln!("{}", result.stderr);
+            exit_codes::FAILURE
+        }
+        Err(e) => {
+            Status::error(&format!("Launch error: {}", e));
+            exit_codes::FAILURE
+        }
+    }
+}
+
+fn run_devices() -> i32 {
+    use foodshare_ios::device;
+
+    match device::list_devices() {
+        Ok(devices) if devices.is_empty() => {
+            Status::warning("No devices found. Connect an iPhone and trust this Mac");
+            exit_codes::SUCCESS
+        }
+        Ok(devices) => {
+            textln!("Connected Devices:");
+            for d in &devices {
+                let status = if d.available { "🟢" } else { "⚪" };
+                let details: Vec<&str> = [d.model.as_deref(), d.os_version.as_deref()]
+                    .into_iter()
+                    .flatten()
+                    .collect();
+                textln!("  {} {} ({}) {}", status, d.name, details.join(", "), d.udid.dimmed());
+            }
+            exit_codes::SUCCESS
+        }
+        Err(e) => {
+            Status::error(&format!("Failed to list devices: {}", e));
+            exit_codes::FAILURE
+        }
+    }
+}
+
+fn run_signing(configuration: &str, device: Option<&str>, list: bool) -> i32 {
+    use foodshare_ios::device as physical;
+    use foodshare_ios::signing;
+
+    if list {
+        match signing::keychain_certificates() {
+            Ok(certificates) => {
+                textln!("{}", "Certificates".bold());
+                for cert in &certificates {
+                    let key = if cert.has_private_key { "" } else { " (no private key)" };
+                    textln!("  {} expires {}{}", cert.name, cert.expires.format("%Y-%m-%d"), key);
+                }
+            }
+            Err(e) => Status::warning(&format!("Failed to read certificates: {}", e)),
+        }
+        textln!();
+        textln!("{}", "Provisioning profiles".bold());
+        for profile in signing::installed_profiles() {
+            textln!(
+                "  {} ({}, {}) {} expires {}",
+                profile.name,
+                profile.kind,
+                profile.team,
+                profile.app_id,
+                profile.expires.format("%Y-%m-%d")
+            );
+        }
+        textln!();
+    }
+
+    // Resolve a device name to the UDID profiles list
+    let udid = match device {
+        Some(query) => match physical::list_devices().and_then(|d| physical::select_device(&d, Some(query))) {
+            Ok(device) => Some(device.udid),
+            Err(_) => Some(query.to_string()),
+        },
+        None => None,
+    };
+
+    match signing::check_project("FoodShare", configuration, udid.as_deref()) {
+        Ok(checks) => {
+            if print_signing_checks(&checks) {
+                exit_codes::FAILURE
+            } else {
+                Status::success("Code signing is ready for device builds");
+                exit_codes::SUCCESS
+            }
+        }
+        Err(e) => {
+            Status::error(&format!("Signing check failed: {}", e));
+            exit_codes::FAILURE
+        }
+    }
+}
+
+/// Print signing checks; returns whether any failed
+fn print_signing_checks(checks: &[foodshare_core::health::CheckResult]) -> bool {
+    use foodshare_core::health::HealthStatus;
+
+    for check in checks {
+        match check.status {
+            HealthStatus::Healthy => {
+                let profile = check
+                    .details
+                    .get("profile")
+                    .or_else(|| check.details.get("certificate"))
+                    .map(|name| format!(" ({})", name))
+                    .unwrap_or_default();
+                Status::success(&format!("{}{}", check.name, profile));
+            }
+            HealthStatus::Degraded => {
+                Status::warning(&format!("{}: {}", check.name, check.message.as_deref().unwrap_or_default()));
+            }
+            _ => {
+                Status::error(&format!("{}: {}", check.name, check.message.as_deref().unwrap_or_default()));
+            }
+        }
+    }
+    checks.iter().any(|c| c.status == HealthStatus::Unhealthy)
+}
+
+fn run_simulator(action: SimulatorAction) -> i32 {
+    use foodshare_ios::simulator::{self, StatusBarOverride};
+
+    // simctl reports most failures through its exit code rather than an error
+    let check = |result: foodshare_core::error::Result<foodshare_core::process::CommandResult>,
+                 action: &str| match result {
+        Ok(result) if result.success => Some(result),
+        Ok(result) => {
+            Status::error(&format!("Failed to {}: {}", action, result.stderr.trim()));
+            None
+        }
+        Err(e) => {
+            Status::error(&format!("Failed to {}: {}", action, e));
+            None
+        }
+    };
+
+    match action {
+        SimulatorAction::List => {
+            match simulator::list_devices() {
+                Ok(devices) => {
+                    textln!("Available Simulators:");
+                    for d in devices.iter().filter(|d| d.is_available) {
+                        let status = if d.state == "Booted" { "🟢" } else { "⚪" };
+                        textln!("  {} {} ({})", status, d.name, d.runtime);
+                    }
+                    exit_codes::SUCCESS
+                }
+                Err(e) => {
+                    Status::error(&format!("Failed to list simulators: {}", e));
+                    exit_codes::FAILURE
+                }
+            }
+        }
+        SimulatorAction::Boot { device } => {
+            match simulator::boot(&device) {
+                Ok(_) => {
+                    Status::success(&format!("Booted {}", device));
+                    exit_codes::SUCCESS
+                }
+                Err(e) => {
+                    Status::error(&format!("Failed to boot: {}", e));
+                    exit_codes::FAILURE
+                }
+            }
+        }
+        SimulatorAction::Shutdown { device } => {
+            let result = match device {
+                Some(ref device) => simulator::shutdown(device),
+                None => simulator::shutdown_all(),
+            };
+            match result {
+                Ok(_) => {
+                    Status::success(&format!("Shutdown {}", device.as_deref().unwrap_or("all simulators")));
+                    exit_codes::SUCCESS
+                }
+                Err(e) => {
+                    Status::error(&format!("Failed to shutdown: {}", e));
+                    exit_codes::FAILURE
+                }
+            }
+        }
+        SimulatorAction::Erase { device } => {
+            // Erasing and cloning need a shut down simulator; already being
+            // shut down is not an error here
+            let _ = simulator::shutdown(&device);
+            match check(simulator::erase(&device), "erase") {
+                Some(_) => {
+                    Status::success(&format!("Erased {}", device));
+                    exit_codes::SUCCESS
+                }
+                None => exit_codes::FAILURE,
+            }
+        }
+        SimulatorAction::Clone { name, device } => {
+            let _ = simulator::shutdown(&device);
+            match check(simulator::clone(&device, &name), "clone") {
+                Some(result) => {
+                    Status::success(&format!("Cloned {} as {} ({})", device, name, result.stdout.trim()));
+                    exit_codes::SUCCESS
+                }
+                None => exit_codes::FAILURE,
+            }
+        }
+        SimulatorAction::StatusBar { device, time, battery, clear } => {
+            if clear {
+                return match check(simulator::clear_status_bar(&device), "clear status bar") {
+                    Some(_) => {
+                        Status::success("Status bar restored");
+                        exit_codes::SUCCESS
+                    }
+                    None => exit_codes::FAILURE,
+                };
+            }
+            let status_bar = StatusBarOverride {
+                time: Some(time),
+                battery_level: Some(battery),
+                ..StatusBarOverride::app_store()
+            };
+            match check(simulator::override_status_bar(&device, &status_bar), "override status bar") {
+                Some(_) => {
+                    Status::success("Status bar overridden (use --clear to restore)");
+                    exit_codes::SUCCESS
+                }
+                None => exit_codes::FAILURE,
+            }
+        }
+        SimulatorAction::Screenshot { output, device } => {
+            match check(simulator::screenshot(&device, &output.to_string_lossy()), "take screenshot") {
+                Some(_) => {
+                    Status::success(&format!("Saved {}", output.display()));
+                    exit_codes::SUCCESS
+                }
+                None => exit_codes::FAILURE,
+            }
+        }
+        SimulatorAction::RecordVideo { output, device } => {
+            Status::info("Recording (Ctrl+C to stop)...");
+            match simulator::record_video(&device, &output.to_string_lossy()) {
+                Ok(_) if output.exists() => {
+                    Status::success(&format!("Saved {}", output.display()));
+                    exit_codes::SUCCESS
+                }
+                Ok(code) => {
+                    Status::error("Recording failed");
+                    code.max(exit_codes::FAILURE)
+                }
+                Err(e) => {
+                    Status::error(&format!("Failed to record video: {}", e));
+                    exit_codes::FAILURE
+                }
+            }
+        }
+    }
+}
+
+fn run_doctor(fix: bool) -> i32 {
+    use foodshare_ios::{swift_tools, xcode};
 
-    println!("Environment Check");
-    println!();
+    let mut missing = Vec::new();
+
+    textln!("Environment Check");
+    textln!();
 
     // Xcode
     if xcode::is_xcode_available() {
@@ -938,6 +2302,7 @@ fn run_doctor(json: bool) -> i32 {
         Status::success("swiftformat: installed");
     } else {
         Status::warning("swiftformat: not found (optional)");
+        missing.push("swiftformat");
     }
 
     // swiftlint
@@ -945,11 +2310,67 @@ fn run_doctor(json: bool) -> i32 {
         Status::success("swiftlint: installed");
     } else {
         Status::warning("swiftlint: not found (optional)");
+        missing.push("swiftlint");
+    }
+
+    // Code signing, only meaningful inside the project
+    if xcode::is_xcode_available() && std::path::Path::new("FoodShare.xcodeproj").exists() {
+        textln!();
+        textln!("Code Signing");
+        textln!();
+        match foodshare_ios::signing::check_project("FoodShare", "Debug", None) {
+            Ok(checks) => {
+                print_signing_checks(&checks);
+            }
+            Err(e) => Status::warning(&format!("Code signing: could not check ({})", e)),
+        }
     }
 
+    if fix {
+        return install_missing_tools(&missing);
+    }
     exit_codes::SUCCESS
 }
 
+/// Offer to install missing tools with Homebrew (`doctor --fix`)
+fn install_missing_tools(missing: &[&str]) -> i32 {
+    use foodshare_core::process;
+
+    if missing.is_empty() {
+        return exit_codes::SUCCESS;
+    }
+    textln!();
+    if !process::command_exists("brew") {
+        Status::warning(&format!("Homebrew not found; install {} manually", missing.join(", ")));
+        return exit_codes::FAILURE;
+    }
+
+    let selected = match prompt::multi_select("Install with Homebrew:", missing, &vec![true; missing.len()]) {
+        Ok(selected) => selected,
+        Err(e) => {
+            Status::error(&e.to_string());
+            return exit_codes::FAILURE;
+        }
+    };
+
+    let mut exit_code = exit_codes::SUCCESS;
+    for (step, tool) in selected.iter().map(|&i| missing[i]).enumerate() {
+        Status::step(step + 1, selected.len(), &format!("brew install {}", tool));
+        match process::run_command("brew", &["install", tool]) {
+            Ok(result) if result.success => Status::success(&format!("{}: installed", tool)),
+            Ok(result) => {
+                Status::error(&format!("{}: brew install failed: {}", tool, result.stderr.trim()));
+                exit_code = exit_codes::FAILURE;
+            }
+            Err(e) => {
+                Status::error(&format!("{}: {}", tool, e));
+                exit_code = exit_codes::FAILURE;
+            }
+        }
+    }
+    exit_code
+}
+
 fn run_verify() -> i32 {
     Status::info("Verifying setup...");
 
@@ -975,13 +2396,13 @@ fn run_verify() -> i32 {
 
 
 fn run_pre_push(
-    _remote: Option<&str>,
-    _url: Option<&str>,
     fail_fast: bool,
     release: bool,
     quick: bool,
     skip: Vec<String>,
     detailed: bool,
+    build_cache: bool,
+    project_config: &Config,
 ) -> i32 {
     use foodshare_ios::hooks::{run_pre_push_checks, print_pre_push_summary, PrePushConfig};
 
@@ -993,28 +2414,35 @@ fn run_pre_push(
         release,
         quick_mode,
         skip_checks: skip,
+        build_cache,
     };
 
     if detailed {
-        println!("Pre-push configuration:");
-        println!("  fail_fast: {}", config.fail_fast);
-        println!("  release: {}", config.release);
-        println!("  quick_mode: {}", config.quick_mode);
-        println!("  skip_checks: {:?}", config.skip_checks);
-        println!();
+        textln!("Pre-push configuration:");
+        textln!("  fail_fast: {}", config.fail_fast);
+        textln!("  release: {}", config.release);
+        textln!("  quick_mode: {}", config.quick_mode);
+        textln!("  skip_checks: {:?}", config.skip_checks);
+        textln!("  build_cache: {}", config.build_cache);
+        textln!();
     }
 
     let results = run_pre_push_checks(&config);
-    print_pre_push_summary(&results)
+    let code = print_pre_push_summary(&results);
+    if code != exit_codes::SUCCESS && config.fail_fast {
+        return code;
+    }
+    code.max(foodshare_hooks::plugins::run_for_hook(Hook::PrePush, &[], &project_config.schema.plugins))
 }
 
-fn run_deps(action: DepsAction) -> i32 {
+fn run_deps(action: DepsAction, config: &Config) -> i32 {
     use foodshare_ios::swift_tools;
 
     // Extract path and determine action type
     let (path, is_update) = match &action {
         DepsAction::Resolve { path } => (path, false),
         DepsAction::Update { path } => (path, true),
+        DepsAction::Audit { path, offline } => return run_deps_audit(path, *offline, config),
     };
 
     // Check for Package.swift before proceeding
@@ -1090,14 +2518,14 @@ fn run_project(action: ProjectAction) -> i32 {
                 Ok(proj) => {
                     match proj.status(&target, &source_dir) {
                         Ok(status) => {
-                            println!();
+                            textln!();
                             status.print();
 
                             if status.is_clean() {
-                                println!();
+                                textln!();
                                 Status::success("Project is clean!");
                             } else {
-                                println!();
+                                textln!();
                                 Status::warning("Project has issues. Run subcommands for details.");
                             }
                             exit_codes::SUCCESS
@@ -1123,13 +2551,13 @@ fn run_project(action: ProjectAction) -> i32 {
                             if missing.is_empty() {
                                 Status::success("No missing files found");
                             } else {
-                                println!("{}", "Missing files (on disk but not in build phase):".bold());
-                                println!();
+                                textln!("{}", "Missing files (on disk but not in build phase):".bold());
+                                textln!();
                                 for path in &missing {
-                                    println!("  {} {}", "+".green(), path.display());
+                                    textln!("  {} {}", "+".green(), path.display());
                                 }
-                                println!();
-                                println!("Total: {} file(s)", missing.len());
+                                textln!();
+                                textln!("Total: {} file(s)", missing.len());
                             }
                             exit_codes::SUCCESS
                         }
@@ -1144,110 +2572,576 @@ fn run_project(action: ProjectAction) -> i32 {
                     exit_codes::FAILURE
                 }
             }
-        }
+        }
+
+        ProjectAction::Broken { project } => {
+            match XcodeProject::open(&project) {
+                Ok(proj) => {
+                    let broken = proj.find_broken_references();
+                    if broken.is_empty() {
+                        Status::success("No broken references found");
+                    } else {
+                        textln!("{}", "Broken references (in project but file doesn't exist):".bold());
+                        textln!();
+                        for fr in &broken {
+                            textln!("  {} {}", "✗".red(), fr.path);
+                        }
+                        textln!();
+                        textln!("Total: {} reference(s)", broken.len());
+                    }
+                    exit_codes::SUCCESS
+                }
+                Err(e) => {
+                    Status::error(&format!("Failed to open project: {}", e));
+                    exit_codes::FAILURE
+                }
+            }
+        }
+
+        ProjectAction::Duplicates { project, target } => {
+            match XcodeProject::open(&project) {
+                Ok(proj) => {
+                    let duplicates = proj.find_duplicate_build_files(&target);
+                    if duplicates.is_empty() {
+                        Status::success("No duplicate references found");
+                    } else {
+                        textln!("{}", "Duplicate build file references:".bold());
+                        textln!();
+                        for (file_ref_id, build_files) in &duplicates {
+                            textln!("  File ref {}: {} duplicates", file_ref_id, build_files.len());
+                        }
+                        textln!();
+                        textln!("Total: {} file(s) with duplicates", duplicates.len());
+                    }
+                    exit_codes::SUCCESS
+                }
+                Err(e) => {
+                    Status::error(&format!("Failed to open project: {}", e));
+                    exit_codes::FAILURE
+                }
+            }
+        }
+
+        ProjectAction::Add { files, project, target, group, dry_run } => {
+            if dry_run {
+                Status::info("Dry run mode - no changes will be made");
+            }
+
+            match XcodeProject::open(&project) {
+                Ok(mut proj) => {
+                    let mut added = 0;
+                    let mut skipped = 0;
+                    let mut failed = 0;
+
+                    for file in &files {
+                        match proj.add_file(file, &target, group.as_deref()) {
+                            Ok(result) => {
+                                if result.already_exists {
+                                    textln!("  {} {} (already in project)", "~".yellow(), file.display());
+                                    skipped += 1;
+                                } else {
+                                    textln!("  {} {}", "+".green(), file.display());
+                                    added += 1;
+                                }
+                            }
+                            Err(e) => {
+                                textln!("  {} {} - {}", "✗".red(), file.display(), e);
+                                failed += 1;
+                            }
+                        }
+                    }
+
+                    textln!();
+                    textln!("Added: {}, Skipped: {}, Failed: {}", added, skipped, failed);
+
+                    if !dry_run && added > 0 {
+                        match proj.save() {
+                            Ok(()) => {
+                                Status::success("Project saved (backup created at project.pbxproj.backup)");
+                            }
+                            Err(e) => {
+                                Status::error(&format!("Failed to save project: {}", e));
+                                return exit_codes::FAILURE;
+                            }
+                        }
+                    } else if dry_run && added > 0 {
+                        Status::info("Run without --dry-run to apply changes");
+                    }
+
+                    if failed > 0 {
+                        exit_codes::FAILURE
+                    } else {
+                        exit_codes::SUCCESS
+                    }
+                }
+                Err(e) => {
+                    Status::error(&format!("Failed to open project: {}", e));
+                    exit_codes::FAILURE
+                }
+            }
+        }
+
+        ProjectAction::Remove { files, project, dry_run } => {
+            if dry_run {
+                Status::info("Dry run mode - no changes will be made");
+            }
+
+            let mut proj = match XcodeProject::open(&project) {
+                Ok(proj) => proj,
+                Err(e) => {
+                    Status::error(&format!("Failed to open project: {}", e));
+                    return exit_codes::FAILURE;
+                }
+            };
+
+            let mut removed = 0;
+            let mut failed = 0;
+            for file in &files {
+                match proj.remove_file(file) {
+                    Ok(result) => {
+                        textln!(
+                            "  {} {} ({} build file(s))",
+                            "-".red(),
+                            file.display(),
+                            result.build_file_ids.len()
+                        );
+                        removed += 1;
+                    }
+                    Err(e) => {
+                        textln!("  {} {} - {}", "✗".red(), file.display(), e);
+                        failed += 1;
+                    }
+                }
+            }
+
+            textln!();
+            textln!("Removed: {}, Failed: {}", removed, failed);
+            finish_project_edit(&proj, dry_run, removed > 0, failed > 0)
+        }
+
+        ProjectAction::Rename { from, to, project, project_only, dry_run } => {
+            if dry_run {
+                Status::info("Dry run mode - no changes will be made");
+            }
+
+            let mut proj = match XcodeProject::open(&project) {
+                Ok(proj) => proj,
+                Err(e) => {
+                    Status::error(&format!("Failed to open project: {}", e));
+                    return exit_codes::FAILURE;
+                }
+            };
+
+            if let Err(e) = proj.rename_file(&from, &to) {
+                Status::error(&format!("Rename failed: {}", e));
+                return exit_codes::FAILURE;
+            }
+            textln!("  {} {} → {}", "~".yellow(), from.display(), to.display());
+
+            if !project_only && !dry_run {
+                let old_path = proj.project_dir.join(&from);
+                let new_path = proj.project_dir.join(&to);
+                if old_path.exists() {
+                    if let Err(e) = std::fs::rename(&old_path, &new_path) {
+                        Status::error(&format!("Failed to rename file on disk: {}", e));
+                        return exit_codes::FAILURE;
+                    }
+                }
+            }
+
+            textln!();
+            finish_project_edit(&proj, dry_run, true, false)
+        }
+
+        ProjectAction::Move { files, group, project, dry_run } => {
+            if dry_run {
+                Status::info("Dry run mode - no changes will be made");
+            }
+
+            let mut proj = match XcodeProject::open(&project) {
+                Ok(proj) => proj,
+                Err(e) => {
+                    Status::error(&format!("Failed to open project: {}", e));
+                    return exit_codes::FAILURE;
+                }
+            };
+
+            let mut moved = 0;
+            let mut failed = 0;
+            for file in &files {
+                match proj.move_file_to_group(file, &group) {
+                    Ok(_) => {
+                        textln!("  {} {} → {}", "→".cyan(), file.display(), group);
+                        moved += 1;
+                    }
+                    Err(e) => {
+                        textln!("  {} {} - {}", "✗".red(), file.display(), e);
+                        failed += 1;
+                    }
+                }
+            }
+
+            textln!();
+            textln!("Moved: {}, Failed: {}", moved, failed);
+            finish_project_edit(&proj, dry_run, moved > 0, failed > 0)
+        }
+
+        ProjectAction::Settings { action } => run_project_settings(action),
+
+        ProjectAction::Scheme { action } => run_project_scheme(action),
+
+        ProjectAction::Fix { project, target, source_dir, dry_run } => {
+            if dry_run {
+                Status::info("Dry run mode - no changes will be made");
+            }
+
+            let mut proj = match XcodeProject::open(&project) {
+                Ok(proj) => proj,
+                Err(e) => {
+                    Status::error(&format!("Failed to open project: {}", e));
+                    return exit_codes::FAILURE;
+                }
+            };
+
+            let report = match proj.fix(&target, &source_dir) {
+                Ok(report) => report,
+                Err(e) => {
+                    Status::error(&format!("Fix failed: {}", e));
+                    return exit_codes::FAILURE;
+                }
+            };
+            if report.is_empty() {
+                Status::success("Project is clean!");
+                return exit_codes::SUCCESS;
+            }
+
+            report.print();
+            textln!();
+            textln!("{}", "project.pbxproj changes:".bold());
+            for line in &report.diff {
+                if line.starts_with('-') {
+                    textln!("  {}", line.red());
+                } else {
+                    textln!("  {}", line.green());
+                }
+            }
+            textln!();
+            finish_project_edit(&proj, dry_run, true, false)
+        }
+        ProjectAction::ResolveConflicts { project, dry_run } => {
+            if dry_run {
+                Status::info("Dry run mode - no changes will be made");
+            }
+
+            let (proj, report) = match XcodeProject::resolve_conflicts(&project) {
+                Ok(Some(resolved)) => resolved,
+                Ok(None) => {
+                    Status::success("No conflict markers in project.pbxproj");
+                    return exit_codes::SUCCESS;
+                }
+                Err(e) => {
+                    Status::error(&format!("Failed to resolve conflicts: {}", e));
+                    return exit_codes::FAILURE;
+                }
+            };
+
+            report.print();
+            textln!();
+            textln!("{}", "Changes to our side:".bold());
+            for line in &report.diff {
+                if line.starts_with('-') {
+                    textln!("  {}", line.red());
+                } else {
+                    textln!("  {}", line.green());
+                }
+            }
+            textln!();
+            if !report.merge.conflicts.is_empty() {
+                Status::warning("Both sides changed the properties marked !, ours were kept - review them");
+            }
+
+            let code = finish_project_edit(&proj, dry_run, true, false);
+            if code == exit_codes::SUCCESS && !dry_run {
+                Status::info(&format!(
+                    "Review, then mark resolved: git add {}",
+                    project.join("project.pbxproj").display()
+                ));
+            }
+            code
+        }
+    }
+}
+
+fn run_project_settings(action: SettingsAction) -> i32 {
+    use foodshare_ios::xcodeproj::{BuildConfiguration, XcodeProject};
+    use owo_colors::OwoColorize;
+
+    let open = |project: &PathBuf| {
+        XcodeProject::open(project).map_err(|e| {
+            Status::error(&format!("Failed to open project: {}", e));
+        })
+    };
+    // "Release" is a project-level configuration, "FoodShare:Release" a target's
+    let find_config = |proj: &XcodeProject, spec: &str| -> Option<BuildConfiguration> {
+        let (target, name) = match spec.split_once(':') {
+            Some((target, name)) => (Some(target), name),
+            None => (None, spec),
+        };
+        match proj.build_configurations(target) {
+            Ok(configs) => {
+                let config = configs.into_iter().find(|config| config.name == name);
+                if config.is_none() {
+                    Status::error(&format!("Configuration not found: {}", spec));
+                }
+                config
+            }
+            Err(e) => {
+                Status::error(&e.to_string());
+                None
+            }
+        }
+    };
+
+    match action {
+        SettingsAction::Get { key, project, target, configuration } => {
+            let Ok(proj) = open(&project) else {
+                return exit_codes::FAILURE;
+            };
+            let configs = match proj.build_configurations(target.as_deref()) {
+                Ok(configs) => configs,
+                Err(e) => {
+                    Status::error(&e.to_string());
+                    return exit_codes::FAILURE;
+                }
+            };
+
+            for build_config in configs
+                .iter()
+                .filter(|c| configuration.as_deref().is_none_or(|name| c.name == name))
+            {
+                match key {
+                    Some(ref key) => {
+                        let value = build_config.settings.get(key).map_or("(not set)", String::as_str);
+                        textln!("{}: {}", build_config.name.bold(), value);
+                    }
+                    None => {
+                        textln!("{}", build_config.name.bold());
+                        for (key, value) in &build_config.settings {
+                            textln!("  {} = {}", key.cyan(), value);
+                        }
+                        textln!();
+                    }
+                }
+            }
+            exit_codes::SUCCESS
+        }
+
+        SettingsAction::Set { key, value, project, target, configuration, dry_run } => {
+            if dry_run {
+                Status::info("Dry run mode - no changes will be made");
+            }
+
+            let Ok(mut proj) = open(&project) else {
+                return exit_codes::FAILURE;
+            };
+            let before = proj.build_configurations(target.as_deref()).unwrap_or_default();
+            let updated = match proj.set_build_setting(target.as_deref(), configuration.as_deref(), &key, &value) {
+                Ok(updated) => updated,
+                Err(e) => {
+                    Status::error(&format!("Failed to set {}: {}", key, e));
+                    return exit_codes::FAILURE;
+                }
+            };
+
+            for name in &updated {
+                let old = before
+                    .iter()
+                    .find(|c| &c.name == name)
+                    .and_then(|c| c.settings.get(&key))
+                    .map_or("(not set)", String::as_str);
+                textln!("  {} {}: {} → {}", "~".yellow(), name, old.red(), value.green());
+            }
+            textln!();
+            finish_project_edit(&proj, dry_run, true, false)
+        }
+
+        SettingsAction::Diff { left, right, project } => {
+            let Ok(proj) = open(&project) else {
+                return exit_codes::FAILURE;
+            };
+            let (Some(left_config), Some(right_config)) =
+                (find_config(&proj, &left), find_config(&proj, &right))
+            else {
+                return exit_codes::FAILURE;
+            };
+
+            let diffs = left_config.diff(&right_config);
+            if diffs.is_empty() {
+                Status::success(&format!("{} and {} have the same settings", left, right));
+                return exit_codes::SUCCESS;
+            }
+
+            textln!("{}", format!("{} → {}", left, right).bold());
+            textln!();
+            for diff in &diffs {
+                textln!(
+                    "  {}: {} → {}",
+                    diff.key.cyan(),
+                    diff.left.as_deref().unwrap_or("(not set)").red(),
+                    diff.right.as_deref().unwrap_or("(not set)").green()
+                );
+            }
+            textln!();
+            textln!("Total: {} setting(s) differ", diffs.len());
+            exit_codes::SUCCESS
+        }
+    }
+}
+
+fn run_project_scheme(action: SchemeAction) -> i32 {
+    use foodshare_ios::scheme::{Scheme, TestPlan};
+    use foodshare_ios::xcodeproj::XcodeProject;
+    use owo_colors::OwoColorize;
+
+    let on_off = |enabled: bool| if enabled { "on".green().to_string() } else { "off".dimmed().to_string() };
+
+    match action {
+        SchemeAction::List { project } => {
+            let schemes = match Scheme::list(&project) {
+                Ok(schemes) => schemes,
+                Err(e) => {
+                    Status::error(&format!("Failed to read schemes: {}", e));
+                    return exit_codes::FAILURE;
+                }
+            };
+            if schemes.is_empty() {
+                Status::warning("No schemes found (Xcode's autocreated schemes are not stored)");
+                return exit_codes::SUCCESS;
+            }
 
-        ProjectAction::Broken { project } => {
-            match XcodeProject::open(&project) {
-                Ok(proj) => {
-                    let broken = proj.find_broken_references();
-                    if broken.is_empty() {
-                        Status::success("No broken references found");
-                    } else {
-                        println!("{}", "Broken references (in project but file doesn't exist):".bold());
-                        println!();
-                        for fr in &broken {
-                            println!("  {} {}", "✗".red(), fr.path);
-                        }
-                        println!();
-                        println!("Total: {} reference(s)", broken.len());
+            for scheme in &schemes {
+                let kind = if scheme.shared { "shared" } else { "user" };
+                textln!("{} ({})", scheme.name.bold(), kind);
+                textln!("  Coverage: {}", on_off(scheme.code_coverage));
+                if scheme.test_plans.is_empty() {
+                    for testable in &scheme.testables {
+                        let skipped = if testable.skipped { " (skipped)" } else { "" };
+                        textln!(
+                            "  Tests: {} parallel {}{}",
+                            testable.target.name,
+                            on_off(testable.parallelizable),
+                            skipped
+                        );
                     }
-                    exit_codes::SUCCESS
                 }
-                Err(e) => {
-                    Status::error(&format!("Failed to open project: {}", e));
-                    exit_codes::FAILURE
+                for path in scheme.test_plan_paths(&project) {
+                    match TestPlan::load(&path) {
+                        Ok(plan) => {
+                            for (name, parallel) in plan.test_targets() {
+                                textln!(
+                                    "  Test plan {}: {} parallel {}",
+                                    path.display(),
+                                    name,
+                                    on_off(parallel)
+                                );
+                            }
+                        }
+                        Err(e) => textln!("  Test plan {}: {}", path.display(), e.to_string().red()),
+                    }
                 }
             }
+            textln!();
+            textln!("Total: {} scheme(s)", schemes.len());
+            exit_codes::SUCCESS
         }
 
-        ProjectAction::Duplicates { project, target } => {
-            match XcodeProject::open(&project) {
-                Ok(proj) => {
-                    let duplicates = proj.find_duplicate_build_files(&target);
-                    if duplicates.is_empty() {
-                        Status::success("No duplicate references found");
-                    } else {
-                        println!("{}", "Duplicate build file references:".bold());
-                        println!();
-                        for (file_ref_id, build_files) in &duplicates {
-                            println!("  File ref {}: {} duplicates", file_ref_id, build_files.len());
-                        }
-                        println!();
-                        println!("Total: {} file(s) with duplicates", duplicates.len());
-                    }
-                    exit_codes::SUCCESS
-                }
+        SchemeAction::CreateCi { name, target, project, force } => {
+            let proj = match XcodeProject::open(&project) {
+                Ok(proj) => proj,
                 Err(e) => {
                     Status::error(&format!("Failed to open project: {}", e));
-                    exit_codes::FAILURE
+                    return exit_codes::FAILURE;
+                }
+            };
+            let scheme = match Scheme::generate(&proj, &name, &target, true) {
+                Ok(scheme) => scheme,
+                Err(e) => {
+                    Status::error(&e.to_string());
+                    return exit_codes::FAILURE;
                 }
+            };
+            if scheme.path.exists() && !force {
+                Status::error(&format!(
+                    "{} already exists (use --force to replace it)",
+                    scheme.path.display()
+                ));
+                return exit_codes::FAILURE;
+            }
+            if let Err(e) = scheme.save() {
+                Status::error(&format!("Failed to write scheme: {}", e));
+                return exit_codes::FAILURE;
+            }
+
+            for testable in &scheme.testables {
+                textln!("  {} {}", "+".green(), testable.target.name);
             }
+            Status::success(&format!(
+                "Created {} ({} test target(s), coverage on)",
+                scheme.path.display(),
+                scheme.testables.len()
+            ));
+            exit_codes::SUCCESS
         }
 
-        ProjectAction::Add { files, project, target, group, dry_run } => {
+        SchemeAction::Parallel { scheme, disable, project, dry_run } => {
             if dry_run {
                 Status::info("Dry run mode - no changes will be made");
             }
+            let enabled = !disable;
+            let mut scheme = match Scheme::find(&project, &scheme) {
+                Ok(scheme) => scheme,
+                Err(e) => {
+                    Status::error(&e.to_string());
+                    return exit_codes::FAILURE;
+                }
+            };
 
-            match XcodeProject::open(&project) {
-                Ok(mut proj) => {
-                    let mut added = 0;
-                    let mut skipped = 0;
-                    let mut failed = 0;
-
-                    for file in &files {
-                        match proj.add_file(file, &target, group.as_deref()) {
-                            Ok(result) => {
-                                if result.already_exists {
-                                    println!("  {} {} (already in project)", "~".yellow(), file.display());
-                                    skipped += 1;
-                                } else {
-                                    println!("  {} {}", "+".green(), file.display());
-                                    added += 1;
-                                }
-                            }
-                            Err(e) => {
-                                println!("  {} {} - {}", "✗".red(), file.display(), e);
-                                failed += 1;
-                            }
-                        }
+            // Xcode ignores the scheme's testables when it uses test plans
+            let result = if scheme.test_plans.is_empty() {
+                scheme.set_parallelizable(enabled).and_then(|changed| {
+                    if !dry_run && changed > 0 {
+                        scheme.save()?;
                     }
-
-                    println!();
-                    println!("Added: {}, Skipped: {}, Failed: {}", added, skipped, failed);
-
-                    if !dry_run && added > 0 {
-                        match proj.save() {
-                            Ok(()) => {
-                                Status::success("Project saved (backup created at project.pbxproj.backup)");
-                            }
-                            Err(e) => {
-                                Status::error(&format!("Failed to save project: {}", e));
-                                return exit_codes::FAILURE;
-                            }
-                        }
-                    } else if dry_run && added > 0 {
-                        Status::info("Run without --dry-run to apply changes");
+                    textln!("  {}: {} target(s) changed", scheme.path.display(), changed);
+                    Ok(changed)
+                })
+            } else {
+                scheme.test_plan_paths(&project).iter().try_fold(0, |total, path| {
+                    let mut plan = TestPlan::load(path)?;
+                    let changed = plan.set_parallelizable(enabled);
+                    if !dry_run && changed > 0 {
+                        plan.save()?;
                     }
+                    textln!("  {}: {} target(s) changed", path.display(), changed);
+                    Ok(total + changed)
+                })
+            };
 
-                    if failed > 0 {
-                        exit_codes::FAILURE
-                    } else {
-                        exit_codes::SUCCESS
-                    }
+            match result {
+                Ok(0) => {
+                    Status::success(&format!("Parallel testing already {}", on_off(enabled)));
+                    exit_codes::SUCCESS
+                }
+                Ok(_) if dry_run => {
+                    Status::info("Run without --dry-run to apply changes");
+                    exit_codes::SUCCESS
+                }
+                Ok(_) => {
+                    Status::success(&format!("Parallel testing {}", on_off(enabled)));
+                    exit_codes::SUCCESS
                 }
                 Err(e) => {
-                    Status::error(&format!("Failed to open project: {}", e));
+                    Status::error(&format!("Failed to update {}: {}", scheme.name, e));
                     exit_codes::FAILURE
                 }
             }
@@ -1255,18 +3149,117 @@ fn run_project(action: ProjectAction) -> i32 {
     }
 }
 
+/// Save an edited project unless this is a dry run, and pick the exit code
+fn finish_project_edit(
+    proj: &foodshare_ios::xcodeproj::XcodeProject,
+    dry_run: bool,
+    changed: bool,
+    failed: bool,
+) -> i32 {
+    if !dry_run && changed {
+        match proj.save() {
+            Ok(()) => {
+                Status::success("Project saved (backup created at project.pbxproj.backup)");
+            }
+            Err(e) => {
+                Status::error(&format!("Failed to save project: {}", e));
+                return exit_codes::FAILURE;
+            }
+        }
+    } else if dry_run && changed {
+        Status::info("Run without --dry-run to apply changes");
+    }
+
+    if failed {
+        exit_codes::FAILURE
+    } else {
+        exit_codes::SUCCESS
+    }
+}
+
 // ============================================================================
 // CODE PROTECTION COMMANDS
 // ============================================================================
 
-fn run_protect(action: ProtectAction) -> i32 {
+fn run_snapshot_sync(
+    config: foodshare_ios::code_protection::ProtectionConfig,
+    bucket: &str,
+    prefix: Option<String>,
+    limit: usize,
+    push: bool,
+) -> i32 {
+    use foodshare_ios::code_protection::SnapshotManager;
+    use foodshare_ios::snapshot_sync::{self, SnapshotRemote};
+
+    let manager = match SnapshotManager::new(config) {
+        Ok(m) => m,
+        Err(e) => {
+            Status::error(&format!("Failed to initialize snapshot manager: {}", e));
+            return exit_codes::FAILURE;
+        }
+    };
+    let prefix = prefix.unwrap_or_else(|| snapshot_sync::default_prefix(&manager));
+    let remote = match SnapshotRemote::from_env(bucket, &prefix) {
+        Ok(r) => r,
+        Err(e) => {
+            Status::error(&format!("Failed to connect to snapshot storage: {}", e));
+            return exit_codes::FAILURE;
+        }
+    };
+
+    Status::info(&format!(
+        "{} {}/{}...",
+        if push { "Pushing snapshots to" } else { "Pulling snapshots from" },
+        bucket,
+        remote.prefix()
+    ));
+    let result = if push { remote.push(&manager, limit) } else { remote.pull(&manager, limit) };
+    match result {
+        Ok(result) if result.snapshots.is_empty() => {
+            Status::success(if push { "Remote is up to date" } else { "All remote snapshots are already local" });
+            exit_codes::SUCCESS
+        }
+        Ok(result) => {
+            for id in &result.snapshots {
+                textln!("  {} {}", "✓".green(), id);
+            }
+            Status::success(&format!(
+                "{} {} snapshots ({} blobs, {:.1} MB)",
+                if push { "Pushed" } else { "Pulled" },
+                result.snapshots.len(),
+                result.blobs,
+                result.bytes as f64 / 1_000_000.0
+            ));
+            exit_codes::SUCCESS
+        }
+        Err(e) => {
+            Status::error(&format!("Snapshot sync failed: {}", e));
+            exit_codes::FAILURE
+        }
+    }
+}
+
+/// Protection defaults with the `[protect]` config section applied
+fn protection_config(config: &Config) -> foodshare_ios::code_protection::ProtectionConfig {
+    let mut protection = foodshare_ios::code_protection::ProtectionConfig::default();
+    let overrides = &config.schema.protect;
+    if let Some(interactive_approval) = overrides.interactive_approval {
+        protection.interactive_approval = interactive_approval;
+    }
+    if let Some(protected_paths) = &overrides.protected_paths {
+        protection.protected_paths = protected_paths.clone();
+    }
+    protection
+}
+
+fn run_protect(action: ProtectAction, app_config: &Config) -> i32 {
     use foodshare_ios::code_protection::{
-        CommitGuard, OperationHistory, ProtectionConfig, PushGuard, SnapshotManager,
-        SnapshotTrigger, print_pending_commit, print_pending_push, print_restore_result,
-        print_snapshot_list, verify_build,
+        CommitGuard, DiffTarget, OperationHistory, PushGuard, SnapshotManager,
+        SnapshotTrigger, print_file_diffs, print_pending_commit, print_pending_push,
+        print_restore_result, print_snapshot_list, verify_build,
     };
 
-    let config = ProtectionConfig::default();
+    let config = protection_config(app_config);
 
     match action {
         ProtectAction::List { limit } => {
@@ -1321,21 +3314,21 @@ fn run_protect(action: ProtectAction) -> i32 {
                 return exit_codes::SUCCESS;
             }
 
-            println!();
-            println!("{}", "Creating snapshot...".bold());
+            textln!();
+            textln!("{}", "Creating snapshot...".bold());
 
             match manager.create_snapshot(&target_files, SnapshotTrigger::Manual, &description) {
                 Ok(snapshot) => {
-                    println!();
+                    textln!();
                     Status::success(&format!(
                         "Snapshot created: {} ({} files)",
                         snapshot.id,
                         snapshot.files.len()
                     ));
-                    println!();
-                    println!("  Recovery command:");
-                    println!("    {} protect restore --snapshot {}", "foodshare-ios".cyan(), snapshot.id);
-                    println!();
+                    textln!();
+                    textln!("  Recovery command:");
+                    textln!("    {} protect restore --snapshot {}", "foodshare-ios".cyan(), snapshot.id);
+                    textln!();
                     exit_codes::SUCCESS
                 }
                 Err(e) => {
@@ -1384,12 +3377,28 @@ fn run_protect(action: ProtectAction) -> i32 {
                 return exit_codes::FAILURE;
             };
 
-            println!();
-            println!("{}", format!("Restoring from snapshot: {}", snap.id).bold());
-            println!("  Created: {}", snap.timestamp);
-            println!("  Trigger: {}", snap.trigger);
-            println!("  Files: {}", snap.files.len());
-            println!();
+            textln!();
+            textln!("{}", format!("Restoring from snapshot: {}", snap.id).bold());
+            textln!("  Created: {}", snap.timestamp);
+            textln!("  Trigger: {}", snap.trigger);
+            textln!("  Files: {}", snap.files.len());
+            textln!();
+
+            if !dry_run {
+                let count = if file.is_some() { 1 } else { snap.files.len() };
+                let question = format!("Overwrite {} file(s) in the working tree?", count);
+                match prompt::confirm(&question, false) {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        Status::info("Restore cancelled");
+                        return exit_codes::SUCCESS;
+                    }
+                    Err(e) => {
+                        Status::error(&e.to_string());
+                        return exit_codes::FAILURE;
+                    }
+                }
+            }
 
             let files_to_restore = file.map(|f| vec![f]);
             match manager.restore_snapshot(&snap, files_to_restore.as_deref(), dry_run) {
@@ -1409,6 +3418,92 @@ fn run_protect(action: ProtectAction) -> i32 {
             }
         }
 
+        ProtectAction::Diff { snapshot, against, file } => {
+            let manager = match SnapshotManager::new(config) {
+                Ok(m) => m,
+                Err(e) => {
+                    Status::error(&format!("Failed to initialize snapshot manager: {}", e));
+                    return exit_codes::FAILURE;
+                }
+            };
+
+            let load = |id: &str| match manager.get_snapshot(id) {
+                Ok(Some(s)) => Some(s),
+                Ok(None) => {
+                    Status::error(&format!("Snapshot not found: {}", id));
+                    None
+                }
+                Err(e) => {
+                    Status::error(&format!("Failed to get snapshot: {}", e));
+                    None
+                }
+            };
+            let Some(snap) = load(&snapshot) else {
+                return exit_codes::FAILURE;
+            };
+            let target = if against == "worktree" {
+                DiffTarget::Worktree
+            } else {
+                match load(&against) {
+                    Some(other) => DiffTarget::Snapshot(other),
+                    None => return exit_codes::FAILURE,
+                }
+            };
+
+            let files = file.map(|f| vec![f]);
+            match manager.diff(&snap, &target, files.as_deref()) {
+                Ok(diffs) => {
+                    if diffs.is_empty() {
+                        Status::info(&format!("No differences between {} and {}", snap.id, against));
+                    } else {
+                        print_file_diffs(&diffs);
+                        textln!();
+                        Status::info(&format!("{} file(s) differ", diffs.len()));
+                    }
+                    exit_codes::SUCCESS
+                }
+                Err(e) => {
+                    Status::error(&format!("Failed to diff snapshot: {}", e));
+                    exit_codes::FAILURE
+                }
+            }
+        }
+
+        ProtectAction::Gc => {
+            let manager = match SnapshotManager::new(config) {
+                Ok(m) => m,
+                Err(e) => {
+                    Status::error(&format!("Failed to initialize snapshot manager: {}", e));
+                    return exit_codes::FAILURE;
+                }
+            };
+
+            match manager.gc() {
+                Ok(result) => {
+                    Status::success(&format!(
+                        "Removed {} snapshots and {} blobs, freed {:.1} MB ({:.1} MB in store)",
+                        result.snapshots_removed,
+                        result.blobs_removed,
+                        result.bytes_freed as f64 / 1_000_000.0,
+                        result.store_bytes as f64 / 1_000_000.0
+                    ));
+                    exit_codes::SUCCESS
+                }
+                Err(e) => {
+                    Status::error(&format!("Garbage collection failed: {}", e));
+                    exit_codes::FAILURE
+                }
+            }
+        }
+
+        ProtectAction::Push { bucket, prefix, limit } => {
+            run_snapshot_sync(config, &bucket, prefix, limit, true)
+        }
+
+        ProtectAction::Pull { bucket, prefix, limit } => {
+            run_snapshot_sync(config, &bucket, prefix, limit, false)
+        }
+
         ProtectAction::CommitGuard => {
             let guard = match CommitGuard::new() {
                 Ok(g) => g,
@@ -1465,16 +3560,16 @@ fn run_protect(action: ProtectAction) -> i32 {
             }
         }
 
-        ProtectAction::VerifyBuild { quick } => {
-            match verify_build(quick) {
+        ProtectAction::VerifyBuild { quick, no_cache } => {
+            match verify_build(quick, !no_cache) {
                 Ok(result) => {
                     if result.success {
                         exit_codes::SUCCESS
                     } else {
-                        println!();
-                        println!("{}", "Build errors:".red().bold());
+                        textln!();
+                        textln!("{}", "Build errors:".red().bold());
                         for error in result.errors.iter().take(10) {
-                            println!("  {}", error);
+                            textln!("  {}", error);
                         }
                         exit_codes::FAILURE
                     }
@@ -1498,14 +3593,14 @@ fn run_protect(action: ProtectAction) -> i32 {
 
             match history.recent(limit) {
                 Ok(records) => {
-                    println!();
-                    println!("{}", "═".repeat(70));
-                    println!("{}", "OPERATION HISTORY".bold());
-                    println!("{}", "═".repeat(70));
-                    println!();
+                    textln!();
+                    textln!("{}", "═".repeat(70));
+                    textln!("{}", "OPERATION HISTORY".bold());
+                    textln!("{}", "═".repeat(70));
+                    textln!();
 
                     if records.is_empty() {
-                        println!("  No operations recorded yet.");
+                        textln!("  No operations recorded yet.");
                     } else {
                         for record in &records {
                             let status = if record.success {
@@ -1513,7 +3608,7 @@ fn run_protect(action: ProtectAction) -> i32 {
                             } else {
                                 "✗".red().to_string()
                             };
-                            println!(
+                            textln!(
                                 "  {} {} {} - {} ({} files)",
                                 status,
                                 record.timestamp.format("%Y-%m-%d %H:%M"),
@@ -1523,7 +3618,7 @@ fn run_protect(action: ProtectAction) -> i32 {
                             );
                         }
                     }
-                    println!();
+                    textln!();
                     exit_codes::SUCCESS
                 }
                 Err(e) => {
@@ -1534,43 +3629,142 @@ fn run_protect(action: ProtectAction) -> i32 {
         }
 
         ProtectAction::Status => {
-            println!();
-            println!("{}", "═".repeat(60));
-            println!("{}", "CODE PROTECTION STATUS".bold());
-            println!("{}", "═".repeat(60));
-            println!();
-
-            let config = ProtectionConfig::default();
-            println!("  Configuration:");
+            textln!();
+            textln!("{}", "═".repeat(60));
+            textln!("{}", "CODE PROTECTION STATUS".bold());
+            textln!("{}", "═".repeat(60));
+            textln!();
+
+            textln!("  Configuration:");
             let snap_status = if config.snapshots_enabled { "✓".green().to_string() } else { "✗".red().to_string() };
             let build_status = if config.verify_build { "✓".green().to_string() } else { "✗".red().to_string() };
             let interactive_status = if config.interactive_approval { "✓".green().to_string() } else { "○".dimmed().to_string() };
-            println!("    Snapshots enabled: {}", snap_status);
-            println!("    Build verification: {}", build_status);
-            println!("    Interactive approval: {}", interactive_status);
-            println!("    Max snapshots: {}", config.max_snapshots);
-            println!();
+            textln!("    Snapshots enabled: {}", snap_status);
+            textln!("    Build verification: {}", build_status);
+            textln!("    Interactive approval: {}", interactive_status);
+            textln!("    Max snapshots: {}", config.max_snapshots);
+            textln!();
 
-            println!("  Protected paths:");
+            textln!("  Protected paths:");
             for path in &config.protected_paths {
-                println!("    {} {}", "•".dimmed(), path);
+                textln!("    {} {}", "•".dimmed(), path);
             }
-            println!();
+            textln!();
 
             // Count snapshots
             if let Ok(manager) = SnapshotManager::new(config.clone()) {
                 if let Ok(snapshots) = manager.list_snapshots() {
-                    println!("  Snapshots: {} stored", snapshots.len());
+                    textln!("  Snapshots: {} stored", snapshots.len());
                     if let Some(latest) = snapshots.first() {
-                        println!("  Latest: {} ({})", latest.id, latest.timestamp.format("%Y-%m-%d %H:%M"));
+                        textln!("  Latest: {} ({})", latest.id, latest.timestamp.format("%Y-%m-%d %H:%M"));
                     }
                 }
             }
 
-            println!();
-            println!("{}", "═".repeat(60));
+            textln!();
+            textln!("{}", "═".repeat(60));
             exit_codes::SUCCESS
         }
+
+        ProtectAction::Approve => run_protect_approve(&config),
+
+        ProtectAction::Watch { pattern, settle, interval } => {
+            use foodshare_ios::code_protection::{AutoSnapshotThrottle, watch_for_changes};
+            use std::time::Duration;
+
+            let patterns = if pattern.is_empty() { config.protected_paths.clone() } else { pattern };
+            let manager = match SnapshotManager::new(config) {
+                Ok(m) => m,
+                Err(e) => {
+                    Status::error(&format!("Failed to initialize snapshot manager: {}", e));
+                    return exit_codes::FAILURE;
+                }
+            };
+
+            Status::info(&format!("Watching {} (Ctrl+C to stop)", patterns.join(", ")));
+            let mut throttle =
+                AutoSnapshotThrottle::new(Duration::from_secs(settle), Duration::from_secs(interval));
+            let result = watch_for_changes(&manager, &patterns, &mut throttle, |snapshot| {
+                let files: Vec<String> =
+                    snapshot.files.iter().map(|f| f.path.display().to_string()).collect();
+                textln!("  {} {} {}", "●".blue(), snapshot.id.cyan(), files.join(", ").dimmed());
+            });
+            match result {
+                Ok(()) => exit_codes::SUCCESS,
+                Err(e) => {
+                    Status::error(&format!("Watch stopped: {}", e));
+                    exit_codes::FAILURE
+                }
+            }
+        }
+    }
+}
+
+fn run_protect_approve(config: &foodshare_ios::code_protection::ProtectionConfig) -> i32 {
+    use foodshare_ios::code_protection::{
+        APPROVE_ENV, ApprovalOutcome, OperationHistory, OperationRecord, OperationType,
+        approve_protected_changes, staged_protected_changes,
+    };
+
+    let changes = match staged_protected_changes(config) {
+        Ok(changes) => changes,
+        Err(e) => {
+            Status::error(&format!("Failed to check staged changes: {}", e));
+            return exit_codes::FAILURE;
+        }
+    };
+    if changes.is_empty() {
+        return exit_codes::SUCCESS;
+    }
+
+    if !config.interactive_approval {
+        Status::warning(&format!("{} protected file(s) staged:", changes.len()));
+        for change in &changes {
+            textln!("  {} {}", "•".dimmed(), change.path.display());
+        }
+        Status::info("Set interactive_approval = true under [protect] to require confirmation");
+        return exit_codes::SUCCESS;
+    }
+
+    let outcome = match approve_protected_changes(&changes) {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            Status::error(&format!("Approval failed: {}", e));
+            return exit_codes::FAILURE;
+        }
+    };
+
+    match &outcome {
+        ApprovalOutcome::Approved => Status::success("Protected changes approved"),
+        ApprovalOutcome::ApprovedByEnv => {
+            Status::success(&format!("Protected changes approved by {}", APPROVE_ENV))
+        }
+        ApprovalOutcome::Rejected => Status::error("Commit aborted: protected changes not approved"),
+        ApprovalOutcome::Unstaged => {
+            Status::warning("Protected files unstaged; commit again to commit the rest")
+        }
+        ApprovalOutcome::NoTerminal => {
+            Status::error("Protected paths changed and no terminal to confirm");
+            Status::info(&format!("Set {}=1 to approve non-interactively", APPROVE_ENV));
+        }
+    }
+
+    // Approvals are part of the audit trail
+    if let Ok(repo) = foodshare_core::git::GitRepo::open_current() {
+        if let Ok(history) = OperationHistory::new(&repo.workdir().join(&config.data_dir)) {
+            let _ = history.record(OperationRecord::new(
+                OperationType::Commit,
+                changes.iter().map(|c| c.path.clone()).collect(),
+                outcome.allows_commit(),
+                format!("Protected path approval: {:?}", outcome),
+            ));
+        }
+    }
+
+    if outcome.allows_commit() {
+        exit_codes::SUCCESS
+    } else {
+        exit_codes::FAILURE
     }
 }
 
@@ -1580,8 +3774,8 @@ fn run_protect(action: ProtectAction) -> i32 {
 
 fn run_supabase(action: SupabaseAction) -> i32 {
     match action {
-        SupabaseAction::AuthFix { secret, yes, check, backend_path } => {
-            run_supabase_auth_fix(secret, yes, check, &backend_path)
+        SupabaseAction::AuthFix { secret, check, backend_path } => {
+            run_supabase_auth_fix(secret, check, &backend_path)
         }
         SupabaseAction::Secrets { list, backend_path } => {
             run_supabase_secrets(list, &backend_path)
@@ -1596,17 +3790,15 @@ fn run_supabase(action: SupabaseAction) -> i32 {
 }
 
 /// Fix auth hook configuration - resolves "Hook requires authorization token" error
-fn run_supabase_auth_fix(secret: Option<String>, yes: bool, check: bool, backend_path: &PathBuf) -> i32 {
-    use std::io::{self, Write};
-
-    println!();
-    println!("{}", "═".repeat(60));
-    println!("{}", "SUPABASE AUTH HOOK FIX".bold());
-    println!("{}", "═".repeat(60));
-    println!();
-    println!("  {} Resolves: \"Hook requires authorization token\" error", "🔧".yellow());
-    println!("  {} Caused by: Missing BEFORE_USER_CREATED_HOOK_SECRET", "📋".dimmed());
-    println!();
+fn run_supabase_auth_fix(secret: Option<String>, check: bool, backend_path: &PathBuf) -> i32 {
+    textln!();
+    textln!("{}", "═".repeat(60));
+    textln!("{}", "SUPABASE AUTH HOOK FIX".bold());
+    textln!("{}", "═".repeat(60));
+    textln!();
+    textln!("  {} Resolves: \"Hook requires authorization token\" error", "🔧".yellow());
+    textln!("  {} Caused by: Missing BEFORE_USER_CREATED_HOOK_SECRET", "📋".dimmed());
+    textln!();
 
     // Check if Supabase CLI is available
     if !has_supabase_cli() {
@@ -1617,9 +3809,9 @@ fn run_supabase_auth_fix(secret: Option<String>, yes: bool, check: bool, backend
     // Verify backend path exists
     if !backend_path.exists() {
         Status::error(&format!("Backend path not found: {}", backend_path.display()));
-        println!();
-        println!("  Hint: Make sure you're running from the iOS project directory");
-        println!("  Or specify: --backend-path /path/to/foodshare-backend/supabase");
+        textln!();
+        textln!("  Hint: Make sure you're running from the iOS project directory");
+        textln!("  Or specify: --backend-path /path/to/foodshare-backend/supabase");
         return exit_codes::FAILURE;
     }
 
@@ -1632,28 +3824,25 @@ fn run_supabase_auth_fix(secret: Option<String>, yes: bool, check: bool, backend
     let webhook_secret = if let Some(s) = secret {
         s
     } else {
-        println!("{}", "Step 1: Get webhook secret from Supabase Dashboard".cyan().bold());
-        println!();
-        println!("  1. Open: https://studio.foodshare.club/project/default/auth/hooks");
-        println!("  2. Find the 'Before User Created' hook");
-        println!("  3. Copy the webhook secret (starts with {})","v1,whsec_...".green());
-        println!();
-
-        if yes {
+        textln!("{}", "Step 1: Get webhook secret from Supabase Dashboard".cyan().bold());
+        textln!();
+        textln!("  1. Open: https://studio.foodshare.club/project/default/auth/hooks");
+        textln!("  2. Find the 'Before User Created' hook");
+        textln!("  3. Copy the webhook secret (starts with {})","v1,whsec_...".green());
+        textln!();
+
+        if prompt::assume_yes() {
             Status::error("Cannot auto-confirm without --secret. Provide the secret or run interactively.");
             return exit_codes::FAILURE;
         }
 
-        print!("  Paste webhook secret: ");
-        io::stdout().flush().unwrap();
-
-        let mut input = String::new();
-        if io::stdin().read_line(&mut input).is_err() {
-            Status::error("Failed to read input");
-            return exit_codes::FAILURE;
+        match prompt::password("  Paste webhook secret:") {
+            Ok(input) => input,
+            Err(e) => {
+                Status::error(&e.to_string());
+                return exit_codes::FAILURE;
+            }
         }
-
-        input.trim().to_string()
     };
 
     if webhook_secret.is_empty() {
@@ -1663,30 +3852,26 @@ fn run_supabase_auth_fix(secret: Option<String>, yes: bool, check: bool, backend
 
     // Validate secret format
     if !webhook_secret.starts_with("v1,whsec_") {
-        println!();
-        println!("  {} Secret doesn't start with 'v1,whsec_'", "⚠️".yellow());
-        println!("  Make sure you copied the full secret from Supabase Dashboard.");
-
-        if !yes {
-            print!("  Continue anyway? (y/n): ");
-            io::stdout().flush().unwrap();
-
-            let mut confirm = String::new();
-            if io::stdin().read_line(&mut confirm).is_err() {
-                Status::error("Failed to read confirmation");
-                return exit_codes::FAILURE;
-            }
+        textln!();
+        textln!("  {} Secret doesn't start with 'v1,whsec_'", "⚠️".yellow());
+        textln!("  Make sure you copied the full secret from Supabase Dashboard.");
 
-            if !confirm.trim().eq_ignore_ascii_case("y") {
+        match prompt::confirm("  Continue anyway?", false) {
+            Ok(true) => {}
+            Ok(false) => {
                 Status::info("Aborted");
                 return exit_codes::SUCCESS;
             }
+            Err(e) => {
+                Status::error(&e.to_string());
+                return exit_codes::FAILURE;
+            }
         }
     }
 
-    println!();
-    println!("{}", "Step 2: Setting secret in Supabase Edge Functions".cyan().bold());
-    println!();
+    textln!();
+    textln!("{}", "Step 2: Setting secret in Supabase Edge Functions".cyan().bold());
+    textln!();
 
     // Run supabase secrets set
     let output = std::process::Command::new("bunx")
@@ -1702,7 +3887,7 @@ fn run_supabase_auth_fix(secret: Option<String>, yes: bool, check: bool, backend
     match output {
         Ok(result) => {
             if result.status.success() {
-                println!("  {} Secret configured successfully", "✓".green());
+                textln!("  {} Secret configured successfully", "✓".green());
             } else {
                 Status::error("Failed to set secret");
                 eprintln!("{}", String::from_utf8_lossy(&result.stderr));
@@ -1715,9 +3900,9 @@ fn run_supabase_auth_fix(secret: Option<String>, yes: bool, check: bool, backend
         }
     }
 
-    println!();
-    println!("{}", "Step 3: Deploying geolocate-user Edge Function".cyan().bold());
-    println!();
+    textln!();
+    textln!("{}", "Step 3: Deploying geolocate-user Edge Function".cyan().bold());
+    textln!();
 
     // Deploy the function
     let deploy_output = std::process::Command::new("bunx")
@@ -1728,7 +3913,7 @@ fn run_supabase_auth_fix(secret: Option<String>, yes: bool, check: bool, backend
     match deploy_output {
         Ok(result) => {
             if result.status.success() {
-                println!("  {} Function deployed successfully", "✓".green());
+                textln!("  {} Function deployed successfully", "✓".green());
             } else {
                 Status::error("Failed to deploy function");
                 eprintln!("{}", String::from_utf8_lossy(&result.stderr));
@@ -1741,28 +3926,28 @@ fn run_supabase_auth_fix(secret: Option<String>, yes: bool, check: bool, backend
         }
     }
 
-    println!();
-    println!("{}", "═".repeat(60));
-    println!("{}", "FIX COMPLETE!".green().bold());
-    println!("{}", "═".repeat(60));
-    println!();
-    println!("  Next steps:");
-    println!("    1. Test Apple Sign In on a physical device");
-    println!("    2. Test Email/Password sign up");
-    println!("    3. Test Google Sign In");
-    println!("    4. If all tests pass, resubmit to App Store");
-    println!();
-    println!("  Monitor logs:");
-    println!("    {} supabase logs geolocate-user --tail", "foodshare-hooks".dimmed());
-    println!();
+    textln!();
+    textln!("{}", "═".repeat(60));
+    textln!("{}", "FIX COMPLETE!".green().bold());
+    textln!("{}", "═".repeat(60));
+    textln!();
+    textln!("  Next steps:");
+    textln!("    1. Test Apple Sign In on a physical device");
+    textln!("    2. Test Email/Password sign up");
+    textln!("    3. Test Google Sign In");
+    textln!("    4. If all tests pass, resubmit to App Store");
+    textln!();
+    textln!("  Monitor logs:");
+    textln!("    {} supabase logs geolocate-user --tail", "foodshare-hooks".dimmed());
+    textln!();
 
     exit_codes::SUCCESS
 }
 
 /// Check current auth hook configuration
 fn run_supabase_auth_check(backend_path: &PathBuf) -> i32 {
-    println!("{}", "Checking auth hook configuration...".cyan());
-    println!();
+    textln!("{}", "Checking auth hook configuration...".cyan());
+    textln!();
 
     // Check if secret is set
     let output = std::process::Command::new("bunx")
@@ -1775,11 +3960,11 @@ fn run_supabase_auth_check(backend_path: &PathBuf) -> i32 {
             let stdout = String::from_utf8_lossy(&result.stdout);
 
             if stdout.contains("BEFORE_USER_CREATED_HOOK_SECRET") {
-                println!("  {} BEFORE_USER_CREATED_HOOK_SECRET is configured", "✓".green());
+                textln!("  {} BEFORE_USER_CREATED_HOOK_SECRET is configured", "✓".green());
             } else {
-                println!("  {} BEFORE_USER_CREATED_HOOK_SECRET is NOT configured", "✗".red());
-                println!();
-                println!("  Run {} to fix this", "foodshare-hooks supabase auth-fix".cyan());
+                textln!("  {} BEFORE_USER_CREATED_HOOK_SECRET is NOT configured", "✗".red());
+                textln!();
+                textln!("  Run {} to fix this", "foodshare-hooks supabase auth-fix".cyan());
                 return exit_codes::FAILURE;
             }
         }
@@ -1789,7 +3974,7 @@ fn run_supabase_auth_check(backend_path: &PathBuf) -> i32 {
         }
     }
 
-    println!();
+    textln!();
     Status::success("Auth hook configuration looks good");
     exit_codes::SUCCESS
 }
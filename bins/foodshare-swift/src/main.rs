@@ -6,11 +6,12 @@ use anyhow::Result;
 use clap::{Parser, Subcommand};
 use colored::Colorize;
 use owo_colors::OwoColorize;
+use foodshare_cli::progress;
 use foodshare_swift_toolchain::{
-    detect::SwiftToolchain, migrate::SwiftMigrator, verify::VerificationReport,
-    REQUIRED_SWIFT_VERSION,
+    detect::SwiftToolchain, install, migrate::SwiftMigrator, shell, verify::VerificationReport,
+    Platform, Shell, SwiftConfig, ToolchainRequest, REQUIRED_SWIFT_VERSION,
 };
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Parser)]
 #[command(name = "foodshare-swift")]
@@ -52,14 +53,28 @@ enum Commands {
 
     /// Configure environment for Swift version
     Configure {
-        /// Swift version to configure
-        version: String,
+        /// Swift version or toolchain name (default: from .foodshare-swift.toml or .swift-version)
+        version: Option<String>,
 
         /// Generate shell export commands
         #[arg(long)]
         export: bool,
     },
 
+    /// Print a shell hook that switches toolchains when entering pinned projects
+    ///
+    /// Add `eval "$(foodshare-swift env --shell zsh)"` to ~/.zshrc (or bash),
+    /// or `foodshare-swift env --shell fish | source` to config.fish.
+    Env {
+        /// Shell to generate the hook for (zsh, bash, fish)
+        #[arg(long)]
+        shell: Shell,
+
+        /// Print the commands for the current directory (called by the hook)
+        #[arg(long, hide = true)]
+        apply: bool,
+    },
+
     /// Migrate project to new Swift version
     Migrate {
         /// Source Swift version
@@ -77,12 +92,34 @@ enum Commands {
 
     /// Use specific Swift version (configure environment)
     Use {
-        /// Swift version or toolchain name
-        version: String,
+        /// Swift version or toolchain name (default: from .foodshare-swift.toml or .swift-version)
+        version: Option<String>,
     },
 
     /// List available Swift toolchains
     List,
+
+    /// Download, verify and install a Swift toolchain from swift.org
+    Install {
+        /// Version: 6.3 (latest branch snapshot), 6.2.1 (release), main, or a snapshot tag
+        version: String,
+
+        /// Expected SHA-256 checksum of the download
+        #[arg(long)]
+        sha256: Option<String>,
+
+        /// Skip PGP signature verification
+        #[arg(long)]
+        no_verify: bool,
+
+        /// Reinstall even if the toolchain is already installed
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Update this binary to the latest signed release
+    #[command(name = "self-update")]
+    SelfUpdate(foodshare_self_update::SelfUpdateArgs),
 }
 
 fn main() -> Result<()> {
@@ -93,12 +130,33 @@ fn main() -> Result<()> {
         Commands::Verify { required } => {
             cmd_verify(&cli.project_root, required.as_deref(), &cli.format)?
         }
-        Commands::Configure { version, export } => cmd_configure(&version, export)?,
+        Commands::Configure { version, export } => {
+            cmd_configure(&cli.project_root, version.as_deref(), export)?
+        }
+        Commands::Env { shell, apply } => cmd_env(shell, apply)?,
         Commands::Migrate { from, to, dry_run } => {
-            cmd_migrate(&cli.project_root, &from, &to, dry_run)?
+            cmd_migrate(&cli.project_root, &from, &to, dry_run, &cli.format)?
         }
-        Commands::Use { version } => cmd_use(&version)?,
+        Commands::Use { version } => cmd_use(&cli.project_root, version.as_deref())?,
         Commands::List => cmd_list()?,
+        Commands::Install {
+            version,
+            sha256,
+            no_verify,
+            force,
+        } => cmd_install(&version, sha256.as_deref(), no_verify, force, &cli.format)?,
+        Commands::SelfUpdate(args) => {
+            let config = foodshare_core::config::Config::load(None)?;
+            let code = foodshare_self_update::run(
+                env!("CARGO_BIN_NAME"),
+                env!("CARGO_PKG_VERSION"),
+                &args,
+                &config.schema.update,
+            );
+            if code != 0 {
+                std::process::exit(code);
+            }
+        }
     }
 
     Ok(())
@@ -113,6 +171,7 @@ fn cmd_detect(all: bool, format: &str) -> Result<()> {
                 "version": active.version.raw,
                 "path": active.path,
                 "is_xcode": active.is_xcode,
+                "is_swiftly": active.is_swiftly,
             }
         });
         println!("{}", serde_json::to_string_pretty(&json)?);
@@ -126,6 +185,8 @@ fn cmd_detect(all: bool, format: &str) -> Result<()> {
             "🔧 Source: {}",
             if active.is_xcode {
                 "Xcode".yellow()
+            } else if active.is_swiftly {
+                "swiftly".green()
             } else {
                 "Standalone".green()
             }
@@ -168,53 +229,35 @@ fn cmd_verify(project_root: &PathBuf, required: Option<&str>, format: &str) -> R
     Ok(())
 }
 
-fn cmd_configure(version: &str, export: bool) -> Result<()> {
-    let toolchains = SwiftToolchain::list_available()?;
-    let matching = toolchains
-        .iter()
-        .find(|tc| tc.version.matches(version))
-        .ok_or_else(|| anyhow::anyhow!("No toolchain found for Swift {}", version))?;
+fn cmd_configure(project_root: &Path, version: Option<&str>, export: bool) -> Result<()> {
+    let mut config = SwiftConfig::load(project_root)?;
+    if let Some(version) = version {
+        config.toolchain = Some(version.to_string());
+        config.toolchain_path = None;
+    }
+    let toolchain = config.select_toolchain()?;
+    let exports = config.generate_env_exports(&toolchain);
 
     if export {
         // Generate shell export commands
-        println!("export TOOLCHAINS=swift");
-        println!(
-            "export PATH=\"{}/usr/bin:$PATH\"",
-            matching
-                .path
-                .parent()
-                .and_then(|p| p.parent())
-                .unwrap()
-                .display()
-        );
+        for line in &exports {
+            println!("{line}");
+        }
     } else {
         println!("\n{}", "🔧 Swift Configuration".bold());
         println!("{}", "=====================".bold());
         println!();
-        println!("Version: {}", matching.version.raw.cyan());
         println!(
-            "Toolchain: {}",
-            matching
-                .path
-                .parent()
-                .and_then(|p| p.parent())
-                .unwrap()
-                .display()
-                .dimmed()
+            "Version: {}",
+            toolchain.display_name.as_deref().unwrap_or(&toolchain.name).cyan()
         );
+        println!("Toolchain: {}", toolchain.path.display().dimmed());
         println!();
         println!("{}", "To use this toolchain:".bold());
         println!();
-        println!("  export TOOLCHAINS=swift");
-        println!(
-            "  export PATH=\"{}/usr/bin:$PATH\"",
-            matching
-                .path
-                .parent()
-                .and_then(|p| p.parent())
-                .unwrap()
-                .display()
-        );
+        for line in &exports {
+            println!("  {line}");
+        }
         println!();
         println!("Or add to your shell profile (~/.zshrc or ~/.bash_profile)");
         println!();
@@ -223,26 +266,77 @@ fn cmd_configure(version: &str, export: bool) -> Result<()> {
     Ok(())
 }
 
-fn cmd_migrate(project_root: &PathBuf, from: &str, to: &str, dry_run: bool) -> Result<()> {
+fn cmd_env(shell: Shell, apply: bool) -> Result<()> {
+    if !apply {
+        let program = std::env::current_exe()
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|_| "foodshare-swift".to_string());
+        print!("{}", shell.hook(&program));
+        return Ok(());
+    }
+
+    let root = SwiftConfig::find_project_root(&std::env::current_dir()?);
+    let current_root = std::env::var_os(shell::ROOT_VAR).map(PathBuf::from);
+    if root == current_root {
+        return Ok(());
+    }
+
+    // A project whose toolchain is missing is still recorded as entered, so
+    // the warning is not repeated for every directory inside it
+    let toolchain = root.as_deref().and_then(|root| {
+        SwiftConfig::load(root)
+            .and_then(|config| config.select_toolchain())
+            .map_err(|e| eprintln!("foodshare-swift: {e}"))
+            .ok()
+    });
+    let current_bin = std::env::var_os(shell::BIN_VAR).map(PathBuf::from);
+    let path = std::env::var("PATH").unwrap_or_default();
+
+    for command in shell::switch_commands(
+        shell,
+        &path,
+        current_bin.as_deref(),
+        root.as_deref(),
+        toolchain.as_ref(),
+    ) {
+        println!("{command}");
+    }
+    Ok(())
+}
+
+fn cmd_migrate(
+    project_root: &PathBuf,
+    from: &str,
+    to: &str,
+    dry_run: bool,
+    format: &str,
+) -> Result<()> {
     let migrator = SwiftMigrator::new(from.to_string(), to.to_string(), dry_run);
-    migrator.run(project_root)?;
+    let report = migrator.run(project_root)?;
+
+    if format == "json" {
+        println!("{}", report.to_json()?);
+    } else {
+        report.print();
+    }
     Ok(())
 }
 
-fn cmd_use(version: &str) -> Result<()> {
+fn cmd_use(project_root: &Path, version: Option<&str>) -> Result<()> {
     println!("\n{}", "🔧 Configuring Swift Environment".bold());
     println!("{}", "=================================".bold());
     println!();
-    println!("Target version: {}", version.cyan());
+    println!("Target version: {}", version.unwrap_or("(pinned)").cyan());
     println!();
     println!("{}", "Run these commands:".bold());
     println!();
-    println!("  source <(foodshare-swift configure {} --export)", version);
+    let args = version.map(|v| format!("{v} ")).unwrap_or_default();
+    println!("  source <(foodshare-swift configure {args}--export)");
     println!();
     println!("Or manually:");
     println!();
 
-    cmd_configure(version, false)?;
+    cmd_configure(project_root, version, false)?;
 
     Ok(())
 }
@@ -297,3 +391,118 @@ fn cmd_list() -> Result<()> {
 
     Ok(())
 }
+
+fn cmd_install(
+    version: &str,
+    sha256: Option<&str>,
+    no_verify: bool,
+    force: bool,
+    format: &str,
+) -> Result<()> {
+    let request = ToolchainRequest::parse(version)?;
+    let platform = Platform::current()?;
+    let text = format != "json";
+
+    let spinner = text.then(|| progress::spinner(&format!("Resolving Swift {version}...")));
+    let download = install::resolve(&request, &platform, install::fetch_text);
+    if let Some(pb) = spinner {
+        pb.finish_and_clear();
+    }
+    let download = download?;
+    let target = platform.install_path(&download.tag)?;
+
+    if target.exists() && !force {
+        if text {
+            println!(
+                "{} {} is already installed at {}",
+                "✓".green(),
+                download.tag.cyan(),
+                target.display().dimmed()
+            );
+        } else {
+            print_install_json(&download.tag, &target, false)?;
+        }
+        return Ok(());
+    }
+
+    if text {
+        println!("\n{}", "📥 Installing Swift Toolchain".bold());
+        println!("{}", "=============================".bold());
+        println!();
+        println!("Toolchain: {}", download.tag.cyan());
+        println!("Source:    {}", download.url.dimmed());
+        println!();
+    }
+
+    let cache = install::download_dir()?;
+    let file = cache.join(download.file_name());
+    let bar = text.then(|| progress::transfer_bar(None, "Downloading"));
+    let downloaded = install::download_file(&download.url, &file, |done, total| {
+        if let Some(ref pb) = bar {
+            progress::set_transfer(pb, done, total);
+        }
+    });
+    if let Some(ref pb) = bar {
+        match downloaded {
+            Ok(()) => progress::finish_success(pb, "Downloaded"),
+            Err(_) => progress::finish_error(pb, "Download failed"),
+        }
+    }
+    downloaded?;
+
+    if let Some(expected) = sha256 {
+        install::verify_checksum(&file, expected)?;
+        if text {
+            println!("{} Checksum verified", "✓".green());
+        }
+    }
+
+    if no_verify {
+        if text {
+            println!("{} Skipping signature verification", "⚠".yellow());
+        }
+    } else {
+        let signature = cache.join(format!("{}.sig", download.file_name()));
+        install::download_file(&download.signature_url, &signature, |_, _| {})?;
+        install::verify_signature(&file, &signature, &cache)?;
+        if text {
+            println!("{} Signature verified", "✓".green());
+        }
+    }
+
+    let spinner = text.then(|| progress::spinner("Installing..."));
+    let installed = install::install_file(&download, &file);
+    if let Some(pb) = spinner {
+        pb.finish_and_clear();
+    }
+    let installed = installed?;
+    // The verified installer is no longer needed once unpacked
+    let _ = std::fs::remove_file(&file);
+
+    if text {
+        println!(
+            "{} Installed {} to {}",
+            "✓".green(),
+            download.tag.cyan(),
+            installed.display().dimmed()
+        );
+        println!();
+        println!("To use it:");
+        println!("  source <(foodshare-swift configure {} --export)", version);
+        println!();
+    } else {
+        print_install_json(&download.tag, &installed, true)?;
+    }
+
+    Ok(())
+}
+
+fn print_install_json(tag: &str, path: &std::path::Path, installed: bool) -> Result<()> {
+    let json = serde_json::json!({
+        "toolchain": tag,
+        "path": path,
+        "installed": installed,
+    });
+    println!("{}", serde_json::to_string_pretty(&json)?);
+    Ok(())
+}
@@ -0,0 +1,888 @@
+//! Lefthook configuration - generate and validate `lefthook.yml`
+//!
+//! `generate-config` writes hooks for the detected platform that run
+//! through `foodshare` (or, with `--direct`, the platform binary).
+//!
+//! `validate` reads the `run:` lines of an existing file and checks every
+//! Foodshare command against the installed binaries: each subcommand and
+//! flag must appear in the binary's `--help`, so a config written for
+//! another version fails before a hook does. Lines running other tools are
+//! left alone. The file is scanned line by line rather than parsed, which is
+//! enough for the keys lefthook uses.
+
+use crate::{locate, platform_tool};
+use anyhow::{Context, Result};
+use foodshare_cli::output::{self, Finding, Level, Status};
+use foodshare_cli::textln;
+use foodshare_core::error::exit_codes;
+use foodshare_core::project::{Project, ProjectKind};
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Config file lefthook reads
+pub const DEFAULT_FILE: &str = "lefthook.yml";
+
+/// Binaries whose commands `validate` checks
+const TOOLS: &[&str] = &[
+    "foodshare",
+    "foodshare-ios",
+    "foodshare-android",
+    "lefthook-rs",
+    "foodshare-i18n",
+    "foodshare-swift",
+    "fs-image",
+];
+
+/// Commands `foodshare` handles itself instead of passing to the platform tool
+const UMBRELLA_COMMANDS: &[&str] = &["detect", "watch", "hooks", "help"];
+
+/// `lefthook.yml` for a platform, with `{bin}` standing for the binary
+fn template(kind: ProjectKind) -> &'static str {
+    match kind {
+        ProjectKind::Ios => {
+            r#"pre-commit:
+  parallel: true
+  commands:
+    format:
+      glob: "*.swift"
+      run: {bin} format --staged
+    lint:
+      glob: "*.swift"
+      run: {bin} lint {staged_files}
+    secrets:
+      run: {bin} secrets
+    style-config:
+      run: {bin} style-config
+    plugins:
+      run: {bin} plugins run --hook pre-commit {staged_files}
+    protected:
+      run: {bin} protect approve
+      interactive: true
+
+commit-msg:
+  commands:
+    validate:
+      run: {bin} commit-msg {1}
+
+pre-push:
+  commands:
+    checks:
+      run: {bin} pre-push --fail-fast
+"#
+        }
+        ProjectKind::Android => {
+            r#"pre-commit:
+  parallel: true
+  commands:
+    format:
+      glob: "*.{kt,kts}"
+      run: {bin} format --staged --lang kotlin
+    lint:
+      glob: "*.{kt,kts}"
+      run: {bin} lint --lang kotlin {staged_files}
+    secrets:
+      run: {bin} secrets
+    plugins:
+      run: {bin} plugins run --hook pre-commit {staged_files}
+
+commit-msg:
+  commands:
+    validate:
+      run: {bin} commit-msg {1}
+
+pre-push:
+  commands:
+    plugins:
+      run: {bin} plugins run --hook pre-push
+"#
+        }
+        ProjectKind::Web => {
+            r#"pre-commit:
+  parallel: true
+  commands:
+    security:
+      glob: "*.{js,jsx,ts,tsx,mjs}"
+      run: {bin} security {staged_files}
+    nextjs-security:
+      glob: "*.{js,jsx,ts,tsx}"
+      run: {bin} nextjs-security {staged_files}
+    accessibility:
+      glob: "*.{jsx,tsx}"
+      run: {bin} accessibility {staged_files}
+    large-files:
+      run: {bin} large-files
+    plugins:
+      run: {bin} plugins run --hook pre-commit {staged_files}
+
+commit-msg:
+  commands:
+    validate:
+      run: {bin} conventional-commit {1}
+
+pre-push:
+  commands:
+    protected-branch:
+      run: {bin} protected-branch
+    plugins:
+      run: {bin} plugins run --hook pre-push
+"#
+        }
+        ProjectKind::Backend => {
+            r#"pre-commit:
+  parallel: true
+  commands:
+    security:
+      glob: "*.{js,ts,mjs}"
+      run: {bin} security {staged_files}
+    supabase-queries:
+      glob: "*.{js,ts}"
+      run: {bin} supabase-queries {staged_files}
+    large-files:
+      run: {bin} large-files
+    plugins:
+      run: {bin} plugins run --hook pre-commit {staged_files}
+
+commit-msg:
+  commands:
+    validate:
+      run: {bin} conventional-commit {1}
+
+pre-push:
+  commands:
+    protected-branch:
+      run: {bin} protected-branch
+    plugins:
+      run: {bin} plugins run --hook pre-push
+"#
+        }
+    }
+}
+
+/// The `lefthook.yml` for a platform, running `bin`
+fn render(kind: ProjectKind, bin: &str) -> String {
+    format!(
+        "# Generated by `foodshare hooks generate-config` (platform: {})\n\n{}",
+        kind,
+        template(kind).replace("{bin}", bin)
+    )
+}
+
+/// Write a `lefthook.yml` for `project`
+pub fn generate(project: &Project, output: &Path, direct: bool, force: bool, stdout: bool) -> i32 {
+    let bin = if direct {
+        platform_tool(project.kind)
+    } else {
+        "foodshare"
+    };
+    let config = render(project.kind, bin);
+
+    if stdout {
+        print!("{}", config);
+        return exit_codes::SUCCESS;
+    }
+    if output.exists() && !force {
+        Status::error(&format!(
+            "{} already exists; pass --force to replace it",
+            output.display()
+        ));
+        return exit_codes::FAILURE;
+    }
+    if let Err(e) = std::fs::write(output, &config) {
+        Status::error(&format!("Failed to write {}: {}", output.display(), e));
+        return exit_codes::FAILURE;
+    }
+    output::data("file", &output.display().to_string());
+    output::data("kind", &project.kind);
+    Status::success(&format!(
+        "Wrote {} for the {} project ({})",
+        output.display(),
+        project.kind,
+        bin
+    ));
+    textln!("  Install the hooks with `lefthook install`");
+    exit_codes::SUCCESS
+}
+
+/// A `run:` command in the config
+#[derive(Debug)]
+struct RunLine {
+    /// Line number (1-based)
+    line: usize,
+    /// Hook and command, e.g. `pre-commit.lint`
+    name: String,
+    script: String,
+}
+
+/// Find the `run:` commands, following block scalars (`run: |`)
+fn run_lines(content: &str) -> Vec<RunLine> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut runs = Vec::new();
+    // Keys of the lines enclosing the current one, with their indentation
+    let mut keys: Vec<(usize, String)> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let raw = lines[i];
+        let trimmed = raw.trim_start();
+        i += 1;
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = raw.len() - trimmed.len();
+        let (indent, entry) = match trimmed.strip_prefix("- ") {
+            Some(rest) => (indent + 2, rest.trim_start()),
+            None => (indent, trimmed),
+        };
+        keys.retain(|(key_indent, _)| *key_indent < indent);
+
+        let Some((key, value)) = entry.split_once(':') else {
+            continue;
+        };
+        let key = key.trim().trim_matches('"').to_string();
+        let value = value.trim();
+        if key != "run" {
+            if value.is_empty() {
+                keys.push((indent, key));
+            }
+            continue;
+        }
+
+        let name = keys
+            .iter()
+            .map(|(_, key)| key.as_str())
+            .filter(|key| !matches!(*key, "commands" | "jobs" | "scripts"))
+            .collect::<Vec<_>>()
+            .join(".");
+        if value.starts_with('|') || value.starts_with('>') {
+            let start = i;
+            while i < lines.len() {
+                let next = lines[i];
+                if !next.trim().is_empty() && next.len() - next.trim_start().len() <= indent {
+                    break;
+                }
+                i += 1;
+            }
+            for (offset, line) in lines[start..i].iter().enumerate() {
+                if !line.trim().is_empty() {
+                    runs.push(RunLine {
+                        line: start + offset + 1,
+                        name: name.clone(),
+                        script: line.trim().to_string(),
+                    });
+                }
+            }
+        } else {
+            runs.push(RunLine {
+                line: i,
+                name,
+                script: unquote(value).to_string(),
+            });
+        }
+    }
+    runs
+}
+
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if value.len() >= 2 && value.starts_with(quote) && value.ends_with(quote) {
+            return &value[1..value.len() - 1];
+        }
+    }
+    value
+}
+
+/// Split a shell command into words, honoring quotes
+fn split_words(script: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+    for c in script.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => word.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    words
+}
+
+/// What a binary's `--help` says a command takes
+#[derive(Debug, Default)]
+struct HelpPage {
+    /// Subcommands and their aliases
+    commands: BTreeSet<String>,
+    /// Whether a subcommand is required
+    requires_command: bool,
+    /// Long flags, with whether they take a value
+    long: HashMap<String, bool>,
+    /// Short flags, with whether they take a value
+    short: HashMap<char, bool>,
+}
+
+impl HelpPage {
+    fn parse(help: &str) -> Self {
+        let mut page = Self::default();
+        let mut section = "";
+        for line in help.lines() {
+            if let Some(usage) = line.strip_prefix("Usage: ") {
+                page.requires_command = usage.contains("<COMMAND>");
+                continue;
+            }
+            if !line.starts_with(' ') && line.ends_with(':') {
+                section = line;
+                continue;
+            }
+            match section {
+                "Commands:" => {
+                    // Wrapped descriptions are indented further
+                    if line.starts_with("   ") {
+                        continue;
+                    }
+                    if let Some(name) = line
+                        .strip_prefix("  ")
+                        .and_then(|l| l.split_whitespace().next())
+                    {
+                        page.commands.insert(name.to_string());
+                        if let Some(aliases) = line
+                            .split_once("[aliases: ")
+                            .or_else(|| line.split_once("[alias: "))
+                            .and_then(|(_, rest)| rest.split_once(']'))
+                        {
+                            page.commands
+                                .extend(aliases.0.split(", ").map(String::from));
+                        }
+                    }
+                }
+                "Options:" => page.parse_option(line),
+                _ => {}
+            }
+        }
+        page
+    }
+
+    /// Read `  -c, --config <CONFIG>  ...` or `      --strict  ...`
+    fn parse_option(&mut self, line: &str) {
+        let spec = if let Some(rest) = line.strip_prefix("      --") {
+            format!("--{}", rest)
+        } else if let Some(rest) = line
+            .strip_prefix("  -")
+            .filter(|_| !line.starts_with("   "))
+        {
+            format!("-{}", rest)
+        } else {
+            return;
+        };
+        // The flags end where the description starts
+        let spec = spec.split("  ").next().unwrap_or_default();
+        let takes_value = spec.contains('<');
+        for flag in spec.split(", ") {
+            let name = flag.split([' ', '=', '[', '.']).next().unwrap_or_default();
+            if let Some(long) = name.strip_prefix("--") {
+                self.long.insert(long.to_string(), takes_value);
+            } else if let Some(short) = name.strip_prefix('-').and_then(|s| s.chars().next()) {
+                self.short.insert(short, takes_value);
+            }
+        }
+    }
+}
+
+/// A problem with one command line
+#[derive(Debug, Serialize)]
+struct Problem {
+    line: usize,
+    hook: String,
+    rule: &'static str,
+    message: String,
+}
+
+/// Result of `validate`
+#[derive(Debug, Serialize)]
+struct Validation {
+    file: String,
+    /// Foodshare commands checked
+    commands: usize,
+    /// Binaries used, with their version
+    binaries: Vec<(String, String)>,
+    problems: Vec<Problem>,
+}
+
+/// Checks commands against installed binaries, running each `--help` once
+struct Checker {
+    platform: Option<ProjectKind>,
+    /// Finds an installed binary
+    locate: fn(&str) -> Option<PathBuf>,
+    pages: HashMap<(PathBuf, Vec<String>), HelpPage>,
+    versions: HashMap<String, String>,
+}
+
+impl Checker {
+    fn new(platform: Option<ProjectKind>) -> Self {
+        Self {
+            platform,
+            locate,
+            pages: HashMap::new(),
+            versions: HashMap::new(),
+        }
+    }
+
+    /// Check the Foodshare commands in a config; returns how many there were
+    fn check_config(&mut self, content: &str) -> (usize, Vec<Problem>) {
+        let mut checked = 0;
+        let mut problems = Vec::new();
+        for run in run_lines(content) {
+            let words = split_words(&run.script);
+            // Each command of `a && b; c | d`
+            for segment in words.split(|w| matches!(w.as_str(), "&&" | "||" | ";" | "|")) {
+                let Some((program, args)) = segment.split_first() else {
+                    continue;
+                };
+                let name = Path::new(program)
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or_default();
+                if !TOOLS.contains(&name) {
+                    continue;
+                }
+                checked += 1;
+                for (rule, message) in self.check(name, args) {
+                    problems.push(Problem {
+                        line: run.line,
+                        hook: run.name.clone(),
+                        rule,
+                        message,
+                    });
+                }
+            }
+        }
+        (checked, problems)
+    }
+
+    fn help(&mut self, path: &Path, commands: &[String]) -> Result<&HelpPage> {
+        let key = (path.to_path_buf(), commands.to_vec());
+        if !self.pages.contains_key(&key) {
+            let output = Command::new(path)
+                .args(commands)
+                .arg("--help")
+                .env("NO_COLOR", "1")
+                .output()
+                .with_context(|| format!("Failed to run {}", path.display()))?;
+            if !output.status.success() {
+                anyhow::bail!("{} {} --help failed", path.display(), commands.join(" "));
+            }
+            let page = HelpPage::parse(&String::from_utf8_lossy(&output.stdout));
+            self.pages.insert(key.clone(), page);
+        }
+        Ok(&self.pages[&key])
+    }
+
+    fn version(&mut self, tool: &str, path: &Path) {
+        if self.versions.contains_key(tool) {
+            return;
+        }
+        let version = Command::new(path)
+            .arg("--version")
+            .output()
+            .ok()
+            .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+            .unwrap_or_default();
+        self.versions.insert(tool.to_string(), version);
+    }
+
+    /// Problems with one command (`words` starts after the binary)
+    fn check(&mut self, tool: &str, words: &[String]) -> Vec<(&'static str, String)> {
+        let (tool, words) = match self.resolve(tool, words) {
+            Ok(resolved) => resolved,
+            Err(e) => return vec![("unknown-platform", e.to_string())],
+        };
+        let Some(path) = (self.locate)(&tool) else {
+            return vec![("missing-binary", format!("`{}` is not installed", tool))];
+        };
+        self.version(&tool, &path);
+
+        let mut problems = Vec::new();
+        let mut commands: Vec<String> = Vec::new();
+        let mut positional = false;
+        let mut words = words.iter();
+        while let Some(word) = words.next() {
+            let page = match self.help(&path, &commands) {
+                Ok(page) => page,
+                Err(e) => return vec![("help-failed", e.to_string())],
+            };
+            let command = if commands.is_empty() {
+                tool.clone()
+            } else {
+                format!("{} {}", tool, commands.join(" "))
+            };
+
+            // Lefthook fills in {staged_files}, {1}, ...
+            if word.contains('{') {
+                positional = true;
+                continue;
+            }
+            if let Some(flag) = word.strip_prefix("--") {
+                if flag.is_empty() {
+                    break;
+                }
+                let (name, inline_value) = match flag.split_once('=') {
+                    Some((name, _)) => (name, true),
+                    None => (flag, false),
+                };
+                match page.long.get(name) {
+                    Some(true) if !inline_value => {
+                        words.next();
+                    }
+                    Some(_) => {}
+                    None => problems.push((
+                        "unknown-flag",
+                        format!("`{}` has no --{} option", command, name),
+                    )),
+                }
+            } else if let Some(shorts) = word.strip_prefix('-').filter(|s| !s.is_empty()) {
+                for (i, short) in shorts.chars().enumerate() {
+                    match page.short.get(&short) {
+                        Some(true) => {
+                            // The rest of the word, or the next one, is the value
+                            if i + 1 == shorts.len() {
+                                words.next();
+                            }
+                            break;
+                        }
+                        Some(false) => {}
+                        None => {
+                            problems.push((
+                                "unknown-flag",
+                                format!("`{}` has no -{} option", command, short),
+                            ));
+                            break;
+                        }
+                    }
+                }
+            } else if !positional && page.commands.contains(word.as_str()) {
+                commands.push(word.clone());
+            } else if !positional && page.requires_command {
+                problems.push((
+                    "unknown-command",
+                    format!("`{}` has no `{}` command", command, word),
+                ));
+                break;
+            } else {
+                positional = true;
+            }
+        }
+
+        if problems.is_empty() {
+            let needs_command = self
+                .help(&path, &commands)
+                .is_ok_and(|page| page.requires_command);
+            if needs_command {
+                let command = std::iter::once(tool)
+                    .chain(commands)
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                problems.push((
+                    "missing-command",
+                    format!("`{}` needs a subcommand", command),
+                ));
+            }
+        }
+        problems
+    }
+
+    /// The binary a `foodshare` command runs with, and its arguments
+    fn resolve(&self, tool: &str, words: &[String]) -> Result<(String, Vec<String>)> {
+        if tool != "foodshare" {
+            return Ok((tool.to_string(), words.to_vec()));
+        }
+        let mut platform = self.platform;
+        let mut rest = Vec::new();
+        let mut words = words.iter();
+        while let Some(word) = words.next() {
+            match word.as_str() {
+                "--platform" => platform = words.next().and_then(|p| p.parse().ok()),
+                "-C" | "--project-dir" => {
+                    words.next();
+                }
+                w if w.starts_with("--platform=") => {
+                    platform = w["--platform=".len()..].parse().ok()
+                }
+                w if w.starts_with("--project-dir=") => {}
+                _ => rest.push(word.clone()),
+            }
+        }
+        let first = rest.iter().position(|w| !w.starts_with('-'));
+        match first.map(|i| rest[i].as_str()) {
+            Some(command) if UMBRELLA_COMMANDS.contains(&command) => {
+                Ok(("foodshare".to_string(), rest))
+            }
+            Some(tool @ ("i18n" | "swift" | "image")) => {
+                let tool = match tool {
+                    "i18n" => "foodshare-i18n",
+                    "swift" => "foodshare-swift",
+                    _ => "fs-image",
+                };
+                Ok((tool.to_string(), rest[first.unwrap_or(0) + 1..].to_vec()))
+            }
+            _ => {
+                let kind = platform
+                    .context("Can't tell which platform `foodshare` runs; pass --platform")?;
+                Ok((platform_tool(kind).to_string(), rest))
+            }
+        }
+    }
+}
+
+/// Check the Foodshare commands of a lefthook config
+pub fn validate(file: &Path, platform: Option<ProjectKind>) -> i32 {
+    let content = match std::fs::read_to_string(file) {
+        Ok(content) => content,
+        Err(e) => {
+            Status::error(&format!("Failed to read {}: {}", file.display(), e));
+            return exit_codes::FAILURE;
+        }
+    };
+    let dir = file
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .map_or_else(|| PathBuf::from("."), Path::to_path_buf);
+    let platform =
+        platform.or_else(|| Project::detect(&dir.canonicalize().unwrap_or(dir)).map(|p| p.kind));
+
+    let mut checker = Checker::new(platform);
+    let (checked, problems) = checker.check_config(&content);
+
+    let mut binaries: Vec<(String, String)> = checker.versions.into_iter().collect();
+    binaries.sort();
+    let validation = Validation {
+        file: file.display().to_string(),
+        commands: checked,
+        binaries,
+        problems,
+    };
+    print_validation(&validation);
+    if validation.problems.is_empty() {
+        exit_codes::SUCCESS
+    } else {
+        exit_codes::VALIDATION_ERROR
+    }
+}
+
+fn print_validation(validation: &Validation) {
+    for problem in &validation.problems {
+        output::finding(
+            Finding::new(
+                Level::Error,
+                format!("{}: {}", problem.hook, problem.message),
+            )
+            .with_rule(problem.rule)
+            .with_location(&validation.file, Some(problem.line)),
+        );
+    }
+    output::data("commands", &validation.commands);
+    output::data("binaries", &validation.binaries);
+
+    for (tool, version) in &validation.binaries {
+        textln!(
+            "  {} {}",
+            "ℹ".blue(),
+            if version.is_empty() { tool } else { version }
+        );
+    }
+    if validation.commands == 0 {
+        Status::info(&format!("{} runs no Foodshare commands", validation.file));
+    } else if validation.problems.is_empty() {
+        Status::success(&format!(
+            "{} Foodshare command(s) in {} match the installed binaries",
+            validation.commands, validation.file
+        ));
+    } else {
+        Status::error(&format!(
+            "{} problem(s) in {} Foodshare command(s) in {}",
+            validation.problems.len(),
+            validation.commands,
+            validation.file
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Commands the templates run, as leaves of the platform binaries
+    const LEAVES: &[&str] = &[
+        "format",
+        "lint",
+        "secrets",
+        "style-config",
+        "commit-msg",
+        "pre-push",
+        "security",
+        "nextjs-security",
+        "accessibility",
+        "large-files",
+        "conventional-commit",
+        "protected-branch",
+        "supabase-queries",
+    ];
+
+    fn help(usage: &str, commands: &[&str], options: &[&str]) -> String {
+        let mut help = format!("A Foodshare tool\n\nUsage: {}\n\n", usage);
+        if !commands.is_empty() {
+            help.push_str("Commands:\n");
+            for command in commands {
+                help.push_str(&format!("  {:<20}Does {}\n", command, command));
+            }
+            help.push('\n');
+        }
+        help.push_str("Options:\n");
+        for option in options {
+            help.push_str(option);
+            help.push('\n');
+        }
+        help
+    }
+
+    /// A checker whose binaries are the platform tools, answering from
+    /// canned `--help` pages instead of running anything
+    fn checker(platform: Option<ProjectKind>) -> Checker {
+        let mut checker = Checker::new(platform);
+        checker.locate = |tool| (tool != "fs-image").then(|| PathBuf::from(tool));
+        let leaf_options = [
+            "      --staged       Only staged files",
+            "      --lang <LANG>  Language to check",
+            "      --fail-fast    Stop at the first failure",
+            "  -h, --help         Print help",
+        ];
+        for tool in ["foodshare-ios", "foodshare-android", "lefthook-rs"] {
+            let mut pages = vec![
+                (
+                    vec![],
+                    help(
+                        &format!("{} [OPTIONS] <COMMAND>", tool),
+                        &[LEAVES, &["plugins", "protect"][..]].concat(),
+                        &["  -v, --verbose          Verbose output", "      --format <FORMAT>  Output format"],
+                    ),
+                ),
+                (vec!["plugins"], help("plugins <COMMAND>", &["run", "list"], &[])),
+                (vec!["plugins", "run"], help("plugins run [OPTIONS] [FILES]...", &[], &["      --hook <HOOK>  Hook to run"])),
+                (vec!["protect"], help("protect <COMMAND>", &["approve"], &[])),
+                (vec!["protect", "approve"], help("protect approve", &[], &[])),
+            ];
+            for leaf in LEAVES {
+                pages.push((vec![*leaf], help(&format!("{} [OPTIONS] [FILES]...", leaf), &[], &leaf_options)));
+            }
+            for (commands, text) in pages {
+                let commands = commands.into_iter().map(String::from).collect();
+                checker.pages.insert((PathBuf::from(tool), commands), HelpPage::parse(&text));
+            }
+        }
+        checker
+    }
+
+    fn rules(problems: &[Problem]) -> Vec<(usize, &str)> {
+        problems.iter().map(|p| (p.line, p.rule)).collect()
+    }
+
+    #[test]
+    fn test_generated_configs_validate() {
+        for kind in ProjectKind::ALL {
+            for bin in ["foodshare", platform_tool(kind)] {
+                let config = render(kind, bin);
+                assert!(config.starts_with(&format!("# Generated by `foodshare hooks generate-config` (platform: {})", kind)));
+                let (checked, problems) = checker(Some(kind)).check_config(&config);
+                assert!(checked > 0, "{} {}", kind, bin);
+                assert!(problems.is_empty(), "{} {}: {:?}", kind, bin, problems);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rejects_bad_commands() {
+        let config = r#"pre-commit:
+  commands:
+    format:
+      run: foodshare-ios format --stagged {staged_files}
+    lint:
+      run: foodshare-ios fromat
+    plugins:
+      run: npx prettier --check && foodshare-ios plugins
+    hook:
+      run: foodshare-ios plugins run --hook
+    style:
+      run: foodshare-ios lint -x
+    images:
+      run: fs-image optimize
+"#;
+        let (checked, problems) = checker(Some(ProjectKind::Ios)).check_config(config);
+        assert_eq!(checked, 6);
+        assert_eq!(
+            rules(&problems),
+            [
+                (4, "unknown-flag"),
+                (6, "unknown-command"),
+                (8, "missing-command"),
+                (12, "unknown-flag"),
+                (14, "missing-binary"),
+            ]
+        );
+        assert_eq!(problems[0].hook, "pre-commit.format");
+        assert_eq!(problems[0].message, "`foodshare-ios format` has no --stagged option");
+        assert_eq!(problems[2].message, "`foodshare-ios plugins` needs a subcommand");
+    }
+
+    #[test]
+    fn test_umbrella_needs_platform() {
+        let config = "pre-commit:\n  commands:\n    lint:\n      run: foodshare lint\n";
+        let (_, problems) = checker(None).check_config(config);
+        assert_eq!(rules(&problems), [(4, "unknown-platform")]);
+
+        let config = "pre-commit:\n  commands:\n    lint:\n      run: foodshare --platform android lint --lang kotlin\n";
+        let (_, problems) = checker(None).check_config(config);
+        assert!(problems.is_empty(), "{:?}", problems);
+    }
+
+    #[test]
+    fn test_run_lines() {
+        let config = r#"pre-push:
+  jobs:
+    - name: checks
+      run: |
+        foodshare-ios secrets
+        foodshare-ios pre-push --fail-fast
+    - run: "foodshare-ios lint 'a b.swift'"
+"#;
+        let runs = run_lines(config);
+        let lines: Vec<(usize, &str, &str)> = runs
+            .iter()
+            .map(|r| (r.line, r.name.as_str(), r.script.as_str()))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                (5, "pre-push", "foodshare-ios secrets"),
+                (6, "pre-push", "foodshare-ios pre-push --fail-fast"),
+                (7, "pre-push", "foodshare-ios lint 'a b.swift'"),
+            ]
+        );
+        assert_eq!(split_words(&runs[2].script), ["foodshare-ios", "lint", "a b.swift"]);
+    }
+}
@@ -0,0 +1,375 @@
+//! Foodshare CLI
+//!
+//! One entry point for every Foodshare repository: detects the project type
+//! and runs the command with that platform's tool, so hooks and docs can
+//! say `foodshare lint` whether the repository is iOS, Android, web or
+//! backend.
+
+use anyhow::{Context, Result};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use foodshare_cli::output::{self, Format, Status};
+use foodshare_cli::textln;
+use foodshare_core::config::Config;
+use foodshare_core::error::exit_codes;
+use foodshare_core::project::{Project, ProjectKind};
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+mod lefthook;
+mod watch;
+
+#[derive(Parser)]
+#[command(name = "foodshare")]
+#[command(about = "Foodshare development tools for iOS, Android, web and backend projects")]
+#[command(version)]
+struct Cli {
+    /// Platform to use instead of detecting it: ios, android, web, backend
+    #[arg(long, global = true, env = "FOODSHARE_PLATFORM")]
+    platform: Option<ProjectKind>,
+
+    /// Run as if started in this directory
+    #[arg(short = 'C', long, global = true, value_name = "DIR")]
+    project_dir: Option<PathBuf>,
+
+    /// Config file path
+    #[arg(short, long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Increase output verbosity
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
+
+    /// Suppress non-error output
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Disable colored output
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Output format: text, json, ndjson [default: text]
+    #[arg(long, global = true)]
+    format: Option<String>,
+
+    /// Answer yes to confirmations and take the default elsewhere
+    #[arg(short, long, global = true)]
+    yes: bool,
+
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Show the detected project and the tool its commands run with
+    Detect,
+
+    /// Re-run checks as files change, with desktop notifications
+    Watch {
+        /// Show the checks of the running watch instead of starting one
+        #[arg(long)]
+        status: bool,
+        /// Don't show desktop notifications
+        #[arg(long)]
+        no_notify: bool,
+    },
+
+    /// Generate or validate the lefthook.yml
+    Hooks {
+        #[command(subcommand)]
+        action: HooksAction,
+    },
+
+    /// Translation management (runs foodshare-i18n)
+    #[command(disable_help_flag = true)]
+    I18n {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<OsString>,
+    },
+
+    /// Swift toolchain management (runs foodshare-swift)
+    #[command(disable_help_flag = true)]
+    Swift {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<OsString>,
+    },
+
+    /// Image processing (runs fs-image)
+    #[command(disable_help_flag = true)]
+    Image {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<OsString>,
+    },
+
+    /// Any other command runs with the project's platform tool
+    #[command(external_subcommand)]
+    Platform(Vec<OsString>),
+}
+
+#[derive(Subcommand)]
+enum HooksAction {
+    /// Write a lefthook.yml wired to the commands of the detected platform
+    #[command(name = "generate-config")]
+    GenerateConfig {
+        /// File to write
+        #[arg(short, long, default_value = lefthook::DEFAULT_FILE)]
+        output: PathBuf,
+        /// Run the platform binary instead of foodshare
+        #[arg(long)]
+        direct: bool,
+        /// Replace an existing file
+        #[arg(long)]
+        force: bool,
+        /// Print the config instead of writing it
+        #[arg(long)]
+        stdout: bool,
+    },
+
+    /// Check that a lefthook.yml only uses commands and flags the installed binaries have
+    Validate {
+        /// Config to check
+        #[arg(default_value = lefthook::DEFAULT_FILE)]
+        file: PathBuf,
+    },
+}
+
+/// Tool the commands of a platform run with
+///
+/// Backend repositories hold TypeScript edge functions, which the web
+/// hooks already check.
+fn platform_tool(kind: ProjectKind) -> &'static str {
+    match kind {
+        ProjectKind::Ios => "foodshare-ios",
+        ProjectKind::Android => "foodshare-android",
+        ProjectKind::Web | ProjectKind::Backend => "lefthook-rs",
+    }
+}
+
+/// Detection result, as printed by `detect`
+#[derive(Serialize)]
+struct Detection {
+    kind: ProjectKind,
+    /// Directory holding the project markers, unless `--platform` was given
+    root: Option<PathBuf>,
+    tool: &'static str,
+    /// Where the tool was found
+    tool_path: Option<PathBuf>,
+}
+
+fn main() -> Result<()> {
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches)?;
+
+    if cli.no_color {
+        owo_colors::set_override(false);
+    }
+    if let Some(dir) = &cli.project_dir {
+        std::env::set_current_dir(dir)
+            .with_context(|| format!("Failed to enter {}", dir.display()))?;
+    }
+    let dir = std::env::current_dir()?;
+
+    let globals = global_args(&cli);
+
+    let (tool, args) = match cli.command {
+        Commands::Detect => {
+            let command = init_output(&matches, cli.format.as_deref());
+            let exit_code = run_detect(&dir, cli.platform);
+            std::process::exit(output::finish(&command, exit_code));
+        }
+        Commands::Hooks { action } => {
+            let command = init_output(&matches, cli.format.as_deref());
+            let exit_code = match action {
+                HooksAction::GenerateConfig {
+                    output,
+                    direct,
+                    force,
+                    stdout,
+                } => {
+                    let project = resolve_project(&dir, cli.platform)?;
+                    lefthook::generate(&project, &output, direct, force, stdout)
+                }
+                HooksAction::Validate { file } => lefthook::validate(&file, cli.platform),
+            };
+            std::process::exit(output::finish(&command, exit_code));
+        }
+        Commands::Watch { status, no_notify } => {
+            let command = init_output(&matches, cli.format.as_deref());
+            let project = resolve_project(&dir, cli.platform)?;
+            let exit_code = if status {
+                watch::print_status(&project.root)
+            } else {
+                let config_path = cli
+                    .config
+                    .as_deref()
+                    .map(|p| {
+                        p.to_str()
+                            .with_context(|| format!("Config path is not valid UTF-8: {}", p.display()))
+                    })
+                    .transpose()?;
+                let config = Config::load(config_path)?;
+                // Check output is captured and shown as text
+                let mut tool_args = globals;
+                if let Some(i) = tool_args.iter().position(|arg| arg == "--format") {
+                    tool_args.drain(i..i + 2);
+                }
+                watch::run(&project, &config.schema.watch, &tool_args, !no_notify)
+            };
+            std::process::exit(output::finish(&command, exit_code));
+        }
+        Commands::I18n { args } => ("foodshare-i18n", args),
+        Commands::Swift { args } => ("foodshare-swift", args),
+        Commands::Image { args } => ("fs-image", args),
+        Commands::Platform(args) => {
+            let project = resolve_project(&dir, cli.platform)?;
+            let forwarded: Vec<OsString> = globals.into_iter().chain(args).collect();
+            let code = run_tool(platform_tool(project.kind), &forwarded, &dir, cli.no_color)?;
+            std::process::exit(code);
+        }
+    };
+
+    // The cross-platform tools each have their own flags; only --no-color
+    // reaches them, through NO_COLOR
+    if globals.iter().any(|arg| arg != "--no-color") {
+        anyhow::bail!(
+            "`{}` takes its own flags; put them after the command, e.g. `foodshare i18n status --format json`",
+            tool
+        );
+    }
+    let code = run_tool(tool, &args, &dir, cli.no_color)?;
+    std::process::exit(code);
+}
+
+/// Start the reporter for `--format`, returning the command path
+fn init_output(matches: &clap::ArgMatches, format: Option<&str>) -> String {
+    match format.unwrap_or("text").parse::<Format>() {
+        Ok(format) => output::init(format),
+        Err(e) => {
+            Status::error(&e);
+            std::process::exit(exit_codes::FAILURE);
+        }
+    }
+    output::command_path(matches)
+}
+
+/// Project detected from `dir`, or `dir` itself with `--platform`
+fn resolve_project(dir: &Path, platform: Option<ProjectKind>) -> Result<Project> {
+    if let Some(kind) = platform {
+        return Ok(Project {
+            root: dir.to_path_buf(),
+            kind,
+        });
+    }
+    Project::detect(dir).with_context(|| {
+        format!(
+            "Could not tell the project type of {}; pass --platform ios, android, web or backend",
+            dir.display()
+        )
+    })
+}
+
+/// The global flags that were given, in the form every platform tool takes
+fn global_args(cli: &Cli) -> Vec<OsString> {
+    let mut args = Vec::new();
+    if let Some(config) = &cli.config {
+        args.push(OsString::from("--config"));
+        args.push(config.clone().into_os_string());
+    }
+    args.extend((0..cli.verbose).map(|_| OsString::from("--verbose")));
+    if cli.quiet {
+        args.push("--quiet".into());
+    }
+    if cli.no_color {
+        args.push("--no-color".into());
+    }
+    if let Some(format) = &cli.format {
+        args.push("--format".into());
+        args.push(format.into());
+    }
+    if cli.yes {
+        args.push("--yes".into());
+    }
+    args
+}
+
+/// Find a tool next to this executable, else on `PATH`
+fn locate(tool: &str) -> Option<PathBuf> {
+    let sibling = std::env::current_exe()
+        .ok()
+        .and_then(|exe| {
+            exe.parent()
+                .map(|dir| dir.join(format!("{}{}", tool, std::env::consts::EXE_SUFFIX)))
+        })
+        .filter(|path| path.is_file());
+    sibling.or_else(|| which::which(tool).ok())
+}
+
+/// Run `tool` in `dir` and return its exit code
+fn run_tool(tool: &str, args: &[OsString], dir: &Path, no_color: bool) -> Result<i32> {
+    let path = locate(tool).with_context(|| {
+        format!(
+            "`{}` is not installed; build it with `cargo install --path bins/{}`",
+            tool, tool
+        )
+    })?;
+    let mut command = Command::new(&path);
+    command.args(args).current_dir(dir);
+    if no_color {
+        command.env("NO_COLOR", "1");
+    }
+    let status = command
+        .status()
+        .with_context(|| format!("Failed to run {}", path.display()))?;
+    Ok(status.code().unwrap_or(exit_codes::FAILURE))
+}
+
+fn run_detect(dir: &Path, platform: Option<ProjectKind>) -> i32 {
+    let detected = Project::detect(dir);
+    let Some(kind) = platform.or(detected.as_ref().map(|p| p.kind)) else {
+        Status::error(&format!(
+            "No iOS, Android, web or backend project found at {}",
+            dir.display()
+        ));
+        textln!("  Pass --platform or set FOODSHARE_PLATFORM to choose one");
+        return exit_codes::FAILURE;
+    };
+    let tool = platform_tool(kind);
+    let detection = Detection {
+        kind,
+        root: if platform.is_some() {
+            None
+        } else {
+            detected.map(|p| p.root)
+        },
+        tool,
+        tool_path: locate(tool),
+    };
+
+    textln!(
+        "{} {}",
+        "Project:".bold(),
+        detection.kind.to_string().cyan()
+    );
+    match &detection.root {
+        Some(root) => textln!("{} {}", "Root:".bold(), root.display()),
+        None => textln!("{} --platform", "Root:".bold()),
+    }
+    match &detection.tool_path {
+        Some(path) => textln!("{} {} ({})", "Tool:".bold(), tool, path.display().dimmed()),
+        None => textln!("{} {} {}", "Tool:".bold(), tool, "(not installed)".yellow()),
+    }
+    output::data("project", &detection);
+
+    if detection.tool_path.is_some() {
+        exit_codes::SUCCESS
+    } else {
+        Status::warning(&format!(
+            "{} is not installed; build it with `cargo install --path bins/{}`",
+            tool, tool
+        ));
+        exit_codes::FAILURE
+    }
+}
@@ -0,0 +1,507 @@
+//! Watch mode - re-run checks as files change
+//!
+//! Changes are collected until the tree has been quiet for the debounce
+//! period, then each check whose patterns match a changed file runs with
+//! the platform tool; checks with `files = true` get just those files.
+//! A desktop notification is shown when a check starts or stops failing.
+//!
+//! While it runs, the watch answers `foodshare watch --status` over a Unix
+//! socket in the temp directory, one per project root.
+
+use anyhow::{Context, Result};
+use foodshare_cli::output::{self, Status};
+use foodshare_cli::textln;
+use foodshare_core::config::{WatchCheck, WatchConfig};
+use foodshare_core::error::exit_codes;
+use foodshare_core::project::{Project, ProjectKind};
+use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Checks run when the config has none
+fn default_checks(kind: ProjectKind) -> Vec<WatchCheck> {
+    let check = |name: &str, run: &[&str], patterns: &[&str]| WatchCheck {
+        name: name.to_string(),
+        run: run.iter().map(ToString::to_string).collect(),
+        patterns: patterns.iter().map(ToString::to_string).collect(),
+        files: true,
+    };
+    let scripts = ["*.ts", "*.tsx", "*.js", "*.jsx", "*.mjs"];
+    match kind {
+        ProjectKind::Ios => vec![
+            check("lint", &["lint"], &["*.swift"]),
+            check("secrets", &["secrets"], &[]),
+        ],
+        ProjectKind::Android => vec![
+            check("lint", &["lint"], &["*.kt", "*.kts", "*.swift"]),
+            check("secrets", &["secrets"], &[]),
+        ],
+        ProjectKind::Web => vec![
+            check("security", &["security"], &scripts),
+            check("accessibility", &["accessibility"], &["*.tsx", "*.jsx"]),
+        ],
+        ProjectKind::Backend => vec![check("security", &["security"], &scripts)],
+    }
+}
+
+/// Outcome of a check's last run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum CheckStatus {
+    /// Not run yet
+    Pending,
+    Running,
+    Pass,
+    Fail,
+    /// The tool couldn't be started
+    Error,
+}
+
+/// A check as reported by `--status`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CheckState {
+    name: String,
+    status: CheckStatus,
+    /// Files the last run was given
+    files: usize,
+    duration_ms: u64,
+    /// Unix time the last run finished
+    finished_at: Option<u64>,
+}
+
+/// State of a running watch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct WatchStatus {
+    pid: u32,
+    kind: String,
+    root: PathBuf,
+    /// Unix time the watch started
+    started_at: u64,
+    /// Batches of changes checked
+    runs: u64,
+    checks: Vec<CheckState>,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Socket the watch of `root` answers on
+fn socket_path(root: &Path) -> PathBuf {
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    root.hash(&mut hasher);
+    std::env::temp_dir().join(format!("foodshare-watch-{:016x}.sock", hasher.finish()))
+}
+
+/// Watch `project` and run the checks on each batch of changes
+///
+/// Runs until the watcher fails or the process is interrupted.
+pub fn run(project: &Project, config: &WatchConfig, tool_args: &[OsString], notify: bool) -> i32 {
+    if output::is_machine() {
+        Status::error("watch prints for people; use `foodshare watch --status --format json`");
+        return exit_codes::FAILURE;
+    }
+    match watch(project, config, tool_args, notify && config.notify) {
+        Ok(()) => exit_codes::SUCCESS,
+        Err(e) => {
+            Status::error(&format!("{:#}", e));
+            exit_codes::FAILURE
+        }
+    }
+}
+
+fn watch(
+    project: &Project,
+    config: &WatchConfig,
+    tool_args: &[OsString],
+    notify: bool,
+) -> Result<()> {
+    use notify::{RecursiveMode, Watcher};
+
+    let tool = crate::platform_tool(project.kind);
+    let tool_path = crate::locate(tool).with_context(|| {
+        format!(
+            "`{}` is not installed; build it with `cargo install --path bins/{}`",
+            tool, tool
+        )
+    })?;
+    let checks = if config.checks.is_empty() {
+        default_checks(project.kind)
+    } else {
+        config.checks.clone()
+    };
+    let patterns = checks
+        .iter()
+        .map(|check| {
+            check
+                .patterns
+                .iter()
+                .map(|p| {
+                    glob::Pattern::new(p).with_context(|| {
+                        format!("Bad pattern `{}` in watch check {}", p, check.name)
+                    })
+                })
+                .collect::<Result<Vec<_>>>()
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let status = Arc::new(Mutex::new(WatchStatus {
+        pid: std::process::id(),
+        kind: project.kind.to_string(),
+        root: project.root.clone(),
+        started_at: unix_now(),
+        runs: 0,
+        checks: checks
+            .iter()
+            .map(|check| CheckState {
+                name: check.name.clone(),
+                status: CheckStatus::Pending,
+                files: 0,
+                duration_ms: 0,
+                finished_at: None,
+            })
+            .collect(),
+    }));
+    #[cfg(unix)]
+    let _socket = StatusSocket::bind(&socket_path(&project.root), Arc::clone(&status))?;
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).context("Failed to start file watcher")?;
+    watcher
+        .watch(&project.root, RecursiveMode::Recursive)
+        .with_context(|| format!("Failed to watch {}", project.root.display()))?;
+
+    textln!();
+    textln!(
+        "{} {} project at {}",
+        "👀 Watching".bold(),
+        project.kind.to_string().cyan(),
+        project.root.display()
+    );
+    textln!(
+        "   {} with {}; Ctrl-C to stop",
+        checks
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", "),
+        tool
+    );
+
+    let debounce = Duration::from_millis(config.debounce_ms);
+    let mut pending = BTreeSet::new();
+    let mut last_change: Option<Instant> = None;
+    loop {
+        match rx.recv_timeout(Duration::from_millis(100)) {
+            Ok(Ok(event)) => {
+                if matches!(event.kind, notify::EventKind::Access(_)) {
+                    continue;
+                }
+                for path in event.paths {
+                    let Ok(relative) = path.strip_prefix(&project.root) else {
+                        continue;
+                    };
+                    let ignored = relative.components().any(|c| {
+                        c.as_os_str() == ".git"
+                            || config
+                                .ignore
+                                .iter()
+                                .any(|name| c.as_os_str() == name.as_str())
+                    });
+                    if !ignored {
+                        pending.insert(path);
+                        last_change = Some(Instant::now());
+                    }
+                }
+            }
+            Ok(Err(e)) => anyhow::bail!("File watcher error: {}", e),
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {}
+            Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => return Ok(()),
+        }
+
+        if last_change.is_some_and(|at| at.elapsed() >= debounce) {
+            last_change = None;
+            let changed: Vec<PathBuf> = std::mem::take(&mut pending).into_iter().collect();
+            run_checks(
+                &changed, &checks, &patterns, &tool_path, tool_args, &status, notify,
+            );
+        }
+    }
+}
+
+/// Run the checks triggered by `changed`
+fn run_checks(
+    changed: &[PathBuf],
+    checks: &[WatchCheck],
+    patterns: &[Vec<glob::Pattern>],
+    tool_path: &Path,
+    tool_args: &[OsString],
+    status: &Mutex<WatchStatus>,
+    notify: bool,
+) {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    textln!();
+    textln!(
+        "{} {} file(s) changed",
+        format!("[{}]", chrono::Local::now().format("%H:%M:%S")).dimmed(),
+        changed.len()
+    );
+    lock(status).runs += 1;
+
+    for (index, (check, patterns)) in checks.iter().zip(patterns).enumerate() {
+        let matching: Vec<&PathBuf> = changed
+            .iter()
+            .filter(|path| {
+                patterns.is_empty()
+                    || path
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .is_some_and(|name| patterns.iter().any(|p| p.matches(name)))
+            })
+            .collect();
+        // Deleted files trigger the check but aren't passed to it
+        let files: Vec<&Path> = matching
+            .iter()
+            .filter(|path| path.is_file())
+            .map(|path| path.strip_prefix(&cwd).unwrap_or(path))
+            .collect();
+        if matching.is_empty() || (check.files && files.is_empty()) {
+            continue;
+        }
+
+        let previous = {
+            let mut status = lock(status);
+            let state = &mut status.checks[index];
+            let previous = state.status;
+            state.status = CheckStatus::Running;
+            previous
+        };
+
+        let mut command = Command::new(tool_path);
+        command.args(tool_args).args(&check.run);
+        if check.files {
+            command.args(&files);
+        }
+        let started = Instant::now();
+        let result = command.stdin(Stdio::null()).output();
+        let elapsed = started.elapsed();
+
+        let passed_files = if check.files { files.len() } else { 0 };
+        let detail = if check.files {
+            format!("{} file(s), {:.1}s", passed_files, elapsed.as_secs_f64())
+        } else {
+            format!("{:.1}s", elapsed.as_secs_f64())
+        };
+        let outcome = match result {
+            Ok(out) if out.status.success() => {
+                textln!(
+                    "  {} {} {}",
+                    "✓".green(),
+                    check.name,
+                    format!("({})", detail).dimmed()
+                );
+                CheckStatus::Pass
+            }
+            Ok(out) => {
+                textln!(
+                    "  {} {} {}",
+                    "✗".red(),
+                    check.name.red(),
+                    format!("({})", detail).dimmed()
+                );
+                let text = format!(
+                    "{}{}",
+                    String::from_utf8_lossy(&out.stdout),
+                    String::from_utf8_lossy(&out.stderr)
+                );
+                for line in text.trim_end().lines() {
+                    textln!("    {}", line);
+                }
+                CheckStatus::Fail
+            }
+            Err(e) => {
+                textln!("  {} {}: {}", "✗".red(), check.name.red(), e);
+                CheckStatus::Error
+            }
+        };
+
+        {
+            let mut status = lock(status);
+            let state = &mut status.checks[index];
+            state.status = outcome;
+            state.files = passed_files;
+            state.duration_ms = u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX);
+            state.finished_at = Some(unix_now());
+        }
+
+        if notify {
+            let failing = |s: CheckStatus| matches!(s, CheckStatus::Fail | CheckStatus::Error);
+            if failing(outcome) && !failing(previous) {
+                desktop_notification("foodshare watch", &format!("✗ {} failed", check.name));
+            } else if !failing(outcome) && failing(previous) {
+                desktop_notification("foodshare watch", &format!("✓ {} passes again", check.name));
+            }
+        }
+    }
+}
+
+fn lock(status: &Mutex<WatchStatus>) -> std::sync::MutexGuard<'_, WatchStatus> {
+    status
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Show a desktop notification, if the platform has a way to
+fn desktop_notification(title: &str, body: &str) {
+    let mut command = if cfg!(target_os = "macos") {
+        let script = format!("display notification {:?} with title {:?}", body, title);
+        let mut command = Command::new("osascript");
+        command.args(["-e", &script]);
+        command
+    } else if cfg!(target_os = "linux") {
+        let mut command = Command::new("notify-send");
+        command.args([title, body]);
+        command
+    } else {
+        return;
+    };
+    // Best effort: a missing notifier shouldn't stop the watch
+    let _ = command.stdout(Stdio::null()).stderr(Stdio::null()).status();
+}
+
+/// Socket answering status requests, removed when dropped
+#[cfg(unix)]
+struct StatusSocket {
+    path: PathBuf,
+}
+
+#[cfg(unix)]
+impl StatusSocket {
+    fn bind(path: &Path, status: Arc<Mutex<WatchStatus>>) -> Result<Self> {
+        use std::io::Write;
+        use std::os::unix::net::{UnixListener, UnixStream};
+
+        if path.exists() {
+            if UnixStream::connect(path).is_ok() {
+                anyhow::bail!(
+                    "A watch is already running for this project (see `foodshare watch --status`)"
+                );
+            }
+            // Left behind by a watch that was killed
+            std::fs::remove_file(path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+        }
+        let listener = UnixListener::bind(path)
+            .with_context(|| format!("Failed to listen on {}", path.display()))?;
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let json = serde_json::to_string(&*lock(&status)).unwrap_or_default();
+                let _ = writeln!(stream, "{}", json);
+            }
+        });
+        Ok(Self {
+            path: path.to_path_buf(),
+        })
+    }
+}
+
+#[cfg(unix)]
+impl Drop for StatusSocket {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Print the checks of the watch running for `root`
+pub fn print_status(root: &Path) -> i32 {
+    let status = match read_status(root) {
+        Ok(Some(status)) => status,
+        Ok(None) => {
+            Status::info(&format!("No watch running for {}", root.display()));
+            return exit_codes::FAILURE;
+        }
+        Err(e) => {
+            Status::error(&format!("{:#}", e));
+            return exit_codes::FAILURE;
+        }
+    };
+
+    let now = unix_now();
+    textln!(
+        "{} {} project at {} (pid {}, {} run(s) in {})",
+        "Watching".bold(),
+        status.kind.cyan(),
+        status.root.display(),
+        status.pid,
+        status.runs,
+        ago(now.saturating_sub(status.started_at))
+    );
+    for check in &status.checks {
+        let (mark, label) = match check.status {
+            CheckStatus::Pending => ("·".dimmed().to_string(), "not run yet".dimmed().to_string()),
+            CheckStatus::Running => ("…".yellow().to_string(), "running".yellow().to_string()),
+            CheckStatus::Pass => ("✓".green().to_string(), "pass".green().to_string()),
+            CheckStatus::Fail => ("✗".red().to_string(), "fail".red().to_string()),
+            CheckStatus::Error => ("✗".red().to_string(), "error".red().to_string()),
+        };
+        let when = check
+            .finished_at
+            .map(|at| {
+                format!(", {} ago", ago(now.saturating_sub(at)))
+                    .dimmed()
+                    .to_string()
+            })
+            .unwrap_or_default();
+        textln!("  {} {:<16} {}{}", mark, check.name, label, when);
+    }
+    output::data("watch", &status);
+
+    if status
+        .checks
+        .iter()
+        .any(|c| matches!(c.status, CheckStatus::Fail | CheckStatus::Error))
+    {
+        exit_codes::FAILURE
+    } else {
+        exit_codes::SUCCESS
+    }
+}
+
+#[cfg(unix)]
+fn read_status(root: &Path) -> Result<Option<WatchStatus>> {
+    use std::io::Read;
+    use std::os::unix::net::UnixStream;
+
+    let Ok(mut stream) = UnixStream::connect(socket_path(root)) else {
+        return Ok(None);
+    };
+    let mut json = String::new();
+    stream
+        .read_to_string(&mut json)
+        .context("Failed to read the watch status")?;
+    serde_json::from_str(&json)
+        .map(Some)
+        .context("Failed to parse the watch status")
+}
+
+#[cfg(not(unix))]
+fn read_status(_root: &Path) -> Result<Option<WatchStatus>> {
+    anyhow::bail!("watch --status needs Unix sockets")
+}
+
+fn ago(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m", secs / 60),
+        _ => format!("{}h", secs / 3600),
+    }
+}
@@ -3,10 +3,12 @@
 //! OWASP security scanning and development tools for Next.js/React.
 
 use anyhow::Result;
-use clap::{Parser, Subcommand};
-use foodshare_cli::output::Status;
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use foodshare_cli::output::{self, Format, Status};
+use foodshare_cli::{prompt, textln};
 use foodshare_core::config::Config;
 use foodshare_core::error::exit_codes;
+use foodshare_hooks::plugins::Hook;
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -14,9 +16,29 @@ use std::path::PathBuf;
 #[command(about = "Fast git hooks for Foodshare web")]
 #[command(version)]
 struct Cli {
-    /// Enable verbose output
+    /// Config file path
     #[arg(short, long, global = true)]
-    verbose: bool,
+    config: Option<PathBuf>,
+
+    /// Increase output verbosity
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
+
+    /// Suppress non-error output
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Disable colored output
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Output format: text, json, ndjson (perf also takes github)
+    #[arg(long, global = true, default_value = "text")]
+    format: String,
+
+    /// Answer yes to confirmations and take the default elsewhere
+    #[arg(short, long, global = true)]
+    yes: bool,
 
     #[command(subcommand)]
     command: Commands,
@@ -26,6 +48,12 @@ struct Cli {
 enum Commands {
     /// Security checks (secrets, credentials, debug statements)
     Security {
+        /// Scan every file again instead of reusing findings for unchanged ones
+        #[arg(long)]
+        no_cache: bool,
+        /// Check found AWS, GitHub, Stripe and Supabase keys with their providers
+        #[arg(long)]
+        verify: bool,
         /// Files to check
         #[arg(trailing_var_arg = true)]
         files: Vec<String>,
@@ -50,6 +78,91 @@ enum Commands {
 
     /// Next.js/React/Vercel security vulnerabilities check
     NextjsSecurity {
+        /// Only report findings at or above this confidence: low, medium, high
+        #[arg(long, default_value = "low")]
+        min_confidence: String,
+        /// Files to check
+        #[arg(trailing_var_arg = true)]
+        files: Vec<String>,
+    },
+
+    /// Check server and client components stay on their side of the boundary
+    Boundaries {
+        /// Next.js app root
+        #[arg(long, default_value = ".")]
+        path: PathBuf,
+    },
+
+    /// Lint Supabase queries (interpolated filters, unbounded reads, service-role key)
+    SupabaseQueries {
+        /// Files to check
+        #[arg(trailing_var_arg = true)]
+        files: Vec<String>,
+    },
+
+    /// Check server-only env vars don't reach client components or chunks
+    EnvLeaks {
+        /// Next.js build output directory
+        #[arg(long, default_value = ".next")]
+        next_dir: PathBuf,
+        /// Only scan sources, not the build output
+        #[arg(long)]
+        skip_build: bool,
+        /// Variable clients may read without the NEXT_PUBLIC_ prefix (repeatable)
+        #[arg(long = "allow", value_name = "NAME")]
+        allow: Vec<String>,
+        /// Files to check
+        #[arg(trailing_var_arg = true)]
+        files: Vec<String>,
+    },
+
+    /// Validate the Content Security Policy
+    Csp {
+        /// Next.js app root
+        #[arg(long, default_value = ".")]
+        path: PathBuf,
+        /// Print a policy recommended from the build output instead
+        #[arg(long)]
+        generate: bool,
+        /// Next.js build output directory (for --generate)
+        #[arg(long, default_value = ".next")]
+        next_dir: PathBuf,
+    },
+
+    /// Find unreachable modules, unused exports and orphaned public assets
+    DeadCode {
+        /// Next.js app root
+        #[arg(long, default_value = ".")]
+        path: PathBuf,
+        /// Allowlist of paths and exports to ignore
+        #[arg(long, default_value = foodshare_web::dead_code::ALLOWLIST_FILE)]
+        allowlist: PathBuf,
+    },
+
+    /// Audit npm dependencies for known vulnerabilities and denied licenses
+    DepsAudit {
+        /// Directory with package-lock.json or pnpm-lock.yaml
+        #[arg(long, default_value = ".")]
+        path: PathBuf,
+        /// Lowest severity that fails: low, medium, high, critical (default from config)
+        #[arg(long)]
+        fail_on: Option<String>,
+        /// Skip development-only packages
+        #[arg(long)]
+        omit_dev: bool,
+        /// Use cached OSV results only
+        #[arg(long)]
+        offline: bool,
+    },
+
+    /// Group and sort imports and enforce path aliases
+    Imports {
+        /// Next.js app root (where tsconfig.json is)
+        #[arg(long, default_value = ".")]
+        path: PathBuf,
+        /// Rewrite the files instead of reporting
+        #[arg(long)]
+        fix: bool,
         /// Files to check
         #[arg(trailing_var_arg = true)]
         files: Vec<String>,
@@ -62,11 +175,33 @@ enum Commands {
         files: Vec<String>,
     },
 
-    /// Analyze bundle size
+    /// Analyze bundle size and per-route first-load JS
     BundleSize {
         /// Threshold in KB
         #[arg(long)]
         threshold: Option<u64>,
+        /// Baseline to compare routes against
+        #[arg(long, default_value = foodshare_web::bundle_size::BASELINE_FILE)]
+        baseline: PathBuf,
+        /// Save this build as the new baseline
+        #[arg(long)]
+        update_baseline: bool,
+    },
+
+    /// Run Lighthouse against a local build and enforce performance budgets
+    Perf {
+        /// Next.js app root
+        #[arg(long, default_value = ".")]
+        path: PathBuf,
+        /// Origin the build is served on (default from config)
+        #[arg(long)]
+        url: Option<String>,
+        /// Route to audit (repeatable, default from config)
+        #[arg(long = "route", value_name = "ROUTE")]
+        routes: Vec<String>,
+        /// Lighthouse runs per route (default from config)
+        #[arg(long)]
+        runs: Option<usize>,
     },
 
     /// Run all pre-commit checks
@@ -75,27 +210,86 @@ enum Commands {
         #[arg(trailing_var_arg = true)]
         files: Vec<String>,
     },
+
+    /// Custom checks from the plugins directory and config
+    Plugins {
+        #[command(subcommand)]
+        action: PluginsAction,
+    },
+
+    /// Update this binary to the latest signed release
+    #[command(name = "self-update")]
+    SelfUpdate(foodshare_self_update::SelfUpdateArgs),
+}
+
+#[derive(Subcommand)]
+enum PluginsAction {
+    /// List the registered plugins
+    List,
+    /// Run the plugins of a hook
+    Run {
+        /// Hook whose plugins run: pre-commit, pre-push
+        #[arg(long, default_value = "pre-commit")]
+        hook: Hook,
+        /// Files to check instead of the staged files
+        #[arg(trailing_var_arg = true)]
+        files: Vec<PathBuf>,
+    },
 }
 
 fn main() -> Result<()> {
-    let cli = Cli::parse();
-    let config = Config::default();
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches)?;
+    let command = output::command_path(&matches);
+
+    // perf prints GitHub annotations itself
+    let github = cli.format == "github" && matches!(cli.command, Commands::Perf { .. });
+    match if github { Ok(Format::Text) } else { cli.format.parse::<Format>() } {
+        Ok(format) => output::init(format),
+        Err(e) => {
+            Status::error(&e);
+            std::process::exit(exit_codes::FAILURE);
+        }
+    }
+
+    if cli.no_color {
+        owo_colors::set_override(false);
+    }
+    prompt::set_assume_yes(cli.yes);
+
+    let config = Config::load(cli.config.as_deref().map(|p| p.to_str().unwrap()))?;
 
     let result = match cli.command {
-        Commands::Security { files } => run_security(&files, &config),
+        Commands::Security { files, no_cache, verify } => run_security(&files, no_cache, verify, &config),
         Commands::ConventionalCommit { message_file } => run_conventional_commit(&message_file, &config),
         Commands::ProtectedBranch => run_protected_branch(),
         Commands::LargeFiles { max_size } => run_large_files(max_size),
-        Commands::NextjsSecurity { files } => run_nextjs_security(&files),
+        Commands::NextjsSecurity { min_confidence, files } => run_nextjs_security(&files, &min_confidence),
+        Commands::Boundaries { path } => run_boundaries(&path, &config),
+        Commands::SupabaseQueries { files } => run_supabase_queries(&files, &config),
+        Commands::EnvLeaks { next_dir, skip_build, allow, files } => run_env_leaks(&files, &next_dir, skip_build, &allow),
+        Commands::Csp { path, generate, next_dir } => run_csp(&path, generate, &next_dir),
+        Commands::DeadCode { path, allowlist } => run_dead_code(&path, &allowlist),
+        Commands::DepsAudit { path, fail_on, omit_dev, offline } => {
+            run_deps_audit(&path, fail_on.as_deref(), omit_dev, offline, &config)
+        }
+        Commands::Imports { path, fix, files } => run_imports(&files, &path, fix, &config),
         Commands::Accessibility { files } => run_accessibility(&files),
-        Commands::BundleSize { threshold } => run_bundle_size(threshold),
+        Commands::BundleSize { threshold, baseline, update_baseline } => {
+            run_bundle_size(threshold, &baseline, update_baseline, &config)
+        }
+        Commands::Perf { path, url, routes, runs } => run_perf(&path, url, routes, runs, github, &config),
         Commands::PreCommit { files } => run_pre_commit(&files, &config),
+        Commands::Plugins { action } => run_plugins(action, &config),
+        Commands::SelfUpdate(args) => {
+            foodshare_self_update::run(env!("CARGO_BIN_NAME"), env!("CARGO_PKG_VERSION"), &args, &config.schema.update)
+        }
     };
 
-    std::process::exit(result);
+    std::process::exit(output::finish(&command, result));
 }
 
-fn run_security(files: &[String], config: &Config) -> i32 {
+fn run_security(files: &[String], no_cache: bool, verify: bool, config: &Config) -> i32 {
     use foodshare_hooks::secrets;
 
     let paths: Vec<PathBuf> = if files.is_empty() {
@@ -106,8 +300,21 @@ fn run_security(files: &[String], config: &Config) -> i32 {
         files.iter().map(PathBuf::from).collect()
     };
 
-    match secrets::scan_files(&paths, &config.schema.secrets) {
-        Ok(matches) => secrets::print_results(&matches),
+    match secrets::load_baseline(&config.schema.secrets) {
+        Ok(baseline) => {
+            let secrets_config = foodshare_core::config::SecretsConfig {
+                cache: config.schema.secrets.cache && !no_cache,
+                ..config.schema.secrets.clone()
+            };
+            let mut output = secrets::scan_files_with_config(&paths, &secrets_config, baseline.as_ref());
+            if verify {
+                if let Err(e) = secrets::verify_output(&mut output) {
+                    Status::error(&format!("Verification error: {}", e));
+                    return exit_codes::FAILURE;
+                }
+            }
+            secrets::print_output(&output)
+        }
         Err(e) => {
             Status::error(&format!("Scan error: {}", e));
             exit_codes::FAILURE
@@ -115,6 +322,15 @@ fn run_security(files: &[String], config: &Config) -> i32 {
     }
 }
 
+fn run_plugins(action: PluginsAction, config: &Config) -> i32 {
+    use foodshare_hooks::plugins;
+
+    match action {
+        PluginsAction::List => plugins::list(&config.schema.plugins),
+        PluginsAction::Run { hook, files } => plugins::run_for_hook(hook, &files, &config.schema.plugins),
+    }
+}
+
 fn run_conventional_commit(message_file: &str, config: &Config) -> i32 {
     use foodshare_hooks::commit_msg;
 
@@ -203,8 +419,16 @@ fn run_large_files(max_size_kb: u64) -> i32 {
     }
 }
 
-fn run_nextjs_security(files: &[String]) -> i32 {
-    use foodshare_web::nextjs_security;
+fn run_nextjs_security(files: &[String], min_confidence: &str) -> i32 {
+    use foodshare_web::nextjs_security::{self, Confidence};
+
+    let min_confidence: Confidence = match min_confidence.parse() {
+        Ok(confidence) => confidence,
+        Err(e) => {
+            Status::error(&e);
+            return exit_codes::FAILURE;
+        }
+    };
 
     let paths: Vec<PathBuf> = if files.is_empty() {
         foodshare_core::file_scanner::scan_ts_files(std::path::Path::new("src"))
@@ -219,7 +443,40 @@ fn run_nextjs_security(files: &[String]) -> i32 {
     }
 
     match nextjs_security::scan_files(&paths) {
-        Ok(findings) => nextjs_security::print_results(&findings),
+        Ok(mut findings) => {
+            findings.retain(|f| f.confidence >= min_confidence);
+            nextjs_security::print_results(&findings)
+        }
+        Err(e) => {
+            Status::error(&format!("Scan error: {}", e));
+            exit_codes::FAILURE
+        }
+    }
+}
+
+fn run_boundaries(root: &std::path::Path, config: &Config) -> i32 {
+    use foodshare_web::boundaries;
+
+    match boundaries::analyze(root, &config.schema.boundaries) {
+        Ok(issues) => boundaries::print_results(&issues),
+        Err(e) => {
+            Status::error(&format!("Analysis error: {}", e));
+            exit_codes::FAILURE
+        }
+    }
+}
+
+fn run_supabase_queries(files: &[String], config: &Config) -> i32 {
+    use foodshare_web::supabase_queries;
+
+    let paths: Vec<PathBuf> = if files.is_empty() {
+        foodshare_core::file_scanner::scan_ts_files(std::path::Path::new("src")).unwrap_or_default()
+    } else {
+        files.iter().map(PathBuf::from).collect()
+    };
+
+    match supabase_queries::scan_files(&paths, &config.schema.supabase) {
+        Ok(findings) => supabase_queries::print_results(&findings),
         Err(e) => {
             Status::error(&format!("Scan error: {}", e));
             exit_codes::FAILURE
@@ -227,6 +484,140 @@ fn run_nextjs_security(files: &[String]) -> i32 {
     }
 }
 
+fn run_env_leaks(files: &[String], next_dir: &std::path::Path, skip_build: bool, allow: &[String]) -> i32 {
+    use foodshare_web::env_leaks;
+
+    let paths: Vec<PathBuf> = if files.is_empty() {
+        foodshare_core::file_scanner::scan_ts_files(std::path::Path::new("src"))
+            .unwrap_or_default()
+    } else {
+        files.iter().map(PathBuf::from).collect()
+    };
+
+    let mut leaks = env_leaks::scan_sources(&paths, allow);
+
+    if !skip_build {
+        if next_dir.join("static").is_dir() {
+            let env = env_leaks::read_env_files(std::path::Path::new("."));
+            match env_leaks::scan_build(next_dir, &env, allow) {
+                Ok(found) => leaks.extend(found),
+                Err(e) => {
+                    Status::error(&format!("Build scan error: {}", e));
+                    return exit_codes::FAILURE;
+                }
+            }
+        } else {
+            Status::info(&format!("No build at {}; scanning sources only", next_dir.display()));
+        }
+    }
+
+    env_leaks::print_results(&leaks)
+}
+
+fn run_csp(root: &std::path::Path, generate: bool, next_dir: &std::path::Path) -> i32 {
+    use foodshare_web::csp;
+
+    if generate {
+        if !next_dir.join("static").is_dir() {
+            Status::error(&format!("No build at {}; run `next build` first", next_dir.display()));
+            return exit_codes::FAILURE;
+        }
+        return match csp::recommend(next_dir) {
+            Ok(policy) => {
+                if output::is_machine() {
+                    output::data("policy", &policy.to_string());
+                } else {
+                    println!("{}", policy);
+                }
+                exit_codes::SUCCESS
+            }
+            Err(e) => {
+                Status::error(&format!("Build scan error: {}", e));
+                exit_codes::FAILURE
+            }
+        };
+    }
+
+    match csp::find_policies(root) {
+        Ok(policies) => csp::print_results(&policies),
+        Err(e) => {
+            Status::error(&format!("Scan error: {}", e));
+            exit_codes::FAILURE
+        }
+    }
+}
+
+fn run_dead_code(root: &std::path::Path, allowlist: &std::path::Path) -> i32 {
+    use foodshare_web::dead_code::{self, Allowlist};
+
+    let allowlist = match Allowlist::load(&root.join(allowlist)) {
+        Ok(allowlist) => allowlist,
+        Err(e) => {
+            Status::error(&format!("Failed to read allowlist: {}", e));
+            return exit_codes::FAILURE;
+        }
+    };
+
+    match dead_code::analyze(root, &allowlist) {
+        Ok(report) => dead_code::print_results(&report),
+        Err(e) => {
+            Status::error(&format!("Analysis error: {}", e));
+            exit_codes::FAILURE
+        }
+    }
+}
+
+fn run_deps_audit(root: &std::path::Path, fail_on: Option<&str>, omit_dev: bool, offline: bool, config: &Config) -> i32 {
+    use foodshare_web::nextjs_security::Severity;
+    use foodshare_web::npm_audit::{self, Osv};
+
+    let deps = &config.schema.deps;
+    let fail_on: Severity = match fail_on.unwrap_or(&deps.fail_on).parse() {
+        Ok(severity) => severity,
+        Err(e) => {
+            Status::error(&e);
+            return exit_codes::FAILURE;
+        }
+    };
+
+    let osv = Osv::new(Osv::default_cache_dir(), offline);
+    match npm_audit::audit(root, &osv, &deps.denied_licenses, omit_dev) {
+        Ok(report) => report.print(fail_on),
+        Err(e) => {
+            Status::error(&format!("Audit error: {:#}", e));
+            Status::info("Use --offline to audit from the OSV cache");
+            exit_codes::FAILURE
+        }
+    }
+}
+
+fn run_imports(files: &[String], root: &std::path::Path, fix: bool, config: &Config) -> i32 {
+    use foodshare_web::imports;
+
+    let paths: Vec<PathBuf> = if files.is_empty() {
+        foodshare_core::file_scanner::FileScanner::new(root.join("src"))
+            .with_extensions(imports::EXTENSIONS)
+            .scan()
+            .unwrap_or_default()
+    } else {
+        files.iter().map(PathBuf::from).collect()
+    };
+
+    check_imports(&paths, root, fix, config)
+}
+
+fn check_imports(paths: &[PathBuf], root: &std::path::Path, fix: bool, config: &Config) -> i32 {
+    use foodshare_web::imports;
+
+    match imports::check_files(root, paths, &config.schema.imports, fix) {
+        Ok(issues) => imports::print_results(&issues, fix),
+        Err(e) => {
+            Status::error(&format!("Check error: {}", e));
+            exit_codes::FAILURE
+        }
+    }
+}
+
 fn run_accessibility(files: &[String]) -> i32 {
     use foodshare_web::accessibility;
 
@@ -253,18 +644,110 @@ fn run_accessibility(files: &[String]) -> i32 {
     }
 }
 
-fn run_bundle_size(threshold: Option<u64>) -> i32 {
-    use foodshare_web::bundle_size;
+fn run_bundle_size(threshold: Option<u64>, baseline_path: &std::path::Path, update_baseline: bool, config: &Config) -> i32 {
+    use foodshare_web::bundle_size::{self, BundleAnalysis};
 
     let build_dir = std::path::Path::new(".");
 
-    match bundle_size::analyze_nextjs_build(build_dir) {
-        Ok(analysis) => {
-            bundle_size::print_analysis(&analysis, threshold);
-            exit_codes::SUCCESS
-        }
+    let analysis = match bundle_size::analyze_nextjs_build(build_dir) {
+        Ok(analysis) => analysis,
         Err(e) => {
             Status::error(&format!("Analysis error: {}", e));
+            return exit_codes::FAILURE;
+        }
+    };
+    bundle_size::print_analysis(&analysis, threshold);
+
+    if update_baseline {
+        return match analysis.save(baseline_path) {
+            Ok(()) => {
+                Status::success(&format!("Baseline saved to {}", baseline_path.display()));
+                exit_codes::SUCCESS
+            }
+            Err(e) => {
+                Status::error(&format!("Failed to save baseline: {}", e));
+                exit_codes::FAILURE
+            }
+        };
+    }
+
+    let baseline = if baseline_path.exists() {
+        match BundleAnalysis::load(baseline_path) {
+            Ok(baseline) => Some(baseline),
+            Err(e) => {
+                Status::error(&format!("Failed to read baseline: {}", e));
+                return exit_codes::FAILURE;
+            }
+        }
+    } else {
+        None
+    };
+    if let Some(baseline) = &baseline {
+        bundle_size::print_diff(&analysis, baseline);
+    }
+
+    let violations = analysis.budget_violations(baseline.as_ref(), &config.schema.bundle);
+    if violations.is_empty() {
+        exit_codes::SUCCESS
+    } else {
+        textln!();
+        for violation in &violations {
+            Status::error(violation);
+        }
+        exit_codes::FAILURE
+    }
+}
+
+fn run_perf(
+    root: &std::path::Path,
+    url: Option<String>,
+    routes: Vec<String>,
+    runs: Option<usize>,
+    github: bool,
+    config: &Config,
+) -> i32 {
+    use foodshare_web::perf;
+
+    let mut perf_config = config.schema.perf.clone();
+    if let Some(url) = url {
+        perf_config.base_url = url;
+    }
+    if !routes.is_empty() {
+        perf_config.routes = routes;
+    }
+    if let Some(runs) = runs {
+        perf_config.runs = runs;
+    }
+
+    // Reuse a server that is already up; otherwise start the configured one
+    let _server = if perf::is_listening(&perf_config.base_url) {
+        None
+    } else if let Some(command) = &perf_config.start_command {
+        let timeout = std::time::Duration::from_secs(perf_config.start_timeout_secs);
+        match perf::start_server(root, command, &perf_config.base_url, timeout) {
+            Ok(server) => Some(server),
+            Err(e) => {
+                Status::error(&format!("Server error: {:#}", e));
+                return exit_codes::FAILURE;
+            }
+        }
+    } else {
+        Status::error(&format!("Nothing is serving {}", perf_config.base_url));
+        eprintln!("Run `next build && next start` first, or set [perf] start_command");
+        return exit_codes::FAILURE;
+    };
+
+    match perf::audit(root, &perf_config) {
+        Ok(results) => {
+            let violations = perf::check_budgets(&results, &perf_config);
+            if github {
+                perf::print_annotations(&results, &violations)
+            } else {
+                perf::print_results(&results, &violations)
+            }
+        }
+        Err(e) => {
+            Status::error(&format!("Lighthouse error: {:#}", e));
             exit_codes::FAILURE
         }
     }
@@ -274,7 +757,7 @@ fn run_pre_commit(files: &[String], config: &Config) -> i32 {
     Status::info("Running pre-commit checks...");
 
     // Security check
-    let security_result = run_security(files, config);
+    let security_result = run_security(files, false, false, config);
     if security_result != exit_codes::SUCCESS {
         return security_result;
     }
@@ -285,6 +768,26 @@ fn run_pre_commit(files: &[String], config: &Config) -> i32 {
         return large_files_result;
     }
 
+    // Import organization of staged sources
+    let staged: Vec<PathBuf> = if files.is_empty() {
+        foodshare_core::git::GitRepo::open_current()
+            .and_then(|r| r.staged_files_with_extension(foodshare_web::imports::EXTENSIONS))
+            .unwrap_or_default()
+    } else {
+        files.iter().map(PathBuf::from).collect()
+    };
+    let imports_result = check_imports(&staged, std::path::Path::new("."), false, config);
+    if imports_result != exit_codes::SUCCESS {
+        return imports_result;
+    }
+
+    // Repository-specific checks
+    let files: Vec<PathBuf> = files.iter().map(PathBuf::from).collect();
+    let plugins_result = foodshare_hooks::plugins::run_for_hook(Hook::PreCommit, &files, &config.schema.plugins);
+    if plugins_result != exit_codes::SUCCESS {
+        return plugins_result;
+    }
+
     Status::success("All pre-commit checks passed");
     exit_codes::SUCCESS
 }
@@ -0,0 +1,425 @@
+//! APK/AAB size analysis
+//!
+//! Reads the zip directory of a built APK or App Bundle and breaks it down
+//! by module and by dex code, resources, assets and native libraries per
+//! ABI, then compares the result with a baseline committed to the repo so CI
+//! can fail when the app outgrows its budget.
+//!
+//! The download size of an APK is its file size. For an App Bundle, Play
+//! serves each device the native libraries of one ABI only, so the download
+//! size counts the compressed size of everything except the native libraries
+//! of all but the largest ABI.
+//!
+//! Sizes are in decimal units (1 MB = 1,000,000 bytes), matching the Play
+//! Console. Baselines are compared and budgets checked with
+//! [`foodshare_cli::size`].
+
+use foodshare_cli::output::format_size_decimal as format_size;
+use foodshare_cli::size::{self, format_change, vs_baseline};
+use foodshare_cli::textln;
+use foodshare_core::config::SizeConfig;
+use foodshare_core::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Default location of the committed baseline
+pub const BASELINE_FILE: &str = ".foodshare/android-size.json";
+
+/// What a part of the package is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComponentKind {
+    /// Compiled code (`classes*.dex`)
+    Dex,
+    /// Native libraries of one ABI
+    NativeLib,
+    /// Compiled resources (`res/`, `resources.arsc`, `resources.pb`)
+    Resources,
+    /// Raw assets (`assets/`)
+    Assets,
+    /// Manifest, signatures and everything else
+    Other,
+}
+
+impl std::fmt::Display for ComponentKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Dex => "dex",
+            Self::NativeLib => "native",
+            Self::Resources => "resources",
+            Self::Assets => "assets",
+            Self::Other => "other",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Size of one part of the package
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SizeComponent {
+    /// Module (`base` for APKs and the base module of a bundle)
+    pub module: String,
+    /// Component name (`dex`, `lib/arm64-v8a`, `assets`)
+    pub name: String,
+    /// What the component is
+    pub kind: ComponentKind,
+    /// Compressed size in the package
+    pub compressed: u64,
+    /// Uncompressed size
+    pub uncompressed: u64,
+}
+
+/// Compressed size change of one component (module and name) against the baseline
+pub type SizeDelta = size::SizeDelta<(String, String)>;
+
+/// One file inside the package
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackageEntry {
+    /// Path inside the package
+    pub path: String,
+    /// Compressed size
+    pub compressed: u64,
+    /// Uncompressed size
+    pub uncompressed: u64,
+}
+
+/// Size breakdown of an APK or App Bundle
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackageSizeReport {
+    /// File name (`app-release.aab`)
+    pub package: String,
+    /// Whether the package is an App Bundle
+    pub bundle: bool,
+    /// Size of the package file
+    pub file_bytes: u64,
+    /// Components, largest first
+    pub components: Vec<SizeComponent>,
+    /// Largest files, largest first
+    #[serde(default)]
+    pub largest_files: Vec<PackageEntry>,
+}
+
+/// Largest files kept in a report
+const LARGEST_FILES: usize = 20;
+
+impl PackageSizeReport {
+    /// Measure an `.apk` or `.aab` file
+    pub fn from_file(path: &Path) -> Result<Self> {
+        if !path.is_file() {
+            return Err(Error::file_not_found(path));
+        }
+        let data = std::fs::read(path)?;
+        let entries = read_zip_entries(&data)
+            .map_err(|e| Error::validation(format!("{}: {}", path.display(), e)))?;
+        let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let bundle = path.extension().is_some_and(|ext| ext == "aab");
+        Ok(Self::from_entries(name, bundle, data.len() as u64, entries))
+    }
+
+    /// Build a report from the package's entries
+    pub fn from_entries(package: String, bundle: bool, file_bytes: u64, mut entries: Vec<PackageEntry>) -> Self {
+        let mut components: BTreeMap<(String, String), SizeComponent> = BTreeMap::new();
+        for entry in &entries {
+            let (module, name, kind) = classify(&entry.path, bundle);
+            let component = components
+                .entry((module.clone(), name.clone()))
+                .or_insert_with(|| SizeComponent {
+                    module,
+                    name,
+                    kind,
+                    compressed: 0,
+                    uncompressed: 0,
+                });
+            component.compressed += entry.compressed;
+            component.uncompressed += entry.uncompressed;
+        }
+        let mut components: Vec<SizeComponent> = components.into_values().collect();
+        components.sort_by(|a, b| b.compressed.cmp(&a.compressed).then_with(|| a.name.cmp(&b.name)));
+
+        entries.sort_by(|a, b| b.compressed.cmp(&a.compressed).then_with(|| a.path.cmp(&b.path)));
+        entries.truncate(LARGEST_FILES);
+
+        Self {
+            package,
+            bundle,
+            file_bytes,
+            components,
+            largest_files: entries,
+        }
+    }
+
+    /// Compressed size of each ABI's native libraries, largest first
+    pub fn abi_sizes(&self) -> Vec<(String, u64)> {
+        let mut abis: BTreeMap<String, u64> = BTreeMap::new();
+        for component in self.components.iter().filter(|c| c.kind == ComponentKind::NativeLib) {
+            let abi = component.name.trim_start_matches("lib/").to_string();
+            *abis.entry(abi).or_default() += component.compressed;
+        }
+        let mut abis: Vec<(String, u64)> = abis.into_iter().collect();
+        abis.sort_by_key(|(_, bytes)| std::cmp::Reverse(*bytes));
+        abis
+    }
+
+    /// Download size: the APK file, or a bundle's contents with one ABI
+    pub fn download_size(&self) -> u64 {
+        if !self.bundle {
+            return self.file_bytes;
+        }
+        let total: u64 = self.components.iter().map(|c| c.compressed).sum();
+        let other_abis: u64 = self.abi_sizes().iter().skip(1).map(|(_, bytes)| bytes).sum();
+        total - other_abis
+    }
+
+    /// Components whose compressed size changed since `baseline`, largest change first
+    pub fn diff(&self, baseline: &Self) -> Vec<SizeDelta> {
+        let index = |report: &Self| -> BTreeMap<(String, String), u64> {
+            report
+                .components
+                .iter()
+                .map(|c| ((c.module.clone(), c.name.clone()), c.compressed))
+                .collect()
+        };
+        size::diff(&index(baseline), &index(self))
+    }
+
+    /// Budget violations against `config`, comparing growth with `baseline`
+    pub fn budget_violations(&self, baseline: Option<&Self>, config: &SizeConfig) -> Vec<String> {
+        size::budget_violations(self.download_size(), baseline.map(Self::download_size), config)
+    }
+
+    /// Load a saved report
+    pub fn load(path: &Path) -> Result<Self> {
+        size::load(path)
+    }
+
+    /// Save the report as JSON, e.g. as the new baseline
+    pub fn save(&self, path: &Path) -> Result<()> {
+        size::save(self, path)
+    }
+
+    /// Print the breakdown, with changes against `baseline` if given
+    pub fn print(&self, baseline: Option<&Self>, top: usize) {
+        use owo_colors::OwoColorize;
+
+        textln!("{}", format!("{} size", self.package).bold());
+        textln!(
+            "  File:     {}{}",
+            format_size(self.file_bytes),
+            vs_baseline(self.file_bytes, baseline.map(|b| b.file_bytes))
+        );
+        textln!(
+            "  Download: {}{}",
+            format_size(self.download_size()),
+            vs_baseline(self.download_size(), baseline.map(Self::download_size))
+        );
+
+        let modules: std::collections::BTreeSet<&str> = self.components.iter().map(|c| c.module.as_str()).collect();
+        let multi_module = modules.len() > 1;
+        if multi_module {
+            textln!();
+            textln!("{}", "Modules".bold());
+            for module in modules {
+                let bytes: u64 = self.components.iter().filter(|c| c.module == module).map(|c| c.compressed).sum();
+                textln!("  {:>10}  {}", format_size(bytes).yellow(), module);
+            }
+        }
+
+        textln!();
+        textln!("{}", format!("Largest components (top {})", top).bold());
+        for component in self.components.iter().take(top) {
+            let name = if multi_module {
+                format!("{}/{}", component.module, component.name)
+            } else {
+                component.name.clone()
+            };
+            textln!(
+                "  {:>10}  {} ({}, {} uncompressed)",
+                format_size(component.compressed).yellow(),
+                name,
+                component.kind,
+                format_size(component.uncompressed)
+            );
+        }
+
+        let abis = self.abi_sizes();
+        if !abis.is_empty() {
+            textln!();
+            textln!("{}", "Native libraries per ABI".bold());
+            for (abi, bytes) in &abis {
+                textln!("  {:>10}  {}", format_size(*bytes).yellow(), abi);
+            }
+        }
+
+        if !self.largest_files.is_empty() {
+            textln!();
+            textln!("{}", format!("Largest files (top {})", top).bold());
+            for entry in self.largest_files.iter().take(top) {
+                textln!("  {:>10}  {}", format_size(entry.compressed).yellow(), entry.path);
+            }
+        }
+
+        if let Some(baseline) = baseline {
+            let deltas = self.diff(baseline);
+            textln!();
+            textln!("{}", "Changes since baseline".bold());
+            if deltas.is_empty() {
+                textln!("  No changes");
+            }
+            for delta in deltas.iter().take(top) {
+                let (module, name) = &delta.key;
+                textln!("  {:>10}  {}/{}{}", format_change(delta.change()), module, name, delta.note());
+            }
+        }
+    }
+}
+
+/// Module, component name and kind of a package path
+///
+/// Bundle paths start with the module (`base/dex/classes.dex`,
+/// `feature_map/lib/arm64-v8a/libmaps.so`); bundle metadata belongs to
+/// `base`.
+fn classify(path: &str, bundle: bool) -> (String, String, ComponentKind) {
+    let (module, rest) = match path.split_once('/') {
+        Some((module, rest)) if bundle && !matches!(module, "BUNDLE-METADATA" | "META-INF") => (module, rest),
+        _ => ("base", path),
+    };
+    let (name, kind) = if (rest.starts_with("classes") && rest.ends_with(".dex")) || rest.starts_with("dex/") {
+        ("dex".to_string(), ComponentKind::Dex)
+    } else if let Some(lib) = rest.strip_prefix("lib/") {
+        let abi = lib.split('/').next().unwrap_or_default();
+        (format!("lib/{}", abi), ComponentKind::NativeLib)
+    } else if rest.starts_with("res/") || rest == "resources.arsc" || rest == "resources.pb" {
+        ("resources".to_string(), ComponentKind::Resources)
+    } else if rest.starts_with("assets/") {
+        ("assets".to_string(), ComponentKind::Assets)
+    } else {
+        ("other".to_string(), ComponentKind::Other)
+    };
+    (module.to_string(), name, kind)
+}
+
+/// Read the entries of a zip file from its central directory
+pub fn read_zip_entries(data: &[u8]) -> std::result::Result<Vec<PackageEntry>, String> {
+    Ok(crate::zip::read_directory(data)?
+        .into_iter()
+        .map(|entry| PackageEntry {
+            path: entry.path,
+            compressed: entry.compressed,
+            uncompressed: entry.uncompressed,
+        })
+        .collect())
+}
+
+/// Find the newest release App Bundle, or else the release APK
+pub fn find_release_package(project_dir: &Path) -> Result<PathBuf> {
+    let outputs = project_dir.join("app/build/outputs");
+    for (dir, extension) in [("bundle/release", "aab"), ("apk/release", "apk")] {
+        let dir = outputs.join(dir);
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut packages: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == extension))
+            .collect();
+        packages.sort();
+        if let Some(package) = packages.into_iter().next() {
+            return Ok(package);
+        }
+    }
+    Err(Error::file_not_found(outputs.join("bundle/release/app-release.aab")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A zip with only a central directory, which is all the reader looks at
+    fn zip(entries: &[(&str, u32, u32)]) -> Vec<u8> {
+        let mut data = Vec::new();
+        for (name, compressed, uncompressed) in entries {
+            data.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+            data.extend_from_slice(&[0; 16]);
+            data.extend_from_slice(&compressed.to_le_bytes());
+            data.extend_from_slice(&uncompressed.to_le_bytes());
+            data.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            data.extend_from_slice(&[0; 16]);
+            data.extend_from_slice(name.as_bytes());
+        }
+        let directory_size = data.len() as u32;
+        data.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+        data.extend_from_slice(&[0; 6]);
+        data.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        data.extend_from_slice(&directory_size.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&0u16.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_bundle_breakdown() {
+        let data = zip(&[
+            ("base/dex/classes.dex", 4_000_000, 9_000_000),
+            ("base/dex/classes2.dex", 1_000_000, 2_000_000),
+            ("base/lib/arm64-v8a/libfoodshare.so", 3_000_000, 7_000_000),
+            ("base/lib/armeabi-v7a/libfoodshare.so", 2_000_000, 5_000_000),
+            ("base/res/", 0, 0),
+            ("base/res/drawable/hero.webp", 500_000, 500_000),
+            ("base/resources.pb", 200_000, 800_000),
+            ("feature_map/assets/tiles.bin", 1_500_000, 1_500_000),
+            ("BUNDLE-METADATA/com.android.tools.build.obfuscation/proguard.map", 300_000, 3_000_000),
+            ("BundleConfig.pb", 1_000, 1_000),
+        ]);
+        let entries = read_zip_entries(&data).unwrap();
+        assert_eq!(entries.len(), 9);
+        assert!(read_zip_entries(b"not a zip").is_err());
+
+        let report = PackageSizeReport::from_entries("app-release.aab".into(), true, data.len() as u64, entries);
+        assert_eq!(report.components[0].name, "dex");
+        assert_eq!(report.components[0].compressed, 5_000_000);
+        assert_eq!(
+            report.abi_sizes(),
+            [("arm64-v8a".to_string(), 3_000_000), ("armeabi-v7a".to_string(), 2_000_000)]
+        );
+        let feature = report.components.iter().find(|c| c.module == "feature_map").unwrap();
+        assert_eq!((feature.name.as_str(), feature.kind), ("assets", ComponentKind::Assets));
+        assert_eq!(report.download_size(), 12_501_000 - 2_000_000);
+        assert_eq!(report.largest_files[0].path, "base/dex/classes.dex");
+    }
+
+    #[test]
+    fn test_baseline_diff_and_budgets() {
+        let apk = |dex: u64, file_bytes: u64| {
+            PackageSizeReport::from_entries(
+                "app-release.apk".into(),
+                false,
+                file_bytes,
+                vec![
+                    PackageEntry { path: "classes.dex".into(), compressed: dex, uncompressed: dex * 2 },
+                    PackageEntry { path: "lib/x86_64/libfoodshare.so".into(), compressed: 1_000, uncompressed: 2_000 },
+                    PackageEntry { path: "resources.arsc".into(), compressed: 5_000, uncompressed: 5_000 },
+                ],
+            )
+        };
+        let baseline = apk(4_000_000, 9_000_000);
+        let current = apk(4_600_000, 9_600_000);
+        assert_eq!(current.download_size(), 9_600_000);
+
+        let deltas = current.diff(&baseline);
+        assert_eq!(deltas.len(), 1);
+        assert_eq!((deltas[0].key.1.as_str(), deltas[0].change()), ("dex", 600_000));
+
+        let config = SizeConfig {
+            budget_mb: Some(9.5),
+            max_growth_kb: Some(500),
+        };
+        assert_eq!(current.budget_violations(Some(&baseline), &config).len(), 2);
+        assert!(baseline.budget_violations(Some(&baseline), &config).is_empty());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(BASELINE_FILE);
+        current.save(&path).unwrap();
+        assert_eq!(PackageSizeReport::load(&path).unwrap(), current);
+    }
+}
@@ -0,0 +1,253 @@
+//! detekt static analysis
+//!
+//! Runs the detekt CLI with the project's config on top of detekt's
+//! defaults, reads its SARIF report into typed [`Finding`]s, and creates or
+//! applies a baseline so existing issues don't fail new work.
+
+use foodshare_cli::textln;
+use foodshare_core::error::{Error, Result};
+use foodshare_core::process::run_command_in_dir;
+use std::path::{Path, PathBuf};
+
+/// Project detekt config, relative to the project root
+pub const CONFIG_FILE: &str = "config/detekt/detekt.yml";
+
+/// Baseline of accepted findings, relative to the project root
+pub const BASELINE_FILE: &str = "config/detekt/baseline.xml";
+
+/// SARIF report written by each run, relative to the project root
+pub const REPORT_FILE: &str = "build/reports/detekt/detekt.sarif";
+
+/// How serious a finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Informational (SARIF `note`)
+    Info,
+    /// SARIF `warning`
+    Warning,
+    /// SARIF `error`
+    Error,
+}
+
+/// One detekt finding
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    /// Rule ID (`detekt.style.MagicNumber`)
+    pub rule: String,
+    /// Severity
+    pub severity: Severity,
+    /// Message
+    pub message: String,
+    /// File, relative to the project root when inside it
+    pub file: PathBuf,
+    /// 1-based line
+    pub line: usize,
+    /// 1-based column
+    pub column: usize,
+}
+
+impl Finding {
+    /// Rule name without the rule set prefix (`MagicNumber`)
+    pub fn rule_name(&self) -> &str {
+        self.rule.rsplit('.').next().unwrap_or(&self.rule)
+    }
+}
+
+/// Options for a detekt run
+#[derive(Debug, Clone, Default)]
+pub struct DetektOptions {
+    /// Paths to analyze (default: the project root)
+    pub input: Vec<PathBuf>,
+    /// Config file (default: [`CONFIG_FILE`] if it exists)
+    pub config: Option<PathBuf>,
+    /// Baseline file (default: [`BASELINE_FILE`] if it exists)
+    pub baseline: Option<PathBuf>,
+    /// Let detekt fix what it can (formatting rules)
+    pub auto_correct: bool,
+}
+
+impl DetektOptions {
+    fn args(&self, root: &Path) -> Vec<String> {
+        let mut args = vec!["--build-upon-default-config".to_string()];
+        if !self.input.is_empty() {
+            let input: Vec<String> = self.input.iter().map(|p| p.to_string_lossy().to_string()).collect();
+            args.extend(["--input".to_string(), input.join(",")]);
+        }
+        let config = self.config.clone().unwrap_or_else(|| PathBuf::from(CONFIG_FILE));
+        if root.join(&config).exists() {
+            args.extend(["--config".to_string(), config.to_string_lossy().to_string()]);
+        }
+        if self.auto_correct {
+            args.push("--auto-correct".to_string());
+        }
+        args
+    }
+
+    fn baseline_path(&self) -> PathBuf {
+        self.baseline.clone().unwrap_or_else(|| PathBuf::from(BASELINE_FILE))
+    }
+}
+
+/// Analyze the project, returning findings not in the baseline
+pub fn analyze(root: &Path, options: &DetektOptions) -> Result<Vec<Finding>> {
+    let mut args = options.args(root);
+    let baseline = options.baseline_path();
+    if root.join(&baseline).exists() {
+        args.extend(["--baseline".to_string(), baseline.to_string_lossy().to_string()]);
+    }
+    let report = root.join(REPORT_FILE);
+    if let Some(parent) = report.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let _ = std::fs::remove_file(&report);
+    args.extend(["--report".to_string(), format!("sarif:{}", REPORT_FILE)]);
+
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let result = run_command_in_dir("detekt", &args, root)?;
+    // 0 = clean, 2 = findings over the config's maxIssues; anything else is a failed run
+    if !matches!(result.exit_code, 0 | 2) || !report.exists() {
+        return Err(Error::process(format!(
+            "detekt failed: {}",
+            result.combined_output().trim()
+        )));
+    }
+    parse_sarif(&std::fs::read_to_string(&report)?, root)
+}
+
+/// Write a baseline of the current findings, returning its path
+pub fn create_baseline(root: &Path, options: &DetektOptions) -> Result<PathBuf> {
+    let baseline = options.baseline_path();
+    if let Some(parent) = root.join(&baseline).parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut args = options.args(root);
+    args.extend([
+        "--create-baseline".to_string(),
+        "--baseline".to_string(),
+        baseline.to_string_lossy().to_string(),
+    ]);
+
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let result = run_command_in_dir("detekt", &args, root)?;
+    if !root.join(&baseline).exists() {
+        return Err(Error::process(format!(
+            "detekt did not write a baseline: {}",
+            result.combined_output().trim()
+        )));
+    }
+    Ok(baseline)
+}
+
+/// Parse a detekt SARIF report
+pub fn parse_sarif(json: &str, root: &Path) -> Result<Vec<Finding>> {
+    let sarif: serde_json::Value = serde_json::from_str(json)?;
+    let mut findings = Vec::new();
+
+    for run in sarif["runs"].as_array().into_iter().flatten() {
+        for result in run["results"].as_array().into_iter().flatten() {
+            let location = &result["locations"][0]["physicalLocation"];
+            let Some(uri) = location["artifactLocation"]["uri"].as_str() else {
+                continue;
+            };
+            let path = PathBuf::from(uri.strip_prefix("file://").unwrap_or(uri));
+            let severity = match result["level"].as_str() {
+                Some("error") => Severity::Error,
+                Some("note") | Some("none") => Severity::Info,
+                _ => Severity::Warning,
+            };
+            findings.push(Finding {
+                rule: result["ruleId"].as_str().unwrap_or("unknown").to_string(),
+                severity,
+                message: result["message"]["text"].as_str().unwrap_or_default().to_string(),
+                file: path.strip_prefix(root).map(Path::to_path_buf).unwrap_or(path),
+                line: location["region"]["startLine"].as_u64().unwrap_or(0) as usize,
+                column: location["region"]["startColumn"].as_u64().unwrap_or(0) as usize,
+            });
+        }
+    }
+    findings.sort_by(|a, b| (&a.file, a.line, a.column).cmp(&(&b.file, b.line, b.column)));
+    Ok(findings)
+}
+
+/// Print findings grouped by file
+pub fn print_findings(findings: &[Finding]) {
+    use owo_colors::OwoColorize;
+
+    let mut current: Option<&Path> = None;
+    for finding in findings {
+        if current != Some(finding.file.as_path()) {
+            textln!("{}", finding.file.display().to_string().bold());
+            current = Some(&finding.file);
+        }
+        let severity = match finding.severity {
+            Severity::Error => "error".red().to_string(),
+            Severity::Warning => "warning".yellow().to_string(),
+            Severity::Info => "info".blue().to_string(),
+        };
+        textln!(
+            "  {}:{} {} {} {}",
+            finding.line,
+            finding.column,
+            severity,
+            finding.message,
+            format!("({})", finding.rule_name()).dimmed()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sarif() {
+        let json = r#"{
+  "version": "2.1.0",
+  "runs": [{
+    "tool": {"driver": {"name": "detekt"}},
+    "results": [
+      {
+        "ruleId": "detekt.style.MagicNumber",
+        "level": "warning",
+        "message": {"text": "This expression contains a magic number."},
+        "locations": [{"physicalLocation": {
+          "artifactLocation": {"uri": "file:///work/app/src/main/java/Feed.kt"},
+          "region": {"startLine": 42, "startColumn": 17}
+        }}]
+      },
+      {
+        "ruleId": "detekt.potential-bugs.UnsafeCast",
+        "level": "error",
+        "message": {"text": "expression cannot be cast to Listing"},
+        "locations": [{"physicalLocation": {
+          "artifactLocation": {"uri": "app/src/main/java/Api.kt"},
+          "region": {"startLine": 7, "startColumn": 3}
+        }}]
+      }
+    ]
+  }]
+}"#;
+        let findings = parse_sarif(json, Path::new("/work")).unwrap();
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].file, Path::new("app/src/main/java/Api.kt"));
+        assert_eq!(findings[0].severity, Severity::Error);
+        assert_eq!(findings[0].rule_name(), "UnsafeCast");
+        assert_eq!(findings[1].file, Path::new("app/src/main/java/Feed.kt"));
+        assert_eq!((findings[1].line, findings[1].column), (42, 17));
+        assert!(parse_sarif(r#"{"runs": []}"#, Path::new("/work")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_args_use_existing_config() {
+        let root = tempfile::tempdir().unwrap();
+        let options = DetektOptions {
+            input: vec![PathBuf::from("app/src")],
+            ..Default::default()
+        };
+        assert_eq!(options.args(root.path()), ["--build-upon-default-config", "--input", "app/src"]);
+
+        std::fs::create_dir_all(root.path().join("config/detekt")).unwrap();
+        std::fs::write(root.path().join(CONFIG_FILE), "style:\n  MagicNumber:\n    active: false\n").unwrap();
+        assert!(options.args(root.path()).contains(&CONFIG_FILE.to_string()));
+    }
+}
@@ -0,0 +1,234 @@
+//! Connected device management
+//!
+//! Lists phones and running emulators with `adb devices -l`, and installs,
+//! launches and follows the logs of the app on them. Devices are picked by
+//! serial or model name, like the iOS `run --device` option.
+
+use foodshare_core::error::{Error, Result};
+use foodshare_core::process::{run_command, run_command_streaming, CommandResult};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// A device or emulator known to adb
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AndroidDevice {
+    /// adb serial (`emulator-5554`, `R58M12ABCDE`)
+    pub serial: String,
+    /// adb state (`device`, `offline`, `unauthorized`)
+    pub state: String,
+    /// Model (`Pixel_8`)
+    pub model: Option<String>,
+    /// API level, read from ready devices only
+    pub api_level: Option<u32>,
+}
+
+impl AndroidDevice {
+    /// Check if the device accepts commands
+    pub fn is_ready(&self) -> bool {
+        self.state == "device"
+    }
+
+    /// Check if this is an emulator rather than a physical device
+    pub fn is_emulator(&self) -> bool {
+        self.serial.starts_with("emulator-")
+    }
+
+    /// Check if `query` is this device's serial or model
+    pub fn matches(&self, query: &str) -> bool {
+        self.serial == query
+            || self
+                .model
+                .as_deref()
+                .is_some_and(|model| model.eq_ignore_ascii_case(&query.replace(' ', "_")))
+    }
+}
+
+/// List connected devices and running emulators
+pub fn list_devices() -> Result<Vec<AndroidDevice>> {
+    let result = run_command("adb", &["devices", "-l"])?;
+    if !result.success {
+        return Err(Error::process(format!("Failed to list devices: {}", result.stderr.trim())));
+    }
+
+    let mut devices = parse_adb_devices(&result.stdout);
+    for device in devices.iter_mut().filter(|d| d.is_ready()) {
+        device.api_level = run_command("adb", &["-s", &device.serial, "shell", "getprop", "ro.build.version.sdk"])
+            .ok()
+            .and_then(|r| r.stdout.trim().parse().ok());
+    }
+    Ok(devices)
+}
+
+/// Parse `adb devices -l` output
+///
+/// Each device line is `<serial> <state>` followed by `key:value` details
+/// such as `model:Pixel_8`.
+pub fn parse_adb_devices(output: &str) -> Vec<AndroidDevice> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with("List of devices") && !l.starts_with('*'))
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let serial = fields.next()?.to_string();
+            let state = fields.next()?.to_string();
+            let model = fields
+                .find_map(|field| field.strip_prefix("model:"))
+                .map(str::to_string);
+            Some(AndroidDevice {
+                serial,
+                state,
+                model,
+                api_level: None,
+            })
+        })
+        .collect()
+}
+
+/// Find a device by serial or model, or the only ready one
+pub fn select_device(devices: &[AndroidDevice], query: Option<&str>) -> Result<AndroidDevice> {
+    if let Some(query) = query {
+        return match devices.iter().find(|d| d.matches(query)) {
+            Some(device) if device.is_ready() => Ok(device.clone()),
+            Some(device) => Err(Error::validation(format!(
+                "Device {} is {}; unlock it and accept the USB debugging prompt",
+                device.serial, device.state
+            ))),
+            None => Err(Error::validation(format!("Device not found: {}", query))),
+        };
+    }
+
+    let ready: Vec<&AndroidDevice> = devices.iter().filter(|d| d.is_ready()).collect();
+    match ready.as_slice() {
+        [device] => Ok((*device).clone()),
+        [] => Err(Error::validation(
+            "No device found. Connect a phone with USB debugging enabled or boot an emulator",
+        )),
+        _ => Err(Error::validation(format!(
+            "{} devices connected; pick one with --device ({})",
+            ready.len(),
+            ready.iter().map(|d| d.serial.as_str()).collect::<Vec<_>>().join(", ")
+        ))),
+    }
+}
+
+/// Find the APK built for a configuration (`debug`, `release`)
+pub fn find_apk(project_dir: &Path, configuration: &str) -> Result<PathBuf> {
+    let dir = project_dir.join("app/build/outputs/apk").join(configuration);
+    let mut apks: Vec<PathBuf> = std::fs::read_dir(&dir)
+        .map_err(|_| Error::file_not_found(&dir))?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "apk"))
+        .collect();
+    apks.sort();
+    // Prefer the universal APK when ABI splits are enabled
+    let apk = apks
+        .iter()
+        .find(|p| p.to_string_lossy().contains("universal"))
+        .or_else(|| apks.first())
+        .cloned();
+    apk.ok_or_else(|| Error::file_not_found(dir.join(format!("app-{}.apk", configuration))))
+}
+
+/// Read the app's `applicationId` from `app/build.gradle.kts` or `app/build.gradle`
+pub fn application_id(project_dir: &Path) -> Option<String> {
+    ["app/build.gradle.kts", "app/build.gradle"]
+        .iter()
+        .filter_map(|file| std::fs::read_to_string(project_dir.join(file)).ok())
+        .find_map(|content| parse_application_id(&content))
+}
+
+fn parse_application_id(build_file: &str) -> Option<String> {
+    build_file.lines().find_map(|line| {
+        let rest = line.trim().strip_prefix("applicationId")?;
+        let rest = rest.trim_start().trim_start_matches('=').trim();
+        let quoted = rest.strip_prefix('"').or_else(|| rest.strip_prefix('\''))?;
+        let end = quoted.find(['"', '\''])?;
+        Some(quoted[..end].to_string())
+    })
+}
+
+/// Install an APK, replacing an existing install
+pub fn install_apk(serial: &str, apk_path: &Path) -> Result<CommandResult> {
+    let apk = apk_path.to_string_lossy();
+    run_command("adb", &["-s", serial, "install", "-r", &apk])
+}
+
+/// Launch the app's main (launcher) activity, restarting it if running
+pub fn launch_app(serial: &str, package: &str) -> Result<CommandResult> {
+    let resolved = run_command(
+        "adb",
+        &["-s", serial, "shell", "cmd", "package", "resolve-activity", "--brief", package],
+    )?;
+    let component = resolved
+        .stdout
+        .lines()
+        .map(str::trim)
+        .rfind(|l| l.starts_with(package) && l.contains('/'))
+        .ok_or_else(|| Error::validation(format!("No launcher activity found for {} on {}", package, serial)))?
+        .to_string();
+    run_command("adb", &["-s", serial, "shell", "am", "start", "-S", "-n", &component])
+}
+
+/// PIDs the app is running as
+pub fn app_pids(serial: &str, package: &str) -> Result<Vec<u32>> {
+    let result = run_command("adb", &["-s", serial, "shell", "pidof", package])?;
+    Ok(result.stdout.split_whitespace().filter_map(|pid| pid.parse().ok()).collect())
+}
+
+/// Stream the app's logcat output until interrupted
+pub fn stream_logcat(serial: &str, package: &str) -> Result<i32> {
+    let Some(pid) = app_pids(serial, package)?.first().map(u32::to_string) else {
+        return Err(Error::process(format!("{} is not running on {}", package, serial)));
+    };
+    run_command_streaming("adb", &["-s", serial, "logcat", "--pid", &pid])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_select_devices() {
+        let output = "\
+* daemon started successfully
+List of devices attached
+emulator-5554          device product:sdk_gphone64_arm64 model:sdk_gphone64_arm64 device:emu64a transport_id:1
+R58M12ABCDE            device usb:1-1 product:oriole model:Pixel_6 device:oriole transport_id:2
+0A281FDD4001RZ         unauthorized usb:1-2 transport_id:3
+
+";
+        let devices = parse_adb_devices(output);
+        assert_eq!(devices.len(), 3);
+        assert!(devices[0].is_emulator());
+        assert_eq!(devices[1].model.as_deref(), Some("Pixel_6"));
+        assert!(!devices[2].is_ready());
+
+        assert_eq!(select_device(&devices, Some("Pixel 6")).unwrap().serial, "R58M12ABCDE");
+        assert!(select_device(&devices, Some("0A281FDD4001RZ")).is_err());
+        assert!(select_device(&devices, None).is_err());
+        assert_eq!(select_device(&devices[1..], None).unwrap().serial, "R58M12ABCDE");
+    }
+
+    #[test]
+    fn test_find_apk_and_application_id() {
+        let project = tempfile::tempdir().unwrap();
+        assert!(find_apk(project.path(), "debug").is_err());
+
+        let outputs = project.path().join("app/build/outputs/apk/debug");
+        std::fs::create_dir_all(&outputs).unwrap();
+        std::fs::write(outputs.join("app-arm64-v8a-debug.apk"), "").unwrap();
+        std::fs::write(outputs.join("app-universal-debug.apk"), "").unwrap();
+        std::fs::write(outputs.join("output-metadata.json"), "{}").unwrap();
+        assert_eq!(find_apk(project.path(), "debug").unwrap(), outputs.join("app-universal-debug.apk"));
+
+        assert_eq!(application_id(project.path()), None);
+        std::fs::write(
+            project.path().join("app/build.gradle.kts"),
+            "android {\n    defaultConfig {\n        applicationId = \"com.flutterflow.foodshare\"\n    }\n}\n",
+        )
+        .unwrap();
+        assert_eq!(application_id(project.path()).as_deref(), Some("com.flutterflow.foodshare"));
+        assert_eq!(parse_application_id("    applicationId 'org.foodshare'"), Some("org.foodshare".into()));
+    }
+}
@@ -1,10 +1,23 @@
 //! Android Emulator management
 //!
-//! Provides tools for managing Android emulators.
+//! Provides tools for managing Android emulators: booting AVDs (headless on
+//! CI, waiting for `sys.boot_completed`), saving and loading snapshots, and
+//! giving parallel emulators their own console ports.
 
-use foodshare_core::error::Result;
+use foodshare_core::error::{Error, Result};
 use foodshare_core::process::{command_exists, run_command, CommandResult};
 use serde::{Deserialize, Serialize};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// First console port the emulator uses; adb connects on the port after it
+pub const FIRST_PORT: u16 = 5554;
+
+/// Last console port adb scans for emulators
+pub const LAST_PORT: u16 = 5682;
+
+/// Default time to wait for an emulator to finish booting
+pub const DEFAULT_BOOT_TIMEOUT: Duration = Duration::from_secs(300);
 
 /// Emulator device info
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -54,10 +67,131 @@ pub fn list_running() -> Result<Vec<String>> {
         .collect())
 }
 
-/// Boot an emulator by AVD name
-pub fn boot(avd_name: &str) -> Result<CommandResult> {
-    // Start emulator in background
-    run_command("emulator", &["-avd", avd_name, "-no-snapshot-load"])
+/// How to boot an emulator
+#[derive(Debug, Clone, Default)]
+pub struct BootOptions {
+    /// No window, audio or boot animation, with software rendering (for CI)
+    pub headless: bool,
+    /// Console port (default: the first free one)
+    pub port: Option<u16>,
+    /// Snapshot to boot from (default: cold boot)
+    pub snapshot: Option<String>,
+    /// Leave the AVD untouched so several instances of it can run at once
+    pub read_only: bool,
+}
+
+impl BootOptions {
+    fn args(&self, avd_name: &str, port: u16) -> Vec<String> {
+        let mut args: Vec<String> = vec!["-avd".into(), avd_name.into(), "-port".into(), port.to_string()];
+        match &self.snapshot {
+            Some(snapshot) => args.extend(["-snapshot".into(), snapshot.clone()]),
+            None => args.push("-no-snapshot-load".into()),
+        }
+        if self.headless {
+            args.extend(
+                ["-no-window", "-no-audio", "-no-boot-anim", "-gpu", "swiftshader_indirect"].map(String::from),
+            );
+        }
+        if self.read_only {
+            args.push("-read-only".into());
+        }
+        args
+    }
+}
+
+/// An emulator process started by [`boot`]
+#[derive(Debug)]
+pub struct StartedEmulator {
+    /// adb serial (`emulator-5556`)
+    pub serial: String,
+    /// Console port
+    pub port: u16,
+    child: Child,
+}
+
+impl StartedEmulator {
+    /// Wait until Android reports `sys.boot_completed`
+    ///
+    /// Fails if the emulator exits or `timeout` passes first.
+    pub fn wait_for_boot(&mut self, timeout: Duration) -> Result<()> {
+        let started = Instant::now();
+        loop {
+            if let Some(status) = self.child.try_wait()? {
+                return Err(Error::process(format!(
+                    "Emulator on port {} exited during boot ({}); run it without --headless to see why",
+                    self.port, status
+                )));
+            }
+            if is_boot_completed(&self.serial) {
+                return Ok(());
+            }
+            if started.elapsed() >= timeout {
+                return Err(Error::process(format!(
+                    "{} did not finish booting within {}s",
+                    self.serial,
+                    timeout.as_secs()
+                )));
+            }
+            std::thread::sleep(Duration::from_secs(2));
+        }
+    }
+}
+
+/// Start an emulator in the background, returning once the process is up
+pub fn boot(avd_name: &str, options: &BootOptions) -> Result<StartedEmulator> {
+    let running = list_running()?;
+    let used: Vec<u16> = running.iter().filter_map(|serial| port_of(serial)).collect();
+    let port = match options.port {
+        Some(port) if port % 2 != 0 || !(FIRST_PORT..=LAST_PORT).contains(&port) => {
+            return Err(Error::validation(format!(
+                "Emulator port must be even and between {} and {}",
+                FIRST_PORT, LAST_PORT
+            )));
+        }
+        Some(port) if used.contains(&port) => {
+            return Err(Error::validation(format!("emulator-{} is already running", port)));
+        }
+        Some(port) => port,
+        None => next_free_port(&used, is_port_bindable)
+            .ok_or_else(|| Error::process("No free emulator port; shut down an emulator first"))?,
+    };
+
+    let child = Command::new("emulator")
+        .args(options.args(avd_name, port))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| Error::process(format!("Failed to start emulator: {}", e)))?;
+    Ok(StartedEmulator {
+        serial: format!("emulator-{}", port),
+        port,
+        child,
+    })
+}
+
+/// Check if Android has finished booting on a device
+pub fn is_boot_completed(serial: &str) -> bool {
+    run_command("adb", &["-s", serial, "shell", "getprop", "sys.boot_completed"])
+        .is_ok_and(|result| result.stdout.trim() == "1")
+}
+
+/// Console port of an emulator serial (`emulator-5554` is 5554)
+pub fn port_of(serial: &str) -> Option<u16> {
+    serial.strip_prefix("emulator-")?.parse().ok()
+}
+
+/// First console port not used by a running emulator whose console and adb
+/// ports `bindable` accepts
+pub fn next_free_port(used: &[u16], bindable: impl Fn(u16) -> bool) -> Option<u16> {
+    (FIRST_PORT..=LAST_PORT)
+        .step_by(2)
+        .find(|port| !used.contains(port) && bindable(*port) && bindable(port + 1))
+}
+
+/// Check if nothing else listens on a local port
+fn is_port_bindable(port: u16) -> bool {
+    std::net::TcpListener::bind(("127.0.0.1", port)).is_ok()
 }
 
 /// Shutdown an emulator
@@ -74,6 +208,60 @@ pub fn shutdown_all() -> Result<()> {
     Ok(())
 }
 
+/// Save the emulator's current state as a snapshot
+pub fn save_snapshot(serial: &str, name: &str) -> Result<()> {
+    snapshot_command(serial, &["save", name]).map(drop)
+}
+
+/// Restore a snapshot on a running emulator
+pub fn load_snapshot(serial: &str, name: &str) -> Result<()> {
+    snapshot_command(serial, &["load", name]).map(drop)
+}
+
+/// Delete a snapshot
+pub fn delete_snapshot(serial: &str, name: &str) -> Result<()> {
+    snapshot_command(serial, &["delete", name]).map(drop)
+}
+
+/// Names of the snapshots of a running emulator's AVD
+pub fn list_snapshots(serial: &str) -> Result<Vec<String>> {
+    let output = snapshot_command(serial, &["list"])?;
+    Ok(parse_snapshot_list(&output))
+}
+
+/// Run `adb emu avd snapshot <args>`, returning its output
+///
+/// The console reports failures as a `KO:` line rather than an exit status.
+fn snapshot_command(serial: &str, args: &[&str]) -> Result<String> {
+    let mut command = vec!["-s", serial, "emu", "avd", "snapshot"];
+    command.extend_from_slice(args);
+    let result = run_command("adb", &command)?;
+    let output = result.combined_output();
+    if let Some(error) = output.lines().find_map(|l| l.trim().strip_prefix("KO:")) {
+        return Err(Error::process(format!("Snapshot {} failed: {}", args[0], error.trim())));
+    }
+    if !result.success {
+        return Err(Error::process(format!("Snapshot {} failed: {}", args[0], output.trim())));
+    }
+    Ok(result.stdout)
+}
+
+/// Parse the emulator console's snapshot table
+///
+/// Rows are `<id> <tag> <vm size> <date> <vm clock>`, with `--` as the ID of
+/// snapshots saved by the emulator itself.
+pub fn parse_snapshot_list(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let id = fields.next()?;
+            let name = fields.next()?;
+            (id == "--" || id.chars().all(|c| c.is_ascii_digit())).then(|| name.to_string())
+        })
+        .collect()
+}
+
 /// Install an APK on an emulator
 pub fn install_apk(serial: &str, apk_path: &str) -> Result<CommandResult> {
     run_command("adb", &["-s", serial, "install", "-r", apk_path])
@@ -134,4 +322,39 @@ mod tests {
         };
         assert_eq!(device.name, "Pixel_7_API_34");
     }
+
+    #[test]
+    fn test_boot_args_and_ports() {
+        let options = BootOptions {
+            headless: true,
+            snapshot: Some("ci-ready".to_string()),
+            read_only: true,
+            ..Default::default()
+        };
+        let args = options.args("Pixel_7_API_34", 5556);
+        assert_eq!(args[..6], ["-avd", "Pixel_7_API_34", "-port", "5556", "-snapshot", "ci-ready"]);
+        assert!(args.contains(&"-no-window".to_string()) && args.ends_with(&["-read-only".to_string()]));
+        assert!(BootOptions::default().args("Pixel", FIRST_PORT).contains(&"-no-snapshot-load".to_string()));
+
+        assert_eq!(port_of("emulator-5556"), Some(5556));
+        assert_eq!(port_of("R58M12ABCDE"), None);
+        assert_eq!(next_free_port(&[], |_| true), Some(FIRST_PORT));
+        assert_eq!(next_free_port(&[5554, 5556], |_| true), Some(5558));
+        // 5555 taken by something else, so 5554 can't be used either
+        assert_eq!(next_free_port(&[], |port| port != 5555), Some(5556));
+        assert_eq!(next_free_port(&[], |_| false), None);
+    }
+
+    #[test]
+    fn test_parse_snapshot_list() {
+        let output = "\
+List of snapshots present on all disks:
+ID        TAG                 VM SIZE                DATE       VM CLOCK
+--        default_boot           141M 2026-10-12 09:14:02   00:03:11.204
+1         ci-ready               152M 2026-10-15 18:40:55   00:01:02.331
+OK
+";
+        assert_eq!(parse_snapshot_list(output), ["default_boot", "ci-ready"]);
+        assert!(parse_snapshot_list("OK\n").is_empty());
+    }
 }
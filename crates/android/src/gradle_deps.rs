@@ -0,0 +1,806 @@
+//! Gradle dependency audit
+//!
+//! Reads the version catalog (`gradle/libs.versions.toml`) and every
+//! `build.gradle(.kts)` and `settings.gradle(.kts)` in the project and
+//! reports:
+//!
+//! - dependencies and plugins declared with a hardcoded version instead of
+//!   through the catalog
+//! - catalog entries no build file references
+//! - modules declared with different versions
+//! - repositories outside the allowlist
+//! - dependencies with a newer stable release (queried from Google Maven and
+//!   Maven Central)
+//!
+//! Build scripts are scanned line by line rather than evaluated, so
+//! dependencies added by convention plugins or computed in code are not seen.
+
+use foodshare_cli::textln;
+use foodshare_core::error::{Error, Result};
+use once_cell::sync::Lazy;
+use owo_colors::OwoColorize;
+use rayon::prelude::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use walkdir::WalkDir;
+
+/// Location of the version catalog, relative to the project root
+pub const VERSION_CATALOG: &str = "gradle/libs.versions.toml";
+
+/// Directories never searched for build files
+const SKIP_DIRS: &[&str] = &[".git", ".gradle", ".idea", "build", "node_modules"];
+
+/// Maven repositories queried for newer releases, in order
+const RELEASE_REPOSITORIES: &[&str] = &["https://dl.google.com/android/maven2", "https://repo1.maven.org/maven2"];
+
+/// URLs of Gradle's built-in repository shorthands
+const BUILTIN_REPOSITORIES: &[(&str, &str)] = &[
+    ("google()", "https://dl.google.com/dl/android/maven2/"),
+    ("mavenCentral()", "https://repo.maven.apache.org/maven2/"),
+    ("gradlePluginPortal()", "https://plugins.gradle.org/m2/"),
+    ("jcenter()", "https://jcenter.bintray.com/"),
+];
+
+/// `implementation("group:artifact:1.0")` and the other configurations
+static HARDCODED_DEPENDENCY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"^\s*([a-z]\w*(?:Implementation|Api|CompileOnly|RuntimeOnly)|implementation|api|kapt|ksp|compileOnly|runtimeOnly|classpath|annotationProcessor|lintChecks|coreLibraryDesugaring|detektPlugins)\b[\s(]*(?:(?:enforcedPlatform|platform)\s*\(\s*)?["']([\w.\-]+):([\w.\-]+):([^"'@:]+)"#,
+    )
+    .expect("valid regex")
+});
+
+/// `id("com.android.application") version "8.2.0"`
+static HARDCODED_PLUGIN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\bid\s*\(?\s*["']([\w.\-]+)["']\s*\)?\s*version\s*\(?\s*["']([^"']+)["']"#).expect("valid regex")
+});
+
+/// Catalog accessors: `libs.androidx.core.ktx`, `libs.plugins.hilt`, `libs.bundles.compose`
+static CATALOG_REFERENCE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\blibs\.([A-Za-z][\w.]*[A-Za-z0-9])").expect("valid regex"));
+
+/// A quoted repository URL
+static REPOSITORY_URL: Lazy<Regex> = Lazy::new(|| Regex::new(r#"["']((?:https?|file)://[^"']+)["']"#).expect("valid regex"));
+
+/// A library or plugin of the version catalog
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CatalogEntry {
+    /// Alias (`androidx-core-ktx`)
+    pub alias: String,
+    /// `group:artifact` for libraries, the plugin ID for plugins
+    pub module: String,
+    /// Resolved version, if the entry has one (BOM-managed entries don't)
+    pub version: Option<String>,
+}
+
+impl CatalogEntry {
+    /// Accessor path of the alias (`androidx.core.ktx`)
+    pub fn accessor(&self) -> String {
+        accessor(&self.alias)
+    }
+}
+
+/// A parsed `libs.versions.toml`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionCatalog {
+    /// `[libraries]`
+    pub libraries: Vec<CatalogEntry>,
+    /// `[plugins]`
+    pub plugins: Vec<CatalogEntry>,
+    /// `[bundles]`: bundle alias to library aliases
+    pub bundles: BTreeMap<String, Vec<String>>,
+}
+
+impl VersionCatalog {
+    /// Parse a version catalog
+    pub fn parse(content: &str) -> Result<Self> {
+        let document: toml::Table =
+            toml::from_str(content).map_err(|e| Error::validation(format!("Invalid version catalog: {}", e)))?;
+        let table = |name: &str| document.get(name).and_then(toml::Value::as_table).cloned().unwrap_or_default();
+
+        let versions: BTreeMap<String, String> = table("versions")
+            .into_iter()
+            .filter_map(|(name, value)| Some((name, version_value(&value)?)))
+            .collect();
+        // `version.ref = "x"`, `version = { ref = "x" }`, `version = "1.0"` or `version = { strictly = "1.0" }`
+        let version_of = |entry: &toml::Table| match entry.get("version") {
+            Some(toml::Value::Table(version)) => match version.get("ref").and_then(toml::Value::as_str) {
+                Some(reference) => versions.get(reference).cloned(),
+                None => version_value(&toml::Value::Table(version.clone())),
+            },
+            Some(value) => version_value(value),
+            None => None,
+        };
+
+        let mut catalog = Self::default();
+        for (alias, value) in table("libraries") {
+            let entry = match &value {
+                toml::Value::String(notation) => {
+                    let mut parts = notation.splitn(3, ':');
+                    let (Some(group), Some(name)) = (parts.next(), parts.next()) else {
+                        continue;
+                    };
+                    CatalogEntry {
+                        alias,
+                        module: format!("{}:{}", group, name),
+                        version: parts.next().map(str::to_string),
+                    }
+                }
+                toml::Value::Table(entry) => {
+                    let module = match (entry.get("module"), entry.get("group"), entry.get("name")) {
+                        (Some(module), _, _) => module.as_str().map(str::to_string),
+                        (None, Some(group), Some(name)) => {
+                            Some(format!("{}:{}", group.as_str().unwrap_or_default(), name.as_str().unwrap_or_default()))
+                        }
+                        _ => None,
+                    };
+                    let Some(module) = module else {
+                        continue;
+                    };
+                    CatalogEntry {
+                        alias,
+                        module,
+                        version: version_of(entry),
+                    }
+                }
+                _ => continue,
+            };
+            catalog.libraries.push(entry);
+        }
+
+        for (alias, value) in table("plugins") {
+            let (module, version) = match &value {
+                toml::Value::String(notation) => match notation.split_once(':') {
+                    Some((id, version)) => (id.to_string(), Some(version.to_string())),
+                    None => (notation.clone(), None),
+                },
+                toml::Value::Table(entry) => {
+                    let Some(id) = entry.get("id").and_then(toml::Value::as_str) else {
+                        continue;
+                    };
+                    (id.to_string(), version_of(entry))
+                }
+                _ => continue,
+            };
+            catalog.plugins.push(CatalogEntry { alias, module, version });
+        }
+
+        for (alias, value) in table("bundles") {
+            let libraries = value
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|library| library.as_str().map(str::to_string))
+                .collect();
+            catalog.bundles.insert(alias, libraries);
+        }
+        Ok(catalog)
+    }
+}
+
+/// A dependency or plugin declared with a literal version in a build file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HardcodedDependency {
+    /// Build file
+    pub file: PathBuf,
+    /// Line number (1-based)
+    pub line: usize,
+    /// Configuration (`implementation`), or `plugin`
+    pub configuration: String,
+    /// `group:artifact`, or the plugin ID
+    pub module: String,
+    /// Declared version
+    pub version: String,
+}
+
+/// A repository declared in a build or settings file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Repository {
+    /// Build or settings file
+    pub file: PathBuf,
+    /// Line number (1-based)
+    pub line: usize,
+    /// Repository URL
+    pub url: String,
+}
+
+impl Repository {
+    /// Host of the URL; `None` for local repositories
+    pub fn host(&self) -> Option<String> {
+        let rest = self.url.split_once("://")?.1;
+        let host = rest.split('/').next()?.rsplit('@').next()?.split(':').next()?;
+        (!host.is_empty()).then(|| host.to_lowercase())
+    }
+}
+
+/// A module declared with different versions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DuplicateDependency {
+    /// `group:artifact`
+    pub module: String,
+    /// Each version and where it is declared (catalog alias or `file:line`)
+    pub versions: BTreeMap<String, Vec<String>>,
+}
+
+/// A dependency with a newer stable release
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutdatedDependency {
+    /// `group:artifact`
+    pub module: String,
+    /// Declared version
+    pub current: String,
+    /// Newest stable release
+    pub latest: String,
+}
+
+/// Result of [`audit`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DependencyAudit {
+    /// Version catalog, if the project has one
+    pub catalog_file: Option<PathBuf>,
+    /// Build and settings files read
+    pub build_files: Vec<PathBuf>,
+    /// The parsed catalog
+    pub catalog: VersionCatalog,
+    /// Dependencies and plugins bypassing the catalog
+    pub hardcoded: Vec<HardcodedDependency>,
+    /// Catalog aliases no build file references
+    pub unused: Vec<String>,
+    /// Modules declared with different versions
+    pub duplicates: Vec<DuplicateDependency>,
+    /// Repositories outside the allowlist
+    pub disallowed: Vec<Repository>,
+    /// Dependencies with newer releases
+    pub outdated: Vec<OutdatedDependency>,
+}
+
+impl DependencyAudit {
+    /// Whether the audit found problems that should fail CI
+    ///
+    /// Hardcoded versions, conflicting versions and disallowed repositories
+    /// fail; unused catalog entries and outdated dependencies are warnings.
+    pub fn has_failures(&self) -> bool {
+        !self.hardcoded.is_empty() || !self.duplicates.is_empty() || !self.disallowed.is_empty()
+    }
+
+    /// Print the findings
+    pub fn print(&self, root: &Path) {
+        let display = |file: &Path| file.strip_prefix(root).unwrap_or(file).display().to_string();
+        textln!(
+            "{}",
+            format!(
+                "{} libraries and {} plugins in the catalog, {} build files",
+                self.catalog.libraries.len(),
+                self.catalog.plugins.len(),
+                self.build_files.len()
+            )
+            .bold()
+        );
+
+        if !self.hardcoded.is_empty() {
+            textln!();
+            textln!("{}", "Hardcoded versions (use the version catalog)".red().bold());
+            for dependency in &self.hardcoded {
+                textln!(
+                    "  {}:{} {} {}:{}",
+                    dependency.module,
+                    dependency.version,
+                    format!("({})", dependency.configuration).dimmed(),
+                    display(&dependency.file).dimmed(),
+                    dependency.line.to_string().dimmed()
+                );
+            }
+        }
+
+        if !self.duplicates.is_empty() {
+            textln!();
+            textln!("{}", "Conflicting versions".red().bold());
+            for duplicate in &self.duplicates {
+                textln!("  {}", duplicate.module);
+                for (version, places) in &duplicate.versions {
+                    textln!("    {:<12} {}", version, places.join(", ").dimmed());
+                }
+            }
+        }
+
+        if !self.disallowed.is_empty() {
+            textln!();
+            textln!("{}", "Repositories outside the allowlist".red().bold());
+            for repository in &self.disallowed {
+                textln!(
+                    "  {} {}:{}",
+                    repository.url,
+                    display(&repository.file).dimmed(),
+                    repository.line.to_string().dimmed()
+                );
+            }
+        }
+
+        if !self.unused.is_empty() {
+            textln!();
+            textln!("{}", "Unused catalog entries".yellow().bold());
+            for alias in &self.unused {
+                textln!("  {}", alias);
+            }
+        }
+
+        if !self.outdated.is_empty() {
+            textln!();
+            textln!("{}", "Outdated".yellow().bold());
+            for dependency in &self.outdated {
+                textln!("  {:<48} {} -> {}", dependency.module, dependency.current, dependency.latest.green());
+            }
+        }
+    }
+}
+
+/// Audit the Gradle project at `root`
+///
+/// With `check_outdated`, Google Maven and Maven Central are queried for each
+/// versioned dependency, which needs network access.
+pub fn audit(root: &Path, allowed_repositories: &[String], check_outdated: bool) -> Result<DependencyAudit> {
+    let catalog_file = root.join(VERSION_CATALOG);
+    let catalog = if catalog_file.is_file() {
+        VersionCatalog::parse(&std::fs::read_to_string(&catalog_file)?)
+            .map_err(|e| Error::validation(format!("{}: {}", catalog_file.display(), e)))?
+    } else {
+        VersionCatalog::default()
+    };
+
+    let build_files = find_build_files(root);
+    let mut hardcoded = Vec::new();
+    let mut repositories = Vec::new();
+    let mut references = BTreeSet::new();
+    for file in &build_files {
+        let content = std::fs::read_to_string(file)?;
+        hardcoded.extend(find_hardcoded(&content, file));
+        repositories.extend(find_repositories(&content, file));
+        references.extend(catalog_references(&content));
+    }
+
+    let mut audit = DependencyAudit {
+        unused: unused_entries(&catalog, &references),
+        duplicates: find_duplicates(&catalog, &hardcoded, root),
+        disallowed: repositories
+            .into_iter()
+            .filter(|repository| !repository_allowed(repository.host().as_deref(), allowed_repositories))
+            .collect(),
+        catalog_file: catalog_file.is_file().then_some(catalog_file),
+        build_files,
+        hardcoded,
+        catalog,
+        ..Default::default()
+    };
+
+    if check_outdated {
+        audit.outdated = find_outdated(&audit.catalog.libraries, &audit.hardcoded);
+    }
+    Ok(audit)
+}
+
+/// `build.gradle(.kts)` and `settings.gradle(.kts)` files under `root`
+pub fn find_build_files(root: &Path) -> Vec<PathBuf> {
+    const NAMES: &[&str] = &["build.gradle", "build.gradle.kts", "settings.gradle", "settings.gradle.kts"];
+    let mut files: Vec<PathBuf> = WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|entry| {
+            !(entry.file_type().is_dir() && SKIP_DIRS.contains(&entry.file_name().to_string_lossy().as_ref()))
+        })
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file() && NAMES.contains(&entry.file_name().to_string_lossy().as_ref()))
+        .map(|entry| entry.into_path())
+        .collect();
+    files.sort();
+    files
+}
+
+/// Lines of a build script with `//` comments removed
+fn code_lines(content: &str) -> impl Iterator<Item = (usize, &str)> {
+    content.lines().enumerate().filter_map(|(index, line)| {
+        let line = match line.find("//") {
+            // Keep `https://` inside strings
+            Some(at) if !line[..at].ends_with(':') => &line[..at],
+            _ => line,
+        };
+        let trimmed = line.trim();
+        (!trimmed.is_empty() && !trimmed.starts_with('*') && !trimmed.starts_with("/*")).then_some((index + 1, line))
+    })
+}
+
+/// Dependencies and plugins with a literal version
+pub fn find_hardcoded(content: &str, file: &Path) -> Vec<HardcodedDependency> {
+    let mut found = Vec::new();
+    for (line, text) in code_lines(content) {
+        if let Some(captures) = HARDCODED_DEPENDENCY.captures(text) {
+            found.push(HardcodedDependency {
+                file: file.to_path_buf(),
+                line,
+                configuration: captures[1].to_string(),
+                module: format!("{}:{}", &captures[2], &captures[3]),
+                version: captures[4].to_string(),
+            });
+        } else if let Some(captures) = HARDCODED_PLUGIN.captures(text) {
+            found.push(HardcodedDependency {
+                file: file.to_path_buf(),
+                line,
+                configuration: "plugin".to_string(),
+                module: captures[1].to_string(),
+                version: captures[2].to_string(),
+            });
+        }
+    }
+    found
+}
+
+/// Repositories declared inside `repositories { }` blocks
+pub fn find_repositories(content: &str, file: &Path) -> Vec<Repository> {
+    let mut found = Vec::new();
+    let mut depth = 0usize;
+    for (line, text) in code_lines(content) {
+        let opens_block = depth == 0 && text.contains("repositories");
+        if depth > 0 || opens_block {
+            let inside = if opens_block {
+                &text[text.find("repositories").unwrap_or(0)..]
+            } else {
+                text
+            };
+            for (shorthand, url) in BUILTIN_REPOSITORIES {
+                if inside.contains(shorthand) {
+                    found.push(Repository {
+                        file: file.to_path_buf(),
+                        line,
+                        url: url.to_string(),
+                    });
+                }
+            }
+            if inside.contains("mavenLocal()") {
+                found.push(Repository {
+                    file: file.to_path_buf(),
+                    line,
+                    url: "file://~/.m2/repository".to_string(),
+                });
+            }
+            for captures in REPOSITORY_URL.captures_iter(inside) {
+                found.push(Repository {
+                    file: file.to_path_buf(),
+                    line,
+                    url: captures[1].to_string(),
+                });
+            }
+            let opened = inside.matches('{').count();
+            let closed = inside.matches('}').count();
+            depth = (depth + opened).saturating_sub(closed);
+        }
+    }
+    found
+}
+
+/// Catalog accessor paths referenced by a build script, without a trailing `.get`
+pub fn catalog_references(content: &str) -> BTreeSet<String> {
+    code_lines(content)
+        .flat_map(|(_, text)| CATALOG_REFERENCE.captures_iter(text))
+        .map(|captures| {
+            let path = &captures[1];
+            path.strip_suffix(".get").unwrap_or(path).to_string()
+        })
+        .collect()
+}
+
+/// Accessor path of a catalog alias: `-`, `_` and `.` all become `.`
+fn accessor(alias: &str) -> String {
+    alias.replace(['-', '_'], ".")
+}
+
+fn unused_entries(catalog: &VersionCatalog, references: &BTreeSet<String>) -> Vec<String> {
+    let mut used: BTreeSet<String> = references.clone();
+    for (bundle, libraries) in &catalog.bundles {
+        if references.contains(&format!("bundles.{}", accessor(bundle))) {
+            used.extend(libraries.iter().map(|library| accessor(library)));
+        }
+    }
+
+    let libraries = catalog
+        .libraries
+        .iter()
+        .filter(|entry| !used.contains(&entry.accessor()))
+        .map(|entry| entry.alias.clone());
+    let plugins = catalog
+        .plugins
+        .iter()
+        .filter(|entry| !used.contains(&format!("plugins.{}", entry.accessor())))
+        .map(|entry| format!("plugins.{}", entry.alias));
+    libraries.chain(plugins).collect()
+}
+
+fn find_duplicates(catalog: &VersionCatalog, hardcoded: &[HardcodedDependency], root: &Path) -> Vec<DuplicateDependency> {
+    let mut by_module: BTreeMap<&str, BTreeMap<String, Vec<String>>> = BTreeMap::new();
+    for entry in catalog.libraries.iter().chain(&catalog.plugins) {
+        if let Some(version) = &entry.version {
+            by_module
+                .entry(&entry.module)
+                .or_default()
+                .entry(version.clone())
+                .or_default()
+                .push(entry.alias.clone());
+        }
+    }
+    for dependency in hardcoded {
+        let file = dependency.file.strip_prefix(root).unwrap_or(&dependency.file);
+        by_module
+            .entry(&dependency.module)
+            .or_default()
+            .entry(dependency.version.clone())
+            .or_default()
+            .push(format!("{}:{}", file.display(), dependency.line));
+    }
+    by_module
+        .into_iter()
+        .filter(|(_, versions)| versions.len() > 1)
+        .map(|(module, versions)| DuplicateDependency {
+            module: module.to_string(),
+            versions,
+        })
+        .collect()
+}
+
+fn find_outdated(libraries: &[CatalogEntry], hardcoded: &[HardcodedDependency]) -> Vec<OutdatedDependency> {
+    // One query per module, against the highest version declared anywhere
+    let mut newest: BTreeMap<&str, &str> = BTreeMap::new();
+    let declared = libraries
+        .iter()
+        .filter_map(|entry| Some((entry.module.as_str(), entry.version.as_deref()?)))
+        .chain(
+            hardcoded
+                .iter()
+                .filter(|dependency| dependency.configuration != "plugin")
+                .map(|dependency| (dependency.module.as_str(), dependency.version.as_str())),
+        );
+    for (module, version) in declared {
+        let current = newest.entry(module).or_insert(version);
+        if compare_versions(version, current) == Ordering::Greater {
+            *current = version;
+        }
+    }
+
+    let mut outdated: Vec<OutdatedDependency> = newest
+        .into_par_iter()
+        .filter_map(|(module, current)| {
+            let latest = latest_release(module).ok()??;
+            (compare_versions(&latest, current) == Ordering::Greater).then(|| OutdatedDependency {
+                module: module.to_string(),
+                current: current.to_string(),
+                latest,
+            })
+        })
+        .collect();
+    outdated.sort_by(|a, b| a.module.cmp(&b.module));
+    outdated
+}
+
+/// Client for the Maven metadata requests, shared by the parallel lookups
+static HTTP: Lazy<reqwest::blocking::Client> = Lazy::new(|| {
+    reqwest::blocking::Client::builder()
+        .user_agent(concat!("foodshare-android/", env!("CARGO_PKG_VERSION")))
+        .timeout(Duration::from_secs(20))
+        .build()
+        .unwrap_or_default()
+});
+
+/// Newest stable release of `group:artifact` on Google Maven or Maven Central
+pub fn latest_release(module: &str) -> Result<Option<String>> {
+    let Some((group, artifact)) = module.split_once(':') else {
+        return Ok(None);
+    };
+    for repository in RELEASE_REPOSITORIES {
+        let url = format!("{}/{}/{}/maven-metadata.xml", repository, group.replace('.', "/"), artifact);
+        let metadata = HTTP
+            .get(&url)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.text());
+        if let Ok(metadata) = metadata {
+            return Ok(latest_version(&metadata));
+        }
+    }
+    Ok(None)
+}
+
+/// Highest stable version listed in a `maven-metadata.xml`
+fn latest_version(metadata: &str) -> Option<String> {
+    metadata
+        .split("<version>")
+        .skip(1)
+        .filter_map(|rest| rest.split_once("</version>").map(|(version, _)| version.trim()))
+        .filter(|version| is_stable(version))
+        .max_by(|a, b| compare_versions(a, b))
+        .map(str::to_string)
+}
+
+/// Whether a Maven version is a release rather than a preview
+fn is_stable(version: &str) -> bool {
+    const PREVIEW: &[&str] = &["alpha", "beta", "rc", "dev", "snapshot", "eap", "preview", "canary"];
+    let lower = version.to_lowercase();
+    let milestone = lower.split(['-', '.']).any(|part| {
+        part.strip_prefix('m').is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+    });
+    !milestone && !PREVIEW.iter().any(|marker| lower.contains(marker))
+}
+
+/// Compare Maven versions segment by segment, numerically where both are numbers
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let segments = |version: &str| version.split(['.', '-']).map(str::to_string).collect::<Vec<_>>();
+    let (a, b) = (segments(a), segments(b));
+    for index in 0..a.len().max(b.len()) {
+        let ordering = match (a.get(index), b.get(index)) {
+            (Some(x), Some(y)) => match (x.parse::<u64>(), y.parse::<u64>()) {
+                (Ok(x), Ok(y)) => x.cmp(&y),
+                _ => x.cmp(y),
+            },
+            // `1.0` < `1.0.1`
+            (Some(_), None) => Ordering::Greater,
+            (None, Some(_)) => Ordering::Less,
+            (None, None) => Ordering::Equal,
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+/// Version string of a `[versions]` value or an inline version table
+fn version_value(value: &toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(version) => Some(version.clone()),
+        toml::Value::Table(table) => ["strictly", "require", "prefer"]
+            .iter()
+            .find_map(|key| table.get(*key)?.as_str().map(str::to_string)),
+        _ => None,
+    }
+}
+
+/// Whether `host` is allowlisted, directly or as a subdomain
+fn repository_allowed(host: Option<&str>, allowed: &[String]) -> bool {
+    // Local repositories have no host
+    let Some(host) = host else {
+        return true;
+    };
+    allowed.iter().any(|allowed| {
+        let allowed = allowed.to_lowercase();
+        host == allowed || host.ends_with(&format!(".{}", allowed))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CATALOG: &str = r#"
+[versions]
+kotlin = "1.9.22"
+compose-bom = "2024.02.00"
+coil = { strictly = "2.5.0" }
+
+[libraries]
+androidx-core-ktx = "androidx.core:core-ktx:1.12.0"
+compose-bom = { group = "androidx.compose", name = "compose-bom", version.ref = "compose-bom" }
+compose-ui = { module = "androidx.compose.ui:ui" }
+coil-compose = { module = "io.coil-kt:coil-compose", version.ref = "coil" }
+timber = { module = "com.jakewharton.timber:timber", version = "5.0.1" }
+
+[bundles]
+compose = ["compose-ui"]
+
+[plugins]
+kotlin-android = { id = "org.jetbrains.kotlin.android", version.ref = "kotlin" }
+"#;
+
+    #[test]
+    fn test_parse_catalog() {
+        let catalog = VersionCatalog::parse(CATALOG).unwrap();
+        let find = |alias: &str| catalog.libraries.iter().find(|e| e.alias == alias).unwrap();
+        assert_eq!(find("androidx-core-ktx").version.as_deref(), Some("1.12.0"));
+        assert_eq!(find("compose-bom").module, "androidx.compose:compose-bom");
+        assert_eq!(find("compose-bom").version.as_deref(), Some("2024.02.00"));
+        assert_eq!(find("compose-ui").version, None);
+        assert_eq!(find("coil-compose").version.as_deref(), Some("2.5.0"));
+        assert_eq!(find("timber").version.as_deref(), Some("5.0.1"));
+        assert_eq!(catalog.plugins[0].version.as_deref(), Some("1.9.22"));
+        assert_eq!(catalog.bundles["compose"], ["compose-ui"]);
+        assert!(VersionCatalog::parse("[libraries\n").is_err());
+    }
+
+    #[test]
+    fn test_build_file_scanning() {
+        let script = r#"
+plugins {
+    alias(libs.plugins.kotlin.android)
+    id("com.google.gms.google-services") version "4.4.0" apply false
+}
+
+repositories {
+    google()
+    mavenCentral()
+    maven { url = uri("https://jitpack.io") }
+}
+
+dependencies {
+    implementation(libs.androidx.core.ktx)
+    implementation(platform(libs.compose.bom))
+    implementation(libs.bundles.compose)
+    implementation("com.squareup.okhttp3:okhttp:4.12.0")
+    debugImplementation 'com.jakewharton.timber:timber:4.7.1'
+    // implementation("com.example:commented:1.0")
+    testImplementation(kotlin("test"))
+}
+"#;
+        let file = Path::new("app/build.gradle.kts");
+        let hardcoded = find_hardcoded(script, file);
+        assert_eq!(hardcoded.len(), 3);
+        assert_eq!(
+            (hardcoded[0].configuration.as_str(), hardcoded[0].module.as_str()),
+            ("plugin", "com.google.gms.google-services")
+        );
+        assert_eq!(hardcoded[1].module, "com.squareup.okhttp3:okhttp");
+        assert_eq!((hardcoded[2].configuration.as_str(), hardcoded[2].version.as_str()), ("debugImplementation", "4.7.1"));
+
+        let repositories = find_repositories(script, file);
+        let hosts: Vec<String> = repositories.iter().filter_map(Repository::host).collect();
+        assert_eq!(hosts, ["dl.google.com", "repo.maven.apache.org", "jitpack.io"]);
+
+        let references = catalog_references(script);
+        assert!(references.contains("plugins.kotlin.android"));
+        assert!(references.contains("bundles.compose"));
+
+        let catalog = VersionCatalog::parse(CATALOG).unwrap();
+        assert_eq!(unused_entries(&catalog, &references), ["coil-compose", "timber"]);
+        let duplicates = find_duplicates(&catalog, &hardcoded, Path::new(""));
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].module, "com.jakewharton.timber:timber");
+        assert_eq!(duplicates[0].versions["4.7.1"], ["app/build.gradle.kts:18"]);
+    }
+
+    #[test]
+    fn test_audit() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |path: &str, content: &str| {
+            let path = dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write(VERSION_CATALOG, CATALOG);
+        write("settings.gradle.kts", "dependencyResolutionManagement {\n    repositories {\n        google()\n        maven(\"https://maven.example.com/releases\")\n    }\n}\n");
+        write("app/build.gradle.kts", "dependencies {\n    implementation(libs.timber)\n}\n");
+        write("app/build/generated/build.gradle.kts", "dependencies { implementation(\"a:b:1.0\") }\n");
+
+        let allowed = foodshare_core::config::DepsConfig::default().allowed_repositories;
+        let audit = audit(dir.path(), &allowed, false).unwrap();
+        assert_eq!(audit.build_files.len(), 2);
+        assert!(audit.hardcoded.is_empty());
+        assert_eq!(audit.disallowed.len(), 1);
+        assert_eq!(audit.disallowed[0].url, "https://maven.example.com/releases");
+        assert!(audit.unused.contains(&"androidx-core-ktx".to_string()));
+        assert!(audit.has_failures());
+    }
+
+    #[test]
+    fn test_versions() {
+        assert_eq!(compare_versions("1.10.0", "1.9.22"), Ordering::Greater);
+        assert_eq!(compare_versions("2024.02.00", "2024.02.00"), Ordering::Equal);
+        assert_eq!(compare_versions("1.0", "1.0.1"), Ordering::Less);
+        assert!(is_stable("1.12.0"));
+        assert!(!is_stable("1.13.0-alpha01"));
+        assert!(!is_stable("2.0.0-RC1"));
+        assert!(!is_stable("3.0-M2"));
+
+        let metadata = "<metadata><versioning><versions>\
+<version>1.11.0</version><version>1.12.0</version><version>1.13.0-beta01</version>\
+</versions></versioning></metadata>";
+        assert_eq!(latest_version(metadata).as_deref(), Some("1.12.0"));
+        assert!(repository_allowed(Some("dl.google.com"), &["google.com".to_string()]));
+        assert!(!repository_allowed(Some("jitpack.io"), &["google.com".to_string()]));
+    }
+}
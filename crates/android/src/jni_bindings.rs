@@ -0,0 +1,686 @@
+//! JNI binding verification
+//!
+//! Compares the Swift side of the bridge with the Java/Kotlin side so drift
+//! fails at hook time instead of crashing the app:
+//!
+//! - `@_cdecl("Java_…")` functions Swift exports must match a Kotlin
+//!   `external fun` / Java `native` method, and every such declaration must
+//!   have a Swift implementation (otherwise `UnsatisfiedLinkError`)
+//! - `@JavaMethod` / `@JavaStaticMethod` members of `@JavaClass` types must
+//!   exist on the Java/Kotlin class they wrap
+//!
+//! Signatures are compared in Java terms (`Int32`, `jint` and Kotlin `Int`
+//! are all `int`). Only classes under the given package prefix are checked,
+//! so wrappers of framework classes are left alone.
+
+use crate::xml::line_at;
+use foodshare_cli::textln;
+use foodshare_core::error::Result;
+use once_cell::sync::Lazy;
+use owo_colors::OwoColorize;
+use regex::Regex;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Package prefix checked by default
+pub const DEFAULT_PACKAGE: &str = "com.foodshare";
+
+static CDECL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"@_cdecl\(\s*"(Java_\w+)"\s*\)\s*(?:(?:public|internal)\s+)?func\s+\w+\s*\(([^)]*)\)\s*(?:->\s*([^{\n]+))?"#).unwrap()
+});
+static JAVA_CLASS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"@JavaClass\(\s*"([\w.$]+)"[^)]*\)\s*(?:(?:public|open|final)\s+)*(?:struct|class)\s+(\w+)"#).unwrap()
+});
+static EXTENSION: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?m)^[ \t]*(?:public\s+)?extension\s+(\w+)").unwrap());
+static JAVA_METHOD: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"@Java(?:Static)?Method(?:\([^)]*\))?\s*(?:(?:public|static|mutating|nonisolated)\s+)*func\s+(\w+)\s*\(([^)]*)\)\s*(?:throws\s*)?(?:->\s*([^{\n]+))?").unwrap()
+});
+static PACKAGE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^\s*package\s+([\w.]+)").unwrap());
+static JVM_CLASS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?m)^[ \t]*(?:(?:public|private|internal|protected|abstract|open|final|static|data|sealed|enum|inner)\s+)*(?:class|interface|object|record)\s+(\w+)").unwrap()
+});
+static KOTLIN_FUN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?m)^[ \t]*((?:@\w+(?:\([^)]*\))?\s+)*(?:(?:public|private|internal|protected|external|override|open|final|suspend|inline|operator|abstract)\s+)*)fun\s+(?:<[^>]*>\s*)?(\w+)\s*\(([^)]*)\)\s*(?::\s*([^={\n]+))?").unwrap()
+});
+static JAVA_FN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?m)^[ \t]*((?:@\w+\s+)*(?:(?:public|protected|private|static|final|native|synchronized|abstract|default)\s+)+)(?:<[^>]*>\s*)?([\w.<>\[\], ?]+?)\s+(\w+)\s*\(([^)]*)\)").unwrap()
+});
+
+const PRIMITIVES: &[&str] = &["int", "long", "short", "byte", "char", "boolean", "float", "double", "void"];
+
+/// A method on either side of the bridge, with types in Java terms
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Method {
+    /// Fully qualified Java class (`com.foodshare.swift.FoodshareCore`)
+    pub class: String,
+    /// Method name
+    pub name: String,
+    /// Parameter types
+    pub params: Vec<String>,
+    /// Return type
+    pub ret: String,
+    /// Kotlin `external` / Java `native` (always false on the Swift side)
+    pub native: bool,
+    /// Declaring file
+    pub file: PathBuf,
+    /// 1-based line
+    pub line: usize,
+}
+
+/// Declarations found in Swift sources
+#[derive(Debug, Default)]
+pub struct SwiftBindings {
+    /// `@_cdecl("Java_…")` implementations of JVM native methods
+    pub natives: Vec<Method>,
+    /// `@JavaMethod`s Swift expects on Java classes
+    pub expected: Vec<Method>,
+}
+
+/// Declarations found in Java/Kotlin sources
+#[derive(Debug, Default)]
+pub struct JvmBindings {
+    /// Fully qualified classes declared
+    pub classes: BTreeSet<String>,
+    /// Methods declared
+    pub methods: Vec<Method>,
+}
+
+/// Kind of drift
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueKind {
+    /// A JVM native method with no Swift implementation
+    MissingNative,
+    /// A Swift export no JVM class declares
+    UnusedNative,
+    /// A `@JavaClass` wrapping a class that doesn't exist
+    MissingClass,
+    /// A `@JavaMethod` the Java class doesn't have
+    MissingMethod,
+    /// Different parameter count
+    ParameterCount,
+    /// Incompatible parameter or return type
+    TypeMismatch,
+}
+
+/// One mismatch between the two sides
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BindingIssue {
+    /// Kind
+    pub kind: IssueKind,
+    /// `Class.method`
+    pub symbol: String,
+    /// What differs
+    pub message: String,
+    /// File to fix
+    pub file: PathBuf,
+    /// 1-based line
+    pub line: usize,
+}
+
+impl BindingIssue {
+    /// Whether this breaks at runtime; unused exports are only warnings
+    pub fn is_error(&self) -> bool {
+        self.kind != IssueKind::UnusedNative
+    }
+}
+
+/// Verify Swift sources against Java/Kotlin sources
+pub fn verify(swift_dir: &Path, jvm_dirs: &[PathBuf], package: &str) -> Result<Vec<BindingIssue>> {
+    let mut swift = SwiftBindings::default();
+    for file in source_files(&[swift_dir.to_path_buf()], &["swift"]) {
+        let content = std::fs::read_to_string(&file)?;
+        swift.extend(parse_swift(&content, &file));
+    }
+    let mut jvm = JvmBindings::default();
+    for file in source_files(jvm_dirs, &["kt", "java"]) {
+        let content = std::fs::read_to_string(&file)?;
+        let parsed = parse_jvm(&content, &file);
+        jvm.classes.extend(parsed.classes);
+        jvm.methods.extend(parsed.methods);
+    }
+    Ok(compare(&swift, &jvm, package))
+}
+
+impl SwiftBindings {
+    fn extend(&mut self, other: SwiftBindings) {
+        self.natives.extend(other.natives);
+        self.expected.extend(other.expected);
+    }
+}
+
+/// Parse one Swift file
+///
+/// `@JavaMethod`s in an `extension` of a `@JavaClass` type declared in
+/// another file are attributed by the type's Swift name, so those resolve
+/// only when the declaration is in the same file.
+pub fn parse_swift(content: &str, file: &Path) -> SwiftBindings {
+    let mut bindings = SwiftBindings::default();
+
+    for caps in CDECL.captures_iter(content) {
+        let Some((class, name)) = jni_symbol(&caps[1]) else {
+            continue;
+        };
+        // Skip the JNIEnv and jobject/jclass receiver
+        let params = split_params(&caps[2]).into_iter().skip(2).map(|p| swift_type(&p)).collect();
+        bindings.natives.push(Method {
+            class,
+            name,
+            params,
+            ret: swift_type(caps.get(3).map_or("", |m| m.as_str())),
+            native: false,
+            file: file.to_path_buf(),
+            line: line_at(content, caps.get(0).unwrap().start()),
+        });
+    }
+
+    // Owners of @JavaMethods: the nearest preceding @JavaClass type or extension
+    let classes: Vec<(String, String)> = JAVA_CLASS
+        .captures_iter(content)
+        .map(|c| (c[2].to_string(), jvm_class_name(&c[1])))
+        .collect();
+    let mut anchors: Vec<(usize, String)> = JAVA_CLASS
+        .captures_iter(content)
+        .map(|c| (c.get(0).unwrap().start(), c[2].to_string()))
+        .chain(EXTENSION.captures_iter(content).map(|c| (c.get(0).unwrap().start(), c[1].to_string())))
+        .collect();
+    anchors.sort();
+
+    for caps in JAVA_METHOD.captures_iter(content) {
+        let start = caps.get(0).unwrap().start();
+        let Some((_, owner)) = anchors.iter().rev().find(|(offset, _)| *offset < start) else {
+            continue;
+        };
+        let Some((_, class)) = classes.iter().find(|(swift_name, _)| swift_name == owner) else {
+            continue;
+        };
+        bindings.expected.push(Method {
+            class: class.clone(),
+            name: caps[1].to_string(),
+            params: split_params(&caps[2]).iter().map(|p| swift_type(p)).collect(),
+            ret: swift_type(caps.get(3).map_or("", |m| m.as_str())),
+            native: false,
+            file: file.to_path_buf(),
+            line: line_at(content, start),
+        });
+    }
+    bindings
+}
+
+/// Parse one Java or Kotlin file; methods belong to the nearest preceding
+/// class declaration
+pub fn parse_jvm(content: &str, file: &Path) -> JvmBindings {
+    let kotlin = file.extension().is_some_and(|e| e == "kt");
+    let package = PACKAGE.captures(content).map(|c| c[1].to_string());
+    let qualify = |name: &str| match &package {
+        Some(package) => format!("{}.{}", package, name),
+        None => name.to_string(),
+    };
+
+    let declarations: Vec<(usize, String)> = JVM_CLASS
+        .captures_iter(content)
+        .map(|c| (c.get(0).unwrap().start(), qualify(&c[1])))
+        .collect();
+    let owner = |offset: usize| {
+        declarations
+            .iter()
+            .rev()
+            .find(|(start, _)| *start < offset)
+            .map(|(_, class)| class.clone())
+    };
+
+    let mut bindings = JvmBindings {
+        classes: declarations.iter().map(|(_, class)| class.clone()).collect(),
+        methods: Vec::new(),
+    };
+    let regex: &Regex = if kotlin { &KOTLIN_FUN } else { &JAVA_FN };
+    for caps in regex.captures_iter(content) {
+        let start = caps.get(0).unwrap().start();
+        let Some(class) = owner(start) else {
+            continue;
+        };
+        let modifiers = &caps[1];
+        let method = if kotlin {
+            Method {
+                class,
+                name: caps[2].to_string(),
+                params: split_params(&caps[3]).iter().map(|p| kotlin_param(p)).collect(),
+                ret: caps.get(4).map_or_else(|| "void".to_string(), |m| kotlin_type(m.as_str())),
+                native: modifiers.split_whitespace().any(|m| m == "external"),
+                file: file.to_path_buf(),
+                line: line_at(content, start),
+            }
+        } else {
+            if matches!(&caps[2], "return" | "new" | "throw" | "else") {
+                continue;
+            }
+            Method {
+                class,
+                name: caps[3].to_string(),
+                params: split_params(&caps[4]).iter().map(|p| java_param(p)).collect(),
+                ret: java_type(&caps[2]),
+                native: modifiers.split_whitespace().any(|m| m == "native"),
+                file: file.to_path_buf(),
+                line: line_at(content, start),
+            }
+        };
+        bindings.methods.push(method);
+    }
+    bindings
+}
+
+/// Compare both sides, returning issues sorted by file and line
+pub fn compare(swift: &SwiftBindings, jvm: &JvmBindings, package: &str) -> Vec<BindingIssue> {
+    let in_scope = |class: &str| class == package || class.starts_with(&format!("{}.", package));
+    let mut issues = Vec::new();
+
+    for native in swift.natives.iter().filter(|m| in_scope(&m.class)) {
+        let candidates: Vec<&Method> = jvm
+            .methods
+            .iter()
+            .filter(|m| m.native && m.class == native.class && m.name == native.name)
+            .collect();
+        match best_match(native, &candidates) {
+            Some(declared) => issues.extend(mismatch(native, declared, &native.file, native.line)),
+            None => issues.push(BindingIssue {
+                kind: IssueKind::UnusedNative,
+                symbol: symbol(native),
+                message: "exported from Swift but not declared `external`/`native` on the JVM side".to_string(),
+                file: native.file.clone(),
+                line: native.line,
+            }),
+        }
+    }
+
+    for declared in jvm.methods.iter().filter(|m| m.native && in_scope(&m.class)) {
+        let implemented = swift
+            .natives
+            .iter()
+            .any(|m| m.class == declared.class && m.name == declared.name);
+        if !implemented {
+            issues.push(BindingIssue {
+                kind: IssueKind::MissingNative,
+                symbol: symbol(declared),
+                message: "declared native but Swift has no matching @_cdecl (UnsatisfiedLinkError at runtime)"
+                    .to_string(),
+                file: declared.file.clone(),
+                line: declared.line,
+            });
+        }
+    }
+
+    for expected in swift.expected.iter().filter(|m| in_scope(&m.class)) {
+        if !jvm.classes.contains(&expected.class) {
+            issues.push(BindingIssue {
+                kind: IssueKind::MissingClass,
+                symbol: symbol(expected),
+                message: format!("@JavaClass wraps {}, which doesn't exist", expected.class),
+                file: expected.file.clone(),
+                line: expected.line,
+            });
+            continue;
+        }
+        let candidates: Vec<&Method> = jvm
+            .methods
+            .iter()
+            .filter(|m| m.class == expected.class && m.name == expected.name)
+            .collect();
+        match best_match(expected, &candidates) {
+            Some(declared) => issues.extend(mismatch(expected, declared, &expected.file, expected.line)),
+            None => issues.push(BindingIssue {
+                kind: IssueKind::MissingMethod,
+                symbol: symbol(expected),
+                message: format!("@JavaMethod has no counterpart on {}", expected.class),
+                file: expected.file.clone(),
+                line: expected.line,
+            }),
+        }
+    }
+
+    issues.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+    issues
+}
+
+/// Print issues grouped by file
+pub fn print_issues(issues: &[BindingIssue]) {
+    let mut current: Option<&Path> = None;
+    for issue in issues {
+        if current != Some(issue.file.as_path()) {
+            textln!("{}", issue.file.display().to_string().bold());
+            current = Some(&issue.file);
+        }
+        let severity = if issue.is_error() {
+            "error".red().to_string()
+        } else {
+            "warning".yellow().to_string()
+        };
+        textln!("  {} {} {}: {}", issue.line, severity, issue.symbol.cyan(), issue.message);
+    }
+}
+
+/// Overload with the same parameter count, else the first declaration
+fn best_match<'a>(method: &Method, candidates: &[&'a Method]) -> Option<&'a Method> {
+    candidates
+        .iter()
+        .find(|c| c.params.len() == method.params.len())
+        .or_else(|| candidates.first())
+        .copied()
+}
+
+fn mismatch(swift: &Method, declared: &Method, file: &Path, line: usize) -> Option<BindingIssue> {
+    let side = if declared.file.extension().is_some_and(|e| e == "kt") { "Kotlin" } else { "Java" };
+    let at = format!("{}:{}", declared.file.display(), declared.line);
+    let (kind, message) = if swift.params.len() != declared.params.len() {
+        (
+            IssueKind::ParameterCount,
+            format!(
+                "Swift takes {} parameter(s), {} declares {} ({})",
+                swift.params.len(),
+                side,
+                declared.params.len(),
+                at
+            ),
+        )
+    } else if let Some((i, (a, b))) = swift
+        .params
+        .iter()
+        .zip(&declared.params)
+        .enumerate()
+        .find(|(_, (a, b))| !compatible(a, b))
+    {
+        (
+            IssueKind::TypeMismatch,
+            format!("parameter {} is `{}` in Swift, `{}` in {} ({})", i + 1, a, b, side, at),
+        )
+    } else if !compatible(&swift.ret, &declared.ret) {
+        (
+            IssueKind::TypeMismatch,
+            format!("returns `{}` in Swift, `{}` in {} ({})", swift.ret, declared.ret, side, at),
+        )
+    } else {
+        return None;
+    };
+    Some(BindingIssue {
+        kind,
+        symbol: symbol(swift),
+        message,
+        file: file.to_path_buf(),
+        line,
+    })
+}
+
+fn symbol(method: &Method) -> String {
+    let class = method.class.rsplit('.').next().unwrap_or(&method.class);
+    format!("{}.{}", class, method.name)
+}
+
+/// `*` (an untyped `jobject`) matches any reference type
+fn compatible(a: &str, b: &str) -> bool {
+    let reference = |t: &str| !PRIMITIVES.contains(&t);
+    a == b
+        || (a == "*" && reference(b))
+        || (b == "*" && reference(a))
+        || (a == "*[]" && b.ends_with("[]") && reference(b.trim_end_matches("[]")))
+        || (b == "*[]" && a.ends_with("[]") && reference(a.trim_end_matches("[]")))
+}
+
+/// Class and method from a JNI symbol (`Java_com_foodshare_Core_1Api_run`)
+fn jni_symbol(symbol: &str) -> Option<(String, String)> {
+    let mangled = symbol.strip_prefix("Java_")?;
+    // Overloaded natives append `__<signature>`
+    let mangled = mangled.split("__").next()?;
+    let mut parts: Vec<String> = mangled
+        .replace("_1", "\u{1}")
+        .replace("_00024", "$")
+        .split('_')
+        .map(|part| part.replace('\u{1}', "_"))
+        .collect();
+    let name = parts.pop()?;
+    if parts.is_empty() {
+        return None;
+    }
+    Some((jvm_class_name(&parts.join(".")), name))
+}
+
+/// Nested classes (`Outer$Inner`) are matched by their innermost name
+fn jvm_class_name(class: &str) -> String {
+    match class.rsplit_once('$') {
+        Some((outer, inner)) => match outer.rsplit_once('.') {
+            Some((package, _)) => format!("{}.{}", package, inner),
+            None => inner.to_string(),
+        },
+        None => class.to_string(),
+    }
+}
+
+/// Split a parameter list on top-level commas
+fn split_params(params: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut current = String::new();
+    for c in params.chars() {
+        match c {
+            '<' | '[' | '(' => depth += 1,
+            '>' | ']' | ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    parts.push(current);
+    parts
+        .into_iter()
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+/// Type of a Swift parameter (`_ id: Int32 = 0`) or return type
+fn swift_type(declaration: &str) -> String {
+    let ty = match declaration.split_once(':') {
+        Some((_, ty)) => ty,
+        None => declaration,
+    };
+    let ty = ty.split('=').next().unwrap_or(ty);
+    let ty = ty.replace("inout ", "").replace("@escaping ", "");
+    let ty = ty.trim().trim_end_matches(['?', '!']).trim();
+    if let Some(element) = ty.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+        return format!("{}[]", swift_type(element));
+    }
+    let mapped = match ty {
+        "" | "Void" | "()" => "void",
+        "Int32" | "CInt" | "jint" => "int",
+        "Int" | "Int64" | "jlong" => "long",
+        "Int16" | "jshort" => "short",
+        "Int8" | "jbyte" => "byte",
+        "UInt16" | "jchar" => "char",
+        "Bool" | "jboolean" => "boolean",
+        "Float" | "jfloat" => "float",
+        "Double" | "jdouble" => "double",
+        "String" | "jstring" => "String",
+        "jobject" | "jclass" | "jthrowable" => "*",
+        "jobjectArray" => "*[]",
+        other => {
+            if let Some(element) = other.strip_prefix('j').and_then(|t| t.strip_suffix("Array")) {
+                return format!("{}[]", element);
+            }
+            return simple_name(other);
+        }
+    };
+    mapped.to_string()
+}
+
+/// Type of a Kotlin parameter (`id: Int = 0`)
+fn kotlin_param(declaration: &str) -> String {
+    let ty = declaration.split_once(':').map_or(declaration, |(_, ty)| ty);
+    kotlin_type(ty.split('=').next().unwrap_or(ty))
+}
+
+fn kotlin_type(ty: &str) -> String {
+    let ty = ty.trim().trim_end_matches('?').trim();
+    if let Some(element) = ty.strip_prefix("Array<").and_then(|t| t.strip_suffix('>')) {
+        return format!("{}[]", kotlin_type(element));
+    }
+    let mapped = match ty {
+        "Unit" => "void",
+        "Int" => "int",
+        "Long" => "long",
+        "Short" => "short",
+        "Byte" => "byte",
+        "Char" => "char",
+        "Boolean" => "boolean",
+        "Float" => "float",
+        "Double" => "double",
+        other => {
+            if let Some(element) = other.strip_suffix("Array").filter(|e| PRIMITIVES.contains(&kotlin_type(e).as_str())) {
+                return format!("{}[]", kotlin_type(element));
+            }
+            return simple_name(other);
+        }
+    };
+    mapped.to_string()
+}
+
+/// Type of a Java parameter (`@NonNull final String name`)
+fn java_param(declaration: &str) -> String {
+    let tokens: Vec<&str> = declaration
+        .split_whitespace()
+        .filter(|t| !t.starts_with('@') && *t != "final")
+        .collect();
+    match tokens.split_last() {
+        Some((_, ty)) if !ty.is_empty() => java_type(&ty.join(" ")),
+        _ => java_type(declaration),
+    }
+}
+
+fn java_type(ty: &str) -> String {
+    let ty = ty.trim().replace("...", "[]");
+    simple_name(&ty)
+}
+
+/// Drop package and generic arguments: `java.util.List<String>[]` → `List[]`
+fn simple_name(ty: &str) -> String {
+    let arrays = ty.matches("[]").count();
+    let base = ty.split('<').next().unwrap_or(ty).trim_end_matches("[]").trim();
+    let base = base.rsplit('.').next().unwrap_or(base);
+    format!("{}{}", base, "[]".repeat(arrays))
+}
+
+fn source_files(dirs: &[PathBuf], extensions: &[&str]) -> BTreeSet<PathBuf> {
+    dirs.iter()
+        .filter(|dir| dir.exists())
+        .flat_map(|dir| {
+            WalkDir::new(dir)
+                .into_iter()
+                .filter_entry(|e| !matches!(e.file_name().to_str(), Some("build" | ".build" | "test" | "Tests")))
+                .filter_map(|e| e.ok())
+        })
+        .map(|e| e.into_path())
+        .filter(|path| path.extension().is_some_and(|e| extensions.iter().any(|x| e == *x)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SWIFT: &str = r#"
+import JavaKit
+
+@_cdecl("Java_com_foodshare_swift_FoodshareCore_validateListing")
+public func validateListing(env: UnsafeMutablePointer<JNIEnv?>!, this: jobject, title: jstring, quantity: jint) -> jboolean {
+    true
+}
+
+@_cdecl("Java_com_foodshare_swift_FoodshareCore_distance")
+func distance(env: UnsafeMutablePointer<JNIEnv?>!, this: jobject, lat: jdouble) -> jdouble { 0 }
+
+@_cdecl("Java_com_foodshare_swift_FoodshareCore_legacy_1hash")
+func legacyHash(env: UnsafeMutablePointer<JNIEnv?>!, this: jobject) -> jlong { 0 }
+
+@JavaClass("com.foodshare.android.Analytics")
+struct Analytics {
+    @JavaMethod
+    func track(_ event: String, _ count: Int32)
+
+    @JavaMethod
+    func flush() -> Bool
+}
+"#;
+
+    const KOTLIN: &str = r#"
+package com.foodshare.swift
+
+object FoodshareCore {
+    init { System.loadLibrary("FoodshareCore") }
+
+    external fun validateListing(title: String, quantity: Int): Boolean
+    external fun distance(lat: Double, lng: Double): Double
+    external fun formatPrice(cents: Long): String?
+}
+"#;
+
+    const JAVA: &str = r#"
+package com.foodshare.android;
+
+public final class Analytics {
+    public void track(@NonNull String event, long count) {
+        return;
+    }
+}
+"#;
+
+    fn sides() -> (SwiftBindings, JvmBindings) {
+        let swift = parse_swift(SWIFT, Path::new("Core/Bridge.swift"));
+        let mut jvm = parse_jvm(KOTLIN, Path::new("app/FoodshareCore.kt"));
+        let java = parse_jvm(JAVA, Path::new("app/Analytics.java"));
+        jvm.classes.extend(java.classes);
+        jvm.methods.extend(java.methods);
+        (swift, jvm)
+    }
+
+    #[test]
+    fn test_parse_both_sides() {
+        let (swift, jvm) = sides();
+        assert_eq!(swift.natives.len(), 3);
+        assert_eq!(swift.natives[0].class, "com.foodshare.swift.FoodshareCore");
+        assert_eq!(swift.natives[0].params, ["String", "int"]);
+        assert_eq!(swift.natives[0].ret, "boolean");
+        assert_eq!(swift.natives[2].name, "legacy_hash");
+        assert_eq!(swift.expected.len(), 2);
+        assert_eq!(swift.expected[0].class, "com.foodshare.android.Analytics");
+
+        let validate = jvm.methods.iter().find(|m| m.name == "validateListing").unwrap();
+        assert!(validate.native);
+        assert_eq!(validate.params, ["String", "int"]);
+        let track = jvm.methods.iter().find(|m| m.name == "track").unwrap();
+        assert_eq!((track.params.clone(), track.ret.as_str()), (vec!["String".to_string(), "long".to_string()], "void"));
+        assert!(jvm.classes.contains("com.foodshare.android.Analytics"));
+    }
+
+    #[test]
+    fn test_compare_reports_drift() {
+        let (swift, jvm) = sides();
+        let issues = compare(&swift, &jvm, DEFAULT_PACKAGE);
+        let found: Vec<(IssueKind, &str)> = issues.iter().map(|i| (i.kind, i.symbol.as_str())).collect();
+        assert_eq!(
+            found,
+            [
+                (IssueKind::ParameterCount, "FoodshareCore.distance"),
+                (IssueKind::UnusedNative, "FoodshareCore.legacy_hash"),
+                (IssueKind::TypeMismatch, "Analytics.track"),
+                (IssueKind::MissingMethod, "Analytics.flush"),
+                (IssueKind::MissingNative, "FoodshareCore.formatPrice"),
+            ]
+        );
+        assert!(issues[2].message.contains("parameter 2 is `int` in Swift, `long` in Java"));
+        assert!(!issues[1].is_error());
+
+        // Out-of-scope packages are not checked
+        assert!(compare(&swift, &jvm, "org.example").is_empty());
+    }
+}
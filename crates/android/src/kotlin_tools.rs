@@ -1,6 +1,7 @@
 //! Kotlin tooling wrappers
 //!
-//! Provides wrappers for Kotlin development tools.
+//! Provides wrappers for Kotlin development tools. detekt analysis lives in
+//! [`crate::detekt`].
 
 use foodshare_core::error::Result;
 use foodshare_core::process::{command_exists, run_command, run_command_in_dir, CommandResult};
@@ -38,30 +39,6 @@ pub fn check_directory(dir: &Path) -> Result<CommandResult> {
     run_command_in_dir("ktlint", &["**/*.kt", "**/*.kts"], dir)
 }
 
-/// Run detekt static analysis
-pub fn detekt_analyze(config_path: Option<&str>) -> Result<CommandResult> {
-    let mut args = vec!["--build-upon-default-config"];
-
-    if let Some(config) = config_path {
-        args.push("--config");
-        args.push(config);
-    }
-
-    run_command("detekt", &args)
-}
-
-/// Run detekt with auto-correct
-pub fn detekt_fix(config_path: Option<&str>) -> Result<CommandResult> {
-    let mut args = vec!["--auto-correct", "--build-upon-default-config"];
-
-    if let Some(config) = config_path {
-        args.push("--config");
-        args.push(config);
-    }
-
-    run_command("detekt", &args)
-}
-
 /// Get ktlint version
 pub fn ktlint_version() -> Result<String> {
     let result = run_command("ktlint", &["--version"])?;
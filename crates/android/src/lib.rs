@@ -1,16 +1,34 @@
 //! Android-specific tools for Foodshare
 //!
 //! This crate provides Android-specific functionality:
-//! - Gradle build system integration
-//! - Emulator management
-//! - Kotlin tooling wrappers
-//! - Swift cross-compilation for Android
+//! - Gradle build system integration and dependency audit
+//! - APK/AAB size analysis and per-ABI native library checks
+//! - AndroidManifest.xml permission and security policy checks
+//! - Emulator and connected device management
+//! - Logcat streaming and crash capture
+//! - Kotlin tooling wrappers and detekt analysis with baselines
+//! - Android Lint reports as text, JSON or SARIF
+//! - String resource localization lint
+//! - Swift cross-compilation for Android and JNI binding verification
 //! - FoodshareCore build scripts
 
 #![warn(missing_docs)]
 
+pub mod app_size;
+pub mod detekt;
+pub mod device;
 pub mod emulator;
 pub mod gradle;
+pub mod gradle_deps;
+pub mod jni_bindings;
 pub mod kotlin_tools;
+pub mod lint_report;
+pub mod localization;
+pub mod logcat;
+pub mod manifest;
+pub mod native_libs;
 pub mod swift_android;
 pub mod swift_core;
+
+mod xml;
+mod zip;
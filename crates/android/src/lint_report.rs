@@ -0,0 +1,387 @@
+//! Android Lint report ingestion
+//!
+//! Runs Android Lint through Gradle, reads the XML report it writes into
+//! typed [`LintFinding`]s, drops findings already accepted in the lint
+//! baseline, and exports the rest as text, JSON or SARIF for CI.
+//!
+//! Baseline entries match on issue ID, message and file, like AGP's own
+//! `lint { baseline = file(...) }` option, so line moves don't resurface them.
+
+use crate::gradle;
+use crate::xml::{attr, escape, tags};
+use foodshare_cli::textln;
+use foodshare_core::error::{Error, Result};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Lint baseline, relative to the project root
+pub const BASELINE_FILE: &str = "app/lint-baseline.xml";
+
+/// How serious a finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Lint `Informational`
+    Info,
+    /// Lint `Warning`
+    Warning,
+    /// Lint `Error` and `Fatal`
+    Error,
+}
+
+impl Severity {
+    /// Map a lint severity; `Ignore` has no mapping
+    pub fn from_lint(severity: &str) -> Option<Self> {
+        match severity {
+            "Fatal" | "Error" => Some(Self::Error),
+            "Warning" => Some(Self::Warning),
+            "Informational" | "Information" => Some(Self::Info),
+            _ => None,
+        }
+    }
+
+    fn sarif_level(&self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Info => "note",
+        }
+    }
+}
+
+/// Output format for findings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// Colored terminal output grouped by file
+    Text,
+    /// JSON array of findings
+    Json,
+    /// SARIF 2.1.0, for code scanning
+    Sarif,
+}
+
+impl std::str::FromStr for ReportFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "sarif" => Ok(Self::Sarif),
+            _ => Err(Error::validation(format!("Unknown format: {} (use text, json or sarif)", s))),
+        }
+    }
+}
+
+/// One Android Lint finding
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LintFinding {
+    /// Issue ID (`HardcodedText`)
+    pub id: String,
+    /// Severity
+    pub severity: Severity,
+    /// Category (`Internationalization`)
+    pub category: String,
+    /// One-line description of the issue type
+    pub summary: String,
+    /// Message for this occurrence
+    pub message: String,
+    /// File, relative to the project root when inside it
+    pub file: PathBuf,
+    /// 1-based line, if lint reported one
+    pub line: Option<usize>,
+    /// 1-based column, if lint reported one
+    pub column: Option<usize>,
+}
+
+impl LintFinding {
+    fn baseline_key(&self) -> (String, String, String) {
+        let file = self.file.file_name().map(|f| f.to_string_lossy().to_string()).unwrap_or_default();
+        (self.id.clone(), self.message.clone(), file)
+    }
+}
+
+/// Run lint for a variant (`debug`, `release`) and return the report path
+///
+/// Lint fails the Gradle task when it finds errors, so a failed task with a
+/// report is not an error here.
+pub fn run(project_dir: &Path, variant: &str) -> Result<PathBuf> {
+    let mut chars = variant.chars();
+    let capitalized: String = chars.next().map(|c| c.to_uppercase().chain(chars).collect()).unwrap_or_default();
+    let report = report_path(project_dir, variant);
+    let _ = std::fs::remove_file(&report);
+
+    let result = gradle::run_task(project_dir, &format!(":app:lint{}", capitalized))?;
+    if !report.exists() {
+        return Err(Error::process(format!(
+            "Lint did not write {}: {}",
+            report.display(),
+            result.combined_output().trim()
+        )));
+    }
+    Ok(report)
+}
+
+/// XML report Gradle writes for a variant
+pub fn report_path(project_dir: &Path, variant: &str) -> PathBuf {
+    project_dir.join(format!("app/build/reports/lint-results-{}.xml", variant))
+}
+
+/// Parse a lint XML report or baseline
+///
+/// Each `<issue>` becomes one finding at its first `<location>`. Ignored
+/// issues are skipped.
+pub fn parse_report(content: &str, root: &Path) -> Result<Vec<LintFinding>> {
+    let tags = tags(content).map_err(|e| Error::validation(format!("Invalid lint report: {}", e)))?;
+    let mut findings = Vec::new();
+    let mut current: Option<LintFinding> = None;
+    let mut located = false;
+
+    for tag in &tags {
+        match (tag.name, tag.close) {
+            ("issue", true) => findings.extend(current.take()),
+            ("issue", false) => {
+                let attrs = tag.attributes(content);
+                let severity = match attr(&attrs, "severity") {
+                    Some(severity) => Severity::from_lint(severity),
+                    // Baselines don't record severity
+                    None => Some(Severity::Warning),
+                };
+                let finding = severity.map(|severity| LintFinding {
+                    id: attr(&attrs, "id").unwrap_or_default().to_string(),
+                    severity,
+                    category: attr(&attrs, "category").unwrap_or_default().to_string(),
+                    summary: attr(&attrs, "summary").unwrap_or_default().to_string(),
+                    message: attr(&attrs, "message").unwrap_or_default().to_string(),
+                    file: PathBuf::new(),
+                    line: None,
+                    column: None,
+                });
+                located = false;
+                if tag.empty {
+                    findings.extend(finding);
+                } else {
+                    current = finding;
+                }
+            }
+            ("location", false) if !located => {
+                let Some(finding) = current.as_mut() else {
+                    continue;
+                };
+                let attrs = tag.attributes(content);
+                let file = PathBuf::from(attr(&attrs, "file").unwrap_or_default());
+                finding.file = file.strip_prefix(root).map(Path::to_path_buf).unwrap_or(file);
+                finding.line = attr(&attrs, "line").and_then(|l| l.parse().ok());
+                finding.column = attr(&attrs, "column").and_then(|c| c.parse().ok());
+                located = true;
+            }
+            _ => {}
+        }
+    }
+    findings.sort_by(|a, b| (&a.file, a.line, &a.id).cmp(&(&b.file, b.line, &b.id)));
+    Ok(findings)
+}
+
+/// Load a report or baseline file
+pub fn load(path: &Path, root: &Path) -> Result<Vec<LintFinding>> {
+    if !path.exists() {
+        return Err(Error::file_not_found(path));
+    }
+    parse_report(&std::fs::read_to_string(path)?, root)
+}
+
+/// Split findings into new ones and ones the baseline already accepts
+pub fn filter_baseline(findings: Vec<LintFinding>, baseline: &[LintFinding]) -> (Vec<LintFinding>, usize) {
+    let known: HashSet<_> = baseline.iter().map(LintFinding::baseline_key).collect();
+    let total = findings.len();
+    let new: Vec<LintFinding> = findings.into_iter().filter(|f| !known.contains(&f.baseline_key())).collect();
+    let accepted = total - new.len();
+    (new, accepted)
+}
+
+/// Write findings as a lint baseline
+pub fn write_baseline(findings: &[LintFinding], path: &Path) -> Result<()> {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<issues format=\"6\" by=\"foodshare-android\">\n");
+    for finding in findings {
+        xml.push_str(&format!(
+            "\n    <issue\n        id=\"{}\"\n        message=\"{}\">\n        <location\n            file=\"{}\"/>\n    </issue>\n",
+            escape(&finding.id),
+            escape(&finding.message),
+            escape(&finding.file.to_string_lossy())
+        ));
+    }
+    xml.push_str("\n</issues>\n");
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, xml)?;
+    Ok(())
+}
+
+/// Render findings as a JSON array
+pub fn to_json(findings: &[LintFinding]) -> Result<String> {
+    Ok(serde_json::to_string_pretty(findings)?)
+}
+
+/// Render findings as a SARIF 2.1.0 log
+pub fn to_sarif(findings: &[LintFinding]) -> Result<String> {
+    let rules: BTreeMap<&str, &LintFinding> = findings.iter().map(|f| (f.id.as_str(), f)).collect();
+    let rules: Vec<serde_json::Value> = rules
+        .values()
+        .map(|f| {
+            serde_json::json!({
+                "id": f.id,
+                "shortDescription": { "text": f.summary },
+                "properties": { "category": f.category },
+            })
+        })
+        .collect();
+    let results: Vec<serde_json::Value> = findings
+        .iter()
+        .map(|f| {
+            let mut region = serde_json::Map::new();
+            if let Some(line) = f.line {
+                region.insert("startLine".into(), line.into());
+            }
+            if let Some(column) = f.column {
+                region.insert("startColumn".into(), column.into());
+            }
+            serde_json::json!({
+                "ruleId": f.id,
+                "level": f.severity.sarif_level(),
+                "message": { "text": f.message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": f.file.to_string_lossy() },
+                        "region": region,
+                    }
+                }],
+            })
+        })
+        .collect();
+
+    let log = serde_json::json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": { "driver": { "name": "Android Lint", "rules": rules } },
+            "results": results,
+        }],
+    });
+    Ok(serde_json::to_string_pretty(&log)?)
+}
+
+/// Print findings grouped by file
+pub fn print_findings(findings: &[LintFinding]) {
+    use owo_colors::OwoColorize;
+
+    let mut current: Option<&Path> = None;
+    for finding in findings {
+        if current != Some(finding.file.as_path()) {
+            textln!("{}", finding.file.display().to_string().bold());
+            current = Some(&finding.file);
+        }
+        let severity = match finding.severity {
+            Severity::Error => "error".red().to_string(),
+            Severity::Warning => "warning".yellow().to_string(),
+            Severity::Info => "info".blue().to_string(),
+        };
+        let location = match (finding.line, finding.column) {
+            (Some(line), Some(column)) => format!("{}:{}", line, column),
+            (Some(line), None) => line.to_string(),
+            _ => "-".to_string(),
+        };
+        textln!(
+            "  {} {} {} {}",
+            location,
+            severity,
+            finding.message,
+            format!("({})", finding.id).dimmed()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPORT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<issues format="6" by="lint 8.5.0">
+
+    <issue
+        id="HardcodedText"
+        severity="Warning"
+        message="Hardcoded string &quot;Share&quot;, should use `@string` resource"
+        category="Internationalization"
+        priority="5"
+        summary="Hardcoded text"
+        errorLine1="        android:text=&quot;Share&quot;"
+        errorLine2="        ~~~~~~~~~~~~~~~~~~~~~">
+        <location
+            file="/work/app/src/main/res/layout/item_listing.xml"
+            line="24"
+            column="9"/>
+    </issue>
+
+    <issue
+        id="MissingPermission"
+        severity="Fatal"
+        message="Missing permissions required by FusedLocationProviderClient.getLastLocation"
+        category="Correctness"
+        summary="Missing Permissions">
+        <location
+            file="/work/app/src/main/java/com/foodshare/MapScreen.kt"
+            line="88"
+            column="13"/>
+        <location
+            file="/work/app/src/main/AndroidManifest.xml"
+            line="3"/>
+    </issue>
+
+    <issue
+        id="UnusedResources"
+        severity="Ignore"
+        message="The resource `R.color.legacy` appears to be unused"
+        category="Performance"
+        summary="Unused resources">
+        <location
+            file="/work/app/src/main/res/values/colors.xml"/>
+    </issue>
+
+</issues>
+"#;
+
+    #[test]
+    fn test_parse_report() {
+        let findings = parse_report(REPORT, Path::new("/work")).unwrap();
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].id, "MissingPermission");
+        assert_eq!(findings[0].severity, Severity::Error);
+        assert_eq!(findings[0].file, Path::new("app/src/main/java/com/foodshare/MapScreen.kt"));
+        assert_eq!((findings[0].line, findings[0].column), (Some(88), Some(13)));
+        assert_eq!(findings[1].message, "Hardcoded string \"Share\", should use `@string` resource");
+        assert_eq!(findings[1].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn test_baseline_round_trip_and_sarif() {
+        let dir = tempfile::tempdir().unwrap();
+        let findings = parse_report(REPORT, Path::new("/work")).unwrap();
+        let path = dir.path().join(BASELINE_FILE);
+        write_baseline(&findings[1..], &path).unwrap();
+
+        let baseline = load(&path, dir.path()).unwrap();
+        assert_eq!(baseline.len(), 1);
+        let (new, accepted) = filter_baseline(findings.clone(), &baseline);
+        assert_eq!(accepted, 1);
+        assert_eq!(new.len(), 1);
+        assert_eq!(new[0].id, "MissingPermission");
+
+        let sarif: serde_json::Value = serde_json::from_str(&to_sarif(&findings).unwrap()).unwrap();
+        let run = &sarif["runs"][0];
+        assert_eq!(run["tool"]["driver"]["rules"].as_array().unwrap().len(), 2);
+        assert_eq!(run["results"][0]["level"], "error");
+        assert_eq!(run["results"][0]["locations"][0]["physicalLocation"]["region"]["startLine"], 88);
+    }
+}
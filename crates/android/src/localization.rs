@@ -0,0 +1,712 @@
+//! String resource localization lint
+//!
+//! Compares every `values-<locale>` directory of each resource directory
+//! against the default `values` strings, the same way the iOS localization
+//! lint compares `.lproj` tables. Reported per locale:
+//!
+//! - **missing**: a source string or plural the locale doesn't have
+//! - **stale**: a name no longer in the source
+//! - **placeholder mismatch**: format arguments that differ in type or
+//!   position from the source, which crash `getString` at runtime
+//! - **untranslated**: a value identical to the source
+//! - **missing quantities**: plurals without the quantities the locale's
+//!   plural rules use (`few` and `many` in Russian)
+//!
+//! Layouts and Compose code are also scanned for hardcoded user-facing text.
+//! Placeholder mismatches are errors; the rest are warnings. Strings marked
+//! `translatable="false"` are skipped.
+
+use crate::xml::{attr, line_at, tags, unescape};
+use foodshare_cli::textln;
+use foodshare_core::error::{Error, Result};
+use once_cell::sync::Lazy;
+use owo_colors::OwoColorize;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Locale of the default `values` directory
+pub const SOURCE_LOCALE: &str = "en";
+
+/// Directories never searched
+const SKIP_DIRS: &[&str] = &[".git", "build", ".gradle", ".idea", "node_modules"];
+
+/// Source sets whose code never reaches users
+const TEST_SOURCE_SETS: &[&str] = &["test", "androidTest"];
+
+/// Layout attributes holding user-facing text
+const LAYOUT_TEXT_ATTRIBUTES: &[&str] = &[
+    "android:text",
+    "android:hint",
+    "android:contentDescription",
+    "android:title",
+    "app:title",
+];
+
+/// A `java.util.Formatter` argument: `%s`, `%d`, `%1$s`, `%.2f`
+static FORMAT_SPECIFIER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"%(?:(\d+)\$)?[-#+ 0,(]*\d*(?:\.\d+)?([sSdoxXeEfgGaAcCbBhHn%])").unwrap());
+
+/// A string literal passed as text in Compose
+static COMPOSE_TEXT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?:\bText\(\s*(?:text\s*=\s*)?|\bcontentDescription\s*=\s*)"((?:[^"\\]|\\.)*)""#).unwrap()
+});
+
+/// A Kotlin string template (`$name`, `${listing.title}`)
+static TEMPLATE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\$\{[^}]*\}|\$\w+").unwrap());
+
+/// Kind of localization problem
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum IssueKind {
+    /// Source string missing from the locale
+    Missing,
+    /// String that is no longer in the source
+    Stale,
+    /// Format arguments differ from the source
+    PlaceholderMismatch {
+        /// Arguments in the source, in position order
+        expected: String,
+        /// Arguments in the translation, in position order
+        found: String,
+    },
+    /// Value not translated yet
+    Untranslated,
+    /// Plural lacks quantities the locale uses
+    MissingQuantities {
+        /// Missing quantities (`few many`)
+        missing: String,
+    },
+    /// User-facing text in a layout or Compose code instead of a resource
+    Hardcoded,
+}
+
+impl IssueKind {
+    /// Whether the issue breaks the app at runtime
+    pub fn is_error(&self) -> bool {
+        matches!(self, Self::PlaceholderMismatch { .. })
+    }
+}
+
+/// A problem with one string in one locale
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LocalizationIssue {
+    /// Locale code (`de`, `pt-BR`)
+    pub locale: String,
+    /// Module the resources belong to (`app`, `feature/listing`)
+    pub module: String,
+    /// Resource name, or the text itself for hardcoded strings
+    pub key: String,
+    /// What is wrong
+    #[serde(flatten)]
+    pub kind: IssueKind,
+    /// File the string lives in (or should)
+    pub file: PathBuf,
+    /// 1-based line, when known
+    pub line: Option<usize>,
+}
+
+/// Issue counts of one locale
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LocaleCounts {
+    /// Missing strings
+    pub missing: usize,
+    /// Stale strings
+    pub stale: usize,
+    /// Placeholder mismatches
+    pub placeholders: usize,
+    /// Untranslated values
+    pub untranslated: usize,
+    /// Plurals missing quantities
+    pub plurals: usize,
+    /// Hardcoded strings (source locale only)
+    pub hardcoded: usize,
+}
+
+/// Result of linting every resource directory under a directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LocalizationReport {
+    /// Locale of the default `values` directory
+    pub source_locale: String,
+    /// Every locale seen, sorted
+    pub locales: Vec<String>,
+    /// Number of resource directories checked
+    pub modules: usize,
+    /// Issues, grouped by module and locale
+    pub issues: Vec<LocalizationIssue>,
+}
+
+impl LocalizationReport {
+    /// Issue counts per locale
+    pub fn counts(&self) -> BTreeMap<String, LocaleCounts> {
+        let mut counts: BTreeMap<String, LocaleCounts> = self
+            .locales
+            .iter()
+            .map(|locale| (locale.clone(), LocaleCounts::default()))
+            .collect();
+        for issue in &self.issues {
+            let entry = counts.entry(issue.locale.clone()).or_default();
+            match issue.kind {
+                IssueKind::Missing => entry.missing += 1,
+                IssueKind::Stale => entry.stale += 1,
+                IssueKind::PlaceholderMismatch { .. } => entry.placeholders += 1,
+                IssueKind::Untranslated => entry.untranslated += 1,
+                IssueKind::MissingQuantities { .. } => entry.plurals += 1,
+                IssueKind::Hardcoded => entry.hardcoded += 1,
+            }
+        }
+        counts
+    }
+
+    /// Number of errors
+    pub fn error_count(&self) -> usize {
+        self.issues.iter().filter(|i| i.kind.is_error()).count()
+    }
+
+    /// Print per-locale counts, then the issues (up to `limit` per locale)
+    pub fn print(&self, limit: usize) {
+        textln!(
+            "{}",
+            format!("Localization ({} resource directories, source: {})", self.modules, self.source_locale).bold()
+        );
+        for (locale, c) in self.counts() {
+            let line = format!(
+                "  {:<8} {:>4} missing  {:>4} stale  {:>4} placeholders  {:>4} untranslated  {:>4} plurals  {:>4} hardcoded",
+                locale, c.missing, c.stale, c.placeholders, c.untranslated, c.plurals, c.hardcoded
+            );
+            if c.placeholders > 0 {
+                textln!("{}", line.red());
+            } else if c.missing + c.stale + c.untranslated + c.plurals + c.hardcoded > 0 {
+                textln!("{}", line.yellow());
+            } else {
+                textln!("{}", line);
+            }
+        }
+
+        let mut shown: BTreeMap<&str, usize> = BTreeMap::new();
+        let mut sorted: Vec<&LocalizationIssue> = self.issues.iter().collect();
+        // Errors first, so a limit never hides them
+        sorted.sort_by_key(|issue| !issue.kind.is_error());
+
+        let mut printed_header = false;
+        for issue in sorted {
+            let count = shown.entry(&issue.locale).or_default();
+            *count += 1;
+            if *count > limit {
+                continue;
+            }
+            if !printed_header {
+                textln!();
+                printed_header = true;
+            }
+            let description = match &issue.kind {
+                IssueKind::Missing => "missing".yellow().to_string(),
+                IssueKind::Stale => "stale".yellow().to_string(),
+                IssueKind::Untranslated => "untranslated".yellow().to_string(),
+                IssueKind::MissingQuantities { missing } => format!("plural lacks {}", missing).yellow().to_string(),
+                IssueKind::Hardcoded => format!(
+                    "hardcoded in {}:{}",
+                    issue.file.display(),
+                    issue.line.unwrap_or_default()
+                )
+                .yellow()
+                .to_string(),
+                IssueKind::PlaceholderMismatch { expected, found } => {
+                    format!("placeholders {} (source has {})", found, expected).red().to_string()
+                }
+            };
+            textln!("  [{}] {}: {}: {}", issue.locale, issue.module, issue.key, description);
+        }
+    }
+}
+
+/// A string or plural resource
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Value {
+    Text(String),
+    /// Quantity to text
+    Plural(BTreeMap<String, String>),
+}
+
+/// Strings of one locale in one resource directory
+#[derive(Debug, Clone, Default)]
+struct LocaleStrings {
+    /// `strings.xml` of the locale, where missing strings belong
+    file: PathBuf,
+    /// Name to (value, file, line)
+    entries: BTreeMap<String, (Value, PathBuf, usize)>,
+}
+
+/// Lint every resource directory and Kotlin source under `root`
+pub fn lint(root: &Path, source_locale: &str) -> Result<LocalizationReport> {
+    let mut report = LocalizationReport {
+        source_locale: source_locale.to_string(),
+        ..Default::default()
+    };
+
+    let walker = WalkDir::new(root).sort_by_file_name().into_iter().filter_entry(|entry| {
+        !(entry.file_type().is_dir() && SKIP_DIRS.contains(&entry.file_name().to_string_lossy().as_ref()))
+    });
+    let mut res_dirs = Vec::new();
+    let mut kotlin_files = Vec::new();
+    for entry in walker.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if entry.file_type().is_dir() && entry.file_name() == "res" && path.join("values").is_dir() {
+            res_dirs.push(path.to_path_buf());
+        } else if entry.file_type().is_file()
+            && path.extension().is_some_and(|ext| ext == "kt")
+            && !is_test_source(path)
+        {
+            kotlin_files.push(path.to_path_buf());
+        }
+    }
+
+    for res in &res_dirs {
+        let module = module_name(root, res);
+        let mut locales: BTreeMap<String, LocaleStrings> = BTreeMap::new();
+        for entry in std::fs::read_dir(res)? {
+            let dir = entry?.path();
+            let name = dir.file_name().unwrap_or_default().to_string_lossy().to_string();
+            if name.starts_with("layout") {
+                scan_layouts(&dir, source_locale, &module, &mut report)?;
+                continue;
+            }
+            let Some(locale) = locale_of(&name, source_locale) else {
+                continue;
+            };
+            let strings = locales.entry(locale).or_default();
+            strings.file = dir.join("strings.xml");
+            for file in xml_files(&dir)? {
+                let content = std::fs::read_to_string(&file)?;
+                let resources = parse_resources(&content)
+                    .map_err(|e| Error::validation(format!("Invalid resource file {}: {}", file.display(), e)))?;
+                for (name, value, line) in resources {
+                    strings.entries.insert(name, (value, file.clone(), line));
+                }
+            }
+        }
+        compare(&module, source_locale, &locales, &mut report);
+        report.modules += 1;
+    }
+
+    for file in &kotlin_files {
+        let content = std::fs::read_to_string(file)?;
+        for (text, line) in compose_strings(&content) {
+            report.issues.push(LocalizationIssue {
+                locale: source_locale.to_string(),
+                module: module_name(root, file),
+                key: text,
+                kind: IssueKind::Hardcoded,
+                file: file.to_path_buf(),
+                line: Some(line),
+            });
+        }
+    }
+
+    if report.issues.iter().any(|i| i.kind == IssueKind::Hardcoded) {
+        report.locales.push(source_locale.to_string());
+    }
+    report.locales.sort();
+    report.locales.dedup();
+    Ok(report)
+}
+
+/// Compare every locale of a resource directory with its source locale
+fn compare(module: &str, source_locale: &str, locales: &BTreeMap<String, LocaleStrings>, report: &mut LocalizationReport) {
+    let Some(source) = locales.get(source_locale) else {
+        return;
+    };
+    report.locales.extend(locales.keys().cloned());
+    let issue = |locale: &str, key: &str, kind: IssueKind, file: &Path, line: Option<usize>| LocalizationIssue {
+        locale: locale.to_string(),
+        module: module.to_string(),
+        key: key.to_string(),
+        kind,
+        file: file.to_path_buf(),
+        line,
+    };
+
+    for (locale, strings) in locales {
+        for (key, (value, file, line)) in &strings.entries {
+            if let Value::Plural(quantities) = value {
+                let missing: Vec<&str> = required_quantities(locale)
+                    .iter()
+                    .filter(|q| !quantities.contains_key(**q))
+                    .copied()
+                    .collect();
+                if !missing.is_empty() {
+                    let kind = IssueKind::MissingQuantities { missing: missing.join(" ") };
+                    report.issues.push(issue(locale, key, kind, file, Some(*line)));
+                }
+            }
+        }
+        if locale == source_locale {
+            continue;
+        }
+
+        for (key, (source_value, _, _)) in &source.entries {
+            let Some((value, file, line)) = strings.entries.get(key) else {
+                report.issues.push(issue(locale, key, IssueKind::Missing, &strings.file, None));
+                continue;
+            };
+            // Compare the text used for most counts when plurals differ in quantities
+            let (source_text, text) = match (source_value, value) {
+                (Value::Text(source_text), Value::Text(text)) => (source_text, text),
+                (Value::Plural(source_q), Value::Plural(q)) => match (source_q.get("other"), q.get("other")) {
+                    (Some(source_text), Some(text)) => (source_text, text),
+                    _ => continue,
+                },
+                _ => continue,
+            };
+
+            let expected = placeholders(source_text);
+            let found = placeholders(text);
+            if expected != found {
+                let kind = IssueKind::PlaceholderMismatch {
+                    expected: describe(&expected),
+                    found: describe(&found),
+                };
+                report.issues.push(issue(locale, key, kind, file, Some(*line)));
+            } else if text == source_text && FORMAT_SPECIFIER.replace_all(text, "").chars().any(char::is_alphabetic) {
+                report.issues.push(issue(locale, key, IssueKind::Untranslated, file, Some(*line)));
+            }
+        }
+        for (key, (_, file, line)) in strings.entries.iter().filter(|(key, _)| !source.entries.contains_key(*key)) {
+            report.issues.push(issue(locale, key, IssueKind::Stale, file, Some(*line)));
+        }
+    }
+}
+
+/// Locale of a `values` directory (`values-pt-rBR` is `pt-BR`)
+///
+/// Directories with other qualifiers (`values-night`, `values-de-land`)
+/// are not string tables of their own and give `None`.
+fn locale_of(dir_name: &str, source_locale: &str) -> Option<String> {
+    let qualifiers = dir_name.strip_prefix("values")?;
+    if qualifiers.is_empty() {
+        return Some(source_locale.to_string());
+    }
+    let mut parts = qualifiers.strip_prefix('-')?.split('-');
+    let language = parts.next()?;
+    if let Some(tag) = language.strip_prefix("b+") {
+        return parts.next().is_none().then(|| tag.replace('+', "-"));
+    }
+    // `car` is the car UI mode, not a language
+    if !(2..=3).contains(&language.len()) || !language.chars().all(|c| c.is_ascii_lowercase()) || language == "car" {
+        return None;
+    }
+    match (parts.next(), parts.next()) {
+        (None, _) => Some(language.to_string()),
+        (Some(region), None) => {
+            let region = region.strip_prefix('r').filter(|r| r.len() == 2 && r.chars().all(|c| c.is_ascii_uppercase()))?;
+            Some(format!("{}-{}", language, region))
+        }
+        _ => None,
+    }
+}
+
+/// Plural quantities a locale's integer plural rules use (CLDR)
+fn required_quantities(locale: &str) -> &'static [&'static str] {
+    let language = locale.split('-').next().unwrap_or(locale);
+    match language {
+        "ja" | "zh" | "ko" | "vi" | "th" | "id" | "in" | "ms" | "my" | "km" | "lo" => &["other"],
+        "ru" | "uk" | "be" | "pl" => &["one", "few", "many", "other"],
+        "cs" | "sk" | "hr" | "sr" | "bs" | "ro" | "lt" => &["one", "few", "other"],
+        "sl" => &["one", "two", "few", "other"],
+        "he" | "iw" => &["one", "two", "other"],
+        "ga" => &["one", "two", "few", "many", "other"],
+        "ar" | "cy" => &["zero", "one", "two", "few", "many", "other"],
+        _ => &["one", "other"],
+    }
+}
+
+/// Format arguments of a string in position order, normalized (`%S` is `%s`)
+fn placeholders(value: &str) -> Vec<String> {
+    let mut next = 0;
+    let mut specifiers: Vec<(usize, String)> = FORMAT_SPECIFIER
+        .captures_iter(value)
+        .filter(|caps| !matches!(&caps[2], "%" | "n"))
+        .map(|caps| {
+            let position = match caps.get(1).and_then(|p| p.as_str().parse::<usize>().ok()) {
+                Some(position) => position,
+                None => {
+                    next += 1;
+                    next
+                }
+            };
+            (position, format!("%{}", caps[2].to_lowercase()))
+        })
+        .collect();
+    specifiers.sort_by_key(|(position, _)| *position);
+    specifiers.into_iter().map(|(_, specifier)| specifier).collect()
+}
+
+fn describe(specifiers: &[String]) -> String {
+    if specifiers.is_empty() {
+        "none".to_string()
+    } else {
+        specifiers.join(" ")
+    }
+}
+
+/// Translatable `<string>` and `<plurals>` resources with their line
+fn parse_resources(content: &str) -> std::result::Result<Vec<(String, Value, usize)>, String> {
+    let tags = tags(content)?;
+    let mut resources = Vec::new();
+    let text_until = |from: usize, name: &str| -> Option<(String, usize)> {
+        let close = tags[from + 1..].iter().position(|t| t.close && t.name == name)? + from + 1;
+        Some((resource_text(&content[tags[from].end..tags[close].start]), close))
+    };
+
+    let mut i = 0;
+    while i < tags.len() {
+        let tag = &tags[i];
+        i += 1;
+        if tag.close || !matches!(tag.name, "string" | "plurals") {
+            continue;
+        }
+        let attrs = tag.attributes(content);
+        let (Some(name), true) = (attr(&attrs, "name"), attr(&attrs, "translatable") != Some("false")) else {
+            continue;
+        };
+        let line = line_at(content, tag.start);
+
+        if tag.name == "string" {
+            let text = if tag.empty {
+                String::new()
+            } else {
+                let (text, close) = text_until(i - 1, "string").ok_or_else(|| format!("unclosed string {}", name))?;
+                i = close + 1;
+                text
+            };
+            resources.push((name.to_string(), Value::Text(text), line));
+            continue;
+        }
+
+        let mut quantities = BTreeMap::new();
+        while i < tags.len() && !(tags[i].close && tags[i].name == "plurals") {
+            if tags[i].name == "item" && !tags[i].close && !tags[i].empty {
+                let item_attrs = tags[i].attributes(content);
+                let (text, close) = text_until(i, "item").ok_or_else(|| format!("unclosed item in {}", name))?;
+                if let Some(quantity) = attr(&item_attrs, "quantity") {
+                    quantities.insert(quantity.to_string(), text);
+                }
+                i = close;
+            }
+            i += 1;
+        }
+        resources.push((name.to_string(), Value::Plural(quantities), line));
+    }
+    Ok(resources)
+}
+
+/// Text of a resource value, with CDATA unwrapped and escapes decoded
+fn resource_text(raw: &str) -> String {
+    let raw = raw.trim();
+    let raw = raw
+        .strip_prefix("<![CDATA[")
+        .and_then(|r| r.strip_suffix("]]>"))
+        .map(str::to_string)
+        .unwrap_or_else(|| unescape(raw));
+    raw.replace("\\'", "'").replace("\\\"", "\"").replace("\\n", "\n")
+}
+
+/// Report hardcoded text attributes in the layouts of a `layout*` directory
+fn scan_layouts(dir: &Path, source_locale: &str, module: &str, report: &mut LocalizationReport) -> Result<()> {
+    for file in xml_files(dir)? {
+        let content = std::fs::read_to_string(&file)?;
+        let Ok(tags) = tags(&content) else {
+            continue;
+        };
+        for tag in tags.iter().filter(|t| !t.close) {
+            let attrs = tag.attributes(&content);
+            for (_, value) in attrs.iter().filter(|(name, _)| LAYOUT_TEXT_ATTRIBUTES.contains(&name.as_str())) {
+                if is_user_text(value) && !value.starts_with(['@', '?']) {
+                    report.issues.push(LocalizationIssue {
+                        locale: source_locale.to_string(),
+                        module: module.to_string(),
+                        key: value.clone(),
+                        kind: IssueKind::Hardcoded,
+                        file: file.clone(),
+                        line: Some(line_at(&content, tag.start)),
+                    });
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// String literals shown as text in Compose code, with their line
+fn compose_strings(content: &str) -> Vec<(String, usize)> {
+    COMPOSE_TEXT
+        .captures_iter(content)
+        .filter(|caps| is_user_text(&caps[1]))
+        .map(|caps| (caps[1].to_string(), line_at(content, caps.get(1).map_or(0, |m| m.start()))))
+        .collect()
+}
+
+/// Whether text has words in it, beyond string templates
+fn is_user_text(text: &str) -> bool {
+    TEMPLATE.replace_all(text, "").chars().any(char::is_alphabetic)
+}
+
+fn xml_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "xml"))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Module of a path: the directories before `src` (`feature/listing`)
+fn module_name(root: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    let module: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .take_while(|c| c != "src")
+        .collect();
+    if module.is_empty() || module.len() == relative.components().count() {
+        ".".to_string()
+    } else {
+        module.join("/")
+    }
+}
+
+fn is_test_source(path: &Path) -> bool {
+    let mut components = path.components().map(|c| c.as_os_str().to_string_lossy());
+    components.any(|c| c == "src") && components.next().is_some_and(|set| TEST_SOURCE_SETS.contains(&set.as_ref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placeholders_and_locales() {
+        assert_eq!(placeholders("%1$s shared %2$d items"), ["%s", "%d"]);
+        assert_eq!(placeholders("%2$d Artikel von %1$s"), ["%s", "%d"]);
+        assert_eq!(placeholders("100%% of %S%n"), ["%s"]);
+        assert_eq!(placeholders("%.1f km"), ["%f"]);
+        assert!(placeholders("No placeholders").is_empty());
+
+        assert_eq!(locale_of("values", "en").as_deref(), Some("en"));
+        assert_eq!(locale_of("values-de", "en").as_deref(), Some("de"));
+        assert_eq!(locale_of("values-pt-rBR", "en").as_deref(), Some("pt-BR"));
+        assert_eq!(locale_of("values-b+sr+Latn", "en").as_deref(), Some("sr-Latn"));
+        for other in ["values-night", "values-v26", "values-de-land", "values-sw600dp", "values-car", "drawable"] {
+            assert_eq!(locale_of(other, "en"), None, "{}", other);
+        }
+    }
+
+    #[test]
+    fn test_lint_resources() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |path: &str, content: &str| {
+            let path = dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write(
+            "app/src/main/res/values/strings.xml",
+            r#"<resources>
+    <string name="app_name" translatable="false">FoodShare</string>
+    <string name="feed_title">Nearby food</string>
+    <string name="feed_count">%1$s shared %2$d items</string>
+    <string name="button_share">Share <b>now</b></string>
+    <plurals name="listings">
+        <item quantity="one">%d listing</item>
+        <item quantity="other">%d listings</item>
+    </plurals>
+</resources>
+"#,
+        );
+        write(
+            "app/src/main/res/values-de/strings.xml",
+            r#"<resources>
+    <string name="feed_title">Essen in der Nähe</string>
+    <string name="feed_count">%2$d Artikel von %1$s</string>
+    <string name="button_share">Share <b>now</b></string>
+    <string name="old_key">Alt</string>
+    <plurals name="listings">
+        <item quantity="one">%d Angebot</item>
+        <item quantity="other">%d Angebote</item>
+    </plurals>
+</resources>
+"#,
+        );
+        write(
+            "app/src/main/res/values-ru/strings.xml",
+            r#"<resources>
+    <string name="feed_title">Еда рядом</string>
+    <string name="feed_count">%s поделился %s</string>
+    <string name="button_share"><![CDATA[Поделиться <b>сейчас</b>]]></string>
+    <plurals name="listings">
+        <item quantity="one">%d объявление</item>
+        <item quantity="other">%d объявлений</item>
+    </plurals>
+</resources>
+"#,
+        );
+        write("app/src/main/res/values-night/colors.xml", "<resources><color name=\"bg\">#000</color></resources>");
+
+        let report = lint(dir.path(), "en").unwrap();
+        assert_eq!(report.modules, 1);
+        assert_eq!(report.locales, ["de", "en", "ru"]);
+
+        let counts = report.counts();
+        assert_eq!(
+            counts["de"],
+            LocaleCounts { stale: 1, untranslated: 1, ..Default::default() }
+        );
+        assert_eq!(
+            counts["ru"],
+            LocaleCounts { placeholders: 1, plurals: 1, ..Default::default() }
+        );
+        assert_eq!(report.error_count(), 1);
+        let mismatch = report.issues.iter().find(|i| i.kind.is_error()).unwrap();
+        assert_eq!((mismatch.module.as_str(), mismatch.key.as_str()), ("app", "feed_count"));
+        assert_eq!(
+            mismatch.kind,
+            IssueKind::PlaceholderMismatch { expected: "%s %d".to_string(), found: "%s %s".to_string() }
+        );
+        let plural = report.issues.iter().find(|i| matches!(i.kind, IssueKind::MissingQuantities { .. })).unwrap();
+        assert_eq!(plural.kind, IssueKind::MissingQuantities { missing: "few many".to_string() });
+        assert_eq!(plural.line, Some(5));
+    }
+
+    #[test]
+    fn test_hardcoded_strings() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |path: &str, content: &str| {
+            let path = dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write("feature/listing/src/main/res/values/strings.xml", "<resources/>");
+        write(
+            "feature/listing/src/main/res/layout/item_listing.xml",
+            "<LinearLayout>\n    <TextView android:text=\"@string/title\" />\n    <Button\n        android:text=\"Claim\" android:contentDescription=\"123\" />\n</LinearLayout>\n",
+        );
+        write(
+            "feature/listing/src/main/java/ListingCard.kt",
+            "@Composable\nfun ListingCard(name: String) {\n    Text(\"Pick up today\")\n    Text(text = \"$name\")\n    Icon(Icons.Default.Share, contentDescription = \"Share listing\")\n    Text(stringResource(R.string.title))\n}\n",
+        );
+        write("feature/listing/src/test/java/ListingTest.kt", "Text(\"Test only\")\n");
+
+        let report = lint(dir.path(), "en").unwrap();
+        let hardcoded: Vec<(&str, Option<usize>)> = report
+            .issues
+            .iter()
+            .filter(|i| i.kind == IssueKind::Hardcoded)
+            .map(|i| (i.key.as_str(), i.line))
+            .collect();
+        assert_eq!(hardcoded, [("Claim", Some(3)), ("Pick up today", Some(3)), ("Share listing", Some(5))]);
+        assert!(report.issues.iter().all(|i| i.module == "feature/listing"));
+        assert_eq!(report.counts()["en"].hardcoded, 3);
+    }
+}
@@ -0,0 +1,422 @@
+//! Logcat streaming and crash capture
+//!
+//! Streams `adb logcat -v threadtime`, keeps the lines logged by the app's
+//! processes (following restarts through ActivityManager's `Start proc`
+//! lines), and picks Java crashes, native crashes and ANRs out of the stream
+//! as [`CrashReport`]s that can be saved for bug reports.
+
+use foodshare_cli::textln;
+use foodshare_core::error::{Error, Result};
+use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// App lines kept before a crash for context
+pub const CONTEXT_LINES: usize = 50;
+
+/// Log priority
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum LogLevel {
+    /// `V`
+    Verbose,
+    /// `D`
+    Debug,
+    /// `I`
+    Info,
+    /// `W`
+    Warn,
+    /// `E`
+    Error,
+    /// `F`
+    Fatal,
+}
+
+impl LogLevel {
+    /// Parse a logcat priority letter or level name
+    pub fn parse(level: &str) -> Option<Self> {
+        match level.to_ascii_lowercase().as_str() {
+            "v" | "verbose" => Some(Self::Verbose),
+            "d" | "debug" => Some(Self::Debug),
+            "i" | "info" => Some(Self::Info),
+            "w" | "warn" | "warning" => Some(Self::Warn),
+            "e" | "error" => Some(Self::Error),
+            "f" | "fatal" | "a" | "assert" => Some(Self::Fatal),
+            _ => None,
+        }
+    }
+
+    fn letter(self) -> char {
+        match self {
+            Self::Verbose => 'V',
+            Self::Debug => 'D',
+            Self::Info => 'I',
+            Self::Warn => 'W',
+            Self::Error => 'E',
+            Self::Fatal => 'F',
+        }
+    }
+}
+
+/// A parsed `threadtime` logcat line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogLine {
+    /// Date and time (`01-15 10:23:45.123`)
+    pub time: String,
+    /// Process ID
+    pub pid: u32,
+    /// Thread ID
+    pub tid: u32,
+    /// Priority
+    pub level: LogLevel,
+    /// Tag
+    pub tag: String,
+    /// Message
+    pub message: String,
+}
+
+impl LogLine {
+    /// Parse `MM-DD HH:MM:SS.mmm  PID  TID L Tag: message`
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.split_whitespace();
+        let date = fields.next()?;
+        let time = fields.next()?;
+        let pid = fields.next()?.parse().ok()?;
+        let tid = fields.next()?.parse().ok()?;
+        let level = LogLevel::parse(fields.next()?)?;
+
+        // The tag may be padded with spaces before the colon
+        let rest = line.split_once(&format!(" {} ", level.letter()))?.1;
+        let (tag, message) = rest.split_once(':')?;
+        Some(Self {
+            time: format!("{} {}", date, time),
+            pid,
+            tid,
+            level,
+            tag: tag.trim().to_string(),
+            message: message.strip_prefix(' ').unwrap_or(message).to_string(),
+        })
+    }
+}
+
+/// Keeps track of the app's process IDs
+#[derive(Debug)]
+pub struct AppFilter {
+    package: String,
+    pids: HashSet<u32>,
+    /// system_server PID while it logs the details of an ANR in the app
+    anr_pid: Option<u32>,
+}
+
+impl AppFilter {
+    /// Filter for a package, with the PIDs it is already running as
+    pub fn new(package: &str, pids: impl IntoIterator<Item = u32>) -> Self {
+        Self {
+            package: package.to_string(),
+            pids: pids.into_iter().collect(),
+            anr_pid: None,
+        }
+    }
+
+    /// Check if a line belongs to the app, learning new PIDs from
+    /// ActivityManager's `Start proc <pid>:<package>/...` lines
+    pub fn matches(&mut self, line: &LogLine) -> bool {
+        if line.tag == "ActivityManager" {
+            if let Some(pid) = started_pid(&line.message, &self.package) {
+                self.pids.insert(pid);
+                return true;
+            }
+            if line.message.starts_with("ANR in") && line.message.contains(&self.package) {
+                self.anr_pid = Some(line.pid);
+                return true;
+            }
+            if self.anr_pid == Some(line.pid) {
+                return true;
+            }
+        }
+        self.anr_pid = None;
+        self.pids.contains(&line.pid)
+    }
+}
+
+fn started_pid(message: &str, package: &str) -> Option<u32> {
+    let rest = message.strip_prefix("Start proc ")?;
+    let (pid, process) = rest.split_once(':')?;
+    let process = process.split(['/', ' ']).next()?;
+    // Secondary processes are named `<package>:<name>`
+    if process == package || process.starts_with(&format!("{}:", package)) {
+        pid.parse().ok()
+    } else {
+        None
+    }
+}
+
+/// Kind of crash
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CrashKind {
+    /// Uncaught Java/Kotlin exception
+    Exception,
+    /// Native signal (SIGSEGV, SIGABRT)
+    Native,
+    /// Application Not Responding
+    Anr,
+}
+
+impl CrashKind {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Exception => "CRASH",
+            Self::Native => "NATIVE CRASH",
+            Self::Anr => "ANR",
+        }
+    }
+}
+
+/// A crash or ANR captured from logcat
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    /// Kind of crash
+    pub kind: CrashKind,
+    /// Logcat time of the first crash line
+    pub time: String,
+    /// Crashed process ID
+    pub pid: u32,
+    /// Summary (`java.lang.IllegalStateException: ...`, `Fatal signal 11`)
+    pub summary: String,
+    /// Crash lines (stack trace, ANR details)
+    pub trace: Vec<String>,
+    /// App log lines leading up to the crash
+    pub context: Vec<String>,
+    /// Device the crash happened on (`Pixel_8, API 34`)
+    pub device: Option<String>,
+}
+
+impl CrashReport {
+    /// Plain-text report for attaching to issues
+    pub fn to_text(&self) -> String {
+        let mut out = format!("{} at {} (pid {})\n", self.kind.label(), self.time, self.pid);
+        if let Some(device) = &self.device {
+            out.push_str(&format!("Device: {}\n", device));
+        }
+        out.push_str(&format!("\n{}\n", self.summary));
+        for line in &self.trace {
+            out.push_str(line);
+            out.push('\n');
+        }
+        if !self.context.is_empty() {
+            out.push_str("\nRecent app logs:\n");
+            for line in &self.context {
+                out.push_str(line);
+                out.push('\n');
+            }
+        }
+        out
+    }
+
+    /// Write the report as JSON (`.json` paths) or plain text
+    pub fn write(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = if path.extension().is_some_and(|ext| ext == "json") {
+            serde_json::to_string_pretty(self)?
+        } else {
+            self.to_text()
+        };
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+}
+
+/// Collects crashes from a stream of app lines
+#[derive(Debug, Default)]
+pub struct CrashDetector {
+    context: VecDeque<String>,
+    current: Option<(CrashReport, String)>,
+}
+
+impl CrashDetector {
+    /// Feed an app line; returns a crash once its last line has been seen
+    pub fn feed(&mut self, line: &LogLine, raw: &str) -> Option<CrashReport> {
+        if let Some((report, tag)) = &mut self.current {
+            if line.pid == report.pid && line.tag == *tag {
+                if report.summary.is_empty() && report.kind == CrashKind::Exception {
+                    // First line after `FATAL EXCEPTION: main` / `Process: ...`
+                    if !line.message.starts_with("Process:") {
+                        report.summary = line.message.clone();
+                    }
+                } else {
+                    report.trace.push(line.message.clone());
+                }
+                return None;
+            }
+        }
+        let finished = self.finish();
+
+        if let Some((kind, tag)) = crash_start(line) {
+            let summary = match kind {
+                CrashKind::Exception => String::new(),
+                _ => line.message.clone(),
+            };
+            self.current = Some((
+                CrashReport {
+                    kind,
+                    time: line.time.clone(),
+                    pid: line.pid,
+                    summary,
+                    trace: Vec::new(),
+                    context: self.context.iter().cloned().collect(),
+                    device: None,
+                },
+                tag.to_string(),
+            ));
+        } else {
+            self.context.push_back(raw.to_string());
+            if self.context.len() > CONTEXT_LINES {
+                self.context.pop_front();
+            }
+        }
+        finished
+    }
+
+    /// Whether the last line fed was part of a crash
+    pub fn in_crash(&self) -> bool {
+        self.current.is_some()
+    }
+
+    /// End the stream, returning a crash still being collected
+    pub fn finish(&mut self) -> Option<CrashReport> {
+        self.current.take().map(|(report, _)| report)
+    }
+}
+
+/// Crash kind and the tag its following lines are logged under
+fn crash_start(line: &LogLine) -> Option<(CrashKind, &'static str)> {
+    if line.tag == "AndroidRuntime" && line.message.starts_with("FATAL EXCEPTION") {
+        Some((CrashKind::Exception, "AndroidRuntime"))
+    } else if line.tag == "libc" && line.message.starts_with("Fatal signal") {
+        Some((CrashKind::Native, "libc"))
+    } else if line.tag == "ActivityManager" && line.message.starts_with("ANR in") {
+        Some((CrashKind::Anr, "ActivityManager"))
+    } else {
+        None
+    }
+}
+
+/// Run `adb logcat -v threadtime`, calling `on_line` for every line
+///
+/// With `dump`, prints the current buffer and returns instead of following.
+pub fn stream(serial: &str, dump: bool, mut on_line: impl FnMut(&str)) -> Result<i32> {
+    let mut args = vec!["-s", serial, "logcat", "-v", "threadtime"];
+    if dump {
+        args.push("-d");
+    }
+    let mut child = Command::new("adb")
+        .args(&args)
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| Error::process(format!("Failed to start adb logcat: {}", e)))?;
+
+    if let Some(stdout) = child.stdout.take() {
+        for line in BufReader::new(stdout).lines() {
+            on_line(&line?);
+        }
+    }
+    Ok(child.wait()?.code().unwrap_or(1))
+}
+
+/// Print a line with its priority colored; crash lines stand out
+pub fn print_line(line: &LogLine, raw: &str, crashing: bool) {
+    if crashing {
+        textln!("{}", raw.red().bold());
+        return;
+    }
+    match line.level {
+        LogLevel::Verbose => textln!("{}", raw.dimmed()),
+        LogLevel::Debug => textln!("{}", raw.blue()),
+        LogLevel::Info => textln!("{}", raw),
+        LogLevel::Warn => textln!("{}", raw.yellow()),
+        LogLevel::Error | LogLevel::Fatal => textln!("{}", raw.red()),
+    }
+}
+
+/// Print a banner for a captured crash
+pub fn print_crash(report: &CrashReport) {
+    textln!();
+    textln!("{} {}", format!("━━ {} ━━", report.kind.label()).red().bold(), report.summary.bold());
+    if let Some(frame) = report.trace.iter().find(|l| l.trim_start().starts_with("at ") || l.contains('#')) {
+        textln!("   {}", frame.trim().dimmed());
+    }
+    textln!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(raw: &str) -> LogLine {
+        LogLine::parse(raw).unwrap_or_else(|| panic!("unparsed: {}", raw))
+    }
+
+    #[test]
+    fn test_parse_and_filter() {
+        let line = parse("01-15 10:23:45.123  4321  4321 D FeedViewModel: Loaded 12 listings");
+        assert_eq!((line.pid, line.level, line.tag.as_str()), (4321, LogLevel::Debug, "FeedViewModel"));
+        assert_eq!(line.message, "Loaded 12 listings");
+        assert_eq!(parse("01-15 10:23:45.123  4321  4330 W OkHttp  : timeout: 10s").message, "timeout: 10s");
+        assert!(LogLine::parse("--------- beginning of main").is_none());
+        assert!(LogLevel::parse("warn").unwrap() > LogLevel::Info);
+
+        let mut filter = AppFilter::new("com.flutterflow.foodshare", [4321]);
+        assert!(filter.matches(&line));
+        let other = parse("01-15 10:23:46.000  1000  1000 I SystemUI: clock tick");
+        assert!(!filter.matches(&other));
+        let start = parse(
+            "01-15 10:24:00.000   600   640 I ActivityManager: Start proc 5555:com.flutterflow.foodshare/u0a190 for pre-top-activity",
+        );
+        assert!(filter.matches(&start));
+        assert!(filter.matches(&parse("01-15 10:24:01.000  5555  5555 I Foodshare: restarted")));
+        assert!(filter.matches(&parse(
+            "01-15 10:30:00.000   600   640 E ActivityManager: ANR in com.flutterflow.foodshare (com.flutterflow.foodshare/.MainActivity)"
+        )));
+        assert!(filter.matches(&parse("01-15 10:30:00.000   600   640 E ActivityManager: Reason: Input dispatching timed out")));
+        assert!(!filter.matches(&other));
+        assert!(!filter.matches(&parse("01-15 10:30:01.000   600   640 I ActivityManager: Killing 1234:com.other")));
+    }
+
+    #[test]
+    fn test_crash_detection() {
+        let lines = [
+            "01-15 10:23:45.123  4321  4321 I FeedViewModel: Opening listing 42",
+            "01-15 10:23:45.200  4321  4321 E AndroidRuntime: FATAL EXCEPTION: main",
+            "01-15 10:23:45.200  4321  4321 E AndroidRuntime: Process: com.flutterflow.foodshare, PID: 4321",
+            "01-15 10:23:45.200  4321  4321 E AndroidRuntime: java.lang.IllegalStateException: listing missing",
+            "01-15 10:23:45.200  4321  4321 E AndroidRuntime: \tat com.foodshare.feed.FeedViewModel.open(FeedViewModel.kt:88)",
+            "01-15 10:23:45.300  4321  4321 I Process: Sending signal. PID: 4321 SIG: 9",
+            "01-15 10:30:00.000   600   640 E ActivityManager: ANR in com.flutterflow.foodshare (com.flutterflow.foodshare/.MainActivity)",
+            "01-15 10:30:00.000   600   640 E ActivityManager: Reason: Input dispatching timed out",
+        ];
+        let mut detector = CrashDetector::default();
+        let mut crashes: Vec<CrashReport> = lines.iter().filter_map(|raw| detector.feed(&parse(raw), raw)).collect();
+        crashes.extend(detector.finish());
+
+        assert_eq!(crashes.len(), 2);
+        let crash = &crashes[0];
+        assert_eq!(crash.kind, CrashKind::Exception);
+        assert_eq!(crash.summary, "java.lang.IllegalStateException: listing missing");
+        assert_eq!(crash.trace.len(), 1);
+        assert_eq!(crash.context, [lines[0]]);
+        assert!(crash.to_text().starts_with("CRASH at 01-15 10:23:45.200 (pid 4321)"));
+
+        assert_eq!(crashes[1].kind, CrashKind::Anr);
+        assert_eq!(crashes[1].trace, ["Reason: Input dispatching timed out"]);
+
+        let dir = tempfile::tempdir().unwrap();
+        crash.write(&dir.path().join("crash.json")).unwrap();
+        let json = std::fs::read_to_string(dir.path().join("crash.json")).unwrap();
+        assert!(json.contains("\"kind\": \"exception\""));
+    }
+}
@@ -0,0 +1,378 @@
+//! AndroidManifest.xml policy checks
+//!
+//! Reads the merged manifest Gradle writes for a variant, so permissions and
+//! components pulled in by libraries are checked too, and flags:
+//!
+//! - permissions Google Play restricts (`QUERY_ALL_PACKAGES`, SMS, call log)
+//! - exported components without a permission guard
+//! - cleartext HTTP traffic
+//! - `debuggable` and `testOnly` left on in release builds
+//!
+//! The launcher activity and deep-link activities are meant to be public and
+//! are not reported as unguarded.
+
+use crate::xml::{attr, line_at, tags};
+use foodshare_core::error::{Error, Result};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Source manifest of the app module, relative to the project root
+pub const SOURCE_MANIFEST: &str = "app/src/main/AndroidManifest.xml";
+
+/// Directories Gradle writes merged manifests to (AGP 8, then AGP 7)
+const MERGED_MANIFEST_DIRS: &[&str] = &[
+    "app/build/intermediates/merged_manifests",
+    "app/build/intermediates/merged_manifest",
+];
+
+/// Permissions Google Play only allows with a declared core use case
+pub const RESTRICTED_PERMISSIONS: &[&str] = &[
+    "android.permission.QUERY_ALL_PACKAGES",
+    "android.permission.MANAGE_EXTERNAL_STORAGE",
+    "android.permission.REQUEST_INSTALL_PACKAGES",
+    "android.permission.ACCESS_BACKGROUND_LOCATION",
+    "android.permission.READ_SMS",
+    "android.permission.SEND_SMS",
+    "android.permission.RECEIVE_SMS",
+    "android.permission.RECEIVE_MMS",
+    "android.permission.READ_CALL_LOG",
+    "android.permission.WRITE_CALL_LOG",
+    "android.permission.PROCESS_OUTGOING_CALLS",
+    "android.permission.BIND_ACCESSIBILITY_SERVICE",
+];
+
+/// Kind of app component
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComponentKind {
+    /// `<activity>` or `<activity-alias>`
+    Activity,
+    /// `<service>`
+    Service,
+    /// `<receiver>`
+    Receiver,
+    /// `<provider>`
+    Provider,
+}
+
+impl ComponentKind {
+    fn from_tag(name: &str) -> Option<Self> {
+        match name {
+            "activity" | "activity-alias" => Some(Self::Activity),
+            "service" => Some(Self::Service),
+            "receiver" => Some(Self::Receiver),
+            "provider" => Some(Self::Provider),
+            _ => None,
+        }
+    }
+
+    /// Element name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Activity => "activity",
+            Self::Service => "service",
+            Self::Receiver => "receiver",
+            Self::Provider => "provider",
+        }
+    }
+}
+
+/// An activity, service, receiver or provider
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Component {
+    /// Kind
+    pub kind: ComponentKind,
+    /// Class name (`android:name`)
+    pub name: String,
+    /// Explicit `android:exported`, if set
+    pub exported: Option<bool>,
+    /// Permissions guarding it (`permission`, `readPermission`, `writePermission`)
+    pub permissions: Vec<String>,
+    /// Intent filter actions
+    pub actions: Vec<String>,
+    /// Intent filter categories
+    pub categories: Vec<String>,
+    /// 1-based line of the element
+    pub line: usize,
+}
+
+impl Component {
+    /// Whether other apps can start or bind it
+    ///
+    /// Without an explicit `android:exported`, components with an intent
+    /// filter are exported (providers never are by default).
+    pub fn is_exported(&self) -> bool {
+        self.exported.unwrap_or(!self.actions.is_empty() && self.kind != ComponentKind::Provider)
+    }
+
+    /// Whether it is the launcher activity or handles browsable deep links
+    pub fn is_public_entry_point(&self) -> bool {
+        let has = |category: &str| self.categories.iter().any(|c| c == category);
+        self.kind == ComponentKind::Activity
+            && (has("android.intent.category.LAUNCHER") || has("android.intent.category.BROWSABLE"))
+    }
+}
+
+/// The parts of a manifest the policy checks read
+#[derive(Debug, Clone, Default)]
+pub struct Manifest {
+    /// `package` attribute of `<manifest>`
+    pub package: Option<String>,
+    /// Requested permissions with their line
+    pub permissions: Vec<(String, usize)>,
+    /// `android:debuggable` on `<application>`
+    pub debuggable: bool,
+    /// `android:testOnly` on `<application>`
+    pub test_only: bool,
+    /// `android:usesCleartextTraffic` on `<application>`, if set
+    pub uses_cleartext_traffic: Option<bool>,
+    /// 1-based line of `<application>`
+    pub application_line: usize,
+    /// Components in document order
+    pub components: Vec<Component>,
+}
+
+impl Manifest {
+    /// Load a manifest file
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Err(Error::file_not_found(path));
+        }
+        let content = std::fs::read_to_string(path)?;
+        Self::parse(&content).map_err(|e| Error::validation(format!("Invalid manifest {}: {}", path.display(), e)))
+    }
+
+    /// Parse manifest XML
+    pub fn parse(content: &str) -> std::result::Result<Self, String> {
+        let mut manifest = Self::default();
+        let mut component: Option<Component> = None;
+
+        for tag in tags(content)? {
+            if tag.close {
+                if ComponentKind::from_tag(tag.name).is_some() {
+                    manifest.components.extend(component.take());
+                }
+                continue;
+            }
+
+            let attrs = tag.attributes(content);
+            let android = |name: &str| attr(&attrs, &format!("android:{}", name));
+            let line = line_at(content, tag.start);
+            match tag.name {
+                "manifest" => manifest.package = attr(&attrs, "package").map(str::to_string),
+                "uses-permission" | "uses-permission-sdk-23" => {
+                    if let Some(name) = android("name") {
+                        manifest.permissions.push((name.to_string(), line));
+                    }
+                }
+                "application" => {
+                    manifest.application_line = line;
+                    manifest.debuggable = android("debuggable") == Some("true");
+                    manifest.test_only = android("testOnly") == Some("true");
+                    manifest.uses_cleartext_traffic = android("usesCleartextTraffic").map(|v| v == "true");
+                }
+                "action" | "category" => {
+                    if let (Some(component), Some(name)) = (component.as_mut(), android("name")) {
+                        let list = if tag.name == "action" { &mut component.actions } else { &mut component.categories };
+                        list.push(name.to_string());
+                    }
+                }
+                name => {
+                    if let Some(kind) = ComponentKind::from_tag(name) {
+                        let parsed = Component {
+                            kind,
+                            name: android("name").or_else(|| android("targetActivity")).unwrap_or_default().to_string(),
+                            exported: android("exported").map(|v| v == "true"),
+                            permissions: ["permission", "readPermission", "writePermission"]
+                                .iter()
+                                .filter_map(|key| android(key).map(str::to_string))
+                                .collect(),
+                            actions: Vec::new(),
+                            categories: Vec::new(),
+                            line,
+                        };
+                        if tag.empty {
+                            manifest.components.push(parsed);
+                        } else {
+                            component = Some(parsed);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(manifest)
+    }
+}
+
+/// Issue severity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Worth a look, doesn't fail the check
+    Warning,
+    /// Policy violation
+    Error,
+}
+
+/// A policy issue in a manifest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestIssue {
+    /// Check that found it (`restricted-permission`)
+    pub check: &'static str,
+    /// Severity
+    pub severity: Severity,
+    /// 1-based line in the manifest
+    pub line: usize,
+    /// Message
+    pub message: String,
+}
+
+/// Check a manifest against the policy
+///
+/// `release` enables the debug attribute checks and makes cleartext traffic
+/// an error. Permissions in `allowed` are not reported as restricted.
+pub fn check(manifest: &Manifest, release: bool, allowed: &[String]) -> Vec<ManifestIssue> {
+    let mut issues = Vec::new();
+
+    for (permission, line) in &manifest.permissions {
+        let short = permission.rsplit('.').next().unwrap_or(permission);
+        if RESTRICTED_PERMISSIONS.contains(&permission.as_str())
+            && !allowed.iter().any(|a| a == permission || a == short)
+        {
+            issues.push(ManifestIssue {
+                check: "restricted-permission",
+                severity: Severity::Error,
+                line: *line,
+                message: format!("{} is restricted by Google Play policy and needs a declared use case", short),
+            });
+        }
+    }
+
+    for component in &manifest.components {
+        if component.is_exported() && component.permissions.is_empty() && !component.is_public_entry_point() {
+            issues.push(ManifestIssue {
+                check: "unguarded-export",
+                severity: Severity::Warning,
+                line: component.line,
+                message: format!(
+                    "Exported {} {} has no permission; set android:exported=\"false\" or add android:permission",
+                    component.kind.as_str(),
+                    component.name
+                ),
+            });
+        }
+    }
+
+    if manifest.uses_cleartext_traffic == Some(true) {
+        issues.push(ManifestIssue {
+            check: "cleartext-traffic",
+            severity: if release { Severity::Error } else { Severity::Warning },
+            line: manifest.application_line,
+            message: "usesCleartextTraffic allows unencrypted HTTP; scope exceptions in a network security config"
+                .to_string(),
+        });
+    }
+
+    if release {
+        for (enabled, attribute) in [(manifest.debuggable, "debuggable"), (manifest.test_only, "testOnly")] {
+            if enabled {
+                issues.push(ManifestIssue {
+                    check: "debug-attribute",
+                    severity: Severity::Error,
+                    line: manifest.application_line,
+                    message: format!("android:{}=\"true\" in a release manifest", attribute),
+                });
+            }
+        }
+    }
+
+    issues.sort_by_key(|issue| (std::cmp::Reverse(issue.severity), issue.line));
+    issues
+}
+
+/// Find the merged manifest Gradle wrote for a variant (`release`, `debug`)
+pub fn find_merged_manifest(project_dir: &Path, variant: &str) -> Result<PathBuf> {
+    MERGED_MANIFEST_DIRS
+        .iter()
+        .map(|dir| project_dir.join(dir).join(variant))
+        .filter(|dir| dir.is_dir())
+        .flat_map(|dir| WalkDir::new(dir).sort_by_file_name())
+        .filter_map(|entry| entry.ok())
+        .find(|entry| entry.file_name() == "AndroidManifest.xml")
+        .map(|entry| entry.into_path())
+        .ok_or_else(|| Error::file_not_found(project_dir.join(MERGED_MANIFEST_DIRS[0]).join(variant)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<manifest xmlns:android="http://schemas.android.com/apk/res/android"
+    package="com.flutterflow.foodshare">
+
+    <uses-permission android:name="android.permission.INTERNET" />
+    <uses-permission android:name="android.permission.QUERY_ALL_PACKAGES" />
+    <uses-permission android:name="android.permission.ACCESS_BACKGROUND_LOCATION" />
+
+    <application
+        android:debuggable="true"
+        android:usesCleartextTraffic="true">
+        <!-- <activity android:name=".Disabled" android:exported="true" /> -->
+        <activity android:name=".MainActivity" android:exported="true">
+            <intent-filter>
+                <action android:name="android.intent.action.MAIN" />
+                <category android:name="android.intent.category.LAUNCHER" />
+            </intent-filter>
+        </activity>
+        <service android:name=".sync.SyncService" android:exported="true" />
+        <receiver android:name=".PushReceiver">
+            <intent-filter>
+                <action android:name="com.google.android.c2dm.intent.RECEIVE" />
+            </intent-filter>
+        </receiver>
+        <provider
+            android:name=".ShareProvider"
+            android:exported="true"
+            android:readPermission="com.flutterflow.foodshare.READ" />
+    </application>
+</manifest>
+"#;
+
+    #[test]
+    fn test_parse_manifest() {
+        let manifest = Manifest::parse(MANIFEST).unwrap();
+        assert_eq!(manifest.package.as_deref(), Some("com.flutterflow.foodshare"));
+        assert_eq!(manifest.permissions.len(), 3);
+        assert_eq!(manifest.permissions[1], ("android.permission.QUERY_ALL_PACKAGES".to_string(), 6));
+        assert!(manifest.debuggable && !manifest.test_only);
+        assert_eq!(manifest.uses_cleartext_traffic, Some(true));
+
+        let names: Vec<&str> = manifest.components.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, [".MainActivity", ".sync.SyncService", ".PushReceiver", ".ShareProvider"]);
+        assert!(manifest.components[0].is_public_entry_point());
+        // Exported implicitly through its intent filter
+        assert!(manifest.components[2].is_exported());
+        assert_eq!(manifest.components[3].permissions, ["com.flutterflow.foodshare.READ"]);
+    }
+
+    #[test]
+    fn test_check_policy() {
+        let manifest = Manifest::parse(MANIFEST).unwrap();
+        let issues = check(&manifest, true, &["ACCESS_BACKGROUND_LOCATION".to_string()]);
+        let checks: Vec<(&str, Severity)> = issues.iter().map(|i| (i.check, i.severity)).collect();
+        assert_eq!(
+            checks,
+            [
+                ("restricted-permission", Severity::Error),
+                ("cleartext-traffic", Severity::Error),
+                ("debug-attribute", Severity::Error),
+                ("unguarded-export", Severity::Warning),
+                ("unguarded-export", Severity::Warning),
+            ]
+        );
+        assert!(issues[3].message.contains(".sync.SyncService"));
+
+        let debug = check(&manifest, false, &[]);
+        assert!(!debug.iter().any(|i| i.check == "debug-attribute"));
+        assert!(debug.iter().any(|i| i.check == "cleartext-traffic" && i.severity == Severity::Warning));
+    }
+}
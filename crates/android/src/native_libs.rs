@@ -0,0 +1,300 @@
+//! Per-ABI native library packaging checks
+//!
+//! Reads the `lib/<abi>/*.so` entries of a built APK or App Bundle and checks
+//! that every shipped ABI carries FoodshareCore and the Swift runtime, that
+//! all ABIs ship the same set of libraries, and that they were built by the
+//! same Swift toolchain. A library copied into `jniLibs` for one ABI only, or
+//! an arm64 runtime left over from an older toolchain, loads fine on one
+//! device and crashes on the next, so CI fails instead.
+//!
+//! The toolchain is read from the `swift-<version>-RELEASE` (or snapshot)
+//! tag the Swift runtime embeds; libraries without one are only checked for
+//! presence.
+
+use crate::zip;
+use foodshare_cli::textln;
+use foodshare_core::error::{Error, Result};
+use once_cell::sync::Lazy;
+use regex::bytes::Regex;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// Libraries every shipped ABI must contain
+pub const REQUIRED_LIBS: &[&str] = &[
+    "libFoodshareCore.so",
+    "libswiftCore.so",
+    "libswift_Concurrency.so",
+    "libswift_StringProcessing.so",
+    "libswiftDispatch.so",
+    "libdispatch.so",
+    "libBlocksRuntime.so",
+    "libc++_shared.so",
+];
+
+/// ABIs the app is built for, which must never diverge
+pub const PAIRED_ABIS: &[&str] = &["arm64-v8a", "x86_64"];
+
+static SWIFT_TAG: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"swift-(?:\d+(?:\.\d+)*-RELEASE|(?:\d+(?:\.\d+)*-)?DEVELOPMENT-SNAPSHOT-\d{4}-\d{2}-\d{2}-a)")
+        .expect("valid regex")
+});
+
+/// A native library inside the package
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NativeLib {
+    /// Module (`base` for APKs and the base module of a bundle)
+    pub module: String,
+    /// ABI directory (`arm64-v8a`)
+    pub abi: String,
+    /// File name (`libswiftCore.so`)
+    pub name: String,
+    /// Uncompressed size
+    pub bytes: u64,
+    /// Swift toolchain tag embedded in the library, if any
+    pub swift_tag: Option<String>,
+}
+
+/// Native libraries of an APK or App Bundle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NativeLibReport {
+    /// File name (`app-release.aab`)
+    pub package: String,
+    /// Libraries, by ABI then name
+    pub libs: Vec<NativeLib>,
+}
+
+impl NativeLibReport {
+    /// Read the native libraries of an `.apk` or `.aab` file
+    pub fn from_file(path: &Path) -> Result<Self> {
+        if !path.is_file() {
+            return Err(Error::file_not_found(path));
+        }
+        let data = std::fs::read(path)?;
+        let invalid = |e: String| Error::validation(format!("{}: {}", path.display(), e));
+        let bundle = path.extension().is_some_and(|ext| ext == "aab");
+
+        let mut libs = Vec::new();
+        for entry in zip::read_directory(&data).map_err(invalid)? {
+            let Some((module, abi, name)) = lib_path(&entry.path, bundle) else {
+                continue;
+            };
+            let contents = zip::read_entry(&data, &entry).map_err(invalid)?;
+            libs.push(NativeLib {
+                module: module.to_string(),
+                abi: abi.to_string(),
+                name: name.to_string(),
+                bytes: entry.uncompressed,
+                swift_tag: swift_tag(&contents),
+            });
+        }
+        let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        Ok(Self::from_libs(name, libs))
+    }
+
+    /// Build a report from the package's libraries
+    pub fn from_libs(package: String, mut libs: Vec<NativeLib>) -> Self {
+        libs.sort_by(|a, b| (&a.abi, &a.name, &a.module).cmp(&(&b.abi, &b.name, &b.module)));
+        Self { package, libs }
+    }
+
+    /// Libraries of each ABI by name; a library in several modules counts once
+    pub fn abis(&self) -> BTreeMap<&str, BTreeMap<&str, &NativeLib>> {
+        let mut abis: BTreeMap<&str, BTreeMap<&str, &NativeLib>> = BTreeMap::new();
+        for lib in &self.libs {
+            abis.entry(lib.abi.as_str()).or_default().entry(lib.name.as_str()).or_insert(lib);
+        }
+        abis
+    }
+
+    /// Packaging problems, given the libraries every ABI must contain
+    pub fn problems(&self, required: &[String]) -> Vec<String> {
+        let abis = self.abis();
+        let mut problems = Vec::new();
+        if abis.is_empty() {
+            if !required.is_empty() {
+                problems.push("Package contains no native libraries".to_string());
+            }
+            return problems;
+        }
+
+        for abi in PAIRED_ABIS.iter().filter(|abi| !abis.contains_key(**abi)) {
+            let shipped: Vec<&str> = abis.keys().copied().collect();
+            problems.push(format!("{} is not shipped (found {})", abi, shipped.join(", ")));
+        }
+
+        for (abi, libs) in &abis {
+            for name in required.iter().filter(|name| !libs.contains_key(name.as_str())) {
+                problems.push(format!("{} is missing {}", abi, name));
+            }
+        }
+
+        // Libraries some ABIs ship and others don't, beyond the required ones
+        let all: BTreeSet<&str> = abis.values().flat_map(|libs| libs.keys().copied()).collect();
+        for name in all.iter().copied().filter(|name| !required.iter().any(|r| r == name)) {
+            let (with, without): (Vec<&str>, Vec<&str>) =
+                abis.keys().copied().partition(|abi| abis[abi].contains_key(name));
+            if !without.is_empty() {
+                problems.push(format!(
+                    "{} is shipped for {} but not for {}",
+                    name,
+                    with.join(", "),
+                    without.join(", ")
+                ));
+            }
+        }
+
+        // The same library must come from the same toolchain on every ABI
+        for name in &all {
+            let tags: BTreeMap<&str, &str> = abis
+                .iter()
+                .filter_map(|(abi, libs)| Some((*abi, libs.get(name)?.swift_tag.as_deref()?)))
+                .collect();
+            if tags.values().collect::<BTreeSet<_>>().len() > 1 {
+                problems.push(format!("{} versions differ across ABIs: {}", name, describe(&tags)));
+            }
+        }
+
+        // And every ABI's libraries from a single toolchain
+        for (abi, libs) in &abis {
+            let tags: BTreeMap<&str, &str> = libs
+                .iter()
+                .filter_map(|(name, lib)| Some((*name, lib.swift_tag.as_deref()?)))
+                .collect();
+            if tags.values().collect::<BTreeSet<_>>().len() > 1 {
+                problems.push(format!("{} mixes Swift toolchains: {}", abi, describe(&tags)));
+            }
+        }
+
+        problems
+    }
+
+    /// Print each ABI's libraries
+    pub fn print(&self) {
+        use owo_colors::OwoColorize;
+
+        textln!("{}", format!("{} native libraries", self.package).bold());
+        for (abi, libs) in self.abis() {
+            textln!();
+            textln!("{}", format!("{} ({} libraries)", abi, libs.len()).bold());
+            for lib in libs.values() {
+                let tag = lib.swift_tag.as_deref().map(|tag| format!(" {}", tag.dimmed())).unwrap_or_default();
+                textln!(
+                    "  {:>10}  {}{}",
+                    foodshare_cli::output::format_size_decimal(lib.bytes).yellow(),
+                    lib.name,
+                    tag
+                );
+            }
+        }
+    }
+}
+
+/// `key tag, key tag` for a problem message
+fn describe(tags: &BTreeMap<&str, &str>) -> String {
+    tags.iter().map(|(key, tag)| format!("{} {}", key, tag)).collect::<Vec<_>>().join(", ")
+}
+
+/// Module, ABI and file name of a `lib/<abi>/<name>.so` package path
+fn lib_path(path: &str, bundle: bool) -> Option<(&str, &str, &str)> {
+    let (module, rest) = if bundle { path.split_once('/')? } else { ("base", path) };
+    let (abi, name) = rest.strip_prefix("lib/")?.split_once('/')?;
+    (name.ends_with(".so") && !name.contains('/')).then_some((module, abi, name))
+}
+
+/// Swift toolchain tag embedded in a library
+pub fn swift_tag(contents: &[u8]) -> Option<String> {
+    SWIFT_TAG
+        .find(contents)
+        .map(|tag| String::from_utf8_lossy(tag.as_bytes()).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lib(abi: &str, name: &str, tag: Option<&str>) -> NativeLib {
+        NativeLib {
+            module: "base".into(),
+            abi: abi.into(),
+            name: name.into(),
+            bytes: 1_000,
+            swift_tag: tag.map(String::from),
+        }
+    }
+
+    fn required() -> Vec<String> {
+        vec!["libFoodshareCore.so".into(), "libswiftCore.so".into()]
+    }
+
+    #[test]
+    fn test_lib_path_and_swift_tag() {
+        assert_eq!(lib_path("lib/x86_64/libswiftCore.so", false), Some(("base", "x86_64", "libswiftCore.so")));
+        assert_eq!(
+            lib_path("base/lib/arm64-v8a/libFoodshareCore.so", true),
+            Some(("base", "arm64-v8a", "libFoodshareCore.so"))
+        );
+        assert_eq!(lib_path("lib/arm64-v8a/README.txt", false), None);
+        assert_eq!(lib_path("base/dex/classes.dex", true), None);
+
+        assert_eq!(
+            swift_tag(b"\0Swift version 6.2 (swift-6.2-RELEASE)\0").as_deref(),
+            Some("swift-6.2-RELEASE")
+        );
+        assert_eq!(
+            swift_tag(b"(swift-DEVELOPMENT-SNAPSHOT-2025-12-17-a)").as_deref(),
+            Some("swift-DEVELOPMENT-SNAPSHOT-2025-12-17-a")
+        );
+        assert_eq!(swift_tag(b"\x7fELF no tag here"), None);
+    }
+
+    #[test]
+    fn test_consistent_abis_pass() {
+        let tag = Some("swift-6.2-RELEASE");
+        let report = NativeLibReport::from_libs(
+            "app-release.aab".into(),
+            vec![
+                lib("arm64-v8a", "libFoodshareCore.so", None),
+                lib("arm64-v8a", "libswiftCore.so", tag),
+                lib("x86_64", "libFoodshareCore.so", None),
+                lib("x86_64", "libswiftCore.so", tag),
+            ],
+        );
+        assert!(report.problems(&required()).is_empty());
+        assert_eq!(report.abis()["x86_64"].len(), 2);
+    }
+
+    #[test]
+    fn test_diverging_abis_fail() {
+        let report = NativeLibReport::from_libs(
+            "app-release.aab".into(),
+            vec![
+                lib("arm64-v8a", "libFoodshareCore.so", None),
+                lib("arm64-v8a", "libswiftCore.so", Some("swift-6.2-RELEASE")),
+                lib("arm64-v8a", "libswift_Concurrency.so", Some("swift-6.1-RELEASE")),
+                lib("arm64-v8a", "libsqlite.so", None),
+                lib("x86_64", "libswiftCore.so", Some("swift-6.1-RELEASE")),
+                lib("x86_64", "libswift_Concurrency.so", Some("swift-6.1-RELEASE")),
+            ],
+        );
+        let problems = report.problems(&required());
+        assert_eq!(
+            problems,
+            [
+                "x86_64 is missing libFoodshareCore.so",
+                "libsqlite.so is shipped for arm64-v8a but not for x86_64",
+                "libswiftCore.so versions differ across ABIs: arm64-v8a swift-6.2-RELEASE, x86_64 swift-6.1-RELEASE",
+                "arm64-v8a mixes Swift toolchains: libswiftCore.so swift-6.2-RELEASE, libswift_Concurrency.so swift-6.1-RELEASE",
+            ]
+        );
+
+        let arm_only = NativeLibReport::from_libs(
+            "app-release.apk".into(),
+            vec![lib("arm64-v8a", "libFoodshareCore.so", None), lib("arm64-v8a", "libswiftCore.so", None)],
+        );
+        assert_eq!(arm_only.problems(&required()), ["x86_64 is not shipped (found arm64-v8a)"]);
+        assert_eq!(
+            NativeLibReport::from_libs("app.apk".into(), vec![]).problems(&required()),
+            ["Package contains no native libraries"]
+        );
+    }
+}
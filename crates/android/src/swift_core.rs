@@ -3,6 +3,10 @@
 //! Rust implementation of the build-android.sh script from foodshare-core.
 //! Builds FoodshareCore Swift library for Android using Swift SDK.
 //!
+//! `setup` automates the toolchain side: it finds (or installs) a supported
+//! NDK, installs the Swift SDK for Android bundle matching the local Swift
+//! toolchain, and proves the result with a hello-world cross-compile.
+//!
 //! # Usage
 //!
 //! ```bash
@@ -14,16 +18,34 @@
 //!
 //! # Check prerequisites
 //! foodshare-android swift-core check
+//!
+//! # Install the Swift SDK and NDK, then verify a cross-compile
+//! foodshare-android swift-core setup --checksum <SHA256>
 //! ```
 
-use foodshare_core::error::Result;
-use foodshare_core::process::{command_exists, run_command, run_command_in_dir};
+use foodshare_cli::textln;
+use foodshare_core::error::{Error, Result};
+use foodshare_core::process::{
+    command_exists, run_command, run_command_with_env, CommandResult,
+};
 use owo_colors::OwoColorize;
 use std::path::{Path, PathBuf};
 
 /// Default Android API level for Swift SDK (matches Swift 6.2 SDK)
 pub const DEFAULT_API_LEVEL: u8 = 24;
 
+/// Oldest NDK major release the Swift SDK for Android supports (r27)
+pub const MIN_NDK_MAJOR: u32 = 27;
+
+/// NDK installed through `sdkmanager` when no supported one is found (r27c)
+pub const DEFAULT_NDK_VERSION: &str = "27.2.12479018";
+
+/// swift.org download host for Swift SDK bundles
+const DOWNLOAD_BASE_URL: &str = "https://download.swift.org";
+
+/// Version suffix of the Android SDK artifact bundle
+const ANDROID_BUNDLE_VERSION: &str = "0.1";
+
 /// Target architecture for Swift cross-compilation
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum SwiftAndroidTarget {
@@ -89,27 +111,334 @@ impl Default for BuildConfig {
     }
 }
 
+/// An installed Android NDK
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NdkInstall {
+    /// NDK root directory
+    pub path: PathBuf,
+    /// `Pkg.Revision` from `source.properties` (`27.2.12479018`)
+    pub version: String,
+}
+
+impl NdkInstall {
+    /// Read the NDK at `path`, or `None` if it isn't one
+    pub fn at(path: &Path) -> Option<Self> {
+        let properties = std::fs::read_to_string(path.join("source.properties")).ok()?;
+        let version = properties.lines().find_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            (key.trim() == "Pkg.Revision").then(|| value.trim().to_string())
+        })?;
+        Some(Self {
+            path: path.to_path_buf(),
+            version,
+        })
+    }
+
+    /// Major release (27 for r27)
+    pub fn major(&self) -> u32 {
+        self.version
+            .split('.')
+            .next()
+            .and_then(|major| major.parse().ok())
+            .unwrap_or(0)
+    }
+
+    /// Whether the Swift SDK for Android can build against this NDK
+    pub fn is_supported(&self) -> bool {
+        self.major() >= MIN_NDK_MAJOR
+    }
+
+    /// Environment the Swift SDK reads to locate the NDK
+    pub fn env(&self) -> Vec<(&'static str, String)> {
+        let path = self.path.display().to_string();
+        vec![("ANDROID_NDK_HOME", path.clone()), ("ANDROID_NDK_ROOT", path)]
+    }
+
+    /// Shell `export` lines for [`NdkInstall::env`], suitable for `eval`
+    pub fn exports(&self) -> String {
+        self.env()
+            .iter()
+            .map(|(key, value)| format!("export {}=\"{}\"\n", key, value))
+            .collect()
+    }
+}
+
+/// Android SDK locations: `ANDROID_HOME`, `ANDROID_SDK_ROOT`, then the
+/// Android Studio defaults
+fn android_sdk_roots() -> Vec<PathBuf> {
+    let mut roots: Vec<PathBuf> = ["ANDROID_HOME", "ANDROID_SDK_ROOT"]
+        .iter()
+        .filter_map(std::env::var_os)
+        .map(PathBuf::from)
+        .collect();
+    if let Some(home) = std::env::var_os("HOME").map(PathBuf::from) {
+        roots.push(home.join("Library/Android/sdk"));
+        roots.push(home.join("Android/Sdk"));
+    }
+    roots
+}
+
+/// Find installed NDKs, newest first
+///
+/// Looks at `ANDROID_NDK_HOME` and `ANDROID_NDK_ROOT`, then the side-by-side
+/// `ndk/<version>` directories and legacy `ndk-bundle` of each Android SDK.
+pub fn find_ndks() -> Vec<NdkInstall> {
+    let mut candidates: Vec<PathBuf> = ["ANDROID_NDK_HOME", "ANDROID_NDK_ROOT"]
+        .iter()
+        .filter_map(std::env::var_os)
+        .map(PathBuf::from)
+        .collect();
+    for sdk in android_sdk_roots() {
+        if let Ok(entries) = std::fs::read_dir(sdk.join("ndk")) {
+            candidates.extend(entries.filter_map(|e| e.ok()).map(|e| e.path()));
+        }
+        candidates.push(sdk.join("ndk-bundle"));
+    }
+    ndks_in(&candidates)
+}
+
+/// NDKs among `candidates`, deduplicated and sorted newest first
+fn ndks_in(candidates: &[PathBuf]) -> Vec<NdkInstall> {
+    let mut seen = Vec::new();
+    let mut ndks = Vec::new();
+    for candidate in candidates {
+        let Some(ndk) = NdkInstall::at(candidate) else {
+            continue;
+        };
+        let canonical = candidate.canonicalize().unwrap_or_else(|_| candidate.clone());
+        if !seen.contains(&canonical) {
+            seen.push(canonical);
+            ndks.push(ndk);
+        }
+    }
+    ndks.sort_by_cached_key(|ndk| {
+        std::cmp::Reverse(
+            ndk.version
+                .split('.')
+                .map(|part| part.parse::<u64>().unwrap_or(0))
+                .collect::<Vec<_>>(),
+        )
+    });
+    ndks
+}
+
+/// Pick the NDK to build with: `preferred` (usually `ANDROID_NDK_HOME`) when
+/// it is supported, otherwise the newest supported one
+pub fn select_ndk<'a>(ndks: &'a [NdkInstall], preferred: Option<&Path>) -> Option<&'a NdkInstall> {
+    let preferred = preferred.and_then(|path| {
+        let path = path.canonicalize().ok()?;
+        ndks.iter()
+            .find(|ndk| ndk.path.canonicalize().ok().as_ref() == Some(&path))
+    });
+    preferred
+        .filter(|ndk| ndk.is_supported())
+        .or_else(|| ndks.iter().find(|ndk| ndk.is_supported()))
+}
+
+/// Detect the NDK builds should use
+pub fn detect_ndk() -> Option<NdkInstall> {
+    let preferred = std::env::var_os("ANDROID_NDK_HOME").map(PathBuf::from);
+    select_ndk(&find_ndks(), preferred.as_deref()).cloned()
+}
+
+/// Install [`DEFAULT_NDK_VERSION`] with the Android SDK's `sdkmanager`
+pub fn install_ndk() -> Result<NdkInstall> {
+    if !command_exists("sdkmanager") {
+        return Err(Error::command_not_found("sdkmanager").with_suggestion(format!(
+            "Install Android command-line tools, or install NDK r{}+ from Android Studio's SDK Manager",
+            MIN_NDK_MAJOR
+        )));
+    }
+    let package = format!("ndk;{}", DEFAULT_NDK_VERSION);
+    let result = run_command("sdkmanager", &["--install", &package])?;
+    if !result.success {
+        return Err(Error::process(format!(
+            "sdkmanager failed to install {}: {}",
+            package,
+            result.combined_output().trim()
+        )));
+    }
+    find_ndks()
+        .into_iter()
+        .find(|ndk| ndk.version == DEFAULT_NDK_VERSION)
+        .ok_or_else(|| Error::process(format!("NDK {} not found after install", DEFAULT_NDK_VERSION)))
+}
+
+/// The Swift toolchain to use; prefers /usr/bin/swift (matches shell script)
+fn swift_command() -> &'static str {
+    if Path::new("/usr/bin/swift").exists() {
+        "/usr/bin/swift"
+    } else {
+        "swift"
+    }
+}
+
+/// Release tag from `swift --version` output (`swift-6.2-RELEASE`)
+///
+/// Only open-source toolchains report one; Xcode's Swift can't target
+/// Android anyway.
+pub fn swift_tag(version_output: &str) -> Option<String> {
+    let start = version_output.find("(swift-")? + 1;
+    let tag = &version_output[start..];
+    let end = tag.find([')', ' ', ',']).unwrap_or(tag.len());
+    Some(tag[..end].to_string())
+}
+
+/// swift.org URL of the Swift SDK for Android bundle for a toolchain tag
+pub fn android_sdk_url(tag: &str) -> String {
+    let branch_dir = if tag.starts_with("swift-DEVELOPMENT-SNAPSHOT") {
+        "development".to_string()
+    } else if let Some(version) = tag
+        .strip_prefix("swift-")
+        .and_then(|rest| rest.strip_suffix("-RELEASE"))
+    {
+        format!("swift-{}-release", version)
+    } else {
+        // swift-6.2-DEVELOPMENT-SNAPSHOT-2025-10-01-a
+        let version = tag
+            .strip_prefix("swift-")
+            .and_then(|rest| rest.split('-').next())
+            .unwrap_or_default();
+        format!("swift-{}-branch", version)
+    };
+    format!(
+        "{}/{}/android-sdk/{}/{}_android-{}.artifactbundle.tar.gz",
+        DOWNLOAD_BASE_URL, branch_dir, tag, tag, ANDROID_BUNDLE_VERSION
+    )
+}
+
+/// Install a Swift SDK for Android bundle from a URL or local path
+///
+/// Remote bundles must come with their published SHA-256 checksum.
+pub fn install_android_sdk(source: &str, checksum: Option<&str>) -> Result<()> {
+    let remote = source.starts_with("https://") || source.starts_with("http://");
+    if remote && checksum.is_none() {
+        return Err(Error::validation(format!("No checksum for {}", source))
+            .with_suggestion("Pass --checksum with the SHA-256 listed on https://www.swift.org/install/"));
+    }
+
+    let mut args = vec!["sdk", "install", source];
+    if let Some(checksum) = checksum {
+        args.extend(["--checksum", checksum]);
+    }
+    let result = run_command(swift_command(), &args)?;
+    if !result.success && !result.combined_output().contains("already installed") {
+        return Err(Error::process(format!(
+            "swift sdk install failed: {}",
+            result.combined_output().trim()
+        )));
+    }
+    Ok(())
+}
+
+/// Post-install script shipped in the Android SDK bundle, which links the
+/// NDK sysroot into the bundle
+pub fn find_sdk_setup_script() -> Option<PathBuf> {
+    let home = PathBuf::from(std::env::var_os("HOME")?);
+    [".swiftpm/swift-sdks", "Library/org.swift.swiftpm/swift-sdks"]
+        .iter()
+        .map(|dir| home.join(dir))
+        .filter(|dir| dir.exists())
+        .flat_map(|dir| {
+            walkdir::WalkDir::new(dir)
+                .max_depth(4)
+                .into_iter()
+                .filter_map(|e| e.ok())
+        })
+        .find(|entry| entry.file_name() == "setup-android-sdk.sh")
+        .map(|entry| entry.into_path())
+}
+
+/// Run the bundle's post-install script against `ndk`
+pub fn run_sdk_setup_script(script: &Path, ndk: &NdkInstall) -> Result<()> {
+    let env = ndk.env();
+    let env: Vec<(&str, &str)> = env.iter().map(|(k, v)| (*k, v.as_str())).collect();
+    let result = run_command_with_env("bash", &[&script.to_string_lossy()], &env)?;
+    if !result.success {
+        return Err(Error::process(format!(
+            "{} failed: {}",
+            script.display(),
+            result.combined_output().trim()
+        )));
+    }
+    Ok(())
+}
+
+/// Run `swift build` for a package with the NDK in the environment
+fn swift_build(package_dir: &Path, args: &[&str], ndk: Option<&NdkInstall>) -> Result<CommandResult> {
+    let package_path = package_dir.to_string_lossy();
+    let mut all_args = vec!["build", "--package-path", &package_path];
+    all_args.extend_from_slice(args);
+
+    let env = ndk.map(NdkInstall::env).unwrap_or_default();
+    let env: Vec<(&str, &str)> = env.iter().map(|(k, v)| (*k, v.as_str())).collect();
+    run_command_with_env(swift_command(), &all_args, &env)
+}
+
+/// Cross-compile a hello-world package to prove the toolchain works
+pub fn verify_cross_compile(
+    target: SwiftAndroidTarget,
+    api_level: u8,
+    ndk: Option<&NdkInstall>,
+) -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("foodshare-swift-android-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("Sources/hello"))?;
+    std::fs::write(
+        dir.join("Package.swift"),
+        "// swift-tools-version:5.9\nimport PackageDescription\n\n\
+         let package = Package(name: \"hello\", targets: [.executableTarget(name: \"hello\")])\n",
+    )?;
+    std::fs::write(dir.join("Sources/hello/main.swift"), "print(\"Hello, Android\")\n")?;
+
+    let sdk_id = target.sdk_id(api_level);
+    let result = swift_build(&dir, &["--swift-sdk", &sdk_id], ndk);
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let result = result?;
+    if !result.success {
+        return Err(Error::process(format!(
+            "Cross-compile for {} failed: {}",
+            sdk_id,
+            result.combined_output().trim()
+        )));
+    }
+    Ok(())
+}
+
 /// Check prerequisites for building Swift for Android
 pub fn check_prerequisites() -> Result<PrerequisiteStatus> {
     let mut status = PrerequisiteStatus::default();
 
     // Check Swift
     if command_exists("swift") {
-        let result = run_command("swift", &["--version"])?;
-        status.swift_version = Some(result.stdout.lines().next().unwrap_or("").to_string());
+        let result = run_command(swift_command(), &["--version"])?;
+        let version = result.combined_output();
+        status.swift_version = Some(version.lines().next().unwrap_or("").to_string());
+        status.swift_tag = swift_tag(&version);
         status.swift_installed = true;
-    }
 
-    // Check Swift SDK for Android
-    let sdk_result = run_command("swift", &["sdk", "list"]);
-    if let Ok(result) = sdk_result {
-        status.android_sdk_installed = result.stdout.contains("android");
+        // Check Swift SDK for Android
+        if let Ok(result) = run_command(swift_command(), &["sdk", "list"]) {
+            status.android_sdks = result
+                .stdout
+                .lines()
+                .map(str::trim)
+                .filter(|line| line.contains("android"))
+                .map(String::from)
+                .collect();
+            status.android_sdk_installed = !status.android_sdks.is_empty();
+        }
     }
 
     // Check Android NDK
-    status.ndk_path = std::env::var("ANDROID_NDK_HOME").ok().map(PathBuf::from);
-    if let Some(ref path) = status.ndk_path {
-        status.ndk_installed = path.exists();
+    let preferred = std::env::var_os("ANDROID_NDK_HOME").map(PathBuf::from);
+    status.ndk_env_set = preferred.is_some();
+    status.ndks = find_ndks();
+    if let Some(ndk) = select_ndk(&status.ndks, preferred.as_deref()) {
+        status.ndk_installed = true;
+        status.ndk_path = Some(ndk.path.clone());
+        status.ndk_version = Some(ndk.version.clone());
     }
 
     Ok(status)
@@ -118,46 +447,86 @@ pub fn check_prerequisites() -> Result<PrerequisiteStatus> {
 /// Prerequisite check status
 #[derive(Debug, Default)]
 pub struct PrerequisiteStatus {
+    /// Whether `swift` is on PATH
     pub swift_installed: bool,
+    /// First line of `swift --version`
     pub swift_version: Option<String>,
+    /// Toolchain release tag, when the toolchain reports one
+    pub swift_tag: Option<String>,
+    /// Whether a Swift SDK for Android is installed
     pub android_sdk_installed: bool,
+    /// Installed Swift SDK bundles for Android
+    pub android_sdks: Vec<String>,
+    /// Whether a supported NDK was found
     pub ndk_installed: bool,
+    /// Path of the selected NDK
     pub ndk_path: Option<PathBuf>,
+    /// Version of the selected NDK
+    pub ndk_version: Option<String>,
+    /// Every NDK found, newest first
+    pub ndks: Vec<NdkInstall>,
+    /// Whether `ANDROID_NDK_HOME` is set
+    pub ndk_env_set: bool,
 }
 
 impl PrerequisiteStatus {
+    /// Whether everything needed to build is in place
     pub fn is_ready(&self) -> bool {
         self.swift_installed && self.android_sdk_installed && self.ndk_installed
     }
 
+    /// Print each prerequisite with how to fix what is missing
     pub fn print_status(&self) {
-        println!("{}", "Prerequisites Check".bold());
-        println!();
+        textln!("{}", "Prerequisites Check".bold());
+        textln!();
 
         if self.swift_installed {
-            println!(
+            textln!(
                 "  {} Swift: {}",
                 "OK".green(),
                 self.swift_version.as_deref().unwrap_or("installed")
             );
         } else {
-            println!("  {} Swift: not found", "ERROR".red());
+            textln!("  {} Swift: not found", "ERROR".red());
+            textln!("       Install a Swift 6.2+ toolchain: swiftly install 6.2");
         }
 
         if self.android_sdk_installed {
-            println!("  {} Swift SDK for Android: installed", "OK".green());
-        } else {
-            println!("  {} Swift SDK for Android: not installed", "ERROR".red());
-        }
-
-        if self.ndk_installed {
-            println!(
-                "  {} Android NDK: {}",
+            textln!(
+                "  {} Swift SDK for Android: {}",
                 "OK".green(),
-                self.ndk_path.as_ref().map(|p| p.display().to_string()).unwrap_or_default()
+                self.android_sdks.join(", ")
             );
         } else {
-            println!("  {} Android NDK: not found (set ANDROID_NDK_HOME)", "ERROR".red());
+            textln!("  {} Swift SDK for Android: not installed", "ERROR".red());
+            textln!("       Run: foodshare-android swift-core setup --checksum <SHA256>");
+        }
+
+        match (&self.ndk_path, &self.ndk_version) {
+            (Some(path), Some(version)) => {
+                textln!("  {} Android NDK: {} ({})", "OK".green(), version, path.display());
+                if !self.ndk_env_set {
+                    textln!(
+                        "       ANDROID_NDK_HOME is not set; builds here set it, for your shell run:"
+                    );
+                    textln!("       eval \"$(foodshare-android swift-core setup --print-env)\"");
+                }
+            }
+            _ => {
+                match self.ndks.first() {
+                    Some(newest) => textln!(
+                        "  {} Android NDK: {} is too old, r{}+ is required",
+                        "ERROR".red(),
+                        newest.version,
+                        MIN_NDK_MAJOR
+                    ),
+                    None => textln!("  {} Android NDK: not found", "ERROR".red()),
+                }
+                textln!(
+                    "       Run: foodshare-android swift-core setup (installs NDK {} via sdkmanager)",
+                    DEFAULT_NDK_VERSION
+                );
+            }
         }
     }
 }
@@ -169,14 +538,14 @@ pub fn build_for_target(
 ) -> Result<BuildResult> {
     let sdk_id = target.sdk_id(config.api_level);
 
-    println!(
+    textln!(
         "  {} Building for {} ({})...",
         "->".blue(),
         target.display_name(),
         sdk_id
     );
 
-    let mut args = vec!["build", "--swift-sdk", &sdk_id];
+    let mut args = vec!["--swift-sdk", sdk_id.as_str()];
 
     if config.static_stdlib {
         args.push("--static-swift-stdlib");
@@ -187,14 +556,8 @@ pub fn build_for_target(
         args.push("release");
     }
 
-    // Use /usr/bin/swift to ensure we use the system Swift (matches shell script)
-    let swift_cmd = if Path::new("/usr/bin/swift").exists() {
-        "/usr/bin/swift"
-    } else {
-        "swift"
-    };
-
-    let result = run_command_in_dir(swift_cmd, &args, &config.project_dir)?;
+    let ndk = detect_ndk();
+    let result = swift_build(&config.project_dir, &args, ndk.as_ref())?;
 
     if !result.success {
         return Ok(BuildResult {
@@ -210,7 +573,7 @@ pub fn build_for_target(
 
     match output_path {
         Some(path) => {
-            println!("  {} Built: {}", "✓".green(), path.display());
+            textln!("  {} Built: {}", "✓".green(), path.display());
             Ok(BuildResult {
                 target,
                 success: true,
@@ -219,7 +582,7 @@ pub fn build_for_target(
             })
         }
         None => {
-            println!("  {} Library not found for {}", "⚠".yellow(), target.jni_arch());
+            textln!("  {} Library not found for {}", "⚠".yellow(), target.jni_arch());
             Ok(BuildResult {
                 target,
                 success: false,
@@ -313,8 +676,8 @@ pub fn build_all(config: &BuildConfig) -> Result<Vec<BuildResult>> {
     // Auto-copy to Android project if configured (matches shell script behavior)
     if let Some(ref android_dir) = config.android_project_dir {
         if android_dir.exists() {
-            println!();
-            println!("  {} Copying to Android project...", "->".blue());
+            textln!();
+            textln!("  {} Copying to Android project...", "->".blue());
             copy_to_android_project(&config.output_dir, android_dir)?;
         }
     }
@@ -347,8 +710,8 @@ pub fn build_single(target_name: &str, config: &BuildConfig) -> Result<BuildResu
     if result.success {
         if let Some(ref android_dir) = config.android_project_dir {
             if android_dir.exists() {
-                println!();
-                println!("  {} Copying to Android project...", "->".blue());
+                textln!();
+                textln!("  {} Copying to Android project...", "->".blue());
                 copy_to_android_project(&config.output_dir, android_dir)?;
             }
         }
@@ -379,7 +742,7 @@ pub fn copy_to_android_project(
                 if lib_path.extension().map_or(false, |e| e == "so") {
                     let dest_path = dest_dir.join(lib_path.file_name().unwrap());
                     std::fs::copy(&lib_path, &dest_path)?;
-                    println!(
+                    textln!(
                         "  {} Copied to: {}",
                         "✓".green(),
                         dest_path.display()
@@ -404,26 +767,26 @@ pub fn detect_android_project(project_dir: &Path) -> Option<PathBuf> {
 
 /// Print setup instructions
 pub fn print_setup_instructions() {
-    println!();
-    println!("{}", "Swift SDK for Android Setup".bold());
-    println!();
-    println!("1. Install Swift 6.2+ toolchain:");
-    println!("   swiftly install main-snapshot-2025-12-17");
-    println!("   swiftly use main-snapshot-2025-12-17");
-    println!();
-    println!("2. Install Swift SDK for Android:");
-    println!("   Visit https://www.swift.org/download/ for the SDK URL");
-    println!("   swift sdk install <URL> --checksum <SHA256>");
-    println!();
-    println!("3. Set Android NDK path:");
-    println!("   export ANDROID_NDK_HOME=/path/to/android-ndk-r27d");
-    println!();
-    println!("4. Build FoodshareCore:");
-    println!("   foodshare-android swift-core build --target all");
-    println!();
-    println!("   Or from foodshare-core directory:");
-    println!("   foodshare-android swift-core build --target all --project-dir .");
-    println!();
+    textln!();
+    textln!("{}", "Swift SDK for Android Setup".bold());
+    textln!();
+    textln!("1. Install Swift 6.2+ toolchain:");
+    textln!("   swiftly install main-snapshot-2025-12-17");
+    textln!("   swiftly use main-snapshot-2025-12-17");
+    textln!();
+    textln!("2. Install the Swift SDK for Android (and NDK r{}+ if missing):", MIN_NDK_MAJOR);
+    textln!("   foodshare-android swift-core setup --checksum <SHA256>");
+    textln!("   The checksum is listed on https://www.swift.org/install/");
+    textln!();
+    textln!("3. Export the Android NDK path:");
+    textln!("   eval \"$(foodshare-android swift-core setup --print-env)\"");
+    textln!();
+    textln!("4. Build FoodshareCore:");
+    textln!("   foodshare-android swift-core build --target all");
+    textln!();
+    textln!("   Or from foodshare-core directory:");
+    textln!("   foodshare-android swift-core build --target all --project-dir .");
+    textln!();
 }
 
 /// Main entry point matching shell script behavior
@@ -434,10 +797,10 @@ pub fn build_android(
     configuration: &str,
     project_dir: &Path,
 ) -> Result<Vec<BuildResult>> {
-    println!("{}", "Building FoodshareCore for Android...".bold());
-    println!("Architecture: {}", arch);
-    println!("Configuration: {}", configuration);
-    println!();
+    textln!("{}", "Building FoodshareCore for Android...".bold());
+    textln!("Architecture: {}", arch);
+    textln!("Configuration: {}", configuration);
+    textln!();
 
     let output_dir = project_dir.join("android-libs");
     let android_project_dir = detect_android_project(project_dir);
@@ -456,8 +819,8 @@ pub fn build_android(
         target => vec![build_single(target, &config)?],
     };
 
-    println!();
-    println!("{}", "Build complete!".green().bold());
+    textln!();
+    textln!("{}", "Build complete!".green().bold());
 
     Ok(results)
 }
@@ -506,4 +869,48 @@ mod tests {
         assert_eq!(SwiftAndroidTarget::Arm64.display_name(), "ARM64");
         assert_eq!(SwiftAndroidTarget::X86_64.display_name(), "x86_64");
     }
+
+    #[test]
+    fn test_ndk_detection() {
+        let root = tempfile::tempdir().unwrap();
+        let write_ndk = |version: &str| {
+            let dir = root.path().join(version);
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(
+                dir.join("source.properties"),
+                format!("Pkg.Desc = Android NDK\nPkg.Revision = {}\n", version),
+            )
+            .unwrap();
+            dir
+        };
+        let old = write_ndk("25.2.9519653");
+        let r27 = write_ndk("27.2.12479018");
+        let r28 = write_ndk("28.0.13004108");
+
+        let candidates = vec![old.clone(), r27.clone(), r28.clone(), r27.clone(), root.path().join("missing")];
+        let ndks = ndks_in(&candidates);
+        let versions: Vec<&str> = ndks.iter().map(|n| n.version.as_str()).collect();
+        assert_eq!(versions, ["28.0.13004108", "27.2.12479018", "25.2.9519653"]);
+        assert_eq!(ndks[2].major(), 25);
+        assert!(!ndks[2].is_supported());
+
+        assert_eq!(select_ndk(&ndks, None).unwrap().path, r28);
+        assert_eq!(select_ndk(&ndks, Some(&r27)).unwrap().path, r27);
+        // An unsupported ANDROID_NDK_HOME falls back to the newest supported NDK
+        assert_eq!(select_ndk(&ndks, Some(&old)).unwrap().path, r28);
+        assert!(select_ndk(&ndks[2..], None).is_none());
+    }
+
+    #[test]
+    fn test_android_sdk_url() {
+        let tag = swift_tag("Swift version 6.2 (swift-6.2-RELEASE)\nTarget: x86_64-unknown-linux-gnu").unwrap();
+        assert_eq!(tag, "swift-6.2-RELEASE");
+        assert_eq!(
+            android_sdk_url(&tag),
+            "https://download.swift.org/swift-6.2-release/android-sdk/swift-6.2-RELEASE/swift-6.2-RELEASE_android-0.1.artifactbundle.tar.gz"
+        );
+        assert!(android_sdk_url("swift-DEVELOPMENT-SNAPSHOT-2025-10-16-a").contains("/development/android-sdk/"));
+        assert!(android_sdk_url("swift-6.3-DEVELOPMENT-SNAPSHOT-2025-10-16-a").contains("/swift-6.3-branch/"));
+        assert!(swift_tag("Apple Swift version 6.2 (swiftlang-6.2.0.19.9 clang-1700.3.19.1)").is_none());
+    }
 }
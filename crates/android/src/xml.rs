@@ -0,0 +1,119 @@
+//! Minimal XML tag scanner
+//!
+//! Enough of XML for the Android files this crate reads (manifests, lint
+//! reports and resources): element tags with their attributes, in document
+//! order. Text content is left to callers, which slice it out between tags;
+//! namespaces and DTDs are not interpreted.
+
+/// An element tag and its byte offset
+pub(crate) struct Tag<'a> {
+    pub name: &'a str,
+    pub close: bool,
+    pub empty: bool,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Tag<'_> {
+    /// Attributes in file order, with entities decoded
+    pub fn attributes(&self, content: &str) -> Vec<(String, String)> {
+        let text = &content[self.start..self.end];
+        let mut attrs = Vec::new();
+        let mut rest = text[1 + self.name.len()..].trim_end_matches(['>', '/']);
+        while let Some((name, after)) = rest.split_once('=') {
+            let after = after.trim_start();
+            let Some(quote) = after.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+                break;
+            };
+            let value = &after[1..];
+            let Some(close) = value.find(quote) else {
+                break;
+            };
+            attrs.push((name.trim().to_string(), unescape(&value[..close])));
+            rest = &value[close + 1..];
+        }
+        attrs
+    }
+}
+
+/// Element tags in document order, skipping declarations, comments and CDATA
+pub(crate) fn tags(content: &str) -> std::result::Result<Vec<Tag<'_>>, String> {
+    let mut tags = Vec::new();
+    let mut pos = 0;
+    while let Some(offset) = content[pos..].find('<') {
+        let start = pos + offset;
+        let rest = &content[start..];
+        if let Some(terminator) = [("<?", "?>"), ("<!--", "-->"), ("<![CDATA[", "]]>"), ("<!", ">")]
+            .iter()
+            .find(|(open, _)| rest.starts_with(open))
+            .map(|(_, terminator)| terminator)
+        {
+            let end = rest
+                .find(terminator)
+                .ok_or_else(|| format!("unterminated tag on line {}", line_at(content, start)))?;
+            pos = start + end + terminator.len();
+            continue;
+        }
+
+        // Find the closing '>' outside attribute values
+        let mut quote = None;
+        let end = rest
+            .char_indices()
+            .find(|&(_, c)| {
+                match quote {
+                    Some(q) if c == q => quote = None,
+                    None if c == '"' || c == '\'' => quote = Some(c),
+                    _ => {}
+                }
+                c == '>' && quote.is_none()
+            })
+            .map(|(i, _)| start + i + 1)
+            .ok_or_else(|| format!("unterminated tag on line {}", line_at(content, start)))?;
+        let text = &content[start..end];
+
+        let close = text.starts_with("</");
+        let name_start = start + if close { 2 } else { 1 };
+        let name_len = content[name_start..end]
+            .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+            .unwrap_or(0);
+        tags.push(Tag {
+            name: &content[name_start..name_start + name_len],
+            close,
+            empty: text.ends_with("/>"),
+            start,
+            end,
+        });
+        pos = end;
+    }
+    Ok(tags)
+}
+
+pub(crate) fn attr<'a>(attrs: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    attrs
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.as_str())
+}
+
+pub(crate) fn unescape(value: &str) -> String {
+    value
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+/// Escape text for an attribute value
+pub(crate) fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// 1-based line of a byte offset
+pub(crate) fn line_at(content: &str, offset: usize) -> usize {
+    content[..offset].matches('\n').count() + 1
+}
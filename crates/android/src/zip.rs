@@ -0,0 +1,104 @@
+//! Minimal zip reader
+//!
+//! Enough of zip for APKs and App Bundles: the central directory, and the
+//! data of a single entry when it is stored or deflated. Zip64 archives,
+//! encryption and multi-disk archives are not supported.
+
+use std::io::Read;
+
+/// Compression method of a stored entry
+const STORED: u16 = 0;
+/// Compression method of a deflated entry
+const DEFLATED: u16 = 8;
+
+/// An entry of the central directory
+pub(crate) struct ZipEntry {
+    pub path: String,
+    pub compressed: u64,
+    pub uncompressed: u64,
+    pub method: u16,
+    pub header_offset: usize,
+}
+
+fn u16_at(data: &[u8], at: usize) -> Option<u16> {
+    data.get(at..at + 2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn u32_at(data: &[u8], at: usize) -> Option<u32> {
+    data.get(at..at + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+/// Entries of the central directory, skipping directories
+pub(crate) fn read_directory(data: &[u8]) -> Result<Vec<ZipEntry>, String> {
+    const END_OF_DIRECTORY: u32 = 0x0605_4b50;
+    const DIRECTORY_ENTRY: u32 = 0x0201_4b50;
+
+    // The end record is the last 22 bytes plus a comment of up to 64 KiB
+    let search_from = data.len().saturating_sub(22 + u16::MAX as usize);
+    let end = (search_from..data.len().saturating_sub(21))
+        .rev()
+        .find(|&at| u32_at(data, at) == Some(END_OF_DIRECTORY))
+        .ok_or("not a zip file")?;
+    let count = u16_at(data, end + 10).ok_or("truncated zip")? as usize;
+    let mut at = u32_at(data, end + 16).ok_or("truncated zip")? as usize;
+    if at == u32::MAX as usize {
+        return Err("zip64 packages are not supported".to_string());
+    }
+
+    let mut entries = Vec::with_capacity(count);
+    for _ in 0..count {
+        if u32_at(data, at) != Some(DIRECTORY_ENTRY) {
+            return Err("corrupt zip directory".to_string());
+        }
+        let field = |offset: usize| u16_at(data, at + offset).map(usize::from).ok_or("truncated zip");
+        let method = field(10)? as u16;
+        let compressed = u32_at(data, at + 20).ok_or("truncated zip")?;
+        let uncompressed = u32_at(data, at + 24).ok_or("truncated zip")?;
+        let name_len = field(28)?;
+        let extra_len = field(30)?;
+        let comment_len = field(32)?;
+        let header_offset = u32_at(data, at + 42).ok_or("truncated zip")? as usize;
+        let name = data.get(at + 46..at + 46 + name_len).ok_or("truncated zip")?;
+        let path = String::from_utf8_lossy(name).to_string();
+        if !path.ends_with('/') {
+            entries.push(ZipEntry {
+                path,
+                compressed: compressed as u64,
+                uncompressed: uncompressed as u64,
+                method,
+                header_offset,
+            });
+        }
+        at += 46 + name_len + extra_len + comment_len;
+    }
+    Ok(entries)
+}
+
+/// Uncompressed contents of an entry
+pub(crate) fn read_entry(data: &[u8], entry: &ZipEntry) -> Result<Vec<u8>, String> {
+    const LOCAL_HEADER: u32 = 0x0403_4b50;
+
+    let at = entry.header_offset;
+    if u32_at(data, at) != Some(LOCAL_HEADER) {
+        return Err(format!("{}: corrupt local header", entry.path));
+    }
+    // The local header has its own name and extra field lengths
+    let name_len = u16_at(data, at + 26).ok_or("truncated zip")? as usize;
+    let extra_len = u16_at(data, at + 28).ok_or("truncated zip")? as usize;
+    let start = at + 30 + name_len + extra_len;
+    let raw = data
+        .get(start..start + entry.compressed as usize)
+        .ok_or_else(|| format!("{}: truncated entry", entry.path))?;
+
+    match entry.method {
+        STORED => Ok(raw.to_vec()),
+        DEFLATED => {
+            let mut contents = Vec::with_capacity(entry.uncompressed as usize);
+            flate2::read::DeflateDecoder::new(raw)
+                .read_to_end(&mut contents)
+                .map_err(|e| format!("{}: {}", entry.path, e))?;
+            Ok(contents)
+        }
+        method => Err(format!("{}: unsupported compression method {}", entry.path, method)),
+    }
+}
@@ -1,15 +1,28 @@
 //! Main API client implementation
 
 use crate::config::ClientConfig;
-use crate::endpoints::{BffApi, HealthApi, LocalizationApi, ProductsApi, TranslationsApi};
+use crate::endpoints::{
+    AuthApi, BffApi, HealthApi, LocalizationApi, ProductsApi, StorageApi, TranslationsApi,
+};
 use crate::error::{ApiError, ApiResult};
+use crate::middleware::http_cache::{CachedResponse, HttpCache};
+use crate::middleware::{Middleware, RequestInfo};
+use crate::offline::{OfflineQueue, QueuedRequest, ReplayReport};
+use crate::progress::{self, TransferProgress};
+use crate::session::{Session, SessionStore, TokenResponse};
+use crate::transport::{HttpTransport, Transport};
+use futures_util::stream::{self, Stream, StreamExt};
 use foodshare_core::rate_limit::RateLimiter;
 use foodshare_core::retry::{CircuitBreaker, CircuitBreakerConfig, CircuitState};
-use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE, USER_AGENT};
+use reqwest::header::{
+    HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE, USER_AGENT,
+};
 use reqwest::{Client, Method, RequestBuilder, Response};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, PoisonError, RwLock};
 use std::time::{Duration, Instant};
 use tracing::{debug, instrument, warn};
 use uuid::Uuid;
@@ -20,19 +33,47 @@ const X_REQUEST_ID: &str = "X-Request-ID";
 /// API key header for Supabase
 const APIKEY_HEADER: &str = "apikey";
 
+/// Idempotency key header sent with mutating requests
+const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Shortest wait between rate limiter polls in a batch
+const MIN_RATE_LIMIT_WAIT: Duration = Duration::from_millis(10);
+
+tokio::task_local! {
+    /// Set inside [`FoodshareClient::batch`]: wait for the rate limiter instead of failing
+    static WAIT_FOR_RATE_LIMIT: bool;
+}
+
 /// Foodshare API client with built-in resilience patterns
 ///
 /// This client wraps `reqwest` and adds:
 /// - Automatic retry with exponential backoff
 /// - Circuit breaker to prevent cascading failures
 /// - Rate limiting to avoid throttling
+/// - Per-endpoint rate limit, circuit breaker and timeout overrides
 /// - Request correlation IDs for tracing
+/// - Session auth with token refresh and retry on 401
+/// - Optional offline queue for mutating requests
+/// - Optional HTTP cache with ETag/Last-Modified revalidation
+/// - Pluggable transport for mocked and recorded/replayed runs
+/// - Request/response middleware hooks
+/// - Optional guard against mutating requests to production
+/// - Progress callbacks for large uploads and downloads
 #[derive(Clone)]
 pub struct FoodshareClient {
     inner: Client,
+    transport: Arc<dyn Transport>,
+    middleware: Vec<Arc<dyn Middleware>>,
     config: Arc<ClientConfig>,
     circuit_breaker: Arc<CircuitBreaker>,
     rate_limiter: Arc<RateLimiter>,
+    endpoint_guards: Arc<HashMap<String, EndpointGuards>>,
+    session: Arc<RwLock<Option<Session>>>,
+    session_store: Option<SessionStore>,
+    refresh_lock: Arc<tokio::sync::Mutex<()>>,
+    offline_queue: Option<Arc<OfflineQueue>>,
+    http_cache: Option<Arc<HttpCache>>,
+    cache_only: bool,
 }
 
 impl FoodshareClient {
@@ -68,15 +109,99 @@ impl FoodshareClient {
 
         let circuit_breaker = Arc::new(CircuitBreaker::new(CircuitBreakerConfig::default()));
         let rate_limiter = Arc::new(RateLimiter::new(config.rate_limit.clone()));
+        let endpoint_guards = config
+            .endpoints
+            .iter()
+            .map(|(name, endpoint)| {
+                let guards = EndpointGuards {
+                    rate_limiter: endpoint.rate_limit.clone().map(RateLimiter::new),
+                    circuit_breaker: endpoint.circuit_breaker.clone().map(CircuitBreaker::new),
+                };
+                (name.clone(), guards)
+            })
+            .collect();
 
         Ok(Self {
+            transport: Arc::new(HttpTransport::new(inner.clone())),
             inner,
+            middleware: Vec::new(),
             config: Arc::new(config),
             circuit_breaker,
             rate_limiter,
+            endpoint_guards: Arc::new(endpoint_guards),
+            session: Arc::new(RwLock::new(None)),
+            session_store: None,
+            refresh_lock: Arc::new(tokio::sync::Mutex::new(())),
+            offline_queue: None,
+            http_cache: None,
+            cache_only: false,
         })
     }
 
+    /// Send requests through `transport` instead of the network
+    ///
+    /// Use a [`MockTransport`](crate::transport::MockTransport) in tests or a
+    /// [`ReplayTransport`](crate::transport::ReplayTransport) for offline runs.
+    #[must_use]
+    pub fn with_transport(mut self, transport: Arc<dyn Transport>) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Run `middleware` around every request
+    ///
+    /// Middleware added first sees requests first and outcomes last.
+    #[must_use]
+    pub fn with_middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Cache GET responses in `cache`, revalidating them with conditional requests
+    #[must_use]
+    pub fn with_http_cache(mut self, cache: HttpCache) -> Self {
+        self.http_cache = Some(Arc::new(cache));
+        self
+    }
+
+    /// Answer GET requests from the HTTP cache alone, never the network
+    ///
+    /// Requests without a cached response, and every other method, fail
+    /// with [`ApiError::NotCached`]. Only useful with [`Self::with_http_cache`].
+    #[must_use]
+    pub fn with_cache_only(mut self) -> Self {
+        self.cache_only = true;
+        self
+    }
+
+    /// Save mutating requests that cannot reach the backend to `queue`
+    ///
+    /// Queued requests are sent again by [`Self::replay_offline_queue`].
+    #[must_use]
+    pub fn with_offline_queue(mut self, queue: OfflineQueue) -> Self {
+        self.offline_queue = Some(Arc::new(queue));
+        self
+    }
+
+    /// Get the offline queue, if enabled
+    #[must_use]
+    pub fn offline_queue(&self) -> Option<&OfflineQueue> {
+        self.offline_queue.as_deref()
+    }
+
+    /// Persist sessions in `store`, restoring any session already saved there
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stored session cannot be read.
+    pub fn with_session_store(mut self, store: SessionStore) -> ApiResult<Self> {
+        if let Some(session) = store.load()? {
+            *self.session.write().unwrap_or_else(PoisonError::into_inner) = Some(session);
+        }
+        self.session_store = Some(store);
+        Ok(self)
+    }
+
     /// Get the current configuration
     #[must_use]
     pub fn config(&self) -> &ClientConfig {
@@ -101,20 +226,192 @@ impl FoodshareClient {
         self.circuit_breaker.state()
     }
 
-    /// Reset the circuit breaker
+    /// Get the state of the circuit breaker guarding an endpoint
+    ///
+    /// This is the shared breaker unless the endpoint has its own.
+    #[must_use]
+    pub fn endpoint_circuit_state(&self, endpoint: &str) -> CircuitState {
+        self.circuit_breaker_for(endpoint).state()
+    }
+
+    /// Reset the circuit breaker, including per-endpoint breakers
     pub fn reset_circuit(&self) {
         self.circuit_breaker.reset();
+        for guards in self.endpoint_guards.values() {
+            if let Some(ref breaker) = guards.circuit_breaker {
+                breaker.reset();
+            }
+        }
     }
 
     /// Reset rate limits for a specific endpoint
     pub fn reset_rate_limit(&self, endpoint: &str) {
-        self.rate_limiter.reset(endpoint);
+        self.rate_limiter_for(endpoint).reset(endpoint);
+    }
+
+    /// Endpoint name for a URL: the first path segment after the base URL
+    fn endpoint_name(&self, url: &str) -> String {
+        let base = self.config.base_url.trim_end_matches('/');
+        url.strip_prefix(base)
+            .and_then(|rest| rest.strip_prefix('/'))
+            .and_then(|rest| rest.split(['/', '?']).next())
+            .filter(|name| !name.is_empty())
+            .map_or_else(|| extract_rate_limit_key(url), str::to_string)
+    }
+
+    /// Count a request the client refused to send
+    fn record_rejection(&self, endpoint: &str, reason: &str) {
+        let environment = self.config.environment.as_str();
+        foodshare_telemetry::metrics().increment(&format!("api.{environment}.{endpoint}.{reason}"));
+    }
+
+    fn rate_limiter_for(&self, endpoint: &str) -> &RateLimiter {
+        self.endpoint_guards
+            .get(endpoint)
+            .and_then(|g| g.rate_limiter.as_ref())
+            .unwrap_or(&self.rate_limiter)
+    }
+
+    fn circuit_breaker_for(&self, endpoint: &str) -> &CircuitBreaker {
+        self.endpoint_guards
+            .get(endpoint)
+            .and_then(|g| g.circuit_breaker.as_ref())
+            .unwrap_or(&self.circuit_breaker)
+    }
+
+    // -------------------------------------------------------------------------
+    // Session management
+    // -------------------------------------------------------------------------
+
+    /// Get the current session, if signed in
+    #[must_use]
+    pub fn session(&self) -> Option<Session> {
+        self.session
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Replace the current session, persisting it if a store is configured
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session cannot be persisted.
+    pub fn set_session(&self, session: Session) -> ApiResult<()> {
+        if let Some(ref store) = self.session_store {
+            store.save(&session)?;
+        }
+        *self.session.write().unwrap_or_else(PoisonError::into_inner) = Some(session);
+        Ok(())
+    }
+
+    /// Drop the current session and remove it from the store
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stored session cannot be removed.
+    pub fn clear_session(&self) -> ApiResult<()> {
+        *self.session.write().unwrap_or_else(PoisonError::into_inner) = None;
+        if let Some(ref store) = self.session_store {
+            store.clear()?;
+        }
+        Ok(())
+    }
+
+    /// Bearer token for requests: the session token, else the service role key
+    fn bearer_token(&self) -> Option<String> {
+        self.session()
+            .map(|s| s.access_token)
+            .or_else(|| self.config.service_role_key.clone())
+    }
+
+    /// Refresh the session unless another task already replaced `stale_token`
+    ///
+    /// Concurrent 401s share a single refresh: callers pass the token that was
+    /// rejected, and only the first one through exchanges the refresh token.
+    pub(crate) async fn refresh_session(&self, stale_token: Option<&str>) -> ApiResult<Session> {
+        let _guard = self.refresh_lock.lock().await;
+
+        let current = self
+            .session()
+            .ok_or_else(|| ApiError::auth("not signed in"))?;
+        if stale_token.is_some_and(|t| t != current.access_token) {
+            return Ok(current);
+        }
+
+        let refresh_token = current
+            .refresh_token
+            .ok_or_else(|| ApiError::auth("session has no refresh token"))?;
+
+        debug!("Refreshing session");
+        let body = serde_json::json!({ "refresh_token": refresh_token });
+        let token: TokenResponse = self
+            .auth_json(Method::POST, "token?grant_type=refresh_token", &body)
+            .await?;
+
+        let session = Session::from(token);
+        self.set_session(session.clone())?;
+        Ok(session)
+    }
+
+    /// Send a request to the Auth API and decode the JSON response
+    pub(crate) async fn auth_json<T: DeserializeOwned, B: Serialize>(
+        &self,
+        method: Method,
+        path: &str,
+        body: &B,
+    ) -> ApiResult<T> {
+        let response = self.auth_send(method, path, Some(body), None).await?;
+        response.json().await.map_err(ApiError::Request)
+    }
+
+    /// Send a request to the Auth API, failing on non-success status
+    ///
+    /// Auth calls bypass retry and 401 handling so a rejected refresh cannot
+    /// recurse.
+    pub(crate) async fn auth_send<B: Serialize>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&B>,
+        bearer: Option<&str>,
+    ) -> ApiResult<Response> {
+        let url = format!("{}/{}", self.config.resolved_auth_url(), path);
+        let mut request = self
+            .inner
+            .request(method, &url)
+            .header(X_REQUEST_ID, Uuid::new_v4().to_string());
+
+        if let Some(token) = bearer {
+            request = request.bearer_auth(token);
+        }
+        if let Some(b) = body {
+            request = request.json(b);
+        }
+
+        let response = self.dispatch(request).await?;
+        let status = response.status();
+        if status.is_success() {
+            Ok(response)
+        } else {
+            let message = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            Err(ApiError::api_response(status.as_u16(), message))
+        }
     }
 
     // -------------------------------------------------------------------------
     // Endpoint API accessors
     // -------------------------------------------------------------------------
 
+    /// Access authentication endpoints
+    #[must_use]
+    pub fn auth(&self) -> AuthApi {
+        AuthApi::new(self.clone())
+    }
+
     /// Access translation endpoints
     #[must_use]
     pub fn translations(&self) -> TranslationsApi {
@@ -145,6 +442,12 @@ impl FoodshareClient {
         ProductsApi::new(self.clone())
     }
 
+    /// Get storage API interface
+    #[must_use]
+    pub fn storage(&self) -> StorageApi {
+        StorageApi::new(self.clone())
+    }
+
     // -------------------------------------------------------------------------
     // Low-level HTTP methods with resilience
     // -------------------------------------------------------------------------
@@ -204,49 +507,214 @@ impl FoodshareClient {
     }
 
     /// Execute a request to an absolute URL with full resilience patterns
+    ///
+    /// Mutating requests carry an idempotency key and, if the offline queue is
+    /// enabled, are queued when the backend is unreachable.
     async fn request_url<T: DeserializeOwned, B: Serialize>(
         &self,
         method: Method,
         url: &str,
         body: Option<&B>,
+    ) -> ApiResult<T> {
+        let idempotency_key = is_mutating(&method).then(|| Uuid::new_v4().to_string());
+        let result = self
+            .send(method.clone(), url, body, idempotency_key.as_deref())
+            .await;
+
+        match (result, &self.offline_queue, idempotency_key) {
+            (Err(e), Some(queue), Some(key)) if e.is_connectivity_error() => {
+                let body = body.map(serde_json::to_value).transpose()?;
+                queue.push(QueuedRequest::new(&key, method.as_str(), url, body))?;
+                warn!(
+                    url = %url,
+                    idempotency_key = %key,
+                    error = %e,
+                    "Backend unreachable, request queued for replay"
+                );
+                Err(ApiError::Queued {
+                    idempotency_key: key,
+                })
+            }
+            (result, ..) => result,
+        }
+    }
+
+    /// Replay queued requests in order
+    ///
+    /// Stops at the first request that still cannot reach the backend (or is
+    /// rate limited), leaving it and the rest queued. Requests the API rejects
+    /// with a retryable error stay queued; other rejections are dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the queue file cannot be updated.
+    pub async fn replay_offline_queue(&self) -> ApiResult<ReplayReport> {
+        let mut report = ReplayReport::default();
+        let Some(queue) = self.offline_queue.clone() else {
+            return Ok(report);
+        };
+
+        for entry in queue.entries() {
+            // Another project's request would go out with this client's credentials
+            if !entry.is_for(self.config.project_url()) {
+                warn!(url = %entry.url, "Skipping queued request for another project");
+                continue;
+            }
+
+            let Ok(method) = Method::from_bytes(entry.method.as_bytes()) else {
+                warn!(method = %entry.method, "Dropping queued request with invalid method");
+                queue.remove(&entry.idempotency_key)?;
+                report.dropped += 1;
+                continue;
+            };
+
+            let result = self
+                .send::<serde::de::IgnoredAny, _>(
+                    method,
+                    &entry.url,
+                    entry.body.as_ref(),
+                    Some(&entry.idempotency_key),
+                )
+                .await;
+
+            match result {
+                Ok(_) => {
+                    queue.remove(&entry.idempotency_key)?;
+                    report.replayed += 1;
+                }
+                Err(e) if e.is_connectivity_error() || matches!(e, ApiError::RateLimited) => {
+                    debug!(error = %e, "Backend still unavailable, stopping replay");
+                    break;
+                }
+                Err(ApiError::ProductionWriteBlocked { .. }) => {
+                    // Every queued request is a write, so none would go through
+                    debug!("Production writes are blocked, stopping replay");
+                    break;
+                }
+                Err(e) if e.is_retryable() => {
+                    debug!(url = %entry.url, error = %e, "Replay failed, keeping request queued");
+                }
+                Err(e) => {
+                    warn!(url = %entry.url, error = %e, "Replay rejected, dropping queued request");
+                    queue.remove(&entry.idempotency_key)?;
+                    report.dropped += 1;
+                }
+            }
+        }
+
+        report.remaining = queue.len();
+        Ok(report)
+    }
+
+    /// Send a request through the circuit breaker, rate limiter, retry and session refresh
+    async fn send<T: DeserializeOwned, B: Serialize>(
+        &self,
+        method: Method,
+        url: &str,
+        body: Option<&B>,
+        idempotency_key: Option<&str>,
     ) -> ApiResult<T> {
         let request_id = Uuid::new_v4().to_string();
-        let rate_limit_key = extract_rate_limit_key(url);
+        let endpoint = self.endpoint_name(url);
+
+        self.check_production_guard(&method, url)?;
+
+        if self.cache_only {
+            let entry = self.cached_only(&method, url)?;
+            return Ok(serde_json::from_str(&entry.body)?);
+        }
 
         // Check circuit breaker
-        if !self.circuit_breaker.can_execute() {
+        if !self.circuit_breaker_for(&endpoint).can_execute() {
             warn!(
                 request_id = %request_id,
                 url = %url,
                 "Circuit breaker is open, rejecting request"
             );
+            self.record_rejection(&endpoint, "circuit_open");
             return Err(ApiError::CircuitOpen);
         }
 
-        // Check rate limiter
-        if !self.rate_limiter.try_acquire(&rate_limit_key) {
-            warn!(
-                request_id = %request_id,
-                url = %url,
-                "Rate limited"
-            );
-            return Err(ApiError::RateLimited);
+        // Check rate limiter (batches wait for a token instead)
+        let rate_limiter = self.rate_limiter_for(&endpoint);
+        if !rate_limiter.try_acquire(&endpoint) {
+            self.record_rejection(&endpoint, "rate_limited");
+            if !WAIT_FOR_RATE_LIMIT.try_with(|wait| *wait).unwrap_or(false) {
+                warn!(
+                    request_id = %request_id,
+                    url = %url,
+                    "Rate limited"
+                );
+                return Err(ApiError::RateLimited);
+            }
+
+            debug!(request_id = %request_id, endpoint = %endpoint, "Rate limited, waiting in batch");
+            while !rate_limiter.try_acquire(&endpoint) {
+                let wait = rate_limiter.time_until_available(&endpoint, 1);
+                tokio::time::sleep(wait.max(MIN_RATE_LIMIT_WAIT)).await;
+            }
+        }
+
+        // Renew an expired session up front rather than waiting for a 401
+        if let Some(session) = self.session().filter(|s| s.is_expired() && s.can_refresh()) {
+            self.refresh_session(Some(&session.access_token)).await?;
         }
 
         // Execute with retry
-        self.execute_with_retry(&request_id, method, url, body)
-            .await
+        let token = self.bearer_token();
+        let result = self
+            .execute_with_retry(&request_id, &endpoint, method.clone(), url, body, idempotency_key)
+            .await;
+
+        // Retry once with a refreshed token if the API rejected the session
+        match result {
+            Err(e) if e.is_unauthorized() && self.session().is_some_and(|s| s.can_refresh()) => {
+                debug!(request_id = %request_id, "Unauthorized, refreshing session");
+                self.refresh_session(token.as_deref()).await?;
+                self.execute_with_retry(&request_id, &endpoint, method, url, body, idempotency_key)
+                    .await
+            }
+            other => other,
+        }
+    }
+
+    /// The cached response for a request in cache-only mode
+    fn cached_only(&self, method: &Method, url: &str) -> ApiResult<CachedResponse> {
+        let token = self.bearer_token();
+        self.http_cache
+            .as_deref()
+            .filter(|_| *method == Method::GET)
+            .and_then(|cache| cache.lookup(url, token.as_deref()))
+            .ok_or_else(|| ApiError::NotCached {
+                method: method.to_string(),
+                url: url.to_string(),
+            })
+    }
+
+    /// Refuse a mutating request if the production guard is enabled
+    fn check_production_guard(&self, method: &Method, url: &str) -> ApiResult<()> {
+        if is_mutating(method) && self.config.production_guard && self.config.environment.is_production() {
+            warn!(method = %method, url = %url, "Refusing unconfirmed write to production");
+            return Err(ApiError::ProductionWriteBlocked {
+                method: method.to_string(),
+                url: url.to_string(),
+            });
+        }
+        Ok(())
     }
 
     /// Execute request with retry logic
     async fn execute_with_retry<T: DeserializeOwned, B: Serialize>(
         &self,
         request_id: &str,
+        endpoint: &str,
         method: Method,
         url: &str,
         body: Option<&B>,
+        idempotency_key: Option<&str>,
     ) -> ApiResult<T> {
         let retry_config = &self.config.retry;
+        let circuit_breaker = self.circuit_breaker_for(endpoint);
         let mut last_error: Option<ApiError> = None;
 
         for attempt in 0..retry_config.max_attempts {
@@ -264,13 +732,13 @@ impl FoodshareClient {
 
             let start = Instant::now();
             let result = self
-                .execute_single_request(request_id, method.clone(), url, body)
+                .execute_single_request(request_id, endpoint, method.clone(), url, body, idempotency_key)
                 .await;
             let elapsed = start.elapsed();
 
             match result {
                 Ok(value) => {
-                    self.circuit_breaker.record_success();
+                    circuit_breaker.record_success();
                     debug!(
                         request_id = %request_id,
                         attempt = attempt + 1,
@@ -280,7 +748,7 @@ impl FoodshareClient {
                     return Ok(value);
                 }
                 Err(e) => {
-                    self.circuit_breaker.record_failure();
+                    circuit_breaker.record_failure();
 
                     if e.is_retryable() && attempt + 1 < retry_config.max_attempts {
                         debug!(
@@ -313,26 +781,91 @@ impl FoodshareClient {
     async fn execute_single_request<T: DeserializeOwned, B: Serialize>(
         &self,
         request_id: &str,
+        endpoint: &str,
         method: Method,
         url: &str,
         body: Option<&B>,
+        idempotency_key: Option<&str>,
     ) -> ApiResult<T> {
+        let cache = self.http_cache.as_deref().filter(|_| method == Method::GET);
         let mut request = self
             .inner
             .request(method, url)
-            .header(X_REQUEST_ID, request_id);
+            .header(X_REQUEST_ID, request_id)
+            .timeout(self.config.timeout_for(endpoint));
 
-        // Add auth header if service role key is set
-        if let Some(ref key) = self.config.service_role_key {
-            request = request.header(AUTHORIZATION, format!("Bearer {key}"));
+        if let Some(key) = idempotency_key {
+            request = request.header(IDEMPOTENCY_KEY_HEADER, key);
+        }
+
+        // Add auth header from the session or service role key
+        let token = self.bearer_token();
+        if let Some(ref token) = token {
+            request = request.header(AUTHORIZATION, format!("Bearer {token}"));
         }
 
         if let Some(b) = body {
             request = request.json(b);
         }
 
-        let response = request.send().await?;
-        self.handle_response(response).await
+        let Some(cache) = cache else {
+            let response = self.dispatch(request).await?;
+            return self.handle_response(response).await;
+        };
+
+        let cached = cache.lookup(url, token.as_deref());
+        if let Some(ref entry) = cached {
+            request = entry.apply_validators(request);
+        }
+        let response = self.dispatch(request).await?;
+        self.handle_cacheable_response(cache, url, token.as_deref(), cached, response)
+            .await
+    }
+
+    /// Build a request and send it through the middleware stack and transport
+    async fn dispatch(&self, request: RequestBuilder) -> ApiResult<Response> {
+        let mut request = request.build()?;
+        let info = RequestInfo::new(&request, self.endpoint_name(request.url().as_str()));
+
+        let result = match self.middleware.iter().try_for_each(|m| m.on_request(&mut request)) {
+            Ok(()) => self.transport.execute(request).await.and_then(|response| {
+                self.middleware
+                    .iter()
+                    .rev()
+                    .try_for_each(|m| m.on_response(&info, &response))?;
+                Ok(response)
+            }),
+            Err(e) => Err(e),
+        };
+
+        result.map_err(|e| self.middleware.iter().rev().fold(e, |e, m| m.on_error(&info, e)))
+    }
+
+    /// Handle a GET response, answering 304 from the cache and storing fresh bodies
+    async fn handle_cacheable_response<T: DeserializeOwned>(
+        &self,
+        cache: &HttpCache,
+        url: &str,
+        credential: Option<&str>,
+        cached: Option<CachedResponse>,
+        response: Response,
+    ) -> ApiResult<T> {
+        let status = response.status();
+
+        match cached {
+            Some(entry) if status == reqwest::StatusCode::NOT_MODIFIED => {
+                debug!(url = %url, "Not modified, using cached response");
+                Ok(serde_json::from_str(&entry.body)?)
+            }
+            _ if status.is_success() => {
+                let headers = response.headers().clone();
+                let bytes = response.bytes().await?;
+                let value = serde_json::from_slice(&bytes)?;
+                cache.store(url, credential, &headers, &bytes);
+                Ok(value)
+            }
+            _ => self.handle_response(response).await,
+        }
     }
 
     /// Handle HTTP response and deserialize
@@ -354,12 +887,19 @@ impl FoodshareClient {
     pub async fn execute_raw(&self, request: RequestBuilder) -> ApiResult<Response> {
         let request_id = Uuid::new_v4().to_string();
 
+        let (client, request) = request.build_split();
+        let request = request?;
+        self.check_production_guard(request.method(), request.url().as_str())?;
+        let request = RequestBuilder::from_parts(client, request);
+
         // Check circuit breaker
         if !self.circuit_breaker.can_execute() {
             return Err(ApiError::CircuitOpen);
         }
 
-        let response = request.header(X_REQUEST_ID, &request_id).send().await?;
+        let response = self
+            .dispatch(request.header(X_REQUEST_ID, &request_id))
+            .await?;
 
         if response.status().is_success() || response.status().as_u16() == 304 {
             self.circuit_breaker.record_success();
@@ -370,6 +910,226 @@ impl FoodshareClient {
         Ok(response)
     }
 
+    /// Download a response body, reporting progress as chunks arrive
+    ///
+    /// Goes through the circuit breaker, middleware, transport and HTTP
+    /// cache, but not the retry loop.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or the API returns an error status.
+    pub async fn download(
+        &self,
+        path: &str,
+        on_progress: impl Fn(TransferProgress),
+    ) -> ApiResult<Vec<u8>> {
+        let url = format!("{}/{}", self.config.base_url.trim_end_matches('/'), path);
+        self.download_url(&url, on_progress).await
+    }
+
+    /// Download from an absolute URL, reporting progress as chunks arrive
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or the API returns an error status.
+    pub async fn download_url(
+        &self,
+        url: &str,
+        on_progress: impl Fn(TransferProgress),
+    ) -> ApiResult<Vec<u8>> {
+        if self.cache_only {
+            let entry = self.cached_only(&Method::GET, url)?;
+            return Ok(entry.body.into_bytes());
+        }
+
+        let endpoint = self.endpoint_name(url);
+        let mut request = self
+            .inner
+            .get(url)
+            .timeout(self.config.timeout_for(&endpoint));
+        let token = self.bearer_token();
+        if let Some(ref token) = token {
+            request = request.header(AUTHORIZATION, format!("Bearer {token}"));
+        }
+        let cache = self.http_cache.as_deref();
+        let cached = cache.and_then(|cache| cache.lookup(url, token.as_deref()));
+        if let Some(ref entry) = cached {
+            request = entry.apply_validators(request);
+        }
+
+        let mut response = self.execute_raw(request).await?;
+        let status = response.status();
+        if let Some(entry) = cached.filter(|_| status == reqwest::StatusCode::NOT_MODIFIED) {
+            debug!(url = %url, "Not modified, using cached download");
+            let total = Some(entry.body.len() as u64);
+            on_progress(TransferProgress {
+                transferred: entry.body.len() as u64,
+                total,
+            });
+            return Ok(entry.body.into_bytes());
+        }
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_default();
+            return Err(ApiError::api_response(status.as_u16(), message));
+        }
+
+        let total = response.content_length();
+        let capacity = total.and_then(|t| usize::try_from(t).ok()).unwrap_or(0);
+        let mut body = Vec::with_capacity(capacity);
+        on_progress(TransferProgress {
+            transferred: 0,
+            total,
+        });
+        let headers = response.headers().clone();
+        while let Some(chunk) = response.chunk().await? {
+            body.extend_from_slice(&chunk);
+            on_progress(TransferProgress {
+                transferred: body.len() as u64,
+                total,
+            });
+        }
+        if let Some(cache) = cache {
+            cache.store(url, token.as_deref(), &headers, &body);
+        }
+        Ok(body)
+    }
+
+    /// Upload `data` to `path` with a POST, reporting progress as chunks are sent
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails, the API returns an error status
+    /// or the production guard refuses the write.
+    pub async fn upload<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        content_type: &str,
+        data: Vec<u8>,
+        on_progress: impl Fn(TransferProgress) + Send + Sync + 'static,
+    ) -> ApiResult<T> {
+        let url = format!("{}/{}", self.config.base_url.trim_end_matches('/'), path);
+        self.upload_url(Method::POST, &url, content_type, data, on_progress)
+            .await
+    }
+
+    /// Upload `data` to an absolute URL, reporting progress as chunks are sent
+    ///
+    /// The body is streamed, so uploads are not retried; the offline queue
+    /// does not apply either.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails, the API returns an error status
+    /// or the production guard refuses the write.
+    pub async fn upload_url<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        url: &str,
+        content_type: &str,
+        data: Vec<u8>,
+        on_progress: impl Fn(TransferProgress) + Send + Sync + 'static,
+    ) -> ApiResult<T> {
+        self.upload_url_with_headers(method, url, content_type, &[], data, on_progress)
+            .await
+    }
+
+    /// [`Self::upload_url`] with extra request headers
+    pub(crate) async fn upload_url_with_headers<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        url: &str,
+        content_type: &str,
+        headers: &[(&'static str, &str)],
+        data: Vec<u8>,
+        on_progress: impl Fn(TransferProgress) + Send + Sync + 'static,
+    ) -> ApiResult<T> {
+        self.check_production_guard(&method, url)?;
+
+        let endpoint = self.endpoint_name(url);
+        let mut request = self
+            .inner
+            .request(method, url)
+            .timeout(self.config.timeout_for(&endpoint))
+            .header(CONTENT_TYPE, content_type)
+            .header(CONTENT_LENGTH, data.len())
+            .body(progress::upload_body(data, Arc::new(on_progress)));
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        if let Some(token) = self.bearer_token() {
+            request = request.header(AUTHORIZATION, format!("Bearer {token}"));
+        }
+
+        let response = self.execute_raw(request).await?;
+        self.handle_response(response).await
+    }
+
+    /// Run `f` over `items` with at most `concurrency` calls in flight
+    ///
+    /// Results are returned in the order of `items`, one per item, so a
+    /// failure does not affect the others. Requests made inside `f` share this
+    /// client's rate limiter, and wait for it rather than failing with
+    /// [`ApiError::RateLimited`]. A `concurrency` of 0 is treated as 1.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use foodshare_api_client::FoodshareClient;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = FoodshareClient::new()?;
+    /// let locales = ["de", "fr", "es"];
+    ///
+    /// let results = client
+    ///     .batch(locales, 2, |locale| {
+    ///         let client = client.clone();
+    ///         async move { client.translations().get(locale).await }
+    ///     })
+    ///     .await;
+    ///
+    /// for (locale, result) in locales.iter().zip(results) {
+    ///     println!("{locale}: {}", if result.is_ok() { "ok" } else { "failed" });
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn batch<I, F, Fut>(&self, items: I, concurrency: usize, f: F) -> Vec<Fut::Output>
+    where
+        I: IntoIterator,
+        F: FnMut(I::Item) -> Fut,
+        Fut: Future,
+    {
+        stream::iter(items)
+            .map(f)
+            .map(|request| WAIT_FOR_RATE_LIMIT.scope(true, request))
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// Run `f` over `items` like [`batch`](Self::batch), yielding each
+    /// output as soon as it is ready
+    ///
+    /// Outputs arrive in completion order, so `f` should return whatever is
+    /// needed to tell them apart. Suits long runs that record progress as
+    /// they go rather than once at the end.
+    pub fn batch_unordered<I, F, Fut>(
+        &self,
+        items: I,
+        concurrency: usize,
+        f: F,
+    ) -> impl Stream<Item = Fut::Output>
+    where
+        I: IntoIterator,
+        F: FnMut(I::Item) -> Fut,
+        Fut: Future,
+    {
+        stream::iter(items)
+            .map(f)
+            .map(|request| WAIT_FOR_RATE_LIMIT.scope(true, request))
+            .buffer_unordered(concurrency.max(1))
+    }
+
     /// Get duration timing for a request
     pub async fn timed_get<T: DeserializeOwned>(&self, path: &str) -> ApiResult<(T, Duration)> {
         let start = Instant::now();
@@ -388,6 +1148,17 @@ impl FoodshareClient {
     }
 }
 
+/// Rate limiter and circuit breaker dedicated to one endpoint
+struct EndpointGuards {
+    rate_limiter: Option<RateLimiter>,
+    circuit_breaker: Option<CircuitBreaker>,
+}
+
+/// Check if a method changes server state (and so needs an idempotency key)
+fn is_mutating(method: &Method) -> bool {
+    matches!(*method, Method::POST | Method::PUT | Method::PATCH | Method::DELETE)
+}
+
 /// Extract a rate limit key from a URL (uses the path)
 fn extract_rate_limit_key(url: &str) -> String {
     url.split('?')
@@ -420,4 +1191,600 @@ mod tests {
         let client = FoodshareClient::with_config(config);
         assert!(client.is_ok());
     }
+
+    /// Serve canned responses in order, recording "<request line> <authorization>[ key=<idempotency key>]" per request
+    async fn serve(responses: Vec<(u16, &'static str)>) -> (String, Arc<std::sync::Mutex<Vec<String>>>) {
+        serve_with_headers(responses.into_iter().map(|(status, body)| (status, "", body)).collect()).await
+    }
+
+    /// Like [`serve`], with extra header lines per response; also records `If-None-Match` as " inm=<etag>"
+    async fn serve_with_headers(
+        responses: Vec<(u16, &'static str, &'static str)>,
+    ) -> (String, Arc<std::sync::Mutex<Vec<String>>>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let requests = Arc::clone(&log);
+
+        tokio::spawn(async move {
+            for (status, headers, body) in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0u8; 8192];
+                let n = stream.read(&mut buf).await.unwrap();
+                let head = String::from_utf8_lossy(&buf[..n]).to_string();
+                let line = head.lines().next().unwrap_or_default().to_string();
+                let auth = head
+                    .lines()
+                    .find_map(|l| l.strip_prefix("authorization: "))
+                    .unwrap_or("-")
+                    .to_string();
+                let key = head
+                    .lines()
+                    .find_map(|l| l.strip_prefix("idempotency-key: "))
+                    .map(|k| format!(" key={k}"))
+                    .unwrap_or_default();
+                let inm = head
+                    .lines()
+                    .find_map(|l| l.strip_prefix("if-none-match: "))
+                    .map(|v| format!(" inm={v}"))
+                    .unwrap_or_default();
+                requests.lock().unwrap().push(format!("{line} {auth}{key}{inm}"));
+
+                let response = format!(
+                    "HTTP/1.1 {status} X\r\ncontent-type: application/json\r\n{headers}content-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        (url, log)
+    }
+
+    #[tokio::test]
+    async fn test_refreshes_and_retries_on_unauthorized() {
+        use foodshare_core::keychain::MemoryKeychain;
+
+        let (url, log) = serve(vec![
+            (401, r#"{"message":"JWT expired"}"#),
+            (200, r#"{"access_token":"new","refresh_token":"r2","expires_in":3600}"#),
+            (200, r#"{"ok":true}"#),
+        ])
+        .await;
+
+        let store = SessionStore::new(Arc::new(MemoryKeychain::new()), "test");
+        let client = FoodshareClient::with_config(
+            ClientConfig::development().with_base_url(format!("{url}/functions/v1")),
+        )
+        .unwrap()
+        .with_session_store(store.clone())
+        .unwrap();
+        client
+            .set_session(Session {
+                refresh_token: Some("r1".to_string()),
+                ..Session::service_key("old")
+            })
+            .unwrap();
+
+        let value: serde_json::Value = client.get("thing").await.unwrap();
+        assert_eq!(value["ok"], true);
+
+        let log = log.lock().unwrap().clone();
+        assert_eq!(
+            log,
+            [
+                "GET /functions/v1/thing HTTP/1.1 Bearer old",
+                "POST /auth/v1/token?grant_type=refresh_token HTTP/1.1 -",
+                "GET /functions/v1/thing HTTP/1.1 Bearer new",
+            ]
+        );
+        assert_eq!(store.load().unwrap().unwrap().access_token, "new");
+    }
+
+    #[tokio::test]
+    async fn test_unauthorized_without_refresh_token_is_returned() {
+        let (url, _log) = serve(vec![(401, r#"{"message":"invalid"}"#)]).await;
+        let client = FoodshareClient::with_config(
+            ClientConfig::development().with_base_url(format!("{url}/functions/v1")),
+        )
+        .unwrap();
+        client.auth().sign_in_with_service_key("key").unwrap();
+
+        let err = client.get::<serde_json::Value>("thing").await.unwrap_err();
+        assert!(err.is_unauthorized());
+    }
+
+    #[tokio::test]
+    async fn test_queues_mutations_when_unreachable() {
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let temp = tempfile::TempDir::new().unwrap();
+        let queue = OfflineQueue::open(temp.path().join("queue.jsonl")).unwrap();
+        let client = FoodshareClient::with_config(
+            ClientConfig::development().with_base_url(format!("http://{addr}/functions/v1")),
+        )
+        .unwrap()
+        .with_offline_queue(queue);
+
+        let err = client
+            .post::<serde_json::Value, _>("translate", &serde_json::json!({"id": 1}))
+            .await
+            .unwrap_err();
+        let ApiError::Queued { idempotency_key } = err else {
+            panic!("expected queued, got {err}");
+        };
+
+        let entries = OfflineQueue::open(temp.path().join("queue.jsonl"))
+            .unwrap()
+            .entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].idempotency_key, idempotency_key);
+        assert_eq!(entries[0].body, Some(serde_json::json!({"id": 1})));
+
+        // Reads are never queued
+        let err = client.get::<serde_json::Value>("thing").await.unwrap_err();
+        assert!(err.is_connectivity_error());
+        assert_eq!(client.offline_queue().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_replays_with_idempotency_key() {
+        let (url, log) = serve(vec![(200, "{}"), (400, r#"{"error":"bad"}"#)]).await;
+        let temp = tempfile::TempDir::new().unwrap();
+        let queue = OfflineQueue::open(temp.path().join("queue.jsonl")).unwrap();
+        queue
+            .push(QueuedRequest::new("k1", "POST", format!("{url}/a"), Some(serde_json::json!({}))))
+            .unwrap();
+        queue
+            .push(QueuedRequest::new("k2", "DELETE", format!("{url}/b"), None))
+            .unwrap();
+
+        let client = FoodshareClient::with_config(
+            ClientConfig::development().with_base_url(format!("{url}/functions/v1")),
+        )
+        .unwrap()
+        .with_offline_queue(queue);
+        let report = client.replay_offline_queue().await.unwrap();
+
+        assert_eq!(
+            report,
+            ReplayReport {
+                replayed: 1,
+                dropped: 1,
+                remaining: 0
+            }
+        );
+        assert_eq!(
+            log.lock().unwrap().clone(),
+            ["POST /a HTTP/1.1 - key=k1", "DELETE /b HTTP/1.1 - key=k2"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_replay_skips_other_projects() {
+        use crate::transport::{MockResponse, MockRoute, MockTransport};
+
+        let temp = tempfile::TempDir::new().unwrap();
+        let queue = OfflineQueue::open(temp.path().join("queue.jsonl")).unwrap();
+        let staging = ClientConfig::staging().with_base_url("https://staging.example.com/functions/v1");
+        let production = ClientConfig::production().with_base_url("https://example.com/functions/v1");
+        queue
+            .push(QueuedRequest::new("k1", "POST", format!("{}/posts", staging.base_url), None))
+            .unwrap();
+        queue
+            .push(QueuedRequest::new("k2", "POST", format!("{}/posts", production.base_url), None))
+            .unwrap();
+
+        let mock = Arc::new(
+            MockTransport::new().with_route(MockRoute::new(Method::POST, "posts", MockResponse::json(201, &1))),
+        );
+        let client = FoodshareClient::with_config(production)
+            .unwrap()
+            .with_transport(mock.clone())
+            .with_offline_queue(queue);
+        let report = client.replay_offline_queue().await.unwrap();
+
+        assert_eq!(
+            report,
+            ReplayReport {
+                replayed: 1,
+                dropped: 0,
+                remaining: 1
+            }
+        );
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].headers["idempotency-key"], "k2");
+        assert_eq!(client.offline_queue().unwrap().entries()[0].idempotency_key, "k1");
+    }
+
+    #[tokio::test]
+    async fn test_replay_keeps_requests_blocked_by_production_guard() {
+        use crate::transport::MockTransport;
+
+        let temp = tempfile::TempDir::new().unwrap();
+        let queue = OfflineQueue::open(temp.path().join("queue.jsonl")).unwrap();
+        let url = format!("{}/a", ClientConfig::production().base_url);
+        queue.push(QueuedRequest::new("k1", "POST", url.clone(), None)).unwrap();
+        queue.push(QueuedRequest::new("k2", "DELETE", url, None)).unwrap();
+
+        let mock = Arc::new(MockTransport::new());
+        let client = FoodshareClient::with_config(ClientConfig::production().with_production_guard(true))
+            .unwrap()
+            .with_transport(mock.clone())
+            .with_offline_queue(queue);
+        let report = client.replay_offline_queue().await.unwrap();
+
+        assert_eq!(
+            report,
+            ReplayReport {
+                replayed: 0,
+                dropped: 0,
+                remaining: 2
+            }
+        );
+        assert!(mock.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_http_cache_revalidates() {
+        use foodshare_core::cache::CacheConfig;
+
+        let (url, log) = serve_with_headers(vec![
+            (200, "", r#"{"v":1}"#),
+            (304, "", ""),
+            (200, "etag: \"abc\"\r\n", r#"{"v":2}"#),
+        ])
+        .await;
+        let temp = tempfile::TempDir::new().unwrap();
+        let cache = HttpCache::new(CacheConfig {
+            cache_dir: temp.path().to_path_buf(),
+            ..CacheConfig::default()
+        })
+        .unwrap();
+        let client = FoodshareClient::with_config(
+            ClientConfig::development().with_base_url(format!("{url}/functions/v1")),
+        )
+        .unwrap()
+        .with_http_cache(cache);
+
+        for expected in [1, 1, 2] {
+            let value: serde_json::Value = client.get("bundle").await.unwrap();
+            assert_eq!(value["v"], expected);
+        }
+
+        let derived = foodshare_compression::generate_etag(br#"{"v":1}"#);
+        assert_eq!(
+            log.lock().unwrap().clone(),
+            [
+                "GET /functions/v1/bundle HTTP/1.1 -".to_string(),
+                format!("GET /functions/v1/bundle HTTP/1.1 - inm={derived}"),
+                format!("GET /functions/v1/bundle HTTP/1.1 - inm={derived}"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cache_only_answers_from_cache() {
+        use foodshare_core::cache::CacheConfig;
+
+        let (url, log) = serve(vec![(200, r#"{"v":1}"#)]).await;
+        let temp = tempfile::TempDir::new().unwrap();
+        let open_cache = || {
+            HttpCache::new(CacheConfig {
+                cache_dir: temp.path().to_path_buf(),
+                ..CacheConfig::default()
+            })
+            .unwrap()
+        };
+        let config = ClientConfig::development().with_base_url(format!("{url}/functions/v1"));
+
+        let online = FoodshareClient::with_config(config.clone())
+            .unwrap()
+            .with_http_cache(open_cache());
+        let body = online.download("bundle", |_| {}).await.unwrap();
+        assert_eq!(body, br#"{"v":1}"#);
+
+        let offline = FoodshareClient::with_config(config)
+            .unwrap()
+            .with_http_cache(open_cache())
+            .with_cache_only();
+        let value: serde_json::Value = offline.get("bundle").await.unwrap();
+        assert_eq!(value["v"], 1);
+        assert!(matches!(
+            offline.get::<serde_json::Value>("other").await,
+            Err(ApiError::NotCached { .. })
+        ));
+        assert_eq!(log.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_mock_transport_fault_is_retried() {
+        use crate::transport::{Fault, MockResponse, MockRoute, MockTransport};
+
+        let mock = Arc::new(
+            MockTransport::new()
+                .with_route(MockRoute::new(Method::GET, "thing", Fault::Status(503)).times(1))
+                .with_route(MockRoute::new(
+                    Method::GET,
+                    "thing",
+                    MockResponse::json(200, &serde_json::json!({"ok": true})),
+                )),
+        );
+        let client = FoodshareClient::with_config(ClientConfig::development())
+            .unwrap()
+            .with_transport(mock.clone());
+
+        let value: serde_json::Value = client.get("thing").await.unwrap();
+        assert_eq!(value["ok"], true);
+        let paths: Vec<_> = mock.requests().into_iter().map(|r| r.path).collect();
+        assert_eq!(paths, ["/functions/v1/thing", "/functions/v1/thing"]);
+    }
+
+    #[tokio::test]
+    async fn test_middleware_hooks() {
+        use crate::middleware::{CorrelationId, TraceContextPropagation};
+        use crate::transport::{MockResponse, MockRoute, MockTransport};
+
+        /// Maps 418 responses to a domain error and counts errors seen
+        struct Teapot(Arc<std::sync::atomic::AtomicUsize>);
+
+        impl Middleware for Teapot {
+            fn on_response(&self, _request: &RequestInfo, response: &Response) -> ApiResult<()> {
+                if response.status().as_u16() == 418 {
+                    return Err(ApiError::config("teapot"));
+                }
+                Ok(())
+            }
+
+            fn on_error(&self, _request: &RequestInfo, error: ApiError) -> ApiError {
+                self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                error
+            }
+        }
+
+        let mock = Arc::new(
+            MockTransport::new()
+                .with_route(MockRoute::new(Method::GET, "tea", MockResponse::empty(418)))
+                .with_route(MockRoute::new(Method::GET, "ok", MockResponse::json(200, &1))),
+        );
+        let errors = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let client = FoodshareClient::with_config(ClientConfig::development())
+            .unwrap()
+            .with_transport(mock.clone())
+            .with_middleware(CorrelationId::with_id("run-1"))
+            .with_middleware(TraceContextPropagation)
+            .with_middleware(Teapot(errors.clone()));
+
+        assert_eq!(client.get::<u32>("ok").await.unwrap(), 1);
+        let err = client.get::<u32>("tea").await.unwrap_err();
+        assert!(matches!(err, ApiError::Config(_)));
+        assert_eq!(errors.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        let headers = &mock.requests()[0].headers;
+        assert_eq!(headers["x-correlation-id"], "run-1");
+        assert!(headers.contains_key("x-request-id"));
+        let traceparent = headers["traceparent"].to_str().unwrap();
+        assert!(foodshare_telemetry::TraceContext::parse(traceparent).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_overrides() {
+        use crate::config::EndpointConfig;
+        use crate::transport::{MockResponse, MockRoute, MockTransport};
+        use foodshare_core::rate_limit::RateLimitConfig;
+
+        let mock = Arc::new(
+            MockTransport::new()
+                .with_route(MockRoute::new(Method::GET, "slow", MockResponse::json(200, &1)))
+                .with_route(MockRoute::new(Method::GET, "fast", MockResponse::json(200, &2))),
+        );
+        let config = ClientConfig::development().with_endpoint(
+            "slow",
+            EndpointConfig::new()
+                .with_rate_limit(RateLimitConfig::strict(1, Duration::from_secs(60)))
+                .with_circuit_breaker(CircuitBreakerConfig {
+                    failure_threshold: 1,
+                    ..CircuitBreakerConfig::default()
+                }),
+        );
+        let client = FoodshareClient::with_config(config)
+            .unwrap()
+            .with_transport(mock);
+
+        assert_eq!(client.endpoint_name(&format!("{}/slow/x?y=1", client.base_url())), "slow");
+        assert_eq!(client.get::<u32>("slow").await.unwrap(), 1);
+        assert!(matches!(client.get::<u32>("slow").await, Err(ApiError::RateLimited)));
+        assert_eq!(client.get::<u32>("fast").await.unwrap(), 2);
+        assert_eq!(client.get::<u32>("fast").await.unwrap(), 2);
+
+        let counters = &foodshare_telemetry::metrics().export_json()["counters"];
+        assert!(counters["api.development.slow.rate_limited"].as_u64().unwrap() >= 1);
+        assert_eq!(client.endpoint_circuit_state("slow"), CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn test_production_guard_blocks_writes() {
+        use crate::transport::{MockResponse, MockRoute, MockTransport};
+
+        let mock = Arc::new(
+            MockTransport::new()
+                .with_route(MockRoute::new(Method::GET, "posts", MockResponse::json(200, &1)))
+                .with_route(MockRoute::new(Method::POST, "posts", MockResponse::json(201, &2))),
+        );
+        let guarded = FoodshareClient::with_config(ClientConfig::production().with_production_guard(true))
+            .unwrap()
+            .with_transport(mock.clone());
+
+        assert_eq!(guarded.get::<u32>("posts").await.unwrap(), 1);
+        let err = guarded.post::<u32, _>("posts", &1).await.unwrap_err();
+        assert!(matches!(err, ApiError::ProductionWriteBlocked { .. }));
+        assert_eq!(mock.requests().len(), 1);
+
+        // Raw requests built by the caller are guarded too
+        let raw = guarded.inner.post(format!("{}/posts", guarded.config.base_url));
+        let err = guarded.execute_raw(raw).await.unwrap_err();
+        assert!(matches!(err, ApiError::ProductionWriteBlocked { .. }));
+        assert_eq!(mock.requests().len(), 1);
+
+        let confirmed = FoodshareClient::with_config(ClientConfig::production())
+            .unwrap()
+            .with_transport(mock.clone());
+        assert_eq!(confirmed.post::<u32, _>("posts", &1).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_download_reports_progress() {
+        use crate::transport::{MockResponse, MockRoute, MockTransport};
+
+        let body = serde_json::json!({"success": true, "data": {"messages": {"hello": "hola"}}});
+        let mock = Arc::new(MockTransport::new().with_route(MockRoute::new(
+            Method::GET,
+            "get-translations?locale=es&platform=ios",
+            MockResponse::json(200, &body),
+        )));
+        let client = FoodshareClient::with_config(ClientConfig::development())
+            .unwrap()
+            .with_transport(mock);
+
+        let seen = std::sync::Mutex::new(Vec::new());
+        let bundle = client
+            .translations()
+            .download("es", |p| seen.lock().unwrap().push(p))
+            .await
+            .unwrap();
+        assert_eq!(bundle.data.unwrap().messages["hello"], "hola");
+
+        let seen = seen.into_inner().unwrap();
+        let last = seen.last().unwrap();
+        assert_eq!(seen[0].transferred, 0);
+        assert!(last.is_complete());
+        assert_eq!(last.total, Some(body.to_string().len() as u64));
+    }
+
+    #[tokio::test]
+    async fn test_upload_reports_progress() {
+        use crate::progress::UPLOAD_CHUNK_SIZE;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // A real socket, so the streamed body is actually polled
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut received = Vec::new();
+            let mut buf = vec![0u8; 16 * 1024];
+            let (head, body_len) = loop {
+                let n = stream.read(&mut buf).await.unwrap();
+                received.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&received).to_string();
+                let Some(end) = text.find("\r\n\r\n") else { continue };
+                let length: usize = text[..end]
+                    .lines()
+                    .find_map(|l| l.strip_prefix("content-length: "))
+                    .unwrap()
+                    .parse()
+                    .unwrap();
+                if received.len() >= end + 4 + length {
+                    break (text[..end].to_string(), received.len() - end - 4);
+                }
+            };
+            let body = r#"{"Key":"images/posts/1.png"}"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+            (head, body_len)
+        });
+
+        let config = ClientConfig::development().with_base_url(format!("{url}/functions/v1"));
+        let client = FoodshareClient::with_config(config).unwrap();
+        let data = vec![b'x'; UPLOAD_CHUNK_SIZE * 3 + 10];
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorder = Arc::clone(&seen);
+
+        let uploaded = client
+            .storage()
+            .upload("images", "posts/1.png", "image/png", data.clone(), move |p| {
+                recorder.lock().unwrap().push(p.transferred);
+            })
+            .await
+            .unwrap();
+        assert_eq!(uploaded.key, "images/posts/1.png");
+
+        let (head, body_len) = server.await.unwrap();
+        assert!(head.starts_with("POST /storage/v1/object/images/posts/1.png"));
+        assert!(head.contains("content-type: image/png"));
+        assert_eq!(body_len, data.len());
+
+        let total = data.len() as u64;
+        let chunk = UPLOAD_CHUNK_SIZE as u64;
+        assert_eq!(*seen.lock().unwrap(), [0, chunk, chunk * 2, chunk * 3, total]);
+    }
+
+    #[tokio::test]
+    async fn test_batch_preserves_order_and_waits_for_rate_limit() {
+        use crate::config::EndpointConfig;
+        use crate::transport::{MockResponse, MockRoute, MockTransport};
+        use foodshare_core::rate_limit::RateLimitConfig;
+
+        let mut mock = MockTransport::new().with_latency(Duration::from_millis(5));
+        for i in 0..6 {
+            // Later items answer faster, so completion order differs from input order
+            let reply = MockResponse::json(200, &i).with_latency(Duration::from_millis(30 - i * 5));
+            mock = mock.with_route(MockRoute::new(Method::GET, format!("items/{i}"), reply));
+        }
+        let mock = Arc::new(mock);
+        let config = ClientConfig::development().with_endpoint(
+            "items",
+            EndpointConfig::new().with_rate_limit(RateLimitConfig::strict(4, Duration::from_millis(200))),
+        );
+        let client = FoodshareClient::with_config(config)
+            .unwrap()
+            .with_transport(mock.clone());
+
+        let results = client
+            .batch(0..7, 3, |i| {
+                let client = client.clone();
+                async move { client.get::<u64>(&format!("items/{i}")).await }
+            })
+            .await;
+
+        let values: Vec<_> = results.iter().take(6).map(|r| *r.as_ref().unwrap()).collect();
+        assert_eq!(values, [0, 1, 2, 3, 4, 5]);
+        // No route for the last item: its failure stays in its own slot
+        assert!(results[6].is_err());
+        assert_eq!(mock.requests().len(), 7);
+
+        // Outside a batch the same limiter rejects immediately
+        assert!(matches!(client.get::<u64>("items/0").await, Err(ApiError::RateLimited)));
+    }
+
+    #[tokio::test]
+    async fn test_batch_unordered_yields_in_completion_order() {
+        use crate::transport::{MockResponse, MockRoute, MockTransport};
+
+        let mut mock = MockTransport::new();
+        for i in 0..4 {
+            let reply = MockResponse::json(200, &i).with_latency(Duration::from_millis(40 - i * 10));
+            mock = mock.with_route(MockRoute::new(Method::GET, format!("items/{i}"), reply));
+        }
+        let client = FoodshareClient::with_config(ClientConfig::development())
+            .unwrap()
+            .with_transport(Arc::new(mock));
+
+        let values: Vec<u64> = client
+            .batch_unordered(0..4, 4, |i| {
+                let client = client.clone();
+                async move { client.get::<u64>(&format!("items/{i}")).await.unwrap() }
+            })
+            .collect()
+            .await;
+        assert_eq!(values, [3, 2, 1, 0]);
+    }
 }
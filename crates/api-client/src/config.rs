@@ -4,8 +4,9 @@
 
 use crate::error::{ApiError, ApiResult};
 use foodshare_core::rate_limit::RateLimitConfig;
-use foodshare_core::retry::RetryConfig;
+use foodshare_core::retry::{CircuitBreakerConfig, RetryConfig};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::env;
 use std::time::Duration;
 
@@ -33,14 +34,36 @@ impl Default for Environment {
 impl Environment {
     /// Parse from environment variable
     pub fn from_env() -> Self {
-        match env::var("FOODSHARE_ENV")
-            .unwrap_or_default()
-            .to_lowercase()
-            .as_str()
-        {
-            "development" | "dev" | "local" => Self::Development,
-            "staging" | "stage" => Self::Staging,
-            _ => Self::Production,
+        env::var("FOODSHARE_ENV")
+            .ok()
+            .and_then(|name| Self::parse(&name))
+            .unwrap_or(Self::Production)
+    }
+
+    /// Parse an environment name or alias (`dev`, `local`, `stage`, `prod`)
+    #[must_use]
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "development" | "dev" | "local" => Some(Self::Development),
+            "staging" | "stage" => Some(Self::Staging),
+            "production" | "prod" => Some(Self::Production),
+            _ => None,
+        }
+    }
+
+    /// Check if this is the production environment
+    #[must_use]
+    pub fn is_production(self) -> bool {
+        self == Self::Production
+    }
+
+    /// Lowercase name, as used in config files and metric names
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Development => "development",
+            Self::Staging => "staging",
+            Self::Production => "production",
         }
     }
 }
@@ -52,6 +75,9 @@ pub struct ClientConfig {
     pub base_url: String,
     /// BFF endpoint URL (derived from base_url if not set)
     pub bff_url: String,
+    /// Supabase Auth (GoTrue) URL (derived from base_url if not set)
+    #[serde(default)]
+    pub auth_url: Option<String>,
     /// Supabase anonymous key (for public endpoints)
     pub anon_key: Option<String>,
     /// Supabase service role key (for admin endpoints)
@@ -65,6 +91,60 @@ pub struct ClientConfig {
     pub rate_limit: RateLimitConfig,
     /// Current environment
     pub environment: Environment,
+    /// Per-endpoint resilience overrides, keyed by endpoint name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub endpoints: BTreeMap<String, EndpointConfig>,
+    /// Refuse mutating requests when `environment` is production
+    #[serde(default)]
+    pub production_guard: bool,
+}
+
+/// Resilience overrides for one endpoint
+///
+/// The endpoint name is the first path segment after the base URL, e.g.
+/// `get-translations` or `bff`. Unset fields fall back to the client-wide
+/// settings; a set `rate_limit` or `circuit_breaker` gives the endpoint its
+/// own limiter or breaker instead of sharing the global one.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EndpointConfig {
+    /// Rate limit (requests per window and burst)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimitConfig>,
+    /// Circuit breaker thresholds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+    /// Request timeout
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "humantime_serde::option")]
+    pub timeout: Option<Duration>,
+}
+
+impl EndpointConfig {
+    /// Create an override that changes nothing
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder-style method to set the rate limit
+    #[must_use]
+    pub fn with_rate_limit(mut self, rate_limit: RateLimitConfig) -> Self {
+        self.rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Builder-style method to set the circuit breaker thresholds
+    #[must_use]
+    pub fn with_circuit_breaker(mut self, circuit_breaker: CircuitBreakerConfig) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    /// Builder-style method to set the request timeout
+    #[must_use]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
 }
 
 mod humantime_serde {
@@ -79,6 +159,25 @@ mod humantime_serde {
         let secs = u64::deserialize(deserializer)?;
         Ok(Duration::from_secs(secs))
     }
+
+    pub mod option {
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+        use std::time::Duration;
+
+        #[allow(clippy::ref_option)] // signature required by `#[serde(with)]`
+        pub fn serialize<S: Serializer>(
+            duration: &Option<Duration>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            duration.map(|d| d.as_secs()).serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<Duration>, D::Error> {
+            Ok(Option::<u64>::deserialize(deserializer)?.map(Duration::from_secs))
+        }
+    }
 }
 
 impl Default for ClientConfig {
@@ -86,12 +185,15 @@ impl Default for ClientConfig {
         Self {
             base_url: DEFAULT_SUPABASE_URL.to_string(),
             bff_url: format!("{DEFAULT_SUPABASE_URL}/bff"),
+            auth_url: None,
             anon_key: None,
             service_role_key: None,
             timeout: Duration::from_secs(30),
             retry: RetryConfig::default(),
             rate_limit: RateLimitConfig::per_minute(100),
             environment: Environment::default(),
+            endpoints: BTreeMap::new(),
+            production_guard: false,
         }
     }
 }
@@ -102,6 +204,7 @@ impl ClientConfig {
     /// Reads the following environment variables:
     /// - `FOODSHARE_API_URL` or `SUPABASE_URL`: Base URL for Edge Functions
     /// - `FOODSHARE_BFF_URL`: BFF endpoint URL (optional, derived from base URL)
+    /// - `FOODSHARE_AUTH_URL`: Auth URL (optional, derived from base URL)
     /// - `SUPABASE_ANON_KEY`: Anonymous key for public endpoints
     /// - `SUPABASE_SERVICE_ROLE_KEY`: Service role key for admin endpoints
     /// - `FOODSHARE_ENV`: Environment (development/staging/production)
@@ -118,6 +221,8 @@ impl ClientConfig {
         let bff_url =
             env::var("FOODSHARE_BFF_URL").unwrap_or_else(|_| format!("{base_url}/bff"));
 
+        let auth_url = env::var("FOODSHARE_AUTH_URL").ok();
+
         let anon_key = env::var("SUPABASE_ANON_KEY").ok();
         let service_role_key = env::var("SUPABASE_SERVICE_ROLE_KEY").ok();
 
@@ -144,12 +249,15 @@ impl ClientConfig {
         Ok(Self {
             base_url,
             bff_url,
+            auth_url,
             anon_key,
             service_role_key,
             timeout,
             retry,
             rate_limit,
             environment,
+            endpoints: BTreeMap::new(),
+            production_guard: false,
         })
     }
 
@@ -159,12 +267,15 @@ impl ClientConfig {
         Self {
             base_url: "http://localhost:54321/functions/v1".to_string(),
             bff_url: "http://localhost:54321/functions/v1/bff".to_string(),
+            auth_url: None,
             anon_key: env::var("SUPABASE_ANON_KEY").ok(),
             service_role_key: env::var("SUPABASE_SERVICE_ROLE_KEY").ok(),
             timeout: Duration::from_secs(10),
             retry: RetryConfig::quick(),
             rate_limit: RateLimitConfig::per_minute(1000),
             environment: Environment::Development,
+            endpoints: BTreeMap::new(),
+            production_guard: false,
         }
     }
 
@@ -177,6 +288,7 @@ impl ClientConfig {
                 .unwrap_or_else(|_| DEFAULT_SUPABASE_URL.to_string()),
             bff_url: env::var("STAGING_BFF_URL")
                 .unwrap_or_else(|_| format!("{DEFAULT_SUPABASE_URL}/bff")),
+            auth_url: None,
             anon_key: env::var("STAGING_SUPABASE_ANON_KEY")
                 .or_else(|_| env::var("SUPABASE_ANON_KEY"))
                 .ok(),
@@ -187,6 +299,8 @@ impl ClientConfig {
             retry: RetryConfig::default(),
             rate_limit: RateLimitConfig::per_minute(200),
             environment: Environment::Staging,
+            endpoints: BTreeMap::new(),
+            production_guard: false,
         }
     }
 
@@ -196,12 +310,15 @@ impl ClientConfig {
         Self {
             base_url: DEFAULT_SUPABASE_URL.to_string(),
             bff_url: format!("{DEFAULT_SUPABASE_URL}/bff"),
+            auth_url: None,
             anon_key: env::var("SUPABASE_ANON_KEY").ok(),
             service_role_key: env::var("SUPABASE_SERVICE_ROLE_KEY").ok(),
             timeout: Duration::from_secs(30),
             retry: RetryConfig::patient(),
             rate_limit: RateLimitConfig::per_minute(100),
             environment: Environment::Production,
+            endpoints: BTreeMap::new(),
+            production_guard: false,
         }
     }
 
@@ -221,6 +338,13 @@ impl ClientConfig {
         self
     }
 
+    /// Builder-style method to set the Auth URL
+    #[must_use]
+    pub fn with_auth_url(mut self, url: impl Into<String>) -> Self {
+        self.auth_url = Some(url.into());
+        self
+    }
+
     /// Builder-style method to set anon key
     #[must_use]
     pub fn with_anon_key(mut self, key: impl Into<String>) -> Self {
@@ -256,6 +380,52 @@ impl ClientConfig {
         self
     }
 
+    /// Builder-style method to override resilience settings for one endpoint
+    #[must_use]
+    pub fn with_endpoint(mut self, name: impl Into<String>, endpoint: EndpointConfig) -> Self {
+        self.endpoints.insert(name.into(), endpoint);
+        self
+    }
+
+    /// Builder-style method to refuse mutating requests against production
+    #[must_use]
+    pub fn with_production_guard(mut self, enabled: bool) -> Self {
+        self.production_guard = enabled;
+        self
+    }
+
+    /// Get the overrides for an endpoint, if any
+    #[must_use]
+    pub fn endpoint(&self, name: &str) -> Option<&EndpointConfig> {
+        self.endpoints.get(name)
+    }
+
+    /// Request timeout for an endpoint
+    #[must_use]
+    pub fn timeout_for(&self, name: &str) -> Duration {
+        self.endpoint(name)
+            .and_then(|e| e.timeout)
+            .unwrap_or(self.timeout)
+    }
+
+    /// Supabase project URL: `base_url` without its `/functions/v1` suffix
+    #[must_use]
+    pub fn project_url(&self) -> &str {
+        let base = self.base_url.trim_end_matches('/');
+        base.strip_suffix("/functions/v1").unwrap_or(base)
+    }
+
+    /// Resolve the Auth URL
+    ///
+    /// Defaults to `<project>/auth/v1`.
+    #[must_use]
+    pub fn resolved_auth_url(&self) -> String {
+        if let Some(ref url) = self.auth_url {
+            return url.trim_end_matches('/').to_string();
+        }
+        format!("{}/auth/v1", self.project_url())
+    }
+
     /// Validate the configuration
     pub fn validate(&self) -> ApiResult<()> {
         if self.base_url.is_empty() {
@@ -270,6 +440,12 @@ impl ClientConfig {
             return Err(ApiError::config("timeout cannot be zero"));
         }
 
+        for (name, endpoint) in &self.endpoints {
+            if endpoint.timeout.is_some_and(|t| t.is_zero()) {
+                return Err(ApiError::config(format!("timeout for endpoint '{name}' cannot be zero")));
+            }
+        }
+
         Ok(())
     }
 }
@@ -306,6 +482,45 @@ mod tests {
         assert_eq!(config.timeout, Duration::from_secs(60));
     }
 
+    #[test]
+    fn test_resolved_auth_url() {
+        let config = ClientConfig::development();
+        assert_eq!(config.resolved_auth_url(), "http://localhost:54321/auth/v1");
+
+        let config = config.with_auth_url("https://auth.example.com/v1/");
+        assert_eq!(config.resolved_auth_url(), "https://auth.example.com/v1");
+    }
+
+    #[test]
+    fn test_endpoint_overrides() {
+        let config = ClientConfig::development().with_endpoint(
+            "translate-batch",
+            EndpointConfig::new()
+                .with_rate_limit(RateLimitConfig::per_minute(10))
+                .with_timeout(Duration::from_secs(120)),
+        );
+        assert_eq!(config.timeout_for("translate-batch"), Duration::from_secs(120));
+        assert_eq!(config.timeout_for("health"), config.timeout);
+
+        let json = serde_json::to_string(&config).unwrap();
+        let parsed: ClientConfig = serde_json::from_str(&json).unwrap();
+        let endpoint = parsed.endpoint("translate-batch").unwrap();
+        assert_eq!(endpoint.timeout, Some(Duration::from_secs(120)));
+        assert_eq!(endpoint.rate_limit.as_ref().map(|r| r.max_requests), Some(10));
+        assert!(endpoint.circuit_breaker.is_none());
+
+        let invalid = config.with_endpoint("bff", EndpointConfig::new().with_timeout(Duration::ZERO));
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_environment_parse() {
+        assert_eq!(Environment::parse("local"), Some(Environment::Development));
+        assert_eq!(Environment::parse("Stage"), Some(Environment::Staging));
+        assert_eq!(Environment::parse("prod"), Some(Environment::Production));
+        assert_eq!(Environment::parse("qa"), None);
+    }
+
     #[test]
     fn test_validation() {
         let valid = ClientConfig::default();
@@ -0,0 +1,158 @@
+//! Authentication endpoints
+//!
+//! Maps to the Supabase Auth (GoTrue) API at `<project>/auth/v1`:
+//! - Sign in with email and password
+//! - Sign in with a magic link (send + verify)
+//! - Refresh an access token
+//! - Sign out
+//!
+//! Successful sign-ins replace the client's session, which is then used for
+//! every request and persisted if the client has a session store.
+
+use crate::client::FoodshareClient;
+use crate::error::ApiResult;
+use crate::session::{Session, TokenResponse};
+use reqwest::Method;
+use serde::Serialize;
+
+/// Authentication API interface
+#[derive(Clone)]
+pub struct AuthApi {
+    client: FoodshareClient,
+}
+
+impl AuthApi {
+    /// Create a new auth API interface
+    pub(crate) fn new(client: FoodshareClient) -> Self {
+        Self { client }
+    }
+
+    /// Sign in with email and password
+    ///
+    /// POST /auth/v1/token?grant_type=password
+    pub async fn sign_in_with_password(&self, email: &str, password: &str) -> ApiResult<Session> {
+        let body = PasswordCredentials { email, password };
+        let token: TokenResponse = self
+            .client
+            .auth_json(Method::POST, "token?grant_type=password", &body)
+            .await?;
+        self.start_session(token.into())
+    }
+
+    /// Email a magic link (one-time sign-in code) to an existing user
+    ///
+    /// POST /auth/v1/otp
+    pub async fn send_magic_link(&self, email: &str) -> ApiResult<()> {
+        let body = MagicLinkRequest {
+            email,
+            create_user: false,
+        };
+        self.client.auth_send(Method::POST, "otp", Some(&body), None).await?;
+        Ok(())
+    }
+
+    /// Complete a magic link sign-in with the token from the email
+    ///
+    /// POST /auth/v1/verify
+    pub async fn verify_magic_link(&self, email: &str, token: &str) -> ApiResult<Session> {
+        let body = VerifyRequest {
+            kind: "magiclink",
+            email,
+            token,
+        };
+        let token: TokenResponse = self.client.auth_json(Method::POST, "verify", &body).await?;
+        self.start_session(token.into())
+    }
+
+    /// Authorize all requests with a service key
+    ///
+    /// No request is made; the key is used as a non-expiring bearer token.
+    pub fn sign_in_with_service_key(&self, key: impl Into<String>) -> ApiResult<Session> {
+        self.start_session(Session::service_key(key))
+    }
+
+    /// Exchange the current refresh token for a new session
+    ///
+    /// POST /auth/v1/token?grant_type=refresh_token
+    pub async fn refresh(&self) -> ApiResult<Session> {
+        let current = self.client.session().map(|s| s.access_token);
+        self.client.refresh_session(current.as_deref()).await
+    }
+
+    /// Sign out, revoking the refresh token server-side if there is one
+    ///
+    /// POST /auth/v1/logout
+    pub async fn sign_out(&self) -> ApiResult<()> {
+        if let Some(session) = self.client.session().filter(Session::can_refresh) {
+            let result = self
+                .client
+                .auth_send::<()>(Method::POST, "logout", None, Some(&session.access_token))
+                .await;
+            // An already-invalid token is as good as revoked
+            if let Err(e) = result {
+                if !e.is_unauthorized() {
+                    return Err(e);
+                }
+            }
+        }
+        self.client.clear_session()
+    }
+
+    /// Get the current session, if signed in
+    #[must_use]
+    pub fn session(&self) -> Option<Session> {
+        self.client.session()
+    }
+
+    fn start_session(&self, session: Session) -> ApiResult<Session> {
+        self.client.set_session(session.clone())?;
+        Ok(session)
+    }
+}
+
+#[derive(Serialize)]
+struct PasswordCredentials<'a> {
+    email: &'a str,
+    password: &'a str,
+}
+
+#[derive(Serialize)]
+struct MagicLinkRequest<'a> {
+    email: &'a str,
+    create_user: bool,
+}
+
+#[derive(Serialize)]
+struct VerifyRequest<'a> {
+    #[serde(rename = "type")]
+    kind: &'a str,
+    email: &'a str,
+    token: &'a str,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ClientConfig;
+
+    #[test]
+    fn test_verify_request_serialize() {
+        let body = VerifyRequest {
+            kind: "magiclink",
+            email: "a@example.com",
+            token: "123456",
+        };
+        let json = serde_json::to_value(&body).unwrap();
+        assert_eq!(json["type"], "magiclink");
+        assert_eq!(json["token"], "123456");
+    }
+
+    #[test]
+    fn test_service_key_sign_in() {
+        let client = FoodshareClient::with_config(ClientConfig::development()).unwrap();
+        let session = client.auth().sign_in_with_service_key("service").unwrap();
+
+        assert_eq!(client.session(), Some(session));
+        assert!(!client.session().unwrap().can_refresh());
+    }
+}
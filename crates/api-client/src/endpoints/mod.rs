@@ -6,20 +6,26 @@
 //!
 //! | Module | Backend Function | Description |
 //! |--------|-----------------|-------------|
+//! | `auth` | Supabase Auth (`/auth/v1`) | Sign-in, token refresh, sign-out |
 //! | `translations` | `get-translations` | Legacy standalone translation function |
 //! | `localization` | `localization` | Consolidated localization service |
 //! | `products` | `api-v1-products` | Products/listings CRUD API |
 //! | `health` | `health`, `health-advanced` | Health check endpoints |
 //! | `bff` | `bff` | Backend-for-frontend aggregation |
+//! | `storage` | Supabase Storage (`/storage/v1`) | Image and file uploads |
 
+pub mod auth;
 pub mod bff;
 pub mod health;
 pub mod localization;
 pub mod products;
+pub mod storage;
 pub mod translations;
 
+pub use auth::AuthApi;
 pub use bff::BffApi;
 pub use health::HealthApi;
 pub use localization::LocalizationApi;
 pub use products::ProductsApi;
+pub use storage::StorageApi;
 pub use translations::TranslationsApi;
@@ -9,6 +9,8 @@
 
 use crate::client::FoodshareClient;
 use crate::error::ApiResult;
+use crate::pagination::{self, PageToken, Paginated};
+use futures_util::Stream;
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
@@ -62,6 +64,39 @@ impl ProductsApi {
         self.client.get(&path).await
     }
 
+    /// List one page of products
+    ///
+    /// GET /api-v1-products
+    pub async fn list_page(&self, params: &ListProductsParams) -> ApiResult<Paginated<Product>> {
+        self.list(params).await.map(Paginated::from)
+    }
+
+    /// Stream pages of products, following pagination cursors
+    ///
+    /// Starts from `params.cursor` (or the beginning) and ends after the last page.
+    pub fn pages(
+        &self,
+        params: ListProductsParams,
+    ) -> impl Stream<Item = ApiResult<Paginated<Product>>> + 'static {
+        let api = self.clone();
+        pagination::paginate(move |token| {
+            let api = api.clone();
+            let mut params = params.clone();
+            if let Some(PageToken::Cursor(cursor)) = token {
+                params.cursor = Some(cursor);
+            }
+            async move { api.list_page(&params).await }
+        })
+    }
+
+    /// Stream every product matching `params`, across all pages
+    pub fn items(
+        &self,
+        params: ListProductsParams,
+    ) -> impl Stream<Item = ApiResult<Product>> + 'static {
+        pagination::items(self.pages(params))
+    }
+
     /// List products with timing
     pub async fn list_timed(
         &self,
@@ -192,6 +227,23 @@ pub struct ListProductsResponse {
     pub error: Option<ErrorInfo>,
 }
 
+impl From<ListProductsResponse> for Paginated<Product> {
+    fn from(response: ListProductsResponse) -> Self {
+        let pagination = response.pagination;
+        let next = pagination
+            .as_ref()
+            .filter(|p| p.has_more)
+            .and_then(|p| p.cursor.clone())
+            .map(PageToken::Cursor);
+        let page = Paginated::new(response.data.unwrap_or_default(), next);
+
+        match pagination.and_then(|p| p.total).and_then(|t| u64::try_from(t).ok()) {
+            Some(total) => page.with_total(total),
+            None => page,
+        }
+    }
+}
+
 /// Single product response
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GetProductResponse {
@@ -338,6 +390,24 @@ mod tests {
         assert_eq!(params.limit, Some(20));
     }
 
+    #[test]
+    fn test_list_response_into_page() {
+        let json = r#"{
+            "success": true,
+            "data": [],
+            "pagination": {"cursor": "abc", "hasMore": true, "total": 120}
+        }"#;
+        let page: Paginated<Product> =
+            serde_json::from_str::<ListProductsResponse>(json).unwrap().into();
+        assert_eq!(page.next, Some(PageToken::Cursor("abc".to_string())));
+        assert_eq!(page.total, Some(120));
+
+        let json = r#"{"success": true, "data": [], "pagination": {"cursor": "abc", "hasMore": false}}"#;
+        let page: Paginated<Product> =
+            serde_json::from_str::<ListProductsResponse>(json).unwrap().into();
+        assert!(!page.has_more());
+    }
+
     #[test]
     fn test_product_deserialize() {
         let json = r#"{
@@ -0,0 +1,98 @@
+//! Supabase Storage endpoints
+
+use crate::client::FoodshareClient;
+use crate::error::ApiResult;
+use crate::progress::TransferProgress;
+use serde::{Deserialize, Serialize};
+
+/// Storage API interface
+#[derive(Clone)]
+pub struct StorageApi {
+    client: FoodshareClient,
+}
+
+impl StorageApi {
+    /// Create a new storage API interface
+    pub(crate) fn new(client: FoodshareClient) -> Self {
+        Self { client }
+    }
+
+    /// Storage URL for an object
+    #[must_use]
+    pub fn object_url(&self, bucket: &str, object_path: &str) -> String {
+        format!(
+            "{}/storage/v1/object/{bucket}/{}",
+            self.client.config().project_url(),
+            object_path.trim_start_matches('/')
+        )
+    }
+
+    /// Public URL for an object in a public bucket
+    #[must_use]
+    pub fn public_url(&self, bucket: &str, object_path: &str) -> String {
+        format!(
+            "{}/storage/v1/object/public/{bucket}/{}",
+            self.client.config().project_url(),
+            object_path.trim_start_matches('/')
+        )
+    }
+
+    /// Upload an object (e.g. a listing image), reporting progress as it is sent
+    pub async fn upload(
+        &self,
+        bucket: &str,
+        object_path: &str,
+        content_type: &str,
+        data: Vec<u8>,
+        on_progress: impl Fn(TransferProgress) + Send + Sync + 'static,
+    ) -> ApiResult<UploadResponse> {
+        let url = self.object_url(bucket, object_path);
+        self.client
+            .upload_url(reqwest::Method::POST, &url, content_type, data, on_progress)
+            .await
+    }
+
+    /// Upload an object, replacing any existing object at the same path
+    pub async fn upsert(
+        &self,
+        bucket: &str,
+        object_path: &str,
+        content_type: &str,
+        data: Vec<u8>,
+        on_progress: impl Fn(TransferProgress) + Send + Sync + 'static,
+    ) -> ApiResult<UploadResponse> {
+        let url = self.object_url(bucket, object_path);
+        self.client
+            .upload_url_with_headers(
+                reqwest::Method::POST,
+                &url,
+                content_type,
+                &[("x-upsert", "true")],
+                data,
+                on_progress,
+            )
+            .await
+    }
+
+    /// Download an object, reporting progress as it arrives
+    pub async fn download(
+        &self,
+        bucket: &str,
+        object_path: &str,
+        on_progress: impl Fn(TransferProgress),
+    ) -> ApiResult<Vec<u8>> {
+        let url = self.object_url(bucket, object_path);
+        self.client.download_url(&url, on_progress).await
+    }
+}
+
+/// Upload response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadResponse {
+    /// Object key (`<bucket>/<path>`)
+    #[serde(rename = "Key")]
+    pub key: String,
+    /// Object ID
+    #[serde(rename = "Id", default)]
+    pub id: Option<String>,
+}
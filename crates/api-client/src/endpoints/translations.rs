@@ -2,6 +2,9 @@
 
 use crate::client::FoodshareClient;
 use crate::error::ApiResult;
+use crate::pagination::{self, PageToken, Paginated};
+use crate::progress::TransferProgress;
+use futures_util::Stream;
 use reqwest::header::IF_NONE_MATCH;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -36,6 +39,17 @@ impl TranslationsApi {
         self.client.timed_get(&path).await
     }
 
+    /// Download the translation bundle for a locale, reporting progress
+    pub async fn download(
+        &self,
+        locale: &str,
+        on_progress: impl Fn(TransferProgress),
+    ) -> ApiResult<TranslationResponse> {
+        let path = format!("get-translations?locale={locale}&platform=ios");
+        let body = self.client.download(&path, on_progress).await?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+
     /// Get translations with ETag for caching
     ///
     /// Returns `Ok(None)` if the ETag matches (304 Not Modified).
@@ -91,6 +105,46 @@ impl TranslationsApi {
         self.client.get(&path).await
     }
 
+    /// Get one page of untranslated keys for a locale
+    pub async fn untranslated_page(
+        &self,
+        locale: &str,
+        limit: usize,
+        offset: usize,
+    ) -> ApiResult<Paginated<UntranslatedKey>> {
+        let path = format!("translation-audit?locale={locale}&limit={limit}&offset={offset}");
+        let audit: AuditResponse = self.client.get(&path).await?;
+        Ok(audit.into_page(offset))
+    }
+
+    /// Stream pages of untranslated keys for a locale, following offsets
+    pub fn untranslated_pages(
+        &self,
+        locale: &str,
+        page_size: usize,
+    ) -> impl Stream<Item = ApiResult<Paginated<UntranslatedKey>>> + 'static {
+        let api = self.clone();
+        let locale = locale.to_string();
+        pagination::paginate(move |token| {
+            let api = api.clone();
+            let locale = locale.clone();
+            let offset = match token {
+                Some(PageToken::Offset(offset)) => offset,
+                _ => 0,
+            };
+            async move { api.untranslated_page(&locale, page_size, offset).await }
+        })
+    }
+
+    /// Stream every untranslated key for a locale, across all pages
+    pub fn untranslated(
+        &self,
+        locale: &str,
+        page_size: usize,
+    ) -> impl Stream<Item = ApiResult<UntranslatedKey>> + 'static {
+        pagination::items(self.untranslated_pages(locale, page_size))
+    }
+
     /// Translate a batch of keys
     pub async fn translate_batch(
         &self,
@@ -249,6 +303,26 @@ pub struct AuditResponse {
     pub by_category: Option<HashMap<String, usize>>,
 }
 
+impl AuditResponse {
+    /// Convert into a page starting at `offset`
+    ///
+    /// There is a next page while `untranslated_count` exceeds the keys seen so far.
+    fn into_page(self, offset: usize) -> Paginated<UntranslatedKey> {
+        let keys = self.untranslated.unwrap_or_default();
+        let seen = offset + keys.len();
+        let next = self
+            .untranslated_count
+            .filter(|&count| !keys.is_empty() && seen < count)
+            .map(|_| PageToken::Offset(seen));
+
+        let page = Paginated::new(keys, next);
+        match self.untranslated_count {
+            Some(count) => page.with_total(count as u64),
+            None => page,
+        }
+    }
+}
+
 /// An untranslated key
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UntranslatedKey {
@@ -275,6 +349,20 @@ pub struct TranslateBatchResponse {
     pub message: Option<String>,
     /// Error message if failed
     pub error: Option<String>,
+    /// LLM tokens the request used, when the service reports them
+    #[serde(default)]
+    pub usage: Option<TokenUsage>,
+}
+
+/// LLM token counts for a request
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    /// Prompt tokens
+    #[serde(rename = "inputTokens", alias = "prompt_tokens", alias = "input_tokens")]
+    pub input_tokens: u64,
+    /// Completion tokens
+    #[serde(rename = "outputTokens", alias = "completion_tokens", alias = "output_tokens")]
+    pub output_tokens: u64,
 }
 
 #[cfg(test)]
@@ -334,4 +422,41 @@ mod tests {
         let stats = response.stats.unwrap();
         assert_eq!(stats.added, 5);
     }
+
+    #[test]
+    fn test_translate_batch_response_usage() {
+        let json = r#"{
+            "success": true,
+            "translated": 2,
+            "usage": {"prompt_tokens": 120, "completion_tokens": 48}
+        }"#;
+        let response: TranslateBatchResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            response.usage,
+            Some(TokenUsage {
+                input_tokens: 120,
+                output_tokens: 48
+            })
+        );
+
+        let response: TranslateBatchResponse =
+            serde_json::from_str(r#"{"success": true}"#).unwrap();
+        assert!(response.usage.is_none());
+    }
+
+    #[test]
+    fn test_audit_response_into_page() {
+        let json = r#"{
+            "untranslatedCount": 3,
+            "untranslated": [{"key": "a"}, {"key": "b"}]
+        }"#;
+
+        let response: AuditResponse = serde_json::from_str(json).unwrap();
+        let page = response.clone().into_page(0);
+        assert_eq!(page.items.len(), 2);
+        assert_eq!(page.next, Some(PageToken::Offset(2)));
+        assert_eq!(page.total, Some(3));
+
+        assert!(!response.into_page(1).has_more());
+    }
 }
@@ -58,6 +58,47 @@ pub enum ApiError {
     /// Invalid URL
     #[error("Invalid URL: {0}")]
     InvalidUrl(String),
+
+    /// Authentication failed or no usable session
+    #[error("Authentication error: {0}")]
+    Auth(String),
+
+    /// File I/O failed
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Request could not reach the backend and was saved to the offline queue
+    #[error("Backend unreachable, request queued for replay (idempotency key {idempotency_key})")]
+    Queued {
+        /// Idempotency key the request will be replayed with
+        idempotency_key: String,
+    },
+
+    /// Request could not be handed to or answered by the transport
+    #[error("Transport error: {0}")]
+    Transport(String),
+
+    /// Mutating request against production without confirmation
+    #[error("Refusing {method} {url}: writes to production need confirmation (--yes-prod)")]
+    ProductionWriteBlocked {
+        /// HTTP method
+        method: String,
+        /// Request URL
+        url: String,
+    },
+
+    /// Session could not be read from or written to the keychain
+    #[error("Keychain error: {0}")]
+    Keychain(#[from] foodshare_core::Error),
+
+    /// Cache-only client has no cached response for the request
+    #[error("Not available offline: no cached response for {method} {url}")]
+    NotCached {
+        /// HTTP method
+        method: String,
+        /// Request URL
+        url: String,
+    },
 }
 
 impl ApiError {
@@ -71,6 +112,11 @@ impl ApiError {
         Self::MissingEnvVar(var.into())
     }
 
+    /// Create an authentication error
+    pub fn auth(msg: impl Into<String>) -> Self {
+        Self::Auth(msg.into())
+    }
+
     /// Create an API response error
     pub fn api_response(status: u16, message: impl Into<String>) -> Self {
         Self::ApiResponse {
@@ -97,16 +143,54 @@ impl ApiError {
             | Self::MissingEnvVar(_)
             | Self::Json(_)
             | Self::InvalidUrl(_)
+            | Self::Auth(_)
+            | Self::Io(_)
+            | Self::Queued { .. }
+            | Self::Transport(_)
+            | Self::ProductionWriteBlocked { .. }
+            | Self::Keychain(_)
+            | Self::NotCached { .. }
             | Self::RetriesExhausted { .. } => false,
         }
     }
 
+    /// Check if the backend was unreachable (connection failure, timeout or open circuit)
+    ///
+    /// These are the failures the offline queue saves requests from.
+    #[must_use]
+    pub fn is_connectivity_error(&self) -> bool {
+        match self {
+            Self::Request(e) => e.is_connect() || e.is_timeout(),
+            Self::Timeout(_) | Self::CircuitOpen => true,
+            _ => false,
+        }
+    }
+
     /// Check if this is a client error (4xx)
     #[must_use]
     pub fn is_client_error(&self) -> bool {
         matches!(self, Self::ApiResponse { status, .. } if (400..500).contains(status))
     }
 
+    /// Check if the API rejected the credentials (401)
+    #[must_use]
+    pub fn is_unauthorized(&self) -> bool {
+        matches!(self, Self::ApiResponse { status: 401, .. })
+    }
+
+    /// Check if the requested resource does not exist
+    ///
+    /// Supabase Storage reports missing objects either as 404 or as 400 with
+    /// an "Object not found" message, depending on the server version.
+    #[must_use]
+    pub fn is_not_found(&self) -> bool {
+        match self {
+            Self::ApiResponse { status: 404, .. } => true,
+            Self::ApiResponse { status: 400, message } => message.contains("not_found") || message.contains("not found"),
+            _ => false,
+        }
+    }
+
     /// Check if this is a server error (5xx)
     #[must_use]
     pub fn is_server_error(&self) -> bool {
@@ -6,10 +6,18 @@
 //! # Features
 //!
 //! - **Environment-based configuration**: Load URLs and keys from environment variables
+//! - **Environment profiles**: Named environments with key references in one TOML file, and a guard against unconfirmed production writes
 //! - **Retry with exponential backoff**: Automatic retry for transient failures
 //! - **Circuit breaker**: Prevent cascading failures during outages
 //! - **Rate limiting**: Avoid hitting API throttling limits
 //! - **Request correlation**: Track requests with unique IDs for debugging
+//! - **Middleware**: Pre/post request hooks, with built-ins for correlation IDs, W3C trace context, redacted logging and per-endpoint metrics
+//! - **Pagination**: Typed pages and auto-paging streams for list endpoints
+//! - **HTTP cache**: Disk cache of GET responses revalidated with ETag/Last-Modified
+//! - **Offline queue**: Persist mutating requests made while offline and replay them with idempotency keys
+//! - **Authentication**: Password, magic link and service key sign-in with automatic token refresh
+//! - **Transfer progress**: Byte-level progress callbacks for uploads and downloads
+//! - **Mock transport**: Canned responses, latency and fault injection, plus record/replay cassettes for offline runs
 //!
 //! # Example
 //!
@@ -43,15 +51,32 @@ pub mod config;
 pub mod endpoints;
 pub mod error;
 pub mod middleware;
+pub mod offline;
+pub mod pagination;
+pub mod profiles;
+pub mod progress;
+pub mod session;
+pub mod transport;
 
 pub use client::FoodshareClient;
 pub use config::{ClientConfig, Environment};
 pub use error::{ApiError, ApiResult};
+pub use offline::{OfflineQueue, ReplayReport};
+pub use pagination::{PageToken, Paginated};
+pub use profiles::{KeyRef, Profile, Profiles};
+pub use progress::TransferProgress;
+pub use session::{Session, SessionStore};
+pub use transport::Transport;
 
 /// Prelude for convenient imports
 pub mod prelude {
     pub use crate::client::FoodshareClient;
     pub use crate::config::{ClientConfig, Environment};
-    pub use crate::endpoints::{BffApi, HealthApi, LocalizationApi, ProductsApi, TranslationsApi};
+    pub use crate::endpoints::{
+        AuthApi, BffApi, HealthApi, LocalizationApi, ProductsApi, StorageApi, TranslationsApi,
+    };
     pub use crate::error::{ApiError, ApiResult};
+    pub use crate::pagination::{PageToken, Paginated};
+    pub use crate::progress::TransferProgress;
+    pub use crate::session::{Session, SessionStore};
 }
@@ -0,0 +1,61 @@
+//! Correlation ID injection
+
+use super::Middleware;
+use crate::error::ApiResult;
+use reqwest::header::HeaderValue;
+use reqwest::Request;
+use uuid::Uuid;
+
+/// Per-request ID header
+pub const REQUEST_ID_HEADER: &str = "X-Request-ID";
+
+/// Header shared by every request of one run
+pub const CORRELATION_ID_HEADER: &str = "X-Correlation-ID";
+
+/// Tags requests so backend logs can be joined with client logs
+///
+/// Adds an `X-Request-ID` to requests that lack one and an `X-Correlation-ID`
+/// that defaults to the `foodshare-telemetry` session ID, so every request
+/// made by one CLI run can be found together.
+#[derive(Debug, Clone)]
+pub struct CorrelationId {
+    correlation_id: HeaderValue,
+}
+
+impl CorrelationId {
+    /// Correlate requests with the telemetry session ID
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            correlation_id: HeaderValue::from_static(foodshare_telemetry::session_id()),
+        }
+    }
+
+    /// Correlate requests with a caller-supplied ID (e.g. a CI job ID)
+    ///
+    /// IDs that are not valid header values fall back to the session ID.
+    #[must_use]
+    pub fn with_id(id: &str) -> Self {
+        HeaderValue::from_str(id).map_or_else(|_| Self::new(), |correlation_id| Self { correlation_id })
+    }
+}
+
+impl Default for CorrelationId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Middleware for CorrelationId {
+    fn on_request(&self, request: &mut Request) -> ApiResult<()> {
+        let headers = request.headers_mut();
+        if !headers.contains_key(REQUEST_ID_HEADER) {
+            // A v4 UUID is always a valid header value
+            if let Ok(value) = HeaderValue::from_str(&Uuid::new_v4().to_string()) {
+                headers.insert(REQUEST_ID_HEADER, value);
+            }
+        }
+        headers.insert(CORRELATION_ID_HEADER, self.correlation_id.clone());
+        Ok(())
+    }
+}
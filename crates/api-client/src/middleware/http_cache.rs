@@ -0,0 +1,196 @@
+//! HTTP response cache with conditional revalidation
+//!
+//! Stores successful GET responses in a `foodshare-core` [`Cache`] together
+//! with their validators. The next request for the same URL sends
+//! `If-None-Match` / `If-Modified-Since`, and a `304 Not Modified` is answered
+//! from the cache instead of re-downloading the body.
+//!
+//! When the server sends no `ETag`, one is derived from the body with
+//! [`foodshare_compression::generate_etag`], the same strong `ETag` the
+//! Foodshare backend computes, so revalidation still works.
+
+use foodshare_core::cache::{Cache, CacheConfig};
+use reqwest::header::{HeaderMap, CACHE_CONTROL, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::RequestBuilder;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::debug;
+
+/// How long entries are kept on disk by default (validators keep them fresh)
+const DEFAULT_ENTRY_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// A cached response body and its validators
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedResponse {
+    /// Entity tag (quoted, as sent in headers)
+    pub etag: Option<String>,
+    /// `Last-Modified` header value
+    pub last_modified: Option<String>,
+    /// Response body
+    pub body: String,
+}
+
+impl CachedResponse {
+    /// Add conditional request headers for this entry
+    pub fn apply_validators(&self, mut request: RequestBuilder) -> RequestBuilder {
+        if let Some(ref etag) = self.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(ref last_modified) = self.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+        request
+    }
+}
+
+/// Disk-backed cache of GET responses
+pub struct HttpCache {
+    cache: Cache,
+    ttl: Duration,
+}
+
+impl HttpCache {
+    /// Create a cache with the given storage configuration
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache directory cannot be created.
+    pub fn new(config: CacheConfig) -> foodshare_core::Result<Self> {
+        Ok(Self {
+            cache: Cache::new(config)?,
+            ttl: DEFAULT_ENTRY_TTL,
+        })
+    }
+
+    /// Create a cache in the default location (`<cache dir>/foodshare-tools/http`)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache directory cannot be created.
+    pub fn open_default() -> foodshare_core::Result<Self> {
+        Self::new(CacheConfig {
+            cache_dir: Self::default_dir(),
+            ..CacheConfig::default()
+        })
+    }
+
+    /// Default cache directory
+    #[must_use]
+    pub fn default_dir() -> PathBuf {
+        CacheConfig::default().cache_dir.join("http")
+    }
+
+    /// Builder-style method to set how long entries are kept
+    #[must_use]
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Look up the cached response for a request
+    ///
+    /// `credential` partitions the cache so users never see each other's responses.
+    pub fn lookup(&self, url: &str, credential: Option<&str>) -> Option<CachedResponse> {
+        match self.cache.get(&cache_key(url, credential)) {
+            Ok(entry) => entry,
+            Err(e) => {
+                debug!(url = %url, error = %e, "HTTP cache read failed");
+                None
+            }
+        }
+    }
+
+    /// Store a successful response, unless it is marked `no-store`
+    pub fn store(&self, url: &str, credential: Option<&str>, headers: &HeaderMap, body: &[u8]) {
+        let no_store = header_str(headers, CACHE_CONTROL.as_str())
+            .is_some_and(|v| v.to_ascii_lowercase().contains("no-store"));
+        if no_store {
+            return;
+        }
+        let Ok(body) = std::str::from_utf8(body) else {
+            return;
+        };
+
+        let entry = CachedResponse {
+            etag: header_str(headers, ETAG.as_str())
+                .map(str::to_string)
+                .or_else(|| Some(foodshare_compression::generate_etag(body.as_bytes()))),
+            last_modified: header_str(headers, LAST_MODIFIED.as_str()).map(str::to_string),
+            body: body.to_string(),
+        };
+
+        if let Err(e) = self.cache.set(&cache_key(url, credential), &entry, Some(self.ttl)) {
+            debug!(url = %url, error = %e, "HTTP cache write failed");
+        }
+    }
+
+    /// Remove every cached response
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache directory cannot be cleared.
+    pub fn clear(&self) -> foodshare_core::Result<()> {
+        self.cache.clear()
+    }
+}
+
+fn cache_key(url: &str, credential: Option<&str>) -> String {
+    // The core cache hashes keys, so the credential is never stored in clear
+    format!("GET {url}\n{}", credential.unwrap_or_default())
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|v| v.to_str().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+    use tempfile::TempDir;
+
+    fn test_cache() -> (HttpCache, TempDir) {
+        let temp = TempDir::new().unwrap();
+        let cache = HttpCache::new(CacheConfig {
+            cache_dir: temp.path().to_path_buf(),
+            ..CacheConfig::default()
+        })
+        .unwrap();
+        (cache, temp)
+    }
+
+    #[test]
+    fn test_derives_etag_when_missing() {
+        let (cache, _temp) = test_cache();
+        cache.store("https://x/a", None, &HeaderMap::new(), b"{\"v\":1}");
+
+        let entry = cache.lookup("https://x/a", None).unwrap();
+        assert_eq!(entry.etag, Some(foodshare_compression::generate_etag(b"{\"v\":1}")));
+        assert_eq!(entry.body, "{\"v\":1}");
+    }
+
+    #[test]
+    fn test_partitions_by_credential() {
+        let (cache, _temp) = test_cache();
+        let mut headers = HeaderMap::new();
+        headers.insert(ETAG, HeaderValue::from_static("\"abc\""));
+        headers.insert(LAST_MODIFIED, HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"));
+        cache.store("https://x/a", Some("alice"), &headers, b"{}");
+
+        let entry = cache.lookup("https://x/a", Some("alice")).unwrap();
+        assert_eq!(entry.etag.as_deref(), Some("\"abc\""));
+        assert!(entry.last_modified.is_some());
+        assert!(cache.lookup("https://x/a", Some("bob")).is_none());
+    }
+
+    #[test]
+    fn test_honors_no_store() {
+        let (cache, _temp) = test_cache();
+        let mut headers = HeaderMap::new();
+        headers.insert(CACHE_CONTROL, HeaderValue::from_static("private, no-store"));
+        cache.store("https://x/a", None, &headers, b"{}");
+
+        assert!(cache.lookup("https://x/a", None).is_none());
+    }
+}
@@ -0,0 +1,181 @@
+//! Request/response logging with secret redaction
+//!
+//! Credentials never reach the log: sensitive headers, query parameters and
+//! JSON body fields are replaced with `<redacted>` before anything is written.
+
+use super::{Middleware, RequestInfo};
+use crate::error::{ApiError, ApiResult};
+use reqwest::header::HeaderMap;
+use reqwest::{Request, Response, Url};
+use serde_json::Value;
+use tracing::{debug, trace, warn};
+
+/// Replacement for redacted values
+pub const REDACTED: &str = "<redacted>";
+
+/// Name fragments that mark a header, parameter or field as secret
+const SENSITIVE_FRAGMENTS: &[&str] = &[
+    "authorization",
+    "apikey",
+    "api_key",
+    "api-key",
+    "cookie",
+    "password",
+    "secret",
+    "token",
+    "credential",
+    "service_role",
+];
+
+/// Check if a header, query parameter or JSON field name holds a secret
+#[must_use]
+pub fn is_sensitive(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    SENSITIVE_FRAGMENTS.iter().any(|fragment| name.contains(fragment))
+}
+
+/// Copy of `url` with sensitive query parameters redacted
+#[must_use]
+pub fn redact_url(url: &Url) -> String {
+    if url.query().is_none() {
+        return url.to_string();
+    }
+    let mut redacted = url.clone();
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(k, v)| {
+            let value = if is_sensitive(&k) { REDACTED.to_string() } else { v.into_owned() };
+            (k.into_owned(), value)
+        })
+        .collect();
+    redacted.query_pairs_mut().clear().extend_pairs(pairs);
+    redacted.to_string()
+}
+
+/// Header names and values with sensitive values redacted
+#[must_use]
+pub fn redact_headers(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if is_sensitive(name.as_str()) {
+                REDACTED.to_string()
+            } else {
+                value.to_str().unwrap_or("<binary>").to_string()
+            };
+            (name.to_string(), value)
+        })
+        .collect()
+}
+
+/// Redact sensitive fields anywhere in a JSON value
+pub fn redact_json(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                if is_sensitive(key) {
+                    *field = Value::String(REDACTED.to_string());
+                } else {
+                    redact_json(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_json),
+        _ => {}
+    }
+}
+
+/// Logs every request and its outcome
+///
+/// Method, URL, status and latency are logged at `debug`; headers and JSON
+/// bodies at `trace`; failures at `warn`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RequestLogger;
+
+impl Middleware for RequestLogger {
+    fn on_request(&self, request: &mut Request) -> ApiResult<()> {
+        debug!(method = %request.method(), url = %redact_url(request.url()), "Sending request");
+
+        let body = request
+            .body()
+            .and_then(reqwest::Body::as_bytes)
+            .and_then(|bytes| serde_json::from_slice::<Value>(bytes).ok())
+            .map_or(Value::Null, |mut body| {
+                redact_json(&mut body);
+                body
+            });
+        trace!(
+            headers = ?redact_headers(request.headers()),
+            body = %body,
+            "Request details"
+        );
+        Ok(())
+    }
+
+    fn on_response(&self, request: &RequestInfo, response: &Response) -> ApiResult<()> {
+        debug!(
+            method = %request.method,
+            url = %redact_url(&request.url),
+            status = response.status().as_u16(),
+            elapsed_ms = request.elapsed().as_millis(),
+            "Received response"
+        );
+        trace!(headers = ?redact_headers(response.headers()), "Response details");
+        Ok(())
+    }
+
+    fn on_error(&self, request: &RequestInfo, error: ApiError) -> ApiError {
+        warn!(
+            method = %request.method,
+            url = %redact_url(&request.url),
+            elapsed_ms = request.elapsed().as_millis(),
+            error = %error,
+            "Request failed"
+        );
+        error
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+
+    #[test]
+    fn test_redact_url() {
+        let url = Url::parse("https://x.supabase.co/rest/v1/posts?apikey=abc&select=id&access_token=t").unwrap();
+        let redacted = redact_url(&url);
+        assert!(!redacted.contains("abc"));
+        assert!(!redacted.contains("=t"));
+        assert!(redacted.contains("select=id"));
+    }
+
+    #[test]
+    fn test_redact_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer secret"));
+        headers.insert("apikey", HeaderValue::from_static("anon"));
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+        let redacted = redact_headers(&headers);
+        assert!(redacted.iter().all(|(_, v)| v != "Bearer secret" && v != "anon"));
+        assert!(redacted.contains(&("content-type".to_string(), "application/json".to_string())));
+    }
+
+    #[test]
+    fn test_redact_json_nested() {
+        let mut body = serde_json::json!({
+            "email": "a@b.c",
+            "password": "hunter2",
+            "session": {"refresh_token": "r", "user": {"id": 1}},
+            "items": [{"api_key": "k", "name": "n"}]
+        });
+        redact_json(&mut body);
+        assert_eq!(body["email"], "a@b.c");
+        assert_eq!(body["password"], REDACTED);
+        assert_eq!(body["session"]["refresh_token"], REDACTED);
+        assert_eq!(body["session"]["user"]["id"], 1);
+        assert_eq!(body["items"][0]["api_key"], REDACTED);
+        assert_eq!(body["items"][0]["name"], "n");
+    }
+}
@@ -0,0 +1,103 @@
+//! Request metrics per endpoint
+//!
+//! Records into the global `foodshare-telemetry` registry, so the numbers
+//! appear in [`MetricsRegistry::export_json`](foodshare_telemetry::MetricsRegistry::export_json)
+//! next to every other metric. Names are labeled by environment and endpoint:
+//!
+//! | Metric | Kind |
+//! |--------|------|
+//! | `api.<env>.<endpoint>.requests` | counter |
+//! | `api.<env>.<endpoint>.errors.4xx` / `.errors.5xx` | counter |
+//! | `api.<env>.<endpoint>.errors.transport` | counter (no response received) |
+//! | `api.<env>.<endpoint>.latency_ms` | histogram |
+//!
+//! Each attempt is recorded, so a request retried twice counts three times.
+
+use super::{Middleware, RequestInfo};
+use crate::config::{ClientConfig, Environment};
+use crate::error::{ApiError, ApiResult};
+use reqwest::Response;
+
+/// Records request count, errors by status class and latency
+#[derive(Debug, Clone, Copy)]
+pub struct RequestMetrics {
+    environment: Environment,
+}
+
+impl RequestMetrics {
+    /// Record metrics labeled with `environment`
+    #[must_use]
+    pub fn new(environment: Environment) -> Self {
+        Self { environment }
+    }
+
+    /// Record metrics labeled with the client's environment
+    #[must_use]
+    pub fn for_config(config: &ClientConfig) -> Self {
+        Self::new(config.environment)
+    }
+
+    fn name(self, request: &RequestInfo, metric: &str) -> String {
+        format!("api.{}.{}.{metric}", self.environment.as_str(), request.endpoint)
+    }
+
+    fn record_attempt(self, request: &RequestInfo) {
+        let metrics = foodshare_telemetry::metrics();
+        metrics.increment(&self.name(request, "requests"));
+        metrics.histogram(
+            &self.name(request, "latency_ms"),
+            request.elapsed().as_secs_f64() * 1000.0,
+        );
+    }
+}
+
+impl Middleware for RequestMetrics {
+    fn on_response(&self, request: &RequestInfo, response: &Response) -> ApiResult<()> {
+        self.record_attempt(request);
+        let status = response.status();
+        if status.is_client_error() || status.is_server_error() {
+            let class = format!("errors.{}xx", status.as_u16() / 100);
+            foodshare_telemetry::metrics().increment(&self.name(request, &class));
+        }
+        Ok(())
+    }
+
+    fn on_error(&self, request: &RequestInfo, error: ApiError) -> ApiError {
+        // Errors raised by other hooks after a response was recorded are not
+        // transport failures; only count requests that never got a response
+        if matches!(error, ApiError::Request(_) | ApiError::Timeout(_) | ApiError::Transport(_)) {
+            self.record_attempt(request);
+            foodshare_telemetry::metrics().increment(&self.name(request, "errors.transport"));
+        }
+        error
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::build_response;
+
+    #[test]
+    fn test_records_by_endpoint_and_status_class() {
+        let request = reqwest::Client::new()
+            .get("http://localhost/functions/v1/metrics-test/x")
+            .build()
+            .unwrap();
+        let info = RequestInfo::new(&request, "metrics-test");
+        let middleware = RequestMetrics::new(Environment::Staging);
+
+        let ok = build_response(200, [], Vec::new()).unwrap();
+        let not_found = build_response(404, [], Vec::new()).unwrap();
+        middleware.on_response(&info, &ok).unwrap();
+        middleware.on_response(&info, &not_found).unwrap();
+        let _ = middleware.on_error(&info, ApiError::Timeout(std::time::Duration::from_secs(1)));
+
+        let exported = foodshare_telemetry::metrics().export_json();
+        let counters = &exported["counters"];
+        assert_eq!(counters["api.staging.metrics-test.requests"], 3);
+        assert_eq!(counters["api.staging.metrics-test.errors.4xx"], 1);
+        assert_eq!(counters["api.staging.metrics-test.errors.transport"], 1);
+        assert_eq!(exported["histograms"]["api.staging.metrics-test.latency_ms"]["count"], 3);
+    }
+}
@@ -1,6 +1,30 @@
 //! Middleware components for request/response processing
 //!
-//! This module re-exports the resilience components from `foodshare-core`.
+//! Requests pass through a stack of [`Middleware`] hooks added with
+//! [`FoodshareClient::with_middleware`](crate::FoodshareClient::with_middleware).
+//! Built-ins:
+//!
+//! | Middleware | Effect |
+//! |------------|--------|
+//! | [`CorrelationId`] | `X-Request-ID` on every request, `X-Correlation-ID` per process |
+//! | [`TraceContextPropagation`] | W3C `traceparent` from the current `foodshare-telemetry` span |
+//! | [`RequestLogger`] | Request/response logging with secrets redacted |
+//! | [`RequestMetrics`] | Request counts, errors and latency per endpoint in `foodshare-telemetry` |
+//!
+//! This module also re-exports the resilience components from `foodshare-core`
+//! and provides the HTTP response cache.
+
+pub mod correlation;
+pub mod http_cache;
+pub mod logging;
+pub mod metrics;
+pub mod trace;
+
+pub use correlation::CorrelationId;
+pub use http_cache::{CachedResponse, HttpCache};
+pub use logging::RequestLogger;
+pub use metrics::RequestMetrics;
+pub use trace::TraceContextPropagation;
 
 // Re-export from foodshare-core for convenience
 pub use foodshare_core::rate_limit::{RateLimitConfig, RateLimitStatus, RateLimiter};
@@ -8,3 +32,73 @@ pub use foodshare_core::retry::{
     retry, CircuitBreaker, CircuitBreakerConfig, CircuitBreakerError, CircuitState, RetryConfig,
     RetryResult,
 };
+
+use crate::error::{ApiError, ApiResult};
+use reqwest::{Method, Request, Response, Url};
+use std::time::{Duration, Instant};
+
+/// Hooks run around every request the client sends
+///
+/// `on_request` hooks run in the order middleware was added; `on_response`
+/// and `on_error` run in reverse, so the first middleware added sees the
+/// request first and the outcome last. Hooks run once per attempt, so a
+/// retried request passes through them again.
+pub trait Middleware: Send + Sync {
+    /// Inspect or modify a request before it is sent
+    ///
+    /// # Errors
+    ///
+    /// Returning an error aborts the request; it is passed through `on_error`.
+    fn on_request(&self, request: &mut Request) -> ApiResult<()> {
+        let _ = request;
+        Ok(())
+    }
+
+    /// Inspect a response before it is decoded
+    ///
+    /// # Errors
+    ///
+    /// Returning an error turns the response into a failure, e.g. to map a
+    /// status code to a domain error; it is passed through `on_error`.
+    fn on_response(&self, request: &RequestInfo, response: &Response) -> ApiResult<()> {
+        let _ = (request, response);
+        Ok(())
+    }
+
+    /// Observe or replace an error from the transport or another hook
+    fn on_error(&self, request: &RequestInfo, error: ApiError) -> ApiError {
+        let _ = request;
+        error
+    }
+}
+
+/// What response and error hooks know about the request
+#[derive(Debug, Clone)]
+pub struct RequestInfo {
+    /// HTTP method
+    pub method: Method,
+    /// Request URL
+    pub url: Url,
+    /// Endpoint name (first path segment after the base URL)
+    pub endpoint: String,
+    started: Instant,
+}
+
+impl RequestInfo {
+    /// Capture a request to `endpoint` about to be sent
+    #[must_use]
+    pub fn new(request: &Request, endpoint: impl Into<String>) -> Self {
+        Self {
+            method: request.method().clone(),
+            url: request.url().clone(),
+            endpoint: endpoint.into(),
+            started: Instant::now(),
+        }
+    }
+
+    /// Time since the request was handed to the transport
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+}
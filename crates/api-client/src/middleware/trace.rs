@@ -0,0 +1,32 @@
+//! W3C trace context propagation
+
+use super::Middleware;
+use crate::error::ApiResult;
+use foodshare_telemetry::TraceContext;
+use reqwest::header::HeaderValue;
+use reqwest::Request;
+
+/// `traceparent` header name
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+
+/// Sends the current span's trace context as a `traceparent` header
+///
+/// Each request gets a child of the active `foodshare-telemetry` span, so the
+/// backend's spans join the CLI's trace. A `traceparent` already set on the
+/// request is left alone.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TraceContextPropagation;
+
+impl Middleware for TraceContextPropagation {
+    fn on_request(&self, request: &mut Request) -> ApiResult<()> {
+        let headers = request.headers_mut();
+        if headers.contains_key(TRACEPARENT_HEADER) {
+            return Ok(());
+        }
+        let context = TraceContext::current().child();
+        if let Ok(value) = HeaderValue::from_str(&context.to_traceparent()) {
+            headers.insert(TRACEPARENT_HEADER, value);
+        }
+        Ok(())
+    }
+}
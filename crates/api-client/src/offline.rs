@@ -0,0 +1,286 @@
+//! Offline queue for mutating requests
+//!
+//! When a client has an [`OfflineQueue`], a POST/PUT/PATCH/DELETE that fails
+//! because the backend is unreachable is written to disk instead of being
+//! lost, and the call returns [`ApiError::Queued`](crate::ApiError::Queued). Calling
+//! [`FoodshareClient::replay_offline_queue`](crate::FoodshareClient::replay_offline_queue)
+//! on the next run sends the queued requests again, in order, with the same
+//! `Idempotency-Key` header so the backend can discard duplicates.
+//!
+//! The queue is stored as JSON Lines and rewritten atomically on every change.
+
+use crate::error::ApiResult;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+/// A request waiting to be replayed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueuedRequest {
+    /// Idempotency key sent with every attempt
+    pub idempotency_key: String,
+    /// HTTP method
+    pub method: String,
+    /// Absolute request URL
+    pub url: String,
+    /// JSON body
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<serde_json::Value>,
+    /// When the request was queued (Unix seconds)
+    pub queued_at: u64,
+}
+
+impl QueuedRequest {
+    /// Create a queue entry stamped with the current time
+    pub fn new(
+        idempotency_key: impl Into<String>,
+        method: impl Into<String>,
+        url: impl Into<String>,
+        body: Option<serde_json::Value>,
+    ) -> Self {
+        Self {
+            idempotency_key: idempotency_key.into(),
+            method: method.into(),
+            url: url.into(),
+            body,
+            queued_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+        }
+    }
+
+    /// Check if the request goes to the project at `project_url`
+    #[must_use]
+    pub fn is_for(&self, project_url: &str) -> bool {
+        self.url
+            .strip_prefix(project_url.trim_end_matches('/'))
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(['/', '?']))
+    }
+}
+
+/// Outcome of replaying the offline queue
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ReplayReport {
+    /// Requests that succeeded and were removed
+    pub replayed: usize,
+    /// Requests the API rejected permanently and were removed
+    pub dropped: usize,
+    /// Requests still queued
+    pub remaining: usize,
+}
+
+/// Disk-backed queue of requests to replay
+#[derive(Debug)]
+pub struct OfflineQueue {
+    path: PathBuf,
+    entries: Mutex<Vec<QueuedRequest>>,
+}
+
+impl OfflineQueue {
+    /// Open the queue at `path`, loading any requests already queued
+    ///
+    /// Unreadable lines (e.g. from a torn write) are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read.
+    pub fn open(path: impl Into<PathBuf>) -> ApiResult<Self> {
+        let path = path.into();
+        let entries = match fs::read_to_string(&path) {
+            Ok(contents) => contents
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .filter_map(|line| match serde_json::from_str(line) {
+                    Ok(entry) => Some(entry),
+                    Err(e) => {
+                        warn!(path = %path.display(), error = %e, "Skipping malformed queue entry");
+                        None
+                    }
+                })
+                .collect(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Self {
+            path,
+            entries: Mutex::new(entries),
+        })
+    }
+
+    /// Open the queue of the project at `project_url` in the default location
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read.
+    pub fn open_default(project_url: &str) -> ApiResult<Self> {
+        Self::open(Self::default_path(project_url))
+    }
+
+    /// Default queue location of the project at `project_url`
+    /// (`<data dir>/foodshare-tools/offline-queue-<host>.jsonl`)
+    ///
+    /// Requests are replayed with the credentials of the client replaying
+    /// them, so every project keeps its own queue.
+    #[must_use]
+    pub fn default_path(project_url: &str) -> PathBuf {
+        let project = project_url
+            .split_once("://")
+            .map_or(project_url, |(_, rest)| rest)
+            .trim_end_matches('/');
+        let name: String = project
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-') { c } else { '_' })
+            .collect();
+        dirs::data_local_dir()
+            .unwrap_or_else(|| PathBuf::from(".local/share"))
+            .join("foodshare-tools")
+            .join(format!("offline-queue-{name}.jsonl"))
+    }
+
+    /// Queue file path
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Number of queued requests
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Check if the queue is empty
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Snapshot of the queued requests, oldest first
+    #[must_use]
+    pub fn entries(&self) -> Vec<QueuedRequest> {
+        self.lock().clone()
+    }
+
+    /// Append a request and persist the queue
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the queue cannot be written.
+    pub fn push(&self, request: QueuedRequest) -> ApiResult<()> {
+        let mut entries = self.lock();
+        entries.push(request);
+        self.persist(&entries)
+    }
+
+    /// Remove a request by idempotency key and persist the queue
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the queue cannot be written.
+    pub fn remove(&self, idempotency_key: &str) -> ApiResult<bool> {
+        let mut entries = self.lock();
+        let before = entries.len();
+        entries.retain(|e| e.idempotency_key != idempotency_key);
+        if entries.len() == before {
+            return Ok(false);
+        }
+        self.persist(&entries)?;
+        Ok(true)
+    }
+
+    /// Drop every queued request
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the queue cannot be written.
+    pub fn clear(&self) -> ApiResult<()> {
+        let mut entries = self.lock();
+        entries.clear();
+        self.persist(&entries)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<QueuedRequest>> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Rewrite the queue file via a temporary file and rename
+    fn persist(&self, entries: &[QueuedRequest]) -> ApiResult<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let tmp = self.path.with_extension("jsonl.tmp");
+        let mut file = fs::File::create(&tmp)?;
+        for entry in entries {
+            serde_json::to_writer(&mut file, entry)?;
+            file.write_all(b"\n")?;
+        }
+        file.sync_all()?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_persists_across_open() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("queue").join("offline.jsonl");
+
+        let queue = OfflineQueue::open(&path).unwrap();
+        assert!(queue.is_empty());
+        queue
+            .push(QueuedRequest::new("k1", "POST", "https://example.com/a", Some(serde_json::json!({"n": 1}))))
+            .unwrap();
+        queue.push(QueuedRequest::new("k2", "DELETE", "https://example.com/b", None)).unwrap();
+
+        let reopened = OfflineQueue::open(&path).unwrap();
+        assert_eq!(reopened.entries(), queue.entries());
+
+        assert!(reopened.remove("k1").unwrap());
+        assert!(!reopened.remove("k1").unwrap());
+        let keys: Vec<_> = OfflineQueue::open(&path)
+            .unwrap()
+            .entries()
+            .into_iter()
+            .map(|e| e.idempotency_key)
+            .collect();
+        assert_eq!(keys, ["k2"]);
+    }
+
+    #[test]
+    fn test_skips_torn_lines() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("offline.jsonl");
+        let entry = serde_json::to_string(&QueuedRequest::new("k1", "POST", "https://example.com", None)).unwrap();
+        fs::write(&path, format!("{entry}\n{{\"idempotency_key\": \"k2\", \"meth")).unwrap();
+
+        let queue = OfflineQueue::open(&path).unwrap();
+        assert_eq!(queue.len(), 1);
+    }
+
+    #[test]
+    fn test_scoped_by_project() {
+        let request = QueuedRequest::new("k1", "POST", "https://staging.example.com/rest/v1/posts", None);
+        assert!(request.is_for("https://staging.example.com"));
+        assert!(request.is_for("https://staging.example.com/"));
+        assert!(!request.is_for("https://example.com"));
+        assert!(!request.is_for("https://staging.example.co"));
+
+        assert_ne!(
+            OfflineQueue::default_path("https://staging.example.com"),
+            OfflineQueue::default_path("https://example.com")
+        );
+        assert!(OfflineQueue::default_path("http://127.0.0.1:54321")
+            .ends_with("offline-queue-127.0.0.1_54321.jsonl"));
+    }
+}
@@ -0,0 +1,189 @@
+//! Typed pagination for list endpoints
+//!
+//! List endpoints return a [`Paginated<T>`] page carrying the token for the
+//! next page. [`paginate`] turns a page-fetching function into a stream of
+//! pages that follows those tokens until the listing is exhausted, and
+//! [`items`] flattens it into a stream of individual items.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use foodshare_api_client::endpoints::products::ListProductsParams;
+//! use foodshare_api_client::FoodshareClient;
+//! use futures_util::TryStreamExt;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = FoodshareClient::new()?;
+//! let params = ListProductsParams::new().with_post_type("food").with_limit(50);
+//!
+//! let products: Vec<_> = client.products().items(params).try_collect().await?;
+//! println!("{} products", products.len());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::ApiResult;
+use futures_util::stream::{self, Stream, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+
+/// Position of a page within a listing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PageToken {
+    /// Opaque cursor returned by the server
+    Cursor(String),
+    /// Number of items to skip
+    Offset(usize),
+}
+
+/// One page of a paginated listing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Paginated<T> {
+    /// Items on this page
+    pub items: Vec<T>,
+    /// Token for the next page (`None` on the last page)
+    pub next: Option<PageToken>,
+    /// Total number of items across all pages, if the server reports it
+    pub total: Option<u64>,
+}
+
+impl<T> Paginated<T> {
+    /// Create a page
+    #[must_use]
+    pub fn new(items: Vec<T>, next: Option<PageToken>) -> Self {
+        Self {
+            items,
+            next,
+            total: None,
+        }
+    }
+
+    /// Builder-style method to set the total item count
+    #[must_use]
+    pub fn with_total(mut self, total: u64) -> Self {
+        self.total = Some(total);
+        self
+    }
+
+    /// Check if there are more pages after this one
+    #[must_use]
+    pub fn has_more(&self) -> bool {
+        self.next.is_some()
+    }
+}
+
+/// Where the page stream is
+enum PageState {
+    /// Fetch with this token (`None` = first page)
+    Fetch(Option<PageToken>),
+    /// Stop
+    Done,
+}
+
+/// Stream pages from `fetch`, following each page's `next` token
+///
+/// `fetch` is called with `None` for the first page. The stream ends after a
+/// page without a `next` token, after an error, or if the server hands back
+/// the token it was just given (which would otherwise loop forever).
+pub fn paginate<T, F, Fut>(fetch: F) -> impl Stream<Item = ApiResult<Paginated<T>>>
+where
+    F: FnMut(Option<PageToken>) -> Fut,
+    Fut: Future<Output = ApiResult<Paginated<T>>>,
+{
+    stream::unfold(
+        (PageState::Fetch(None), fetch),
+        |(state, mut fetch)| async move {
+            let PageState::Fetch(token) = state else {
+                return None;
+            };
+
+            match fetch(token.clone()).await {
+                Ok(page) => {
+                    let next = match page.next {
+                        Some(ref next) if token.as_ref() != Some(next) => {
+                            PageState::Fetch(Some(next.clone()))
+                        }
+                        _ => PageState::Done,
+                    };
+                    Some((Ok(page), (next, fetch)))
+                }
+                Err(e) => Some((Err(e), (PageState::Done, fetch))),
+            }
+        },
+    )
+}
+
+/// Flatten a stream of pages into a stream of items
+pub fn items<T>(
+    pages: impl Stream<Item = ApiResult<Paginated<T>>>,
+) -> impl Stream<Item = ApiResult<T>> {
+    pages
+        .map_ok(|page| stream::iter(page.items.into_iter().map(Ok)))
+        .try_flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ApiError;
+    use futures_util::StreamExt;
+
+    fn offset_pages(
+        data: Vec<u32>,
+        page_size: usize,
+    ) -> impl FnMut(Option<PageToken>) -> std::future::Ready<ApiResult<Paginated<u32>>> {
+        move |token| {
+            let offset = match token {
+                Some(PageToken::Offset(n)) => n,
+                _ => 0,
+            };
+            let end = (offset + page_size).min(data.len());
+            let next = (end < data.len()).then_some(PageToken::Offset(end));
+            std::future::ready(Ok(Paginated::new(data[offset..end].to_vec(), next)))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_follows_offsets() {
+        let pages: Vec<_> = paginate(offset_pages((0..7).collect(), 3)).collect().await;
+        assert_eq!(pages.len(), 3);
+
+        let all: Vec<u32> = items(paginate(offset_pages((0..7).collect(), 3)))
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(all, (0..7).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_stops_on_repeated_cursor() {
+        let pages: Vec<_> = paginate(|_| {
+            std::future::ready(Ok(Paginated::new(
+                vec![1],
+                Some(PageToken::Cursor("same".to_string())),
+            )))
+        })
+        .collect()
+        .await;
+        assert_eq!(pages.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_stops_after_error() {
+        let mut calls = 0;
+        let results: Vec<ApiResult<Paginated<u32>>> = paginate(|_| {
+            calls += 1;
+            std::future::ready(if calls == 1 {
+                Ok(Paginated::new(vec![1], Some(PageToken::Offset(1))))
+            } else {
+                Err(ApiError::RateLimited)
+            })
+        })
+        .collect()
+        .await;
+
+        assert_eq!(results.len(), 2);
+        assert!(results[1].is_err());
+    }
+}
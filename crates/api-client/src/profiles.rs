@@ -0,0 +1,341 @@
+//! Named environment profiles
+//!
+//! One TOML file describes every environment a tool can talk to. Keys are
+//! stored as references to an environment variable or a keychain entry, so
+//! the file itself can be committed:
+//!
+//! ```toml
+//! default = "staging"
+//!
+//! [environments.local]
+//! environment = "development"
+//! base_url = "http://localhost:54321/functions/v1"
+//! anon_key = { env = "SUPABASE_ANON_KEY" }
+//!
+//! [environments.staging]
+//! environment = "staging"
+//! base_url = "https://staging.foodshare.club/functions/v1"
+//! anon_key = { env = "STAGING_SUPABASE_ANON_KEY" }
+//!
+//! [environments.prod]
+//! environment = "production"
+//! base_url = "https://api.foodshare.club/functions/v1"
+//! service_role_key = { keychain = "prod-service-role" }
+//! timeout_secs = 60
+//! ```
+//!
+//! The active profile is the one named by `--env`, then `FOODSHARE_ENV`,
+//! then `default`. Configs built from a production profile have the
+//! [production guard](crate::ClientConfig::production_guard) enabled.
+
+use crate::config::{ClientConfig, EndpointConfig, Environment};
+use crate::error::{ApiError, ApiResult};
+use foodshare_core::keychain::{FileKeychain, Keychain, MemoryKeychain};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Environment variable naming the profiles file
+pub const PROFILES_FILE_ENV: &str = "FOODSHARE_ENVIRONMENTS";
+
+/// Keychain service holding keys referenced by profiles
+pub const KEYCHAIN_SERVICE: &str = "foodshare-environments";
+
+/// Reference to a secret key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyRef {
+    /// Read from an environment variable
+    Env(String),
+    /// Read from the keychain (account name under [`KEYCHAIN_SERVICE`])
+    Keychain(String),
+    /// Literal value, for non-secret keys such as a local anon key
+    Value(String),
+}
+
+impl KeyRef {
+    /// Resolve the key, reading keychain references from `keychain`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the variable is unset or the keychain entry is
+    /// missing or unreadable.
+    pub fn resolve(&self, keychain: &dyn Keychain) -> ApiResult<String> {
+        match self {
+            Self::Env(var) => env::var(var).map_err(|_| ApiError::missing_env(var)),
+            Self::Keychain(account) => keychain
+                .get(KEYCHAIN_SERVICE, account)?
+                .ok_or_else(|| ApiError::config(format!("keychain entry '{account}' not found"))),
+            Self::Value(value) => Ok(value.clone()),
+        }
+    }
+
+    fn is_keychain(&self) -> bool {
+        matches!(self, Self::Keychain(_))
+    }
+}
+
+/// One named environment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    /// Environment kind, which picks retry and rate limit defaults
+    pub environment: Environment,
+    /// Base URL for Edge Functions
+    pub base_url: String,
+    /// BFF endpoint URL (derived from `base_url` if not set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bff_url: Option<String>,
+    /// Auth URL (derived from `base_url` if not set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_url: Option<String>,
+    /// Anonymous key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anon_key: Option<KeyRef>,
+    /// Service role key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service_role_key: Option<KeyRef>,
+    /// Request timeout in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    /// Per-endpoint resilience overrides
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub endpoints: BTreeMap<String, EndpointConfig>,
+}
+
+impl Profile {
+    /// Build a client configuration, reading keychain keys from the default keychain
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a key cannot be resolved.
+    pub fn to_client_config(&self) -> ApiResult<ClientConfig> {
+        let uses_keychain = [&self.anon_key, &self.service_role_key]
+            .into_iter()
+            .flatten()
+            .any(KeyRef::is_keychain);
+        if uses_keychain {
+            self.to_client_config_with(&FileKeychain::default_keychain()?)
+        } else {
+            self.to_client_config_with(&MemoryKeychain::new())
+        }
+    }
+
+    /// Build a client configuration, reading keychain keys from `keychain`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a key cannot be resolved.
+    pub fn to_client_config_with(&self, keychain: &dyn Keychain) -> ApiResult<ClientConfig> {
+        let preset = match self.environment {
+            Environment::Development => ClientConfig::development(),
+            Environment::Staging => ClientConfig::staging(),
+            Environment::Production => ClientConfig::production(),
+        };
+        let mut config = preset.with_base_url(&self.base_url);
+
+        if let Some(ref url) = self.bff_url {
+            config.bff_url.clone_from(url);
+        }
+        config.auth_url.clone_from(&self.auth_url);
+        config.anon_key = self.anon_key.as_ref().map(|k| k.resolve(keychain)).transpose()?;
+        config.service_role_key = self
+            .service_role_key
+            .as_ref()
+            .map(|k| k.resolve(keychain))
+            .transpose()?;
+        if let Some(secs) = self.timeout_secs {
+            config.timeout = Duration::from_secs(secs);
+        }
+        config.endpoints.clone_from(&self.endpoints);
+        config.production_guard = true;
+
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+/// A profiles file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Profiles {
+    /// Profile used when none is named
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    /// Profiles by name
+    #[serde(default)]
+    pub environments: BTreeMap<String, Profile>,
+}
+
+impl Profiles {
+    /// Parse profiles from TOML
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the TOML is invalid.
+    pub fn parse(content: &str) -> ApiResult<Self> {
+        toml::from_str(content).map_err(|e| ApiError::config(format!("invalid profiles file: {e}")))
+    }
+
+    /// Load profiles from a file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed.
+    pub fn load(path: &Path) -> ApiResult<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Self::parse(&content)
+            .map_err(|e| ApiError::config(format!("{}: {e}", path.display())))
+    }
+
+    /// Find and load the profiles file, if there is one
+    ///
+    /// Looks at `FOODSHARE_ENVIRONMENTS`, then `.foodshare/environments.toml`
+    /// in the working directory, then `<config dir>/foodshare-tools/environments.toml`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file was found but cannot be loaded.
+    pub fn discover() -> ApiResult<Option<Self>> {
+        if let Ok(path) = env::var(PROFILES_FILE_ENV) {
+            return Self::load(Path::new(&path)).map(Some);
+        }
+
+        let mut candidates = vec![PathBuf::from(".foodshare/environments.toml")];
+        if let Some(dir) = dirs::config_dir() {
+            candidates.push(dir.join("foodshare-tools").join("environments.toml"));
+        }
+        candidates
+            .iter()
+            .find(|path| path.exists())
+            .map(|path| Self::load(path))
+            .transpose()
+    }
+
+    /// Look up a profile by name
+    ///
+    /// Falls back to an environment alias (`prod`, `production`, `dev`, ...)
+    /// when exactly one profile has that environment kind.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        if let Some(profile) = self.environments.get(name) {
+            return Some(profile);
+        }
+        let environment = Environment::parse(name)?;
+        let mut matches = self
+            .environments
+            .values()
+            .filter(|p| p.environment == environment);
+        let profile = matches.next()?;
+        matches.next().is_none().then_some(profile)
+    }
+
+    /// Select a profile by `name`, `FOODSHARE_ENV` or the file's default
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no profile is named or the named profile does
+    /// not exist.
+    pub fn select(&self, name: Option<&str>) -> ApiResult<&Profile> {
+        let name = name
+            .map(str::to_string)
+            .or_else(|| env::var("FOODSHARE_ENV").ok())
+            .or_else(|| self.default.clone())
+            .ok_or_else(|| ApiError::config("no environment selected (use --env or FOODSHARE_ENV)"))?;
+
+        self.get(&name).ok_or_else(|| {
+            let available: Vec<_> = self.environments.keys().map(String::as_str).collect();
+            ApiError::config(format!(
+                "unknown environment '{name}' (available: {})",
+                available.join(", ")
+            ))
+        })
+    }
+}
+
+/// Build the client configuration for the selected environment
+///
+/// Uses the profiles file if one is found. Without one, `name` selects a
+/// built-in preset (`local`, `staging`, `prod`) and no name falls back to
+/// [`ClientConfig::from_env`].
+///
+/// # Errors
+///
+/// Returns an error if the profiles file is invalid, the environment is
+/// unknown or a key cannot be resolved.
+pub fn select_config(name: Option<&str>) -> ApiResult<ClientConfig> {
+    if let Some(profiles) = Profiles::discover()? {
+        return profiles.select(name)?.to_client_config();
+    }
+
+    match name {
+        None => ClientConfig::from_env(),
+        Some(name) => match Environment::parse(name) {
+            Some(Environment::Development) => Ok(ClientConfig::development()),
+            Some(Environment::Staging) => Ok(ClientConfig::staging()),
+            Some(Environment::Production) => Ok(ClientConfig::production()),
+            None => Err(ApiError::config(format!(
+                "unknown environment '{name}' and no profiles file found"
+            ))),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROFILES: &str = r#"
+default = "local"
+
+[environments.local]
+environment = "development"
+base_url = "http://localhost:54321/functions/v1"
+anon_key = { value = "local-anon" }
+
+[environments.prod]
+environment = "production"
+base_url = "https://api.example.com/functions/v1"
+service_role_key = { keychain = "prod-service-role" }
+timeout_secs = 60
+
+[environments.prod.endpoints.translate-batch]
+timeout = 120
+"#;
+
+    #[test]
+    fn test_parse_and_select() {
+        let profiles = Profiles::parse(PROFILES).unwrap();
+        assert_eq!(profiles.environments.len(), 2);
+
+        let local = profiles.select(Some("local")).unwrap();
+        assert_eq!(local.environment, Environment::Development);
+        assert_eq!(local.anon_key, Some(KeyRef::Value("local-anon".to_string())));
+
+        // Environment aliases resolve when unambiguous
+        let prod = profiles.get("production").unwrap();
+        assert_eq!(prod.base_url, "https://api.example.com/functions/v1");
+
+        let err = profiles.select(Some("qa")).unwrap_err().to_string();
+        assert!(err.contains("available: local, prod"));
+    }
+
+    #[test]
+    fn test_to_client_config_resolves_keys() {
+        let profiles = Profiles::parse(PROFILES).unwrap();
+        let keychain = MemoryKeychain::new();
+        keychain.set(KEYCHAIN_SERVICE, "prod-service-role", "service-key").unwrap();
+
+        let config = profiles.environments["prod"].to_client_config_with(&keychain).unwrap();
+        assert_eq!(config.environment, Environment::Production);
+        assert_eq!(config.bff_url, "https://api.example.com/functions/v1/bff");
+        assert_eq!(config.service_role_key.as_deref(), Some("service-key"));
+        assert_eq!(config.anon_key, None);
+        assert_eq!(config.timeout, Duration::from_secs(60));
+        assert_eq!(config.timeout_for("translate-batch"), Duration::from_secs(120));
+        assert!(config.production_guard);
+
+        let missing = profiles.environments["prod"].to_client_config_with(&MemoryKeychain::new());
+        assert!(matches!(missing, Err(ApiError::Config(_))));
+    }
+}
@@ -0,0 +1,101 @@
+//! Transfer progress reporting
+//!
+//! [`FoodshareClient::download`](crate::FoodshareClient::download) and
+//! [`FoodshareClient::upload`](crate::FoodshareClient::upload) call a
+//! callback with a [`TransferProgress`] as bytes move, so CLI commands can
+//! drive a progress bar. Callbacks run on the request task and should return
+//! quickly.
+
+use futures_util::stream;
+use std::sync::Arc;
+
+/// Bytes handed to the transport per upload chunk
+pub const UPLOAD_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Progress of an upload or download
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransferProgress {
+    /// Bytes transferred so far
+    pub transferred: u64,
+    /// Total size, if known (downloads without `Content-Length` have none)
+    pub total: Option<u64>,
+}
+
+impl TransferProgress {
+    /// Fraction complete between 0.0 and 1.0, if the total is known
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn fraction(&self) -> Option<f64> {
+        match self.total {
+            Some(0) => Some(1.0),
+            Some(total) => Some((self.transferred as f64 / total as f64).min(1.0)),
+            None => None,
+        }
+    }
+
+    /// Check if every byte has been transferred
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.total.is_some_and(|total| self.transferred >= total)
+    }
+}
+
+/// Callback invoked as bytes are transferred
+pub type ProgressCallback = Arc<dyn Fn(TransferProgress) + Send + Sync>;
+
+/// Streaming request body that reports each chunk as it is sent
+pub(crate) fn upload_body(data: Vec<u8>, on_progress: ProgressCallback) -> reqwest::Body {
+    let total = data.len() as u64;
+    on_progress(TransferProgress {
+        transferred: 0,
+        total: Some(total),
+    });
+
+    let data = Arc::new(data);
+    let chunks = stream::unfold(0usize, move |offset| {
+        let data = Arc::clone(&data);
+        let on_progress = Arc::clone(&on_progress);
+        async move {
+            if offset >= data.len() {
+                return None;
+            }
+            let end = (offset + UPLOAD_CHUNK_SIZE).min(data.len());
+            let chunk = data[offset..end].to_vec();
+            on_progress(TransferProgress {
+                transferred: end as u64,
+                total: Some(total),
+            });
+            Some((Ok::<_, std::io::Error>(chunk), end))
+        }
+    });
+    reqwest::Body::wrap_stream(chunks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fraction() {
+        let half = TransferProgress {
+            transferred: 50,
+            total: Some(100),
+        };
+        assert_eq!(half.fraction(), Some(0.5));
+        assert!(!half.is_complete());
+
+        let unknown = TransferProgress {
+            transferred: 50,
+            total: None,
+        };
+        assert_eq!(unknown.fraction(), None);
+        assert!(!unknown.is_complete());
+
+        let empty = TransferProgress {
+            transferred: 0,
+            total: Some(0),
+        };
+        assert_eq!(empty.fraction(), Some(1.0));
+        assert!(empty.is_complete());
+    }
+}
@@ -0,0 +1,251 @@
+//! Authenticated sessions and their persistence
+//!
+//! A [`Session`] holds the bearer token sent with every request. Sessions can
+//! be persisted across runs with a [`SessionStore`] backed by the
+//! `foodshare-core` keychain.
+
+use crate::config::Environment;
+use crate::error::ApiResult;
+use foodshare_core::keychain::Keychain;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Keychain service name for stored sessions
+const KEYCHAIN_SERVICE: &str = "foodshare-api-client";
+
+/// Seconds before expiry at which a session is treated as expired
+const EXPIRY_LEEWAY_SECS: u64 = 30;
+
+/// Authenticated user attached to a session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthUser {
+    /// User ID
+    pub id: String,
+    /// Email address
+    #[serde(default)]
+    pub email: Option<String>,
+    /// Database role (e.g. "authenticated")
+    #[serde(default)]
+    pub role: Option<String>,
+}
+
+/// Bearer credentials used to authorize requests
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Session {
+    /// Access token sent as `Authorization: Bearer <token>`
+    pub access_token: String,
+    /// Refresh token, if the session can be renewed
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    /// Token type (usually "bearer")
+    #[serde(default = "default_token_type")]
+    pub token_type: String,
+    /// Expiry as Unix seconds (`None` = never expires)
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+    /// Signed-in user
+    #[serde(default)]
+    pub user: Option<AuthUser>,
+}
+
+fn default_token_type() -> String {
+    "bearer".to_string()
+}
+
+impl fmt::Debug for Session {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Session")
+            .field("access_token", &"<redacted>")
+            .field("refresh_token", &self.refresh_token.as_ref().map(|_| "<redacted>"))
+            .field("token_type", &self.token_type)
+            .field("expires_at", &self.expires_at)
+            .field("user", &self.user)
+            .finish()
+    }
+}
+
+impl Session {
+    /// Create a non-expiring session that authorizes with a service key
+    pub fn service_key(key: impl Into<String>) -> Self {
+        Self {
+            access_token: key.into(),
+            refresh_token: None,
+            token_type: default_token_type(),
+            expires_at: None,
+            user: None,
+        }
+    }
+
+    /// Check if the access token has expired (or is about to)
+    #[must_use]
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|at| unix_now() + EXPIRY_LEEWAY_SECS >= at)
+    }
+
+    /// Check if the session can be renewed with a refresh token
+    #[must_use]
+    pub fn can_refresh(&self) -> bool {
+        self.refresh_token.is_some()
+    }
+}
+
+/// Token response returned by the Auth API
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default = "default_token_type")]
+    token_type: String,
+    #[serde(default)]
+    expires_in: Option<u64>,
+    #[serde(default)]
+    expires_at: Option<u64>,
+    #[serde(default)]
+    user: Option<AuthUser>,
+}
+
+impl From<TokenResponse> for Session {
+    fn from(token: TokenResponse) -> Self {
+        let expires_at = token
+            .expires_at
+            .or_else(|| token.expires_in.map(|secs| unix_now() + secs));
+
+        Self {
+            access_token: token.access_token,
+            refresh_token: token.refresh_token,
+            token_type: token.token_type,
+            expires_at,
+            user: token.user,
+        }
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Persists a session in a keychain
+#[derive(Clone)]
+pub struct SessionStore {
+    keychain: Arc<dyn Keychain>,
+    account: String,
+}
+
+impl SessionStore {
+    /// Create a store for the given keychain account
+    pub fn new(keychain: Arc<dyn Keychain>, account: impl Into<String>) -> Self {
+        Self {
+            keychain,
+            account: account.into(),
+        }
+    }
+
+    /// Create a store with one account per environment
+    pub fn for_environment(keychain: Arc<dyn Keychain>, environment: Environment) -> Self {
+        let account = match environment {
+            Environment::Development => "development",
+            Environment::Staging => "staging",
+            Environment::Production => "production",
+        };
+        Self::new(keychain, account)
+    }
+
+    /// Keychain account the session is stored under
+    #[must_use]
+    pub fn account(&self) -> &str {
+        &self.account
+    }
+
+    /// Load the stored session, if any
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the keychain cannot be read or the stored value is malformed.
+    pub fn load(&self) -> ApiResult<Option<Session>> {
+        match self.keychain.get(KEYCHAIN_SERVICE, &self.account)? {
+            Some(json) => Ok(Some(serde_json::from_str(&json)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Store a session, replacing any previous one
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the keychain cannot be written.
+    pub fn save(&self, session: &Session) -> ApiResult<()> {
+        let json = serde_json::to_string(session)?;
+        self.keychain.set(KEYCHAIN_SERVICE, &self.account, &json)?;
+        Ok(())
+    }
+
+    /// Remove the stored session, returning whether one existed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the keychain cannot be written.
+    pub fn clear(&self) -> ApiResult<bool> {
+        Ok(self.keychain.delete(KEYCHAIN_SERVICE, &self.account)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foodshare_core::keychain::MemoryKeychain;
+
+    #[test]
+    fn test_token_response_into_session() {
+        let json = r#"{
+            "access_token": "at",
+            "token_type": "bearer",
+            "expires_in": 3600,
+            "refresh_token": "rt",
+            "user": {"id": "u1", "email": "a@example.com", "role": "authenticated", "aud": "authenticated"}
+        }"#;
+
+        let session: Session = serde_json::from_str::<TokenResponse>(json).unwrap().into();
+        assert_eq!(session.access_token, "at");
+        assert!(session.can_refresh());
+        assert!(!session.is_expired());
+        assert_eq!(session.user.unwrap().email.as_deref(), Some("a@example.com"));
+    }
+
+    #[test]
+    fn test_expiry() {
+        let mut session = Session::service_key("key");
+        assert!(!session.is_expired());
+        assert!(!session.can_refresh());
+
+        session.expires_at = Some(unix_now() + 10);
+        assert!(session.is_expired(), "within leeway counts as expired");
+    }
+
+    #[test]
+    fn test_debug_redacts_tokens() {
+        let mut session = Session::service_key("super-secret");
+        session.refresh_token = Some("also-secret".to_string());
+        let debug = format!("{session:?}");
+        assert!(!debug.contains("secret"));
+    }
+
+    #[test]
+    fn test_store_roundtrip() {
+        let store = SessionStore::for_environment(Arc::new(MemoryKeychain::new()), Environment::Staging);
+        assert_eq!(store.account(), "staging");
+        assert_eq!(store.load().unwrap(), None);
+
+        let session = Session::service_key("key");
+        store.save(&session).unwrap();
+        assert_eq!(store.load().unwrap(), Some(session));
+
+        assert!(store.clear().unwrap());
+        assert_eq!(store.load().unwrap(), None);
+    }
+}
@@ -0,0 +1,300 @@
+//! Record/replay of HTTP exchanges ("cassettes")
+//!
+//! A [`RecordingTransport`] forwards requests to a real transport and appends
+//! each request/response pair to a cassette file. A [`ReplayTransport`] later
+//! answers the same requests from that file without any network access, which
+//! is what the CLI's `--offline-fixtures` flag uses.
+//!
+//! Requests are matched on method, path and query, and JSON body; the host is
+//! ignored so a cassette recorded against staging replays against any
+//! configuration. `Set-Cookie` headers are never written to disk.
+
+use super::{build_response, path_and_query, Transport};
+use crate::error::{ApiError, ApiResult};
+use futures_util::future::BoxFuture;
+use reqwest::{Request, Response};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use tracing::debug;
+
+/// Response headers never written to a cassette
+const SKIPPED_HEADERS: &[&str] = &["set-cookie", "content-length", "transfer-encoding"];
+
+/// The request half of an [`Interaction`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CassetteRequest {
+    /// HTTP method
+    pub method: String,
+    /// Path and query (`/functions/v1/health?x=1`)
+    pub path: String,
+    /// JSON body
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<serde_json::Value>,
+}
+
+impl CassetteRequest {
+    fn from_request(request: &Request) -> Self {
+        Self {
+            method: request.method().to_string(),
+            path: path_and_query(request.url()),
+            body: request
+                .body()
+                .and_then(reqwest::Body::as_bytes)
+                .and_then(|b| serde_json::from_slice(b).ok()),
+        }
+    }
+}
+
+/// The response half of an [`Interaction`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CassetteResponse {
+    /// HTTP status code
+    pub status: u16,
+    /// Response headers
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Response body
+    #[serde(default)]
+    pub body: String,
+}
+
+/// A recorded request and the response it received
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    /// What was sent
+    pub request: CassetteRequest,
+    /// What came back
+    pub response: CassetteResponse,
+}
+
+/// A list of recorded interactions, stored as a JSON file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Cassette {
+    /// Interactions in the order they were recorded
+    pub interactions: Vec<Interaction>,
+}
+
+impl Cassette {
+    /// Load a cassette file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a valid cassette.
+    pub fn load(path: impl AsRef<Path>) -> ApiResult<Self> {
+        let contents = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// Load a cassette file, or start an empty one if it does not exist
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or parsed.
+    pub fn load_or_default(path: impl AsRef<Path>) -> ApiResult<Self> {
+        match Self::load(path) {
+            Err(ApiError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            other => other,
+        }
+    }
+
+    /// Write the cassette to `path`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: impl AsRef<Path>) -> ApiResult<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
+
+/// Transport that records every exchange made through `inner`
+pub struct RecordingTransport {
+    inner: Arc<dyn Transport>,
+    path: PathBuf,
+    cassette: Mutex<Cassette>,
+}
+
+impl RecordingTransport {
+    /// Record exchanges made through `inner` to the cassette at `path`
+    ///
+    /// New interactions are appended to an existing cassette.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an existing cassette cannot be read.
+    pub fn new(inner: Arc<dyn Transport>, path: impl Into<PathBuf>) -> ApiResult<Self> {
+        let path = path.into();
+        Ok(Self {
+            inner,
+            cassette: Mutex::new(Cassette::load_or_default(&path)?),
+            path,
+        })
+    }
+
+    /// Snapshot of the recorded cassette
+    #[must_use]
+    pub fn cassette(&self) -> Cassette {
+        self.cassette
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    async fn record(&self, request: Request) -> ApiResult<Response> {
+        let recorded = CassetteRequest::from_request(&request);
+        let response = self.inner.execute(request).await?;
+
+        let status = response.status().as_u16();
+        let headers: BTreeMap<String, String> = response
+            .headers()
+            .iter()
+            .filter(|(name, _)| !SKIPPED_HEADERS.contains(&name.as_str()))
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        let body = response.text().await?;
+
+        let interaction = Interaction {
+            request: recorded,
+            response: CassetteResponse {
+                status,
+                headers: headers.clone(),
+                body: body.clone(),
+            },
+        };
+        {
+            let mut cassette = self.cassette.lock().unwrap_or_else(PoisonError::into_inner);
+            cassette.interactions.push(interaction);
+            cassette.save(&self.path)?;
+        }
+
+        build_response(status, headers, body.into_bytes())
+    }
+}
+
+impl Transport for RecordingTransport {
+    fn execute(&self, request: Request) -> BoxFuture<'_, ApiResult<Response>> {
+        Box::pin(self.record(request))
+    }
+}
+
+/// Transport that answers from a cassette, never touching the network
+///
+/// Each interaction is used once, in recorded order, so repeated requests
+/// replay repeated responses. A request with no unused match is an error.
+#[derive(Debug)]
+pub struct ReplayTransport {
+    interactions: Mutex<Vec<(Interaction, bool)>>,
+}
+
+impl ReplayTransport {
+    /// Replay the given cassette
+    #[must_use]
+    pub fn new(cassette: Cassette) -> Self {
+        Self {
+            interactions: Mutex::new(cassette.interactions.into_iter().map(|i| (i, false)).collect()),
+        }
+    }
+
+    /// Replay the cassette file at `path`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cassette cannot be read.
+    pub fn open(path: impl AsRef<Path>) -> ApiResult<Self> {
+        Ok(Self::new(Cassette::load(path)?))
+    }
+
+    /// Number of interactions not yet replayed
+    #[must_use]
+    pub fn remaining(&self) -> usize {
+        self.interactions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .filter(|(_, used)| !used)
+            .count()
+    }
+
+    fn take(&self, request: &CassetteRequest) -> Option<CassetteResponse> {
+        let mut interactions = self.interactions.lock().unwrap_or_else(PoisonError::into_inner);
+        let (interaction, used) = interactions
+            .iter_mut()
+            .find(|(i, used)| !used && i.request == *request)?;
+        *used = true;
+        Some(interaction.response.clone())
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn execute(&self, request: Request) -> BoxFuture<'_, ApiResult<Response>> {
+        let recorded = CassetteRequest::from_request(&request);
+        let response = self.take(&recorded);
+        Box::pin(async move {
+            let Some(response) = response else {
+                return Err(ApiError::Transport(format!(
+                    "no recorded interaction for {} {}",
+                    recorded.method, recorded.path
+                )));
+            };
+            debug!(method = %recorded.method, path = %recorded.path, "Replaying recorded response");
+            build_response(response.status, response.headers, response.body.into_bytes())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::{MockResponse, MockRoute, MockTransport};
+    use reqwest::Method;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_record_then_replay() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("fixtures").join("cassette.json");
+        let mock = Arc::new(MockTransport::new().with_route(MockRoute::new(
+            Method::POST,
+            "translate",
+            MockResponse::json(200, &serde_json::json!({"ok": true}))
+                .with_header("etag", "\"v1\"")
+                .with_header("set-cookie", "session=secret"),
+        )));
+
+        let http = reqwest::Client::new();
+        let request = || {
+            http.post("http://localhost/functions/v1/translate")
+                .json(&serde_json::json!({"text": "hi"}))
+                .build()
+                .unwrap()
+        };
+
+        let recorder = RecordingTransport::new(mock, &path).unwrap();
+        recorder.execute(request()).await.unwrap();
+
+        let cassette = Cassette::load(&path).unwrap();
+        assert_eq!(cassette.interactions.len(), 1);
+        let headers = &cassette.interactions[0].response.headers;
+        assert_eq!(headers.get("etag").map(String::as_str), Some("\"v1\""));
+        assert!(!headers.contains_key("set-cookie"));
+
+        let replay = ReplayTransport::open(&path).unwrap();
+        let response = replay.execute(request()).await.unwrap();
+        assert_eq!(response.status().as_u16(), 200);
+        assert_eq!(response.text().await.unwrap(), r#"{"ok":true}"#);
+
+        // Each interaction replays once
+        assert_eq!(replay.remaining(), 0);
+        assert!(matches!(replay.execute(request()).await, Err(ApiError::Transport(_))));
+    }
+}
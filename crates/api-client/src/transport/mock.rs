@@ -0,0 +1,346 @@
+//! In-memory mock transport
+//!
+//! Answers requests from a list of routes without touching the network.
+//!
+//! # Example
+//!
+//! ```rust
+//! use foodshare_api_client::transport::{MockResponse, MockRoute, MockTransport};
+//! use foodshare_api_client::{ClientConfig, FoodshareClient};
+//! use reqwest::Method;
+//! use std::sync::Arc;
+//!
+//! let mock = Arc::new(MockTransport::new().with_route(MockRoute::new(
+//!     Method::GET,
+//!     "get-translations/health",
+//!     MockResponse::json(200, &serde_json::json!({
+//!         "status": "healthy",
+//!         "version": "1.0.0",
+//!         "timestamp": "2024-01-01T00:00:00Z"
+//!     })),
+//! )));
+//!
+//! let client = FoodshareClient::with_config(ClientConfig::development())
+//!     .unwrap()
+//!     .with_transport(mock.clone());
+//! ```
+
+use super::{build_response, path_and_query, Transport};
+use crate::error::{ApiError, ApiResult};
+use futures_util::future::BoxFuture;
+use reqwest::{Method, Request, Response};
+use serde::Serialize;
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+/// A canned response
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    latency: Option<Duration>,
+}
+
+impl MockResponse {
+    /// Response with a JSON body
+    pub fn json<T: Serialize>(status: u16, body: &T) -> Self {
+        Self {
+            status,
+            headers: vec![("content-type".to_string(), "application/json".to_string())],
+            body: serde_json::to_vec(body).unwrap_or_default(),
+            latency: None,
+        }
+    }
+
+    /// Response with a plain body
+    pub fn text(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.into().into_bytes(),
+            latency: None,
+        }
+    }
+
+    /// Empty response (e.g. 204, 304)
+    #[must_use]
+    pub fn empty(status: u16) -> Self {
+        Self::text(status, "")
+    }
+
+    /// Builder-style method to add a header
+    #[must_use]
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Builder-style method to delay this response
+    #[must_use]
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = Some(latency);
+        self
+    }
+}
+
+/// An injected failure
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// The request times out
+    Timeout,
+    /// The server answers with this status and an error body
+    Status(u16),
+}
+
+/// What a route answers with
+#[derive(Debug, Clone)]
+pub enum MockReply {
+    /// A canned response
+    Response(MockResponse),
+    /// A failure
+    Fault(Fault),
+}
+
+impl From<MockResponse> for MockReply {
+    fn from(response: MockResponse) -> Self {
+        Self::Response(response)
+    }
+}
+
+impl From<Fault> for MockReply {
+    fn from(fault: Fault) -> Self {
+        Self::Fault(fault)
+    }
+}
+
+/// A request matcher and its reply
+///
+/// `path` matches when the request path ends with it (leading `/` optional).
+/// If `path` contains a query string, the request's path and query must end
+/// with it instead.
+#[derive(Debug, Clone)]
+pub struct MockRoute {
+    method: Method,
+    path: String,
+    reply: MockReply,
+    remaining: Option<usize>,
+}
+
+impl MockRoute {
+    /// Answer `method` requests to `path` with `reply`
+    pub fn new(method: Method, path: impl Into<String>, reply: impl Into<MockReply>) -> Self {
+        Self {
+            method,
+            path: path.into(),
+            reply: reply.into(),
+            remaining: None,
+        }
+    }
+
+    /// Builder-style method to only match the next `n` requests
+    #[must_use]
+    pub fn times(mut self, n: usize) -> Self {
+        self.remaining = Some(n);
+        self
+    }
+
+    fn matches(&self, method: &Method, url: &reqwest::Url) -> bool {
+        if self.remaining == Some(0) || *method != self.method {
+            return false;
+        }
+        let route = self.path.trim_start_matches('/');
+        if route.contains('?') {
+            path_and_query(url).ends_with(route)
+        } else {
+            url.path().ends_with(route)
+        }
+    }
+}
+
+/// A request received by a [`MockTransport`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedRequest {
+    /// HTTP method
+    pub method: Method,
+    /// Path and query
+    pub path: String,
+    /// Request headers
+    pub headers: reqwest::header::HeaderMap,
+    /// JSON body, if any
+    pub body: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    routes: Vec<MockRoute>,
+    requests: Vec<RecordedRequest>,
+}
+
+/// Transport answering from configured routes
+///
+/// Unmatched requests get a 404. Routes are tried in the order they were added.
+#[derive(Debug, Default)]
+pub struct MockTransport {
+    state: Mutex<MockState>,
+    latency: Duration,
+    fault_every: Option<(usize, Fault)>,
+}
+
+impl MockTransport {
+    /// Create a transport with no routes
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder-style method to add a route
+    #[must_use]
+    pub fn with_route(self, route: MockRoute) -> Self {
+        self.add_route(route);
+        self
+    }
+
+    /// Builder-style method to delay every response
+    #[must_use]
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Builder-style method to fail every `n`th request (1-based) with `fault`
+    #[must_use]
+    pub fn with_fault_every(mut self, n: usize, fault: Fault) -> Self {
+        self.fault_every = (n > 0).then_some((n, fault));
+        self
+    }
+
+    /// Add a route after construction
+    pub fn add_route(&self, route: MockRoute) {
+        self.lock().routes.push(route);
+    }
+
+    /// Requests received so far
+    #[must_use]
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.lock().requests.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Record the request and pick its reply
+    fn reply_for(&self, request: &Request) -> MockReply {
+        let mut state = self.lock();
+        state.requests.push(RecordedRequest {
+            method: request.method().clone(),
+            path: path_and_query(request.url()),
+            headers: request.headers().clone(),
+            body: request
+                .body()
+                .and_then(reqwest::Body::as_bytes)
+                .and_then(|b| serde_json::from_slice(b).ok()),
+        });
+
+        if let Some((n, fault)) = self.fault_every {
+            if state.requests.len() % n == 0 {
+                return MockReply::Fault(fault);
+            }
+        }
+
+        let route = state
+            .routes
+            .iter_mut()
+            .find(|r| r.matches(request.method(), request.url()));
+        match route {
+            Some(route) => {
+                if let Some(ref mut remaining) = route.remaining {
+                    *remaining -= 1;
+                }
+                route.reply.clone()
+            }
+            None => MockReply::Response(MockResponse::json(
+                404,
+                &serde_json::json!({
+                    "error": format!("no mock route for {} {}", request.method(), request.url().path())
+                }),
+            )),
+        }
+    }
+}
+
+impl Transport for MockTransport {
+    fn execute(&self, request: Request) -> BoxFuture<'_, ApiResult<Response>> {
+        let reply = self.reply_for(&request);
+        Box::pin(async move {
+            match reply {
+                MockReply::Response(response) => {
+                    let latency = response.latency.unwrap_or(self.latency);
+                    if !latency.is_zero() {
+                        tokio::time::sleep(latency).await;
+                    }
+                    build_response(response.status, response.headers, response.body)
+                }
+                MockReply::Fault(Fault::Timeout) => {
+                    if !self.latency.is_zero() {
+                        tokio::time::sleep(self.latency).await;
+                    }
+                    Err(ApiError::Timeout(self.latency))
+                }
+                MockReply::Fault(Fault::Status(status)) => build_response(
+                    status,
+                    [("content-type".to_string(), "application/json".to_string())],
+                    br#"{"error":"injected fault"}"#.to_vec(),
+                ),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get(url: &str) -> Request {
+        reqwest::Client::new().get(url).build().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_routes_and_times() {
+        let mock = MockTransport::new()
+            .with_route(MockRoute::new(Method::GET, "/health", MockResponse::empty(503)).times(1))
+            .with_route(MockRoute::new(Method::GET, "health", MockResponse::json(200, &"ok")));
+
+        let statuses: Vec<u16> = [
+            mock.execute(get("http://x/functions/v1/health")).await.unwrap().status().as_u16(),
+            mock.execute(get("http://x/functions/v1/health")).await.unwrap().status().as_u16(),
+            mock.execute(get("http://x/other")).await.unwrap().status().as_u16(),
+        ]
+        .into();
+        assert_eq!(statuses, [503, 200, 404]);
+        assert_eq!(mock.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_query_routes() {
+        let mock = MockTransport::new()
+            .with_route(MockRoute::new(Method::GET, "get-translations?locale=de", MockResponse::empty(204)));
+
+        let hit = mock.execute(get("http://x/get-translations?locale=de")).await.unwrap();
+        let miss = mock.execute(get("http://x/get-translations?locale=fr")).await.unwrap();
+        assert_eq!(hit.status().as_u16(), 204);
+        assert_eq!(miss.status().as_u16(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_fault_every() {
+        let mock = MockTransport::new()
+            .with_route(MockRoute::new(Method::GET, "a", MockResponse::empty(200)))
+            .with_fault_every(2, Fault::Timeout);
+
+        assert!(mock.execute(get("http://x/a")).await.is_ok());
+        assert!(matches!(mock.execute(get("http://x/a")).await, Err(ApiError::Timeout(_))));
+        assert!(mock.execute(get("http://x/a")).await.is_ok());
+    }
+}
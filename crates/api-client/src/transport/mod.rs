@@ -0,0 +1,74 @@
+//! Pluggable request transport
+//!
+//! Every request the client makes goes through a [`Transport`]. The default
+//! [`HttpTransport`] sends it over the network; the others let tests and
+//! offline runs work without a backend:
+//!
+//! | Transport | Use |
+//! |-----------|-----|
+//! | [`HttpTransport`] | Real HTTP via `reqwest` |
+//! | [`MockTransport`] | Canned responses per route, with latency and fault injection |
+//! | [`RecordingTransport`] | Real HTTP, saving each exchange to a cassette file |
+//! | [`ReplayTransport`] | Answers from a cassette file, no network |
+
+pub mod cassette;
+pub mod mock;
+
+pub use cassette::{
+    Cassette, CassetteRequest, CassetteResponse, Interaction, RecordingTransport, ReplayTransport,
+};
+pub use mock::{Fault, MockReply, MockResponse, MockRoute, MockTransport, RecordedRequest};
+
+use crate::error::ApiResult;
+use futures_util::future::BoxFuture;
+use reqwest::{Client, Request, Response};
+
+/// Sends built requests and returns their responses
+pub trait Transport: Send + Sync {
+    /// Execute a request
+    fn execute(&self, request: Request) -> BoxFuture<'_, ApiResult<Response>>;
+}
+
+/// Transport that sends requests over the network
+#[derive(Debug, Clone)]
+pub struct HttpTransport {
+    client: Client,
+}
+
+impl HttpTransport {
+    /// Wrap a configured `reqwest` client
+    #[must_use]
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+}
+
+impl Transport for HttpTransport {
+    fn execute(&self, request: Request) -> BoxFuture<'_, ApiResult<Response>> {
+        Box::pin(async move { Ok(self.client.execute(request).await?) })
+    }
+}
+
+/// Path and query of a URL (`/functions/v1/health?x=1`)
+pub(crate) fn path_and_query(url: &reqwest::Url) -> String {
+    match url.query() {
+        Some(query) => format!("{}?{query}", url.path()),
+        None => url.path().to_string(),
+    }
+}
+
+/// Build a `reqwest` response from parts
+pub(crate) fn build_response(
+    status: u16,
+    headers: impl IntoIterator<Item = (String, String)>,
+    body: Vec<u8>,
+) -> ApiResult<Response> {
+    let mut builder = http::Response::builder().status(status);
+    for (name, value) in headers {
+        builder = builder.header(name, value);
+    }
+    let response = builder
+        .body(body)
+        .map_err(|e| crate::error::ApiError::Transport(e.to_string()))?;
+    Ok(Response::from(response))
+}
@@ -3,9 +3,14 @@
 //! Provides shared CLI functionality:
 //! - Terminal output formatting
 //! - Progress indicators
+//! - Interactive prompts
 //! - Status messages
+//! - Machine-readable (JSON/NDJSON) output
+//! - App size baselines and budgets
 
 #![warn(missing_docs)]
 
 pub mod output;
 pub mod progress;
+pub mod prompt;
+pub mod size;
@@ -1,8 +1,414 @@
 //! Terminal output utilities
 //!
 //! Provides consistent formatting for CLI output.
+//!
+//! Commands report through a [`Reporter`] chosen once with [`init`] from the
+//! global `--format` option:
+//!
+//! - `text` prints for people, as [`Status`] always has
+//! - `json` prints one document when the command finishes
+//! - `ndjson` prints each event as a line while the command runs
+//!
+//! Both machine formats share one schema ([`SCHEMA_VERSION`]): messages
+//! (`level`, `message`), findings (`level`, `rule`, `file`, `line`,
+//! `message`), named data, and the command's result (`command`, `success`,
+//! `exit_code`). Output meant only for people goes through [`textln!`] and
+//! [`text!`], which print nothing in the machine formats so stdout stays
+//! parseable.
+//!
+//! [`diff`] renders colored unified diffs.
 
 use owo_colors::OwoColorize;
+use serde::Serialize;
+use std::io::Write;
+use std::sync::{Mutex, OnceLock};
+
+pub mod diff;
+
+/// Version of the JSON and NDJSON schema, bumped on incompatible changes
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Print for people, without a newline; nothing with `--format json` or `ndjson`
+#[macro_export]
+macro_rules! text {
+    ($($arg:tt)*) => {
+        if !$crate::output::is_machine() {
+            print!($($arg)*);
+        }
+    };
+}
+
+/// Print a line for people; nothing with `--format json` or `ndjson`
+#[macro_export]
+macro_rules! textln {
+    ($($arg:tt)*) => {
+        if !$crate::output::is_machine() {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// Output format selected with `--format`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    /// Human-readable output
+    #[default]
+    Text,
+    /// One JSON document on stdout
+    Json,
+    /// One JSON event per line on stdout
+    Ndjson,
+}
+
+impl Format {
+    /// Whether the format is for programs rather than people
+    #[must_use]
+    pub fn is_machine(self) -> bool {
+        self != Self::Text
+    }
+}
+
+impl std::str::FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "ndjson" => Ok(Self::Ndjson),
+            other => Err(format!("Unknown format '{}' (use text, json or ndjson)", other)),
+        }
+    }
+}
+
+/// Message and finding level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    /// Something succeeded
+    Success,
+    /// Informational
+    Info,
+    /// Needs attention but doesn't fail the command
+    Warning,
+    /// Fails the command
+    Error,
+}
+
+/// A diagnostic about a file or a rule
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Finding {
+    /// How serious it is
+    pub level: Level,
+    /// Rule or check ID
+    pub rule: Option<String>,
+    /// File it is in
+    pub file: Option<String>,
+    /// Line number (1-based)
+    pub line: Option<usize>,
+    /// What is wrong
+    pub message: String,
+}
+
+impl Finding {
+    /// Create a finding without a rule or location
+    pub fn new(level: Level, message: impl Into<String>) -> Self {
+        Self {
+            level,
+            rule: None,
+            file: None,
+            line: None,
+            message: message.into(),
+        }
+    }
+
+    /// Set the rule ID
+    #[must_use]
+    pub fn with_rule(mut self, rule: impl Into<String>) -> Self {
+        self.rule = Some(rule.into());
+        self
+    }
+
+    /// Set the file and line
+    #[must_use]
+    pub fn with_location(mut self, file: impl Into<String>, line: Option<usize>) -> Self {
+        self.file = Some(file.into());
+        self.line = line;
+        self
+    }
+}
+
+/// Something a command reports
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Event {
+    /// A status message
+    Message {
+        /// Message level
+        level: Level,
+        /// Message text, without colors
+        message: String,
+    },
+    /// A diagnostic
+    Finding(Finding),
+    /// A named result value (`routes`, `packages`, ...)
+    Data {
+        /// Name of the value
+        key: String,
+        /// The value
+        value: serde_json::Value,
+    },
+}
+
+/// Backend that renders a command's events
+pub trait Reporter: Send + Sync {
+    /// Report an event
+    fn report(&self, event: Event);
+
+    /// Report that the command finished with `exit_code`
+    fn finish(&self, command: &str, exit_code: i32);
+}
+
+/// Human-readable output on the terminal
+#[derive(Debug, Default)]
+pub struct TextReporter;
+
+impl Reporter for TextReporter {
+    fn report(&self, event: Event) {
+        match event {
+            Event::Message {
+                level: Level::Success,
+                message,
+            } => println!("{} {}", "✓".green(), message),
+            Event::Message {
+                level: Level::Info,
+                message,
+            } => println!("{} {}", "ℹ".blue(), message),
+            Event::Message {
+                level: Level::Warning,
+                message,
+            } => eprintln!("{} {}", "⚠".yellow(), message),
+            Event::Message {
+                level: Level::Error,
+                message,
+            } => eprintln!("{} {}", "✗".red(), message),
+            Event::Finding(finding) => {
+                let location = match (&finding.file, finding.line) {
+                    (Some(file), Some(line)) => format!("{}:{} ", file, line),
+                    (Some(file), None) => format!("{} ", file),
+                    _ => String::new(),
+                };
+                let rule = finding.rule.as_ref().map(|r| format!(" ({})", r)).unwrap_or_default();
+                let marker = match finding.level {
+                    Level::Error => "✗".red().to_string(),
+                    Level::Warning => "⚠".yellow().to_string(),
+                    Level::Info => "ℹ".blue().to_string(),
+                    Level::Success => "✓".green().to_string(),
+                };
+                eprintln!("  {} {}{}{}", marker, location, finding.message, rule.dimmed());
+            }
+            // Text commands print their results themselves
+            Event::Data { .. } => {}
+        }
+    }
+
+    fn finish(&self, _command: &str, _exit_code: i32) {}
+}
+
+/// A status message in the JSON document
+#[derive(Debug, Serialize)]
+struct Message {
+    level: Level,
+    message: String,
+}
+
+/// The JSON document printed by [`JsonReporter`]
+#[derive(Debug, Default, Serialize)]
+struct Document {
+    schema_version: u32,
+    command: String,
+    success: bool,
+    exit_code: i32,
+    messages: Vec<Message>,
+    findings: Vec<Finding>,
+    data: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Collects events and writes one JSON document when the command finishes
+pub struct JsonReporter<W> {
+    out: Mutex<W>,
+    document: Mutex<Document>,
+}
+
+impl<W: Write + Send> JsonReporter<W> {
+    /// Write the document to `out`
+    pub fn new(out: W) -> Self {
+        Self {
+            out: Mutex::new(out),
+            document: Mutex::new(Document::default()),
+        }
+    }
+}
+
+impl<W: Write + Send> Reporter for JsonReporter<W> {
+    fn report(&self, event: Event) {
+        let mut document = self.document.lock().unwrap_or_else(|e| e.into_inner());
+        match event {
+            Event::Message { level, message } => document.messages.push(Message { level, message }),
+            Event::Finding(finding) => document.findings.push(finding),
+            Event::Data { key, value } => {
+                document.data.insert(key, value);
+            }
+        }
+    }
+
+    fn finish(&self, command: &str, exit_code: i32) {
+        let mut document = self.document.lock().unwrap_or_else(|e| e.into_inner());
+        document.schema_version = SCHEMA_VERSION;
+        document.command = command.to_string();
+        document.success = exit_code == 0;
+        document.exit_code = exit_code;
+
+        let mut out = self.out.lock().unwrap_or_else(|e| e.into_inner());
+        if let Ok(json) = serde_json::to_string_pretty(&*document) {
+            let _ = writeln!(out, "{}", json);
+            let _ = out.flush();
+        }
+    }
+}
+
+/// Writes each event as one JSON line, then a `result` line
+pub struct NdjsonReporter<W> {
+    out: Mutex<W>,
+}
+
+impl<W: Write + Send> NdjsonReporter<W> {
+    /// Write events to `out`
+    pub fn new(out: W) -> Self {
+        Self { out: Mutex::new(out) }
+    }
+
+    fn write_line(&self, value: &impl Serialize) {
+        let mut out = self.out.lock().unwrap_or_else(|e| e.into_inner());
+        if let Ok(json) = serde_json::to_string(value) {
+            let _ = writeln!(out, "{}", json);
+            let _ = out.flush();
+        }
+    }
+}
+
+impl<W: Write + Send> Reporter for NdjsonReporter<W> {
+    fn report(&self, event: Event) {
+        self.write_line(&event);
+    }
+
+    fn finish(&self, command: &str, exit_code: i32) {
+        self.write_line(&serde_json::json!({
+            "type": "result",
+            "schema_version": SCHEMA_VERSION,
+            "command": command,
+            "success": exit_code == 0,
+            "exit_code": exit_code,
+        }));
+    }
+}
+
+static OUTPUT: OnceLock<(Format, Box<dyn Reporter>)> = OnceLock::new();
+
+fn output() -> &'static (Format, Box<dyn Reporter>) {
+    OUTPUT.get_or_init(|| (Format::Text, Box::new(TextReporter)))
+}
+
+/// Select the output format; call once, before anything is reported
+pub fn init(format: Format) {
+    let reporter: Box<dyn Reporter> = match format {
+        Format::Text => Box::new(TextReporter),
+        Format::Json => Box::new(JsonReporter::new(std::io::stdout())),
+        Format::Ndjson => Box::new(NdjsonReporter::new(std::io::stdout())),
+    };
+    let _ = OUTPUT.set((format, reporter));
+}
+
+/// The selected output format
+pub fn format() -> Format {
+    output().0
+}
+
+/// Whether output is JSON or NDJSON
+pub fn is_machine() -> bool {
+    format().is_machine()
+}
+
+/// Report an event to the selected reporter
+pub fn report(event: Event) {
+    let event = match event {
+        Event::Message { level, message } if is_machine() => Event::Message {
+            level,
+            message: strip_ansi(&message),
+        },
+        Event::Finding(mut finding) if is_machine() => {
+            finding.message = strip_ansi(&finding.message);
+            Event::Finding(finding)
+        }
+        event => event,
+    };
+    output().1.report(event);
+}
+
+/// Report a diagnostic
+pub fn finding(finding: Finding) {
+    report(Event::Finding(finding));
+}
+
+/// Report a named result value; only the machine formats show it
+pub fn data(key: &str, value: &impl Serialize) {
+    if is_machine()
+        && let Ok(value) = serde_json::to_value(value)
+    {
+        report(Event::Data {
+            key: key.to_string(),
+            value,
+        });
+    }
+}
+
+/// Subcommand path of parsed arguments (`simulator boot`), for [`finish`]
+pub fn command_path(matches: &clap::ArgMatches) -> String {
+    let mut names = Vec::new();
+    let mut current = matches;
+    while let Some((name, sub)) = current.subcommand() {
+        names.push(name);
+        current = sub;
+    }
+    names.join(" ")
+}
+
+/// Report that `command` finished; returns `exit_code` for `std::process::exit`
+pub fn finish(command: &str, exit_code: i32) -> i32 {
+    output().1.finish(command, exit_code);
+    exit_code
+}
+
+/// Remove ANSI escape sequences (colors) from `text`
+pub fn strip_ansi(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            // CSI: ESC [ parameters final-byte
+            if chars.next() == Some('[') {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
 
 /// Status message helpers
 pub struct Status;
@@ -10,44 +416,56 @@ pub struct Status;
 impl Status {
     /// Print a success message
     pub fn success(message: &str) {
-        println!("{} {}", "✓".green(), message);
+        report(Event::Message {
+            level: Level::Success,
+            message: message.to_string(),
+        });
     }
 
     /// Print an error message
     pub fn error(message: &str) {
-        eprintln!("{} {}", "✗".red(), message);
+        report(Event::Message {
+            level: Level::Error,
+            message: message.to_string(),
+        });
     }
 
     /// Print a warning message
     pub fn warning(message: &str) {
-        eprintln!("{} {}", "⚠".yellow(), message);
+        report(Event::Message {
+            level: Level::Warning,
+            message: message.to_string(),
+        });
     }
 
     /// Print an info message
     pub fn info(message: &str) {
-        println!("{} {}", "ℹ".blue(), message);
+        report(Event::Message {
+            level: Level::Info,
+            message: message.to_string(),
+        });
     }
 
     /// Print a step message (for multi-step operations)
     pub fn step(step: usize, total: usize, message: &str) {
-        println!(
-            "{} {}",
-            format!("[{}/{}]", step, total).dimmed(),
-            message
-        );
+        if is_machine() {
+            Self::info(&format!("[{}/{}] {}", step, total, message));
+            return;
+        }
+        println!("{} {}", format!("[{}/{}]", step, total).dimmed(), message);
     }
 
     /// Print a header
     pub fn header(message: &str) {
-        println!();
-        println!("{}", message.bold());
-        println!("{}", "─".repeat(message.len()));
+        textln!();
+        textln!("{}", message.bold());
+        textln!("{}", "─".repeat(message.len()));
     }
 
     /// Print a subheader
     pub fn subheader(message: &str) {
-        println!();
-        println!("{}", message.bold().dimmed());
+        textln!();
+        textln!("{}", message.bold().dimmed());
     }
 }
 
@@ -82,6 +500,24 @@ pub fn format_size(bytes: u64) -> String {
     }
 }
 
+/// Format a file size in decimal units (1 MB = 1,000,000 bytes), as app
+/// stores report sizes
+pub fn format_size_decimal(bytes: u64) -> String {
+    const KB: u64 = 1000;
+    const MB: u64 = KB * 1000;
+    const GB: u64 = MB * 1000;
+
+    if bytes >= GB {
+        format!("{:.2} GB", bytes as f64 / GB as f64)
+    } else if bytes >= MB {
+        format!("{:.2} MB", bytes as f64 / MB as f64)
+    } else if bytes >= KB {
+        format!("{:.1} KB", bytes as f64 / KB as f64)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
 /// Format a count with singular/plural
 pub fn format_count(count: usize, singular: &str, plural: &str) -> String {
     if count == 1 {
@@ -129,6 +565,13 @@ mod tests {
         assert_eq!(format_size(5 * 1024 * 1024), "5.00 MB");
     }
 
+    #[test]
+    fn test_format_size_decimal() {
+        assert_eq!(format_size_decimal(999), "999 B");
+        assert_eq!(format_size_decimal(2_500), "2.5 KB");
+        assert_eq!(format_size_decimal(14_200_000), "14.20 MB");
+    }
+
     #[test]
     fn test_format_count_singular() {
         assert_eq!(format_count(1, "file", "files"), "1 file");
@@ -138,4 +581,93 @@ mod tests {
     fn test_format_count_plural() {
         assert_eq!(format_count(5, "file", "files"), "5 files");
     }
+
+    /// Writer whose output stays readable after the reporter took it
+    #[derive(Clone, Default)]
+    struct Buffer(std::sync::Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Buffer {
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    fn events() -> Vec<Event> {
+        vec![
+            Event::Message {
+                level: Level::Info,
+                message: "Checking 2 files".to_string(),
+            },
+            Event::Finding(
+                Finding::new(Level::Error, "Hardcoded secret")
+                    .with_rule("secret")
+                    .with_location("src/api.ts", Some(3)),
+            ),
+            Event::Data {
+                key: "files".to_string(),
+                value: serde_json::json!(2),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_json_reporter_document() {
+        let buffer = Buffer::default();
+        let reporter = JsonReporter::new(buffer.clone());
+        for event in events() {
+            reporter.report(event);
+        }
+        reporter.finish("secrets", 1);
+
+        let document: serde_json::Value = serde_json::from_str(&buffer.contents()).unwrap();
+        assert_eq!(document["schema_version"], SCHEMA_VERSION);
+        assert_eq!(document["command"], "secrets");
+        assert_eq!(document["success"], false);
+        assert_eq!(document["exit_code"], 1);
+        assert_eq!(document["messages"][0]["level"], "info");
+        assert_eq!(document["findings"][0]["rule"], "secret");
+        assert_eq!(document["findings"][0]["line"], 3);
+        assert_eq!(document["data"]["files"], 2);
+    }
+
+    #[test]
+    fn test_ndjson_reporter_lines() {
+        let buffer = Buffer::default();
+        let reporter = NdjsonReporter::new(buffer.clone());
+        for event in events() {
+            reporter.report(event);
+        }
+        reporter.finish("secrets", 0);
+
+        let lines: Vec<serde_json::Value> = buffer
+            .contents()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let types: Vec<&str> = lines.iter().map(|line| line["type"].as_str().unwrap()).collect();
+        assert_eq!(types, ["message", "finding", "data", "result"]);
+        assert_eq!(lines[1]["file"], "src/api.ts");
+        assert_eq!(lines[3]["success"], true);
+    }
+
+    #[test]
+    fn test_format_and_strip_ansi() {
+        assert_eq!("NDJSON".parse::<Format>().unwrap(), Format::Ndjson);
+        assert!("sarif".parse::<Format>().is_err());
+        assert!(!Format::Text.is_machine());
+        assert_eq!(
+            strip_ansi("\u{1b}[31m✗\u{1b}[0m failed \u{1b}[1;2mx\u{1b}[0m"),
+            "✗ failed x"
+        );
+    }
 }
@@ -0,0 +1,394 @@
+//! Colored unified diffs
+//!
+//! [`Diff`] compares two texts and renders a unified diff for people:
+//! headers bold, hunk ranges cyan, removed lines red, added lines green, and
+//! inside a line that was rewritten rather than replaced, the changed words
+//! highlighted. Diffs kept as text (snapshots, reports) render the same way
+//! through [`render_unified`].
+
+use owo_colors::OwoColorize;
+use similar::{ChangeTag, TextDiff};
+
+/// Context lines around each change unless [`Diff::context`] says otherwise
+pub const DEFAULT_CONTEXT: usize = 3;
+
+/// Below this similarity a rewritten line is shown whole, not word by word
+const WORD_DIFF_MIN_RATIO: f32 = 0.5;
+
+/// Lines added and removed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiffStats {
+    /// Lines only in the new text
+    pub insertions: usize,
+    /// Lines only in the old text
+    pub deletions: usize,
+}
+
+/// How a diff is rendered
+#[derive(Debug, Clone, Copy)]
+pub struct DiffStyle {
+    /// Highlight the changed words in rewritten lines
+    pub word_level: bool,
+    /// Stop after this many lines, noting how many were left out
+    pub max_lines: Option<usize>,
+    /// Spaces before every line
+    pub indent: usize,
+}
+
+impl Default for DiffStyle {
+    fn default() -> Self {
+        Self {
+            word_level: true,
+            max_lines: None,
+            indent: 0,
+        }
+    }
+}
+
+/// A line-by-line comparison of two texts
+pub struct Diff<'a> {
+    old: &'a str,
+    new: &'a str,
+    header: Option<(String, String)>,
+    context: usize,
+    style: DiffStyle,
+}
+
+impl<'a> Diff<'a> {
+    /// Compare `old` to `new`
+    pub fn new(old: &'a str, new: &'a str) -> Self {
+        Self {
+            old,
+            new,
+            header: None,
+            context: DEFAULT_CONTEXT,
+            style: DiffStyle::default(),
+        }
+    }
+
+    /// Label the sides in `---`/`+++` header lines
+    pub fn header(mut self, old_label: &str, new_label: &str) -> Self {
+        self.header = Some((old_label.to_string(), new_label.to_string()));
+        self
+    }
+
+    /// Unchanged lines shown around each change
+    pub fn context(mut self, lines: usize) -> Self {
+        self.context = lines;
+        self
+    }
+
+    /// Highlight changed words in rewritten lines (on by default)
+    pub fn word_level(mut self, enabled: bool) -> Self {
+        self.style.word_level = enabled;
+        self
+    }
+
+    /// Render at most `lines` lines
+    pub fn max_lines(mut self, lines: usize) -> Self {
+        self.style.max_lines = Some(lines);
+        self
+    }
+
+    /// Indent every rendered line by `width` spaces
+    pub fn indent(mut self, width: usize) -> Self {
+        self.style.indent = width;
+        self
+    }
+
+    /// Whether the texts are the same
+    pub fn is_empty(&self) -> bool {
+        self.old == self.new
+    }
+
+    /// Count added and removed lines
+    pub fn stats(&self) -> DiffStats {
+        let mut stats = DiffStats::default();
+        for change in TextDiff::from_lines(self.old, self.new).iter_all_changes() {
+            match change.tag() {
+                ChangeTag::Insert => stats.insertions += 1,
+                ChangeTag::Delete => stats.deletions += 1,
+                ChangeTag::Equal => {}
+            }
+        }
+        stats
+    }
+
+    /// The diff as plain unified-diff text, for storing or writing to a file
+    pub fn unified(&self) -> String {
+        let diff = TextDiff::from_lines(self.old, self.new);
+        let mut unified = diff.unified_diff();
+        unified.context_radius(self.context);
+        if let Some((old_label, new_label)) = &self.header {
+            unified.header(old_label, new_label);
+        }
+        unified.to_string()
+    }
+
+    /// The diff as colored lines
+    pub fn render(&self) -> Vec<String> {
+        render_unified(&self.unified(), &self.style)
+    }
+
+    /// Print the diff for people (nothing with `--format json` or `ndjson`)
+    pub fn print(&self) {
+        print_unified(&self.unified(), &self.style);
+    }
+}
+
+/// Color unified-diff text, highlighting changed words where lines were rewritten
+pub fn render_unified(unified: &str, style: &DiffStyle) -> Vec<String> {
+    let lines = classify(unified);
+    let mut rendered = Vec::with_capacity(lines.len());
+
+    let mut i = 0;
+    while i < lines.len() {
+        if !matches!(lines[i], Line::Removed(_)) {
+            rendered.push(render_plain(&lines[i]));
+            i += 1;
+            continue;
+        }
+
+        // A run of removed lines followed by as many added lines reads as a rewrite
+        let removed_end = run_end(&lines, i, |line| matches!(line, Line::Removed(_)));
+        let added_end = run_end(&lines, removed_end, |line| matches!(line, Line::Added(_)));
+        let (removed, added) = (&lines[i..removed_end], &lines[removed_end..added_end]);
+        let rewrite = style.word_level && removed.len() == added.len();
+        for (k, line) in removed.iter().enumerate() {
+            rendered.push(render_changed(line, rewrite.then(|| &added[k])));
+        }
+        for (k, line) in added.iter().enumerate() {
+            rendered.push(render_changed(line, rewrite.then(|| &removed[k])));
+        }
+        i = added_end;
+    }
+
+    let indent = " ".repeat(style.indent);
+    if let Some(max) = style.max_lines
+        && rendered.len() > max
+    {
+        let hidden = rendered.len() - max;
+        rendered.truncate(max);
+        rendered.push(format!("… {} more lines", hidden).dimmed().to_string());
+    }
+    rendered.into_iter().map(|line| format!("{}{}", indent, line)).collect()
+}
+
+/// Print unified-diff text for people (nothing with `--format json` or `ndjson`)
+pub fn print_unified(unified: &str, style: &DiffStyle) {
+    for line in render_unified(unified, style) {
+        crate::textln!("{}", line);
+    }
+}
+
+/// Color one rewritten line as a removed and an added line, changed words highlighted
+pub fn render_replacement(old: &str, new: &str) -> (String, String) {
+    let (old_line, new_line) = (Line::Removed(old), Line::Added(new));
+    (
+        render_changed(&old_line, Some(&new_line)),
+        render_changed(&new_line, Some(&old_line)),
+    )
+}
+
+/// A unified-diff line, without its prefix
+#[derive(Debug, PartialEq)]
+enum Line<'a> {
+    /// `---`/`+++` file header
+    File(&'a str),
+    /// `@@ … @@` hunk range
+    Hunk(&'a str),
+    Context(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+    /// Anything else (`\ No newline at end of file`, `Binary files differ`)
+    Other(&'a str),
+}
+
+/// Split unified-diff text into lines, telling content that starts with
+/// `---` or `+++` apart from file headers by the hunk's line counts
+fn classify(unified: &str) -> Vec<Line<'_>> {
+    let (mut old_left, mut new_left) = (0usize, 0usize);
+    unified
+        .lines()
+        .map(|line| {
+            let in_hunk = old_left > 0 || new_left > 0;
+            if !in_hunk && (line.starts_with("--- ") || line.starts_with("+++ ")) {
+                return Line::File(line);
+            }
+            if line.starts_with("@@") {
+                (old_left, new_left) = hunk_lengths(line).unwrap_or((usize::MAX, usize::MAX));
+                return Line::Hunk(line);
+            }
+            match line.chars().next() {
+                Some('-') if in_hunk => {
+                    old_left = old_left.saturating_sub(1);
+                    Line::Removed(&line[1..])
+                }
+                Some('+') if in_hunk => {
+                    new_left = new_left.saturating_sub(1);
+                    Line::Added(&line[1..])
+                }
+                Some(' ') if in_hunk => {
+                    old_left = old_left.saturating_sub(1);
+                    new_left = new_left.saturating_sub(1);
+                    Line::Context(&line[1..])
+                }
+                _ => Line::Other(line),
+            }
+        })
+        .collect()
+}
+
+/// Old and new line counts from `@@ -1,4 +1,5 @@`
+fn hunk_lengths(header: &str) -> Option<(usize, usize)> {
+    let mut ranges = header.trim_start_matches("@@").split_whitespace();
+    let length = |range: Option<&str>, sign: char| -> Option<usize> {
+        let range = range?.strip_prefix(sign)?;
+        match range.split_once(',') {
+            Some((_, len)) => len.parse().ok(),
+            None => Some(1),
+        }
+    };
+    Some((length(ranges.next(), '-')?, length(ranges.next(), '+')?))
+}
+
+fn run_end(lines: &[Line], start: usize, belongs: impl Fn(&Line) -> bool) -> usize {
+    start + lines[start..].iter().take_while(|line| belongs(line)).count()
+}
+
+fn render_plain(line: &Line) -> String {
+    match line {
+        Line::File(text) => text.bold().to_string(),
+        Line::Hunk(text) => text.cyan().to_string(),
+        Line::Context(text) => format!(" {}", text),
+        Line::Removed(text) => format!("-{}", text).red().to_string(),
+        Line::Added(text) => format!("+{}", text).green().to_string(),
+        Line::Other(text) => text.dimmed().to_string(),
+    }
+}
+
+/// Render a removed or added line, highlighting what differs from `partner`
+fn render_changed(line: &Line, partner: Option<&Line>) -> String {
+    let segments = match (line, partner) {
+        (Line::Removed(old), Some(Line::Added(new))) => word_segments(old, new).map(|(old, _)| old),
+        (Line::Added(new), Some(Line::Removed(old))) => word_segments(old, new).map(|(_, new)| new),
+        _ => None,
+    };
+    let Some(segments) = segments else {
+        return render_plain(line);
+    };
+
+    let removed = matches!(line, Line::Removed(_));
+    let mut rendered = if removed {
+        "-".red().to_string()
+    } else {
+        "+".green().to_string()
+    };
+    for (changed, text) in segments {
+        let segment = match (removed, changed) {
+            (true, false) => text.red().to_string(),
+            (true, true) => text.red().reversed().to_string(),
+            (false, false) => text.green().to_string(),
+            (false, true) => text.green().reversed().to_string(),
+        };
+        rendered.push_str(&segment);
+    }
+    rendered
+}
+
+/// Word segments of `old` and `new`, each flagged when it changed; `None`
+/// when the lines have too little in common for highlights to help
+type Segments<'a> = Vec<(bool, &'a str)>;
+
+fn word_segments<'a>(old: &'a str, new: &'a str) -> Option<(Segments<'a>, Segments<'a>)> {
+    let diff = TextDiff::from_words(old, new);
+    if diff.ratio() < WORD_DIFF_MIN_RATIO {
+        return None;
+    }
+
+    let (mut old_segments, mut new_segments) = (Vec::new(), Vec::new());
+    for change in diff.iter_all_changes() {
+        let text = change.value();
+        match change.tag() {
+            ChangeTag::Equal => {
+                old_segments.push((false, text));
+                new_segments.push((false, text));
+            }
+            ChangeTag::Delete => old_segments.push((true, text)),
+            ChangeTag::Insert => new_segments.push((true, text)),
+        }
+    }
+    Some((old_segments, new_segments))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_and_unified() {
+        let diff = Diff::new("a\nb\nc\n", "a\nB\nc\nd\n").header("old", "new").context(0);
+
+        assert_eq!(
+            diff.stats(),
+            DiffStats {
+                insertions: 2,
+                deletions: 1
+            }
+        );
+        let unified = diff.unified();
+        assert!(unified.starts_with("--- old\n+++ new\n"));
+        assert!(unified.contains("-b\n+B\n"));
+        assert!(!unified.contains(" a\n"));
+        assert!(Diff::new("same", "same").is_empty());
+    }
+
+    #[test]
+    fn test_classify_header_like_content() {
+        let unified = "--- a\n+++ b\n@@ -1,2 +1,2 @@\n--- rule\n+++ rule\n keep\n\\ No newline at end of file\n";
+        assert_eq!(
+            classify(unified),
+            vec![
+                Line::File("--- a"),
+                Line::File("+++ b"),
+                Line::Hunk("@@ -1,2 +1,2 @@"),
+                Line::Removed("-- rule"),
+                Line::Added("++ rule"),
+                Line::Context("keep"),
+                Line::Other("\\ No newline at end of file"),
+            ]
+        );
+        assert_eq!(hunk_lengths("@@ -3 +3,0 @@"), Some((1, 0)));
+    }
+
+    #[test]
+    fn test_word_segments() {
+        let (old, new) = word_segments("let total = price * 2;", "let total = price * 3;").unwrap();
+        let changed = |segments: &Segments| -> String {
+            segments
+                .iter()
+                .filter(|(changed, _)| *changed)
+                .map(|(_, text)| *text)
+                .collect()
+        };
+        assert_eq!(changed(&old), "2;");
+        assert_eq!(changed(&new), "3;");
+
+        assert!(word_segments("completely different", "nothing alike here").is_none());
+    }
+
+    #[test]
+    fn test_render_max_lines_and_indent() {
+        let old: String = (0..10).map(|i| format!("line {}\n", i)).collect();
+        let style = DiffStyle {
+            max_lines: Some(3),
+            indent: 2,
+            ..DiffStyle::default()
+        };
+
+        let rendered = render_unified(&Diff::new(&old, "").unified(), &style);
+        assert_eq!(rendered.len(), 4);
+        assert!(rendered.iter().all(|line| line.starts_with("  ")));
+        assert!(rendered[3].contains("8 more lines"));
+    }
+}
@@ -1,9 +1,17 @@
 //! Progress indicators
 //!
-//! Provides progress bars and spinners for long-running operations.
+//! Provides progress bars and spinners for long-running operations, and a
+//! [`Dashboard`] for several tasks running at once.
 
-use indicatif::{ProgressBar, ProgressStyle};
-use std::time::Duration;
+use console::Term;
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::output::{self, format_duration};
 
 /// Create a spinner for indeterminate progress
 pub fn spinner(message: &str) -> ProgressBar {
@@ -45,6 +53,30 @@ pub fn file_progress(total: u64) -> ProgressBar {
     pb
 }
 
+/// Create a progress bar for an upload or download, in bytes
+///
+/// Pass `None` when the size is not known yet; [`set_transfer`] fills it in
+/// once it is.
+pub fn transfer_bar(total: Option<u64>, message: &str) -> ProgressBar {
+    let pb = ProgressBar::new(total.unwrap_or(0));
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{msg} [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta})")
+            .unwrap()
+            .progress_chars("█▓░"),
+    );
+    pb.set_message(message.to_string());
+    pb
+}
+
+/// Update a transfer bar with bytes transferred and, if known, the total size
+pub fn set_transfer(pb: &ProgressBar, transferred: u64, total: Option<u64>) {
+    if let Some(total) = total {
+        pb.set_length(total);
+    }
+    pb.set_position(transferred);
+}
+
 /// Finish a progress bar with a success message
 pub fn finish_success(pb: &ProgressBar, message: &str) {
     pb.finish_with_message(format!("✓ {}", message));
@@ -55,6 +87,282 @@ pub fn finish_error(pb: &ProgressBar, message: &str) {
     pb.finish_with_message(format!("✗ {}", message));
 }
 
+/// How often a [`Dashboard`] prints status lines by default
+pub const DEFAULT_STATUS_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How a [`Dashboard`] shows progress
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DashboardMode {
+    /// A bar or spinner per task, redrawn in place
+    Bars,
+    /// Plain status lines now and then, for logs
+    Lines,
+    /// Nothing
+    Hidden,
+}
+
+impl DashboardMode {
+    /// Bars when stdout is a terminal, lines when it isn't, nothing with
+    /// machine-readable output
+    pub fn detect() -> Self {
+        if output::is_machine() {
+            Self::Hidden
+        } else if Term::stdout().is_term() {
+            Self::Bars
+        } else {
+            Self::Lines
+        }
+    }
+}
+
+/// Progress of tasks running at the same time
+///
+/// In [`DashboardMode::Lines`] each task prints a line when it starts and
+/// when it finishes, and the unfinished ones are listed every interval.
+/// Dropping the dashboard stops those periodic lines.
+pub struct Dashboard {
+    mode: DashboardMode,
+    interval: Duration,
+    multi: MultiProgress,
+    shared: Arc<Shared>,
+    ticker: Mutex<Option<JoinHandle<()>>>,
+}
+
+struct Shared {
+    tasks: Mutex<Vec<Arc<TaskState>>>,
+    out: Mutex<Box<dyn Write + Send>>,
+    stopped: Mutex<bool>,
+    wake: Condvar,
+}
+
+impl Shared {
+    fn print(&self, line: &str) {
+        let mut out = self.out.lock().unwrap();
+        writeln!(out, "{}", line).ok();
+        out.flush().ok();
+    }
+
+    fn print_status(&self) {
+        let tasks = self.tasks.lock().unwrap();
+        for task in tasks.iter().filter(|t| !t.finished.load(Ordering::Relaxed)) {
+            self.print(&task.status_line());
+        }
+    }
+}
+
+struct TaskState {
+    name: String,
+    total: Option<u64>,
+    position: AtomicU64,
+    message: Mutex<String>,
+    started: Instant,
+    finished: AtomicBool,
+}
+
+impl TaskState {
+    fn status_line(&self) -> String {
+        let position = self.position.load(Ordering::Relaxed);
+        let mut line = format!("  … {}:", self.name);
+        if let Some(total) = self.total {
+            let percent = (position * 100).checked_div(total).unwrap_or(100);
+            line.push_str(&format!(" {}/{} ({}%)", position, total, percent));
+        }
+        let message = self.message.lock().unwrap();
+        if !message.is_empty() {
+            line.push_str(&format!(" {}", message));
+        }
+        line.push_str(&format!(" ({})", format_duration(self.started.elapsed())));
+        line
+    }
+}
+
+impl Dashboard {
+    /// Create a dashboard for the current output ([`DashboardMode::detect`])
+    pub fn new() -> Self {
+        Self::with_mode(DashboardMode::detect())
+    }
+
+    /// Create a dashboard that shows progress a given way
+    pub fn with_mode(mode: DashboardMode) -> Self {
+        Self::build(mode, Box::new(std::io::stdout()))
+    }
+
+    /// Print status lines every `interval` instead of [`DEFAULT_STATUS_INTERVAL`]
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    fn build(mode: DashboardMode, out: Box<dyn Write + Send>) -> Self {
+        let target = match mode {
+            DashboardMode::Bars => ProgressDrawTarget::stdout(),
+            _ => ProgressDrawTarget::hidden(),
+        };
+        Self {
+            mode,
+            interval: DEFAULT_STATUS_INTERVAL,
+            multi: MultiProgress::with_draw_target(target),
+            shared: Arc::new(Shared {
+                tasks: Mutex::new(Vec::new()),
+                out: Mutex::new(out),
+                stopped: Mutex::new(false),
+                wake: Condvar::new(),
+            }),
+            ticker: Mutex::new(None),
+        }
+    }
+
+    /// How progress is shown
+    pub fn mode(&self) -> DashboardMode {
+        self.mode
+    }
+
+    /// Add a task with `total` steps, or a spinner when the total is unknown
+    pub fn add(&self, name: &str, total: Option<u64>) -> Task {
+        let state = Arc::new(TaskState {
+            name: name.to_string(),
+            total,
+            position: AtomicU64::new(0),
+            message: Mutex::new(String::new()),
+            started: Instant::now(),
+            finished: AtomicBool::new(false),
+        });
+
+        match self.mode {
+            DashboardMode::Bars => {
+                let bar = match total {
+                    Some(total) => ProgressBar::new(total).with_style(
+                        ProgressStyle::default_bar()
+                            .template("{prefix:.bold} [{bar:30.cyan/blue}] {pos}/{len} {msg}")
+                            .unwrap()
+                            .progress_chars("█▓░"),
+                    ),
+                    None => ProgressBar::new_spinner().with_style(
+                        ProgressStyle::default_spinner()
+                            .tick_chars("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏")
+                            .template("{spinner:.blue} {prefix:.bold} {msg} ({elapsed})")
+                            .unwrap(),
+                    ),
+                };
+                let bar = self.multi.add(bar.with_prefix(name.to_string()));
+                if total.is_none() {
+                    bar.enable_steady_tick(Duration::from_millis(80));
+                }
+                Task {
+                    bar: Some(bar),
+                    state,
+                    shared: None,
+                }
+            }
+            DashboardMode::Lines => {
+                self.shared.print(&format!("  ● {}", name));
+                self.shared.tasks.lock().unwrap().push(Arc::clone(&state));
+                self.start_ticker();
+                Task {
+                    bar: None,
+                    state,
+                    shared: Some(Arc::clone(&self.shared)),
+                }
+            }
+            DashboardMode::Hidden => Task {
+                bar: None,
+                state,
+                shared: None,
+            },
+        }
+    }
+
+    fn start_ticker(&self) {
+        let mut ticker = self.ticker.lock().unwrap();
+        if ticker.is_some() {
+            return;
+        }
+        let shared = Arc::clone(&self.shared);
+        let interval = self.interval;
+        *ticker = Some(std::thread::spawn(move || {
+            let mut stopped = shared.stopped.lock().unwrap();
+            while !*stopped {
+                let (guard, timeout) = shared.wake.wait_timeout(stopped, interval).unwrap();
+                stopped = guard;
+                if timeout.timed_out() && !*stopped {
+                    shared.print_status();
+                }
+            }
+        }));
+    }
+}
+
+impl Default for Dashboard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Dashboard {
+    fn drop(&mut self) {
+        *self.shared.stopped.lock().unwrap() = true;
+        self.shared.wake.notify_all();
+        if let Some(ticker) = self.ticker.lock().unwrap().take() {
+            ticker.join().ok();
+        }
+    }
+}
+
+/// A task on a [`Dashboard`]; clones update the same task
+#[derive(Clone)]
+pub struct Task {
+    bar: Option<ProgressBar>,
+    state: Arc<TaskState>,
+    shared: Option<Arc<Shared>>,
+}
+
+impl Task {
+    /// Advance by `delta` steps
+    pub fn inc(&self, delta: u64) {
+        self.state.position.fetch_add(delta, Ordering::Relaxed);
+        if let Some(bar) = &self.bar {
+            bar.inc(delta);
+        }
+    }
+
+    /// Show what the task is doing
+    pub fn set_message(&self, message: impl Into<String>) {
+        let message = message.into();
+        if let Some(bar) = &self.bar {
+            bar.set_message(message.clone());
+        }
+        *self.state.message.lock().unwrap() = message;
+    }
+
+    /// Finish the task with a success message
+    pub fn finish_success(&self, message: &str) {
+        self.finish("✓", message);
+    }
+
+    /// Finish the task with an error message
+    pub fn finish_error(&self, message: &str) {
+        self.finish("✗", message);
+    }
+
+    fn finish(&self, symbol: &str, message: &str) {
+        if self.state.finished.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        if let Some(bar) = &self.bar {
+            bar.finish_with_message(format!("{} {}", symbol, message));
+        }
+        if let Some(shared) = &self.shared {
+            shared.print(&format!(
+                "  {} {}: {} ({})",
+                symbol,
+                self.state.name,
+                message,
+                format_duration(self.state.started.elapsed())
+            ));
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -71,4 +379,71 @@ mod tests {
         pb.inc(50);
         pb.finish();
     }
+
+    #[test]
+    fn test_transfer_bar_learns_total() {
+        let pb = transfer_bar(None, "Downloading");
+        set_transfer(&pb, 512, Some(2048));
+        assert_eq!(pb.length(), Some(2048));
+        assert_eq!(pb.position(), 512);
+        pb.finish();
+    }
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Buffer {
+        fn lines(&self) -> Vec<String> {
+            String::from_utf8(self.0.lock().unwrap().clone())
+                .unwrap()
+                .lines()
+                .map(String::from)
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_dashboard_lines() {
+        let out = Buffer::default();
+        let dashboard = Dashboard::build(DashboardMode::Lines, Box::new(out.clone()));
+
+        let scan = dashboard.add("secrets", Some(4));
+        let lint = dashboard.add("lint", None);
+        scan.inc(3);
+        lint.set_message("swiftlint");
+        dashboard.shared.print_status();
+        scan.inc(1);
+        scan.finish_success("4 files");
+        lint.finish_error("2 violations");
+        lint.finish_success("ignored");
+        drop(dashboard);
+
+        let lines = out.lines();
+        assert_eq!(lines[0], "  ● secrets");
+        assert_eq!(lines[1], "  ● lint");
+        assert!(lines[2].starts_with("  … secrets: 3/4 (75%) ("), "{}", lines[2]);
+        assert!(lines[3].starts_with("  … lint: swiftlint ("), "{}", lines[3]);
+        assert!(lines[4].starts_with("  ✓ secrets: 4 files ("), "{}", lines[4]);
+        assert!(lines[5].starts_with("  ✗ lint: 2 violations ("), "{}", lines[5]);
+        assert_eq!(lines.len(), 6);
+    }
+
+    #[test]
+    fn test_dashboard_hidden() {
+        let dashboard = Dashboard::with_mode(DashboardMode::Hidden);
+        let task = dashboard.add("scan", Some(2));
+        task.inc(2);
+        task.finish_success("done");
+        assert_eq!(task.state.position.load(Ordering::Relaxed), 2);
+    }
 }
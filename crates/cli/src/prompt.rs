@@ -0,0 +1,228 @@
+//! Interactive prompts
+//!
+//! Confirmations, selections and masked input, asked on stderr so stdout
+//! stays clean. Nothing is asked without a terminal (CI, piped input,
+//! machine-readable output): with `--yes` ([`set_assume_yes`]) a prompt takes
+//! its yes or default answer, otherwise it fails with a hint to pass `--yes`.
+
+use console::Term;
+use foodshare_core::error::{Error, ErrorCode, Result};
+use owo_colors::OwoColorize;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::output;
+
+static ASSUME_YES: AtomicBool = AtomicBool::new(false);
+
+/// Answer every prompt with yes or its default (the global `--yes` flag)
+pub fn set_assume_yes(yes: bool) {
+    ASSUME_YES.store(yes, Ordering::Relaxed);
+}
+
+/// Whether prompts are answered without asking
+pub fn assume_yes() -> bool {
+    ASSUME_YES.load(Ordering::Relaxed)
+}
+
+/// Whether running under CI (`CI` set to anything but `false` or `0`)
+pub fn is_ci() -> bool {
+    std::env::var("CI").is_ok_and(|v| !v.is_empty() && v != "false" && v != "0")
+}
+
+/// Whether prompts can ask someone
+pub fn is_interactive() -> bool {
+    !output::is_machine() && !is_ci() && Term::stderr().is_term()
+}
+
+/// Ask a yes/no question
+///
+/// `--yes` answers yes; an empty answer takes `default`.
+pub fn confirm(question: &str, default: bool) -> Result<bool> {
+    if assume_yes() {
+        return Ok(true);
+    }
+    require_terminal(question)?;
+
+    let hint = if default { "[Y/n]" } else { "[y/N]" };
+    loop {
+        let answer = ask(&format!("{} {} ", question.bold(), hint.dimmed()))?;
+        match parse_confirm(&answer, default) {
+            Some(yes) => return Ok(yes),
+            None => eprintln!("  Please answer y or n"),
+        }
+    }
+}
+
+/// Pick one of `items`; returns its index
+///
+/// `--yes` and an empty answer take `default`.
+pub fn select<T: Display>(question: &str, items: &[T], default: usize) -> Result<usize> {
+    if assume_yes() {
+        return Ok(default);
+    }
+    require_terminal(question)?;
+
+    eprintln!("{}", question.bold());
+    print_items(items, |index| index == default);
+    loop {
+        let answer = ask(&format!("Choice {} ", format!("[{}]", default + 1).dimmed()))?;
+        match parse_choice(&answer, items.len(), default) {
+            Some(index) => return Ok(index),
+            None => eprintln!("  Please enter a number from 1 to {}", items.len()),
+        }
+    }
+}
+
+/// Pick any of `items`; returns their indices in order
+///
+/// Answers are numbers separated by commas or spaces, `all` or `none`.
+/// `--yes` and an empty answer take the items whose `defaults` entry is true.
+pub fn multi_select<T: Display>(question: &str, items: &[T], defaults: &[bool]) -> Result<Vec<usize>> {
+    let preselected: Vec<usize> = (0..items.len())
+        .filter(|&i| defaults.get(i).copied().unwrap_or(false))
+        .collect();
+    if assume_yes() {
+        return Ok(preselected);
+    }
+    require_terminal(question)?;
+
+    eprintln!("{}", question.bold());
+    print_items(items, |index| preselected.contains(&index));
+    let default_hint = if preselected.is_empty() {
+        "none".to_string()
+    } else {
+        preselected
+            .iter()
+            .map(|i| (i + 1).to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    loop {
+        let answer = ask(&format!("Choices {} ", format!("[{}]", default_hint).dimmed()))?;
+        match parse_choices(&answer, items.len(), &preselected) {
+            Some(indices) => return Ok(indices),
+            None => eprintln!("  Please enter numbers from 1 to {}, 'all' or 'none'", items.len()),
+        }
+    }
+}
+
+/// Read a secret without echoing it
+///
+/// `--yes` can't answer this, so it fails without a terminal.
+pub fn password(question: &str) -> Result<String> {
+    if !is_interactive() {
+        return Err(
+            Error::new(ErrorCode::InvalidInput, format!("No terminal to ask: {}", question))
+                .with_suggestion("Pass the value as an option instead"),
+        );
+    }
+
+    let term = Term::stderr();
+    term.write_str(&format!("{} ", question.bold())).map_err(read_error)?;
+    let answer = term.read_secure_line().map_err(read_error)?;
+    Ok(answer.trim().to_string())
+}
+
+fn require_terminal(question: &str) -> Result<()> {
+    if is_interactive() {
+        return Ok(());
+    }
+    Err(
+        Error::new(ErrorCode::InvalidInput, format!("No terminal to ask: {}", question))
+            .with_suggestion("Pass --yes to answer prompts without a terminal"),
+    )
+}
+
+fn ask(prompt: &str) -> Result<String> {
+    let term = Term::stderr();
+    term.write_str(prompt).map_err(read_error)?;
+    term.read_line().map_err(read_error)
+}
+
+fn read_error(e: std::io::Error) -> Error {
+    Error::io(format!("Failed to read answer: {}", e)).with_source(e)
+}
+
+fn print_items<T: Display>(items: &[T], marked: impl Fn(usize) -> bool) {
+    for (index, item) in items.iter().enumerate() {
+        let marker = if marked(index) { "*" } else { " " };
+        eprintln!("  {} {} {}", marker.green(), format!("{:>2})", index + 1).cyan(), item);
+    }
+}
+
+/// Parse a yes/no answer; `None` when it is neither
+fn parse_confirm(answer: &str, default: bool) -> Option<bool> {
+    match answer.trim().to_lowercase().as_str() {
+        "" => Some(default),
+        "y" | "yes" => Some(true),
+        "n" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parse a 1-based choice out of `len` items
+fn parse_choice(answer: &str, len: usize, default: usize) -> Option<usize> {
+    let answer = answer.trim();
+    if answer.is_empty() {
+        return Some(default);
+    }
+    answer
+        .parse::<usize>()
+        .ok()
+        .filter(|n| (1..=len).contains(n))
+        .map(|n| n - 1)
+}
+
+/// Parse 1-based choices out of `len` items
+fn parse_choices(answer: &str, len: usize, defaults: &[usize]) -> Option<Vec<usize>> {
+    match answer.trim().to_lowercase().as_str() {
+        "" => return Some(defaults.to_vec()),
+        "all" => return Some((0..len).collect()),
+        "none" => return Some(Vec::new()),
+        _ => {}
+    }
+
+    let mut indices = Vec::new();
+    for part in answer
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|p| !p.is_empty())
+    {
+        indices.push(parse_choice(part, len, 0)?);
+    }
+    indices.sort_unstable();
+    indices.dedup();
+    Some(indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_confirm() {
+        assert_eq!(parse_confirm("", true), Some(true));
+        assert_eq!(parse_confirm("  ", false), Some(false));
+        assert_eq!(parse_confirm("Y", false), Some(true));
+        assert_eq!(parse_confirm("no", true), Some(false));
+        assert_eq!(parse_confirm("maybe", true), None);
+    }
+
+    #[test]
+    fn test_parse_choice() {
+        assert_eq!(parse_choice("", 3, 1), Some(1));
+        assert_eq!(parse_choice("3", 3, 0), Some(2));
+        assert_eq!(parse_choice("0", 3, 0), None);
+        assert_eq!(parse_choice("4", 3, 0), None);
+        assert_eq!(parse_choice("two", 3, 0), None);
+    }
+
+    #[test]
+    fn test_parse_choices() {
+        assert_eq!(parse_choices("", 4, &[1]), Some(vec![1]));
+        assert_eq!(parse_choices("all", 3, &[]), Some(vec![0, 1, 2]));
+        assert_eq!(parse_choices("None", 3, &[0]), Some(vec![]));
+        assert_eq!(parse_choices("3, 1 3", 3, &[]), Some(vec![0, 2]));
+        assert_eq!(parse_choices("1,5", 3, &[]), None);
+    }
+}
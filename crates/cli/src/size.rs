@@ -0,0 +1,176 @@
+//! App size baselines
+//!
+//! The iOS and Android size reports are saved as JSON baselines committed
+//! to the repo. Each build's report is compared with the baseline component
+//! by component, and its download size is checked against the `[size]`
+//! budget. Sizes are in decimal units (1 MB = 1,000,000 bytes), matching
+//! App Store Connect and the Play Console.
+
+use crate::output::format_size_decimal;
+use foodshare_core::config::SizeConfig;
+use foodshare_core::error::{Error, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// Bytes in a kilobyte
+pub const KB: u64 = 1000;
+
+/// Bytes in a megabyte
+pub const MB: u64 = KB * 1000;
+
+/// Size change of one component against the baseline
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeDelta<K> {
+    /// Component the sizes belong to
+    pub key: K,
+    /// Size in the baseline (0 if new)
+    pub before: u64,
+    /// Current size (0 if removed)
+    pub after: u64,
+}
+
+impl<K> SizeDelta<K> {
+    /// Bytes added (negative if the component shrank)
+    pub fn change(&self) -> i64 {
+        self.after as i64 - self.before as i64
+    }
+
+    /// `(new)` or `(removed)` for components on one side only
+    pub fn note(&self) -> &'static str {
+        match (self.before, self.after) {
+            (0, _) => " (new)",
+            (_, 0) => " (removed)",
+            _ => "",
+        }
+    }
+}
+
+/// Components whose size changed from `before` to `after`, largest change first
+pub fn diff<K: Ord + Clone>(before: &BTreeMap<K, u64>, after: &BTreeMap<K, u64>) -> Vec<SizeDelta<K>> {
+    let mut deltas: Vec<SizeDelta<K>> = before
+        .keys()
+        .chain(after.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|key| SizeDelta {
+            key: key.clone(),
+            before: before.get(key).copied().unwrap_or(0),
+            after: after.get(key).copied().unwrap_or(0),
+        })
+        .filter(|delta| delta.change() != 0)
+        .collect();
+    deltas.sort_by_key(|delta| std::cmp::Reverse(delta.change().abs()));
+    deltas
+}
+
+/// Budget violations of a download `size`, comparing growth with the
+/// baseline's download size
+pub fn budget_violations(size: u64, baseline: Option<u64>, config: &SizeConfig) -> Vec<String> {
+    let mut violations = Vec::new();
+
+    if let Some(budget_mb) = config.budget_mb {
+        let budget = (budget_mb * MB as f64) as u64;
+        if size > budget {
+            violations.push(format!(
+                "Download size {} exceeds the {} budget",
+                format_size_decimal(size),
+                format_size_decimal(budget)
+            ));
+        }
+    }
+    if let (Some(max_growth_kb), Some(baseline)) = (config.max_growth_kb, baseline) {
+        let growth = size as i64 - baseline as i64;
+        if growth > (max_growth_kb * KB) as i64 {
+            violations.push(format!(
+                "Download size grew by {} since the baseline (limit {})",
+                format_size_decimal(growth as u64),
+                format_size_decimal(max_growth_kb * KB)
+            ));
+        }
+    }
+    violations
+}
+
+/// A size change with its sign, red for growth and green for shrinking
+pub fn format_change(bytes: i64) -> String {
+    use owo_colors::OwoColorize;
+
+    let text = format!(
+        "{}{}",
+        if bytes < 0 { "-" } else { "+" },
+        format_size_decimal(bytes.unsigned_abs())
+    );
+    if bytes > 0 {
+        text.red().to_string()
+    } else {
+        text.green().to_string()
+    }
+}
+
+/// ` (+1.2 MB)` when `current` differs from the baseline's `previous`
+pub fn vs_baseline(current: u64, previous: Option<u64>) -> String {
+    match previous {
+        Some(previous) if previous != current => {
+            format!(" ({})", format_change(current as i64 - previous as i64))
+        }
+        _ => String::new(),
+    }
+}
+
+/// Load a saved report
+pub fn load<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    if !path.exists() {
+        return Err(Error::file_not_found(path));
+    }
+    Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+/// Save a report as JSON, e.g. as the new baseline
+pub fn save<T: Serialize>(report: &T, path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(report)? + "\n")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let before = BTreeMap::from([("dex", 4_000), ("lib", 1_000), ("res", 500)]);
+        let after = BTreeMap::from([("dex", 4_600), ("res", 500), ("assets", 200)]);
+
+        let deltas = diff(&before, &after);
+        let changes: Vec<_> = deltas.iter().map(|d| (d.key, d.change(), d.note())).collect();
+        assert_eq!(
+            changes,
+            [("lib", -1_000, " (removed)"), ("dex", 600, ""), ("assets", 200, " (new)")]
+        );
+    }
+
+    #[test]
+    fn test_budget_violations() {
+        let config = SizeConfig {
+            budget_mb: Some(9.5),
+            max_growth_kb: Some(500),
+        };
+        assert_eq!(budget_violations(9_600_000, Some(9_000_000), &config).len(), 2);
+        assert_eq!(budget_violations(9_600_000, None, &config).len(), 1);
+        assert!(budget_violations(9_000_000, Some(9_000_000), &config).is_empty());
+        assert!(budget_violations(u64::MAX / 2, None, &SizeConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".foodshare/size.json");
+        save(&BTreeMap::from([("dex", 1u64)]), &path).unwrap();
+        assert_eq!(load::<BTreeMap<String, u64>>(&path).unwrap()["dex"], 1);
+        assert!(load::<BTreeMap<String, u64>>(&dir.path().join("missing.json")).is_err());
+    }
+}
@@ -83,4 +83,52 @@ mod tests {
         let config = Config::load(None);
         assert!(config.is_ok());
     }
+
+    #[test]
+    fn test_watch_config() {
+        let schema: ConfigSchema = toml::from_str(
+            r#"
+            [watch]
+            debounce_ms = 500
+
+            [[watch.checks]]
+            name = "lint"
+            run = ["lint", "--strict"]
+            patterns = ["*.swift"]
+            files = true
+            "#,
+        )
+        .unwrap();
+        assert_eq!(schema.watch.debounce_ms, 500);
+        assert!(schema.watch.notify);
+        assert!(schema.watch.ignore.contains(&"node_modules".to_string()));
+        assert_eq!(schema.watch.checks[0].run, ["lint", "--strict"]);
+
+        let defaults = ConfigSchema::default();
+        assert_eq!(defaults.watch.debounce_ms, 300);
+        assert!(defaults.watch.checks.is_empty());
+    }
+
+    #[test]
+    fn test_plugins_config() {
+        let schema: ConfigSchema = toml::from_str(
+            r#"
+            [plugins]
+            disabled = ["slow"]
+
+            [[plugins.checks]]
+            name = "env-files"
+            command = ["python3", "scripts/check_env.py"]
+            patterns = [".env*"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(schema.plugins.dir, "plugins");
+        assert_eq!(schema.plugins.timeout_secs, 60);
+        assert_eq!(schema.plugins.disabled, ["slow"]);
+        let check = &schema.plugins.checks[0];
+        assert_eq!(check.hooks, ["pre-commit"]);
+        assert!(check.required);
+        assert_eq!(check.timeout_secs, None);
+    }
 }
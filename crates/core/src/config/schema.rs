@@ -26,6 +26,54 @@ pub struct ConfigSchema {
     /// Secrets scanning configuration
     #[serde(default)]
     pub secrets: SecretsConfig,
+
+    /// App size budget configuration
+    #[serde(default)]
+    pub size: SizeConfig,
+
+    /// Web bundle budget configuration
+    #[serde(default)]
+    pub bundle: BundleConfig,
+
+    /// Dependency audit configuration
+    #[serde(default)]
+    pub deps: DepsConfig,
+
+    /// Import organization configuration
+    #[serde(default)]
+    pub imports: ImportsConfig,
+
+    /// Server/client component boundary configuration
+    #[serde(default)]
+    pub boundaries: BoundariesConfig,
+
+    /// Supabase query lint configuration
+    #[serde(default)]
+    pub supabase: SupabaseConfig,
+
+    /// Lighthouse performance budget configuration
+    #[serde(default)]
+    pub perf: PerfConfig,
+
+    /// Self-update release source configuration
+    #[serde(default)]
+    pub update: UpdateConfig,
+
+    /// Code protection configuration
+    #[serde(default)]
+    pub protect: ProtectConfig,
+
+    /// Lint and format configuration sync
+    #[serde(default)]
+    pub style: StyleConfig,
+
+    /// Watch mode configuration
+    #[serde(default)]
+    pub watch: WatchConfig,
+
+    /// Custom check plugins
+    #[serde(default)]
+    pub plugins: PluginsConfig,
 }
 
 /// General project configuration
@@ -159,12 +207,17 @@ pub struct TestConfig {
     /// Minimum coverage threshold (percentage)
     #[serde(default = "default_coverage_threshold")]
     pub coverage_threshold: u8,
+
+    /// Flakiness rate (0 to 1) at which failures stop failing quarantined runs
+    #[serde(default = "default_flaky_threshold")]
+    pub flaky_threshold: f64,
 }
 
 impl Default for TestConfig {
     fn default() -> Self {
         Self {
             coverage_threshold: default_coverage_threshold(),
+            flaky_threshold: default_flaky_threshold(),
         }
     }
 }
@@ -173,8 +226,12 @@ fn default_coverage_threshold() -> u8 {
     70
 }
 
+fn default_flaky_threshold() -> f64 {
+    0.1
+}
+
 /// Secrets scanning configuration
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SecretsConfig {
     /// Additional patterns to check
     #[serde(default)]
@@ -187,4 +244,446 @@ pub struct SecretsConfig {
     /// Files to exclude from scanning
     #[serde(default)]
     pub exclude_files: Vec<String>,
+
+    /// Baseline of accepted findings, used when the file exists
+    #[serde(default = "default_secrets_baseline")]
+    pub baseline: String,
+
+    /// Reuse the findings of files unchanged since their last scan
+    #[serde(default = "default_true")]
+    pub cache: bool,
+}
+
+impl Default for SecretsConfig {
+    fn default() -> Self {
+        Self {
+            additional_patterns: Vec::new(),
+            exclude_patterns: Vec::new(),
+            exclude_files: Vec::new(),
+            baseline: default_secrets_baseline(),
+            cache: true,
+        }
+    }
+}
+
+fn default_secrets_baseline() -> String {
+    ".secrets.baseline.json".to_string()
+}
+
+/// App size budget configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SizeConfig {
+    /// Maximum download size in megabytes
+    #[serde(default)]
+    pub budget_mb: Option<f64>,
+
+    /// Maximum growth over the committed baseline in kilobytes
+    #[serde(default)]
+    pub max_growth_kb: Option<u64>,
+}
+
+/// Web bundle budget configuration
+///
+/// Budgets apply to each route's first-load JavaScript, gzipped.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct BundleConfig {
+    /// Maximum first-load JS of any route in kilobytes
+    #[serde(default)]
+    pub budget_kb: Option<u64>,
+
+    /// Budgets for specific routes in kilobytes (`"/listing/[id]" = 180`)
+    #[serde(default)]
+    pub routes: std::collections::BTreeMap<String, u64>,
+
+    /// Maximum growth of a route over the committed baseline in kilobytes
+    #[serde(default)]
+    pub max_growth_kb: Option<u64>,
+}
+
+/// Dependency audit configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepsConfig {
+    /// Hosts dependencies may be fetched from
+    #[serde(default = "default_allowed_hosts")]
+    pub allowed_hosts: Vec<String>,
+
+    /// Hosts Gradle repositories may point at
+    #[serde(default = "default_allowed_repositories")]
+    pub allowed_repositories: Vec<String>,
+
+    /// npm package licenses to reject (SPDX prefixes, `GPL-3.0` covers `GPL-3.0-only`)
+    #[serde(default = "default_denied_licenses")]
+    pub denied_licenses: Vec<String>,
+
+    /// Lowest advisory severity that fails the npm audit
+    #[serde(default = "default_fail_on")]
+    pub fail_on: String,
+}
+
+impl Default for DepsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_hosts: default_allowed_hosts(),
+            allowed_repositories: default_allowed_repositories(),
+            denied_licenses: default_denied_licenses(),
+            fail_on: default_fail_on(),
+        }
+    }
+}
+
+fn default_allowed_hosts() -> Vec<String> {
+    vec!["github.com".to_string()]
+}
+
+fn default_allowed_repositories() -> Vec<String> {
+    ["dl.google.com", "maven.google.com", "repo.maven.apache.org", "repo1.maven.org", "plugins.gradle.org"]
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+fn default_denied_licenses() -> Vec<String> {
+    ["AGPL-3.0", "GPL-2.0", "GPL-3.0", "SSPL-1.0"]
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+fn default_fail_on() -> String {
+    "high".to_string()
+}
+
+/// Import organization configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportsConfig {
+    /// Parent directories a relative import may climb before it must use a path alias
+    #[serde(default = "default_max_parent_dirs")]
+    pub max_parent_dirs: usize,
+}
+
+impl Default for ImportsConfig {
+    fn default() -> Self {
+        Self {
+            max_parent_dirs: default_max_parent_dirs(),
+        }
+    }
+}
+
+fn default_max_parent_dirs() -> usize {
+    1
+}
+
+/// Server/client component boundary configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct BoundariesConfig {
+    /// Modules client components must not import: globs of source paths
+    /// (`src/lib/server/**`) or package names (`stripe`)
+    #[serde(default)]
+    pub server_only: Vec<String>,
+}
+
+/// Supabase query lint configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SupabaseConfig {
+    /// Tables that need explicit columns and a limit (empty = every table)
+    #[serde(default)]
+    pub large_tables: Vec<String>,
+
+    /// Globs of files that run in the browser besides `"use client"` modules
+    #[serde(default)]
+    pub client_paths: Vec<String>,
+}
+
+/// Lighthouse performance budget configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerfConfig {
+    /// Origin the production build is served on
+    #[serde(default = "default_perf_base_url")]
+    pub base_url: String,
+
+    /// Command serving the build (`npm run start`); unset = already running
+    #[serde(default)]
+    pub start_command: Option<String>,
+
+    /// Seconds to wait for the started server to accept connections
+    #[serde(default = "default_start_timeout_secs")]
+    pub start_timeout_secs: u64,
+
+    /// Routes to audit
+    #[serde(default = "default_perf_routes")]
+    pub routes: Vec<String>,
+
+    /// Lighthouse runs per route; the run with the median performance score counts
+    #[serde(default = "default_perf_runs")]
+    pub runs: usize,
+
+    /// Emulate a desktop instead of a throttled mobile device
+    #[serde(default)]
+    pub desktop: bool,
+
+    /// Budgets for every route
+    #[serde(default)]
+    pub budget: PerfBudget,
+
+    /// Budgets for specific routes, overriding `budget` field by field
+    #[serde(default)]
+    pub route_budgets: std::collections::BTreeMap<String, PerfBudget>,
+}
+
+impl Default for PerfConfig {
+    fn default() -> Self {
+        Self {
+            base_url: default_perf_base_url(),
+            start_command: None,
+            start_timeout_secs: default_start_timeout_secs(),
+            routes: default_perf_routes(),
+            runs: default_perf_runs(),
+            desktop: false,
+            budget: PerfBudget::default(),
+            route_budgets: std::collections::BTreeMap::new(),
+        }
+    }
+}
+
+fn default_perf_base_url() -> String {
+    "http://localhost:3000".to_string()
+}
+
+fn default_start_timeout_secs() -> u64 {
+    60
+}
+
+fn default_perf_routes() -> Vec<String> {
+    vec!["/".to_string()]
+}
+
+fn default_perf_runs() -> usize {
+    1
+}
+
+/// Lighthouse budgets; unset budgets aren't checked
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+pub struct PerfBudget {
+    /// Minimum performance score (0-100)
+    #[serde(default)]
+    pub min_performance: Option<u32>,
+
+    /// Minimum accessibility score (0-100)
+    #[serde(default)]
+    pub min_accessibility: Option<u32>,
+
+    /// Minimum best practices score (0-100)
+    #[serde(default)]
+    pub min_best_practices: Option<u32>,
+
+    /// Minimum SEO score (0-100)
+    #[serde(default)]
+    pub min_seo: Option<u32>,
+
+    /// Maximum Largest Contentful Paint in milliseconds
+    #[serde(default)]
+    pub max_lcp_ms: Option<u64>,
+
+    /// Maximum Cumulative Layout Shift
+    #[serde(default)]
+    pub max_cls: Option<f64>,
+
+    /// Maximum Total Blocking Time in milliseconds
+    #[serde(default)]
+    pub max_tbt_ms: Option<u64>,
+}
+
+/// Self-update release source configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateConfig {
+    /// GitHub repository publishing the releases, as `owner/name`
+    #[serde(default = "default_update_repository")]
+    pub repository: String,
+
+    /// Internal bucket mirroring the releases; used instead of GitHub when set
+    #[serde(default)]
+    pub bucket_url: Option<String>,
+}
+
+impl Default for UpdateConfig {
+    fn default() -> Self {
+        Self {
+            repository: default_update_repository(),
+            bucket_url: None,
+        }
+    }
+}
+
+fn default_update_repository() -> String {
+    "Foodshareclub/foodshare-tools".to_string()
+}
+
+/// Code protection configuration
+///
+/// Unset fields keep the platform tool's defaults.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ProtectConfig {
+    /// Require confirmation before committing changes to protected paths
+    #[serde(default)]
+    pub interactive_approval: Option<bool>,
+
+    /// Glob patterns for paths that need approval
+    #[serde(default)]
+    pub protected_paths: Option<Vec<String>>,
+}
+
+/// Lint and format configuration sync
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct StyleConfig {
+    /// Directory with the canonical config files (unset = built-in copies)
+    #[serde(default)]
+    pub template_dir: Option<String>,
+}
+
+/// Watch mode configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchConfig {
+    /// Quiet period after the last change before checks run, in milliseconds
+    #[serde(default = "default_watch_debounce_ms")]
+    pub debounce_ms: u64,
+
+    /// Show a desktop notification when a check starts or stops failing
+    #[serde(default = "default_true")]
+    pub notify: bool,
+
+    /// Directory names never watched, besides `.git`
+    #[serde(default = "default_watch_ignore")]
+    pub ignore: Vec<String>,
+
+    /// Checks to run (empty = the platform's defaults)
+    #[serde(default)]
+    pub checks: Vec<WatchCheck>,
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self {
+        Self {
+            debounce_ms: default_watch_debounce_ms(),
+            notify: true,
+            ignore: default_watch_ignore(),
+            checks: Vec::new(),
+        }
+    }
+}
+
+fn default_watch_debounce_ms() -> u64 {
+    300
+}
+
+fn default_watch_ignore() -> Vec<String> {
+    [".build", ".gradle", ".next", "build", "DerivedData", "node_modules", "target"]
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// A check run by watch mode
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchCheck {
+    /// Name shown in the status and notifications
+    pub name: String,
+
+    /// Command and arguments for the platform tool, e.g. `["lint", "--strict"]`
+    pub run: Vec<String>,
+
+    /// File name globs whose changes trigger the check (empty = any file)
+    #[serde(default)]
+    pub patterns: Vec<String>,
+
+    /// Append the changed files matching `patterns` to the command
+    #[serde(default)]
+    pub files: bool,
+}
+
+/// Custom check plugins
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginsConfig {
+    /// Run plugins at all
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Directory searched for plugin executables and `.wasm` modules
+    #[serde(default = "default_plugins_dir")]
+    pub dir: String,
+
+    /// Command that runs a `.wasm` module, which is appended to it
+    #[serde(default = "default_wasm_runtime")]
+    pub wasm_runtime: Vec<String>,
+
+    /// Time a plugin gets to answer, in seconds
+    #[serde(default = "default_plugin_timeout_secs")]
+    pub timeout_secs: u64,
+
+    /// Names of plugins not to run
+    #[serde(default)]
+    pub disabled: Vec<String>,
+
+    /// Plugins declared here instead of found in `dir`
+    #[serde(default)]
+    pub checks: Vec<PluginCheck>,
+}
+
+impl Default for PluginsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            dir: default_plugins_dir(),
+            wasm_runtime: default_wasm_runtime(),
+            timeout_secs: default_plugin_timeout_secs(),
+            disabled: Vec::new(),
+            checks: Vec::new(),
+        }
+    }
+}
+
+fn default_plugins_dir() -> String {
+    "plugins".to_string()
+}
+
+fn default_wasm_runtime() -> Vec<String> {
+    vec!["wasmtime".to_string(), "run".to_string(), "--dir=.".to_string()]
+}
+
+fn default_plugin_timeout_secs() -> u64 {
+    60
+}
+
+/// A plugin declared in config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginCheck {
+    /// Name shown in results and used by `disabled`
+    pub name: String,
+
+    /// Program and arguments, e.g. `["python3", "scripts/check_env.py"]`;
+    /// a `.wasm` program runs with `wasm_runtime`
+    pub command: Vec<String>,
+
+    /// One-line description for `plugins list`
+    #[serde(default)]
+    pub description: String,
+
+    /// Hooks the check runs in: `pre-commit`, `pre-push`
+    #[serde(default = "default_plugin_hooks")]
+    pub hooks: Vec<String>,
+
+    /// File name globs the check cares about (empty = any file)
+    #[serde(default)]
+    pub patterns: Vec<String>,
+
+    /// Fail the hook when the check fails, rather than only warn
+    #[serde(default = "default_true")]
+    pub required: bool,
+
+    /// Overrides `timeout_secs` for this plugin
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+fn default_plugin_hooks() -> Vec<String> {
+    vec!["pre-commit".to_string()]
 }
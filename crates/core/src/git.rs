@@ -180,6 +180,53 @@ impl GitRepo {
             .collect())
     }
 
+    /// Check that a revision names a commit
+    #[must_use] pub fn rev_exists(&self, rev: &str) -> bool {
+        let result = run_command_in_dir(
+            "git",
+            &["rev-parse", "--verify", "--quiet", &format!("{rev}^{{commit}}")],
+            &self.workdir,
+        );
+
+        result.is_ok_and(|r| r.success)
+    }
+
+    /// Get the files under a directory (relative to the root) at a revision
+    pub fn files_at(&self, rev: &str, dir: &Path) -> Result<Vec<PathBuf>> {
+        let result = run_command_in_dir(
+            "git",
+            &["ls-tree", "-r", "--name-only", rev, "--", &dir.to_string_lossy()],
+            &self.workdir,
+        )?;
+        if !result.success {
+            return Err(Error::git(format!(
+                "git ls-tree {rev} failed: {}",
+                result.stderr.trim()
+            )));
+        }
+
+        Ok(result
+            .stdout
+            .lines()
+            .filter(|l| !l.is_empty())
+            .map(PathBuf::from)
+            .collect())
+    }
+
+    /// Get a file's content (path relative to the root) at a revision
+    pub fn file_at(&self, rev: &str, path: &Path) -> Result<String> {
+        let spec = format!("{rev}:{}", path.to_string_lossy());
+        let result = run_command_in_dir("git", &["show", &spec], &self.workdir)?;
+        if !result.success {
+            return Err(Error::git(format!(
+                "git show {spec} failed: {}",
+                result.stderr.trim()
+            )));
+        }
+
+        Ok(result.stdout)
+    }
+
     /// Check if there are uncommitted changes
     pub fn has_uncommitted_changes(&self) -> Result<bool> {
         let result = run_command_in_dir(
@@ -318,4 +365,29 @@ mod tests {
         assert_eq!(cloned.insertions, 20);
         assert_eq!(cloned.deletions, 10);
     }
+
+    #[test]
+    fn test_files_at_revision() {
+        let dir = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            let result = run_command_in_dir("git", args, dir.path()).unwrap();
+            assert!(result.success, "git {args:?}: {}", result.stderr);
+        };
+        git(&["init", "-q"]);
+        std::fs::create_dir(dir.path().join("messages")).unwrap();
+        std::fs::write(dir.path().join("messages/en.json"), "{}").unwrap();
+        git(&["add", "."]);
+        git(&["-c", "user.name=t", "-c", "user.email=t@t", "commit", "-qm", "init"]);
+        git(&["tag", "v1"]);
+
+        let repo = GitRepo::open(dir.path()).unwrap();
+        assert!(repo.rev_exists("v1"));
+        assert!(!repo.rev_exists("v2"));
+        assert_eq!(
+            repo.files_at("v1", Path::new("messages")).unwrap(),
+            vec![PathBuf::from("messages/en.json")]
+        );
+        assert_eq!(repo.file_at("v1", Path::new("messages/en.json")).unwrap(), "{}");
+        assert!(repo.file_at("v1", Path::new("messages/de.json")).is_err());
+    }
 }
@@ -0,0 +1,243 @@
+//! Secret storage for credentials and session tokens
+//!
+//! Provides a small keychain abstraction keyed by `(service, account)`:
+//! - [`FileKeychain`]: owner-only files under the user config directory
+//! - [`MemoryKeychain`]: process-local storage for tests and ephemeral sessions
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use foodshare_core::keychain::{FileKeychain, Keychain};
+//!
+//! let keychain = FileKeychain::default_keychain()?;
+//! keychain.set("foodshare-api-client", "production", "secret")?;
+//!
+//! if let Some(secret) = keychain.get("foodshare-api-client", "production")? {
+//!     println!("Stored {} bytes", secret.len());
+//! }
+//! ```
+
+use crate::error::{Error, ErrorCode, Result};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// Storage backend for secrets
+pub trait Keychain: Send + Sync {
+    /// Read a secret, returning `None` if it is not stored
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backend cannot be read.
+    fn get(&self, service: &str, account: &str) -> Result<Option<String>>;
+
+    /// Store a secret, replacing any previous value
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backend cannot be written.
+    fn set(&self, service: &str, account: &str, secret: &str) -> Result<()>;
+
+    /// Delete a secret, returning whether it existed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backend cannot be written.
+    fn delete(&self, service: &str, account: &str) -> Result<bool>;
+}
+
+/// File-backed keychain
+///
+/// Each secret lives in its own file named by a hash of the service and
+/// account. On Unix the directory is created `0700` and files `0600`.
+#[derive(Debug, Clone)]
+pub struct FileKeychain {
+    dir: PathBuf,
+}
+
+impl FileKeychain {
+    /// Create a keychain rooted at `dir`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be created.
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        restrict_permissions(&dir, 0o700)?;
+        Ok(Self { dir })
+    }
+
+    /// Create a keychain in the default location (`<config dir>/foodshare-tools/keychain`)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be created.
+    pub fn default_keychain() -> Result<Self> {
+        let dir = dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from(".config"))
+            .join("foodshare-tools")
+            .join("keychain");
+        Self::new(dir)
+    }
+
+    /// Directory holding the secrets
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn entry_path(&self, service: &str, account: &str) -> PathBuf {
+        let mut hasher = Sha256::new();
+        hasher.update(service.as_bytes());
+        hasher.update([0]);
+        hasher.update(account.as_bytes());
+        self.dir.join(hex::encode(hasher.finalize()))
+    }
+}
+
+impl Keychain for FileKeychain {
+    fn get(&self, service: &str, account: &str) -> Result<Option<String>> {
+        match fs::read_to_string(self.entry_path(service, account)) {
+            Ok(secret) => Ok(Some(secret)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn set(&self, service: &str, account: &str, secret: &str) -> Result<()> {
+        let path = self.entry_path(service, account);
+        let tmp = path.with_extension("tmp");
+
+        // Write then rename so a crash never leaves a truncated secret
+        let mut file = open_private(&tmp)?;
+        file.write_all(secret.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    fn delete(&self, service: &str, account: &str) -> Result<bool> {
+        match fs::remove_file(self.entry_path(service, account)) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn open_private(path: &Path) -> Result<fs::File> {
+    use std::os::unix::fs::OpenOptionsExt;
+    Ok(fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?)
+}
+
+#[cfg(not(unix))]
+fn open_private(path: &Path) -> Result<fs::File> {
+    Ok(fs::File::create(path)?)
+}
+
+#[cfg(unix)]
+fn restrict_permissions(path: &Path, mode: u32) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn restrict_permissions(_path: &Path, _mode: u32) -> Result<()> {
+    Ok(())
+}
+
+/// In-memory keychain
+///
+/// Secrets are lost when the process exits.
+#[derive(Debug, Default)]
+pub struct MemoryKeychain {
+    entries: RwLock<HashMap<(String, String), String>>,
+}
+
+impl MemoryKeychain {
+    /// Create an empty keychain
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+fn lock_error() -> Error {
+    Error::new(ErrorCode::Internal, "Failed to acquire keychain lock")
+}
+
+impl Keychain for MemoryKeychain {
+    fn get(&self, service: &str, account: &str) -> Result<Option<String>> {
+        let guard = self.entries.read().map_err(|_| lock_error())?;
+        Ok(guard.get(&(service.to_string(), account.to_string())).cloned())
+    }
+
+    fn set(&self, service: &str, account: &str, secret: &str) -> Result<()> {
+        let mut guard = self.entries.write().map_err(|_| lock_error())?;
+        guard.insert((service.to_string(), account.to_string()), secret.to_string());
+        Ok(())
+    }
+
+    fn delete(&self, service: &str, account: &str) -> Result<bool> {
+        let mut guard = self.entries.write().map_err(|_| lock_error())?;
+        Ok(guard.remove(&(service.to_string(), account.to_string())).is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn roundtrip(keychain: &dyn Keychain) {
+        assert_eq!(keychain.get("svc", "alice").unwrap(), None);
+
+        keychain.set("svc", "alice", "s3cret").unwrap();
+        keychain.set("svc", "bob", "other").unwrap();
+        assert_eq!(keychain.get("svc", "alice").unwrap().as_deref(), Some("s3cret"));
+
+        keychain.set("svc", "alice", "rotated").unwrap();
+        assert_eq!(keychain.get("svc", "alice").unwrap().as_deref(), Some("rotated"));
+
+        assert!(keychain.delete("svc", "alice").unwrap());
+        assert!(!keychain.delete("svc", "alice").unwrap());
+        assert_eq!(keychain.get("svc", "bob").unwrap().as_deref(), Some("other"));
+    }
+
+    #[test]
+    fn test_memory_keychain() {
+        roundtrip(&MemoryKeychain::new());
+    }
+
+    #[test]
+    fn test_file_keychain() {
+        let temp = TempDir::new().unwrap();
+        let keychain = FileKeychain::new(temp.path().join("keychain")).unwrap();
+        roundtrip(&keychain);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_keychain_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = TempDir::new().unwrap();
+        let keychain = FileKeychain::new(temp.path()).unwrap();
+        keychain.set("svc", "alice", "s3cret").unwrap();
+
+        let path = keychain.entry_path("svc", "alice");
+        let mode = fs::metadata(path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
@@ -8,6 +8,8 @@
 //! - **Process execution**: Safe command execution with timeouts
 //! - **Configuration**: TOML-based configuration with validation
 //! - **Health checks**: Verify tool dependencies and environment
+//! - **Keychain**: Secret storage for credentials and session tokens
+//! - **Project detection**: Tell iOS, Android, web and backend projects apart
 //!
 //! # Example
 //!
@@ -41,7 +43,9 @@ pub mod feature_flags;
 pub mod file_scanner;
 pub mod git;
 pub mod health;
+pub mod keychain;
 pub mod process;
+pub mod project;
 pub mod rate_limit;
 pub mod retry;
 pub mod validation;
@@ -56,6 +60,8 @@ pub mod prelude {
     pub use crate::feature_flags::{FeatureFlags, Flag, FlagValue};
     pub use crate::git::GitRepo;
     pub use crate::health::{HealthChecker, HealthReport, HealthStatus};
+    pub use crate::keychain::{FileKeychain, Keychain, MemoryKeychain};
+    pub use crate::project::{Project, ProjectKind};
     pub use crate::rate_limit::{RateLimitConfig, RateLimiter};
     pub use crate::retry::{retry, CircuitBreaker, RetryConfig};
     pub use crate::validation::{ValidationResult, Validator};
@@ -0,0 +1,244 @@
+//! Project type detection
+//!
+//! Works out which Foodshare platform a directory belongs to from the files
+//! at its root, so a single entry point can pick the right tool. Detection
+//! walks up from the starting directory and stops at the first directory
+//! with a marker, or at the git root.
+//!
+//! | Kind | Markers |
+//! |------|---------|
+//! | iOS | `*.xcodeproj`, `*.xcworkspace`, `Package.swift` |
+//! | Android | `settings.gradle(.kts)`, `build.gradle(.kts)`, `gradlew` |
+//! | Web | `next.config.*`, `package.json` depending on `next` or `react` |
+//! | Backend | `supabase/`, `deno.json(c)` |
+//!
+//! When one directory has markers of several kinds, the first in that order
+//! wins: an app repository with a `supabase/` folder is still the app.
+
+use crate::error::{Error, Result};
+use serde::Serialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Platform of a project
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectKind {
+    /// Xcode / Swift Package Manager project
+    Ios,
+    /// Gradle project
+    Android,
+    /// Next.js / React app
+    Web,
+    /// Supabase migrations and edge functions
+    Backend,
+}
+
+impl ProjectKind {
+    /// All kinds, in detection priority order
+    pub const ALL: [Self; 4] = [Self::Ios, Self::Android, Self::Web, Self::Backend];
+
+    /// Name used on the command line and in config
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Ios => "ios",
+            Self::Android => "android",
+            Self::Web => "web",
+            Self::Backend => "backend",
+        }
+    }
+
+    /// Whether `dir` has a marker file of this kind
+    #[must_use]
+    pub fn matches(self, dir: &Path) -> bool {
+        match self {
+            Self::Ios => {
+                dir.join("Package.swift").is_file()
+                    || has_entry_with_extension(dir, &["xcodeproj", "xcworkspace"])
+            }
+            Self::Android => [
+                "settings.gradle",
+                "settings.gradle.kts",
+                "build.gradle",
+                "build.gradle.kts",
+                "gradlew",
+            ]
+            .iter()
+            .any(|name| dir.join(name).is_file()),
+            Self::Web => {
+                ["next.config.js", "next.config.mjs", "next.config.ts"]
+                    .iter()
+                    .any(|name| dir.join(name).is_file())
+                    || package_uses_react(&dir.join("package.json"))
+            }
+            Self::Backend => {
+                dir.join("supabase").is_dir()
+                    || dir.join("deno.json").is_file()
+                    || dir.join("deno.jsonc").is_file()
+            }
+        }
+    }
+}
+
+impl fmt::Display for ProjectKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ProjectKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "ios" => Ok(Self::Ios),
+            "android" => Ok(Self::Android),
+            "web" => Ok(Self::Web),
+            "backend" => Ok(Self::Backend),
+            other => Err(Error::validation(format!(
+                "Unknown platform: {other} (expected ios, android, web or backend)"
+            ))),
+        }
+    }
+}
+
+/// A detected project
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Project {
+    /// Directory holding the markers
+    pub root: PathBuf,
+    /// Platform
+    pub kind: ProjectKind,
+}
+
+impl Project {
+    /// Detect the project containing `start`
+    ///
+    /// Returns `None` when no directory between `start` and the git root (or
+    /// the filesystem root outside a repository) has a marker.
+    #[must_use]
+    pub fn detect(start: &Path) -> Option<Self> {
+        for dir in start.ancestors() {
+            if let Some(kind) = ProjectKind::ALL.into_iter().find(|kind| kind.matches(dir)) {
+                return Some(Self {
+                    root: dir.to_path_buf(),
+                    kind,
+                });
+            }
+            if dir.join(".git").exists() {
+                break;
+            }
+        }
+        None
+    }
+}
+
+fn has_entry_with_extension(dir: &Path, extensions: &[&str]) -> bool {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return false;
+    };
+    entries.flatten().any(|entry| {
+        entry
+            .path()
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| extensions.contains(&e))
+    })
+}
+
+fn package_uses_react(path: &Path) -> bool {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return false;
+    };
+    let Ok(package) = serde_json::from_str::<serde_json::Value>(&content) else {
+        return false;
+    };
+    ["dependencies", "devDependencies"].iter().any(|section| {
+        package
+            .get(section)
+            .and_then(|deps| deps.as_object())
+            .is_some_and(|deps| deps.contains_key("next") || deps.contains_key("react"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn touch(dir: &Path, name: &str) {
+        let path = dir.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, "").unwrap();
+    }
+
+    #[test]
+    fn test_detects_each_kind() {
+        let cases: [(&str, ProjectKind); 5] = [
+            ("FoodShare.xcodeproj/project.pbxproj", ProjectKind::Ios),
+            ("settings.gradle.kts", ProjectKind::Android),
+            ("next.config.mjs", ProjectKind::Web),
+            ("supabase/config.toml", ProjectKind::Backend),
+            ("deno.json", ProjectKind::Backend),
+        ];
+        for (marker, kind) in cases {
+            let dir = TempDir::new().unwrap();
+            touch(dir.path(), marker);
+            let project = Project::detect(dir.path()).unwrap();
+            assert_eq!(project.kind, kind, "{marker}");
+            assert_eq!(project.root, dir.path());
+        }
+    }
+
+    #[test]
+    fn test_package_json_needs_react() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{"devDependencies": {"eslint": "9"}}"#,
+        )
+        .unwrap();
+        std::fs::create_dir(dir.path().join(".git")).unwrap();
+        assert_eq!(Project::detect(dir.path()), None);
+
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{"dependencies": {"next": "15"}}"#,
+        )
+        .unwrap();
+        assert_eq!(Project::detect(dir.path()).unwrap().kind, ProjectKind::Web);
+    }
+
+    #[test]
+    fn test_app_wins_over_backend() {
+        let dir = TempDir::new().unwrap();
+        touch(dir.path(), "supabase/config.toml");
+        touch(dir.path(), "build.gradle.kts");
+        assert_eq!(
+            Project::detect(dir.path()).unwrap().kind,
+            ProjectKind::Android
+        );
+    }
+
+    #[test]
+    fn test_walks_up_to_git_root() {
+        let dir = TempDir::new().unwrap();
+        touch(dir.path(), "Package.swift");
+        touch(dir.path(), "Sources/App/main.swift");
+        let nested = dir.path().join("Sources/App");
+        assert_eq!(Project::detect(&nested).unwrap().root, dir.path());
+
+        let repo = dir.path().join("Vendor/lib");
+        std::fs::create_dir_all(repo.join(".git")).unwrap();
+        assert_eq!(Project::detect(&repo), None);
+    }
+
+    #[test]
+    fn test_parse_kind() {
+        assert_eq!("iOS".parse::<ProjectKind>().unwrap(), ProjectKind::Ios);
+        assert_eq!(ProjectKind::Backend.to_string(), "backend");
+        assert!("desktop".parse::<ProjectKind>().is_err());
+    }
+}
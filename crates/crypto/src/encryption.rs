@@ -0,0 +1,110 @@
+//! Authenticated encryption for data at rest.
+//!
+//! ChaCha20-Poly1305 with a random 96-bit nonce prepended to each
+//! ciphertext, and PBKDF2-HMAC-SHA256 to turn passphrases into keys.
+
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use sha2::Sha256;
+
+use crate::{CryptoError, Result};
+
+/// Key length in bytes.
+pub const KEY_LEN: usize = 32;
+
+/// Nonce length in bytes, prepended to every ciphertext.
+pub const NONCE_LEN: usize = 12;
+
+/// PBKDF2 rounds for [`derive_key`] (OWASP recommendation for HMAC-SHA256).
+pub const KEY_DERIVATION_ROUNDS: u32 = 600_000;
+
+/// Derive an encryption key from a passphrase.
+///
+/// # Arguments
+/// * `passphrase` - Secret passphrase
+/// * `salt` - Salt; the same passphrase and salt always give the same key
+///
+/// # Returns
+/// 32-byte key for [`encrypt`] and [`decrypt`]
+pub fn derive_key(passphrase: &[u8], salt: &[u8]) -> [u8; KEY_LEN] {
+    let mut key = [0u8; KEY_LEN];
+    pbkdf2::pbkdf2_hmac::<Sha256>(passphrase, salt, KEY_DERIVATION_ROUNDS, &mut key);
+    key
+}
+
+/// Encrypt data with ChaCha20-Poly1305.
+///
+/// # Arguments
+/// * `key` - 32-byte key
+/// * `plaintext` - Data to encrypt
+///
+/// # Returns
+/// Nonce followed by ciphertext and authentication tag
+pub fn encrypt(key: &[u8; KEY_LEN], plaintext: &[u8]) -> Result<Vec<u8>> {
+    let mut nonce = [0u8; NONCE_LEN];
+    getrandom::getrandom(&mut nonce)
+        .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?;
+
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?;
+
+    let mut output = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+    output.extend_from_slice(&nonce);
+    output.extend_from_slice(&ciphertext);
+    Ok(output)
+}
+
+/// Decrypt data produced by [`encrypt`].
+///
+/// # Arguments
+/// * `key` - 32-byte key used to encrypt
+/// * `data` - Nonce followed by ciphertext and authentication tag
+///
+/// # Returns
+/// The plaintext, or [`CryptoError::DecryptionFailed`] if the key is wrong
+/// or the data was modified
+pub fn decrypt(key: &[u8; KEY_LEN], data: &[u8]) -> Result<Vec<u8>> {
+    if data.len() < NONCE_LEN {
+        return Err(CryptoError::DecryptionFailed);
+    }
+    let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+    ChaCha20Poly1305::new(Key::from_slice(key))
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| CryptoError::DecryptionFailed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_roundtrip() {
+        let key = [7u8; KEY_LEN];
+        let sealed = encrypt(&key, b"hello world").unwrap();
+
+        assert_eq!(sealed.len(), NONCE_LEN + 11 + 16);
+        assert_eq!(decrypt(&key, &sealed).unwrap(), b"hello world");
+        // Fresh nonce every time
+        assert_ne!(encrypt(&key, b"hello world").unwrap(), sealed);
+    }
+
+    #[test]
+    fn test_decrypt_rejects_wrong_key_and_tampering() {
+        let key = [7u8; KEY_LEN];
+        let mut sealed = encrypt(&key, b"hello world").unwrap();
+
+        assert!(decrypt(&[8u8; KEY_LEN], &sealed).is_err());
+        sealed[NONCE_LEN] ^= 1;
+        assert!(decrypt(&key, &sealed).is_err());
+        assert!(decrypt(&key, b"short").is_err());
+    }
+
+    #[test]
+    fn test_derive_key() {
+        let key = derive_key(b"passphrase", b"salt");
+        assert_eq!(key, derive_key(b"passphrase", b"salt"));
+        assert_ne!(key, derive_key(b"passphrase", b"other salt"));
+    }
+}
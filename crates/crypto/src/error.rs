@@ -23,4 +23,12 @@ pub enum CryptoError {
     /// Encoding error
     #[error("Encoding error: {0}")]
     EncodingError(String),
+
+    /// Encryption failed
+    #[error("Encryption failed: {0}")]
+    EncryptionFailed(String),
+
+    /// Decryption failed (wrong key or corrupted data)
+    #[error("Decryption failed")]
+    DecryptionFailed,
 }
@@ -3,17 +3,25 @@
 //! This crate provides:
 //! - HMAC signature generation and verification
 //! - Constant-time comparison for security
+//! - Authenticated encryption (ChaCha20-Poly1305) with passphrase-derived keys
+//! - Ed25519 signature verification for signed downloads
 //! - Provider-specific webhook verification (Meta, Stripe, GitHub)
 
 #![warn(missing_docs)]
 
+mod encryption;
 mod hmac_impl;
+#[cfg(not(target_arch = "wasm32"))]
+mod signing;
 mod timing;
 mod error;
 
 #[cfg(feature = "wasm")]
 mod wasm;
 
+pub use encryption::{decrypt, derive_key, encrypt, KEY_LEN};
 pub use hmac_impl::{hmac_sha256, hmac_sha1, verify_signature};
+#[cfg(not(target_arch = "wasm32"))]
+pub use signing::{verify_ed25519, ED25519_PUBLIC_KEY_LEN, ED25519_SIGNATURE_LEN};
 pub use timing::constant_time_compare;
 pub use error::{CryptoError, Result};
@@ -0,0 +1,81 @@
+//! Ed25519 signature verification for signed downloads.
+
+use ring::signature::{ED25519, UnparsedPublicKey};
+
+use crate::{CryptoError, Result};
+
+/// Length of an Ed25519 public key in bytes.
+pub const ED25519_PUBLIC_KEY_LEN: usize = 32;
+
+/// Length of an Ed25519 signature in bytes.
+pub const ED25519_SIGNATURE_LEN: usize = 64;
+
+/// Verify an Ed25519 signature.
+///
+/// # Arguments
+/// * `public_key` - Raw 32-byte public key
+/// * `message` - The signed bytes
+/// * `signature` - Raw 64-byte signature
+///
+/// # Returns
+/// Ok(()) if the signature is valid for the message, Err otherwise
+pub fn verify_ed25519(public_key: &[u8], message: &[u8], signature: &[u8]) -> Result<()> {
+    if public_key.len() != ED25519_PUBLIC_KEY_LEN {
+        return Err(CryptoError::InvalidKey(format!(
+            "expected {} bytes, got {}",
+            ED25519_PUBLIC_KEY_LEN,
+            public_key.len()
+        )));
+    }
+    if signature.len() != ED25519_SIGNATURE_LEN {
+        return Err(CryptoError::InvalidSignature(format!(
+            "expected {} bytes, got {}",
+            ED25519_SIGNATURE_LEN,
+            signature.len()
+        )));
+    }
+
+    UnparsedPublicKey::new(&ED25519, public_key)
+        .verify(message, signature)
+        .map_err(|_| CryptoError::SignatureMismatch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::rand::SystemRandom;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    fn key_pair() -> Ed25519KeyPair {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap()
+    }
+
+    #[test]
+    fn test_verify_ed25519() {
+        let pair = key_pair();
+        let signature = pair.sign(b"checksums");
+        let public_key = pair.public_key().as_ref();
+
+        assert!(verify_ed25519(public_key, b"checksums", signature.as_ref()).is_ok());
+        assert!(matches!(
+            verify_ed25519(public_key, b"tampered", signature.as_ref()),
+            Err(CryptoError::SignatureMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_verify_ed25519_bad_lengths() {
+        let pair = key_pair();
+        let signature = pair.sign(b"checksums");
+
+        assert!(matches!(
+            verify_ed25519(&[0; 16], b"checksums", signature.as_ref()),
+            Err(CryptoError::InvalidKey(_))
+        ));
+        assert!(matches!(
+            verify_ed25519(pair.public_key().as_ref(), b"checksums", &[0; 10]),
+            Err(CryptoError::InvalidSignature(_))
+        ));
+    }
+}
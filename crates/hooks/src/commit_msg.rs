@@ -3,6 +3,7 @@
 //! Validates commit messages against the conventional commits specification.
 //! https://www.conventionalcommits.org
 
+use foodshare_cli::output::{self, Finding, Level};
 use foodshare_core::config::CommitMsgConfig;
 use foodshare_core::error::exit_codes;
 use owo_colors::OwoColorize;
@@ -90,30 +91,23 @@ pub fn validate_commit_message(
     // Check subject length (warning only)
     if subject.len() > config.max_length {
         // This is a warning, not an error
-        eprintln!(
-            "{}: Subject line is long ({} chars, recommended max {})",
-            "warning".yellow(),
+        warn(&format!(
+            "Subject line is long ({} chars, recommended max {})",
             subject.len(),
             config.max_length
-        );
+        ));
     }
 
     // Check for capitalization (warning only)
     if let Some(first_char) = description.chars().next() {
         if first_char.is_uppercase() {
-            eprintln!(
-                "{}: Description should start with lowercase letter",
-                "warning".yellow()
-            );
+            warn("Description should start with lowercase letter");
         }
     }
 
     // Check for trailing period (warning only)
     if description.ends_with('.') {
-        eprintln!(
-            "{}: Description should not end with a period",
-            "warning".yellow()
-        );
+        warn("Description should not end with a period");
     }
 
     Ok(ValidationResult {
@@ -123,8 +117,26 @@ pub fn validate_commit_message(
     })
 }
 
+/// Print a style warning that doesn't fail validation
+fn warn(message: &str) {
+    if output::is_machine() {
+        output::finding(Finding::new(Level::Warning, message).with_rule("commit-style"));
+    } else {
+        eprintln!("{}: {}", "warning".yellow(), message);
+    }
+}
+
 /// Print error message with formatting
 pub fn print_error(subject: &str, types: &[String]) {
+    if output::is_machine() {
+        output::finding(
+            Finding::new(Level::Error, format!("Invalid commit message format: {}", subject.trim()))
+                .with_rule("conventional-commit"),
+        );
+        output::data("types", &types);
+        return;
+    }
+
     eprintln!("{}", "Invalid commit message format".red().bold());
     eprintln!();
     eprintln!("  Received: {}", subject.red());
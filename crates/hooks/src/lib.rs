@@ -5,6 +5,7 @@
 //! - Secret scanning (enterprise-grade)
 //! - Migration checks
 //! - Pre-push validation
+//! - Check plugins (external executables and WASM modules)
 //!
 //! # Secret Scanning
 //!
@@ -14,6 +15,7 @@
 //! - Allowlisting and fingerprint suppression
 //! - Parallel file scanning
 //! - Entropy-based detection
+//! - Live verification with the providers (`verify` feature)
 //!
 //! See [`secrets`] module for full documentation.
 
@@ -21,8 +23,11 @@
 
 pub mod commit_msg;
 pub mod migrations;
+pub mod plugins;
 pub mod pre_push;
 pub mod secrets;
+#[cfg(feature = "verify")]
+pub mod verify;
 
 pub use foodshare_core::error::{exit_codes, Result};
 
@@ -38,6 +43,7 @@ pub use secrets::{
     ScanResult,
     SecretScanner,
     Severity,
+    Verification,
     // Constants
     CONFIG_API_VERSION,
     PATTERN_VERSION,
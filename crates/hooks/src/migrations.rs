@@ -1,13 +1,34 @@
 //! Supabase migrations status checking
 //!
-//! Validates that migrations are properly committed and staged.
+//! Validates that migrations are properly committed and staged, and asks
+//! before letting through new migrations that drop or delete data.
 
+use foodshare_cli::output::{self, Finding, Level};
+use foodshare_cli::prompt;
 use foodshare_core::error::exit_codes;
 use foodshare_core::git::GitRepo;
+use once_cell::sync::Lazy;
 use owo_colors::OwoColorize;
+use regex::Regex;
 use std::path::Path;
 use walkdir::WalkDir;
 
+/// Statements that lose data, by rule
+static DESTRUCTIVE_PATTERNS: Lazy<Vec<(&'static str, Regex)>> = Lazy::new(|| {
+    [
+        ("drop-table", r"(?i)^DROP\s+TABLE\b"),
+        ("drop-schema", r"(?i)^DROP\s+SCHEMA\b"),
+        ("drop-column", r"(?i)^ALTER\s+TABLE\b.*\bDROP\s+COLUMN\b"),
+        ("truncate", r"(?i)^TRUNCATE\b"),
+    ]
+    .into_iter()
+    .map(|(rule, pattern)| (rule, Regex::new(pattern).unwrap()))
+    .collect()
+});
+
+static DELETE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)^DELETE\s+FROM\b").unwrap());
+static WHERE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)\bWHERE\b").unwrap());
+
 /// Migration file info
 #[derive(Debug)]
 pub struct MigrationFile {
@@ -16,11 +37,33 @@ pub struct MigrationFile {
     pub timestamp: String,
 }
 
+/// A statement that drops or deletes data
+#[derive(Debug)]
+pub struct DestructiveStatement {
+    /// What it does, e.g. `drop-table`
+    pub rule: &'static str,
+    /// Line the statement starts on
+    pub line: usize,
+    /// The statement with its whitespace collapsed
+    pub statement: String,
+}
+
+/// An uncommitted or staged migration with destructive statements
+#[derive(Debug)]
+pub struct DestructiveMigration {
+    /// The migration
+    pub migration: MigrationFile,
+    /// Its destructive statements
+    pub statements: Vec<DestructiveStatement>,
+}
+
 /// Check migrations status
 pub struct MigrationsCheck {
     pub migrations_dir: String,
     pub uncommitted: Vec<MigrationFile>,
     pub staged: Vec<MigrationFile>,
+    /// Uncommitted or staged migrations that drop or delete data
+    pub destructive: Vec<DestructiveMigration>,
     pub total: usize,
 }
 
@@ -34,6 +77,7 @@ pub fn check_migrations(
         migrations_dir: migrations_dir.to_string_lossy().to_string(),
         uncommitted: Vec::new(),
         staged: Vec::new(),
+        destructive: Vec::new(),
         total: 0,
     };
 
@@ -82,9 +126,87 @@ pub fn check_migrations(
         }
     }
 
+    let mut pending: Vec<String> = result
+        .uncommitted
+        .iter()
+        .chain(&result.staged)
+        .map(|m| m.path.clone())
+        .collect();
+    pending.sort();
+    pending.dedup();
+    for path in pending {
+        let path = Path::new(&path);
+        let Ok(content) = std::fs::read_to_string(path) else {
+            continue;
+        };
+        let statements = destructive_statements(&content);
+        if let Some(migration) = parse_migration_file(path).filter(|_| !statements.is_empty()) {
+            result.destructive.push(DestructiveMigration { migration, statements });
+        }
+    }
+
     Ok(result)
 }
 
+/// Statements in a migration that drop or delete data
+fn destructive_statements(sql: &str) -> Vec<DestructiveStatement> {
+    fn classify(statement: &str) -> Option<&'static str> {
+        if let Some((rule, _)) = DESTRUCTIVE_PATTERNS.iter().find(|(_, re)| re.is_match(statement)) {
+            return Some(rule);
+        }
+        (DELETE.is_match(statement) && !WHERE.is_match(statement)).then_some("delete-without-where")
+    }
+
+    let mut found = Vec::new();
+    let mut statement = String::new();
+    let mut start = 0;
+    let mut finish = |statement: &mut String, start: usize| {
+        if let Some(rule) = classify(statement) {
+            found.push(DestructiveStatement {
+                rule,
+                line: start,
+                statement: statement.clone(),
+            });
+        }
+        statement.clear();
+    };
+
+    for (index, line) in sql.lines().enumerate() {
+        let code = line.split("--").next().unwrap_or("");
+        for (i, part) in code.split(';').enumerate() {
+            if i > 0 {
+                finish(&mut statement, start);
+            }
+            for word in part.split_whitespace() {
+                if statement.is_empty() {
+                    start = index + 1;
+                } else {
+                    statement.push(' ');
+                }
+                statement.push_str(word);
+            }
+        }
+    }
+    finish(&mut statement, start);
+    found
+}
+
+/// Ask whether to keep each migration that drops or deletes data
+///
+/// Returns whether all were approved; `--yes` approves them.
+pub fn approve_destructive(check: &MigrationsCheck) -> foodshare_core::Result<bool> {
+    for destructive in &check.destructive {
+        let question = format!(
+            "Migration {} drops or deletes data. Keep it?",
+            destructive.migration.name
+        );
+        if !prompt::confirm(&question, false)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
 /// Parse migration file name to extract info
 fn parse_migration_file(path: &Path) -> Option<MigrationFile> {
     let file_name = path.file_name()?.to_string_lossy().to_string();
@@ -107,6 +229,32 @@ fn parse_migration_file(path: &Path) -> Option<MigrationFile> {
 
 /// Print migrations check results
 pub fn print_results(check: &MigrationsCheck) -> i32 {
+    if output::is_machine() {
+        output::data("migrations_dir", &check.migrations_dir);
+        output::data("total", &check.total);
+        output::data("staged", &check.staged.iter().map(|m| &m.name).collect::<Vec<_>>());
+        for m in &check.uncommitted {
+            output::finding(
+                Finding::new(Level::Error, format!("Migration {} is not committed", m.name))
+                    .with_rule("uncommitted-migration")
+                    .with_location(&m.path, None),
+            );
+        }
+        for destructive in &check.destructive {
+            for statement in &destructive.statements {
+                output::finding(
+                    Finding::new(
+                        Level::Warning,
+                        format!("Migration {} runs: {}", destructive.migration.name, statement.statement),
+                    )
+                    .with_rule(statement.rule)
+                    .with_location(&destructive.migration.path, Some(statement.line)),
+                );
+            }
+        }
+        return if check.uncommitted.is_empty() { exit_codes::SUCCESS } else { exit_codes::FAILURE };
+    }
+
     println!(
         "{} Found {} migration(s) in {}",
         "ℹ".blue(),
@@ -141,6 +289,26 @@ pub fn print_results(check: &MigrationsCheck) -> i32 {
         }
     }
 
+    if !check.destructive.is_empty() {
+        eprintln!();
+        eprintln!(
+            "{} {} migration(s) drop or delete data:",
+            "⚠".yellow(),
+            check.destructive.len()
+        );
+        for destructive in &check.destructive {
+            eprintln!("  - {}", destructive.migration.name.yellow());
+            for statement in &destructive.statements {
+                eprintln!(
+                    "      line {}: {} {}",
+                    statement.line,
+                    statement.statement,
+                    format!("[{}]", statement.rule).dimmed()
+                );
+            }
+        }
+    }
+
     if has_issues {
         exit_codes::FAILURE
     } else {
@@ -169,4 +337,27 @@ mod tests {
 
         assert_eq!(migration.timestamp, "20240101120000.sql");
     }
+
+    #[test]
+    fn test_destructive_statements() {
+        let sql = "-- DROP TABLE old_listings;\n\
+                   create table listings (id uuid);\n\
+                   alter table profiles\n  drop column legacy_avatar;\n\
+                   delete from messages where created_at < now();\n\
+                   DELETE FROM notifications;\n\
+                   truncate audit_log; drop table if exists tmp_import;\n";
+        let found = destructive_statements(sql);
+        let rules: Vec<_> = found.iter().map(|s| (s.rule, s.line)).collect();
+
+        assert_eq!(
+            rules,
+            vec![
+                ("drop-column", 3),
+                ("delete-without-where", 6),
+                ("truncate", 7),
+                ("drop-table", 7),
+            ]
+        );
+        assert_eq!(found[0].statement, "alter table profiles drop column legacy_avatar");
+    }
 }
@@ -0,0 +1,879 @@
+//! Check plugins - repository-specific checks run in the git hooks
+//!
+//! A plugin is an executable, or a WASM module run by a WASI runtime
+//! (`wasmtime` by default), that answers one JSON request on stdin with one
+//! JSON response on stdout. Plugins are found in the `plugins/` directory of
+//! the repository and in `[[plugins.checks]]` of the config, so a team can
+//! add checks of its own without changing this crate.
+//!
+//! # Protocol (version 1)
+//!
+//! Plugins found in the directory are first asked to describe themselves:
+//!
+//! ```json
+//! {"method": "describe", "protocol": 1}
+//! ```
+//!
+//! ```json
+//! {"name": "env-files", "description": "No .env files in commits",
+//!  "hooks": ["pre-commit"], "patterns": [".env*"], "required": true}
+//! ```
+//!
+//! Every field of the answer is optional: the name defaults to the file
+//! name, the hooks to `pre-commit`. Plugins declared in config are never
+//! asked; the config says the same things.
+//!
+//! A check then gets the hook, the repository root and the files (staged
+//! files for `pre-commit`, none for `pre-push`):
+//!
+//! ```json
+//! {"method": "check", "protocol": 1, "hook": "pre-commit",
+//!  "root": "/src/app", "files": ["config/.env.local"]}
+//! ```
+//!
+//! ```json
+//! {"success": false, "findings": [{"level": "error", "message": "Don't commit env files",
+//!   "file": "config/.env.local", "line": 1, "rule": "env-file"}]}
+//! ```
+//!
+//! Without `success`, the check passes when the plugin exits 0 and reports
+//! no error findings. In `pre-commit`, a plugin with `patterns` is skipped
+//! when none of the staged files match.
+
+use foodshare_cli::output::{self, Finding, Level};
+use foodshare_cli::textln;
+use foodshare_core::config::{PluginCheck, PluginsConfig};
+use foodshare_core::error::{Error, Result, exit_codes};
+use owo_colors::OwoColorize;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Version of the JSON protocol spoken with plugins
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Git hook a plugin runs in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Hook {
+    /// Before a commit, on the staged files
+    PreCommit,
+    /// Before a push
+    PrePush,
+}
+
+impl Hook {
+    /// Name used in config and on the command line
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::PreCommit => "pre-commit",
+            Self::PrePush => "pre-push",
+        }
+    }
+}
+
+impl std::fmt::Display for Hook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Hook {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "pre-commit" => Ok(Self::PreCommit),
+            "pre-push" => Ok(Self::PrePush),
+            _ => Err(format!("Unknown hook: {} (use pre-commit or pre-push)", s)),
+        }
+    }
+}
+
+/// Where a plugin was registered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    /// Found in the plugins directory
+    Dir,
+    /// Declared in `[[plugins.checks]]`
+    Config,
+}
+
+/// A registered plugin
+#[derive(Debug, Clone, Serialize)]
+pub struct Plugin {
+    /// Name shown in results
+    pub name: String,
+    /// One-line description
+    pub description: String,
+    /// Program and arguments, with the WASM runtime in front for modules
+    pub command: Vec<String>,
+    /// Hooks the plugin runs in
+    pub hooks: Vec<Hook>,
+    /// File name globs the plugin cares about (empty = any file)
+    pub patterns: Vec<String>,
+    /// Whether a failure fails the hook
+    pub required: bool,
+    /// Time the plugin gets to answer
+    #[serde(skip)]
+    pub timeout: Duration,
+    /// Where the plugin was registered
+    pub source: Source,
+}
+
+/// Request sent to a plugin
+#[derive(Serialize)]
+#[serde(tag = "method", rename_all = "lowercase")]
+enum Request<'a> {
+    Describe {
+        protocol: u32,
+    },
+    Check {
+        protocol: u32,
+        hook: Hook,
+        root: &'a Path,
+        files: &'a [PathBuf],
+    },
+}
+
+/// Answer to `describe`
+#[derive(Deserialize)]
+struct Description {
+    name: Option<String>,
+    #[serde(default)]
+    description: String,
+    hooks: Option<Vec<Hook>>,
+    #[serde(default)]
+    patterns: Vec<String>,
+    required: Option<bool>,
+}
+
+/// Answer to `check`
+#[derive(Deserialize)]
+struct CheckResponse {
+    success: Option<bool>,
+    #[serde(default)]
+    findings: Vec<PluginFinding>,
+}
+
+/// Level of a plugin finding
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PluginLevel {
+    /// Fails the check
+    #[default]
+    Error,
+    /// Shown, but doesn't fail the check
+    Warning,
+    /// Informational
+    Info,
+}
+
+impl From<PluginLevel> for Level {
+    fn from(level: PluginLevel) -> Self {
+        match level {
+            PluginLevel::Error => Level::Error,
+            PluginLevel::Warning => Level::Warning,
+            PluginLevel::Info => Level::Info,
+        }
+    }
+}
+
+/// A problem reported by a plugin
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PluginFinding {
+    /// How serious it is
+    #[serde(default)]
+    pub level: PluginLevel,
+    /// What's wrong
+    pub message: String,
+    /// File it's in
+    #[serde(default)]
+    pub file: Option<String>,
+    /// 1-based line
+    #[serde(default)]
+    pub line: Option<usize>,
+    /// Rule that found it (defaults to the plugin name)
+    #[serde(default)]
+    pub rule: Option<String>,
+}
+
+/// Outcome of one plugin
+#[derive(Debug, Clone)]
+pub struct PluginResult {
+    /// Plugin name
+    pub name: String,
+    /// Whether the check passed
+    pub success: bool,
+    /// Whether a failure fails the hook
+    pub required: bool,
+    /// No files matched its patterns
+    pub skipped: bool,
+    /// Time the plugin took
+    pub duration: Duration,
+    /// Problems it reported
+    pub findings: Vec<PluginFinding>,
+    /// Output of a plugin that failed without answering
+    pub output: Option<String>,
+}
+
+impl Plugin {
+    fn from_config(check: &PluginCheck, root: &Path, config: &PluginsConfig) -> Result<Self> {
+        let Some((program, args)) = check.command.split_first() else {
+            return Err(Error::config(format!(
+                "Plugin {} has an empty command",
+                check.name
+            )));
+        };
+        // Paths in config are relative to the repository, not the caller
+        let program = if program.contains('/') && root.join(program).exists() {
+            root.join(program).to_string_lossy().to_string()
+        } else {
+            program.clone()
+        };
+        let hooks = check
+            .hooks
+            .iter()
+            .map(|hook| hook.parse::<Hook>())
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| Error::config(format!("Plugin {}: {}", check.name, e)))?;
+
+        Ok(Self {
+            name: check.name.clone(),
+            description: check.description.clone(),
+            command: with_runtime(program, args, config),
+            hooks,
+            patterns: check.patterns.clone(),
+            required: check.required,
+            timeout: Duration::from_secs(check.timeout_secs.unwrap_or(config.timeout_secs)),
+            source: Source::Config,
+        })
+    }
+
+    fn from_dir(path: &Path, root: &Path, config: &PluginsConfig) -> Result<Self> {
+        let file_name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let mut plugin = Self {
+            name: file_name,
+            description: String::new(),
+            command: with_runtime(path.to_string_lossy().to_string(), &[], config),
+            hooks: vec![Hook::PreCommit],
+            patterns: Vec::new(),
+            required: true,
+            timeout: Duration::from_secs(config.timeout_secs),
+            source: Source::Dir,
+        };
+
+        let answer = plugin.call(
+            &Request::Describe {
+                protocol: PROTOCOL_VERSION,
+            },
+            root,
+        )?;
+        let description: Description = serde_json::from_str(&answer.stdout).map_err(|e| {
+            Error::config(format!(
+                "Plugin {} answered describe with invalid JSON: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        if let Some(name) = description.name {
+            plugin.name = name;
+        }
+        if let Some(hooks) = description.hooks {
+            plugin.hooks = hooks;
+        }
+        if let Some(required) = description.required {
+            plugin.required = required;
+        }
+        plugin.description = description.description;
+        plugin.patterns = description.patterns;
+        Ok(plugin)
+    }
+
+    /// Whether the plugin runs in `hook`
+    #[must_use]
+    pub fn runs_in(&self, hook: Hook) -> bool {
+        self.hooks.contains(&hook)
+    }
+
+    /// Files matching the plugin's patterns, by path or file name
+    fn matching_files(&self, files: &[PathBuf]) -> Vec<PathBuf> {
+        let patterns: Vec<glob::Pattern> = self
+            .patterns
+            .iter()
+            .filter_map(|p| glob::Pattern::new(p).ok())
+            .collect();
+        if patterns.is_empty() {
+            return files.to_vec();
+        }
+        files
+            .iter()
+            .filter(|file| {
+                let name = file
+                    .file_name()
+                    .map(|n| n.to_string_lossy())
+                    .unwrap_or_default();
+                patterns
+                    .iter()
+                    .any(|p| p.matches_path(file) || p.matches(&name))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Run the check for `hook` on `files`
+    #[must_use]
+    pub fn check(&self, hook: Hook, root: &Path, files: &[PathBuf]) -> PluginResult {
+        let mut result = PluginResult {
+            name: self.name.clone(),
+            success: true,
+            required: self.required,
+            skipped: false,
+            duration: Duration::ZERO,
+            findings: Vec::new(),
+            output: None,
+        };
+
+        let files = self.matching_files(files);
+        if files.is_empty() && !self.patterns.is_empty() && hook == Hook::PreCommit {
+            result.skipped = true;
+            return result;
+        }
+
+        let start = Instant::now();
+        let request = Request::Check {
+            protocol: PROTOCOL_VERSION,
+            hook,
+            root,
+            files: &files,
+        };
+        let answer = self.call(&request, root);
+        result.duration = start.elapsed();
+
+        let answer = match answer {
+            Ok(answer) => answer,
+            Err(e) => {
+                result.success = false;
+                result.output = Some(e.to_string());
+                return result;
+            }
+        };
+        match serde_json::from_str::<CheckResponse>(&answer.stdout) {
+            Ok(response) => {
+                let has_errors = response
+                    .findings
+                    .iter()
+                    .any(|f| f.level == PluginLevel::Error);
+                result.success = answer.success && response.success.unwrap_or(!has_errors);
+                result.findings = response.findings;
+                if !result.success && result.findings.is_empty() && !answer.stderr.is_empty() {
+                    result.output = Some(answer.stderr);
+                }
+            }
+            Err(_) => {
+                result.success = false;
+                let output = [answer.stdout.trim(), answer.stderr.trim()]
+                    .into_iter()
+                    .filter(|s| !s.is_empty())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                result.output = Some(if output.is_empty() {
+                    "Plugin gave no answer".to_string()
+                } else {
+                    format!("Plugin gave no valid answer:\n{}", output)
+                });
+            }
+        }
+        result
+    }
+
+    /// Send `request` and wait for the plugin to exit, up to its timeout
+    fn call(&self, request: &Request<'_>, root: &Path) -> Result<Answer> {
+        let (program, args) = self
+            .command
+            .split_first()
+            .ok_or_else(|| Error::config(format!("Plugin {} has an empty command", self.name)))?;
+        let mut child = Command::new(program)
+            .args(args)
+            .current_dir(root)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| Error::process(format!("Failed to run plugin {}: {}", self.name, e)))?;
+
+        let input = serde_json::to_vec(request)?;
+        let mut stdin = child.stdin.take();
+        let writer = std::thread::spawn(move || {
+            if let Some(stdin) = stdin.as_mut() {
+                // A plugin may exit without reading its input
+                let _ = stdin.write_all(&input);
+            }
+        });
+        let stdout = read_in_background(child.stdout.take());
+        let stderr = read_in_background(child.stderr.take());
+
+        let start = Instant::now();
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if start.elapsed() >= self.timeout {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(Error::process(format!(
+                    "Plugin {} timed out after {}s",
+                    self.name,
+                    self.timeout.as_secs()
+                )));
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        };
+        let _ = writer.join();
+
+        Ok(Answer {
+            success: status.success(),
+            stdout: stdout.join().unwrap_or_default(),
+            stderr: stderr.join().unwrap_or_default(),
+        })
+    }
+}
+
+/// What a plugin printed before exiting
+struct Answer {
+    success: bool,
+    stdout: String,
+    stderr: String,
+}
+
+fn read_in_background<R: Read + Send + 'static>(
+    pipe: Option<R>,
+) -> std::thread::JoinHandle<String> {
+    std::thread::spawn(move || {
+        let mut buffer = String::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_string(&mut buffer);
+        }
+        buffer
+    })
+}
+
+/// `program` and `args`, behind the WASM runtime when `program` is a module
+fn with_runtime(program: String, args: &[String], config: &PluginsConfig) -> Vec<String> {
+    let mut command = Vec::new();
+    if Path::new(&program).extension().is_some_and(|e| e == "wasm") {
+        command.extend(config.wasm_runtime.iter().cloned());
+    }
+    command.push(program);
+    command.extend(args.iter().cloned());
+    command
+}
+
+/// Whether a file in the plugins directory is a plugin
+fn is_plugin_file(path: &Path) -> bool {
+    let Ok(metadata) = path.metadata() else {
+        return false;
+    };
+    let hidden = path
+        .file_name()
+        .is_some_and(|n| n.to_string_lossy().starts_with('.'));
+    if !metadata.is_file() || hidden {
+        return false;
+    }
+    if path.extension().is_some_and(|e| e == "wasm") {
+        return true;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        metadata.permissions().mode() & 0o111 != 0
+    }
+    #[cfg(not(unix))]
+    {
+        path.extension()
+            .is_some_and(|e| e == "exe" || e == "bat" || e == "cmd")
+    }
+}
+
+/// Plugins of the repository at `root`: those declared in config, then those
+/// in the plugins directory, without the disabled ones
+///
+/// A directory plugin with the name of a config one is left out, so config
+/// can override how it runs. A plugin that can't describe itself is an
+/// error rather than a check that silently never runs.
+pub fn discover(root: &Path, config: &PluginsConfig) -> Result<Vec<Plugin>> {
+    if !config.enabled {
+        return Ok(Vec::new());
+    }
+
+    let mut plugins = Vec::new();
+    for check in &config.checks {
+        plugins.push(Plugin::from_config(check, root, config)?);
+    }
+
+    let dir = root.join(&config.dir);
+    if dir.is_dir() {
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(&dir)?
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| is_plugin_file(p))
+            .collect();
+        paths.sort();
+        for path in paths {
+            let plugin = Plugin::from_dir(&path, root, config)?;
+            if !plugins.iter().any(|p| p.name == plugin.name) {
+                plugins.push(plugin);
+            }
+        }
+    }
+
+    plugins.retain(|p| !config.disabled.contains(&p.name));
+    Ok(plugins)
+}
+
+/// Plugins of the repository containing the current directory, with its root
+pub fn discover_current(config: &PluginsConfig) -> Result<(PathBuf, Vec<Plugin>)> {
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let root = foodshare_core::git::git_root(&cwd).unwrap_or(cwd);
+    let plugins = discover(&root, config)?;
+    Ok((root, plugins))
+}
+
+/// Run the plugins of the current repository registered for `hook` and
+/// print their results, returning the exit code
+///
+/// Without `files`, pre-commit plugins get the staged files. A plugin that
+/// can't be discovered fails the hook.
+pub fn run_for_hook(hook: Hook, files: &[PathBuf], config: &PluginsConfig) -> i32 {
+    let (root, plugins) = match discover_current(config) {
+        Ok(found) => found,
+        Err(e) => {
+            output::Status::error(&format!("Plugin error: {}", e));
+            return exit_codes::FAILURE;
+        }
+    };
+    let files = if files.is_empty() && hook == Hook::PreCommit {
+        foodshare_core::git::GitRepo::open_current()
+            .and_then(|r| r.staged_files())
+            .unwrap_or_default()
+    } else {
+        files.to_vec()
+    };
+    print_results(&run(&plugins, hook, &root, &files))
+}
+
+/// Print the plugins of the current repository, returning the exit code
+pub fn list(config: &PluginsConfig) -> i32 {
+    match discover_current(config) {
+        Ok((_, plugins)) => {
+            print_list(&plugins);
+            exit_codes::SUCCESS
+        }
+        Err(e) => {
+            output::Status::error(&format!("Plugin error: {}", e));
+            exit_codes::FAILURE
+        }
+    }
+}
+
+/// Run the plugins registered for `hook` at the same time
+pub fn run(plugins: &[Plugin], hook: Hook, root: &Path, files: &[PathBuf]) -> Vec<PluginResult> {
+    if plugins.iter().any(|p| p.runs_in(hook)) {
+        textln!();
+        textln!("{}", format!("Running {} plugins...", hook).bold());
+    }
+    plugins
+        .par_iter()
+        .filter(|p| p.runs_in(hook))
+        .map(|p| p.check(hook, root, files))
+        .collect()
+}
+
+/// Print plugin results, returning the exit code
+///
+/// Only a failed required plugin fails; an optional one is a warning.
+pub fn print_results(results: &[PluginResult]) -> i32 {
+    let failed = results.iter().any(|r| !r.success && r.required);
+    let exit_code = if failed {
+        exit_codes::FAILURE
+    } else {
+        exit_codes::SUCCESS
+    };
+
+    if output::is_machine() {
+        for result in results {
+            for f in &result.findings {
+                let mut finding = Finding::new(f.level.into(), &f.message)
+                    .with_rule(f.rule.as_deref().unwrap_or(&result.name));
+                if let Some(file) = &f.file {
+                    finding = finding.with_location(file, f.line);
+                }
+                output::finding(finding);
+            }
+            if let Some(message) = result.output.as_deref().filter(|_| !result.success) {
+                let level = if result.required {
+                    Level::Error
+                } else {
+                    Level::Warning
+                };
+                output::finding(Finding::new(level, message).with_rule(&result.name));
+            }
+        }
+        let plugins: Vec<_> = results
+            .iter()
+            .map(|r| {
+                serde_json::json!({
+                    "name": r.name,
+                    "success": r.success,
+                    "required": r.required,
+                    "skipped": r.skipped,
+                    "duration_ms": r.duration.as_millis() as u64,
+                })
+            })
+            .collect();
+        output::data("plugins", &plugins);
+        return exit_code;
+    }
+
+    for result in results {
+        let time = format!("({:.1}s)", result.duration.as_secs_f32());
+        if result.skipped {
+            textln!(
+                "  {} {} {}",
+                "⊘".dimmed(),
+                result.name.dimmed(),
+                "(no matching files)".dimmed()
+            );
+        } else if result.success {
+            textln!("  {} {} {}", "✓".green(), result.name, time.dimmed());
+        } else if result.required {
+            eprintln!("  {} {} {}", "✗".red(), result.name.red(), time.dimmed());
+        } else {
+            eprintln!(
+                "  {} {} {} {}",
+                "⚠".yellow(),
+                result.name.yellow(),
+                time.dimmed(),
+                "(optional)".dimmed()
+            );
+        }
+
+        for f in &result.findings {
+            let location = match (&f.file, f.line) {
+                (Some(file), Some(line)) => format!("{}:{} ", file, line),
+                (Some(file), None) => format!("{} ", file),
+                _ => String::new(),
+            };
+            let level = match f.level {
+                PluginLevel::Error => "error".red().to_string(),
+                PluginLevel::Warning => "warning".yellow().to_string(),
+                PluginLevel::Info => "info".cyan().to_string(),
+            };
+            eprintln!("    {}{}: {}", location.dimmed(), level, f.message);
+        }
+        if let Some(output) = result.output.as_deref().filter(|_| !result.success) {
+            for line in output.lines().take(10) {
+                eprintln!("    {}", line.dimmed());
+            }
+        }
+    }
+    exit_code
+}
+
+/// Print the registered plugins
+pub fn print_list(plugins: &[Plugin]) {
+    if output::is_machine() {
+        output::data("plugins", &plugins);
+        return;
+    }
+    if plugins.is_empty() {
+        textln!("No plugins registered");
+        return;
+    }
+    for plugin in plugins {
+        let hooks: Vec<&str> = plugin.hooks.iter().map(|h| h.as_str()).collect();
+        let source = match plugin.source {
+            Source::Dir => "dir",
+            Source::Config => "config",
+        };
+        textln!(
+            "{} {} {}",
+            plugin.name.bold(),
+            format!("[{}]", hooks.join(", ")).cyan(),
+            format!(
+                "({}{})",
+                source,
+                if plugin.required { "" } else { ", optional" }
+            )
+            .dimmed()
+        );
+        if !plugin.description.is_empty() {
+            textln!("  {}", plugin.description);
+        }
+        if !plugin.patterns.is_empty() {
+            textln!("  {} {}", "Files:".dimmed(), plugin.patterns.join(", "));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[cfg(unix)]
+    fn write_plugin(dir: &Path, name: &str, script: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.join(name);
+        std::fs::create_dir_all(dir).unwrap();
+        std::fs::write(&path, format!("#!/bin/sh\n{}", script)).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    fn config_check(name: &str, command: &[&str]) -> PluginCheck {
+        PluginCheck {
+            name: name.to_string(),
+            command: command.iter().map(ToString::to_string).collect(),
+            description: String::new(),
+            hooks: vec!["pre-commit".to_string()],
+            patterns: Vec::new(),
+            required: true,
+            timeout_secs: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_hook() {
+        assert_eq!("pre-push".parse::<Hook>().unwrap(), Hook::PrePush);
+        assert_eq!(Hook::PreCommit.to_string(), "pre-commit");
+        assert!("post-merge".parse::<Hook>().is_err());
+    }
+
+    #[test]
+    fn test_wasm_modules_run_with_runtime() {
+        let config = PluginsConfig::default();
+        assert_eq!(
+            with_runtime(
+                "plugins/check.wasm".to_string(),
+                &["--strict".to_string()],
+                &config
+            ),
+            [
+                "wasmtime",
+                "run",
+                "--dir=.",
+                "plugins/check.wasm",
+                "--strict"
+            ]
+        );
+        assert_eq!(
+            with_runtime("check.sh".to_string(), &[], &config),
+            ["check.sh"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_discover_and_run_dir_plugin() {
+        let root = TempDir::new().unwrap();
+        write_plugin(
+            &root.path().join("plugins"),
+            "env-files",
+            r#"read request
+case "$request" in
+  *describe*) echo '{"name": "env", "hooks": ["pre-commit", "pre-push"], "patterns": [".env*"]}' ;;
+  *) echo '{"findings": [{"message": "env file", "file": ".env.local", "line": 1}]}' ;;
+esac
+"#,
+        );
+        std::fs::write(root.path().join("plugins/README.md"), "not a plugin").unwrap();
+
+        let plugins = discover(root.path(), &PluginsConfig::default()).unwrap();
+        assert_eq!(plugins.len(), 1);
+        assert_eq!(plugins[0].name, "env");
+        assert_eq!(plugins[0].source, Source::Dir);
+        assert!(plugins[0].runs_in(Hook::PrePush));
+
+        let results = run(
+            &plugins,
+            Hook::PreCommit,
+            root.path(),
+            &[PathBuf::from("src/main.rs")],
+        );
+        assert!(results[0].skipped);
+
+        let results = run(
+            &plugins,
+            Hook::PreCommit,
+            root.path(),
+            &[PathBuf::from("config/.env.local")],
+        );
+        assert!(!results[0].success);
+        assert_eq!(results[0].findings[0].level, PluginLevel::Error);
+        assert_eq!(results[0].findings[0].line, Some(1));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_config_plugins() {
+        let root = TempDir::new().unwrap();
+        write_plugin(
+            root.path(),
+            "ok.sh",
+            "cat > /dev/null\necho '{\"success\": true}'\n",
+        );
+        write_plugin(root.path(), "slow.sh", "sleep 5\n");
+        write_plugin(root.path(), "crash.sh", "echo boom >&2\nexit 3\n");
+
+        let mut slow = config_check("slow", &["./slow.sh"]);
+        slow.timeout_secs = Some(1);
+        let mut crash = config_check("crash", &["./crash.sh"]);
+        crash.required = false;
+        let config = PluginsConfig {
+            dir: "missing".to_string(),
+            disabled: vec!["off".to_string()],
+            checks: vec![
+                config_check("ok", &["./ok.sh"]),
+                slow,
+                crash,
+                config_check("off", &["false"]),
+            ],
+            ..Default::default()
+        };
+
+        let plugins = discover(root.path(), &config).unwrap();
+        let names: Vec<_> = plugins.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["ok", "slow", "crash"]);
+
+        let results = run(&plugins, Hook::PreCommit, root.path(), &[]);
+        let outcomes: Vec<_> = results
+            .iter()
+            .map(|r| (r.name.as_str(), r.success))
+            .collect();
+        assert_eq!(outcomes, [("ok", true), ("slow", false), ("crash", false)]);
+        assert!(results[1].output.as_deref().unwrap().contains("timed out"));
+        assert!(results[2].output.as_deref().unwrap().contains("boom"));
+        assert!(run(&plugins, Hook::PrePush, root.path(), &[]).is_empty());
+    }
+
+    #[test]
+    fn test_unknown_hook_in_config() {
+        let mut check = config_check("lint", &["true"]);
+        check.hooks = vec!["pre-merge".to_string()];
+        let config = PluginsConfig {
+            checks: vec![check],
+            ..Default::default()
+        };
+        assert!(discover(Path::new("."), &config).is_err());
+    }
+}
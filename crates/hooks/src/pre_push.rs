@@ -1,7 +1,11 @@
 //! Pre-push hook - run validation checks before pushing
 //!
-//! Runs a series of checks with fail-fast behavior and progress display.
+//! Runs a series of checks with fail-fast behavior and progress display,
+//! one after another or all at once on a progress dashboard.
 
+use foodshare_cli::output::{self, Finding, Level};
+use foodshare_cli::progress::Dashboard;
+use foodshare_cli::textln;
 use foodshare_core::error::exit_codes;
 use foodshare_core::process::{command_exists, run_command};
 use owo_colors::OwoColorize;
@@ -33,6 +37,8 @@ pub struct PrePushConfig {
     pub quick_mode: bool,
     pub timeout: Duration,
     pub skip_checks: Vec<String>,
+    /// Run the checks at the same time; `fail_fast` doesn't apply
+    pub parallel: bool,
 }
 
 impl Default for PrePushConfig {
@@ -42,16 +48,21 @@ impl Default for PrePushConfig {
             quick_mode: false,
             timeout: Duration::from_secs(300),
             skip_checks: Vec::new(),
+            parallel: false,
         }
     }
 }
 
 /// Run pre-push checks
 pub fn run_checks(checks: &[Check], config: &PrePushConfig) -> Vec<CheckResult> {
+    if config.parallel {
+        return run_checks_parallel(checks, config);
+    }
+
     let mut results = Vec::new();
 
-    println!("{}", "Running pre-push checks...".bold());
-    println!();
+    textln!("{}", "Running pre-push checks...".bold());
+    textln!();
 
     for check in checks {
         // Skip if in skip list
@@ -63,7 +74,7 @@ pub fn run_checks(checks: &[Check], config: &PrePushConfig) -> Vec<CheckResult>
                 output: None,
                 skipped: true,
             });
-            println!("  {} {} {}", "⊘".dimmed(), check.name.dimmed(), "(skipped)".dimmed());
+            textln!("  {} {} {}", "⊘".dimmed(), check.name.dimmed(), "(skipped)".dimmed());
             continue;
         }
 
@@ -76,7 +87,7 @@ pub fn run_checks(checks: &[Check], config: &PrePushConfig) -> Vec<CheckResult>
                 output: None,
                 skipped: true,
             });
-            println!("  {} {} {}", "⊘".dimmed(), check.name.dimmed(), "(quick mode)".dimmed());
+            textln!("  {} {} {}", "⊘".dimmed(), check.name.dimmed(), "(quick mode)".dimmed());
             continue;
         }
 
@@ -101,7 +112,7 @@ pub fn run_checks(checks: &[Check], config: &PrePushConfig) -> Vec<CheckResult>
                     break;
                 }
             } else {
-                println!(
+                textln!(
                     "  {} {} - {} not found {}",
                     "⊘".dimmed(),
                     check.name.dimmed(),
@@ -113,7 +124,9 @@ pub fn run_checks(checks: &[Check], config: &PrePushConfig) -> Vec<CheckResult>
         }
 
         // Run the check
-        print!("  {} {}...", "●".blue(), check.name);
+        if !output::is_machine() {
+            print!("  {} {}...", "●".blue(), check.name);
+        }
         let start = Instant::now();
 
         let result = run_command(check.command, &check.args);
@@ -131,9 +144,11 @@ pub fn run_checks(checks: &[Check], config: &PrePushConfig) -> Vec<CheckResult>
                 });
 
                 // Clear the line and print result
-                print!("\r");
+                if !output::is_machine() {
+                    print!("\r");
+                }
                 if success {
-                    println!(
+                    textln!(
                         "  {} {} {}",
                         "✓".green(),
                         check.name,
@@ -174,8 +189,87 @@ pub fn run_checks(checks: &[Check], config: &PrePushConfig) -> Vec<CheckResult>
     results
 }
 
+/// Run the checks at the same time, each on its own dashboard task
+fn run_checks_parallel(checks: &[Check], config: &PrePushConfig) -> Vec<CheckResult> {
+    textln!("{}", "Running pre-push checks in parallel...".bold());
+    textln!();
+
+    let dashboard = Dashboard::new();
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = checks
+            .iter()
+            .map(|check| {
+                let skipped =
+                    config.skip_checks.iter().any(|s| s == check.name) || (config.quick_mode && !check.required);
+                let task = (!skipped).then(|| dashboard.add(check.name, None));
+                scope.spawn(move || {
+                    let mut result = CheckResult {
+                        name: check.name.to_string(),
+                        success: true,
+                        duration: Duration::ZERO,
+                        output: None,
+                        skipped,
+                    };
+                    let Some(task) = task else {
+                        return result;
+                    };
+
+                    if !command_exists(check.command) {
+                        result.success = !check.required;
+                        result.output = Some(format!("Command not found: {}", check.command));
+                        if check.required {
+                            task.finish_error(&format!("{} not found", check.command));
+                        } else {
+                            task.finish_success(&format!("{} not found (optional)", check.command));
+                        }
+                        return result;
+                    }
+
+                    task.set_message(check.description);
+                    let start = Instant::now();
+                    let (success, output) = match run_command(check.command, &check.args) {
+                        Ok(cmd_result) => (cmd_result.success, cmd_result.combined_output()),
+                        Err(e) => (false, e.to_string()),
+                    };
+                    result.duration = start.elapsed();
+                    result.success = success;
+                    result.output = Some(output);
+                    if success {
+                        task.finish_success("passed");
+                    } else {
+                        task.finish_error("failed");
+                    }
+                    result
+                })
+            })
+            .collect();
+
+        handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+    })
+}
+
 /// Print summary of check results
 pub fn print_summary(results: &[CheckResult]) -> i32 {
+    if output::is_machine() {
+        for result in results.iter().filter(|r| !r.success) {
+            let message = result.output.as_deref().unwrap_or("Check failed").lines().take(10).collect::<Vec<_>>();
+            output::finding(Finding::new(Level::Error, message.join("\n")).with_rule(&result.name));
+        }
+        let checks: Vec<_> = results
+            .iter()
+            .map(|r| {
+                serde_json::json!({
+                    "name": r.name,
+                    "success": r.success,
+                    "skipped": r.skipped,
+                    "duration_ms": r.duration.as_millis() as u64,
+                })
+            })
+            .collect();
+        output::data("checks", &checks);
+        return if results.iter().all(|r| r.success) { exit_codes::SUCCESS } else { exit_codes::FAILURE };
+    }
+
     println!();
 
     let passed = results.iter().filter(|r| r.success && !r.skipped).count();
@@ -228,6 +322,42 @@ mod tests {
         assert_eq!(config.timeout, Duration::from_secs(300));
     }
 
+    #[test]
+    fn test_run_checks_parallel() {
+        let check = |name, command, required| Check {
+            name,
+            description: name,
+            command,
+            args: Vec::new(),
+            required,
+            timeout: Duration::from_secs(10),
+        };
+        let checks = vec![
+            check("ok", "true", true),
+            check("broken", "false", true),
+            check("missing", "foodshare-no-such-command", false),
+            check("skipped", "true", true),
+        ];
+        let config = PrePushConfig {
+            parallel: true,
+            skip_checks: vec!["skipped".to_string()],
+            ..Default::default()
+        };
+
+        let results = run_checks(&checks, &config);
+        let outcomes: Vec<_> = results.iter().map(|r| (r.name.as_str(), r.success, r.skipped)).collect();
+
+        assert_eq!(
+            outcomes,
+            vec![
+                ("ok", true, false),
+                ("broken", false, false),
+                ("missing", true, false),
+                ("skipped", true, true),
+            ]
+        );
+    }
+
     #[test]
     fn test_check_result_skipped() {
         let result = CheckResult {
@@ -0,0 +1,278 @@
+//! Live verification of detected secrets
+//!
+//! Asks each provider whether a secret still works, with the cheapest
+//! authenticated read it offers:
+//!
+//! | Pattern | Request | Live on |
+//! |---------|---------|---------|
+//! | `aws-access-key` | STS `GetCallerIdentity`, signed with the secret key assigned nearest to it | 200 |
+//! | `github-token` | `GET /user` | 200, 403 |
+//! | `stripe-secret-key` | `GET /v1/balance` | 200, 403 |
+//! | `supabase-key` | `GET /rest/v1/` of the project named in the JWT, for `service_role` keys | 200 |
+//!
+//! A 401 (or 403 from AWS) means the provider rejected the secret, as does
+//! an expired Supabase JWT; anything else leaves it unknown. Supabase
+//! `anon` keys are public by design, so they are never sent and stay
+//! unknown. The requests only carry the secret being checked.
+
+use crate::secrets::Verification;
+use base64::Engine;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+/// Patterns whose secrets can be verified.
+pub const VERIFIABLE: [&str; 4] = [
+    "aws-access-key",
+    "github-token",
+    "stripe-secret-key",
+    "supabase-key",
+];
+
+/// Time allowed for each provider request.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Body of the STS `GetCallerIdentity` request.
+const STS_BODY: &str = "Action=GetCallerIdentity&Version=2011-06-15";
+
+/// Content type of the STS request.
+const STS_CONTENT_TYPE: &str = "application/x-www-form-urlencoded; charset=utf-8";
+
+/// Base URLs the checks are sent to.
+#[derive(Debug, Clone)]
+pub struct Endpoints {
+    /// AWS Security Token Service.
+    pub aws_sts: String,
+    /// GitHub REST API.
+    pub github: String,
+    /// Stripe API.
+    pub stripe: String,
+    /// Supabase project, with `{ref}` standing for the project ref.
+    pub supabase: String,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Self {
+            aws_sts: "https://sts.amazonaws.com".to_string(),
+            github: "https://api.github.com".to_string(),
+            stripe: "https://api.stripe.com".to_string(),
+            supabase: "https://{ref}.supabase.co".to_string(),
+        }
+    }
+}
+
+/// Findings counted by [`SecretScanner::verify_findings`](crate::SecretScanner::verify_findings).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct VerifySummary {
+    /// Secrets the provider accepted.
+    pub live: usize,
+    /// Secrets the provider rejected.
+    pub invalid: usize,
+    /// Secrets that couldn't be checked.
+    pub unknown: usize,
+}
+
+/// HTTP client checking secrets with their providers.
+pub(crate) struct Verifier {
+    client: reqwest::Client,
+    endpoints: Endpoints,
+}
+
+impl Verifier {
+    pub(crate) fn new(endpoints: Endpoints) -> reqwest::Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(TIMEOUT)
+            .user_agent(concat!("foodshare-hooks/", env!("CARGO_PKG_VERSION")))
+            .build()?;
+        Ok(Self { client, endpoints })
+    }
+
+    /// Check `secret`, matched by `pattern_id`
+    ///
+    /// `paired` is the secret access key that goes with an AWS access key;
+    /// without one the key can't be checked.
+    pub(crate) async fn check(
+        &self,
+        pattern_id: &str,
+        secret: &str,
+        paired: Option<&str>,
+    ) -> Verification {
+        let request = match pattern_id {
+            "aws-access-key" => paired.and_then(|secret_key| self.aws(secret, secret_key)),
+            "github-token" => Some(
+                self.client
+                    .get(format!("{}/user", self.endpoints.github))
+                    .bearer_auth(secret)
+                    .header("accept", "application/vnd.github+json"),
+            ),
+            "stripe-secret-key" => Some(
+                self.client
+                    .get(format!("{}/v1/balance", self.endpoints.stripe))
+                    .basic_auth(secret, None::<&str>),
+            ),
+            "supabase-key" => match supabase_project(secret) {
+                Ok(project) => Some(
+                    self.client
+                        .get(format!(
+                            "{}/rest/v1/",
+                            self.endpoints.supabase.replace("{ref}", &project)
+                        ))
+                        .header("apikey", secret)
+                        .bearer_auth(secret),
+                ),
+                Err(verification) => return verification,
+            },
+            _ => None,
+        };
+        let Some(request) = request else {
+            return Verification::Unknown;
+        };
+        match request.send().await {
+            Ok(response) => classify(pattern_id, response.status().as_u16()),
+            Err(_) => Verification::Unknown,
+        }
+    }
+
+    /// STS `GetCallerIdentity`, signed with AWS Signature Version 4
+    fn aws(&self, access_key: &str, secret_key: &str) -> Option<reqwest::RequestBuilder> {
+        let url = reqwest::Url::parse(&self.endpoints.aws_sts).ok()?;
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", url.host_str()?, port),
+            None => url.host_str()?.to_string(),
+        };
+        let now = chrono::Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let scope = format!("{}/us-east-1/sts/aws4_request", date);
+
+        let canonical_request = format!(
+            "POST\n/\n\ncontent-type:{}\nhost:{}\nx-amz-date:{}\n\ncontent-type;host;x-amz-date\n{}",
+            STS_CONTENT_TYPE,
+            host,
+            amz_date,
+            hex::encode(Sha256::digest(STS_BODY))
+        );
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(&canonical_request))
+        );
+        let mut key = hmac_sha256(format!("AWS4{}", secret_key).as_bytes(), date.as_bytes());
+        for part in ["us-east-1", "sts", "aws4_request"] {
+            key = hmac_sha256(&key, part.as_bytes());
+        }
+        let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()));
+
+        Some(
+            self.client
+                .post(url)
+                .header("content-type", STS_CONTENT_TYPE)
+                .header("x-amz-date", amz_date)
+                .header(
+                    "authorization",
+                    format!(
+                        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders=content-type;host;x-amz-date, Signature={}",
+                        access_key, scope, signature
+                    ),
+                )
+                .body(STS_BODY),
+        )
+    }
+}
+
+/// What a provider's response status says about the secret
+fn classify(pattern_id: &str, status: u16) -> Verification {
+    match (pattern_id, status) {
+        (_, 200) => Verification::Live,
+        // App installation tokens can't read /user and restricted Stripe
+        // keys can't read the balance, but both were accepted
+        ("github-token" | "stripe-secret-key", 403) => Verification::Live,
+        (_, 401) | ("aws-access-key", 403) => Verification::Invalid,
+        _ => Verification::Unknown,
+    }
+}
+
+/// Project ref named in a Supabase `service_role` JWT
+///
+/// Fails with the verification when the token settles it without a
+/// request: an expired token is invalid; one with another role (public
+/// `anon` keys) or without a hosted project (local development keys) is
+/// unknown.
+fn supabase_project(jwt: &str) -> Result<String, Verification> {
+    let claims: serde_json::Value = jwt
+        .split('.')
+        .nth(1)
+        .and_then(|payload| base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(payload).ok())
+        .and_then(|payload| serde_json::from_slice(&payload).ok())
+        .ok_or(Verification::Unknown)?;
+    if claims["role"] != "service_role" {
+        return Err(Verification::Unknown);
+    }
+    if claims["exp"]
+        .as_i64()
+        .is_some_and(|exp| exp < chrono::Utc::now().timestamp())
+    {
+        return Err(Verification::Invalid);
+    }
+    // A ref is part of a host name; anything else isn't a hosted project
+    match claims["ref"].as_str() {
+        Some(project) if !project.is_empty() && project.bytes().all(|b| b.is_ascii_alphanumeric()) => {
+            Ok(project.to_ascii_lowercase())
+        }
+        _ => Err(Verification::Unknown),
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    use hmac::{Hmac, Mac};
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jwt(claims: &str) -> String {
+        let payload = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(claims);
+        format!("eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.{}.c2lnbmF0dXJl", payload)
+    }
+
+    #[test]
+    fn test_supabase_project() {
+        assert_eq!(
+            supabase_project(&jwt(r#"{"ref":"abcdefghij","role":"service_role","exp":4102444800}"#)),
+            Ok("abcdefghij".to_string())
+        );
+        assert_eq!(
+            supabase_project(&jwt(r#"{"ref":"abcdefghij","role":"service_role","exp":1}"#)),
+            Err(Verification::Invalid)
+        );
+        // Anon keys are public and never checked
+        assert_eq!(
+            supabase_project(&jwt(r#"{"ref":"abcdefghij","role":"anon","exp":4102444800}"#)),
+            Err(Verification::Unknown)
+        );
+        assert_eq!(
+            supabase_project(&jwt(r#"{"iss":"supabase-demo","role":"service_role"}"#)),
+            Err(Verification::Unknown)
+        );
+        assert_eq!(
+            supabase_project(&jwt(r#"{"ref":"evil.example/x#","role":"service_role"}"#)),
+            Err(Verification::Unknown)
+        );
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify("github-token", 200), Verification::Live);
+        assert_eq!(classify("github-token", 403), Verification::Live);
+        assert_eq!(classify("aws-access-key", 403), Verification::Invalid);
+        assert_eq!(classify("stripe-secret-key", 401), Verification::Invalid);
+        assert_eq!(classify("supabase-key", 500), Verification::Unknown);
+    }
+}
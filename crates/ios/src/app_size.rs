@@ -0,0 +1,557 @@
+//! App size analysis
+//!
+//! Breaks an archived `.app` bundle down by executable, framework, extension
+//! and asset, reads the per-device download sizes from the App Thinning Size
+//! Report that `xcodebuild -exportArchive` writes, and compares both against
+//! a baseline committed to the repo so CI can fail when the app outgrows its
+//! budget.
+//!
+//! Sizes are in decimal units (1 MB = 1,000,000 bytes), matching Xcode and
+//! App Store Connect. Baselines are compared and budgets checked with
+//! [`foodshare_cli::size`].
+
+use foodshare_cli::output::format_size_decimal as format_size;
+use foodshare_cli::size::{self, format_change, vs_baseline, KB, MB};
+use foodshare_cli::textln;
+use foodshare_core::config::SizeConfig;
+use foodshare_core::error::{Error, Result};
+use foodshare_core::process::run_command;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Default location of the committed baseline
+pub const BASELINE_FILE: &str = ".foodshare/app-size.json";
+
+/// Name of the report `xcodebuild -exportArchive` writes when thinning
+pub const THINNING_REPORT: &str = "App Thinning Size Report.txt";
+
+/// What a part of the bundle is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComponentKind {
+    /// The main executable
+    Executable,
+    /// An embedded framework or dylib
+    Framework,
+    /// An app extension or watch app
+    Extension,
+    /// The compiled asset catalog, or one asset inside it
+    Asset,
+    /// Any other resource
+    Resource,
+}
+
+impl std::fmt::Display for ComponentKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Executable => "executable",
+            Self::Framework => "framework",
+            Self::Extension => "extension",
+            Self::Asset => "asset",
+            Self::Resource => "resource",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Size of one part of the bundle
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SizeComponent {
+    /// Path inside the bundle, or the asset name
+    pub name: String,
+    /// What the component is
+    pub kind: ComponentKind,
+    /// Uncompressed size on disk
+    pub bytes: u64,
+}
+
+/// One device variant from the App Thinning Size Report
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThinningVariant {
+    /// Variant file name (`FoodShare-<uuid>.ipa`, or `FoodShare.ipa` for universal)
+    pub name: String,
+    /// Device models the variant is served to; empty for the universal variant
+    pub devices: Vec<String>,
+    /// Download size
+    pub compressed: u64,
+    /// Install size
+    pub uncompressed: u64,
+}
+
+/// Size change of one component (kind and name) against the baseline
+pub type SizeDelta = size::SizeDelta<(ComponentKind, String)>;
+
+/// Size breakdown of an app
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppSizeReport {
+    /// Bundle name (`FoodShare.app`)
+    pub app: String,
+    /// Uncompressed size of the whole bundle
+    pub total_bytes: u64,
+    /// Top-level parts of the bundle, largest first
+    pub components: Vec<SizeComponent>,
+    /// Assets inside `Assets.car`, largest first
+    #[serde(default)]
+    pub assets: Vec<SizeComponent>,
+    /// Thinned variants, largest download first
+    #[serde(default)]
+    pub variants: Vec<ThinningVariant>,
+}
+
+impl AppSizeReport {
+    /// Measure an `.app` bundle
+    pub fn from_bundle(app: &Path) -> Result<Self> {
+        if !app.is_dir() {
+            return Err(Error::file_not_found(app));
+        }
+        let app_name = app.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let executable = app_name.trim_end_matches(".app").to_string();
+
+        let mut components = Vec::new();
+        for entry in std::fs::read_dir(app)? {
+            let path = entry?.path();
+            let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+
+            match name.as_str() {
+                // Each embedded framework and extension is its own component
+                "Frameworks" | "PlugIns" | "Extensions" | "Watch" if path.is_dir() => {
+                    let kind = if name == "Frameworks" {
+                        ComponentKind::Framework
+                    } else {
+                        ComponentKind::Extension
+                    };
+                    for child in std::fs::read_dir(&path)? {
+                        let child = child?.path();
+                        components.push(SizeComponent {
+                            name: format!("{}/{}", name, child.file_name().unwrap_or_default().to_string_lossy()),
+                            kind,
+                            bytes: disk_size(&child),
+                        });
+                    }
+                }
+                _ => {
+                    let kind = if name == executable {
+                        ComponentKind::Executable
+                    } else if name == "Assets.car" {
+                        ComponentKind::Asset
+                    } else {
+                        ComponentKind::Resource
+                    };
+                    components.push(SizeComponent {
+                        name,
+                        kind,
+                        bytes: disk_size(&path),
+                    });
+                }
+            }
+        }
+        sort_by_size(&mut components);
+
+        Ok(Self {
+            app: app_name,
+            total_bytes: components.iter().map(|c| c.bytes).sum(),
+            components,
+            assets: Vec::new(),
+            variants: Vec::new(),
+        })
+    }
+
+    /// Add the variants of an App Thinning Size Report
+    pub fn with_thinning(mut self, report: &str) -> Self {
+        self.variants = parse_thinning_report(report);
+        self.variants.sort_by_key(|v| std::cmp::Reverse(v.compressed));
+        self
+    }
+
+    /// Add the assets of the bundle's compiled asset catalog
+    pub fn with_assets(mut self, mut assets: Vec<SizeComponent>) -> Self {
+        sort_by_size(&mut assets);
+        self.assets = assets;
+        self
+    }
+
+    /// Largest download size over all variants, or the bundle size without thinning
+    pub fn download_size(&self) -> u64 {
+        self.variants
+            .iter()
+            .map(|v| v.compressed)
+            .max()
+            .unwrap_or(self.total_bytes)
+    }
+
+    /// Components and assets whose size changed since `baseline`, largest change first
+    pub fn diff(&self, baseline: &Self) -> Vec<SizeDelta> {
+        let index = |report: &Self| -> BTreeMap<(ComponentKind, String), u64> {
+            report
+                .components
+                .iter()
+                .chain(&report.assets)
+                // Assets.car itself is covered by the per-asset entries when both sides have them
+                .filter(|c| c.name != "Assets.car" || report.assets.is_empty())
+                .map(|c| ((c.kind, c.name.clone()), c.bytes))
+                .collect()
+        };
+        size::diff(&index(baseline), &index(self))
+    }
+
+    /// Budget violations against `config`, comparing growth with `baseline`
+    pub fn budget_violations(&self, baseline: Option<&Self>, config: &SizeConfig) -> Vec<String> {
+        size::budget_violations(self.download_size(), baseline.map(Self::download_size), config)
+    }
+
+    /// Load a saved report
+    pub fn load(path: &Path) -> Result<Self> {
+        size::load(path)
+    }
+
+    /// Save the report as JSON, e.g. as the new baseline
+    pub fn save(&self, path: &Path) -> Result<()> {
+        size::save(self, path)
+    }
+
+    /// Print the breakdown, with changes against `baseline` if given
+    pub fn print(&self, baseline: Option<&Self>, top: usize) {
+        use owo_colors::OwoColorize;
+
+        textln!("{}", format!("{} size", self.app).bold());
+        textln!(
+            "  Bundle:   {}{}",
+            format_size(self.total_bytes),
+            vs_baseline(self.total_bytes, baseline.map(|b| b.total_bytes))
+        );
+        textln!(
+            "  Download: {}{}",
+            format_size(self.download_size()),
+            vs_baseline(self.download_size(), baseline.map(Self::download_size))
+        );
+
+        textln!();
+        textln!("{}", format!("Largest components (top {})", top).bold());
+        for component in self.components.iter().take(top) {
+            textln!("  {:>10}  {} ({})", format_size(component.bytes).yellow(), component.name, component.kind);
+        }
+
+        if !self.assets.is_empty() {
+            textln!();
+            textln!("{}", format!("Largest assets (top {})", top).bold());
+            for asset in self.assets.iter().take(top) {
+                textln!("  {:>10}  {}", format_size(asset.bytes).yellow(), asset.name);
+            }
+        }
+
+        if !self.variants.is_empty() {
+            textln!();
+            textln!("{}", "Thinned variants".bold());
+            for variant in self.variants.iter().take(top) {
+                let devices = if variant.devices.is_empty() {
+                    "universal".to_string()
+                } else {
+                    variant.devices.join(", ")
+                };
+                textln!(
+                    "  {:>10}  {} download, {} installed",
+                    format_size(variant.compressed).yellow(),
+                    devices,
+                    format_size(variant.uncompressed)
+                );
+            }
+        }
+
+        if let Some(baseline) = baseline {
+            let deltas = self.diff(baseline);
+            textln!();
+            textln!("{}", "Changes since baseline".bold());
+            if deltas.is_empty() {
+                textln!("  No changes");
+            }
+            for delta in deltas.iter().take(top) {
+                textln!("  {:>10}  {}{}", format_change(delta.change()), delta.key.1, delta.note());
+            }
+        }
+    }
+}
+
+/// Parse an App Thinning Size Report
+///
+/// Each variant is a block like:
+///
+/// ```text
+/// Variant: FoodShare-5B1E0C44.ipa
+/// Supported variant descriptors: [device: iPhone15,2, os-version: 17.0], ...
+/// App + On Demand Resources size: 12.3 MB compressed, 30.1 MB uncompressed
+/// App size: 12.3 MB compressed, 30.1 MB uncompressed
+/// ```
+pub fn parse_thinning_report(report: &str) -> Vec<ThinningVariant> {
+    let mut variants: Vec<ThinningVariant> = Vec::new();
+
+    for line in report.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix("Variant:") {
+            variants.push(ThinningVariant {
+                name: name.trim().to_string(),
+                devices: Vec::new(),
+                compressed: 0,
+                uncompressed: 0,
+            });
+            continue;
+        }
+        let Some(variant) = variants.last_mut() else {
+            continue;
+        };
+
+        if let Some(descriptors) = line.strip_prefix("Supported variant descriptors:") {
+            // Model identifiers contain commas themselves (`iPhone15,2`)
+            for descriptor in descriptors.split("device:").skip(1) {
+                let device = descriptor.split(']').next().unwrap_or("");
+                let device = device.split(", ").next().unwrap_or("").trim();
+                if !device.is_empty() && !variant.devices.iter().any(|d| d == device) {
+                    variant.devices.push(device.to_string());
+                }
+            }
+        } else if let Some(sizes) = line.strip_prefix("App size:") {
+            for part in sizes.split(',') {
+                let part = part.trim();
+                if let Some(size) = part.strip_suffix(" uncompressed") {
+                    variant.uncompressed = parse_size(size).unwrap_or(0);
+                } else if let Some(size) = part.strip_suffix(" compressed") {
+                    variant.compressed = parse_size(size).unwrap_or(0);
+                }
+            }
+        }
+    }
+    variants
+}
+
+/// Per-asset sizes of a compiled asset catalog (`Assets.car`)
+pub fn asset_catalog_entries(car: &Path) -> Result<Vec<SizeComponent>> {
+    let result = run_command("xcrun", &["assetutil", "--info", &car.to_string_lossy()])?;
+    if !result.success {
+        return Err(Error::process(format!(
+            "assetutil failed: {}",
+            result.stderr.trim()
+        )));
+    }
+    parse_assetutil(&result.stdout)
+}
+
+/// Parse `assetutil --info` output, summing the renditions of each asset
+pub fn parse_assetutil(json: &str) -> Result<Vec<SizeComponent>> {
+    let entries: Vec<serde_json::Value> = serde_json::from_str(json)?;
+    let mut sizes: BTreeMap<String, u64> = BTreeMap::new();
+
+    // The first entry describes the catalog itself and has no name
+    for entry in &entries {
+        if let (Some(name), Some(bytes)) = (entry["Name"].as_str(), entry["SizeOnDisk"].as_u64()) {
+            *sizes.entry(name.to_string()).or_default() += bytes;
+        }
+    }
+
+    let mut assets: Vec<SizeComponent> = sizes
+        .into_iter()
+        .map(|(name, bytes)| SizeComponent {
+            name,
+            kind: ComponentKind::Asset,
+            bytes,
+        })
+        .collect();
+    sort_by_size(&mut assets);
+    Ok(assets)
+}
+
+/// Export an archive with app thinning for all device variants
+///
+/// Returns the path of the App Thinning Size Report. `method` is the export
+/// method (`development`, `ad-hoc`, `app-store-connect`); exporting signs the
+/// app, so it needs the same signing setup as a device build.
+pub fn export_thinned(archive: &Path, export_dir: &Path, method: &str) -> Result<PathBuf> {
+    std::fs::create_dir_all(export_dir)?;
+    let options = export_dir.join("ExportOptions.plist");
+    std::fs::write(&options, export_options(method))?;
+
+    let result = run_command(
+        "xcodebuild",
+        &[
+            "-exportArchive",
+            "-archivePath",
+            &archive.to_string_lossy(),
+            "-exportPath",
+            &export_dir.to_string_lossy(),
+            "-exportOptionsPlist",
+            &options.to_string_lossy(),
+        ],
+    )?;
+    if !result.success {
+        return Err(Error::process(format!(
+            "Export failed: {}",
+            result.stderr.trim()
+        )));
+    }
+
+    let report = export_dir.join(THINNING_REPORT);
+    if !report.exists() {
+        return Err(Error::file_not_found(&report));
+    }
+    Ok(report)
+}
+
+/// The `.app` bundle inside an `.xcarchive`
+pub fn archived_app(archive: &Path) -> Result<PathBuf> {
+    let applications = archive.join("Products").join("Applications");
+    if !applications.is_dir() {
+        return Err(Error::file_not_found(&applications));
+    }
+    std::fs::read_dir(&applications)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .find(|path| path.extension().is_some_and(|ext| ext == "app"))
+        .ok_or_else(|| Error::validation(format!("No .app in {}", applications.display())))
+}
+
+/// Export options that thin for every device variant
+fn export_options(method: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>method</key>
+	<string>{}</string>
+	<key>thinning</key>
+	<string>&lt;thin-for-all-variants&gt;</string>
+</dict>
+</plist>
+"#,
+        method
+    )
+}
+
+const GB: u64 = MB * 1000;
+
+/// Parse a size like `12.3 MB` or `Zero KB`
+fn parse_size(text: &str) -> Option<u64> {
+    let (value, unit) = text.trim().split_once(' ')?;
+    let value: f64 = if value == "Zero" { 0.0 } else { value.parse().ok()? };
+    let multiplier = match unit {
+        "bytes" | "B" => 1,
+        "KB" => KB,
+        "MB" => MB,
+        "GB" => GB,
+        _ => return None,
+    };
+    Some((value * multiplier as f64).round() as u64)
+}
+
+/// Total size of the files under `path`
+fn disk_size(path: &Path) -> u64 {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+fn sort_by_size(components: &mut [SizeComponent]) {
+    components.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: &Path, bytes: usize) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, vec![0u8; bytes]).unwrap();
+    }
+
+    #[test]
+    fn test_from_bundle_and_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = dir.path().join("FoodShare.app");
+        write(&app.join("FoodShare"), 5000);
+        write(&app.join("Assets.car"), 3000);
+        write(&app.join("Info.plist"), 100);
+        write(&app.join("Frameworks/Supabase.framework/Supabase"), 4000);
+        write(&app.join("Frameworks/Supabase.framework/Info.plist"), 50);
+        write(&app.join("PlugIns/Widgets.appex/Widgets"), 1000);
+
+        let baseline = AppSizeReport::from_bundle(&app).unwrap();
+        assert_eq!(baseline.total_bytes, 13150);
+        assert_eq!(baseline.components[0].kind, ComponentKind::Executable);
+        assert_eq!(baseline.components[1].name, "Frameworks/Supabase.framework");
+        assert_eq!(baseline.components[1].bytes, 4050);
+        assert_eq!(baseline.components[3].kind, ComponentKind::Extension);
+
+        write(&app.join("FoodShare"), 6000);
+        write(&app.join("Frameworks/Lottie.framework/Lottie"), 2000);
+        std::fs::remove_dir_all(app.join("PlugIns")).unwrap();
+        let report = AppSizeReport::from_bundle(&app).unwrap();
+
+        let deltas = report.diff(&baseline);
+        let changes: Vec<_> = deltas.iter().map(|d| (d.key.1.as_str(), d.change())).collect();
+        assert_eq!(
+            changes,
+            [("Frameworks/Lottie.framework", 2000), ("FoodShare", 1000), ("PlugIns/Widgets.appex", -1000)]
+        );
+
+        let config = SizeConfig {
+            budget_mb: Some(0.01),
+            max_growth_kb: Some(1),
+        };
+        assert_eq!(report.budget_violations(Some(&baseline), &config).len(), 2);
+        assert!(report.budget_violations(None, &SizeConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn test_parse_thinning_report() {
+        let report = "\
+App Thinning Size Report for All Variants of FoodShare
+
+Variant: FoodShare-5B1E0C44-2F44-4C1A-9C5D-1E0B9E6A0F11.ipa
+Supported variant descriptors: [device: iPhone15,2, os-version: 17.0], [device: iPhone16,1, os-version: 17.0]
+App + On Demand Resources size: 14.2 MB compressed, 38.9 MB uncompressed
+App size: 14.2 MB compressed, 38.9 MB uncompressed
+On Demand Resources size: Zero KB compressed, Zero KB uncompressed
+
+Variant: FoodShare.ipa
+Supported variant descriptors: Universal
+App + On Demand Resources size: 21.5 MB compressed, 52 MB uncompressed
+App size: 21.5 MB compressed, 52 MB uncompressed
+On Demand Resources size: Zero KB compressed, Zero KB uncompressed
+";
+        let variants = parse_thinning_report(report);
+        assert_eq!(variants.len(), 2);
+        assert_eq!(variants[0].devices, ["iPhone15,2", "iPhone16,1"]);
+        assert_eq!(variants[0].compressed, 14_200_000);
+        assert_eq!(variants[0].uncompressed, 38_900_000);
+        assert!(variants[1].devices.is_empty());
+
+        let app = AppSizeReport {
+            app: "FoodShare.app".to_string(),
+            total_bytes: 50_000_000,
+            components: Vec::new(),
+            assets: Vec::new(),
+            variants: Vec::new(),
+        };
+        assert_eq!(app.download_size(), 50_000_000);
+        let app = app.with_thinning(report);
+        assert_eq!(app.variants[0].name, "FoodShare.ipa");
+        assert_eq!(app.download_size(), 21_500_000);
+    }
+
+    #[test]
+    fn test_parse_assetutil() {
+        let json = r#"[
+  {"AssetStorageVersion": "Xcode 16.0", "Platform": "ios"},
+  {"AssetType": "Image", "Name": "Onboarding", "RenditionName": "onboarding@2x.png", "Scale": 2, "SizeOnDisk": 120000},
+  {"AssetType": "Image", "Name": "Onboarding", "RenditionName": "onboarding@3x.png", "Scale": 3, "SizeOnDisk": 240000},
+  {"AssetType": "Color", "Name": "AccentColor", "SizeOnDisk": 300}
+]"#;
+        let assets = parse_assetutil(json).unwrap();
+        assert_eq!(assets.len(), 2);
+        assert_eq!(assets[0].name, "Onboarding");
+        assert_eq!(assets[0].bytes, 360_000);
+        assert_eq!(assets[1].kind, ComponentKind::Asset);
+    }
+}
@@ -0,0 +1,281 @@
+//! Build artifact cache keyed on a source hash
+//!
+//! A build key is a SHA-256 over every build input in the repository (Swift
+//! and Objective-C sources, project files, xcconfigs, plists, entitlements,
+//! `Package.swift` and `Package.resolved`) plus the build settings passed to
+//! `xcodebuild` and the toolchain building them: the `xcodebuild -version`
+//! output and the `DEVELOPER_DIR` and `TOOLCHAINS` selection. Each successful build records its key under
+//! `.foodshare-hooks/build-cache/`, optionally with a copy of its products,
+//! so an unchanged tree can skip the build or restore the products instead.
+//!
+//! Resources such as asset catalogs are not part of the key; use `--no-cache`
+//! after changing only resources.
+
+use chrono::Utc;
+use foodshare_core::error::Result;
+use foodshare_core::process::run_command;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Cache directory, relative to the repository root
+pub const CACHE_DIR: &str = ".foodshare-hooks/build-cache";
+
+/// Cached builds kept; older ones are pruned
+pub const MAX_ENTRIES: usize = 5;
+
+/// File extensions that are build inputs
+const INPUT_EXTENSIONS: &[&str] = &[
+    "swift", "h", "m", "mm", "c", "pbxproj", "xcscheme", "xcconfig", "plist", "entitlements",
+];
+
+/// File names that are build inputs regardless of extension
+const INPUT_FILES: &[&str] = &["Package.swift", "Package.resolved"];
+
+/// Directories holding build output or tool state rather than inputs
+const SKIPPED_DIRS: &[&str] = &["build", "DerivedData", "xcuserdata", "node_modules"];
+
+/// Environment variables selecting the Xcode and Swift toolchain
+const TOOLCHAIN_VARS: &[&str] = &["DEVELOPER_DIR", "TOOLCHAINS"];
+
+/// Hash the build inputs under `root` together with the build settings and
+/// the active toolchain
+pub fn source_hash(root: &Path, settings: &[&str]) -> Result<String> {
+    let toolchain = toolchain_settings();
+    hash_inputs(
+        root,
+        settings.iter().copied().chain(toolchain.iter().map(String::as_str)),
+    )
+}
+
+/// The active Xcode and toolchain selection, which change the build output
+/// without changing any input
+///
+/// Without `xcodebuild` the version is empty.
+fn toolchain_settings() -> Vec<String> {
+    let xcode = run_command("xcodebuild", &["-version"])
+        .map(|result| result.stdout)
+        .unwrap_or_default();
+    std::iter::once(xcode)
+        .chain(
+            TOOLCHAIN_VARS
+                .iter()
+                .map(|var| format!("{}={}", var, std::env::var(var).unwrap_or_default())),
+        )
+        .collect()
+}
+
+fn hash_inputs<'a>(root: &Path, settings: impl Iterator<Item = &'a str>) -> Result<String> {
+    let mut hasher = Sha256::new();
+    for setting in settings {
+        hasher.update(setting.as_bytes());
+        hasher.update(b"\0");
+    }
+
+    let walker = WalkDir::new(root)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| e.depth() == 0 || !e.file_type().is_dir() || !is_skipped_dir(e.file_name().to_str()));
+    for entry in walker {
+        let entry = entry.map_err(|e| std::io::Error::other(e.to_string()))?;
+        if !entry.file_type().is_file() || !is_input(entry.path()) {
+            continue;
+        }
+        let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+        hasher.update(relative.to_string_lossy().as_bytes());
+        hasher.update(b"\0");
+        hasher.update(Sha256::digest(std::fs::read(entry.path())?));
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn is_skipped_dir(name: Option<&str>) -> bool {
+    name.is_none_or(|name| name.starts_with('.') || SKIPPED_DIRS.contains(&name))
+}
+
+fn is_input(path: &Path) -> bool {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+    INPUT_FILES.contains(&name)
+        || path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|ext| INPUT_EXTENSIONS.contains(&ext))
+}
+
+/// Successful builds by key, with optional copies of their products
+pub struct BuildCache {
+    dir: PathBuf,
+}
+
+impl BuildCache {
+    /// Cache in a directory
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Cache of the repository at `root`
+    pub fn open(root: &Path) -> Self {
+        Self::new(root.join(CACHE_DIR))
+    }
+
+    /// Whether a build with this key already succeeded
+    pub fn is_fresh(&self, key: &str) -> bool {
+        self.entry(key).is_dir()
+    }
+
+    /// Record a successful build, copying `products` into the cache if given
+    pub fn record(&self, key: &str, products: Option<&Path>) -> Result<()> {
+        let entry = self.entry(key);
+        if entry.exists() {
+            std::fs::remove_dir_all(&entry)?;
+        }
+        std::fs::create_dir_all(&entry)?;
+        if let Some(products) = products.filter(|p| p.is_dir()) {
+            copy_dir(products, &entry.join("products"))?;
+        }
+        std::fs::write(entry.join("built-at"), Utc::now().to_rfc3339())?;
+        self.prune(MAX_ENTRIES)
+    }
+
+    /// Copy a cached build's products to `dest`
+    ///
+    /// Returns false if the key has no cached products.
+    pub fn restore(&self, key: &str, dest: &Path) -> Result<bool> {
+        let products = self.entry(key).join("products");
+        if !products.is_dir() {
+            return Ok(false);
+        }
+        copy_dir(&products, dest)?;
+        Ok(true)
+    }
+
+    /// Remove every cached build, returning how many were removed
+    pub fn clear(&self) -> Result<usize> {
+        let entries = self.entries()?;
+        for (path, _) in &entries {
+            std::fs::remove_dir_all(path)?;
+        }
+        Ok(entries.len())
+    }
+
+    /// Keep only the `keep` most recently recorded builds
+    fn prune(&self, keep: usize) -> Result<()> {
+        let mut entries = self.entries()?;
+        entries.sort_by(|a, b| b.1.cmp(&a.1));
+        for (path, _) in entries.iter().skip(keep) {
+            std::fs::remove_dir_all(path)?;
+        }
+        Ok(())
+    }
+
+    /// Entry directories with their recording time
+    fn entries(&self) -> Result<Vec<(PathBuf, String)>> {
+        if !self.dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut entries = Vec::new();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                let built_at = std::fs::read_to_string(path.join("built-at")).unwrap_or_default();
+                entries.push((path, built_at));
+            }
+        }
+        Ok(entries)
+    }
+
+    fn entry(&self, key: &str) -> PathBuf {
+        self.dir.join(key)
+    }
+}
+
+/// Copy a directory tree, replacing files that already exist
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    for entry in WalkDir::new(from) {
+        let entry = entry.map_err(|e| std::io::Error::other(e.to_string()))?;
+        let target = to.join(entry.path().strip_prefix(from).unwrap_or(entry.path()));
+        if entry.file_type().is_dir() {
+            std::fs::create_dir_all(&target)?;
+        } else if entry.file_type().is_file() {
+            std::fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_source_hash_inputs() {
+        let repo = TempDir::new().unwrap();
+        let write = |path: &str, content: &str| {
+            let path = repo.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write("FoodShare/Feed.swift", "struct Feed {}");
+        write("FoodShare.xcworkspace/xcshareddata/swiftpm/Package.resolved", "{}");
+
+        let key = source_hash(repo.path(), &["Debug"]).unwrap();
+        assert_eq!(key, source_hash(repo.path(), &["Debug"]).unwrap());
+        assert_ne!(key, source_hash(repo.path(), &["Release"]).unwrap());
+
+        write("README.md", "# FoodShare");
+        write("build/Intermediates/Generated.swift", "struct Generated {}");
+        write(".foodshare-hooks/snapshots/Feed.swift", "struct Old {}");
+        assert_eq!(key, source_hash(repo.path(), &["Debug"]).unwrap());
+
+        write("FoodShare.xcworkspace/xcshareddata/swiftpm/Package.resolved", r#"{"pins":[]}"#);
+        let resolved = source_hash(repo.path(), &["Debug"]).unwrap();
+        assert_ne!(key, resolved);
+        write("FoodShare/Feed.swift", "struct Feed { let id: Int }");
+        assert_ne!(resolved, source_hash(repo.path(), &["Debug"]).unwrap());
+    }
+
+    #[test]
+    fn test_source_hash_toolchain() {
+        let repo = TempDir::new().unwrap();
+        std::fs::write(repo.path().join("Feed.swift"), "struct Feed {}").unwrap();
+
+        let toolchain = toolchain_settings();
+        assert!(toolchain.iter().any(|s| s.starts_with("DEVELOPER_DIR=")));
+        assert!(toolchain.iter().any(|s| s.starts_with("TOOLCHAINS=")));
+        let key = source_hash(repo.path(), &["Debug"]).unwrap();
+        let with = |toolchain: &[&str]| {
+            hash_inputs(repo.path(), ["Debug"].into_iter().chain(toolchain.iter().copied())).unwrap()
+        };
+        assert_eq!(key, with(&toolchain.iter().map(String::as_str).collect::<Vec<_>>()));
+        assert_ne!(
+            with(&["Xcode 16.2\nBuild version 16C5032a\n", "DEVELOPER_DIR=", "TOOLCHAINS="]),
+            with(&["Xcode 26.0\nBuild version 17A324\n", "DEVELOPER_DIR=", "TOOLCHAINS="])
+        );
+    }
+
+    #[test]
+    fn test_record_restore_and_prune() {
+        let (dir, products) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        std::fs::create_dir_all(products.path().join("FoodShare.app")).unwrap();
+        std::fs::write(products.path().join("FoodShare.app/FoodShare"), "binary").unwrap();
+
+        let cache = BuildCache::new(dir.path());
+        assert!(!cache.is_fresh("abc"));
+        cache.record("abc", Some(products.path())).unwrap();
+        cache.record("def", None).unwrap();
+        assert!(cache.is_fresh("abc") && cache.is_fresh("def"));
+
+        let restored = TempDir::new().unwrap();
+        let dest = restored.path().join("Products");
+        assert!(cache.restore("abc", &dest).unwrap());
+        assert_eq!(std::fs::read_to_string(dest.join("FoodShare.app/FoodShare")).unwrap(), "binary");
+        assert!(!cache.restore("def", &dest).unwrap());
+
+        for i in 0..MAX_ENTRIES {
+            cache.record(&format!("key-{}", i), None).unwrap();
+        }
+        assert!(!cache.is_fresh("abc"));
+        assert!(cache.is_fresh(&format!("key-{}", MAX_ENTRIES - 1)));
+    }
+}
@@ -0,0 +1,338 @@
+//! xcodebuild log analysis
+//!
+//! Builds run with [`timing_settings`] make the Swift frontend print how long
+//! each function body took to type-check, and warn about expressions over
+//! [`SLOW_EXPRESSION_MS`]. [`BuildLog::parse`] turns that output into
+//! per-file compile times, the expensive type checks, and function bodies
+//! checked more than once (the same file compiled in several targets or
+//! frontend jobs).
+
+use foodshare_cli::textln;
+use foodshare_telemetry::{TrendLog, TrendPoint};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Threshold for the expression type-check warnings, in milliseconds
+pub const SLOW_EXPRESSION_MS: u32 = 100;
+
+/// Build setting that enables the timing output parsed here
+///
+/// Changing `OTHER_SWIFT_FLAGS` invalidates the build cache, so the first
+/// build with (or without) it recompiles every Swift file.
+pub fn timing_settings() -> String {
+    format!(
+        "OTHER_SWIFT_FLAGS=$(inherited) -Xfrontend -debug-time-function-bodies \
+         -Xfrontend -warn-long-expression-type-checking={SLOW_EXPRESSION_MS}"
+    )
+}
+
+/// Where `foodshare-ios build` keeps its trend history
+pub const TRENDS_FILE: &str = ".foodshare/build-trends.jsonl";
+
+/// `12.34ms\t/path/File.swift:10:5\tinstance method body()`
+static FUNCTION_TIMING: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(\d+(?:\.\d+)?)ms\t(.+):(\d+):(\d+)\t(.*)$").unwrap()
+});
+
+/// `/path/File.swift:10:5: warning: expression took 250ms to type-check (limit: 100ms)`
+static SLOW_TYPE_CHECK: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(.+):(\d+):(\d+): warning: (.+) took (\d+)ms to type-check \(limit: \d+ms\)$")
+        .unwrap()
+});
+
+/// Type-check time of a source file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileTiming {
+    /// Source file
+    pub file: PathBuf,
+    /// Sum of its function body type-check times, in milliseconds
+    pub duration_ms: f64,
+    /// Number of function bodies timed
+    pub functions: usize,
+}
+
+/// An expression or function body over the type-check warning limit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SlowTypeCheck {
+    /// Source file
+    pub file: PathBuf,
+    /// Line in `file`
+    pub line: u32,
+    /// Column in `file`
+    pub column: u32,
+    /// What was checked (`expression`, `instance method 'body()'`)
+    pub subject: String,
+    /// Type-check time in milliseconds
+    pub duration_ms: f64,
+}
+
+/// A function body type-checked more than once
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DuplicateWork {
+    /// Source file
+    pub file: PathBuf,
+    /// Line in `file`
+    pub line: u32,
+    /// Function description from the compiler
+    pub function: String,
+    /// Number of times it was checked
+    pub count: usize,
+    /// Time spent on the repeated checks, in milliseconds
+    pub wasted_ms: f64,
+}
+
+/// Timing data extracted from an xcodebuild log
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BuildLog {
+    /// Files, slowest first
+    pub files: Vec<FileTiming>,
+    /// Expensive type checks, slowest first
+    pub slow_type_checks: Vec<SlowTypeCheck>,
+    /// Repeated function body checks, most time wasted first
+    pub duplicates: Vec<DuplicateWork>,
+}
+
+impl BuildLog {
+    /// Parse xcodebuild output
+    pub fn parse(log: &str) -> Self {
+        let mut files: HashMap<&str, FileTiming> = HashMap::new();
+        let mut functions: HashMap<(&str, u32, u32), (&str, Vec<f64>)> = HashMap::new();
+        let mut slow: HashMap<(&str, u32, u32), SlowTypeCheck> = HashMap::new();
+
+        for line in log.lines() {
+            let line = line.trim_end_matches('\r');
+            if let Some(caps) = FUNCTION_TIMING.captures(line) {
+                let ms: f64 = caps[1].parse().unwrap_or(0.0);
+                let file = caps.get(2).map_or("", |m| m.as_str());
+                let (row, col) = (caps[3].parse().unwrap_or(0), caps[4].parse().unwrap_or(0));
+                let function = caps.get(5).map_or("", |m| m.as_str());
+
+                let timing = files.entry(file).or_insert_with(|| FileTiming {
+                    file: PathBuf::from(file),
+                    duration_ms: 0.0,
+                    functions: 0,
+                });
+                timing.duration_ms += ms;
+                timing.functions += 1;
+                functions
+                    .entry((file, row, col))
+                    .or_insert((function, Vec::new()))
+                    .1
+                    .push(ms);
+            } else if let Some(caps) = SLOW_TYPE_CHECK.captures(line) {
+                let file = caps.get(1).map_or("", |m| m.as_str());
+                let (row, col) = (caps[2].parse().unwrap_or(0), caps[3].parse().unwrap_or(0));
+                // xcodebuild repeats diagnostics in its summary, keep one per location
+                slow.entry((file, row, col)).or_insert_with(|| SlowTypeCheck {
+                    file: PathBuf::from(file),
+                    line: row,
+                    column: col,
+                    subject: caps[4].to_string(),
+                    duration_ms: caps[5].parse().unwrap_or(0.0),
+                });
+            }
+        }
+
+        let mut files: Vec<FileTiming> = files.into_values().collect();
+        files.sort_by(|a, b| b.duration_ms.total_cmp(&a.duration_ms).then(a.file.cmp(&b.file)));
+
+        let mut slow_type_checks: Vec<SlowTypeCheck> = slow.into_values().collect();
+        slow_type_checks.sort_by(|a, b| {
+            b.duration_ms
+                .total_cmp(&a.duration_ms)
+                .then(a.file.cmp(&b.file))
+                .then(a.line.cmp(&b.line))
+        });
+
+        let mut duplicates: Vec<DuplicateWork> = functions
+            .into_iter()
+            .filter(|(_, (_, times))| times.len() > 1)
+            .map(|((file, line, _), (function, times))| DuplicateWork {
+                file: PathBuf::from(file),
+                line,
+                function: function.to_string(),
+                count: times.len(),
+                wasted_ms: times.iter().sum::<f64>() - times[0],
+            })
+            .collect();
+        duplicates.sort_by(|a, b| {
+            b.wasted_ms
+                .total_cmp(&a.wasted_ms)
+                .then(a.file.cmp(&b.file))
+                .then(a.line.cmp(&b.line))
+        });
+
+        Self {
+            files,
+            slow_type_checks,
+            duplicates,
+        }
+    }
+
+    /// Check if the log had no timing output
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty() && self.slow_type_checks.is_empty()
+    }
+
+    /// Total type-check time across all files, in milliseconds
+    pub fn total_ms(&self) -> f64 {
+        self.files.iter().map(|f| f.duration_ms).sum()
+    }
+
+    /// The `n` slowest files
+    pub fn slowest_files(&self, n: usize) -> &[FileTiming] {
+        &self.files[..n.min(self.files.len())]
+    }
+
+    /// Print the `top` slowest files, expensive type checks and duplicated work
+    ///
+    /// Paths under `root` are shown relative to it.
+    pub fn print_report(&self, top: usize, root: &Path) {
+        use owo_colors::OwoColorize;
+
+        let display = |file: &Path| file.strip_prefix(root).unwrap_or(file).display().to_string();
+
+        textln!("{}", format!("Slowest files (top {})", top).bold());
+        for timing in self.slowest_files(top) {
+            textln!(
+                "  {:>10}  {} ({} functions)",
+                format!("{:.1}ms", timing.duration_ms).yellow(),
+                display(&timing.file),
+                timing.functions
+            );
+        }
+
+        if !self.slow_type_checks.is_empty() {
+            textln!();
+            textln!(
+                "{}",
+                format!("Expensive type checks (over {}ms)", SLOW_EXPRESSION_MS).bold()
+            );
+            for check in self.slow_type_checks.iter().take(top) {
+                textln!(
+                    "  {:>10}  {}:{}:{} {}",
+                    format!("{:.0}ms", check.duration_ms).red(),
+                    display(&check.file),
+                    check.line,
+                    check.column,
+                    check.subject
+                );
+            }
+        }
+
+        if !self.duplicates.is_empty() {
+            textln!();
+            textln!("{}", "Duplicated work".bold());
+            for dup in self.duplicates.iter().take(top) {
+                textln!(
+                    "  {:>10}  {}:{} {} (checked {}x)",
+                    format!("{:.1}ms", dup.wasted_ms).yellow(),
+                    display(&dup.file),
+                    dup.line,
+                    dup.function,
+                    dup.count
+                );
+            }
+        }
+
+        textln!();
+        textln!(
+            "Type-check total: {:.1}ms across {} files",
+            self.total_ms(),
+            self.files.len()
+        );
+    }
+
+    /// Trend points for this build: totals plus the `top` slowest files
+    pub fn trend_points(&self, top: usize, root: &Path) -> Vec<TrendPoint> {
+        let wasted: f64 = self.duplicates.iter().map(|d| d.wasted_ms).sum();
+        let mut points = vec![
+            TrendPoint::new("ios.build.type_check_ms", self.total_ms()),
+            TrendPoint::new("ios.build.slow_type_checks", self.slow_type_checks.len() as f64),
+            TrendPoint::new("ios.build.duplicated_ms", wasted),
+        ];
+        for timing in self.slowest_files(top) {
+            let file = timing.file.strip_prefix(root).unwrap_or(&timing.file);
+            points.push(
+                TrendPoint::new("ios.build.file_type_check_ms", timing.duration_ms)
+                    .with_label("file", file.to_string_lossy()),
+            );
+        }
+        points
+    }
+
+    /// Append this build's trend points to `log`, returning the previous
+    /// build's total type-check time if there was one
+    pub fn record_trends(&self, log: &TrendLog, top: usize, root: &Path) -> anyhow::Result<Option<f64>> {
+        let previous = log
+            .series("ios.build.type_check_ms", &[])?
+            .last()
+            .map(|point| point.value);
+        log.record(&self.trend_points(top, root))?;
+        Ok(previous)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOG: &str = "\
+CompileSwift normal arm64 /work/FoodShare/Feed/FeedView.swift (in target 'FoodShare' from project 'FoodShare')
+412.50ms\t/work/FoodShare/Feed/FeedView.swift:24:9\tgetter body
+0.21ms\t/work/FoodShare/Feed/FeedView.swift:80:10\tinstance method load()
+3.00ms\t/work/FoodShare/Core/Money.swift:5:17\tstatic method +(_:_:)
+/work/FoodShare/Feed/FeedView.swift:31:22: warning: expression took 380ms to type-check (limit: 100ms)
+3.50ms\t/work/FoodShare/Core/Money.swift:5:17\tstatic method +(_:_:)
+/work/FoodShare/Feed/FeedView.swift:31:22: warning: expression took 380ms to type-check (limit: 100ms)
+** BUILD SUCCEEDED **
+";
+
+    #[test]
+    fn test_parse_file_timings() {
+        let log = BuildLog::parse(LOG);
+        assert_eq!(log.files.len(), 2);
+        assert_eq!(log.files[0].file, Path::new("/work/FoodShare/Feed/FeedView.swift"));
+        assert!((log.files[0].duration_ms - 412.71).abs() < 1e-9);
+        assert_eq!(log.files[0].functions, 2);
+        assert!((log.total_ms() - 419.21).abs() < 1e-9);
+        assert_eq!(log.slowest_files(1).len(), 1);
+        assert_eq!(log.slowest_files(20).len(), 2);
+    }
+
+    #[test]
+    fn test_parse_slow_type_checks_and_duplicates() {
+        let log = BuildLog::parse(LOG);
+        assert_eq!(log.slow_type_checks.len(), 1);
+        assert_eq!(log.slow_type_checks[0].subject, "expression");
+        assert_eq!(log.slow_type_checks[0].duration_ms, 380.0);
+        assert_eq!((log.slow_type_checks[0].line, log.slow_type_checks[0].column), (31, 22));
+
+        assert_eq!(log.duplicates.len(), 1);
+        assert_eq!(log.duplicates[0].function, "static method +(_:_:)");
+        assert_eq!(log.duplicates[0].count, 2);
+        assert_eq!(log.duplicates[0].wasted_ms, 3.5);
+
+        assert!(BuildLog::parse("** BUILD SUCCEEDED **").is_empty());
+    }
+
+    #[test]
+    fn test_record_trends() {
+        let dir = tempfile::tempdir().unwrap();
+        let trends = TrendLog::new(dir.path().join("trends.jsonl"));
+        let log = BuildLog::parse(LOG);
+        let root = Path::new("/work");
+
+        assert_eq!(log.record_trends(&trends, 20, root).unwrap(), None);
+        let previous = log.record_trends(&trends, 20, root).unwrap().unwrap();
+        assert!((previous - 419.21).abs() < 1e-9);
+
+        let feed = trends
+            .series("ios.build.file_type_check_ms", &[("file", "FoodShare/Feed/FeedView.swift")])
+            .unwrap();
+        assert_eq!(feed.len(), 2);
+    }
+}
@@ -4,7 +4,8 @@
 //!
 //! ## Safety Layers
 //!
-//! 1. **Snapshots**: Full file content backup before any operation
+//! 1. **Snapshots**: Full file content backup before any operation, stored
+//!    as deduplicated, zstd-compressed blobs
 //! 2. **Verification**: Build check after modifications to catch breakage early
 //! 3. **Interactive Approval**: Show diff and require explicit confirmation
 //! 4. **Rollback**: One-command recovery to any previous state
@@ -25,6 +26,15 @@
 //! # Restore specific file from snapshot
 //! foodshare-ios protect restore --snapshot <id> --file <path>
 //!
+//! # Show what a restore would change
+//! foodshare-ios protect diff --snapshot <id>
+//!
+//! # Drop expired snapshots and unreferenced blobs
+//! foodshare-ios protect gc
+//!
+//! # Snapshot protected files automatically as they change
+//! foodshare-ios protect watch
+//!
 //! # Show what would be committed
 //! foodshare-ios protect commit-guard
 //!
@@ -32,13 +42,17 @@
 //! foodshare-ios protect push-guard
 //! ```
 
+use crate::build_cache::{source_hash, BuildCache};
 use chrono::{DateTime, Local, Utc};
-use foodshare_core::error::Result;
+use foodshare_cli::output::diff::{print_unified, Diff, DiffStyle};
+use foodshare_cli::{text, textln};
+use foodshare_core::error::{Error, Result};
 use foodshare_core::git::GitRepo;
 use foodshare_core::process::run_command;
 use owo_colors::OwoColorize;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::collections::HashSet;
 use std::fs;
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
@@ -121,6 +135,8 @@ pub enum SnapshotTrigger {
     PrePush,
     PreRebase,
     Manual,
+    /// Taken by `protect watch` after a burst of changes
+    Auto,
 }
 
 impl std::fmt::Display for SnapshotTrigger {
@@ -131,6 +147,7 @@ impl std::fmt::Display for SnapshotTrigger {
             Self::PrePush => write!(f, "pre-push"),
             Self::PreRebase => write!(f, "pre-rebase"),
             Self::Manual => write!(f, "manual"),
+            Self::Auto => write!(f, "auto"),
         }
     }
 }
@@ -149,27 +166,64 @@ pub struct FileSnapshot {
     pub content: Option<String>,
 }
 
+/// zstd level for snapshot blobs (fast, still ~4x on source files)
+const BLOB_COMPRESSION_LEVEL: i32 = 3;
+
+/// How long to wait for another process writing to the snapshot store
+const STORE_LOCK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A store lock this old was left behind by a process that died holding it
+const STORE_LOCK_STALE: Duration = Duration::from_secs(600);
+
 /// Manages snapshots for code protection
+///
+/// File contents live in a content-addressed store: each distinct content is
+/// written once to `objects/<first 2 hash chars>/<rest of hash>`, zstd
+/// compressed, no matter how many snapshots reference it.
+///
+/// Writers hold `store.lock` while adding blobs and snapshots or collecting
+/// garbage, so a `protect watch` snapshot and a hook snapshot running at the
+/// same time never collect each other's blobs before their JSON is written.
 pub struct SnapshotManager {
     config: ProtectionConfig,
     repo: GitRepo,
     snapshots_dir: PathBuf,
-    content_dir: PathBuf,
+    objects_dir: PathBuf,
+    /// Uncompressed store used before blobs were compressed, read for old snapshots
+    legacy_content_dir: PathBuf,
     index_file: PathBuf,
+    lock_file: PathBuf,
+}
+
+/// Exclusive hold on the snapshot store, released on drop
+pub(crate) struct StoreLock {
+    path: PathBuf,
+}
+
+impl Drop for StoreLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
 }
 
 impl SnapshotManager {
-    /// Create a new snapshot manager
+    /// Create a new snapshot manager for the repository in the current directory
     pub fn new(config: ProtectionConfig) -> Result<Self> {
-        let repo = GitRepo::open_current()?;
+        Self::open(config, GitRepo::open_current()?)
+    }
+
+    /// Create a snapshot manager for `repo`
+    pub fn open(config: ProtectionConfig, repo: GitRepo) -> Result<Self> {
         let data_dir = repo.workdir().join(&config.data_dir);
         let snapshots_dir = data_dir.join("snapshots");
-        let content_dir = data_dir.join("content");
+        let objects_dir = data_dir.join("objects");
+        let legacy_content_dir = data_dir.join("content");
         let index_file = data_dir.join("snapshot-index.json");
+        let lock_file = data_dir.join("store.lock");
 
         // Ensure directories exist
         fs::create_dir_all(&snapshots_dir)?;
-        fs::create_dir_all(&content_dir)?;
+        fs::create_dir_all(&objects_dir)?;
 
         // Add to .gitignore if not already
         Self::ensure_gitignore(&repo, &config.data_dir)?;
@@ -178,11 +232,47 @@ impl SnapshotManager {
             config,
             repo,
             snapshots_dir,
-            content_dir,
+            objects_dir,
+            legacy_content_dir,
             index_file,
+            lock_file,
         })
     }
 
+    /// Take the store lock, waiting for another writer to finish
+    pub(crate) fn lock_store(&self) -> Result<StoreLock> {
+        let start = Instant::now();
+        loop {
+            match fs::OpenOptions::new().write(true).create_new(true).open(&self.lock_file) {
+                Ok(mut file) => {
+                    let _ = writeln!(file, "{}", std::process::id());
+                    return Ok(StoreLock {
+                        path: self.lock_file.clone(),
+                    });
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    let age = fs::metadata(&self.lock_file)
+                        .and_then(|m| m.modified())
+                        .ok()
+                        .and_then(|modified| modified.elapsed().ok());
+                    if age.is_some_and(|age| age > STORE_LOCK_STALE) {
+                        let _ = fs::remove_file(&self.lock_file);
+                        continue;
+                    }
+                    if start.elapsed() > STORE_LOCK_TIMEOUT {
+                        return Err(Error::io(format!(
+                            "Snapshot store is locked by another process ({})",
+                            self.lock_file.display()
+                        ))
+                        .with_suggestion("Remove the lock file if no other foodshare-ios is running"));
+                    }
+                    std::thread::sleep(Duration::from_millis(20));
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
     /// Ensure the data directory is in .gitignore
     fn ensure_gitignore(repo: &GitRepo, data_dir: &Path) -> Result<()> {
         let gitignore = repo.workdir().join(".gitignore");
@@ -205,11 +295,17 @@ impl SnapshotManager {
         trigger: SnapshotTrigger,
         description: &str,
     ) -> Result<Snapshot> {
-        let id = generate_snapshot_id();
-        let timestamp = Utc::now();
         let branch = self.repo.current_branch().unwrap_or_else(|_| "unknown".to_string());
         let commit = get_head_commit().unwrap_or_else(|_| "unknown".to_string());
 
+        let _lock = self.lock_store()?;
+        // IDs have millisecond resolution; another writer may have just taken this one
+        let mut id = generate_snapshot_id();
+        while self.snapshots_dir.join(format!("{}.json", id)).exists() {
+            std::thread::sleep(Duration::from_millis(1));
+            id = generate_snapshot_id();
+        }
+        let timestamp = Utc::now();
         let mut file_snapshots = Vec::new();
 
         for file in files {
@@ -218,16 +314,10 @@ impl SnapshotManager {
                 continue;
             }
 
-            let content = fs::read_to_string(&full_path)?;
-            let hash = compute_hash(&content);
+            let content = fs::read(&full_path)?;
+            let hash = self.write_blob(&content)?;
             let size = content.len() as u64;
 
-            // Store content by hash (deduplication)
-            let content_file = self.content_dir.join(&hash);
-            if !content_file.exists() {
-                fs::write(&content_file, &content)?;
-            }
-
             file_snapshots.push(FileSnapshot {
                 path: file.clone(),
                 hash,
@@ -246,23 +336,77 @@ impl SnapshotManager {
             commit,
         };
 
-        // Save snapshot metadata
-        let snapshot_file = self.snapshots_dir.join(format!("{}.json", id));
-        let json = serde_json::to_string_pretty(&snapshot)?;
+        self.save_snapshot(&snapshot)?;
+
+        // Drop snapshots beyond the retention limit and their blobs
+        self.collect_garbage()?;
+
+        Ok(snapshot)
+    }
+
+    /// Write snapshot metadata and add it to the index
+    ///
+    /// The snapshot's blobs must already be in the store, written under the
+    /// same store lock.
+    pub(crate) fn save_snapshot(&self, snapshot: &Snapshot) -> Result<()> {
+        let snapshot_file = self.snapshots_dir.join(format!("{}.json", snapshot.id));
+        let json = serde_json::to_string_pretty(snapshot)?;
         fs::write(&snapshot_file, json)?;
+        self.update_index(snapshot)
+    }
 
-        // Update index
-        self.update_index(&snapshot)?;
+    /// Repository working directory
+    pub(crate) fn workdir(&self) -> &Path {
+        self.repo.workdir()
+    }
 
-        // Cleanup old snapshots
-        self.cleanup_old_snapshots()?;
+    /// Whether content with this hash is in the store
+    pub(crate) fn has_blob(&self, hash: &str) -> bool {
+        self.blob_path(hash).exists() || self.legacy_content_dir.join(hash).exists()
+    }
 
-        Ok(snapshot)
+    /// Path of the blob for a content hash
+    fn blob_path(&self, hash: &str) -> PathBuf {
+        let (shard, rest) = hash.split_at(hash.len().min(2));
+        self.objects_dir.join(shard).join(rest)
+    }
+
+    /// Store content in the blob store, returning its hash
+    ///
+    /// Callers hold the store lock until a snapshot referencing the blob is saved.
+    pub(crate) fn write_blob(&self, content: &[u8]) -> Result<String> {
+        let hash = compute_hash(content);
+        let path = self.blob_path(&hash);
+        if !path.exists() {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            // Write then rename, so an interrupted write never leaves a truncated blob
+            let compressed = zstd::encode_all(content, BLOB_COMPRESSION_LEVEL)?;
+            let temp = path.with_extension("tmp");
+            fs::write(&temp, compressed)?;
+            fs::rename(&temp, &path)?;
+        }
+        Ok(hash)
+    }
+
+    /// Read content from the blob store
+    pub(crate) fn read_blob(&self, hash: &str) -> Result<Option<Vec<u8>>> {
+        let path = self.blob_path(hash);
+        if path.exists() {
+            return Ok(Some(zstd::decode_all(fs::File::open(&path)?)?));
+        }
+        let legacy = self.legacy_content_dir.join(hash);
+        if legacy.exists() {
+            return Ok(Some(fs::read(&legacy)?));
+        }
+        Ok(None)
     }
 
     /// Update the snapshot index
     fn update_index(&self, snapshot: &Snapshot) -> Result<()> {
         let mut index = self.load_index()?;
+        index.retain(|entry| entry.id != snapshot.id);
         index.push(SnapshotIndexEntry {
             id: snapshot.id.clone(),
             timestamp: snapshot.timestamp,
@@ -286,30 +430,86 @@ impl SnapshotManager {
         }
     }
 
-    /// Cleanup old snapshots beyond the retention limit
-    fn cleanup_old_snapshots(&self) -> Result<()> {
+    /// Remove snapshots beyond `max_snapshots` and blobs no snapshot references
+    ///
+    /// Blobs in the old uncompressed store that are still referenced are
+    /// compressed into the blob store; the old store is then removed.
+    pub fn gc(&self) -> Result<GcResult> {
+        let _lock = self.lock_store()?;
+        self.collect_garbage()
+    }
+
+    /// [`Self::gc`] with the store lock already held
+    fn collect_garbage(&self) -> Result<GcResult> {
+        let size_before = dir_size(&self.objects_dir) + dir_size(&self.legacy_content_dir);
+        let mut result = GcResult::default();
+
         let mut index = self.load_index()?;
+        if index.len() > self.config.max_snapshots {
+            // Sort by timestamp (oldest first)
+            index.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+
+            // Remove oldest snapshots
+            let to_remove = index.len() - self.config.max_snapshots;
+            for entry in index.iter().take(to_remove) {
+                let snapshot_file = self.snapshots_dir.join(format!("{}.json", entry.id));
+                let _ = fs::remove_file(snapshot_file);
+            }
+            result.snapshots_removed = to_remove;
 
-        if index.len() <= self.config.max_snapshots {
-            return Ok(());
+            // Update index
+            let remaining: Vec<_> = index.into_iter().skip(to_remove).collect();
+            let json = serde_json::to_string_pretty(&remaining)?;
+            fs::write(&self.index_file, json)?;
         }
 
-        // Sort by timestamp (oldest first)
-        index.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        // Every snapshot file on disk counts, indexed or not. A snapshot that
+        // can't be read aborts the sweep rather than losing its blobs.
+        let mut referenced = HashSet::new();
+        for entry in fs::read_dir(&self.snapshots_dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                let snapshot: Snapshot = serde_json::from_str(&fs::read_to_string(&path)?)?;
+                referenced.extend(snapshot.files.into_iter().map(|f| f.hash));
+            }
+        }
 
-        // Remove oldest snapshots
-        let to_remove = index.len() - self.config.max_snapshots;
-        for entry in index.iter().take(to_remove) {
-            let snapshot_file = self.snapshots_dir.join(format!("{}.json", entry.id));
-            let _ = fs::remove_file(snapshot_file);
+        for shard in fs::read_dir(&self.objects_dir)? {
+            let shard = shard?.path();
+            if !shard.is_dir() {
+                continue;
+            }
+            let prefix = shard.file_name().unwrap_or_default().to_string_lossy().into_owned();
+            for blob in fs::read_dir(&shard)? {
+                let blob = blob?.path();
+                let hash = format!("{}{}", prefix, blob.file_name().unwrap_or_default().to_string_lossy());
+                if !referenced.contains(&hash) {
+                    fs::remove_file(&blob)?;
+                    result.blobs_removed += 1;
+                }
+            }
+            // Only succeeds once the shard is empty
+            let _ = fs::remove_dir(&shard);
         }
 
-        // Update index
-        let remaining: Vec<_> = index.into_iter().skip(to_remove).collect();
-        let json = serde_json::to_string_pretty(&remaining)?;
-        fs::write(&self.index_file, json)?;
+        if self.legacy_content_dir.exists() {
+            for entry in fs::read_dir(&self.legacy_content_dir)? {
+                let path = entry?.path();
+                let hash = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+                if referenced.contains(&hash) {
+                    self.write_blob(&fs::read(&path)?)?;
+                } else {
+                    result.blobs_removed += 1;
+                }
+                fs::remove_file(&path)?;
+            }
+            fs::remove_dir(&self.legacy_content_dir)?;
+        }
 
-        Ok(())
+        let size_after = dir_size(&self.objects_dir);
+        result.bytes_freed = size_before.saturating_sub(size_after);
+        result.store_bytes = size_after;
+        Ok(result)
     }
 
     /// List all snapshots
@@ -363,21 +563,17 @@ impl SnapshotManager {
             }
 
             let full_path = self.repo.workdir().join(&file_snap.path);
-            let content_file = self.content_dir.join(&file_snap.hash);
-
-            if !content_file.exists() {
+            let Some(content) = self.read_blob(&file_snap.hash)? else {
                 result.failed_files.push((
                     file_snap.path.clone(),
                     "Content not found in store".to_string(),
                 ));
                 continue;
-            }
-
-            let content = fs::read_to_string(&content_file)?;
+            };
 
             // Check if file has changed
             if full_path.exists() {
-                let current = fs::read_to_string(&full_path)?;
+                let current = fs::read(&full_path)?;
                 if current == content {
                     result.skipped_files.push(file_snap.path.clone());
                     continue;
@@ -409,14 +605,141 @@ impl SnapshotManager {
     pub fn get_file_content(&self, snapshot: &Snapshot, path: &Path) -> Result<Option<String>> {
         for file_snap in &snapshot.files {
             if file_snap.path == path {
-                let content_file = self.content_dir.join(&file_snap.hash);
-                if content_file.exists() {
-                    return Ok(Some(fs::read_to_string(&content_file)?));
+                if let Some(content) = self.read_blob(&file_snap.hash)? {
+                    return Ok(Some(String::from_utf8_lossy(&content).into_owned()));
                 }
             }
         }
         Ok(None)
     }
+
+    /// Content of a file in a snapshot, which must be in the store
+    fn read_snapshot_blob(&self, snapshot: &Snapshot, file: &FileSnapshot) -> Result<Vec<u8>> {
+        self.read_blob(&file.hash)?.ok_or_else(|| {
+            Error::io(format!(
+                "Content of {} in snapshot {} is missing from the store",
+                file.path.display(),
+                snapshot.id
+            ))
+        })
+    }
+
+    /// Diff a snapshot's files against another snapshot or the working tree
+    ///
+    /// Diffs go from `against` to `snapshot`: they show what restoring
+    /// `snapshot` would change. Only files in `snapshot` are compared, and
+    /// unchanged files are left out.
+    pub fn diff(
+        &self,
+        snapshot: &Snapshot,
+        against: &DiffTarget,
+        files: Option<&[PathBuf]>,
+    ) -> Result<Vec<FileDiff>> {
+        let against_label = match against {
+            DiffTarget::Snapshot(other) => other.id.as_str(),
+            DiffTarget::Worktree => "worktree",
+        };
+
+        let mut diffs = Vec::new();
+        for file_snap in &snapshot.files {
+            if let Some(requested) = files {
+                if !requested.iter().any(|p| p == &file_snap.path) {
+                    continue;
+                }
+            }
+
+            let new = self.read_snapshot_blob(snapshot, file_snap)?;
+            let old = match against {
+                DiffTarget::Snapshot(other) => match other.files.iter().find(|f| f.path == file_snap.path) {
+                    Some(f) if f.hash == file_snap.hash => continue,
+                    // Not `None`: that would show a file whose content is lost as added
+                    Some(f) => Some(self.read_snapshot_blob(other, f)?),
+                    None => None,
+                },
+                DiffTarget::Worktree => {
+                    let full_path = self.repo.workdir().join(&file_snap.path);
+                    if full_path.exists() { Some(fs::read(&full_path)?) } else { None }
+                }
+            };
+
+            if old.as_deref() == Some(new.as_slice()) {
+                continue;
+            }
+            let path = file_snap.path.display();
+            diffs.push(FileDiff::new(
+                file_snap.path.clone(),
+                old.as_deref(),
+                &new,
+                &format!("{}/{}", against_label, path),
+                &format!("{}/{}", snapshot.id, path),
+            ));
+        }
+        Ok(diffs)
+    }
+}
+
+/// What [`SnapshotManager::diff`] compares a snapshot against
+pub enum DiffTarget {
+    /// Another snapshot
+    Snapshot(Snapshot),
+    /// Current files on disk
+    Worktree,
+}
+
+/// Differences in one file between two versions
+#[derive(Debug)]
+pub struct FileDiff {
+    /// Relative path to file
+    pub path: PathBuf,
+    /// Change going from the compared version to the snapshot
+    pub change_type: ChangeType,
+    /// Lines only in the snapshot
+    pub insertions: usize,
+    /// Lines only in the compared version
+    pub deletions: usize,
+    /// Unified diff, empty for binary files
+    pub unified: String,
+}
+
+impl FileDiff {
+    fn new(path: PathBuf, old: Option<&[u8]>, new: &[u8], old_label: &str, new_label: &str) -> Self {
+        let change_type = if old.is_some() { ChangeType::Modified } else { ChangeType::Added };
+        let (Ok(old_text), Ok(new_text)) = (std::str::from_utf8(old.unwrap_or_default()), std::str::from_utf8(new))
+        else {
+            return Self {
+                path,
+                change_type,
+                insertions: 0,
+                deletions: 0,
+                unified: String::new(),
+            };
+        };
+
+        let old_label = if old.is_some() { old_label } else { "/dev/null" };
+        let diff = Diff::new(old_text, new_text).header(old_label, new_label);
+        let stats = diff.stats();
+
+        Self {
+            path,
+            change_type,
+            insertions: stats.insertions,
+            deletions: stats.deletions,
+            unified: diff.unified(),
+        }
+    }
+}
+
+/// Result of [`SnapshotManager::gc`]
+#[derive(Debug, Default)]
+pub struct GcResult {
+    /// Snapshots dropped by the retention limit
+    pub snapshots_removed: usize,
+    /// Blobs no remaining snapshot referenced
+    pub blobs_removed: usize,
+    /// Bytes reclaimed from the store
+    pub bytes_freed: u64,
+    /// Size of the blob store after collection
+    pub store_bytes: u64,
 }
 
 /// Index entry for quick snapshot lookup
@@ -438,17 +761,130 @@ pub struct RestoreResult {
     pub dry_run: bool,
 }
 
+// ============================================================================
+// AUTO SNAPSHOTS
+// ============================================================================
+
+/// Decides when a burst of file changes should become a snapshot
+///
+/// A snapshot is due once no change has arrived for `quiet_period`, and at
+/// least `min_interval` has passed since the previous auto snapshot. Changes
+/// arriving in between accumulate into the next snapshot.
+#[derive(Debug)]
+pub struct AutoSnapshotThrottle {
+    quiet_period: Duration,
+    min_interval: Duration,
+    pending: std::collections::BTreeSet<PathBuf>,
+    last_change: Option<Instant>,
+    last_snapshot: Option<Instant>,
+}
+
+impl AutoSnapshotThrottle {
+    /// Create a throttle
+    pub fn new(quiet_period: Duration, min_interval: Duration) -> Self {
+        Self {
+            quiet_period,
+            min_interval,
+            pending: Default::default(),
+            last_change: None,
+            last_snapshot: None,
+        }
+    }
+
+    /// Record a changed file
+    pub fn record(&mut self, path: PathBuf, now: Instant) {
+        self.pending.insert(path);
+        self.last_change = Some(now);
+    }
+
+    /// Files to snapshot now, if a snapshot is due
+    pub fn take_due(&mut self, now: Instant) -> Option<Vec<PathBuf>> {
+        let last_change = self.last_change?;
+        if now.duration_since(last_change) < self.quiet_period {
+            return None;
+        }
+        if self.last_snapshot.is_some_and(|last| now.duration_since(last) < self.min_interval) {
+            return None;
+        }
+        self.last_change = None;
+        self.last_snapshot = Some(now);
+        Some(std::mem::take(&mut self.pending).into_iter().collect())
+    }
+}
+
+/// Watch the repository and snapshot files matching `patterns` as they change
+///
+/// Runs until the watcher fails or the process is interrupted. Snapshots use
+/// [`SnapshotTrigger::Auto`] and go through the normal retention policy.
+pub fn watch_for_changes(
+    manager: &SnapshotManager,
+    patterns: &[String],
+    throttle: &mut AutoSnapshotThrottle,
+    mut on_snapshot: impl FnMut(&Snapshot),
+) -> Result<()> {
+    use notify::{RecursiveMode, Watcher};
+
+    let workdir = manager.workdir().to_path_buf();
+    let data_dir = workdir.join(&manager.config.data_dir);
+    let (tx, rx) = std::sync::mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)
+        .map_err(|e| Error::io(format!("Failed to start file watcher: {}", e)))?;
+    watcher
+        .watch(&workdir, RecursiveMode::Recursive)
+        .map_err(|e| Error::io(format!("Failed to watch {}: {}", workdir.display(), e)))?;
+
+    loop {
+        match rx.recv_timeout(Duration::from_millis(250)) {
+            Ok(Ok(event)) => {
+                if matches!(event.kind, notify::EventKind::Access(_)) {
+                    continue;
+                }
+                for path in event.paths {
+                    if path.starts_with(&data_dir) || path.components().any(|c| c.as_os_str() == ".git") {
+                        continue;
+                    }
+                    let Ok(relative) = path.strip_prefix(&workdir) else {
+                        continue;
+                    };
+                    let excluded = manager
+                        .config
+                        .exclude_patterns
+                        .iter()
+                        .any(|pattern| relative.components().any(|c| c.as_os_str() == pattern.as_str()));
+                    if !excluded && is_protected(relative, patterns) {
+                        throttle.record(relative.to_path_buf(), Instant::now());
+                    }
+                }
+            }
+            Ok(Err(e)) => return Err(Error::io(format!("File watcher error: {}", e))),
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {}
+            Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => return Ok(()),
+        }
+
+        if let Some(files) = throttle.take_due(Instant::now()) {
+            // Deleted files have nothing to keep
+            let files: Vec<PathBuf> = files.into_iter().filter(|f| workdir.join(f).is_file()).collect();
+            if files.is_empty() {
+                continue;
+            }
+            let description = format!("Auto: {} file(s) changed", files.len());
+            let snapshot = manager.create_snapshot(&files, SnapshotTrigger::Auto, &description)?;
+            on_snapshot(&snapshot);
+        }
+    }
+}
+
 // ============================================================================
 // BUILD VERIFICATION
 // ============================================================================
 
 /// Verify that code still builds after modifications
-pub fn verify_build(quick: bool) -> Result<BuildVerification> {
+///
+/// With `use_cache`, the build is skipped when the sources and settings are
+/// unchanged since the last successful verification.
+pub fn verify_build(quick: bool, use_cache: bool) -> Result<BuildVerification> {
     let start = Instant::now();
 
-    print!("  {} Verifying build...", "●".blue());
-    io::stdout().flush().ok();
-
     let args = if quick {
         // Quick syntax check only
         vec![
@@ -468,13 +904,43 @@ pub fn verify_build(quick: bool) -> Result<BuildVerification> {
         ]
     };
 
+    let root = std::env::current_dir()?;
+    let cache = BuildCache::open(&root);
+    let key = if use_cache {
+        let mut settings = vec!["verify-build"];
+        settings.extend_from_slice(&args);
+        Some(source_hash(&root, &settings)?)
+    } else {
+        None
+    };
+
+    if key.as_deref().is_some_and(|key| cache.is_fresh(key)) {
+        textln!(
+            "  {} Build verification passed {}",
+            "✓".green(),
+            "(cached: sources unchanged)".dimmed()
+        );
+        return Ok(BuildVerification {
+            success: true,
+            duration: start.elapsed(),
+            errors: Vec::new(),
+            cached: true,
+        });
+    }
+
+    text!("  {} Verifying build...", "●".blue());
+    io::stdout().flush().ok();
+
     let result = run_command("xcodebuild", &args)?;
     let duration = start.elapsed();
 
-    print!("\r");
+    text!("\r");
 
     if result.success {
-        println!(
+        if let Some(key) = &key {
+            cache.record(key, None)?;
+        }
+        textln!(
             "  {} Build verification passed {}",
             "✓".green(),
             format!("({:.1}s)", duration.as_secs_f32()).dimmed()
@@ -483,9 +949,10 @@ pub fn verify_build(quick: bool) -> Result<BuildVerification> {
             success: true,
             duration,
             errors: Vec::new(),
+            cached: false,
         })
     } else {
-        println!(
+        textln!(
             "  {} Build verification FAILED {}",
             "✗".red(),
             format!("({:.1}s)", duration.as_secs_f32()).dimmed()
@@ -503,6 +970,7 @@ pub fn verify_build(quick: bool) -> Result<BuildVerification> {
             success: false,
             duration,
             errors,
+            cached: false,
         })
     }
 }
@@ -513,6 +981,8 @@ pub struct BuildVerification {
     pub success: bool,
     pub duration: Duration,
     pub errors: Vec<String>,
+    /// Skipped because the last successful build had the same source hash
+    pub cached: bool,
 }
 
 // ============================================================================
@@ -521,11 +991,11 @@ pub struct BuildVerification {
 
 /// Show changes and get user approval
 pub fn get_interactive_approval(changes: &[FileChange]) -> Result<ApprovalDecision> {
-    println!();
-    println!("{}", "═".repeat(60));
-    println!("{}", "Changes requiring approval:".bold());
-    println!("{}", "═".repeat(60));
-    println!();
+    textln!();
+    textln!("{}", "═".repeat(60));
+    textln!("{}", "Changes requiring approval:".bold());
+    textln!("{}", "═".repeat(60));
+    textln!();
 
     for change in changes {
         let change_marker = match change.change_type {
@@ -533,7 +1003,7 @@ pub fn get_interactive_approval(changes: &[FileChange]) -> Result<ApprovalDecisi
             ChangeType::Added => "A".green().to_string(),
             ChangeType::Deleted => "D".red().to_string(),
         };
-        println!(
+        textln!(
             "  {} {} ({} → {})",
             change_marker,
             change.path.display(),
@@ -545,29 +1015,28 @@ pub fn get_interactive_approval(changes: &[FileChange]) -> Result<ApprovalDecisi
         if !change.preview.is_empty() {
             for line in change.preview.iter().take(5) {
                 if line.starts_with('+') {
-                    println!("    {}", line.green());
+                    textln!("    {}", line.green());
                 } else if line.starts_with('-') {
-                    println!("    {}", line.red());
+                    textln!("    {}", line.red());
                 } else {
-                    println!("    {}", line.dimmed());
+                    textln!("    {}", line.dimmed());
                 }
             }
             if change.preview.len() > 5 {
-                println!("    {} more lines...", format!("... {} ", change.preview.len() - 5).dimmed());
+                textln!("    {} more lines...", format!("... {} ", change.preview.len() - 5).dimmed());
             }
         }
-        println!();
+        textln!();
     }
 
-    println!("{}", "─".repeat(60));
-    print!(
+    textln!("{}", "─".repeat(60));
+    text!(
         "{}",
         "Apply these changes? [y]es / [n]o / [p]review all / [r]evert: ".bold()
     );
     io::stdout().flush().ok();
 
-    let mut input = String::new();
-    io::stdin().read_line(&mut input)?;
+    let input = read_answer()?;
 
     match input.trim().to_lowercase().as_str() {
         "y" | "yes" => Ok(ApprovalDecision::Approve),
@@ -578,6 +1047,24 @@ pub fn get_interactive_approval(changes: &[FileChange]) -> Result<ApprovalDecisi
     }
 }
 
+/// Read a line from the terminal
+///
+/// Git hooks may run with stdin redirected, so fall back to the controlling
+/// terminal. Fails when there is none (CI, GUI git clients).
+fn read_answer() -> Result<String> {
+    use std::io::{BufRead, IsTerminal};
+
+    let mut input = String::new();
+    if io::stdin().is_terminal() {
+        io::stdin().read_line(&mut input)?;
+    } else {
+        let tty = fs::File::open("/dev/tty")
+            .map_err(|_| Error::validation("No terminal available for interactive approval"))?;
+        io::BufReader::new(tty).read_line(&mut input)?;
+    }
+    Ok(input)
+}
+
 /// A file change for approval
 #[derive(Debug)]
 pub struct FileChange {
@@ -605,6 +1092,143 @@ pub enum ApprovalDecision {
     Revert,
 }
 
+// ============================================================================
+// PROTECTED PATH APPROVAL
+// ============================================================================
+
+/// Environment variable that approves protected changes without a prompt (CI)
+pub const APPROVE_ENV: &str = "FOODSHARE_APPROVE";
+
+/// Whether a path matches one of the protected patterns
+///
+/// Patterns are globs matched against the file name (`Info.plist`,
+/// `*.entitlements`) and against the repository-relative path
+/// (`FoodShare/Config/*.xcconfig`).
+pub fn is_protected(path: &Path, patterns: &[String]) -> bool {
+    let file_name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+    patterns.iter().any(|pattern| {
+        glob::Pattern::new(pattern)
+            .map(|p| p.matches(&file_name) || p.matches_path(path))
+            .unwrap_or(false)
+    })
+}
+
+/// Staged changes under protected paths, with their diffs as previews
+pub fn staged_protected_changes(config: &ProtectionConfig) -> Result<Vec<FileChange>> {
+    let result = run_command("git", &["diff", "--cached", "--numstat", "--no-renames"])?;
+    let mut changes = Vec::new();
+
+    for line in result.stdout.lines() {
+        let mut parts = line.splitn(3, '\t');
+        let (Some(insertions), Some(deletions), Some(path)) = (parts.next(), parts.next(), parts.next()) else {
+            continue;
+        };
+        let path = PathBuf::from(path);
+        if !is_protected(&path, &config.protected_paths) {
+            continue;
+        }
+
+        let status = run_command(
+            "git",
+            &["diff", "--cached", "--name-status", "--", &path.to_string_lossy()],
+        )?;
+        let change_type = match status.stdout.chars().next() {
+            Some('A') => ChangeType::Added,
+            Some('D') => ChangeType::Deleted,
+            _ => ChangeType::Modified,
+        };
+        let diff = run_command("git", &["diff", "--cached", "--", &path.to_string_lossy()])?;
+
+        changes.push(FileChange {
+            path,
+            change_type,
+            // Binary files report "-"
+            insertions: insertions.parse().unwrap_or(0),
+            deletions: deletions.parse().unwrap_or(0),
+            preview: diff_body(&diff.stdout),
+        });
+    }
+
+    Ok(changes)
+}
+
+/// Diff lines after the file header
+fn diff_body(diff: &str) -> Vec<String> {
+    diff.lines()
+        .skip_while(|line| !line.starts_with("@@") && !line.starts_with("Binary files"))
+        .map(String::from)
+        .collect()
+}
+
+/// Outcome of [`approve_protected_changes`]
+#[derive(Debug, PartialEq)]
+pub enum ApprovalOutcome {
+    /// Approved at the prompt
+    Approved,
+    /// Approved through [`APPROVE_ENV`]
+    ApprovedByEnv,
+    /// Rejected at the prompt
+    Rejected,
+    /// The protected files were unstaged
+    Unstaged,
+    /// No terminal to ask and no [`APPROVE_ENV`]
+    NoTerminal,
+}
+
+impl ApprovalOutcome {
+    /// Whether the commit may proceed
+    pub fn allows_commit(&self) -> bool {
+        matches!(self, Self::Approved | Self::ApprovedByEnv)
+    }
+}
+
+/// Ask for confirmation of staged protected changes
+///
+/// `[p]review all` prints the full diffs and asks again; `[r]evert`
+/// unstages the protected files, leaving the working tree untouched.
+pub fn approve_protected_changes(changes: &[FileChange]) -> Result<ApprovalOutcome> {
+    if std::env::var(APPROVE_ENV).is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true")) {
+        return Ok(ApprovalOutcome::ApprovedByEnv);
+    }
+    if !io::IsTerminal::is_terminal(&io::stdin()) && fs::File::open("/dev/tty").is_err() {
+        return Ok(ApprovalOutcome::NoTerminal);
+    }
+
+    loop {
+        match get_interactive_approval(changes)? {
+            ApprovalDecision::Approve => return Ok(ApprovalOutcome::Approved),
+            ApprovalDecision::Reject => return Ok(ApprovalOutcome::Rejected),
+            ApprovalDecision::PreviewAll => {
+                for change in changes {
+                    textln!();
+                    textln!("{}", change.path.display().to_string().bold());
+                    for line in &change.preview {
+                        if line.starts_with('+') {
+                            textln!("{}", line.green());
+                        } else if line.starts_with('-') {
+                            textln!("{}", line.red());
+                        } else if line.starts_with("@@") {
+                            textln!("{}", line.cyan());
+                        } else {
+                            textln!("{}", line);
+                        }
+                    }
+                }
+            }
+            ApprovalDecision::Revert => {
+                let mut args = vec!["restore".to_string(), "--staged".to_string(), "--".to_string()];
+                args.extend(changes.iter().map(|c| c.path.to_string_lossy().into_owned()));
+                let args: Vec<&str> = args.iter().map(String::as_str).collect();
+                let result = run_command("git", &args)?;
+                if !result.success {
+                    return Err(Error::git(format!("Failed to unstage: {}", result.stderr.trim())));
+                }
+                return Ok(ApprovalOutcome::Unstaged);
+            }
+        }
+    }
+}
+
 // ============================================================================
 // COMMIT GUARD
 // ============================================================================
@@ -857,6 +1481,22 @@ pub struct OperationRecord {
     pub details: String,
 }
 
+impl OperationRecord {
+    /// Record of an operation that happened now, without a snapshot
+    pub fn new(operation: OperationType, affected_files: Vec<PathBuf>, success: bool, details: impl Into<String>) -> Self {
+        let timestamp = Utc::now();
+        Self {
+            id: format!("op-{:x}", timestamp.timestamp_millis()),
+            timestamp,
+            operation,
+            affected_files,
+            snapshot_id: None,
+            success,
+            details: details.into(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum OperationType {
     Format,
@@ -941,12 +1581,23 @@ fn generate_snapshot_id() -> String {
 }
 
 /// Compute SHA256 hash of content
-fn compute_hash(content: &str) -> String {
+fn compute_hash(content: impl AsRef<[u8]>) -> String {
     let mut hasher = Sha256::new();
-    hasher.update(content.as_bytes());
+    hasher.update(content.as_ref());
     format!("{:x}", hasher.finalize())
 }
 
+/// Total size of the files under a directory
+fn dir_size(dir: &Path) -> u64 {
+    walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.metadata().ok())
+        .filter(|m| m.is_file())
+        .map(|m| m.len())
+        .sum()
+}
+
 /// Get HEAD commit hash
 fn get_head_commit() -> Result<String> {
     let result = run_command("git", &["rev-parse", "HEAD"])?;
@@ -959,13 +1610,13 @@ fn get_head_commit() -> Result<String> {
 
 /// Print pending commit info
 pub fn print_pending_commit(pending: &PendingCommit) {
-    println!();
-    println!("{}", "═".repeat(60));
-    println!("{}", "COMMIT GUARD - What will be committed:".bold());
-    println!("{}", "═".repeat(60));
-    println!();
-    println!("  Branch: {}", pending.branch.cyan());
-    println!();
+    textln!();
+    textln!("{}", "═".repeat(60));
+    textln!("{}", "COMMIT GUARD - What will be committed:".bold());
+    textln!("{}", "═".repeat(60));
+    textln!();
+    textln!("  Branch: {}", pending.branch.cyan());
+    textln!();
 
     for file in &pending.files {
         let status_marker = if file.is_new {
@@ -973,7 +1624,7 @@ pub fn print_pending_commit(pending: &PendingCommit) {
         } else {
             "M".yellow().to_string()
         };
-        println!(
+        textln!(
             "  {} {} ({}, {})",
             status_marker,
             file.path.display(),
@@ -982,30 +1633,30 @@ pub fn print_pending_commit(pending: &PendingCommit) {
         );
     }
 
-    println!();
-    println!(
+    textln!();
+    textln!(
         "  Total: {} files, {}, {}",
         pending.files.len(),
         format!("+{}", pending.total_insertions).green(),
         format!("-{}", pending.total_deletions).red()
     );
-    println!("{}", "═".repeat(60));
+    textln!("{}", "═".repeat(60));
 }
 
 /// Print pending push info
 pub fn print_pending_push(pending: &PendingPush) {
-    println!();
-    println!("{}", "═".repeat(60));
-    println!("{}", "PUSH GUARD - What will be pushed:".bold());
-    println!("{}", "═".repeat(60));
-    println!();
-    println!(
+    textln!();
+    textln!("{}", "═".repeat(60));
+    textln!("{}", "PUSH GUARD - What will be pushed:".bold());
+    textln!("{}", "═".repeat(60));
+    textln!();
+    textln!(
         "  Target: {}/{}",
         pending.remote.cyan(),
         pending.branch.cyan()
     );
-    println!("  Commits: {}", pending.commits.len());
-    println!();
+    textln!("  Commits: {}", pending.commits.len());
+    textln!();
 
     for commit in pending.commits.iter().take(10) {
         let short_hash = if commit.hash.len() >= 7 {
@@ -1013,48 +1664,48 @@ pub fn print_pending_push(pending: &PendingPush) {
         } else {
             &commit.hash
         };
-        println!("    {} {}", short_hash.yellow(), commit.message);
+        textln!("    {} {}", short_hash.yellow(), commit.message);
     }
     if pending.commits.len() > 10 {
-        println!("    ... and {} more commits", pending.commits.len() - 10);
+        textln!("    ... and {} more commits", pending.commits.len() - 10);
     }
 
-    println!();
-    println!(
+    textln!();
+    textln!(
         "  Total: {} files, {}, {}",
         pending.files_changed,
         format!("+{}", pending.insertions).green(),
         format!("-{}", pending.deletions).red()
     );
-    println!("{}", "═".repeat(60));
+    textln!("{}", "═".repeat(60));
 }
 
 /// Print snapshot list
 pub fn print_snapshot_list(snapshots: &[SnapshotIndexEntry]) {
-    println!();
-    println!("{}", "═".repeat(70));
-    println!("{}", "CODE PROTECTION SNAPSHOTS".bold());
-    println!("{}", "═".repeat(70));
-    println!();
+    textln!();
+    textln!("{}", "═".repeat(70));
+    textln!("{}", "CODE PROTECTION SNAPSHOTS".bold());
+    textln!("{}", "═".repeat(70));
+    textln!();
 
     if snapshots.is_empty() {
-        println!("  No snapshots found.");
-        println!();
+        textln!("  No snapshots found.");
+        textln!();
         return;
     }
 
-    println!(
+    textln!(
         "  {:<20} {:<12} {:<8} {}",
         "ID".bold(),
         "TRIGGER".bold(),
         "FILES".bold(),
         "TIMESTAMP".bold()
     );
-    println!("  {}", "─".repeat(66));
+    textln!("  {}", "─".repeat(66));
 
     for snap in snapshots.iter().take(20) {
         let local_time: DateTime<Local> = snap.timestamp.into();
-        println!(
+        textln!(
             "  {:<20} {:<12} {:<8} {}",
             snap.id.cyan(),
             snap.trigger.to_string(),
@@ -1064,58 +1715,84 @@ pub fn print_snapshot_list(snapshots: &[SnapshotIndexEntry]) {
     }
 
     if snapshots.len() > 20 {
-        println!("  ... and {} more snapshots", snapshots.len() - 20);
+        textln!("  ... and {} more snapshots", snapshots.len() - 20);
     }
 
-    println!();
-    println!("  {}", "Recovery commands:".bold());
-    println!("    foodshare-ios protect restore --latest");
-    println!("    foodshare-ios protect restore --snapshot <ID>");
-    println!("{}", "═".repeat(70));
+    textln!();
+    textln!("  {}", "Recovery commands:".bold());
+    textln!("    foodshare-ios protect restore --latest");
+    textln!("    foodshare-ios protect restore --snapshot <ID>");
+    textln!("{}", "═".repeat(70));
+}
+
+/// Print snapshot diffs
+pub fn print_file_diffs(diffs: &[FileDiff]) {
+    for diff in diffs {
+        let change_marker = match diff.change_type {
+            ChangeType::Modified => "M".yellow().to_string(),
+            ChangeType::Added => "A".green().to_string(),
+            ChangeType::Deleted => "D".red().to_string(),
+        };
+        textln!();
+        textln!(
+            "{} {} ({}, {})",
+            change_marker,
+            diff.path.display().bold(),
+            format!("+{}", diff.insertions).green(),
+            format!("-{}", diff.deletions).red()
+        );
+
+        if diff.unified.is_empty() {
+            textln!("  {}", "Binary files differ".dimmed());
+            continue;
+        }
+        print_unified(&diff.unified, &DiffStyle::default());
+    }
 }
 
 /// Print restore result
 pub fn print_restore_result(result: &RestoreResult) {
-    println!();
+    textln!();
 
     if result.dry_run {
-        println!("{}", "DRY RUN - No files were modified".yellow().bold());
-        println!();
+        textln!("{}", "DRY RUN - No files were modified".yellow().bold());
+        textln!();
     }
 
     if !result.restored_files.is_empty() {
-        println!(
+        textln!(
             "{} {} file(s):",
             if result.dry_run { "Would restore" } else { "Restored" },
             result.restored_files.len()
         );
         for file in &result.restored_files {
-            println!("  {} {}", "✓".green(), file.display());
+            textln!("  {} {}", "✓".green(), file.display());
         }
     }
 
     if !result.skipped_files.is_empty() {
-        println!();
-        println!("Skipped {} file(s) (no changes):", result.skipped_files.len());
+        textln!();
+        textln!("Skipped {} file(s) (no changes):", result.skipped_files.len());
         for file in &result.skipped_files {
-            println!("  {} {}", "○".dimmed(), file.display());
+            textln!("  {} {}", "○".dimmed(), file.display());
         }
     }
 
     if !result.failed_files.is_empty() {
-        println!();
-        println!("{} {} file(s):", "Failed".red(), result.failed_files.len());
+        textln!();
+        textln!("{} {} file(s):", "Failed".red(), result.failed_files.len());
         for (file, error) in &result.failed_files {
-            println!("  {} {} - {}", "✗".red(), file.display(), error);
+            textln!("  {} {} - {}", "✗".red(), file.display(), error);
         }
     }
 
-    println!();
+    textln!();
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use foodshare_core::process::run_command_in_dir;
 
     #[test]
     fn test_compute_hash() {
@@ -1129,6 +1806,246 @@ mod tests {
         assert!(id.starts_with("snap-"));
     }
 
+    #[test]
+    fn test_snapshot_store_dedup_and_gc() {
+        let dir = tempfile::tempdir().unwrap();
+        run_command_in_dir("git", &["init", "-q"], dir.path()).unwrap();
+        let repo = GitRepo::open(dir.path()).unwrap();
+        let workdir = repo.workdir().to_path_buf();
+        let config = ProtectionConfig {
+            max_snapshots: 2,
+            ..Default::default()
+        };
+        let manager = SnapshotManager::open(config, repo).unwrap();
+
+        let source = "struct Listing {}\n".repeat(200);
+        fs::write(workdir.join("A.swift"), &source).unwrap();
+        fs::write(workdir.join("B.swift"), &source).unwrap();
+        let files = [PathBuf::from("A.swift"), PathBuf::from("B.swift")];
+
+        let first = manager.create_snapshot(&files, SnapshotTrigger::Manual, "first").unwrap();
+        let blobs = || {
+            walkdir::WalkDir::new(&manager.objects_dir)
+                .into_iter()
+                .filter(|e| e.as_ref().unwrap().file_type().is_file())
+                .count()
+        };
+        assert_eq!(blobs(), 1);
+        assert!(dir_size(&manager.objects_dir) < source.len() as u64 / 4);
+
+        for edit in ["second", "third"] {
+            std::thread::sleep(Duration::from_millis(2));
+            fs::write(workdir.join("A.swift"), edit).unwrap();
+            manager.create_snapshot(&files, SnapshotTrigger::Manual, edit).unwrap();
+        }
+        // The first snapshot aged out; its A.swift content is still B.swift's
+        assert!(manager.get_snapshot(&first.id).unwrap().is_none());
+        assert_eq!(manager.list_snapshots().unwrap().len(), 2);
+        assert_eq!(blobs(), 3);
+
+        std::thread::sleep(Duration::from_millis(2));
+        fs::write(workdir.join("B.swift"), "fourth").unwrap();
+        manager.create_snapshot(&files, SnapshotTrigger::Manual, "fourth").unwrap();
+        assert_eq!(blobs(), 3); // "second" collected, "fourth" added
+
+        let latest = manager.get_latest_snapshot().unwrap().unwrap();
+        fs::write(workdir.join("A.swift"), "lost").unwrap();
+
+        let diffs = manager.diff(&latest, &DiffTarget::Worktree, None).unwrap();
+        assert_eq!(diffs.len(), 1);
+        assert_eq!((diffs[0].insertions, diffs[0].deletions), (1, 1));
+        assert!(diffs[0].unified.contains("--- worktree/A.swift"));
+        assert!(diffs[0].unified.contains("-lost\n"));
+
+        let previous = manager.list_snapshots().unwrap()[1].id.clone();
+        let previous = manager.get_snapshot(&previous).unwrap().unwrap();
+        let diffs = manager.diff(&latest, &DiffTarget::Snapshot(previous), None).unwrap();
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].path, PathBuf::from("B.swift"));
+        assert_eq!(diffs[0].deletions, 200);
+
+        let result = manager.restore_snapshot(&latest, None, false).unwrap();
+        assert_eq!(result.restored_files, [PathBuf::from("A.swift")]);
+        assert_eq!(fs::read_to_string(workdir.join("A.swift")).unwrap(), "third");
+    }
+
+    fn open_store(dir: &Path, max_snapshots: usize) -> SnapshotManager {
+        let config = ProtectionConfig {
+            max_snapshots,
+            ..Default::default()
+        };
+        SnapshotManager::open(config, GitRepo::open(dir).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_concurrent_snapshots_keep_their_blobs() {
+        let dir = tempfile::tempdir().unwrap();
+        run_command_in_dir("git", &["init", "-q"], dir.path()).unwrap();
+
+        // Two writers (say `protect watch` and a pre-commit hook) on one store,
+        // each snapshotting a file with fresh content every time
+        std::thread::scope(|scope| {
+            for name in ["Watch.swift", "Hook.swift"] {
+                let manager = open_store(dir.path(), 3);
+                scope.spawn(move || {
+                    let file = PathBuf::from(name);
+                    for i in 0..15 {
+                        fs::write(manager.workdir().join(&file), format!("{} {}", name, i)).unwrap();
+                        manager.create_snapshot(std::slice::from_ref(&file), SnapshotTrigger::Manual, name).unwrap();
+                    }
+                });
+            }
+        });
+
+        let manager = open_store(dir.path(), 3);
+        assert!(!manager.lock_file.exists());
+        for entry in fs::read_dir(&manager.snapshots_dir).unwrap() {
+            let snapshot: Snapshot = serde_json::from_str(&fs::read_to_string(entry.unwrap().path()).unwrap()).unwrap();
+            for file in &snapshot.files {
+                assert!(manager.read_blob(&file.hash).unwrap().is_some(), "{} lost {}", snapshot.id, file.path.display());
+            }
+        }
+
+        // gc waits for a writer holding the lock
+        let lock = manager.lock_store().unwrap();
+        let (done_tx, done_rx) = std::sync::mpsc::channel();
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                manager.gc().unwrap();
+                done_tx.send(()).unwrap();
+            });
+            assert!(done_rx.recv_timeout(Duration::from_millis(200)).is_err());
+            drop(lock);
+            done_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        });
+    }
+
+    #[test]
+    fn test_snapshot_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        run_command_in_dir("git", &["init", "-q"], dir.path()).unwrap();
+        let manager = open_store(dir.path(), 10);
+        let workdir = manager.workdir().to_path_buf();
+        let files = [PathBuf::from("A.swift"), PathBuf::from("B.swift"), PathBuf::from("Icon.png")];
+
+        fs::write(workdir.join("A.swift"), "let a = 1\n").unwrap();
+        fs::write(workdir.join("Icon.png"), [0x89, b'P', b'N', b'G', 0xff]).unwrap();
+        let older = manager.create_snapshot(&files, SnapshotTrigger::Manual, "older").unwrap();
+
+        fs::write(workdir.join("A.swift"), "let a = 2\n").unwrap();
+        fs::write(workdir.join("B.swift"), "let b = 1\n").unwrap();
+        fs::write(workdir.join("Icon.png"), [0x89, b'P', b'N', b'G', 0xfe]).unwrap();
+        let newer = manager.create_snapshot(&files, SnapshotTrigger::Manual, "newer").unwrap();
+
+        // Against the worktree: unchanged files are left out, deleted ones come back
+        assert!(manager.diff(&newer, &DiffTarget::Worktree, None).unwrap().is_empty());
+        fs::remove_file(workdir.join("B.swift")).unwrap();
+        let diffs = manager.diff(&newer, &DiffTarget::Worktree, None).unwrap();
+        assert_eq!(diffs.len(), 1);
+        assert!(matches!(diffs[0].change_type, ChangeType::Added));
+        assert!(diffs[0].unified.contains("--- /dev/null"));
+
+        // Against another snapshot: new files are added, binary files have no hunks
+        let diffs = manager.diff(&newer, &DiffTarget::Snapshot(older.clone()), None).unwrap();
+        let paths: Vec<_> = diffs.iter().map(|d| d.path.to_str().unwrap()).collect();
+        assert_eq!(paths, ["A.swift", "B.swift", "Icon.png"]);
+        assert!(matches!(diffs[0].change_type, ChangeType::Modified));
+        assert_eq!((diffs[0].insertions, diffs[0].deletions), (1, 1));
+        assert!(diffs[0].unified.contains(&format!("--- {}/A.swift", older.id)));
+        assert!(diffs[0].unified.contains(&format!("+++ {}/A.swift", newer.id)));
+        assert!(matches!(diffs[1].change_type, ChangeType::Added));
+        assert!(diffs[2].unified.is_empty());
+
+        let only = [PathBuf::from("A.swift")];
+        let diffs = manager.diff(&newer, &DiffTarget::Snapshot(older.clone()), Some(&only)).unwrap();
+        assert_eq!(diffs.len(), 1);
+
+        // A blob lost from the store is an error on either side, never an added file
+        fs::remove_file(manager.blob_path(&older.files[0].hash)).unwrap();
+        let err = manager.diff(&newer, &DiffTarget::Snapshot(older.clone()), Some(&only)).unwrap_err();
+        assert!(err.to_string().contains(&format!("A.swift in snapshot {} is missing", older.id)));
+        let err = manager.diff(&older, &DiffTarget::Worktree, None).unwrap_err();
+        assert!(err.to_string().contains("missing from the store"));
+    }
+
+    #[test]
+    fn test_gc_migrates_legacy_store() {
+        let dir = tempfile::tempdir().unwrap();
+        run_command_in_dir("git", &["init", "-q"], dir.path()).unwrap();
+        let manager = open_store(dir.path(), 10);
+
+        // Snapshots from before compression kept raw content under content/<hash>
+        fs::create_dir_all(&manager.legacy_content_dir).unwrap();
+        let kept = "let apiKey = \"\"\n";
+        let kept_hash = compute_hash(kept);
+        fs::write(manager.legacy_content_dir.join(&kept_hash), kept).unwrap();
+        fs::write(manager.legacy_content_dir.join(compute_hash("gone")), "gone").unwrap();
+        let snapshot = Snapshot {
+            id: "snap-legacy".to_string(),
+            timestamp: Utc::now(),
+            trigger: SnapshotTrigger::PreFormat,
+            description: "legacy".to_string(),
+            files: vec![FileSnapshot {
+                path: PathBuf::from("Config.swift"),
+                hash: kept_hash.clone(),
+                size: kept.len() as u64,
+                content: None,
+            }],
+            branch: "main".to_string(),
+            commit: "unknown".to_string(),
+        };
+        manager.save_snapshot(&snapshot).unwrap();
+        assert!(manager.has_blob(&kept_hash));
+
+        let result = manager.gc().unwrap();
+        assert_eq!(result.blobs_removed, 1);
+        assert!(!manager.legacy_content_dir.exists());
+        assert!(manager.blob_path(&kept_hash).exists());
+        assert_eq!(
+            manager.get_file_content(&snapshot, Path::new("Config.swift")).unwrap().as_deref(),
+            Some(kept)
+        );
+    }
+
+    #[test]
+    fn test_auto_snapshot_throttle() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut throttle = AutoSnapshotThrottle::new(Duration::from_secs(2), Duration::from_secs(60));
+        assert_eq!(throttle.take_due(at(0)), None);
+
+        // A burst settles into one snapshot
+        throttle.record(PathBuf::from("Info.plist"), at(0));
+        throttle.record(PathBuf::from("App.entitlements"), at(1_500));
+        assert_eq!(throttle.take_due(at(3_000)), None);
+        assert_eq!(
+            throttle.take_due(at(3_500)),
+            Some(vec![PathBuf::from("App.entitlements"), PathBuf::from("Info.plist")])
+        );
+
+        // The next burst waits for the minimum interval
+        throttle.record(PathBuf::from("Info.plist"), at(10_000));
+        assert_eq!(throttle.take_due(at(20_000)), None);
+        assert_eq!(throttle.take_due(at(63_500)), Some(vec![PathBuf::from("Info.plist")]));
+        assert_eq!(throttle.take_due(at(200_000)), None);
+    }
+
+    #[test]
+    fn test_is_protected() {
+        let patterns = ProtectionConfig::default().protected_paths;
+        assert!(is_protected(Path::new("FoodShare/FoodShare.entitlements"), &patterns));
+        assert!(is_protected(Path::new("FoodShare/Info.plist"), &patterns));
+        assert!(is_protected(Path::new("FoodShare.xcodeproj/project.pbxproj"), &patterns));
+        assert!(!is_protected(Path::new("FoodShare/Views/FeedView.swift"), &patterns));
+
+        let patterns = vec!["FoodShare/Config/*".to_string()];
+        assert!(is_protected(Path::new("FoodShare/Config/Secrets.swift"), &patterns));
+        assert!(!is_protected(Path::new("Secrets.swift"), &patterns));
+
+        let diff = "diff --git a/Info.plist b/Info.plist\nindex 1..2 100644\n--- a/Info.plist\n+++ b/Info.plist\n@@ -1 +1 @@\n-a\n+b\n";
+        assert_eq!(diff_body(diff), ["@@ -1 +1 @@", "-a", "+b"]);
+    }
+
     #[test]
     fn test_protection_config_default() {
         let config = ProtectionConfig::default();
@@ -0,0 +1,587 @@
+//! Crash report symbolication
+//!
+//! Reads crash reports downloaded from TestFlight or Xcode's Organizer, in
+//! both the JSON `.ips` format (iOS 15+) and the older text `.crash` format,
+//! and resolves the app's own frames to functions and source lines with
+//! `atos` and the matching dSYMs.
+
+use foodshare_cli::textln;
+use foodshare_core::error::{Error, Result};
+use foodshare_core::process::run_command;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Frame line of a text report: `1   FoodShare   0x0000000100a1b2c4 0x100a00000 + 111300`
+static TEXT_FRAME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(\d+)\s+(\S+)\s+0x([0-9a-fA-F]+)\s+(.*)$").unwrap()
+});
+
+/// Binary image line of a text report: `0x100a00000 - 0x100bfffff FoodShare arm64 <uuid> /path`
+static TEXT_IMAGE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*0x([0-9a-fA-F]+)\s+-\s+0x[0-9a-fA-F]+\s+\+?(\S+)\s+(\S+)\s+<([0-9a-fA-F-]+)>").unwrap()
+});
+
+/// Source location suffix added by `atos` and symbolicated reports: `(FeedView.swift:42)`
+static SOURCE_LOCATION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\s\(([^()\s]+:\d+)\)$").unwrap()
+});
+
+/// A binary loaded into the crashed process
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BinaryImage {
+    /// Image name (`FoodShare`, `libswiftCore.dylib`)
+    pub name: String,
+    /// Build UUID, uppercase without dashes
+    pub uuid: Option<String>,
+    /// Load address
+    pub base: u64,
+    /// CPU architecture (`arm64`)
+    pub arch: Option<String>,
+}
+
+/// A stack frame
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrashFrame {
+    /// Image the frame's code belongs to
+    pub image: String,
+    /// Instruction address
+    pub address: u64,
+    /// Function name, once known
+    pub symbol: Option<String>,
+    /// `File.swift:42`, once known
+    pub location: Option<String>,
+}
+
+/// A thread's stack at the time of the crash
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrashThread {
+    /// Thread number
+    pub index: usize,
+    /// Thread or dispatch queue name
+    pub name: Option<String>,
+    /// Whether this thread crashed
+    pub crashed: bool,
+    /// Frames, innermost first
+    pub frames: Vec<CrashFrame>,
+}
+
+/// A parsed crash report
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrashReport {
+    /// Process name
+    pub app: Option<String>,
+    /// App version and build (`3.0.1 (42)`)
+    pub version: Option<String>,
+    /// OS version (`iPhone OS 17.4 (21E219)`)
+    pub os_version: Option<String>,
+    /// Exception type and signal (`EXC_BAD_ACCESS (SIGSEGV)`)
+    pub exception: Option<String>,
+    /// Threads in report order
+    pub threads: Vec<CrashThread>,
+    /// Loaded binaries
+    pub images: Vec<BinaryImage>,
+}
+
+/// Outcome of [`CrashReport::symbolicate`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Symbolication {
+    /// Frames resolved
+    pub frames: usize,
+    /// Images whose dSYM was found but belongs to a different build
+    pub mismatched: Vec<String>,
+}
+
+impl CrashReport {
+    /// Read and parse a crash report file
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Err(Error::file_not_found(path));
+        }
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Parse a crash report, detecting the format
+    pub fn parse(content: &str) -> Result<Self> {
+        if content.trim_start().starts_with('{') {
+            parse_ips(content)
+        } else {
+            Ok(parse_text(content))
+        }
+    }
+
+    /// The thread that crashed
+    pub fn crashed_thread(&self) -> Option<&CrashThread> {
+        self.threads.iter().find(|t| t.crashed)
+    }
+
+    /// Resolve the frames of every image that has a dSYM under `dsym_dir`
+    pub fn symbolicate(&mut self, dsym_dir: &Path) -> Result<Symbolication> {
+        let dsyms = find_dsyms(dsym_dir);
+        let mut outcome = Symbolication::default();
+
+        for image in &self.images {
+            let Some(dwarf) = dsyms.get(&image.name) else {
+                continue;
+            };
+            // A dSYM from another build resolves to plausible but wrong lines
+            if let (Some(uuid), Ok(dsym_uuids)) = (&image.uuid, dwarf_uuids(dwarf)) {
+                if !dsym_uuids.contains(uuid) {
+                    outcome.mismatched.push(image.name.clone());
+                    continue;
+                }
+            }
+
+            let addresses: Vec<u64> = self
+                .threads
+                .iter()
+                .flat_map(|t| &t.frames)
+                .filter(|f| f.image == image.name)
+                .map(|f| f.address)
+                .collect::<std::collections::BTreeSet<_>>()
+                .into_iter()
+                .collect();
+            if addresses.is_empty() {
+                continue;
+            }
+
+            let resolved = atos(dwarf, image, &addresses)?;
+            for frame in self.threads.iter_mut().flat_map(|t| &mut t.frames) {
+                if frame.image != image.name {
+                    continue;
+                }
+                if let Some((symbol, location)) = resolved.get(&frame.address) {
+                    frame.symbol = Some(symbol.clone());
+                    frame.location = location.clone();
+                    outcome.frames += 1;
+                }
+            }
+        }
+        Ok(outcome)
+    }
+
+    /// Print the crashed thread, or every thread, with source locations where known
+    pub fn print(&self, all_threads: bool) {
+        use owo_colors::OwoColorize;
+
+        let app = self.app.as_deref().unwrap_or("Unknown app");
+        textln!(
+            "{} {}",
+            app.bold(),
+            self.version.as_deref().unwrap_or_default()
+        );
+        if let Some(os_version) = &self.os_version {
+            textln!("  OS:        {}", os_version);
+        }
+        if let Some(exception) = &self.exception {
+            textln!("  Exception: {}", exception.red());
+        }
+
+        let threads: Vec<&CrashThread> = if all_threads {
+            self.threads.iter().collect()
+        } else {
+            self.crashed_thread().into_iter().collect()
+        };
+        for thread in threads {
+            textln!();
+            let title = match &thread.name {
+                Some(name) => format!("Thread {} ({})", thread.index, name),
+                None => format!("Thread {}", thread.index),
+            };
+            if thread.crashed {
+                textln!("{}", format!("{} crashed", title).red().bold());
+            } else {
+                textln!("{}", title.bold());
+            }
+
+            for (index, frame) in thread.frames.iter().enumerate() {
+                let symbol = frame
+                    .symbol
+                    .clone()
+                    .unwrap_or_else(|| format!("0x{:x}", frame.address));
+                let line = format!("{:>3}  {:<28} {}", index, frame.image, symbol);
+                match &frame.location {
+                    Some(location) => textln!("{} {}", line.bold(), format!("at {}", location).cyan()),
+                    None => textln!("{}", line.dimmed()),
+                }
+            }
+        }
+    }
+}
+
+/// Parse a JSON `.ips` report: a header line followed by the report body
+fn parse_ips(content: &str) -> Result<CrashReport> {
+    let content = content.trim_start();
+    let (header, body) = content.split_once('\n').unwrap_or((content, ""));
+    let header: serde_json::Value = serde_json::from_str(header)?;
+    let body: serde_json::Value = if body.trim().is_empty() {
+        header.clone()
+    } else {
+        serde_json::from_str(body)?
+    };
+
+    let images: Vec<BinaryImage> = body["usedImages"]
+        .as_array()
+        .map(|images| {
+            images
+                .iter()
+                .map(|image| BinaryImage {
+                    name: image["name"].as_str().unwrap_or("???").to_string(),
+                    uuid: image["uuid"].as_str().map(normalize_uuid),
+                    base: image["base"].as_u64().unwrap_or(0),
+                    arch: image["arch"].as_str().map(str::to_string),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let threads = body["threads"]
+        .as_array()
+        .map(|threads| {
+            threads
+                .iter()
+                .enumerate()
+                .map(|(index, thread)| CrashThread {
+                    index,
+                    name: thread["name"]
+                        .as_str()
+                        .or_else(|| thread["queue"].as_str())
+                        .map(str::to_string),
+                    crashed: thread["triggered"].as_bool().unwrap_or(false),
+                    frames: thread["frames"]
+                        .as_array()
+                        .map(|frames| frames.iter().map(|frame| parse_ips_frame(frame, &images)).collect())
+                        .unwrap_or_default(),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let exception = body["exception"]["type"].as_str().map(|kind| {
+        match body["exception"]["signal"].as_str() {
+            Some(signal) => format!("{} ({})", kind, signal),
+            None => kind.to_string(),
+        }
+    });
+    let version = match (header["app_version"].as_str(), header["build_version"].as_str()) {
+        (Some(version), Some(build)) => Some(format!("{} ({})", version, build)),
+        (Some(version), None) => Some(version.to_string()),
+        _ => None,
+    };
+
+    Ok(CrashReport {
+        app: header["app_name"]
+            .as_str()
+            .or_else(|| body["procName"].as_str())
+            .map(str::to_string),
+        version,
+        os_version: header["os_version"].as_str().map(str::to_string),
+        exception,
+        threads,
+        images,
+    })
+}
+
+fn parse_ips_frame(frame: &serde_json::Value, images: &[BinaryImage]) -> CrashFrame {
+    let image = frame["imageIndex"]
+        .as_u64()
+        .and_then(|index| images.get(index as usize));
+    let location = match (frame["sourceFile"].as_str(), frame["sourceLine"].as_u64()) {
+        (Some(file), Some(line)) => Some(format!("{}:{}", file, line)),
+        _ => None,
+    };
+    CrashFrame {
+        image: image.map_or_else(|| "???".to_string(), |i| i.name.clone()),
+        address: image.map_or(0, |i| i.base) + frame["imageOffset"].as_u64().unwrap_or(0),
+        symbol: frame["symbol"].as_str().map(str::to_string),
+        location,
+    }
+}
+
+/// Parse a text `.crash` report
+fn parse_text(content: &str) -> CrashReport {
+    let mut report = CrashReport::default();
+    let mut names: BTreeMap<usize, String> = BTreeMap::new();
+    let mut in_images = false;
+
+    for line in content.lines() {
+        if in_images {
+            if let Some(caps) = TEXT_IMAGE.captures(line) {
+                report.images.push(BinaryImage {
+                    name: caps[2].to_string(),
+                    uuid: Some(normalize_uuid(&caps[4])),
+                    base: u64::from_str_radix(&caps[1], 16).unwrap_or(0),
+                    arch: Some(caps[3].to_string()),
+                });
+            }
+            continue;
+        }
+
+        if let Some((key, value)) = line.split_once(':') {
+            let value = value.trim();
+            match key {
+                "Process" => {
+                    report.app = Some(value.split(" [").next().unwrap_or(value).to_string());
+                    continue;
+                }
+                "Version" => {
+                    report.version = Some(value.to_string());
+                    continue;
+                }
+                "OS Version" => {
+                    report.os_version = Some(value.to_string());
+                    continue;
+                }
+                "Exception Type" => {
+                    report.exception = Some(value.to_string());
+                    continue;
+                }
+                "Binary Images" => {
+                    in_images = true;
+                    continue;
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(rest) = line.strip_prefix("Thread ") {
+            let (number, rest) = rest.split_once([' ', ':']).unwrap_or((rest, ""));
+            let Ok(index) = number.parse::<usize>() else {
+                continue;
+            };
+            if let Some(name) = rest.trim().strip_prefix("name:") {
+                names.insert(index, name.trim().to_string());
+            } else {
+                report.threads.push(CrashThread {
+                    index,
+                    name: names.get(&index).cloned(),
+                    crashed: rest.contains("Crashed"),
+                    frames: Vec::new(),
+                });
+            }
+            continue;
+        }
+
+        if let (Some(caps), Some(thread)) = (TEXT_FRAME.captures(line), report.threads.last_mut()) {
+            thread.frames.push(parse_text_frame(&caps));
+        }
+    }
+    report
+}
+
+fn parse_text_frame(caps: &regex::Captures) -> CrashFrame {
+    let rest = caps[4].trim();
+    // Unsymbolicated frames are `<load address> + <offset>`
+    let symbolicated = !rest.starts_with("0x");
+    let (symbol, location) = if symbolicated {
+        let (symbol, location) = split_location(rest);
+        let symbol = symbol.rsplit_once(" + ").map_or(symbol, |(name, _)| name);
+        (Some(symbol.to_string()), location)
+    } else {
+        (None, None)
+    };
+    CrashFrame {
+        image: caps[2].to_string(),
+        address: u64::from_str_radix(&caps[3], 16).unwrap_or(0),
+        symbol,
+        location,
+    }
+}
+
+/// Split a trailing `(File.swift:42)` off a symbol
+fn split_location(text: &str) -> (&str, Option<String>) {
+    match SOURCE_LOCATION.captures(text) {
+        Some(caps) => (
+            text[..caps.get(0).map_or(text.len(), |m| m.start())].trim(),
+            Some(caps[1].to_string()),
+        ),
+        None => (text, None),
+    }
+}
+
+/// Parse one line of `atos` output
+///
+/// Resolved lines look like `FeedViewModel.load() (in FoodShare) (FeedViewModel.swift:87)`;
+/// unresolved ones are just the address.
+fn parse_atos_line(line: &str) -> Option<(String, Option<String>)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with("0x") {
+        return None;
+    }
+    let (rest, location) = split_location(line);
+    let symbol = rest.split(" (in ").next().unwrap_or(rest).trim();
+    Some((symbol.to_string(), location))
+}
+
+/// Resolve addresses of `image` with `atos`
+fn atos(
+    dwarf: &Path,
+    image: &BinaryImage,
+    addresses: &[u64],
+) -> Result<BTreeMap<u64, (String, Option<String>)>> {
+    let load_address = format!("0x{:x}", image.base);
+    let addresses_hex: Vec<String> = addresses.iter().map(|a| format!("0x{:x}", a)).collect();
+    let dwarf = dwarf.to_string_lossy();
+
+    let mut args = vec!["atos", "-o", dwarf.as_ref(), "-l", &load_address];
+    if let Some(arch) = &image.arch {
+        args.extend(["-arch", arch.as_str()]);
+    }
+    args.extend(addresses_hex.iter().map(String::as_str));
+
+    let result = run_command("xcrun", &args)?;
+    if !result.success {
+        return Err(Error::process(format!(
+            "atos failed for {}: {}",
+            image.name,
+            result.stderr.trim()
+        )));
+    }
+
+    Ok(addresses
+        .iter()
+        .zip(result.stdout.lines())
+        .filter_map(|(address, line)| Some((*address, parse_atos_line(line)?)))
+        .collect())
+}
+
+/// DWARF files of the dSYMs under `dir`, keyed by binary name
+pub fn find_dsyms(dir: &Path) -> BTreeMap<String, PathBuf> {
+    WalkDir::new(dir)
+        .max_depth(6)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter(|entry| entry.path().parent().is_some_and(|p| p.ends_with("Contents/Resources/DWARF")))
+        .map(|entry| {
+            (
+                entry.file_name().to_string_lossy().to_string(),
+                entry.path().to_path_buf(),
+            )
+        })
+        .collect()
+}
+
+/// Build UUIDs of a DWARF file, one per architecture
+fn dwarf_uuids(dwarf: &Path) -> Result<Vec<String>> {
+    let result = run_command("xcrun", &["dwarfdump", "--uuid", &dwarf.to_string_lossy()])?;
+    if !result.success {
+        return Err(Error::process(result.stderr.trim().to_string()));
+    }
+    // UUID: 5B1E0C44-2F44-4C1A-9C5D-1E0B9E6A0F11 (arm64) /path/to/DWARF/FoodShare
+    Ok(result
+        .stdout
+        .lines()
+        .filter_map(|line| line.strip_prefix("UUID: "))
+        .filter_map(|rest| rest.split_whitespace().next())
+        .map(normalize_uuid)
+        .collect())
+}
+
+fn normalize_uuid(uuid: &str) -> String {
+    uuid.replace('-', "").to_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ips() {
+        let ips = r#"{"app_name":"FoodShare","app_version":"3.0.1","build_version":"42","os_version":"iPhone OS 17.4 (21E219)","bug_type":"309"}
+{
+  "procName": "FoodShare",
+  "exception": {"type": "EXC_BREAKPOINT", "signal": "SIGTRAP"},
+  "threads": [
+    {"triggered": true, "queue": "com.apple.main-thread", "frames": [
+      {"imageOffset": 111300, "imageIndex": 0},
+      {"imageOffset": 4096, "symbol": "UIApplicationMain", "symbolLocation": 340, "imageIndex": 1}
+    ]},
+    {"frames": [{"imageOffset": 1024, "symbol": "__workq_kernreturn", "imageIndex": 1}]}
+  ],
+  "usedImages": [
+    {"base": 4305453056, "name": "FoodShare", "uuid": "5b1e0c44-2f44-4c1a-9c5d-1e0b9e6a0f11", "arch": "arm64"},
+    {"base": 6442450944, "name": "UIKitCore", "uuid": "0f1e2d3c-0000-0000-0000-000000000000", "arch": "arm64e"}
+  ]
+}"#;
+        let report = CrashReport::parse(ips).unwrap();
+        assert_eq!(report.app.as_deref(), Some("FoodShare"));
+        assert_eq!(report.version.as_deref(), Some("3.0.1 (42)"));
+        assert_eq!(report.exception.as_deref(), Some("EXC_BREAKPOINT (SIGTRAP)"));
+        assert_eq!(report.threads.len(), 2);
+
+        let crashed = report.crashed_thread().unwrap();
+        assert_eq!(crashed.name.as_deref(), Some("com.apple.main-thread"));
+        assert_eq!(crashed.frames[0].image, "FoodShare");
+        assert_eq!(crashed.frames[0].address, 4305453056 + 111300);
+        assert_eq!(crashed.frames[0].symbol, None);
+        assert_eq!(crashed.frames[1].symbol.as_deref(), Some("UIApplicationMain"));
+        assert_eq!(report.images[0].uuid.as_deref(), Some("5B1E0C442F444C1A9C5D1E0B9E6A0F11"));
+    }
+
+    #[test]
+    fn test_parse_text() {
+        let crash = "\
+Incident Identifier: 3B4E2A10-1C3D-4E5F-8A9B-0C1D2E3F4A5B
+Process:             FoodShare [1234]
+Version:             3.0.1 (42)
+OS Version:          iPhone OS 17.4 (21E219)
+Exception Type:  EXC_CRASH (SIGABRT)
+
+Thread 0 name:  Dispatch queue: com.apple.main-thread
+Thread 0 Crashed:
+0   libsystem_kernel.dylib        \t0x00000001e8c5a0dc __pthread_kill + 8
+1   FoodShare                     \t0x0000000100a1b2c4 0x100a00000 + 111300
+2   FoodShare                     \t0x0000000100a1c000 FeedView.body.getter + 52 (FeedView.swift:31)
+
+Thread 1:
+0   libsystem_pthread.dylib       \t0x00000001f8d0e0c4 start_wqthread + 0
+
+Binary Images:
+       0x100a00000 -        0x100bfffff FoodShare arm64  <5b1e0c442f444c1a9c5d1e0b9e6a0f11> /private/var/containers/Bundle/Application/FoodShare.app/FoodShare
+";
+        let report = CrashReport::parse(crash).unwrap();
+        assert_eq!(report.app.as_deref(), Some("FoodShare"));
+        assert_eq!(report.exception.as_deref(), Some("EXC_CRASH (SIGABRT)"));
+        assert_eq!(report.threads.len(), 2);
+
+        let crashed = report.crashed_thread().unwrap();
+        assert_eq!(crashed.name.as_deref(), Some("Dispatch queue: com.apple.main-thread"));
+        assert_eq!(crashed.frames.len(), 3);
+        assert_eq!(crashed.frames[0].symbol.as_deref(), Some("__pthread_kill"));
+        assert_eq!(crashed.frames[1].address, 0x100a1b2c4);
+        assert_eq!(crashed.frames[1].symbol, None);
+        assert_eq!(crashed.frames[2].symbol.as_deref(), Some("FeedView.body.getter"));
+        assert_eq!(crashed.frames[2].location.as_deref(), Some("FeedView.swift:31"));
+
+        assert_eq!(report.images.len(), 1);
+        assert_eq!(report.images[0].base, 0x100a00000);
+        assert_eq!(report.images[0].arch.as_deref(), Some("arm64"));
+    }
+
+    #[test]
+    fn test_parse_atos_and_find_dsyms() {
+        assert_eq!(
+            parse_atos_line("closure #1 in FeedViewModel.load() (in FoodShare) (FeedViewModel.swift:87)"),
+            Some((
+                "closure #1 in FeedViewModel.load()".to_string(),
+                Some("FeedViewModel.swift:87".to_string())
+            ))
+        );
+        assert_eq!(
+            parse_atos_line("FoodShare.main() (in FoodShare) + 12"),
+            Some(("FoodShare.main()".to_string(), None))
+        );
+        assert_eq!(parse_atos_line("0x100a1b2c4 (in FoodShare)"), None);
+
+        let dir = tempfile::tempdir().unwrap();
+        let dwarf = dir.path().join("dSYMs/FoodShare.app.dSYM/Contents/Resources/DWARF");
+        std::fs::create_dir_all(&dwarf).unwrap();
+        std::fs::write(dwarf.join("FoodShare"), "").unwrap();
+        let dsyms = find_dsyms(dir.path());
+        assert_eq!(dsyms.get("FoodShare"), Some(&dwarf.join("FoodShare")));
+    }
+}
@@ -0,0 +1,320 @@
+//! Physical device management
+//!
+//! Lists connected iPhones and iPads with `xcrun devicectl` (Xcode 15+),
+//! falling back to `xcrun xctrace list devices` on older Xcodes, and installs
+//! and launches apps on them. Before building for a device,
+//! [`signing_issues`] checks the settings that otherwise fail deep inside
+//! `xcodebuild`.
+
+use foodshare_core::error::{Error, Result};
+use foodshare_core::process::{run_command, run_command_streaming, CommandResult};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A connected physical device
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhysicalDevice {
+    /// Hardware UDID, used in `xcodebuild -destination id=...`
+    pub udid: String,
+    /// Device name (`Jane's iPhone`)
+    pub name: String,
+    /// Model (`iPhone 15 Pro`)
+    pub model: Option<String>,
+    /// OS version (`17.4`)
+    pub os_version: Option<String>,
+    /// Whether the device is paired and reachable
+    pub available: bool,
+}
+
+impl PhysicalDevice {
+    /// Check if `query` is this device's name or UDID
+    pub fn matches(&self, query: &str) -> bool {
+        self.udid.eq_ignore_ascii_case(query) || self.name == query
+    }
+}
+
+/// List connected devices
+pub fn list_devices() -> Result<Vec<PhysicalDevice>> {
+    let output = std::env::temp_dir().join(format!("foodshare-devicectl-{}.json", std::process::id()));
+    let output_arg = output.to_string_lossy().to_string();
+    let result = run_command(
+        "xcrun",
+        &["devicectl", "list", "devices", "--quiet", "--json-output", &output_arg],
+    );
+    let json = std::fs::read_to_string(&output);
+    let _ = std::fs::remove_file(&output);
+
+    match (result, json) {
+        (Ok(result), Ok(json)) if result.success => parse_devicectl(&json),
+        _ => {
+            let result = run_command("xcrun", &["xctrace", "list", "devices"])?;
+            if !result.success {
+                return Err(Error::process(format!(
+                    "Failed to list devices: {}",
+                    result.stderr.trim()
+                )));
+            }
+            Ok(parse_xctrace(&result.stdout))
+        }
+    }
+}
+
+/// Find a device by name or UDID, or the first available one
+pub fn select_device(devices: &[PhysicalDevice], query: Option<&str>) -> Result<PhysicalDevice> {
+    let device = match query {
+        Some(query) => devices.iter().find(|d| d.matches(query)),
+        None => devices.iter().find(|d| d.available),
+    };
+    match (device, query) {
+        (Some(device), _) => Ok(device.clone()),
+        (None, Some(query)) => Err(Error::validation(format!("Device not found: {}", query))),
+        (None, None) => Err(Error::validation(
+            "No connected device found. Connect and unlock an iPhone, then trust this Mac",
+        )),
+    }
+}
+
+/// Parse `devicectl list devices --json-output` output, keeping physical devices
+pub fn parse_devicectl(json: &str) -> Result<Vec<PhysicalDevice>> {
+    let json: serde_json::Value = serde_json::from_str(json)?;
+    let devices = json["result"]["devices"].as_array().cloned().unwrap_or_default();
+
+    Ok(devices
+        .iter()
+        .filter(|d| d["hardwareProperties"]["reality"].as_str() != Some("virtual"))
+        .filter_map(|d| {
+            let hardware = &d["hardwareProperties"];
+            let connection = &d["connectionProperties"];
+            Some(PhysicalDevice {
+                udid: hardware["udid"].as_str()?.to_string(),
+                name: d["deviceProperties"]["name"].as_str()?.to_string(),
+                model: hardware["marketingName"].as_str().map(str::to_string),
+                os_version: d["deviceProperties"]["osVersionNumber"].as_str().map(str::to_string),
+                available: connection["pairingState"].as_str() == Some("paired")
+                    && connection["tunnelState"].as_str() != Some("unavailable"),
+            })
+        })
+        .collect())
+}
+
+/// Parse `xctrace list devices` output
+///
+/// Devices are listed as `Name (OS version) (UDID)` under `== Devices ==`
+/// (connected) and `== Devices Offline ==`. The first connected entry is the
+/// Mac running the command, which is skipped.
+pub fn parse_xctrace(output: &str) -> Vec<PhysicalDevice> {
+    let mut devices = Vec::new();
+    let mut section = "";
+    let mut host_skipped = false;
+
+    for line in output.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if let Some(name) = line.strip_prefix("== ").and_then(|l| l.strip_suffix(" ==")) {
+            section = name;
+            continue;
+        }
+        let available = match section {
+            "Devices" => true,
+            "Devices Offline" => false,
+            _ => continue,
+        };
+        if available && !host_skipped {
+            host_skipped = true;
+            continue;
+        }
+
+        let Some((rest, udid)) = line.strip_suffix(')').and_then(|l| l.rsplit_once(" (")) else {
+            continue;
+        };
+        let (name, os_version) = match rest.strip_suffix(')').and_then(|r| r.rsplit_once(" (")) {
+            Some((name, version)) => (name, Some(version.to_string())),
+            None => (rest, None),
+        };
+        devices.push(PhysicalDevice {
+            udid: udid.to_string(),
+            name: name.to_string(),
+            model: None,
+            os_version,
+            available,
+        });
+    }
+    devices
+}
+
+/// Problems that would make signing a device build fail
+///
+/// `settings` are the app target's resolved build settings and `identities`
+/// the valid code signing identities in the keychain.
+pub fn signing_issues(settings: &BTreeMap<String, String>, identities: &[String]) -> Vec<String> {
+    let setting = |key: &str| settings.get(key).map(String::as_str).filter(|v| !v.is_empty());
+    let mut issues = Vec::new();
+
+    if setting("DEVELOPMENT_TEAM").is_none() {
+        issues.push("DEVELOPMENT_TEAM is not set; select a team under Signing & Capabilities".to_string());
+    }
+    if setting("PRODUCT_BUNDLE_IDENTIFIER").is_none() {
+        issues.push("PRODUCT_BUNDLE_IDENTIFIER is not set".to_string());
+    }
+    if setting("CODE_SIGN_STYLE") == Some("Manual")
+        && setting("PROVISIONING_PROFILE_SPECIFIER").is_none()
+        && setting("PROVISIONING_PROFILE").is_none()
+    {
+        issues.push("Manual signing needs PROVISIONING_PROFILE_SPECIFIER".to_string());
+    }
+    if identities.is_empty() {
+        issues.push("No valid code signing identity in the keychain; sign in to Xcode with your Apple ID".to_string());
+    } else if let Some(team) = setting("DEVELOPMENT_TEAM") {
+        if !identities.iter().any(|identity| identity.contains(&format!("({})", team))) {
+            issues.push(format!("No code signing identity for team {} in the keychain", team));
+        }
+    }
+    issues
+}
+
+/// Valid code signing identities (`Apple Development: Jane Doe (TEAMID)`)
+pub fn codesigning_identities() -> Result<Vec<String>> {
+    let result = run_command("security", &["find-identity", "-v", "-p", "codesigning"])?;
+    Ok(parse_identities(&result.stdout))
+}
+
+/// Parse `security find-identity -v` output
+fn parse_identities(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (_, rest) = line.trim().split_once(") ")?;
+            let name = rest.split_once(" \"")?.1.strip_suffix('"')?;
+            Some(name.to_string())
+        })
+        .collect()
+}
+
+/// Install an app on a device
+pub fn install_app(udid: &str, app_path: &str) -> Result<CommandResult> {
+    run_command(
+        "xcrun",
+        &["devicectl", "device", "install", "app", "--device", udid, app_path],
+    )
+}
+
+/// Launch an app on a device, replacing a running instance
+pub fn launch_app(udid: &str, bundle_id: &str) -> Result<CommandResult> {
+    run_command(
+        "xcrun",
+        &[
+            "devicectl",
+            "device",
+            "process",
+            "launch",
+            "--terminate-existing",
+            "--device",
+            udid,
+            bundle_id,
+        ],
+    )
+}
+
+/// Launch an app on a device and stream its console output until it exits
+pub fn launch_app_with_console(udid: &str, bundle_id: &str) -> Result<i32> {
+    run_command_streaming(
+        "xcrun",
+        &[
+            "devicectl",
+            "device",
+            "process",
+            "launch",
+            "--terminate-existing",
+            "--console",
+            "--device",
+            udid,
+            bundle_id,
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_devicectl() {
+        let json = r#"{
+  "info": {"outcome": "success"},
+  "result": {
+    "devices": [
+      {
+        "identifier": "5B1E0C44-2F44-4C1A-9C5D-1E0B9E6A0F11",
+        "connectionProperties": {"pairingState": "paired", "transportType": "wired", "tunnelState": "connected"},
+        "deviceProperties": {"name": "Jane's iPhone", "osVersionNumber": "18.1"},
+        "hardwareProperties": {"marketingName": "iPhone 16 Pro", "platform": "iOS", "reality": "physical", "udid": "00008140-001A2B3C4D5E801C"}
+      },
+      {
+        "identifier": "0F1E2D3C-0000-0000-0000-000000000000",
+        "connectionProperties": {"pairingState": "unpaired", "tunnelState": "unavailable"},
+        "deviceProperties": {"name": "Test iPad"},
+        "hardwareProperties": {"platform": "iOS", "reality": "physical", "udid": "00008027-000A1B2C3D4E002E"}
+      }
+    ]
+  }
+}"#;
+        let devices = parse_devicectl(json).unwrap();
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].udid, "00008140-001A2B3C4D5E801C");
+        assert_eq!(devices[0].model.as_deref(), Some("iPhone 16 Pro"));
+        assert!(devices[0].available);
+        assert!(!devices[1].available);
+
+        assert_eq!(select_device(&devices, None).unwrap().name, "Jane's iPhone");
+        assert_eq!(select_device(&devices, Some("00008027-000A1B2C3D4E002E")).unwrap().name, "Test iPad");
+        assert!(select_device(&devices, Some("Nope")).is_err());
+        assert!(select_device(&devices[1..], None).is_err());
+    }
+
+    #[test]
+    fn test_parse_xctrace() {
+        let output = "\
+== Devices ==
+Jane's MacBook Pro (00006000-000A1B2C3D4E801E)
+Jane's iPhone (18.1) (00008140-001A2B3C4D5E801C)
+
+== Devices Offline ==
+Old iPad (16.7.2) (00008020-000A1B2C3D4E002E)
+
+== Simulators ==
+iPhone 16 Pro Simulator (18.1) (5B1E0C44-2F44-4C1A-9C5D-1E0B9E6A0F11)
+";
+        let devices = parse_xctrace(output);
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].name, "Jane's iPhone");
+        assert_eq!(devices[0].os_version.as_deref(), Some("18.1"));
+        assert!(devices[0].available);
+        assert_eq!(devices[1].udid, "00008020-000A1B2C3D4E002E");
+        assert!(!devices[1].available);
+    }
+
+    #[test]
+    fn test_signing_issues() {
+        let identities = parse_identities(
+            "  1) 3A1B2C3D4E5F60718293A4B5C6D7E8F901234567 \"Apple Development: Jane Doe (ABCDE12345)\"\n     1 valid identities found\n",
+        );
+        assert_eq!(identities, ["Apple Development: Jane Doe (ABCDE12345)"]);
+
+        let mut settings: BTreeMap<String, String> = [
+            ("DEVELOPMENT_TEAM", "ABCDE12345"),
+            ("PRODUCT_BUNDLE_IDENTIFIER", "com.flutterflow.foodshare"),
+            ("CODE_SIGN_STYLE", "Automatic"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        assert!(signing_issues(&settings, &identities).is_empty());
+
+        settings.insert("CODE_SIGN_STYLE".to_string(), "Manual".to_string());
+        settings.insert("DEVELOPMENT_TEAM".to_string(), "ZZZZZ99999".to_string());
+        let issues = signing_issues(&settings, &identities);
+        assert_eq!(issues.len(), 2);
+        assert!(issues[0].contains("PROVISIONING_PROFILE_SPECIFIER"));
+        assert!(issues[1].contains("ZZZZZ99999"));
+
+        assert_eq!(signing_issues(&BTreeMap::new(), &[]).len(), 3);
+    }
+}
@@ -3,6 +3,7 @@
 //! This module provides safe, auditable hook operations with:
 //! - **Preview mode**: See what will change before modifying files
 //! - **Auto-backup**: Stash changes before formatting for safety
+//! - **Staged lines only**: Leave code outside the staged hunks untouched
 //! - **Diff summary**: Show exactly what was modified
 //! - **Audit logging**: Track all hook operations
 //! - **Recovery**: Easy rollback if something goes wrong
@@ -10,12 +11,15 @@
 use crate::code_protection::{ProtectionConfig, SnapshotManager, SnapshotTrigger};
 use crate::swift_tools;
 use chrono::Local;
-use foodshare_core::error::{exit_codes, Result};
+use foodshare_cli::output::diff::{print_unified, Diff, DiffStyle};
+use foodshare_cli::{text, textln};
+use foodshare_core::error::{exit_codes, Error, Result};
 use foodshare_core::git::GitRepo;
-use foodshare_core::process::run_command;
+use foodshare_core::process::{run_command, run_command_in_dir};
 use owo_colors::OwoColorize;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use similar::{DiffTag, TextDiff};
+use std::collections::{BTreeSet, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
@@ -24,6 +28,12 @@ use std::time::{Duration, Instant};
 // SAFE FORMAT - Enterprise-grade formatting with safety features
 // ============================================================================
 
+/// Diff lines shown per file in a preview
+const PREVIEW_DIFF_LINES: usize = 40;
+
+/// Diff lines shown per file after formatting
+const FORMAT_DIFF_LINES: usize = 12;
+
 /// Configuration for safe formatting operations
 #[derive(Debug, Clone)]
 pub struct SafeFormatConfig {
@@ -39,6 +49,8 @@ pub struct SafeFormatConfig {
     pub timeout: Duration,
     /// Create a snapshot before formatting (Code Protection System)
     pub create_snapshot: bool,
+    /// Only apply formatting changes that touch staged lines
+    pub staged_only: bool,
 }
 
 impl Default for SafeFormatConfig {
@@ -50,6 +62,7 @@ impl Default for SafeFormatConfig {
             audit: true,
             timeout: Duration::from_secs(120),
             create_snapshot: true, // Enable snapshots by default for maximum safety
+            staged_only: false,
         }
     }
 }
@@ -82,7 +95,8 @@ pub struct SafeFormatResult {
 pub struct FileDiff {
     pub insertions: usize,
     pub deletions: usize,
-    pub hunks: Vec<String>,
+    /// Unified diff with one line of context
+    pub unified: String,
 }
 
 /// Safe format executor with enterprise features
@@ -121,7 +135,7 @@ impl SafeFormat {
             .collect();
 
         if swift_files.is_empty() {
-            println!("  {} No Swift files to format", "ℹ".blue());
+            textln!("  {} No Swift files to format", "ℹ".blue());
             result.duration = start.elapsed();
             return Ok(result);
         }
@@ -136,7 +150,7 @@ impl SafeFormat {
                 ) {
                     Ok(snapshot) => {
                         result.snapshot_id = Some(snapshot.id.clone());
-                        println!(
+                        textln!(
                             "  {} Snapshot created: {} ({} files)",
                             "📸".green(),
                             snapshot.id.dimmed(),
@@ -144,7 +158,7 @@ impl SafeFormat {
                         );
                     }
                     Err(e) => {
-                        println!(
+                        textln!(
                             "  {} Snapshot failed (continuing): {}",
                             "⚠".yellow(),
                             e.to_string().dimmed()
@@ -158,7 +172,7 @@ impl SafeFormat {
         if self.config.backup && !self.config.preview {
             result.backup_ref = self.create_backup()?;
             if let Some(ref backup) = result.backup_ref {
-                println!(
+                textln!(
                     "  {} Stash backup: {}",
                     "✓".green(),
                     backup.dimmed()
@@ -178,7 +192,7 @@ impl SafeFormat {
 
         // Step 3: Preview or format
         if self.config.preview {
-            println!("\n  {} Preview mode - no files will be modified\n", "👁".yellow());
+            textln!("\n  {} Preview mode - no files will be modified\n", "👁".yellow());
             self.preview_format(&swift_files, &original_contents, &mut result)?;
         } else {
             self.execute_format(&swift_files, &original_contents, &mut result)?;
@@ -230,24 +244,41 @@ impl SafeFormat {
                 None => continue,
             };
 
-            // Run swiftformat with --lint to check for changes
-            let cmd_result = run_command(
-                "swiftformat",
-                &[&file.to_string_lossy(), "--lint", "--lenient"],
-            )?;
+            let formatted = if self.config.staged_only {
+                self.format_staged_lines(file, original)
+            } else {
+                // Run swiftformat with --lint to check for changes
+                let cmd_result = run_command(
+                    "swiftformat",
+                    &[&file.to_string_lossy(), "--lint", "--lenient"],
+                )?;
+                if cmd_result.success {
+                    Ok(original.clone())
+                } else {
+                    // Would have changes - get what they would be
+                    self.get_formatted_content(file)
+                }
+            };
+
+            let formatted = match formatted {
+                Ok(formatted) => formatted,
+                Err(e) => {
+                    result.failed_files.push((file.clone(), e.to_string()));
+                    textln!("  {} {} - {}", "✗".red(), file.display(), e);
+                    continue;
+                }
+            };
 
-            if cmd_result.success {
+            if formatted == *original {
                 result.unchanged_files.push(file.clone());
-                println!("  {} {} (no changes)", "○".dimmed(), file.display());
+                textln!("  {} {} (no changes)", "○".dimmed(), file.display());
             } else {
-                // Would have changes - get what they would be
-                let formatted = self.get_formatted_content(file)?;
                 let diff = self.compute_diff(original, &formatted);
 
                 result.formatted_files.push(file.clone());
                 result.lines_changed += diff.insertions + diff.deletions;
 
-                println!(
+                textln!(
                     "  {} {} ({} insertions, {} deletions)",
                     "●".yellow(),
                     file.display(),
@@ -255,10 +286,13 @@ impl SafeFormat {
                     format!("-{}", diff.deletions).red()
                 );
 
-                if self.config.show_diff && !diff.hunks.is_empty() {
-                    for hunk in &diff.hunks {
-                        println!("    {}", hunk.dimmed());
-                    }
+                if self.config.show_diff {
+                    let style = DiffStyle {
+                        max_lines: Some(PREVIEW_DIFF_LINES),
+                        indent: 4,
+                        ..DiffStyle::default()
+                    };
+                    print_unified(&diff.unified, &style);
                 }
 
                 result.diffs.insert(file.clone(), diff);
@@ -281,32 +315,49 @@ impl SafeFormat {
                 None => continue,
             };
 
-            // Run swiftformat
-            let cmd_result = run_command("swiftformat", &[&file.to_string_lossy()])?;
-
-            if !cmd_result.success {
-                result.failed_files.push((file.clone(), cmd_result.stderr.clone()));
-                println!(
-                    "  {} {} - {}",
-                    "✗".red(),
-                    file.display(),
-                    cmd_result.stderr.lines().next().unwrap_or("Unknown error")
-                );
-                continue;
-            }
+            let formatted = if self.config.staged_only {
+                self.format_staged_lines(file, &original)
+                    .and_then(|formatted| {
+                        if formatted != original {
+                            fs::write(file, &formatted)?;
+                        }
+                        Ok(formatted)
+                    })
+                    .map_err(|e| e.to_string())
+            } else {
+                // Run swiftformat
+                let cmd_result = run_command("swiftformat", &[&file.to_string_lossy()])?;
+                if cmd_result.success {
+                    // Read the formatted content
+                    Ok(fs::read_to_string(file).unwrap_or_default())
+                } else {
+                    Err(cmd_result.stderr)
+                }
+            };
 
-            // Read the formatted content
-            let formatted = fs::read_to_string(file).unwrap_or_default();
+            let formatted = match formatted {
+                Ok(formatted) => formatted,
+                Err(error) => {
+                    textln!(
+                        "  {} {} - {}",
+                        "✗".red(),
+                        file.display(),
+                        error.lines().next().unwrap_or("Unknown error")
+                    );
+                    result.failed_files.push((file.clone(), error));
+                    continue;
+                }
+            };
 
             if formatted == original {
                 result.unchanged_files.push(file.clone());
-                println!("  {} {} (no changes)", "○".dimmed(), file.display());
+                textln!("  {} {} (no changes)", "○".dimmed(), file.display());
             } else {
                 let diff = self.compute_diff(&original, &formatted);
                 result.formatted_files.push(file.clone());
                 result.lines_changed += diff.insertions + diff.deletions;
 
-                println!(
+                textln!(
                     "  {} {} ({} insertions, {} deletions)",
                     "✓".green(),
                     file.display(),
@@ -314,13 +365,13 @@ impl SafeFormat {
                     format!("-{}", diff.deletions).red()
                 );
 
-                if self.config.show_diff && !diff.hunks.is_empty() {
-                    for hunk in diff.hunks.iter().take(3) {
-                        println!("    {}", hunk.dimmed());
-                    }
-                    if diff.hunks.len() > 3 {
-                        println!("    {} more changes...", format!("... {} ", diff.hunks.len() - 3).dimmed());
-                    }
+                if self.config.show_diff {
+                    let style = DiffStyle {
+                        max_lines: Some(FORMAT_DIFF_LINES),
+                        indent: 4,
+                        ..DiffStyle::default()
+                    };
+                    print_unified(&diff.unified, &style);
                 }
 
                 result.diffs.insert(file.clone(), diff);
@@ -335,13 +386,13 @@ impl SafeFormat {
         // Read original
         let original = fs::read_to_string(file)?;
 
-        // Create temp file
-        let temp_dir = std::env::temp_dir();
-        let temp_file = temp_dir.join(format!("swiftformat-preview-{}.swift", std::process::id()));
+        // Create temp file next to the original so the same .swiftformat applies
+        let name = file.file_stem().unwrap_or_default().to_string_lossy();
+        let temp_file = file.with_file_name(format!("{}.swiftformat-preview-{}.swift", name, std::process::id()));
         fs::write(&temp_file, &original)?;
 
         // Format temp file
-        let _ = run_command("swiftformat", &[&temp_file.to_string_lossy()]);
+        let cmd_result = run_command("swiftformat", &[&temp_file.to_string_lossy()]);
 
         // Read formatted
         let formatted = fs::read_to_string(&temp_file).unwrap_or(original);
@@ -349,47 +400,45 @@ impl SafeFormat {
         // Cleanup
         let _ = fs::remove_file(&temp_file);
 
-        Ok(formatted)
+        match cmd_result {
+            Ok(r) if !r.success => Err(Error::process(format!(
+                "swiftformat failed: {}",
+                r.stderr.lines().next().unwrap_or("Unknown error")
+            ))),
+            Ok(_) => Ok(formatted),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Format a file, keeping only the changes that touch staged lines
+    fn format_staged_lines(&self, file: &Path, current: &str) -> Result<String> {
+        let path = file.strip_prefix(self.repo.workdir()).unwrap_or(file).to_string_lossy();
+        let workdir = self.repo.workdir();
+
+        let index = run_command_in_dir("git", &["show", &format!(":{}", path)], workdir)?;
+        if !index.success {
+            return Err(Error::git(format!("{} is not staged", path)));
+        }
+        let head = run_command_in_dir("git", &["show", &format!("HEAD:{}", path)], workdir)?;
+        let head = head.success.then_some(head.stdout);
+
+        let lines = staged_lines(head.as_deref(), &index.stdout, current);
+        if lines.is_empty() {
+            return Ok(current.to_string());
+        }
+        let formatted = self.get_formatted_content(file)?;
+        Ok(apply_to_lines(current, &formatted, &lines))
     }
 
     /// Compute diff between two strings
     fn compute_diff(&self, original: &str, formatted: &str) -> FileDiff {
-        let original_lines: Vec<_> = original.lines().collect();
-        let formatted_lines: Vec<_> = formatted.lines().collect();
-
-        let mut insertions = 0;
-        let mut deletions = 0;
-        let mut hunks = Vec::new();
-
-        // Simple line-by-line diff
-        let max_lines = original_lines.len().max(formatted_lines.len());
-        for i in 0..max_lines {
-            let orig = original_lines.get(i).map(|s| *s);
-            let fmt = formatted_lines.get(i).map(|s| *s);
-
-            match (orig, fmt) {
-                (Some(o), Some(f)) if o != f => {
-                    deletions += 1;
-                    insertions += 1;
-                    if hunks.len() < 10 {
-                        hunks.push(format!("-{}: {}", i + 1, truncate(o, 60)));
-                        hunks.push(format!("+{}: {}", i + 1, truncate(f, 60)));
-                    }
-                }
-                (Some(_), None) => {
-                    deletions += 1;
-                }
-                (None, Some(_)) => {
-                    insertions += 1;
-                }
-                _ => {}
-            }
-        }
+        let diff = Diff::new(original, formatted).context(1);
+        let stats = diff.stats();
 
         FileDiff {
-            insertions,
-            deletions,
-            hunks,
+            insertions: stats.insertions,
+            deletions: stats.deletions,
+            unified: diff.unified(),
         }
     }
 
@@ -445,19 +494,81 @@ impl SafeFormat {
     }
 }
 
+/// Working tree lines (0-based) that belong to staged changes
+///
+/// Lines added or changed between `head` and `index` are mapped onto
+/// `worktree` through the lines the index and working tree share, so
+/// unstaged edits are never included. A staged deletion marks the lines
+/// on either side of it.
+fn staged_lines(head: Option<&str>, index: &str, worktree: &str) -> BTreeSet<usize> {
+    let index_lines = index.lines().count();
+    let mut staged = BTreeSet::new();
+    for op in TextDiff::from_lines(head.unwrap_or_default(), index).ops() {
+        let (tag, _, new) = op.as_tag_tuple();
+        match tag {
+            DiffTag::Equal => {}
+            _ if new.is_empty() => {
+                staged.extend(new.start.checked_sub(1));
+                staged.extend(Some(new.start).filter(|&line| line < index_lines));
+            }
+            _ => staged.extend(new),
+        }
+    }
+
+    let mut lines = BTreeSet::new();
+    for op in TextDiff::from_lines(index, worktree).ops() {
+        if let (DiffTag::Equal, old, new) = op.as_tag_tuple() {
+            lines.extend(old.zip(new).filter(|(old, _)| staged.contains(old)).map(|(_, new)| new));
+        }
+    }
+    lines
+}
+
+/// Apply the changes from `original` to `formatted` that touch `lines` of `original`
+fn apply_to_lines(original: &str, formatted: &str, lines: &BTreeSet<usize>) -> String {
+    let diff = TextDiff::from_lines(original, formatted);
+    let (old, new) = (diff.old_slices(), diff.new_slices());
+    let mut merged = String::with_capacity(original.len());
+
+    for op in diff.ops() {
+        let (tag, old_range, new_range) = op.as_tag_tuple();
+        match tag {
+            DiffTag::Equal => merged.extend(old[old_range].iter().copied()),
+            // Line-for-line rewrites (indentation, spacing) are applied per line
+            DiffTag::Replace if old_range.len() == new_range.len() => {
+                for (o, n) in old_range.zip(new_range) {
+                    merged.push_str(if lines.contains(&o) { new[n] } else { old[o] });
+                }
+            }
+            _ => {
+                // Pure insertions touch the lines they sit between
+                let touches = if old_range.is_empty() {
+                    lines.contains(&old_range.start)
+                        || old_range.start.checked_sub(1).is_some_and(|line| lines.contains(&line))
+                } else {
+                    old_range.clone().any(|line| lines.contains(&line))
+                };
+                let slices = if touches { &new[new_range] } else { &old[old_range] };
+                merged.extend(slices.iter().copied());
+            }
+        }
+    }
+    merged
+}
+
 /// Print format result summary
 pub fn print_format_summary(result: &SafeFormatResult) {
-    println!();
+    textln!();
 
     let mode = if result.was_preview { " (preview)" } else { "" };
 
     if result.failed_files.is_empty() {
-        print!("{} ", "✓".green());
+        text!("{} ", "✓".green());
     } else {
-        print!("{} ", "⚠".yellow());
+        text!("{} ", "⚠".yellow());
     }
 
-    println!(
+    textln!(
         "Format complete{}: {} formatted, {} unchanged, {} failed in {:.2}s",
         mode.yellow(),
         result.formatted_files.len(),
@@ -467,7 +578,7 @@ pub fn print_format_summary(result: &SafeFormatResult) {
     );
 
     if result.lines_changed > 0 {
-        println!(
+        textln!(
             "  {} lines changed across {} files",
             result.lines_changed,
             result.formatted_files.len()
@@ -475,7 +586,7 @@ pub fn print_format_summary(result: &SafeFormatResult) {
     }
 
     if let Some(ref snapshot) = result.snapshot_id {
-        println!(
+        textln!(
             "  {} Snapshot: foodshare-ios protect restore --snapshot {}",
             "📸".blue(),
             snapshot
@@ -483,7 +594,7 @@ pub fn print_format_summary(result: &SafeFormatResult) {
     }
 
     if let Some(ref backup) = result.backup_ref {
-        println!(
+        textln!(
             "  {} Stash: git stash apply (search for '{}')",
             "💾".blue(),
             backup
@@ -491,10 +602,10 @@ pub fn print_format_summary(result: &SafeFormatResult) {
     }
 
     if !result.failed_files.is_empty() {
-        println!();
-        println!("  {} Failed files:", "Errors:".red().bold());
+        textln!();
+        textln!("  {} Failed files:", "Errors:".red().bold());
         for (file, error) in &result.failed_files {
-            println!("    {} {}: {}", "✗".red(), file.display(), error);
+            textln!("    {} {}: {}", "✗".red(), file.display(), error);
         }
     }
 }
@@ -534,6 +645,8 @@ pub struct PrePushConfig {
     pub quick_mode: bool,
     /// Checks to skip
     pub skip_checks: Vec<String>,
+    /// Skip the build when sources are unchanged since the last successful one
+    pub build_cache: bool,
 }
 
 impl Default for PrePushConfig {
@@ -543,6 +656,7 @@ impl Default for PrePushConfig {
             release: false,
             quick_mode: false,
             skip_checks: Vec::new(),
+            build_cache: true,
         }
     }
 }
@@ -551,10 +665,10 @@ impl Default for PrePushConfig {
 pub fn run_pre_push_checks(config: &PrePushConfig) -> Vec<PrePushCheckResult> {
     let mut results = Vec::new();
 
-    println!();
-    println!("{}", "Pre-push Validation".bold());
-    println!("{}", "═".repeat(50));
-    println!();
+    textln!();
+    textln!("{}", "Pre-push Validation".bold());
+    textln!("{}", "═".repeat(50));
+    textln!();
 
     // Define checks as a struct-based approach for type safety
     struct CheckDef {
@@ -566,6 +680,7 @@ pub fn run_pre_push_checks(config: &PrePushConfig) -> Vec<PrePushCheckResult> {
     let checks = vec![
         CheckDef { name: "lint", description: "Swift lint check", required: true },
         CheckDef { name: "build", description: "Build validation", required: true },
+        CheckDef { name: "l10n", description: "Localization lint", required: true },
         CheckDef { name: "test", description: "Unit tests", required: false },
     ];
 
@@ -580,7 +695,7 @@ pub fn run_pre_push_checks(config: &PrePushConfig) -> Vec<PrePushCheckResult> {
                 skipped: true,
                 required: check.required,
             });
-            println!("  {} {} {}", "⊘".dimmed(), check.name.dimmed(), "(skipped)".dimmed());
+            textln!("  {} {} {}", "⊘".dimmed(), check.name.dimmed(), "(skipped)".dimmed());
             continue;
         }
 
@@ -594,12 +709,12 @@ pub fn run_pre_push_checks(config: &PrePushConfig) -> Vec<PrePushCheckResult> {
                 skipped: true,
                 required: check.required,
             });
-            println!("  {} {} {}", "⊘".dimmed(), check.name.dimmed(), "(quick mode)".dimmed());
+            textln!("  {} {} {}", "⊘".dimmed(), check.name.dimmed(), "(quick mode)".dimmed());
             continue;
         }
 
         // Run the check
-        print!("  {} {}...", "●".blue(), check.description);
+        text!("  {} {}...", "●".blue(), check.description);
         use std::io::Write;
         std::io::stdout().flush().ok();
 
@@ -607,6 +722,7 @@ pub fn run_pre_push_checks(config: &PrePushConfig) -> Vec<PrePushCheckResult> {
         let check_result = match check.name {
             "lint" => check_lint(config),
             "build" => check_build(config),
+            "l10n" => check_localization(config),
             "test" => check_tests(config),
             _ => Ok(()),
         };
@@ -625,16 +741,16 @@ pub fn run_pre_push_checks(config: &PrePushConfig) -> Vec<PrePushCheckResult> {
         });
 
         // Clear line and print result
-        print!("\r");
+        text!("\r");
         if success {
-            println!(
+            textln!(
                 "  {} {} {}",
                 "✓".green(),
                 check.description,
                 format!("({:.1}s)", duration.as_secs_f32()).dimmed()
             );
         } else if !check.required {
-            println!(
+            textln!(
                 "  {} {} {} {}",
                 "⚠".yellow(),
                 check.description.yellow(),
@@ -644,11 +760,11 @@ pub fn run_pre_push_checks(config: &PrePushConfig) -> Vec<PrePushCheckResult> {
 
             if let Some(ref err) = output {
                 for line in err.lines().take(3) {
-                    println!("    {}", line.dimmed());
+                    textln!("    {}", line.dimmed());
                 }
             }
         } else {
-            println!(
+            textln!(
                 "  {} {} {}",
                 "✗".red(),
                 check.description.red(),
@@ -658,7 +774,7 @@ pub fn run_pre_push_checks(config: &PrePushConfig) -> Vec<PrePushCheckResult> {
             if let Some(ref err) = output {
                 // Show first few lines of error
                 for line in err.lines().take(5) {
-                    println!("    {}", line.dimmed());
+                    textln!("    {}", line.dimmed());
                 }
             }
 
@@ -687,23 +803,47 @@ fn check_lint(_config: &PrePushConfig) -> std::result::Result<(), String> {
 }
 
 fn check_build(config: &PrePushConfig) -> std::result::Result<(), String> {
+    use crate::build_cache::{source_hash, BuildCache};
+
     let configuration = if config.release { "Release" } else { "Debug" };
+    let destination = "platform=iOS Simulator,name=iPhone 17 Pro Max";
 
-    let result = crate::xcode::build(
-        "FoodShare",
-        configuration,
-        "platform=iOS Simulator,name=iPhone 17 Pro Max",
-        false,
-    )
-    .map_err(|e| e.to_string())?;
+    let root = std::env::current_dir().map_err(|e| e.to_string())?;
+    let cache = BuildCache::open(&root);
+    let key = if config.build_cache {
+        let settings = ["build", "FoodShare", configuration, destination];
+        Some(source_hash(&root, &settings).map_err(|e| e.to_string())?)
+    } else {
+        None
+    };
+    if key.as_deref().is_some_and(|key| cache.is_fresh(key)) {
+        return Ok(());
+    }
+
+    let result = crate::xcode::build("FoodShare", configuration, destination, false)
+        .map_err(|e| e.to_string())?;
 
     if result.success {
+        if let Some(key) = &key {
+            cache.record(key, None).map_err(|e| e.to_string())?;
+        }
         Ok(())
     } else {
         Err(result.stderr)
     }
 }
 
+fn check_localization(_config: &PrePushConfig) -> std::result::Result<(), String> {
+    let report = crate::localization::lint(Path::new("."), crate::localization::SOURCE_LOCALE)
+        .map_err(|e| e.to_string())?;
+
+    if report.error_count() == 0 {
+        Ok(())
+    } else {
+        Err(report.summary())
+    }
+}
+
 fn check_tests(_config: &PrePushConfig) -> std::result::Result<(), String> {
     let result = crate::xcode::test(
         "FoodShare",
@@ -721,8 +861,8 @@ fn check_tests(_config: &PrePushConfig) -> std::result::Result<(), String> {
 
 /// Print pre-push summary
 pub fn print_pre_push_summary(results: &[PrePushCheckResult]) -> i32 {
-    println!();
-    println!("{}", "─".repeat(50));
+    textln!();
+    textln!("{}", "─".repeat(50));
 
     let passed = results.iter().filter(|r| r.success && !r.skipped).count();
     let failed_required = results.iter().filter(|r| !r.success && r.required).count();
@@ -732,7 +872,7 @@ pub fn print_pre_push_summary(results: &[PrePushCheckResult]) -> i32 {
 
     if failed_required == 0 {
         if warned > 0 {
-            println!(
+            textln!(
                 "{} Checks passed with {} warning(s) ({} passed, {} skipped) in {:.1}s",
                 "✓".green().bold(),
                 warned,
@@ -741,7 +881,7 @@ pub fn print_pre_push_summary(results: &[PrePushCheckResult]) -> i32 {
                 total_time.as_secs_f32()
             );
         } else {
-            println!(
+            textln!(
                 "{} All checks passed ({} passed, {} skipped) in {:.1}s",
                 "✓".green().bold(),
                 passed,
@@ -749,10 +889,10 @@ pub fn print_pre_push_summary(results: &[PrePushCheckResult]) -> i32 {
                 total_time.as_secs_f32()
             );
         }
-        println!();
+        textln!();
         exit_codes::SUCCESS
     } else {
-        println!(
+        textln!(
             "{} {} check(s) failed ({} passed, {} warned, {} skipped)",
             "✗".red().bold(),
             failed_required,
@@ -760,33 +900,20 @@ pub fn print_pre_push_summary(results: &[PrePushCheckResult]) -> i32 {
             warned,
             skipped
         );
-        println!();
+        textln!();
 
         // Show recovery hint
-        println!(
+        textln!(
             "  {} To push anyway: {}",
             "ℹ".blue(),
             "git push --no-verify".yellow()
         );
-        println!();
+        textln!();
 
         exit_codes::FAILURE
     }
 }
 
-// ============================================================================
-// UTILITIES
-// ============================================================================
-
-/// Truncate string with ellipsis
-fn truncate(s: &str, max_len: usize) -> String {
-    if s.len() <= max_len {
-        s.to_string()
-    } else {
-        format!("{}...", &s[..max_len - 3])
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -801,9 +928,21 @@ mod tests {
     }
 
     #[test]
-    fn test_truncate() {
-        assert_eq!(truncate("hello", 10), "hello");
-        assert_eq!(truncate("hello world", 8), "hello...");
+    fn test_staged_lines_only() {
+        let head = "struct A {\nlet a=1\n}\n";
+        let index = "struct A {\nlet a=1\nlet b=2\n}\n";
+        let worktree = "struct A {\nlet a=1\nlet b=2\nlet c=3\n}\n";
+        let lines = staged_lines(Some(head), index, worktree);
+        assert_eq!(lines, BTreeSet::from([2]));
+
+        let formatted = "struct A {\n    let a = 1\n    let b = 2\n    let c = 3\n}\n";
+        assert_eq!(
+            apply_to_lines(worktree, formatted, &lines),
+            "struct A {\nlet a=1\n    let b = 2\nlet c=3\n}\n"
+        );
+
+        // A new file is staged in full
+        assert_eq!(staged_lines(None, index, index).len(), 4);
     }
 
     #[test]
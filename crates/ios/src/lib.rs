@@ -2,17 +2,37 @@
 //!
 //! This crate provides iOS/Xcode-specific functionality:
 //! - Xcode project manipulation
-//! - Simulator management
-//! - Swift tooling wrappers
-//! - Build analysis
+//! - Simulator and physical device management
+//! - Code signing diagnostics
+//! - Swift tooling wrappers and SwiftLint/SwiftFormat config drift checks
+//! - Swift Package Manager dependency audit
+//! - Build analysis, source-hash build caching, test result parsing and flaky test tracking
+//! - App size analysis
+//! - Localization lint
+//! - Crash report symbolication
 //! - Enterprise-grade git hooks
-//! - Code protection system
+//! - Code protection system, with encrypted remote snapshot backup
 
 #![warn(missing_docs)]
 
+pub mod app_size;
+pub mod build_cache;
+pub mod build_log;
 pub mod code_protection;
+pub mod crash;
+pub mod device;
 pub mod hooks;
+pub mod localization;
+pub mod pbxproj;
+pub mod plist;
+pub mod scheme;
+pub mod signing;
 pub mod simulator;
+pub mod snapshot_sync;
+pub mod spm;
+pub mod style_config;
 pub mod swift_tools;
+pub mod test_history;
 pub mod xcode;
 pub mod xcodeproj;
+pub mod xcresult;
@@ -0,0 +1,584 @@
+//! Localization lint
+//!
+//! Compares every locale of the app's string tables against the source
+//! locale. Both the classic `<locale>.lproj/<Table>.strings` files and
+//! String Catalogs (`.xcstrings`) are read. Reported per locale:
+//!
+//! - **missing**: a source key the locale doesn't have
+//! - **stale**: a key no longer in the source (or marked stale by Xcode)
+//! - **placeholder mismatch**: format specifiers that differ in type or
+//!   order from the source, which crash or garble text at runtime
+//! - **untranslated**: a value identical to the source, or a catalog entry
+//!   not marked translated
+//!
+//! Placeholder mismatches are errors; the rest are warnings.
+
+use foodshare_cli::textln;
+use foodshare_core::error::Result;
+use once_cell::sync::Lazy;
+use owo_colors::OwoColorize;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Default source locale
+pub const SOURCE_LOCALE: &str = "en";
+
+/// Directories never searched for string tables
+const SKIP_DIRS: &[&str] = &[".git", "build", "DerivedData", "Pods", ".build", "Carthage", "node_modules"];
+
+/// A format specifier: `%@`, `%d`, `%1$@`, `%lld`, `%.2f`
+static FORMAT_SPECIFIER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"%(?:(\d+)\$)?[-+ #0']*\d*(?:\.\d+)?(hh|h|ll|l|q|z|t|j|L)?([@dDiuUxXoOfFeEgGcCsSpaA%])").unwrap()
+});
+
+/// Kind of localization problem
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum IssueKind {
+    /// Source key missing from the locale
+    Missing,
+    /// Key that is no longer in the source
+    Stale,
+    /// Format specifiers differ from the source
+    PlaceholderMismatch {
+        /// Specifiers in the source, in argument order
+        expected: String,
+        /// Specifiers in the translation, in argument order
+        found: String,
+    },
+    /// Value not translated yet
+    Untranslated,
+}
+
+impl IssueKind {
+    /// Whether the issue breaks the app at runtime
+    pub fn is_error(&self) -> bool {
+        matches!(self, Self::PlaceholderMismatch { .. })
+    }
+}
+
+/// A problem with one key in one locale
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LocalizationIssue {
+    /// Locale code (`de`, `pt-BR`)
+    pub locale: String,
+    /// Table the key belongs to (`Localizable`)
+    pub table: String,
+    /// String key
+    pub key: String,
+    /// What is wrong
+    #[serde(flatten)]
+    pub kind: IssueKind,
+    /// File the key lives in (or should)
+    pub file: PathBuf,
+}
+
+/// Issue counts of one locale
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LocaleCounts {
+    /// Missing keys
+    pub missing: usize,
+    /// Stale keys
+    pub stale: usize,
+    /// Placeholder mismatches
+    pub placeholders: usize,
+    /// Untranslated values
+    pub untranslated: usize,
+}
+
+/// Result of linting every string table under a directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LocalizationReport {
+    /// Source locale the others are compared against
+    pub source_locale: String,
+    /// Every locale seen, sorted
+    pub locales: Vec<String>,
+    /// Number of tables checked
+    pub tables: usize,
+    /// Issues, grouped by table and locale
+    pub issues: Vec<LocalizationIssue>,
+}
+
+impl LocalizationReport {
+    /// Issue counts per locale
+    pub fn counts(&self) -> BTreeMap<String, LocaleCounts> {
+        let mut counts: BTreeMap<String, LocaleCounts> = self
+            .locales
+            .iter()
+            .map(|locale| (locale.clone(), LocaleCounts::default()))
+            .collect();
+        for issue in &self.issues {
+            let entry = counts.entry(issue.locale.clone()).or_default();
+            match issue.kind {
+                IssueKind::Missing => entry.missing += 1,
+                IssueKind::Stale => entry.stale += 1,
+                IssueKind::PlaceholderMismatch { .. } => entry.placeholders += 1,
+                IssueKind::Untranslated => entry.untranslated += 1,
+            }
+        }
+        counts
+    }
+
+    /// Number of errors
+    pub fn error_count(&self) -> usize {
+        self.issues.iter().filter(|i| i.kind.is_error()).count()
+    }
+
+    /// One line per locale with its counts
+    pub fn summary(&self) -> String {
+        self.counts()
+            .iter()
+            .map(|(locale, c)| {
+                format!(
+                    "{}: {} missing, {} stale, {} placeholder mismatches, {} untranslated",
+                    locale, c.missing, c.stale, c.placeholders, c.untranslated
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Print per-locale counts, then the issues (up to `limit` per locale)
+    pub fn print(&self, limit: usize) {
+        textln!(
+            "{}",
+            format!("Localization ({} tables, source: {})", self.tables, self.source_locale).bold()
+        );
+        for (locale, c) in self.counts() {
+            let line = format!(
+                "  {:<8} {:>4} missing  {:>4} stale  {:>4} placeholders  {:>4} untranslated",
+                locale, c.missing, c.stale, c.placeholders, c.untranslated
+            );
+            if c.placeholders > 0 {
+                textln!("{}", line.red());
+            } else if c.missing + c.stale + c.untranslated > 0 {
+                textln!("{}", line.yellow());
+            } else {
+                textln!("{}", line);
+            }
+        }
+
+        let mut shown: BTreeMap<&str, usize> = BTreeMap::new();
+        let mut sorted: Vec<&LocalizationIssue> = self.issues.iter().collect();
+        // Errors first, so a limit never hides them
+        sorted.sort_by_key(|issue| !issue.kind.is_error());
+
+        let mut printed_header = false;
+        for issue in sorted {
+            let count = shown.entry(&issue.locale).or_default();
+            *count += 1;
+            if *count > limit {
+                continue;
+            }
+            if !printed_header {
+                textln!();
+                printed_header = true;
+            }
+            let description = match &issue.kind {
+                IssueKind::Missing => "missing".yellow().to_string(),
+                IssueKind::Stale => "stale".yellow().to_string(),
+                IssueKind::Untranslated => "untranslated".yellow().to_string(),
+                IssueKind::PlaceholderMismatch { expected, found } => {
+                    format!("placeholders {} (source has {})", found, expected).red().to_string()
+                }
+            };
+            textln!("  [{}] {}.{}: {}", issue.locale, issue.table, issue.key, description);
+        }
+    }
+}
+
+/// Values of one table in one locale
+#[derive(Debug, Clone, Default)]
+struct Table {
+    file: PathBuf,
+    /// Key to (value, translated)
+    entries: BTreeMap<String, (String, bool)>,
+    /// Keys Xcode marked as no longer extracted from source
+    stale: Vec<String>,
+}
+
+/// Lint every string table under `root`
+pub fn lint(root: &Path, source_locale: &str) -> Result<LocalizationReport> {
+    let mut report = LocalizationReport {
+        source_locale: source_locale.to_string(),
+        ..Default::default()
+    };
+    // (directory containing the .lproj folders, table name) -> locale -> table
+    let mut strings_tables: BTreeMap<(PathBuf, String), BTreeMap<String, Table>> = BTreeMap::new();
+
+    let walker = WalkDir::new(root).into_iter().filter_entry(|entry| {
+        !(entry.file_type().is_dir() && SKIP_DIRS.contains(&entry.file_name().to_string_lossy().as_ref()))
+    });
+    for entry in walker.filter_map(|e| e.ok()).filter(|e| e.file_type().is_file()) {
+        let path = entry.path();
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("xcstrings") => {
+                let content = std::fs::read_to_string(path)?;
+                let (source, tables) = parse_xcstrings(&content, path)?;
+                let name = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+                let source = if source.is_empty() { source_locale.to_string() } else { source };
+                compare(&name, &source, &tables, &mut report);
+                report.tables += 1;
+            }
+            Some("strings") => {
+                let Some(lproj) = path.parent() else { continue };
+                let Some(locale) = lproj
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .and_then(|n| n.strip_suffix(".lproj"))
+                else {
+                    continue;
+                };
+                let name = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+                let container = lproj.parent().unwrap_or(root).to_path_buf();
+                let table = Table {
+                    file: path.to_path_buf(),
+                    entries: parse_strings(&read_strings_file(path)?)
+                        .into_iter()
+                        .map(|(k, v)| (k, (v, true)))
+                        .collect(),
+                    stale: Vec::new(),
+                };
+                strings_tables
+                    .entry((container, name))
+                    .or_default()
+                    .insert(locale.to_string(), table);
+            }
+            _ => {}
+        }
+    }
+
+    for ((_, name), tables) in &strings_tables {
+        // Projects without an explicit source lproj keep it in Base
+        let source = if tables.contains_key(source_locale) { source_locale } else { "Base" };
+        compare(name, source, tables, &mut report);
+        report.tables += 1;
+    }
+
+    report.locales.sort();
+    report.locales.dedup();
+    Ok(report)
+}
+
+/// Compare every locale of a table with its source locale
+fn compare(name: &str, source_locale: &str, tables: &BTreeMap<String, Table>, report: &mut LocalizationReport) {
+    let Some(source) = tables.get(source_locale) else {
+        return;
+    };
+    report.locales.extend(tables.keys().filter(|l| *l != "Base").cloned());
+    let issue = |locale: &str, key: &str, kind: IssueKind, file: &Path| LocalizationIssue {
+        locale: locale.to_string(),
+        table: name.to_string(),
+        key: key.to_string(),
+        kind,
+        file: file.to_path_buf(),
+    };
+
+    for key in &source.stale {
+        report.issues.push(issue(source_locale, key, IssueKind::Stale, &source.file));
+    }
+
+    for (locale, table) in tables.iter().filter(|(locale, _)| *locale != source_locale && *locale != "Base") {
+        for (key, (source_value, _)) in &source.entries {
+            if source.stale.contains(key) {
+                continue;
+            }
+            let Some((value, translated)) = table.entries.get(key) else {
+                report.issues.push(issue(locale, key, IssueKind::Missing, &table.file));
+                continue;
+            };
+
+            let expected = placeholders(source_value);
+            let found = placeholders(value);
+            if expected != found {
+                report.issues.push(issue(
+                    locale,
+                    key,
+                    IssueKind::PlaceholderMismatch {
+                        expected: describe(&expected),
+                        found: describe(&found),
+                    },
+                    &table.file,
+                ));
+            } else if !translated || (value == source_value && value.chars().any(char::is_alphabetic)) {
+                report.issues.push(issue(locale, key, IssueKind::Untranslated, &table.file));
+            }
+        }
+        for key in table.entries.keys().filter(|key| !source.entries.contains_key(*key)) {
+            report.issues.push(issue(locale, key, IssueKind::Stale, &table.file));
+        }
+    }
+}
+
+/// Format specifiers of a string in argument order, normalized (`%i` is `%d`)
+fn placeholders(value: &str) -> Vec<String> {
+    let mut next = 0;
+    let mut specifiers: Vec<(usize, String)> = FORMAT_SPECIFIER
+        .captures_iter(value)
+        .filter(|caps| &caps[3] != "%")
+        .map(|caps| {
+            let position = match caps.get(1).and_then(|p| p.as_str().parse::<usize>().ok()) {
+                Some(position) => position,
+                None => {
+                    next += 1;
+                    next
+                }
+            };
+            let conversion = match &caps[3] {
+                "i" => "d",
+                other => other,
+            };
+            let length = caps.get(2).map_or("", |l| l.as_str());
+            (position, format!("%{}{}", length, conversion))
+        })
+        .collect();
+    specifiers.sort_by_key(|(position, _)| *position);
+    specifiers.into_iter().map(|(_, specifier)| specifier).collect()
+}
+
+fn describe(specifiers: &[String]) -> String {
+    if specifiers.is_empty() {
+        "none".to_string()
+    } else {
+        specifiers.join(" ")
+    }
+}
+
+/// Read a `.strings` file, which may be UTF-16 (older Xcode default) or UTF-8
+fn read_strings_file(path: &Path) -> Result<String> {
+    let bytes = std::fs::read(path)?;
+    let utf16 = |bytes: &[u8], big_endian: bool| {
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|pair| {
+                if big_endian {
+                    u16::from_be_bytes([pair[0], pair[1]])
+                } else {
+                    u16::from_le_bytes([pair[0], pair[1]])
+                }
+            })
+            .collect();
+        String::from_utf16_lossy(&units)
+    };
+    Ok(match bytes.as_slice() {
+        [0xff, 0xfe, rest @ ..] => utf16(rest, false),
+        [0xfe, 0xff, rest @ ..] => utf16(rest, true),
+        [0xef, 0xbb, 0xbf, rest @ ..] => String::from_utf8_lossy(rest).to_string(),
+        _ => String::from_utf8_lossy(&bytes).to_string(),
+    })
+}
+
+/// Parse `"key" = "value";` pairs, skipping comments
+fn parse_strings(content: &str) -> BTreeMap<String, String> {
+    let mut entries = BTreeMap::new();
+    let mut chars = content.chars().peekable();
+    let mut pending_key: Option<String> = None;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '"' => {
+                let mut text = String::new();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => match chars.next() {
+                            Some('n') => text.push('\n'),
+                            Some('t') => text.push('\t'),
+                            Some(other) => text.push(other),
+                            None => {}
+                        },
+                        other => text.push(other),
+                    }
+                }
+                match pending_key.take() {
+                    Some(key) => {
+                        entries.insert(key, text);
+                    }
+                    None => pending_key = Some(text),
+                }
+            }
+            ';' => pending_key = None,
+            _ => {}
+        }
+    }
+    entries
+}
+
+/// Parse a String Catalog into its source locale and a table per locale
+fn parse_xcstrings(content: &str, path: &Path) -> Result<(String, BTreeMap<String, Table>)> {
+    let json: serde_json::Value = serde_json::from_str(content)?;
+    let source = json["sourceLanguage"].as_str().unwrap_or_default().to_string();
+    let new_table = || Table {
+        file: path.to_path_buf(),
+        ..Default::default()
+    };
+    // The source table always exists, even when every key uses its own text
+    let mut tables: BTreeMap<String, Table> = BTreeMap::new();
+    tables.insert(source.clone(), new_table());
+
+    let Some(strings) = json["strings"].as_object() else {
+        return Ok((source, tables));
+    };
+    for (key, entry) in strings {
+        if entry["shouldTranslate"].as_bool() == Some(false) {
+            continue;
+        }
+        let localizations = entry["localizations"].as_object();
+
+        // Keys without a source localization use the key as their text
+        let source_value = localizations
+            .and_then(|l| l.get(&source))
+            .and_then(catalog_value)
+            .map_or_else(|| key.clone(), |(value, _)| value);
+        let source_table = tables.get_mut(&source).expect("source table");
+        source_table.entries.insert(key.clone(), (source_value, true));
+        if entry["extractionState"].as_str() == Some("stale") {
+            source_table.stale.push(key.clone());
+        }
+
+        for (locale, localization) in localizations.into_iter().flatten() {
+            if *locale == source {
+                continue;
+            }
+            if let Some(value) = catalog_value(localization) {
+                tables.entry(locale.clone()).or_insert_with(new_table).entries
+                    .insert(key.clone(), value);
+            }
+        }
+    }
+    Ok((source, tables))
+}
+
+/// Value and translated state of a catalog localization
+///
+/// Plural and device variations are compared through their `other` case,
+/// since other cases may legitimately drop the number.
+fn catalog_value(localization: &serde_json::Value) -> Option<(String, bool)> {
+    let unit = if localization["stringUnit"].is_object() {
+        &localization["stringUnit"]
+    } else {
+        let variations = localization["variations"].as_object()?;
+        let cases = variations.values().next()?;
+        let case = cases.get("other").or_else(|| cases.as_object()?.values().next())?;
+        &case["stringUnit"]
+    };
+    let value = unit["value"].as_str()?.to_string();
+    let translated = unit["state"].as_str().is_none_or(|state| state == "translated");
+    Some((value, translated))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placeholders() {
+        assert_eq!(placeholders("%@ shared %lld items"), ["%@", "%lld"]);
+        assert_eq!(placeholders("%2$lld Artikel von %1$@"), ["%@", "%lld"]);
+        assert_eq!(placeholders("100%% of %i"), ["%d"]);
+        assert_eq!(placeholders("%.1f km"), ["%f"]);
+        assert!(placeholders("No placeholders").is_empty());
+    }
+
+    #[test]
+    fn test_lint_strings_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |path: &str, content: &str| {
+            let path = dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write(
+            "FoodShare/en.lproj/Localizable.strings",
+            "/* Feed title */\n\"feed.title\" = \"Nearby food\";\n\"feed.count\" = \"%@ shared %lld items\";\n// Button\n\"button.ok\" = \"OK\";\n\"button.share\" = \"Share \\\"now\\\"\";\n",
+        );
+        write(
+            "FoodShare/de.lproj/Localizable.strings",
+            "\"feed.title\" = \"Essen in der Nähe\";\n\"feed.count\" = \"%lld Artikel von %@\";\n\"button.ok\" = \"Okay\";\n\"old.key\" = \"Alt\";\n",
+        );
+        write(
+            "FoodShare/fr.lproj/Localizable.strings",
+            "\"feed.title\" = \"Nearby food\";\n\"feed.count\" = \"%2$lld articles de %1$@\";\n\"button.ok\" = \"D'accord\";\n\"button.share\" = \"Partager\";\n",
+        );
+
+        let report = lint(dir.path(), "en").unwrap();
+        assert_eq!(report.tables, 1);
+        assert_eq!(report.locales, ["de", "en", "fr"]);
+
+        let counts = report.counts();
+        assert_eq!(
+            counts["de"],
+            LocaleCounts { missing: 1, stale: 1, placeholders: 1, untranslated: 0 }
+        );
+        assert_eq!(
+            counts["fr"],
+            LocaleCounts { missing: 0, stale: 0, placeholders: 0, untranslated: 1 }
+        );
+        assert_eq!(report.error_count(), 1);
+        let mismatch = report.issues.iter().find(|i| i.kind.is_error()).unwrap();
+        assert_eq!(mismatch.key, "feed.count");
+        assert_eq!(
+            mismatch.kind,
+            IssueKind::PlaceholderMismatch { expected: "%@ %lld".to_string(), found: "%lld %@".to_string() }
+        );
+    }
+
+    #[test]
+    fn test_lint_string_catalog() {
+        let dir = tempfile::tempdir().unwrap();
+        let catalog = r#"{
+  "sourceLanguage" : "en",
+  "strings" : {
+    "Hello, %@!" : {
+      "localizations" : {
+        "de" : { "stringUnit" : { "state" : "translated", "value" : "Hallo, %@!" } },
+        "es" : { "stringUnit" : { "state" : "new", "value" : "Hello, %@!" } }
+      }
+    },
+    "items.count" : {
+      "localizations" : {
+        "en" : { "variations" : { "plural" : {
+          "one" : { "stringUnit" : { "state" : "translated", "value" : "One item" } },
+          "other" : { "stringUnit" : { "state" : "translated", "value" : "%lld items" } }
+        } } },
+        "de" : { "variations" : { "plural" : {
+          "one" : { "stringUnit" : { "state" : "translated", "value" : "Ein Artikel" } },
+          "other" : { "stringUnit" : { "state" : "translated", "value" : "%@ Artikel" } }
+        } } }
+      }
+    },
+    "Removed" : { "extractionState" : "stale" },
+    "FoodShare" : { "shouldTranslate" : false }
+  },
+  "version" : "1.0"
+}"#;
+        std::fs::write(dir.path().join("Localizable.xcstrings"), catalog).unwrap();
+
+        let report = lint(dir.path(), "en").unwrap();
+        let counts = report.counts();
+        assert_eq!(counts["de"].placeholders, 1);
+        assert_eq!(counts["es"], LocaleCounts { missing: 1, stale: 0, placeholders: 0, untranslated: 1 });
+        assert_eq!(counts["en"].stale, 1);
+        assert!(report.summary().contains("es: 1 missing"));
+    }
+}
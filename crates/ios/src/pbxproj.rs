@@ -0,0 +1,1419 @@
+//! project.pbxproj parsing and editing
+//!
+//! Xcode stores projects as an old-style (OpenStep) property list.
+//! [`Document`] parses the whole file into a tree of [`Value`]s and keeps the
+//! whitespace and comments around every token, so writing it back reproduces
+//! the input byte for byte. Values added through the API are laid out the way
+//! Xcode writes them, which keeps diffs down to the lines that changed.
+//!
+//! [`split_conflicts`] and [`Document::merge`] resolve files left with git
+//! conflict markers by merging both sides as parsed projects.
+
+use foodshare_core::error::{Error, Result};
+use std::collections::HashMap;
+use std::fmt;
+
+/// A parsed `project.pbxproj`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    root: Value,
+    trailing: String,
+}
+
+/// A string, array or dictionary, with the whitespace and comments before it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Value {
+    leading: String,
+    node: Node,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Scalar(Scalar),
+    Array(Array),
+    Dict(Dict),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Scalar {
+    /// Decoded value
+    value: String,
+    /// Token as written, including quotes
+    raw: String,
+    /// Comment on the same line after the token (`/* App.swift */`), with the
+    /// spaces before it
+    annotation: String,
+}
+
+/// Dictionary, keeping its entries in file order
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Dict {
+    entries: Vec<Entry>,
+    close: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+    key: Value,
+    before_eq: String,
+    value: Value,
+    before_semi: String,
+}
+
+/// Array of values
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Array {
+    items: Vec<Item>,
+    close: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Item {
+    value: Value,
+    /// Trivia before the comma, `None` for a last item without one
+    comma: Option<String>,
+}
+
+// ============================================================================
+// Document
+// ============================================================================
+
+impl Document {
+    /// Parse the contents of a `project.pbxproj`
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut parser = Parser { text, pos: 0 };
+        let root = parser.value()?;
+        let trailing = parser.trivia()?;
+        if parser.pos < text.len() {
+            return Err(parser.error("unexpected content after the root dictionary"));
+        }
+
+        let has_objects = root
+            .as_dict()
+            .and_then(|root| root.get("objects"))
+            .and_then(Value::as_dict)
+            .is_some();
+        if !has_objects {
+            return Err(Error::validation(
+                "Invalid project.pbxproj: no objects dictionary",
+            ));
+        }
+        Ok(Self { root, trailing })
+    }
+
+    /// Root dictionary (`archiveVersion`, `objects`, `rootObject`, ...)
+    pub fn root(&self) -> &Dict {
+        self.root.as_dict().expect("root is checked in parse")
+    }
+
+    /// ID of the PBXProject object
+    pub fn root_object_id(&self) -> Option<&str> {
+        self.root().get_str("rootObject")
+    }
+
+    /// All objects, keyed by ID
+    pub fn objects(&self) -> &Dict {
+        self.root()
+            .get("objects")
+            .and_then(Value::as_dict)
+            .expect("objects are checked in parse")
+    }
+
+    /// All objects, keyed by ID
+    pub fn objects_mut(&mut self) -> &mut Dict {
+        self.root
+            .as_dict_mut()
+            .and_then(|root| root.get_mut("objects"))
+            .and_then(Value::as_dict_mut)
+            .expect("objects are checked in parse")
+    }
+
+    /// Object with the given ID
+    pub fn object(&self, id: &str) -> Option<&Dict> {
+        self.objects().get(id).and_then(Value::as_dict)
+    }
+
+    /// Object with the given ID
+    pub fn object_mut(&mut self, id: &str) -> Option<&mut Dict> {
+        self.objects_mut().get_mut(id).and_then(Value::as_dict_mut)
+    }
+
+    /// Objects of one type, as `(id, object)` pairs in file order
+    pub fn objects_of<'a>(&'a self, isa: &'a str) -> impl Iterator<Item = (&'a str, &'a Dict)> {
+        self.objects().iter().filter_map(move |(id, value)| {
+            value
+                .as_dict()
+                .filter(|object| object.get_str("isa") == Some(isa))
+                .map(|object| (id, object))
+        })
+    }
+
+    /// Add an object at the end of the section for its `isa`
+    ///
+    /// The section (`/* Begin PBXGroup section */ ... /* End ... */`) is
+    /// created in alphabetical order if the project has none for that type yet.
+    pub fn insert_object(&mut self, id: &str, comment: &str, object: Value) {
+        let isa = object
+            .as_dict()
+            .and_then(|object| object.get_str("isa"))
+            .unwrap_or_default()
+            .to_string();
+        let objects = self.objects_mut();
+        let isa_of = |entry: &Entry| {
+            entry
+                .value
+                .as_dict()
+                .and_then(|object| object.get_str("isa"))
+                .unwrap_or_default()
+                .to_string()
+        };
+        let indent = objects
+            .entries
+            .first()
+            .and_then(|entry| indent_of(&entry.key.leading))
+            .unwrap_or("\t\t")
+            .to_string();
+
+        let (index, leading) = match objects.entries.iter().rposition(|e| isa_of(e) == isa) {
+            Some(last) => (last + 1, format!("\n{indent}")),
+            None => {
+                let index = objects
+                    .entries
+                    .iter()
+                    .position(|e| isa_of(e) > isa)
+                    .unwrap_or(objects.entries.len());
+                let next = match objects.entries.get_mut(index) {
+                    Some(entry) => &mut entry.key.leading,
+                    None => &mut objects.close,
+                };
+
+                // The previous section's end marker stays in front of the new section
+                let split = next
+                    .find("/* End ")
+                    .and_then(|start| next[start..].find("*/").map(|end| start + end + 2))
+                    .unwrap_or(0);
+                let leading = format!("{}\n\n/* Begin {isa} section */\n{indent}", &next[..split]);
+                *next = format!("\n/* End {isa} section */{}", &next[split..]);
+                (index, leading)
+            }
+        };
+
+        let mut key = Value::string(id);
+        if !comment.is_empty() {
+            key.set_comment(comment);
+        }
+        key.leading = leading;
+        let mut value = object;
+        value.leading = " ".to_string();
+        value.layout(&indent, false);
+        objects.entries.insert(
+            index,
+            Entry {
+                key,
+                before_eq: " ".to_string(),
+                value,
+                before_semi: String::new(),
+            },
+        );
+    }
+
+    /// Remove an object, dropping its section markers if it was the last of its type
+    pub fn remove_object(&mut self, id: &str) -> Option<Value> {
+        let objects = self.objects_mut();
+        let index = objects.position(id)?;
+        let removed = objects.entries.remove(index);
+        let leading = removed.key.leading;
+
+        if let Some(begin) = leading.find("/* Begin ") {
+            let next = match objects.entries.get_mut(index) {
+                Some(entry) => &mut entry.key.leading,
+                None => &mut objects.close,
+            };
+            *next = match next.find("/* End ") {
+                Some(start) => {
+                    let end = next[start..].find("*/").map_or(next.len(), |end| start + end + 2);
+                    format!("{}{}", leading[..begin].trim_end_matches('\n'), &next[end..])
+                }
+                // The next object now opens the section
+                None => leading,
+            };
+        }
+        Some(removed.value)
+    }
+
+    /// Every array property of every object (group children, build phase files, ...)
+    pub fn arrays_mut(&mut self) -> impl Iterator<Item = &mut Array> {
+        self.objects_mut()
+            .values_mut()
+            .filter_map(Value::as_dict_mut)
+            .flat_map(Dict::values_mut)
+            .filter_map(Value::as_array_mut)
+    }
+
+    /// Remove `id` from every array it appears in
+    pub fn remove_references(&mut self, id: &str) {
+        for array in self.arrays_mut() {
+            array.retain(|value| value.as_str() != Some(id));
+        }
+    }
+
+    /// Rename the comments next to every occurrence of `id`
+    ///
+    /// Xcode annotates IDs with the object's name (`/* App.swift */`), or with
+    /// the name and phase for build files (`/* App.swift in Sources */`).
+    pub fn rename_comments(&mut self, id: &str, old_name: &str, new_name: &str) {
+        let in_phase = format!("{old_name} in ");
+        self.root.walk_scalars_mut(&mut |value| {
+            if value.as_str() != Some(id) {
+                return;
+            }
+            let renamed = match value.comment() {
+                Some(comment) if comment == old_name => new_name.to_string(),
+                Some(comment) if comment.starts_with(&in_phase) => {
+                    format!("{new_name}{}", &comment[old_name.len()..])
+                }
+                _ => return,
+            };
+            value.set_comment(&renamed);
+        });
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.root, self.trailing)
+    }
+}
+
+// ============================================================================
+// Values
+// ============================================================================
+
+impl Value {
+    /// String value, quoted if Xcode would quote it
+    pub fn string(value: impl Into<String>) -> Self {
+        let value = value.into();
+        Self {
+            leading: String::new(),
+            node: Node::Scalar(Scalar {
+                raw: quote(&value),
+                value,
+                annotation: String::new(),
+            }),
+        }
+    }
+
+    /// Array value
+    pub fn array(values: impl IntoIterator<Item = Value>) -> Self {
+        let items = values
+            .into_iter()
+            .map(|value| Item {
+                value,
+                comma: Some(String::new()),
+            })
+            .collect();
+        Self {
+            leading: String::new(),
+            node: Node::Array(Array {
+                items,
+                close: String::new(),
+            }),
+        }
+    }
+
+    /// Dictionary value, with entries in the given order
+    pub fn dict<K: Into<String>>(entries: impl IntoIterator<Item = (K, Value)>) -> Self {
+        let entries = entries
+            .into_iter()
+            .map(|(key, value)| Entry {
+                key: Value::string(key),
+                before_eq: String::new(),
+                value,
+                before_semi: String::new(),
+            })
+            .collect();
+        Self {
+            leading: String::new(),
+            node: Node::Dict(Dict {
+                entries,
+                close: String::new(),
+            }),
+        }
+    }
+
+    /// Add a `/* comment */` after a string, as Xcode does after object IDs
+    pub fn with_comment(mut self, comment: &str) -> Self {
+        self.set_comment(comment);
+        self
+    }
+
+    /// String contents, if this is a string
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.node {
+            Node::Scalar(scalar) => Some(&scalar.value),
+            _ => None,
+        }
+    }
+
+    /// Array contents, if this is an array
+    pub fn as_array(&self) -> Option<&Array> {
+        match &self.node {
+            Node::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    /// Array contents, if this is an array
+    pub fn as_array_mut(&mut self) -> Option<&mut Array> {
+        match &mut self.node {
+            Node::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    /// Dictionary contents, if this is a dictionary
+    pub fn as_dict(&self) -> Option<&Dict> {
+        match &self.node {
+            Node::Dict(dict) => Some(dict),
+            _ => None,
+        }
+    }
+
+    /// Dictionary contents, if this is a dictionary
+    pub fn as_dict_mut(&mut self) -> Option<&mut Dict> {
+        match &mut self.node {
+            Node::Dict(dict) => Some(dict),
+            _ => None,
+        }
+    }
+
+    /// Text of the comment after a string
+    pub fn comment(&self) -> Option<&str> {
+        match &self.node {
+            Node::Scalar(scalar) => scalar
+                .annotation
+                .trim()
+                .strip_prefix("/*")
+                .and_then(|comment| comment.strip_suffix("*/"))
+                .map(str::trim),
+            _ => None,
+        }
+    }
+
+    /// Replace the comment after a string
+    pub fn set_comment(&mut self, comment: &str) {
+        if let Node::Scalar(scalar) = &mut self.node {
+            scalar.annotation = format!(" /* {comment} */");
+        }
+    }
+
+    /// Replace object IDs found in `remap`, keeping their comments
+    fn remap_ids(&mut self, remap: &HashMap<String, String>) {
+        self.walk_scalars_mut(&mut |value| {
+            let Node::Scalar(scalar) = &mut value.node else {
+                return;
+            };
+            if let Some(id) = remap.get(&scalar.value) {
+                scalar.raw = quote(id);
+                scalar.value = id.clone();
+            }
+        });
+    }
+
+    /// Call `f` on every string in this value, dictionary keys included
+    fn walk_scalars_mut(&mut self, f: &mut dyn FnMut(&mut Value)) {
+        if matches!(self.node, Node::Scalar(_)) {
+            f(self);
+            return;
+        }
+        match &mut self.node {
+            Node::Scalar(_) => {}
+            Node::Array(array) => {
+                for item in &mut array.items {
+                    item.value.walk_scalars_mut(f);
+                }
+            }
+            Node::Dict(dict) => {
+                for entry in &mut dict.entries {
+                    entry.key.walk_scalars_mut(f);
+                    entry.value.walk_scalars_mut(f);
+                }
+            }
+        }
+    }
+
+    /// Lay out a value added through the API the way Xcode writes it
+    ///
+    /// `indent` is the indentation of the line the value starts on. Build
+    /// files and file references go on a single line, as do their contents.
+    fn layout(&mut self, indent: &str, inline: bool) {
+        let nested = format!("{indent}\t");
+        let separator = |index: usize| if index == 0 { "" } else { " " }.to_string();
+        match &mut self.node {
+            Node::Scalar(_) => {}
+            Node::Array(array) => {
+                for (index, item) in array.items.iter_mut().enumerate() {
+                    item.value.leading = if inline {
+                        separator(index)
+                    } else {
+                        format!("\n{nested}")
+                    };
+                    item.comma = Some(String::new());
+                    item.value.layout(&nested, inline);
+                }
+                array.close = match (inline, array.items.is_empty()) {
+                    (false, _) => format!("\n{indent}"),
+                    (true, true) => String::new(),
+                    (true, false) => " ".to_string(),
+                };
+            }
+            Node::Dict(dict) => {
+                let inline = inline
+                    || matches!(
+                        dict.get_str("isa"),
+                        Some("PBXBuildFile" | "PBXFileReference")
+                    );
+                for (index, entry) in dict.entries.iter_mut().enumerate() {
+                    entry.key.leading = if inline {
+                        separator(index)
+                    } else {
+                        format!("\n{nested}")
+                    };
+                    entry.before_eq = " ".to_string();
+                    entry.value.leading = " ".to_string();
+                    entry.before_semi = String::new();
+                    entry.value.layout(&nested, inline);
+                }
+                dict.close = match (inline, dict.entries.is_empty()) {
+                    (false, _) => format!("\n{indent}"),
+                    (true, true) => String::new(),
+                    (true, false) => " ".to_string(),
+                };
+            }
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.leading)?;
+        match &self.node {
+            Node::Scalar(scalar) => write!(f, "{}{}", scalar.raw, scalar.annotation),
+            Node::Array(array) => {
+                f.write_str("(")?;
+                for item in &array.items {
+                    write!(f, "{}", item.value)?;
+                    if let Some(ref before_comma) = item.comma {
+                        write!(f, "{before_comma},")?;
+                    }
+                }
+                write!(f, "{})", array.close)
+            }
+            Node::Dict(dict) => {
+                f.write_str("{")?;
+                for entry in &dict.entries {
+                    write!(
+                        f,
+                        "{}{}={}{};",
+                        entry.key, entry.before_eq, entry.value, entry.before_semi
+                    )?;
+                }
+                write!(f, "{}}}", dict.close)
+            }
+        }
+    }
+}
+
+impl Dict {
+    /// Number of entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if there are no entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn position(&self, key: &str) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|entry| entry.key.as_str() == Some(key))
+    }
+
+    /// Check if `key` is present
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.position(key).is_some()
+    }
+
+    /// Value for `key`
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.position(key).map(|index| &self.entries[index].value)
+    }
+
+    /// Value for `key`
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        self.position(key)
+            .map(move |index| &mut self.entries[index].value)
+    }
+
+    /// String value for `key`
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(Value::as_str)
+    }
+
+    /// Entries in file order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.entries
+            .iter()
+            .map(|entry| (entry.key.as_str().unwrap_or_default(), &entry.value))
+    }
+
+    /// Keys in file order
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.iter().map(|(key, _)| key)
+    }
+
+    /// Values in file order
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut Value> {
+        self.entries.iter_mut().map(|entry| &mut entry.value)
+    }
+
+    /// Set `key`, keeping its position if it is already present
+    ///
+    /// New keys go in alphabetical order after `isa`, which is how Xcode
+    /// sorts object properties and build settings.
+    pub fn set(&mut self, key: &str, mut value: Value) {
+        if let Some(index) = self.position(key) {
+            let entry = &mut self.entries[index];
+            value.leading = std::mem::take(&mut entry.value.leading);
+            match indent_of(&entry.key.leading) {
+                Some(indent) => value.layout(indent, false),
+                None => value.layout("", true),
+            }
+            entry.value = value;
+            return;
+        }
+
+        let index = self
+            .entries
+            .iter()
+            .position(|entry| {
+                let existing = entry.key.as_str().unwrap_or_default();
+                existing != "isa" && existing > key
+            })
+            .unwrap_or(self.entries.len());
+
+        let mut leading = self.entry_leading();
+        if index == 0 {
+            if let Some(first) = self.entries.first_mut() {
+                std::mem::swap(&mut leading, &mut first.key.leading);
+            }
+        }
+        match indent_of(&leading) {
+            Some(indent) => value.layout(indent, false),
+            None => value.layout("", true),
+        }
+        let mut key = Value::string(key);
+        key.leading = leading;
+        value.leading = " ".to_string();
+        self.entries.insert(
+            index,
+            Entry {
+                key,
+                before_eq: " ".to_string(),
+                value,
+                before_semi: String::new(),
+            },
+        );
+    }
+
+    /// Remove `key`, returning its value
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        let index = self.position(key)?;
+        let removed = self.entries.remove(index);
+        if index == 0 {
+            if let Some(first) = self.entries.first_mut() {
+                first.key.leading = removed.key.leading;
+            }
+        }
+        Some(removed.value)
+    }
+
+    /// Trivia for a new entry, following the last one
+    fn entry_leading(&self) -> String {
+        match self.entries.last() {
+            Some(last) => match indent_of(&last.key.leading) {
+                Some(indent) => format!("\n{indent}"),
+                None => " ".to_string(),
+            },
+            None => match indent_of(&self.close) {
+                Some(indent) => format!("\n{indent}\t"),
+                None => " ".to_string(),
+            },
+        }
+    }
+}
+
+impl Array {
+    /// Number of items
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Check if there are no items
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Items in order
+    pub fn iter(&self) -> impl Iterator<Item = &Value> {
+        self.items.iter().map(|item| &item.value)
+    }
+
+    /// String items, skipping anything else
+    pub fn strs(&self) -> impl Iterator<Item = &str> {
+        self.iter().filter_map(Value::as_str)
+    }
+
+    /// Append an item
+    pub fn push(&mut self, value: Value) {
+        self.insert(self.items.len(), value);
+    }
+
+    /// Insert an item at `index`
+    pub fn insert(&mut self, index: usize, mut value: Value) {
+        let mut leading = match self.items.last() {
+            Some(last) => match indent_of(&last.value.leading) {
+                Some(indent) => format!("\n{indent}"),
+                None => " ".to_string(),
+            },
+            None => match indent_of(&self.close) {
+                Some(indent) => format!("\n{indent}\t"),
+                None => String::new(),
+            },
+        };
+        if index == 0 {
+            if let Some(first) = self.items.first_mut() {
+                std::mem::swap(&mut leading, &mut first.value.leading);
+            }
+        }
+        // Xcode ends every item with a comma
+        if index == self.items.len() {
+            if let Some(last) = self.items.last_mut() {
+                last.comma.get_or_insert_with(String::new);
+            }
+        }
+        match indent_of(&leading) {
+            Some(indent) => value.layout(indent, false),
+            None => value.layout("", true),
+        }
+        value.leading = leading;
+        self.items.insert(
+            index,
+            Item {
+                value,
+                comma: Some(String::new()),
+            },
+        );
+    }
+
+    /// Remove the item at `index`
+    pub fn remove(&mut self, index: usize) -> Value {
+        let removed = self.items.remove(index);
+        if index == 0 {
+            if let Some(first) = self.items.first_mut() {
+                first.value.leading = removed.value.leading.clone();
+            }
+        }
+        removed.value
+    }
+
+    /// Keep only the items for which `keep` returns true
+    pub fn retain(&mut self, mut keep: impl FnMut(&Value) -> bool) {
+        let first_leading = self.items.first().map(|item| item.value.leading.clone());
+        self.items.retain(|item| keep(&item.value));
+        if let (Some(leading), Some(first)) = (first_leading, self.items.first_mut()) {
+            first.value.leading = leading;
+        }
+    }
+}
+
+/// Indentation of the last line of some trivia, or `None` if it has no line break
+fn indent_of(trivia: &str) -> Option<&str> {
+    trivia.rfind('\n').map(|newline| &trivia[newline + 1..])
+}
+
+/// Quote a string the way Xcode does
+///
+/// Letters, digits and `_$/:.` are written bare; anything else (spaces,
+/// dashes, `<group>`, empty strings) is quoted and escaped.
+pub fn quote(value: &str) -> String {
+    let bare = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_$/:.".contains(c))
+        && !value.contains("//")
+        && !value.contains("___");
+    if bare {
+        return value.to_string();
+    }
+
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// ============================================================================
+// Merging
+// ============================================================================
+
+/// Result of [`Document::merge`]
+#[derive(Debug, Default)]
+pub struct MergeReport {
+    /// Objects taken from the other side
+    pub added: Vec<String>,
+    /// Objects the other side added that duplicate one added on this side
+    pub deduplicated: Vec<String>,
+    /// Properties both sides set differently, where this side's value was kept
+    pub conflicts: Vec<String>,
+}
+
+impl Document {
+    /// Merge another version of this project into this one
+    ///
+    /// Objects only in `other` are added and arrays (group children, build
+    /// phase files, ...) are unioned, so files added or removed on either
+    /// side all end up in the project. An object `other` added that
+    /// duplicates one added here (the same file in the same group, the same
+    /// file in the same build phase) is dropped, and references to it point
+    /// to this side's object instead.
+    pub fn merge(&mut self, other: &Document) -> MergeReport {
+        let mut report = MergeReport::default();
+        let remap = self.duplicates_in(other);
+
+        for entry in &other.objects().entries {
+            let id = entry.key.as_str().unwrap_or_default();
+            let name = entry.key.comment().unwrap_or(id).to_string();
+            if remap.contains_key(id) {
+                report.deduplicated.push(name);
+                continue;
+            }
+
+            let mut value = entry.value.clone();
+            value.remap_ids(&remap);
+            match self.objects_mut().get_mut(id) {
+                Some(existing) => merge_value(existing, &value, &name, &mut report.conflicts),
+                None => {
+                    self.insert_object(id, entry.key.comment().unwrap_or_default(), value);
+                    report.added.push(name);
+                }
+            }
+        }
+        report
+    }
+
+    /// Map IDs of objects `other` added to equivalent objects added here
+    fn duplicates_in(&self, other: &Document) -> HashMap<String, String> {
+        let (parents, other_parents) = (self.parents(), other.parents());
+        let no_remap = HashMap::new();
+        let mut remap = HashMap::new();
+
+        // Groups first, so files in a duplicated group match, then files,
+        // then the build files pointing at them
+        for isa in ["PBXGroup", "PBXVariantGroup", "PBXFileReference", "PBXBuildFile"] {
+            let added_here: HashMap<String, &str> = self
+                .objects_of(isa)
+                .filter(|(id, _)| !other.objects().contains_key(id))
+                .filter_map(|(id, object)| Some((identity(object, parents.get(id).copied(), &no_remap)?, id)))
+                .collect();
+
+            // Nested groups match once their parent has
+            loop {
+                let mut matched = false;
+                for (id, object) in other.objects_of(isa) {
+                    if remap.contains_key(id) || self.objects().contains_key(id) {
+                        continue;
+                    }
+                    let existing = identity(object, other_parents.get(id).copied(), &remap)
+                        .and_then(|key| added_here.get(&key));
+                    if let Some(existing) = existing {
+                        remap.insert(id.to_string(), existing.to_string());
+                        matched = true;
+                    }
+                }
+                if !matched || !isa.ends_with("Group") {
+                    break;
+                }
+            }
+        }
+        remap
+    }
+
+    /// Group or build phase listing each object
+    fn parents(&self) -> HashMap<&str, &str> {
+        let mut parents = HashMap::new();
+        for (id, object) in self.objects().iter() {
+            let Some(object) = object.as_dict() else {
+                continue;
+            };
+            for key in ["children", "files"] {
+                for child in object.get(key).and_then(Value::as_array).into_iter().flat_map(Array::strs) {
+                    parents.insert(child, id);
+                }
+            }
+        }
+        parents
+    }
+}
+
+/// What makes two groups, files or build files the same, regardless of ID
+fn identity(object: &Dict, parent: Option<&str>, remap: &HashMap<String, String>) -> Option<String> {
+    let isa = object.get_str("isa")?;
+    let keys: &[&str] = match isa {
+        "PBXGroup" | "PBXVariantGroup" | "PBXFileReference" => &["name", "path", "sourceTree"],
+        "PBXBuildFile" => &["fileRef", "productRef"],
+        _ => return None,
+    };
+    let mapped = |id: &str| remap.get(id).cloned().unwrap_or_else(|| id.to_string());
+    let mut identity = format!("{isa}\0{}", mapped(parent?));
+    for key in keys {
+        identity.push('\0');
+        identity.push_str(&object.get_str(key).map(mapped).unwrap_or_default());
+    }
+    Some(identity)
+}
+
+/// Merge `theirs` into `ours`: union arrays, recurse into dictionaries and
+/// keep `ours` for differing strings
+fn merge_value(ours: &mut Value, theirs: &Value, path: &str, conflicts: &mut Vec<String>) {
+    if let (Some(ours), Some(theirs)) = (ours.as_str(), theirs.as_str()) {
+        if ours != theirs {
+            conflicts.push(format!("{path}: kept {} over {}", quote(ours), quote(theirs)));
+        }
+        return;
+    }
+
+    match (&mut ours.node, &theirs.node) {
+        (Node::Array(ours), Node::Array(theirs)) => {
+            for value in theirs.iter() {
+                if !ours.iter().any(|existing| same_value(existing, value)) {
+                    ours.push(value.clone());
+                }
+            }
+        }
+        (Node::Dict(ours), Node::Dict(theirs)) => {
+            for entry in &theirs.entries {
+                let key = entry.key.as_str().unwrap_or_default();
+                match ours.get_mut(key) {
+                    Some(existing) => merge_value(existing, &entry.value, &format!("{path} {key}"), conflicts),
+                    None => ours.set(key, entry.value.clone()),
+                }
+            }
+        }
+        _ => conflicts.push(format!("{path}: kept this side's value, the other side changed its type")),
+    }
+}
+
+fn same_value(a: &Value, b: &Value) -> bool {
+    match (a.as_str(), b.as_str()) {
+        (Some(a), Some(b)) => a == b,
+        _ => a.to_string().trim() == b.to_string().trim(),
+    }
+}
+
+/// Split a file with git conflict markers into the two sides
+///
+/// Returns `(ours, theirs)`, or `None` if there are no conflicts. Base
+/// sections from `merge.conflictStyle = diff3` are dropped.
+pub fn split_conflicts(text: &str) -> Result<Option<(String, String)>> {
+    #[derive(PartialEq)]
+    enum Side {
+        Both,
+        Ours,
+        Base,
+        Theirs,
+    }
+
+    let (mut ours, mut theirs) = (String::new(), String::new());
+    let mut side = Side::Both;
+    let mut conflicted = false;
+    for line in text.split_inclusive('\n') {
+        side = match side {
+            Side::Both if line.starts_with("<<<<<<<") => {
+                conflicted = true;
+                Side::Ours
+            }
+            Side::Ours if line.starts_with("|||||||") => Side::Base,
+            Side::Ours | Side::Base if line.starts_with("=======") => Side::Theirs,
+            Side::Theirs if line.starts_with(">>>>>>>") => Side::Both,
+            side => {
+                match side {
+                    Side::Both => {
+                        ours.push_str(line);
+                        theirs.push_str(line);
+                    }
+                    Side::Ours => ours.push_str(line),
+                    Side::Theirs => theirs.push_str(line),
+                    Side::Base => {}
+                }
+                side
+            }
+        };
+    }
+
+    if side != Side::Both {
+        return Err(Error::validation("Unterminated conflict in project.pbxproj"));
+    }
+    Ok(conflicted.then_some((ours, theirs)))
+}
+
+// ============================================================================
+// Parser
+// ============================================================================
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> Error {
+        let line = self.text[..self.pos].matches('\n').count() + 1;
+        Error::validation(format!("Invalid project.pbxproj at line {line}: {message}"))
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<()> {
+        if self.peek() != Some(byte) {
+            return Err(self.error(&format!("expected '{}'", byte as char)));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    /// Whitespace and comments
+    fn trivia(&mut self) -> Result<String> {
+        let text = self.text;
+        let start = self.pos;
+        loop {
+            let rest = &text[self.pos..];
+            if let Some(comment) = rest.strip_prefix("/*") {
+                let end = comment
+                    .find("*/")
+                    .ok_or_else(|| self.error("unterminated comment"))?;
+                self.pos += end + 4;
+            } else if rest.starts_with("//") {
+                self.pos += rest.find('\n').map_or(rest.len(), |end| end + 1);
+            } else if let Some(c) = rest.chars().next().filter(|c| c.is_whitespace()) {
+                self.pos += c.len_utf8();
+            } else {
+                return Ok(text[start..self.pos].to_string());
+            }
+        }
+    }
+
+    /// Comment on the same line right after a token
+    fn annotation(&mut self) -> Result<String> {
+        let text = self.text;
+        let rest = &text[self.pos..];
+        let start = rest.len() - rest.trim_start_matches([' ', '\t']).len();
+        if !rest[start..].starts_with("/*") {
+            return Ok(String::new());
+        }
+        let end = rest[start + 2..]
+            .find("*/")
+            .ok_or_else(|| self.error("unterminated comment"))?
+            + start
+            + 4;
+        self.pos += end;
+        Ok(rest[..end].to_string())
+    }
+
+    fn value(&mut self) -> Result<Value> {
+        let leading = self.trivia()?;
+        self.value_after(leading)
+    }
+
+    fn value_after(&mut self, leading: String) -> Result<Value> {
+        let node = match self.peek() {
+            Some(b'{') => {
+                self.pos += 1;
+                Node::Dict(self.dict()?)
+            }
+            Some(b'(') => {
+                self.pos += 1;
+                Node::Array(self.array()?)
+            }
+            Some(_) => Node::Scalar(self.scalar()?),
+            None => return Err(self.error("unexpected end of file")),
+        };
+        Ok(Value { leading, node })
+    }
+
+    fn dict(&mut self) -> Result<Dict> {
+        let mut entries = Vec::new();
+        loop {
+            let leading = self.trivia()?;
+            if self.peek() == Some(b'}') {
+                self.pos += 1;
+                return Ok(Dict {
+                    entries,
+                    close: leading,
+                });
+            }
+
+            let key = self.value_after(leading)?;
+            if key.as_str().is_none() {
+                return Err(self.error("expected a key"));
+            }
+            let before_eq = self.trivia()?;
+            self.expect(b'=')?;
+            let value = self.value()?;
+            let before_semi = self.trivia()?;
+            self.expect(b';')?;
+            entries.push(Entry {
+                key,
+                before_eq,
+                value,
+                before_semi,
+            });
+        }
+    }
+
+    fn array(&mut self) -> Result<Array> {
+        let mut items: Vec<Item> = Vec::new();
+        loop {
+            let leading = self.trivia()?;
+            if self.peek() == Some(b')') {
+                self.pos += 1;
+                return Ok(Array {
+                    items,
+                    close: leading,
+                });
+            }
+            if items.last().is_some_and(|item| item.comma.is_none()) {
+                return Err(self.error("expected ',' or ')'"));
+            }
+
+            let value = self.value_after(leading)?;
+            let checkpoint = self.pos;
+            let before_comma = self.trivia()?;
+            let comma = if self.peek() == Some(b',') {
+                self.pos += 1;
+                Some(before_comma)
+            } else {
+                self.pos = checkpoint;
+                None
+            };
+            items.push(Item { value, comma });
+        }
+    }
+
+    fn scalar(&mut self) -> Result<Scalar> {
+        let text = self.text;
+        let start = self.pos;
+        let value = match self.peek() {
+            Some(b'"' | b'\'') => self.quoted()?,
+            Some(b'<') => {
+                let end = text[start..]
+                    .find('>')
+                    .ok_or_else(|| self.error("unterminated data"))?;
+                self.pos += end + 1;
+                text[start + 1..start + end]
+                    .split_whitespace()
+                    .collect()
+            }
+            _ => {
+                let rest = &text[start..];
+                let len = rest
+                    .char_indices()
+                    .find(|&(offset, c)| {
+                        c.is_whitespace()
+                            || "{}()=;,\"'<>".contains(c)
+                            || rest[offset..].starts_with("/*")
+                            || rest[offset..].starts_with("//")
+                    })
+                    .map_or(rest.len(), |(offset, _)| offset);
+                if len == 0 {
+                    return Err(self.error("unexpected character"));
+                }
+                self.pos += len;
+                rest[..len].to_string()
+            }
+        };
+
+        let raw = text[start..self.pos].to_string();
+        let annotation = self.annotation()?;
+        Ok(Scalar {
+            value,
+            raw,
+            annotation,
+        })
+    }
+
+    fn quoted(&mut self) -> Result<String> {
+        let text = self.text;
+        let quote = text.as_bytes()[self.pos] as char;
+        let mut value = String::new();
+        let mut chars = text[self.pos + 1..].char_indices();
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                _ if c == quote => {
+                    self.pos += offset + 2;
+                    return Ok(value);
+                }
+                '\\' => match chars.next() {
+                    Some((_, 'n')) => value.push('\n'),
+                    Some((_, 't')) => value.push('\t'),
+                    Some((_, 'r')) => value.push('\r'),
+                    Some((_, 'U')) => {
+                        let hex: String = chars.by_ref().take(4).map(|(_, c)| c).collect();
+                        let c = u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| self.error("invalid \\U escape"))?;
+                        value.push(c);
+                    }
+                    Some((_, other)) => value.push(other),
+                    None => break,
+                },
+                _ => value.push(c),
+            }
+        }
+        Err(self.error("unterminated string"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROJECT: &str = r#"// !$*UTF8*$!
+{
+	archiveVersion = 1;
+	classes = {
+	};
+	objectVersion = 56;
+	objects = {
+
+/* Begin PBXBuildFile section */
+		A10000000000000000000001 /* App.swift in Sources */ = {isa = PBXBuildFile; fileRef = B10000000000000000000001 /* App.swift */; };
+/* End PBXBuildFile section */
+
+/* Begin PBXFileReference section */
+		B10000000000000000000001 /* App.swift */ = {isa = PBXFileReference; lastKnownFileType = sourcecode.swift; path = App.swift; sourceTree = "<group>"; };
+/* End PBXFileReference section */
+
+/* Begin PBXGroup section */
+		C10000000000000000000001 = {
+			isa = PBXGroup;
+			children = (
+				B10000000000000000000001 /* App.swift */,
+			);
+			name = "Food \"Share\"";
+			sourceTree = "<group>";
+		};
+/* End PBXGroup section */
+	};
+	rootObject = C10000000000000000000001;
+}
+"#;
+
+    #[test]
+    fn test_round_trip() {
+        let document = Document::parse(PROJECT).unwrap();
+        assert_eq!(document.to_string(), PROJECT);
+
+        assert_eq!(document.root_object_id(), Some("C10000000000000000000001"));
+        let group = document.object("C10000000000000000000001").unwrap();
+        assert_eq!(group.get_str("name"), Some("Food \"Share\""));
+        assert_eq!(group.get_str("sourceTree"), Some("<group>"));
+        let children = group.get("children").and_then(Value::as_array).unwrap();
+        assert_eq!(children.strs().collect::<Vec<_>>(), ["B10000000000000000000001"]);
+        assert_eq!(children.iter().next().unwrap().comment(), Some("App.swift"));
+        assert_eq!(document.objects_of("PBXFileReference").count(), 1);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Document::parse("{ objects = { }; ").is_err());
+        assert!(Document::parse("{ objects = (a b); }").is_err());
+        assert!(Document::parse("{ archiveVersion = 1; }").is_err());
+        assert!(Document::parse("{ objects = {}; } extra").is_err());
+        assert!(Document::parse("{ objects = {}; /* open").is_err());
+    }
+
+    #[test]
+    fn test_edits_use_xcode_layout() {
+        let mut document = Document::parse(PROJECT).unwrap();
+        let group = document.object_mut("C10000000000000000000001").unwrap();
+        group
+            .get_mut("children")
+            .and_then(Value::as_array_mut)
+            .unwrap()
+            .push(Value::string("B10000000000000000000002").with_comment("Feed.swift"));
+        group.set("path", Value::string("Food Share"));
+        group.remove("name");
+
+        let text = document.to_string();
+        assert!(text.contains(
+            "\t\t\tchildren = (\n\t\t\t\tB10000000000000000000001 /* App.swift */,\n\t\t\t\tB10000000000000000000002 /* Feed.swift */,\n\t\t\t);\n\t\t\tpath = \"Food Share\";\n\t\t\tsourceTree"
+        ));
+        assert!(!text.contains("name ="));
+        Document::parse(&text).unwrap();
+    }
+
+    #[test]
+    fn test_insert_and_remove_objects() {
+        let mut document = Document::parse(PROJECT).unwrap();
+        document.insert_object(
+            "A10000000000000000000002",
+            "Feed.swift in Sources",
+            Value::dict([
+                ("isa", Value::string("PBXBuildFile")),
+                (
+                    "fileRef",
+                    Value::string("B10000000000000000000002").with_comment("Feed.swift"),
+                ),
+            ]),
+        );
+        document.insert_object(
+            "E10000000000000000000001",
+            "Sources",
+            Value::dict([
+                ("isa", Value::string("PBXSourcesBuildPhase")),
+                ("files", Value::array([Value::string("A10000000000000000000001")])),
+            ]),
+        );
+
+        let text = document.to_string();
+        assert!(text.contains(
+            "fileRef = B10000000000000000000001 /* App.swift */; };\n\t\tA10000000000000000000002 /* Feed.swift in Sources */ = {isa = PBXBuildFile; fileRef = B10000000000000000000002 /* Feed.swift */; };\n/* End PBXBuildFile section */"
+        ));
+        assert!(text.contains(
+            "/* End PBXGroup section */\n\n/* Begin PBXSourcesBuildPhase section */\n\t\tE10000000000000000000001 /* Sources */ = {\n\t\t\tisa = PBXSourcesBuildPhase;\n\t\t\tfiles = (\n\t\t\t\tA10000000000000000000001,\n\t\t\t);\n\t\t};\n/* End PBXSourcesBuildPhase section */\n\t};"
+        ));
+
+        // Removing the only object of a type drops its section
+        document.remove_object("E10000000000000000000001").unwrap();
+        document.remove_object("A10000000000000000000002").unwrap();
+        assert_eq!(document.to_string(), PROJECT);
+
+        document.remove_object("A10000000000000000000001").unwrap();
+        let text = document.to_string();
+        assert!(text.contains("\tobjects = {\n\n/* Begin PBXFileReference section */\n"));
+        assert!(!text.contains("PBXBuildFile section"));
+    }
+
+    #[test]
+    fn test_references_and_comments() {
+        let mut document = Document::parse(PROJECT).unwrap();
+        document.rename_comments("B10000000000000000000001", "App.swift", "Main.swift");
+        document.rename_comments("A10000000000000000000001", "App.swift", "Main.swift");
+        let text = document.to_string();
+        assert!(text.contains("A10000000000000000000001 /* Main.swift in Sources */"));
+        assert!(text.contains("fileRef = B10000000000000000000001 /* Main.swift */"));
+        assert!(!text.contains("App.swift */"));
+
+        document.remove_references("B10000000000000000000001");
+        assert!(document.to_string().contains("\t\t\tchildren = (\n\t\t\t);"));
+    }
+
+    #[test]
+    fn test_merge_conflicted_sides() {
+        let file = |id: &str, name: &str| {
+            format!("\t\t{id} /* {name} */ = {{isa = PBXFileReference; path = {name}; sourceTree = \"<group>\"; }};\n")
+        };
+        let child = |id: &str, name: &str| format!("\t\t\t\t{id} /* {name} */,\n");
+        let conflicted = PROJECT
+            .replace(
+                "/* End PBXFileReference section */",
+                &format!(
+                    "<<<<<<< HEAD\n{}{}=======\n{}{}>>>>>>> feature\n/* End PBXFileReference section */",
+                    file("B10000000000000000000002", "Feed.swift"),
+                    file("B10000000000000000000003", "Shared.swift"),
+                    file("B10000000000000000000004", "Map.swift"),
+                    file("B10000000000000000000005", "Shared.swift"),
+                ),
+            )
+            .replace(
+                "\t\t\t\tB10000000000000000000001 /* App.swift */,\n",
+                &format!(
+                    "{}<<<<<<< HEAD\n{}{}||||||| base\n=======\n{}{}>>>>>>> feature\n",
+                    child("B10000000000000000000001", "App.swift"),
+                    child("B10000000000000000000002", "Feed.swift"),
+                    child("B10000000000000000000003", "Shared.swift"),
+                    child("B10000000000000000000004", "Map.swift"),
+                    child("B10000000000000000000005", "Shared.swift"),
+                ),
+            )
+            .replace(
+                "\t\t\tname = \"Food \\\"Share\\\"\";\n",
+                "<<<<<<< HEAD\n\t\t\tname = FoodShare;\n=======\n\t\t\tname = Food;\n>>>>>>> feature\n",
+            );
+
+        let (ours, theirs) = split_conflicts(&conflicted).unwrap().unwrap();
+        let mut merged = Document::parse(&ours).unwrap();
+        let report = merged.merge(&Document::parse(&theirs).unwrap());
+        assert_eq!(report.added, ["Map.swift"]);
+        assert_eq!(report.deduplicated, ["Shared.swift"]);
+        assert_eq!(report.conflicts, ["C10000000000000000000001 name: kept FoodShare over Food"]);
+
+        let text = merged.to_string();
+        assert!(text.contains(&format!(
+            "\t\t\tchildren = (\n{}{}{}{}\t\t\t);",
+            child("B10000000000000000000001", "App.swift"),
+            child("B10000000000000000000002", "Feed.swift"),
+            child("B10000000000000000000003", "Shared.swift"),
+            child("B10000000000000000000004", "Map.swift"),
+        )));
+        assert!(!text.contains("B10000000000000000000005"));
+        Document::parse(&text).unwrap();
+
+        assert!(split_conflicts(PROJECT).unwrap().is_none());
+        assert!(split_conflicts("<<<<<<< HEAD\na\n=======\nb\n").is_err());
+    }
+
+    #[test]
+    fn test_quote_simple() {
+        assert_eq!(quote("simple"), "simple");
+    }
+
+    #[test]
+    fn test_quote_with_spaces() {
+        assert_eq!(quote("path with spaces"), "\"path with spaces\"");
+    }
+
+    #[test]
+    fn test_quote_with_slashes_and_dots() {
+        assert_eq!(quote("FoodShare/Core/File.swift"), "FoodShare/Core/File.swift");
+        assert_eq!(quote("file.swift"), "file.swift");
+    }
+
+    #[test]
+    fn test_quote_with_dashes() {
+        assert_eq!(quote("my-file"), "\"my-file\"");
+    }
+
+    #[test]
+    fn test_quote_special_values() {
+        assert_eq!(quote(""), "\"\"");
+        assert_eq!(quote("<group>"), "\"<group>\"");
+        assert_eq!(quote("$(inherited)"), "\"$(inherited)\"");
+        assert_eq!(quote("say \"hi\""), r#""say \"hi\"""#);
+    }
+}
@@ -0,0 +1,228 @@
+//! XML property list parsing
+//!
+//! Entitlements files and decoded provisioning profiles are XML plists.
+//! [`parse`] turns one into a [`serde_json::Value`]: dictionaries become
+//! objects, `<date>` becomes its ISO 8601 string and `<data>` its base64
+//! text with whitespace removed.
+
+use foodshare_core::error::{Error, Result};
+use serde_json::{Map, Value};
+
+/// Parse an XML plist
+pub fn parse(xml: &str) -> Result<Value> {
+    let mut parser = Parser { xml, pos: 0 };
+    let root = parser
+        .find_open("plist")
+        .ok_or_else(|| Error::validation("Not an XML property list"))?;
+    if root.self_closing {
+        return Ok(Value::Null);
+    }
+    parser.value()
+}
+
+/// Read an XML plist file
+pub fn load(path: &std::path::Path) -> Result<Value> {
+    if !path.exists() {
+        return Err(Error::file_not_found(path));
+    }
+    parse(&std::fs::read_to_string(path)?)
+}
+
+struct Tag<'a> {
+    name: &'a str,
+    closing: bool,
+    self_closing: bool,
+}
+
+struct Parser<'a> {
+    xml: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// Next element tag, skipping the prolog, doctype and comments
+    fn next_tag(&mut self) -> Option<Tag<'a>> {
+        loop {
+            let start = self.pos + self.xml[self.pos..].find('<')?;
+            let rest = &self.xml[start..];
+            if rest.starts_with("<!--") {
+                self.pos = start + rest.find("-->")? + 3;
+                continue;
+            }
+            let end = start + rest.find('>')?;
+            self.pos = end + 1;
+            let inner = &self.xml[start + 1..end];
+            if inner.starts_with('?') || inner.starts_with('!') {
+                continue;
+            }
+
+            let closing = inner.starts_with('/');
+            let self_closing = inner.ends_with('/');
+            let name = inner
+                .trim_start_matches('/')
+                .trim_end_matches('/')
+                .split_whitespace()
+                .next()
+                .unwrap_or("");
+            return Some(Tag {
+                name,
+                closing,
+                self_closing,
+            });
+        }
+    }
+
+    fn find_open(&mut self, name: &str) -> Option<Tag<'a>> {
+        loop {
+            let tag = self.next_tag()?;
+            if tag.name == name && !tag.closing {
+                return Some(tag);
+            }
+        }
+    }
+
+    /// Text up to the closing tag of `name`
+    fn text(&mut self, name: &str) -> Result<String> {
+        let close = format!("</{}>", name);
+        let end = self.xml[self.pos..]
+            .find(&close)
+            .ok_or_else(|| Error::validation(format!("Unclosed <{}>", name)))?;
+        let text = unescape(&self.xml[self.pos..self.pos + end]);
+        self.pos += end + close.len();
+        Ok(text)
+    }
+
+    fn value(&mut self) -> Result<Value> {
+        let tag = self
+            .next_tag()
+            .ok_or_else(|| Error::validation("Unexpected end of property list"))?;
+        self.value_for(tag)
+    }
+
+    fn value_for(&mut self, tag: Tag<'a>) -> Result<Value> {
+        if tag.closing {
+            return Err(Error::validation(format!("Unexpected </{}>", tag.name)));
+        }
+        if tag.self_closing {
+            return Ok(match tag.name {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                "dict" => Value::Object(Map::new()),
+                "array" => Value::Array(Vec::new()),
+                _ => Value::String(String::new()),
+            });
+        }
+
+        match tag.name {
+            "dict" => {
+                let mut map = Map::new();
+                loop {
+                    let tag = self
+                        .next_tag()
+                        .ok_or_else(|| Error::validation("Unclosed <dict>"))?;
+                    match (tag.name, tag.closing) {
+                        ("dict", true) => return Ok(Value::Object(map)),
+                        ("key", false) => {
+                            let key = self.text("key")?;
+                            let value = self.value()?;
+                            map.insert(key, value);
+                        }
+                        (name, _) => {
+                            return Err(Error::validation(format!("Expected <key> in <dict>, found <{}>", name)));
+                        }
+                    }
+                }
+            }
+            "array" => {
+                let mut items = Vec::new();
+                loop {
+                    let tag = self
+                        .next_tag()
+                        .ok_or_else(|| Error::validation("Unclosed <array>"))?;
+                    if tag.name == "array" && tag.closing {
+                        return Ok(Value::Array(items));
+                    }
+                    items.push(self.value_for(tag)?);
+                }
+            }
+            "integer" => {
+                let text = self.text("integer")?;
+                text.trim()
+                    .parse::<i64>()
+                    .map(Value::from)
+                    .map_err(|_| Error::validation(format!("Invalid <integer>: {}", text)))
+            }
+            "real" => {
+                let text = self.text("real")?;
+                text.trim()
+                    .parse::<f64>()
+                    .map(Value::from)
+                    .map_err(|_| Error::validation(format!("Invalid <real>: {}", text)))
+            }
+            "data" => Ok(Value::String(
+                self.text("data")?.split_whitespace().collect(),
+            )),
+            "string" | "date" => Ok(Value::String(self.text(tag.name)?)),
+            name => Err(Error::validation(format!("Unsupported plist element <{}>", name))),
+        }
+    }
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<!-- comment -->
+	<key>AppIDName</key>
+	<string>FoodShare &amp; Friends</string>
+	<key>ExpirationDate</key>
+	<date>2025-05-02T10:15:00Z</date>
+	<key>DeveloperCertificates</key>
+	<array>
+		<data>
+		MIIFxTCC
+		BK2gAwIB
+		</data>
+	</array>
+	<key>Entitlements</key>
+	<dict>
+		<key>get-task-allow</key>
+		<true/>
+		<key>keychain-access-groups</key>
+		<array>
+			<string>ABCDE12345.*</string>
+		</array>
+	</dict>
+	<key>TimeToLive</key>
+	<integer>365</integer>
+	<key>Empty</key>
+	<dict/>
+</dict>
+</plist>"#;
+        let plist = parse(xml).unwrap();
+        assert_eq!(plist["AppIDName"], "FoodShare & Friends");
+        assert_eq!(plist["ExpirationDate"], "2025-05-02T10:15:00Z");
+        assert_eq!(plist["DeveloperCertificates"][0], "MIIFxTCCBK2gAwIB");
+        assert_eq!(plist["Entitlements"]["get-task-allow"], true);
+        assert_eq!(plist["Entitlements"]["keychain-access-groups"][0], "ABCDE12345.*");
+        assert_eq!(plist["TimeToLive"], 365);
+        assert!(plist["Empty"].as_object().unwrap().is_empty());
+
+        assert!(parse("<dict></dict>").is_err());
+        assert!(parse("<plist><dict><string>x</string></dict></plist>").is_err());
+    }
+}
@@ -0,0 +1,870 @@
+//! Xcode schemes and test plans
+//!
+//! Shared schemes live in `<project>.xcodeproj/xcshareddata/xcschemes` and
+//! per-user ones in `xcuserdata/<user>.xcuserdatad/xcschemes`, as XML.
+//! Test plans (`.xctestplan`) are JSON files a scheme's `TestAction` refers
+//! to; when a scheme has any, parallel testing is set in the plans instead
+//! of the scheme.
+//!
+//! Edits only touch the attributes they change, so the rest of each file
+//! stays as Xcode wrote it.
+
+use crate::xcodeproj::{Target, XcodeProject};
+use foodshare_core::error::{Error, Result};
+use std::path::{Path, PathBuf};
+
+/// Directory of shared schemes inside an `.xcodeproj`
+pub const SHARED_SCHEMES_DIR: &str = "xcshareddata/xcschemes";
+
+/// A target referenced from a scheme
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildableReference {
+    /// ID of the target in `project.pbxproj`
+    pub blueprint_id: String,
+    /// Target name
+    pub name: String,
+    /// Product file name (`FoodShare.app`)
+    pub buildable_name: String,
+    /// Project containing the target (`container:FoodShare.xcodeproj`)
+    pub container: String,
+}
+
+/// A test target in a scheme's `TestAction`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Testable {
+    /// Test target
+    pub target: BuildableReference,
+    /// Whether its tests run in parallel
+    pub parallelizable: bool,
+    /// Whether it is disabled in the scheme
+    pub skipped: bool,
+}
+
+/// An `.xcscheme` file
+#[derive(Debug, Clone)]
+pub struct Scheme {
+    /// Scheme name (file name without `.xcscheme`)
+    pub name: String,
+    /// Path of the scheme file
+    pub path: PathBuf,
+    /// Whether the scheme is shared (committed) rather than per-user
+    pub shared: bool,
+    /// Targets built by the scheme
+    pub build_targets: Vec<BuildableReference>,
+    /// Test targets
+    pub testables: Vec<Testable>,
+    /// Test plan references (`container:FoodShare.xctestplan`)
+    pub test_plans: Vec<String>,
+    /// Whether the `TestAction` gathers code coverage
+    pub code_coverage: bool,
+    content: String,
+}
+
+impl Scheme {
+    /// Load a scheme file
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Err(Error::file_not_found(path));
+        }
+        let content = std::fs::read_to_string(path)?;
+        let shared = path
+            .parent()
+            .is_some_and(|dir| dir.ends_with(SHARED_SCHEMES_DIR));
+        Self::parse(path, shared, content)
+    }
+
+    /// List the shared and per-user schemes of a project, shared first
+    pub fn list(project_path: &Path) -> Result<Vec<Self>> {
+        if !project_path.exists() {
+            return Err(Error::file_not_found(project_path));
+        }
+
+        let mut dirs = vec![project_path.join(SHARED_SCHEMES_DIR)];
+        if let Ok(entries) = std::fs::read_dir(project_path.join("xcuserdata")) {
+            let mut user_dirs: Vec<PathBuf> = entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path().join("xcschemes"))
+                .collect();
+            user_dirs.sort();
+            dirs.extend(user_dirs);
+        }
+
+        let mut schemes = Vec::new();
+        for dir in dirs {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            let mut paths: Vec<PathBuf> = entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "xcscheme"))
+                .collect();
+            paths.sort();
+            for path in paths {
+                schemes.push(Self::load(&path)?);
+            }
+        }
+        Ok(schemes)
+    }
+
+    /// Find a scheme by name, preferring the shared one
+    pub fn find(project_path: &Path, name: &str) -> Result<Self> {
+        Self::list(project_path)?
+            .into_iter()
+            .find(|scheme| scheme.name == name)
+            .ok_or_else(|| Error::validation(format!("Scheme not found: {}", name)))
+    }
+
+    /// Generate a shared scheme that builds and launches `app_target` and runs
+    /// every test bundle in the project
+    ///
+    /// Tests run in parallel; `code_coverage` enables coverage for them.
+    pub fn generate(
+        project: &XcodeProject,
+        name: &str,
+        app_target: &str,
+        code_coverage: bool,
+    ) -> Result<Self> {
+        let container = format!(
+            "container:{}",
+            project
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy())
+                .unwrap_or_default()
+        );
+        let app = project
+            .find_target(app_target)
+            .ok_or_else(|| Error::validation(format!("Target not found: {}", app_target)))?;
+        let app = buildable_reference(&app, &container);
+        let tests: Vec<BuildableReference> = project
+            .targets()
+            .iter()
+            .filter(|target| target.is_test_bundle())
+            .map(|target| buildable_reference(target, &container))
+            .collect();
+
+        let content = scheme_xml(&app, &tests, code_coverage);
+        let path = project
+            .path
+            .join(SHARED_SCHEMES_DIR)
+            .join(format!("{}.xcscheme", name));
+        Self::parse(&path, true, content)
+    }
+
+    fn parse(path: &Path, shared: bool, content: String) -> Result<Self> {
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let tags = tags(&content).map_err(|e| {
+            Error::validation(format!("Invalid scheme {}: {}", path.display(), e))
+        })?;
+
+        let mut scheme = Self {
+            name,
+            path: path.to_path_buf(),
+            shared,
+            build_targets: Vec::new(),
+            testables: Vec::new(),
+            test_plans: Vec::new(),
+            code_coverage: false,
+            content: String::new(),
+        };
+
+        // Element names of the open tags enclosing the current one, with the
+        // attributes of the enclosing TestableReference
+        let mut stack: Vec<&str> = Vec::new();
+        let mut testable: Option<Vec<(String, String)>> = None;
+        for tag in &tags {
+            match tag.kind {
+                TagKind::Close => {
+                    if stack.pop() == Some("TestableReference") {
+                        testable = None;
+                    }
+                    continue;
+                }
+                TagKind::Open | TagKind::Empty => {}
+            }
+
+            let attrs = tag.attributes(&content);
+            match tag.name {
+                "TestAction" => scheme.code_coverage = attr(&attrs, "codeCoverageEnabled") == Some("YES"),
+                "TestPlanReference" => {
+                    if let Some(reference) = attr(&attrs, "reference") {
+                        scheme.test_plans.push(reference.to_string());
+                    }
+                }
+                "TestableReference" => testable = Some(attrs.clone()),
+                "BuildableReference" => {
+                    let reference = BuildableReference {
+                        blueprint_id: attr(&attrs, "BlueprintIdentifier").unwrap_or_default().to_string(),
+                        name: attr(&attrs, "BlueprintName").unwrap_or_default().to_string(),
+                        buildable_name: attr(&attrs, "BuildableName").unwrap_or_default().to_string(),
+                        container: attr(&attrs, "ReferencedContainer").unwrap_or_default().to_string(),
+                    };
+                    match stack.last() {
+                        Some(&"BuildActionEntry") => scheme.build_targets.push(reference),
+                        Some(&"TestableReference") => {
+                            let parent = testable.as_deref().unwrap_or_default();
+                            scheme.testables.push(Testable {
+                                target: reference,
+                                parallelizable: attr(parent, "parallelizable") == Some("YES"),
+                                skipped: attr(parent, "skipped") == Some("YES"),
+                            });
+                        }
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+            if tag.kind == TagKind::Open {
+                stack.push(tag.name);
+            }
+        }
+
+        scheme.content = content;
+        Ok(scheme)
+    }
+
+    /// Scheme file contents
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// Paths of the test plans the scheme uses
+    ///
+    /// `container:` references are relative to the directory holding the
+    /// `.xcodeproj`.
+    pub fn test_plan_paths(&self, project_path: &Path) -> Vec<PathBuf> {
+        let root = project_path.parent().unwrap_or(Path::new(""));
+        self.test_plans
+            .iter()
+            .map(|reference| root.join(reference.strip_prefix("container:").unwrap_or(reference)))
+            .collect()
+    }
+
+    /// Enable or disable code coverage in the `TestAction`
+    pub fn set_code_coverage(&mut self, enabled: bool) -> Result<()> {
+        self.set_attribute("TestAction", "codeCoverageEnabled", yes_no(enabled))?;
+        Ok(())
+    }
+
+    /// Enable or disable parallel testing for every testable, returning how
+    /// many changed
+    pub fn set_parallelizable(&mut self, enabled: bool) -> Result<usize> {
+        let changed = self
+            .testables
+            .iter()
+            .filter(|testable| testable.parallelizable != enabled)
+            .count();
+        self.set_attribute("TestableReference", "parallelizable", yes_no(enabled))?;
+        Ok(changed)
+    }
+
+    /// Set `name` on every `element` tag, then re-read the scheme
+    fn set_attribute(&mut self, element: &str, name: &str, value: &str) -> Result<()> {
+        let mut content = self.content.clone();
+        let ranges: Vec<(usize, usize)> = tags(&content)
+            .map_err(Error::validation)?
+            .iter()
+            .filter(|tag| tag.name == element && tag.kind != TagKind::Close)
+            .map(|tag| (tag.start, tag.end))
+            .collect();
+        for &(start, end) in ranges.iter().rev() {
+            let text = set_tag_attribute(&content[start..end], name, value);
+            content.replace_range(start..end, &text);
+        }
+        *self = Self::parse(&self.path, self.shared, content)?;
+        Ok(())
+    }
+
+    /// Write the scheme, creating the schemes directory if needed
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, &self.content)?;
+        Ok(())
+    }
+}
+
+/// An `.xctestplan` file
+#[derive(Debug, Clone)]
+pub struct TestPlan {
+    /// Path of the test plan
+    pub path: PathBuf,
+    json: serde_json::Value,
+}
+
+impl TestPlan {
+    /// Load a test plan
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Err(Error::file_not_found(path));
+        }
+        let json = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            json,
+        })
+    }
+
+    /// Names of the test targets and whether each runs in parallel
+    pub fn test_targets(&self) -> Vec<(String, bool)> {
+        self.json["testTargets"]
+            .as_array()
+            .map(|targets| {
+                targets
+                    .iter()
+                    .map(|target| {
+                        (
+                            target["target"]["name"].as_str().unwrap_or_default().to_string(),
+                            target["parallelizable"].as_bool().unwrap_or(false),
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Enable or disable parallel testing for every test target, returning
+    /// how many changed
+    pub fn set_parallelizable(&mut self, enabled: bool) -> usize {
+        let Some(targets) = self.json["testTargets"].as_array_mut() else {
+            return 0;
+        };
+        let mut changed = 0;
+        for target in targets.iter_mut().filter_map(serde_json::Value::as_object_mut) {
+            if target.get("parallelizable").and_then(serde_json::Value::as_bool) != Some(enabled) {
+                changed += 1;
+            }
+            target.insert("parallelizable".to_string(), enabled.into());
+        }
+        changed
+    }
+
+    /// Test plan contents, formatted like Xcode (`"key" : value`)
+    pub fn content(&self) -> Result<String> {
+        let mut out = Vec::new();
+        let mut serializer = serde_json::Serializer::with_formatter(&mut out, XcodeJsonFormatter::default());
+        serde::Serialize::serialize(&self.json, &mut serializer)?;
+        out.push(b'\n');
+        Ok(String::from_utf8_lossy(&out).into_owned())
+    }
+
+    /// Write the test plan
+    pub fn save(&self) -> Result<()> {
+        std::fs::write(&self.path, self.content()?)?;
+        Ok(())
+    }
+}
+
+/// Pretty printer matching Xcode's JSON files: two-space indent, `" : "`
+/// between keys and values
+#[derive(Default)]
+struct XcodeJsonFormatter(serde_json::ser::PrettyFormatter<'static>);
+
+impl serde_json::ser::Formatter for XcodeJsonFormatter {
+    fn begin_array<W: ?Sized + std::io::Write>(&mut self, writer: &mut W) -> std::io::Result<()> {
+        self.0.begin_array(writer)
+    }
+
+    fn end_array<W: ?Sized + std::io::Write>(&mut self, writer: &mut W) -> std::io::Result<()> {
+        self.0.end_array(writer)
+    }
+
+    fn begin_array_value<W: ?Sized + std::io::Write>(&mut self, writer: &mut W, first: bool) -> std::io::Result<()> {
+        self.0.begin_array_value(writer, first)
+    }
+
+    fn end_array_value<W: ?Sized + std::io::Write>(&mut self, writer: &mut W) -> std::io::Result<()> {
+        self.0.end_array_value(writer)
+    }
+
+    fn begin_object<W: ?Sized + std::io::Write>(&mut self, writer: &mut W) -> std::io::Result<()> {
+        self.0.begin_object(writer)
+    }
+
+    fn end_object<W: ?Sized + std::io::Write>(&mut self, writer: &mut W) -> std::io::Result<()> {
+        self.0.end_object(writer)
+    }
+
+    fn begin_object_key<W: ?Sized + std::io::Write>(&mut self, writer: &mut W, first: bool) -> std::io::Result<()> {
+        self.0.begin_object_key(writer, first)
+    }
+
+    fn begin_object_value<W: ?Sized + std::io::Write>(&mut self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(b" : ")
+    }
+
+    fn end_object_value<W: ?Sized + std::io::Write>(&mut self, writer: &mut W) -> std::io::Result<()> {
+        self.0.end_object_value(writer)
+    }
+}
+
+// ============================================================================
+// XML
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TagKind {
+    /// `<Name ...>`
+    Open,
+    /// `<Name .../>`
+    Empty,
+    /// `</Name>`
+    Close,
+}
+
+/// An element tag and its byte range in the file
+#[derive(Debug)]
+struct Tag<'a> {
+    name: &'a str,
+    kind: TagKind,
+    start: usize,
+    end: usize,
+}
+
+impl Tag<'_> {
+    /// Attributes in file order, with entities decoded
+    fn attributes(&self, content: &str) -> Vec<(String, String)> {
+        let text = &content[self.start..self.end];
+        let mut attrs = Vec::new();
+        let mut rest = text[1 + self.name.len()..].trim_end_matches(['>', '/']);
+        while let Some((name, after)) = rest.split_once('=') {
+            let Some(value) = after.trim_start().strip_prefix('"') else {
+                break;
+            };
+            let Some(close) = value.find('"') else {
+                break;
+            };
+            attrs.push((name.trim().to_string(), unescape(&value[..close])));
+            rest = &value[close + 1..];
+        }
+        attrs
+    }
+}
+
+/// Element tags in document order, skipping the XML declaration and comments
+fn tags(content: &str) -> std::result::Result<Vec<Tag<'_>>, String> {
+    let mut tags = Vec::new();
+    let mut pos = 0;
+    while let Some(offset) = content[pos..].find('<') {
+        let start = pos + offset;
+        let rest = &content[start..];
+        if rest.starts_with("<?") || rest.starts_with("<!--") {
+            let terminator = if rest.starts_with("<?") { "?>" } else { "-->" };
+            let end = rest
+                .find(terminator)
+                .ok_or_else(|| format!("unterminated tag at byte {}", start))?;
+            pos = start + end + terminator.len();
+            continue;
+        }
+
+        // Find the closing '>' outside attribute values
+        let mut in_quotes = false;
+        let end = rest
+            .char_indices()
+            .find(|&(_, c)| {
+                if c == '"' {
+                    in_quotes = !in_quotes;
+                }
+                c == '>' && !in_quotes
+            })
+            .map(|(i, _)| start + i + 1)
+            .ok_or_else(|| format!("unterminated tag at byte {}", start))?;
+        let text = &content[start..end];
+
+        let (kind, name_start) = if text.starts_with("</") {
+            (TagKind::Close, start + 2)
+        } else if text.ends_with("/>") {
+            (TagKind::Empty, start + 1)
+        } else {
+            (TagKind::Open, start + 1)
+        };
+        let name_len = content[name_start..end]
+            .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+            .unwrap_or(0);
+        tags.push(Tag {
+            name: &content[name_start..name_start + name_len],
+            kind,
+            start,
+            end,
+        });
+        pos = end;
+    }
+    Ok(tags)
+}
+
+fn attr<'a>(attrs: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    attrs
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.as_str())
+}
+
+/// Set an attribute in a tag's text, appending it on its own line in Xcode's
+/// layout when missing
+fn set_tag_attribute(tag: &str, name: &str, value: &str) -> String {
+    let value = escape(value);
+    let pattern = format!("{} = \"", name);
+    let existing = tag
+        .match_indices(&pattern)
+        .find(|&(i, _)| tag[..i].ends_with(char::is_whitespace));
+    if let Some((i, _)) = existing {
+        let value_start = i + pattern.len();
+        let value_end = value_start + tag[value_start..].find('"').unwrap_or(0);
+        return format!("{}{}{}", &tag[..value_start], value, &tag[value_end..]);
+    }
+
+    let close = if tag.ends_with("/>") { tag.len() - 2 } else { tag.len() - 1 };
+    let body = tag[..close].trim_end();
+    // Indent like the existing attributes, which Xcode puts one per line
+    let indent = tag
+        .lines()
+        .nth(1)
+        .map(|line| &line[..line.len() - line.trim_start().len()])
+        .filter(|indent| !indent.is_empty());
+    match indent {
+        Some(indent) => format!("{}\n{}{} = \"{}\"{}", body, indent, name, value, &tag[close..]),
+        None => format!("{} {} = \"{}\"{}", body, name, value, &tag[close..]),
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "YES"
+    } else {
+        "NO"
+    }
+}
+
+fn buildable_reference(target: &Target, container: &str) -> BuildableReference {
+    BuildableReference {
+        blueprint_id: target.id.clone(),
+        name: target.name.clone(),
+        buildable_name: target
+            .product_name
+            .clone()
+            .unwrap_or_else(|| target.name.clone()),
+        container: container.to_string(),
+    }
+}
+
+fn buildable_reference_xml(reference: &BuildableReference, indent: &str) -> String {
+    format!(
+        r#"{indent}<BuildableReference
+{indent}   BuildableIdentifier = "primary"
+{indent}   BlueprintIdentifier = "{}"
+{indent}   BuildableName = "{}"
+{indent}   BlueprintName = "{}"
+{indent}   ReferencedContainer = "{}">
+{indent}</BuildableReference>
+"#,
+        escape(&reference.blueprint_id),
+        escape(&reference.buildable_name),
+        escape(&reference.name),
+        escape(&reference.container),
+    )
+}
+
+fn scheme_xml(app: &BuildableReference, tests: &[BuildableReference], code_coverage: bool) -> String {
+    let testables: String = tests
+        .iter()
+        .map(|test| {
+            format!(
+                r#"         <TestableReference
+            skipped = "NO"
+            parallelizable = "YES">
+{}         </TestableReference>
+"#,
+                buildable_reference_xml(test, "            ")
+            )
+        })
+        .collect();
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<Scheme
+   LastUpgradeVersion = "1600"
+   version = "1.7">
+   <BuildAction
+      parallelizeBuildables = "YES"
+      buildImplicitDependencies = "YES">
+      <BuildActionEntries>
+         <BuildActionEntry
+            buildForTesting = "YES"
+            buildForRunning = "YES"
+            buildForProfiling = "YES"
+            buildForArchiving = "YES"
+            buildForAnalyzing = "YES">
+{app_build}         </BuildActionEntry>
+      </BuildActionEntries>
+   </BuildAction>
+   <TestAction
+      buildConfiguration = "Debug"
+      selectedDebuggerIdentifier = "Xcode.DebuggerFoundation.Debugger.LLDB"
+      selectedLauncherIdentifier = "Xcode.DebuggerFoundation.Launcher.LLDB"
+      shouldUseLaunchSchemeArgsEnv = "YES"
+      codeCoverageEnabled = "{coverage}">
+      <Testables>
+{testables}      </Testables>
+   </TestAction>
+   <LaunchAction
+      buildConfiguration = "Debug"
+      selectedDebuggerIdentifier = "Xcode.DebuggerFoundation.Debugger.LLDB"
+      selectedLauncherIdentifier = "Xcode.DebuggerFoundation.Launcher.LLDB"
+      launchStyle = "0"
+      useCustomWorkingDirectory = "NO"
+      ignoresPersistentStateOnLaunch = "NO"
+      debugDocumentVersioning = "YES"
+      debugServiceExtension = "internal"
+      allowLocationSimulation = "YES">
+      <BuildableProductRunnable
+         runnableDebuggingMode = "0">
+{app_run}      </BuildableProductRunnable>
+   </LaunchAction>
+   <ProfileAction
+      buildConfiguration = "Release"
+      shouldUseLaunchSchemeArgsEnv = "YES"
+      savedToolIdentifier = ""
+      useCustomWorkingDirectory = "NO"
+      debugDocumentVersioning = "YES">
+      <BuildableProductRunnable
+         runnableDebuggingMode = "0">
+{app_run}      </BuildableProductRunnable>
+   </ProfileAction>
+   <AnalyzeAction
+      buildConfiguration = "Debug">
+   </AnalyzeAction>
+   <ArchiveAction
+      buildConfiguration = "Release"
+      revealArchiveInOrganizer = "YES">
+   </ArchiveAction>
+</Scheme>
+"#,
+        app_build = buildable_reference_xml(app, "            "),
+        app_run = buildable_reference_xml(app, "         "),
+        coverage = yes_no(code_coverage),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    const SCHEME: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Scheme
+   LastUpgradeVersion = "1600"
+   version = "1.7">
+   <BuildAction
+      parallelizeBuildables = "YES"
+      buildImplicitDependencies = "YES">
+      <BuildActionEntries>
+         <BuildActionEntry
+            buildForTesting = "YES"
+            buildForRunning = "YES">
+            <BuildableReference
+               BuildableIdentifier = "primary"
+               BlueprintIdentifier = "D10000000000000000000001"
+               BuildableName = "FoodShare.app"
+               BlueprintName = "FoodShare"
+               ReferencedContainer = "container:FoodShare.xcodeproj">
+            </BuildableReference>
+         </BuildActionEntry>
+      </BuildActionEntries>
+   </BuildAction>
+   <TestAction
+      buildConfiguration = "Debug"
+      shouldUseLaunchSchemeArgsEnv = "YES">
+      <Testables>
+         <TestableReference
+            skipped = "NO">
+            <BuildableReference
+               BuildableIdentifier = "primary"
+               BlueprintIdentifier = "D10000000000000000000002"
+               BuildableName = "FoodShareTests.xctest"
+               BlueprintName = "FoodShareTests"
+               ReferencedContainer = "container:FoodShare.xcodeproj">
+            </BuildableReference>
+         </TestableReference>
+      </Testables>
+   </TestAction>
+   <LaunchAction
+      buildConfiguration = "Debug">
+      <!-- The app is launched from the build entry -->
+   </LaunchAction>
+</Scheme>
+"#;
+
+    fn parse(content: &str) -> Scheme {
+        Scheme::parse(
+            Path::new("FoodShare.xcodeproj/xcshareddata/xcschemes/FoodShare.xcscheme"),
+            true,
+            content.to_string(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_scheme() {
+        let scheme = parse(SCHEME);
+        assert_eq!(scheme.name, "FoodShare");
+        assert_eq!(scheme.build_targets.len(), 1);
+        assert_eq!(scheme.build_targets[0].buildable_name, "FoodShare.app");
+        assert_eq!(scheme.testables.len(), 1);
+        assert_eq!(scheme.testables[0].target.name, "FoodShareTests");
+        assert!(!scheme.testables[0].parallelizable);
+        assert!(!scheme.code_coverage);
+        assert!(scheme.test_plans.is_empty());
+
+        assert!(Scheme::parse(Path::new("Broken.xcscheme"), true, "<Scheme".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_edit_scheme() {
+        let mut scheme = parse(SCHEME);
+        assert_eq!(scheme.set_parallelizable(true).unwrap(), 1);
+        scheme.set_code_coverage(true).unwrap();
+        assert!(scheme.testables[0].parallelizable);
+        assert!(scheme.code_coverage);
+        assert!(scheme.content().contains(
+            "         <TestableReference\n            skipped = \"NO\"\n            parallelizable = \"YES\">\n"
+        ));
+        assert!(scheme
+            .content()
+            .contains("      shouldUseLaunchSchemeArgsEnv = \"YES\"\n      codeCoverageEnabled = \"YES\">\n"));
+
+        // Changing an existing attribute leaves the rest of the file alone
+        assert_eq!(scheme.set_parallelizable(false).unwrap(), 1);
+        assert_eq!(
+            scheme.content(),
+            SCHEME
+                .replace(
+                    "            skipped = \"NO\">",
+                    "            skipped = \"NO\"\n            parallelizable = \"NO\">"
+                )
+                .replace(
+                    "      shouldUseLaunchSchemeArgsEnv = \"YES\">",
+                    "      shouldUseLaunchSchemeArgsEnv = \"YES\"\n      codeCoverageEnabled = \"YES\">"
+                )
+        );
+    }
+
+    #[test]
+    fn test_list_and_generate() {
+        let dir = tempfile::tempdir().unwrap();
+        let project_path = dir.path().join("FoodShare.xcodeproj");
+        fs::create_dir_all(project_path.join(SHARED_SCHEMES_DIR)).unwrap();
+        fs::create_dir_all(project_path.join("xcuserdata/dev.xcuserdatad/xcschemes")).unwrap();
+        fs::write(project_path.join(SHARED_SCHEMES_DIR).join("FoodShare.xcscheme"), SCHEME).unwrap();
+        fs::write(
+            project_path.join("xcuserdata/dev.xcuserdatad/xcschemes/Scratch.xcscheme"),
+            SCHEME,
+        )
+        .unwrap();
+        // The fixture app plus a unit test bundle
+        let pbxproj = crate::xcodeproj::tests::FIXTURE
+            .replace(
+                "/* End PBXFileReference section */",
+                "\t\tB10000000000000000000009 /* FoodShareTests.xctest */ = {isa = PBXFileReference; explicitFileType = wrapper.cfbundle; path = FoodShareTests.xctest; sourceTree = BUILT_PRODUCTS_DIR; };\n/* End PBXFileReference section */",
+            )
+            .replace(
+                "/* End PBXNativeTarget section */",
+                "\t\tD10000000000000000000002 /* FoodShareTests */ = {\n\t\t\tisa = PBXNativeTarget;\n\t\t\tname = FoodShareTests;\n\t\t\tproductReference = B10000000000000000000009 /* FoodShareTests.xctest */;\n\t\t\tproductType = \"com.apple.product-type.bundle.unit-test\";\n\t\t};\n/* End PBXNativeTarget section */",
+            );
+        fs::write(project_path.join("project.pbxproj"), pbxproj).unwrap();
+
+        let schemes = Scheme::list(&project_path).unwrap();
+        let names: Vec<(&str, bool)> = schemes.iter().map(|s| (s.name.as_str(), s.shared)).collect();
+        assert_eq!(names, [("FoodShare", true), ("Scratch", false)]);
+
+        let project = XcodeProject::open(&project_path).unwrap();
+        let ci = Scheme::generate(&project, "FoodShare-CI", "FoodShare", true).unwrap();
+        assert!(ci.shared);
+        assert!(ci.code_coverage);
+        assert_eq!(ci.build_targets[0].blueprint_id, "D10000000000000000000001");
+        assert_eq!(ci.build_targets[0].buildable_name, "FoodShare.app");
+        assert_eq!(ci.build_targets[0].container, "container:FoodShare.xcodeproj");
+        assert_eq!(ci.testables.len(), 1);
+        assert_eq!(ci.testables[0].target.buildable_name, "FoodShareTests.xctest");
+        assert!(ci.testables[0].parallelizable);
+        ci.save().unwrap();
+        assert_eq!(Scheme::find(&project_path, "FoodShare-CI").unwrap().content(), ci.content());
+
+        assert!(Scheme::generate(&project, "CI", "Missing", true).is_err());
+    }
+
+    #[test]
+    fn test_test_plan() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("FoodShare.xctestplan");
+        fs::write(
+            &path,
+            r#"{
+  "configurations" : [
+    {
+      "id" : "1F2E3D4C-0000-0000-0000-000000000001",
+      "name" : "Default",
+      "options" : {
+
+      }
+    }
+  ],
+  "defaultOptions" : {
+    "codeCoverage" : true
+  },
+  "testTargets" : [
+    {
+      "target" : {
+        "containerPath" : "container:FoodShare.xcodeproj",
+        "identifier" : "D10000000000000000000002",
+        "name" : "FoodShareTests"
+      }
+    }
+  ],
+  "version" : 1
+}
+"#,
+        )
+        .unwrap();
+
+        let mut plan = TestPlan::load(&path).unwrap();
+        assert_eq!(plan.test_targets(), [("FoodShareTests".to_string(), false)]);
+        assert_eq!(plan.set_parallelizable(true), 1);
+        assert_eq!(plan.set_parallelizable(true), 0);
+        plan.save().unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.contains("  \"testTargets\" : [\n    {\n      \"parallelizable\" : true,\n"));
+        assert!(TestPlan::load(&path).unwrap().test_targets()[0].1);
+
+        let scheme = parse(&SCHEME.replace(
+            "      <Testables>",
+            "      <TestPlans>\n         <TestPlanReference\n            reference = \"container:FoodShare.xctestplan\"\n            default = \"YES\">\n         </TestPlanReference>\n      </TestPlans>\n      <Testables>",
+        ));
+        assert_eq!(
+            scheme.test_plan_paths(Path::new("ios/FoodShare.xcodeproj")),
+            [PathBuf::from("ios/FoodShare.xctestplan")]
+        );
+    }
+}
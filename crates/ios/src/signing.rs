@@ -0,0 +1,801 @@
+//! Code signing diagnostics
+//!
+//! A device build needs, for every app and extension target, an installed
+//! provisioning profile that covers the bundle ID and entitlements, hasn't
+//! expired, includes the device, and was issued for a certificate whose
+//! private key is in the keychain. `xcodebuild` reports a missing piece as
+//! a generic "No profiles for ..." error; [`check_project`] names the exact
+//! profile and certificate that don't line up.
+
+use crate::{plist, xcode};
+use base64::Engine;
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use foodshare_core::error::{Error, Result};
+use foodshare_core::health::{CheckResult, HealthStatus};
+use foodshare_core::process::run_command;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha1::{Digest, Sha1};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Certificates and profiles expiring within this many days are reported
+pub const EXPIRY_WARNING_DAYS: i64 = 30;
+
+/// Entitlements that profiles don't need to list for signing to succeed
+const IMPLICIT_ENTITLEMENTS: &[&str] = &[
+    "application-identifier",
+    "com.apple.developer.team-identifier",
+    "keychain-access-groups",
+];
+
+/// A code signing certificate
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Certificate {
+    /// Common name (`Apple Development: Jane Doe (XYZ9876543)`)
+    pub name: String,
+    /// Team ID (the certificate's organizational unit)
+    pub team: Option<String>,
+    /// SHA-1 fingerprint, uppercase hex
+    pub sha1: String,
+    /// Expiry date
+    pub expires: DateTime<Utc>,
+    /// Whether the private key is in the keychain, so it can sign
+    pub has_private_key: bool,
+}
+
+impl Certificate {
+    /// Read a DER-encoded X.509 certificate
+    pub fn from_der(der: &[u8]) -> Option<Self> {
+        let (name, team, expires) = parse_x509(der)?;
+        Some(Self {
+            name,
+            team,
+            sha1: hex::encode_upper(Sha1::digest(der)),
+            expires,
+            has_private_key: false,
+        })
+    }
+
+    /// Whether this is an Apple development or distribution certificate
+    pub fn is_apple_signing(&self) -> bool {
+        ["Apple Development", "Apple Distribution", "iPhone Developer", "iPhone Distribution"]
+            .iter()
+            .any(|prefix| self.name.starts_with(prefix))
+    }
+}
+
+/// What a provisioning profile can be used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProfileKind {
+    /// Debug builds on registered devices
+    Development,
+    /// Release builds on registered devices
+    AdHoc,
+    /// App Store and TestFlight uploads; can't install on devices
+    AppStore,
+    /// In-house distribution to any device
+    Enterprise,
+}
+
+impl std::fmt::Display for ProfileKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Development => "development",
+            Self::AdHoc => "ad hoc",
+            Self::AppStore => "App Store",
+            Self::Enterprise => "enterprise",
+        };
+        f.write_str(name)
+    }
+}
+
+/// An installed provisioning profile
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProvisioningProfile {
+    /// Profile name
+    pub name: String,
+    /// Profile UUID, used by `PROVISIONING_PROFILE`
+    pub uuid: String,
+    /// Team ID
+    pub team: String,
+    /// App ID without the team prefix (`com.flutterflow.foodshare`, `com.flutterflow.*`, `*`)
+    pub app_id: String,
+    /// What the profile can be used for
+    pub kind: ProfileKind,
+    /// Expiry date
+    pub expires: DateTime<Utc>,
+    /// Granted entitlements
+    pub entitlements: Map<String, Value>,
+    /// SHA-1 fingerprints of the certificates the profile was issued for
+    pub certificates: Vec<String>,
+    /// UDIDs of the registered devices
+    pub devices: Vec<String>,
+    /// File the profile was read from
+    pub path: PathBuf,
+}
+
+impl ProvisioningProfile {
+    /// Decode a `.mobileprovision` file
+    pub fn load(path: &Path) -> Result<Self> {
+        let result = run_command("security", &["cms", "-D", "-i", &path.to_string_lossy()])?;
+        if !result.success {
+            return Err(Error::process(format!(
+                "Failed to decode {}: {}",
+                path.display(),
+                result.stderr.trim()
+            )));
+        }
+        Self::from_plist(&plist::parse(&result.stdout)?, path)
+    }
+
+    /// Read a decoded profile
+    pub fn from_plist(plist: &Value, path: &Path) -> Result<Self> {
+        let field = |key: &str| {
+            plist[key]
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| Error::validation(format!("{}: missing {}", path.display(), key)))
+        };
+        let team = plist["TeamIdentifier"][0]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| Error::validation(format!("{}: missing TeamIdentifier", path.display())))?;
+        let expires = DateTime::parse_from_rfc3339(&field("ExpirationDate")?)
+            .map_err(|e| Error::validation(format!("{}: invalid ExpirationDate: {}", path.display(), e)))?
+            .with_timezone(&Utc);
+
+        let entitlements = plist["Entitlements"].as_object().cloned().unwrap_or_default();
+        let app_id = entitlements
+            .get("application-identifier")
+            .and_then(Value::as_str)
+            .map(|id| id.split_once('.').map_or(id, |(_, rest)| rest).to_string())
+            .unwrap_or_else(|| "*".to_string());
+
+        let engine = base64::engine::general_purpose::STANDARD;
+        let certificates = plist["DeveloperCertificates"]
+            .as_array()
+            .map(|certs| {
+                certs
+                    .iter()
+                    .filter_map(|cert| engine.decode(cert.as_str()?).ok())
+                    .map(|der| hex::encode_upper(Sha1::digest(&der)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let devices: Vec<String> = plist["ProvisionedDevices"]
+            .as_array()
+            .map(|devices| devices.iter().filter_map(|d| d.as_str().map(str::to_string)).collect())
+            .unwrap_or_default();
+
+        let kind = if plist["ProvisionsAllDevices"].as_bool() == Some(true) {
+            ProfileKind::Enterprise
+        } else if entitlements.get("get-task-allow").and_then(Value::as_bool) == Some(true) {
+            ProfileKind::Development
+        } else if !devices.is_empty() {
+            ProfileKind::AdHoc
+        } else {
+            ProfileKind::AppStore
+        };
+
+        Ok(Self {
+            name: field("Name")?,
+            uuid: field("UUID")?,
+            team,
+            app_id,
+            kind,
+            expires,
+            entitlements,
+            certificates,
+            devices,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Whether the profile's App ID covers `bundle_id`
+    pub fn matches_bundle_id(&self, bundle_id: &str) -> bool {
+        wildcard_match(&self.app_id, bundle_id)
+    }
+
+    /// Whether the profile can be installed on `udid`
+    pub fn includes_device(&self, udid: &str) -> bool {
+        self.kind == ProfileKind::Enterprise
+            || self.devices.iter().any(|d| d.eq_ignore_ascii_case(udid))
+    }
+
+    /// Required entitlements the profile doesn't grant
+    pub fn missing_entitlements(&self, required: &Map<String, Value>) -> Vec<String> {
+        required
+            .iter()
+            .filter(|(key, _)| !IMPLICIT_ENTITLEMENTS.contains(&key.as_str()))
+            .filter(|(key, value)| match self.entitlements.get(*key) {
+                None => true,
+                Some(granted) => !entitlement_covered(value, granted),
+            })
+            .map(|(key, _)| key.clone())
+            .collect()
+    }
+}
+
+/// Signing settings of an app or extension target
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SigningTarget {
+    /// Target name
+    pub name: String,
+    /// `PRODUCT_BUNDLE_IDENTIFIER`
+    pub bundle_id: Option<String>,
+    /// `DEVELOPMENT_TEAM`
+    pub team: Option<String>,
+    /// Whether `CODE_SIGN_STYLE` is `Manual`
+    pub manual: bool,
+    /// `PROVISIONING_PROFILE_SPECIFIER` (a profile name) or `PROVISIONING_PROFILE` (a UUID)
+    pub profile: Option<String>,
+    /// Entitlements from `CODE_SIGN_ENTITLEMENTS`, with build variables expanded
+    pub entitlements: Map<String, Value>,
+}
+
+impl SigningTarget {
+    /// Read a target's resolved build settings, loading its entitlements file
+    pub fn from_build_settings(name: &str, settings: &BTreeMap<String, String>) -> Result<Self> {
+        let setting = |key: &str| settings.get(key).filter(|v| !v.is_empty()).cloned();
+        let mut target = Self {
+            name: name.to_string(),
+            bundle_id: setting("PRODUCT_BUNDLE_IDENTIFIER"),
+            team: setting("DEVELOPMENT_TEAM"),
+            manual: setting("CODE_SIGN_STYLE").as_deref() == Some("Manual"),
+            profile: setting("PROVISIONING_PROFILE_SPECIFIER").or_else(|| setting("PROVISIONING_PROFILE")),
+            entitlements: Map::new(),
+        };
+
+        if let Some(file) = setting("CODE_SIGN_ENTITLEMENTS") {
+            let path = match setting("SRCROOT") {
+                Some(root) => Path::new(&root).join(&file),
+                None => PathBuf::from(&file),
+            };
+            let entitlements = plist::load(&path)?;
+            target.entitlements = entitlements.as_object().cloned().unwrap_or_default();
+            target.expand_variables();
+        }
+        Ok(target)
+    }
+
+    /// Replace the build variables entitlements files commonly use
+    fn expand_variables(&mut self) {
+        let prefix = self.team.as_ref().map(|team| format!("{}.", team)).unwrap_or_default();
+        let bundle_id = self.bundle_id.clone().unwrap_or_default();
+        let expand = |text: &str| {
+            text.replace("$(AppIdentifierPrefix)", &prefix)
+                .replace("$(TeamIdentifierPrefix)", &prefix)
+                .replace("$(PRODUCT_BUNDLE_IDENTIFIER)", &bundle_id)
+        };
+        for value in self.entitlements.values_mut() {
+            match value {
+                Value::String(text) => *text = expand(text),
+                Value::Array(items) => {
+                    for item in items.iter_mut() {
+                        if let Value::String(text) = item {
+                            *text = expand(text);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Code signing certificates in the keychain
+pub fn keychain_certificates() -> Result<Vec<Certificate>> {
+    let result = run_command("security", &["find-certificate", "-a", "-p"])?;
+    if !result.success {
+        return Err(Error::process(format!(
+            "Failed to read the keychain: {}",
+            result.stderr.trim()
+        )));
+    }
+    let identities = run_command("security", &["find-identity", "-v", "-p", "codesigning"])?;
+    let with_keys = parse_identity_hashes(&identities.stdout);
+
+    Ok(parse_pem_certificates(&result.stdout)
+        .iter()
+        .filter_map(|der| Certificate::from_der(der))
+        .filter(Certificate::is_apple_signing)
+        .map(|mut cert| {
+            cert.has_private_key = with_keys.contains(&cert.sha1);
+            cert
+        })
+        .collect())
+}
+
+/// Directories Xcode installs provisioning profiles into
+pub fn profile_dirs() -> Vec<PathBuf> {
+    let Some(home) = std::env::var_os("HOME").map(PathBuf::from) else {
+        return Vec::new();
+    };
+    vec![
+        // Xcode 16+
+        home.join("Library/Developer/Xcode/UserData/Provisioning Profiles"),
+        home.join("Library/MobileDevice/Provisioning Profiles"),
+    ]
+}
+
+/// Installed provisioning profiles; unreadable files are skipped
+pub fn installed_profiles() -> Vec<ProvisioningProfile> {
+    let mut seen = HashSet::new();
+    profile_dirs()
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "mobileprovision"))
+        .filter_map(|path| ProvisioningProfile::load(&path).ok())
+        .filter(|profile| seen.insert(profile.uuid.clone()))
+        .collect()
+}
+
+/// Check every app and extension target the scheme builds for a device
+///
+/// `device` is the UDID the build will be installed on, if known.
+pub fn check_project(scheme: &str, configuration: &str, device: Option<&str>) -> Result<Vec<CheckResult>> {
+    let certificates = keychain_certificates()?;
+    let profiles = installed_profiles();
+    let now = Utc::now();
+
+    let mut checks = Vec::new();
+    let mut teams = Vec::new();
+    for (name, settings) in xcode::target_build_settings(scheme, configuration, "iphoneos")? {
+        let product_type = settings.get("PRODUCT_TYPE").map(String::as_str).unwrap_or_default();
+        if !is_signed_product(product_type) {
+            continue;
+        }
+        let target = SigningTarget::from_build_settings(&name, &settings)?;
+        if let Some(team) = &target.team {
+            if !teams.contains(team) {
+                teams.push(team.clone());
+            }
+        }
+        checks.push(check_target(&target, &profiles, &certificates, device, now));
+    }
+
+    for team in &teams {
+        checks.push(check_team_certificate(team, &certificates, now));
+    }
+    checks.extend(expiry_checks(&profiles, &certificates, now));
+    Ok(checks)
+}
+
+/// Whether a product type needs its own provisioning profile
+fn is_signed_product(product_type: &str) -> bool {
+    product_type == "com.apple.product-type.application"
+        || product_type.starts_with("com.apple.product-type.app-extension")
+        || product_type.starts_with("com.apple.product-type.application.watchapp")
+        || product_type == "com.apple.product-type.extensionkit-extension"
+}
+
+/// Check that some installed profile can sign `target`
+pub fn check_target(
+    target: &SigningTarget,
+    profiles: &[ProvisioningProfile],
+    certificates: &[Certificate],
+    device: Option<&str>,
+    now: DateTime<Utc>,
+) -> CheckResult {
+    let check = format!("Signing: {}", target.name);
+    let (Some(bundle_id), Some(team)) = (&target.bundle_id, &target.team) else {
+        return CheckResult::unhealthy(
+            check,
+            "PRODUCT_BUNDLE_IDENTIFIER and DEVELOPMENT_TEAM must both be set",
+        );
+    };
+
+    let mut candidates: Vec<&ProvisioningProfile> = profiles
+        .iter()
+        .filter(|p| &p.team == team && p.matches_bundle_id(bundle_id))
+        .collect();
+
+    if let Some(specifier) = &target.profile {
+        let named: Vec<&ProvisioningProfile> = profiles
+            .iter()
+            .filter(|p| &p.name == specifier || p.uuid.eq_ignore_ascii_case(specifier))
+            .collect();
+        if named.is_empty() {
+            return CheckResult::unhealthy(check, format!("Profile '{}' is not installed", specifier));
+        }
+        candidates.retain(|p| named.contains(p));
+        if candidates.is_empty() {
+            let other = named[0];
+            return CheckResult::unhealthy(
+                check,
+                format!(
+                    "Profile '{}' is for {} (team {}), not {} (team {})",
+                    specifier, other.app_id, other.team, bundle_id, team
+                ),
+            );
+        }
+    }
+
+    if candidates.is_empty() {
+        let message = format!("No installed profile for {} (team {})", bundle_id, team);
+        return if target.manual {
+            CheckResult::unhealthy(check, message)
+        } else {
+            CheckResult::degraded(
+                check,
+                format!("{}; Xcode creates one with -allowProvisioningUpdates when signed in", message),
+            )
+        };
+    }
+
+    let mut rejected = Vec::new();
+    for profile in &candidates {
+        let problems = profile_problems(profile, target, certificates, device, now);
+        if problems.is_empty() {
+            let certificate = certificates
+                .iter()
+                .find(|c| usable(c, now) && profile.certificates.contains(&c.sha1))
+                .map(|c| c.name.as_str())
+                .unwrap_or_default();
+            return CheckResult::healthy(check)
+                .with_detail("profile", &profile.name)
+                .with_detail("expires", profile.expires.format("%Y-%m-%d").to_string())
+                .with_detail("certificate", certificate);
+        }
+        rejected.push(format!("profile '{}' {}", profile.name, problems.join(", ")));
+    }
+
+    let result = CheckResult::unhealthy(check, rejected.join("; "));
+    if target.manual {
+        result
+    } else {
+        CheckResult {
+            status: HealthStatus::Degraded,
+            ..result
+        }
+    }
+}
+
+/// Why `profile` can't sign `target`
+fn profile_problems(
+    profile: &ProvisioningProfile,
+    target: &SigningTarget,
+    certificates: &[Certificate],
+    device: Option<&str>,
+    now: DateTime<Utc>,
+) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    if profile.expires <= now {
+        problems.push(format!("expired on {}", profile.expires.format("%Y-%m-%d")));
+    }
+    if profile.kind == ProfileKind::AppStore {
+        problems.push("is an App Store profile and can't install on devices".to_string());
+    } else if let Some(udid) = device {
+        if !profile.includes_device(udid) {
+            problems.push(format!("doesn't include device {}", udid));
+        }
+    }
+    if !certificates
+        .iter()
+        .any(|c| usable(c, now) && profile.certificates.contains(&c.sha1))
+    {
+        problems.push("was issued for a certificate that isn't in the keychain or has expired".to_string());
+    }
+    let missing = profile.missing_entitlements(&target.entitlements);
+    if !missing.is_empty() {
+        problems.push(format!("lacks entitlements {}", missing.join(", ")));
+    }
+    problems
+}
+
+/// Check that the keychain can sign for `team`
+pub fn check_team_certificate(team: &str, certificates: &[Certificate], now: DateTime<Utc>) -> CheckResult {
+    let check = format!("Certificate: team {}", team);
+    let team_certs: Vec<&Certificate> = certificates
+        .iter()
+        .filter(|c| c.team.as_deref() == Some(team))
+        .collect();
+
+    match team_certs.iter().find(|c| usable(c, now)) {
+        Some(cert) => CheckResult::healthy(check)
+            .with_detail("certificate", &cert.name)
+            .with_detail("expires", cert.expires.format("%Y-%m-%d").to_string()),
+        None if team_certs.is_empty() => CheckResult::unhealthy(
+            check,
+            "No signing certificate for this team; sign in to Xcode with your Apple ID",
+        ),
+        None => {
+            let reasons: Vec<String> = team_certs
+                .iter()
+                .map(|c| {
+                    if c.expires <= now {
+                        format!("'{}' expired on {}", c.name, c.expires.format("%Y-%m-%d"))
+                    } else {
+                        format!("'{}' has no private key on this Mac", c.name)
+                    }
+                })
+                .collect();
+            CheckResult::unhealthy(check, reasons.join("; "))
+        }
+    }
+}
+
+/// Warnings for certificates and profiles about to expire
+pub fn expiry_checks(
+    profiles: &[ProvisioningProfile],
+    certificates: &[Certificate],
+    now: DateTime<Utc>,
+) -> Vec<CheckResult> {
+    let soon = now + Duration::days(EXPIRY_WARNING_DAYS);
+    let expiring = |expires: DateTime<Utc>| expires > now && expires <= soon;
+
+    let certs = certificates
+        .iter()
+        .filter(|c| c.has_private_key && expiring(c.expires))
+        .map(|c| {
+            CheckResult::degraded(
+                format!("Certificate: {}", c.name),
+                format!("expires on {}", c.expires.format("%Y-%m-%d")),
+            )
+        });
+    let profiles = profiles.iter().filter(|p| expiring(p.expires)).map(|p| {
+        CheckResult::degraded(
+            format!("Profile: {}", p.name),
+            format!("expires on {}", p.expires.format("%Y-%m-%d")),
+        )
+    });
+    certs.chain(profiles).collect()
+}
+
+/// A certificate that can sign right now
+fn usable(certificate: &Certificate, now: DateTime<Utc>) -> bool {
+    certificate.has_private_key && certificate.expires > now
+}
+
+/// Match `value` against a pattern with an optional trailing `*`
+fn wildcard_match(pattern: &str, value: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => value.starts_with(prefix),
+        None => pattern == value,
+    }
+}
+
+/// Whether a granted entitlement covers a required one
+///
+/// Lists (app groups, associated domains, iCloud containers) must be covered
+/// entry by entry, where the profile may use wildcards; other values only
+/// need to be present.
+fn entitlement_covered(required: &Value, granted: &Value) -> bool {
+    let patterns: Vec<&str> = match granted {
+        Value::Array(items) => items.iter().filter_map(Value::as_str).collect(),
+        Value::String(text) => vec![text.as_str()],
+        _ => return true,
+    };
+    match required {
+        Value::Array(items) => items
+            .iter()
+            .filter_map(Value::as_str)
+            .all(|item| patterns.iter().any(|pattern| wildcard_match(pattern, item))),
+        _ => true,
+    }
+}
+
+/// SHA-1 hashes of `security find-identity -v` output
+fn parse_identity_hashes(output: &str) -> HashSet<String> {
+    output
+        .lines()
+        .filter_map(|line| line.trim().split_once(") ")?.1.split_whitespace().next())
+        .filter(|hash| hash.len() == 40 && hash.chars().all(|c| c.is_ascii_hexdigit()))
+        .map(str::to_uppercase)
+        .collect()
+}
+
+/// DER bytes of the PEM certificates in `text`
+fn parse_pem_certificates(text: &str) -> Vec<Vec<u8>> {
+    let engine = base64::engine::general_purpose::STANDARD;
+    text.split("-----BEGIN CERTIFICATE-----")
+        .skip(1)
+        .filter_map(|block| {
+            let body: String = block
+                .split("-----END CERTIFICATE-----")
+                .next()?
+                .split_whitespace()
+                .collect();
+            engine.decode(body).ok()
+        })
+        .collect()
+}
+
+/// Minimal DER reader, enough to walk an X.509 certificate
+struct Der<'a>(&'a [u8]);
+
+impl<'a> Der<'a> {
+    /// Next element's tag and contents
+    fn next(&mut self) -> Option<(u8, &'a [u8])> {
+        let tag = *self.0.first()?;
+        let first = *self.0.get(1)? as usize;
+        let (len, header) = if first < 0x80 {
+            (first, 2)
+        } else {
+            let count = first & 0x7f;
+            if count == 0 || count > 4 {
+                return None;
+            }
+            let mut len = 0usize;
+            for i in 0..count {
+                len = (len << 8) | *self.0.get(2 + i)? as usize;
+            }
+            (len, 2 + count)
+        };
+        let contents = self.0.get(header..header + len)?;
+        self.0 = &self.0[header + len..];
+        Some((tag, contents))
+    }
+}
+
+const OID_COMMON_NAME: &[u8] = &[0x55, 0x04, 0x03];
+const OID_ORGANIZATIONAL_UNIT: &[u8] = &[0x55, 0x04, 0x0b];
+
+/// Subject common name, organizational unit and expiry of a certificate
+fn parse_x509(der: &[u8]) -> Option<(String, Option<String>, DateTime<Utc>)> {
+    let (_, certificate) = Der(der).next()?;
+    let (_, tbs) = Der(certificate).next()?;
+    let mut tbs = Der(tbs);
+
+    // [0] version is optional; serial number follows
+    if tbs.next()?.0 == 0xa0 {
+        tbs.next()?;
+    }
+    tbs.next()?; // signature algorithm
+    tbs.next()?; // issuer
+    let (_, validity) = tbs.next()?;
+    let (_, subject) = tbs.next()?;
+
+    let mut validity = Der(validity);
+    validity.next()?; // not before
+    let (time_tag, not_after) = validity.next()?;
+    let not_after = std::str::from_utf8(not_after).ok()?;
+    let format = if time_tag == 0x17 { "%y%m%d%H%M%SZ" } else { "%Y%m%d%H%M%SZ" };
+    let expires = NaiveDateTime::parse_from_str(not_after, format).ok()?.and_utc();
+
+    let mut name = None;
+    let mut unit = None;
+    let mut subject = Der(subject);
+    while let Some((_, set)) = subject.next() {
+        let Some((_, attribute)) = Der(set).next() else {
+            continue;
+        };
+        let mut attribute = Der(attribute);
+        let (Some((_, oid)), Some((_, value))) = (attribute.next(), attribute.next()) else {
+            continue;
+        };
+        let value = String::from_utf8_lossy(value).to_string();
+        match oid {
+            OID_COMMON_NAME => name = Some(value),
+            OID_ORGANIZATIONAL_UNIT => unit = Some(value),
+            _ => {}
+        }
+    }
+    Some((name?, unit, expires))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Self-signed `Apple Development: Jane Doe (XYZ9876543)`, OU ABCDE12345,
+    /// valid 2024-05-01 to 2025-05-01
+    const CERTIFICATE: &str = "MIICYDCCAgWgAwIBAgIUZUgtRmIwh7/m+ZXGuzagKKGBsnMwCgYIKoZIzj0EAwIwgYQxGjAYBgoJkiaJk/IsZAEBDApBQkNERTEyMzQ1MTEwLwYDVQQDDChBcHBsZSBEZXZlbG9wbWVudDogSmFuZSBEb2UgKFhZWjk4NzY1NDMpMRMwEQYDVQQLDApBQkNERTEyMzQ1MREwDwYDVQQKDAhKYW5lIERvZTELMAkGA1UEBhMCVVMwHhcNMjQwNTAxMDAwMDAwWhcNMjUwNTAxMDAwMDAwWjCBhDEaMBgGCgmSJomT8ixkAQEMCkFCQ0RFMTIzNDUxMTAvBgNVBAMMKEFwcGxlIERldmVsb3BtZW50OiBKYW5lIERvZSAoWFlaOTg3NjU0MykxEzARBgNVBAsMCkFCQ0RFMTIzNDUxETAPBgNVBAoMCEphbmUgRG9lMQswCQYDVQQGEwJVUzBZMBMGByqGSM49AgEGCCqGSM49AwEHA0IABMyHn6DW80q/HRs2tl9xeMlka4xrStHUreQF53rXwbDKyjmp7YOxJ8S5nZHCF2VsQccaOmL0g3YdnV9h8LKmuCajUzBRMB0GA1UdDgQWBBSFZX/6qPQTe+LTpnwu0Sv0jdxjCzAfBgNVHSMEGDAWgBSFZX/6qPQTe+LTpnwu0Sv0jdxjCzAPBgNVHRMBAf8EBTADAQH/MAoGCCqGSM49BAMCA0kAMEYCIQD6nH6DJN3OYpH8QyYMh4dSLtchCPJ5w4vK380Nd/txDgIhALVrS9m0YV142+ZEoEadJAtU6tcsQJetcn9C1LK/Mij9";
+
+    fn date(text: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(text).unwrap().with_timezone(&Utc)
+    }
+
+    fn certificate() -> Certificate {
+        let pem = format!("-----BEGIN CERTIFICATE-----\n{}\n-----END CERTIFICATE-----\n", CERTIFICATE);
+        let der = parse_pem_certificates(&pem).remove(0);
+        Certificate::from_der(&der).unwrap()
+    }
+
+    fn profile(devices: &[&str]) -> ProvisioningProfile {
+        let devices: String = devices.iter().map(|d| format!("<string>{}</string>", d)).collect();
+        let xml = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0">
+<dict>
+	<key>Name</key><string>FoodShare Development</string>
+	<key>UUID</key><string>1F2E3D4C-5B6A-4978-8695-A4B3C2D1E0F9</string>
+	<key>TeamIdentifier</key><array><string>ABCDE12345</string></array>
+	<key>ExpirationDate</key><date>2025-04-01T00:00:00Z</date>
+	<key>DeveloperCertificates</key><array><data>{}</data></array>
+	<key>ProvisionedDevices</key><array>{}</array>
+	<key>Entitlements</key>
+	<dict>
+		<key>application-identifier</key><string>ABCDE12345.com.flutterflow.*</string>
+		<key>get-task-allow</key><true/>
+		<key>com.apple.developer.associated-domains</key><array><string>*</string></array>
+	</dict>
+</dict>
+</plist>"#,
+            CERTIFICATE, devices
+        );
+        ProvisioningProfile::from_plist(&plist::parse(&xml).unwrap(), Path::new("dev.mobileprovision")).unwrap()
+    }
+
+    fn target() -> SigningTarget {
+        let mut entitlements = Map::new();
+        entitlements.insert(
+            "com.apple.developer.associated-domains".to_string(),
+            Value::from(vec!["applinks:foodshare.club"]),
+        );
+        SigningTarget {
+            name: "FoodShare".to_string(),
+            bundle_id: Some("com.flutterflow.foodshare".to_string()),
+            team: Some("ABCDE12345".to_string()),
+            entitlements,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_certificate_and_profile() {
+        let cert = certificate();
+        assert_eq!(cert.name, "Apple Development: Jane Doe (XYZ9876543)");
+        assert_eq!(cert.team.as_deref(), Some("ABCDE12345"));
+        assert_eq!(cert.expires, date("2025-05-01T00:00:00Z"));
+        assert_eq!(cert.sha1, "5525A69D0BE8F739F7E17BEC210B7EF1AD07829C");
+        assert!(cert.is_apple_signing());
+
+        let hashes = parse_identity_hashes(
+            "  1) 5525a69d0be8f739f7e17bec210b7ef1ad07829c \"Apple Development: Jane Doe (XYZ9876543)\"\n     1 valid identities found\n",
+        );
+        assert!(hashes.contains(&cert.sha1));
+
+        let profile = profile(&["00008140-001A2B3C4D5E801C"]);
+        assert_eq!(profile.kind, ProfileKind::Development);
+        assert_eq!(profile.app_id, "com.flutterflow.*");
+        assert!(profile.matches_bundle_id("com.flutterflow.foodshare"));
+        assert!(!profile.matches_bundle_id("club.foodshare.app"));
+        assert_eq!(profile.certificates, [cert.sha1]);
+    }
+
+    #[test]
+    fn test_check_target() {
+        let now = date("2025-03-15T00:00:00Z");
+        let mut cert = certificate();
+        let profiles = [profile(&["00008140-001A2B3C4D5E801C"])];
+
+        // Certificate without its private key
+        let result = check_target(&target(), &profiles, std::slice::from_ref(&cert), None, now);
+        assert_eq!(result.status, HealthStatus::Degraded);
+        assert!(result.message.unwrap().contains("certificate that isn't in the keychain"));
+
+        cert.has_private_key = true;
+        let certs = [cert];
+        let result = check_target(&target(), &profiles, &certs, Some("00008140-001A2B3C4D5E801C"), now);
+        assert_eq!(result.status, HealthStatus::Healthy);
+        assert_eq!(result.details["profile"], "FoodShare Development");
+
+        let result = check_target(&target(), &profiles, &certs, Some("00008027-000A1B2C3D4E002E"), now);
+        assert!(result.message.unwrap().contains("doesn't include device 00008027-000A1B2C3D4E002E"));
+
+        let mut manual = target();
+        manual.manual = true;
+        manual.entitlements.insert("aps-environment".to_string(), Value::from("development"));
+        let result = check_target(&manual, &profiles, &certs, None, now);
+        assert_eq!(result.status, HealthStatus::Unhealthy);
+        assert!(result.message.unwrap().contains("lacks entitlements aps-environment"));
+
+        manual.profile = Some("FoodShare AppStore".to_string());
+        let result = check_target(&manual, &profiles, &certs, None, now);
+        assert_eq!(result.message.as_deref(), Some("Profile 'FoodShare AppStore' is not installed"));
+
+        let later = date("2025-04-15T00:00:00Z");
+        let result = check_target(&target(), &profiles, &certs, None, later);
+        assert!(result.message.unwrap().contains("expired on 2025-04-01"));
+
+        assert_eq!(check_team_certificate("ABCDE12345", &certs, now).status, HealthStatus::Healthy);
+        assert_eq!(check_team_certificate("ZZZZZ99999", &certs, now).status, HealthStatus::Unhealthy);
+        assert_eq!(expiry_checks(&profiles, &certs, now).len(), 1);
+    }
+}
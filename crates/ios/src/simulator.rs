@@ -1,9 +1,11 @@
 //! iOS Simulator management
 //!
-//! Provides tools for managing iOS simulators.
+//! Provides tools for managing iOS simulators: lifecycle (boot, erase,
+//! clone), app install and launch, and capture (screenshots, video, and
+//! status bar overrides for App Store screenshots).
 
-use foodshare_core::error::Result;
-use foodshare_core::process::{run_command, CommandResult};
+use foodshare_core::error::{Error, Result};
+use foodshare_core::process::{run_command, run_command_streaming, CommandResult};
 use serde::{Deserialize, Serialize};
 
 /// Simulator device info
@@ -69,6 +71,13 @@ pub fn erase(device: &str) -> Result<CommandResult> {
     run_command("xcrun", &["simctl", "erase", device])
 }
 
+/// Clone a simulator, returning the result whose stdout is the new UDID
+///
+/// The source simulator must be shut down.
+pub fn clone(device: &str, new_name: &str) -> Result<CommandResult> {
+    run_command("xcrun", &["simctl", "clone", device, new_name])
+}
+
 /// Install an app on a simulator
 pub fn install_app(device: &str, app_path: &str) -> Result<CommandResult> {
     run_command("xcrun", &["simctl", "install", device, app_path])
@@ -87,6 +96,109 @@ pub fn screenshot(device: &str, output_path: &str) -> Result<CommandResult> {
     )
 }
 
+/// Record a video of the simulator screen until interrupted (Ctrl+C)
+///
+/// Returns the exit code of `simctl`, which finishes writing the file when
+/// it receives the interrupt.
+pub fn record_video(device: &str, output_path: &str) -> Result<i32> {
+    run_command_streaming(
+        "xcrun",
+        &["simctl", "io", device, "recordVideo", "--codec", "h264", "--force", output_path],
+    )
+}
+
+/// Status bar values to show instead of the real ones
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatusBarOverride {
+    /// Time text (`9:41`)
+    pub time: Option<String>,
+    /// Battery level, 0-100
+    pub battery_level: Option<u8>,
+    /// Battery state: `charged`, `charging` or `discharging`
+    pub battery_state: Option<String>,
+    /// Wi-Fi bars, 0-3
+    pub wifi_bars: Option<u8>,
+    /// Cellular bars, 0-4
+    pub cellular_bars: Option<u8>,
+    /// Carrier name; empty hides it
+    pub operator_name: Option<String>,
+}
+
+impl StatusBarOverride {
+    /// The status bar Apple uses in marketing screenshots: 9:41, full
+    /// battery and signal, no carrier
+    pub fn app_store() -> Self {
+        Self {
+            time: Some("9:41".to_string()),
+            battery_level: Some(100),
+            battery_state: Some("charged".to_string()),
+            wifi_bars: Some(3),
+            cellular_bars: Some(4),
+            operator_name: Some(String::new()),
+        }
+    }
+
+    /// `simctl status_bar <device> override` arguments
+    pub fn args(&self) -> Result<Vec<String>> {
+        let check = |name: &str, value: Option<u8>, max: u8| match value {
+            Some(v) if v > max => Err(Error::validation(format!("{} must be between 0 and {}", name, max))),
+            _ => Ok(()),
+        };
+        check("Battery level", self.battery_level, 100)?;
+        check("Wi-Fi bars", self.wifi_bars, 3)?;
+        check("Cellular bars", self.cellular_bars, 4)?;
+        if let Some(ref state) = self.battery_state {
+            if !["charged", "charging", "discharging"].contains(&state.as_str()) {
+                return Err(Error::validation(format!(
+                    "Unknown battery state '{}' (expected charged, charging or discharging)",
+                    state
+                )));
+            }
+        }
+
+        let mut args = Vec::new();
+        let mut push = |flag: &str, value: String| {
+            args.push(flag.to_string());
+            args.push(value);
+        };
+        if let Some(ref time) = self.time {
+            push("--time", time.clone());
+        }
+        if let Some(level) = self.battery_level {
+            push("--batteryLevel", level.to_string());
+        }
+        if let Some(ref state) = self.battery_state {
+            push("--batteryState", state.clone());
+        }
+        if let Some(bars) = self.wifi_bars {
+            push("--wifiMode", "active".to_string());
+            push("--wifiBars", bars.to_string());
+            push("--dataNetwork", "wifi".to_string());
+        }
+        if let Some(bars) = self.cellular_bars {
+            push("--cellularMode", "active".to_string());
+            push("--cellularBars", bars.to_string());
+        }
+        if let Some(ref name) = self.operator_name {
+            push("--operatorName", name.clone());
+        }
+        Ok(args)
+    }
+}
+
+/// Override the status bar of a booted simulator
+pub fn override_status_bar(device: &str, status_bar: &StatusBarOverride) -> Result<CommandResult> {
+    let overrides = status_bar.args()?;
+    let mut args = vec!["simctl", "status_bar", device, "override"];
+    args.extend(overrides.iter().map(String::as_str));
+    run_command("xcrun", &args)
+}
+
+/// Restore the real status bar
+pub fn clear_status_bar(device: &str) -> Result<CommandResult> {
+    run_command("xcrun", &["simctl", "status_bar", device, "clear"])
+}
+
 /// Open Simulator app
 pub fn open_simulator() -> Result<CommandResult> {
     run_command("open", &["-a", "Simulator"])
@@ -116,4 +228,26 @@ mod tests {
         };
         assert_eq!(device.name, "iPhone 15 Pro");
     }
+
+    #[test]
+    fn test_status_bar_override_args() {
+        let args = StatusBarOverride::app_store().args().unwrap();
+        assert_eq!(
+            args.join(" "),
+            "--time 9:41 --batteryLevel 100 --batteryState charged --wifiMode active --wifiBars 3 \
+             --dataNetwork wifi --cellularMode active --cellularBars 4 --operatorName "
+        );
+
+        let time_only = StatusBarOverride {
+            time: Some("10:00".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(time_only.args().unwrap(), ["--time", "10:00"]);
+
+        let invalid = StatusBarOverride {
+            battery_level: Some(101),
+            ..Default::default()
+        };
+        assert!(invalid.args().is_err());
+    }
 }
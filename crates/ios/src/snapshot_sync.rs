@@ -0,0 +1,286 @@
+//! Remote backup of code protection snapshots
+//!
+//! Pushes snapshots to a Supabase Storage bucket and pulls them back on
+//! another machine. Everything leaves the machine encrypted with a key
+//! derived from `FOODSHARE_SNAPSHOT_KEY`; blob object names are keyed
+//! hashes, so the bucket reveals neither file contents nor file hashes.
+//!
+//! Remote layout under `<bucket>/<prefix>/`:
+//!
+//! - `index` - snapshot list and uploaded blob hashes
+//! - `snapshots/<id>` - snapshot metadata
+//! - `objects/<keyed hash>` - zstd-compressed file contents
+
+use crate::code_protection::{Snapshot, SnapshotIndexEntry, SnapshotManager};
+use foodshare_api_client::endpoints::StorageApi;
+use foodshare_api_client::{ApiError, FoodshareClient};
+use foodshare_core::error::{Error, Result};
+use foodshare_core::process::run_command_in_dir;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Environment variable holding the snapshot encryption passphrase
+pub const PASSPHRASE_ENV: &str = "FOODSHARE_SNAPSHOT_KEY";
+
+/// Default Storage bucket for snapshots
+pub const DEFAULT_BUCKET: &str = "code-snapshots";
+
+const CONTENT_TYPE: &str = "application/octet-stream";
+
+/// Snapshot list kept alongside the remote snapshots
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RemoteIndex {
+    snapshots: Vec<SnapshotIndexEntry>,
+    /// Content hashes already uploaded
+    blobs: BTreeSet<String>,
+}
+
+/// Result of a push or pull
+#[derive(Debug, Default)]
+pub struct SyncResult {
+    /// Snapshots transferred
+    pub snapshots: Vec<String>,
+    /// Blobs transferred
+    pub blobs: usize,
+    /// Encrypted bytes transferred
+    pub bytes: u64,
+}
+
+/// Encrypted snapshot storage in a Supabase Storage bucket
+pub struct SnapshotRemote {
+    storage: StorageApi,
+    bucket: String,
+    prefix: String,
+    key: [u8; foodshare_crypto::KEY_LEN],
+    runtime: tokio::runtime::Runtime,
+}
+
+impl SnapshotRemote {
+    /// Connect with the API client's environment configuration
+    ///
+    /// The passphrase comes from [`PASSPHRASE_ENV`]. The same passphrase and
+    /// prefix must be used on every machine.
+    pub fn from_env(bucket: &str, prefix: &str) -> Result<Self> {
+        let passphrase = std::env::var(PASSPHRASE_ENV).map_err(|_| {
+            Error::config(format!("Set {} to the snapshot encryption passphrase", PASSPHRASE_ENV))
+        })?;
+        let client = FoodshareClient::new().map_err(storage_error)?;
+        Self::new(client, bucket, prefix, &passphrase)
+    }
+
+    /// Connect with a configured client
+    pub fn new(client: FoodshareClient, bucket: &str, prefix: &str, passphrase: &str) -> Result<Self> {
+        if passphrase.is_empty() {
+            return Err(Error::config("Snapshot encryption passphrase is empty"));
+        }
+        let prefix = prefix.trim_matches('/').to_string();
+        let salt = format!("foodshare-snapshots:{}", prefix);
+        Ok(Self {
+            storage: client.storage(),
+            bucket: bucket.to_string(),
+            key: foodshare_crypto::derive_key(passphrase.as_bytes(), salt.as_bytes()),
+            prefix,
+            runtime: tokio::runtime::Runtime::new()?,
+        })
+    }
+
+    /// Object path prefix inside the bucket
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Upload the newest `limit` local snapshots the remote doesn't have
+    pub fn push(&self, manager: &SnapshotManager, limit: usize) -> Result<SyncResult> {
+        let mut index = self.load_index()?.unwrap_or_default();
+        let remote_ids: BTreeSet<String> = index.snapshots.iter().map(|s| s.id.clone()).collect();
+        let mut result = SyncResult::default();
+
+        for entry in manager.list_snapshots()?.into_iter().take(limit) {
+            if remote_ids.contains(&entry.id) {
+                continue;
+            }
+            let Some(snapshot) = manager.get_snapshot(&entry.id)? else {
+                continue;
+            };
+
+            for file in &snapshot.files {
+                if index.blobs.contains(&file.hash) {
+                    continue;
+                }
+                let content = manager
+                    .read_blob(&file.hash)?
+                    .ok_or_else(|| Error::file_not_found(&file.path))?;
+                let compressed = zstd::encode_all(content.as_slice(), 3)?;
+                result.bytes += self.put(&self.object_path(&file.hash), &compressed)?;
+                index.blobs.insert(file.hash.clone());
+                result.blobs += 1;
+            }
+
+            result.bytes += self.put(&self.snapshot_path(&snapshot.id), &serde_json::to_vec(&snapshot)?)?;
+            index.snapshots.push(entry);
+            result.snapshots.push(snapshot.id);
+        }
+
+        if !result.snapshots.is_empty() {
+            self.put(&self.index_path(), &serde_json::to_vec(&index)?)?;
+        }
+        Ok(result)
+    }
+
+    /// Download the newest `limit` remote snapshots missing locally
+    pub fn pull(&self, manager: &SnapshotManager, limit: usize) -> Result<SyncResult> {
+        let mut index = self.load_index()?.ok_or_else(|| {
+            Error::validation(format!("No snapshots in {}/{}", self.bucket, self.prefix))
+        })?;
+        index.snapshots.sort_by_key(|s| std::cmp::Reverse(s.timestamp));
+        let mut result = SyncResult::default();
+
+        for entry in index.snapshots.iter().take(limit) {
+            if manager.get_snapshot(&entry.id)?.is_some() {
+                continue;
+            }
+            let data = self.get(&self.snapshot_path(&entry.id))?;
+            result.bytes += data.len() as u64;
+            let snapshot: Snapshot = serde_json::from_slice(&self.open(&data)?)?;
+
+            // Held until the snapshot is saved, so a local gc can't collect its blobs
+            let _lock = manager.lock_store()?;
+            for file in &snapshot.files {
+                if manager.has_blob(&file.hash) {
+                    continue;
+                }
+                let data = self.get(&self.object_path(&file.hash))?;
+                result.bytes += data.len() as u64;
+                let content = zstd::decode_all(self.open(&data)?.as_slice())?;
+                if manager.write_blob(&content)? != file.hash {
+                    return Err(Error::security(format!(
+                        "Content of {} in snapshot {} does not match its hash",
+                        file.path.display(),
+                        snapshot.id
+                    )));
+                }
+                result.blobs += 1;
+            }
+
+            manager.save_snapshot(&snapshot)?;
+            result.snapshots.push(snapshot.id);
+        }
+        Ok(result)
+    }
+
+    fn load_index(&self) -> Result<Option<RemoteIndex>> {
+        match self.runtime.block_on(self.storage.download(&self.bucket, &self.index_path(), |_| {})) {
+            Ok(data) => Ok(Some(serde_json::from_slice(&self.open(&data)?)?)),
+            Err(e) if e.is_not_found() => Ok(None),
+            Err(e) => Err(storage_error(e)),
+        }
+    }
+
+    /// Encrypt and upload, returning the bytes sent
+    fn put(&self, path: &str, plaintext: &[u8]) -> Result<u64> {
+        let sealed = self.seal(plaintext)?;
+        let len = sealed.len() as u64;
+        self.runtime
+            .block_on(self.storage.upsert(&self.bucket, path, CONTENT_TYPE, sealed, |_| {}))
+            .map_err(storage_error)?;
+        Ok(len)
+    }
+
+    fn get(&self, path: &str) -> Result<Vec<u8>> {
+        self.runtime
+            .block_on(self.storage.download(&self.bucket, path, |_| {}))
+            .map_err(storage_error)
+    }
+
+    fn seal(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        foodshare_crypto::encrypt(&self.key, plaintext).map_err(|e| Error::security(e.to_string()))
+    }
+
+    fn open(&self, data: &[u8]) -> Result<Vec<u8>> {
+        foodshare_crypto::decrypt(&self.key, data).map_err(|_| {
+            Error::security(format!(
+                "Cannot decrypt remote snapshot data: wrong {} or corrupted object",
+                PASSPHRASE_ENV
+            ))
+        })
+    }
+
+    fn index_path(&self) -> String {
+        format!("{}/index", self.prefix)
+    }
+
+    fn snapshot_path(&self, id: &str) -> String {
+        format!("{}/snapshots/{}", self.prefix, id)
+    }
+
+    /// Keyed hash, so object names can't be matched against known contents
+    fn object_path(&self, hash: &str) -> String {
+        format!(
+            "{}/objects/{}",
+            self.prefix,
+            foodshare_crypto::hmac_sha256(&self.key, hash.as_bytes())
+        )
+    }
+}
+
+/// Default remote prefix: `<git user.email>/<repository directory name>`
+pub fn default_prefix(manager: &SnapshotManager) -> String {
+    let workdir = manager.workdir();
+    let user = run_command_in_dir("git", &["config", "user.email"], workdir)
+        .ok()
+        .map(|r| r.stdout.trim().to_string())
+        .filter(|email| !email.is_empty())
+        .or_else(|| std::env::var("USER").ok())
+        .unwrap_or_else(|| "unknown".to_string());
+    let repo = workdir
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "repo".to_string());
+    format!("{}/{}", sanitize_path_segment(&user), sanitize_path_segment(&repo))
+}
+
+/// Replace characters Storage object keys don't allow
+fn sanitize_path_segment(segment: &str) -> String {
+    segment
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || "@._-".contains(c) { c } else { '-' })
+        .collect()
+}
+
+fn storage_error(e: ApiError) -> Error {
+    Error::io(format!("Snapshot storage: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foodshare_api_client::ClientConfig;
+
+    fn remote_with(passphrase: &str) -> SnapshotRemote {
+        let client = FoodshareClient::with_config(ClientConfig::development()).unwrap();
+        SnapshotRemote::new(client, DEFAULT_BUCKET, "/dev@example.com/app/", passphrase).unwrap()
+    }
+
+    #[test]
+    fn test_seal_and_object_names() {
+        let remote = remote_with("correct horse");
+        assert_eq!(remote.prefix(), "dev@example.com/app");
+        assert_eq!(remote.index_path(), "dev@example.com/app/index");
+
+        let sealed = remote.seal(b"struct Listing {}").unwrap();
+        assert_eq!(remote.open(&sealed).unwrap(), b"struct Listing {}");
+        assert!(remote_with("battery staple").open(&sealed).is_err());
+
+        let hash = "ab".repeat(32);
+        let object = remote.object_path(&hash);
+        assert!(object.starts_with("dev@example.com/app/objects/"));
+        assert!(!object.contains(&hash));
+        assert_eq!(object, remote.object_path(&hash));
+    }
+
+    #[test]
+    fn test_sanitize_path_segment() {
+        assert_eq!(sanitize_path_segment("jane.doe+ios@example.com"), "jane.doe-ios@example.com");
+        assert_eq!(sanitize_path_segment("Food Share"), "Food-Share");
+    }
+}
@@ -0,0 +1,411 @@
+//! Swift Package Manager dependency audit
+//!
+//! Reads every `Package.resolved` in the repository (packages and Xcode
+//! projects alike) and reports:
+//!
+//! - packages resolved to different versions in different places
+//! - packages fetched from hosts outside the allowlist
+//! - packages tracking a branch instead of a version
+//! - packages with a newer release tag (queried with `git ls-remote`)
+
+use foodshare_cli::textln;
+use foodshare_core::error::{Error, Result};
+use foodshare_core::process::run_command;
+use owo_colors::OwoColorize;
+use rayon::prelude::*;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Directories never searched for lockfiles (checkouts and build output)
+const SKIP_DIRS: &[&str] = &[".git", ".build", "build", "DerivedData", "SourcePackages", "Pods", "node_modules"];
+
+/// A package pinned in a `Package.resolved`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolvedPin {
+    /// Package identity, lowercase (`supabase-swift`)
+    pub identity: String,
+    /// Repository URL
+    pub location: String,
+    /// Resolved version, if pinned to one
+    pub version: Option<String>,
+    /// Branch, if tracking one
+    pub branch: Option<String>,
+    /// Resolved commit
+    pub revision: String,
+    /// Lockfile the pin was read from
+    pub file: PathBuf,
+}
+
+impl ResolvedPin {
+    /// Version, or the short revision for branch and commit pins
+    pub fn resolved(&self) -> String {
+        self.version
+            .clone()
+            .unwrap_or_else(|| self.revision.chars().take(8).collect())
+    }
+
+    /// Host of the repository URL (`github.com`)
+    pub fn host(&self) -> Option<String> {
+        repository_host(&self.location)
+    }
+}
+
+/// A package with a newer release than the pinned one
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutdatedPackage {
+    /// Package identity
+    pub identity: String,
+    /// Pinned version
+    pub current: String,
+    /// Newest release tag
+    pub latest: String,
+}
+
+/// A package resolved to different versions across lockfiles
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DuplicatePackage {
+    /// Package identity
+    pub identity: String,
+    /// Each resolved version and the lockfiles using it
+    pub versions: BTreeMap<String, Vec<PathBuf>>,
+}
+
+/// Result of [`audit`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DependencyAudit {
+    /// Lockfiles read
+    pub files: Vec<PathBuf>,
+    /// Every pin of every lockfile
+    pub pins: Vec<ResolvedPin>,
+    /// Packages with different versions across lockfiles
+    pub duplicates: Vec<DuplicatePackage>,
+    /// Pins from hosts outside the allowlist
+    pub disallowed: Vec<ResolvedPin>,
+    /// Pins tracking a branch or bare commit
+    pub unpinned: Vec<ResolvedPin>,
+    /// Packages with newer releases
+    pub outdated: Vec<OutdatedPackage>,
+}
+
+impl DependencyAudit {
+    /// Whether the audit found problems that should fail CI
+    ///
+    /// Duplicates and disallowed hosts fail; unpinned and outdated packages
+    /// are warnings.
+    pub fn has_failures(&self) -> bool {
+        !self.duplicates.is_empty() || !self.disallowed.is_empty()
+    }
+
+    /// Print the findings
+    pub fn print(&self, root: &Path) {
+        let display = |file: &Path| file.strip_prefix(root).unwrap_or(file).display().to_string();
+        let packages: BTreeSet<&str> = self.pins.iter().map(|p| p.identity.as_str()).collect();
+        textln!(
+            "{}",
+            format!("{} packages in {} lockfiles", packages.len(), self.files.len()).bold()
+        );
+
+        if !self.duplicates.is_empty() {
+            textln!();
+            textln!("{}", "Conflicting versions".red().bold());
+            for duplicate in &self.duplicates {
+                textln!("  {}", duplicate.identity);
+                for (version, files) in &duplicate.versions {
+                    let files: Vec<String> = files.iter().map(|f| display(f)).collect();
+                    textln!("    {:<12} {}", version, files.join(", ").dimmed());
+                }
+            }
+        }
+
+        if !self.disallowed.is_empty() {
+            textln!();
+            textln!("{}", "Fetched from non-allowlisted hosts".red().bold());
+            for pin in &self.disallowed {
+                textln!("  {} {} {}", pin.identity, pin.location, display(&pin.file).dimmed());
+            }
+        }
+
+        if !self.unpinned.is_empty() {
+            textln!();
+            textln!("{}", "Not pinned to a version".yellow().bold());
+            for pin in &self.unpinned {
+                let tracking = match &pin.branch {
+                    Some(branch) => format!("branch {}", branch),
+                    None => format!("revision {}", pin.resolved()),
+                };
+                textln!("  {} ({}) {}", pin.identity, tracking, display(&pin.file).dimmed());
+            }
+        }
+
+        if !self.outdated.is_empty() {
+            textln!();
+            textln!("{}", "Outdated".yellow().bold());
+            for package in &self.outdated {
+                textln!("  {:<32} {} -> {}", package.identity, package.current, package.latest.green());
+            }
+        }
+    }
+}
+
+/// Audit every `Package.resolved` under `root`
+///
+/// With `check_outdated`, each package's repository is queried for release
+/// tags, which needs network access.
+pub fn audit(root: &Path, allowed_hosts: &[String], check_outdated: bool) -> Result<DependencyAudit> {
+    let files = find_resolved_files(root);
+    let mut pins = Vec::new();
+    for file in &files {
+        pins.extend(parse_package_resolved(&std::fs::read_to_string(file)?, file)?);
+    }
+
+    let mut audit = DependencyAudit {
+        duplicates: find_duplicates(&pins),
+        disallowed: pins
+            .iter()
+            .filter(|pin| !host_allowed(pin.host().as_deref(), allowed_hosts))
+            .cloned()
+            .collect(),
+        unpinned: pins.iter().filter(|pin| pin.version.is_none()).cloned().collect(),
+        files,
+        ..Default::default()
+    };
+
+    if check_outdated {
+        audit.outdated = find_outdated(&pins);
+    }
+    audit.pins = pins;
+    Ok(audit)
+}
+
+/// `Package.resolved` files under `root`, including Xcode's
+/// `*.xcodeproj/project.xcworkspace/xcshareddata/swiftpm/Package.resolved`
+pub fn find_resolved_files(root: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|entry| {
+            !(entry.file_type().is_dir() && SKIP_DIRS.contains(&entry.file_name().to_string_lossy().as_ref()))
+        })
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file() && entry.file_name() == "Package.resolved")
+        .map(|entry| entry.into_path())
+        .collect();
+    files.sort();
+    files
+}
+
+/// Parse a `Package.resolved` (format version 1, 2 or 3)
+pub fn parse_package_resolved(content: &str, file: &Path) -> Result<Vec<ResolvedPin>> {
+    let json: serde_json::Value = serde_json::from_str(content)?;
+    let (pins, v1) = match json["version"].as_u64() {
+        Some(1) => (&json["object"]["pins"], true),
+        Some(2 | 3) => (&json["pins"], false),
+        other => {
+            return Err(Error::validation(format!(
+                "{}: unsupported Package.resolved version {:?}",
+                file.display(),
+                other
+            )));
+        }
+    };
+
+    Ok(pins
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|pin| {
+            let state = &pin["state"];
+            let (identity, location) = if v1 {
+                (pin["package"].as_str()?, pin["repositoryURL"].as_str()?)
+            } else {
+                (pin["identity"].as_str()?, pin["location"].as_str()?)
+            };
+            Some(ResolvedPin {
+                identity: identity.to_lowercase(),
+                location: location.to_string(),
+                version: state["version"].as_str().map(str::to_string),
+                branch: state["branch"].as_str().map(str::to_string),
+                revision: state["revision"].as_str().unwrap_or_default().to_string(),
+                file: file.to_path_buf(),
+            })
+        })
+        .collect())
+}
+
+fn find_duplicates(pins: &[ResolvedPin]) -> Vec<DuplicatePackage> {
+    let mut by_identity: BTreeMap<&str, BTreeMap<String, Vec<PathBuf>>> = BTreeMap::new();
+    for pin in pins {
+        by_identity
+            .entry(&pin.identity)
+            .or_default()
+            .entry(pin.resolved())
+            .or_default()
+            .push(pin.file.clone());
+    }
+    by_identity
+        .into_iter()
+        .filter(|(_, versions)| versions.len() > 1)
+        .map(|(identity, versions)| DuplicatePackage {
+            identity: identity.to_string(),
+            versions,
+        })
+        .collect()
+}
+
+fn find_outdated(pins: &[ResolvedPin]) -> Vec<OutdatedPackage> {
+    // One query per package, against the highest version pinned anywhere
+    let mut newest: BTreeMap<&str, (&ResolvedPin, Version)> = BTreeMap::new();
+    for pin in pins {
+        let Some(version) = pin.version.as_deref().and_then(parse_version) else {
+            continue;
+        };
+        let entry = newest.entry(&pin.identity).or_insert((pin, version.clone()));
+        if version > entry.1 {
+            *entry = (pin, version);
+        }
+    }
+
+    let mut outdated: Vec<OutdatedPackage> = newest
+        .into_par_iter()
+        .filter_map(|(identity, (pin, current))| {
+            let latest = latest_release(&pin.location).ok()??;
+            (latest > current).then(|| OutdatedPackage {
+                identity: identity.to_string(),
+                current: current.to_string(),
+                latest: latest.to_string(),
+            })
+        })
+        .collect();
+    outdated.sort_by(|a, b| a.identity.cmp(&b.identity));
+    outdated
+}
+
+/// Newest stable release tag of a repository
+pub fn latest_release(url: &str) -> Result<Option<Version>> {
+    let result = run_command("git", &["ls-remote", "--tags", "--refs", url])?;
+    if !result.success {
+        return Err(Error::process(format!(
+            "git ls-remote {} failed: {}",
+            url,
+            result.stderr.trim()
+        )));
+    }
+    Ok(latest_tag(&result.stdout))
+}
+
+/// Highest stable semantic version among `git ls-remote --tags` lines
+fn latest_tag(output: &str) -> Option<Version> {
+    output
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(1)?.strip_prefix("refs/tags/"))
+        .filter_map(parse_version)
+        .filter(|version| version.pre.is_empty())
+        .max()
+}
+
+/// Parse `1.2.3`, `v1.2.3` or `1.2`
+fn parse_version(text: &str) -> Option<Version> {
+    let text = text.strip_prefix('v').unwrap_or(text);
+    Version::parse(text).ok().or_else(|| {
+        let parts = text.split('.').count();
+        (parts == 2).then(|| Version::parse(&format!("{}.0", text)).ok())?
+    })
+}
+
+/// Host of a git URL: `https://github.com/x/y`, `git@github.com:x/y.git`, `ssh://git@host/x`
+fn repository_host(url: &str) -> Option<String> {
+    let rest = match url.split_once("://") {
+        Some((_, rest)) => rest,
+        None => url.split_once(':').map(|(host, _)| host)?,
+    };
+    let authority = rest.split('/').next()?;
+    let host = authority.rsplit('@').next()?;
+    let host = host.split(':').next()?;
+    (!host.is_empty()).then(|| host.to_lowercase())
+}
+
+/// Whether `host` is allowlisted, directly or as a subdomain
+fn host_allowed(host: Option<&str>, allowed_hosts: &[String]) -> bool {
+    // Local path dependencies have no host
+    let Some(host) = host else {
+        return true;
+    };
+    allowed_hosts.iter().any(|allowed| {
+        let allowed = allowed.to_lowercase();
+        host == allowed || host.ends_with(&format!(".{}", allowed))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_package_resolved_versions() {
+        let v1 = r#"{"object": {"pins": [
+  {"package": "Kingfisher", "repositoryURL": "https://github.com/onevcat/Kingfisher.git",
+   "state": {"branch": null, "revision": "3ec0ab0bca4feb56e8b33e289c9496e89059dd08", "version": "7.10.0"}}
+]}, "version": 1}"#;
+        let pins = parse_package_resolved(v1, Path::new("Package.resolved")).unwrap();
+        assert_eq!(pins[0].identity, "kingfisher");
+        assert_eq!(pins[0].version.as_deref(), Some("7.10.0"));
+
+        let v2 = r#"{"pins": [
+  {"identity": "supabase-swift", "kind": "remoteSourceControl", "location": "https://github.com/supabase/supabase-swift",
+   "state": {"revision": "8f5b94f6a7a5e5e0d9e7a1b0c2d3e4f5a6b7c8d9", "version": "2.5.1"}},
+  {"identity": "internal-kit", "kind": "remoteSourceControl", "location": "git@git.example.com:mobile/internal-kit.git",
+   "state": {"branch": "main", "revision": "0123456789abcdef0123456789abcdef01234567"}}
+], "version": 2}"#;
+        let pins = parse_package_resolved(v2, Path::new("Package.resolved")).unwrap();
+        assert_eq!(pins.len(), 2);
+        assert_eq!(pins[1].branch.as_deref(), Some("main"));
+        assert_eq!(pins[1].resolved(), "01234567");
+        assert_eq!(pins[1].host().as_deref(), Some("git.example.com"));
+
+        assert!(parse_package_resolved(r#"{"version": 9}"#, Path::new("x")).is_err());
+    }
+
+    #[test]
+    fn test_audit() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |path: &str, version: &str, location: &str| {
+            let path = dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            let content = format!(
+                r#"{{"pins": [{{"identity": "supabase-swift", "location": "{}", "state": {{"revision": "abc", "version": "{}"}}}}], "version": 3}}"#,
+                location, version
+            );
+            std::fs::write(path, content).unwrap();
+        };
+        write("FoodShare.xcodeproj/project.xcworkspace/xcshareddata/swiftpm/Package.resolved", "2.5.1", "https://github.com/supabase/supabase-swift");
+        write("Packages/Core/Package.resolved", "2.4.0", "https://github.com/supabase/supabase-swift");
+        write("Packages/Feed/Package.resolved", "2.5.1", "https://mirror.example.com/supabase-swift");
+        write("Packages/Feed/.build/checkouts/x/Package.resolved", "1.0.0", "https://github.com/x/x");
+
+        let audit = audit(dir.path(), &["github.com".to_string()], false).unwrap();
+        assert_eq!(audit.files.len(), 3);
+        assert_eq!(audit.duplicates.len(), 1);
+        assert_eq!(audit.duplicates[0].versions.keys().collect::<Vec<_>>(), ["2.4.0", "2.5.1"]);
+        assert_eq!(audit.disallowed.len(), 1);
+        assert!(audit.has_failures());
+    }
+
+    #[test]
+    fn test_latest_tag_and_hosts() {
+        let output = "\
+a1\trefs/tags/2.4.0
+b2\trefs/tags/v2.10.1
+c3\trefs/tags/3.0.0-beta.1
+d4\trefs/tags/nightly
+e5\trefs/tags/2.9
+";
+        assert_eq!(latest_tag(output), Some(Version::new(2, 10, 1)));
+
+        assert_eq!(repository_host("https://github.com/a/b.git").as_deref(), Some("github.com"));
+        assert_eq!(repository_host("ssh://git@GitHub.com:22/a/b").as_deref(), Some("github.com"));
+        assert!(host_allowed(Some("codeload.github.com"), &["github.com".to_string()]));
+        assert!(!host_allowed(Some("evilgithub.com"), &["github.com".to_string()]));
+    }
+}
@@ -0,0 +1,203 @@
+//! SwiftLint/SwiftFormat configuration drift check
+//!
+//! Compares `.swiftlint.yml` and `.swiftformat` at the repository root
+//! against the canonical copies shipped with this crate (or a central
+//! template directory) so every developer lints and formats with the same
+//! rules. Line endings and trailing whitespace are ignored.
+
+use foodshare_cli::output::diff::{print_unified, Diff, DiffStyle};
+use foodshare_cli::textln;
+use foodshare_core::error::{Error, Result};
+use owo_colors::OwoColorize;
+use std::path::{Path, PathBuf};
+
+/// A style configuration file kept in sync with a canonical copy
+#[derive(Debug, Clone, Copy)]
+pub struct StyleFile {
+    /// File name at the repository root
+    pub name: &'static str,
+    /// Canonical contents shipped with the tools
+    pub canonical: &'static str,
+}
+
+/// Configuration files checked, with their built-in canonical contents
+pub const STYLE_FILES: &[StyleFile] = &[
+    StyleFile {
+        name: ".swiftlint.yml",
+        canonical: include_str!("../templates/swiftlint.yml"),
+    },
+    StyleFile {
+        name: ".swiftformat",
+        canonical: include_str!("../templates/swiftformat"),
+    },
+];
+
+/// How a repository file compares to its canonical copy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StyleStatus {
+    /// Same rules
+    InSync,
+    /// Rules differ
+    Drifted,
+    /// File missing from the repository
+    Missing,
+}
+
+/// Comparison of one configuration file
+#[derive(Debug, Clone)]
+pub struct StyleCheck {
+    /// Path in the repository
+    pub path: PathBuf,
+    /// Comparison result
+    pub status: StyleStatus,
+    /// Unified diff from the repository file to the canonical copy
+    pub diff: String,
+    canonical: String,
+}
+
+impl StyleCheck {
+    /// Whether the repository file needs syncing
+    pub fn needs_sync(&self) -> bool {
+        self.status != StyleStatus::InSync
+    }
+}
+
+/// Compare the repository's style files with the canonical copies
+///
+/// With `template_dir`, canonical copies are read from files of the same
+/// name in that directory instead of the built-in ones.
+pub fn check(root: &Path, template_dir: Option<&Path>) -> Result<Vec<StyleCheck>> {
+    STYLE_FILES
+        .iter()
+        .map(|file| {
+            let canonical = match template_dir {
+                Some(dir) => {
+                    let path = dir.join(file.name);
+                    if !path.exists() {
+                        return Err(Error::file_not_found(&path));
+                    }
+                    std::fs::read_to_string(&path)?
+                }
+                None => file.canonical.to_string(),
+            };
+            let path = root.join(file.name);
+            let current = if path.exists() { Some(std::fs::read_to_string(&path)?) } else { None };
+            Ok(compare(path, current.as_deref(), canonical, file.name))
+        })
+        .collect()
+}
+
+/// Overwrite drifted or missing files with their canonical copies
+///
+/// Returns the paths written.
+pub fn sync(checks: &[StyleCheck]) -> Result<Vec<PathBuf>> {
+    let mut written = Vec::new();
+    for check in checks.iter().filter(|c| c.needs_sync()) {
+        std::fs::write(&check.path, &check.canonical)?;
+        written.push(check.path.clone());
+    }
+    Ok(written)
+}
+
+/// Print each file's status, with diffs for drifted files
+pub fn print_checks(checks: &[StyleCheck], root: &Path) {
+    for check in checks {
+        let name = check.path.strip_prefix(root).unwrap_or(&check.path).display();
+        match check.status {
+            StyleStatus::InSync => textln!("  {} {}", "✓".green(), name),
+            StyleStatus::Missing => textln!("  {} {} {}", "✗".red(), name, "(missing)".dimmed()),
+            StyleStatus::Drifted => {
+                textln!("  {} {} {}", "✗".red(), name, "(differs from canonical)".dimmed());
+                let style = DiffStyle {
+                    indent: 4,
+                    ..DiffStyle::default()
+                };
+                print_unified(&check.diff, &style);
+            }
+        }
+    }
+}
+
+fn compare(path: PathBuf, current: Option<&str>, canonical: String, name: &str) -> StyleCheck {
+    let Some(current) = current else {
+        return StyleCheck {
+            path,
+            status: StyleStatus::Missing,
+            diff: String::new(),
+            canonical,
+        };
+    };
+
+    let (current, expected) = (normalize(current), normalize(&canonical));
+    if current == expected {
+        return StyleCheck {
+            path,
+            status: StyleStatus::InSync,
+            diff: String::new(),
+            canonical,
+        };
+    }
+
+    let diff = Diff::new(&current, &expected)
+        .context(2)
+        .header(name, &format!("{} (canonical)", name))
+        .unified();
+    StyleCheck {
+        path,
+        status: StyleStatus::Drifted,
+        diff,
+        canonical,
+    }
+}
+
+/// Drop line-ending, trailing-whitespace and trailing-blank-line differences
+fn normalize(content: &str) -> String {
+    let mut lines: Vec<&str> = content.lines().map(str::trim_end).collect();
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    let mut normalized = lines.join("\n");
+    normalized.push('\n');
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_check_and_sync() {
+        let repo = TempDir::new().unwrap();
+        let swiftlint = STYLE_FILES[0];
+        std::fs::write(
+            repo.path().join(swiftlint.name),
+            swiftlint.canonical.replace('\n', "  \r\n"),
+        )
+        .unwrap();
+        std::fs::write(repo.path().join(".swiftformat"), "--indent 2\n").unwrap();
+
+        let checks = check(repo.path(), None).unwrap();
+        assert_eq!(checks[0].status, StyleStatus::InSync);
+        assert_eq!(checks[1].status, StyleStatus::Drifted);
+        assert!(checks[1].diff.contains("---indent 2"));
+
+        assert_eq!(sync(&checks).unwrap(), vec![repo.path().join(".swiftformat")]);
+        assert!(check(repo.path(), None).unwrap().iter().all(|c| !c.needs_sync()));
+    }
+
+    #[test]
+    fn test_template_dir() {
+        let (repo, templates) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        std::fs::write(templates.path().join(".swiftlint.yml"), "line_length: 100\n").unwrap();
+        assert!(check(repo.path(), Some(templates.path())).is_err());
+
+        std::fs::write(templates.path().join(".swiftformat"), "--indent 4\n").unwrap();
+        let checks = check(repo.path(), Some(templates.path())).unwrap();
+        assert!(checks.iter().all(|c| c.status == StyleStatus::Missing));
+
+        sync(&checks).unwrap();
+        let written = std::fs::read_to_string(repo.path().join(".swiftlint.yml")).unwrap();
+        assert_eq!(written, "line_length: 100\n");
+    }
+}
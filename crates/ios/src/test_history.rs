@@ -0,0 +1,233 @@
+//! Test flakiness tracking
+//!
+//! Each `foodshare-ios test` run records every test's outcome in
+//! `.foodshare-hooks/test-history.json`, keeping the last [`WINDOW`] runs per
+//! test. A run counts as flaky when the test failed and then passed on a
+//! retry, or failed between two passing runs; a test's flakiness rate is the
+//! share of its recorded runs that were flaky. Tests that fail every time
+//! are broken rather than flaky and score zero.
+
+use crate::xcresult::{TestCase, TestStatus};
+use foodshare_cli::textln;
+use foodshare_core::error::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Default history file, relative to the repository root
+pub const HISTORY_FILE: &str = ".foodshare-hooks/test-history.json";
+
+/// Runs kept per test
+pub const WINDOW: usize = 50;
+
+/// Runs needed before a test can be considered for quarantine
+pub const MIN_RUNS: usize = 5;
+
+/// Outcome of a test in one run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    /// Passed on the first attempt
+    Passed,
+    /// Failed on every attempt
+    Failed,
+    /// Failed, then passed on a retry
+    Retried,
+}
+
+impl Outcome {
+    fn of(test: &TestCase) -> Option<Self> {
+        match test.status {
+            TestStatus::Passed if test.passed_on_retry() => Some(Self::Retried),
+            TestStatus::Passed => Some(Self::Passed),
+            TestStatus::Failed => Some(Self::Failed),
+            TestStatus::Skipped | TestStatus::ExpectedFailure => None,
+        }
+    }
+
+    fn passed(self) -> bool {
+        self != Self::Failed
+    }
+}
+
+/// Flakiness of one test
+#[derive(Debug, Clone, PartialEq)]
+pub struct Flakiness {
+    /// Test identifier (`LoginTests/testLogin()`)
+    pub identifier: String,
+    /// Recorded runs
+    pub runs: usize,
+    /// Runs that failed on every attempt
+    pub failures: usize,
+    /// Runs that passed only on a retry
+    pub retried: usize,
+    /// Share of runs that were flaky, from 0 to 1
+    pub rate: f64,
+}
+
+/// Recent outcomes of every test
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TestHistory {
+    /// Outcomes per test identifier, oldest first
+    pub tests: BTreeMap<String, Vec<Outcome>>,
+}
+
+impl TestHistory {
+    /// Load the history, or start an empty one if the file doesn't exist
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// Save the history, creating parent directories
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Record the outcomes of a run; skipped tests are not recorded
+    pub fn record(&mut self, tests: &[TestCase]) {
+        for test in tests {
+            let Some(outcome) = Outcome::of(test) else {
+                continue;
+            };
+            let outcomes = self.tests.entry(test.identifier.clone()).or_default();
+            outcomes.push(outcome);
+            if outcomes.len() > WINDOW {
+                outcomes.drain(..outcomes.len() - WINDOW);
+            }
+        }
+    }
+
+    /// Flakiness of a test, if it has any recorded runs
+    pub fn flakiness(&self, identifier: &str) -> Option<Flakiness> {
+        let outcomes = self.tests.get(identifier).filter(|o| !o.is_empty())?;
+        let retried = outcomes.iter().filter(|&&o| o == Outcome::Retried).count();
+        let failures = outcomes.iter().filter(|&&o| o == Outcome::Failed).count();
+        // A failure between two passes; the latest run counts if the one before passed
+        let intermittent = (0..outcomes.len())
+            .filter(|&i| outcomes[i] == Outcome::Failed)
+            .filter(|&i| i > 0 && outcomes[i - 1].passed())
+            .filter(|&i| outcomes.get(i + 1).is_none_or(|next| next.passed()))
+            .count();
+
+        Some(Flakiness {
+            identifier: identifier.to_string(),
+            runs: outcomes.len(),
+            failures,
+            retried,
+            rate: (retried + intermittent) as f64 / outcomes.len() as f64,
+        })
+    }
+
+    /// Tests with a flakiness rate above zero, flakiest first
+    pub fn flaky_tests(&self) -> Vec<Flakiness> {
+        let mut flaky: Vec<Flakiness> = self
+            .tests
+            .keys()
+            .filter_map(|identifier| self.flakiness(identifier))
+            .filter(|f| f.rate > 0.0)
+            .collect();
+        flaky.sort_by(|a, b| b.rate.total_cmp(&a.rate).then_with(|| a.identifier.cmp(&b.identifier)));
+        flaky
+    }
+
+    /// Check if a test is flaky enough that its failures shouldn't fail a run
+    pub fn is_quarantined(&self, identifier: &str, threshold: f64) -> bool {
+        self.flakiness(identifier)
+            .is_some_and(|f| f.runs >= MIN_RUNS && f.rate >= threshold)
+    }
+}
+
+/// Print the flakiest tests as a table
+pub fn print_flaky_tests(flaky: &[Flakiness]) {
+    use owo_colors::OwoColorize;
+
+    textln!(
+        "  {:>6}  {:>5}  {:>7}  {:>6}  {}",
+        "RATE".bold(),
+        "RUNS".bold(),
+        "RETRIED".bold(),
+        "FAILED".bold(),
+        "TEST".bold()
+    );
+    for test in flaky {
+        textln!(
+            "  {:>5.1}%  {:>5}  {:>7}  {:>6}  {}",
+            test.rate * 100.0,
+            test.runs,
+            test.retried,
+            test.failures,
+            test.identifier
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(identifier: &str, status: TestStatus, attempts: &[TestStatus]) -> TestCase {
+        TestCase {
+            identifier: identifier.to_string(),
+            name: identifier.to_string(),
+            suite: "FeedTests".to_string(),
+            bundle: "FoodShareTests".to_string(),
+            status,
+            duration: None,
+            failures: Vec::new(),
+            attempts: attempts.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_flakiness() {
+        use TestStatus::{Failed, Passed, Skipped};
+
+        let mut history = TestHistory::default();
+        let runs = [
+            (Passed, &[][..], Failed, Passed),
+            (Failed, &[][..], Failed, Passed),
+            (Passed, &[][..], Failed, Passed),
+            (Passed, &[Failed, Passed][..], Failed, Passed),
+            (Passed, &[][..], Failed, Skipped),
+        ];
+        for (flaky, attempts, broken, skipped) in runs {
+            history.record(&[
+                run("flaky", flaky, attempts),
+                run("broken", broken, &[]),
+                run("sometimes-skipped", skipped, &[]),
+            ]);
+        }
+
+        let flaky = history.flakiness("flaky").unwrap();
+        assert_eq!((flaky.runs, flaky.failures, flaky.retried), (5, 1, 1));
+        assert!((flaky.rate - 0.4).abs() < 1e-9);
+        assert!(history.is_quarantined("flaky", 0.25));
+        assert!(!history.is_quarantined("flaky", 0.5));
+
+        assert_eq!(history.flakiness("broken").unwrap().rate, 0.0);
+        assert_eq!(history.tests["sometimes-skipped"].len(), 4);
+        assert_eq!(history.flaky_tests().len(), 1);
+    }
+
+    #[test]
+    fn test_history_window_and_round_trip() {
+        let mut history = TestHistory::default();
+        for _ in 0..WINDOW + 3 {
+            history.record(&[run("LoginTests/testLogin()", TestStatus::Passed, &[])]);
+        }
+        assert_eq!(history.tests["LoginTests/testLogin()"].len(), WINDOW);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested/test-history.json");
+        assert!(TestHistory::load(&path).unwrap().tests.is_empty());
+        history.save(&path).unwrap();
+        assert_eq!(TestHistory::load(&path).unwrap().tests, history.tests);
+    }
+}
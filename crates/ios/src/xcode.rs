@@ -2,8 +2,9 @@
 //!
 //! Provides tools for working with Xcode projects and workspaces.
 
-use foodshare_core::error::Result;
+use foodshare_core::error::{Error, Result};
 use foodshare_core::process::{command_exists, run_command, CommandResult};
+use std::collections::BTreeMap;
 use std::path::Path;
 
 /// Check if xcodebuild is available
@@ -23,6 +24,18 @@ pub fn build(
     configuration: &str,
     destination: &str,
     clean: bool,
+) -> Result<CommandResult> {
+    build_with_args(scheme, configuration, destination, clean, &[])
+}
+
+/// Build an Xcode project with extra `xcodebuild` arguments (options, or
+/// `NAME=value` build setting overrides)
+pub fn build_with_args(
+    scheme: &str,
+    configuration: &str,
+    destination: &str,
+    clean: bool,
+    extra_args: &[&str],
 ) -> Result<CommandResult> {
     let mut args = vec![
         "-scheme",
@@ -33,6 +46,8 @@ pub fn build(
         destination,
     ];
 
+    args.extend_from_slice(extra_args);
+
     if clean {
         args.push("clean");
     }
@@ -41,6 +56,72 @@ pub fn build(
     run_command("xcodebuild", &args)
 }
 
+/// Resolved build settings of a scheme's target for an SDK (`iphoneos`)
+pub fn build_settings(scheme: &str, configuration: &str, sdk: &str) -> Result<BTreeMap<String, String>> {
+    let targets = target_build_settings(scheme, configuration, sdk)?;
+    Ok(select_target(targets, scheme))
+}
+
+/// Resolved build settings of every target a scheme builds, in build order
+pub fn target_build_settings(
+    scheme: &str,
+    configuration: &str,
+    sdk: &str,
+) -> Result<Vec<(String, BTreeMap<String, String>)>> {
+    let result = run_command(
+        "xcodebuild",
+        &[
+            "-scheme",
+            scheme,
+            "-configuration",
+            configuration,
+            "-sdk",
+            sdk,
+            "-showBuildSettings",
+            "-json",
+        ],
+    )?;
+    if !result.success {
+        return Err(Error::process(format!(
+            "Failed to read build settings: {}",
+            result.stderr.trim()
+        )));
+    }
+    parse_target_build_settings(&result.stdout)
+}
+
+/// Parse `xcodebuild -showBuildSettings -json` output
+fn parse_target_build_settings(json: &str) -> Result<Vec<(String, BTreeMap<String, String>)>> {
+    let json: serde_json::Value = serde_json::from_str(json)?;
+    let targets = json.as_array().cloned().unwrap_or_default();
+
+    Ok(targets
+        .iter()
+        .map(|target| {
+            let name = target["target"].as_str().unwrap_or_default().to_string();
+            let settings = target["buildSettings"]
+                .as_object()
+                .map(|settings| {
+                    settings
+                        .iter()
+                        .filter_map(|(k, v)| Some((k.clone(), v.as_str()?.to_string())))
+                        .collect()
+                })
+                .unwrap_or_default();
+            (name, settings)
+        })
+        .collect())
+}
+
+/// Pick the target named like the scheme, or the first one
+fn select_target(
+    targets: Vec<(String, BTreeMap<String, String>)>,
+    scheme: &str,
+) -> BTreeMap<String, String> {
+    let index = targets.iter().position(|(name, _)| name == scheme).unwrap_or(0);
+    targets.into_iter().nth(index).map(|(_, settings)| settings).unwrap_or_default()
+}
+
 /// Run tests for an Xcode project
 pub fn test(scheme: &str, destination: &str, coverage: bool) -> Result<CommandResult> {
     let mut args = vec!["-scheme", scheme, "-destination", destination, "test"];
@@ -53,6 +134,45 @@ pub fn test(scheme: &str, destination: &str, coverage: bool) -> Result<CommandRe
     run_command("xcodebuild", &args)
 }
 
+/// Run tests, writing an `.xcresult` bundle to `result_bundle`
+///
+/// An existing bundle at that path is replaced, since `xcodebuild` refuses to
+/// overwrite one. Failing tests are run again up to `retries` times; the
+/// bundle records every attempt.
+pub fn test_with_result_bundle(
+    scheme: &str,
+    destination: &str,
+    coverage: bool,
+    retries: usize,
+    result_bundle: &Path,
+) -> Result<CommandResult> {
+    if result_bundle.exists() {
+        std::fs::remove_dir_all(result_bundle)?;
+    }
+    let bundle = result_bundle.to_string_lossy();
+    let iterations = (retries + 1).to_string();
+    let mut args = vec![
+        "-scheme",
+        scheme,
+        "-destination",
+        destination,
+        "-resultBundlePath",
+        &bundle,
+        "test",
+    ];
+
+    if coverage {
+        args.push("-enableCodeCoverage");
+        args.push("YES");
+    }
+
+    if retries > 0 {
+        args.extend(["-retry-tests-on-failure", "-test-iterations", &iterations]);
+    }
+
+    run_command("xcodebuild", &args)
+}
+
 /// Archive an Xcode project
 pub fn archive(scheme: &str, archive_path: &Path) -> Result<CommandResult> {
     run_command(
@@ -97,4 +217,19 @@ mod tests {
         // This will be true on macOS with Xcode installed
         let _ = is_xcode_available();
     }
+
+    #[test]
+    fn test_parse_build_settings() {
+        let json = r#"[
+  {"action": "build", "target": "FoodShareWidgets", "buildSettings": {"DEVELOPMENT_TEAM": "OTHER"}},
+  {"action": "build", "target": "FoodShare", "buildSettings": {"DEVELOPMENT_TEAM": "ABCDE12345", "SDKROOT": "iphoneos"}}
+]"#;
+        let targets = parse_target_build_settings(json).unwrap();
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[0].0, "FoodShareWidgets");
+
+        let settings = select_target(targets.clone(), "FoodShare");
+        assert_eq!(settings.get("DEVELOPMENT_TEAM").map(String::as_str), Some("ABCDE12345"));
+        assert_eq!(select_target(targets, "Other")["DEVELOPMENT_TEAM"], "OTHER");
+    }
 }
@@ -1,11 +1,13 @@
 //! Xcode project file manipulation
 //!
 //! Provides tools for reading and modifying .xcodeproj/project.pbxproj files.
-//! This is a Rust implementation of common xcodeproj gem operations.
+//! This is a Rust implementation of common xcodeproj gem operations, built
+//! on the [`pbxproj`](crate::pbxproj) parser.
 
+use crate::pbxproj::{self, Document, MergeReport, Value};
+use foodshare_cli::textln;
 use foodshare_core::error::{Error, Result};
-use regex::Regex;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -14,18 +16,7 @@ use std::path::{Path, PathBuf};
 pub struct XcodeProject {
     pub path: PathBuf,
     pub project_dir: PathBuf,
-    content: String,
-    objects: HashMap<String, PBXObject>,
-    root_object_id: String,
-}
-
-/// A generic PBX object from the project file
-#[derive(Debug, Clone)]
-pub struct PBXObject {
-    pub id: String,
-    pub isa: String,
-    pub properties: HashMap<String, String>,
-    pub raw: String,
+    document: Document,
 }
 
 /// File reference in the project
@@ -51,6 +42,20 @@ pub struct Target {
     pub id: String,
     pub name: String,
     pub build_phases: Vec<String>,
+    /// Product type (`com.apple.product-type.application`)
+    pub product_type: Option<String>,
+    /// File name of the built product (`FoodShare.app`)
+    pub product_name: Option<String>,
+}
+
+impl Target {
+    /// Check if the target builds a unit or UI test bundle
+    pub fn is_test_bundle(&self) -> bool {
+        matches!(
+            self.product_type.as_deref(),
+            Some("com.apple.product-type.bundle.unit-test" | "com.apple.product-type.bundle.ui-testing")
+        )
+    }
 }
 
 /// File type classification for Xcode
@@ -109,6 +114,60 @@ pub struct AddFileResult {
     pub already_exists: bool,
 }
 
+/// Result of removing a file from the project
+#[derive(Debug)]
+pub struct RemoveFileResult {
+    /// Path that was removed
+    pub path: PathBuf,
+    /// Removed PBXFileReference IDs
+    pub file_ref_ids: Vec<String>,
+    /// Removed PBXBuildFile IDs
+    pub build_file_ids: Vec<String>,
+}
+
+/// An XCBuildConfiguration of the project or one of its targets
+#[derive(Debug, Clone)]
+pub struct BuildConfiguration {
+    /// XCBuildConfiguration ID
+    pub id: String,
+    /// Configuration name (`Debug`, `Release`)
+    pub name: String,
+    /// Owning target, `None` for project-level settings
+    pub target: Option<String>,
+    /// Build settings; list values are joined with spaces
+    pub settings: BTreeMap<String, String>,
+}
+
+impl BuildConfiguration {
+    /// Settings whose values differ from `other`, sorted by key
+    pub fn diff(&self, other: &BuildConfiguration) -> Vec<SettingDiff> {
+        let keys: std::collections::BTreeSet<&String> =
+            self.settings.keys().chain(other.settings.keys()).collect();
+        keys.into_iter()
+            .filter_map(|key| {
+                let left = self.settings.get(key);
+                let right = other.settings.get(key);
+                (left != right).then(|| SettingDiff {
+                    key: key.clone(),
+                    left: left.cloned(),
+                    right: right.cloned(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// A build setting that differs between two configurations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettingDiff {
+    /// Setting name
+    pub key: String,
+    /// Value in the first configuration
+    pub left: Option<String>,
+    /// Value in the second configuration
+    pub right: Option<String>,
+}
+
 /// Reference to a PBXGroup in the project
 #[derive(Debug, Clone)]
 pub struct GroupReference {
@@ -133,203 +192,103 @@ impl XcodeProject {
             .ok_or_else(|| Error::validation("Invalid project path"))?
             .to_path_buf();
 
-        let mut project = Self {
+        Ok(Self {
             path: path.to_path_buf(),
             project_dir,
-            content: content.clone(),
-            objects: HashMap::new(),
-            root_object_id: String::new(),
-        };
-
-        project.parse()?;
-        Ok(project)
+            document: Document::parse(&content)?,
+        })
     }
 
-    /// Parse the project file
-    fn parse(&mut self) -> Result<()> {
-        // Extract rootObject
-        let root_re = Regex::new(r#"rootObject\s*=\s*([A-F0-9]{24})"#).unwrap();
-        if let Some(cap) = root_re.captures(&self.content) {
-            self.root_object_id = cap[1].to_string();
-        }
-
-        // Parse PBXFileReference entries
-        let file_ref_re = Regex::new(
-            r#"([A-F0-9]{24})\s*/\*[^*]*\*/\s*=\s*\{isa\s*=\s*PBXFileReference;[^}]+\}"#
-        ).unwrap();
-        
-        for cap in file_ref_re.captures_iter(&self.content) {
-            let id = cap[0].split_whitespace().next().unwrap_or("").to_string();
-            let body = &cap[0];
-            
-            let mut properties = HashMap::new();
-            properties.insert("isa".to_string(), "PBXFileReference".to_string());
-            
-            // Extract path
-            let path_re = Regex::new(r#"path\s*=\s*"?([^";]+)"?"#).unwrap();
-            if let Some(path_cap) = path_re.captures(body) {
-                properties.insert("path".to_string(), path_cap[1].trim().to_string());
-            }
-            
-            // Extract name
-            let name_re = Regex::new(r#"name\s*=\s*"?([^";]+)"?"#).unwrap();
-            if let Some(name_cap) = name_re.captures(body) {
-                properties.insert("name".to_string(), name_cap[1].trim().to_string());
-            }
-            
-            // Extract sourceTree
-            let tree_re = Regex::new(r#"sourceTree\s*=\s*"?([^";]+)"?"#).unwrap();
-            if let Some(tree_cap) = tree_re.captures(body) {
-                properties.insert("sourceTree".to_string(), tree_cap[1].trim().to_string());
-            }
-            
-            self.objects.insert(
-                id.clone(),
-                PBXObject {
-                    id,
-                    isa: "PBXFileReference".to_string(),
-                    properties,
-                    raw: body.to_string(),
-                },
-            );
+    /// Open a project whose project.pbxproj has git conflict markers
+    ///
+    /// Both sides of the conflicts are parsed as whole projects and merged
+    /// with [`Document::merge`], starting from ours (the checked-out side).
+    /// Returns `None` if there are no conflict markers.
+    pub fn resolve_conflicts(path: &Path) -> Result<Option<(Self, ConflictReport)>> {
+        let pbxproj_path = path.join("project.pbxproj");
+        if !pbxproj_path.exists() {
+            return Err(Error::file_not_found(&pbxproj_path));
         }
 
-        // Parse PBXBuildFile entries
-        let build_file_re = Regex::new(
-            r#"([A-F0-9]{24})\s*/\*[^*]*\*/\s*=\s*\{isa\s*=\s*PBXBuildFile;\s*fileRef\s*=\s*([A-F0-9]{24})"#
-        ).unwrap();
-        
-        for cap in build_file_re.captures_iter(&self.content) {
-            let id = cap[1].to_string();
-            let file_ref = cap[2].to_string();
-            
-            let mut properties = HashMap::new();
-            properties.insert("isa".to_string(), "PBXBuildFile".to_string());
-            properties.insert("fileRef".to_string(), file_ref);
-            
-            self.objects.insert(
-                id.clone(),
-                PBXObject {
-                    id,
-                    isa: "PBXBuildFile".to_string(),
-                    properties,
-                    raw: cap[0].to_string(),
-                },
-            );
-        }
+        let content = fs::read_to_string(&pbxproj_path)?;
+        let Some((ours, theirs)) = pbxproj::split_conflicts(&content)? else {
+            return Ok(None);
+        };
+        let parse_side = |text: &str, side: &str| {
+            Document::parse(text).map_err(|e| {
+                Error::validation(format!("Cannot merge automatically, {} side is not a valid project: {}", side, e))
+            })
+        };
+        let mut document = parse_side(&ours, "our")?;
+        let merge = document.merge(&parse_side(&theirs, "their")?);
 
-        // Parse PBXNativeTarget entries
-        let target_re = Regex::new(
-            r#"([A-F0-9]{24})\s*/\*\s*([^*]+)\s*\*/\s*=\s*\{[^}]*isa\s*=\s*PBXNativeTarget"#
-        ).unwrap();
-        
-        for cap in target_re.captures_iter(&self.content) {
-            let id = cap[1].to_string();
-            let name = cap[2].trim().to_string();
-            
-            let mut properties = HashMap::new();
-            properties.insert("isa".to_string(), "PBXNativeTarget".to_string());
-            properties.insert("name".to_string(), name);
-            
-            // Find buildPhases for this target - search in a larger context
-            let target_start = cap.get(0).unwrap().start();
-            let search_end = (target_start + 2000).min(self.content.len());
-            let target_context = &self.content[target_start..search_end];
-            
-            let phases_re = Regex::new(r#"buildPhases\s*=\s*\(([^)]+)\)"#).unwrap();
-            if let Some(phases_cap) = phases_re.captures(target_context) {
-                properties.insert("buildPhases".to_string(), phases_cap[1].to_string());
-            }
-            
-            self.objects.insert(
-                id.clone(),
-                PBXObject {
-                    id,
-                    isa: "PBXNativeTarget".to_string(),
-                    properties,
-                    raw: String::new(),
-                },
-            );
-        }
+        let project_dir = path
+            .parent()
+            .ok_or_else(|| Error::validation("Invalid project path"))?
+            .to_path_buf();
+        let report = ConflictReport {
+            conflicts: content.lines().filter(|line| line.starts_with("<<<<<<<")).count(),
+            diff: line_diff(&ours, &document.to_string()),
+            merge,
+        };
+        let project = Self {
+            path: path.to_path_buf(),
+            project_dir,
+            document,
+        };
+        Ok(Some((project, report)))
+    }
 
-        // Parse PBXSourcesBuildPhase entries
-        let sources_re = Regex::new(
-            r#"([A-F0-9]{24})\s*/\*[^*]*\*/\s*=\s*\{[^}]*isa\s*=\s*PBXSourcesBuildPhase"#
-        ).unwrap();
-        
-        for cap in sources_re.captures_iter(&self.content) {
-            let id = cap[1].to_string();
-            
-            let mut properties = HashMap::new();
-            properties.insert("isa".to_string(), "PBXSourcesBuildPhase".to_string());
-            
-            // Find files for this phase
-            let phase_start = cap.get(0).unwrap().start();
-            let search_end = (phase_start + 50000).min(self.content.len());
-            let phase_context = &self.content[phase_start..search_end];
-            
-            let files_re = Regex::new(r#"files\s*=\s*\(([^)]+)\)"#).unwrap();
-            if let Some(files_cap) = files_re.captures(phase_context) {
-                properties.insert("files".to_string(), files_cap[1].to_string());
-            }
-            
-            self.objects.insert(
-                id.clone(),
-                PBXObject {
-                    id,
-                    isa: "PBXSourcesBuildPhase".to_string(),
-                    properties,
-                    raw: String::new(),
-                },
-            );
-        }
+    /// The parsed project file
+    pub fn document(&self) -> &Document {
+        &self.document
+    }
 
-        Ok(())
+    /// Contents of project.pbxproj with the current changes
+    pub fn content(&self) -> String {
+        self.document.to_string()
     }
 
     /// Get all file references
     pub fn file_references(&self) -> Vec<FileReference> {
-        self.objects
-            .values()
-            .filter(|obj| obj.isa == "PBXFileReference")
-            .map(|obj| FileReference {
-                id: obj.id.clone(),
-                path: obj.properties.get("path").cloned().unwrap_or_default(),
-                name: obj.properties.get("name").cloned(),
-                source_tree: obj
-                    .properties
-                    .get("sourceTree")
-                    .cloned()
-                    .unwrap_or_else(|| "<group>".to_string()),
-                file_type: obj.properties.get("lastKnownFileType").cloned(),
+        self.document
+            .objects_of("PBXFileReference")
+            .map(|(id, obj)| FileReference {
+                id: id.to_string(),
+                path: obj.get_str("path").unwrap_or_default().to_string(),
+                name: obj.get_str("name").map(str::to_string),
+                source_tree: obj.get_str("sourceTree").unwrap_or("<group>").to_string(),
+                file_type: obj.get_str("lastKnownFileType").map(str::to_string),
             })
             .collect()
     }
 
     /// Get all targets
     pub fn targets(&self) -> Vec<Target> {
-        self.objects
-            .values()
-            .filter(|obj| obj.isa == "PBXNativeTarget")
-            .map(|obj| {
-                let name = obj.properties.get("name").cloned().unwrap_or_default();
-                // Parse buildPhases array
-                let phases_re = Regex::new(r#"([A-F0-9]{24})"#).unwrap();
-                let phases_str = obj.properties.get("buildPhases").cloned().unwrap_or_default();
-                let build_phases: Vec<String> = phases_re
-                    .captures_iter(&phases_str)
-                    .map(|c| c[1].to_string())
-                    .collect();
-
-                Target {
-                    id: obj.id.clone(),
-                    name,
-                    build_phases,
-                }
+        self.document
+            .objects_of("PBXNativeTarget")
+            .map(|(id, obj)| Target {
+                id: id.to_string(),
+                name: obj.get_str("name").unwrap_or_default().to_string(),
+                build_phases: Self::id_list(obj.get("buildPhases")),
+                product_type: obj.get_str("productType").map(str::to_string),
+                product_name: obj
+                    .get_str("productReference")
+                    .and_then(|id| self.document.object(id))
+                    .and_then(|product| product.get_str("path"))
+                    .map(str::to_string),
             })
             .collect()
     }
 
+    /// IDs in an array property
+    fn id_list(value: Option<&Value>) -> Vec<String> {
+        value
+            .and_then(Value::as_array)
+            .map(|ids| ids.strs().map(str::to_string).collect())
+            .unwrap_or_default()
+    }
+
     /// Find target by name
     pub fn find_target(&self, name: &str) -> Option<Target> {
         self.targets().into_iter().find(|t| t.name == name)
@@ -337,27 +296,22 @@ impl XcodeProject {
 
     /// Get build files for a source build phase
     pub fn build_files_for_phase(&self, phase_id: &str) -> Vec<BuildFile> {
-        if let Some(phase) = self.objects.get(phase_id) {
-            if phase.isa == "PBXSourcesBuildPhase" {
-                let files_re = Regex::new(r#"([A-F0-9]{24})"#).unwrap();
-                let files_str = phase.properties.get("files").cloned().unwrap_or_default();
-                return files_re
-                    .captures_iter(&files_str)
-                    .filter_map(|c| {
-                        let bf_id = c[1].to_string();
-                        self.objects.get(&bf_id).map(|bf| BuildFile {
-                            id: bf_id,
-                            file_ref_id: bf
-                                .properties
-                                .get("fileRef")
-                                .cloned()
-                                .unwrap_or_default(),
-                        })
-                    })
-                    .collect();
-            }
+        let Some(phase) = self.document.object(phase_id) else {
+            return Vec::new();
+        };
+        if phase.get_str("isa") != Some("PBXSourcesBuildPhase") {
+            return Vec::new();
         }
-        Vec::new()
+        Self::id_list(phase.get("files"))
+            .into_iter()
+            .filter_map(|bf_id| {
+                let file_ref_id = self.document.object(&bf_id)?.get_str("fileRef");
+                Some(BuildFile {
+                    file_ref_id: file_ref_id.unwrap_or_default().to_string(),
+                    id: bf_id,
+                })
+            })
+            .collect()
     }
 
     /// Get all Swift files in the build phase for a target
@@ -370,8 +324,8 @@ impl XcodeProject {
         let mut files = Vec::new();
         for phase_id in &target.build_phases {
             for build_file in self.build_files_for_phase(phase_id) {
-                if let Some(file_ref) = self.objects.get(&build_file.file_ref_id) {
-                    if let Some(path) = file_ref.properties.get("path") {
+                if let Some(file_ref) = self.document.object(&build_file.file_ref_id) {
+                    if let Some(path) = file_ref.get_str("path") {
                         if path.ends_with(".swift") {
                             files.push(PathBuf::from(path));
                         }
@@ -433,7 +387,11 @@ impl XcodeProject {
     }
 
     /// Find broken references (in project but file doesn't exist)
+    ///
+    /// Only references relative to the project (`<group>`, `SOURCE_ROOT`)
+    /// or absolute are checked; build products and SDK files are skipped.
     pub fn find_broken_references(&self) -> Vec<FileReference> {
+        let parents = self.parent_dirs();
         self.file_references()
             .into_iter()
             .filter(|fr| {
@@ -442,8 +400,10 @@ impl XcodeProject {
                 }
                 let full_path = if fr.path.starts_with('/') {
                     PathBuf::from(&fr.path)
+                } else if matches!(fr.source_tree.as_str(), "<group>" | "SOURCE_ROOT") {
+                    self.project_dir.join(Self::resolve_with(fr, &parents))
                 } else {
-                    self.project_dir.join(&fr.path)
+                    return false;
                 };
                 !full_path.exists()
             })
@@ -511,7 +471,7 @@ impl XcodeProject {
     fn generate_unique_uuid(&self) -> String {
         loop {
             let id = self.generate_uuid();
-            if !self.objects.contains_key(&id) {
+            if !self.document.objects().contains_key(&id) {
                 return id;
             }
         }
@@ -523,64 +483,16 @@ impl XcodeProject {
 
     /// Parse all PBXGroup entries from the project file
     fn parse_groups(&self) -> Vec<GroupReference> {
-        let mut groups = Vec::new();
-
-        // Match PBXGroup entries
-        let group_re = Regex::new(
-            r#"([A-F0-9]{24})\s*/\*[^*]*\*/\s*=\s*\{[^}]*isa\s*=\s*PBXGroup"#,
-        )
-        .unwrap();
-
-        for cap in group_re.captures_iter(&self.content) {
-            let id = cap[1].to_string();
-            let start = cap.get(0).unwrap().start();
-
-            // Find the full group block - need to find matching closing brace
-            let search_end = (start + 5000).min(self.content.len());
-            let block = &self.content[start..search_end];
-
-            // Extract name
-            let name = Regex::new(r#"name\s*=\s*"?([^";]+)"?"#)
-                .ok()
-                .and_then(|re| re.captures(block))
-                .map(|c| c[1].trim().to_string());
-
-            // Extract path
-            let path = Regex::new(r#"(?m)^\s*path\s*=\s*"?([^";]+)"?"#)
-                .ok()
-                .and_then(|re| re.captures(block))
-                .map(|c| c[1].trim().to_string());
-
-            // Extract sourceTree
-            let source_tree = Regex::new(r#"sourceTree\s*=\s*"?([^";]+)"?"#)
-                .ok()
-                .and_then(|re| re.captures(block))
-                .map(|c| c[1].trim().to_string())
-                .unwrap_or_else(|| "<group>".to_string());
-
-            // Extract children
-            let children = Regex::new(r#"children\s*=\s*\(([^)]*)\)"#)
-                .ok()
-                .and_then(|re| re.captures(block))
-                .map(|c| {
-                    Regex::new(r#"([A-F0-9]{24})"#)
-                        .unwrap()
-                        .captures_iter(&c[1])
-                        .map(|m| m[1].to_string())
-                        .collect()
-                })
-                .unwrap_or_default();
-
-            groups.push(GroupReference {
-                id,
-                name,
-                path,
-                children,
-                source_tree,
-            });
-        }
-
-        groups
+        self.document
+            .objects_of("PBXGroup")
+            .map(|(id, obj)| GroupReference {
+                id: id.to_string(),
+                name: obj.get_str("name").map(str::to_string),
+                path: obj.get_str("path").map(str::to_string),
+                children: Self::id_list(obj.get("children")),
+                source_tree: obj.get_str("sourceTree").unwrap_or("<group>").to_string(),
+            })
+            .collect()
     }
 
     /// Get all groups in the project
@@ -590,8 +502,11 @@ impl XcodeProject {
 
     /// Find the main (root) group ID from PBXProject
     pub fn find_main_group_id(&self) -> Option<String> {
-        let re = Regex::new(r#"mainGroup\s*=\s*([A-F0-9]{24})"#).ok()?;
-        re.captures(&self.content).map(|c| c[1].to_string())
+        let root_id = self.document.root_object_id()?;
+        self.document
+            .object(root_id)?
+            .get_str("mainGroup")
+            .map(str::to_string)
     }
 
     /// Find a group by its path (e.g., "FoodShare/Core/Design")
@@ -647,15 +562,6 @@ impl XcodeProject {
             .any(|fr| fr.path == path_str || fr.path.ends_with(file_name))
     }
 
-    /// Quote a string if it contains special characters
-    fn quote_if_needed(s: &str) -> String {
-        if s.contains(' ') || s.contains('/') || s.contains('-') || s.contains('.') {
-            format!("\"{}\"", s)
-        } else {
-            s.to_string()
-        }
-    }
-
     /// Add a file to the project
     pub fn add_file(
         &mut self,
@@ -698,62 +604,37 @@ impl XcodeProject {
             .and_then(|n| n.to_str())
             .unwrap_or("unknown");
 
-        // Generate IDs
-        let file_ref_id = self.generate_unique_uuid();
-
-        // Register the file_ref_id to avoid collisions
-        self.objects.insert(
-            file_ref_id.clone(),
-            PBXObject {
-                id: file_ref_id.clone(),
-                isa: "PBXFileReference".to_string(),
-                properties: HashMap::new(),
-                raw: String::new(),
-            },
-        );
-
-        let build_file_id = if file_type.should_add_to_sources() {
-            let id = self.generate_unique_uuid();
-            self.objects.insert(
-                id.clone(),
-                PBXObject {
-                    id: id.clone(),
-                    isa: "PBXBuildFile".to_string(),
-                    properties: HashMap::new(),
-                    raw: String::new(),
-                },
-            );
-            Some(id)
-        } else {
-            None
-        };
-
         // Find or create the target group
         let group_id = self.find_or_create_group(group_path, file_path)?;
 
         // Add PBXFileReference entry
-        self.add_file_reference(&file_ref_id, file_path, &file_type)?;
+        let file_ref_id = self.generate_unique_uuid();
+        self.add_file_reference(&file_ref_id, file_path, &file_type);
 
         // Add file to group's children
         self.add_child_to_group(&group_id, &file_ref_id, file_name)?;
 
         // If it's a source file, add to build phase
-        if let Some(ref bf_id) = build_file_id {
+        let build_file_id = if file_type.should_add_to_sources() {
             // Add PBXBuildFile entry
-            self.add_build_file(bf_id, &file_ref_id, file_name)?;
+            let bf_id = self.generate_unique_uuid();
+            self.add_build_file(&bf_id, &file_ref_id, file_name);
 
             // Find the Sources build phase for the target and add the build file
             if let Some(target) = self.find_target(target_name) {
                 for phase_id in &target.build_phases {
-                    if let Some(phase) = self.objects.get(phase_id) {
-                        if phase.isa == "PBXSourcesBuildPhase" {
-                            self.add_file_to_build_phase(phase_id, bf_id, file_name)?;
+                    if let Some(phase) = self.document.object(phase_id) {
+                        if phase.get_str("isa") == Some("PBXSourcesBuildPhase") {
+                            self.add_file_to_build_phase(phase_id, &bf_id, file_name)?;
                             break;
                         }
                     }
                 }
             }
-        }
+            Some(bf_id)
+        } else {
+            None
+        };
 
         Ok(AddFileResult {
             file_ref_id,
@@ -764,58 +645,48 @@ impl XcodeProject {
         })
     }
 
-    /// Add a PBXFileReference entry to the project content
-    fn add_file_reference(
-        &mut self,
-        id: &str,
-        path: &Path,
-        file_type: &FileType,
-    ) -> Result<()> {
-        let path_str = path.to_string_lossy();
+    /// Add a PBXFileReference object
+    fn add_file_reference(&mut self, id: &str, path: &Path, file_type: &FileType) {
         let file_name = path
             .file_name()
             .and_then(|n| n.to_str())
             .unwrap_or("unknown");
-        let last_known = file_type.last_known_file_type();
 
-        // Format the entry
-        let entry = format!(
-            "\t\t{} /* {} */ = {{isa = PBXFileReference; lastKnownFileType = {}; path = {}; sourceTree = SOURCE_ROOT; }};\n",
+        self.document.insert_object(
             id,
             file_name,
-            last_known,
-            Self::quote_if_needed(&path_str)
+            Value::dict([
+                ("isa", Value::string("PBXFileReference")),
+                ("lastKnownFileType", Value::string(file_type.last_known_file_type())),
+                ("path", Value::string(path.to_string_lossy())),
+                ("sourceTree", Value::string("SOURCE_ROOT")),
+            ]),
         );
-
-        // Find the end of PBXFileReference section and insert before it
-        let marker = "/* End PBXFileReference section */";
-        if let Some(pos) = self.content.find(marker) {
-            self.content.insert_str(pos, &entry);
-        } else {
-            return Err(Error::validation(
-                "Could not find PBXFileReference section in project file",
-            ));
-        }
-
-        Ok(())
     }
 
-    /// Add a PBXBuildFile entry to the project content
-    fn add_build_file(&mut self, id: &str, file_ref_id: &str, file_name: &str) -> Result<()> {
-        let entry = format!(
-            "\t\t{} /* {} in Sources */ = {{isa = PBXBuildFile; fileRef = {} /* {} */; }};\n",
-            id, file_name, file_ref_id, file_name
+    /// Add a PBXBuildFile object
+    fn add_build_file(&mut self, id: &str, file_ref_id: &str, file_name: &str) {
+        self.document.insert_object(
+            id,
+            &format!("{file_name} in Sources"),
+            Value::dict([
+                ("isa", Value::string("PBXBuildFile")),
+                ("fileRef", Value::string(file_ref_id).with_comment(file_name)),
+            ]),
         );
+    }
 
-        let marker = "/* End PBXBuildFile section */";
-        if let Some(pos) = self.content.find(marker) {
-            self.content.insert_str(pos, &entry);
-        } else {
-            return Err(Error::validation(
-                "Could not find PBXBuildFile section in project file",
-            ));
+    /// Add an ID to an array property of an object, creating the array if needed
+    fn push_to_list(&mut self, object_id: &str, key: &str, id: &str, comment: &str) -> Result<()> {
+        let object = self
+            .document
+            .object_mut(object_id)
+            .ok_or_else(|| Error::validation(format!("Object {object_id} not found in project")))?;
+        let entry = Value::string(id).with_comment(comment);
+        match object.get_mut(key).and_then(Value::as_array_mut) {
+            Some(list) => list.push(entry),
+            None => object.set(key, Value::array([entry])),
         }
-
         Ok(())
     }
 
@@ -826,60 +697,33 @@ impl XcodeProject {
         build_file_id: &str,
         file_name: &str,
     ) -> Result<()> {
-        // Find the build phase entry
-        let phase_pattern = format!(r#"{}\s*/\*[^*]*\*/\s*=\s*\{{[^}}]*files\s*=\s*\("#, phase_id);
-        let phase_re = Regex::new(&phase_pattern).map_err(|e| {
-            Error::validation(&format!("Invalid regex pattern: {}", e))
-        })?;
-
-        if let Some(cap) = phase_re.find(&self.content) {
-            let insert_pos = cap.end();
-            let entry = format!(
-                "\n\t\t\t\t{} /* {} in Sources */,",
-                build_file_id, file_name
-            );
-            self.content.insert_str(insert_pos, &entry);
-        }
-
-        Ok(())
+        self.push_to_list(phase_id, "files", build_file_id, &format!("{file_name} in Sources"))
     }
 
     /// Add a child ID to a group's children array
     fn add_child_to_group(&mut self, group_id: &str, child_id: &str, file_name: &str) -> Result<()> {
-        // Find the group entry and its children array
-        let group_pattern = format!(r#"{}\s*/\*[^*]*\*/\s*=\s*\{{[^}}]*children\s*=\s*\("#, group_id);
-        let group_re = Regex::new(&group_pattern).map_err(|e| {
-            Error::validation(&format!("Invalid regex pattern: {}", e))
-        })?;
-
-        if let Some(cap) = group_re.find(&self.content) {
-            let insert_pos = cap.end();
-            let entry = format!("\n\t\t\t\t{} /* {} */,", child_id, file_name);
-            self.content.insert_str(insert_pos, &entry);
-        }
-
-        Ok(())
+        self.push_to_list(group_id, "children", child_id, file_name)
     }
 
     /// Find or create a group for the file
+    ///
+    /// Uses `explicit_group` if given, otherwise the group matching the file's
+    /// directory. Missing groups are created either way.
     fn find_or_create_group(
         &mut self,
         explicit_group: Option<&str>,
         file_path: &Path,
     ) -> Result<String> {
-        // If explicit group path provided, try to find it
         if let Some(group_path) = explicit_group {
-            if let Some(group) = self.find_group_by_path(group_path) {
-                return Ok(group.id);
-            }
-            // Group doesn't exist - we could create it, but for now just use parent dir
+            return self.find_or_create_group_path(group_path);
         }
 
         // Use the file's parent directory as the group path
-        let parent = file_path
-            .parent()
-            .and_then(|p| p.to_str())
-            .unwrap_or("");
+        let relative = file_path.strip_prefix(&self.project_dir).unwrap_or(file_path);
+        let parent = relative.parent().and_then(|p| p.to_str()).unwrap_or("");
+        if relative.is_relative() {
+            return self.find_or_create_group_path(parent);
+        }
 
         if let Some(group) = self.find_group_by_path(parent) {
             return Ok(group.id);
@@ -890,33 +734,507 @@ impl XcodeProject {
             .ok_or_else(|| Error::validation("Could not find main group in project"))
     }
 
-    /// Create a new group in the project
-    #[allow(dead_code)]
-    fn create_group(&mut self, id: &str, name: &str, path: Option<&str>) -> Result<()> {
-        let path_attr = path
-            .map(|p| format!("path = {}; ", Self::quote_if_needed(p)))
-            .unwrap_or_default();
+    /// Find a group by path (e.g., "FoodShare/Core/Design"), creating any
+    /// missing groups along the way
+    ///
+    /// New groups point at the folder of the same name inside their parent
+    /// group, so the group tree mirrors the directories on disk.
+    pub fn find_or_create_group_path(&mut self, path: &str) -> Result<String> {
+        let mut group_id = self
+            .find_main_group_id()
+            .ok_or_else(|| Error::validation("Could not find main group in project"))?;
+
+        for part in path.split('/').filter(|s| !s.is_empty()) {
+            let groups = self.parse_groups();
+            let existing = groups
+                .iter()
+                .find(|g| g.id == group_id)
+                .into_iter()
+                .flat_map(|parent| &parent.children)
+                .find_map(|child_id| {
+                    groups.iter().find(|g| {
+                        &g.id == child_id
+                            && (g.name.as_deref() == Some(part) || g.path.as_deref() == Some(part))
+                    })
+                });
+
+            group_id = match existing {
+                Some(group) => group.id.clone(),
+                None => {
+                    let id = self.generate_unique_uuid();
+                    self.create_group(&id, part);
+                    self.add_child_to_group(&group_id, &id, part)?;
+                    id
+                }
+            };
+        }
+        Ok(group_id)
+    }
 
-        let entry = format!(
-            "\t\t{} /* {} */ = {{\n\t\t\tisa = PBXGroup;\n\t\t\tchildren = (\n\t\t\t);\n\t\t\t{}name = {};\n\t\t\tsourceTree = \"<group>\";\n\t\t}};\n",
+    /// Create a new group for a folder inside its parent group's folder
+    fn create_group(&mut self, id: &str, path: &str) {
+        self.document.insert_object(
             id,
-            name,
-            path_attr,
-            Self::quote_if_needed(name)
+            path,
+            Value::dict([
+                ("isa", Value::string("PBXGroup")),
+                ("children", Value::array([])),
+                ("path", Value::string(path)),
+                ("sourceTree", Value::string("<group>")),
+            ]),
         );
+    }
 
-        let marker = "/* End PBXGroup section */";
-        if let Some(pos) = self.content.find(marker) {
-            self.content.insert_str(pos, &entry);
-        } else {
-            return Err(Error::validation(
-                "Could not find PBXGroup section in project file",
-            ));
+    // ========================================================================
+    // Remove, Rename and Move
+    // ========================================================================
+
+    /// Resolve each group's directory relative to the project directory
+    fn group_dirs(&self) -> HashMap<String, PathBuf> {
+        let groups = self.parse_groups();
+        let by_id: HashMap<&str, &GroupReference> =
+            groups.iter().map(|g| (g.id.as_str(), g)).collect();
+
+        let mut dirs = HashMap::new();
+        let Some(main_id) = self.find_main_group_id() else {
+            return dirs;
+        };
+        let mut stack = vec![(main_id, PathBuf::new())];
+        while let Some((id, parent_dir)) = stack.pop() {
+            let Some(group) = by_id.get(id.as_str()) else {
+                continue;
+            };
+            let dir = match (&group.path, group.source_tree.as_str()) {
+                (Some(path), "SOURCE_ROOT") => PathBuf::from(path),
+                (Some(path), _) => parent_dir.join(path),
+                (None, _) => parent_dir,
+            };
+            for child in &group.children {
+                if by_id.contains_key(child.as_str()) {
+                    stack.push((child.clone(), dir.clone()));
+                }
+            }
+            dirs.insert(id, dir);
+        }
+        dirs
+    }
+
+    /// Directory of the group containing each child, keyed by child ID
+    fn parent_dirs(&self) -> HashMap<String, PathBuf> {
+        let dirs = self.group_dirs();
+        let mut parents = HashMap::new();
+        for group in self.parse_groups() {
+            if let Some(dir) = dirs.get(&group.id) {
+                for child in group.children {
+                    parents.insert(child, dir.clone());
+                }
+            }
         }
+        parents
+    }
 
+    fn resolve_with(file_ref: &FileReference, parents: &HashMap<String, PathBuf>) -> PathBuf {
+        match parents.get(&file_ref.id) {
+            Some(dir) if file_ref.source_tree == "<group>" => dir.join(&file_ref.path),
+            _ => PathBuf::from(&file_ref.path),
+        }
+    }
+
+    /// Path of a file reference relative to the project directory
+    ///
+    /// `<group>` references are resolved through their enclosing groups.
+    pub fn resolve_file_path(&self, file_ref: &FileReference) -> PathBuf {
+        if file_ref.source_tree != "<group>" {
+            return PathBuf::from(&file_ref.path);
+        }
+        Self::resolve_with(file_ref, &self.parent_dirs())
+    }
+
+    /// Find the file references for a path relative to the project directory
+    pub fn find_file_references(&self, file_path: &Path) -> Vec<FileReference> {
+        let path_str = file_path.to_string_lossy();
+        let parents = self.parent_dirs();
+        self.file_references()
+            .into_iter()
+            .filter(|fr| {
+                !fr.path.is_empty()
+                    && (fr.path == path_str || Self::resolve_with(fr, &parents) == file_path)
+            })
+            .collect()
+    }
+
+    fn find_file_references_or_err(&self, file_path: &Path) -> Result<Vec<FileReference>> {
+        let refs = self.find_file_references(file_path);
+        if refs.is_empty() {
+            return Err(Error::validation(format!(
+                "File not in project: {}",
+                file_path.display()
+            )));
+        }
+        Ok(refs)
+    }
+
+    /// Build files pointing at a file reference
+    fn build_files_for_ref(&self, file_ref_id: &str) -> Vec<String> {
+        self.document
+            .objects_of("PBXBuildFile")
+            .filter(|(_, obj)| obj.get_str("fileRef") == Some(file_ref_id))
+            .map(|(id, _)| id.to_string())
+            .collect()
+    }
+
+    /// Remove an object definition
+    fn remove_object(&mut self, id: &str) {
+        self.document.remove_object(id);
+    }
+
+    /// Remove `id` from every list it appears in (group children, build phase files)
+    fn remove_list_entries(&mut self, id: &str) {
+        self.document.remove_references(id);
+    }
+
+    /// Set a property inside an object definition, adding it if missing
+    fn set_object_property(&mut self, id: &str, key: &str, value: &str) -> Result<()> {
+        self.document
+            .object_mut(id)
+            .ok_or_else(|| Error::validation(format!("Object {id} not found in project")))?
+            .set(key, Value::string(value));
         Ok(())
     }
 
+    /// Remove a property from an object definition
+    fn remove_object_property(&mut self, id: &str, key: &str) {
+        if let Some(object) = self.document.object_mut(id) {
+            object.remove(key);
+        }
+    }
+
+    /// Rename the `/* name */` comments that follow `id` throughout the file
+    fn rename_comments(&mut self, id: &str, old_name: &str, new_name: &str) {
+        self.document.rename_comments(id, old_name, new_name);
+    }
+
+    /// Remove a file from the project
+    ///
+    /// Deletes its PBXFileReference, every PBXBuildFile pointing at it, and
+    /// their entries in group children and build phases. The file on disk is
+    /// left alone.
+    pub fn remove_file(&mut self, file_path: &Path) -> Result<RemoveFileResult> {
+        let refs = self.find_file_references_or_err(file_path)?;
+
+        let mut result = RemoveFileResult {
+            path: file_path.to_path_buf(),
+            file_ref_ids: Vec::new(),
+            build_file_ids: Vec::new(),
+        };
+        for file_ref in refs {
+            result.build_file_ids.extend(self.remove_reference(&file_ref.id));
+            result.file_ref_ids.push(file_ref.id);
+        }
+        Ok(result)
+    }
+
+    /// Remove a file reference and its build files, returning the build file IDs
+    fn remove_reference(&mut self, file_ref_id: &str) -> Vec<String> {
+        let build_file_ids = self.build_files_for_ref(file_ref_id);
+        for build_file_id in &build_file_ids {
+            self.remove_list_entries(build_file_id);
+            self.remove_object(build_file_id);
+        }
+        self.remove_list_entries(file_ref_id);
+        self.remove_object(file_ref_id);
+        build_file_ids
+    }
+
+    /// Rename a file in the project
+    ///
+    /// Updates the reference's path (and name, if it has one), its file type
+    /// when the extension changes, and the comments next to every reference
+    /// to it. Only the project is changed; moving the file on disk is up to
+    /// the caller.
+    pub fn rename_file(&mut self, old_path: &Path, new_path: &Path) -> Result<String> {
+        let refs = self.find_file_references_or_err(old_path)?;
+        if !self.find_file_references(new_path).is_empty() {
+            return Err(Error::validation(format!(
+                "File already in project: {}",
+                new_path.display()
+            )));
+        }
+
+        let file_name = |path: &Path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default()
+                .to_string()
+        };
+        let old_name = file_name(old_path);
+        let new_name = file_name(new_path);
+        let file_ref = &refs[0];
+
+        // `<group>` paths stay relative when the directory is unchanged;
+        // otherwise the reference is re-anchored to the project root
+        if file_ref.source_tree == "<group>" && old_path.parent() == new_path.parent() {
+            let relative = Path::new(&file_ref.path).with_file_name(&new_name);
+            self.set_object_property(&file_ref.id, "path", &relative.to_string_lossy())?;
+        } else {
+            self.set_object_property(&file_ref.id, "path", &new_path.to_string_lossy())?;
+            self.set_object_property(&file_ref.id, "sourceTree", "SOURCE_ROOT")?;
+        }
+        if file_ref.name.is_some() {
+            self.set_object_property(&file_ref.id, "name", &new_name)?;
+        }
+        let old_type = FileType::from_extension(
+            old_path.extension().and_then(|e| e.to_str()).unwrap_or_default(),
+        );
+        let new_type = FileType::from_extension(
+            new_path.extension().and_then(|e| e.to_str()).unwrap_or_default(),
+        );
+        if old_type != new_type {
+            self.set_object_property(
+                &file_ref.id,
+                "lastKnownFileType",
+                new_type.last_known_file_type(),
+            )?;
+        }
+
+        self.rename_comments(&file_ref.id, &old_name, &new_name);
+        for build_file_id in self.build_files_for_ref(&file_ref.id) {
+            self.rename_comments(&build_file_id, &old_name, &new_name);
+        }
+        Ok(file_ref.id.clone())
+    }
+
+    /// Move a file to another group
+    ///
+    /// The reference is re-anchored to the project root so it keeps pointing
+    /// at the same file on disk regardless of the new group's path.
+    pub fn move_file_to_group(&mut self, file_path: &Path, group_path: &str) -> Result<String> {
+        let file_ref = self.find_file_references_or_err(file_path)?.remove(0);
+        let group = self
+            .find_group_by_path(group_path)
+            .ok_or_else(|| Error::validation(format!("Group not found: {group_path}")))?;
+        let file_name = file_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+
+        let resolved = self.resolve_file_path(&file_ref);
+        if file_ref.source_tree == "<group>" {
+            self.set_object_property(&file_ref.id, "path", &resolved.to_string_lossy())?;
+            self.set_object_property(&file_ref.id, "sourceTree", "SOURCE_ROOT")?;
+            self.remove_object_property(&file_ref.id, "name");
+        }
+
+        self.remove_list_entries(&file_ref.id);
+        self.add_child_to_group(&group.id, &file_ref.id, &file_name)?;
+        Ok(group.id)
+    }
+
+    // ========================================================================
+    // Build Settings
+    // ========================================================================
+
+    /// XCConfigurationList of a target, or of the project when `target` is `None`
+    fn configuration_list_id(&self, target: Option<&str>) -> Result<String> {
+        let owner_id = match target {
+            Some(name) => {
+                self.find_target(name)
+                    .ok_or_else(|| Error::validation(format!("Target not found: {name}")))?
+                    .id
+            }
+            None => self
+                .document
+                .root_object_id()
+                .ok_or_else(|| Error::validation("Project has no root object"))?
+                .to_string(),
+        };
+        self.document
+            .object(&owner_id)
+            .and_then(|owner| owner.get_str("buildConfigurationList"))
+            .map(str::to_string)
+            .ok_or_else(|| Error::validation(format!("No build configurations for {owner_id}")))
+    }
+
+    /// Build configurations of a target, or of the project when `target` is `None`
+    pub fn build_configurations(&self, target: Option<&str>) -> Result<Vec<BuildConfiguration>> {
+        let list_id = self.configuration_list_id(target)?;
+        let ids = Self::id_list(
+            self.document
+                .object(&list_id)
+                .and_then(|list| list.get("buildConfigurations")),
+        );
+
+        Ok(ids
+            .into_iter()
+            .filter_map(|id| {
+                let config = self.document.object(&id)?;
+                let settings = config
+                    .get("buildSettings")
+                    .and_then(Value::as_dict)
+                    .map(|settings| {
+                        settings
+                            .iter()
+                            .map(|(key, value)| (key.to_string(), Self::setting_text(value)))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                Some(BuildConfiguration {
+                    name: config.get_str("name").unwrap_or_default().to_string(),
+                    target: target.map(str::to_string),
+                    settings,
+                    id,
+                })
+            })
+            .collect())
+    }
+
+    /// A setting value as text, with list items joined by spaces
+    fn setting_text(value: &Value) -> String {
+        match value.as_array() {
+            Some(items) => items.strs().collect::<Vec<_>>().join(" "),
+            None => value.as_str().unwrap_or_default().to_string(),
+        }
+    }
+
+    /// Read a build setting from one configuration
+    pub fn build_setting(
+        &self,
+        target: Option<&str>,
+        configuration: &str,
+        key: &str,
+    ) -> Result<Option<String>> {
+        let config = self
+            .build_configurations(target)?
+            .into_iter()
+            .find(|config| config.name == configuration)
+            .ok_or_else(|| Error::validation(format!("Configuration not found: {configuration}")))?;
+        Ok(config.settings.get(key).cloned())
+    }
+
+    /// Set a build setting in one configuration, or in all of them when
+    /// `configuration` is `None`
+    ///
+    /// Settings that are lists (`OTHER_SWIFT_FLAGS`) stay lists, split on
+    /// whitespace. Returns the names of the configurations that were updated.
+    pub fn set_build_setting(
+        &mut self,
+        target: Option<&str>,
+        configuration: Option<&str>,
+        key: &str,
+        value: &str,
+    ) -> Result<Vec<String>> {
+        let configs: Vec<BuildConfiguration> = self
+            .build_configurations(target)?
+            .into_iter()
+            .filter(|config| configuration.is_none_or(|name| config.name == name))
+            .collect();
+        if configs.is_empty() {
+            return Err(Error::validation(format!(
+                "Configuration not found: {}",
+                configuration.unwrap_or_default()
+            )));
+        }
+
+        for config in &configs {
+            let object = self
+                .document
+                .object_mut(&config.id)
+                .ok_or_else(|| Error::validation(format!("Object {} not found in project", config.id)))?;
+            if object.get("buildSettings").and_then(Value::as_dict).is_none() {
+                object.set("buildSettings", Value::dict::<&str>([]));
+            }
+            let settings = object
+                .get_mut("buildSettings")
+                .and_then(Value::as_dict_mut)
+                .expect("buildSettings was just set");
+
+            let new_value = if settings.get(key).and_then(Value::as_array).is_some() {
+                Value::array(value.split_whitespace().map(Value::string))
+            } else {
+                Value::string(value)
+            };
+            settings.set(key, new_value);
+        }
+        Ok(configs.into_iter().map(|config| config.name).collect())
+    }
+
+    // ========================================================================
+    // Repair
+    // ========================================================================
+
+    /// Keep only the first `id` entry in each list it is repeated in
+    fn dedupe_list_entries(&mut self, id: &str) {
+        for list in self.document.arrays_mut() {
+            let mut seen = false;
+            list.retain(|value| {
+                if value.as_str() != Some(id) {
+                    return true;
+                }
+                !std::mem::replace(&mut seen, true)
+            });
+        }
+    }
+
+    /// Repair the issues reported by [`XcodeProject::status`]
+    ///
+    /// Removes broken references, drops duplicate build files (keeping the
+    /// first), then adds Swift files found under `source_dir` but missing from
+    /// the target. Only the in-memory project is changed; call
+    /// [`XcodeProject::save`] to write it.
+    pub fn fix(&mut self, target_name: &str, source_dir: &str) -> Result<FixReport> {
+        if self.find_target(target_name).is_none() {
+            return Err(Error::validation(format!("Target not found: {target_name}")));
+        }
+        let original = self.content();
+        let mut report = FixReport::default();
+
+        let parents = self.parent_dirs();
+        for file_ref in self.find_broken_references() {
+            report
+                .removed_references
+                .push(Self::resolve_with(&file_ref, &parents).to_string_lossy().to_string());
+            self.remove_reference(&file_ref.id);
+        }
+
+        let parents = self.parent_dirs();
+        let file_refs: HashMap<String, FileReference> = self
+            .file_references()
+            .into_iter()
+            .map(|fr| (fr.id.clone(), fr))
+            .collect();
+        for (file_ref_id, build_files) in self.find_duplicate_build_files(target_name) {
+            let keep = &build_files[0].id;
+            let mut removed = HashSet::new();
+            for extra in &build_files[1..] {
+                if &extra.id != keep && removed.insert(extra.id.clone()) {
+                    self.remove_list_entries(&extra.id);
+                    self.remove_object(&extra.id);
+                }
+            }
+            self.dedupe_list_entries(keep);
+            report.deduplicated.push(match file_refs.get(&file_ref_id) {
+                Some(fr) => Self::resolve_with(fr, &parents).to_string_lossy().to_string(),
+                None => file_ref_id,
+            });
+        }
+
+        for path in self.find_missing_files(target_name, source_dir)? {
+            let relative = path
+                .strip_prefix(&self.project_dir)
+                .map(Path::to_path_buf)
+                .unwrap_or(path);
+            if !self.add_file(&relative, target_name, None)?.already_exists {
+                report.added_files.push(relative);
+            }
+        }
+
+        report.removed_references.sort();
+        report.deduplicated.sort();
+        report.diff = line_diff(&original, &self.content());
+        Ok(report)
+    }
+
     // ========================================================================
     // Save Operations
     // ========================================================================
@@ -930,12 +1248,125 @@ impl XcodeProject {
         fs::copy(&pbxproj_path, &backup_path)?;
 
         // Write updated content
-        fs::write(&pbxproj_path, &self.content)?;
+        fs::write(&pbxproj_path, self.content())?;
 
         Ok(())
     }
 }
 
+/// Changes made by [`XcodeProject::fix`]
+#[derive(Debug, Default)]
+pub struct FixReport {
+    /// Files added to the target, relative to the project directory
+    pub added_files: Vec<PathBuf>,
+    /// Paths of removed references whose files no longer exist
+    pub removed_references: Vec<String>,
+    /// Paths of files whose duplicate build files were removed
+    pub deduplicated: Vec<String>,
+    /// Removed (`-`) and added (`+`) lines of `project.pbxproj`
+    pub diff: Vec<String>,
+}
+
+impl FixReport {
+    /// Check if nothing needed fixing
+    pub fn is_empty(&self) -> bool {
+        self.added_files.is_empty()
+            && self.removed_references.is_empty()
+            && self.deduplicated.is_empty()
+    }
+
+    /// Print the changes, one file per line
+    pub fn print(&self) {
+        use owo_colors::OwoColorize;
+
+        for path in &self.removed_references {
+            textln!("  {} {} (broken reference)", "-".red(), path);
+        }
+        for path in &self.deduplicated {
+            textln!("  {} {} (duplicate build file)", "~".yellow(), path);
+        }
+        for path in &self.added_files {
+            textln!("  {} {}", "+".green(), path.display());
+        }
+        textln!();
+        textln!(
+            "Removed: {}, Deduplicated: {}, Added: {}",
+            self.removed_references.len(),
+            self.deduplicated.len(),
+            self.added_files.len()
+        );
+    }
+}
+
+/// Result of [`XcodeProject::resolve_conflicts`]
+#[derive(Debug)]
+pub struct ConflictReport {
+    /// Conflicting hunks in the file
+    pub conflicts: usize,
+    /// What the merge took from their side
+    pub merge: MergeReport,
+    /// Lines removed (`-`) and added (`+`) compared to our side
+    pub diff: Vec<String>,
+}
+
+impl ConflictReport {
+    /// Print the merged objects and the properties that need a look
+    pub fn print(&self) {
+        use owo_colors::OwoColorize;
+
+        for name in &self.merge.added {
+            textln!("  {} {}", "+".green(), name);
+        }
+        for name in &self.merge.deduplicated {
+            textln!("  {} {} (added on both sides)", "~".yellow(), name);
+        }
+        for conflict in &self.merge.conflicts {
+            textln!("  {} {}", "!".red(), conflict);
+        }
+        textln!();
+        textln!(
+            "Conflicts: {}, Added: {}, Deduplicated: {}, Kept ours: {}",
+            self.conflicts,
+            self.merge.added.len(),
+            self.merge.deduplicated.len(),
+            self.merge.conflicts.len()
+        );
+    }
+}
+
+/// Lines removed from `old` and added in `new`, each in file order
+///
+/// Project edits insert and delete whole lines, so comparing line counts is
+/// enough to summarize them without a full diff.
+fn line_diff(old: &str, new: &str) -> Vec<String> {
+    let mut counts: HashMap<&str, isize> = HashMap::new();
+    for line in old.lines() {
+        *counts.entry(line).or_default() += 1;
+    }
+    for line in new.lines() {
+        *counts.entry(line).or_default() -= 1;
+    }
+
+    let mut removed = counts.clone();
+    let mut diff: Vec<String> = old
+        .lines()
+        .filter(|line| {
+            let count = removed.get_mut(line).unwrap();
+            (*count > 0).then(|| *count -= 1).is_some()
+        })
+        .map(|line| format!("- {}", line.trim()))
+        .collect();
+    diff.extend(
+        new.lines()
+            .filter(|line| {
+                let count = counts.get_mut(line).unwrap();
+                (*count < 0).then(|| *count += 1).is_some()
+            })
+            .map(|line| format!("+ {}", line.trim())),
+    );
+    diff
+}
+
 /// Project status summary
 #[derive(Debug)]
 pub struct ProjectStatus {
@@ -955,46 +1386,464 @@ impl ProjectStatus {
     pub fn print(&self) {
         use owo_colors::OwoColorize;
 
-        println!("{}", "Xcode Project Status".bold());
-        println!();
-        println!("  Total build files: {}", self.total_build_files);
+        textln!("{}", "Xcode Project Status".bold());
+        textln!();
+        textln!("  Total build files: {}", self.total_build_files);
 
         if self.missing_files > 0 {
-            println!(
+            textln!(
                 "  {} Missing files: {}",
                 "⚠".yellow(),
                 self.missing_files
             );
         } else {
-            println!("  {} No missing files", "✓".green());
+            textln!("  {} No missing files", "✓".green());
         }
 
         if self.broken_references > 0 {
-            println!(
+            textln!(
                 "  {} Broken references: {}",
                 "⚠".yellow(),
                 self.broken_references
             );
         } else {
-            println!("  {} No broken references", "✓".green());
+            textln!("  {} No broken references", "✓".green());
         }
 
         if self.duplicate_references > 0 {
-            println!(
+            textln!(
                 "  {} Duplicate references: {}",
                 "⚠".yellow(),
                 self.duplicate_references
             );
         } else {
-            println!("  {} No duplicates", "✓".green());
+            textln!("  {} No duplicates", "✓".green());
         }
     }
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
 
+    /// Minimal app project: FoodShare/App.swift and FoodShare/Features/Feed.swift
+    pub(crate) const FIXTURE: &str = r#"// !$*UTF8*$!
+{
+	archiveVersion = 1;
+	classes = {
+	};
+	objectVersion = 56;
+	objects = {
+
+/* Begin PBXBuildFile section */
+		A10000000000000000000001 /* App.swift in Sources */ = {isa = PBXBuildFile; fileRef = B10000000000000000000001 /* App.swift */; };
+		A10000000000000000000002 /* Feed.swift in Sources */ = {isa = PBXBuildFile; fileRef = B10000000000000000000002 /* Feed.swift */; };
+/* End PBXBuildFile section */
+
+/* Begin PBXFileReference section */
+		B10000000000000000000001 /* App.swift */ = {isa = PBXFileReference; lastKnownFileType = sourcecode.swift; path = App.swift; sourceTree = "<group>"; };
+		B10000000000000000000002 /* Feed.swift */ = {isa = PBXFileReference; lastKnownFileType = sourcecode.swift; path = Feed.swift; sourceTree = "<group>"; };
+		B10000000000000000000003 /* FoodShare.app */ = {isa = PBXFileReference; explicitFileType = wrapper.application; includeInIndex = 0; path = FoodShare.app; sourceTree = BUILT_PRODUCTS_DIR; };
+/* End PBXFileReference section */
+
+/* Begin PBXGroup section */
+		C10000000000000000000001 = {
+			isa = PBXGroup;
+			children = (
+				C10000000000000000000002 /* FoodShare */,
+				C10000000000000000000004 /* Products */,
+			);
+			sourceTree = "<group>";
+		};
+		C10000000000000000000002 /* FoodShare */ = {
+			isa = PBXGroup;
+			children = (
+				B10000000000000000000001 /* App.swift */,
+				C10000000000000000000003 /* Features */,
+			);
+			path = FoodShare;
+			sourceTree = "<group>";
+		};
+		C10000000000000000000003 /* Features */ = {
+			isa = PBXGroup;
+			children = (
+				B10000000000000000000002 /* Feed.swift */,
+			);
+			path = Features;
+			sourceTree = "<group>";
+		};
+		C10000000000000000000004 /* Products */ = {
+			isa = PBXGroup;
+			children = (
+				B10000000000000000000003 /* FoodShare.app */,
+			);
+			name = Products;
+			sourceTree = "<group>";
+		};
+/* End PBXGroup section */
+
+/* Begin PBXNativeTarget section */
+		D10000000000000000000001 /* FoodShare */ = {
+			isa = PBXNativeTarget;
+			buildConfigurationList = F10000000000000000000002 /* Build configuration list for PBXNativeTarget "FoodShare" */;
+			buildPhases = (
+				E10000000000000000000001 /* Sources */,
+			);
+			buildRules = (
+			);
+			dependencies = (
+			);
+			name = FoodShare;
+			productName = FoodShare;
+			productReference = B10000000000000000000003 /* FoodShare.app */;
+			productType = "com.apple.product-type.application";
+		};
+/* End PBXNativeTarget section */
+
+/* Begin PBXProject section */
+		0A0000000000000000000001 /* Project object */ = {
+			isa = PBXProject;
+			buildConfigurationList = F10000000000000000000001 /* Build configuration list for PBXProject "FoodShare" */;
+			mainGroup = C10000000000000000000001;
+			productRefGroup = C10000000000000000000004 /* Products */;
+			projectDirPath = "";
+			projectRoot = "";
+			targets = (
+				D10000000000000000000001 /* FoodShare */,
+			);
+		};
+/* End PBXProject section */
+
+/* Begin PBXSourcesBuildPhase section */
+		E10000000000000000000001 /* Sources */ = {
+			isa = PBXSourcesBuildPhase;
+			buildActionMask = 2147483647;
+			files = (
+				A10000000000000000000001 /* App.swift in Sources */,
+				A10000000000000000000002 /* Feed.swift in Sources */,
+			);
+			runOnlyForDeploymentInstallationPhase = 0;
+		};
+/* End PBXSourcesBuildPhase section */
+
+/* Begin XCBuildConfiguration section */
+		F20000000000000000000001 /* Debug */ = {
+			isa = XCBuildConfiguration;
+			buildSettings = {
+				IPHONEOS_DEPLOYMENT_TARGET = 17.0;
+				SDKROOT = iphoneos;
+			};
+			name = Debug;
+		};
+		F20000000000000000000002 /* Release */ = {
+			isa = XCBuildConfiguration;
+			buildSettings = {
+				IPHONEOS_DEPLOYMENT_TARGET = 17.0;
+				SDKROOT = iphoneos;
+			};
+			name = Release;
+		};
+		F20000000000000000000003 /* Debug */ = {
+			isa = XCBuildConfiguration;
+			buildSettings = {
+				CODE_SIGN_STYLE = Automatic;
+				PRODUCT_BUNDLE_IDENTIFIER = club.foodshare.app;
+				SWIFT_VERSION = 5.0;
+			};
+			name = Debug;
+		};
+		F20000000000000000000004 /* Release */ = {
+			isa = XCBuildConfiguration;
+			buildSettings = {
+				CODE_SIGN_STYLE = Automatic;
+				PRODUCT_BUNDLE_IDENTIFIER = club.foodshare.app;
+				SWIFT_VERSION = 5.0;
+			};
+			name = Release;
+		};
+/* End XCBuildConfiguration section */
+
+/* Begin XCConfigurationList section */
+		F10000000000000000000001 /* Build configuration list for PBXProject "FoodShare" */ = {
+			isa = XCConfigurationList;
+			buildConfigurations = (
+				F20000000000000000000001 /* Debug */,
+				F20000000000000000000002 /* Release */,
+			);
+			defaultConfigurationIsVisible = 0;
+			defaultConfigurationName = Release;
+		};
+		F10000000000000000000002 /* Build configuration list for PBXNativeTarget "FoodShare" */ = {
+			isa = XCConfigurationList;
+			buildConfigurations = (
+				F20000000000000000000003 /* Debug */,
+				F20000000000000000000004 /* Release */,
+			);
+			defaultConfigurationIsVisible = 0;
+			defaultConfigurationName = Release;
+		};
+/* End XCConfigurationList section */
+	};
+	rootObject = 0A0000000000000000000001 /* Project object */;
+}
+"#;
+
+    /// Write the fixture (and its source files) to a temp dir and open it
+    fn open_fixture() -> (tempfile::TempDir, XcodeProject) {
+        let dir = tempfile::tempdir().unwrap();
+        let xcodeproj = dir.path().join("FoodShare.xcodeproj");
+        fs::create_dir_all(&xcodeproj).unwrap();
+        fs::write(xcodeproj.join("project.pbxproj"), FIXTURE).unwrap();
+        fs::create_dir_all(dir.path().join("FoodShare/Features")).unwrap();
+        fs::write(dir.path().join("FoodShare/App.swift"), "").unwrap();
+        fs::write(dir.path().join("FoodShare/Features/Feed.swift"), "").unwrap();
+
+        let project = XcodeProject::open(&xcodeproj).unwrap();
+        (dir, project)
+    }
+
+    #[test]
+    fn test_resolve_group_relative_paths() {
+        let (_dir, project) = open_fixture();
+        let refs = project.find_file_references(Path::new("FoodShare/Features/Feed.swift"));
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].id, "B10000000000000000000002");
+        assert!(project.find_broken_references().is_empty());
+    }
+
+    #[test]
+    fn test_add_file() {
+        let (dir, mut project) = open_fixture();
+        assert_eq!(project.content(), FIXTURE);
+        fs::write(dir.path().join("FoodShare/Features/Profile.swift"), "").unwrap();
+
+        let result = project
+            .add_file(Path::new("FoodShare/Features/Profile.swift"), "FoodShare", None)
+            .unwrap();
+        assert_eq!(result.group_id, "C10000000000000000000003");
+        let build_file_id = result.build_file_id.unwrap();
+
+        // Only the four new lines are added; everything else is untouched
+        let content = project.content();
+        assert_eq!(content.lines().count(), FIXTURE.lines().count() + 4);
+        assert!(content.contains(&format!(
+            "\t\t\t\tA10000000000000000000002 /* Feed.swift in Sources */,\n\t\t\t\t{build_file_id} /* Profile.swift in Sources */,\n\t\t\t);"
+        )));
+
+        project.save().unwrap();
+        let reopened = XcodeProject::open(&project.path).unwrap();
+        assert!(reopened
+            .swift_files_in_build("FoodShare")
+            .contains(&PathBuf::from("FoodShare/Features/Profile.swift")));
+        assert!(reopened.find_broken_references().is_empty());
+    }
+
+    #[test]
+    fn test_add_file_creates_nested_groups() {
+        let (dir, mut project) = open_fixture();
+        let file = Path::new("FoodShare/Features/Profile/Views/ProfileView.swift");
+        fs::create_dir_all(dir.path().join(file.parent().unwrap())).unwrap();
+        fs::write(dir.path().join(file), "").unwrap();
+
+        let result = project
+            .add_file(file, "FoodShare", Some("FoodShare/Features/Profile/Views"))
+            .unwrap();
+        let views = project.find_group_by_path("FoodShare/Features/Profile/Views").unwrap();
+        assert_eq!(result.group_id, views.id);
+        assert_eq!(views.path.as_deref(), Some("Views"));
+        assert_eq!(views.children, std::slice::from_ref(&result.file_ref_id));
+
+        // Existing groups are reused, and the new ones sit in the Features group
+        let profile = project.find_group_by_path("FoodShare/Features/Profile").unwrap();
+        assert_eq!(profile.children, std::slice::from_ref(&views.id));
+        let features = project.find_group_by_path("FoodShare/Features").unwrap();
+        assert_eq!(features.id, "C10000000000000000000003");
+        assert!(features.children.contains(&profile.id));
+        assert_eq!(project.groups().len(), 6);
+
+        assert!(project.content().contains(&format!(
+            "\t\t{} /* Views */ = {{\n\t\t\tisa = PBXGroup;\n\t\t\tchildren = (\n\t\t\t\t{} /* ProfileView.swift */,\n\t\t\t);\n\t\t\tpath = Views;\n\t\t\tsourceTree = \"<group>\";\n\t\t}};",
+            views.id, result.file_ref_id
+        )));
+        assert_eq!(
+            project
+                .find_or_create_group_path("FoodShare/Features/Profile/Views")
+                .unwrap(),
+            views.id
+        );
+    }
+
+    #[test]
+    fn test_remove_file() {
+        let (_dir, mut project) = open_fixture();
+        let result = project.remove_file(Path::new("FoodShare/Features/Feed.swift")).unwrap();
+        assert_eq!(result.file_ref_ids, ["B10000000000000000000002"]);
+        assert_eq!(result.build_file_ids, ["A10000000000000000000002"]);
+
+        assert!(!project.content().contains("B10000000000000000000002"));
+        assert!(!project.content().contains("A10000000000000000000002"));
+        assert!(project.content().contains("A10000000000000000000001 /* App.swift in Sources */,"));
+
+        // The project still parses and no longer lists the file
+        project.save().unwrap();
+        let mut reopened = XcodeProject::open(&project.path).unwrap();
+        assert_eq!(reopened.swift_files_in_build("FoodShare"), [PathBuf::from("App.swift")]);
+        assert!(reopened.remove_file(Path::new("FoodShare/Features/Feed.swift")).is_err());
+    }
+
+    #[test]
+    fn test_rename_file() {
+        let (_dir, mut project) = open_fixture();
+        project
+            .rename_file(Path::new("FoodShare/App.swift"), Path::new("FoodShare/FoodShareApp.swift"))
+            .unwrap();
+
+        assert!(project.content().contains(
+            "B10000000000000000000001 /* FoodShareApp.swift */ = {isa = PBXFileReference; lastKnownFileType = sourcecode.swift; path = FoodShareApp.swift; sourceTree = \"<group>\"; };"
+        ));
+        assert!(project.content().contains("A10000000000000000000001 /* FoodShareApp.swift in Sources */"));
+        assert!(project.content().contains("fileRef = B10000000000000000000001 /* FoodShareApp.swift */"));
+        assert!(!project.content().contains("/* App.swift"));
+    }
+
+    #[test]
+    fn test_move_file_to_group() {
+        let (_dir, mut project) = open_fixture();
+        let group_id = project
+            .move_file_to_group(Path::new("FoodShare/Features/Feed.swift"), "FoodShare")
+            .unwrap();
+        assert_eq!(group_id, "C10000000000000000000002");
+
+        let groups = project.groups();
+        let features = groups.iter().find(|g| g.id == "C10000000000000000000003").unwrap();
+        assert!(features.children.is_empty());
+        let app = groups.iter().find(|g| g.id == group_id).unwrap();
+        assert!(app.children.contains(&"B10000000000000000000002".to_string()));
+
+        // Re-anchored so it still points at the same file
+        assert!(project
+            .content()
+            .contains("path = FoodShare/Features/Feed.swift; sourceTree = SOURCE_ROOT;"));
+    }
+
+    #[test]
+    fn test_fix() {
+        let (dir, mut project) = open_fixture();
+        fs::remove_file(dir.path().join("FoodShare/Features/Feed.swift")).unwrap();
+        fs::write(dir.path().join("FoodShare/Features/Profile.swift"), "").unwrap();
+        project
+            .add_file_to_build_phase("E10000000000000000000001", "A10000000000000000000001", "App.swift")
+            .unwrap();
+
+        let report = project.fix("FoodShare", "FoodShare").unwrap();
+        assert_eq!(report.removed_references, ["FoodShare/Features/Feed.swift"]);
+        assert_eq!(report.deduplicated, ["FoodShare/App.swift"]);
+        assert_eq!(report.added_files, [PathBuf::from("FoodShare/Features/Profile.swift")]);
+        assert!(report.diff.contains(&"- A10000000000000000000001 /* App.swift in Sources */,".to_string()));
+        assert!(report.diff.iter().any(|line| line.starts_with("+ ") && line.contains("Profile.swift")));
+
+        let status = project.status("FoodShare", "FoodShare").unwrap();
+        assert!(status.is_clean());
+        assert!(project.fix("FoodShare", "FoodShare").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_resolve_conflicts() {
+        let (_dir, project) = open_fixture();
+        assert!(XcodeProject::resolve_conflicts(&project.path).unwrap().is_none());
+
+        // Each branch added a file to Features and to the Sources phase
+        let conflict = |after: &str, ours: String, theirs: String| {
+            format!("{after}<<<<<<< HEAD\n{ours}=======\n{theirs}>>>>>>> feature\n")
+        };
+        let build_file = |id: &str, file: &str, name: &str| {
+            format!("\t\t{id} /* {name} in Sources */ = {{isa = PBXBuildFile; fileRef = {file} /* {name} */; }};\n")
+        };
+        let file_ref = |id: &str, name: &str| {
+            format!("\t\t{id} /* {name} */ = {{isa = PBXFileReference; lastKnownFileType = sourcecode.swift; path = {name}; sourceTree = \"<group>\"; }};\n")
+        };
+        let mut content = FIXTURE.to_string();
+        for (after, ours, theirs) in [
+            (
+                build_file("A10000000000000000000002", "B10000000000000000000002", "Feed.swift"),
+                build_file("A10000000000000000000003", "B10000000000000000000004", "Profile.swift"),
+                build_file("A10000000000000000000004", "B10000000000000000000005", "Map.swift"),
+            ),
+            (
+                file_ref("B10000000000000000000002", "Feed.swift"),
+                file_ref("B10000000000000000000004", "Profile.swift"),
+                file_ref("B10000000000000000000005", "Map.swift"),
+            ),
+            (
+                "\t\t\t\tB10000000000000000000002 /* Feed.swift */,\n".to_string(),
+                "\t\t\t\tB10000000000000000000004 /* Profile.swift */,\n".to_string(),
+                "\t\t\t\tB10000000000000000000005 /* Map.swift */,\n".to_string(),
+            ),
+            (
+                "\t\t\t\tA10000000000000000000002 /* Feed.swift in Sources */,\n".to_string(),
+                "\t\t\t\tA10000000000000000000003 /* Profile.swift in Sources */,\n".to_string(),
+                "\t\t\t\tA10000000000000000000004 /* Map.swift in Sources */,\n".to_string(),
+            ),
+        ] {
+            content = content.replacen(&after, &conflict(&after, ours, theirs), 1);
+        }
+        fs::write(project.path.join("project.pbxproj"), content).unwrap();
+
+        let (merged, report) = XcodeProject::resolve_conflicts(&project.path).unwrap().unwrap();
+        assert_eq!(report.conflicts, 4);
+        assert_eq!(report.merge.added, ["Map.swift in Sources", "Map.swift"]);
+        assert!(report.merge.conflicts.is_empty());
+        assert_eq!(report.diff.len(), 4);
+
+        merged.save().unwrap();
+        let reopened = XcodeProject::open(&project.path).unwrap();
+        let mut files = reopened.swift_files_in_build("FoodShare");
+        files.sort();
+        assert_eq!(files, ["App.swift", "Feed.swift", "Map.swift", "Profile.swift"].map(PathBuf::from));
+    }
+
+    #[test]
+    fn test_build_settings() {
+        let (_dir, mut project) = open_fixture();
+        let configs = project.build_configurations(Some("FoodShare")).unwrap();
+        assert_eq!(
+            configs.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(),
+            ["Debug", "Release"]
+        );
+        assert_eq!(configs[0].settings["SWIFT_VERSION"], "5.0");
+        assert_eq!(
+            project.build_setting(None, "Release", "IPHONEOS_DEPLOYMENT_TARGET").unwrap().as_deref(),
+            Some("17.0")
+        );
+        assert!(project.build_setting(Some("Missing"), "Debug", "SWIFT_VERSION").is_err());
+
+        let updated = project
+            .set_build_setting(Some("FoodShare"), None, "SWIFT_VERSION", "6.0")
+            .unwrap();
+        assert_eq!(updated, ["Debug", "Release"]);
+        project
+            .set_build_setting(Some("FoodShare"), Some("Release"), "CODE_SIGN_IDENTITY", "Apple Distribution")
+            .unwrap();
+        assert!(project
+            .set_build_setting(None, Some("Beta"), "SWIFT_VERSION", "6.0")
+            .is_err());
+
+        // New keys are inserted in sorted order, in Xcode's layout
+        assert!(project.content().contains(
+            "\t\t\t\tCODE_SIGN_IDENTITY = \"Apple Distribution\";\n\t\t\t\tCODE_SIGN_STYLE = Automatic;\n\t\t\t\tPRODUCT_BUNDLE_IDENTIFIER = club.foodshare.app;\n\t\t\t\tSWIFT_VERSION = 6.0;"
+        ));
+
+        let configs = project.build_configurations(Some("FoodShare")).unwrap();
+        assert_eq!(
+            configs[0].diff(&configs[1]),
+            [SettingDiff {
+                key: "CODE_SIGN_IDENTITY".to_string(),
+                left: None,
+                right: Some("Apple Distribution".to_string()),
+            }]
+        );
+    }
+
     #[test]
     fn test_project_status_is_clean() {
         let status = ProjectStatus {
@@ -1087,41 +1936,4 @@ mod tests {
         assert!(!FileType::Framework.should_add_to_sources());
         assert!(!FileType::Unknown.should_add_to_sources());
     }
-
-    #[test]
-    fn test_quote_if_needed_simple() {
-        assert_eq!(XcodeProject::quote_if_needed("simple"), "simple");
-    }
-
-    #[test]
-    fn test_quote_if_needed_with_spaces() {
-        assert_eq!(
-            XcodeProject::quote_if_needed("path with spaces"),
-            "\"path with spaces\""
-        );
-    }
-
-    #[test]
-    fn test_quote_if_needed_with_slashes() {
-        assert_eq!(
-            XcodeProject::quote_if_needed("FoodShare/Core/File.swift"),
-            "\"FoodShare/Core/File.swift\""
-        );
-    }
-
-    #[test]
-    fn test_quote_if_needed_with_dots() {
-        assert_eq!(
-            XcodeProject::quote_if_needed("file.swift"),
-            "\"file.swift\""
-        );
-    }
-
-    #[test]
-    fn test_quote_if_needed_with_dashes() {
-        assert_eq!(
-            XcodeProject::quote_if_needed("my-file"),
-            "\"my-file\""
-        );
-    }
 }
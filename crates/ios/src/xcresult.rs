@@ -0,0 +1,703 @@
+//! Xcode result bundle parsing
+//!
+//! `xcodebuild -resultBundlePath` writes an `.xcresult` bundle holding the
+//! test results, code coverage and build issues of a run. [`XcResult::load`]
+//! reads it through `xcrun xcresulttool` (tests and build issues) and
+//! `xcrun xccov` (coverage), which both print JSON.
+
+use foodshare_cli::textln;
+use foodshare_core::error::{Error, Result};
+use foodshare_core::process::run_command;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Outcome of a single test
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TestStatus {
+    /// Test passed
+    Passed,
+    /// Test failed
+    Failed,
+    /// Test was skipped
+    Skipped,
+    /// Test failed inside `XCTExpectFailure` or `withKnownIssue`
+    ExpectedFailure,
+}
+
+impl TestStatus {
+    fn parse(result: &str) -> Option<Self> {
+        match result {
+            "Passed" => Some(Self::Passed),
+            "Failed" => Some(Self::Failed),
+            "Skipped" => Some(Self::Skipped),
+            "Expected Failure" => Some(Self::ExpectedFailure),
+            _ => None,
+        }
+    }
+}
+
+/// A failed assertion or thrown error
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestFailure {
+    /// Failure message
+    pub message: String,
+    /// Source file name, when the message has a location
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Line in `file`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+}
+
+impl TestFailure {
+    /// Parse an xcresulttool failure message (`LoginTests.swift:42: XCTAssertTrue failed`)
+    fn parse(text: &str) -> Self {
+        let location = text.split_once(": ").and_then(|(location, message)| {
+            let (file, line) = location.rsplit_once(':')?;
+            Some((file, line.parse().ok()?, message))
+        });
+        match location {
+            Some((file, line, message)) => Self {
+                message: message.to_string(),
+                file: Some(file.to_string()),
+                line: Some(line),
+            },
+            None => Self {
+                message: text.to_string(),
+                file: None,
+                line: None,
+            },
+        }
+    }
+}
+
+/// A test case and its outcome
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestCase {
+    /// Identifier accepted by `xcodebuild -only-testing` (`LoginTests/testLogin()`)
+    pub identifier: String,
+    /// Test function name
+    pub name: String,
+    /// Enclosing test class or suite
+    pub suite: String,
+    /// Test bundle (`FoodShareTests`)
+    pub bundle: String,
+    /// Outcome
+    pub status: TestStatus,
+    /// Duration in seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<f64>,
+    /// Failure messages, empty unless the test failed
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<TestFailure>,
+    /// Outcome of each attempt, when the run retried failing tests
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attempts: Vec<TestStatus>,
+}
+
+impl TestCase {
+    /// Check if the test failed and then passed on a retry
+    pub fn passed_on_retry(&self) -> bool {
+        self.status == TestStatus::Passed && self.attempts.contains(&TestStatus::Failed)
+    }
+}
+
+/// A compiler warning or error recorded in the bundle
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildIssue {
+    /// Issue type (`Swift Compiler Warning`)
+    pub kind: String,
+    /// Issue message
+    pub message: String,
+    /// Target the issue belongs to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Source file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<PathBuf>,
+    /// Line in `file`, starting at 1
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+}
+
+/// Line coverage of a run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Coverage {
+    /// Fraction of executable lines covered, from 0 to 1
+    pub line_coverage: f64,
+    /// Covered lines
+    pub covered_lines: u64,
+    /// Executable lines
+    pub executable_lines: u64,
+    /// Coverage of each target
+    pub targets: Vec<TargetCoverage>,
+}
+
+/// Line coverage of a single target
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TargetCoverage {
+    /// Product name (`FoodShare.app`)
+    pub name: String,
+    /// Fraction of executable lines covered, from 0 to 1
+    pub line_coverage: f64,
+    /// Covered lines
+    pub covered_lines: u64,
+    /// Executable lines
+    pub executable_lines: u64,
+}
+
+/// Test counts of a run
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TestSummary {
+    /// Number of tests
+    pub total: usize,
+    /// Passed tests
+    pub passed: usize,
+    /// Failed tests
+    pub failed: usize,
+    /// Skipped tests
+    pub skipped: usize,
+    /// Tests that failed as expected
+    pub expected_failures: usize,
+    /// Sum of the test durations in seconds
+    pub duration: f64,
+}
+
+/// Contents of an `.xcresult` bundle
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct XcResult {
+    /// Path of the bundle
+    pub path: PathBuf,
+    /// Test counts
+    pub summary: TestSummary,
+    /// Test cases in the order they appear in the bundle
+    pub tests: Vec<TestCase>,
+    /// Build warnings
+    pub warnings: Vec<BuildIssue>,
+    /// Build errors
+    pub errors: Vec<BuildIssue>,
+    /// Line coverage, if the tests ran with coverage enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coverage: Option<Coverage>,
+}
+
+impl XcResult {
+    /// Load a result bundle written by `xcodebuild -resultBundlePath`
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Err(Error::file_not_found(path));
+        }
+        let bundle = path.to_string_lossy();
+
+        let tests = xcrun(&["xcresulttool", "get", "test-results", "tests", "--path", &bundle])?;
+        let build = xcrun(&["xcresulttool", "get", "build-results", "--path", &bundle])?;
+        // Bundles of runs without coverage have no report, which is not an error
+        let coverage = xcrun(&["xccov", "view", "--report", "--json", &bundle])
+            .ok()
+            .and_then(|json| parse_coverage(&json).ok());
+
+        let tests = parse_tests(&tests)?;
+        let (warnings, errors) = parse_build_results(&build)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            summary: TestSummary::from_tests(&tests),
+            tests,
+            warnings,
+            errors,
+            coverage,
+        })
+    }
+
+    /// Write the results as pretty-printed JSON, creating parent directories
+    pub fn write_json(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Tests that failed
+    pub fn failed_tests(&self) -> impl Iterator<Item = &TestCase> {
+        self.tests.iter().filter(|t| t.status == TestStatus::Failed)
+    }
+
+    /// Print one line per test, followed by failures and totals
+    pub fn print(&self) {
+        use owo_colors::OwoColorize;
+
+        let mut suite = None;
+        for test in &self.tests {
+            if suite != Some(&test.suite) {
+                textln!("  {}", test.suite.bold());
+                suite = Some(&test.suite);
+            }
+            let marker = match test.status {
+                TestStatus::Passed => "✓".green().to_string(),
+                TestStatus::Failed => "✗".red().to_string(),
+                TestStatus::Skipped => "○".dimmed().to_string(),
+                TestStatus::ExpectedFailure => "✓".yellow().to_string(),
+            };
+            let retried = if test.passed_on_retry() {
+                format!(" {}", format!("passed on attempt {}", test.attempts.len()).yellow())
+            } else {
+                String::new()
+            };
+            match test.duration {
+                Some(duration) => textln!("    {} {} ({:.2}s){}", marker, test.name, duration, retried),
+                None => textln!("    {} {}{}", marker, test.name, retried),
+            }
+        }
+
+        let failed: Vec<&TestCase> = self.failed_tests().collect();
+        if !failed.is_empty() {
+            textln!();
+            textln!("{}", "Failures:".red().bold());
+            for test in failed {
+                textln!("  {}", test.identifier);
+                for failure in &test.failures {
+                    match (&failure.file, failure.line) {
+                        (Some(file), Some(line)) => {
+                            textln!("    {}:{}: {}", file, line, failure.message);
+                        }
+                        _ => textln!("    {}", failure.message),
+                    }
+                }
+            }
+        }
+
+        if !self.errors.is_empty() || !self.warnings.is_empty() {
+            textln!();
+            textln!(
+                "Build: {} errors, {} warnings",
+                self.errors.len(),
+                self.warnings.len()
+            );
+        }
+        if let Some(ref coverage) = self.coverage {
+            textln!("Coverage: {:.1}%", coverage.line_coverage * 100.0);
+        }
+
+        let s = &self.summary;
+        textln!();
+        textln!(
+            "Tests: {} passed, {} failed, {} skipped ({} total, {:.2}s)",
+            s.passed, s.failed, s.skipped, s.total, s.duration
+        );
+    }
+}
+
+impl TestSummary {
+    /// Count the outcomes of `tests`
+    pub fn from_tests(tests: &[TestCase]) -> Self {
+        let mut summary = Self::default();
+        for test in tests {
+            summary.total += 1;
+            summary.duration += test.duration.unwrap_or(0.0);
+            match test.status {
+                TestStatus::Passed => summary.passed += 1,
+                TestStatus::Failed => summary.failed += 1,
+                TestStatus::Skipped => summary.skipped += 1,
+                TestStatus::ExpectedFailure => summary.expected_failures += 1,
+            }
+        }
+        summary
+    }
+}
+
+/// Run an `xcrun` tool and return its standard output
+fn xcrun(args: &[&str]) -> Result<String> {
+    let result = run_command("xcrun", args)?;
+    if !result.success {
+        return Err(Error::process(format!(
+            "xcrun {} failed: {}",
+            args[0],
+            result.stderr.trim()
+        )));
+    }
+    Ok(result.stdout)
+}
+
+// ============================================================================
+// JSON
+// ============================================================================
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTests {
+    #[serde(default)]
+    test_nodes: Vec<RawNode>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawNode {
+    name: String,
+    node_type: String,
+    node_identifier: Option<String>,
+    result: Option<String>,
+    duration: Option<String>,
+    duration_in_seconds: Option<f64>,
+    #[serde(default)]
+    children: Vec<RawNode>,
+}
+
+/// Parse `xcresulttool get test-results tests` output
+pub fn parse_tests(json: &str) -> Result<Vec<TestCase>> {
+    let raw: RawTests = serde_json::from_str(json)?;
+    let mut tests = Vec::new();
+    for node in &raw.test_nodes {
+        collect_tests(node, "", "", &mut tests);
+    }
+    Ok(tests)
+}
+
+fn collect_tests(node: &RawNode, bundle: &str, suite: &str, tests: &mut Vec<TestCase>) {
+    match node.node_type.as_str() {
+        "Unit test bundle" | "UI test bundle" => {
+            for child in &node.children {
+                collect_tests(child, &node.name, suite, tests);
+            }
+        }
+        "Test Suite" => {
+            for child in &node.children {
+                collect_tests(child, bundle, &node.name, tests);
+            }
+        }
+        "Test Case" => {
+            let Some(status) = node.result.as_deref().and_then(TestStatus::parse) else {
+                return;
+            };
+            let mut failures = Vec::new();
+            collect_failures(node, &mut failures);
+            let attempts = node
+                .children
+                .iter()
+                .filter(|child| child.node_type == "Repetition")
+                .filter_map(|child| child.result.as_deref().and_then(TestStatus::parse))
+                .collect();
+            tests.push(TestCase {
+                identifier: node
+                    .node_identifier
+                    .clone()
+                    .unwrap_or_else(|| format!("{}/{}", suite, node.name)),
+                name: node.name.clone(),
+                suite: suite.to_string(),
+                bundle: bundle.to_string(),
+                status,
+                duration: node
+                    .duration_in_seconds
+                    .or_else(|| node.duration.as_deref().and_then(parse_duration)),
+                failures,
+                attempts,
+            });
+        }
+        _ => {
+            for child in &node.children {
+                collect_tests(child, bundle, suite, tests);
+            }
+        }
+    }
+}
+
+/// Failure messages anywhere below a test case, including repetitions and
+/// parameterized arguments
+fn collect_failures(node: &RawNode, failures: &mut Vec<TestFailure>) {
+    for child in &node.children {
+        if child.node_type == "Failure Message" {
+            failures.push(TestFailure::parse(&child.name));
+        } else {
+            collect_failures(child, failures);
+        }
+    }
+}
+
+/// Parse a duration like `0.12s` or `1m 5s` into seconds
+fn parse_duration(text: &str) -> Option<f64> {
+    let mut seconds = 0.0;
+    for part in text.split_whitespace() {
+        let part = part.replace(',', ".");
+        seconds += if let Some(minutes) = part.strip_suffix('m') {
+            minutes.parse::<f64>().ok()? * 60.0
+        } else {
+            part.strip_suffix('s')?.parse::<f64>().ok()?
+        };
+    }
+    Some(seconds)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawBuildResults {
+    #[serde(default)]
+    warnings: Vec<RawIssue>,
+    #[serde(default)]
+    errors: Vec<RawIssue>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawIssue {
+    issue_type: String,
+    message: String,
+    target_name: Option<String>,
+    #[serde(rename = "sourceURL")]
+    source_url: Option<String>,
+}
+
+impl From<RawIssue> for BuildIssue {
+    fn from(raw: RawIssue) -> Self {
+        let (file, line) = raw
+            .source_url
+            .as_deref()
+            .map(parse_source_url)
+            .unwrap_or_default();
+        Self {
+            kind: raw.issue_type,
+            message: raw.message,
+            target: raw.target_name,
+            file,
+            line,
+        }
+    }
+}
+
+/// Parse `xcresulttool get build-results` output into warnings and errors
+pub fn parse_build_results(json: &str) -> Result<(Vec<BuildIssue>, Vec<BuildIssue>)> {
+    let raw: RawBuildResults = serde_json::from_str(json)?;
+    Ok((
+        raw.warnings.into_iter().map(BuildIssue::from).collect(),
+        raw.errors.into_iter().map(BuildIssue::from).collect(),
+    ))
+}
+
+/// Split a source URL (`file:///a/B.swift#StartingLineNumber=41&...`) into
+/// the file and its line
+///
+/// Xcode counts lines from zero in these URLs.
+fn parse_source_url(url: &str) -> (Option<PathBuf>, Option<u32>) {
+    let (path, fragment) = url.split_once('#').unwrap_or((url, ""));
+    let file = path
+        .strip_prefix("file://")
+        .map(|path| PathBuf::from(percent_decode(path)));
+    let line = fragment
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("StartingLineNumber="))
+        .and_then(|line| line.parse::<u32>().ok())
+        .map(|line| line + 1);
+    (file, line)
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| text.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawCoverage {
+    line_coverage: f64,
+    covered_lines: u64,
+    executable_lines: u64,
+    #[serde(default)]
+    targets: Vec<RawTargetCoverage>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTargetCoverage {
+    name: String,
+    line_coverage: f64,
+    covered_lines: u64,
+    executable_lines: u64,
+}
+
+/// Parse `xcrun xccov view --report --json` output
+pub fn parse_coverage(json: &str) -> Result<Coverage> {
+    let raw: RawCoverage = serde_json::from_str(json)?;
+    Ok(Coverage {
+        line_coverage: raw.line_coverage,
+        covered_lines: raw.covered_lines,
+        executable_lines: raw.executable_lines,
+        targets: raw
+            .targets
+            .into_iter()
+            .map(|t| TargetCoverage {
+                name: t.name,
+                line_coverage: t.line_coverage,
+                covered_lines: t.covered_lines,
+                executable_lines: t.executable_lines,
+            })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TESTS: &str = r#"{
+  "devices": [{"deviceName": "iPhone 17 Pro Max", "platform": "iOS Simulator"}],
+  "testNodes": [{
+    "name": "FoodShare",
+    "nodeType": "Test Plan",
+    "result": "Failed",
+    "children": [{
+      "name": "FoodShareTests",
+      "nodeType": "Unit test bundle",
+      "result": "Failed",
+      "children": [{
+        "name": "LoginTests",
+        "nodeType": "Test Suite",
+        "result": "Failed",
+        "children": [
+          {
+            "name": "testLogin()",
+            "nodeIdentifier": "LoginTests/testLogin()",
+            "nodeType": "Test Case",
+            "result": "Passed",
+            "duration": "0.12s"
+          },
+          {
+            "name": "testLogout()",
+            "nodeIdentifier": "LoginTests/testLogout()",
+            "nodeType": "Test Case",
+            "result": "Failed",
+            "durationInSeconds": 1.5,
+            "children": [{
+              "name": "LoginTests.swift:42: XCTAssertTrue failed - session still active",
+              "nodeType": "Failure Message",
+              "result": "Failed"
+            }]
+          },
+          {
+            "name": "testOffline()",
+            "nodeIdentifier": "LoginTests/testOffline()",
+            "nodeType": "Test Case",
+            "result": "Skipped",
+            "duration": "0s"
+          }
+        ]
+      }]
+    }]
+  }]
+}"#;
+
+    #[test]
+    fn test_parse_tests() {
+        let tests = parse_tests(TESTS).unwrap();
+        assert_eq!(tests.len(), 3);
+        assert_eq!(tests[0].identifier, "LoginTests/testLogin()");
+        assert_eq!(tests[0].suite, "LoginTests");
+        assert_eq!(tests[0].bundle, "FoodShareTests");
+        assert_eq!(tests[0].duration, Some(0.12));
+
+        let failed = &tests[1];
+        assert_eq!(failed.status, TestStatus::Failed);
+        assert_eq!(
+            failed.failures,
+            [TestFailure {
+                message: "XCTAssertTrue failed - session still active".to_string(),
+                file: Some("LoginTests.swift".to_string()),
+                line: Some(42),
+            }]
+        );
+
+        let summary = TestSummary::from_tests(&tests);
+        assert_eq!((summary.total, summary.passed, summary.failed, summary.skipped), (3, 1, 1, 1));
+        assert!((summary.duration - 1.62).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_parse_retried_test() {
+        let json = r#"{"testNodes": [{
+          "name": "FeedTests", "nodeType": "Test Suite", "result": "Passed",
+          "children": [{
+            "name": "testRefresh()", "nodeIdentifier": "FeedTests/testRefresh()",
+            "nodeType": "Test Case", "result": "Passed",
+            "children": [
+              {"name": "First Run", "nodeType": "Repetition", "result": "Failed",
+               "children": [{"name": "FeedTests.swift:7: timed out", "nodeType": "Failure Message"}]},
+              {"name": "Retry 1", "nodeType": "Repetition", "result": "Passed"}
+            ]
+          }]
+        }]}"#;
+        let tests = parse_tests(json).unwrap();
+        assert_eq!(tests[0].attempts, [TestStatus::Failed, TestStatus::Passed]);
+        assert!(tests[0].passed_on_retry());
+        assert!(!parse_tests(TESTS).unwrap()[0].passed_on_retry());
+    }
+
+    #[test]
+    fn test_parse_duration_and_failures() {
+        assert_eq!(parse_duration("1m 5s"), Some(65.0));
+        assert_eq!(parse_duration("0,5s"), Some(0.5));
+        assert_eq!(parse_duration("soon"), None);
+
+        let failure = TestFailure::parse("Expectation failed: (count → 2) == 3");
+        assert_eq!(failure.file, None);
+        assert_eq!(failure.message, "Expectation failed: (count → 2) == 3");
+    }
+
+    #[test]
+    fn test_parse_build_results() {
+        let json = r#"{
+  "status": "succeeded",
+  "warningCount": 1,
+  "errorCount": 0,
+  "warnings": [{
+    "issueType": "Swift Compiler Warning",
+    "message": "variable 'x' was never used",
+    "targetName": "FoodShare",
+    "sourceURL": "file:///Users/dev/Food%20Share/FeedView.swift#EndingColumnNumber=12&EndingLineNumber=40&StartingColumnNumber=9&StartingLineNumber=40&Timestamp=0"
+  }],
+  "errors": []
+}"#;
+        let (warnings, errors) = parse_build_results(json).unwrap();
+        assert!(errors.is_empty());
+        assert_eq!(warnings[0].kind, "Swift Compiler Warning");
+        assert_eq!(
+            warnings[0].file.as_deref(),
+            Some(Path::new("/Users/dev/Food Share/FeedView.swift"))
+        );
+        assert_eq!(warnings[0].line, Some(41));
+    }
+
+    #[test]
+    fn test_parse_coverage() {
+        let json = r#"{
+  "coveredLines": 300,
+  "executableLines": 400,
+  "lineCoverage": 0.75,
+  "targets": [{
+    "name": "FoodShare.app",
+    "coveredLines": 300,
+    "executableLines": 400,
+    "lineCoverage": 0.75,
+    "files": []
+  }]
+}"#;
+        let coverage = parse_coverage(json).unwrap();
+        assert_eq!(coverage.line_coverage, 0.75);
+        assert_eq!(coverage.targets[0].name, "FoodShare.app");
+    }
+}
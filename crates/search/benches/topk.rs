@@ -0,0 +1,53 @@
+//! Benchmarks for top-k selection against score-everything-then-sort.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use foodshare_search::{calculate_relevance, search_top_k};
+
+const WORDS: &[&str] = &[
+    "apple", "bread", "carrot", "soup", "pasta", "rice", "bagel", "cheese", "milk", "tomato",
+    "banana", "yogurt", "lettuce", "onion", "pepper", "muffin", "salad", "beans", "lentils", "oats",
+];
+
+fn create_corpus(count: usize) -> Vec<String> {
+    (0..count)
+        .map(|i| {
+            let a = WORDS[i % WORDS.len()];
+            let b = WORDS[(i / WORDS.len()) % WORDS.len()];
+            let c = WORDS[(i * 7 + 3) % WORDS.len()];
+            format!("{a} {b} {c} {i}")
+        })
+        .collect()
+}
+
+fn full_sort<'a>(corpus: &'a [String], query: &str, k: usize) -> Vec<(&'a String, u32)> {
+    let mut scored: Vec<(&String, u32)> = corpus
+        .iter()
+        .map(|t| (t, calculate_relevance(t, query)))
+        .filter(|(_, s)| *s > 0)
+        .collect();
+    scored.sort_by_key(|s| std::cmp::Reverse(s.1));
+    scored.truncate(k);
+    scored
+}
+
+fn bench_top_k(c: &mut Criterion) {
+    let corpus = create_corpus(100_000);
+    let mut group = c.benchmark_group("top_k_100k");
+
+    for query in ["apple", "apple bread", "xyz"] {
+        for k in [10, 100] {
+            let id = format!("{query}/k={k}");
+            group.bench_with_input(BenchmarkId::new("full_sort", &id), &k, |b, &k| {
+                b.iter(|| full_sort(black_box(&corpus), black_box(query), k))
+            });
+            group.bench_with_input(BenchmarkId::new("heap", &id), &k, |b, &k| {
+                b.iter(|| search_top_k(black_box(&corpus), black_box(query), k, |s| s.as_str()))
+            });
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_top_k);
+criterion_main!(benches);
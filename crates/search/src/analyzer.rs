@@ -0,0 +1,244 @@
+//! Text analysis pipeline applied before tokenization.
+//!
+//! Normalizes text so that equivalent spellings compare equal:
+//! - NFKC normalization ("ﬁ" → "fi", full-width → ASCII)
+//! - Lowercasing
+//! - Optional Cyrillic/Greek → Latin transliteration
+//! - Diacritic folding ("café" → "cafe", "straße" → "strasse")
+
+use serde::{Deserialize, Serialize};
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Configurable text analyzer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Analyzer {
+    /// Apply NFKC compatibility normalization
+    pub nfkc: bool,
+    /// Lowercase text
+    pub lowercase: bool,
+    /// Strip diacritics and expand ligatures
+    pub fold_diacritics: bool,
+    /// Transliterate Cyrillic and Greek letters to Latin
+    pub transliterate: bool,
+}
+
+impl Default for Analyzer {
+    fn default() -> Self {
+        Self {
+            nfkc: true,
+            lowercase: true,
+            fold_diacritics: true,
+            transliterate: false,
+        }
+    }
+}
+
+impl Analyzer {
+    /// Creates an analyzer with the default pipeline (NFKC, lowercase, folding).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enables or disables NFKC normalization.
+    pub fn with_nfkc(mut self, enabled: bool) -> Self {
+        self.nfkc = enabled;
+        self
+    }
+
+    /// Enables or disables lowercasing.
+    pub fn with_lowercase(mut self, enabled: bool) -> Self {
+        self.lowercase = enabled;
+        self
+    }
+
+    /// Enables or disables diacritic folding.
+    pub fn with_fold_diacritics(mut self, enabled: bool) -> Self {
+        self.fold_diacritics = enabled;
+        self
+    }
+
+    /// Enables or disables Cyrillic/Greek → Latin transliteration.
+    pub fn with_transliterate(mut self, enabled: bool) -> Self {
+        self.transliterate = enabled;
+        self
+    }
+
+    /// Run the pipeline over a text and return the normalized form.
+    pub fn normalize(&self, text: &str) -> String {
+        let mut out: String = if self.nfkc {
+            text.nfkc().collect()
+        } else {
+            text.to_string()
+        };
+
+        if self.lowercase {
+            out = out.to_lowercase();
+        }
+
+        if self.transliterate {
+            out = transliterate(&out);
+        }
+
+        if self.fold_diacritics {
+            out = fold_diacritics(&out);
+        }
+
+        out
+    }
+
+    /// Normalize a text and split it into words.
+    pub fn tokenize(&self, text: &str) -> Vec<String> {
+        self.normalize(text)
+            .unicode_words()
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Calculate relevance after normalizing both text and query.
+    pub fn relevance(&self, text: &str, query: &str) -> u32 {
+        crate::calculate_relevance(&self.normalize(text), &self.normalize(query))
+    }
+}
+
+/// Strip combining marks and expand letters that do not decompose.
+pub fn fold_diacritics(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.nfd().filter(|c| !is_combining_mark(*c)) {
+        match c {
+            'ß' => out.push_str("ss"),
+            'æ' => out.push_str("ae"),
+            'Æ' => out.push_str("AE"),
+            'œ' => out.push_str("oe"),
+            'Œ' => out.push_str("OE"),
+            'ø' => out.push('o'),
+            'Ø' => out.push('O'),
+            'ł' => out.push('l'),
+            'Ł' => out.push('L'),
+            'đ' => out.push('d'),
+            'Đ' => out.push('D'),
+            'ı' => out.push('i'),
+            'þ' => out.push_str("th"),
+            'Þ' => out.push_str("TH"),
+            _ => out.push(c),
+        }
+    }
+    out.nfc().collect()
+}
+
+/// Transliterate Cyrillic and Greek letters to Latin; other text is unchanged.
+pub fn transliterate(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match transliterate_char(c) {
+            Some(latin) => out.push_str(latin),
+            None => out.push(c),
+        }
+    }
+    out
+}
+
+fn transliterate_char(c: char) -> Option<&'static str> {
+    let lower = c.to_lowercase().next().unwrap_or(c);
+    let latin = cyrillic_to_latin(lower).or_else(|| {
+        // Greek accents (tonos, dialytika) decompose; map the base letter
+        let base = lower.nfd().next().unwrap_or(lower);
+        greek_to_latin(base)
+    })?;
+
+    if c.is_uppercase() {
+        Some(capitalized(latin))
+    } else {
+        Some(latin)
+    }
+}
+
+fn capitalized(latin: &'static str) -> &'static str {
+    // Capitalized forms of every multi-letter and single-letter output below
+    match latin {
+        "a" => "A", "b" => "B", "v" => "V", "g" => "G", "d" => "D", "e" => "E",
+        "yo" => "Yo", "zh" => "Zh", "z" => "Z", "i" => "I", "y" => "Y", "k" => "K",
+        "l" => "L", "m" => "M", "n" => "N", "o" => "O", "p" => "P", "r" => "R",
+        "s" => "S", "t" => "T", "u" => "U", "f" => "F", "kh" => "Kh", "ts" => "Ts",
+        "ch" => "Ch", "sh" => "Sh", "shch" => "Shch", "ye" => "Ye", "yi" => "Yi",
+        "yu" => "Yu", "ya" => "Ya", "th" => "Th", "x" => "X", "ps" => "Ps",
+        other => other,
+    }
+}
+
+fn cyrillic_to_latin(c: char) -> Option<&'static str> {
+    Some(match c {
+        'а' => "a", 'б' => "b", 'в' => "v", 'г' => "g", 'ґ' => "g", 'д' => "d",
+        'е' => "e", 'ё' => "yo", 'є' => "ye", 'ж' => "zh", 'з' => "z", 'и' => "i",
+        'і' => "i", 'ї' => "yi", 'й' => "y", 'к' => "k", 'л' => "l", 'м' => "m",
+        'н' => "n", 'о' => "o", 'п' => "p", 'р' => "r", 'с' => "s", 'т' => "t",
+        'у' => "u", 'ф' => "f", 'х' => "kh", 'ц' => "ts", 'ч' => "ch", 'ш' => "sh",
+        'щ' => "shch", 'ъ' => "", 'ы' => "y", 'ь' => "", 'э' => "e", 'ю' => "yu",
+        'я' => "ya",
+        _ => return None,
+    })
+}
+
+fn greek_to_latin(c: char) -> Option<&'static str> {
+    Some(match c {
+        'α' => "a", 'β' => "v", 'γ' => "g", 'δ' => "d", 'ε' => "e", 'ζ' => "z",
+        'η' => "i", 'θ' => "th", 'ι' => "i", 'κ' => "k", 'λ' => "l", 'μ' => "m",
+        'ν' => "n", 'ξ' => "x", 'ο' => "o", 'π' => "p", 'ρ' => "r", 'σ' => "s",
+        'ς' => "s", 'τ' => "t", 'υ' => "y", 'φ' => "f", 'χ' => "ch", 'ψ' => "ps",
+        'ω' => "o",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fold_diacritics() {
+        let analyzer = Analyzer::new();
+        assert_eq!(analyzer.normalize("Café"), "cafe");
+        assert_eq!(analyzer.normalize("Straße"), "strasse");
+        assert_eq!(analyzer.normalize("Crème Brûlée"), "creme brulee");
+    }
+
+    #[test]
+    fn test_nfkc() {
+        let analyzer = Analyzer::new();
+        assert_eq!(analyzer.normalize("ﬁne"), "fine");
+        assert_eq!(analyzer.normalize("ＡＢＣ"), "abc");
+    }
+
+    #[test]
+    fn test_transliterate_cyrillic() {
+        let analyzer = Analyzer::new().with_transliterate(true);
+        assert_eq!(analyzer.normalize("Борщ"), "borshch");
+        assert_eq!(analyzer.normalize("Йогурт"), "yogurt");
+    }
+
+    #[test]
+    fn test_transliterate_greek() {
+        let analyzer = Analyzer::new().with_transliterate(true);
+        assert_eq!(analyzer.normalize("Φέτα"), "feta");
+        assert_eq!(transliterate("Θ"), "Th");
+    }
+
+    #[test]
+    fn test_transliterate_disabled_by_default() {
+        assert_eq!(Analyzer::new().normalize("Борщ"), "борщ");
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let tokens = Analyzer::new().tokenize("Café au lait, s'il vous plaît");
+        assert_eq!(tokens, vec!["cafe", "au", "lait", "s'il", "vous", "plait"]);
+    }
+
+    #[test]
+    fn test_relevance_matches_across_accents() {
+        let analyzer = Analyzer::new();
+        assert_eq!(analyzer.relevance("Café", "cafe"), crate::RelevanceScore::Exact as u32);
+    }
+}
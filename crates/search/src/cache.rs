@@ -0,0 +1,485 @@
+//! Thread-safe LRU query cache with TTL and invalidation hooks.
+//!
+//! [`QueryCache`] stores results keyed on the query and its options. Entries
+//! are evicted least-recently-used once the cache is full and expire after an
+//! optional TTL. Index updates must invalidate the cache; [`CachedIndex`] does
+//! this automatically, and hooks registered with [`QueryCache::on_invalidate`]
+//! are told about every invalidation.
+//!
+//! With the `telemetry` feature, hits, misses, evictions and size are reported
+//! through `foodshare-telemetry` as `<prefix>.hits`, `<prefix>.misses`,
+//! `<prefix>.evictions` and `<prefix>.entries`.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard, RwLock};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::index::{Document, InvertedIndex};
+use crate::SearchResult;
+
+/// Query cache configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// Maximum number of cached queries
+    pub capacity: usize,
+    /// Time-to-live in seconds (0 = no expiry)
+    pub ttl_secs: u64,
+    /// Metric name prefix
+    pub metrics_prefix: String,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 1024,
+            ttl_secs: 300,
+            metrics_prefix: "search.cache".to_string(),
+        }
+    }
+}
+
+/// Cache key: the query plus a serialized form of its options.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    query: String,
+    options: String,
+}
+
+impl CacheKey {
+    /// Creates a key from a query and any serializable options (limit, filters, locale...).
+    pub fn new(query: impl Into<String>, options: &impl Serialize) -> Self {
+        Self {
+            query: query.into(),
+            options: serde_json::to_string(options).unwrap_or_default(),
+        }
+    }
+
+    /// The query part of the key.
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+}
+
+/// Why cache entries were invalidated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvalidationReason {
+    /// A document was added, replaced or removed
+    IndexUpdated {
+        /// ID of the changed document
+        doc_id: String,
+    },
+    /// The index was rebuilt or compacted
+    IndexRebuilt,
+    /// Invalidated explicitly by the caller
+    Manual,
+}
+
+/// Cache statistics.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CacheStats {
+    /// Lookups answered from the cache
+    pub hits: u64,
+    /// Lookups not found or expired
+    pub misses: u64,
+    /// Entries dropped to stay within capacity
+    pub evictions: u64,
+    /// Invalidation events
+    pub invalidations: u64,
+    /// Current number of entries
+    pub entries: usize,
+}
+
+impl CacheStats {
+    /// Fraction of lookups that were hits (0.0 when there were none).
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 { 0.0 } else { self.hits as f64 / total as f64 }
+    }
+}
+
+type InvalidationHook = Box<dyn Fn(&InvalidationReason) + Send + Sync>;
+
+struct Entry<V> {
+    value: V,
+    inserted: Instant,
+    tick: u64,
+}
+
+struct Lru<V> {
+    entries: HashMap<CacheKey, Entry<V>>,
+    /// Recency order: tick → key, oldest first
+    order: BTreeMap<u64, CacheKey>,
+    next_tick: u64,
+}
+
+impl<V> Lru<V> {
+    fn touch(&mut self, key: &CacheKey) {
+        if let Some(entry) = self.entries.get_mut(key) {
+            self.order.remove(&entry.tick);
+            entry.tick = self.next_tick;
+            self.order.insert(self.next_tick, key.clone());
+            self.next_tick += 1;
+        }
+    }
+
+    fn remove(&mut self, key: &CacheKey) -> Option<Entry<V>> {
+        let entry = self.entries.remove(key)?;
+        self.order.remove(&entry.tick);
+        Some(entry)
+    }
+}
+
+/// Thread-safe LRU cache for query results.
+pub struct QueryCache<V> {
+    config: CacheConfig,
+    lru: Mutex<Lru<V>>,
+    hooks: RwLock<Vec<InvalidationHook>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    invalidations: AtomicU64,
+}
+
+impl<V: Clone> std::fmt::Debug for QueryCache<V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QueryCache")
+            .field("config", &self.config)
+            .field("stats", &self.stats())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<V: Clone> QueryCache<V> {
+    /// Creates a cache with the given configuration.
+    pub fn new(config: CacheConfig) -> Self {
+        Self {
+            config,
+            lru: Mutex::new(Lru { entries: HashMap::new(), order: BTreeMap::new(), next_tick: 0 }),
+            hooks: RwLock::new(Vec::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+            invalidations: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the configuration.
+    pub fn config(&self) -> &CacheConfig {
+        &self.config
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Lru<V>> {
+        // A panic while holding the lock cannot leave the LRU half-updated
+        // in a way that matters for a cache, so recover from poisoning.
+        self.lru.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn ttl(&self) -> Option<Duration> {
+        (self.config.ttl_secs > 0).then(|| Duration::from_secs(self.config.ttl_secs))
+    }
+
+    /// Looks up a cached value, refreshing its recency.
+    pub fn get(&self, key: &CacheKey) -> Option<V> {
+        let mut lru = self.lock();
+        let expired = match lru.entries.get(key) {
+            Some(entry) => self.ttl().is_some_and(|ttl| entry.inserted.elapsed() >= ttl),
+            None => {
+                drop(lru);
+                self.record("misses", &self.misses);
+                return None;
+            }
+        };
+
+        if expired {
+            lru.remove(key);
+            let len = lru.entries.len();
+            drop(lru);
+            self.record("misses", &self.misses);
+            self.report_size(len);
+            return None;
+        }
+
+        lru.touch(key);
+        let value = lru.entries.get(key).map(|e| e.value.clone());
+        drop(lru);
+        self.record("hits", &self.hits);
+        value
+    }
+
+    /// Inserts a value, evicting the least recently used entry if full.
+    pub fn insert(&self, key: CacheKey, value: V) {
+        if self.config.capacity == 0 {
+            return;
+        }
+
+        let mut lru = self.lock();
+        lru.remove(&key);
+
+        let mut evicted = 0;
+        while lru.entries.len() >= self.config.capacity {
+            let Some((_, oldest)) = lru.order.pop_first() else { break };
+            lru.entries.remove(&oldest);
+            evicted += 1;
+        }
+
+        let tick = lru.next_tick;
+        lru.next_tick += 1;
+        lru.order.insert(tick, key.clone());
+        lru.entries.insert(key, Entry { value, inserted: Instant::now(), tick });
+        let len = lru.entries.len();
+        drop(lru);
+
+        if evicted > 0 {
+            self.evictions.fetch_add(evicted, Ordering::Relaxed);
+            #[cfg(feature = "telemetry")]
+            foodshare_telemetry::metrics().increment_by(&format!("{}.evictions", self.config.metrics_prefix), evicted);
+        }
+        self.report_size(len);
+    }
+
+    /// Returns the cached value or computes and caches it.
+    pub fn get_or_insert_with(&self, key: CacheKey, compute: impl FnOnce() -> V) -> V {
+        if let Some(value) = self.get(&key) {
+            return value;
+        }
+        let value = compute();
+        self.insert(key, value.clone());
+        value
+    }
+
+    /// Removes every entry and notifies hooks.
+    pub fn invalidate_all(&self, reason: InvalidationReason) {
+        {
+            let mut lru = self.lock();
+            lru.entries.clear();
+            lru.order.clear();
+        }
+        self.report_size(0);
+        self.notify(&reason);
+    }
+
+    /// Removes entries whose key matches the predicate and notifies hooks.
+    pub fn invalidate_where(&self, reason: InvalidationReason, predicate: impl Fn(&CacheKey) -> bool) {
+        let len = {
+            let mut lru = self.lock();
+            let keys: Vec<CacheKey> = lru.entries.keys().filter(|k| predicate(k)).cloned().collect();
+            for key in &keys {
+                lru.remove(key);
+            }
+            lru.entries.len()
+        };
+        self.report_size(len);
+        self.notify(&reason);
+    }
+
+    /// Registers a hook called on every invalidation.
+    pub fn on_invalidate(&self, hook: impl Fn(&InvalidationReason) + Send + Sync + 'static) {
+        self.hooks.write().unwrap_or_else(|e| e.into_inner()).push(Box::new(hook));
+    }
+
+    /// Current statistics.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            invalidations: self.invalidations.load(Ordering::Relaxed),
+            entries: self.lock().entries.len(),
+        }
+    }
+
+    /// Number of cached entries (including expired ones not yet looked up).
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Returns true if the cache holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn notify(&self, reason: &InvalidationReason) {
+        self.invalidations.fetch_add(1, Ordering::Relaxed);
+        for hook in self.hooks.read().unwrap_or_else(|e| e.into_inner()).iter() {
+            hook(reason);
+        }
+    }
+
+    #[cfg_attr(not(feature = "telemetry"), allow(unused_variables))]
+    fn record(&self, metric: &str, counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+        #[cfg(feature = "telemetry")]
+        foodshare_telemetry::metrics().increment(&format!("{}.{metric}", self.config.metrics_prefix));
+    }
+
+    #[cfg_attr(not(feature = "telemetry"), allow(unused_variables))]
+    fn report_size(&self, len: usize) {
+        #[cfg(feature = "telemetry")]
+        foodshare_telemetry::metrics().gauge(&format!("{}.entries", self.config.metrics_prefix), len as u64);
+    }
+}
+
+/// An [`InvertedIndex`] whose searches are cached and invalidated on update.
+#[derive(Debug)]
+pub struct CachedIndex {
+    index: InvertedIndex,
+    cache: QueryCache<Vec<SearchResult<String>>>,
+}
+
+impl CachedIndex {
+    /// Wraps an index with a query cache.
+    pub fn new(index: InvertedIndex, config: CacheConfig) -> Self {
+        Self { index, cache: QueryCache::new(config) }
+    }
+
+    /// Returns the wrapped index.
+    pub fn index(&self) -> &InvertedIndex {
+        &self.index
+    }
+
+    /// Returns the cache, e.g. to register hooks or read stats.
+    pub fn cache(&self) -> &QueryCache<Vec<SearchResult<String>>> {
+        &self.cache
+    }
+
+    /// Cached [`InvertedIndex::search`].
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchResult<&Document>> {
+        let key = CacheKey::new(query, &("search", limit));
+        let cached = self.cache.get_or_insert_with(key, || {
+            self.index
+                .search(query, limit)
+                .into_iter()
+                .map(|r| SearchResult { item: r.item.id.clone(), score: r.score, highlights: r.highlights })
+                .collect()
+        });
+
+        cached
+            .into_iter()
+            .filter_map(|r| {
+                let doc = self.index.get(&r.item)?;
+                Some(SearchResult { item: doc, score: r.score, highlights: r.highlights })
+            })
+            .collect()
+    }
+
+    /// Adds or replaces a document and invalidates the cache.
+    pub fn add(&mut self, doc: Document) {
+        let doc_id = doc.id.clone();
+        self.index.add(doc);
+        self.cache.invalidate_all(InvalidationReason::IndexUpdated { doc_id });
+    }
+
+    /// Removes a document and invalidates the cache if it existed.
+    pub fn remove(&mut self, id: &str) -> bool {
+        let removed = self.index.remove(id);
+        if removed {
+            self.cache.invalidate_all(InvalidationReason::IndexUpdated { doc_id: id.to_string() });
+        }
+        removed
+    }
+
+    /// Compacts the index and invalidates the cache.
+    pub fn compact(&mut self) {
+        self.index.compact();
+        self.cache.invalidate_all(InvalidationReason::IndexRebuilt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Analyzer;
+    use std::sync::Arc;
+
+    fn key(q: &str) -> CacheKey {
+        CacheKey::new(q, &10)
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let cache = QueryCache::new(CacheConfig { capacity: 2, ..CacheConfig::default() });
+        cache.insert(key("a"), 1);
+        cache.insert(key("b"), 2);
+        assert_eq!(cache.get(&key("a")), Some(1));
+        cache.insert(key("c"), 3);
+
+        assert_eq!(cache.get(&key("b")), None);
+        assert_eq!(cache.get(&key("a")), Some(1));
+        assert_eq!(cache.get(&key("c")), Some(3));
+        assert_eq!(cache.stats().evictions, 1);
+    }
+
+    #[test]
+    fn test_options_are_part_of_key() {
+        let cache = QueryCache::new(CacheConfig::default());
+        cache.insert(CacheKey::new("bread", &10), 1);
+        assert_eq!(cache.get(&CacheKey::new("bread", &20)), None);
+    }
+
+    #[test]
+    fn test_ttl_expiry() {
+        let cache = QueryCache::new(CacheConfig { ttl_secs: 1, ..CacheConfig::default() });
+        cache.insert(key("a"), 1);
+        assert_eq!(cache.get(&key("a")), Some(1));
+        std::thread::sleep(Duration::from_millis(1100));
+        assert_eq!(cache.get(&key("a")), None);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_stats_and_hooks() {
+        let cache = QueryCache::new(CacheConfig::default());
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+        cache.on_invalidate(move |reason| sink.lock().unwrap().push(reason.clone()));
+
+        cache.insert(key("a"), 1);
+        cache.insert(key("b"), 2);
+        cache.get(&key("a"));
+        cache.get(&key("z"));
+        cache.invalidate_where(InvalidationReason::Manual, |k| k.query() == "a");
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+        assert!((stats.hit_rate() - 0.5).abs() < f64::EPSILON);
+        assert_eq!(*seen.lock().unwrap(), vec![InvalidationReason::Manual]);
+    }
+
+    #[test]
+    fn test_cached_index_invalidates_on_update() {
+        let mut index = CachedIndex::new(InvertedIndex::new(Analyzer::default()), CacheConfig::default());
+        index.add(Document::new("1").with_field("title", "Apple pie"));
+
+        assert_eq!(index.search("apple", 10).len(), 1);
+        assert_eq!(index.search("apple", 10).len(), 1);
+        assert_eq!(index.cache().stats().hits, 1);
+
+        index.add(Document::new("2").with_field("title", "Apple juice"));
+        assert_eq!(index.search("apple", 10).len(), 2);
+    }
+
+    #[test]
+    fn test_concurrent_access() {
+        let cache = Arc::new(QueryCache::new(CacheConfig { capacity: 16, ..CacheConfig::default() }));
+        let handles: Vec<_> = (0..4)
+            .map(|t| {
+                let cache = Arc::clone(&cache);
+                std::thread::spawn(move || {
+                    for i in 0..200 {
+                        let k = key(&format!("q{}", (i + t) % 32));
+                        cache.get_or_insert_with(k, || i);
+                    }
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+        assert!(cache.len() <= 16);
+        assert_eq!(cache.stats().hits + cache.stats().misses, 800);
+    }
+}
@@ -0,0 +1,269 @@
+//! Synonym and stop-word dictionaries applied at query time.
+//!
+//! Dictionaries are loaded from TOML:
+//!
+//! ```toml
+//! synonyms = [
+//!     ["courgette", "zucchini"],
+//!     ["aubergine", "eggplant", "brinjal"],
+//! ]
+//!
+//! [stopwords]
+//! en = ["the", "a", "of", "with"]
+//! fr = ["le", "la", "les", "de", "avec"]
+//! ```
+//!
+//! Entries are normalized with the dictionary's [`Analyzer`], so they match
+//! regardless of case or accents.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Analyzer, Result, SearchError};
+
+/// Maximum number of query variants generated by synonym expansion.
+pub const MAX_QUERY_VARIANTS: usize = 32;
+
+/// Raw dictionary contents as stored in TOML.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DictionaryConfig {
+    /// Groups of interchangeable terms
+    #[serde(default)]
+    pub synonyms: Vec<Vec<String>>,
+    /// Stop words keyed by locale (e.g. "en", "fr", "pt-br")
+    #[serde(default)]
+    pub stopwords: HashMap<String, Vec<String>>,
+}
+
+/// A query after stop-word removal and synonym expansion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpandedQuery {
+    /// Alternatives for each remaining query term; the first is the original term
+    pub terms: Vec<Vec<String>>,
+}
+
+impl ExpandedQuery {
+    /// Returns true if every term was removed as a stop word.
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Query strings formed by substituting synonyms, original first.
+    ///
+    /// At most [`MAX_QUERY_VARIANTS`] variants are produced.
+    pub fn variants(&self) -> Vec<String> {
+        let mut variants: Vec<Vec<&str>> = vec![Vec::new()];
+        for alternatives in &self.terms {
+            let mut next = Vec::with_capacity(variants.len() * alternatives.len());
+            'outer: for alt in alternatives {
+                for prefix in &variants {
+                    if next.len() >= MAX_QUERY_VARIANTS {
+                        break 'outer;
+                    }
+                    let mut v = prefix.clone();
+                    v.push(alt.as_str());
+                    next.push(v);
+                }
+            }
+            variants = next;
+        }
+        variants.into_iter().map(|v| v.join(" ")).collect()
+    }
+}
+
+/// Compiled synonym and stop-word lookup tables.
+#[derive(Debug, Clone, Default)]
+pub struct Dictionary {
+    analyzer: Analyzer,
+    synonyms: HashMap<String, Vec<String>>,
+    stopwords: HashMap<String, HashSet<String>>,
+}
+
+impl Dictionary {
+    /// Creates an empty dictionary using the default analyzer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds a dictionary from its configuration.
+    pub fn from_config(config: DictionaryConfig, analyzer: Analyzer) -> Self {
+        let mut dict = Self { analyzer, ..Self::default() };
+        for group in &config.synonyms {
+            dict.add_synonyms(group);
+        }
+        for (locale, words) in &config.stopwords {
+            dict.add_stopwords(locale, words);
+        }
+        dict
+    }
+
+    /// Parses a dictionary from TOML.
+    pub fn from_toml(content: &str) -> Result<Self> {
+        let config: DictionaryConfig = toml::from_str(content)
+            .map_err(|e| SearchError::Config(format!("invalid dictionary: {e}")))?;
+        Ok(Self::from_config(config, Analyzer::default()))
+    }
+
+    /// Loads a dictionary from a TOML file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Self::from_toml(&content)
+    }
+
+    /// Returns the analyzer used to normalize entries and queries.
+    pub fn analyzer(&self) -> &Analyzer {
+        &self.analyzer
+    }
+
+    /// Registers a group of interchangeable terms.
+    pub fn add_synonyms<S: AsRef<str>>(&mut self, group: &[S]) {
+        let normalized: Vec<String> = group
+            .iter()
+            .map(|t| self.analyzer.normalize(t.as_ref()))
+            .filter(|t| !t.is_empty())
+            .collect();
+
+        for term in &normalized {
+            let entry = self.synonyms.entry(term.clone()).or_default();
+            for other in &normalized {
+                if other != term && !entry.contains(other) {
+                    entry.push(other.clone());
+                }
+            }
+        }
+    }
+
+    /// Registers stop words for a locale.
+    pub fn add_stopwords<S: AsRef<str>>(&mut self, locale: &str, words: &[S]) {
+        let set = self.stopwords.entry(locale.to_lowercase()).or_default();
+        set.extend(words.iter().map(|w| self.analyzer.normalize(w.as_ref())));
+    }
+
+    /// Returns the synonyms of a term (excluding the term itself).
+    pub fn synonyms(&self, term: &str) -> &[String] {
+        self.synonyms
+            .get(&self.analyzer.normalize(term))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns true if `word` is a stop word for `locale`.
+    ///
+    /// Regional locales fall back to their language ("en-GB" → "en").
+    pub fn is_stopword(&self, word: &str, locale: &str) -> bool {
+        let word = self.analyzer.normalize(word);
+        self.locale_stopwords(locale).any(|set| set.contains(&word))
+    }
+
+    fn locale_stopwords<'a>(&'a self, locale: &str) -> impl Iterator<Item = &'a HashSet<String>> {
+        let locale = locale.to_lowercase().replace('_', "-");
+        let language = locale.split('-').next().unwrap_or_default().to_string();
+        let mut keys = vec![locale];
+        if keys[0] != language {
+            keys.push(language);
+        }
+        keys.into_iter().filter_map(move |k| self.stopwords.get(&k))
+    }
+
+    /// Normalize a query, drop stop words and expand synonyms.
+    ///
+    /// If every term is a stop word the terms are kept, so a query like
+    /// "the" still searches for something.
+    pub fn expand(&self, query: &str, locale: &str) -> ExpandedQuery {
+        let tokens = self.analyzer.tokenize(query);
+        let stopwords: Vec<&HashSet<String>> = self.locale_stopwords(locale).collect();
+        let kept: Vec<&String> = tokens
+            .iter()
+            .filter(|t| !stopwords.iter().any(|set| set.contains(*t)))
+            .collect();
+        let kept = if kept.is_empty() { tokens.iter().collect() } else { kept };
+
+        let terms = kept
+            .into_iter()
+            .map(|t| {
+                let mut alternatives = vec![t.clone()];
+                if let Some(syns) = self.synonyms.get(t) {
+                    alternatives.extend(syns.iter().cloned());
+                }
+                alternatives
+            })
+            .collect();
+
+        ExpandedQuery { terms }
+    }
+
+    /// Calculate relevance using the best-scoring expanded query variant.
+    pub fn relevance(&self, text: &str, query: &str, locale: &str) -> u32 {
+        let text = self.analyzer.normalize(text);
+        self.expand(query, locale)
+            .variants()
+            .iter()
+            .map(|q| crate::calculate_relevance(&text, q))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DICT: &str = r#"
+        synonyms = [["courgette", "zucchini"], ["aubergine", "eggplant"]]
+
+        [stopwords]
+        en = ["the", "of", "with"]
+        fr = ["le", "de", "avec"]
+    "#;
+
+    #[test]
+    fn test_synonyms_are_bidirectional() {
+        let dict = Dictionary::from_toml(DICT).unwrap();
+        assert_eq!(dict.synonyms("Courgette"), ["zucchini"]);
+        assert_eq!(dict.synonyms("zucchini"), ["courgette"]);
+        assert!(dict.synonyms("carrot").is_empty());
+    }
+
+    #[test]
+    fn test_stopwords_locale_fallback() {
+        let dict = Dictionary::from_toml(DICT).unwrap();
+        assert!(dict.is_stopword("The", "en-GB"));
+        assert!(dict.is_stopword("avec", "fr_FR"));
+        assert!(!dict.is_stopword("avec", "en"));
+    }
+
+    #[test]
+    fn test_expand_drops_stopwords() {
+        let dict = Dictionary::from_toml(DICT).unwrap();
+        let expanded = dict.expand("bag of courgettes with the zucchini", "en");
+        assert_eq!(expanded.terms.len(), 3);
+        assert_eq!(expanded.terms[2], vec!["zucchini", "courgette"]);
+    }
+
+    #[test]
+    fn test_expand_keeps_all_stopword_query() {
+        let dict = Dictionary::from_toml(DICT).unwrap();
+        assert_eq!(dict.expand("the", "en").terms, vec![vec!["the".to_string()]]);
+    }
+
+    #[test]
+    fn test_relevance_uses_synonyms() {
+        let dict = Dictionary::from_toml(DICT).unwrap();
+        assert_eq!(dict.relevance("Fresh zucchini", "courgette", "en"), crate::RelevanceScore::WordBoundary as u32);
+        assert_eq!(crate::calculate_relevance("Fresh zucchini", "courgette"), 0);
+    }
+
+    #[test]
+    fn test_variants_are_capped() {
+        let terms = vec![vec!["a".to_string(), "b".to_string(), "c".to_string()]; 6];
+        let variants = ExpandedQuery { terms }.variants();
+        assert_eq!(variants.len(), MAX_QUERY_VARIANTS);
+        assert_eq!(variants[0], "a a a a a a");
+    }
+
+    #[test]
+    fn test_invalid_toml() {
+        assert!(matches!(Dictionary::from_toml("synonyms = 3"), Err(SearchError::Config(_))));
+    }
+}
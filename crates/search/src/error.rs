@@ -15,4 +15,25 @@ pub enum SearchError {
     /// Index error
     #[error("Index error: {0}")]
     IndexError(String),
+
+    /// Index file failed integrity checks
+    #[error("Index corrupted: {0}")]
+    Corrupted(String),
+
+    /// Index file written by an incompatible format version
+    #[error("Unsupported index format version {found} (expected {expected})")]
+    UnsupportedVersion {
+        /// Version found in the file
+        found: u32,
+        /// Version this build reads
+        expected: u32,
+    },
+
+    /// Invalid configuration (dictionaries, analyzers)
+    #[error("Configuration error: {0}")]
+    Config(String),
+
+    /// IO error
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
 }
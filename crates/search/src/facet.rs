@@ -0,0 +1,418 @@
+//! Faceted filtering and aggregation.
+//!
+//! Documents carry typed [`AttributeValue`]s (category, dietary tags,
+//! distance). A [`SearchRequest`] filters on them and asks for facet counts,
+//! returned alongside the results in a single pass.
+//!
+//! Facet counts are disjunctive: the counts for a field ignore filters on that
+//! same field, so a sidebar can show how many results each other option would
+//! add.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::index::{Document, InvertedIndex};
+use crate::SearchResult;
+
+/// A typed document attribute.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AttributeValue {
+    /// Numeric value (distance, price, quantity)
+    Number(f64),
+    /// Single keyword (category)
+    Keyword(String),
+    /// Multiple keywords (dietary tags)
+    Tags(Vec<String>),
+}
+
+impl AttributeValue {
+    /// Keyword values of this attribute (empty for numbers).
+    pub fn keywords(&self) -> Vec<&str> {
+        match self {
+            Self::Number(_) => Vec::new(),
+            Self::Keyword(k) => vec![k.as_str()],
+            Self::Tags(tags) => tags.iter().map(String::as_str).collect(),
+        }
+    }
+
+    /// Numeric value of this attribute, if any.
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            Self::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+}
+
+impl From<f64> for AttributeValue {
+    fn from(value: f64) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl From<&str> for AttributeValue {
+    fn from(value: &str) -> Self {
+        Self::Keyword(value.to_string())
+    }
+}
+
+impl From<String> for AttributeValue {
+    fn from(value: String) -> Self {
+        Self::Keyword(value)
+    }
+}
+
+impl From<Vec<String>> for AttributeValue {
+    fn from(value: Vec<String>) -> Self {
+        Self::Tags(value)
+    }
+}
+
+impl From<Vec<&str>> for AttributeValue {
+    fn from(value: Vec<&str>) -> Self {
+        Self::Tags(value.into_iter().map(str::to_string).collect())
+    }
+}
+
+/// A filter on a document attribute.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Filter {
+    /// Attribute has any of the given keywords
+    Terms {
+        /// Attribute name
+        field: String,
+        /// Accepted values
+        values: Vec<String>,
+    },
+    /// Numeric attribute lies in `[min, max)`
+    Range {
+        /// Attribute name
+        field: String,
+        /// Inclusive lower bound
+        min: Option<f64>,
+        /// Exclusive upper bound
+        max: Option<f64>,
+    },
+}
+
+impl Filter {
+    /// Creates a keyword filter.
+    pub fn terms<S: Into<String>>(field: impl Into<String>, values: impl IntoIterator<Item = S>) -> Self {
+        Self::Terms {
+            field: field.into(),
+            values: values.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Creates a numeric range filter.
+    pub fn range(field: impl Into<String>, min: Option<f64>, max: Option<f64>) -> Self {
+        Self::Range { field: field.into(), min, max }
+    }
+
+    /// Attribute this filter applies to.
+    pub fn field(&self) -> &str {
+        match self {
+            Self::Terms { field, .. } | Self::Range { field, .. } => field,
+        }
+    }
+
+    /// Returns true if the document passes this filter.
+    pub fn matches(&self, doc: &Document) -> bool {
+        let Some(value) = doc.attribute(self.field()) else {
+            return false;
+        };
+        match self {
+            Self::Terms { values, .. } => value.keywords().iter().any(|k| values.iter().any(|v| v == k)),
+            Self::Range { min, max, .. } => value
+                .as_number()
+                .is_some_and(|n| min.is_none_or(|m| n >= m) && max.is_none_or(|m| n < m)),
+        }
+    }
+}
+
+/// A named numeric bucket, e.g. "< 1 km".
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RangeBucket {
+    /// Bucket label returned in counts
+    pub label: String,
+    /// Inclusive lower bound
+    pub min: Option<f64>,
+    /// Exclusive upper bound
+    pub max: Option<f64>,
+}
+
+impl RangeBucket {
+    /// Creates a bucket.
+    pub fn new(label: impl Into<String>, min: Option<f64>, max: Option<f64>) -> Self {
+        Self { label: label.into(), min, max }
+    }
+
+    fn contains(&self, n: f64) -> bool {
+        self.min.is_none_or(|m| n >= m) && self.max.is_none_or(|m| n < m)
+    }
+}
+
+/// How to aggregate a facet.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FacetKind {
+    /// Count each distinct keyword
+    Terms,
+    /// Count numeric values per bucket
+    Ranges {
+        /// Buckets in display order
+        buckets: Vec<RangeBucket>,
+    },
+}
+
+/// A requested facet.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FacetRequest {
+    /// Attribute name
+    pub field: String,
+    /// Aggregation kind
+    pub kind: FacetKind,
+}
+
+impl FacetRequest {
+    /// Count distinct keywords of an attribute.
+    pub fn terms(field: impl Into<String>) -> Self {
+        Self { field: field.into(), kind: FacetKind::Terms }
+    }
+
+    /// Count a numeric attribute per bucket.
+    pub fn ranges(field: impl Into<String>, buckets: Vec<RangeBucket>) -> Self {
+        Self { field: field.into(), kind: FacetKind::Ranges { buckets } }
+    }
+}
+
+/// Count for one facet value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FacetCount {
+    /// Keyword or bucket label
+    pub value: String,
+    /// Matching documents
+    pub count: usize,
+}
+
+/// Aggregated counts for one facet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FacetCounts {
+    /// Attribute name
+    pub field: String,
+    /// Counts; terms sorted by count then value, ranges in bucket order
+    pub counts: Vec<FacetCount>,
+}
+
+/// A search with filters and facets.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchRequest {
+    /// Text query (blank matches every document)
+    pub query: String,
+    /// Maximum results to return (0 for all)
+    pub limit: usize,
+    /// Filters, all of which must pass
+    pub filters: Vec<Filter>,
+    /// Facets to aggregate
+    pub facets: Vec<FacetRequest>,
+}
+
+impl SearchRequest {
+    /// Creates a request for a text query.
+    pub fn new(query: impl Into<String>) -> Self {
+        Self { query: query.into(), ..Self::default() }
+    }
+
+    /// Sets the result limit.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Adds a filter.
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    /// Adds a facet.
+    pub fn with_facet(mut self, facet: FacetRequest) -> Self {
+        self.facets.push(facet);
+        self
+    }
+}
+
+/// Results of a faceted search.
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResponse<'a> {
+    /// Ranked results
+    pub results: Vec<SearchResult<&'a Document>>,
+    /// Total matching documents before `limit`
+    pub total: usize,
+    /// Facet counts, in request order
+    pub facets: Vec<FacetCounts>,
+}
+
+impl InvertedIndex {
+    /// Search with filters and facet aggregation.
+    pub fn search_faceted(&self, request: &SearchRequest) -> SearchResponse<'_> {
+        let matches = self.matches(&request.query);
+
+        let facets = request
+            .facets
+            .iter()
+            .map(|facet| {
+                // Disjunctive: ignore filters on the facet's own field
+                let docs = matches.iter().map(|(doc, _)| *doc).filter(|doc| {
+                    request
+                        .filters
+                        .iter()
+                        .filter(|f| f.field() != facet.field)
+                        .all(|f| f.matches(doc))
+                });
+                aggregate(facet, docs)
+            })
+            .collect();
+
+        let filtered: Vec<(&Document, u32)> = matches
+            .into_iter()
+            .filter(|(doc, _)| request.filters.iter().all(|f| f.matches(doc)))
+            .collect();
+        let total = filtered.len();
+
+        SearchResponse {
+            results: self.rank(filtered, &request.query, request.limit),
+            total,
+            facets,
+        }
+    }
+}
+
+fn aggregate<'a>(facet: &FacetRequest, docs: impl Iterator<Item = &'a Document>) -> FacetCounts {
+    let values = docs.filter_map(|doc| doc.attribute(&facet.field));
+
+    let counts = match &facet.kind {
+        FacetKind::Terms => {
+            let mut by_value: BTreeMap<&str, usize> = BTreeMap::new();
+            for value in values {
+                let mut keywords = value.keywords();
+                keywords.sort_unstable();
+                keywords.dedup();
+                for k in keywords {
+                    *by_value.entry(k).or_default() += 1;
+                }
+            }
+            let mut counts: Vec<FacetCount> = by_value
+                .into_iter()
+                .map(|(value, count)| FacetCount { value: value.to_string(), count })
+                .collect();
+            counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+            counts
+        }
+        FacetKind::Ranges { buckets } => {
+            let mut counts: Vec<FacetCount> = buckets
+                .iter()
+                .map(|b| FacetCount { value: b.label.clone(), count: 0 })
+                .collect();
+            for n in values.filter_map(AttributeValue::as_number) {
+                for (bucket, count) in buckets.iter().zip(counts.iter_mut()) {
+                    if bucket.contains(n) {
+                        count.count += 1;
+                    }
+                }
+            }
+            counts
+        }
+    };
+
+    FacetCounts { field: facet.field.clone(), counts }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Analyzer;
+
+    fn index() -> InvertedIndex {
+        let mut index = InvertedIndex::new(Analyzer::default());
+        index.add(
+            Document::new("1")
+                .with_field("title", "Apple pie")
+                .with_attribute("category", "bakery")
+                .with_attribute("dietary", vec!["vegetarian"])
+                .with_attribute("distance_km", 0.5),
+        );
+        index.add(
+            Document::new("2")
+                .with_field("title", "Apple juice")
+                .with_attribute("category", "drinks")
+                .with_attribute("dietary", vec!["vegan", "vegetarian"])
+                .with_attribute("distance_km", 3.0),
+        );
+        index.add(
+            Document::new("3")
+                .with_field("title", "Apple crumble")
+                .with_attribute("category", "bakery")
+                .with_attribute("distance_km", 12.0),
+        );
+        index
+    }
+
+    fn count(facets: &FacetCounts, value: &str) -> usize {
+        facets.counts.iter().find(|c| c.value == value).map_or(0, |c| c.count)
+    }
+
+    #[test]
+    fn test_terms_facet() {
+        let index = index();
+        let response = index.search_faceted(&SearchRequest::new("apple").with_facet(FacetRequest::terms("dietary")));
+        assert_eq!(response.total, 3);
+        assert_eq!(response.facets[0].counts[0], FacetCount { value: "vegetarian".into(), count: 2 });
+        assert_eq!(count(&response.facets[0], "vegan"), 1);
+    }
+
+    #[test]
+    fn test_range_facet_and_filter() {
+        let index = index();
+        let buckets = vec![
+            RangeBucket::new("< 1 km", None, Some(1.0)),
+            RangeBucket::new("< 5 km", None, Some(5.0)),
+            RangeBucket::new("5 km+", Some(5.0), None),
+        ];
+        let request = SearchRequest::new("apple")
+            .with_filter(Filter::range("distance_km", None, Some(5.0)))
+            .with_facet(FacetRequest::ranges("distance_km", buckets));
+        let response = index.search_faceted(&request);
+
+        assert_eq!(response.total, 2);
+        // Own-field filter is ignored for the facet's counts
+        assert_eq!(count(&response.facets[0], "5 km+"), 1);
+        assert_eq!(count(&response.facets[0], "< 5 km"), 2);
+    }
+
+    #[test]
+    fn test_disjunctive_counts() {
+        let index = index();
+        let request = SearchRequest::new("")
+            .with_filter(Filter::terms("category", ["bakery"]))
+            .with_facet(FacetRequest::terms("category"))
+            .with_facet(FacetRequest::terms("dietary"));
+        let response = index.search_faceted(&request);
+
+        assert_eq!(response.total, 2);
+        assert_eq!(count(&response.facets[0], "drinks"), 1);
+        assert_eq!(count(&response.facets[1], "vegan"), 0);
+    }
+
+    #[test]
+    fn test_attribute_serde_roundtrip() {
+        let doc = Document::new("1").with_attribute("tags", vec!["a", "b"]).with_attribute("km", 2.5);
+        let json = serde_json::to_string(&doc).unwrap();
+        let back: Document = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, doc);
+    }
+}
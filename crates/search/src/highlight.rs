@@ -0,0 +1,234 @@
+//! Match highlighting for search results.
+//!
+//! Computes the substrings of a text that caused it to match a query, using
+//! the same matching rules as [`calculate_relevance`](crate::calculate_relevance),
+//! so UIs can highlight results without re-implementing the scorer.
+
+use serde::{Deserialize, Serialize};
+
+use crate::relevance::RelevanceScore;
+
+/// A matched region of a text.
+///
+/// Both byte offsets (for slicing Rust strings) and character offsets
+/// (for clients that index by code point) are provided. Ranges are half-open.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchSpan {
+    /// Start byte offset (inclusive)
+    pub start: usize,
+    /// End byte offset (exclusive)
+    pub end: usize,
+    /// Start character offset (inclusive)
+    pub char_start: usize,
+    /// End character offset (exclusive)
+    pub char_end: usize,
+}
+
+/// Matched regions for a single named field of a search result.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldHighlight {
+    /// Field name (e.g. "title", "description")
+    pub field: String,
+    /// Matched spans, sorted and non-overlapping
+    pub spans: Vec<MatchSpan>,
+}
+
+/// Relevance score together with the spans that produced it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Highlight {
+    /// Relevance score, identical to `calculate_relevance(text, query)`
+    pub score: u32,
+    /// Matched spans in the original text
+    pub spans: Vec<MatchSpan>,
+}
+
+/// Lowercased view of a text that remembers where each lowered byte came from.
+struct LoweredText {
+    lowered: String,
+    /// For each byte in `lowered`, the index of the originating char
+    origin: Vec<usize>,
+    /// Byte offset of each original char, plus a trailing `text.len()`
+    char_offsets: Vec<usize>,
+}
+
+impl LoweredText {
+    fn new(text: &str) -> Self {
+        let mut lowered = String::with_capacity(text.len());
+        let mut origin = Vec::with_capacity(text.len());
+        let mut char_offsets = Vec::with_capacity(text.len() + 1);
+
+        for (char_idx, (byte_idx, c)) in text.char_indices().enumerate() {
+            char_offsets.push(byte_idx);
+            for lc in c.to_lowercase() {
+                lowered.push(lc);
+                origin.extend(std::iter::repeat_n(char_idx, lc.len_utf8()));
+            }
+        }
+        char_offsets.push(text.len());
+
+        Self { lowered, origin, char_offsets }
+    }
+
+    /// Map a byte range of the lowered text back to a span of the original.
+    fn span(&self, start: usize, end: usize) -> MatchSpan {
+        let char_start = self.origin[start];
+        let char_end = self.origin[end - 1] + 1;
+        MatchSpan {
+            start: self.char_offsets[char_start],
+            end: self.char_offsets[char_end],
+            char_start,
+            char_end,
+        }
+    }
+}
+
+/// Score a text against a query and return the matching spans.
+///
+/// # Arguments
+/// * `text` - The text to score
+/// * `query` - The search query
+///
+/// # Returns
+/// The relevance score and the spans of `text` that matched. Spans are empty
+/// when the score is zero or the query is empty.
+pub fn highlight(text: &str, query: &str) -> Highlight {
+    let lowered = LoweredText::new(text);
+    let text_lower = lowered.lowered.as_str();
+    let query_lower = query.to_lowercase();
+
+    if query_lower.is_empty() {
+        return Highlight { score: crate::calculate_relevance(text, query), spans: Vec::new() };
+    }
+
+    let whole = |score: RelevanceScore, start: usize| Highlight {
+        score: score as u32,
+        spans: vec![lowered.span(start, start + query_lower.len())],
+    };
+
+    if text_lower == query_lower {
+        return whole(RelevanceScore::Exact, 0);
+    }
+
+    if text_lower.starts_with(&query_lower) {
+        return whole(RelevanceScore::StartsWith, 0);
+    }
+
+    for (offset, word) in word_offsets(text_lower) {
+        if word.starts_with(&query_lower) {
+            return whole(RelevanceScore::WordBoundary, offset);
+        }
+    }
+
+    if let Some(pos) = text_lower.find(&query_lower) {
+        return whole(RelevanceScore::Contains, pos);
+    }
+
+    match fuzzy_positions(text_lower, &query_lower) {
+        Some(positions) => Highlight {
+            score: RelevanceScore::Fuzzy as u32,
+            spans: merge_spans(positions.into_iter().map(|(s, e)| lowered.span(s, e)).collect()),
+        },
+        None => Highlight { score: RelevanceScore::None as u32, spans: Vec::new() },
+    }
+}
+
+/// Find the spans of `text` that match `query`.
+///
+/// Convenience wrapper around [`highlight`] that discards the score.
+pub fn find_match_spans(text: &str, query: &str) -> Vec<MatchSpan> {
+    highlight(text, query).spans
+}
+
+/// Whitespace-separated words with their byte offsets, mirroring `split_whitespace`.
+fn word_offsets(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split(char::is_whitespace)
+        .filter(|w| !w.is_empty())
+        .map(move |w| (w.as_ptr() as usize - text.as_ptr() as usize, w))
+}
+
+/// Byte ranges of each query character matched greedily in order.
+fn fuzzy_positions(text: &str, query: &str) -> Option<Vec<(usize, usize)>> {
+    let mut positions = Vec::with_capacity(query.len());
+    let mut text_chars = text.char_indices();
+
+    for query_char in query.chars() {
+        loop {
+            match text_chars.next() {
+                Some((i, c)) if c == query_char => {
+                    positions.push((i, i + c.len_utf8()));
+                    break;
+                }
+                Some(_) => continue,
+                None => return None,
+            }
+        }
+    }
+
+    Some(positions)
+}
+
+/// Merge adjacent or overlapping spans (input must be sorted).
+fn merge_spans(spans: Vec<MatchSpan>) -> Vec<MatchSpan> {
+    let mut merged: Vec<MatchSpan> = Vec::with_capacity(spans.len());
+    for span in spans {
+        match merged.last_mut() {
+            Some(last) if span.start <= last.end => {
+                last.end = last.end.max(span.end);
+                last.char_end = last.char_end.max(span.char_end);
+            }
+            _ => merged.push(span),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranges(spans: &[MatchSpan]) -> Vec<(usize, usize)> {
+        spans.iter().map(|s| (s.start, s.end)).collect()
+    }
+
+    #[test]
+    fn test_highlight_starts_with() {
+        let h = highlight("Hello World", "hello");
+        assert_eq!(h.score, RelevanceScore::StartsWith as u32);
+        assert_eq!(ranges(&h.spans), vec![(0, 5)]);
+    }
+
+    #[test]
+    fn test_highlight_word_boundary() {
+        let h = highlight("Say  Hello", "hel");
+        assert_eq!(h.score, RelevanceScore::WordBoundary as u32);
+        assert_eq!(ranges(&h.spans), vec![(5, 8)]);
+    }
+
+    #[test]
+    fn test_highlight_fuzzy_merges_adjacent() {
+        let h = highlight("hello world", "hewo");
+        assert_eq!(h.score, RelevanceScore::Fuzzy as u32);
+        assert_eq!(ranges(&h.spans), vec![(0, 2), (6, 8)]);
+    }
+
+    #[test]
+    fn test_highlight_multibyte_offsets() {
+        let text = "Crème Brûlée";
+        let h = highlight(text, "brûl");
+        assert_eq!(h.spans.len(), 1);
+        assert_eq!(&text[h.spans[0].start..h.spans[0].end], "Brûl");
+        assert_eq!((h.spans[0].char_start, h.spans[0].char_end), (6, 10));
+    }
+
+    #[test]
+    fn test_highlight_score_matches_relevance() {
+        for (text, query) in [("Apple Pie", "pie"), ("Pineapple", "apple"), ("Goodbye", "hello")] {
+            assert_eq!(highlight(text, query).score, crate::calculate_relevance(text, query));
+        }
+    }
+
+    #[test]
+    fn test_no_match_has_no_spans() {
+        assert!(find_match_spans("Goodbye", "hello").is_empty());
+    }
+}
@@ -0,0 +1,680 @@
+//! Inverted index with an on-disk format and incremental updates.
+//!
+//! [`InvertedIndex`] is the in-memory structure: analyzed terms mapped to the
+//! documents containing them. [`PersistentIndex`] stores it on disk as a
+//! checksummed snapshot plus an append-only update log, so opening an index
+//! does not re-analyze every document:
+//!
+//! - `<path>`     - snapshot: magic, format version, length, SHA-256, JSON payload
+//! - `<path>.log` - one checksummed add/remove operation per line
+//!
+//! Updates are appended to the log and replayed on open. [`PersistentIndex::compact`]
+//! folds the log into a fresh snapshot and drops postings of removed documents.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::facet::AttributeValue;
+use crate::phonetic::PhoneticConfig;
+use crate::relevance::RelevanceScore;
+use crate::topk::TopK;
+use crate::{Analyzer, Result, SearchError, SearchResult};
+
+/// Current on-disk format version.
+pub const INDEX_FORMAT_VERSION: u32 = 1;
+
+/// Magic bytes at the start of every snapshot file.
+const MAGIC: &[u8; 4] = b"FSIX";
+
+/// Snapshot header: magic + version + payload length + SHA-256.
+const HEADER_LEN: usize = 4 + 4 + 8 + 32;
+
+/// Prefix marking phonetic codes in the postings (never produced by tokenization).
+const PHONETIC_PREFIX: char = '~';
+
+/// Hex characters of the SHA-256 used to checksum each log line.
+const LOG_CHECKSUM_LEN: usize = 16;
+
+/// An indexed document with named text fields and typed attributes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Document {
+    /// Unique document ID
+    pub id: String,
+    /// Text fields by name
+    pub fields: BTreeMap<String, String>,
+    /// Typed attributes used for filtering and facets (not searched as text)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: BTreeMap<String, AttributeValue>,
+}
+
+impl Document {
+    /// Creates a document with no fields.
+    pub fn new(id: impl Into<String>) -> Self {
+        Self { id: id.into(), fields: BTreeMap::new(), attributes: BTreeMap::new() }
+    }
+
+    /// Adds a text field.
+    pub fn with_field(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.fields.insert(name.into(), value.into());
+        self
+    }
+
+    /// Adds a typed attribute.
+    pub fn with_attribute(mut self, name: impl Into<String>, value: impl Into<AttributeValue>) -> Self {
+        self.attributes.insert(name.into(), value.into());
+        self
+    }
+
+    /// Returns an attribute value.
+    pub fn attribute(&self, name: &str) -> Option<&AttributeValue> {
+        self.attributes.get(name)
+    }
+
+    /// Returns a field value.
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields.get(name).map(String::as_str)
+    }
+}
+
+/// In-memory inverted index.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InvertedIndex {
+    analyzer: Analyzer,
+    docs: BTreeMap<u32, Document>,
+    ids: HashMap<String, u32>,
+    postings: BTreeMap<String, Vec<u32>>,
+    next_doc: u32,
+    stale_postings: usize,
+    #[serde(default)]
+    phonetic: Option<PhoneticConfig>,
+}
+
+impl InvertedIndex {
+    /// Creates an empty index using the given analyzer.
+    pub fn new(analyzer: Analyzer) -> Self {
+        Self { analyzer, ..Self::default() }
+    }
+
+    /// Enables phonetic matching for the configured fields.
+    pub fn with_phonetic(mut self, config: PhoneticConfig) -> Self {
+        self.set_phonetic(Some(config));
+        self
+    }
+
+    /// Changes phonetic matching, re-indexing existing documents.
+    pub fn set_phonetic(&mut self, config: Option<PhoneticConfig>) {
+        self.phonetic = config;
+        let docs = std::mem::take(&mut self.docs);
+        self.ids.clear();
+        self.postings.clear();
+        self.stale_postings = 0;
+        for doc in docs.into_values() {
+            self.add(doc);
+        }
+    }
+
+    /// Returns the phonetic configuration, if enabled.
+    pub fn phonetic(&self) -> Option<&PhoneticConfig> {
+        self.phonetic.as_ref()
+    }
+
+    /// Returns the analyzer used for documents and queries.
+    pub fn analyzer(&self) -> &Analyzer {
+        &self.analyzer
+    }
+
+    /// Number of live documents.
+    pub fn len(&self) -> usize {
+        self.docs.len()
+    }
+
+    /// Returns true if the index holds no documents.
+    pub fn is_empty(&self) -> bool {
+        self.docs.is_empty()
+    }
+
+    /// Number of distinct terms.
+    pub fn term_count(&self) -> usize {
+        self.postings.len()
+    }
+
+    /// Returns a document by ID.
+    pub fn get(&self, id: &str) -> Option<&Document> {
+        self.ids.get(id).and_then(|n| self.docs.get(n))
+    }
+
+    /// Iterates over live documents.
+    pub fn documents(&self) -> impl Iterator<Item = &Document> {
+        self.docs.values()
+    }
+
+    /// Adds a document, replacing any existing document with the same ID.
+    pub fn add(&mut self, doc: Document) {
+        self.remove(&doc.id);
+
+        let num = self.next_doc;
+        self.next_doc += 1;
+
+        let terms = self.terms(&doc);
+        for term in terms {
+            // Doc numbers only grow, so pushing keeps postings sorted
+            self.postings.entry(term).or_default().push(num);
+        }
+
+        self.ids.insert(doc.id.clone(), num);
+        self.docs.insert(num, doc);
+    }
+
+    /// Removes a document. Returns true if it existed.
+    ///
+    /// Postings referencing the document are left in place until [`compact`](Self::compact).
+    pub fn remove(&mut self, id: &str) -> bool {
+        let Some(num) = self.ids.remove(id) else {
+            return false;
+        };
+        if let Some(doc) = self.docs.remove(&num) {
+            self.stale_postings += self.terms(&doc).len();
+        }
+        true
+    }
+
+    /// Distinct terms of a document, including phonetic codes.
+    fn terms(&self, doc: &Document) -> Vec<String> {
+        let mut terms = Vec::new();
+        for (name, value) in &doc.fields {
+            let tokens = self.analyzer.tokenize(value);
+            if let Some(phonetic) = self.phonetic.as_ref().filter(|p| p.applies_to(name)) {
+                terms.extend(tokens.iter().filter_map(|t| phonetic_term(phonetic, t)));
+            }
+            terms.extend(tokens);
+        }
+        terms.sort_unstable();
+        terms.dedup();
+        terms
+    }
+
+    /// Number of postings entries that reference removed documents.
+    pub fn stale_postings(&self) -> usize {
+        self.stale_postings
+    }
+
+    /// Drops postings of removed documents and empty terms.
+    pub fn compact(&mut self) {
+        let docs = &self.docs;
+        for list in self.postings.values_mut() {
+            list.retain(|n| docs.contains_key(n));
+        }
+        self.postings.retain(|_, list| !list.is_empty());
+        self.stale_postings = 0;
+    }
+
+    /// Documents containing a term that starts with each query token.
+    ///
+    /// Returns internal document numbers in ascending order.
+    fn candidates(&self, query: &str) -> Vec<u32> {
+        let mut nums: Vec<u32> = Vec::new();
+        for token in self.analyzer.tokenize(query) {
+            for (_, list) in self
+                .postings
+                .range(token.clone()..)
+                .take_while(|(term, _)| term.starts_with(&token))
+            {
+                nums.extend(list.iter().filter(|n| self.docs.contains_key(n)));
+            }
+            if let Some(list) = self
+                .phonetic
+                .as_ref()
+                .and_then(|p| phonetic_term(p, &token))
+                .and_then(|term| self.postings.get(&term))
+            {
+                nums.extend(list.iter().filter(|n| self.docs.contains_key(n)));
+            }
+        }
+        nums.sort_unstable();
+        nums.dedup();
+        nums
+    }
+
+    /// Score a document: the best relevance across its fields.
+    ///
+    /// Phonetic fields without a stronger text match score [`RelevanceScore::Phonetic`]
+    /// when every query token sounds like one of the field's tokens.
+    fn score(&self, doc: &Document, query: &str) -> u32 {
+        let text_score = doc
+            .fields
+            .values()
+            .map(|v| crate::calculate_relevance(&self.analyzer.normalize(v), query))
+            .max()
+            .unwrap_or(0);
+        if text_score >= RelevanceScore::Phonetic as u32 {
+            return text_score;
+        }
+
+        let Some(phonetic) = &self.phonetic else {
+            return text_score;
+        };
+        let query_codes: Vec<String> = self
+            .analyzer
+            .tokenize(query)
+            .iter()
+            .filter_map(|t| phonetic_term(phonetic, t))
+            .collect();
+        if query_codes.is_empty() {
+            return text_score;
+        }
+
+        let sounds_alike = doc.fields.iter().filter(|(name, _)| phonetic.applies_to(name)).any(|(_, value)| {
+            let codes: Vec<String> = self
+                .analyzer
+                .tokenize(value)
+                .iter()
+                .filter_map(|t| phonetic_term(phonetic, t))
+                .collect();
+            query_codes.iter().all(|q| codes.contains(q))
+        });
+
+        if sounds_alike { RelevanceScore::Phonetic as u32 } else { text_score }
+    }
+
+    /// Scored matches for a query, in document order.
+    ///
+    /// A blank query matches every document with a score of zero.
+    pub(crate) fn matches(&self, query: &str) -> Vec<(&Document, u32)> {
+        if query.trim().is_empty() {
+            return self.docs.values().map(|doc| (doc, 0)).collect();
+        }
+
+        let normalized = self.analyzer.normalize(query);
+        self.candidates(query)
+            .into_iter()
+            .filter_map(|n| self.docs.get(&n))
+            .filter_map(|doc| {
+                let score = self.score(doc, &normalized);
+                (score > 0).then_some((doc, score))
+            })
+            .collect()
+    }
+
+    /// Sort matches by score (ties by ID), truncate and attach highlights.
+    pub(crate) fn rank<'a>(
+        &self,
+        mut matches: Vec<(&'a Document, u32)>,
+        query: &str,
+        limit: usize,
+    ) -> Vec<SearchResult<&'a Document>> {
+        if limit > 0 {
+            let mut top = TopK::new(limit);
+            for (doc, score) in matches {
+                top.push(score, doc.id.as_str(), doc);
+            }
+            matches = top.into_sorted();
+        } else {
+            matches.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.id.cmp(&b.0.id)));
+        }
+        matches
+            .into_iter()
+            .map(|(doc, score)| self.with_highlights(SearchResult::new(doc, score), query))
+            .collect()
+    }
+
+    /// Search the index.
+    ///
+    /// Candidates are documents with a term starting with one of the query
+    /// tokens; they are scored with [`calculate_relevance`](crate::calculate_relevance)
+    /// against each field and sorted by score (ties by ID).
+    ///
+    /// # Arguments
+    /// * `query` - Search query
+    /// * `limit` - Maximum results to return (0 for all)
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchResult<&Document>> {
+        if query.trim().is_empty() {
+            return Vec::new();
+        }
+        self.rank(self.matches(query), query, limit)
+    }
+
+    /// Attach highlight spans for every field that matches the raw query.
+    fn with_highlights<'a>(&self, mut result: SearchResult<&'a Document>, query: &str) -> SearchResult<&'a Document> {
+        for (name, value) in &result.item.fields {
+            let spans = crate::find_match_spans(value, query);
+            if !spans.is_empty() {
+                result = result.with_highlight(name.clone(), spans);
+            }
+        }
+        result
+    }
+}
+
+/// Postings term for the phonetic code of a token, if it has one.
+fn phonetic_term(config: &PhoneticConfig, token: &str) -> Option<String> {
+    let code = config.algorithm.encode(token);
+    (!code.is_empty()).then(|| format!("{PHONETIC_PREFIX}{code}"))
+}
+
+/// A logged index update.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum LogOp {
+    Add { doc: Document },
+    Remove { id: String },
+}
+
+/// An [`InvertedIndex`] backed by a snapshot file and an update log.
+#[derive(Debug)]
+pub struct PersistentIndex {
+    path: PathBuf,
+    index: InvertedIndex,
+    log: File,
+    log_entries: usize,
+}
+
+impl PersistentIndex {
+    /// Creates a new empty index at `path`, overwriting any existing one.
+    pub fn create(path: impl AsRef<Path>, analyzer: Analyzer) -> Result<Self> {
+        Self::build(path, analyzer, std::iter::empty())
+    }
+
+    /// Builds an index from documents and writes it to `path`.
+    pub fn build(
+        path: impl AsRef<Path>,
+        analyzer: Analyzer,
+        docs: impl IntoIterator<Item = Document>,
+    ) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut index = InvertedIndex::new(analyzer);
+        for doc in docs {
+            index.add(doc);
+        }
+
+        write_snapshot(&path, &index)?;
+        let log = File::create(log_path(&path))?;
+
+        Ok(Self { path, index, log, log_entries: 0 })
+    }
+
+    /// Opens an existing index, verifying the snapshot and replaying the log.
+    ///
+    /// A truncated final log line (from an interrupted write) is discarded;
+    /// any other checksum mismatch is reported as corruption.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut index = read_snapshot(&path)?;
+
+        let log_path = log_path(&path);
+        let mut log_entries = 0;
+        let mut valid_len = 0u64;
+        if log_path.exists() {
+            let reader = BufReader::new(File::open(&log_path)?);
+            let mut lines = reader.lines().peekable();
+            while let Some(line) = lines.next() {
+                let line = line?;
+                let is_last = lines.peek().is_none();
+                match parse_log_line(&line) {
+                    Some(LogOp::Add { doc }) => index.add(doc),
+                    Some(LogOp::Remove { id }) => {
+                        index.remove(&id);
+                    }
+                    None if is_last => break,
+                    None => {
+                        return Err(SearchError::Corrupted(format!(
+                            "{}: bad checksum at entry {}",
+                            log_path.display(),
+                            log_entries + 1
+                        )))
+                    }
+                }
+                log_entries += 1;
+                valid_len += line.len() as u64 + 1;
+            }
+        }
+
+        let log = OpenOptions::new().create(true).append(true).open(&log_path)?;
+        log.set_len(valid_len)?;
+
+        Ok(Self { path, index, log, log_entries })
+    }
+
+    /// Returns the in-memory index.
+    pub fn index(&self) -> &InvertedIndex {
+        &self.index
+    }
+
+    /// Path of the snapshot file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Number of updates in the log since the last compaction.
+    pub fn pending_updates(&self) -> usize {
+        self.log_entries
+    }
+
+    /// Adds or replaces a document.
+    pub fn add(&mut self, doc: Document) -> Result<()> {
+        self.append(&LogOp::Add { doc: doc.clone() })?;
+        self.index.add(doc);
+        Ok(())
+    }
+
+    /// Removes a document. Returns true if it existed.
+    pub fn remove(&mut self, id: &str) -> Result<bool> {
+        if self.index.get(id).is_none() {
+            return Ok(false);
+        }
+        self.append(&LogOp::Remove { id: id.to_string() })?;
+        Ok(self.index.remove(id))
+    }
+
+    /// Folds the update log into a new snapshot and drops stale postings.
+    ///
+    /// The snapshot is written to a temporary file and renamed into place.
+    pub fn compact(&mut self) -> Result<()> {
+        self.index.compact();
+        write_snapshot(&self.path, &self.index)?;
+        self.log.set_len(0)?;
+        self.log_entries = 0;
+        Ok(())
+    }
+
+    fn append(&mut self, op: &LogOp) -> Result<()> {
+        let json = serde_json::to_string(op).map_err(|e| SearchError::IndexError(e.to_string()))?;
+        writeln!(self.log, "{}\t{}", log_checksum(&json), json)?;
+        self.log.flush()?;
+        self.log_entries += 1;
+        Ok(())
+    }
+}
+
+fn log_path(path: &Path) -> PathBuf {
+    let mut p = path.as_os_str().to_owned();
+    p.push(".log");
+    PathBuf::from(p)
+}
+
+fn log_checksum(json: &str) -> String {
+    let mut hex = hex::encode(Sha256::digest(json.as_bytes()));
+    hex.truncate(LOG_CHECKSUM_LEN);
+    hex
+}
+
+fn parse_log_line(line: &str) -> Option<LogOp> {
+    let (checksum, json) = line.split_once('\t')?;
+    if checksum != log_checksum(json) {
+        return None;
+    }
+    serde_json::from_str(json).ok()
+}
+
+fn write_snapshot(path: &Path, index: &InvertedIndex) -> Result<()> {
+    let payload = serde_json::to_vec(index).map_err(|e| SearchError::IndexError(e.to_string()))?;
+
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+
+    {
+        let mut file = File::create(&tmp)?;
+        file.write_all(MAGIC)?;
+        file.write_all(&INDEX_FORMAT_VERSION.to_le_bytes())?;
+        file.write_all(&(payload.len() as u64).to_le_bytes())?;
+        file.write_all(&Sha256::digest(&payload))?;
+        file.write_all(&payload)?;
+        file.sync_all()?;
+    }
+
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+fn read_snapshot(path: &Path) -> Result<InvertedIndex> {
+    let mut bytes = Vec::new();
+    File::open(path)?.read_to_end(&mut bytes)?;
+
+    let corrupted = |reason: &str| SearchError::Corrupted(format!("{}: {reason}", path.display()));
+
+    if bytes.len() < HEADER_LEN || &bytes[..4] != MAGIC {
+        return Err(corrupted("not a search index"));
+    }
+
+    let version = u32::from_le_bytes(bytes[4..8].try_into().expect("4-byte slice"));
+    if version != INDEX_FORMAT_VERSION {
+        return Err(SearchError::UnsupportedVersion { found: version, expected: INDEX_FORMAT_VERSION });
+    }
+
+    let len = u64::from_le_bytes(bytes[8..16].try_into().expect("8-byte slice"));
+    let payload = &bytes[HEADER_LEN..];
+    if payload.len() as u64 != len {
+        return Err(corrupted("truncated payload"));
+    }
+    if Sha256::digest(payload).as_slice() != &bytes[16..HEADER_LEN] {
+        return Err(corrupted("checksum mismatch"));
+    }
+
+    serde_json::from_slice(payload).map_err(|e| corrupted(&e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listing(id: &str, title: &str) -> Document {
+        Document::new(id).with_field("title", title)
+    }
+
+    #[test]
+    fn test_search_prefix_terms() {
+        let mut index = InvertedIndex::new(Analyzer::default());
+        index.add(listing("1", "Fresh Apples"));
+        index.add(listing("2", "Apple Pie"));
+        index.add(listing("3", "Carrots"));
+
+        let results = index.search("apple", 0);
+        let ids: Vec<&str> = results.iter().map(|r| r.item.id.as_str()).collect();
+        assert_eq!(ids, vec!["2", "1"]);
+        assert_eq!(results[0].highlights[0].field, "title");
+    }
+
+    #[test]
+    fn test_phonetic_fields_only() {
+        use crate::phonetic::PhoneticAlgorithm;
+
+        let mut index = InvertedIndex::new(Analyzer::default())
+            .with_phonetic(PhoneticConfig::new(PhoneticAlgorithm::Metaphone, ["name"]));
+        index.add(Document::new("1").with_field("name", "Michelle Dupont"));
+        index.add(Document::new("2").with_field("title", "Michelle's muffins"));
+
+        let results = index.search("michele", 0);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].item.id, "1");
+        assert_eq!(results[0].score, RelevanceScore::Phonetic as u32);
+    }
+
+    #[test]
+    fn test_replace_and_remove() {
+        let mut index = InvertedIndex::new(Analyzer::default());
+        index.add(listing("1", "Bread"));
+        index.add(listing("1", "Milk"));
+        assert_eq!(index.len(), 1);
+        assert!(index.search("bread", 0).is_empty());
+
+        assert!(index.remove("1"));
+        assert!(!index.remove("1"));
+        assert!(index.stale_postings() > 0);
+        index.compact();
+        assert_eq!(index.term_count(), 0);
+    }
+
+    #[test]
+    fn test_persist_and_replay_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("listings.idx");
+
+        let mut index = PersistentIndex::build(&path, Analyzer::default(), vec![listing("1", "Café")]).unwrap();
+        index.add(listing("2", "Bagels")).unwrap();
+        index.remove("1").unwrap();
+        drop(index);
+
+        let mut index = PersistentIndex::open(&path).unwrap();
+        assert_eq!(index.pending_updates(), 2);
+        assert_eq!(index.index().len(), 1);
+        assert!(index.index().get("2").is_some());
+
+        index.compact().unwrap();
+        drop(index);
+        let index = PersistentIndex::open(&path).unwrap();
+        assert_eq!(index.pending_updates(), 0);
+        assert_eq!(index.index().search("bagel", 0).len(), 1);
+    }
+
+    #[test]
+    fn test_detects_corrupt_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("listings.idx");
+        PersistentIndex::build(&path, Analyzer::default(), vec![listing("1", "Soup")]).unwrap();
+
+        let mut bytes = std::fs::read(&path).unwrap();
+        let last = bytes.len() - 2;
+        bytes[last] ^= 0xff;
+        std::fs::write(&path, bytes).unwrap();
+
+        assert!(matches!(PersistentIndex::open(&path), Err(SearchError::Corrupted(_))));
+    }
+
+    #[test]
+    fn test_rejects_unknown_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("listings.idx");
+        PersistentIndex::create(&path, Analyzer::default()).unwrap();
+
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[4..8].copy_from_slice(&99u32.to_le_bytes());
+        std::fs::write(&path, bytes).unwrap();
+
+        assert!(matches!(
+            PersistentIndex::open(&path),
+            Err(SearchError::UnsupportedVersion { found: 99, .. })
+        ));
+    }
+
+    #[test]
+    fn test_discards_torn_log_tail() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("listings.idx");
+        let mut index = PersistentIndex::create(&path, Analyzer::default()).unwrap();
+        index.add(listing("1", "Rice")).unwrap();
+        drop(index);
+
+        let mut log = OpenOptions::new().append(true).open(log_path(&path)).unwrap();
+        log.write_all(b"deadbeef\t{\"op\":\"add\"").unwrap();
+        drop(log);
+
+        let index = PersistentIndex::open(&path).unwrap();
+        assert_eq!(index.index().len(), 1);
+        assert_eq!(index.pending_updates(), 1);
+    }
+}
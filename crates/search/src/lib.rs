@@ -3,14 +3,31 @@
 //! This crate provides:
 //! - Multi-level relevance scoring
 //! - Levenshtein edit distance
+//! - Match highlighting spans
 //! - Unicode-aware tokenization
-//! - Thread-safe caching
+//! - Normalization, diacritic folding and transliteration
+//! - Synonym and stop-word dictionaries
+//! - Persistent inverted index with incremental updates
+//! - Faceted filtering and aggregation
+//! - Phonetic matching for name fields
+//! - Top-k selection with early termination
+//! - Query language with field filters, phrases, negation and ranges
+//! - Thread-safe LRU query caching with invalidation hooks
 
 #![warn(missing_docs)]
 
 mod relevance;
+pub mod analyzer;
+pub mod dictionary;
+pub mod index;
+pub mod facet;
+pub mod phonetic;
+pub mod topk;
+pub mod query;
+pub mod cache;
 mod fuzzy;
 mod error;
+mod highlight;
 
 #[cfg(feature = "wasm")]
 mod wasm;
@@ -18,6 +35,15 @@ mod wasm;
 pub use relevance::{calculate_relevance, RelevanceScore};
 pub use fuzzy::{fuzzy_match, levenshtein_distance};
 pub use error::{SearchError, Result};
+pub use analyzer::Analyzer;
+pub use dictionary::{Dictionary, DictionaryConfig, ExpandedQuery};
+pub use facet::{AttributeValue, FacetCounts, FacetRequest, Filter, RangeBucket, SearchRequest, SearchResponse};
+pub use phonetic::{PhoneticAlgorithm, PhoneticConfig};
+pub use topk::{search_top_k, top_k, top_k_bounded};
+pub use query::{Query, RangeOp};
+pub use cache::{CacheConfig, CacheKey, CachedIndex, InvalidationReason, QueryCache};
+pub use index::{Document, InvertedIndex, PersistentIndex};
+pub use highlight::{find_match_spans, highlight, FieldHighlight, Highlight, MatchSpan};
 
 /// Search result with relevance score.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -26,4 +52,20 @@ pub struct SearchResult<T> {
     pub item: T,
     /// Relevance score (higher is better)
     pub score: u32,
+    /// Matched spans per field, for highlighting in UIs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub highlights: Vec<FieldHighlight>,
+}
+
+impl<T> SearchResult<T> {
+    /// Creates a result without highlights.
+    pub fn new(item: T, score: u32) -> Self {
+        Self { item, score, highlights: Vec::new() }
+    }
+
+    /// Adds the matched spans for a field.
+    pub fn with_highlight(mut self, field: impl Into<String>, spans: Vec<MatchSpan>) -> Self {
+        self.highlights.push(FieldHighlight { field: field.into(), spans });
+        self
+    }
 }
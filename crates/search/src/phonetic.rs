@@ -0,0 +1,271 @@
+//! Phonetic encoders for name matching.
+//!
+//! Encodes words by pronunciation so spelling variants ("Michele"/"Michelle",
+//! "Aleksandr"/"Alexander") produce the same code. Intended for person and
+//! name fields only; see [`PhoneticConfig`].
+
+use serde::{Deserialize, Serialize};
+
+/// Phonetic encoding algorithm.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PhoneticAlgorithm {
+    /// American Soundex (letter + three digits)
+    Soundex,
+    /// Original Metaphone (variable-length consonant skeleton)
+    #[default]
+    Metaphone,
+}
+
+impl PhoneticAlgorithm {
+    /// Encode a single word. Returns an empty string if it has no ASCII letters.
+    pub fn encode(self, word: &str) -> String {
+        match self {
+            Self::Soundex => soundex(word),
+            Self::Metaphone => metaphone(word),
+        }
+    }
+}
+
+/// Which fields get phonetic matching, and with which algorithm.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhoneticConfig {
+    /// Encoding algorithm
+    #[serde(default)]
+    pub algorithm: PhoneticAlgorithm,
+    /// Field names to encode (e.g. "name", "display_name")
+    pub fields: Vec<String>,
+}
+
+impl PhoneticConfig {
+    /// Enable phonetic matching for the given fields.
+    pub fn new<S: Into<String>>(algorithm: PhoneticAlgorithm, fields: impl IntoIterator<Item = S>) -> Self {
+        Self { algorithm, fields: fields.into_iter().map(Into::into).collect() }
+    }
+
+    /// Returns true if `field` is phonetically encoded.
+    pub fn applies_to(&self, field: &str) -> bool {
+        self.fields.iter().any(|f| f == field)
+    }
+}
+
+fn ascii_letters(word: &str) -> Vec<u8> {
+    word.bytes()
+        .filter(u8::is_ascii_alphabetic)
+        .map(|b| b.to_ascii_uppercase())
+        .collect()
+}
+
+/// Encode a word with American Soundex.
+///
+/// # Example
+/// ```
+/// use foodshare_search::phonetic::soundex;
+///
+/// assert_eq!(soundex("Robert"), "R163");
+/// assert_eq!(soundex("Rupert"), "R163");
+/// ```
+pub fn soundex(word: &str) -> String {
+    fn digit(b: u8) -> u8 {
+        match b {
+            b'B' | b'F' | b'P' | b'V' => b'1',
+            b'C' | b'G' | b'J' | b'K' | b'Q' | b'S' | b'X' | b'Z' => b'2',
+            b'D' | b'T' => b'3',
+            b'L' => b'4',
+            b'M' | b'N' => b'5',
+            b'R' => b'6',
+            _ => b'0',
+        }
+    }
+
+    let letters = ascii_letters(word);
+    let Some(&first) = letters.first() else {
+        return String::new();
+    };
+
+    let mut code = vec![first];
+    let mut last = digit(first);
+    for &b in &letters[1..] {
+        let d = digit(b);
+        if d != b'0' && d != last {
+            code.push(d);
+            if code.len() == 4 {
+                break;
+            }
+        }
+        // H and W do not separate letters with the same code; vowels do
+        if b != b'H' && b != b'W' {
+            last = d;
+        }
+    }
+    code.resize(4, b'0');
+
+    String::from_utf8(code).unwrap_or_default()
+}
+
+/// Encode a word with the original Metaphone algorithm.
+///
+/// # Example
+/// ```
+/// use foodshare_search::phonetic::metaphone;
+///
+/// assert_eq!(metaphone("Michele"), metaphone("Michelle"));
+/// assert_eq!(metaphone("Knight"), "NT");
+/// ```
+pub fn metaphone(word: &str) -> String {
+    let mut w = ascii_letters(word);
+    if w.is_empty() {
+        return String::new();
+    }
+
+    // Initial letter exceptions
+    match (w[0], w.get(1).copied()) {
+        (b'A', Some(b'E')) | (b'G' | b'K' | b'P', Some(b'N')) | (b'W', Some(b'R')) => {
+            w.remove(0);
+        }
+        (b'X', _) => w[0] = b'S',
+        (b'W', Some(b'H')) => {
+            w.remove(1);
+        }
+        _ => {}
+    }
+
+    let is_vowel = |b: u8| matches!(b, b'A' | b'E' | b'I' | b'O' | b'U');
+    let at = |i: usize| w.get(i).copied().unwrap_or(0);
+    let mut out = String::with_capacity(w.len());
+
+    for i in 0..w.len() {
+        let c = w[i];
+        let prev = if i > 0 { w[i - 1] } else { 0 };
+        let next = at(i + 1);
+        let next2 = at(i + 2);
+
+        // Skip doubled letters except C
+        if c == prev && c != b'C' {
+            continue;
+        }
+
+        match c {
+            b'A' | b'E' | b'I' | b'O' | b'U' => {
+                if i == 0 {
+                    out.push(c as char);
+                }
+            }
+            b'B' => {
+                if !(prev == b'M' && i + 1 == w.len()) {
+                    out.push('B');
+                }
+            }
+            b'C' => {
+                if next == b'I' && next2 == b'A' {
+                    out.push('X');
+                } else if next == b'H' {
+                    out.push(if prev == b'S' { 'K' } else { 'X' });
+                } else if matches!(next, b'I' | b'E' | b'Y') {
+                    if prev != b'S' {
+                        out.push('S');
+                    }
+                } else {
+                    out.push('K');
+                }
+            }
+            b'D' => {
+                if next == b'G' && matches!(next2, b'E' | b'Y' | b'I') {
+                    out.push('J');
+                } else {
+                    out.push('T');
+                }
+            }
+            b'G' => {
+                let silent_gh = next == b'H' && !(i + 2 >= w.len() || is_vowel(next2));
+                let silent_gn = next == b'N' && (i + 2 == w.len() || (&w[i + 1..] == b"NED"));
+                if silent_gh || silent_gn {
+                    continue;
+                }
+                if matches!(next, b'I' | b'E' | b'Y') && prev != b'G' {
+                    out.push('J');
+                } else {
+                    out.push('K');
+                }
+            }
+            b'H' => {
+                let after_modifier = matches!(prev, b'C' | b'S' | b'P' | b'T' | b'G');
+                // Silent after a vowel unless another vowel follows
+                if !after_modifier && (!is_vowel(prev) || is_vowel(next)) {
+                    out.push('H');
+                }
+            }
+            b'K' => {
+                if prev != b'C' {
+                    out.push('K');
+                }
+            }
+            b'P' => out.push(if next == b'H' { 'F' } else { 'P' }),
+            b'Q' => out.push('K'),
+            b'S' => {
+                if next == b'H' || (next == b'I' && matches!(next2, b'O' | b'A')) {
+                    out.push('X');
+                } else {
+                    out.push('S');
+                }
+            }
+            b'T' => {
+                if next == b'I' && matches!(next2, b'O' | b'A') {
+                    out.push('X');
+                } else if next == b'H' {
+                    out.push('0');
+                } else if !(next == b'C' && next2 == b'H') {
+                    out.push('T');
+                }
+            }
+            b'V' => out.push('F'),
+            b'W' | b'Y' => {
+                if is_vowel(next) {
+                    out.push(c as char);
+                }
+            }
+            b'X' => out.push_str("KS"),
+            b'Z' => out.push('S'),
+            _ => out.push(c as char),
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_soundex() {
+        assert_eq!(soundex("Robert"), "R163");
+        assert_eq!(soundex("Tymczak"), "T522");
+        assert_eq!(soundex("Ashcraft"), "A261");
+        assert_eq!(soundex("Lee"), "L000");
+        assert_eq!(soundex("123"), "");
+    }
+
+    #[test]
+    fn test_metaphone_name_variants() {
+        assert_eq!(metaphone("Michele"), "MXL");
+        assert_eq!(metaphone("Michelle"), "MXL");
+        assert_eq!(metaphone("Aleksandr"), metaphone("Alexander"));
+        assert_eq!(metaphone("Catherine"), metaphone("Kathryn"));
+    }
+
+    #[test]
+    fn test_metaphone_rules() {
+        assert_eq!(metaphone("Knight"), "NT");
+        assert_eq!(metaphone("Phillip"), "FLP");
+        assert_eq!(metaphone("Thompson"), "0MPSN");
+        assert_eq!(metaphone("Xavier"), "SFR");
+    }
+
+    #[test]
+    fn test_config_applies_to() {
+        let config = PhoneticConfig::new(PhoneticAlgorithm::Soundex, ["name"]);
+        assert!(config.applies_to("name"));
+        assert!(!config.applies_to("title"));
+    }
+}
@@ -0,0 +1,480 @@
+//! Query language with field filters, phrases, negation and ranges.
+//!
+//! Syntax, shared by the CLI tools and the admin UI:
+//!
+//! | Form | Meaning |
+//! |------|---------|
+//! | `apple` | any text field has a word starting with "apple" |
+//! | `"apple pie"` | any text field contains the phrase |
+//! | `category:bakery` | field or attribute equals / starts with value |
+//! | `title:"apple pie"` | phrase in a specific field |
+//! | `-vegan`, `NOT vegan` | negation |
+//! | `distance<5km`, `price>=2` | numeric ranges (`<`, `<=`, `>`, `>=`) |
+//! | `a b`, `a OR b`, `(a OR b) c` | AND (implicit), OR, grouping |
+//!
+//! Distance values accept `km`, `m` and `mi` suffixes and are normalized to
+//! kilometers, matching the convention of `distance_km`-style attributes.
+
+use serde::{Deserialize, Serialize};
+
+use crate::index::{Document, InvertedIndex};
+use crate::relevance::RelevanceScore;
+use crate::{Analyzer, Result, SearchError, SearchResult};
+
+/// Comparison operator of a range clause.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RangeOp {
+    /// `<`
+    Lt,
+    /// `<=`
+    Le,
+    /// `>`
+    Gt,
+    /// `>=`
+    Ge,
+}
+
+impl RangeOp {
+    fn test(self, lhs: f64, rhs: f64) -> bool {
+        match self {
+            Self::Lt => lhs < rhs,
+            Self::Le => lhs <= rhs,
+            Self::Gt => lhs > rhs,
+            Self::Ge => lhs >= rhs,
+        }
+    }
+}
+
+/// Parsed query AST.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Query {
+    /// Matches every document (empty query)
+    All,
+    /// Word-prefix match, optionally restricted to a field or attribute
+    Term {
+        /// Field or attribute name
+        field: Option<String>,
+        /// Value to match
+        value: String,
+    },
+    /// Contiguous word sequence, optionally restricted to a field
+    Phrase {
+        /// Field name
+        field: Option<String>,
+        /// Phrase text
+        value: String,
+    },
+    /// Numeric comparison against an attribute
+    Range {
+        /// Attribute name
+        field: String,
+        /// Comparison operator
+        op: RangeOp,
+        /// Right-hand side (distances in km)
+        value: f64,
+    },
+    /// Negation
+    Not(Box<Query>),
+    /// All sub-queries must match
+    And(Vec<Query>),
+    /// Any sub-query must match
+    Or(Vec<Query>),
+}
+
+impl Query {
+    /// Parse a query string.
+    ///
+    /// # Errors
+    /// Returns [`SearchError::InvalidQuery`] with the byte position of the problem.
+    pub fn parse(input: &str) -> Result<Self> {
+        let mut parser = Parser { input, pos: 0 };
+        let query = parser.parse_or()?;
+        parser.skip_ws();
+        if parser.pos < input.len() {
+            return Err(parser.error("unexpected ')'"));
+        }
+        Ok(query)
+    }
+
+    /// Returns true if the document satisfies the query.
+    pub fn matches(&self, doc: &Document, analyzer: &Analyzer) -> bool {
+        match self {
+            Self::All => true,
+            Self::Term { field, value } => term_matches(doc, analyzer, field.as_deref(), value),
+            Self::Phrase { field, value } => phrase_matches(doc, analyzer, field.as_deref(), value),
+            Self::Range { field, op, value } => doc
+                .attribute(field)
+                .and_then(|a| a.as_number())
+                .is_some_and(|n| op.test(n, *value)),
+            Self::Not(inner) => !inner.matches(doc, analyzer),
+            Self::And(parts) => parts.iter().all(|q| q.matches(doc, analyzer)),
+            Self::Or(parts) => parts.iter().any(|q| q.matches(doc, analyzer)),
+        }
+    }
+
+    /// Positive, unfielded text terms and phrases, used for ranking.
+    pub fn text_terms(&self) -> Vec<&str> {
+        let mut terms = Vec::new();
+        self.collect_text(&mut terms);
+        terms
+    }
+
+    fn collect_text<'a>(&'a self, out: &mut Vec<&'a str>) {
+        match self {
+            Self::Term { field: None, value } | Self::Phrase { field: None, value } => out.push(value),
+            Self::And(parts) | Self::Or(parts) => parts.iter().for_each(|q| q.collect_text(out)),
+            _ => {}
+        }
+    }
+}
+
+impl std::str::FromStr for Query {
+    type Err = SearchError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+fn field_values<'a>(doc: &'a Document, field: Option<&str>) -> Vec<&'a str> {
+    match field {
+        Some(name) => doc.field(name).into_iter().collect(),
+        None => doc.fields.values().map(String::as_str).collect(),
+    }
+}
+
+fn term_matches(doc: &Document, analyzer: &Analyzer, field: Option<&str>, value: &str) -> bool {
+    let needle = analyzer.normalize(value);
+
+    if let Some(attr) = field.and_then(|f| doc.attribute(f)) {
+        if let (Some(n), Ok(v)) = (attr.as_number(), value.parse::<f64>()) {
+            return n == v;
+        }
+        return attr.keywords().iter().any(|k| analyzer.normalize(k) == needle);
+    }
+
+    field_values(doc, field)
+        .into_iter()
+        .any(|text| analyzer.tokenize(text).iter().any(|t| t.starts_with(&needle)))
+}
+
+fn phrase_matches(doc: &Document, analyzer: &Analyzer, field: Option<&str>, value: &str) -> bool {
+    let phrase = analyzer.tokenize(value);
+    if phrase.is_empty() {
+        return true;
+    }
+    field_values(doc, field).into_iter().any(|text| {
+        analyzer
+            .tokenize(text)
+            .windows(phrase.len())
+            .any(|w| w == phrase.as_slice())
+    })
+}
+
+/// Parse a number with an optional distance unit, returning kilometers for distances.
+fn parse_number(raw: &str) -> Option<f64> {
+    let lower = raw.to_ascii_lowercase();
+    let (number, factor) = if let Some(n) = lower.strip_suffix("km") {
+        (n, 1.0)
+    } else if let Some(n) = lower.strip_suffix("mi") {
+        (n, 1.609_344)
+    } else if let Some(n) = lower.strip_suffix('m') {
+        (n, 0.001)
+    } else {
+        (lower.as_str(), 1.0)
+    };
+    number.parse::<f64>().ok().map(|n| n * factor)
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> SearchError {
+        SearchError::InvalidQuery(format!("{message} at position {}", self.pos))
+    }
+
+    fn rest(&self) -> &str {
+        &self.input[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn skip_ws(&mut self) {
+        let trimmed = self.rest().trim_start();
+        self.pos = self.input.len() - trimmed.len();
+    }
+
+    /// Returns true if a keyword follows as a whole word.
+    fn at_keyword(&self, keyword: &str) -> bool {
+        let rest = self.rest();
+        rest.starts_with(keyword)
+            && rest[keyword.len()..].chars().next().is_none_or(|c| c.is_whitespace() || c == '(')
+    }
+
+    /// Consume a keyword if it follows as a whole word.
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let matches = self.at_keyword(keyword);
+        if matches {
+            self.pos += keyword.len();
+        }
+        matches
+    }
+
+    fn parse_or(&mut self) -> Result<Query> {
+        let mut parts = vec![self.parse_and()?];
+        loop {
+            self.skip_ws();
+            if !self.eat_keyword("OR") {
+                break;
+            }
+            parts.push(self.parse_and()?);
+        }
+        Ok(if parts.len() == 1 { parts.remove(0) } else { Query::Or(parts) })
+    }
+
+    fn parse_and(&mut self) -> Result<Query> {
+        let mut parts = Vec::new();
+        loop {
+            self.skip_ws();
+            match self.peek() {
+                None | Some(')') => break,
+                _ if self.at_keyword("OR") => {
+                    if parts.is_empty() {
+                        return Err(self.error("expected a term before OR"));
+                    }
+                    break;
+                }
+                _ => parts.push(self.parse_unary()?),
+            }
+        }
+        Ok(match parts.len() {
+            0 => Query::All,
+            1 => parts.remove(0),
+            _ => Query::And(parts),
+        })
+    }
+
+    fn parse_unary(&mut self) -> Result<Query> {
+        self.skip_ws();
+        if self.eat_keyword("NOT") {
+            return Ok(Query::Not(Box::new(self.parse_unary()?)));
+        }
+        if self.peek() == Some('-') {
+            self.pos += 1;
+            if self.peek().is_none_or(char::is_whitespace) {
+                return Err(self.error("expected a term after '-'"));
+            }
+            return Ok(Query::Not(Box::new(self.parse_unary()?)));
+        }
+        if self.peek() == Some('(') {
+            self.pos += 1;
+            let inner = self.parse_or()?;
+            self.skip_ws();
+            if self.peek() != Some(')') {
+                return Err(self.error("expected ')'"));
+            }
+            self.pos += 1;
+            return Ok(inner);
+        }
+        self.parse_clause()
+    }
+
+    fn parse_quoted(&mut self) -> Result<String> {
+        self.pos += 1; // opening quote
+        let Some(end) = self.rest().find('"') else {
+            return Err(self.error("unterminated quote"));
+        };
+        let value = self.rest()[..end].to_string();
+        self.pos += end + 1;
+        Ok(value)
+    }
+
+    fn read_word(&mut self, stop: impl Fn(char) -> bool) -> &str {
+        let start = self.pos;
+        let len = self.rest().find(|c: char| c.is_whitespace() || c == '(' || c == ')' || stop(c));
+        self.pos += len.unwrap_or(self.rest().len());
+        &self.input[start..self.pos]
+    }
+
+    fn parse_clause(&mut self) -> Result<Query> {
+        if self.peek() == Some('"') {
+            return Ok(Query::Phrase { field: None, value: self.parse_quoted()? });
+        }
+
+        let word = self.read_word(|c| matches!(c, ':' | '<' | '>' | '=' | '"')).to_string();
+        if word.is_empty() {
+            return Err(self.error("expected a term"));
+        }
+
+        let op = if self.rest().starts_with("<=") {
+            Some((RangeOp::Le, 2))
+        } else if self.rest().starts_with(">=") {
+            Some((RangeOp::Ge, 2))
+        } else if self.rest().starts_with('<') {
+            Some((RangeOp::Lt, 1))
+        } else if self.rest().starts_with('>') {
+            Some((RangeOp::Gt, 1))
+        } else {
+            None
+        };
+
+        if let Some((op, len)) = op {
+            self.pos += len;
+            let raw = self.read_word(|_| false).to_string();
+            let value = parse_number(&raw).ok_or_else(|| self.error(&format!("invalid number '{raw}'")))?;
+            return Ok(Query::Range { field: word, op, value });
+        }
+
+        if matches!(self.peek(), Some(':' | '=')) {
+            self.pos += 1;
+            if self.peek() == Some('"') {
+                return Ok(Query::Phrase { field: Some(word), value: self.parse_quoted()? });
+            }
+            let value = self.read_word(|_| false).to_string();
+            if value.is_empty() {
+                return Err(self.error(&format!("expected a value for '{word}'")));
+            }
+            return Ok(Query::Term { field: Some(word), value });
+        }
+
+        // A bare word that happens to contain a quote, e.g. 5"
+        let tail = self.read_word(|_| false);
+        Ok(Query::Term { field: None, value: format!("{word}{tail}") })
+    }
+}
+
+impl InvertedIndex {
+    /// Run a parsed query.
+    ///
+    /// Every document is tested against the query; matches are ranked by the
+    /// summed relevance of the query's positive free-text terms (zero for
+    /// filter-only queries, which then sort by ID). Only word-boundary or
+    /// stronger matches count, in line with the query's word-prefix semantics.
+    pub fn query(&self, query: &Query, limit: usize) -> Vec<SearchResult<&Document>> {
+        let analyzer = self.analyzer();
+        let terms: Vec<String> = query.text_terms().into_iter().map(|t| analyzer.normalize(t)).collect();
+
+        let matches: Vec<(&Document, u32)> = self
+            .documents()
+            .filter(|doc| query.matches(doc, analyzer))
+            .map(|doc| {
+                let score = terms
+                    .iter()
+                    .map(|t| {
+                        doc.fields
+                            .values()
+                            .map(|v| crate::calculate_relevance(&analyzer.normalize(v), t))
+                            .filter(|s| *s >= RelevanceScore::WordBoundary as u32)
+                            .max()
+                            .unwrap_or(0)
+                    })
+                    .sum();
+                (doc, score)
+            })
+            .collect();
+
+        self.rank(matches, &query.text_terms().join(" "), limit)
+    }
+
+    /// Parse and run a query string.
+    pub fn search_query(&self, input: &str, limit: usize) -> Result<Vec<SearchResult<&Document>>> {
+        Ok(self.query(&Query::parse(input)?, limit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(field: Option<&str>, value: &str) -> Query {
+        Query::Term { field: field.map(str::to_string), value: value.to_string() }
+    }
+
+    #[test]
+    fn test_parse_terms_and_fields() {
+        let q = Query::parse(r#"apple category:bakery title:"apple pie""#).unwrap();
+        assert_eq!(
+            q,
+            Query::And(vec![
+                term(None, "apple"),
+                term(Some("category"), "bakery"),
+                Query::Phrase { field: Some("title".into()), value: "apple pie".into() },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_negation_or_and_groups() {
+        let q = Query::parse("(bread OR bagel) -gluten NOT nuts").unwrap();
+        assert_eq!(
+            q,
+            Query::And(vec![
+                Query::Or(vec![term(None, "bread"), term(None, "bagel")]),
+                Query::Not(Box::new(term(None, "gluten"))),
+                Query::Not(Box::new(term(None, "nuts"))),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_ranges_with_units() {
+        assert_eq!(
+            Query::parse("distance<5km").unwrap(),
+            Query::Range { field: "distance".into(), op: RangeOp::Lt, value: 5.0 }
+        );
+        assert_eq!(
+            Query::parse("distance<=500m").unwrap(),
+            Query::Range { field: "distance".into(), op: RangeOp::Le, value: 0.5 }
+        );
+        assert!(matches!(Query::parse("qty>=2").unwrap(), Query::Range { op: RangeOp::Ge, .. }));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for input in [r#""unterminated"#, "(bread", "bread)", "distance<far", "- bread", "category:", "OR bread"] {
+            assert!(matches!(Query::parse(input), Err(SearchError::InvalidQuery(_))), "{input}");
+        }
+        assert_eq!(Query::parse("  ").unwrap(), Query::All);
+        assert_eq!(Query::parse("oranges").unwrap(), term(None, "oranges"));
+    }
+
+    #[test]
+    fn test_evaluate_against_index() {
+        let mut index = InvertedIndex::new(Analyzer::default());
+        index.add(
+            Document::new("1")
+                .with_field("title", "Apple pie")
+                .with_attribute("category", "bakery")
+                .with_attribute("distance", 2.0),
+        );
+        index.add(
+            Document::new("2")
+                .with_field("title", "Apple juice")
+                .with_attribute("category", "drinks")
+                .with_attribute("distance", 8.0),
+        );
+        index.add(
+            Document::new("3")
+                .with_field("title", "Crème brûlée")
+                .with_attribute("category", "Bakery")
+                .with_attribute("distance", 1.0),
+        );
+
+        let ids = |q: &str| -> Vec<String> {
+            index.search_query(q, 0).unwrap().into_iter().map(|r| r.item.id.clone()).collect()
+        };
+
+        assert_eq!(ids("apple distance<5km"), vec!["1"]);
+        assert_eq!(ids("category:bakery"), vec!["1", "3"]);
+        assert_eq!(ids("apple -juice"), vec!["1"]);
+        assert_eq!(ids(r#""creme brulee""#), vec!["3"]);
+        assert_eq!(ids("pie OR juice"), vec!["1", "2"]);
+    }
+}
@@ -7,6 +7,8 @@ pub enum RelevanceScore {
     None = 0,
     /// Fuzzy match
     Fuzzy = 10,
+    /// Sounds alike (phonetic fields only)
+    Phonetic = 15,
     /// Contains substring
     Contains = 20,
     /// Word boundary match
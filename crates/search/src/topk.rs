@@ -0,0 +1,221 @@
+//! Top-k selection with early termination.
+//!
+//! Keeps the best `k` results in a bounded min-heap instead of scoring and
+//! sorting everything. A cheap per-item upper bound lets items that cannot
+//! beat the current k-th score skip full scoring, and selection stops as soon
+//! as the heap is full of maximum scores.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+use crate::relevance::RelevanceScore;
+use crate::SearchResult;
+
+/// Highest score [`calculate_relevance`](crate::calculate_relevance) can return.
+pub const MAX_RELEVANCE: u32 = RelevanceScore::Exact as u32;
+
+/// Heap entry ordered so the *worst* result is at the top of a max-heap:
+/// lower score first, then larger tie key (later / greater items lose ties).
+struct Entry<T, K> {
+    score: u32,
+    key: K,
+    item: T,
+}
+
+impl<T, K: Ord> Ord for Entry<T, K> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        Reverse(self.score)
+            .cmp(&Reverse(other.score))
+            .then_with(|| self.key.cmp(&other.key))
+    }
+}
+
+impl<T, K: Ord> PartialOrd for Entry<T, K> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T, K: Ord> PartialEq for Entry<T, K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T, K: Ord> Eq for Entry<T, K> {}
+
+/// Bounded collector of the `k` best-scoring items.
+///
+/// Ties are broken by `K`: the smaller key wins.
+pub(crate) struct TopK<T, K> {
+    k: usize,
+    heap: BinaryHeap<Entry<T, K>>,
+}
+
+impl<T, K: Ord> TopK<T, K> {
+    pub(crate) fn new(k: usize) -> Self {
+        Self { k, heap: BinaryHeap::with_capacity(k.saturating_add(1).min(1024)) }
+    }
+
+    /// Returns true once the heap holds `k` items.
+    pub(crate) fn is_full(&self) -> bool {
+        self.heap.len() >= self.k
+    }
+
+    /// Score of the current k-th item, if full.
+    pub(crate) fn threshold(&self) -> Option<u32> {
+        if self.is_full() { self.heap.peek().map(|e| e.score) } else { None }
+    }
+
+    /// Returns true if an item bounded by `upper_bound` could still enter.
+    ///
+    /// Equal scores can enter only through a smaller tie key, which callers
+    /// iterating in key order never have, so ties are pruned too.
+    pub(crate) fn could_enter(&self, upper_bound: u32) -> bool {
+        self.threshold().is_none_or(|t| upper_bound > t)
+    }
+
+    pub(crate) fn push(&mut self, score: u32, key: K, item: T) {
+        if self.k == 0 {
+            return;
+        }
+        let entry = Entry { score, key, item };
+        if !self.is_full() {
+            self.heap.push(entry);
+        } else if let Some(mut worst) = self.heap.peek_mut() {
+            if entry < *worst {
+                *worst = entry;
+            }
+        }
+    }
+
+    /// Results sorted best first.
+    pub(crate) fn into_sorted(self) -> Vec<(T, u32)> {
+        self.heap.into_sorted_vec().into_iter().map(|e| (e.item, e.score)).collect()
+    }
+}
+
+/// Select the `k` best items by score, best first.
+///
+/// Items scoring zero are dropped. Ties keep input order. Scores must not
+/// exceed [`MAX_RELEVANCE`].
+pub fn top_k<T>(items: impl IntoIterator<Item = T>, k: usize, score: impl FnMut(&T) -> u32) -> Vec<SearchResult<T>> {
+    top_k_bounded(items, k, |_| MAX_RELEVANCE, score)
+}
+
+/// Select the `k` best items, skipping items whose upper bound cannot beat the current k-th score.
+///
+/// # Arguments
+/// * `items` - Candidates, in tie-break order
+/// * `k` - Number of results to keep
+/// * `upper_bound` - Cheap bound on an item's score; must never be below the real score
+/// * `score` - Full scoring function
+///
+/// Iteration stops early once `k` items have the bound's global maximum,
+/// [`MAX_RELEVANCE`].
+pub fn top_k_bounded<T>(
+    items: impl IntoIterator<Item = T>,
+    k: usize,
+    mut upper_bound: impl FnMut(&T) -> u32,
+    mut score: impl FnMut(&T) -> u32,
+) -> Vec<SearchResult<T>> {
+    if k == 0 {
+        return Vec::new();
+    }
+    let mut top = TopK::new(k);
+    for (seq, item) in items.into_iter().enumerate() {
+        if top.threshold() >= Some(MAX_RELEVANCE) {
+            break;
+        }
+        if !top.could_enter(upper_bound(&item)) {
+            continue;
+        }
+        let s = score(&item);
+        if s > 0 {
+            top.push(s, seq, item);
+        }
+    }
+    top.into_sorted()
+        .into_iter()
+        .map(|(item, score)| SearchResult::new(item, score))
+        .collect()
+}
+
+/// Upper bound on `calculate_relevance(text, query)` computed from lengths only.
+///
+/// A text shorter than the query cannot match; one of a different length
+/// cannot be an exact match.
+pub fn relevance_upper_bound(text: &str, query: &str) -> u32 {
+    // Lengths after lowercasing, which can expand characters ("İ" → "i̇")
+    let lowered_len = |s: &str| s.chars().map(|c| c.to_lowercase().count()).sum::<usize>();
+    let (text_len, query_len) = (lowered_len(text), lowered_len(query));
+    match text_len.cmp(&query_len) {
+        Ordering::Less => RelevanceScore::None as u32,
+        Ordering::Equal => MAX_RELEVANCE,
+        Ordering::Greater => RelevanceScore::StartsWith as u32,
+    }
+}
+
+/// Search a slice of items for the `k` most relevant, with early termination.
+///
+/// Equivalent to scoring every item with [`calculate_relevance`](crate::calculate_relevance),
+/// sorting by score and truncating to `k`, but avoids scoring items that
+/// cannot make the cut.
+pub fn search_top_k<'a, T>(
+    items: &'a [T],
+    query: &str,
+    k: usize,
+    text: impl Fn(&T) -> &str,
+) -> Vec<SearchResult<&'a T>> {
+    top_k_bounded(
+        items,
+        k,
+        |item| relevance_upper_bound(text(item), query),
+        |item| crate::calculate_relevance(text(item), query),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn naive<'a>(items: &'a [&'a str], query: &str, k: usize) -> Vec<(&'a str, u32)> {
+        let mut scored: Vec<(&str, u32)> = items
+            .iter()
+            .map(|t| (*t, crate::calculate_relevance(t, query)))
+            .filter(|(_, s)| *s > 0)
+            .collect();
+        scored.sort_by_key(|s| std::cmp::Reverse(s.1));
+        scored.truncate(k);
+        scored
+    }
+
+    #[test]
+    fn test_matches_full_sort() {
+        let items = ["apple pie", "Apple", "pineapple", "a p p l e", "banana", "apple", "crab apple", "apples"];
+        for k in 0..=items.len() {
+            let fast: Vec<(&str, u32)> =
+                search_top_k(&items, "apple", k, |t| t).into_iter().map(|r| (*r.item, r.score)).collect();
+            assert_eq!(fast, naive(&items, "apple", k), "k = {k}");
+        }
+    }
+
+    #[test]
+    fn test_early_termination() {
+        let mut scored = 0;
+        let items = vec!["milk"; 1000];
+        let results = top_k(&items, 3, |t| {
+            scored += 1;
+            crate::calculate_relevance(t, "milk")
+        });
+        assert_eq!(results.len(), 3);
+        assert_eq!(scored, 3);
+    }
+
+    #[test]
+    fn test_upper_bound_never_below_score() {
+        for (text, query) in [("Apple", "apple"), ("Apples", "apple"), ("app", "apple"), ("İstanbul", "i̇stanbul")] {
+            assert!(relevance_upper_bound(text, query) >= crate::calculate_relevance(text, query));
+        }
+    }
+}
@@ -37,7 +37,8 @@ pub fn edit_distance(a: &str, b: &str) -> usize {
 /// * `max_results` - Maximum results to return (0 for all)
 ///
 /// # Returns
-/// JSON array of results with `id` and `score` fields, sorted by score
+/// JSON array of results with `id`, `score` and `spans` fields, sorted by score.
+/// `spans` holds the matched byte and character ranges of `text`.
 #[wasm_bindgen]
 pub fn search_items(query: &str, items_json: &str, max_results: usize) -> String {
     use serde::{Deserialize, Serialize};
@@ -52,6 +53,7 @@ pub fn search_items(query: &str, items_json: &str, max_results: usize) -> String
     struct Result {
         id: String,
         score: u32,
+        spans: Vec<crate::MatchSpan>,
     }
 
     let items: Vec<Item> = match serde_json::from_str(items_json) {
@@ -62,8 +64,8 @@ pub fn search_items(query: &str, items_json: &str, max_results: usize) -> String
     let mut results: Vec<Result> = items
         .into_iter()
         .map(|item| {
-            let score = crate::calculate_relevance(&item.text, query);
-            Result { id: item.id, score }
+            let crate::Highlight { score, spans } = crate::highlight(&item.text, query);
+            Result { id: item.id, score, spans }
         })
         .filter(|r| r.score > 0)
         .collect();
@@ -0,0 +1,45 @@
+//! Error types for self-update.
+
+use thiserror::Error;
+
+/// Result type alias for self-update operations.
+pub type Result<T> = std::result::Result<T, UpdateError>;
+
+/// Errors that can occur while updating a binary.
+#[derive(Debug, Error)]
+pub enum UpdateError {
+    /// Release metadata or an asset couldn't be downloaded
+    #[error("Download failed: {0}")]
+    Download(String),
+
+    /// A signature or checksum didn't match
+    #[error("Verification failed: {0}")]
+    Verification(String),
+
+    /// No release build exists for this platform
+    #[error("No release build for this platform ({0})")]
+    UnsupportedPlatform(String),
+
+    /// A version or release tag couldn't be parsed
+    #[error("Invalid version: {0}")]
+    InvalidVersion(String),
+
+    /// The running binary couldn't be replaced
+    #[error("Failed to replace {path}: {source}")]
+    Install {
+        /// Path of the binary being replaced
+        path: String,
+        /// Underlying error
+        source: std::io::Error,
+    },
+
+    /// IO error
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl From<foodshare_crypto::CryptoError> for UpdateError {
+    fn from(e: foodshare_crypto::CryptoError) -> Self {
+        Self::Verification(e.to_string())
+    }
+}
@@ -0,0 +1,92 @@
+//! Platform artifacts and atomic replacement of the running binary.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Result, UpdateError};
+
+/// Release artifact name for this build's platform (matches the release workflow matrix)
+pub fn artifact() -> Option<&'static str> {
+    if cfg!(all(target_os = "linux", target_arch = "x86_64", target_env = "musl")) {
+        Some("linux-x64-musl")
+    } else if cfg!(all(target_os = "linux", target_arch = "x86_64")) {
+        Some("linux-x64")
+    } else if cfg!(all(target_os = "macos", target_arch = "x86_64")) {
+        Some("macos-x64")
+    } else if cfg!(all(target_os = "macos", target_arch = "aarch64")) {
+        Some("macos-arm64")
+    } else {
+        None
+    }
+}
+
+/// Name of the gzipped release asset for `binary` on this platform
+pub fn asset_name(binary: &str) -> Result<String> {
+    let artifact = artifact().ok_or_else(|| {
+        UpdateError::UnsupportedPlatform(format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH))
+    })?;
+    Ok(format!("{}-{}.gz", binary, artifact))
+}
+
+/// Path of the running binary, with symlinks resolved
+pub fn current_exe() -> Result<PathBuf> {
+    Ok(std::env::current_exe()?.canonicalize()?)
+}
+
+/// Replace `target` with `contents` in one rename
+///
+/// The new binary is written next to `target` first, so the rename never
+/// crosses filesystems and an interrupted update leaves the old one in place.
+pub fn replace_executable(target: &Path, contents: &[u8]) -> Result<()> {
+    let install_error = |source: std::io::Error| UpdateError::Install {
+        path: target.display().to_string(),
+        source,
+    };
+
+    let dir = target.parent().unwrap_or_else(|| Path::new("."));
+    let name = target.file_name().and_then(|n| n.to_str()).unwrap_or("binary");
+    let mut file = tempfile::Builder::new()
+        .prefix(&format!(".{}.update", name))
+        .tempfile_in(dir)
+        .map_err(install_error)?;
+    file.write_all(contents).map_err(install_error)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(target)
+            .map(|m| m.permissions().mode())
+            .unwrap_or(0o755);
+        file.as_file()
+            .set_permissions(std::fs::Permissions::from_mode(mode | 0o755))
+            .map_err(install_error)?;
+    }
+
+    file.as_file().sync_all().map_err(install_error)?;
+    file.persist(target).map_err(|e| install_error(e.error))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_executable() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("tool");
+        std::fs::write(&target, b"old").unwrap();
+
+        replace_executable(&target, b"new").unwrap();
+
+        assert_eq!(std::fs::read(&target).unwrap(), b"new");
+        // Only the binary is left behind
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&target).unwrap().permissions().mode();
+            assert_eq!(mode & 0o755, 0o755);
+        }
+    }
+}
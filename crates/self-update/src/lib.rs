@@ -0,0 +1,144 @@
+//! Self-update for the Foodshare CLI binaries
+//!
+//! Every binary exposes a `self-update` subcommand backed by [`run`]:
+//! - Find the latest (or a requested) release on GitHub Releases or an internal bucket
+//! - Verify the release's `checksums.txt` against its Ed25519 signature
+//! - Check that the signed checksums name the requested release
+//! - Check the downloaded binary against the signed checksums
+//! - Replace the running binary in one atomic rename
+//!
+//! Builds without an embedded release key
+//! ([`verify::RELEASE_PUBLIC_KEY`]) can check for updates but refuse to install them.
+
+pub mod error;
+pub mod install;
+pub mod release;
+pub mod verify;
+
+use clap::Args;
+use foodshare_cli::output::{self, Status};
+use foodshare_cli::progress;
+use foodshare_core::config::UpdateConfig;
+use foodshare_core::error::exit_codes;
+use semver::Version;
+
+pub use error::{Result, UpdateError};
+pub use release::ReleaseSource;
+
+/// Options for the `self-update` subcommand
+#[derive(Debug, Clone, Args)]
+pub struct SelfUpdateArgs {
+    /// Only check whether an update is available
+    #[arg(long)]
+    pub check: bool,
+
+    /// Install this version instead of the latest (allows downgrades)
+    #[arg(long, value_name = "VERSION")]
+    pub to: Option<String>,
+
+    /// Reinstall even when already on the target version
+    #[arg(long)]
+    pub force: bool,
+}
+
+/// Run `self-update` for `binary` at `current_version`; returns the exit code
+pub fn run(binary: &str, current_version: &str, args: &SelfUpdateArgs, config: &UpdateConfig) -> i32 {
+    match update(binary, current_version, args, config) {
+        Ok(code) => code,
+        Err(e) => {
+            Status::error(&e.to_string());
+            match e {
+                UpdateError::Verification(_) => exit_codes::SECURITY_ERROR,
+                UpdateError::Install { .. } => {
+                    Status::info("Re-run with permission to write the binary's directory (e.g. sudo)");
+                    exit_codes::FAILURE
+                }
+                _ => exit_codes::FAILURE,
+            }
+        }
+    }
+}
+
+fn update(binary: &str, current_version: &str, args: &SelfUpdateArgs, config: &UpdateConfig) -> Result<i32> {
+    let current = Version::parse(current_version)
+        .map_err(|e| UpdateError::InvalidVersion(format!("{}: {}", current_version, e)))?;
+    let source = ReleaseSource::from_config(config);
+    let tag = match &args.to {
+        Some(version) => release::normalize_tag(version),
+        None => source.latest_tag()?,
+    };
+    let target = release::tag_version(&tag)?;
+
+    let update_available = target > current;
+    output::data("current", &current.to_string());
+    output::data("target", &target.to_string());
+    output::data("update_available", &update_available);
+
+    if args.check {
+        if update_available {
+            Status::info(&format!("{} {} is available (installed: {})", binary, target, current));
+            Status::info(&format!("Run `{} self-update` to install it", binary));
+        } else {
+            Status::success(&format!("{} {} is up to date", binary, current));
+        }
+        return Ok(exit_codes::SUCCESS);
+    }
+
+    if !args.force && args.to.is_none() && target <= current {
+        Status::success(&format!("{} {} is up to date", binary, current));
+        return Ok(exit_codes::SUCCESS);
+    }
+    if !args.force && target == current {
+        Status::success(&format!("{} {} is already installed", binary, current));
+        return Ok(exit_codes::SUCCESS);
+    }
+
+    let public_key = verify::RELEASE_PUBLIC_KEY.ok_or_else(|| {
+        UpdateError::Verification(
+            "this build has no release signing key; install a release build to enable self-update".to_string(),
+        )
+    })?;
+    let asset = install::asset_name(binary)?;
+    let exe = install::current_exe()?;
+
+    Status::info(&format!(
+        "Updating {} {} → {} from {}",
+        binary,
+        current,
+        target,
+        source.describe()
+    ));
+
+    let checksums = release::fetch_bytes(&source.asset_url(&tag, "checksums.txt"), |_, _| {})?;
+    let signature = release::fetch_bytes(&source.asset_url(&tag, "checksums.txt.sig"), |_, _| {})?;
+    verify::verify_checksums_signature(&checksums, &signature, public_key)?;
+    let checksums = verify::parse_checksums(&String::from_utf8_lossy(&checksums));
+
+    let bar = (!output::is_machine()).then(|| progress::transfer_bar(None, "Downloading"));
+    let downloaded = release::fetch_bytes(&source.asset_url(&tag, &asset), |done, total| {
+        if let Some(ref pb) = bar {
+            progress::set_transfer(pb, done, total);
+        }
+    });
+    if let Some(ref pb) = bar {
+        match downloaded {
+            Ok(_) => progress::finish_success(pb, "Downloaded"),
+            Err(_) => progress::finish_error(pb, "Download failed"),
+        }
+    }
+    let compressed = downloaded?;
+
+    verify::verify_asset(&compressed, &checksums, &tag, &asset)?;
+    let binary_data = foodshare_compression::gzip_decompress(&compressed)
+        .map_err(|e| UpdateError::Download(format!("{}: {}", asset, e)))?;
+    install::replace_executable(&exe, &binary_data)?;
+
+    Status::success(&format!(
+        "Updated {} {} → {} ({})",
+        binary,
+        current,
+        target,
+        exe.display()
+    ));
+    Ok(exit_codes::SUCCESS)
+}
@@ -0,0 +1,168 @@
+//! Release sources: GitHub Releases or an internal bucket mirror.
+
+use std::io::Read;
+
+use foodshare_core::config::UpdateConfig;
+use serde::Deserialize;
+
+use crate::error::{Result, UpdateError};
+
+/// Where releases are published
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReleaseSource {
+    /// GitHub Releases of `owner/name`
+    GitHub {
+        /// Repository as `owner/name`
+        repository: String,
+    },
+    /// A bucket holding `latest.txt` and one directory per tag
+    Bucket {
+        /// Base URL of the bucket
+        url: String,
+    },
+}
+
+#[derive(Deserialize)]
+struct GitHubRelease {
+    tag_name: String,
+}
+
+impl ReleaseSource {
+    /// The bucket when one is configured, GitHub otherwise
+    pub fn from_config(config: &UpdateConfig) -> Self {
+        match &config.bucket_url {
+            Some(url) => Self::Bucket {
+                url: url.trim_end_matches('/').to_string(),
+            },
+            None => Self::GitHub {
+                repository: config.repository.clone(),
+            },
+        }
+    }
+
+    /// Human-readable name of the source
+    pub fn describe(&self) -> String {
+        match self {
+            Self::GitHub { repository } => format!("github.com/{}", repository),
+            Self::Bucket { url } => url.clone(),
+        }
+    }
+
+    /// Tag of the latest release
+    pub fn latest_tag(&self) -> Result<String> {
+        match self {
+            Self::GitHub { repository } => {
+                let url = format!("https://api.github.com/repos/{}/releases/latest", repository);
+                let body = fetch_text(&url)?;
+                let release: GitHubRelease =
+                    serde_json::from_str(&body).map_err(|e| UpdateError::Download(format!("{}: {}", url, e)))?;
+                Ok(release.tag_name)
+            }
+            Self::Bucket { url } => {
+                let tag = fetch_text(&format!("{}/latest.txt", url))?.trim().to_string();
+                if tag.is_empty() {
+                    return Err(UpdateError::Download(format!("{}/latest.txt is empty", url)));
+                }
+                Ok(tag)
+            }
+        }
+    }
+
+    /// URL of a release asset
+    pub fn asset_url(&self, tag: &str, asset: &str) -> String {
+        match self {
+            Self::GitHub { repository } => {
+                format!("https://github.com/{}/releases/download/{}/{}", repository, tag, asset)
+            }
+            Self::Bucket { url } => format!("{}/{}/{}", url, tag, asset),
+        }
+    }
+}
+
+/// Release tag for a version (`1.5.0` and `v1.5.0` both give `v1.5.0`)
+pub fn normalize_tag(version: &str) -> String {
+    format!("v{}", version.trim().trim_start_matches('v'))
+}
+
+/// Version of a release tag
+pub fn tag_version(tag: &str) -> Result<semver::Version> {
+    semver::Version::parse(tag.trim().trim_start_matches('v'))
+        .map_err(|e| UpdateError::InvalidVersion(format!("{}: {}", tag, e)))
+}
+
+fn http_get(url: &str) -> Result<reqwest::blocking::Response> {
+    let client = reqwest::blocking::Client::builder()
+        .user_agent(concat!("foodshare-self-update/", env!("CARGO_PKG_VERSION")))
+        .timeout(None)
+        .build()
+        .map_err(|e| UpdateError::Download(e.to_string()))?;
+
+    let mut request = client.get(url);
+    // Raises the API rate limit on shared CI runners
+    if url.starts_with("https://api.github.com/")
+        && let Ok(token) = std::env::var("GITHUB_TOKEN")
+    {
+        request = request.bearer_auth(token);
+    }
+
+    let response = request.send().map_err(|e| UpdateError::Download(e.to_string()))?;
+    if !response.status().is_success() {
+        return Err(UpdateError::Download(format!("{}: HTTP {}", url, response.status())));
+    }
+    Ok(response)
+}
+
+/// Download a small text file
+pub fn fetch_text(url: &str) -> Result<String> {
+    http_get(url)?
+        .text()
+        .map_err(|e| UpdateError::Download(format!("{}: {}", url, e)))
+}
+
+/// Download `url` into memory, calling `on_progress(downloaded, total)` as bytes arrive
+pub fn fetch_bytes(url: &str, on_progress: impl Fn(u64, Option<u64>)) -> Result<Vec<u8>> {
+    let mut response = http_get(url)?;
+    let total = response.content_length();
+    let mut data = Vec::with_capacity(total.unwrap_or(0) as usize);
+    let mut buf = vec![0u8; 64 * 1024];
+    on_progress(0, total);
+    loop {
+        let n = response.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        data.extend_from_slice(&buf[..n]);
+        on_progress(data.len() as u64, total);
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_from_config() {
+        let mut config = UpdateConfig::default();
+        let github = ReleaseSource::from_config(&config);
+        assert_eq!(
+            github.asset_url("v1.5.0", "checksums.txt"),
+            "https://github.com/Foodshareclub/foodshare-tools/releases/download/v1.5.0/checksums.txt"
+        );
+
+        config.bucket_url = Some("https://releases.example.com/tools/".to_string());
+        let bucket = ReleaseSource::from_config(&config);
+        assert_eq!(
+            bucket.asset_url("v1.5.0", "checksums.txt"),
+            "https://releases.example.com/tools/v1.5.0/checksums.txt"
+        );
+    }
+
+    #[test]
+    fn test_tags() {
+        assert_eq!(normalize_tag("1.5.0"), "v1.5.0");
+        assert_eq!(normalize_tag("v1.5.0"), "v1.5.0");
+        assert_eq!(tag_version("v1.5.0-rc.1").unwrap().to_string(), "1.5.0-rc.1");
+        assert!(tag_version("latest").is_err());
+    }
+}
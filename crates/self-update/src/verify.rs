@@ -0,0 +1,114 @@
+//! Release verification: a signed checksum list covering every asset.
+
+use std::collections::HashMap;
+
+use base64::Engine;
+use sha2::{Digest, Sha256};
+
+use crate::error::{Result, UpdateError};
+use crate::release::normalize_tag;
+
+/// Release signing key (base64 of the raw Ed25519 public key), embedded at build time
+pub const RELEASE_PUBLIC_KEY: Option<&str> = option_env!("FOODSHARE_RELEASE_PUBLIC_KEY");
+
+/// Check the detached signature over `checksums.txt`
+pub fn verify_checksums_signature(checksums: &[u8], signature: &[u8], public_key: &str) -> Result<()> {
+    let key = base64::engine::general_purpose::STANDARD
+        .decode(public_key.trim())
+        .map_err(|e| UpdateError::Verification(format!("release public key is not base64: {}", e)))?;
+    foodshare_crypto::verify_ed25519(&key, checksums, signature)?;
+    Ok(())
+}
+
+/// Signed checksum list of a release
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Checksums {
+    /// Release tag named by the `version: <tag>` line
+    pub version: Option<String>,
+    /// Asset name → hex digest
+    pub assets: HashMap<String, String>,
+}
+
+/// Parse `checksums.txt`: a `version: <tag>` line followed by `sha256sum` output
+pub fn parse_checksums(text: &str) -> Checksums {
+    let mut checksums = Checksums::default();
+    for line in text.lines() {
+        if let Some(version) = line.trim().strip_prefix("version:") {
+            checksums.version = Some(version.trim().to_string());
+            continue;
+        }
+        let Some((digest, name)) = line.trim().split_once(char::is_whitespace) else {
+            continue;
+        };
+        let name = name.trim_start().trim_start_matches('*').trim_start_matches("./");
+        checksums.assets.insert(name.to_string(), digest.to_lowercase());
+    }
+    checksums
+}
+
+/// Check downloaded `data` against its entry in the checksum list of release `tag`
+///
+/// The signed list must name `tag` as its version, so a validly signed list
+/// of another release can't be passed off as this one.
+pub fn verify_asset(data: &[u8], checksums: &Checksums, tag: &str, asset: &str) -> Result<()> {
+    match checksums.version.as_deref() {
+        Some(version) if normalize_tag(version) == normalize_tag(tag) => {}
+        Some(version) => {
+            return Err(UpdateError::Verification(format!(
+                "checksums.txt is for {}, not {}",
+                version, tag
+            )));
+        }
+        None => {
+            return Err(UpdateError::Verification("checksums.txt names no version".to_string()));
+        }
+    }
+    let expected = checksums
+        .assets
+        .get(asset)
+        .ok_or_else(|| UpdateError::Verification(format!("{} is not listed in checksums.txt", asset)))?;
+    let actual = hex::encode(Sha256::digest(data));
+    if &actual != expected {
+        return Err(UpdateError::Verification(format!(
+            "checksum mismatch for {}: expected {}, got {}",
+            asset, expected, actual
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_checksums() {
+        let checksums = parse_checksums(
+            "version: v1.4.0\nABC123  lefthook-rs-linux-x64.gz\ndef456 *./foodshare-ios-macos-arm64.gz\n\nmalformed\n",
+        );
+        assert_eq!(checksums.version.as_deref(), Some("v1.4.0"));
+        assert_eq!(checksums.assets.len(), 2);
+        assert_eq!(checksums.assets["lefthook-rs-linux-x64.gz"], "abc123");
+        assert_eq!(checksums.assets["foodshare-ios-macos-arm64.gz"], "def456");
+    }
+
+    #[test]
+    fn test_verify_asset() {
+        let digest = hex::encode(Sha256::digest(b"binary"));
+        let checksums = parse_checksums(&format!("version: v1.4.0\n{}  tool-linux-x64.gz", digest));
+
+        assert!(verify_asset(b"binary", &checksums, "v1.4.0", "tool-linux-x64.gz").is_ok());
+        assert!(verify_asset(b"tampered", &checksums, "v1.4.0", "tool-linux-x64.gz").is_err());
+        assert!(verify_asset(b"binary", &checksums, "v1.4.0", "tool-macos-x64.gz").is_err());
+    }
+
+    #[test]
+    fn test_verify_asset_checks_version() {
+        let digest = hex::encode(Sha256::digest(b"binary"));
+        let checksums = parse_checksums(&format!("version: v1.3.0\n{}  tool-linux-x64.gz", digest));
+        assert!(verify_asset(b"binary", &checksums, "v1.4.0", "tool-linux-x64.gz").is_err());
+
+        let unversioned = parse_checksums(&format!("{}  tool-linux-x64.gz", digest));
+        assert!(verify_asset(b"binary", &unversioned, "v1.4.0", "tool-linux-x64.gz").is_err());
+    }
+}
@@ -1,51 +1,209 @@
+use crate::detect::{discover_toolchains, version_from_name, ToolchainBundle};
 use crate::error::{Result, SwiftError};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
+/// Pin file naming the toolchain a repository uses
+pub const PIN_FILE: &str = ".swift-version";
+
+/// Optional project configuration file
+pub const CONFIG_FILE: &str = ".foodshare-swift.toml";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SwiftConfig {
     pub required_version: String,
+    /// Explicit toolchain bundle to use, bypassing discovery
     pub toolchain_path: Option<PathBuf>,
+    /// Toolchain to select by bundle name, identifier or major.minor version
+    pub toolchain: Option<String>,
     pub auto_configure: bool,
+    /// Packages allowed to stay on other tools-versions
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub overrides: Vec<VersionOverride>,
+}
+
+/// Allowed tools-version range for packages under a path
+///
+/// ```toml
+/// [[overrides]]
+/// path = "packages/LegacyKit/**"
+/// version = ">=5.9, <6.0"
+/// reason = "Depends on a pre-concurrency SDK"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionOverride {
+    /// Glob relative to the project root, matched against the package directory
+    /// and its `Package.swift`
+    pub path: String,
+    /// Comma-separated comparators (`>=5.9, <6.0`); a bare version like `5.10`
+    /// matches that release and its patches
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl VersionOverride {
+    /// Check if the package at `package_file` (relative to the project root) is covered
+    pub fn matches(&self, package_file: &Path) -> Result<bool> {
+        let pattern = glob::Pattern::new(&self.path)
+            .map_err(|e| SwiftError::Config(format!("invalid override path '{}': {e}", self.path)))?;
+        Ok(pattern.matches_path(package_file)
+            || package_file.parent().is_some_and(|dir| pattern.matches_path(dir)))
+    }
+
+    /// Check if `version` is within the allowed range
+    pub fn allows(&self, version: &str) -> Result<bool> {
+        let version = version_key(version);
+        for comparator in self.version.split(',').map(str::trim) {
+            let (op, bound) = ["<=", ">=", "<", ">", "="]
+                .iter()
+                .find_map(|op| comparator.strip_prefix(op).map(|rest| (*op, rest.trim())))
+                .unwrap_or(("", comparator));
+            if bound.is_empty() || bound.split('.').any(|part| part.parse::<u32>().is_err()) {
+                return Err(SwiftError::Config(format!(
+                    "invalid override version range '{}'",
+                    self.version
+                )));
+            }
+
+            let bound_key = version_key(bound);
+            let ok = match op {
+                "<=" => version <= bound_key,
+                ">=" => version >= bound_key,
+                "<" => version < bound_key,
+                ">" => version > bound_key,
+                "=" => version == bound_key,
+                // Bare versions match as a prefix, so `5.10` allows `5.10.2`
+                _ => bound
+                    .split('.')
+                    .zip(&version)
+                    .all(|(part, component)| part.parse() == Ok(*component)),
+            };
+            if !ok {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Numeric components of a dotted version, padded to major.minor.patch
+fn version_key(version: &str) -> Vec<u32> {
+    let mut key: Vec<u32> = version
+        .trim()
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect();
+    key.resize(key.len().max(3), 0);
+    key
 }
 
 impl SwiftConfig {
+    /// Load configuration for a project
+    ///
+    /// Reads `.foodshare-swift.toml` if present; the toolchain comes from the
+    /// `.swift-version` pin file unless the config file names one.
+    pub fn load(project_root: &Path) -> Result<Self> {
+        let config_file = project_root.join(CONFIG_FILE);
+        let mut config = if config_file.exists() {
+            let content = std::fs::read_to_string(&config_file)?;
+            toml::from_str::<Self>(&content)
+                .map_err(|e| SwiftError::Config(format!("{}: {e}", config_file.display())))?
+        } else {
+            return Self::from_swift_version_file(project_root);
+        };
+
+        let pinned = Self::from_swift_version_file(project_root)?;
+        if config.toolchain.is_none() {
+            config.toolchain = pinned.toolchain;
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Find the nearest directory at or above `start` with a pin or config file
+    pub fn find_project_root(start: &Path) -> Option<PathBuf> {
+        start
+            .ancestors()
+            .find(|dir| dir.join(PIN_FILE).is_file() || dir.join(CONFIG_FILE).is_file())
+            .map(Path::to_path_buf)
+    }
+
     /// Load configuration from .swift-version file
     pub fn from_swift_version_file(project_root: &Path) -> Result<Self> {
-        let swift_version_file = project_root.join(".swift-version");
-        
+        let swift_version_file = project_root.join(PIN_FILE);
+
         if swift_version_file.exists() {
             let content = std::fs::read_to_string(&swift_version_file)?;
-            let version = content.trim();
-            
+            let pin = content.trim();
+
             // Extract version number from snapshot name
-            let required_version = if version.contains("6.3") {
-                "6.3".to_string()
-            } else if version.contains("6.2") {
-                "6.2".to_string()
-            } else {
-                version.to_string()
-            };
+            let required_version = version_from_name(pin).unwrap_or_else(|| pin.to_string());
 
             Ok(Self {
                 required_version,
                 toolchain_path: None,
+                toolchain: Some(pin.to_string()),
                 auto_configure: true,
+                overrides: Vec::new(),
             })
         } else {
             Ok(Self::default())
         }
     }
 
+    /// Check that every override has a valid glob and version range
+    pub fn validate(&self) -> Result<()> {
+        for entry in &self.overrides {
+            entry.matches(Path::new(""))?;
+            entry.allows("0")?;
+        }
+        Ok(())
+    }
+
+    /// Override covering the package at `package_file`, if any
+    pub fn override_for(&self, package_file: &Path) -> Option<&VersionOverride> {
+        self.overrides
+            .iter()
+            .find(|entry| entry.matches(package_file).unwrap_or(false))
+    }
+
+    /// Select the installed toolchain this configuration asks for
+    ///
+    /// Uses `toolchain_path` if set, otherwise the newest installed bundle
+    /// matching `toolchain` (or `required_version` when nothing is pinned).
+    pub fn select_toolchain(&self) -> Result<ToolchainBundle> {
+        if let Some(ref path) = self.toolchain_path {
+            return ToolchainBundle::from_path(path);
+        }
+        let selector = self.toolchain.as_deref().unwrap_or(&self.required_version);
+        Self::find_toolchain(&discover_toolchains(), selector)
+    }
+
+    /// Pick the toolchain matching `selector` from `bundles` (newest first)
+    pub fn find_toolchain(bundles: &[ToolchainBundle], selector: &str) -> Result<ToolchainBundle> {
+        bundles
+            .iter()
+            .find(|bundle| bundle.matches(selector))
+            .cloned()
+            .ok_or_else(|| SwiftError::NoMatchingToolchain(selector.to_string()))
+    }
+
     /// Generate shell export commands for environment configuration
-    pub fn generate_env_exports(&self, toolchain_path: &Path) -> Vec<String> {
-        vec![
-            "export TOOLCHAINS=swift".to_string(),
-            format!(
-                "export PATH=\"{}/usr/bin:$PATH\"",
-                toolchain_path.display()
-            ),
-        ]
+    ///
+    /// `TOOLCHAINS` is only exported for Xcode toolchains, which have a
+    /// bundle identifier; Linux toolchains are selected by `PATH` alone.
+    pub fn generate_env_exports(&self, toolchain: &ToolchainBundle) -> Vec<String> {
+        let mut exports = Vec::new();
+        if let Some(ref identifier) = toolchain.identifier {
+            exports.push(format!("export TOOLCHAINS={identifier}"));
+        }
+        exports.push(format!(
+            "export PATH=\"{}:$PATH\"",
+            toolchain.bin_dir().display()
+        ));
+        exports
     }
 }
 
@@ -54,7 +212,114 @@ impl Default for SwiftConfig {
         Self {
             required_version: crate::REQUIRED_SWIFT_VERSION.to_string(),
             toolchain_path: None,
+            toolchain: None,
             auto_configure: true,
+            overrides: Vec::new(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundle(name: &str) -> ToolchainBundle {
+        ToolchainBundle {
+            path: PathBuf::from(format!("/Toolchains/{name}.xctoolchain")),
+            name: name.to_string(),
+            identifier: None,
+            display_name: None,
+            version: None,
+        }
+    }
+
+    #[test]
+    fn test_pin_file_and_config_file() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(PIN_FILE),
+            "swift-6.3-DEVELOPMENT-SNAPSHOT-2026-01-16-a\n",
+        )
+        .unwrap();
+
+        let config = SwiftConfig::load(dir.path()).unwrap();
+        assert_eq!(config.required_version, "6.3");
+        assert_eq!(
+            config.toolchain.as_deref(),
+            Some("swift-6.3-DEVELOPMENT-SNAPSHOT-2026-01-16-a")
+        );
+
+        std::fs::write(dir.path().join(CONFIG_FILE), "required_version = \"6.2\"\ntoolchain = \"6.2\"\n").unwrap();
+        let config = SwiftConfig::load(dir.path()).unwrap();
+        assert_eq!(config.required_version, "6.2");
+        assert_eq!(config.toolchain.as_deref(), Some("6.2"));
+    }
+
+    #[test]
+    fn test_find_project_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("packages/Core/Sources");
+        std::fs::create_dir_all(&nested).unwrap();
+        assert_eq!(SwiftConfig::find_project_root(&nested), None);
+
+        std::fs::write(dir.path().join(PIN_FILE), "6.3\n").unwrap();
+        assert_eq!(
+            SwiftConfig::find_project_root(&nested).as_deref(),
+            Some(dir.path())
+        );
+    }
+
+    #[test]
+    fn test_version_overrides() {
+        let config: SwiftConfig = toml::from_str(
+            r#"
+[[overrides]]
+path = "packages/Legacy*"
+version = ">=5.9, <6.0"
+
+[[overrides]]
+path = "tools/**"
+version = "5.10"
+"#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+
+        let legacy = config
+            .override_for(Path::new("packages/LegacyKit/Package.swift"))
+            .unwrap();
+        assert!(legacy.allows("5.9").unwrap());
+        assert!(legacy.allows("5.10.1").unwrap());
+        assert!(!legacy.allows("6.0").unwrap());
+
+        let tools = config.override_for(Path::new("tools/lint/Package.swift")).unwrap();
+        assert!(tools.allows("5.10.2").unwrap());
+        assert!(!tools.allows("5.9").unwrap());
+
+        assert!(config.override_for(Path::new("packages/Core/Package.swift")).is_none());
+
+        let invalid = VersionOverride {
+            path: "x".to_string(),
+            version: ">= six".to_string(),
+            reason: None,
+        };
+        assert!(invalid.allows("6.0").is_err());
+    }
+
+    #[test]
+    fn test_find_toolchain_prefers_first_match() {
+        let bundles = [
+            bundle("swift-6.3-DEVELOPMENT-SNAPSHOT-2026-02-01-a"),
+            bundle("swift-6.3-DEVELOPMENT-SNAPSHOT-2026-01-16-a"),
+            bundle("swift-6.2.1-RELEASE"),
+        ];
+        let newest = SwiftConfig::find_toolchain(&bundles, "6.3").unwrap();
+        assert_eq!(newest.name, "swift-6.3-DEVELOPMENT-SNAPSHOT-2026-02-01-a");
+
+        let pinned =
+            SwiftConfig::find_toolchain(&bundles, "swift-6.3-DEVELOPMENT-SNAPSHOT-2026-01-16-a").unwrap();
+        assert_eq!(pinned.name, "swift-6.3-DEVELOPMENT-SNAPSHOT-2026-01-16-a");
+
+        assert!(SwiftConfig::find_toolchain(&bundles, "6.1").is_err());
+    }
+}
@@ -1,4 +1,5 @@
 use crate::error::{Result, SwiftError};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
@@ -74,6 +75,8 @@ pub struct SwiftToolchain {
     pub version: SwiftVersion,
     pub path: PathBuf,
     pub is_xcode: bool,
+    /// Selected through swiftly's proxy rather than directly on `PATH`
+    pub is_swiftly: bool,
 }
 
 impl SwiftToolchain {
@@ -94,13 +97,27 @@ impl SwiftToolchain {
         // Try to find the swift binary path
         let swift_path = which::which("swift")
             .map_err(|_| SwiftError::SwiftNotFound)?;
+        let swift_path = swift_path.canonicalize().unwrap_or(swift_path);
 
         let is_xcode = swift_path.to_string_lossy().contains("Xcode.app");
 
+        // swiftly puts a proxy on PATH; report the toolchain it dispatches to
+        let swiftly_home = swiftly_home();
+        let is_swiftly = swiftly_home
+            .as_ref()
+            .is_some_and(|home| swift_path.starts_with(home));
+        let swift_path = match swiftly_in_use() {
+            Some(toolchain) if is_swiftly && !swift_path.starts_with(&toolchain) => {
+                toolchain.join("usr/bin/swift")
+            }
+            _ => swift_path,
+        };
+
         Ok(Self {
             version,
             path: swift_path,
             is_xcode,
+            is_swiftly,
         })
     }
 
@@ -132,46 +149,236 @@ impl SwiftToolchain {
             version,
             path: swift_bin,
             is_xcode: false,
+            is_swiftly: false,
         })
     }
 
-    /// List all available Swift toolchains on macOS
+    /// List all available Swift toolchains, running each one's `swift --version`
     pub fn list_available() -> Result<Vec<Self>> {
-        let mut toolchains = Vec::new();
-
-        // Check user toolchains
-        let user_toolchains = dirs::home_dir()
-            .map(|h| h.join("Library/Developer/Toolchains"))
-            .filter(|p| p.exists());
-
-        if let Some(toolchains_dir) = user_toolchains {
-            if let Ok(entries) = std::fs::read_dir(toolchains_dir) {
-                for entry in entries.flatten() {
-                    if entry.path().extension().and_then(|s| s.to_str()) == Some("xctoolchain") {
-                        if let Ok(toolchain) = Self::from_path(&entry.path()) {
-                            toolchains.push(toolchain);
-                        }
-                    }
-                }
-            }
+        Ok(discover_toolchains()
+            .iter()
+            .filter_map(|bundle| Self::from_path(&bundle.path).ok())
+            .collect())
+    }
+}
+
+/// An installed toolchain
+///
+/// On macOS this is an `.xctoolchain` bundle described by its `Info.plist`.
+/// On Linux it is a swiftly or tarball install directory, which has no
+/// `Info.plist`, or the system toolchain under `/usr`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolchainBundle {
+    /// Toolchain root (the directory containing `usr/bin/swift`)
+    pub path: PathBuf,
+    /// Bundle name without extension (`swift-6.3-DEVELOPMENT-SNAPSHOT-2026-01-16-a`,
+    /// `6.0.3` for swiftly, `system` for `/usr`)
+    pub name: String,
+    /// `CFBundleIdentifier` (`org.swift.63202601161a`), used as `TOOLCHAINS`
+    pub identifier: Option<String>,
+    /// `DisplayName`
+    pub display_name: Option<String>,
+    /// `Version`
+    pub version: Option<String>,
+}
+
+impl ToolchainBundle {
+    /// Read a toolchain bundle and its `Info.plist`
+    pub fn from_path(path: &Path) -> Result<Self> {
+        if !path.join("usr/bin/swift").exists() {
+            return Err(SwiftError::ToolchainNotFound(path.display().to_string()));
+        }
+        // swiftly names directories after versions, so only strip `.xctoolchain`
+        let name = path
+            .file_name()
+            .map(|s| s.to_string_lossy().trim_end_matches(".xctoolchain").to_string())
+            .unwrap_or_default();
+        let plist = std::fs::read_to_string(path.join("Info.plist"))
+            .map(|content| parse_info_plist(&content))
+            .unwrap_or_default();
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            name,
+            identifier: plist.get("CFBundleIdentifier").cloned(),
+            display_name: plist.get("DisplayName").cloned(),
+            version: plist.get("Version").cloned(),
+        })
+    }
+
+    /// The system toolchain, if Swift is installed under `/usr`
+    ///
+    /// Unlike bundles this has no name to read a version from, so its
+    /// `swift --version` is run.
+    pub fn system() -> Option<Self> {
+        if !Path::new(SYSTEM_SWIFT_LIB).is_dir() {
+            return None;
         }
+        let mut toolchain = Self::from_path(Path::new("/")).ok()?;
+        toolchain.name = "system".to_string();
+        toolchain.version = SwiftToolchain::from_path(&toolchain.path)
+            .ok()
+            .map(|swift| swift.version.raw);
+        Some(toolchain)
+    }
+
+    /// Directory holding the toolchain's `swift` binary
+    pub fn bin_dir(&self) -> PathBuf {
+        self.path.join("usr/bin")
+    }
+
+    /// Swift version (major.minor) this toolchain provides, if it can be told
+    pub fn short_version(&self) -> Option<String> {
+        version_from_name(&self.name)
+            .or_else(|| {
+                self.display_name
+                    .as_deref()
+                    .and_then(|name| name.split_whitespace().find_map(version_from_name))
+            })
+            .or_else(|| {
+                self.version
+                    .as_deref()
+                    .and_then(|v| SwiftVersion::parse(v).ok())
+                    .map(|v| v.short_version())
+            })
+    }
+
+    /// Check if `selector` names this toolchain
+    ///
+    /// Accepts the bundle name, identifier, display name, or a major.minor version.
+    pub fn matches(&self, selector: &str) -> bool {
+        let selector = selector.trim().trim_end_matches(".xctoolchain");
+        self.name == selector
+            || self.identifier.as_deref() == Some(selector)
+            || self.display_name.as_deref() == Some(selector)
+            || self.short_version().as_deref() == Some(selector)
+    }
+}
+
+/// Extract a major.minor version from a toolchain or pin name
+///
+/// `swift-6.3-DEVELOPMENT-SNAPSHOT-2026-01-16-a`, `swift-6.2.1-RELEASE` and
+/// `6.3` all work; `swift-DEVELOPMENT-SNAPSHOT-…` has no version.
+pub fn version_from_name(name: &str) -> Option<String> {
+    let (major, minor, _) = version_numbers(name)?;
+    Some(format!("{major}.{minor}"))
+}
+
+/// Major, minor and patch (0 when absent) of a toolchain or pin name
+fn version_numbers(name: &str) -> Option<(u32, u32, u32)> {
+    let name = name.trim().trim_start_matches("swift-");
+    let number = name.split('-').next()?;
+    let mut parts = number.split('.');
+    let major: u32 = parts.next()?.parse().ok()?;
+    let minor: u32 = parts.next()?.parse().ok()?;
+    let patch: u32 = parts.next().and_then(|p| p.parse().ok()).unwrap_or(0);
+    Some((major, minor, patch))
+}
+
+/// `YYYY-MM-DD` date embedded in a snapshot name
+fn snapshot_date(name: &str) -> Option<String> {
+    let parts: Vec<&str> = name.split('-').collect();
+    parts
+        .windows(3)
+        .find(|w| {
+            w.iter().map(|p| p.len()).eq([4, 2, 2])
+                && w.iter().all(|p| p.bytes().all(|b| b.is_ascii_digit()))
+        })
+        .map(|w| w.join("-"))
+}
+
+/// Ordering key of a toolchain name, newest greatest
+///
+/// Trunk snapshots name no version and are ahead of every release.
+fn name_order(name: &str) -> (bool, Option<(u32, u32, u32)>, Option<String>) {
+    let version = version_numbers(name);
+    (version.is_none(), version, snapshot_date(name))
+}
 
-        // Check system toolchains
-        let system_toolchains = Path::new("/Library/Developer/Toolchains");
-        if system_toolchains.exists() {
-            if let Ok(entries) = std::fs::read_dir(system_toolchains) {
-                for entry in entries.flatten() {
-                    if entry.path().extension().and_then(|s| s.to_str()) == Some("xctoolchain") {
-                        if let Ok(toolchain) = Self::from_path(&entry.path()) {
-                            toolchains.push(toolchain);
-                        }
-                    }
-                }
+/// Read the top-level string entries of an XML property list
+pub fn parse_info_plist(content: &str) -> HashMap<String, String> {
+    let mut entries = HashMap::new();
+    let mut rest = content;
+    while let Some(start) = rest.find("<key>") {
+        rest = &rest[start + "<key>".len()..];
+        let Some(end) = rest.find("</key>") else { break };
+        let key = rest[..end].trim().to_string();
+        rest = rest[end + "</key>".len()..].trim_start();
+        if let Some(value) = rest.strip_prefix("<string>") {
+            if let Some(end) = value.find("</string>") {
+                entries.insert(key, value[..end].trim().to_string());
             }
         }
+    }
+    entries
+}
+
+/// Runtime library directory of a system-wide Linux install
+const SYSTEM_SWIFT_LIB: &str = "/usr/lib/swift";
+
+/// swiftly's home directory (`$SWIFTLY_HOME_DIR`, default `~/.local/share/swiftly`)
+pub fn swiftly_home() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("SWIFTLY_HOME_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    dirs::home_dir().map(|home| home.join(".local/share/swiftly"))
+}
+
+/// Toolchain directory swiftly currently has in use, from its `config.json`
+pub fn swiftly_in_use() -> Option<PathBuf> {
+    let home = swiftly_home()?;
+    let content = std::fs::read_to_string(home.join("config.json")).ok()?;
+    let config: serde_json::Value = serde_json::from_str(&content).ok()?;
+    let name = config.get("inUse")?.as_str()?;
+    Some(home.join("toolchains").join(name))
+}
 
-        Ok(toolchains)
+/// Directories toolchains are installed in
+///
+/// Xcode toolchain directories on macOS, plus swiftly's toolchain directory
+/// (which is also where `foodshare-swift install` unpacks Linux tarballs).
+pub fn toolchain_dirs() -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    if cfg!(target_os = "macos") {
+        if let Some(home) = dirs::home_dir() {
+            paths.push(home.join("Library/Developer/Toolchains"));
+        }
+        paths.push(PathBuf::from("/Library/Developer/Toolchains"));
+    }
+    if let Some(home) = swiftly_home() {
+        paths.push(home.join("toolchains"));
+    }
+    paths
+}
+
+/// Find installed toolchains, newest first, followed by the system toolchain
+///
+/// Only reads each bundle's `Info.plist`; the system toolchain under `/usr`
+/// is the one thing executed, to learn its version.
+pub fn discover_toolchains() -> Vec<ToolchainBundle> {
+    let mut bundles = discover_toolchains_in(&toolchain_dirs());
+    if !cfg!(target_os = "macos") {
+        bundles.extend(ToolchainBundle::system());
     }
+    bundles
+}
+
+/// Find toolchains directly inside `dirs`, newest first
+pub fn discover_toolchains_in(dirs: &[PathBuf]) -> Vec<ToolchainBundle> {
+    let mut bundles: Vec<ToolchainBundle> = dirs
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .filter_map(|path| ToolchainBundle::from_path(&path).ok())
+        .collect();
+    bundles.sort_by(|a, b| {
+        name_order(&b.name)
+            .cmp(&name_order(&a.name))
+            .then_with(|| b.name.cmp(&a.name))
+    });
+    bundles
 }
 
 #[cfg(test)]
@@ -195,6 +402,97 @@ mod tests {
         assert!(!version.is_dev);
     }
 
+    #[test]
+    fn test_toolchain_bundle_from_info_plist() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path().join("swift-6.3-DEVELOPMENT-SNAPSHOT-2026-01-16-a.xctoolchain");
+        std::fs::create_dir_all(bundle.join("usr/bin")).unwrap();
+        std::fs::write(bundle.join("usr/bin/swift"), "").unwrap();
+        std::fs::write(
+            bundle.join("Info.plist"),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0">
+<dict>
+	<key>CFBundleIdentifier</key>
+	<string>org.swift.63202601161a</string>
+	<key>DisplayName</key>
+	<string>Swift 6.3 Development Snapshot 2026-01-16 (a)</string>
+	<key>ReportProblemURL</key>
+	<string>https://bugs.swift.org/</string>
+	<key>Version</key>
+	<string>6.3.202601161</string>
+</dict>
+</plist>"#,
+        )
+        .unwrap();
+
+        let toolchain = ToolchainBundle::from_path(&bundle).unwrap();
+        assert_eq!(toolchain.identifier.as_deref(), Some("org.swift.63202601161a"));
+        assert_eq!(toolchain.short_version().as_deref(), Some("6.3"));
+        assert!(toolchain.matches("6.3"));
+        assert!(toolchain.matches("org.swift.63202601161a"));
+        assert!(toolchain.matches("swift-6.3-DEVELOPMENT-SNAPSHOT-2026-01-16-a"));
+        assert!(!toolchain.matches("6.2"));
+    }
+
+    #[test]
+    fn test_discover_swiftly_toolchains() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["6.0.3", "6.1.2", "main-snapshot-2026-01-16"] {
+            std::fs::create_dir_all(dir.path().join(name).join("usr/bin")).unwrap();
+            std::fs::write(dir.path().join(name).join("usr/bin/swift"), "").unwrap();
+        }
+        // Not a toolchain
+        std::fs::create_dir_all(dir.path().join("downloads")).unwrap();
+
+        let bundles = discover_toolchains_in(&[dir.path().to_path_buf()]);
+        let names: Vec<_> = bundles.iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(names, ["main-snapshot-2026-01-16", "6.1.2", "6.0.3"]);
+        assert_eq!(bundles[1].short_version().as_deref(), Some("6.1"));
+        assert!(bundles[2].matches("6.0"));
+        assert_eq!(bundles[2].bin_dir(), dir.path().join("6.0.3/usr/bin"));
+        assert_eq!(bundles[0].identifier, None);
+    }
+
+    #[test]
+    fn test_discover_orders_by_version() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in [
+            "swift-5.9-RELEASE.xctoolchain",
+            "swift-5.10-RELEASE.xctoolchain",
+            "swift-6.2.9-RELEASE.xctoolchain",
+            "swift-6.2.10-RELEASE.xctoolchain",
+            "swift-6.3-DEVELOPMENT-SNAPSHOT-2026-01-09-a.xctoolchain",
+            "swift-6.3-DEVELOPMENT-SNAPSHOT-2026-01-16-a.xctoolchain",
+        ] {
+            std::fs::create_dir_all(dir.path().join(name).join("usr/bin")).unwrap();
+            std::fs::write(dir.path().join(name).join("usr/bin/swift"), "").unwrap();
+        }
+
+        let bundles = discover_toolchains_in(&[dir.path().to_path_buf()]);
+        let names: Vec<_> = bundles.iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "swift-6.3-DEVELOPMENT-SNAPSHOT-2026-01-16-a",
+                "swift-6.3-DEVELOPMENT-SNAPSHOT-2026-01-09-a",
+                "swift-6.2.10-RELEASE",
+                "swift-6.2.9-RELEASE",
+                "swift-5.10-RELEASE",
+                "swift-5.9-RELEASE",
+            ]
+        );
+        // The first match is the newest
+        assert!(bundles.iter().find(|b| b.matches("6.2")).unwrap().name.contains("6.2.10"));
+    }
+
+    #[test]
+    fn test_version_from_name() {
+        assert_eq!(version_from_name("swift-6.2.1-RELEASE").as_deref(), Some("6.2"));
+        assert_eq!(version_from_name("6.3").as_deref(), Some("6.3"));
+        assert_eq!(version_from_name("swift-DEVELOPMENT-SNAPSHOT-2026-01-16-a"), None);
+    }
+
     #[test]
     fn test_version_matches() {
         let version = SwiftVersion::parse("6.3-dev").unwrap();
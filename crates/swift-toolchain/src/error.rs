@@ -13,6 +13,9 @@ pub enum SwiftError {
     #[error("Toolchain not found at path: {0}")]
     ToolchainNotFound(String),
 
+    #[error("No installed toolchain matches '{0}'")]
+    NoMatchingToolchain(String),
+
     #[error("Invalid Package.swift file: {0}")]
     InvalidPackageFile(String),
 
@@ -30,4 +33,10 @@ pub enum SwiftError {
 
     #[error("Configuration error: {0}")]
     Config(String),
+
+    #[error("Download failed: {0}")]
+    Download(String),
+
+    #[error("Verification failed: {0}")]
+    Verification(String),
 }
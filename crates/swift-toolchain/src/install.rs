@@ -0,0 +1,486 @@
+//! Toolchain download and installation
+//!
+//! Resolves a requested version to a download on swift.org, fetches it,
+//! verifies it and installs it where [`SwiftToolchain::list_available`]
+//! finds it:
+//!
+//! | Request | Resolves to |
+//! |---------|-------------|
+//! | `6.3` | latest snapshot of the `swift-6.3-branch` (from `latest-build.yml`) |
+//! | `main` | latest snapshot of the development branch |
+//! | `6.2.1` | the `swift-6.2.1-RELEASE` release |
+//! | `swift-6.3-DEVELOPMENT-SNAPSHOT-2026-01-16-a` | that exact snapshot |
+//!
+//! Downloads are verified against the swift.org PGP signature (with `gpg`)
+//! and, when given, an expected SHA-256 checksum.
+//!
+//! [`SwiftToolchain::list_available`]: crate::detect::SwiftToolchain::list_available
+
+use crate::detect::swiftly_home;
+use crate::error::{Result, SwiftError};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Base URL for toolchain downloads
+pub const DOWNLOAD_BASE_URL: &str = "https://download.swift.org";
+
+/// swift.org PGP keys used to sign toolchains
+pub const SWIFT_KEYS_URL: &str = "https://www.swift.org/keys/all-keys.asc";
+
+/// A requested toolchain version
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToolchainRequest {
+    /// Latest snapshot of a release branch (`6.3`)
+    BranchSnapshot(String),
+    /// Latest snapshot of the development branch (`main`)
+    DevelopmentSnapshot,
+    /// A release (`6.2.1`)
+    Release(String),
+    /// An exact snapshot tag
+    Snapshot(String),
+}
+
+impl ToolchainRequest {
+    /// Parse a version argument
+    pub fn parse(version: &str) -> Result<Self> {
+        let version = version.trim();
+        if version.starts_with("swift-") {
+            return Ok(Self::Snapshot(version.to_string()));
+        }
+        if matches!(version, "main" | "development" | "trunk") {
+            return Ok(Self::DevelopmentSnapshot);
+        }
+
+        let number = version.trim_end_matches("-snapshot");
+        let parts: Vec<&str> = number.split('.').collect();
+        if !(2..=3).contains(&parts.len()) || parts.iter().any(|p| p.parse::<u32>().is_err()) {
+            return Err(SwiftError::ParseError(version.to_string()));
+        }
+        if parts.len() == 3 && number == version {
+            Ok(Self::Release(number.to_string()))
+        } else {
+            Ok(Self::BranchSnapshot(format!("{}.{}", parts[0], parts[1])))
+        }
+    }
+
+    /// Download directory for the branch this request belongs to
+    fn branch_dir(&self) -> String {
+        match self {
+            Self::BranchSnapshot(version) => format!("swift-{version}-branch"),
+            Self::DevelopmentSnapshot => "development".to_string(),
+            Self::Release(version) => format!("swift-{version}-release"),
+            Self::Snapshot(tag) => {
+                // swift-6.3-DEVELOPMENT-SNAPSHOT-… belongs to a branch,
+                // swift-DEVELOPMENT-SNAPSHOT-… to the development branch
+                match tag.strip_prefix("swift-").and_then(|t| t.split_once("-DEVELOPMENT")) {
+                    Some((version, _)) if !version.is_empty() => format!("swift-{version}-branch"),
+                    _ => "development".to_string(),
+                }
+            }
+        }
+    }
+}
+
+/// Platform a toolchain is built for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Platform {
+    /// macOS `.pkg` installer
+    MacOS,
+    /// Linux tarball
+    Linux {
+        /// Download directory name (`ubuntu2204`, `ubuntu2204-aarch64`)
+        dir: String,
+        /// Archive name suffix (`ubuntu22.04`, `ubuntu22.04-aarch64`)
+        suffix: String,
+    },
+}
+
+impl Platform {
+    /// Detect the platform of this machine
+    pub fn current() -> Result<Self> {
+        if cfg!(target_os = "macos") {
+            return Ok(Self::MacOS);
+        }
+        let os_release = std::fs::read_to_string("/etc/os-release")
+            .map_err(|_| SwiftError::Config("cannot read /etc/os-release".to_string()))?;
+        Self::from_os_release(&os_release, std::env::consts::ARCH)
+    }
+
+    /// Linux platform from the contents of `/etc/os-release`
+    pub fn from_os_release(os_release: &str, arch: &str) -> Result<Self> {
+        let fields: HashMap<&str, &str> = os_release
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .map(|(k, v)| (k.trim(), v.trim().trim_matches('"')))
+            .collect();
+        let id = fields.get("ID").copied().unwrap_or_default();
+        let version = fields.get("VERSION_ID").copied().unwrap_or_default();
+
+        let (dir, suffix) = match id {
+            "ubuntu" => (format!("ubuntu{}", version.replace('.', "")), format!("ubuntu{version}")),
+            "debian" | "fedora" => (format!("{id}{version}"), format!("{id}{version}")),
+            "amzn" => (format!("amazonlinux{version}"), format!("amazonlinux{version}")),
+            "rhel" | "centos" | "rocky" | "almalinux" => {
+                let major = version.split('.').next().unwrap_or(version);
+                (format!("ubi{major}"), format!("ubi{major}"))
+            }
+            _ => {
+                return Err(SwiftError::Config(format!(
+                    "unsupported Linux distribution '{id} {version}'"
+                )))
+            }
+        };
+
+        Ok(match arch {
+            "aarch64" => Self::Linux {
+                dir: format!("{dir}-aarch64"),
+                suffix: format!("{suffix}-aarch64"),
+            },
+            _ => Self::Linux { dir, suffix },
+        })
+    }
+
+    fn dir(&self) -> &str {
+        match self {
+            Self::MacOS => "xcode",
+            Self::Linux { dir, .. } => dir,
+        }
+    }
+
+    fn file_name(&self, tag: &str) -> String {
+        match self {
+            Self::MacOS => format!("{tag}-osx.pkg"),
+            Self::Linux { suffix, .. } => format!("{tag}-{suffix}.tar.gz"),
+        }
+    }
+
+    /// Directory toolchains are installed into
+    pub fn toolchains_dir(&self) -> Result<PathBuf> {
+        let missing_home =
+            || SwiftError::Config("cannot determine home directory".to_string());
+        Ok(match self {
+            Self::MacOS => dirs::home_dir()
+                .ok_or_else(missing_home)?
+                .join("Library/Developer/Toolchains"),
+            Self::Linux { .. } => swiftly_home().ok_or_else(missing_home)?.join("toolchains"),
+        })
+    }
+
+    /// Where a toolchain with `tag` ends up once installed
+    pub fn install_path(&self, tag: &str) -> Result<PathBuf> {
+        let name = match self {
+            Self::MacOS => format!("{tag}.xctoolchain"),
+            Self::Linux { .. } => tag.to_string(),
+        };
+        Ok(self.toolchains_dir()?.join(name))
+    }
+}
+
+/// A resolved toolchain download
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolchainDownload {
+    /// Snapshot or release tag (`swift-6.3-DEVELOPMENT-SNAPSHOT-2026-01-16-a`)
+    pub tag: String,
+    /// Installer or archive URL
+    pub url: String,
+    /// Detached PGP signature URL
+    pub signature_url: String,
+    /// Target platform
+    pub platform: Platform,
+}
+
+impl ToolchainDownload {
+    /// File name of the download
+    pub fn file_name(&self) -> String {
+        self.platform.file_name(&self.tag)
+    }
+}
+
+/// Parse the flat `key: value` YAML of a `latest-build.yml` file
+pub fn parse_latest_build(yaml: &str) -> HashMap<String, String> {
+    yaml.lines()
+        .filter_map(|line| line.split_once(':'))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().trim_matches('"').to_string()))
+        .filter(|(k, v)| !k.starts_with('#') && !v.is_empty())
+        .collect()
+}
+
+/// Resolve a request to a download URL
+///
+/// `fetch` retrieves text from a URL; it is only called for snapshot
+/// requests that need the branch's `latest-build.yml`.
+pub fn resolve(
+    request: &ToolchainRequest,
+    platform: &Platform,
+    fetch: impl Fn(&str) -> Result<String>,
+) -> Result<ToolchainDownload> {
+    let branch_url = format!("{DOWNLOAD_BASE_URL}/{}/{}", request.branch_dir(), platform.dir());
+
+    let tag = match request {
+        ToolchainRequest::Release(version) => format!("swift-{version}-RELEASE"),
+        ToolchainRequest::Snapshot(tag) => tag.clone(),
+        ToolchainRequest::BranchSnapshot(_) | ToolchainRequest::DevelopmentSnapshot => {
+            let metadata = parse_latest_build(&fetch(&format!("{branch_url}/latest-build.yml"))?);
+            metadata
+                .get("dir")
+                .cloned()
+                .ok_or_else(|| SwiftError::Download("latest-build.yml has no 'dir' entry".to_string()))?
+        }
+    };
+
+    let url = format!("{branch_url}/{tag}/{}", platform.file_name(&tag));
+    Ok(ToolchainDownload {
+        signature_url: format!("{url}.sig"),
+        url,
+        tag,
+        platform: platform.clone(),
+    })
+}
+
+/// Fetch a URL as text
+pub fn fetch_text(url: &str) -> Result<String> {
+    let response = http_get(url)?;
+    response
+        .text()
+        .map_err(|e| SwiftError::Download(format!("{url}: {e}")))
+}
+
+fn http_get(url: &str) -> Result<reqwest::blocking::Response> {
+    let client = reqwest::blocking::Client::builder()
+        .user_agent(concat!("foodshare-swift/", env!("CARGO_PKG_VERSION")))
+        .timeout(None)
+        .build()
+        .map_err(|e| SwiftError::Download(e.to_string()))?;
+    let response = client
+        .get(url)
+        .send()
+        .map_err(|e| SwiftError::Download(format!("{url}: {e}")))?;
+    if !response.status().is_success() {
+        return Err(SwiftError::Download(format!("{url}: HTTP {}", response.status())));
+    }
+    Ok(response)
+}
+
+/// Download `url` to `dest`, calling `on_progress(downloaded, total)` as bytes arrive
+pub fn download_file(url: &str, dest: &Path, on_progress: impl Fn(u64, Option<u64>)) -> Result<()> {
+    let mut response = http_get(url)?;
+    let total = response.content_length();
+
+    // Write to a temporary name so an interrupted download is never mistaken for a complete one
+    let partial = dest.with_extension("partial");
+    let mut file = File::create(&partial)?;
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut downloaded = 0u64;
+    on_progress(0, total);
+    loop {
+        let n = response.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        file.write_all(&buf[..n])?;
+        downloaded += n as u64;
+        on_progress(downloaded, total);
+    }
+    file.sync_all()?;
+    std::fs::rename(&partial, dest)?;
+    Ok(())
+}
+
+/// SHA-256 of a file, hex encoded
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Check a file against an expected SHA-256 checksum
+pub fn verify_checksum(path: &Path, expected: &str) -> Result<()> {
+    let actual = sha256_file(path)?;
+    if actual.eq_ignore_ascii_case(expected.trim()) {
+        Ok(())
+    } else {
+        Err(SwiftError::Verification(format!(
+            "checksum mismatch for {}: expected {expected}, got {actual}",
+            path.display()
+        )))
+    }
+}
+
+/// Verify a detached PGP signature against the swift.org keys
+///
+/// The keys are imported into a private keyring under `work_dir`, so the
+/// user's own keyring is left untouched.
+pub fn verify_signature(path: &Path, signature: &Path, work_dir: &Path) -> Result<()> {
+    if which::which("gpg").is_err() {
+        return Err(SwiftError::Verification(
+            "gpg not found; install GnuPG or pass --no-verify".to_string(),
+        ));
+    }
+
+    let keys = work_dir.join("swift-keys.asc");
+    if !keys.exists() {
+        std::fs::write(&keys, fetch_text(SWIFT_KEYS_URL)?)?;
+    }
+    let keyring = work_dir.join("swift-keyring.gpg");
+    let gpg = |args: &[&str]| {
+        Command::new("gpg")
+            .args(["--batch", "--quiet", "--no-default-keyring", "--keyring"])
+            .arg(&keyring)
+            .args(args)
+            .output()
+            .map_err(|e| SwiftError::CommandFailed(format!("gpg: {e}")))
+    };
+
+    let import = gpg(&["--import", &keys.to_string_lossy()])?;
+    if !import.status.success() {
+        return Err(SwiftError::Verification(format!(
+            "failed to import swift.org keys: {}",
+            String::from_utf8_lossy(&import.stderr).trim()
+        )));
+    }
+
+    let verify = gpg(&["--verify", &signature.to_string_lossy(), &path.to_string_lossy()])?;
+    if verify.status.success() {
+        Ok(())
+    } else {
+        Err(SwiftError::Verification(format!(
+            "bad signature for {}: {}",
+            path.display(),
+            String::from_utf8_lossy(&verify.stderr).trim()
+        )))
+    }
+}
+
+/// Install a downloaded toolchain into the platform's toolchains directory
+///
+/// Returns the installed toolchain directory.
+pub fn install_file(download: &ToolchainDownload, file: &Path) -> Result<PathBuf> {
+    let target = download.platform.install_path(&download.tag)?;
+    let output = match download.platform {
+        Platform::MacOS => Command::new("installer")
+            .arg("-pkg")
+            .arg(file)
+            .args(["-target", "CurrentUserHomeDirectory"])
+            .output(),
+        Platform::Linux { .. } => {
+            std::fs::create_dir_all(&target)?;
+            Command::new("tar")
+                .arg("-xzf")
+                .arg(file)
+                .arg("-C")
+                .arg(&target)
+                .arg("--strip-components=1")
+                .output()
+        }
+    }
+    .map_err(|e| SwiftError::CommandFailed(e.to_string()))?;
+
+    if !output.status.success() {
+        return Err(SwiftError::CommandFailed(format!(
+            "installing {} failed: {}",
+            download.tag,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    if !target.join("usr/bin/swift").exists() {
+        return Err(SwiftError::ToolchainNotFound(target.display().to_string()));
+    }
+    Ok(target)
+}
+
+/// Directory downloads are cached in
+pub fn download_dir() -> Result<PathBuf> {
+    let dir = dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from(".cache"))
+        .join("foodshare-tools")
+        .join("toolchains");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_request() {
+        assert_eq!(
+            ToolchainRequest::parse("6.3").unwrap(),
+            ToolchainRequest::BranchSnapshot("6.3".to_string())
+        );
+        assert_eq!(
+            ToolchainRequest::parse("6.2.1").unwrap(),
+            ToolchainRequest::Release("6.2.1".to_string())
+        );
+        assert_eq!(
+            ToolchainRequest::parse("main").unwrap(),
+            ToolchainRequest::DevelopmentSnapshot
+        );
+        assert!(matches!(
+            ToolchainRequest::parse("swift-6.3-DEVELOPMENT-SNAPSHOT-2026-01-16-a").unwrap(),
+            ToolchainRequest::Snapshot(_)
+        ));
+        assert!(ToolchainRequest::parse("six").is_err());
+    }
+
+    #[test]
+    fn test_resolve_branch_snapshot() {
+        let yaml = "date: 2026-01-16\ndir: swift-6.3-DEVELOPMENT-SNAPSHOT-2026-01-16-a\ndownload: swift-6.3-DEVELOPMENT-SNAPSHOT-2026-01-16-a-osx.pkg\n";
+        let download = resolve(
+            &ToolchainRequest::parse("6.3").unwrap(),
+            &Platform::MacOS,
+            |url| {
+                assert_eq!(url, "https://download.swift.org/swift-6.3-branch/xcode/latest-build.yml");
+                Ok(yaml.to_string())
+            },
+        )
+        .unwrap();
+
+        assert_eq!(download.tag, "swift-6.3-DEVELOPMENT-SNAPSHOT-2026-01-16-a");
+        assert_eq!(
+            download.url,
+            "https://download.swift.org/swift-6.3-branch/xcode/swift-6.3-DEVELOPMENT-SNAPSHOT-2026-01-16-a/swift-6.3-DEVELOPMENT-SNAPSHOT-2026-01-16-a-osx.pkg"
+        );
+        assert!(download.signature_url.ends_with(".pkg.sig"));
+    }
+
+    #[test]
+    fn test_resolve_release_on_linux() {
+        let platform =
+            Platform::from_os_release("ID=ubuntu\nVERSION_ID=\"22.04\"\n", "aarch64").unwrap();
+        let download = resolve(&ToolchainRequest::parse("6.2.1").unwrap(), &platform, |_| {
+            panic!("releases need no metadata")
+        })
+        .unwrap();
+        assert_eq!(
+            download.url,
+            "https://download.swift.org/swift-6.2.1-release/ubuntu2204-aarch64/swift-6.2.1-RELEASE/swift-6.2.1-RELEASE-ubuntu22.04-aarch64.tar.gz"
+        );
+    }
+
+    #[test]
+    fn test_verify_checksum() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("toolchain.pkg");
+        std::fs::write(&file, b"abc").unwrap();
+
+        let expected = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        assert!(verify_checksum(&file, expected).is_ok());
+        assert!(matches!(
+            verify_checksum(&file, &"0".repeat(64)),
+            Err(SwiftError::Verification(_))
+        ));
+    }
+}
@@ -4,23 +4,28 @@
 //! - Detect installed Swift versions
 //! - Verify Swift version consistency across Package.swift files
 //! - Configure environment for specific Swift versions
+//! - Switch toolchains automatically from shell hooks
 //! - Migrate between Swift versions
+//! - Download and install toolchains from swift.org
+//! - Check toolchains against the installed Xcode
 
 pub mod config;
 pub mod detect;
 pub mod error;
+pub mod install;
 pub mod migrate;
+pub mod shell;
 pub mod verify;
+pub mod xcode;
 
 pub use config::SwiftConfig;
-pub use detect::{SwiftToolchain, SwiftVersion};
+pub use detect::{SwiftToolchain, SwiftVersion, ToolchainBundle};
 pub use error::{Result, SwiftError};
+pub use install::{Platform, ToolchainDownload, ToolchainRequest};
+pub use shell::Shell;
 pub use verify::VerificationReport;
+pub use xcode::XcodeVersion;
 
 /// Swift version requirements for the project
 pub const REQUIRED_SWIFT_VERSION: &str = "6.3";
 pub const SWIFT_TOOLS_VERSION: &str = "6.3";
-
-/// Default toolchain path on macOS
-pub const DEFAULT_TOOLCHAIN_PATH: &str =
-    "/Users/organic/Library/Developer/Toolchains/swift-6.3-DEVELOPMENT-SNAPSHOT-2026-01-16-a.xctoolchain";
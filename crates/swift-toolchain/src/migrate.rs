@@ -1,8 +1,143 @@
 use crate::error::{Result, SwiftError};
 use colored::Colorize;
-use std::path::Path;
+use foodshare_cli::output::diff::render_replacement;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
+/// Kind of file a migration touched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileKind {
+    /// `Package.swift`
+    PackageManifest,
+    /// `project.pbxproj` inside an `.xcodeproj`
+    XcodeProject,
+    /// GitHub Actions workflow
+    Workflow,
+    /// Markdown or shell script
+    Documentation,
+}
+
+/// A single rewritten line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineEdit {
+    /// 1-based line number
+    pub line: usize,
+    pub before: String,
+    pub after: String,
+}
+
+/// Edits made (or, in a dry run, proposed) to one file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileChange {
+    pub path: PathBuf,
+    pub kind: FileKind,
+    pub edits: Vec<LineEdit>,
+}
+
+impl FileChange {
+    /// Diff between `before` and `after`, or `None` if nothing changed
+    ///
+    /// Rewrites only ever replace text within a line, so the diff is a list
+    /// of changed lines.
+    fn diff(path: &Path, kind: FileKind, before: &str, after: &str) -> Option<Self> {
+        let edits: Vec<LineEdit> = before
+            .lines()
+            .zip(after.lines())
+            .enumerate()
+            .filter(|(_, (old, new))| old != new)
+            .map(|(index, (old, new))| LineEdit {
+                line: index + 1,
+                before: old.to_string(),
+                after: new.to_string(),
+            })
+            .collect();
+
+        (!edits.is_empty()).then(|| Self {
+            path: path.to_path_buf(),
+            kind,
+            edits,
+        })
+    }
+}
+
+/// Result of a migration run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationReport {
+    pub from_version: String,
+    pub to_version: String,
+    pub dry_run: bool,
+    pub files: Vec<FileChange>,
+}
+
+impl MigrationReport {
+    /// Changed files of one kind
+    pub fn files_of(&self, kind: FileKind) -> impl Iterator<Item = &FileChange> {
+        self.files.iter().filter(move |file| file.kind == kind)
+    }
+
+    /// Print the per-file report, with a diff of every change
+    pub fn print(&self) {
+        println!(
+            "\n{} {} → {}",
+            "🔄 Migrating Swift version:".bold(),
+            self.from_version.cyan(),
+            self.to_version.green()
+        );
+
+        if self.dry_run {
+            println!("{}", "  (Dry run - no files will be modified)".yellow());
+        }
+        println!();
+
+        let sections = [
+            (FileKind::PackageManifest, "📝 Package.swift files", "files"),
+            (FileKind::XcodeProject, "🎯 Xcode projects", "projects"),
+            (FileKind::Workflow, "⚙️  CI workflows", "files"),
+            (FileKind::Documentation, "📚 Documentation", "files"),
+        ];
+        for (kind, title, unit) in sections {
+            println!("{}", format!("{title}:").bold());
+            let files: Vec<_> = self.files_of(kind).collect();
+            for file in &files {
+                println!(
+                    "  {} {} ({} lines)",
+                    "✓".green(),
+                    file.path.display(),
+                    file.edits.len()
+                );
+                for edit in &file.edits {
+                    let (before, after) = render_replacement(edit.before.trim(), edit.after.trim());
+                    println!("    {:>4} {}", edit.line, before);
+                    println!("    {:>4} {}", "", after);
+                }
+            }
+            println!("  {} {unit}", files.len());
+            println!();
+        }
+
+        if self.dry_run {
+            println!(
+                "{}",
+                "✅ Dry run complete! Run without --dry-run to apply changes.".green().bold()
+            );
+        } else {
+            println!("{}", "✅ Migration complete!".green().bold());
+            println!();
+            println!("Next steps:");
+            println!("  1. Clean build artifacts: rm -rf .build */build");
+            println!("  2. Verify: foodshare-ios swift verify");
+            println!("  3. Test builds: swift build");
+        }
+    }
+
+    /// Export report as JSON
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(|e| SwiftError::Config(e.to_string()))
+    }
+}
+
 pub struct SwiftMigrator {
     from_version: String,
     to_version: String,
@@ -19,7 +154,7 @@ impl SwiftMigrator {
     }
 
     /// Migrate all Package.swift files to new version
-    pub fn migrate_package_files(&self, project_root: &Path) -> Result<Vec<String>> {
+    pub fn migrate_package_files(&self, project_root: &Path) -> Result<Vec<FileChange>> {
         let mut migrated = Vec::new();
 
         for entry in WalkDir::new(project_root)
@@ -27,27 +162,20 @@ impl SwiftMigrator {
             .into_iter()
             .filter_entry(|e| {
                 let name = e.file_name().to_string_lossy();
-                !name.starts_with('.')
-                    && name != "build"
-                    && name != "SourcePackages"
-                    && name != "swift-android-contributions"
+                // The root itself may be `.` or a hidden directory
+                e.depth() == 0
+                    || (!name.starts_with('.')
+                        && name != "build"
+                        && name != "SourcePackages"
+                        && name != "swift-android-contributions")
             })
             .filter_map(|e| e.ok())
         {
             if entry.file_name() == "Package.swift" {
-                let path = entry.path();
-                let content = std::fs::read_to_string(path)?;
-
-                if content.contains(&format!("swift-tools-version: {}", self.from_version)) {
-                    if !self.dry_run {
-                        let new_content = content.replace(
-                            &format!("swift-tools-version: {}", self.from_version),
-                            &format!("swift-tools-version: {}", self.to_version),
-                        );
-                        std::fs::write(path, new_content)?;
-                    }
-                    migrated.push(path.display().to_string());
-                }
+                let change = self.rewrite_file(entry.path(), FileKind::PackageManifest, |content| {
+                    self.rewrite_package_manifest(content)
+                })?;
+                migrated.extend(change);
             }
         }
 
@@ -55,7 +183,7 @@ impl SwiftMigrator {
     }
 
     /// Migrate Xcode project files
-    pub fn migrate_xcode_projects(&self, project_root: &Path) -> Result<Vec<String>> {
+    pub fn migrate_xcode_projects(&self, project_root: &Path) -> Result<Vec<FileChange>> {
         let mut migrated = Vec::new();
 
         for entry in WalkDir::new(project_root)
@@ -72,18 +200,12 @@ impl SwiftMigrator {
             {
                 let pbxproj = entry.path().join("project.pbxproj");
                 if pbxproj.exists() {
-                    let content = std::fs::read_to_string(&pbxproj)?;
-
-                    if content.contains(&format!("SWIFT_VERSION = {};", self.from_version)) {
-                        if !self.dry_run {
-                            let new_content = content.replace(
-                                &format!("SWIFT_VERSION = {};", self.from_version),
-                                &format!("SWIFT_VERSION = {};", self.to_version),
-                            );
-                            std::fs::write(&pbxproj, new_content)?;
-                        }
-                        migrated.push(entry.path().display().to_string());
-                    }
+                    let from = format!("SWIFT_VERSION = {};", self.from_version);
+                    let to = format!("SWIFT_VERSION = {};", self.to_version);
+                    let change = self.rewrite_file(&pbxproj, FileKind::XcodeProject, |content| {
+                        content.replace(&from, &to)
+                    })?;
+                    migrated.extend(change);
                 }
             }
         }
@@ -91,10 +213,38 @@ impl SwiftMigrator {
         Ok(migrated)
     }
 
+    /// Migrate version matrices in GitHub Actions workflows
+    pub fn migrate_workflows(&self, project_root: &Path) -> Result<Vec<FileChange>> {
+        let mut migrated = Vec::new();
+
+        for entry in WalkDir::new(project_root.join(".github/workflows"))
+            .max_depth(1)
+            .follow_links(false)
+            .into_iter()
+            .filter_map(|e| e.ok())
+        {
+            let ext = entry.path().extension().and_then(|s| s.to_str());
+            if matches!(ext, Some("yml" | "yaml")) {
+                let change = self.rewrite_file(entry.path(), FileKind::Workflow, |content| {
+                    self.rewrite_workflow(content)
+                })?;
+                migrated.extend(change);
+            }
+        }
+
+        Ok(migrated)
+    }
+
     /// Migrate documentation files
-    pub fn migrate_documentation(&self, project_root: &Path) -> Result<Vec<String>> {
+    pub fn migrate_documentation(&self, project_root: &Path) -> Result<Vec<FileChange>> {
         let mut migrated = Vec::new();
 
+        let patterns = [
+            format!("Swift {}", self.from_version),
+            format!("swift {}", self.from_version),
+            format!("swift-tools-version: {}", self.from_version),
+        ];
+
         for entry in WalkDir::new(project_root)
             .max_depth(3)
             .follow_links(false)
@@ -103,34 +253,15 @@ impl SwiftMigrator {
         {
             if let Some(ext) = entry.path().extension() {
                 if ext == "md" || ext == "sh" {
-                    let path = entry.path();
-                    let content = std::fs::read_to_string(path)?;
-
-                    let patterns = vec![
-                        format!("Swift {}", self.from_version),
-                        format!("swift {}", self.from_version),
-                        format!("swift-tools-version: {}", self.from_version),
-                    ];
-
-                    let mut needs_update = false;
-                    for pattern in &patterns {
-                        if content.contains(pattern) {
-                            needs_update = true;
-                            break;
-                        }
-                    }
-
-                    if needs_update && !self.dry_run {
-                        let mut new_content = content;
+                    let change = self.rewrite_file(entry.path(), FileKind::Documentation, |content| {
+                        let mut new_content = content.to_string();
                         for pattern in &patterns {
                             let replacement = pattern.replace(&self.from_version, &self.to_version);
                             new_content = new_content.replace(pattern, &replacement);
                         }
-                        std::fs::write(path, new_content)?;
-                        migrated.push(path.display().to_string());
-                    } else if needs_update {
-                        migrated.push(path.display().to_string());
-                    }
+                        new_content
+                    })?;
+                    migrated.extend(change);
                 }
             }
         }
@@ -138,58 +269,240 @@ impl SwiftMigrator {
         Ok(migrated)
     }
 
-    /// Run full migration
-    pub fn run(&self, project_root: &Path) -> Result<()> {
-        println!(
-            "\n{} {} → {}",
-            "🔄 Migrating Swift version:".bold(),
-            self.from_version.cyan(),
-            self.to_version.green()
-        );
+    /// Rewrite a `Package.swift`
+    ///
+    /// Updates the `// swift-tools-version:` header and, when the major
+    /// version changes, language mode settings (`swiftLanguageModes`,
+    /// `swiftLanguageVersions`, `.swiftLanguageMode(...)` in `swiftSettings`
+    /// and `-swift-version` unsafe flags).
+    pub fn rewrite_package_manifest(&self, content: &str) -> String {
+        let from_mode = language_mode(&self.from_version);
+        let to_mode = language_mode(&self.to_version);
 
-        if self.dry_run {
-            println!("{}", "  (Dry run - no files will be modified)".yellow());
-        }
-        println!();
+        map_lines(content, |index, line| {
+            if index == 0 && line.contains("swift-tools-version") {
+                return replace_version(line, &self.from_version, &self.to_version);
+            }
+            match (from_mode, to_mode) {
+                (Some(from), Some(to)) if from != to => {
+                    if line.contains("swiftLanguageMode") || line.contains("swiftLanguageVersions") {
+                        replace_token(line, &format!(".v{from}"), &format!(".v{to}"))
+                    } else if line.contains("-swift-version") {
+                        line.replace(
+                            &format!("\"-swift-version\", \"{from}\""),
+                            &format!("\"-swift-version\", \"{to}\""),
+                        )
+                    } else {
+                        line.to_string()
+                    }
+                }
+                _ => line.to_string(),
+            }
+        })
+    }
 
-        println!("{}", "📝 Migrating Package.swift files...".bold());
-        let package_files = self.migrate_package_files(project_root)?;
-        for file in &package_files {
-            println!("  {} {}", "✓".green(), file);
-        }
-        println!("  {} files", package_files.len());
-        println!();
+    /// Rewrite Swift versions in a workflow file
+    ///
+    /// Only values of keys mentioning `swift` (`swift-version: "6.2"`,
+    /// `swift: ["6.2", "6.3"]`, `SWIFT_VERSION: 6.2`) and list items under
+    /// such keys are touched, so unrelated versions stay put.
+    pub fn rewrite_workflow(&self, content: &str) -> String {
+        // Indentation of the enclosing `swift...:` block key, while inside one
+        let mut swift_block: Option<usize> = None;
 
-        println!("{}", "🎯 Migrating Xcode projects...".bold());
-        let xcode_projects = self.migrate_xcode_projects(project_root)?;
-        for proj in &xcode_projects {
-            println!("  {} {}", "✓".green(), proj);
-        }
-        println!("  {} projects", xcode_projects.len());
-        println!();
+        map_lines(content, |_, line| {
+            let trimmed = line.trim_start();
+            let indent = line.len() - trimmed.len();
 
-        println!("{}", "📚 Migrating documentation...".bold());
-        let docs = self.migrate_documentation(project_root)?;
-        for doc in &docs {
-            println!("  {} {}", "✓".green(), doc);
-        }
-        println!("  {} files", docs.len());
-        println!();
+            if swift_block.is_some_and(|block| indent <= block && !trimmed.is_empty()) {
+                swift_block = None;
+            }
+            if swift_block.is_some() && trimmed.starts_with('-') {
+                return replace_version(line, &self.from_version, &self.to_version);
+            }
 
-        if self.dry_run {
-            println!(
-                "{}",
-                "✅ Dry run complete! Run without --dry-run to apply changes.".green().bold()
-            );
-        } else {
-            println!("{}", "✅ Migration complete!".green().bold());
-            println!();
-            println!("Next steps:");
-            println!("  1. Clean build artifacts: rm -rf .build */build");
-            println!("  2. Verify: foodshare-ios swift verify");
-            println!("  3. Test builds: swift build");
+            let Some((key, value)) = trimmed.split_once(':') else {
+                return line.to_string();
+            };
+            let key = key.trim_start_matches('-').trim();
+            if !key.to_ascii_lowercase().contains("swift") {
+                return line.to_string();
+            }
+            if value.trim().is_empty() {
+                swift_block = Some(indent);
+                return line.to_string();
+            }
+            replace_version(line, &self.from_version, &self.to_version)
+        })
+    }
+
+    /// Apply `rewrite` to a file, writing it back unless this is a dry run
+    fn rewrite_file(
+        &self,
+        path: &Path,
+        kind: FileKind,
+        rewrite: impl FnOnce(&str) -> String,
+    ) -> Result<Option<FileChange>> {
+        let content = std::fs::read_to_string(path)?;
+        let new_content = rewrite(&content);
+        let change = FileChange::diff(path, kind, &content, &new_content);
+
+        if change.is_some() && !self.dry_run {
+            std::fs::write(path, new_content)?;
         }
+        Ok(change)
+    }
+
+    /// Run full migration
+    pub fn run(&self, project_root: &Path) -> Result<MigrationReport> {
+        let mut files = self.migrate_package_files(project_root)?;
+        files.extend(self.migrate_xcode_projects(project_root)?);
+        files.extend(self.migrate_workflows(project_root)?);
+        files.extend(self.migrate_documentation(project_root)?);
+
+        Ok(MigrationReport {
+            from_version: self.from_version.clone(),
+            to_version: self.to_version.clone(),
+            dry_run: self.dry_run,
+            files,
+        })
+    }
+}
+
+/// Apply `f` to every line, keeping line endings and a trailing newline
+fn map_lines(content: &str, mut f: impl FnMut(usize, &str) -> String) -> String {
+    content
+        .split_inclusive('\n')
+        .enumerate()
+        .map(|(index, line)| {
+            let body = line.trim_end_matches(['\n', '\r']);
+            let ending = &line[body.len()..];
+            f(index, body) + ending
+        })
+        .collect()
+}
+
+/// Language mode major version (`6` for `6.3`)
+fn language_mode(version: &str) -> Option<u32> {
+    version.split('.').next()?.parse().ok()
+}
+
+/// Replace `version` where it appears as a whole version number
+///
+/// `6.2` matches in `"6.2"` and `:6.2` but not in `16.2` or `6.2.1`.
+fn replace_version(line: &str, from: &str, to: &str) -> String {
+    replace_bounded(line, from, to, |c| c.is_ascii_digit() || c == '.')
+}
+
+/// Replace `token` where it is not part of a longer identifier
+fn replace_token(line: &str, from: &str, to: &str) -> String {
+    replace_bounded(line, from, to, |c| c.is_alphanumeric() || c == '_')
+}
+
+fn replace_bounded(line: &str, from: &str, to: &str, continues: impl Fn(char) -> bool) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(pos) = rest.find(from) {
+        let before = rest[..pos].chars().next_back();
+        let after = rest[pos + from.len()..].chars().next();
+        let starts_cleanly = !from.starts_with(|c: char| c.is_alphanumeric())
+            || before.is_none_or(|c| !continues(c));
+        let ends_cleanly = after.is_none_or(|c| !continues(c));
+
+        result.push_str(&rest[..pos]);
+        result.push_str(if starts_cleanly && ends_cleanly { to } else { from });
+        rest = &rest[pos + from.len()..];
+    }
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn migrator(from: &str, to: &str) -> SwiftMigrator {
+        SwiftMigrator::new(from.to_string(), to.to_string(), true)
+    }
+
+    #[test]
+    fn test_rewrite_package_manifest() {
+        let manifest = r#"// swift-tools-version:5.10
+import PackageDescription
+
+let package = Package(
+    name: "FoodshareCore",
+    targets: [
+        .target(
+            name: "FoodshareCore",
+            swiftSettings: [
+                .swiftLanguageMode(.v5),
+                .unsafeFlags(["-swift-version", "5"]),
+            ]
+        ),
+    ],
+    swiftLanguageModes: [.v5]
+)
+"#;
+        let rewritten = migrator("5.10", "6.0").rewrite_package_manifest(manifest);
+        assert!(rewritten.starts_with("// swift-tools-version:6.0\n"));
+        assert!(rewritten.contains(".swiftLanguageMode(.v6)"));
+        assert!(rewritten.contains(r#".unsafeFlags(["-swift-version", "6"])"#));
+        assert!(rewritten.contains("swiftLanguageModes: [.v6]"));
+
+        // Minor bumps leave the language mode alone
+        let minor = migrator("6.2", "6.3")
+            .rewrite_package_manifest("// swift-tools-version: 6.2\n    swiftLanguageModes: [.v6]\n");
+        assert_eq!(minor, "// swift-tools-version: 6.3\n    swiftLanguageModes: [.v6]\n");
+    }
+
+    #[test]
+    fn test_rewrite_workflow_matrix() {
+        let workflow = r#"env:
+  SWIFT_VERSION: "6.2"
+  NODE_VERSION: "6.2"
+jobs:
+  build:
+    strategy:
+      matrix:
+        swift: ["6.2", "6.2.1", "16.2"]
+        swift-version:
+          - "6.2"
+          - '6.1'
+        xcode:
+          - "6.2"
+"#;
+        let rewritten = migrator("6.2", "6.3").rewrite_workflow(workflow);
+        assert!(rewritten.contains(r#"SWIFT_VERSION: "6.3""#));
+        assert!(rewritten.contains(r#"NODE_VERSION: "6.2""#));
+        assert!(rewritten.contains(r#"swift: ["6.3", "6.2.1", "16.2"]"#));
+        assert!(rewritten.contains("          - \"6.3\"\n          - '6.1'\n"));
+        assert!(rewritten.ends_with("        xcode:\n          - \"6.2\"\n"));
+    }
+
+    #[test]
+    fn test_dry_run_reports_without_writing() {
+        let dir = tempfile::tempdir().unwrap();
+        let package = dir.path().join("Package.swift");
+        std::fs::write(&package, "// swift-tools-version: 6.2\nimport PackageDescription\n").unwrap();
+
+        let report = migrator("6.2", "6.3").run(dir.path()).unwrap();
+        assert_eq!(report.files.len(), 1);
+        let change = &report.files[0];
+        assert_eq!(change.kind, FileKind::PackageManifest);
+        assert_eq!(
+            change.edits,
+            [LineEdit {
+                line: 1,
+                before: "// swift-tools-version: 6.2".to_string(),
+                after: "// swift-tools-version: 6.3".to_string(),
+            }]
+        );
+        assert!(std::fs::read_to_string(&package).unwrap().contains("6.2"));
 
-        Ok(())
+        SwiftMigrator::new("6.2".to_string(), "6.3".to_string(), false)
+            .run(dir.path())
+            .unwrap();
+        assert!(std::fs::read_to_string(&package).unwrap().contains("6.3"));
     }
 }
@@ -0,0 +1,250 @@
+//! Shell integration
+//!
+//! `foodshare-swift env --shell <shell>` prints a hook that runs whenever the
+//! working directory changes. The hook calls back into
+//! `foodshare-swift env --shell <shell> --apply`, which looks for the nearest
+//! pinned project and prints the commands that switch `PATH`/`TOOLCHAINS` to
+//! its toolchain, or restore them when leaving it.
+//!
+//! The hook remembers what it did in [`ROOT_VAR`] and [`BIN_VAR`], so it only
+//! does work when crossing a project boundary and can undo its own `PATH`
+//! entry.
+
+use crate::detect::ToolchainBundle;
+use crate::error::SwiftError;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Project root the hook last switched to
+pub const ROOT_VAR: &str = "FOODSHARE_SWIFT_ROOT";
+
+/// Toolchain `bin` directory the hook prepended to `PATH`
+pub const BIN_VAR: &str = "FOODSHARE_SWIFT_BIN";
+
+/// Supported shells
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl FromStr for Shell {
+    type Err = SwiftError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bash" => Ok(Self::Bash),
+            "zsh" => Ok(Self::Zsh),
+            "fish" => Ok(Self::Fish),
+            _ => Err(SwiftError::Config(format!(
+                "unsupported shell '{s}' (expected zsh, bash or fish)"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Shell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Bash => "bash",
+            Self::Zsh => "zsh",
+            Self::Fish => "fish",
+        })
+    }
+}
+
+impl Shell {
+    /// Hook script to `eval` (bash/zsh) or `source` (fish) from the shell's rc file
+    ///
+    /// `program` is the command used to call back into `foodshare-swift`.
+    pub fn hook(self, program: &str) -> String {
+        let apply = format!("{} env --shell {self} --apply", quote(program));
+        match self {
+            Self::Zsh => format!(
+                r#"_foodshare_swift_hook() {{
+  eval "$({apply})"
+}}
+typeset -ag chpwd_functions
+if (( ! ${{chpwd_functions[(I)_foodshare_swift_hook]}} )); then
+  chpwd_functions=(_foodshare_swift_hook $chpwd_functions)
+fi
+_foodshare_swift_hook
+"#
+            ),
+            // bash has no directory change hook, so check on every prompt
+            Self::Bash => format!(
+                r#"_foodshare_swift_hook() {{
+  if [[ "$PWD" != "${{_FOODSHARE_SWIFT_PWD:-}}" ]]; then
+    _FOODSHARE_SWIFT_PWD="$PWD"
+    eval "$({apply})"
+  fi
+}}
+if [[ ";${{PROMPT_COMMAND:-}};" != *";_foodshare_swift_hook;"* ]]; then
+  PROMPT_COMMAND="_foodshare_swift_hook${{PROMPT_COMMAND:+;$PROMPT_COMMAND}}"
+fi
+_foodshare_swift_hook
+"#
+            ),
+            Self::Fish => format!(
+                r#"function _foodshare_swift_hook --on-variable PWD
+    {apply} | source
+end
+_foodshare_swift_hook
+"#
+            ),
+        }
+    }
+
+    /// Command setting an exported variable
+    pub fn export(self, name: &str, value: &str) -> String {
+        match self {
+            // fish keeps PATH as a list
+            Self::Fish if name == "PATH" => {
+                let entries: Vec<String> = value.split(':').map(quote).collect();
+                format!("set -gx PATH {}", entries.join(" "))
+            }
+            Self::Fish => format!("set -gx {name} {}", quote(value)),
+            Self::Bash | Self::Zsh => format!("export {name}={}", quote(value)),
+        }
+    }
+
+    /// Command removing a variable
+    pub fn unset(self, name: &str) -> String {
+        match self {
+            Self::Fish => format!("set -e {name}"),
+            Self::Bash | Self::Zsh => format!("unset {name}"),
+        }
+    }
+}
+
+/// Commands moving the shell from its current project to `root`
+///
+/// `current_bin` is the toolchain directory the hook added to `path` earlier,
+/// if any. `root` is the project now entered (`None` when outside every
+/// project) and `toolchain` the toolchain it selects, if one could be found.
+pub fn switch_commands(
+    shell: Shell,
+    path: &str,
+    current_bin: Option<&Path>,
+    root: Option<&Path>,
+    toolchain: Option<&ToolchainBundle>,
+) -> Vec<String> {
+    let current_bin = current_bin.map(|bin| bin.to_string_lossy().to_string());
+    let mut entries: Vec<String> = path
+        .split(':')
+        .filter(|entry| Some(*entry) != current_bin.as_deref())
+        .map(str::to_string)
+        .collect();
+
+    let mut commands = Vec::new();
+    if current_bin.is_some() {
+        commands.push(shell.unset("TOOLCHAINS"));
+    }
+
+    match toolchain {
+        Some(toolchain) => {
+            let bin = toolchain.bin_dir().to_string_lossy().to_string();
+            entries.insert(0, bin.clone());
+            commands.push(shell.export("PATH", &entries.join(":")));
+            if let Some(ref identifier) = toolchain.identifier {
+                commands.push(shell.export("TOOLCHAINS", identifier));
+            }
+            commands.push(shell.export(BIN_VAR, &bin));
+        }
+        None => {
+            if current_bin.is_some() {
+                commands.push(shell.export("PATH", &entries.join(":")));
+            }
+            commands.push(shell.unset(BIN_VAR));
+        }
+    }
+
+    match root {
+        Some(root) => commands.push(shell.export(ROOT_VAR, &root.to_string_lossy())),
+        None => commands.push(shell.unset(ROOT_VAR)),
+    }
+    commands
+}
+
+/// Single-quote a value for POSIX shells and fish
+fn quote(value: &str) -> String {
+    if !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "/._-+:@%=".contains(c))
+    {
+        return value.to_string();
+    }
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn toolchain() -> ToolchainBundle {
+        ToolchainBundle {
+            path: PathBuf::from("/Toolchains/swift-6.3.xctoolchain"),
+            name: "swift-6.3".to_string(),
+            identifier: Some("org.swift.63".to_string()),
+            display_name: None,
+            version: None,
+        }
+    }
+
+    #[test]
+    fn test_hooks() {
+        let zsh = Shell::Zsh.hook("/opt/bin/foodshare-swift");
+        assert!(zsh.contains("chpwd_functions"));
+        assert!(zsh.contains(r#"eval "$(/opt/bin/foodshare-swift env --shell zsh --apply)""#));
+
+        assert!(Shell::Bash.hook("foodshare-swift").contains("PROMPT_COMMAND"));
+        assert!(Shell::Fish
+            .hook("foodshare-swift")
+            .contains("--on-variable PWD\n    foodshare-swift env --shell fish --apply | source"));
+        assert!("tcsh".parse::<Shell>().is_err());
+    }
+
+    #[test]
+    fn test_switch_commands() {
+        let root = Path::new("/work/foodshare");
+        let enter = switch_commands(Shell::Zsh, "/usr/bin:/bin", None, Some(root), Some(&toolchain()));
+        assert_eq!(
+            enter,
+            [
+                "export PATH=/Toolchains/swift-6.3.xctoolchain/usr/bin:/usr/bin:/bin",
+                "export TOOLCHAINS=org.swift.63",
+                "export FOODSHARE_SWIFT_BIN=/Toolchains/swift-6.3.xctoolchain/usr/bin",
+                "export FOODSHARE_SWIFT_ROOT=/work/foodshare",
+            ]
+        );
+
+        let bin = Path::new("/Toolchains/swift-6.3.xctoolchain/usr/bin");
+        let leave = switch_commands(
+            Shell::Fish,
+            "/Toolchains/swift-6.3.xctoolchain/usr/bin:/usr/bin:/bin",
+            Some(bin),
+            None,
+            None,
+        );
+        assert_eq!(
+            leave,
+            [
+                "set -e TOOLCHAINS",
+                "set -gx PATH /usr/bin /bin",
+                "set -e FOODSHARE_SWIFT_BIN",
+                "set -e FOODSHARE_SWIFT_ROOT",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("/usr/bin"), "/usr/bin");
+        assert_eq!(quote("/Users/me/My Tools"), "'/Users/me/My Tools'");
+        assert_eq!(quote("it's"), r"'it'\''s'");
+    }
+}
@@ -1,5 +1,7 @@
+use crate::config::SwiftConfig;
 use crate::detect::{SwiftToolchain, SwiftVersion};
 use crate::error::{Result, SwiftError};
+use crate::xcode::{self, Compatibility, XcodeVersion};
 use colored::Colorize;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
@@ -12,6 +14,19 @@ pub struct PackageSwiftInfo {
     pub matches_required: bool,
 }
 
+/// A package covered by a [`VersionOverride`](crate::config::VersionOverride)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverriddenPackage {
+    pub path: PathBuf,
+    pub tools_version: String,
+    /// Allowed version range from the override
+    pub allowed: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Whether the tools-version is within the allowed range
+    pub within_range: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct XcodeProjectInfo {
     pub path: PathBuf,
@@ -25,7 +40,13 @@ pub struct VerificationReport {
     pub installed_version: String,
     pub required_version: String,
     pub active_toolchain: String,
+    /// Selected Xcode, when `xcodebuild` is available
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xcode: Option<XcodeVersion>,
     pub package_files: Vec<PackageSwiftInfo>,
+    /// Packages checked against an override instead of the required version
+    #[serde(default)]
+    pub overridden_packages: Vec<OverriddenPackage>,
     pub xcode_projects: Vec<XcodeProjectInfo>,
     pub all_match: bool,
     pub issues: Vec<String>,
@@ -33,12 +54,17 @@ pub struct VerificationReport {
 
 impl VerificationReport {
     /// Verify Swift version consistency across the project
+    ///
+    /// Packages matching an override in the project's `.foodshare-swift.toml`
+    /// are checked against the override's range and listed separately.
     pub fn generate(project_root: &Path, required_version: &str) -> Result<Self> {
+        let config = SwiftConfig::load(project_root)?;
         let toolchain = SwiftToolchain::detect_active()?;
         let installed_version = toolchain.version.short_version();
         let active_toolchain = toolchain.path.display().to_string();
 
         let mut package_files = Vec::new();
+        let mut overridden_packages = Vec::new();
         let mut xcode_projects = Vec::new();
         let mut issues = Vec::new();
 
@@ -48,15 +74,38 @@ impl VerificationReport {
             .into_iter()
             .filter_entry(|e| {
                 let name = e.file_name().to_string_lossy();
-                !name.starts_with('.')
-                    && name != "build"
-                    && name != "SourcePackages"
-                    && name != "swift-android-contributions"
+                // The root itself may be `.` or a hidden directory
+                e.depth() == 0
+                    || (!name.starts_with('.')
+                        && name != "build"
+                        && name != "SourcePackages"
+                        && name != "swift-android-contributions")
             })
             .filter_map(|e| e.ok())
         {
             if entry.file_name() == "Package.swift" {
                 if let Ok(info) = Self::check_package_swift(entry.path(), required_version) {
+                    let relative = entry.path().strip_prefix(project_root).unwrap_or(entry.path());
+                    if let Some(rule) = config.override_for(relative) {
+                        let within_range = rule.allows(&info.tools_version).unwrap_or(false);
+                        if !within_range {
+                            issues.push(format!(
+                                "Package.swift at {} uses version {} (override allows {})",
+                                info.path.display(),
+                                info.tools_version,
+                                rule.version
+                            ));
+                        }
+                        overridden_packages.push(OverriddenPackage {
+                            path: info.path,
+                            tools_version: info.tools_version,
+                            allowed: rule.version.clone(),
+                            reason: rule.reason.clone(),
+                            within_range,
+                        });
+                        continue;
+                    }
+
                     if !info.matches_required {
                         issues.push(format!(
                             "Package.swift at {} uses version {} (expected {})",
@@ -105,13 +154,25 @@ impl VerificationReport {
             ));
         }
 
+        // Cross-check the toolchain against the selected Xcode
+        let xcode = XcodeVersion::detect();
+        if let Some(ref xcode) = xcode {
+            if let Compatibility::Incompatible { guidance, .. } =
+                xcode::check(&toolchain.version, xcode)
+            {
+                issues.push(guidance);
+            }
+        }
+
         let all_match = issues.is_empty();
 
         Ok(Self {
             installed_version,
             required_version: required_version.to_string(),
             active_toolchain,
+            xcode,
             package_files,
+            overridden_packages,
             xcode_projects,
             all_match,
             issues,
@@ -187,6 +248,10 @@ impl VerificationReport {
         println!("📦 Installed Swift: {}", self.installed_version.cyan());
         println!("🎯 Required Version: {}", self.required_version.cyan());
         println!("🔧 Active Toolchain: {}", self.active_toolchain.dimmed());
+        if let Some(ref xcode) = self.xcode {
+            let build = xcode.build.as_deref().map(|b| format!(" ({b})")).unwrap_or_default();
+            println!("🛠  Xcode: {}{}", xcode.version.cyan(), build.dimmed());
+        }
         println!();
 
         println!("{}", "📄 Package.swift Files:".bold());
@@ -205,6 +270,28 @@ impl VerificationReport {
         }
         println!();
 
+        if !self.overridden_packages.is_empty() {
+            println!("{}", "📌 Overridden Packages:".bold());
+            for pkg in &self.overridden_packages {
+                let status = if pkg.within_range {
+                    "✓".yellow()
+                } else {
+                    "✗".red()
+                };
+                println!(
+                    "  {} {} ({}, allowed {})",
+                    status,
+                    pkg.path.display(),
+                    pkg.tools_version,
+                    pkg.allowed
+                );
+                if let Some(ref reason) = pkg.reason {
+                    println!("      {}", reason.dimmed());
+                }
+            }
+            println!();
+        }
+
         if !self.xcode_projects.is_empty() {
             println!("{}", "🎯 Xcode Projects:".bold());
             for proj in &self.xcode_projects {
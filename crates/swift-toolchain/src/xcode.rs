@@ -0,0 +1,171 @@
+//! Xcode compatibility checks
+//!
+//! Swift toolchains on macOS build against the SDK of the selected Xcode, and
+//! a toolchain newer than that Xcode supports fails in confusing ways (missing
+//! macro plugins, SDK module errors). [`COMPATIBILITY`] records the oldest
+//! Xcode each Swift release works with.
+
+use crate::detect::SwiftVersion;
+use crate::error::{Result, SwiftError};
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+/// Oldest Xcode a Swift release works with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct XcodeRequirement {
+    /// Swift major.minor version
+    pub swift: &'static str,
+    /// Minimum Xcode version
+    pub min_xcode: &'static str,
+}
+
+/// Swift versions and the Xcode releases they need, oldest first
+pub const COMPATIBILITY: &[XcodeRequirement] = &[
+    XcodeRequirement { swift: "5.9", min_xcode: "15.0" },
+    XcodeRequirement { swift: "5.10", min_xcode: "15.3" },
+    XcodeRequirement { swift: "6.0", min_xcode: "16.0" },
+    XcodeRequirement { swift: "6.1", min_xcode: "16.3" },
+    XcodeRequirement { swift: "6.2", min_xcode: "26.0" },
+    XcodeRequirement { swift: "6.3", min_xcode: "26.0" },
+];
+
+/// An installed Xcode, as reported by `xcodebuild -version`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct XcodeVersion {
+    /// Marketing version (`16.2`)
+    pub version: String,
+    /// Build number (`16C5032a`)
+    pub build: Option<String>,
+}
+
+impl XcodeVersion {
+    /// Detect the selected Xcode
+    ///
+    /// Returns `None` when `xcodebuild` is unavailable (Linux, or only the
+    /// Command Line Tools are installed).
+    pub fn detect() -> Option<Self> {
+        let output = Command::new("xcodebuild").arg("-version").output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        Self::parse(&String::from_utf8_lossy(&output.stdout)).ok()
+    }
+
+    /// Parse `xcodebuild -version` output
+    pub fn parse(output: &str) -> Result<Self> {
+        let mut lines = output.lines();
+        let version = lines
+            .next()
+            .and_then(|line| line.trim().strip_prefix("Xcode "))
+            .map(|v| v.trim().to_string())
+            .ok_or_else(|| SwiftError::ParseError(output.trim().to_string()))?;
+        let build = lines
+            .find_map(|line| line.trim().strip_prefix("Build version "))
+            .map(|b| b.trim().to_string());
+
+        Ok(Self { version, build })
+    }
+}
+
+/// Outcome of checking a toolchain against an Xcode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Compatibility {
+    /// Known to work
+    Compatible,
+    /// Known to be broken; `guidance` says how to fix it
+    Incompatible { min_xcode: String, guidance: String },
+    /// The Swift version is not in [`COMPATIBILITY`]
+    Unknown,
+}
+
+/// Check whether `swift` works with `xcode`
+pub fn check(swift: &SwiftVersion, xcode: &XcodeVersion) -> Compatibility {
+    let Some(requirement) = requirement_for(swift) else {
+        return Compatibility::Unknown;
+    };
+    if version_key(&xcode.version) >= version_key(requirement.min_xcode) {
+        return Compatibility::Compatible;
+    }
+
+    let mut guidance = format!(
+        "Swift {} needs Xcode {} or later, but Xcode {} is selected. Install a newer Xcode \
+         and select it with `sudo xcode-select -s /Applications/Xcode.app`",
+        swift.short_version(),
+        requirement.min_xcode,
+        xcode.version
+    );
+    if let Some(supported) = newest_swift_for(xcode) {
+        guidance.push_str(&format!(
+            ", or switch to a Swift {} toolchain with `foodshare-swift use {}`",
+            supported.swift, supported.swift
+        ));
+    }
+
+    Compatibility::Incompatible {
+        min_xcode: requirement.min_xcode.to_string(),
+        guidance,
+    }
+}
+
+/// Compatibility entry for a Swift version
+pub fn requirement_for(swift: &SwiftVersion) -> Option<&'static XcodeRequirement> {
+    COMPATIBILITY
+        .iter()
+        .find(|requirement| swift.matches(requirement.swift))
+}
+
+/// Newest Swift release that works with `xcode`
+pub fn newest_swift_for(xcode: &XcodeVersion) -> Option<&'static XcodeRequirement> {
+    COMPATIBILITY
+        .iter()
+        .rev()
+        .find(|requirement| version_key(&xcode.version) >= version_key(requirement.min_xcode))
+}
+
+/// Numeric components of a dotted version, for ordering (`16.2` < `16.10`)
+fn version_key(version: &str) -> Vec<u32> {
+    version
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn xcode(version: &str) -> XcodeVersion {
+        XcodeVersion {
+            version: version.to_string(),
+            build: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_xcodebuild_version() {
+        let xcode = XcodeVersion::parse("Xcode 16.2\nBuild version 16C5032a\n").unwrap();
+        assert_eq!(xcode.version, "16.2");
+        assert_eq!(xcode.build.as_deref(), Some("16C5032a"));
+
+        assert!(XcodeVersion::parse("xcode-select: error: tool 'xcodebuild' requires Xcode").is_err());
+    }
+
+    #[test]
+    fn test_check_compatibility() {
+        let swift_63 = SwiftVersion::parse("6.3-dev").unwrap();
+        assert_eq!(check(&swift_63, &xcode("26.1")), Compatibility::Compatible);
+
+        let Compatibility::Incompatible { min_xcode, guidance } = check(&swift_63, &xcode("16.2")) else {
+            panic!("Swift 6.3 with Xcode 16.2 should be incompatible");
+        };
+        assert_eq!(min_xcode, "26.0");
+        assert!(guidance.contains("foodshare-swift use 6.0"));
+
+        // 16.10 sorts after 16.3, not before
+        let swift_61 = SwiftVersion::parse("6.1").unwrap();
+        assert_eq!(check(&swift_61, &xcode("16.10")), Compatibility::Compatible);
+
+        let swift_70 = SwiftVersion::parse("7.0").unwrap();
+        assert_eq!(check(&swift_70, &xcode("26.0")), Compatibility::Unknown);
+    }
+}
@@ -5,6 +5,8 @@
 //! - Metrics collection and export
 //! - Performance tracking
 //! - Error reporting
+//! - W3C trace context propagation
+//! - Metric trends persisted across runs
 
 use chrono::{DateTime, Utc};
 use once_cell::sync::Lazy;
@@ -16,6 +18,12 @@ use std::time::{Duration, Instant};
 use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 use uuid::Uuid;
 
+pub mod trace;
+pub mod trends;
+
+pub use trace::{TraceContext, TraceContextLayer};
+pub use trends::{TrendLog, TrendPoint};
+
 /// Global metrics registry
 static METRICS: Lazy<MetricsRegistry> = Lazy::new(MetricsRegistry::new);
 
@@ -34,6 +42,7 @@ pub fn init_with_config(config: TelemetryConfig) -> anyhow::Result<()> {
 
     let subscriber = tracing_subscriber::registry()
         .with(filter)
+        .with(TraceContextLayer)
         .with(fmt::layer()
             .with_target(config.show_target)
             .with_thread_ids(config.show_thread_ids)
@@ -0,0 +1,198 @@
+//! W3C trace context for tracing spans
+//!
+//! [`TraceContextLayer`] gives every `tracing` span a [`TraceContext`]: a
+//! child of its parent span's context, or of the process root context for
+//! top-level spans. [`TraceContext::current`] reads the context of the active
+//! span so it can be propagated to other services as a `traceparent` header.
+//!
+//! The root context is taken from the `TRACEPARENT` environment variable when
+//! a caller (e.g. a CI job) started the trace, otherwise it is generated once
+//! per process, like [`session_id`](crate::session_id).
+
+use once_cell::sync::Lazy;
+use std::fmt;
+use tracing::span::{Attributes, Id};
+use tracing::Subscriber;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{Layer, Registry};
+use uuid::Uuid;
+
+/// Environment variable carrying a caller's `traceparent`
+pub const TRACEPARENT_ENV: &str = "TRACEPARENT";
+
+/// Root context for this process
+static ROOT: Lazy<TraceContext> = Lazy::new(|| {
+    std::env::var(TRACEPARENT_ENV)
+        .ok()
+        .and_then(|value| TraceContext::parse(&value))
+        .unwrap_or_else(TraceContext::new_root)
+});
+
+/// A W3C trace context (`version-traceid-parentid-flags`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TraceContext {
+    /// 16-byte trace ID shared by every span in the trace
+    pub trace_id: u128,
+    /// 8-byte ID of this span
+    pub span_id: u64,
+    /// Whether the trace is sampled
+    pub sampled: bool,
+}
+
+impl TraceContext {
+    /// Start a new sampled trace
+    pub fn new_root() -> Self {
+        Self {
+            trace_id: Uuid::new_v4().as_u128(),
+            span_id: new_span_id(),
+            sampled: true,
+        }
+    }
+
+    /// Create a child span context in the same trace
+    pub fn child(&self) -> Self {
+        Self {
+            span_id: new_span_id(),
+            ..*self
+        }
+    }
+
+    /// The process root context
+    pub fn root() -> Self {
+        *ROOT
+    }
+
+    /// Context of the current `tracing` span
+    ///
+    /// Falls back to the root context when no span is active or the
+    /// subscriber was not built with [`TraceContextLayer`].
+    pub fn current() -> Self {
+        tracing::Span::current()
+            .with_subscriber(|(id, dispatch)| {
+                let registry = dispatch.downcast_ref::<Registry>()?;
+                registry.span(id)?.extensions().get::<TraceContext>().copied()
+            })
+            .flatten()
+            .unwrap_or_else(Self::root)
+    }
+
+    /// Parse a `traceparent` header value
+    ///
+    /// Returns `None` for malformed values and the all-zero IDs the spec
+    /// forbids.
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.trim().split('-');
+        let version = parts.next()?;
+        let trace_id = parts.next()?;
+        let span_id = parts.next()?;
+        let flags = parts.next()?;
+
+        if version.len() != 2 || version == "ff" || trace_id.len() != 32 || span_id.len() != 16 || flags.len() != 2 {
+            return None;
+        }
+        // Version 00 has exactly four fields
+        if version == "00" && parts.next().is_some() {
+            return None;
+        }
+
+        let trace_id = u128::from_str_radix(trace_id, 16).ok()?;
+        let span_id = u64::from_str_radix(span_id, 16).ok()?;
+        let flags = u8::from_str_radix(flags, 16).ok()?;
+        u8::from_str_radix(version, 16).ok()?;
+
+        (trace_id != 0 && span_id != 0).then_some(Self {
+            trace_id,
+            span_id,
+            sampled: flags & 0x01 != 0,
+        })
+    }
+
+    /// Format as a `traceparent` header value
+    pub fn to_traceparent(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for TraceContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "00-{:032x}-{:016x}-{:02x}",
+            self.trace_id,
+            self.span_id,
+            u8::from(self.sampled)
+        )
+    }
+}
+
+fn new_span_id() -> u64 {
+    // The low half of a v4 UUID is random apart from the variant bits
+    let id = Uuid::new_v4().as_u128() as u64;
+    if id == 0 { 1 } else { id }
+}
+
+/// Layer that attaches a [`TraceContext`] to every new span
+///
+/// Installed by [`init_with_config`](crate::init_with_config); add it
+/// yourself when building a custom subscriber.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TraceContextLayer;
+
+impl<S> Layer<S> for TraceContextLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, _attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let parent = span
+            .parent()
+            .and_then(|parent| parent.extensions().get::<TraceContext>().copied())
+            .unwrap_or_else(TraceContext::root);
+        span.extensions_mut().insert(parent.child());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::prelude::*;
+
+    #[test]
+    fn test_traceparent_roundtrip() {
+        let context = TraceContext::new_root();
+        let header = context.to_traceparent();
+        assert_eq!(header.len(), 55);
+        assert_eq!(TraceContext::parse(&header), Some(context));
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid() {
+        let valid = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        assert!(TraceContext::parse(valid).is_some());
+        assert!(TraceContext::parse("00-00000000000000000000000000000000-00f067aa0ba902b7-01").is_none());
+        assert!(TraceContext::parse("ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01").is_none());
+        assert!(TraceContext::parse("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7").is_none());
+        assert!(TraceContext::parse("not a header").is_none());
+    }
+
+    #[test]
+    fn test_spans_inherit_trace_id() {
+        let subscriber = tracing_subscriber::registry().with(TraceContextLayer);
+        tracing::subscriber::with_default(subscriber, || {
+            let outer = tracing::info_span!("outer");
+            let _outer = outer.enter();
+            let parent = TraceContext::current();
+
+            let inner = tracing::info_span!("inner");
+            let _inner = inner.enter();
+            let child = TraceContext::current();
+
+            assert_eq!(parent.trace_id, TraceContext::root().trace_id);
+            assert_eq!(child.trace_id, parent.trace_id);
+            assert_ne!(child.span_id, parent.span_id);
+        });
+    }
+}
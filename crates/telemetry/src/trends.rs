@@ -0,0 +1,142 @@
+//! Persisted metric trends
+//!
+//! The [`MetricsRegistry`](crate::MetricsRegistry) only lives as long as the
+//! process. Values worth comparing across runs (build times, bundle sizes)
+//! are also appended to a [`TrendLog`], a JSON lines file with one
+//! [`TrendPoint`] per line, so later runs can read the history back.
+
+use crate::{metrics, session_id};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// A single recorded value
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrendPoint {
+    pub timestamp: DateTime<Utc>,
+    pub session_id: String,
+    pub metric: String,
+    pub value: f64,
+    /// Extra dimensions, e.g. the file a compile time belongs to
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+}
+
+impl TrendPoint {
+    /// Create a point for the current session
+    pub fn new(metric: impl Into<String>, value: f64) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            session_id: session_id().to_string(),
+            metric: metric.into(),
+            value,
+            labels: BTreeMap::new(),
+        }
+    }
+
+    /// Add a label
+    pub fn with_label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.insert(key.into(), value.into());
+        self
+    }
+}
+
+/// Append-only store of [`TrendPoint`]s
+#[derive(Debug, Clone)]
+pub struct TrendLog {
+    path: PathBuf,
+}
+
+impl TrendLog {
+    /// Open the log at `path`; the file is created on the first write
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Path of the log file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append points, also recording each value in the global metrics registry
+    pub fn record(&self, points: &[TrendPoint]) -> anyhow::Result<()> {
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut lines = String::new();
+        for point in points {
+            metrics().histogram(&point.metric, point.value);
+            lines.push_str(&serde_json::to_string(point)?);
+            lines.push('\n');
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(lines.as_bytes())?;
+        Ok(())
+    }
+
+    /// Read every point, oldest first
+    ///
+    /// A missing file is an empty history; unreadable lines are skipped so a
+    /// truncated write doesn't lose the rest of it.
+    pub fn read(&self) -> anyhow::Result<Vec<TrendPoint>> {
+        let content = match std::fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    /// Points for `metric` whose labels include every pair in `labels`, oldest first
+    pub fn series(&self, metric: &str, labels: &[(&str, &str)]) -> anyhow::Result<Vec<TrendPoint>> {
+        Ok(self
+            .read()?
+            .into_iter()
+            .filter(|point| {
+                point.metric == metric
+                    && labels
+                        .iter()
+                        .all(|(k, v)| point.labels.get(*k).map(String::as_str) == Some(*v))
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = TrendLog::new(dir.path().join("trends").join("build.jsonl"));
+        assert!(log.read().unwrap().is_empty());
+
+        log.record(&[
+            TrendPoint::new("build.total_ms", 1200.0),
+            TrendPoint::new("build.file_ms", 300.0).with_label("file", "A.swift"),
+            TrendPoint::new("build.file_ms", 200.0).with_label("file", "B.swift"),
+        ])
+        .unwrap();
+        log.record(&[TrendPoint::new("build.total_ms", 900.0)]).unwrap();
+
+        let totals = log.series("build.total_ms", &[]).unwrap();
+        assert_eq!(totals.iter().map(|p| p.value).collect::<Vec<_>>(), [1200.0, 900.0]);
+
+        let file = log.series("build.file_ms", &[("file", "B.swift")]).unwrap();
+        assert_eq!(file.len(), 1);
+        assert_eq!(file[0].value, 200.0);
+        assert_eq!(file[0].session_id, session_id());
+    }
+}
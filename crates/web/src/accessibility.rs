@@ -1,11 +1,17 @@
 //! Accessibility (a11y) checks for JSX/TSX
 //!
-//! Checks React components for common accessibility issues.
+//! Checks React components for common accessibility issues. Files are
+//! parsed into their JSX element tree, so rules see whole elements (including
+//! ones spread over several lines) and report the line of the offending
+//! element or attribute. Rule IDs follow `eslint-plugin-jsx-a11y`.
 
+use crate::jsx::{self, Element, Value};
+use foodshare_cli::output::{self, Finding, Level};
 use foodshare_core::error::exit_codes;
 use once_cell::sync::Lazy;
 use owo_colors::OwoColorize;
 use regex::Regex;
+use std::collections::HashSet;
 use std::path::Path;
 
 /// Accessibility issue
@@ -24,76 +30,560 @@ pub enum A11ySeverity {
     Warning,
 }
 
-/// A11y check pattern
-struct A11yPattern {
-    name: &'static str,
-    pattern: Regex,
-    message: &'static str,
-    severity: A11ySeverity,
-}
-
-/// Built-in a11y patterns
-static PATTERNS: Lazy<Vec<A11yPattern>> = Lazy::new(|| {
-    vec![
-        // Autofocus (simple pattern that works)
-        A11yPattern {
-            name: "no-autofocus",
-            pattern: Regex::new(r#"autoFocus"#).unwrap(),
-            message: "Avoid using autoFocus as it can cause accessibility issues",
-            severity: A11ySeverity::Warning,
-        },
-        // Empty anchor href
-        A11yPattern {
-            name: "anchor-has-content",
-            pattern: Regex::new(r#"<a[^>]*href=["']#["'][^>]*>\s*</a>"#).unwrap(),
-            message: "Anchor with href='#' should have meaningful content",
-            severity: A11ySeverity::Warning,
-        },
-        // tabIndex with positive value (bad practice)
-        A11yPattern {
-            name: "no-positive-tabindex",
-            pattern: Regex::new(r#"tabIndex=\{?[1-9]"#).unwrap(),
-            message: "Avoid positive tabIndex values as they disrupt natural tab order",
-            severity: A11ySeverity::Warning,
-        },
-        // role="presentation" or role="none" on interactive elements
-        A11yPattern {
-            name: "no-interactive-element-to-noninteractive-role",
-            pattern: Regex::new(r#"<(button|a|input)[^>]*role=["'](presentation|none)["']"#).unwrap(),
-            message: "Interactive elements should not have presentation/none role",
-            severity: A11ySeverity::Error,
-        },
-    ]
-});
+/// Rule IDs with a short description
+const RULES: &[(&str, &str)] = &[
+    ("alt-text", "images need alternative text"),
+    ("anchor-has-content", "links need content or an accessible name"),
+    ("heading-has-content", "headings need content"),
+    ("heading-order", "heading levels only increase one at a time"),
+    ("control-has-label", "form controls need a label"),
+    ("label-has-associated-control", "labels point at or wrap a control"),
+    (
+        "click-events-have-key-events",
+        "click handlers need a keyboard equivalent",
+    ),
+    ("interactive-supports-focus", "clickable elements must be focusable"),
+    ("aria-props", "ARIA attributes must exist"),
+    ("aria-proptypes", "ARIA attributes need valid values"),
+    ("aria-role", "roles must be valid ARIA roles"),
+    (
+        "no-interactive-element-to-noninteractive-role",
+        "interactive elements keep their role",
+    ),
+    ("no-autofocus", "autoFocus disorients screen reader users"),
+    ("no-positive-tabindex", "positive tabIndex breaks the tab order"),
+    ("color-contrast", "inline text colors meet WCAG AA contrast"),
+];
 
-/// Check a file for a11y issues
-pub fn check_file(path: &Path) -> anyhow::Result<Vec<A11yIssue>> {
-    let content = std::fs::read_to_string(path)?;
-    let file_str = path.to_string_lossy().to_string();
+/// Valid `aria-*` attributes (WAI-ARIA 1.2)
+const ARIA_ATTRIBUTES: &[&str] = &[
+    "aria-activedescendant",
+    "aria-atomic",
+    "aria-autocomplete",
+    "aria-braillelabel",
+    "aria-brailleroledescription",
+    "aria-busy",
+    "aria-checked",
+    "aria-colcount",
+    "aria-colindex",
+    "aria-colindextext",
+    "aria-colspan",
+    "aria-controls",
+    "aria-current",
+    "aria-describedby",
+    "aria-description",
+    "aria-details",
+    "aria-disabled",
+    "aria-dropeffect",
+    "aria-errormessage",
+    "aria-expanded",
+    "aria-flowto",
+    "aria-grabbed",
+    "aria-haspopup",
+    "aria-hidden",
+    "aria-invalid",
+    "aria-keyshortcuts",
+    "aria-label",
+    "aria-labelledby",
+    "aria-level",
+    "aria-live",
+    "aria-modal",
+    "aria-multiline",
+    "aria-multiselectable",
+    "aria-orientation",
+    "aria-owns",
+    "aria-placeholder",
+    "aria-posinset",
+    "aria-pressed",
+    "aria-readonly",
+    "aria-relevant",
+    "aria-required",
+    "aria-roledescription",
+    "aria-rowcount",
+    "aria-rowindex",
+    "aria-rowindextext",
+    "aria-rowspan",
+    "aria-selected",
+    "aria-setsize",
+    "aria-sort",
+    "aria-valuemax",
+    "aria-valuemin",
+    "aria-valuenow",
+    "aria-valuetext",
+];
 
-    // Only check JSX/TSX files
-    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
-    if !["jsx", "tsx"].contains(&ext) {
-        return Ok(Vec::new());
-    }
+/// `aria-*` attributes that only take `true` or `false`
+const ARIA_BOOLEANS: &[&str] = &[
+    "aria-atomic",
+    "aria-busy",
+    "aria-disabled",
+    "aria-hidden",
+    "aria-modal",
+    "aria-multiline",
+    "aria-multiselectable",
+    "aria-readonly",
+    "aria-required",
+];
+
+/// Valid non-abstract ARIA roles
+const ROLES: &[&str] = &[
+    "alert",
+    "alertdialog",
+    "application",
+    "article",
+    "banner",
+    "blockquote",
+    "button",
+    "caption",
+    "cell",
+    "checkbox",
+    "code",
+    "columnheader",
+    "combobox",
+    "complementary",
+    "contentinfo",
+    "definition",
+    "deletion",
+    "dialog",
+    "directory",
+    "document",
+    "emphasis",
+    "feed",
+    "figure",
+    "form",
+    "generic",
+    "grid",
+    "gridcell",
+    "group",
+    "heading",
+    "img",
+    "insertion",
+    "link",
+    "list",
+    "listbox",
+    "listitem",
+    "log",
+    "main",
+    "marquee",
+    "math",
+    "menu",
+    "menubar",
+    "menuitem",
+    "menuitemcheckbox",
+    "menuitemradio",
+    "meter",
+    "navigation",
+    "none",
+    "note",
+    "option",
+    "paragraph",
+    "presentation",
+    "progressbar",
+    "radio",
+    "radiogroup",
+    "region",
+    "row",
+    "rowgroup",
+    "rowheader",
+    "scrollbar",
+    "search",
+    "searchbox",
+    "separator",
+    "slider",
+    "spinbutton",
+    "status",
+    "strong",
+    "subscript",
+    "superscript",
+    "switch",
+    "tab",
+    "table",
+    "tablist",
+    "tabpanel",
+    "term",
+    "textbox",
+    "time",
+    "timer",
+    "toolbar",
+    "tooltip",
+    "tree",
+    "treegrid",
+    "treeitem",
+];
+
+/// HTML elements that are focusable and operable by keyboard
+const INTERACTIVE_ELEMENTS: &[&str] = &["a", "button", "input", "select", "textarea", "option", "summary"];
+
+/// Form controls that need a label
+const FORM_CONTROLS: &[&str] = &["input", "select", "textarea"];
+
+/// Input types that are labelled by their value or are not shown
+const UNLABELLED_INPUTS: &[&str] = &["hidden", "submit", "reset", "button", "image"];
+
+/// Attributes giving an element an accessible name
+const NAME_ATTRIBUTES: &[&str] = &["aria-label", "aria-labelledby", "title"];
+
+const KEY_HANDLERS: &[&str] = &["onKeyDown", "onKeyUp", "onKeyPress"];
 
+/// `key: value` pairs of an inline style object
+static STYLE_PROPERTY: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(\w+)\s*:\s*(?:'([^']*)'|"([^"]*)"|(\d+(?:\.\d+)?))"#).unwrap());
+
+/// Look up a rule's ID
+fn rule(id: &str) -> &'static str {
+    RULES
+        .iter()
+        .find(|(rule, _)| *rule == id)
+        .map(|(rule, _)| *rule)
+        .expect("known rule")
+}
+
+/// All rule IDs with their descriptions
+pub fn rules() -> &'static [(&'static str, &'static str)] {
+    RULES
+}
+
+/// Check JSX/TSX source for a11y issues
+pub fn check_content(content: &str, file: &str) -> Vec<A11yIssue> {
+    let elements = jsx::parse(content);
     let mut issues = Vec::new();
+    let mut report = |line: usize, id: &str, severity: A11ySeverity, message: String| {
+        issues.push(A11yIssue {
+            file: file.to_string(),
+            line,
+            rule: rule(id).to_string(),
+            message,
+            severity,
+        });
+    };
+
+    // Controls referenced by <label htmlFor>
+    let mut labelled_ids = HashSet::new();
+    jsx::walk(&elements, &mut |element, _| {
+        if element.name == "label" {
+            if let Some(id) = element.literal("htmlFor") {
+                labelled_ids.insert(id.to_string());
+            }
+        }
+    });
+
+    let mut previous_heading: Option<usize> = None;
+    jsx::walk(&elements, &mut |element, ancestors| {
+        let native = element.is_native();
+        let name = element.name.as_str();
+
+        for attr in &element.attributes {
+            if attr.name == "autoFocus" && element.literal("autoFocus") != Some("false") {
+                report(
+                    attr.line,
+                    "no-autofocus",
+                    A11ySeverity::Warning,
+                    "Avoid using autoFocus as it can cause accessibility issues".to_string(),
+                );
+            }
+            if attr.name == "tabIndex"
+                && element
+                    .literal("tabIndex")
+                    .and_then(|v| v.parse::<i64>().ok())
+                    .is_some_and(|v| v > 0)
+            {
+                report(
+                    attr.line,
+                    "no-positive-tabindex",
+                    A11ySeverity::Warning,
+                    "Avoid positive tabIndex values as they disrupt natural tab order".to_string(),
+                );
+            }
+            if let Some(aria) = attr.name.strip_prefix("aria-") {
+                if !ARIA_ATTRIBUTES.contains(&attr.name.as_str()) {
+                    report(
+                        attr.line,
+                        "aria-props",
+                        A11ySeverity::Error,
+                        format!("'aria-{}' is not a valid ARIA attribute", aria),
+                    );
+                } else if ARIA_BOOLEANS.contains(&attr.name.as_str()) {
+                    if let Value::Str(value) = &attr.value {
+                        if value != "true" && value != "false" {
+                            report(
+                                attr.line,
+                                "aria-proptypes",
+                                A11ySeverity::Error,
+                                format!("{} must be \"true\" or \"false\", not \"{}\"", attr.name, value),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        if !native {
+            if name == "Image" && !element.spread && element.attr("alt").is_none() {
+                report(
+                    element.line,
+                    "alt-text",
+                    A11ySeverity::Error,
+                    "<Image> needs an alt prop (alt=\"\" for decorative images)".to_string(),
+                );
+            }
+            return;
+        }
+
+        let role = element.literal("role");
+        if let Some(role) = role {
+            if let Some(invalid) = role.split_whitespace().find(|r| !ROLES.contains(r)) {
+                report(
+                    element.line,
+                    "aria-role",
+                    A11ySeverity::Error,
+                    format!("'{}' is not a valid ARIA role", invalid),
+                );
+            }
+            if INTERACTIVE_ELEMENTS[..5].contains(&name) && matches!(role, "presentation" | "none") {
+                report(
+                    element.line,
+                    "no-interactive-element-to-noninteractive-role",
+                    A11ySeverity::Error,
+                    "Interactive elements should not have presentation/none role".to_string(),
+                );
+            }
+        }
+
+        let input_type = element.literal("type").unwrap_or("text");
+        let needs_alt = matches!(name, "img" | "area") || (name == "input" && input_type == "image");
+        if needs_alt && !element.spread && element.attr("alt").is_none() {
+            report(
+                element.line,
+                "alt-text",
+                A11ySeverity::Error,
+                format!("<{}> needs an alt attribute (alt=\"\" for decorative images)", name),
+            );
+        }
+
+        if name == "a" && !element.spread && !element.has_content() && !element.has_any(NAME_ATTRIBUTES) {
+            report(
+                element.line,
+                "anchor-has-content",
+                A11ySeverity::Warning,
+                "Anchor should have meaningful content or an aria-label".to_string(),
+            );
+        }
+
+        if let Some(level) = heading_level(name) {
+            if !element.spread && !element.has_content() && !element.has_any(NAME_ATTRIBUTES) {
+                report(
+                    element.line,
+                    "heading-has-content",
+                    A11ySeverity::Error,
+                    format!("<{}> has no content for screen readers", name),
+                );
+            }
+            if let Some(previous) = previous_heading {
+                if level > previous + 1 {
+                    report(
+                        element.line,
+                        "heading-order",
+                        A11ySeverity::Warning,
+                        format!("Heading level jumps from h{} to h{}", previous, level),
+                    );
+                }
+            }
+            previous_heading = Some(level);
+        }
+
+        if FORM_CONTROLS.contains(&name) && !(name == "input" && UNLABELLED_INPUTS.contains(&input_type)) {
+            let labelled = element.spread
+                || element.has_any(NAME_ATTRIBUTES)
+                || ancestors.iter().any(|a| a.name == "label")
+                || match element.attr("id").map(|id| &id.value) {
+                    Some(Value::Str(id)) => labelled_ids.contains(id),
+                    Some(Value::Expression(_)) => true,
+                    _ => false,
+                };
+            if !labelled {
+                report(
+                    element.line,
+                    "control-has-label",
+                    A11ySeverity::Error,
+                    format!(
+                        "<{}> has no label: wrap it in <label>, point a <label htmlFor> at its id, or add aria-label",
+                        name
+                    ),
+                );
+            }
+        }
+
+        if name == "label" && !element.spread && element.attr("htmlFor").is_none() && !wraps_control(element) {
+            report(
+                element.line,
+                "label-has-associated-control",
+                A11ySeverity::Warning,
+                "<label> has no htmlFor and does not wrap a form control".to_string(),
+            );
+        }
 
-    for (line_num, line) in content.lines().enumerate() {
-        for pattern in PATTERNS.iter() {
-            if pattern.pattern.is_match(line) {
-                issues.push(A11yIssue {
-                    file: file_str.clone(),
-                    line: line_num + 1,
-                    rule: pattern.name.to_string(),
-                    message: pattern.message.to_string(),
-                    severity: pattern.severity,
-                });
+        let interactive = INTERACTIVE_ELEMENTS.contains(&name) && (name != "a" || element.attr("href").is_some());
+        let hidden = element.literal("aria-hidden") == Some("true");
+        if let Some(click) = element
+            .attr("onClick")
+            .filter(|_| !interactive && !hidden && !element.spread)
+        {
+            if !element.has_any(KEY_HANDLERS) {
+                report(
+                    click.line,
+                    "click-events-have-key-events",
+                    A11ySeverity::Error,
+                    format!("<{}> with onClick needs onKeyDown or onKeyUp for keyboard users", name),
+                );
+            }
+            if element.attr("tabIndex").is_none() {
+                report(
+                    click.line,
+                    "interactive-supports-focus",
+                    A11ySeverity::Warning,
+                    format!(
+                        "<{}> with onClick is not focusable: add tabIndex={{0}} and a role, or use <button>",
+                        name
+                    ),
+                );
             }
         }
+
+        if let Some(style) = element.attr("style") {
+            if let Value::Expression(expr) = &style.value {
+                if let Some((ratio, required)) = style_contrast(&expr.text) {
+                    if ratio < required {
+                        report(
+                            style.line,
+                            "color-contrast",
+                            A11ySeverity::Warning,
+                            format!(
+                                "Text contrast {:.2}:1 is below the WCAG AA minimum of {}:1",
+                                ratio, required
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+    });
+
+    issues.sort_by_key(|issue| issue.line);
+    issues
+}
+
+fn heading_level(name: &str) -> Option<usize> {
+    name.strip_prefix('h')
+        .and_then(|level| level.parse().ok())
+        .filter(|level| (1..=6).contains(level))
+}
+
+/// Whether a label contains a form control or a component that may render one
+fn wraps_control(label: &Element) -> bool {
+    let mut found = false;
+    jsx::walk(std::slice::from_ref(label), &mut |element, _| {
+        found |= FORM_CONTROLS.contains(&element.name.as_str()) || !element.is_native();
+    });
+    found
+}
+
+/// Contrast ratio of an inline style's text and background colors, with the
+/// ratio WCAG AA requires for its text size
+fn style_contrast(style: &str) -> Option<(f64, f64)> {
+    let mut color = None;
+    let mut background = None;
+    let mut font_size = None;
+    let mut bold = false;
+    for captures in STYLE_PROPERTY.captures_iter(style) {
+        let value = captures.get(2).or(captures.get(3)).or(captures.get(4))?.as_str();
+        match &captures[1] {
+            "color" => color = parse_color(value),
+            "background" | "backgroundColor" => background = parse_color(value),
+            "fontSize" => font_size = parse_pixels(value),
+            "fontWeight" => bold = value == "bold" || value.parse::<u32>().is_ok_and(|w| w >= 700),
+            _ => {}
+        }
+    }
+
+    let (color, background) = (luminance(color?), luminance(background?));
+    let ratio = (color.max(background) + 0.05) / (color.min(background) + 0.05);
+    let large = font_size.is_some_and(|px| px >= 24.0 || (bold && px >= 18.66));
+    Some((ratio, if large { 3.0 } else { 4.5 }))
+}
+
+fn parse_pixels(value: &str) -> Option<f64> {
+    if let Some(rem) = value.strip_suffix("rem") {
+        return rem.trim().parse::<f64>().ok().map(|r| r * 16.0);
+    }
+    value.trim_end_matches("px").trim().parse().ok()
+}
+
+/// RGB of a hex, `rgb()` or common named color
+fn parse_color(value: &str) -> Option<[u8; 3]> {
+    let value = value.trim().to_ascii_lowercase();
+    if let Some(hex) = value.strip_prefix('#') {
+        let digits: Vec<u8> = match hex.len() {
+            3 | 4 => hex
+                .chars()
+                .take(3)
+                .map(|c| c.to_digit(16).map(|d| (d * 17) as u8))
+                .collect::<Option<_>>()?,
+            6 | 8 => (0..3)
+                .map(|i| u8::from_str_radix(hex.get(i * 2..i * 2 + 2)?, 16).ok())
+                .collect::<Option<_>>()?,
+            _ => return None,
+        };
+        return Some([digits[0], digits[1], digits[2]]);
+    }
+    if let Some(args) = value.strip_prefix("rgb(").or(value.strip_prefix("rgba(")) {
+        let channels: Vec<u8> = args
+            .trim_end_matches(')')
+            .split([',', ' ', '/'])
+            .filter(|c| !c.is_empty())
+            .take(3)
+            .map(|c| c.parse().ok())
+            .collect::<Option<_>>()?;
+        return (channels.len() == 3).then(|| [channels[0], channels[1], channels[2]]);
+    }
+    let rgb = match value.as_str() {
+        "black" => [0, 0, 0],
+        "white" => [255, 255, 255],
+        "gray" | "grey" => [128, 128, 128],
+        "silver" => [192, 192, 192],
+        "red" => [255, 0, 0],
+        "green" => [0, 128, 0],
+        "blue" => [0, 0, 255],
+        "yellow" => [255, 255, 0],
+        "orange" => [255, 165, 0],
+        "navy" => [0, 0, 128],
+        _ => return None,
+    };
+    Some(rgb)
+}
+
+/// WCAG relative luminance
+fn luminance([r, g, b]: [u8; 3]) -> f64 {
+    let channel = |c: u8| {
+        let c = c as f64 / 255.0;
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * channel(r) + 0.7152 * channel(g) + 0.0722 * channel(b)
+}
+
+/// Check a file for a11y issues
+pub fn check_file(path: &Path) -> anyhow::Result<Vec<A11yIssue>> {
+    // Only check JSX/TSX files
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    if !["jsx", "tsx"].contains(&ext) {
+        return Ok(Vec::new());
     }
 
-    Ok(issues)
+    let content = std::fs::read_to_string(path)?;
+    Ok(check_content(&content, &path.to_string_lossy()))
 }
 
 /// Check multiple files
@@ -116,6 +606,22 @@ pub fn check_files(paths: &[std::path::PathBuf]) -> anyhow::Result<Vec<A11yIssue
 
 /// Print a11y check results
 pub fn print_results(issues: &[A11yIssue]) -> i32 {
+    if output::is_machine() {
+        for issue in issues {
+            let level = match issue.severity {
+                A11ySeverity::Error => Level::Error,
+                A11ySeverity::Warning => Level::Warning,
+            };
+            output::finding(
+                Finding::new(level, &issue.message)
+                    .with_rule(&issue.rule)
+                    .with_location(&issue.file, Some(issue.line)),
+            );
+        }
+        let errors = issues.iter().any(|i| i.severity == A11ySeverity::Error);
+        return if errors { exit_codes::FAILURE } else { exit_codes::SUCCESS };
+    }
+
     if issues.is_empty() {
         println!("{} No accessibility issues detected", "OK".green());
         return exit_codes::SUCCESS;
@@ -161,23 +667,130 @@ pub fn print_results(issues: &[A11yIssue]) -> i32 {
 mod tests {
     use super::*;
 
+    fn rules_for(source: &str) -> Vec<(usize, String)> {
+        check_content(source, "Page.tsx")
+            .into_iter()
+            .map(|i| (i.line, i.rule))
+            .collect()
+    }
+
     #[test]
     fn test_a11y_severity() {
         assert_ne!(A11ySeverity::Error, A11ySeverity::Warning);
     }
 
     #[test]
-    fn test_autofocus_pattern() {
-        let pattern = &PATTERNS[0];
-        assert!(pattern.pattern.is_match("<input autoFocus />"));
-        assert!(!pattern.pattern.is_match("<input />"));
+    fn test_autofocus() {
+        assert_eq!(
+            rules_for("const a = <input aria-label=\"Search\" autoFocus />;"),
+            [(1, "no-autofocus".to_string())]
+        );
+        assert!(rules_for("const a = <input aria-label=\"Search\" />;").is_empty());
+    }
+
+    #[test]
+    fn test_positive_tabindex() {
+        assert_eq!(
+            rules_for("<span tabIndex={5}>x</span>"),
+            [(1, "no-positive-tabindex".to_string())]
+        );
+        assert!(rules_for("<span tabIndex={0}>x</span>").is_empty());
+        assert!(rules_for("<span tabIndex={-1}>x</span>").is_empty());
+    }
+
+    #[test]
+    fn test_images_links_and_headings() {
+        let source = r#"
+export default function Page() {
+  return (
+    <main>
+      <h1>Listings</h1>
+      <img
+        src="/hero.png"
+      />
+      <img src="/divider.png" alt="" />
+      <Image src={photo} />
+      <h3>Nearby</h3>
+      <a href="/share"></a>
+      <a href="/map" aria-label="Map" />
+      <h2></h2>
+    </main>
+  );
+}
+"#;
+        assert_eq!(
+            rules_for(source),
+            [
+                (6, "alt-text".to_string()),
+                (10, "alt-text".to_string()),
+                (11, "heading-order".to_string()),
+                (12, "anchor-has-content".to_string()),
+                (14, "heading-has-content".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_form_labels() {
+        let source = r#"
+<form>
+  <label htmlFor="email">Email</label>
+  <input id="email" type="email" />
+  <label>Name <input name="name" /></label>
+  <input name="phone" placeholder="Phone" />
+  <input type="hidden" name="token" />
+  <textarea aria-label="Message" />
+  <label>Orphan</label>
+  <select {...field} />
+</form>
+"#;
+        assert_eq!(
+            rules_for(source),
+            [
+                (6, "control-has-label".to_string()),
+                (9, "label-has-associated-control".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_keyboard_and_aria() {
+        let source = r#"
+<div onClick={toggle}>Open</div>
+<div role="button" tabIndex={0} onClick={toggle} onKeyDown={onKey}>Open</div>
+<button onClick={toggle} aria-labeledby="t" aria-hidden="yes">Open</button>
+<span role="buton">x</span>
+<button role="presentation">x</button>
+"#;
+        assert_eq!(
+            rules_for(source),
+            [
+                (2, "click-events-have-key-events".to_string()),
+                (2, "interactive-supports-focus".to_string()),
+                (4, "aria-props".to_string()),
+                (4, "aria-proptypes".to_string()),
+                (5, "aria-role".to_string()),
+                (6, "no-interactive-element-to-noninteractive-role".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_color_contrast() {
+        let low = r##"<p style={{ color: '#999', backgroundColor: "#fff" }}>Faint</p>"##;
+        assert_eq!(rules_for(low), [(1, "color-contrast".to_string())]);
+        let large = r#"<p style={{ color: '#767676', background: 'white', fontSize: 24 }}>Big</p>"#;
+        assert!(rules_for(large).is_empty());
+        let unknown = r#"<p style={{ color: '#999', backgroundColor: theme.bg }}>?</p>"#;
+        assert!(rules_for(unknown).is_empty());
+
+        let (ratio, required) = style_contrast("{ color: 'black', backgroundColor: 'rgb(255, 255, 255)' }").unwrap();
+        assert!((ratio - 21.0).abs() < 0.01 && required == 4.5);
     }
 
     #[test]
-    fn test_positive_tabindex_pattern() {
-        let pattern = &PATTERNS[2];
-        assert!(pattern.pattern.is_match("tabIndex={5}"));
-        assert!(!pattern.pattern.is_match("tabIndex={0}"));
-        assert!(!pattern.pattern.is_match("tabIndex={-1}"));
+    fn test_rule_ids_are_unique() {
+        let ids: HashSet<_> = rules().iter().map(|(id, _)| id).collect();
+        assert_eq!(ids.len(), rules().len());
     }
 }
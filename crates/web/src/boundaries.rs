@@ -0,0 +1,524 @@
+//! Server/client component boundary checks for the App Router
+//!
+//! Everything a `"use client"` module imports is bundled for the browser, and
+//! everything an app router file imports without crossing a `"use client"`
+//! boundary runs only on the server. Mistakes on either side tend to surface
+//! as runtime errors that don't point at the cause. This walks both import
+//! graphs from `src/` and reports:
+//!
+//! - client code importing server-only modules: Node built-ins, `server-only`,
+//!   `next/headers`, modules that `import 'server-only'` or match
+//!   `[boundaries] server_only`, and modules reading server-only env vars
+//! - server components using browser globals (`window`, `localStorage`, ...)
+//! - server modules calling client hooks (`useState`, `useRouter`, ...) or
+//!   importing `client-only`
+//!
+//! `"use server"` modules are server actions: client code calls them over the
+//! network, so their imports never reach the browser.
+
+use crate::dead_code::{APP_ENTRY_FILES, EXTENSIONS, is_auxiliary, path_aliases, resolve};
+use crate::env_leaks::{env_reads, has_directive, is_client_component, is_public};
+use foodshare_cli::output::{self, Finding, Level};
+use foodshare_core::config::BoundariesConfig;
+use foodshare_core::error::exit_codes;
+use once_cell::sync::Lazy;
+use owo_colors::OwoColorize;
+use regex::Regex;
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+
+/// Node built-ins Next.js has no browser fallback for
+const NODE_ONLY_BUILTINS: &[&str] = &[
+    "async_hooks",
+    "child_process",
+    "cluster",
+    "dgram",
+    "diagnostics_channel",
+    "dns",
+    "fs",
+    "http2",
+    "inspector",
+    "module",
+    "net",
+    "perf_hooks",
+    "readline",
+    "repl",
+    "tls",
+    "v8",
+    "worker_threads",
+];
+
+/// Packages that throw when bundled for the browser
+const SERVER_ONLY_PACKAGES: &[&str] = &["server-only", "next/headers"];
+
+/// Hooks that need client state or the browser router
+const CLIENT_HOOKS: &[&str] = &[
+    "useState",
+    "useReducer",
+    "useEffect",
+    "useLayoutEffect",
+    "useInsertionEffect",
+    "useRef",
+    "useContext",
+    "useImperativeHandle",
+    "useSyncExternalStore",
+    "useTransition",
+    "useDeferredValue",
+    "useOptimistic",
+    "useActionState",
+    "useFormStatus",
+    "useRouter",
+    "usePathname",
+    "useSearchParams",
+    "useParams",
+    "useSelectedLayoutSegment",
+    "useSelectedLayoutSegments",
+];
+
+/// Globals that only exist in the browser
+const BROWSER_GLOBALS: &[&str] = &["window", "document", "localStorage", "sessionStorage", "navigator"];
+
+/// `import x from 'y'`, `import 'y'`; group 1 marks `import type`
+static IMPORT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?m)^[ \t]*import\s+(type\s+)?(?:[^;'"]*?\s*from\s*)?['"]([^'"]+)['"]"#).unwrap());
+
+/// `export { x } from 'y'`, `export * from 'y'`; group 1 marks `export type`
+static EXPORT_FROM: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?m)^[ \t]*export\s+(type\s+)?(?:\*(?:\s+as\s+[\w$]+)?|\{[^}]*\})\s*from\s*['"]([^'"]+)['"]"#)
+        .unwrap()
+});
+
+static DYNAMIC_IMPORT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"\b(?:import|require)\s*\(\s*['"]([^'"]+)['"]\s*\)"#).unwrap());
+
+static HOOK_CALL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(&format!(
+        r"\b(function\s+)?({})\s*(?:<[^>()]*>)?\s*\(",
+        CLIENT_HOOKS.join("|")
+    ))
+    .unwrap()
+});
+
+static BROWSER_GLOBAL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(&format!(r"(^|[^.\w$])({})\.[A-Za-z_]", BROWSER_GLOBALS.join("|"))).unwrap());
+
+/// A module on the wrong side of the server/client boundary
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoundaryIssue {
+    /// File path, relative to the app root
+    pub file: PathBuf,
+    /// Line number
+    pub line: usize,
+    /// Rule ID
+    pub rule: &'static str,
+    /// What is wrong and where the module is used from
+    pub message: String,
+}
+
+struct Source {
+    content: String,
+    /// Runtime imports: specifier, line and the module it resolves to
+    imports: Vec<(String, usize, Option<PathBuf>)>,
+    client: bool,
+    server_action: bool,
+}
+
+/// Imports that exist at runtime (not `import type`) with their lines
+fn runtime_imports(content: &str) -> Vec<(String, usize)> {
+    let line_of = |offset: usize| content[..offset].matches('\n').count() + 1;
+    let mut imports = Vec::new();
+    for regex in [&IMPORT, &EXPORT_FROM] {
+        for captures in regex.captures_iter(content) {
+            if captures.get(1).is_none() {
+                imports.push((captures[2].to_string(), line_of(captures.get(0).unwrap().end())));
+            }
+        }
+    }
+    for captures in DYNAMIC_IMPORT.captures_iter(content) {
+        imports.push((captures[1].to_string(), line_of(captures.get(0).unwrap().start())));
+    }
+    imports.sort_by_key(|(_, line)| *line);
+    imports
+}
+
+/// Whether a package import can't run in the browser
+fn is_server_package(specifier: &str, server_only: &[glob::Pattern]) -> bool {
+    let builtin = specifier.strip_prefix("node:");
+    let name = builtin.unwrap_or(specifier);
+    builtin.is_some()
+        || NODE_ONLY_BUILTINS.contains(&name.split('/').next().unwrap_or(name))
+        || SERVER_ONLY_PACKAGES.contains(&specifier)
+        || server_only.iter().any(|pattern| pattern.matches(specifier))
+}
+
+/// Lines that aren't comments, with their numbers
+fn code_lines(content: &str) -> impl Iterator<Item = (usize, &str)> {
+    content.lines().enumerate().filter_map(|(index, line)| {
+        let trimmed = line.trim_start();
+        let comment = trimmed.starts_with("//") || trimmed.starts_with("/*") || trimmed.starts_with('*');
+        (!comment).then_some((index + 1, line))
+    })
+}
+
+/// Client hooks called in a module
+fn hook_calls(content: &str) -> Vec<(usize, String)> {
+    let mut calls = Vec::new();
+    for (line, code) in code_lines(content) {
+        for captures in HOOK_CALL.captures_iter(code) {
+            if captures.get(1).is_none() {
+                calls.push((line, captures[2].to_string()));
+            }
+        }
+    }
+    calls
+}
+
+/// Browser globals used in a module, unless it checks for them with `typeof`
+fn browser_globals(content: &str) -> Vec<(usize, String)> {
+    if BROWSER_GLOBALS
+        .iter()
+        .any(|global| content.contains(&format!("typeof {global}")))
+    {
+        return Vec::new();
+    }
+    let mut uses = Vec::new();
+    for (line, code) in code_lines(content) {
+        for captures in BROWSER_GLOBAL.captures_iter(code) {
+            uses.push((line, captures[2].to_string()));
+        }
+    }
+    uses
+}
+
+/// Check the boundaries of the app at `root`
+pub fn analyze(root: &Path, config: &BoundariesConfig) -> anyhow::Result<Vec<BoundaryIssue>> {
+    let relative = |path: &Path| path.strip_prefix(root).unwrap_or(path).to_path_buf();
+    let paths: Vec<PathBuf> = foodshare_core::file_scanner::FileScanner::new(root.join("src"))
+        .with_extensions(EXTENSIONS)
+        .exclude(&["**/node_modules/**"])
+        .scan()?
+        .iter()
+        .map(|path| relative(path))
+        .filter(|path| !is_auxiliary(path))
+        .collect();
+    let module_set: HashSet<PathBuf> = paths.iter().cloned().collect();
+    let aliases = path_aliases(root);
+    let server_only: Vec<glob::Pattern> = config
+        .server_only
+        .iter()
+        .filter_map(|pattern| glob::Pattern::new(pattern).ok())
+        .collect();
+
+    let mut sources: BTreeMap<PathBuf, Source> = BTreeMap::new();
+    for path in &paths {
+        let content = std::fs::read_to_string(root.join(path))?;
+        let imports = runtime_imports(&content)
+            .into_iter()
+            .map(|(specifier, line)| {
+                let target = resolve(path, &specifier, &aliases, &module_set);
+                (specifier, line, target)
+            })
+            .collect();
+        sources.insert(
+            path.clone(),
+            Source {
+                client: is_client_component(&content),
+                server_action: has_directive(&content, "use server"),
+                content,
+                imports,
+            },
+        );
+    }
+    let marked_server_only = |path: &Path| {
+        server_only.iter().any(|pattern| pattern.matches_path(path))
+            || sources[path]
+                .imports
+                .iter()
+                .any(|(specifier, _, _)| specifier == "server-only")
+    };
+
+    let mut issues = Vec::new();
+
+    // Client graph: everything reachable from "use client" modules
+    let mut origin: BTreeMap<PathBuf, PathBuf> = BTreeMap::new();
+    let mut queue: VecDeque<PathBuf> = VecDeque::new();
+    for (path, source) in &sources {
+        if source.client {
+            origin.insert(path.clone(), path.clone());
+            queue.push_back(path.clone());
+        }
+    }
+    while let Some(path) = queue.pop_front() {
+        let source = &sources[&path];
+        let from = origin[&path].clone();
+        let context = if from == path {
+            "this is a client component".to_string()
+        } else {
+            format!("this module is bundled for the browser through {}", from.display())
+        };
+
+        for (specifier, line, target) in &source.imports {
+            let Some(target) = target else {
+                if is_server_package(specifier, &server_only) {
+                    issues.push(BoundaryIssue {
+                        file: path.clone(),
+                        line: *line,
+                        rule: "server-import-in-client",
+                        message: format!("Imports '{}', which only runs on the server; {}", specifier, context),
+                    });
+                }
+                continue;
+            };
+            if sources[target].server_action {
+                continue;
+            }
+            if marked_server_only(target) {
+                issues.push(BoundaryIssue {
+                    file: path.clone(),
+                    line: *line,
+                    rule: "server-import-in-client",
+                    message: format!("Imports {}, which is server-only; {}", target.display(), context),
+                });
+                continue;
+            }
+            if !origin.contains_key(target) {
+                origin.insert(target.clone(), from.clone());
+                queue.push_back(target.clone());
+            }
+        }
+
+        for (line, name) in env_reads(&source.content) {
+            if !is_public(&name, &[]) {
+                issues.push(BoundaryIssue {
+                    file: path.clone(),
+                    line,
+                    rule: "server-env-in-client",
+                    message: format!("process.env.{} is undefined in the browser; {}", name, context),
+                });
+            }
+        }
+    }
+
+    // Server graph: app router files and their imports up to "use client"
+    let mut origin: BTreeMap<PathBuf, PathBuf> = BTreeMap::new();
+    let mut queue: VecDeque<PathBuf> = VecDeque::new();
+    for (path, source) in &sources {
+        let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+        if path.starts_with("src/app") && APP_ENTRY_FILES.contains(&stem) && !source.client {
+            origin.insert(path.clone(), path.clone());
+            queue.push_back(path.clone());
+        }
+    }
+    while let Some(path) = queue.pop_front() {
+        let source = &sources[&path];
+        let from = origin[&path].clone();
+        let context = if from == path {
+            String::new()
+        } else {
+            format!(" (rendered on the server from {})", from.display())
+        };
+
+        for (specifier, line, target) in &source.imports {
+            if specifier == "client-only" {
+                issues.push(BoundaryIssue {
+                    file: path.clone(),
+                    line: *line,
+                    rule: "client-import-in-server",
+                    message: format!("Imports 'client-only' in a server module{}", context),
+                });
+            }
+            if let Some(target) = target
+                && !sources[target].client
+                && !origin.contains_key(target)
+            {
+                origin.insert(target.clone(), from.clone());
+                queue.push_back(target.clone());
+            }
+        }
+
+        for (line, hook) in hook_calls(&source.content) {
+            issues.push(BoundaryIssue {
+                file: path.clone(),
+                line,
+                rule: "client-hook-in-server",
+                message: format!(
+                    "{} only works in client components; add \"use client\" or move it into one{}",
+                    hook, context
+                ),
+            });
+        }
+
+        let component = path.extension().is_some_and(|ext| ext == "tsx" || ext == "jsx");
+        if component {
+            for (line, global) in browser_globals(&source.content) {
+                issues.push(BoundaryIssue {
+                    file: path.clone(),
+                    line,
+                    rule: "browser-api-in-server",
+                    message: format!("{} doesn't exist in a server component{}", global, context),
+                });
+            }
+        }
+    }
+
+    issues.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+    Ok(issues)
+}
+
+/// Print the issues
+pub fn print_results(issues: &[BoundaryIssue]) -> i32 {
+    if output::is_machine() {
+        for issue in issues {
+            output::finding(
+                Finding::new(Level::Error, &issue.message)
+                    .with_rule(issue.rule)
+                    .with_location(issue.file.display().to_string(), Some(issue.line)),
+            );
+        }
+        return if issues.is_empty() { exit_codes::SUCCESS } else { exit_codes::FAILURE };
+    }
+
+    if issues.is_empty() {
+        println!("{} Server and client components are separated", "OK".green());
+        return exit_codes::SUCCESS;
+    }
+
+    eprintln!("{} Found {} component boundary issue(s)", "ERROR".red(), issues.len());
+    eprintln!();
+    for issue in issues {
+        eprintln!("  {}:{} [{}]", issue.file.display(), issue.line, issue.rule.cyan());
+        eprintln!("    {}", issue.message);
+        eprintln!();
+    }
+    exit_codes::FAILURE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_runtime_imports() {
+        let imports = runtime_imports(
+            r#"import type { Database } from '@/lib/db';
+import {
+  readFile,
+} from 'node:fs/promises';
+import './globals.css';
+export type { Row } from './types';
+export * from './format';
+const Map = dynamic(() => import('./Map'));
+"#,
+        );
+        assert_eq!(
+            imports,
+            [
+                ("node:fs/promises".to_string(), 4),
+                ("./globals.css".to_string(), 5),
+                ("./format".to_string(), 7),
+                ("./Map".to_string(), 8),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_analyze() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "src/app/page.tsx",
+            "import { Cart } from '@/components/Cart';\nimport { Greeting } from '@/components/Greeting';\n\
+             export default function Page() { return <Cart />; }\n",
+        );
+        write(
+            root,
+            "src/app/listing/page.tsx",
+            "import { useState } from 'react';\nexport default function Listing() {\n  const [open] = useState(false);\n  \
+             return <p>{window.location.href}</p>;\n}\n",
+        );
+        write(
+            root,
+            "src/components/Greeting.tsx",
+            "export function Greeting() {\n  return <p>{localStorage.getItem('name')}</p>;\n}\n",
+        );
+        write(
+            root,
+            "src/components/Cart.tsx",
+            "'use client';\nimport { useState } from 'react';\nimport { total } from '@/lib/pricing';\n\
+             import { checkout } from '@/app/actions';\nexport function Cart() { const [n] = useState(0); }\n",
+        );
+        write(
+            root,
+            "src/lib/pricing.ts",
+            "import { readFileSync } from 'fs';\nimport { stripe } from './stripe';\n\
+             export const total = process.env.NEXT_PUBLIC_CURRENCY + process.env.STRIPE_SECRET_KEY;\n",
+        );
+        write(
+            root,
+            "src/lib/stripe.ts",
+            "import 'server-only';\nexport const stripe = {};\n",
+        );
+        write(
+            root,
+            "src/app/actions.ts",
+            "'use server';\nimport { db } from '@/lib/db';\nexport async function checkout() {}\n",
+        );
+        write(
+            root,
+            "src/lib/db.ts",
+            "import { Pool } from 'pg';\nexport const db = new Pool();\n",
+        );
+
+        let config = BoundariesConfig {
+            server_only: vec!["pg".to_string()],
+        };
+        let issues = analyze(root, &config).unwrap();
+        let found: Vec<(&str, usize, &str)> = issues
+            .iter()
+            .map(|i| (i.file.to_str().unwrap(), i.line, i.rule))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("src/app/listing/page.tsx", 3, "client-hook-in-server"),
+                ("src/app/listing/page.tsx", 4, "browser-api-in-server"),
+                ("src/components/Greeting.tsx", 2, "browser-api-in-server"),
+                ("src/lib/pricing.ts", 1, "server-import-in-client"),
+                ("src/lib/pricing.ts", 2, "server-import-in-client"),
+                ("src/lib/pricing.ts", 3, "server-env-in-client"),
+            ]
+        );
+        assert!(
+            issues[2]
+                .message
+                .contains("rendered on the server from src/app/page.tsx")
+        );
+        assert!(
+            issues[3]
+                .message
+                .contains("bundled for the browser through src/components/Cart.tsx")
+        );
+        assert!(issues[4].message.contains("src/lib/stripe.ts, which is server-only"));
+        assert!(issues[5].message.contains("STRIPE_SECRET_KEY"));
+    }
+
+    #[test]
+    fn test_guarded_browser_globals() {
+        assert!(browser_globals("if (typeof window !== 'undefined') { window.scrollTo(0, 0); }").is_empty());
+        assert_eq!(
+            browser_globals("// window.foo\nconst w = document.body;\nconst x = props.window.y;"),
+            [(2, "document".to_string())]
+        );
+        assert_eq!(
+            hook_calls("export function useRouter() {}\nconst r = useRouter();"),
+            [(2, "useRouter".to_string())]
+        );
+    }
+}
@@ -1,11 +1,27 @@
 //! Bundle size analysis
 //!
-//! Analyzes Next.js build output for bundle sizes.
+//! Analyzes Next.js build output for bundle sizes: every chunk in
+//! `.next/static/chunks`, and the first-load JavaScript of each route as
+//! listed by `build-manifest.json` (pages router) and
+//! `app-build-manifest.json` (app router).
+//!
+//! A route's first load is the set of chunks the browser fetches before the
+//! page is interactive: the shared framework chunks, the route's own chunks
+//! and, for the app router, the chunks of every layout above it. Sizes are
+//! gzipped, as Next.js reports them. Routes are checked against the
+//! `[bundle]` budgets and against a baseline committed to the repo.
 
 use anyhow::Result;
+use foodshare_cli::output::{self, Finding, Level};
+use foodshare_core::config::BundleConfig;
 use owo_colors::OwoColorize;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Default location of the committed baseline
+pub const BASELINE_FILE: &str = ".foodshare/web-bundle.json";
 
 /// Bundle info
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +31,37 @@ pub struct BundleInfo {
     pub gzip_size: Option<u64>,
 }
 
+/// First-load JavaScript of one route
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RouteSize {
+    /// URL pattern (`/listing/[id]`)
+    pub route: String,
+    /// Whether the route is served by the app router
+    pub app_router: bool,
+    /// Gzipped size of all first-load chunks
+    pub first_load: u64,
+    /// Number of first-load chunks
+    pub chunks: usize,
+}
+
+/// Size change of one route against the baseline
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RouteDelta {
+    /// URL pattern
+    pub route: String,
+    /// First load in the baseline (0 if new)
+    pub before: u64,
+    /// Current first load (0 if removed)
+    pub after: u64,
+}
+
+impl RouteDelta {
+    /// Bytes added (negative if the route shrank)
+    pub fn change(&self) -> i64 {
+        self.after as i64 - self.before as i64
+    }
+}
+
 /// Bundle analysis result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BundleAnalysis {
@@ -22,29 +69,37 @@ pub struct BundleAnalysis {
     pub total_gzip: u64,
     pub bundles: Vec<BundleInfo>,
     pub largest_bundle: Option<String>,
+    /// First-load JS per route, largest first
+    #[serde(default)]
+    pub routes: Vec<RouteSize>,
 }
 
 /// Analyze Next.js build output
 pub fn analyze_nextjs_build(build_dir: &Path) -> Result<BundleAnalysis> {
     let mut bundles = Vec::new();
     let mut total_size = 0u64;
-    let total_gzip = 0u64;
+    let mut total_gzip = 0u64;
+    let next_dir = build_dir.join(".next");
+    let mut sizes = ChunkSizes::new(&next_dir);
 
     // Look for .next/static/chunks
-    let chunks_dir = build_dir.join(".next/static/chunks");
+    let chunks_dir = next_dir.join("static/chunks");
     if chunks_dir.exists() {
         for entry in std::fs::read_dir(&chunks_dir)? {
             let entry = entry?;
             let path = entry.path();
 
             if path.extension().map_or(false, |e| e == "js") {
+                let name = path.file_name().unwrap().to_string_lossy().to_string();
                 let size = std::fs::metadata(&path)?.len();
+                let gzip_size = sizes.gzip(&format!("static/chunks/{}", name));
                 total_size += size;
+                total_gzip += gzip_size;
 
                 bundles.push(BundleInfo {
-                    name: path.file_name().unwrap().to_string_lossy().to_string(),
+                    name,
                     size,
-                    gzip_size: None, // Would need to actually gzip to get this
+                    gzip_size: Some(gzip_size),
                 });
             }
         }
@@ -55,14 +110,205 @@ pub fn analyze_nextjs_build(build_dir: &Path) -> Result<BundleAnalysis> {
 
     let largest_bundle = bundles.first().map(|b| b.name.clone());
 
+    let mut routes = Vec::new();
+    for (route, chunks, app_router) in route_chunks(&next_dir)? {
+        routes.push(RouteSize {
+            route,
+            app_router,
+            first_load: chunks.iter().map(|chunk| sizes.gzip(chunk)).sum(),
+            chunks: chunks.len(),
+        });
+    }
+    routes.sort_by(|a, b| b.first_load.cmp(&a.first_load).then_with(|| a.route.cmp(&b.route)));
+
     Ok(BundleAnalysis {
         total_size,
         total_gzip,
         bundles,
         largest_bundle,
+        routes,
     })
 }
 
+/// Gzipped chunk sizes, computed once per file
+struct ChunkSizes {
+    next_dir: PathBuf,
+    cache: HashMap<String, u64>,
+}
+
+impl ChunkSizes {
+    fn new(next_dir: &Path) -> Self {
+        Self {
+            next_dir: next_dir.to_path_buf(),
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Gzipped size of a chunk path relative to `.next` (0 if missing)
+    fn gzip(&mut self, chunk: &str) -> u64 {
+        let path = self.next_dir.join(chunk);
+        *self
+            .cache
+            .entry(chunk.to_string())
+            .or_insert_with(|| std::fs::read(&path).map(|data| gzip_size(&data)).unwrap_or(0))
+    }
+}
+
+/// Size of `data` after gzip at the default level
+pub fn gzip_size(data: &[u8]) -> u64 {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    let _ = encoder.write_all(data);
+    encoder.finish().map(|out| out.len() as u64).unwrap_or(data.len() as u64)
+}
+
+/// First-load chunks of every route, from the build manifests
+fn route_chunks(next_dir: &Path) -> Result<Vec<(String, BTreeSet<String>, bool)>> {
+    let read = |name: &str| -> Result<Option<serde_json::Value>> {
+        let path = next_dir.join(name);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&std::fs::read_to_string(&path)?)?))
+    };
+    let files = |value: &serde_json::Value| -> BTreeSet<String> {
+        value
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|file| file.as_str())
+            .filter(|file| file.ends_with(".js"))
+            .map(str::to_string)
+            .collect()
+    };
+
+    let mut routes = Vec::new();
+    let build_manifest = read("build-manifest.json")?;
+    let root_main = build_manifest.as_ref().map(|m| files(&m["rootMainFiles"])).unwrap_or_default();
+
+    if let Some(manifest) = &build_manifest {
+        let pages = &manifest["pages"];
+        let app = files(&pages["/_app"]);
+        for (page, chunks) in pages.as_object().into_iter().flatten() {
+            if page.starts_with("/_") {
+                continue;
+            }
+            let mut first_load = app.clone();
+            first_load.extend(files(chunks));
+            routes.push((page.clone(), first_load, false));
+        }
+    }
+
+    if let Some(manifest) = read("app-build-manifest.json")? {
+        let entries = &manifest["pages"];
+        for entry in entries.as_object().into_iter().flatten().map(|(entry, _)| entry) {
+            let Some(dir) = entry.strip_suffix("/page") else {
+                continue;
+            };
+            if dir.starts_with("/_") {
+                continue;
+            }
+            let mut first_load = root_main.clone();
+            first_load.extend(files(&entries[entry.as_str()]));
+            // Layouts of every ancestor segment, root first
+            let mut layout_dir = String::new();
+            first_load.extend(files(&entries["/layout"]));
+            for segment in dir.split('/').filter(|s| !s.is_empty()) {
+                layout_dir.push('/');
+                layout_dir.push_str(segment);
+                first_load.extend(files(&entries[format!("{}/layout", layout_dir).as_str()]));
+            }
+            routes.push((app_route(dir), first_load, true));
+        }
+    }
+    Ok(routes)
+}
+
+/// URL pattern of an app router directory: route groups and parallel slots are dropped
+fn app_route(dir: &str) -> String {
+    let segments: Vec<&str> = dir
+        .split('/')
+        .filter(|s| !s.is_empty() && !s.starts_with('@'))
+        .filter(|s| !(s.starts_with('(') && s.ends_with(')')))
+        .collect();
+    format!("/{}", segments.join("/"))
+}
+
+impl BundleAnalysis {
+    /// Routes whose first load changed since `baseline`, largest change first
+    pub fn diff(&self, baseline: &Self) -> Vec<RouteDelta> {
+        let index = |analysis: &Self| -> BTreeMap<String, u64> {
+            analysis.routes.iter().map(|r| (r.route.clone(), r.first_load)).collect()
+        };
+        let before = index(baseline);
+        let after = index(self);
+
+        let mut deltas: Vec<RouteDelta> = before
+            .keys()
+            .chain(after.keys())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|route| RouteDelta {
+                route: route.clone(),
+                before: before.get(route).copied().unwrap_or(0),
+                after: after.get(route).copied().unwrap_or(0),
+            })
+            .filter(|delta| delta.change() != 0)
+            .collect();
+        deltas.sort_by_key(|delta| std::cmp::Reverse(delta.change().abs()));
+        deltas
+    }
+
+    /// Budget violations against `config`, comparing growth with `baseline`
+    ///
+    /// A route's entry in `[bundle.routes]` overrides `budget_kb`.
+    pub fn budget_violations(&self, baseline: Option<&Self>, config: &BundleConfig) -> Vec<String> {
+        let mut violations = Vec::new();
+        for route in &self.routes {
+            let budget_kb = config.routes.get(&route.route).copied().or(config.budget_kb);
+            if let Some(budget_kb) = budget_kb {
+                if route.first_load > budget_kb * 1024 {
+                    violations.push(format!(
+                        "{} first-load JS {} exceeds the {} budget",
+                        route.route,
+                        format_size(route.first_load),
+                        format_size(budget_kb * 1024)
+                    ));
+                }
+            }
+        }
+
+        if let (Some(max_growth_kb), Some(baseline)) = (config.max_growth_kb, baseline) {
+            for delta in self.diff(baseline) {
+                if delta.before > 0 && delta.change() > (max_growth_kb * 1024) as i64 {
+                    violations.push(format!(
+                        "{} first-load JS grew by {} since the baseline ({} -> {}, limit {})",
+                        delta.route,
+                        format_size(delta.change() as u64),
+                        format_size(delta.before),
+                        format_size(delta.after),
+                        format_size(max_growth_kb * 1024)
+                    ));
+                }
+            }
+        }
+        violations
+    }
+
+    /// Load a saved analysis
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// Save the analysis as JSON, e.g. as the new baseline
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)? + "\n")?;
+        Ok(())
+    }
+}
+
 /// Format size for display
 fn format_size(bytes: u64) -> String {
     const KB: u64 = 1024;
@@ -79,6 +325,19 @@ fn format_size(bytes: u64) -> String {
 
 /// Print bundle analysis
 pub fn print_analysis(analysis: &BundleAnalysis, threshold_kb: Option<u64>) {
+    if output::is_machine() {
+        output::data("analysis", analysis);
+        for bundle in &analysis.bundles {
+            if threshold_kb.is_some_and(|t| bundle.size > t * 1024) {
+                output::finding(
+                    Finding::new(Level::Warning, format!("{} is {}", bundle.name, format_size(bundle.size)))
+                        .with_rule("bundle-threshold"),
+                );
+            }
+        }
+        return;
+    }
+
     println!("{}", "Bundle Size Analysis".bold());
     println!();
     println!("Total size: {}", format_size(analysis.total_size).cyan());
@@ -106,6 +365,20 @@ pub fn print_analysis(analysis: &BundleAnalysis, threshold_kb: Option<u64>) {
         }
     }
 
+    if !analysis.routes.is_empty() {
+        println!();
+        println!("{}", "First-load JS per route (gzipped):".bold());
+        for route in &analysis.routes {
+            let router = if route.app_router { "app" } else { "pages" };
+            println!(
+                "  {:>10}  {} {}",
+                format_size(route.first_load).cyan(),
+                route.route,
+                format!("({}, {} chunks)", router, route.chunks).dimmed()
+            );
+        }
+    }
+
     if let Some(threshold) = threshold_kb {
         let over_threshold: Vec<_> = analysis
             .bundles
@@ -125,6 +398,32 @@ pub fn print_analysis(analysis: &BundleAnalysis, threshold_kb: Option<u64>) {
     }
 }
 
+/// Print route changes against the baseline
+pub fn print_diff(analysis: &BundleAnalysis, baseline: &BundleAnalysis) {
+    let deltas = analysis.diff(baseline);
+    if output::is_machine() {
+        output::data("changes", &deltas);
+        return;
+    }
+
+    println!();
+    println!("{}", "Changes since baseline:".bold());
+    if deltas.is_empty() {
+        println!("  No changes");
+    }
+    for delta in &deltas {
+        let bytes = delta.change();
+        let change = format!("{}{}", if bytes < 0 { "-" } else { "+" }, format_size(bytes.unsigned_abs()));
+        let change = if bytes > 0 { change.red().to_string() } else { change.green().to_string() };
+        let note = match (delta.before, delta.after) {
+            (0, _) => " (new)".to_string(),
+            (_, 0) => " (removed)".to_string(),
+            (before, after) => format!(" ({} -> {})", format_size(before), format_size(after)),
+        };
+        println!("  {:>12}  {}{}", change, delta.route, note.dimmed());
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -144,6 +443,90 @@ mod tests {
         assert_eq!(format_size(2 * 1024 * 1024), "2.00 MB");
     }
 
+    fn write(dir: &Path, path: &str, content: &str) {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_route_first_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for chunk in ["webpack", "framework", "main", "main-app", "pages/_app", "pages/about", "app/layout", "app/page", "app/listing/layout", "app/listing/[id]/page"] {
+            write(root, &format!(".next/static/chunks/{}.js", chunk), &format!("/* {} */", chunk.repeat(50)));
+        }
+        write(
+            root,
+            ".next/build-manifest.json",
+            r#"{"rootMainFiles": ["static/chunks/webpack.js", "static/chunks/main-app.js"],
+                "pages": {
+                  "/_app": ["static/chunks/webpack.js", "static/chunks/framework.js", "static/chunks/main.js", "static/chunks/pages/_app.js"],
+                  "/_error": ["static/chunks/pages/_error.js"],
+                  "/about": ["static/chunks/pages/about.js", "static/css/about.css"]
+                }}"#,
+        );
+        write(
+            root,
+            ".next/app-build-manifest.json",
+            r#"{"pages": {
+                  "/layout": ["static/chunks/app/layout.js"],
+                  "/(marketing)/page": ["static/chunks/app/page.js"],
+                  "/listing/layout": ["static/chunks/app/listing/layout.js"],
+                  "/listing/[id]/page": ["static/chunks/app/listing/[id]/page.js"],
+                  "/_not-found/page": ["static/chunks/app/not-found.js"]
+                }}"#,
+        );
+
+        let analysis = analyze_nextjs_build(root).unwrap();
+        let routes: BTreeMap<&str, (usize, bool)> =
+            analysis.routes.iter().map(|r| (r.route.as_str(), (r.chunks, r.app_router))).collect();
+        assert_eq!(routes["/about"], (5, false));
+        assert_eq!(routes["/"], (4, true));
+        assert_eq!(routes["/listing/[id]"], (5, true));
+        assert_eq!(routes.len(), 3);
+        assert!(analysis.routes.iter().all(|r| r.first_load > 0));
+        assert!(analysis.total_gzip > 0);
+    }
+
+    #[test]
+    fn test_route_budgets_and_baseline() {
+        let analysis = |listing: u64| BundleAnalysis {
+            total_size: 0,
+            total_gzip: 0,
+            bundles: Vec::new(),
+            largest_bundle: None,
+            routes: vec![
+                RouteSize { route: "/".into(), app_router: true, first_load: 90 * 1024, chunks: 4 },
+                RouteSize { route: "/listing/[id]".into(), app_router: true, first_load: listing * 1024, chunks: 6 },
+            ],
+        };
+        let baseline = analysis(150);
+        let current = analysis(175);
+
+        let deltas = current.diff(&baseline);
+        assert_eq!(deltas.len(), 1);
+        assert_eq!((deltas[0].route.as_str(), deltas[0].change()), ("/listing/[id]", 25 * 1024));
+
+        let mut config = BundleConfig {
+            budget_kb: Some(100),
+            routes: BTreeMap::from([("/listing/[id]".to_string(), 200)]),
+            max_growth_kb: Some(10),
+        };
+        let violations = current.budget_violations(Some(&baseline), &config);
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].starts_with("/listing/[id] first-load JS grew by 25.00 KB"));
+
+        config.routes.clear();
+        assert_eq!(baseline.budget_violations(Some(&baseline), &config).len(), 1);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(BASELINE_FILE);
+        current.save(&path).unwrap();
+        assert_eq!(BundleAnalysis::load(&path).unwrap().routes, current.routes);
+        assert_eq!(app_route("/(shop)/@modal/cart"), "/cart");
+    }
+
     #[test]
     fn test_bundle_info_struct() {
         let bundle = BundleInfo {
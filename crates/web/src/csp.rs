@@ -0,0 +1,690 @@
+//! Content Security Policy validation
+//!
+//! Finds the policies a Next.js app sends — `headers()` in `next.config.*`,
+//! `headers.set(...)` in middleware and `<meta httpEquiv>` tags — and checks
+//! them for `'unsafe-inline'`/`'unsafe-eval'` scripts, wildcard and insecure
+//! sources, and missing directives. A recommended policy can be generated
+//! from the origins the build output loads assets from.
+
+use crate::jsx::{self, Value};
+use crate::nextjs_security::Severity;
+use foodshare_cli::output::{self, Finding, Level};
+use foodshare_core::error::exit_codes;
+use once_cell::sync::Lazy;
+use owo_colors::OwoColorize;
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Header names, enforced and report-only
+const HEADER: &str = "Content-Security-Policy";
+const REPORT_ONLY_HEADER: &str = "Content-Security-Policy-Report-Only";
+
+/// Files that usually set the CSP header, relative to the app root
+const CONFIG_FILES: &[&str] = &[
+    "next.config.js",
+    "next.config.mjs",
+    "next.config.ts",
+    "middleware.ts",
+    "middleware.js",
+    "src/middleware.ts",
+    "src/middleware.js",
+];
+
+/// Fetch directives that fall back to `default-src`
+const FETCH_DIRECTIVES: &[&str] = &[
+    "script-src",
+    "style-src",
+    "img-src",
+    "connect-src",
+    "font-src",
+    "object-src",
+    "media-src",
+    "frame-src",
+    "worker-src",
+    "manifest-src",
+];
+
+/// Directives that don't fall back to `default-src` and should be set
+const STANDALONE_DIRECTIVES: &[(&str, Severity, &str)] = &[
+    (
+        "base-uri",
+        Severity::Medium,
+        "injected <base> tags can redirect relative script URLs",
+    ),
+    (
+        "frame-ancestors",
+        Severity::Medium,
+        "the app can be framed for clickjacking",
+    ),
+    ("form-action", Severity::Low, "forms can post to any origin"),
+];
+
+/// Origins in build output that are references rather than loaded assets
+const IGNORED_ORIGINS: &[&str] = &[
+    "www.w3.org",
+    "reactjs.org",
+    "react.dev",
+    "nextjs.org",
+    "fb.me",
+    "github.com",
+    "localhost",
+    "127.0.0.1",
+];
+
+/// The header name in source, followed by what may introduce its value
+static HEADER_NAME: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"["'`](Content-Security-Policy(?:-Report-Only)?)["'`]\s*([,:])?"#).unwrap());
+
+/// The `value:` of a `{ key, value }` header object
+static VALUE_KEY: Lazy<Regex> = Lazy::new(|| Regex::new(r"\bvalue\s*:").unwrap());
+
+static TEMPLATE_EXPRESSION: Lazy<Regex> = Lazy::new(|| Regex::new(r"\$\{\s*([^}]*?)\s*\}").unwrap());
+
+/// Absolute URLs in string literals or `url(...)`
+static ASSET_URL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"["'`(]((?:https?|wss?)://[a-zA-Z0-9.-]+(?::\d+)?)([^"'`)\s]*)"#).unwrap());
+
+/// A parsed policy: directive names to their source lists
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Policy {
+    /// Directives in the order they were first declared
+    pub directives: Vec<(String, Vec<String>)>,
+}
+
+impl Policy {
+    /// Parse a policy string; later duplicates of a directive are ignored, as browsers do
+    pub fn parse(policy: &str) -> Self {
+        let mut directives: Vec<(String, Vec<String>)> = Vec::new();
+        for directive in policy.split(';') {
+            let mut tokens = directive.split_whitespace();
+            let Some(name) = tokens.next() else {
+                continue;
+            };
+            let name = name.to_ascii_lowercase();
+            if directives.iter().any(|(existing, _)| *existing == name) {
+                continue;
+            }
+            directives.push((name, tokens.map(str::to_string).collect()));
+        }
+        Self { directives }
+    }
+
+    /// Sources of a directive as declared
+    pub fn get(&self, directive: &str) -> Option<&[String]> {
+        self.directives
+            .iter()
+            .find(|(name, _)| name == directive)
+            .map(|(_, sources)| sources.as_slice())
+    }
+
+    /// Sources in effect for a directive, following the `default-src` fallback
+    pub fn effective(&self, directive: &str) -> Option<&[String]> {
+        self.get(directive).or_else(|| {
+            FETCH_DIRECTIVES
+                .contains(&directive)
+                .then(|| self.get("default-src"))
+                .flatten()
+        })
+    }
+}
+
+impl fmt::Display for Policy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let directives: Vec<String> = self
+            .directives
+            .iter()
+            .map(|(name, sources)| {
+                std::iter::once(name.as_str())
+                    .chain(sources.iter().map(String::as_str))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect();
+        write!(f, "{}", directives.join("; "))
+    }
+}
+
+/// A policy found in the app's source
+#[derive(Debug, Clone)]
+pub struct CspSource {
+    /// File declaring the policy
+    pub file: PathBuf,
+    /// Line of the header name or meta tag
+    pub line: usize,
+    /// Sent as `Content-Security-Policy-Report-Only`, so not enforced
+    pub report_only: bool,
+    /// The policy; template expressions such as a nonce appear as `{nonce}`
+    pub policy: Policy,
+}
+
+/// A weakness in a policy
+#[derive(Debug, Clone)]
+pub struct CspFinding {
+    /// Directive concerned
+    pub directive: String,
+    /// Severity
+    pub severity: Severity,
+    /// What is wrong and why it matters
+    pub message: String,
+}
+
+/// Find the policies declared by the Next.js app at `root`
+///
+/// Meta tags are looked up in `app/` and `pages/`, with or without `src/`.
+pub fn find_policies(root: &Path) -> anyhow::Result<Vec<CspSource>> {
+    let mut policies = Vec::new();
+    for file in CONFIG_FILES {
+        let path = root.join(file);
+        if path.is_file() {
+            policies.extend(header_policies(&std::fs::read_to_string(&path)?, &path));
+        }
+    }
+    for dir in ["app", "pages", "src/app", "src/pages"] {
+        let dir = root.join(dir);
+        if !dir.is_dir() {
+            continue;
+        }
+        for entry in walkdir::WalkDir::new(&dir).into_iter().filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "tsx" || ext == "jsx") {
+                policies.extend(meta_policies(&std::fs::read_to_string(path)?, path));
+            }
+        }
+    }
+    Ok(policies)
+}
+
+/// Policies set as headers in a config or middleware file
+pub fn header_policies(content: &str, file: &Path) -> Vec<CspSource> {
+    let mut policies = Vec::new();
+    for captures in HEADER_NAME.captures_iter(content) {
+        let whole = captures.get(0).unwrap();
+        let rest = &content[whole.end()..];
+        // { key: name, value } / headers.set(name, value) / { 'name': value }
+        let expression = match captures.get(2).map(|m| m.as_str()) {
+            Some(",") => match VALUE_KEY.find(rest) {
+                Some(key) if rest[..key.start()].trim().is_empty() => &rest[key.end()..],
+                _ => rest,
+            },
+            Some(_) => rest,
+            None => match VALUE_KEY.find(rest) {
+                Some(key) if key.start() < 200 => &rest[key.end()..],
+                _ => continue,
+            },
+        };
+        if let Some(policy) = resolve(content, expression.trim_start(), 0) {
+            policies.push(CspSource {
+                file: file.to_path_buf(),
+                line: content[..whole.start()].matches('\n').count() + 1,
+                report_only: &captures[1] == REPORT_ONLY_HEADER,
+                policy: Policy::parse(&policy),
+            });
+        }
+    }
+    policies
+}
+
+/// Policies in `<meta httpEquiv="Content-Security-Policy">` tags
+pub fn meta_policies(content: &str, file: &Path) -> Vec<CspSource> {
+    let elements = jsx::parse(content);
+    let mut policies = Vec::new();
+    jsx::walk(&elements, &mut |element, _| {
+        let equiv = element.literal("httpEquiv").or(element.literal("http-equiv"));
+        let Some(header) =
+            equiv.filter(|h| h.eq_ignore_ascii_case(HEADER) || h.eq_ignore_ascii_case(REPORT_ONLY_HEADER))
+        else {
+            return;
+        };
+        let policy = match element.attr("content").map(|attr| &attr.value) {
+            Some(Value::Str(policy)) => Some(policy.clone()),
+            Some(Value::Expression(expr)) => resolve(content, expr.text.trim(), 0),
+            _ => None,
+        };
+        if let Some(policy) = policy {
+            policies.push(CspSource {
+                file: file.to_path_buf(),
+                line: element.line,
+                report_only: header.eq_ignore_ascii_case(REPORT_ONLY_HEADER),
+                policy: Policy::parse(&policy),
+            });
+        }
+    });
+    policies
+}
+
+/// The string an expression evaluates to: a literal, an array of literals
+/// joined into a policy, or a variable declared in the same file
+fn resolve(content: &str, expression: &str, depth: usize) -> Option<String> {
+    let first = expression.chars().next()?;
+    match first {
+        '"' | '\'' | '`' => {
+            let end = expression[1..].find(first)? + 1;
+            let literal = &expression[1..end];
+            Some(TEMPLATE_EXPRESSION.replace_all(literal, "{$1}").into_owned())
+        }
+        '[' => {
+            let end = expression.find(']')?;
+            let parts: Vec<String> = expression[1..end]
+                .split(',')
+                .filter_map(|part| resolve(content, part.trim(), depth + 1))
+                .collect();
+            Some(parts.join("; "))
+        }
+        _ if depth < 4 => {
+            let name: String = expression
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '$')
+                .collect();
+            if name.is_empty() {
+                return None;
+            }
+            let declaration = Regex::new(&format!(r"\b(?:const|let|var)\s+{}\s*=\s*", regex::escape(&name))).ok()?;
+            let found = declaration.find(content)?;
+            resolve(content, content[found.end()..].trim_start(), depth + 1)
+        }
+        _ => None,
+    }
+}
+
+fn is_nonce_or_hash(source: &str) -> bool {
+    let source = source.trim_matches('\'');
+    ["nonce-", "sha256-", "sha384-", "sha512-"]
+        .iter()
+        .any(|prefix| source.starts_with(prefix))
+}
+
+/// Check a policy for unsafe keywords, wildcard sources and missing directives
+pub fn check_policy(policy: &Policy) -> Vec<CspFinding> {
+    let mut findings = Vec::new();
+    let mut finding = |directive: &str, severity: Severity, message: String| {
+        findings.push(CspFinding {
+            directive: directive.to_string(),
+            severity,
+            message,
+        });
+    };
+
+    if policy.get("default-src").is_none() {
+        finding(
+            "default-src",
+            Severity::High,
+            "No default-src: fetch directives that aren't set allow any origin".to_string(),
+        );
+    }
+
+    for directive in ["default-src", "script-src", "style-src"] {
+        let Some(sources) = policy.get(directive) else {
+            continue;
+        };
+        // Browsers ignore 'unsafe-inline' when a nonce or hash is present
+        let nonce = sources.iter().any(|s| is_nonce_or_hash(s));
+        let scripts = directive != "style-src";
+        if sources.iter().any(|s| s == "'unsafe-inline'") && !nonce {
+            if scripts {
+                finding(
+                    directive,
+                    Severity::High,
+                    "'unsafe-inline' allows injected inline scripts; use a nonce or hashes".to_string(),
+                );
+            } else {
+                finding(
+                    directive,
+                    Severity::Low,
+                    "'unsafe-inline' allows injected styles; use a nonce if possible".to_string(),
+                );
+            }
+        }
+        if scripts && sources.iter().any(|s| s == "'unsafe-eval'") {
+            finding(
+                directive,
+                Severity::High,
+                "'unsafe-eval' allows eval() and new Function(); only `next dev` needs it".to_string(),
+            );
+        }
+    }
+
+    for (directive, sources) in &policy.directives {
+        let scripts = matches!(directive.as_str(), "default-src" | "script-src" | "object-src");
+        for source in sources {
+            let wildcard = match source.as_str() {
+                "*" => Some(if scripts { Severity::High } else { Severity::Medium }),
+                "https:" | "data:" | "blob:" if scripts => Some(Severity::High),
+                "http:" => Some(Severity::Medium),
+                s if s.contains("://*.") || s.starts_with("*.") => {
+                    Some(if scripts { Severity::Medium } else { Severity::Low })
+                }
+                _ => None,
+            };
+            if let Some(severity) = wildcard {
+                finding(
+                    directive,
+                    severity,
+                    format!("'{}' allows content from any matching origin", source),
+                );
+            } else if source.starts_with("http://") && !source.contains("localhost") {
+                finding(
+                    directive,
+                    Severity::Medium,
+                    format!("'{}' is loaded over plain HTTP", source),
+                );
+            }
+        }
+    }
+
+    let object_sources = policy.effective("object-src");
+    if !object_sources.is_some_and(|sources| sources.iter().any(|s| s == "'none'")) {
+        finding(
+            "object-src",
+            Severity::Medium,
+            "object-src should be 'none': plugins can run scripts outside script-src".to_string(),
+        );
+    }
+    for (directive, severity, why) in STANDALONE_DIRECTIVES {
+        if policy.get(directive).is_none() {
+            finding(directive, *severity, format!("No {}: {}", directive, why));
+        }
+    }
+
+    findings
+}
+
+/// Recommend a policy from the origins referenced by the build output
+///
+/// URLs found in `.next/static` and the prerendered HTML are sorted into
+/// directives by file type; anything that isn't a script, style, font or
+/// image is assumed to be fetched at runtime (`connect-src`).
+pub fn recommend(next_dir: &Path) -> anyhow::Result<Policy> {
+    let mut origins: BTreeMap<&str, BTreeSet<String>> = BTreeMap::new();
+    for dir in ["static", "server/app", "server/pages"] {
+        let dir = next_dir.join(dir);
+        if !dir.is_dir() {
+            continue;
+        }
+        for entry in walkdir::WalkDir::new(&dir).into_iter().filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if !path
+                .extension()
+                .is_some_and(|ext| ext == "js" || ext == "css" || ext == "html")
+            {
+                continue;
+            }
+            let content = std::fs::read_to_string(path)?;
+            for (directive, origin) in asset_origins(&content) {
+                origins.entry(directive).or_default().insert(origin);
+            }
+        }
+    }
+
+    let with_origins = |directive: &str, base: &[&str]| {
+        let mut sources: Vec<String> = base.iter().map(|s| s.to_string()).collect();
+        sources.extend(origins.get(directive).into_iter().flatten().cloned());
+        (directive.to_string(), sources)
+    };
+    Ok(Policy {
+        directives: vec![
+            ("default-src".to_string(), vec!["'self'".to_string()]),
+            with_origins("script-src", &["'self'"]),
+            with_origins("style-src", &["'self'"]),
+            with_origins("img-src", &["'self'", "blob:", "data:"]),
+            with_origins("font-src", &["'self'"]),
+            with_origins("connect-src", &["'self'"]),
+            ("object-src".to_string(), vec!["'none'".to_string()]),
+            ("base-uri".to_string(), vec!["'self'".to_string()]),
+            ("form-action".to_string(), vec!["'self'".to_string()]),
+            ("frame-ancestors".to_string(), vec!["'none'".to_string()]),
+            ("upgrade-insecure-requests".to_string(), Vec::new()),
+        ],
+    })
+}
+
+/// Origins of absolute URLs in built content, with the directive that loads them
+fn asset_origins(content: &str) -> Vec<(&'static str, String)> {
+    let mut found = Vec::new();
+    for captures in ASSET_URL.captures_iter(content) {
+        let origin = &captures[1];
+        let host = origin.split("://").nth(1).unwrap_or_default();
+        let host = host.split(':').next().unwrap_or_default();
+        if IGNORED_ORIGINS.contains(&host) || !host.contains('.') {
+            continue;
+        }
+        let path = captures[2]
+            .split(['?', '#'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let extension = path.rsplit_once('.').map(|(_, ext)| ext).unwrap_or_default();
+        let directive = match extension {
+            "js" | "mjs" => "script-src",
+            "css" => "style-src",
+            "woff" | "woff2" | "ttf" | "otf" | "eot" => "font-src",
+            "png" | "jpg" | "jpeg" | "gif" | "svg" | "webp" | "avif" | "ico" => "img-src",
+            _ => "connect-src",
+        };
+        found.push((directive, origin.to_string()));
+    }
+    found
+}
+
+/// Print the findings for each policy
+pub fn print_results(policies: &[CspSource]) -> i32 {
+    if output::is_machine() {
+        return report_results(policies);
+    }
+
+    if policies.is_empty() {
+        eprintln!(
+            "{} No Content-Security-Policy found in next.config, middleware or <meta> tags",
+            "ERROR".red()
+        );
+        eprintln!("  {}", "Generate one with `lefthook-rs csp --generate`".dimmed());
+        return exit_codes::FAILURE;
+    }
+
+    let mut blocking = 0;
+    for source in policies {
+        let findings = check_policy(&source.policy);
+        let location = format!("{}:{}", source.file.display(), source.line);
+        if source.report_only {
+            println!("{} {} is report-only and not enforced", "WARNING".yellow(), location);
+        }
+        if findings.is_empty() {
+            println!("{} {} has no weaknesses", "OK".green(), location);
+            continue;
+        }
+
+        eprintln!("{} {} has {} weakness(es):", "CSP".bold(), location, findings.len());
+        for finding in &findings {
+            let severity_str = match finding.severity {
+                Severity::Critical => "CRITICAL".red().bold().to_string(),
+                Severity::High => "HIGH".red().to_string(),
+                Severity::Medium => "MEDIUM".yellow().to_string(),
+                Severity::Low => "LOW".dimmed().to_string(),
+                Severity::Info => "INFO".blue().to_string(),
+            };
+            eprintln!("  [{}] {} {}", severity_str, finding.directive.cyan(), finding.message);
+        }
+        if !source.report_only {
+            blocking += findings.iter().filter(|f| f.severity >= Severity::High).count();
+        }
+    }
+
+    if blocking > 0 {
+        exit_codes::FAILURE
+    } else {
+        exit_codes::SUCCESS
+    }
+}
+
+/// Report the policy checks as findings
+fn report_results(policies: &[CspSource]) -> i32 {
+    if policies.is_empty() {
+        output::finding(
+            Finding::new(Level::Error, "No Content-Security-Policy found in next.config, middleware or <meta> tags")
+                .with_rule("missing-policy"),
+        );
+        return exit_codes::FAILURE;
+    }
+
+    let mut blocking = false;
+    for source in policies {
+        let file = source.file.display().to_string();
+        if source.report_only {
+            output::finding(
+                Finding::new(Level::Warning, "Policy is report-only and not enforced")
+                    .with_rule("report-only")
+                    .with_location(&file, Some(source.line)),
+            );
+        }
+        for finding in check_policy(&source.policy) {
+            let blocks = !source.report_only && finding.severity >= Severity::High;
+            blocking |= blocks;
+            output::finding(
+                Finding::new(if blocks { Level::Error } else { Level::Warning }, finding.message)
+                    .with_rule(finding.directive)
+                    .with_location(&file, Some(source.line)),
+            );
+        }
+    }
+
+    if blocking {
+        exit_codes::FAILURE
+    } else {
+        exit_codes::SUCCESS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn directives(findings: &[CspFinding], severity: Severity) -> Vec<&str> {
+        findings
+            .iter()
+            .filter(|f| f.severity == severity)
+            .map(|f| f.directive.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        let policy = Policy::parse(
+            "default-src 'self';  script-src 'self' cdn.example.com; script-src *; upgrade-insecure-requests;",
+        );
+        assert_eq!(policy.get("script-src").unwrap(), ["'self'", "cdn.example.com"]);
+        assert_eq!(policy.effective("img-src").unwrap(), ["'self'"]);
+        assert_eq!(policy.effective("frame-ancestors"), None);
+        assert_eq!(
+            policy.to_string(),
+            "default-src 'self'; script-src 'self' cdn.example.com; upgrade-insecure-requests"
+        );
+    }
+
+    #[test]
+    fn test_check_policy() {
+        let weak = Policy::parse("script-src 'self' 'unsafe-inline' 'unsafe-eval' https:; img-src *");
+        let findings = check_policy(&weak);
+        assert_eq!(
+            directives(&findings, Severity::High),
+            ["default-src", "script-src", "script-src", "script-src"]
+        );
+        assert_eq!(
+            directives(&findings, Severity::Medium),
+            ["img-src", "object-src", "base-uri", "frame-ancestors"]
+        );
+
+        let strict = Policy::parse(
+            "default-src 'self'; script-src 'self' 'nonce-{nonce}' 'strict-dynamic' 'unsafe-inline'; \
+             object-src 'none'; base-uri 'self'; frame-ancestors 'none'; form-action 'self'",
+        );
+        assert!(check_policy(&strict).is_empty());
+    }
+
+    #[test]
+    fn test_header_policies() {
+        let config = r#"
+const ContentSecurityPolicy = `
+  default-src 'self';
+  script-src 'self' 'nonce-${nonce}';
+`;
+
+module.exports = {
+  async headers() {
+    return [{
+      source: '/(.*)',
+      headers: [
+        { key: 'Content-Security-Policy', value: ContentSecurityPolicy.replace(/\n/g, '') },
+        { key: "Content-Security-Policy-Report-Only", value: ["default-src 'none'", "img-src 'self'"].join('; ') },
+      ],
+    }];
+  },
+};
+"#;
+        let policies = header_policies(config, Path::new("next.config.js"));
+        assert_eq!(policies.len(), 2);
+        assert_eq!((policies[0].line, policies[0].report_only), (12, false));
+        assert_eq!(
+            policies[0].policy.get("script-src").unwrap(),
+            ["'self'", "'nonce-{nonce}'"]
+        );
+        assert!(policies[1].report_only);
+        assert_eq!(policies[1].policy.to_string(), "default-src 'none'; img-src 'self'");
+
+        let middleware = "response.headers.set('Content-Security-Policy', \"default-src 'self'\");";
+        assert_eq!(header_policies(middleware, Path::new("middleware.ts")).len(), 1);
+    }
+
+    #[test]
+    fn test_meta_policies() {
+        let layout = r#"
+export default function RootLayout({ children }) {
+  return (
+    <html>
+      <head>
+        <meta httpEquiv="Content-Security-Policy" content={csp} />
+      </head>
+      <body>{children}</body>
+    </html>
+  );
+}
+const csp = "default-src 'self'; object-src 'none'";
+"#;
+        let policies = meta_policies(layout, Path::new("app/layout.tsx"));
+        assert_eq!(policies.len(), 1);
+        assert_eq!(policies[0].line, 6);
+        assert_eq!(policies[0].policy.get("object-src").unwrap(), ["'none'"]);
+    }
+
+    #[test]
+    fn test_recommend() {
+        let dir = tempfile::tempdir().unwrap();
+        let chunks = dir.path().join("static/chunks");
+        std::fs::create_dir_all(&chunks).unwrap();
+        std::fs::write(
+            chunks.join("app.js"),
+            r#"fetch("https://api.foodshare.club/rest/v1/listings");
+               const ns = "http://www.w3.org/2000/svg";
+               const ws = new WebSocket("wss://realtime.foodshare.club/socket");
+               img.src = "https://cdn.foodshare.club/images/hero.webp?w=640";"#,
+        )
+        .unwrap();
+        std::fs::write(
+            chunks.join("app.css"),
+            "@font-face { src: url(https://fonts.gstatic.com/s/inter.woff2) }",
+        )
+        .unwrap();
+
+        let policy = recommend(dir.path()).unwrap();
+        assert_eq!(
+            policy.get("connect-src").unwrap(),
+            ["'self'", "https://api.foodshare.club", "wss://realtime.foodshare.club"]
+        );
+        assert_eq!(
+            policy.get("img-src").unwrap(),
+            ["'self'", "blob:", "data:", "https://cdn.foodshare.club"]
+        );
+        assert_eq!(policy.get("font-src").unwrap(), ["'self'", "https://fonts.gstatic.com"]);
+        assert!(check_policy(&policy).is_empty());
+    }
+}
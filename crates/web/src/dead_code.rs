@@ -0,0 +1,716 @@
+//! Dead code detection for the web app
+//!
+//! Builds the import graph of `src/` from the Next.js entry points (app
+//! router special files, `pages/`, middleware) and reports modules nothing
+//! reaches, exports no other module imports, and files in `public/` that no
+//! source references.
+//!
+//! Findings that are used in ways the graph can't see (dynamic paths,
+//! external consumers) go in an allowlist: one glob per line matching paths
+//! relative to the app root, or `path#Export` for a single export. Lines
+//! starting with `#` are comments.
+
+use foodshare_cli::output::{self, Finding, Level};
+use foodshare_core::error::exit_codes;
+use once_cell::sync::Lazy;
+use owo_colors::OwoColorize;
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::path::{Component, Path, PathBuf};
+
+/// Default allowlist location
+pub const ALLOWLIST_FILE: &str = ".foodshare/dead-code-allow";
+
+/// Module extensions, in resolution order
+pub(crate) const EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx", "mjs"];
+
+/// App router files Next.js loads by convention
+pub(crate) const APP_ENTRY_FILES: &[&str] = &[
+    "page",
+    "layout",
+    "template",
+    "loading",
+    "error",
+    "global-error",
+    "not-found",
+    "default",
+    "route",
+    "opengraph-image",
+    "twitter-image",
+    "icon",
+    "apple-icon",
+    "sitemap",
+    "robots",
+    "manifest",
+];
+
+/// Files at the root of `src/` Next.js loads by convention
+const ROOT_ENTRY_FILES: &[&str] = &["middleware", "instrumentation", "instrumentation-client"];
+
+/// Public files browsers and crawlers request by name
+const WELL_KNOWN_ASSETS: &[&str] = &[
+    "favicon.ico",
+    "robots.txt",
+    "sitemap*.xml",
+    "manifest.json",
+    "*.webmanifest",
+    "apple-touch-icon*.png",
+    "browserconfig.xml",
+    "humans.txt",
+    "ads.txt",
+    "sw.js",
+    "workbox-*.js",
+    "google*.html",
+];
+
+/// Text files searched for references to public assets
+const REFERENCE_EXTENSIONS: &[&str] = &[
+    "ts",
+    "tsx",
+    "js",
+    "jsx",
+    "mjs",
+    "css",
+    "scss",
+    "md",
+    "mdx",
+    "json",
+    "html",
+    "xml",
+    "webmanifest",
+];
+
+static IMPORT_FROM: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"\bimport\s+(?:type\s+)?([^;'"]*?)\s*from\s*['"]([^'"]+)['"]"#).unwrap());
+
+static SIDE_EFFECT_IMPORT: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(?m)^\s*import\s*['"]([^'"]+)['"]"#).unwrap());
+
+static DYNAMIC_IMPORT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"\b(?:import|require)\s*\(\s*['"]([^'"]+)['"]\s*\)"#).unwrap());
+
+static REEXPORT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\bexport\s+(?:type\s+)?(\*(?:\s+as\s+[\w$]+)?|\{[^}]*\})\s*from\s*['"]([^'"]+)['"]"#).unwrap()
+});
+
+static EXPORT_DECLARATION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?m)^\s*export\s+(?:declare\s+)?(?:async\s+)?(?:abstract\s+)?(?:function\*?|class|const|let|var|interface|type|enum|namespace)\s+([A-Za-z_$][\w$]*)",
+    )
+    .unwrap()
+});
+
+static EXPORT_DEFAULT: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^\s*export\s+default\b").unwrap());
+
+static EXPORT_LIST: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?m)^\s*export\s+(?:type\s+)?\{([^}]*)\}\s*(from\b)?"#).unwrap());
+
+/// An export no module imports
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnusedExport {
+    /// Module path relative to the app root
+    pub file: PathBuf,
+    /// Line of the export
+    pub line: usize,
+    /// Exported name (`default` for default exports)
+    pub name: String,
+}
+
+/// Dead code analysis result
+#[derive(Debug, Default)]
+pub struct DeadCodeReport {
+    /// Number of modules in `src/`
+    pub modules: usize,
+    /// Modules not reachable from any entry point
+    pub unreachable: Vec<PathBuf>,
+    /// Exports of reachable modules that nothing imports
+    pub unused_exports: Vec<UnusedExport>,
+    /// Files in `public/` no source references
+    pub orphaned_assets: Vec<PathBuf>,
+}
+
+impl DeadCodeReport {
+    /// Whether nothing dead was found
+    pub fn is_empty(&self) -> bool {
+        self.unreachable.is_empty() && self.unused_exports.is_empty() && self.orphaned_assets.is_empty()
+    }
+}
+
+/// Paths and exports to leave out of the report
+#[derive(Debug, Default)]
+pub struct Allowlist {
+    files: Vec<glob::Pattern>,
+    exports: Vec<(glob::Pattern, String)>,
+}
+
+impl Allowlist {
+    /// Parse allowlist lines; lines starting with `#` are comments
+    pub fn parse(content: &str) -> Self {
+        let mut allowlist = Self::default();
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (path, export) = match line.split_once('#') {
+                Some((path, export)) => (path.trim(), Some(export.trim())),
+                None => (line, None),
+            };
+            let Ok(pattern) = glob::Pattern::new(path) else {
+                continue;
+            };
+            match export.filter(|name| !name.is_empty()) {
+                Some(name) => allowlist.exports.push((pattern, name.to_string())),
+                None => allowlist.files.push(pattern),
+            }
+        }
+        allowlist
+    }
+
+    /// Load an allowlist file; a missing file allows nothing
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(Self::parse(&std::fs::read_to_string(path)?))
+    }
+
+    fn allows_file(&self, path: &Path) -> bool {
+        self.files.iter().any(|pattern| pattern.matches_path(path))
+    }
+
+    fn allows_export(&self, path: &Path, name: &str) -> bool {
+        self.allows_file(path)
+            || self
+                .exports
+                .iter()
+                .any(|(pattern, export)| export == name && pattern.matches_path(path))
+    }
+}
+
+/// What a module takes from an import
+#[derive(Debug, Clone, PartialEq)]
+enum Imported {
+    /// Named bindings (`default` for the default export)
+    Names(Vec<String>),
+    /// The whole namespace: `import * as`, `export *`, dynamic imports
+    All,
+}
+
+#[derive(Debug, Default)]
+struct Module {
+    exports: Vec<(String, usize)>,
+    imports: Vec<(String, Imported)>,
+}
+
+/// Imports and exports of a module's source
+fn parse_module(content: &str) -> Module {
+    let line_of = |offset: usize| content[..offset].matches('\n').count() + 1;
+    let mut module = Module::default();
+
+    for captures in IMPORT_FROM.captures_iter(content) {
+        module
+            .imports
+            .push((captures[2].to_string(), import_clause(&captures[1])));
+    }
+    for captures in SIDE_EFFECT_IMPORT.captures_iter(content) {
+        module
+            .imports
+            .push((captures[1].to_string(), Imported::Names(Vec::new())));
+    }
+    for captures in DYNAMIC_IMPORT.captures_iter(content) {
+        module.imports.push((captures[1].to_string(), Imported::All));
+    }
+    for captures in REEXPORT.captures_iter(content) {
+        let clause = &captures[1];
+        if clause.starts_with('*') {
+            module.imports.push((captures[2].to_string(), Imported::All));
+            if let Some((_, alias)) = clause.split_once(" as ") {
+                module
+                    .exports
+                    .push((alias.trim().to_string(), line_of(captures.get(0).unwrap().start())));
+            }
+            continue;
+        }
+        let mut imported = Vec::new();
+        for (local, exported) in specifiers(clause) {
+            imported.push(local);
+            module
+                .exports
+                .push((exported, line_of(captures.get(0).unwrap().start())));
+        }
+        module
+            .imports
+            .push((captures[2].to_string(), Imported::Names(imported)));
+    }
+
+    for captures in EXPORT_DECLARATION.captures_iter(content) {
+        module
+            .exports
+            .push((captures[1].to_string(), line_of(captures.get(1).unwrap().start())));
+    }
+    for found in EXPORT_DEFAULT.find_iter(content) {
+        module.exports.push(("default".to_string(), line_of(found.end())));
+    }
+    for captures in EXPORT_LIST.captures_iter(content) {
+        if captures.get(2).is_some() {
+            continue;
+        }
+        for (_, exported) in specifiers(&captures[1]) {
+            module.exports.push((exported, line_of(captures.get(0).unwrap().end())));
+        }
+    }
+    module
+}
+
+/// `(imported, local)` names of a `{ a, b as c, type d }` list
+fn specifiers(list: &str) -> Vec<(String, String)> {
+    list.trim_matches(|c| c == '{' || c == '}')
+        .split(',')
+        .map(|s| s.trim().trim_start_matches("type ").trim())
+        .filter(|s| !s.is_empty())
+        .map(|s| match s.split_once(" as ") {
+            Some((name, alias)) => (name.trim().to_string(), alias.trim().to_string()),
+            None => (s.to_string(), s.to_string()),
+        })
+        .collect()
+}
+
+/// Names taken by an import clause (`React, { useState }`, `* as utils`)
+fn import_clause(clause: &str) -> Imported {
+    if clause.contains('*') {
+        return Imported::All;
+    }
+    let mut names = Vec::new();
+    let (default, named) = match clause.find('{') {
+        Some(brace) => (&clause[..brace], &clause[brace..]),
+        None => (clause, ""),
+    };
+    if !default.trim().trim_end_matches(',').trim().is_empty() {
+        names.push("default".to_string());
+    }
+    names.extend(specifiers(named).into_iter().map(|(imported, _)| imported));
+    Imported::Names(names)
+}
+
+/// Path aliases from `tsconfig.json`/`jsconfig.json` (`@/*` → `src/*` if none)
+pub(crate) fn path_aliases(root: &Path) -> Vec<(String, PathBuf)> {
+    let mut aliases = tsconfig_paths(root);
+    if aliases.is_empty() {
+        aliases.push(("@/".to_string(), PathBuf::from("src")));
+    }
+    aliases
+}
+
+/// `compilerOptions.paths` of `tsconfig.json`/`jsconfig.json`, longest prefix first
+pub(crate) fn tsconfig_paths(root: &Path) -> Vec<(String, PathBuf)> {
+    static COMMENT: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^\s*//.*$").unwrap());
+    static TRAILING_COMMA: Lazy<Regex> = Lazy::new(|| Regex::new(r",(\s*[}\]])").unwrap());
+
+    let mut aliases = Vec::new();
+    for name in ["tsconfig.json", "jsconfig.json"] {
+        let Ok(content) = std::fs::read_to_string(root.join(name)) else {
+            continue;
+        };
+        let content = TRAILING_COMMA
+            .replace_all(&COMMENT.replace_all(&content, ""), "$1")
+            .into_owned();
+        let Ok(config) = serde_json::from_str::<serde_json::Value>(&content) else {
+            continue;
+        };
+        let options = &config["compilerOptions"];
+        let base = options["baseUrl"].as_str().unwrap_or(".");
+        for (alias, targets) in options["paths"].as_object().into_iter().flatten() {
+            if let Some(target) = targets.get(0).and_then(|t| t.as_str()) {
+                let prefix = alias.trim_end_matches('*').to_string();
+                aliases.push((prefix, normalize(&Path::new(base).join(target.trim_end_matches('*')))));
+            }
+        }
+        break;
+    }
+    // Longest prefix first, so `@/components/` wins over `@/`
+    aliases.sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()));
+    aliases
+}
+
+/// Resolve `.` and `..` without touching the filesystem
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Module a specifier refers to, if it is one of `modules`
+pub(crate) fn resolve(from: &Path, specifier: &str, aliases: &[(String, PathBuf)], modules: &HashSet<PathBuf>) -> Option<PathBuf> {
+    let base = if specifier.starts_with("./") || specifier.starts_with("../") {
+        normalize(&from.parent()?.join(specifier))
+    } else {
+        let (prefix, target) = aliases
+            .iter()
+            .find(|(prefix, _)| specifier.starts_with(prefix.as_str()))?;
+        normalize(&target.join(&specifier[prefix.len()..]))
+    };
+
+    let mut candidates = vec![base.clone()];
+    // TypeScript ESM imports name the compiled `.js` file
+    let stem = base.with_extension("");
+    for ext in EXTENSIONS {
+        candidates.push(PathBuf::from(format!("{}.{}", base.display(), ext)));
+        candidates.push(stem.with_extension(ext));
+        candidates.push(base.join(format!("index.{}", ext)));
+    }
+    candidates.into_iter().find(|candidate| modules.contains(candidate))
+}
+
+fn is_entry(path: &Path) -> bool {
+    let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
+        return false;
+    };
+    let mut components = path.components().map(|c| c.as_os_str().to_string_lossy());
+    let (Some(_src), Some(first)) = (components.next(), components.next()) else {
+        return false;
+    };
+    match first.as_ref() {
+        "pages" => true,
+        "app" => APP_ENTRY_FILES.contains(&stem),
+        _ => path.parent() == Some(Path::new("src")) && ROOT_ENTRY_FILES.contains(&stem),
+    }
+}
+
+/// Tests, stories and declarations: not shipped, so neither entries nor dead code
+pub(crate) fn is_auxiliary(path: &Path) -> bool {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+    name.ends_with(".d.ts")
+        || [".test.", ".spec.", ".stories."]
+            .iter()
+            .any(|marker| name.contains(marker))
+        || path
+            .components()
+            .any(|c| c.as_os_str() == "__tests__" || c.as_os_str() == "__mocks__")
+}
+
+/// Analyze the app at `root`
+pub fn analyze(root: &Path, allowlist: &Allowlist) -> anyhow::Result<DeadCodeReport> {
+    let relative = |path: &Path| path.strip_prefix(root).unwrap_or(path).to_path_buf();
+    let sources: Vec<PathBuf> = foodshare_core::file_scanner::FileScanner::new(root.join("src"))
+        .with_extensions(EXTENSIONS)
+        .exclude(&["**/node_modules/**"])
+        .scan()?
+        .iter()
+        .map(|path| relative(path))
+        .collect();
+    let module_set: HashSet<PathBuf> = sources.iter().cloned().collect();
+    let aliases = path_aliases(root);
+
+    let mut modules: BTreeMap<PathBuf, Module> = BTreeMap::new();
+    let mut edges: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+    let mut used: HashMap<PathBuf, HashSet<String>> = HashMap::new();
+    let mut used_entirely: HashSet<PathBuf> = HashSet::new();
+    for path in &sources {
+        let module = parse_module(&std::fs::read_to_string(root.join(path))?);
+        for (specifier, imported) in &module.imports {
+            let Some(target) = resolve(path, specifier, &aliases, &module_set) else {
+                continue;
+            };
+            if !is_auxiliary(path) {
+                edges.entry(path.clone()).or_default().push(target.clone());
+            }
+            match imported {
+                Imported::All => {
+                    used_entirely.insert(target);
+                }
+                Imported::Names(names) => used.entry(target).or_default().extend(names.iter().cloned()),
+            }
+        }
+        modules.insert(path.clone(), module);
+    }
+
+    let mut reachable: HashSet<PathBuf> = HashSet::new();
+    let mut queue: VecDeque<PathBuf> = sources
+        .iter()
+        .filter(|path| is_entry(path) || allowlist.allows_file(path))
+        .cloned()
+        .collect();
+    while let Some(path) = queue.pop_front() {
+        if reachable.insert(path.clone()) {
+            queue.extend(edges.get(&path).into_iter().flatten().cloned());
+        }
+    }
+
+    let mut report = DeadCodeReport {
+        modules: modules.len(),
+        ..Default::default()
+    };
+    for (path, module) in &modules {
+        if is_auxiliary(path) || allowlist.allows_file(path) {
+            continue;
+        }
+        if !reachable.contains(path) {
+            report.unreachable.push(path.clone());
+            continue;
+        }
+        if is_entry(path) || used_entirely.contains(path) {
+            continue;
+        }
+        let imported = used.get(path);
+        let mut seen = BTreeSet::new();
+        for (name, line) in &module.exports {
+            if !seen.insert(name) || imported.is_some_and(|names| names.contains(name)) {
+                continue;
+            }
+            if !allowlist.allows_export(path, name) {
+                report.unused_exports.push(UnusedExport {
+                    file: path.clone(),
+                    line: *line,
+                    name: name.clone(),
+                });
+            }
+        }
+    }
+    report
+        .unused_exports
+        .sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+    report.orphaned_assets = orphaned_assets(root, allowlist)?;
+    Ok(report)
+}
+
+/// Files in `public/` whose path appears in no source, config or public text file
+fn orphaned_assets(root: &Path, allowlist: &Allowlist) -> anyhow::Result<Vec<PathBuf>> {
+    let public = root.join("public");
+    if !public.is_dir() {
+        return Ok(Vec::new());
+    }
+    let well_known: Vec<glob::Pattern> = WELL_KNOWN_ASSETS
+        .iter()
+        .filter_map(|pattern| glob::Pattern::new(pattern).ok())
+        .collect();
+
+    let mut corpus = String::new();
+    let text_files = foodshare_core::file_scanner::FileScanner::new(root.join("src"))
+        .with_extensions(REFERENCE_EXTENSIONS)
+        .scan()?
+        .into_iter()
+        .chain(
+            foodshare_core::file_scanner::FileScanner::new(&public)
+                .with_extensions(REFERENCE_EXTENSIONS)
+                .scan()?,
+        );
+    for path in text_files {
+        corpus.push_str(&std::fs::read_to_string(&path).unwrap_or_default());
+    }
+    for entry in std::fs::read_dir(root)?.filter_map(|e| e.ok()) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with("next.config") || name.starts_with("tailwind.config") {
+            corpus.push_str(&std::fs::read_to_string(entry.path()).unwrap_or_default());
+        }
+    }
+
+    let mut orphaned = Vec::new();
+    for path in foodshare_core::file_scanner::FileScanner::new(&public).scan()? {
+        let asset = path.strip_prefix(&public).unwrap_or(&path);
+        let url = asset.to_string_lossy().replace('\\', "/");
+        let name = asset.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        let relative = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+        if well_known.iter().any(|pattern| pattern.matches(name))
+            || allowlist.allows_file(&relative)
+            || corpus.contains(&format!("/{}", url))
+            || corpus.contains(&url)
+        {
+            continue;
+        }
+        orphaned.push(relative);
+    }
+    orphaned.sort();
+    Ok(orphaned)
+}
+
+/// Print the report
+pub fn print_results(report: &DeadCodeReport) -> i32 {
+    if output::is_machine() {
+        output::data("modules", &report.modules);
+        for path in &report.unreachable {
+            output::finding(
+                Finding::new(Level::Error, "Module unreachable from any entry point")
+                    .with_rule("unreachable-module")
+                    .with_location(path.display().to_string(), None),
+            );
+        }
+        for export in &report.unused_exports {
+            output::finding(
+                Finding::new(Level::Error, format!("Export {} is never imported", export.name))
+                    .with_rule("unused-export")
+                    .with_location(export.file.display().to_string(), Some(export.line)),
+            );
+        }
+        for path in &report.orphaned_assets {
+            output::finding(
+                Finding::new(Level::Error, "Public file referenced nowhere")
+                    .with_rule("orphaned-asset")
+                    .with_location(path.display().to_string(), None),
+            );
+        }
+        return if report.is_empty() { exit_codes::SUCCESS } else { exit_codes::FAILURE };
+    }
+
+    if report.is_empty() {
+        println!("{} No dead code in {} modules", "OK".green(), report.modules);
+        return exit_codes::SUCCESS;
+    }
+
+    if !report.unreachable.is_empty() {
+        eprintln!(
+            "{} {} module(s) unreachable from any entry point:",
+            "ERROR".red(),
+            report.unreachable.len()
+        );
+        for path in &report.unreachable {
+            eprintln!("  {}", path.display());
+        }
+        eprintln!();
+    }
+    if !report.unused_exports.is_empty() {
+        eprintln!("{} {} unused export(s):", "ERROR".red(), report.unused_exports.len());
+        for export in &report.unused_exports {
+            eprintln!("  {}:{} {}", export.file.display(), export.line, export.name.cyan());
+        }
+        eprintln!();
+    }
+    if !report.orphaned_assets.is_empty() {
+        eprintln!(
+            "{} {} file(s) in public/ referenced nowhere:",
+            "ERROR".red(),
+            report.orphaned_assets.len()
+        );
+        for path in &report.orphaned_assets {
+            eprintln!("  {}", path.display());
+        }
+        eprintln!();
+    }
+    eprintln!(
+        "  {}",
+        format!("Delete them, or list paths (or path#Export) in {}", ALLOWLIST_FILE).dimmed()
+    );
+    exit_codes::FAILURE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_parse_module() {
+        let module = parse_module(
+            r#"
+import React, { useState, type FC as Component } from 'react';
+import * as utils from '@/lib/utils';
+import './globals.css';
+const Map = dynamic(() => import('./Map'));
+export { formatDate as format, parse } from './date';
+export * from './types';
+export const LIMIT = 10;
+export async function load() {}
+export default function Page() {}
+const a = 1, b = 2;
+export { a, b as beta };
+"#,
+        );
+        assert_eq!(
+            module.imports[0],
+            (
+                "react".to_string(),
+                Imported::Names(vec!["default".into(), "useState".into(), "FC".into()])
+            )
+        );
+        assert_eq!(module.imports[1], ("@/lib/utils".to_string(), Imported::All));
+        assert_eq!(
+            module.imports[2],
+            ("./globals.css".to_string(), Imported::Names(Vec::new()))
+        );
+        assert_eq!(module.imports[3], ("./Map".to_string(), Imported::All));
+        assert!(module.imports.contains(&(
+            "./date".to_string(),
+            Imported::Names(vec!["formatDate".into(), "parse".into()])
+        )));
+        assert!(module.imports.contains(&("./types".to_string(), Imported::All)));
+
+        let exports: Vec<&str> = module.exports.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(exports, ["format", "parse", "LIMIT", "load", "default", "a", "beta"]);
+        assert_eq!(module.exports[2].1, 8);
+    }
+
+    #[test]
+    fn test_analyze() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "tsconfig.json",
+            r#"{ "compilerOptions": { "baseUrl": ".", "paths": { "@/*": ["./src/*"], }, } }"#,
+        );
+        write(
+            root,
+            "src/app/page.tsx",
+            "import { Button } from '@/components/Button';\nexport default function Page() { return <Button><img src=\"/images/hero.png\" /></Button>; }\n",
+        );
+        write(
+            root,
+            "src/app/listing/[id]/page.tsx",
+            "import Card from '../../../components/Card';\nexport default Card;\n",
+        );
+        write(
+            root,
+            "src/components/Button.tsx",
+            "export function Button() {}\nexport const buttonVariants = {};\n",
+        );
+        write(
+            root,
+            "src/components/Card/index.tsx",
+            "export default function Card() {}\n",
+        );
+        write(
+            root,
+            "src/components/OldBanner.tsx",
+            "import { Button } from './Button';\nexport function OldBanner() {}\n",
+        );
+        write(
+            root,
+            "src/components/OldBanner.test.tsx",
+            "import { OldBanner } from './OldBanner';\n",
+        );
+        write(root, "src/lib/legacy.ts", "export const x = 1;\n");
+        write(root, "public/images/hero.png", "png");
+        write(root, "public/images/old-banner.png", "png");
+        write(root, "public/favicon.ico", "ico");
+        write(root, "public/fonts/keep.woff2", "font");
+
+        let allowlist = Allowlist::parse("# kept for the email templates\nsrc/lib/*\npublic/fonts/**\n");
+        let report = analyze(root, &allowlist).unwrap();
+        assert_eq!(report.unreachable, [PathBuf::from("src/components/OldBanner.tsx")]);
+        assert_eq!(
+            report.unused_exports,
+            [UnusedExport {
+                file: PathBuf::from("src/components/Button.tsx"),
+                line: 2,
+                name: "buttonVariants".to_string(),
+            }]
+        );
+        assert_eq!(report.orphaned_assets, [PathBuf::from("public/images/old-banner.png")]);
+
+        let allowlist = Allowlist::parse(
+            "src/lib/*\npublic/**\nsrc/components/Button.tsx#buttonVariants\nsrc/components/OldBanner.tsx\n",
+        );
+        assert!(analyze(root, &allowlist).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,365 @@
+//! Client bundle environment variable leakage detection
+//!
+//! Next.js only inlines `NEXT_PUBLIC_*` variables into browser code. Any
+//! other `process.env` read in a client component is either a bug (it is
+//! `undefined` in the browser) or, if someone exposed it through
+//! `next.config.js`, a leaked secret. This module checks both sides:
+//!
+//! - sources: `process.env` reads in files starting with `"use client"`
+//! - build output: `process.env` references and the values of server-only
+//!   variables from the `.env*` files inside `.next/static` chunks
+
+use foodshare_cli::output::{self, Finding, Level};
+use foodshare_core::error::exit_codes;
+use once_cell::sync::Lazy;
+use owo_colors::OwoColorize;
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Prefix of variables Next.js exposes to the browser
+pub const PUBLIC_PREFIX: &str = "NEXT_PUBLIC_";
+
+/// Variables Next.js itself inlines into client code
+const BUILTIN_ALLOWED: &[&str] = &["NODE_ENV", "NEXT_RUNTIME", "NEXT_DEPLOYMENT_ID", "TURBOPACK"];
+
+/// Env files whose values are searched for in the build output
+const ENV_FILES: &[&str] = &[".env", ".env.local", ".env.production", ".env.production.local"];
+
+/// Values shorter than this are too common to search for (`true`, `3000`)
+const MIN_VALUE_LEN: usize = 8;
+
+/// `process.env.NAME` and `process.env["NAME"]`
+static ENV_ACCESS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"process\.env(?:\.([A-Za-z_][A-Za-z0-9_]*)|\[\s*["']([A-Za-z_][A-Za-z0-9_]*)["']\s*\])"#).unwrap()
+});
+
+/// `const { A, B: b } = process.env`
+static ENV_DESTRUCTURE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{([^}]*)\}\s*=\s*process\.env\b").unwrap());
+
+/// Names that look like credentials
+static SECRET_NAME: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)KEY|SECRET|TOKEN|PASSWORD|PASSWD|PRIVATE|SERVICE_ROLE|CREDENTIAL|DATABASE_URL|DSN").unwrap());
+
+/// Where a leak was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LeakKind {
+    /// A client component reads a server-only variable
+    ClientSource,
+    /// A client chunk references a server-only variable
+    ChunkReference,
+    /// A client chunk contains the value of a server-only variable
+    ChunkValue,
+}
+
+/// A server-only variable reachable from the browser
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvLeak {
+    /// Source file or chunk
+    pub file: PathBuf,
+    /// Line number (1-based)
+    pub line: usize,
+    /// Variable name
+    pub name: String,
+    /// Where the leak was found
+    pub kind: LeakKind,
+}
+
+impl EnvLeak {
+    /// Whether the variable looks like a credential
+    pub fn is_secret(&self) -> bool {
+        self.kind == LeakKind::ChunkValue || SECRET_NAME.is_match(&self.name)
+    }
+
+    /// Where the variable leaks
+    pub fn message(&self) -> String {
+        match self.kind {
+            LeakKind::ClientSource => format!("{} is read in a client component", self.name),
+            LeakKind::ChunkReference => format!("{} is referenced in a client chunk", self.name),
+            LeakKind::ChunkValue => format!("the value of {} is in a client chunk", self.name),
+        }
+    }
+}
+
+/// Whether a variable may be read from client code
+pub fn is_public(name: &str, allowed: &[String]) -> bool {
+    name.starts_with(PUBLIC_PREFIX)
+        || name.starts_with("__NEXT")
+        || BUILTIN_ALLOWED.contains(&name)
+        || allowed.iter().any(|a| a == name)
+}
+
+/// Whether a file starts with the `"use client"` directive
+pub fn is_client_component(content: &str) -> bool {
+    has_directive(content, "use client")
+}
+
+/// Whether a file starts with a directive such as `"use server"`
+pub(crate) fn has_directive(content: &str, directive: &str) -> bool {
+    content
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with("//") && !line.starts_with("/*") && !line.starts_with('*'))
+        .is_some_and(|line| {
+            let found = line.trim_end_matches(';');
+            found == format!("'{directive}'") || found == format!("\"{directive}\"")
+        })
+}
+
+/// Variables read through `process.env` on each line
+pub(crate) fn env_reads(content: &str) -> Vec<(usize, String)> {
+    let mut reads = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        for captures in ENV_ACCESS.captures_iter(line) {
+            if let Some(name) = captures.get(1).or_else(|| captures.get(2)) {
+                reads.push((index + 1, name.as_str().to_string()));
+            }
+        }
+        for captures in ENV_DESTRUCTURE.captures_iter(line) {
+            for binding in captures[1].split(',') {
+                let name = binding.split(':').next().unwrap_or_default().split('=').next().unwrap_or_default().trim();
+                if !name.is_empty() && !name.starts_with("...") {
+                    reads.push((index + 1, name.to_string()));
+                }
+            }
+        }
+    }
+    reads
+}
+
+/// Server-only variables read by client components
+pub fn scan_content(content: &str, file: &Path, allowed: &[String]) -> Vec<EnvLeak> {
+    if !is_client_component(content) {
+        return Vec::new();
+    }
+    env_reads(content)
+        .into_iter()
+        .filter(|(_, name)| !is_public(name, allowed))
+        .map(|(line, name)| EnvLeak {
+            file: file.to_path_buf(),
+            line,
+            name,
+            kind: LeakKind::ClientSource,
+        })
+        .collect()
+}
+
+/// Scan source files for client components reading server-only variables
+pub fn scan_sources(paths: &[PathBuf], allowed: &[String]) -> Vec<EnvLeak> {
+    paths
+        .iter()
+        .filter(|path| path.is_file())
+        .filter_map(|path| Some(scan_content(&std::fs::read_to_string(path).ok()?, path, allowed)))
+        .flatten()
+        .collect()
+}
+
+/// Variables defined in the project's `.env*` files (later files win)
+pub fn read_env_files(root: &Path) -> BTreeMap<String, String> {
+    let mut values = BTreeMap::new();
+    for name in ENV_FILES {
+        let Ok(content) = std::fs::read_to_string(root.join(name)) else {
+            continue;
+        };
+        values.extend(parse_env(&content));
+    }
+    values
+}
+
+/// Parse `KEY=value` lines of a dotenv file
+fn parse_env(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+            let value = value.trim();
+            let value = match value.chars().next() {
+                Some(quote @ ('"' | '\'')) => value[1..].split(quote).next().unwrap_or_default(),
+                _ => value.split(" #").next().unwrap_or_default().trim(),
+            };
+            Some((key.trim().to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Scan the client chunks of a Next.js build (`.next/static`)
+///
+/// `env` holds the project's variables; the values of server-only ones are
+/// searched for verbatim, except values a public variable shares (a
+/// `SUPABASE_URL` equal to `NEXT_PUBLIC_SUPABASE_URL` is not a leak).
+pub fn scan_build(next_dir: &Path, env: &BTreeMap<String, String>, allowed: &[String]) -> anyhow::Result<Vec<EnvLeak>> {
+    let static_dir = next_dir.join("static");
+    if !static_dir.is_dir() {
+        anyhow::bail!("{} not found; run `next build` first", static_dir.display());
+    }
+
+    let public_values: BTreeSet<&str> = env
+        .iter()
+        .filter(|(name, _)| is_public(name, allowed))
+        .map(|(_, value)| value.as_str())
+        .collect();
+    let secret_values: Vec<(&str, &str)> = env
+        .iter()
+        .filter(|(name, value)| {
+            !is_public(name, allowed) && value.len() >= MIN_VALUE_LEN && !public_values.contains(value.as_str())
+        })
+        .map(|(name, value)| (name.as_str(), value.as_str()))
+        .collect();
+
+    let mut leaks = Vec::new();
+    for entry in WalkDir::new(&static_dir).into_iter().filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if !(entry.file_type().is_file() && path.extension().is_some_and(|ext| ext == "js")) {
+            continue;
+        }
+        let content = std::fs::read_to_string(path)?;
+
+        let mut seen = BTreeSet::new();
+        for (line, name) in env_reads(&content) {
+            if !is_public(&name, allowed) && seen.insert(name.clone()) {
+                leaks.push(EnvLeak {
+                    file: path.to_path_buf(),
+                    line,
+                    name,
+                    kind: LeakKind::ChunkReference,
+                });
+            }
+        }
+        for (name, value) in &secret_values {
+            if let Some(at) = content.find(value) {
+                leaks.push(EnvLeak {
+                    file: path.to_path_buf(),
+                    line: content[..at].lines().count().max(1),
+                    name: name.to_string(),
+                    kind: LeakKind::ChunkValue,
+                });
+            }
+        }
+    }
+    leaks.sort_by(|a, b| (&a.file, a.line, &a.name).cmp(&(&b.file, b.line, &b.name)));
+    Ok(leaks)
+}
+
+/// Print leaks and return the exit code
+pub fn print_results(leaks: &[EnvLeak]) -> i32 {
+    if output::is_machine() {
+        for leak in leaks {
+            output::finding(
+                Finding::new(Level::Error, leak.message())
+                    .with_rule(if leak.is_secret() { "secret" } else { "server-env" })
+                    .with_location(leak.file.display().to_string(), Some(leak.line)),
+            );
+        }
+        return if leaks.is_empty() { exit_codes::SUCCESS } else { exit_codes::FAILURE };
+    }
+
+    if leaks.is_empty() {
+        println!("{} No server-only environment variables reach the client", "OK".green());
+        return exit_codes::SUCCESS;
+    }
+
+    let secrets = leaks.iter().filter(|l| l.is_secret()).count();
+    eprintln!(
+        "{} Found {} server-only environment variable(s) in client code ({} look like secrets)",
+        "ERROR".red(),
+        leaks.len(),
+        secrets
+    );
+
+    for leak in leaks {
+        let label = if leak.is_secret() {
+            "SECRET".red().bold().to_string()
+        } else {
+            "SERVER".yellow().to_string()
+        };
+        eprintln!("  [{}] {}:{}", label, leak.file.display(), leak.line);
+        eprintln!("    {}", leak.message());
+    }
+    eprintln!();
+    eprintln!(
+        "  Read these on the server (Server Components, route handlers, server actions), or rename to {}* if public",
+        PUBLIC_PREFIX
+    );
+
+    exit_codes::FAILURE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_component_reads() {
+        let content = r#""use client";
+
+import { createClient } from "@supabase/supabase-js";
+
+const url = process.env.NEXT_PUBLIC_SUPABASE_URL!;
+const key = process.env.SUPABASE_SERVICE_KEY!;
+const { STRIPE_SECRET_KEY, NODE_ENV, API_HOST: host } = process.env;
+const region = process.env["VERCEL_REGION"];
+"#;
+        let leaks = scan_content(content, Path::new("app/listing/Map.tsx"), &[]);
+        let names: Vec<(&str, usize)> = leaks.iter().map(|l| (l.name.as_str(), l.line)).collect();
+        assert_eq!(
+            names,
+            [("SUPABASE_SERVICE_KEY", 6), ("STRIPE_SECRET_KEY", 7), ("API_HOST", 7), ("VERCEL_REGION", 8)]
+        );
+        assert!(leaks[0].is_secret());
+        assert!(!leaks[2].is_secret());
+
+        assert_eq!(scan_content(content, Path::new("x.tsx"), &["VERCEL_REGION".to_string()]).len(), 3);
+        let server = content.replacen("\"use client\";", "import 'server-only';", 1);
+        assert!(scan_content(&server, Path::new("lib/supabase.ts"), &[]).is_empty());
+    }
+
+    #[test]
+    fn test_is_client_component() {
+        assert!(is_client_component("// Map view\n'use client'\nexport default function Map() {}"));
+        assert!(!is_client_component("export const x = 'use client';"));
+        assert!(!is_client_component(""));
+    }
+
+    #[test]
+    fn test_parse_env() {
+        let content = "# Supabase\nNEXT_PUBLIC_SUPABASE_URL=https://abc.supabase.co\nexport SUPABASE_SERVICE_KEY=\"eyJhbGciOi.service\"\nPORT=3000 # dev\n";
+        let values: BTreeMap<String, String> = parse_env(content).into_iter().collect();
+        assert_eq!(values["SUPABASE_SERVICE_KEY"], "eyJhbGciOi.service");
+        assert_eq!(values["PORT"], "3000");
+    }
+
+    #[test]
+    fn test_scan_build() {
+        let dir = tempfile::tempdir().unwrap();
+        let chunks = dir.path().join("static/chunks/app");
+        std::fs::create_dir_all(&chunks).unwrap();
+        std::fs::write(
+            chunks.join("page-1a2b.js"),
+            "(self.webpackChunk=[]).push([[1],{1:function(e){let t=\"https://abc.supabase.co\",n=\"eyJhbGciOi.service\";process.env.NODE_ENV;process.env.SUPABASE_SERVICE_KEY}}]);",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("static/chunks/framework.css"), "eyJhbGciOi.service").unwrap();
+
+        let env: BTreeMap<String, String> = [
+            ("NEXT_PUBLIC_SUPABASE_URL", "https://abc.supabase.co"),
+            ("SUPABASE_URL", "https://abc.supabase.co"),
+            ("SUPABASE_SERVICE_KEY", "eyJhbGciOi.service"),
+            ("PORT", "3000"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        let leaks = scan_build(dir.path(), &env, &[]).unwrap();
+        let kinds: Vec<(&str, LeakKind)> = leaks.iter().map(|l| (l.name.as_str(), l.kind)).collect();
+        assert_eq!(
+            kinds,
+            [("SUPABASE_SERVICE_KEY", LeakKind::ChunkReference), ("SUPABASE_SERVICE_KEY", LeakKind::ChunkValue)]
+        );
+        assert!(scan_build(&dir.path().join("missing"), &env, &[]).is_err());
+    }
+}
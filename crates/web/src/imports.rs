@@ -0,0 +1,451 @@
+//! Import organization for TypeScript and JavaScript sources
+//!
+//! The import block at the top of a module is kept in groups separated by a
+//! blank line: external packages (`react`, `@supabase/ssr`, `node:fs`), path
+//! aliases from `tsconfig.json` (`@/lib/db`), then relative imports
+//! (`./Button`), each sorted by specifier. Side-effect imports
+//! (`import './globals.css'`) go first and keep their order, since what they
+//! run depends on it. Comments directly above an import move with it.
+//!
+//! Relative imports that climb more than `[imports] max_parent_dirs`
+//! directories into an aliased tree must use the alias instead.
+
+use crate::dead_code::{normalize, tsconfig_paths};
+use foodshare_cli::output::{self, Finding, Level};
+use foodshare_core::config::ImportsConfig;
+use foodshare_core::error::exit_codes;
+use once_cell::sync::Lazy;
+use owo_colors::OwoColorize;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// Extensions of the files imports are organized in
+pub const EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx", "mjs"];
+
+static IMPORT_START: Lazy<Regex> = Lazy::new(|| Regex::new(r#"^import(\s|\{|\*|'|")"#).unwrap());
+static IMPORT_EQUALS: Lazy<Regex> = Lazy::new(|| Regex::new(r"^import\s+(type\s+)?[\w$]+\s*=").unwrap());
+static SIDE_EFFECT: Lazy<Regex> = Lazy::new(|| Regex::new(r#"^import\s*['"]([^'"\n]+)['"]"#).unwrap());
+static FROM: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\bfrom\s*['"]([^'"\n]+)['"]"#).unwrap());
+static STATEMENT_END: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*((with|assert)\s*\{[^}]*\})?\s*;?\s*(//.*|/\*.*\*/)?\s*$").unwrap());
+static DIRECTIVE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"^['"]use [\w ]+['"];?$"#).unwrap());
+
+/// An import that needs changing
+#[derive(Debug, Clone)]
+pub struct ImportIssue {
+    /// File path
+    pub file: PathBuf,
+    /// Line number
+    pub line: usize,
+    /// What is wrong
+    pub message: String,
+}
+
+/// Group an import is placed in, in output order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Group {
+    SideEffect,
+    External,
+    Alias,
+    Relative,
+}
+
+/// One import statement and the comments above it
+#[derive(Debug, Clone)]
+struct Import {
+    comments: Vec<String>,
+    text: String,
+    specifier: String,
+    side_effect: bool,
+    line: usize,
+}
+
+impl Import {
+    fn group(&self, aliases: &[(String, PathBuf)]) -> Group {
+        let specifier = self.specifier.as_str();
+        if self.side_effect {
+            Group::SideEffect
+        } else if specifier.starts_with("./") || specifier.starts_with("../") || specifier == "." || specifier == ".." {
+            Group::Relative
+        } else if aliases
+            .iter()
+            .any(|(prefix, _)| !prefix.is_empty() && specifier.starts_with(prefix.as_str()))
+        {
+            Group::Alias
+        } else {
+            Group::External
+        }
+    }
+
+    /// Point the statement at another specifier
+    fn respecify(&mut self, specifier: &str) {
+        for quote in ['\'', '"'] {
+            let quoted = format!("{quote}{}{quote}", self.specifier);
+            if let Some(at) = self.text.rfind(&quoted) {
+                self.text
+                    .replace_range(at..at + quoted.len(), &format!("{quote}{specifier}{quote}"));
+                self.specifier = specifier.to_string();
+                return;
+            }
+        }
+    }
+}
+
+/// The leading imports of a module
+struct Block {
+    /// Byte range of the statements, from the first import to the end of the last
+    start: usize,
+    end: usize,
+    imports: Vec<Import>,
+}
+
+/// Find the import block; `None` if there is none or it can't be rewritten safely
+fn import_block(content: &str) -> Option<Block> {
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        lines.push((offset, line));
+        offset += line.len();
+    }
+
+    let mut imports: Vec<Import> = Vec::new();
+    let mut comments = Vec::new();
+    let (mut start, mut end) = (0, 0);
+    let mut i = 0;
+    while i < lines.len() {
+        let trimmed = lines[i].1.trim();
+        if trimmed.is_empty() {
+            i += 1;
+            continue;
+        }
+        if trimmed.starts_with("//") || trimmed.starts_with("/*") {
+            let mut j = i;
+            if trimmed.starts_with("/*") {
+                while !lines[j].1.contains("*/") && j + 1 < lines.len() {
+                    j += 1;
+                }
+            }
+            // Comments above the first import belong to the file header
+            if !imports.is_empty() {
+                comments.extend(lines[i..=j].iter().map(|(_, l)| l.trim_end().to_string()));
+            }
+            i = j + 1;
+            continue;
+        }
+        if imports.is_empty() && DIRECTIVE.is_match(trimmed) {
+            i += 1;
+            continue;
+        }
+        if !IMPORT_START.is_match(trimmed) || IMPORT_EQUALS.is_match(trimmed) {
+            break;
+        }
+
+        let mut text = String::new();
+        let mut j = i;
+        let (specifier, side_effect, rest) = loop {
+            text.push_str(lines[j].1);
+            if let Some(captures) = SIDE_EFFECT.captures(text.trim_start()) {
+                let rest = text.trim_start()[captures.get(0).unwrap().end()..].to_string();
+                break (captures[1].to_string(), true, rest);
+            }
+            if let Some(captures) = FROM.captures(&text) {
+                break (
+                    captures[1].to_string(),
+                    false,
+                    text[captures.get(0).unwrap().end()..].to_string(),
+                );
+            }
+            j += 1;
+            if j == lines.len() {
+                return None;
+            }
+        };
+        // Code after the statement on the same line
+        if !STATEMENT_END.is_match(&rest) {
+            return None;
+        }
+
+        if imports.is_empty() {
+            start = lines[i].0;
+        }
+        end = lines[j].0 + lines[j].1.len();
+        imports.push(Import {
+            comments: std::mem::take(&mut comments),
+            text: text.lines().map(str::trim_end).collect::<Vec<_>>().join("\n"),
+            specifier,
+            side_effect,
+            line: i + 1,
+        });
+        i = j + 1;
+    }
+
+    if imports.is_empty() {
+        None
+    } else {
+        Some(Block { start, end, imports })
+    }
+}
+
+/// Imports grouped and sorted, groups separated by a blank line
+fn render(imports: &[Import], aliases: &[(String, PathBuf)], newline: &str) -> String {
+    let mut sorted: Vec<(Group, &Import)> = imports.iter().map(|i| (i.group(aliases), i)).collect();
+    sorted.sort_by(|(a_group, a), (b_group, b)| {
+        a_group.cmp(b_group).then_with(|| {
+            if *a_group == Group::SideEffect {
+                std::cmp::Ordering::Equal
+            } else {
+                a.specifier.to_lowercase().cmp(&b.specifier.to_lowercase())
+            }
+        })
+    });
+
+    let mut out = String::new();
+    let mut previous = None;
+    for (group, import) in sorted {
+        if previous.is_some_and(|p| p != group) {
+            out.push_str(newline);
+        }
+        previous = Some(group);
+        for comment in &import.comments {
+            out.push_str(comment);
+            out.push_str(newline);
+        }
+        out.push_str(&import.text.replace('\n', newline));
+        out.push_str(newline);
+    }
+    out
+}
+
+/// Alias specifier for a relative import, if its target is in an aliased tree
+fn alias_for(file: &Path, specifier: &str, aliases: &[(String, PathBuf)]) -> Option<String> {
+    let target = normalize(&file.parent()?.join(specifier));
+    aliases
+        .iter()
+        .filter(|(prefix, _)| prefix.ends_with('/'))
+        .filter_map(|(prefix, dir)| {
+            let rest = target.strip_prefix(dir).ok()?;
+            if rest.as_os_str().is_empty() {
+                return None;
+            }
+            let rest = rest.to_string_lossy().replace('\\', "/");
+            Some((dir.components().count(), format!("{prefix}{rest}")))
+        })
+        .max_by_key(|(depth, _)| *depth)
+        .map(|(_, specifier)| specifier)
+}
+
+/// Organize the imports of one file (`file` relative to the app root)
+///
+/// Returns the new content and what was changed.
+fn organize(
+    content: &str,
+    file: &Path,
+    aliases: &[(String, PathBuf)],
+    config: &ImportsConfig,
+) -> (String, Vec<ImportIssue>) {
+    let Some(mut block) = import_block(content) else {
+        return (content.to_string(), Vec::new());
+    };
+    let newline = if content.contains("\r\n") { "\r\n" } else { "\n" };
+    let original = &content[block.start..block.end];
+    // A file that ends right after its last import
+    let render = |imports: &[Import]| {
+        let rendered = render(imports, aliases, newline);
+        if original.ends_with('\n') {
+            rendered
+        } else {
+            rendered.trim_end().to_string()
+        }
+    };
+    let mut issues = Vec::new();
+
+    if render(&block.imports) != original {
+        issues.push(ImportIssue {
+            file: file.to_path_buf(),
+            line: block.imports[0].line,
+            message: "Imports are not grouped and sorted (external, alias, relative)".to_string(),
+        });
+    }
+
+    for import in &mut block.imports {
+        let parents = import.specifier.split('/').take_while(|s| *s == "..").count();
+        if import.side_effect || parents <= config.max_parent_dirs {
+            continue;
+        }
+        if let Some(alias) = alias_for(file, &import.specifier, aliases) {
+            issues.push(ImportIssue {
+                file: file.to_path_buf(),
+                line: import.line,
+                message: format!("'{}' climbs {} directories; use '{}'", import.specifier, parents, alias),
+            });
+            import.respecify(&alias);
+        }
+    }
+
+    if issues.is_empty() {
+        return (content.to_string(), issues);
+    }
+    let mut organized = String::with_capacity(content.len());
+    organized.push_str(&content[..block.start]);
+    organized.push_str(&render(&block.imports));
+    organized.push_str(&content[block.end..]);
+    (organized, issues)
+}
+
+/// Check the imports of files under an app root, rewriting them if `fix` is set
+///
+/// Files with other extensions, or that no longer exist, are skipped.
+pub fn check_files(
+    root: &Path,
+    paths: &[PathBuf],
+    config: &ImportsConfig,
+    fix: bool,
+) -> anyhow::Result<Vec<ImportIssue>> {
+    let aliases = tsconfig_paths(root);
+    let mut issues = Vec::new();
+
+    for path in paths {
+        let is_source = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| EXTENSIONS.contains(&e));
+        if !is_source || !path.is_file() {
+            continue;
+        }
+        let content = std::fs::read_to_string(path)?;
+        let relative = normalize(path.strip_prefix(root).unwrap_or(path));
+        let (organized, found) = organize(&content, &relative, &aliases, config);
+        if fix && !found.is_empty() {
+            std::fs::write(path, organized)?;
+        }
+        issues.extend(found);
+    }
+
+    Ok(issues)
+}
+
+/// Print the issues; `fixed` if they were already rewritten
+pub fn print_results(issues: &[ImportIssue], fixed: bool) -> i32 {
+    if output::is_machine() {
+        let level = if fixed { Level::Success } else { Level::Error };
+        for issue in issues {
+            output::finding(
+                Finding::new(level, &issue.message).with_location(issue.file.display().to_string(), Some(issue.line)),
+            );
+        }
+        return if fixed || issues.is_empty() { exit_codes::SUCCESS } else { exit_codes::FAILURE };
+    }
+
+    if issues.is_empty() {
+        println!("{} Imports are organized", "OK".green());
+        return exit_codes::SUCCESS;
+    }
+
+    if fixed {
+        let mut files: Vec<&PathBuf> = issues.iter().map(|i| &i.file).collect();
+        files.dedup();
+        println!("{} Organized imports in {} file(s)", "FIXED".green(), files.len());
+        for file in files {
+            println!("  {}", file.display());
+        }
+        return exit_codes::SUCCESS;
+    }
+
+    eprintln!("{} {} import issue(s):", "ERROR".red(), issues.len());
+    for issue in issues {
+        eprintln!("  {}:{} {}", issue.file.display(), issue.line, issue.message);
+    }
+    eprintln!();
+    eprintln!("  {}", "Run `lefthook-rs imports --fix` to rewrite them".dimmed());
+    exit_codes::FAILURE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aliases() -> Vec<(String, PathBuf)> {
+        vec![("@/".to_string(), PathBuf::from("src"))]
+    }
+
+    fn run(content: &str, file: &str) -> (String, Vec<ImportIssue>) {
+        organize(content, Path::new(file), &aliases(), &ImportsConfig::default())
+    }
+
+    #[test]
+    fn test_groups_and_sorts() {
+        let content = r#"'use client';
+
+// Listing card
+import { Button } from './Button';
+import { useState } from 'react';
+import './card.css';
+import {
+  formatDistance,
+  formatRelative,
+} from 'date-fns';
+
+// Shared client
+import { supabase } from '@/lib/supabase';
+import type { Listing } from '@/types';
+
+export function Card() {}
+"#;
+        let (organized, issues) = run(content, "src/components/Card.tsx");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].line, 4);
+        assert_eq!(
+            organized,
+            r#"'use client';
+
+// Listing card
+import './card.css';
+
+import {
+  formatDistance,
+  formatRelative,
+} from 'date-fns';
+import { useState } from 'react';
+
+// Shared client
+import { supabase } from '@/lib/supabase';
+import type { Listing } from '@/types';
+
+import { Button } from './Button';
+
+export function Card() {}
+"#
+        );
+
+        let (again, issues) = run(&organized, "src/components/Card.tsx");
+        assert!(issues.is_empty());
+        assert_eq!(again, organized);
+    }
+
+    #[test]
+    fn test_deep_relative_uses_alias() {
+        let content = "import { db } from '../../../lib/db';\nimport { Map } from '../Map';\n\nconst x = 1;\n";
+        let (organized, issues) = run(content, "src/app/listing/[id]/page.tsx");
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].message.contains("use '@/lib/db'"));
+        assert_eq!(
+            organized,
+            "import { db } from '@/lib/db';\n\nimport { Map } from '../Map';\n\nconst x = 1;\n"
+        );
+
+        // Outside every aliased tree
+        let (_, issues) = run("import config from '../../next.config';\n", "src/lib/env.ts");
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn test_leaves_unsafe_blocks_alone() {
+        let content = "import b from 'b'; b();\nimport a from 'a';\n";
+        let (organized, issues) = run(content, "src/x.ts");
+        assert_eq!(organized, content);
+        assert!(issues.is_empty());
+
+        let content = "const x = 1;\nimport b from 'b';\nimport a from 'a';\n";
+        assert!(run(content, "src/x.ts").1.is_empty());
+    }
+}
@@ -0,0 +1,440 @@
+//! Minimal JSX/TSX parser
+//!
+//! Extracts the element tree from a source file: tag names, attributes and
+//! children, each with its line number. The JavaScript around the markup is
+//! skipped with strings, template literals and comments honoured, so elements
+//! inside `{items.map(...)}` or attribute expressions are found too.
+//! Malformed markup is skipped rather than reported.
+
+/// A JSX element (`name` is empty for fragments)
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Element {
+    pub name: String,
+    pub line: usize,
+    pub attributes: Vec<Attribute>,
+    /// Whether the element spreads props (`{...props}`), hiding attributes
+    pub spread: bool,
+    pub children: Vec<Node>,
+}
+
+/// A JSX attribute
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Attribute {
+    pub name: String,
+    pub value: Value,
+    pub line: usize,
+}
+
+/// Value of an attribute
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    /// Bare attribute (`disabled`)
+    None,
+    /// String literal (`alt="Logo"`)
+    Str(String),
+    /// Expression container (`alt={title}`)
+    Expression(Expression),
+}
+
+/// Child of an element
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Node {
+    Text(String),
+    Expression(Expression),
+    Element(Element),
+}
+
+/// JavaScript inside `{...}`, with the elements it contains
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) struct Expression {
+    pub text: String,
+    pub elements: Vec<Element>,
+}
+
+impl Element {
+    /// Attribute by name
+    pub fn attr(&self, name: &str) -> Option<&Attribute> {
+        self.attributes.iter().find(|attr| attr.name == name)
+    }
+
+    /// Whether any of `names` is set
+    pub fn has_any(&self, names: &[&str]) -> bool {
+        names.iter().any(|name| self.attr(name).is_some())
+    }
+
+    /// String value of an attribute, also for `{"literal"}` and `{3}`
+    pub fn literal(&self, name: &str) -> Option<&str> {
+        match &self.attr(name)?.value {
+            Value::Str(value) => Some(value),
+            Value::Expression(expr) => {
+                let text = expr.text.trim();
+                let unquoted = text
+                    .strip_prefix('"')
+                    .and_then(|t| t.strip_suffix('"'))
+                    .or_else(|| text.strip_prefix('\'').and_then(|t| t.strip_suffix('\'')));
+                match unquoted {
+                    Some(value) => Some(value),
+                    None if text.parse::<f64>().is_ok() || text == "true" || text == "false" => Some(text),
+                    None => None,
+                }
+            }
+            Value::None => None,
+        }
+    }
+
+    /// Whether this is an HTML element rather than a component
+    pub fn is_native(&self) -> bool {
+        self.name.starts_with(|c: char| c.is_ascii_lowercase()) && !self.name.contains('.')
+    }
+
+    /// Whether the element renders any text, child element or expression
+    pub fn has_content(&self) -> bool {
+        self.children.iter().any(|child| match child {
+            Node::Text(text) => !text.trim().is_empty(),
+            Node::Expression(expr) => !expr.text.trim().is_empty(),
+            Node::Element(_) => true,
+        })
+    }
+}
+
+/// Visit every element in document order with its ancestors, outermost first
+pub(crate) fn walk<'a>(elements: &'a [Element], visit: &mut impl FnMut(&'a Element, &[&'a Element])) {
+    fn go<'a>(
+        elements: &'a [Element],
+        ancestors: &mut Vec<&'a Element>,
+        visit: &mut impl FnMut(&'a Element, &[&'a Element]),
+    ) {
+        for element in elements {
+            visit(element, ancestors);
+            ancestors.push(element);
+            for attr in &element.attributes {
+                if let Value::Expression(expr) = &attr.value {
+                    go(&expr.elements, ancestors, visit);
+                }
+            }
+            for child in &element.children {
+                match child {
+                    Node::Element(child) => go(std::slice::from_ref(child), ancestors, visit),
+                    Node::Expression(expr) => go(&expr.elements, ancestors, visit),
+                    Node::Text(_) => {}
+                }
+            }
+            ancestors.pop();
+        }
+    }
+    go(elements, &mut Vec::new(), visit);
+}
+
+/// Parse the top-level elements of a source file
+pub(crate) fn parse(source: &str) -> Vec<Element> {
+    let mut line_starts = vec![0];
+    line_starts.extend(source.match_indices('\n').map(|(i, _)| i + 1));
+    let mut parser = Parser {
+        source,
+        src: source.as_bytes(),
+        pos: 0,
+        line_starts,
+    };
+    parser.script(false).elements
+}
+
+/// Words after which `<` starts markup rather than a comparison
+const KEYWORDS: &[&str] = &["return", "yield", "default", "case", "else", "in", "of", "await", "do"];
+
+struct Parser<'a> {
+    source: &'a str,
+    src: &'a [u8],
+    pos: usize,
+    line_starts: Vec<usize>,
+}
+
+impl Parser<'_> {
+    fn peek(&self, ahead: usize) -> Option<u8> {
+        self.src.get(self.pos + ahead).copied()
+    }
+
+    fn line(&self) -> usize {
+        self.line_starts.partition_point(|&start| start <= self.pos)
+    }
+
+    fn text(&self, start: usize) -> String {
+        self.source.get(start..self.pos).unwrap_or_default().to_string()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek(0).is_some_and(|c| c.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    /// JavaScript up to the `}` closing an expression container, or to the end
+    fn script(&mut self, in_container: bool) -> Expression {
+        let start = self.pos;
+        let mut depth = 0usize;
+        let mut elements = Vec::new();
+        while let Some(c) = self.peek(0) {
+            match c {
+                b'"' | b'\'' => self.skip_string(c),
+                b'`' => self.skip_template(),
+                b'/' if self.peek(1) == Some(b'/') => {
+                    while self.peek(0).is_some_and(|c| c != b'\n') {
+                        self.pos += 1;
+                    }
+                }
+                b'/' if self.peek(1) == Some(b'*') => {
+                    self.pos += 2;
+                    while self.peek(0).is_some() && !(self.peek(0) == Some(b'*') && self.peek(1) == Some(b'/')) {
+                        self.pos += 1;
+                    }
+                    self.pos += 2;
+                }
+                b'{' => {
+                    depth += 1;
+                    self.pos += 1;
+                }
+                b'}' if depth == 0 && in_container => {
+                    let text = self.text(start);
+                    self.pos += 1;
+                    return Expression { text, elements };
+                }
+                b'}' => {
+                    depth = depth.saturating_sub(1);
+                    self.pos += 1;
+                }
+                b'<' if self.markup_starts() => {
+                    let tag = self.pos;
+                    match self.element() {
+                        Some(element) => elements.push(element),
+                        None => self.pos = tag + 1,
+                    }
+                }
+                _ => self.pos += 1,
+            }
+        }
+        self.pos = self.pos.min(self.src.len());
+        Expression {
+            text: self.text(start),
+            elements,
+        }
+    }
+
+    /// Whether the `<` at the cursor opens an element rather than comparing
+    /// or starting type arguments
+    fn markup_starts(&self) -> bool {
+        if !self.peek(1).is_some_and(|c| c.is_ascii_alphabetic() || c == b'>') {
+            return false;
+        }
+        let before = &self.src[..self.pos];
+        let end = before.iter().rposition(|c| !c.is_ascii_whitespace());
+        let Some(end) = end else {
+            return true;
+        };
+        let prev = before[end];
+        if b"(,=:?&|!{}[;>".contains(&prev) {
+            return true;
+        }
+        if prev.is_ascii_alphanumeric() || prev == b'_' || prev == b'$' {
+            let word_start = before[..=end]
+                .iter()
+                .rposition(|c| !(c.is_ascii_alphanumeric() || *c == b'_' || *c == b'$'))
+                .map_or(0, |i| i + 1);
+            let word = &self.source[word_start..=end];
+            return KEYWORDS.contains(&word);
+        }
+        false
+    }
+
+    fn skip_string(&mut self, quote: u8) {
+        self.pos += 1;
+        while let Some(c) = self.peek(0) {
+            self.pos += 1;
+            match c {
+                b'\\' => self.pos += 1,
+                b'\n' => return,
+                c if c == quote => return,
+                _ => {}
+            }
+        }
+    }
+
+    fn skip_template(&mut self) {
+        self.pos += 1;
+        while let Some(c) = self.peek(0) {
+            match c {
+                b'\\' => self.pos += 2,
+                b'`' => {
+                    self.pos += 1;
+                    return;
+                }
+                b'$' if self.peek(1) == Some(b'{') => {
+                    self.pos += 2;
+                    self.script(true);
+                }
+                _ => self.pos += 1,
+            }
+        }
+    }
+
+    fn name(&mut self) -> String {
+        let start = self.pos;
+        while self
+            .peek(0)
+            .is_some_and(|c| c.is_ascii_alphanumeric() || b"_$-:.".contains(&c))
+        {
+            self.pos += 1;
+        }
+        self.text(start)
+    }
+
+    /// Element at the cursor's `<`, or `None` if the markup is malformed
+    fn element(&mut self) -> Option<Element> {
+        let line = self.line();
+        self.pos += 1;
+        let name = self.name();
+        let mut element = Element {
+            name,
+            line,
+            attributes: Vec::new(),
+            spread: false,
+            children: Vec::new(),
+        };
+
+        loop {
+            self.skip_whitespace();
+            match self.peek(0)? {
+                b'/' if self.peek(1) == Some(b'>') => {
+                    self.pos += 2;
+                    return Some(element);
+                }
+                b'>' => {
+                    self.pos += 1;
+                    break;
+                }
+                b'{' => {
+                    self.pos += 1;
+                    let expr = self.script(true);
+                    element.spread |= expr.text.trim_start().starts_with("...");
+                }
+                c if c.is_ascii_alphabetic() || c == b'_' => {
+                    let line = self.line();
+                    let name = self.name();
+                    self.skip_whitespace();
+                    let value = if self.peek(0) == Some(b'=') {
+                        self.pos += 1;
+                        self.skip_whitespace();
+                        match self.peek(0)? {
+                            quote @ (b'"' | b'\'') => {
+                                let start = self.pos + 1;
+                                while self.peek(1).is_some_and(|c| c != quote) {
+                                    self.pos += 1;
+                                }
+                                self.pos += 1;
+                                let value = self.text(start);
+                                self.pos += 1;
+                                Value::Str(value)
+                            }
+                            b'{' => {
+                                self.pos += 1;
+                                Value::Expression(self.script(true))
+                            }
+                            _ => return None,
+                        }
+                    } else {
+                        Value::None
+                    };
+                    element.attributes.push(Attribute { name, value, line });
+                }
+                _ => return None,
+            }
+        }
+
+        loop {
+            match self.peek(0)? {
+                b'<' if self.peek(1) == Some(b'/') => {
+                    self.pos += 2;
+                    self.skip_whitespace();
+                    let closing = self.name();
+                    self.skip_whitespace();
+                    if self.peek(0)? != b'>' || closing != element.name {
+                        return None;
+                    }
+                    self.pos += 1;
+                    return Some(element);
+                }
+                b'<' => {
+                    let child = self.element()?;
+                    element.children.push(Node::Element(child));
+                }
+                b'{' => {
+                    self.pos += 1;
+                    element.children.push(Node::Expression(self.script(true)));
+                }
+                _ => {
+                    let start = self.pos;
+                    while self.peek(0).is_some_and(|c| c != b'<' && c != b'{') {
+                        self.pos += 1;
+                    }
+                    element.children.push(Node::Text(self.text(start)));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tree() {
+        let source = r#"
+export function Card({ items, title }: Props) {
+  if (items.length < 1) return null;
+  const label = `${title} <b>`;
+  return (
+    <section className="card" {...rest}>
+      <h2>{title}</h2>
+      <ul>
+        {items.map((item) => (
+          <li key={item.id} onClick={() => open(item)}>
+            <img src={item.src} />
+          </li>
+        ))}
+      </ul>
+      <>Don't stop</>
+    </section>
+  );
+}
+"#;
+        let elements = parse(source);
+        assert_eq!(elements.len(), 1);
+        let section = &elements[0];
+        assert_eq!(
+            (section.name.as_str(), section.line, section.spread),
+            ("section", 6, true)
+        );
+        assert_eq!(section.literal("className"), Some("card"));
+
+        let mut names = Vec::new();
+        walk(&elements, &mut |element, ancestors| {
+            names.push((element.name.clone(), element.line, ancestors.len()));
+        });
+        assert_eq!(
+            names,
+            [
+                ("section".to_string(), 6, 0),
+                ("h2".to_string(), 7, 1),
+                ("ul".to_string(), 8, 1),
+                ("li".to_string(), 10, 2),
+                ("img".to_string(), 11, 3),
+                (String::new(), 15, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_comparisons_and_generics_are_not_markup() {
+        let source = "const a = b < c;\nconst s = useState<string>('');\nfunction f<T>(x: Array<T>) {}\n";
+        assert!(parse(source).is_empty());
+    }
+}
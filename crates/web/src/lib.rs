@@ -2,12 +2,28 @@
 //!
 //! This crate provides web-specific functionality:
 //! - Next.js security scanning (OWASP)
+//! - Server/client component boundary checks
+//! - Content Security Policy validation
+//! - Supabase query linting
+//! - Server-only environment variable leak detection
 //! - Bundle size analysis
-//! - Accessibility checks
+//! - Lighthouse performance budgets
+//! - Dead code and unused export detection
+//! - Accessibility checks on the parsed JSX tree
+//! - npm vulnerability and license audit
 //! - Import organization
 
 #![warn(missing_docs)]
 
 pub mod accessibility;
+pub mod boundaries;
 pub mod bundle_size;
+pub mod csp;
+pub mod dead_code;
+pub mod env_leaks;
+pub mod imports;
+mod jsx;
 pub mod nextjs_security;
+pub mod npm_audit;
+pub mod perf;
+pub mod supabase_queries;
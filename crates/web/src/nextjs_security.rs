@@ -1,7 +1,15 @@
 //! Next.js/React security scanning
 //!
-//! OWASP-based security checks for Next.js applications.
+//! OWASP-based security checks for Next.js applications: injection and XSS
+//! sinks, SSRF in `fetch`/axios calls, open redirects, insecure cookies and
+//! prototype pollution.
+//!
+//! Every rule has an ID and a confidence level. Only high or critical
+//! findings of medium or high confidence fail the check; low-confidence
+//! matches are for review. A reviewed finding is suppressed with a
+//! `// security-ignore: <rule>` comment on its line or the line above.
 
+use foodshare_cli::output::{self, Finding, Level};
 use foodshare_core::error::exit_codes;
 use once_cell::sync::Lazy;
 use owo_colors::OwoColorize;
@@ -18,6 +26,21 @@ pub enum Severity {
     Critical,
 }
 
+impl std::str::FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "info" => Ok(Self::Info),
+            "low" => Ok(Self::Low),
+            "medium" | "moderate" => Ok(Self::Medium),
+            "high" => Ok(Self::High),
+            "critical" => Ok(Self::Critical),
+            other => Err(format!("Unknown severity '{}' (use low, medium, high or critical)", other)),
+        }
+    }
+}
+
 /// OWASP category
 #[derive(Debug, Clone)]
 pub enum OwaspCategory {
@@ -50,77 +73,300 @@ impl OwaspCategory {
     }
 }
 
+/// How likely a match is a real vulnerability rather than a safe use
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Confidence {
+    /// The pattern is often used safely; review before acting
+    Low,
+    /// Usually a problem, but context can make it safe
+    Medium,
+    /// Almost always a problem
+    High,
+}
+
+impl Confidence {
+    /// Lowercase name, as accepted by `--min-confidence`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        }
+    }
+}
+
+impl std::str::FromStr for Confidence {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "low" => Ok(Self::Low),
+            "medium" => Ok(Self::Medium),
+            "high" => Ok(Self::High),
+            other => Err(format!("Unknown confidence '{}' (use low, medium or high)", other)),
+        }
+    }
+}
+
 /// Security finding
 #[derive(Debug)]
 pub struct SecurityFinding {
+    /// File the finding is in
     pub file: String,
+    /// Line number (1-based)
     pub line: usize,
+    /// Rule ID, usable in `security-ignore:` comments
+    pub rule: &'static str,
+    /// How serious the issue is
     pub severity: Severity,
+    /// How likely the finding is a real vulnerability
+    pub confidence: Confidence,
+    /// OWASP Top 10 category
     pub category: OwaspCategory,
+    /// What is wrong
     pub message: String,
+    /// Text the rule matched
     pub matched_text: String,
 }
 
 /// Security pattern
-#[allow(dead_code)]
 struct SecurityPattern {
-    name: &'static str,
+    id: &'static str,
     pattern: Regex,
+    /// Safe use nearby (a sanitizer, cookie flags) that clears the match
+    unless: Option<Regex>,
+    severity: Severity,
+    confidence: Confidence,
+    category: OwaspCategory,
+    message: &'static str,
+}
+
+/// Lines after a match searched for an `unless` pattern, so multi-line
+/// calls and option objects are covered
+const UNLESS_WINDOW: usize = 6;
+
+/// Inline suppression: `// security-ignore: ssrf-user-url, eval`
+static SUPPRESSION: Lazy<Regex> = Lazy::new(|| Regex::new(r"security-ignore:\s*([\w\-]+(?:\s*,\s*[\w\-]+)*)").unwrap());
+
+/// Request data an attacker controls
+const USER_INPUT: &str = r"(?:searchParams|req\.query|req\.body|request\.url|request\.json|params\.|query\.|body\.|formData)";
+
+fn rule(
+    id: &'static str,
+    pattern: &str,
     severity: Severity,
+    confidence: Confidence,
     category: OwaspCategory,
     message: &'static str,
+) -> SecurityPattern {
+    SecurityPattern {
+        id,
+        pattern: Regex::new(&pattern.replace("{USER_INPUT}", USER_INPUT)).unwrap(),
+        unless: None,
+        severity,
+        confidence,
+        category,
+        message,
+    }
+}
+
+fn unless(mut pattern: SecurityPattern, safe: &str) -> SecurityPattern {
+    pattern.unless = Some(Regex::new(safe).unwrap());
+    pattern
 }
 
 /// Built-in security patterns
 static PATTERNS: Lazy<Vec<SecurityPattern>> = Lazy::new(|| {
+    use Confidence as C;
+    use OwaspCategory as O;
+    use Severity as S;
+
     vec![
-        SecurityPattern {
-            name: "dangerouslySetInnerHTML",
-            pattern: Regex::new(r"dangerouslySetInnerHTML").unwrap(),
-            severity: Severity::High,
-            category: OwaspCategory::A07IdentificationFailures,
-            message: "Potential XSS vulnerability",
-        },
-        SecurityPattern {
-            name: "eval",
-            pattern: Regex::new(r"\beval\s*\(").unwrap(),
-            severity: Severity::Critical,
-            category: OwaspCategory::A03Injection,
-            message: "Code injection risk - eval() usage",
-        },
-        SecurityPattern {
-            name: "innerHTML",
-            pattern: Regex::new(r"\.innerHTML\s*=").unwrap(),
-            severity: Severity::High,
-            category: OwaspCategory::A03Injection,
-            message: "Potential XSS - direct innerHTML assignment",
-        },
+        // Injection and XSS
+        unless(
+            rule(
+                "dangerously-set-inner-html",
+                r"dangerouslySetInnerHTML",
+                S::High,
+                C::Medium,
+                O::A03Injection,
+                "Potential XSS - dangerouslySetInnerHTML without sanitization",
+            ),
+            r"(?i)sanitize|DOMPurify|purify",
+        ),
+        rule("eval", r"\beval\s*\(", S::Critical, C::High, O::A03Injection, "Code injection risk - eval() usage"),
+        rule(
+            "function-constructor",
+            r"\bnew\s+Function\s*\(",
+            S::Critical,
+            C::High,
+            O::A03Injection,
+            "Code injection risk - Function constructor",
+        ),
+        rule(
+            "string-timer",
+            r#"\bset(?:Timeout|Interval)\s*\(\s*["'`]"#,
+            S::High,
+            C::Medium,
+            O::A03Injection,
+            "Code injection risk - timer with a string body is evaluated like eval()",
+        ),
+        unless(
+            rule("inner-html", r"\.innerHTML\s*=", S::High, C::Medium, O::A03Injection, "Potential XSS - direct innerHTML assignment"),
+            r"(?i)sanitize|DOMPurify|purify",
+        ),
+        // Server-side request forgery
+        rule(
+            "ssrf-user-url",
+            r"\b(?:fetch|axios(?:\.(?:get|post|put|patch|delete|head|request))?|got|ky)\s*\([^)]*{USER_INPUT}",
+            S::High,
+            C::High,
+            O::A10Ssrf,
+            "SSRF - request URL built from user input",
+        ),
+        rule(
+            "ssrf-dynamic-url",
+            r"\b(?:fetch|axios(?:\.(?:get|post|put|patch|delete|head|request))?|got|ky)\s*\(\s*(?:`\$\{|[A-Za-z_$][\w$]*(?:\.[\w$]+)*\s*[,)])",
+            S::Medium,
+            C::Low,
+            O::A10Ssrf,
+            "Possible SSRF - request host comes from a variable; make sure it is allowlisted",
+        ),
+        // Open redirects
+        rule(
+            "open-redirect",
+            r"\b(?:redirect|permanentRedirect|router\.(?:push|replace)|NextResponse\.redirect|res\.redirect|location\.(?:assign|replace))\s*\([^)]*{USER_INPUT}",
+            S::Medium,
+            C::Medium,
+            O::A01BrokenAccessControl,
+            "Open redirect - redirect target taken from the request",
+        ),
+        rule(
+            "open-redirect-location",
+            r"\blocation(?:\.href)?\s*=\s*[^=;]*{USER_INPUT}",
+            S::Medium,
+            C::Medium,
+            O::A01BrokenAccessControl,
+            "Open redirect - window.location set from the request",
+        ),
+        // Cookies
+        rule(
+            "insecure-cookie-flag",
+            r"\b(?:httpOnly|secure)\s*:\s*false\b",
+            S::Medium,
+            C::High,
+            O::A05SecurityMisconfiguration,
+            "Insecure cookie - httpOnly or secure explicitly disabled",
+        ),
+        unless(
+            rule(
+                "cookie-missing-flags",
+                r"\b(?:cookies\(\)\.set|\.cookies\.set|res\.cookie|setCookie|cookie\.serialize)\s*\(",
+                S::Medium,
+                C::Low,
+                O::A05SecurityMisconfiguration,
+                "Cookie set without httpOnly - session cookies should be httpOnly, secure and sameSite",
+            ),
+            r"\bhttpOnly\b",
+        ),
+        rule(
+            "document-cookie",
+            r"\bdocument\.cookie\s*=[^=]",
+            S::Medium,
+            C::Medium,
+            O::A05SecurityMisconfiguration,
+            "Cookie written from JavaScript can never be httpOnly",
+        ),
+        // Prototype pollution
+        rule(
+            "prototype-pollution-merge",
+            r"\b(?:_\.(?:merge|mergeWith|defaultsDeep|set)|merge|deepmerge|deepMerge|Object\.assign)\s*\([^)]*(?:{USER_INPUT}|JSON\.parse)",
+            S::High,
+            C::Medium,
+            O::A08SoftwareIntegrity,
+            "Prototype pollution - deep merge of user-controlled objects",
+        ),
+        rule(
+            "prototype-pollution-proto",
+            r#"\.__proto__\b|\[\s*["']__proto__["']\s*\]"#,
+            S::Medium,
+            C::Medium,
+            O::A08SoftwareIntegrity,
+            "Prototype pollution - direct __proto__ access",
+        ),
     ]
 });
 
-/// Scan a file for security issues
-pub fn scan_file(path: &Path) -> anyhow::Result<Vec<SecurityFinding>> {
-    let content = std::fs::read_to_string(path)?;
-    let file_str = path.to_string_lossy().to_string();
+/// IDs of all rules, in scan order
+pub fn rule_ids() -> Vec<&'static str> {
+    PATTERNS.iter().map(|p| p.id).collect()
+}
+
+/// Rule IDs suppressed by a `security-ignore:` comment on a line
+fn suppressed(line: &str) -> Vec<&str> {
+    SUPPRESSION
+        .captures(line)
+        .and_then(|c| c.get(1))
+        .map(|ids| ids.as_str().split(',').map(str::trim).collect())
+        .unwrap_or_default()
+}
+
+/// Whether a line is only a comment
+fn is_comment(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with("//") || trimmed.starts_with("/*") || trimmed.starts_with('*') || trimmed.starts_with("{/*")
+}
 
+/// Scan source text for security issues
+///
+/// A `security-ignore: <rule>[, <rule>]` comment suppresses those rules on
+/// its own line and on the line after it.
+pub fn scan_content(content: &str, file: &str) -> Vec<SecurityFinding> {
+    let lines: Vec<&str> = content.lines().collect();
     let mut findings = Vec::new();
 
-    for (line_num, line) in content.lines().enumerate() {
-        for pattern in PATTERNS.iter() {
-            if let Some(m) = pattern.pattern.find(line) {
-                findings.push(SecurityFinding {
-                    file: file_str.clone(),
-                    line: line_num + 1,
-                    severity: pattern.severity,
-                    category: pattern.category.clone(),
-                    message: pattern.message.to_string(),
-                    matched_text: m.as_str().to_string(),
-                });
+    for (index, line) in lines.iter().enumerate() {
+        if is_comment(line) {
+            continue;
+        }
+        let mut ignored = suppressed(line);
+        if let Some(previous) = index.checked_sub(1).map(|i| lines[i]) {
+            ignored.extend(suppressed(previous));
+        }
+
+        for pattern in PATTERNS.iter().filter(|p| !ignored.contains(&p.id)) {
+            let Some(m) = pattern.pattern.find(line) else {
+                continue;
+            };
+            if let Some(safe) = &pattern.unless {
+                let window = &lines[index..(index + UNLESS_WINDOW).min(lines.len())];
+                if window.iter().any(|l| safe.is_match(l)) {
+                    continue;
+                }
             }
+            findings.push(SecurityFinding {
+                file: file.to_string(),
+                line: index + 1,
+                rule: pattern.id,
+                severity: pattern.severity,
+                confidence: pattern.confidence,
+                category: pattern.category.clone(),
+                message: pattern.message.to_string(),
+                matched_text: m.as_str().to_string(),
+            });
         }
     }
 
-    Ok(findings)
+    // A specific SSRF match makes the generic one on the same line redundant
+    let specific: Vec<usize> = findings.iter().filter(|f| f.rule == "ssrf-user-url").map(|f| f.line).collect();
+    findings.retain(|f| f.rule != "ssrf-dynamic-url" || !specific.contains(&f.line));
+    findings
+}
+
+/// Scan a file for security issues
+pub fn scan_file(path: &Path) -> anyhow::Result<Vec<SecurityFinding>> {
+    let content = std::fs::read_to_string(path)?;
+    Ok(scan_content(&content, &path.to_string_lossy()))
 }
 
 /// Scan multiple files
@@ -135,12 +381,29 @@ pub fn scan_files(paths: &[std::path::PathBuf]) -> anyhow::Result<Vec<SecurityFi
         }
     }
 
-    all_findings.sort_by(|a, b| b.severity.cmp(&a.severity));
+    all_findings.sort_by(|a, b| b.severity.cmp(&a.severity).then(b.confidence.cmp(&a.confidence)));
     Ok(all_findings)
 }
 
+/// Whether a finding fails the check: critical or high severity, unless low confidence
+fn is_blocking(finding: &SecurityFinding) -> bool {
+    finding.severity >= Severity::High && finding.confidence >= Confidence::Medium
+}
+
 /// Print scan results
 pub fn print_results(findings: &[SecurityFinding]) -> i32 {
+    if output::is_machine() {
+        for finding in findings {
+            let level = if is_blocking(finding) { Level::Error } else { Level::Warning };
+            output::finding(
+                Finding::new(level, &finding.message)
+                    .with_rule(finding.rule)
+                    .with_location(&finding.file, Some(finding.line)),
+            );
+        }
+        return if findings.iter().any(is_blocking) { exit_codes::FAILURE } else { exit_codes::SUCCESS };
+    }
+
     if findings.is_empty() {
         println!("{} No security issues detected", "OK".green());
         return exit_codes::SUCCESS;
@@ -148,10 +411,11 @@ pub fn print_results(findings: &[SecurityFinding]) -> i32 {
 
     let critical = findings.iter().filter(|f| f.severity == Severity::Critical).count();
     let high = findings.iter().filter(|f| f.severity == Severity::High).count();
+    let blocking = findings.iter().filter(|f| is_blocking(f)).count();
 
     eprintln!(
         "{} Found {} security issue(s): {} critical, {} high",
-        "ERROR".red(),
+        if blocking > 0 { "ERROR".red().to_string() } else { "WARNING".yellow().to_string() },
         findings.len(),
         critical,
         high
@@ -166,13 +430,24 @@ pub fn print_results(findings: &[SecurityFinding]) -> i32 {
             Severity::Info => "INFO".blue().to_string(),
         };
 
-        eprintln!("  [{}] {}:{}", severity_str, finding.file, finding.line);
+        eprintln!(
+            "  [{}] {}:{} {} {}",
+            severity_str,
+            finding.file,
+            finding.line,
+            finding.rule.cyan(),
+            format!("({} confidence, {})", finding.confidence.as_str(), finding.category.code()).dimmed()
+        );
         eprintln!("    {}", finding.message);
     }
 
-    if critical > 0 || high > 0 {
+    if blocking > 0 {
         exit_codes::FAILURE
     } else {
+        eprintln!(
+            "  {}",
+            "Suppress a reviewed finding with a `// security-ignore: <rule>` comment".dimmed()
+        );
         exit_codes::SUCCESS
     }
 }
@@ -192,4 +467,67 @@ mod tests {
         assert_eq!(OwaspCategory::A01BrokenAccessControl.code(), "A01:2021");
         assert_eq!(OwaspCategory::A03Injection.code(), "A03:2021");
     }
+
+    fn rules(content: &str) -> Vec<&'static str> {
+        scan_content(content, "page.tsx").iter().map(|f| f.rule).collect()
+    }
+
+    #[test]
+    fn test_injection_rules() {
+        assert_eq!(rules("<div dangerouslySetInnerHTML={{ __html: post.body }} />"), ["dangerously-set-inner-html"]);
+        assert!(rules("<div dangerouslySetInnerHTML={{ __html: DOMPurify.sanitize(post.body) }} />").is_empty());
+        assert!(rules("<div\n  dangerouslySetInnerHTML={{\n    __html: sanitizeHtml(body),\n  }}\n/>").is_empty());
+        assert_eq!(rules("const fn = new Function('a', code);"), ["function-constructor"]);
+        assert_eq!(rules("setTimeout(\"refresh()\", 100);"), ["string-timer"]);
+        assert!(rules("setTimeout(() => refresh(), 100);").is_empty());
+        assert!(rules("const evaluation = evaluate(x);").is_empty());
+    }
+
+    #[test]
+    fn test_ssrf_and_redirect_rules() {
+        assert_eq!(rules("const res = await fetch(searchParams.get('url'));"), ["ssrf-user-url"]);
+        assert_eq!(rules("await axios.get(`${req.query.host}/api`)"), ["ssrf-user-url"]);
+        assert_eq!(rules("const res = await fetch(target, { cache: 'no-store' });"), ["ssrf-dynamic-url"]);
+        assert!(rules("const res = await fetch('/api/listings');").is_empty());
+
+        assert_eq!(rules("redirect(searchParams.get('next') ?? '/')"), ["open-redirect"]);
+        assert_eq!(rules("window.location.href = params.returnTo;"), ["open-redirect-location"]);
+        assert!(rules("redirect('/login')").is_empty());
+    }
+
+    #[test]
+    fn test_cookie_and_prototype_rules() {
+        assert_eq!(rules("cookies().set('session', token)"), ["cookie-missing-flags"]);
+        assert!(rules("cookies().set('session', token, {\n  httpOnly: true,\n  secure: true,\n})").is_empty());
+        assert_eq!(rules("res.cookie('sid', id, { httpOnly: false })"), ["insecure-cookie-flag"]);
+        assert_eq!(rules("document.cookie = `theme=${theme}`;"), ["document-cookie"]);
+
+        assert_eq!(rules("const config = _.merge({}, defaults, req.body);"), ["prototype-pollution-merge"]);
+        assert_eq!(rules("target['__proto__'] = value;"), ["prototype-pollution-proto"]);
+        assert!(rules("const merged = { ...defaults, ...overrides };").is_empty());
+    }
+
+    #[test]
+    fn test_inline_suppression() {
+        let content = "\
+// security-ignore: ssrf-dynamic-url
+const res = await fetch(endpoint);
+const html = el.innerHTML = markup; // security-ignore: inner-html
+eval(code); // security-ignore: ssrf-user-url
+// eval(code) in a comment
+";
+        let findings = scan_content(content, "lib/api.ts");
+        assert_eq!(findings.len(), 1);
+        assert_eq!((findings[0].rule, findings[0].line), ("eval", 4));
+        assert_eq!(findings[0].confidence, Confidence::High);
+        assert!(is_blocking(&findings[0]));
+        assert!(rule_ids().contains(&"open-redirect"));
+    }
+
+    #[test]
+    fn test_confidence_parse() {
+        assert_eq!("Medium".parse::<Confidence>(), Ok(Confidence::Medium));
+        assert!("certain".parse::<Confidence>().is_err());
+        assert!(Confidence::High > Confidence::Low);
+    }
 }
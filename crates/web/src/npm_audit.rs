@@ -0,0 +1,768 @@
+//! npm dependency vulnerability and license audit
+//!
+//! Reads the installed packages from `package-lock.json` or `pnpm-lock.yaml`,
+//! looks them up in the OSV database (<https://osv.dev>) and checks their
+//! licenses against a denylist.
+//!
+//! OSV answers are cached under the user cache directory: package lookups
+//! for a day, advisories until OSV reports them modified. Offline, only the
+//! cache is used and packages it doesn't cover are counted as unchecked.
+
+use crate::nextjs_security::Severity;
+use anyhow::{Context, Result};
+use foodshare_cli::output::{self, Finding, Level};
+use foodshare_core::error::exit_codes;
+use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const OSV_API: &str = "https://api.osv.dev/v1";
+
+/// Packages per OSV batch query
+const BATCH_SIZE: usize = 500;
+
+/// Time allowed for each OSV request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How long a package lookup stays fresh
+const QUERY_TTL_SECS: u64 = 24 * 60 * 60;
+
+/// An installed package
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Package {
+    /// Package name (`@scope/name`)
+    pub name: String,
+    /// Installed version
+    pub version: String,
+    /// Only needed for development
+    pub dev: bool,
+    /// SPDX license expression, if known
+    pub license: Option<String>,
+}
+
+impl Package {
+    fn key(&self) -> String {
+        format!("{}@{}", self.name, self.version)
+    }
+}
+
+/// An OSV advisory affecting a package
+#[derive(Debug, Clone, PartialEq)]
+pub struct Advisory {
+    /// OSV ID (`GHSA-...`, `MAL-...`)
+    pub id: String,
+    /// One-line summary
+    pub summary: String,
+    /// Severity; advisories without a rating count as medium
+    pub severity: Severity,
+    /// Lowest fixed version above the installed one
+    pub fixed: Option<String>,
+}
+
+/// A package whose license is denied
+#[derive(Debug, Clone)]
+pub struct LicenseViolation {
+    /// The package
+    pub package: Package,
+    /// Its license expression
+    pub license: String,
+}
+
+/// Audit result
+#[derive(Debug)]
+pub struct NpmAudit {
+    /// Lockfile the packages came from
+    pub lockfile: PathBuf,
+    /// Number of distinct packages
+    pub packages: usize,
+    /// Vulnerable packages with their advisories, most severe first
+    pub vulnerable: Vec<(Package, Advisory)>,
+    /// Packages with a denied license
+    pub licenses: Vec<LicenseViolation>,
+    /// Packages not checked because OSV wasn't reachable and nothing was cached
+    pub unchecked: usize,
+}
+
+impl NpmAudit {
+    /// Number of findings at or above `fail_on`, plus license violations
+    pub fn failures(&self, fail_on: Severity) -> usize {
+        self.vulnerable.iter().filter(|(_, a)| a.severity >= fail_on).count() + self.licenses.len()
+    }
+
+    /// Print the audit and return the exit code
+    pub fn print(&self, fail_on: Severity) -> i32 {
+        if output::is_machine() {
+            return self.report(fail_on);
+        }
+
+        println!(
+            "{} {} packages from {}",
+            "Audited".bold(),
+            self.packages,
+            self.lockfile.display()
+        );
+        if self.unchecked > 0 {
+            println!(
+                "{} {} package(s) unchecked: OSV unreachable and not cached",
+                "WARNING".yellow(),
+                self.unchecked
+            );
+        }
+
+        if !self.vulnerable.is_empty() {
+            eprintln!();
+            eprintln!("{} {} vulnerable package(s):", "ERROR".red(), self.vulnerable.len());
+            for (package, advisory) in &self.vulnerable {
+                let severity_str = match advisory.severity {
+                    Severity::Critical => "CRITICAL".red().bold().to_string(),
+                    Severity::High => "HIGH".red().to_string(),
+                    Severity::Medium => "MEDIUM".yellow().to_string(),
+                    Severity::Low => "LOW".dimmed().to_string(),
+                    Severity::Info => "INFO".blue().to_string(),
+                };
+                let fix = advisory
+                    .fixed
+                    .as_ref()
+                    .map(|v| format!(" (fixed in {})", v))
+                    .unwrap_or_default();
+                eprintln!(
+                    "  [{}] {}{} {}{}",
+                    severity_str,
+                    package.key(),
+                    if package.dev { " (dev)" } else { "" },
+                    advisory.id.cyan(),
+                    fix.green()
+                );
+                eprintln!("    {}", advisory.summary);
+            }
+        }
+
+        if !self.licenses.is_empty() {
+            eprintln!();
+            eprintln!(
+                "{} {} package(s) with a denied license:",
+                "ERROR".red(),
+                self.licenses.len()
+            );
+            for violation in &self.licenses {
+                eprintln!("  {} {}", violation.package.key(), violation.license.yellow());
+            }
+        }
+
+        let failures = self.failures(fail_on);
+        if failures > 0 {
+            exit_codes::FAILURE
+        } else {
+            if self.vulnerable.is_empty() && self.licenses.is_empty() {
+                println!("{} No known vulnerabilities or denied licenses", "OK".green());
+            } else {
+                println!(
+                    "{} Nothing at or above {} severity",
+                    "OK".green(),
+                    format!("{:?}", fail_on).to_lowercase()
+                );
+            }
+            exit_codes::SUCCESS
+        }
+    }
+
+    /// Report the audit as findings and data
+    fn report(&self, fail_on: Severity) -> i32 {
+        output::data("lockfile", &self.lockfile);
+        output::data("packages", &self.packages);
+        output::data("unchecked", &self.unchecked);
+        for (package, advisory) in &self.vulnerable {
+            let level = if advisory.severity >= fail_on { Level::Error } else { Level::Warning };
+            let fix = advisory.fixed.as_ref().map(|v| format!(" (fixed in {})", v)).unwrap_or_default();
+            output::finding(
+                Finding::new(level, format!("{}: {}{}", package.key(), advisory.summary, fix)).with_rule(&advisory.id),
+            );
+        }
+        for violation in &self.licenses {
+            output::finding(
+                Finding::new(Level::Error, format!("{} is licensed {}", violation.package.key(), violation.license))
+                    .with_rule("denied-license"),
+            );
+        }
+
+        if self.failures(fail_on) > 0 {
+            exit_codes::FAILURE
+        } else {
+            exit_codes::SUCCESS
+        }
+    }
+}
+
+/// Read the packages of the app at `root`, preferring `package-lock.json`
+pub fn read_lockfile(root: &Path) -> Result<(PathBuf, Vec<Package>)> {
+    let npm = root.join("package-lock.json");
+    let pnpm = root.join("pnpm-lock.yaml");
+    let (path, mut packages) = if npm.is_file() {
+        let packages = parse_package_lock(&std::fs::read_to_string(&npm)?)
+            .with_context(|| format!("Failed to parse {}", npm.display()))?;
+        (npm, packages)
+    } else if pnpm.is_file() {
+        (pnpm.clone(), parse_pnpm_lock(&std::fs::read_to_string(&pnpm)?))
+    } else {
+        anyhow::bail!("No package-lock.json or pnpm-lock.yaml in {}", root.display());
+    };
+
+    for package in packages.iter_mut().filter(|p| p.license.is_none()) {
+        package.license = installed_license(root, package);
+    }
+    Ok((path, packages))
+}
+
+/// Packages in an npm lockfile (v1 `dependencies` or v2/v3 `packages`)
+pub fn parse_package_lock(content: &str) -> Result<Vec<Package>> {
+    let lock: serde_json::Value = serde_json::from_str(content)?;
+    let mut packages = BTreeSet::new();
+
+    if let Some(entries) = lock["packages"].as_object() {
+        for (path, entry) in entries {
+            // "" is the app itself; workspace sources sit outside node_modules
+            if !path.contains("node_modules/") || entry["link"].as_bool() == Some(true) {
+                continue;
+            }
+            let Some(version) = entry["version"].as_str() else {
+                continue;
+            };
+            let name = entry["name"]
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| path.rsplit("node_modules/").next().unwrap_or(path).to_string());
+            packages.insert(Package {
+                name,
+                version: version.to_string(),
+                dev: entry["dev"].as_bool().unwrap_or(false),
+                license: entry["license"].as_str().map(str::to_string),
+            });
+        }
+    } else {
+        fn walk(dependencies: &serde_json::Value, packages: &mut BTreeSet<Package>) {
+            for (name, entry) in dependencies.as_object().into_iter().flatten() {
+                if let Some(version) = entry["version"].as_str().filter(|v| !v.starts_with("file:")) {
+                    packages.insert(Package {
+                        name: name.clone(),
+                        version: version.to_string(),
+                        dev: entry["dev"].as_bool().unwrap_or(false),
+                        license: None,
+                    });
+                }
+                walk(&entry["dependencies"], packages);
+            }
+        }
+        walk(&lock["dependencies"], &mut packages);
+    }
+    Ok(dedup(packages))
+}
+
+/// Packages in a pnpm lockfile (v5 `/name/1.0.0`, v6 `/name@1.0.0`, v9 `name@1.0.0`)
+pub fn parse_pnpm_lock(content: &str) -> Vec<Package> {
+    let mut packages = BTreeSet::new();
+    let mut in_packages = false;
+    let mut current: Option<Package> = None;
+
+    for line in content.lines() {
+        if !line.starts_with(' ') && !line.trim().is_empty() {
+            in_packages = line.trim_end() == "packages:";
+            packages.extend(current.take());
+            continue;
+        }
+        if !in_packages {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+        let trimmed = line.trim();
+        if indent == 2 && trimmed.ends_with(':') {
+            packages.extend(current.take());
+            current = pnpm_key(trimmed.trim_end_matches(':'));
+        } else if indent == 4 {
+            if let Some(package) = current.as_mut() {
+                if trimmed == "dev: true" {
+                    package.dev = true;
+                }
+            }
+        }
+    }
+    packages.extend(current);
+    dedup(packages)
+}
+
+fn pnpm_key(key: &str) -> Option<Package> {
+    let key = key.trim_matches(|c| c == '\'' || c == '"').trim_start_matches('/');
+    // Peer dependency suffixes: `(react@18.2.0)` (v6+) or `_react@18.2.0` (v5)
+    let key = key.split('(').next().unwrap_or(key);
+    let (name, version) = match key.rsplit_once('/') {
+        Some((name, version)) if version.starts_with(|c: char| c.is_ascii_digit()) => (name, version),
+        _ => {
+            let at = key[1..].rfind('@')? + 1;
+            (&key[..at], &key[at + 1..])
+        }
+    };
+    let version = version.split('_').next().unwrap_or(version);
+    Some(Package {
+        name: name.to_string(),
+        version: version.to_string(),
+        dev: false,
+        license: None,
+    })
+}
+
+/// One entry per name and version; a package is dev-only if every copy is
+fn dedup(packages: BTreeSet<Package>) -> Vec<Package> {
+    let mut unique: BTreeMap<(String, String), Package> = BTreeMap::new();
+    for package in packages {
+        unique
+            .entry((package.name.clone(), package.version.clone()))
+            .and_modify(|existing| {
+                existing.dev &= package.dev;
+                if existing.license.is_none() {
+                    existing.license = package.license.clone();
+                }
+            })
+            .or_insert(package);
+    }
+    unique.into_values().collect()
+}
+
+/// License from the installed `package.json` (npm or pnpm layout)
+fn installed_license(root: &Path, package: &Package) -> Option<String> {
+    let candidates = [
+        root.join("node_modules").join(&package.name).join("package.json"),
+        root.join("node_modules/.pnpm")
+            .join(format!("{}@{}", package.name.replace('/', "+"), package.version))
+            .join("node_modules")
+            .join(&package.name)
+            .join("package.json"),
+    ];
+    candidates.iter().find_map(|path| {
+        let manifest: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()?;
+        if manifest["version"].as_str() != Some(package.version.as_str()) {
+            return None;
+        }
+        match &manifest["license"] {
+            serde_json::Value::String(license) => Some(license.clone()),
+            serde_json::Value::Object(license) => license.get("type")?.as_str().map(str::to_string),
+            _ => None,
+        }
+    })
+}
+
+/// Whether a license expression is denied: every `OR` alternative uses a denied license
+///
+/// Denied entries are SPDX prefixes, so `GPL-3.0` covers `GPL-3.0-only` and
+/// `GPL-3.0-or-later` but not `LGPL-3.0`.
+pub fn license_denied(expression: &str, denied: &[String]) -> bool {
+    let expression = expression.replace(['(', ')'], " ");
+    let upper = expression.to_uppercase();
+    let alternatives: Vec<&str> = upper.split(" OR ").collect();
+    !denied.is_empty()
+        && alternatives.iter().all(|alternative| {
+            alternative
+                .split_whitespace()
+                .filter(|token| !matches!(*token, "AND" | "WITH"))
+                .any(|license| denied.iter().any(|d| license.starts_with(&d.to_uppercase())))
+        })
+}
+
+/// `(id, modified)` of each advisory affecting a package version
+type AdvisoryRefs = Vec<(String, String)>;
+
+/// Cached OSV lookups of one package version
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct CachedQuery {
+    vulns: AdvisoryRefs,
+    fetched: u64,
+}
+
+/// OSV client with an on-disk cache
+pub struct Osv {
+    client: reqwest::blocking::Client,
+    cache_dir: PathBuf,
+    offline: bool,
+}
+
+impl Osv {
+    /// Client caching under `cache_dir`; `offline` never touches the network
+    pub fn new(cache_dir: PathBuf, offline: bool) -> Self {
+        let client = reqwest::blocking::Client::builder()
+            .user_agent(concat!("foodshare-web/", env!("CARGO_PKG_VERSION")))
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self { client, cache_dir, offline }
+    }
+
+    /// Default cache location in the user cache directory
+    pub fn default_cache_dir() -> PathBuf {
+        dirs::cache_dir()
+            .unwrap_or_else(|| PathBuf::from(".cache"))
+            .join("foodshare-tools")
+            .join("osv")
+    }
+
+    fn queries_file(&self) -> PathBuf {
+        self.cache_dir.join("npm-queries.json")
+    }
+
+    fn load_queries(&self) -> BTreeMap<String, CachedQuery> {
+        std::fs::read_to_string(self.queries_file())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Advisory IDs per package key, and the number of packages left unchecked
+    fn query(&self, packages: &[Package]) -> Result<(BTreeMap<String, AdvisoryRefs>, usize)> {
+        let mut cache = self.load_queries();
+        let now = now();
+        let stale: Vec<&Package> = packages
+            .iter()
+            .filter(|p| {
+                cache
+                    .get(&p.key())
+                    .is_none_or(|cached| !self.offline && now - cached.fetched > QUERY_TTL_SECS)
+            })
+            .collect();
+
+        let mut unchecked = 0;
+        if self.offline {
+            unchecked = stale.len();
+        } else if !stale.is_empty() {
+            std::fs::create_dir_all(&self.cache_dir)?;
+            for batch in stale.chunks(BATCH_SIZE) {
+                let queries: Vec<serde_json::Value> = batch
+                    .iter()
+                    .map(|p| {
+                        serde_json::json!({
+                            "package": { "name": p.name, "ecosystem": "npm" },
+                            "version": p.version,
+                        })
+                    })
+                    .collect();
+                let response = self.post("querybatch", &serde_json::json!({ "queries": queries }))?;
+                let results = response["results"].as_array().cloned().unwrap_or_default();
+                for (package, result) in batch.iter().zip(results) {
+                    let vulns = result["vulns"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|v| {
+                            Some((
+                                v["id"].as_str()?.to_string(),
+                                v["modified"].as_str().unwrap_or_default().to_string(),
+                            ))
+                        })
+                        .collect();
+                    cache.insert(package.key(), CachedQuery { vulns, fetched: now });
+                }
+            }
+            std::fs::write(self.queries_file(), serde_json::to_string(&cache)?)?;
+        }
+
+        let found = packages
+            .iter()
+            .filter_map(|p| cache.get(&p.key()).map(|cached| (p.key(), cached.vulns.clone())))
+            .collect();
+        Ok((found, unchecked))
+    }
+
+    /// Full OSV record of an advisory, refetched when `modified` changed
+    fn vulnerability(&self, id: &str, modified: &str) -> Result<Option<serde_json::Value>> {
+        let path = self.cache_dir.join("vulns").join(format!("{}.json", id));
+        let cached: Option<serde_json::Value> = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok());
+        if let Some(record) = &cached {
+            if self.offline || record["modified"].as_str() == Some(modified) {
+                return Ok(cached);
+            }
+        }
+        if self.offline {
+            return Ok(None);
+        }
+
+        let body = self
+            .client
+            .get(format!("{}/vulns/{}", OSV_API, id))
+            .timeout(Duration::from_secs(30))
+            .send()
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.text())
+            .with_context(|| format!("OSV request for {} failed", id))?;
+        let record: serde_json::Value = serde_json::from_str(&body)?;
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        std::fs::write(&path, &body)?;
+        Ok(Some(record))
+    }
+
+    fn post(&self, endpoint: &str, body: &serde_json::Value) -> Result<serde_json::Value> {
+        self.client
+            .post(format!("{}/{}", OSV_API, endpoint))
+            .json(body)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.json())
+            .with_context(|| format!("OSV {} failed", endpoint))
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Advisory details from an OSV record for `package`
+pub fn advisory(record: &serde_json::Value, package: &Package) -> Advisory {
+    let id = record["id"].as_str().unwrap_or_default().to_string();
+    let severity = if id.starts_with("MAL-") {
+        // Malicious package reports have no rating but are always critical
+        Severity::Critical
+    } else {
+        match record["database_specific"]["severity"]
+            .as_str()
+            .map(str::to_uppercase)
+            .as_deref()
+        {
+            Some("CRITICAL") => Severity::Critical,
+            Some("HIGH") => Severity::High,
+            Some("LOW") => Severity::Low,
+            _ => Severity::Medium,
+        }
+    };
+
+    let fixed = record["affected"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|affected| affected["package"]["name"].as_str() == Some(package.name.as_str()))
+        .flat_map(|affected| affected["ranges"].as_array().cloned().unwrap_or_default())
+        .flat_map(|range| range["events"].as_array().cloned().unwrap_or_default())
+        .filter_map(|event| event["fixed"].as_str().map(str::to_string))
+        .filter(|fixed| compare_versions(fixed, &package.version) == Ordering::Greater)
+        .min_by(|a, b| compare_versions(a, b));
+
+    Advisory {
+        summary: record["summary"]
+            .as_str()
+            .or(record["details"].as_str())
+            .unwrap_or("No summary")
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .to_string(),
+        id,
+        severity,
+        fixed,
+    }
+}
+
+/// Compare dotted versions numerically; prerelease tags sort before the release
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let split = |v: &str| -> (Vec<u64>, bool) {
+        let (release, pre) = match v.split_once('-') {
+            Some((release, _)) => (release, true),
+            None => (v, false),
+        };
+        (release.split('.').map(|part| part.parse().unwrap_or(0)).collect(), pre)
+    };
+    let (a_parts, a_pre) = split(a);
+    let (b_parts, b_pre) = split(b);
+    let len = a_parts.len().max(b_parts.len());
+    for i in 0..len {
+        let ordering = a_parts.get(i).unwrap_or(&0).cmp(b_parts.get(i).unwrap_or(&0));
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    b_pre.cmp(&a_pre)
+}
+
+/// Audit the app at `root`
+pub fn audit(root: &Path, osv: &Osv, denied_licenses: &[String], omit_dev: bool) -> Result<NpmAudit> {
+    let (lockfile, mut packages) = read_lockfile(root)?;
+    if omit_dev {
+        packages.retain(|p| !p.dev);
+    }
+
+    let (found, unchecked) = osv.query(&packages)?;
+    let mut vulnerable = Vec::new();
+    for package in &packages {
+        for (id, modified) in found.get(&package.key()).into_iter().flatten() {
+            let advisory = match osv.vulnerability(id, modified)? {
+                Some(record) => advisory(&record, package),
+                None => Advisory {
+                    id: id.clone(),
+                    summary: "Details not cached".to_string(),
+                    severity: Severity::Medium,
+                    fixed: None,
+                },
+            };
+            vulnerable.push((package.clone(), advisory));
+        }
+    }
+    vulnerable.sort_by(|(a_pkg, a), (b_pkg, b)| b.severity.cmp(&a.severity).then_with(|| a_pkg.cmp(b_pkg)));
+
+    let licenses = packages
+        .iter()
+        .filter_map(|package| {
+            let license = package.license.as_ref()?;
+            license_denied(license, denied_licenses).then(|| LicenseViolation {
+                package: package.clone(),
+                license: license.clone(),
+            })
+        })
+        .collect();
+
+    Ok(NpmAudit {
+        lockfile,
+        packages: packages.len(),
+        vulnerable,
+        licenses,
+        unchecked,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn denied() -> Vec<String> {
+        foodshare_core::config::DepsConfig::default().denied_licenses
+    }
+
+    #[test]
+    fn test_parse_package_lock() {
+        let lock = r#"{
+  "lockfileVersion": 3,
+  "packages": {
+    "": { "name": "web", "version": "1.0.0" },
+    "node_modules/next": { "version": "14.1.0", "license": "MIT" },
+    "node_modules/@types/react": { "version": "18.2.0", "dev": true, "license": "MIT" },
+    "node_modules/foo/node_modules/semver": { "version": "5.7.1", "dev": true },
+    "node_modules/semver": { "version": "5.7.1" },
+    "packages/ui": { "version": "0.0.0", "link": true }
+  }
+}"#;
+        let packages = parse_package_lock(lock).unwrap();
+        let keys: Vec<(String, bool)> = packages.iter().map(|p| (p.key(), p.dev)).collect();
+        assert_eq!(
+            keys,
+            [
+                ("@types/react@18.2.0".to_string(), true),
+                ("next@14.1.0".to_string(), false),
+                ("semver@5.7.1".to_string(), false),
+            ]
+        );
+
+        let v1 = r#"{ "lockfileVersion": 1, "dependencies": { "lodash": { "version": "4.17.20", "dependencies": { "minimist": { "version": "0.0.8", "dev": true } } } } }"#;
+        let keys: Vec<String> = parse_package_lock(v1).unwrap().iter().map(Package::key).collect();
+        assert_eq!(keys, ["lodash@4.17.20", "minimist@0.0.8"]);
+    }
+
+    #[test]
+    fn test_parse_pnpm_lock() {
+        let v6 = "lockfileVersion: '6.0'\n\ndependencies:\n  next:\n    specifier: 14.1.0\n    version: 14.1.0\n\npackages:\n\n  /@babel/code-frame@7.22.13:\n    resolution: {integrity: sha512-x}\n    dev: true\n\n  /next@14.1.0(react@18.2.0):\n    resolution: {integrity: sha512-y}\n";
+        let keys: Vec<(String, bool)> = parse_pnpm_lock(v6).iter().map(|p| (p.key(), p.dev)).collect();
+        assert_eq!(
+            keys,
+            [
+                ("@babel/code-frame@7.22.13".to_string(), true),
+                ("next@14.1.0".to_string(), false)
+            ]
+        );
+
+        let v9 = "lockfileVersion: '9.0'\n\npackages:\n\n  '@next/env@14.1.0':\n    resolution: {integrity: sha512-z}\n\nsnapshots:\n\n  '@next/env@14.1.0': {}\n";
+        assert_eq!(parse_pnpm_lock(v9)[0].key(), "@next/env@14.1.0");
+
+        assert_eq!(
+            pnpm_key("/@babel/core/7.20.0_supports-color@8.1.1").unwrap().key(),
+            "@babel/core@7.20.0"
+        );
+    }
+
+    #[test]
+    fn test_licenses() {
+        assert!(license_denied("GPL-3.0-only", &denied()));
+        assert!(license_denied("(AGPL-3.0 AND MIT)", &denied()));
+        assert!(!license_denied("LGPL-3.0", &denied()));
+        assert!(!license_denied("(MIT OR GPL-3.0)", &denied()));
+        assert!(!license_denied("MIT", &denied()));
+    }
+
+    #[test]
+    fn test_advisory() {
+        let package = Package {
+            name: "semver".to_string(),
+            version: "5.7.1".to_string(),
+            dev: false,
+            license: None,
+        };
+        let record = serde_json::json!({
+            "id": "GHSA-c2qf-rxjj-qqgw",
+            "summary": "semver vulnerable to Regular Expression Denial of Service",
+            "modified": "2024-01-01T00:00:00Z",
+            "database_specific": { "severity": "MODERATE" },
+            "affected": [
+                { "package": { "name": "semver", "ecosystem": "npm" },
+                  "ranges": [{ "type": "SEMVER", "events": [{ "introduced": "0" }, { "fixed": "5.7.2" }] }] },
+                { "package": { "name": "semver", "ecosystem": "npm" },
+                  "ranges": [{ "type": "SEMVER", "events": [{ "introduced": "7.0.0" }, { "fixed": "7.5.2" }] }] }
+            ]
+        });
+        let found = advisory(&record, &package);
+        assert_eq!(found.severity, Severity::Medium);
+        assert_eq!(found.fixed.as_deref(), Some("5.7.2"));
+
+        let malicious = serde_json::json!({ "id": "MAL-2024-1", "summary": "Malicious code in foo" });
+        assert_eq!(advisory(&malicious, &package).severity, Severity::Critical);
+
+        assert_eq!(compare_versions("7.5.2", "7.10.0"), Ordering::Less);
+        assert_eq!(compare_versions("2.0.0-rc.1", "2.0.0"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_offline_audit_uses_cache() {
+        let app = tempfile::tempdir().unwrap();
+        std::fs::write(
+            app.path().join("package-lock.json"),
+            r#"{ "lockfileVersion": 3, "packages": {
+                "node_modules/semver": { "version": "5.7.1", "license": "ISC" },
+                "node_modules/left-pad": { "version": "1.3.0", "license": "GPL-3.0" },
+                "node_modules/uncached": { "version": "1.0.0", "dev": true }
+            } }"#,
+        )
+        .unwrap();
+
+        let cache = tempfile::tempdir().unwrap();
+        let queries = serde_json::json!({
+            "semver@5.7.1": { "vulns": [["GHSA-c2qf-rxjj-qqgw", "2024-01-01T00:00:00Z"]], "fetched": 0 },
+            "left-pad@1.3.0": { "vulns": [], "fetched": 0 }
+        });
+        std::fs::write(cache.path().join("npm-queries.json"), queries.to_string()).unwrap();
+        std::fs::create_dir_all(cache.path().join("vulns")).unwrap();
+        std::fs::write(
+            cache.path().join("vulns/GHSA-c2qf-rxjj-qqgw.json"),
+            r#"{ "id": "GHSA-c2qf-rxjj-qqgw", "summary": "ReDoS", "modified": "2024-01-01T00:00:00Z", "database_specific": { "severity": "HIGH" } }"#,
+        )
+        .unwrap();
+
+        let osv = Osv::new(cache.path().to_path_buf(), true);
+        let report = audit(app.path(), &osv, &denied(), false).unwrap();
+        assert_eq!((report.packages, report.unchecked), (3, 1));
+        assert_eq!(report.vulnerable.len(), 1);
+        assert_eq!(report.vulnerable[0].1.severity, Severity::High);
+        assert_eq!(report.licenses[0].package.name, "left-pad");
+        assert_eq!(report.failures(Severity::High), 2);
+        assert_eq!(report.failures(Severity::Critical), 1);
+
+        let report = audit(app.path(), &osv, &denied(), true).unwrap();
+        assert_eq!((report.packages, report.unchecked), (2, 0));
+    }
+}
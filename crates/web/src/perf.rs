@@ -0,0 +1,536 @@
+//! Lighthouse performance budgets
+//!
+//! Runs the Lighthouse CLI against a local production build for each
+//! configured route, reads the category scores and the LCP, CLS and TBT
+//! metrics from its JSON report and checks them against the `[perf]`
+//! budgets. With several runs per route, the run with the median performance
+//! score is reported, as Lighthouse CI does.
+
+use anyhow::{Context, Result, bail};
+use foodshare_cli::output::{self, Finding, Level};
+use foodshare_core::config::{PerfBudget, PerfConfig};
+use foodshare_core::error::exit_codes;
+use foodshare_core::process::{command_exists, run_command_in_dir};
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use serde_json::Value;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Lighthouse categories audited, as named in the report
+const CATEGORIES: &[&str] = &["performance", "accessibility", "best-practices", "seo"];
+
+/// Category scores (0-100); missing when Lighthouse couldn't compute one
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Scores {
+    /// Performance score
+    pub performance: Option<u32>,
+    /// Accessibility score
+    pub accessibility: Option<u32>,
+    /// Best practices score
+    pub best_practices: Option<u32>,
+    /// SEO score
+    pub seo: Option<u32>,
+}
+
+/// Lighthouse result for one route
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RouteResult {
+    /// Route path (`/listing/42`)
+    pub route: String,
+    /// Category scores
+    pub scores: Scores,
+    /// Largest Contentful Paint in milliseconds
+    pub lcp_ms: Option<f64>,
+    /// Cumulative Layout Shift
+    pub cls: Option<f64>,
+    /// Total Blocking Time in milliseconds
+    pub tbt_ms: Option<f64>,
+}
+
+/// A route over one of its budgets
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BudgetViolation {
+    /// Route path
+    pub route: String,
+    /// Metric name (`performance`, `lcp`, ...)
+    pub metric: &'static str,
+    /// Measured value
+    pub actual: f64,
+    /// Budget it breaks
+    pub budget: f64,
+}
+
+impl BudgetViolation {
+    /// One-line description
+    pub fn message(&self) -> String {
+        match self.metric {
+            "lcp" => format!("LCP {:.0} ms over budget of {:.0} ms", self.actual, self.budget),
+            "tbt" => format!("TBT {:.0} ms over budget of {:.0} ms", self.actual, self.budget),
+            "cls" => format!("CLS {:.3} over budget of {}", self.actual, self.budget),
+            category => format!("{} score {:.0} below {:.0}", category, self.actual, self.budget),
+        }
+    }
+}
+
+/// A server started for the audit; stopped when dropped
+pub struct Server {
+    child: Child,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        // npm and next spawn children of their own: stop the whole process group
+        #[cfg(unix)]
+        {
+            let group = format!("-{}", self.child.id());
+            let _ = Command::new("kill").args(["-TERM", "--", &group]).status();
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Start `command` in `root` and wait until `base_url` accepts connections
+pub fn start_server(root: &Path, command: &str, base_url: &str, timeout: Duration) -> Result<Server> {
+    let mut process = Command::new("sh");
+    process
+        .args(["-c", command])
+        .current_dir(root)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        process.process_group(0);
+    }
+    let child = process
+        .spawn()
+        .with_context(|| format!("Failed to run `{}`", command))?;
+    let mut server = Server { child };
+
+    let started = Instant::now();
+    while !is_listening(base_url) {
+        if let Some(status) = server.child.try_wait()? {
+            bail!("`{}` exited with {} before serving {}", command, status, base_url);
+        }
+        if started.elapsed() > timeout {
+            bail!(
+                "{} not reachable {}s after running `{}`",
+                base_url,
+                timeout.as_secs(),
+                command
+            );
+        }
+        std::thread::sleep(Duration::from_millis(500));
+    }
+    Ok(server)
+}
+
+/// Whether something accepts connections on the host and port of `base_url`
+pub fn is_listening(base_url: &str) -> bool {
+    let (scheme, rest) = base_url.split_once("://").unwrap_or(("http", base_url));
+    let authority = rest.split('/').next().unwrap_or(rest);
+    let address = if authority.contains(':') {
+        authority.to_string()
+    } else if scheme == "https" {
+        format!("{}:443", authority)
+    } else {
+        format!("{}:80", authority)
+    };
+
+    address
+        .to_socket_addrs()
+        .map(|mut addrs| addrs.any(|addr| TcpStream::connect_timeout(&addr, Duration::from_secs(1)).is_ok()))
+        .unwrap_or(false)
+}
+
+/// Lighthouse binary and its leading arguments: the project's, a global one, or `npx`
+fn lighthouse_command(root: &Path) -> (String, Vec<String>) {
+    let local = root.join("node_modules/.bin/lighthouse");
+    if local.is_file() {
+        (local.display().to_string(), Vec::new())
+    } else if command_exists("lighthouse") {
+        ("lighthouse".to_string(), Vec::new())
+    } else {
+        ("npx".to_string(), vec!["--yes".to_string(), "lighthouse".to_string()])
+    }
+}
+
+/// Run Lighthouse on `url` and return its JSON report
+pub fn run_lighthouse(root: &Path, url: &str, desktop: bool) -> Result<Value> {
+    let (program, mut args) = lighthouse_command(root);
+    args.extend([
+        url.to_string(),
+        "--output=json".to_string(),
+        "--output-path=stdout".to_string(),
+        "--quiet".to_string(),
+        "--chrome-flags=--headless=new --no-sandbox".to_string(),
+        format!("--only-categories={}", CATEGORIES.join(",")),
+    ]);
+    if desktop {
+        args.push("--preset=desktop".to_string());
+    }
+
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let result = run_command_in_dir(&program, &args, root)?;
+    if !result.success {
+        let stderr = result.stderr.trim();
+        let tail: Vec<&str> = stderr.lines().rev().take(5).collect();
+        bail!(
+            "Lighthouse failed on {} (exit {}): {}",
+            url,
+            result.exit_code,
+            tail.into_iter().rev().collect::<Vec<_>>().join("\n")
+        );
+    }
+
+    let report: Value = serde_json::from_str(&result.stdout).context("Failed to parse the Lighthouse report")?;
+    if let Some(message) = report.pointer("/runtimeError/message").and_then(Value::as_str) {
+        bail!("Lighthouse could not load {}: {}", url, message);
+    }
+    Ok(report)
+}
+
+/// Scores and key metrics of a Lighthouse JSON report
+pub fn parse_report(route: &str, report: &Value) -> Result<RouteResult> {
+    let categories = report
+        .get("categories")
+        .and_then(Value::as_object)
+        .context("Lighthouse report has no categories")?;
+    let score = |name: &str| {
+        categories
+            .get(name)
+            .and_then(|category| category.get("score"))
+            .and_then(Value::as_f64)
+            .map(|score| (score * 100.0).round() as u32)
+    };
+    let metric = |audit: &str| {
+        report
+            .get("audits")
+            .and_then(|audits| audits.get(audit))
+            .and_then(|audit| audit.get("numericValue"))
+            .and_then(Value::as_f64)
+    };
+
+    Ok(RouteResult {
+        route: route.to_string(),
+        scores: Scores {
+            performance: score("performance"),
+            accessibility: score("accessibility"),
+            best_practices: score("best-practices"),
+            seo: score("seo"),
+        },
+        lcp_ms: metric("largest-contentful-paint"),
+        cls: metric("cumulative-layout-shift"),
+        tbt_ms: metric("total-blocking-time"),
+    })
+}
+
+/// The run with the median performance score
+fn median_run(mut runs: Vec<RouteResult>) -> Option<RouteResult> {
+    runs.sort_by_key(|run| run.scores.performance);
+    let middle = runs.len() / 2;
+    runs.into_iter().nth(middle)
+}
+
+/// Audit each route of `config`, served on `config.base_url`
+pub fn audit(root: &Path, config: &PerfConfig) -> Result<Vec<RouteResult>> {
+    let base = config.base_url.trim_end_matches('/');
+    let mut results = Vec::new();
+    for route in &config.routes {
+        let url = format!("{}{}", base, route);
+        let mut runs = Vec::new();
+        for run in 1..=config.runs.max(1) {
+            // Progress goes to stderr so JSON output stays parseable
+            eprintln!("{} {} (run {}/{})", "Lighthouse".dimmed(), url, run, config.runs.max(1));
+            let report = run_lighthouse(root, &url, config.desktop)?;
+            runs.push(parse_report(route, &report)?);
+        }
+        results.extend(median_run(runs));
+    }
+    Ok(results)
+}
+
+/// Budget of `route`: the route's own fields, falling back to the shared budget
+fn route_budget(config: &PerfConfig, route: &str) -> PerfBudget {
+    let shared = config.budget;
+    let Some(own) = config.route_budgets.get(route) else {
+        return shared;
+    };
+    PerfBudget {
+        min_performance: own.min_performance.or(shared.min_performance),
+        min_accessibility: own.min_accessibility.or(shared.min_accessibility),
+        min_best_practices: own.min_best_practices.or(shared.min_best_practices),
+        min_seo: own.min_seo.or(shared.min_seo),
+        max_lcp_ms: own.max_lcp_ms.or(shared.max_lcp_ms),
+        max_cls: own.max_cls.or(shared.max_cls),
+        max_tbt_ms: own.max_tbt_ms.or(shared.max_tbt_ms),
+    }
+}
+
+/// Budgets the results break
+pub fn check_budgets(results: &[RouteResult], config: &PerfConfig) -> Vec<BudgetViolation> {
+    let mut violations = Vec::new();
+    for result in results {
+        let budget = route_budget(config, &result.route);
+        let mut check = |metric: &'static str, actual: Option<f64>, limit: Option<f64>, minimum: bool| {
+            if let (Some(actual), Some(limit)) = (actual, limit) {
+                let broken = if minimum { actual < limit } else { actual > limit };
+                if broken {
+                    violations.push(BudgetViolation {
+                        route: result.route.clone(),
+                        metric,
+                        actual,
+                        budget: limit,
+                    });
+                }
+            }
+        };
+
+        let scores = &result.scores;
+        let as_f64 = |value: Option<u32>| value.map(f64::from);
+        check(
+            "performance",
+            as_f64(scores.performance),
+            as_f64(budget.min_performance),
+            true,
+        );
+        check(
+            "accessibility",
+            as_f64(scores.accessibility),
+            as_f64(budget.min_accessibility),
+            true,
+        );
+        check(
+            "best-practices",
+            as_f64(scores.best_practices),
+            as_f64(budget.min_best_practices),
+            true,
+        );
+        check("seo", as_f64(scores.seo), as_f64(budget.min_seo), true);
+        check("lcp", result.lcp_ms, budget.max_lcp_ms.map(|ms| ms as f64), false);
+        check("cls", result.cls, budget.max_cls, false);
+        check("tbt", result.tbt_ms, budget.max_tbt_ms.map(|ms| ms as f64), false);
+    }
+    violations
+}
+
+/// GitHub workflow command data: `%`, CR and LF are encoded
+fn escape_data(value: &str) -> String {
+    value.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+/// GitHub workflow command property: data escaping plus `:` and `,`
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+fn score_text(score: Option<u32>) -> String {
+    score.map_or_else(|| "-".to_string(), |s| s.to_string())
+}
+
+fn ms_text(value: Option<f64>) -> String {
+    value.map_or_else(|| "-".to_string(), |v| format!("{:.0} ms", v))
+}
+
+fn cls_text(value: Option<f64>) -> String {
+    value.map_or_else(|| "-".to_string(), |v| format!("{:.3}", v))
+}
+
+fn summary(result: &RouteResult) -> String {
+    format!(
+        "performance {}, accessibility {}, best practices {}, SEO {}; LCP {}, CLS {}, TBT {}",
+        score_text(result.scores.performance),
+        score_text(result.scores.accessibility),
+        score_text(result.scores.best_practices),
+        score_text(result.scores.seo),
+        ms_text(result.lcp_ms),
+        cls_text(result.cls),
+        ms_text(result.tbt_ms)
+    )
+}
+
+/// Annotations for GitHub Actions: a notice per route, an error per violation
+pub fn github_annotations(results: &[RouteResult], violations: &[BudgetViolation]) -> Vec<String> {
+    let mut lines: Vec<String> = results
+        .iter()
+        .map(|result| {
+            format!(
+                "::notice title={}::{}",
+                escape_property(&format!("Lighthouse {}", result.route)),
+                escape_data(&summary(result))
+            )
+        })
+        .collect();
+    lines.extend(violations.iter().map(|violation| {
+        format!(
+            "::error title={}::{}",
+            escape_property(&format!("Performance budget {}", violation.route)),
+            escape_data(&violation.message())
+        )
+    }));
+    lines
+}
+
+/// Print the results, or report them as data and findings with `--format json`
+///
+/// Returns exit code (0 = within budget, 1 = a budget is broken)
+pub fn print_results(results: &[RouteResult], violations: &[BudgetViolation]) -> i32 {
+    if output::is_machine() {
+        output::data("routes", &results);
+        for violation in violations {
+            output::finding(
+                Finding::new(Level::Error, format!("{}: {}", violation.route, violation.message()))
+                    .with_rule(violation.metric),
+            );
+        }
+    } else {
+        println!(
+            "{:<32} {:>5} {:>5} {:>5} {:>5} {:>9} {:>7} {:>9}",
+            "Route".bold(),
+            "Perf",
+            "A11y",
+            "BP",
+            "SEO",
+            "LCP",
+            "CLS",
+            "TBT"
+        );
+        for result in results {
+            println!(
+                "{:<32} {:>5} {:>5} {:>5} {:>5} {:>9} {:>7} {:>9}",
+                result.route,
+                score_text(result.scores.performance),
+                score_text(result.scores.accessibility),
+                score_text(result.scores.best_practices),
+                score_text(result.scores.seo),
+                ms_text(result.lcp_ms),
+                cls_text(result.cls),
+                ms_text(result.tbt_ms)
+            );
+        }
+
+        if violations.is_empty() {
+            println!();
+            println!("{} All routes within budget", "OK".green());
+        } else {
+            eprintln!();
+            eprintln!("{} {} budget(s) broken:", "ERROR".red(), violations.len());
+            for violation in violations {
+                eprintln!("  {} {}", violation.route.cyan(), violation.message());
+            }
+        }
+    }
+
+    if violations.is_empty() {
+        exit_codes::SUCCESS
+    } else {
+        exit_codes::FAILURE
+    }
+}
+
+/// Print GitHub Actions annotations; returns the exit code like [`print_results`]
+pub fn print_annotations(results: &[RouteResult], violations: &[BudgetViolation]) -> i32 {
+    for line in github_annotations(results, violations) {
+        println!("{}", line);
+    }
+    if violations.is_empty() {
+        exit_codes::SUCCESS
+    } else {
+        exit_codes::FAILURE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(performance: f64, lcp: f64) -> Value {
+        serde_json::json!({
+            "categories": {
+                "performance": { "score": performance },
+                "accessibility": { "score": 0.98 },
+                "best-practices": { "score": null },
+                "seo": { "score": 1 }
+            },
+            "audits": {
+                "largest-contentful-paint": { "numericValue": lcp },
+                "cumulative-layout-shift": { "numericValue": 0.042 },
+                "total-blocking-time": { "numericValue": 180.5 }
+            }
+        })
+    }
+
+    #[test]
+    fn test_parse_report_and_median_run() {
+        let result = parse_report("/", &report(0.914, 2100.0)).unwrap();
+        assert_eq!(result.scores.performance, Some(91));
+        assert_eq!(result.scores.accessibility, Some(98));
+        assert_eq!(result.scores.best_practices, None);
+        assert_eq!(result.scores.seo, Some(100));
+        assert_eq!(result.lcp_ms, Some(2100.0));
+        assert_eq!(result.cls, Some(0.042));
+
+        let runs = [(0.95, 1800.0), (0.70, 4000.0), (0.88, 2500.0)]
+            .iter()
+            .map(|&(score, lcp)| parse_report("/", &report(score, lcp)).unwrap())
+            .collect();
+        let median = median_run(runs).unwrap();
+        assert_eq!(median.scores.performance, Some(88));
+        assert_eq!(median.lcp_ms, Some(2500.0));
+    }
+
+    #[test]
+    fn test_check_budgets_with_route_overrides() {
+        let mut config = PerfConfig {
+            budget: PerfBudget {
+                min_performance: Some(90),
+                max_lcp_ms: Some(2500),
+                max_cls: Some(0.1),
+                ..PerfBudget::default()
+            },
+            ..PerfConfig::default()
+        };
+        config.route_budgets.insert(
+            "/map".to_string(),
+            PerfBudget {
+                min_performance: Some(70),
+                ..PerfBudget::default()
+            },
+        );
+
+        let home = parse_report("/", &report(0.85, 3000.0)).unwrap();
+        let map = parse_report("/map", &report(0.75, 2000.0)).unwrap();
+        let violations = check_budgets(&[home, map], &config);
+
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[0].metric, "performance");
+        assert_eq!(violations[0].message(), "performance score 85 below 90");
+        assert_eq!(violations[1].metric, "lcp");
+        assert_eq!(violations[1].message(), "LCP 3000 ms over budget of 2500 ms");
+    }
+
+    #[test]
+    fn test_github_annotations_escape() {
+        let result = parse_report("/search", &report(0.5, 4000.0)).unwrap();
+        let violation = BudgetViolation {
+            route: "/search".to_string(),
+            metric: "tbt",
+            actual: 640.0,
+            budget: 300.0,
+        };
+        let lines = github_annotations(&[result], &[violation]);
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("::notice title=Lighthouse /search::performance 50, "));
+        assert_eq!(
+            lines[1],
+            "::error title=Performance budget /search::TBT 640 ms over budget of 300 ms"
+        );
+        assert_eq!(escape_property("a: b, 100%"), "a%3A b%2C 100%25");
+    }
+}
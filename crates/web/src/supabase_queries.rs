@@ -0,0 +1,392 @@
+//! Supabase query linting
+//!
+//! Follows `.from('table')` query chains of the Supabase client, across
+//! lines, and checks:
+//!
+//! - filters built by string interpolation (`.or(`name.eq.${q}`)`), which let
+//!   input rewrite the PostgREST filter
+//! - `select('*')` and reads without `.limit()`/`.range()`/`.single()` on
+//!   large tables
+//! - the service-role key referenced from code that runs in the browser
+//!
+//! A finding is suppressed with a `// supabase-ignore: <rule>` comment on its
+//! line or the line above.
+
+use crate::env_leaks::is_client_component;
+use crate::nextjs_security::Severity;
+use foodshare_cli::output::{self, Finding, Level};
+use foodshare_core::config::SupabaseConfig;
+use foodshare_core::error::exit_codes;
+use once_cell::sync::Lazy;
+use owo_colors::OwoColorize;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// Rule IDs with their severity
+pub const RULES: &[(&str, Severity)] = &[
+    ("interpolated-filter", Severity::High),
+    ("select-star", Severity::Medium),
+    ("missing-limit", Severity::Medium),
+    ("service-role-in-client", Severity::Critical),
+];
+
+/// First calls of a query chain; storage buckets also use `.from()`
+const QUERY_METHODS: &[&str] = &["select", "insert", "update", "upsert", "delete"];
+
+/// Calls whose arguments are PostgREST syntax rather than bound values
+const SYNTAX_METHODS: &[&str] = &["or", "filter", "not", "select", "order"];
+
+/// Calls that bound how many rows a read returns
+const LIMITING_METHODS: &[&str] = &["limit", "range", "single", "maybeSingle"];
+
+static FROM_CALL: Lazy<Regex> = Lazy::new(|| Regex::new(r"\.from\s*\(").unwrap());
+
+/// Next call of a chain: `.name(`, `?.name(`, `.returns<T>(`
+static METHOD: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\??\.\s*([A-Za-z_$][\w$]*)\s*(?:<[^()]*?>)?\s*\(").unwrap());
+
+static STRING_ARG: Lazy<Regex> = Lazy::new(|| Regex::new(r#"^\s*(['"`])([^'"`]*)(['"`])"#).unwrap());
+
+/// `${...}` in a template, or a string literal joined with `+`
+static INTERPOLATION: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\$\{|['"`]\s*\+|\+\s*['"`]"#).unwrap());
+
+static SERVICE_ROLE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)service[_-]?role").unwrap());
+
+/// Inline suppression: `// supabase-ignore: select-star, missing-limit`
+static SUPPRESSION: Lazy<Regex> = Lazy::new(|| Regex::new(r"supabase-ignore:\s*([\w\-]+(?:\s*,\s*[\w\-]+)*)").unwrap());
+
+/// Supabase query finding
+#[derive(Debug, Clone)]
+pub struct QueryFinding {
+    /// File the finding is in
+    pub file: String,
+    /// Line number (1-based)
+    pub line: usize,
+    /// Rule ID, usable in `supabase-ignore:` comments
+    pub rule: &'static str,
+    /// How serious the issue is
+    pub severity: Severity,
+    /// What is wrong
+    pub message: String,
+}
+
+/// One call of a query chain
+#[derive(Debug)]
+struct Call<'a> {
+    name: &'a str,
+    args: &'a str,
+    line: usize,
+}
+
+/// Index of the bracket closing the one at `open`, skipping string contents
+fn closing_bracket(source: &str, open: usize) -> Option<usize> {
+    let bytes = source.as_bytes();
+    let mut depth = 0usize;
+    let mut i = open;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            quote @ (b'\'' | b'"' | b'`') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    if bytes[i] == b'\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Calls chained from the `.` at `start`
+fn query_chain(source: &str, start: usize) -> Vec<Call<'_>> {
+    let line_of = |offset: usize| source[..offset].matches('\n').count() + 1;
+    let mut calls = Vec::new();
+    let mut position = start;
+    loop {
+        let rest = &source[position..];
+        let at = position + (rest.len() - rest.trim_start().len());
+        let Some(captures) = METHOD.captures(&source[at..]) else {
+            break;
+        };
+        let open = at + captures.get(0).unwrap().end() - 1;
+        let Some(close) = closing_bracket(source, open) else {
+            break;
+        };
+        calls.push(Call {
+            name: captures.get(1).unwrap().as_str(),
+            args: &source[open + 1..close],
+            line: line_of(at),
+        });
+        position = close + 1;
+    }
+    calls
+}
+
+/// Rule IDs suppressed by a `supabase-ignore:` comment on a line
+fn suppressed(line: &str) -> Vec<&str> {
+    SUPPRESSION
+        .captures(line)
+        .and_then(|c| c.get(1))
+        .map(|ids| ids.as_str().split(',').map(str::trim).collect())
+        .unwrap_or_default()
+}
+
+fn severity(rule: &str) -> Severity {
+    RULES
+        .iter()
+        .find(|(id, _)| *id == rule)
+        .map(|(_, severity)| *severity)
+        .unwrap_or(Severity::Medium)
+}
+
+/// Whether a line is only a comment
+fn is_comment(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with("//") || trimmed.starts_with("/*") || trimmed.starts_with('*')
+}
+
+/// Scan source text for Supabase query issues
+pub fn scan_content(content: &str, file: &str, config: &SupabaseConfig) -> Vec<QueryFinding> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut found: Vec<(usize, &'static str, String)> = Vec::new();
+
+    for m in FROM_CALL.find_iter(content) {
+        let calls = query_chain(content, m.start());
+        let Some(first) = calls.get(1) else {
+            continue;
+        };
+        if !QUERY_METHODS.contains(&first.name) || lines.get(calls[0].line - 1).is_some_and(|l| is_comment(l)) {
+            continue;
+        }
+        let table = STRING_ARG
+            .captures(calls[0].args)
+            .filter(|c| c[1] == c[3] && !c[2].contains("${"))
+            .map(|c| c[2].to_string());
+        let large = config.large_tables.is_empty()
+            || table
+                .as_ref()
+                .is_some_and(|t| config.large_tables.iter().any(|l| l == t));
+        let name = table.as_deref().unwrap_or("table");
+
+        for call in &calls {
+            if SYNTAX_METHODS.contains(&call.name) && INTERPOLATION.is_match(call.args) {
+                found.push((
+                    call.line,
+                    "interpolated-filter",
+                    format!(
+                        ".{}() on {} builds PostgREST syntax from a string; input can change the query. Use .eq()/.in() or escape the value",
+                        call.name, name
+                    ),
+                ));
+            }
+        }
+
+        if first.name != "select" || !large {
+            continue;
+        }
+        let columns = STRING_ARG.captures(first.args).map(|c| c[2].to_string());
+        let star = match &columns {
+            Some(columns) => columns.split(',').any(|c| c.trim() == "*"),
+            None => first.args.trim().is_empty(),
+        };
+        if star {
+            found.push((
+                first.line,
+                "select-star",
+                format!("select('*') on {} fetches every column; list the ones you use", name),
+            ));
+        }
+        let head_only = first.args.contains("head: true") || first.args.contains("head:true");
+        if !head_only && !calls.iter().any(|c| LIMITING_METHODS.contains(&c.name)) {
+            found.push((
+                calls[0].line,
+                "missing-limit",
+                format!("Read from {} without .limit(), .range() or .single()", name),
+            ));
+        }
+    }
+
+    let client = is_client_component(content)
+        || config
+            .client_paths
+            .iter()
+            .filter_map(|pattern| glob::Pattern::new(pattern).ok())
+            .any(|pattern| pattern.matches_path(Path::new(file)));
+    if client {
+        for (index, line) in lines.iter().enumerate() {
+            if !is_comment(line) && SERVICE_ROLE.is_match(line) {
+                found.push((
+                    index + 1,
+                    "service-role-in-client",
+                    "Service-role key in code that runs in the browser; it bypasses row level security".to_string(),
+                ));
+            }
+        }
+    }
+
+    let mut findings: Vec<QueryFinding> = found
+        .into_iter()
+        .filter(|(line, rule, _)| {
+            let own = lines.get(line - 1).map(|l| suppressed(l)).unwrap_or_default();
+            let above = line
+                .checked_sub(2)
+                .and_then(|i| lines.get(i))
+                .map(|l| suppressed(l))
+                .unwrap_or_default();
+            !own.contains(rule) && !above.contains(rule)
+        })
+        .map(|(line, rule, message)| QueryFinding {
+            file: file.to_string(),
+            line,
+            rule,
+            severity: severity(rule),
+            message,
+        })
+        .collect();
+    findings.sort_by_key(|f| f.line);
+    findings
+}
+
+/// Scan multiple files
+pub fn scan_files(paths: &[PathBuf], config: &SupabaseConfig) -> anyhow::Result<Vec<QueryFinding>> {
+    let mut findings = Vec::new();
+    for path in paths {
+        if path.is_file() {
+            let content = std::fs::read_to_string(path)?;
+            findings.extend(scan_content(&content, &path.to_string_lossy(), config));
+        }
+    }
+    Ok(findings)
+}
+
+/// Print findings; high and critical ones fail
+pub fn print_results(findings: &[QueryFinding]) -> i32 {
+    if output::is_machine() {
+        for finding in findings {
+            let level = if finding.severity >= Severity::High { Level::Error } else { Level::Warning };
+            output::finding(
+                Finding::new(level, &finding.message)
+                    .with_rule(finding.rule)
+                    .with_location(&finding.file, Some(finding.line)),
+            );
+        }
+        let blocking = findings.iter().any(|f| f.severity >= Severity::High);
+        return if blocking { exit_codes::FAILURE } else { exit_codes::SUCCESS };
+    }
+
+    if findings.is_empty() {
+        println!("{} No Supabase query issues detected", "OK".green());
+        return exit_codes::SUCCESS;
+    }
+
+    let blocking = findings.iter().filter(|f| f.severity >= Severity::High).count();
+    eprintln!(
+        "{} Found {} Supabase query issue(s), {} blocking",
+        if blocking > 0 {
+            "ERROR".red().to_string()
+        } else {
+            "WARNING".yellow().to_string()
+        },
+        findings.len(),
+        blocking
+    );
+    for finding in findings {
+        let severity_str = match finding.severity {
+            Severity::Critical => "CRITICAL".red().bold().to_string(),
+            Severity::High => "HIGH".red().to_string(),
+            _ => "MEDIUM".yellow().to_string(),
+        };
+        eprintln!(
+            "  [{}] {}:{} {}",
+            severity_str,
+            finding.file,
+            finding.line,
+            finding.rule.cyan()
+        );
+        eprintln!("    {}", finding.message);
+    }
+    eprintln!(
+        "  {}",
+        "Suppress a reviewed finding with a `// supabase-ignore: <rule>` comment".dimmed()
+    );
+
+    if blocking > 0 {
+        exit_codes::FAILURE
+    } else {
+        exit_codes::SUCCESS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(content: &str) -> Vec<(usize, &'static str)> {
+        scan_content(content, "src/lib/listings.ts", &SupabaseConfig::default())
+            .iter()
+            .map(|f| (f.line, f.rule))
+            .collect()
+    }
+
+    #[test]
+    fn test_query_chains() {
+        let content = r#"const { data } = await supabase
+  .from('listings')
+  .select('*, profiles(name)')
+  .or(`title.ilike.%${query}%,description.ilike.%${query}%`);
+
+const { data: one } = await supabase.from("profiles").select("id, name").eq("id", id).single();
+const { count } = await supabase.from('messages').select('*', { count: 'exact', head: true });
+await supabase.from('listings').insert({ title }).select('id').single();
+const { data: file } = await supabase.storage.from('avatars').download(path);
+const letters = Array.from('abc');
+"#;
+        assert_eq!(
+            rules(content),
+            [
+                (2, "missing-limit"),
+                (3, "select-star"),
+                (4, "interpolated-filter"),
+                (7, "select-star")
+            ]
+        );
+
+        let config = SupabaseConfig {
+            large_tables: vec!["messages".to_string()],
+            ..Default::default()
+        };
+        let found: Vec<&str> = scan_content(content, "x.ts", &config).iter().map(|f| f.rule).collect();
+        assert_eq!(found, ["interpolated-filter", "select-star"]);
+    }
+
+    #[test]
+    fn test_service_role_in_client() {
+        let content = "'use client';\nconst admin = createClient(url, process.env.SUPABASE_SERVICE_ROLE_KEY!);\n";
+        assert_eq!(rules(content), [(2, "service-role-in-client")]);
+        assert!(rules(&content.replace("'use client';", "import 'server-only';")).is_empty());
+
+        let config = SupabaseConfig {
+            client_paths: vec!["src/hooks/**".to_string()],
+            ..Default::default()
+        };
+        let content = "const key = env.serviceRoleKey;\n";
+        assert_eq!(scan_content(content, "src/hooks/useAdmin.ts", &config).len(), 1);
+    }
+
+    #[test]
+    fn test_suppression() {
+        let content = "// supabase-ignore: missing-limit, select-star\nconst { data } = await supabase.from('tags').select();\n\
+                       supabase.from('tags').select('id').filter('id', 'in', `(${ids})`); // supabase-ignore: interpolated-filter\n";
+        assert_eq!(rules(content), [(3, "missing-limit")]);
+    }
+}
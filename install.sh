@@ -35,6 +35,7 @@ echo ""
 echo -e "${GREEN}Build complete!${NC}"
 echo ""
 echo "Binaries available at:"
+echo "  - target/release/foodshare"
 echo "  - target/release/foodshare-ios"
 echo "  - target/release/foodshare-android"
 echo "  - target/release/lefthook-rs"
@@ -43,6 +44,7 @@ echo "  - target/release/lefthook-rs"
 if [[ "$1" == "--install" ]]; then
     echo ""
     echo "Installing to ~/.cargo/bin..."
+    cargo install --path bins/foodshare
     cargo install --path bins/foodshare-ios
     cargo install --path bins/foodshare-android
     cargo install --path bins/lefthook-rs